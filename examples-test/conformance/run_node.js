@@ -0,0 +1,77 @@
+// Node layer of the cross-binding conformance harness.
+//
+// Usage: node run_node.js <nervusdb_node.node> <scenarios.json> <work_dir>
+//
+// Prints {scenario_id: [outcome, ...]} as JSON on stdout. Driven by
+// nervusdb-capi/tests/binding_conformance.rs, which diffs it against the C API.
+"use strict";
+
+const fs = require("fs");
+const path = require("path");
+
+const [addonPath, scenariosPath, workDir] = process.argv.slice(2);
+const addon = require(path.resolve(addonPath));
+
+function expandParams(value) {
+  if (Array.isArray(value)) return value.map(expandParams);
+  if (value !== null && typeof value === "object") {
+    const keys = Object.keys(value);
+    if (keys.length === 1 && keys[0] === "$repeat") {
+      const [text, count] = value.$repeat;
+      return text.repeat(count);
+    }
+    return Object.fromEntries(keys.map((k) => [k, expandParams(value[k])]));
+  }
+  return value;
+}
+
+// Native errors carry a JSON payload: {"code", "category", "message"}.
+function errorOutcome(err) {
+  const payload = JSON.parse(err.message);
+  return { error: payload.category };
+}
+
+function runStep(db, state, step) {
+  const params = "params" in step ? expandParams(step.params) : undefined;
+  switch (step.op) {
+    case "write":
+      return { count: db.executeWrite(step.query, params) };
+    case "query":
+      return { rows: db.query(step.query, params) };
+    case "begin":
+      state.txn = db.beginWrite();
+      return { ok: true };
+    case "txn_query":
+      state.txn.query(step.query);
+      return { ok: true };
+    case "commit":
+    case "rollback": {
+      const txn = state.txn;
+      delete state.txn;
+      txn[step.op]();
+      return { ok: true };
+    }
+    default:
+      throw new Error(`unknown conformance op ${step.op}`);
+  }
+}
+
+function runScenario(scenario) {
+  const db = addon.Db.open(path.join(workDir, scenario.id));
+  const state = {};
+  const outcomes = scenario.steps.map((step) => {
+    try {
+      return runStep(db, state, step);
+    } catch (err) {
+      return errorOutcome(err);
+    }
+  });
+  db.close();
+  return outcomes;
+}
+
+fs.mkdirSync(workDir, { recursive: true });
+const { scenarios } = JSON.parse(fs.readFileSync(scenariosPath, "utf8"));
+const out = {};
+for (const scenario of scenarios) out[scenario.id] = runScenario(scenario);
+process.stdout.write(JSON.stringify(out));
@@ -0,0 +1,87 @@
+"""
+Python layer of the cross-binding conformance harness.
+
+Usage: run_python.py <scenarios.json> <work_dir>
+
+Prints {scenario_id: [outcome, ...]} as JSON on stdout. Driven by
+nervusdb-capi/tests/binding_conformance.rs, which diffs it against the C API.
+"""
+
+import json
+import os
+import sys
+
+import nervusdb
+
+ERROR_CATEGORIES = [
+    (nervusdb.SyntaxError, "syntax"),
+    (nervusdb.CompatibilityError, "compatibility"),
+    (nervusdb.StorageError, "storage"),
+    (nervusdb.ExecutionError, "execution"),
+]
+
+
+def expand_params(value):
+    if isinstance(value, dict):
+        if len(value) == 1 and "$repeat" in value:
+            text, count = value["$repeat"]
+            return text * count
+        return {k: expand_params(v) for k, v in value.items()}
+    if isinstance(value, list):
+        return [expand_params(v) for v in value]
+    return value
+
+
+def error_outcome(exc):
+    for cls, name in ERROR_CATEGORIES:
+        if isinstance(exc, cls):
+            return {"error": name}
+    raise exc
+
+
+def run_step(db, state, step):
+    op = step["op"]
+    params = expand_params(step["params"]) if "params" in step else None
+    if op == "write":
+        return {"count": db.execute_write(step["query"], params)}
+    if op == "query":
+        return {"rows": db.query(step["query"], params)}
+    if op == "begin":
+        state["txn"] = db.begin_write()
+        return {"ok": True}
+    if op == "txn_query":
+        state["txn"].query(step["query"])
+        return {"ok": True}
+    if op in ("commit", "rollback"):
+        txn = state.pop("txn")
+        getattr(txn, op)()
+        return {"ok": True}
+    raise ValueError(f"unknown conformance op {op!r}")
+
+
+def run_scenario(scenario, work_dir):
+    db = nervusdb.open(os.path.join(work_dir, scenario["id"]))
+    state = {}
+    outcomes = []
+    for step in scenario["steps"]:
+        try:
+            outcomes.append(run_step(db, state, step))
+        except nervusdb.NervusError as exc:
+            if step["op"] in ("commit", "rollback"):
+                state.pop("txn", None)
+            outcomes.append(error_outcome(exc))
+    db.close()
+    return outcomes
+
+
+def main():
+    scenarios_path, work_dir = sys.argv[1], sys.argv[2]
+    os.makedirs(work_dir, exist_ok=True)
+    with open(scenarios_path, encoding="utf-8") as f:
+        scenarios = json.load(f)["scenarios"]
+    out = {s["id"]: run_scenario(s, work_dir) for s in scenarios}
+    json.dump(out, sys.stdout, ensure_ascii=False)
+
+
+if __name__ == "__main__":
+    main()
@@ -1878,6 +1878,7 @@ fn t26_params_with_options() {
         max_collection_items: 10,
         soft_timeout_ms: 1000,
         max_apply_rows_per_outer: 10,
+        ..Default::default()
     };
     let params = Params::with_execute_options(opts);
     assert_eq!(params.execute_options().max_intermediate_rows, 10);
@@ -48,6 +48,7 @@ fuzz_target!(|data: &[u8]| {
         max_collection_items: 100_000,
         soft_timeout_ms: 250,
         max_apply_rows_per_outer: 50_000,
+        ..ExecuteOptions::default()
     });
 
     let _ = prepared
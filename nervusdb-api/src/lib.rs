@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 /// External identifier for a node, assigned by the user.
 ///
@@ -34,6 +35,13 @@ pub type RelTypeId = u32;
 /// - Blob: Raw binary data
 /// - List: Ordered list of PropertyValues
 /// - Map: String-keyed map of PropertyValues
+/// - Date: 32-bit signed days since Unix epoch
+/// - LocalTime: 64-bit signed nanoseconds since midnight
+/// - Duration: calendar months, days and nanoseconds, kept separate so that
+///   `P1M` stays one month regardless of the date it is added to
+/// - IntArray / FloatArray: lists of one element type (64-bit integers or
+///   32-bit floats), stored packed rather than as a `List` of tagged values,
+///   for tag ids and feature vectors
 #[derive(Debug, Clone, PartialEq)]
 pub enum PropertyValue {
     Null,
@@ -45,6 +53,11 @@ pub enum PropertyValue {
     Blob(Vec<u8>),
     List(Vec<PropertyValue>),
     Map(BTreeMap<String, PropertyValue>),
+    Date(i32),
+    LocalTime(i64),
+    Duration { months: i64, days: i64, nanos: i64 },
+    IntArray(Vec<i64>),
+    FloatArray(Vec<f32>),
 }
 
 impl From<&str> for PropertyValue {
@@ -139,6 +152,70 @@ impl PropertyValue {
                 }
                 out
             }
+            PropertyValue::Date(d) => {
+                let mut out = vec![9];
+                out.extend_from_slice(&d.to_le_bytes());
+                out
+            }
+            PropertyValue::LocalTime(t) => {
+                let mut out = vec![10];
+                out.extend_from_slice(&t.to_le_bytes());
+                out
+            }
+            PropertyValue::Duration {
+                months,
+                days,
+                nanos,
+            } => {
+                let mut out = vec![11];
+                out.extend_from_slice(&months.to_le_bytes());
+                out.extend_from_slice(&days.to_le_bytes());
+                out.extend_from_slice(&nanos.to_le_bytes());
+                out
+            }
+            PropertyValue::IntArray(items) => {
+                let mut out = vec![12];
+                let len = u32::try_from(items.len()).expect("array length should fit in u32");
+                out.extend_from_slice(&len.to_le_bytes());
+                for item in items {
+                    out.extend_from_slice(&item.to_le_bytes());
+                }
+                out
+            }
+            PropertyValue::FloatArray(items) => {
+                let mut out = vec![13];
+                let len = u32::try_from(items.len()).expect("array length should fit in u32");
+                out.extend_from_slice(&len.to_le_bytes());
+                for item in items {
+                    out.extend_from_slice(&item.to_le_bytes());
+                }
+                out
+            }
+        }
+    }
+
+    /// Length of [`PropertyValue::encode`]'s output, without encoding.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            PropertyValue::Null => 1,
+            PropertyValue::Bool(_) => 2,
+            PropertyValue::Date(_) => 5,
+            PropertyValue::Int(_)
+            | PropertyValue::Float(_)
+            | PropertyValue::DateTime(_)
+            | PropertyValue::LocalTime(_) => 9,
+            PropertyValue::Duration { .. } => 25,
+            PropertyValue::IntArray(items) => 5 + 8 * items.len(),
+            PropertyValue::FloatArray(items) => 5 + 4 * items.len(),
+            PropertyValue::String(s) => 5 + s.len(),
+            PropertyValue::Blob(b) => 5 + b.len(),
+            PropertyValue::List(l) => 5 + l.iter().map(Self::encoded_len).sum::<usize>(),
+            PropertyValue::Map(m) => {
+                5 + m
+                    .iter()
+                    .map(|(k, v)| 4 + k.len() + v.encoded_len())
+                    .sum::<usize>()
+            }
         }
     }
 
@@ -253,6 +330,65 @@ impl PropertyValue {
                 }
                 Ok((PropertyValue::Map(map), pos))
             }
+            9 => {
+                if bytes.len() < 5 {
+                    return Err(DecodeError::InvalidLength);
+                }
+                let d = i32::from_le_bytes(bytes[1..5].try_into().expect("slice length checked"));
+                Ok((PropertyValue::Date(d), 5))
+            }
+            10 => {
+                if bytes.len() < 9 {
+                    return Err(DecodeError::InvalidLength);
+                }
+                let t = i64::from_le_bytes(bytes[1..9].try_into().expect("slice length checked"));
+                Ok((PropertyValue::LocalTime(t), 9))
+            }
+            11 => {
+                if bytes.len() < 25 {
+                    return Err(DecodeError::InvalidLength);
+                }
+                let field = |at: usize| {
+                    i64::from_le_bytes(bytes[at..at + 8].try_into().expect("slice length checked"))
+                };
+                Ok((
+                    PropertyValue::Duration {
+                        months: field(1),
+                        days: field(9),
+                        nanos: field(17),
+                    },
+                    25,
+                ))
+            }
+            12 | 13 => {
+                if bytes.len() < 5 {
+                    return Err(DecodeError::InvalidLength);
+                }
+                let count =
+                    u32::from_le_bytes(bytes[1..5].try_into().expect("slice length checked"))
+                        as usize;
+                let width = if ty == 12 { 8 } else { 4 };
+                let end = count
+                    .checked_mul(width)
+                    .and_then(|len| len.checked_add(5))
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or(DecodeError::InvalidLength)?;
+                let chunks = bytes[5..end].chunks_exact(width);
+                let value = if ty == 12 {
+                    PropertyValue::IntArray(
+                        chunks
+                            .map(|c| i64::from_le_bytes(c.try_into().expect("chunk width")))
+                            .collect(),
+                    )
+                } else {
+                    PropertyValue::FloatArray(
+                        chunks
+                            .map(|c| f32::from_le_bytes(c.try_into().expect("chunk width")))
+                            .collect(),
+                    )
+                };
+                Ok((value, end))
+            }
             _ => Err(DecodeError::UnknownType(ty)),
         }
     }
@@ -297,6 +433,148 @@ pub struct EdgeKey {
     pub dst: InternalNodeId,
 }
 
+/// Labels, relationship types, property keys and indexes of a database,
+/// from [`GraphSnapshot::schema`]. Every list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Schema {
+    pub labels: Vec<String>,
+    pub rel_types: Vec<String>,
+    pub property_keys: Vec<String>,
+    pub indexes: Vec<IndexInfo>,
+}
+
+/// How an [`IndexInfo`] is organized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexKind {
+    /// Equality and range lookups on one property.
+    BTree,
+    /// Trigram index for `CONTAINS`.
+    Text,
+    /// Lookups on an ordered list of properties, or a unique constraint.
+    Composite,
+}
+
+impl IndexKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IndexKind::BTree => "btree",
+            IndexKind::Text => "text",
+            IndexKind::Composite => "composite",
+        }
+    }
+}
+
+/// One index in a [`Schema`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub kind: IndexKind,
+    pub label: String,
+    pub properties: Vec<String>,
+    /// The index backs a unique constraint.
+    pub unique: bool,
+}
+
+/// Degree histogram of one relationship type in one direction.
+///
+/// `buckets[b]` counts the nodes with a degree in `[2^b, 2^(b+1))`; nodes
+/// without a relationship of the type in that direction are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct DegreeHistogram {
+    pub buckets: Vec<u64>,
+    pub max_degree: u64,
+    /// A node with `max_degree` relationships.
+    pub max_node: InternalNodeId,
+}
+
+impl DegreeHistogram {
+    /// Counts `node` with `degree` relationships; a degree of 0 is ignored.
+    pub fn record(&mut self, node: InternalNodeId, degree: u64) {
+        if degree == 0 {
+            return;
+        }
+        let bucket = degree.ilog2() as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+        if degree > self.max_degree || (degree == self.max_degree && node < self.max_node) {
+            self.max_degree = degree;
+            self.max_node = node;
+        }
+    }
+
+    /// Number of nodes counted.
+    pub fn nodes(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+}
+
+/// Which local files `LOAD CSV` may read, from [`GraphSnapshot::file_import`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FileImport {
+    /// None; `LOAD CSV` fails.
+    #[default]
+    Disabled,
+    /// Any file the process can read.
+    Anywhere,
+    /// Files inside this canonical directory. Relative paths resolve
+    /// against it, and paths leading out of it through `..` or symlinks
+    /// are rejected.
+    Under(PathBuf),
+}
+
+/// Out- and in-degree histograms of a relationship type, from
+/// [`GraphSnapshot::degree_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RelTypeDegrees {
+    pub rel_type: String,
+    pub relationships: u64,
+    /// Degrees of the source nodes.
+    pub outgoing: DegreeHistogram,
+    /// Degrees of the target nodes.
+    pub incoming: DegreeHistogram,
+}
+
+/// Reservoir key of a sampled edge, ordered by key and then by edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SampleKey(f64, EdgeKey);
+
+impl Eq for SampleKey {}
+
+impl Ord for SampleKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| self.1.cmp(&other.1))
+    }
+}
+
+impl PartialOrd for SampleKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// SplitMix64, a small seeded generator for [`GraphSnapshot::sample_neighbors`].
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `(0, 1)`, so its logarithm is finite.
+    fn next_open_unit(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+}
+
 /// Provides access to a snapshot of the graph at a point in time.
 ///
 /// Implementors must ensure that the returned snapshot is immutable and
@@ -338,6 +616,55 @@ pub trait GraphSnapshot {
         rel: Option<RelTypeId>,
     ) -> Self::Neighbors<'_>;
 
+    /// Draws up to `k` distinct outgoing edges of `src` at random, optionally
+    /// filtered by relationship type, in the order they were drawn.
+    ///
+    /// With `weight_property`, each draw picks an edge with probability
+    /// proportional to its numeric weight, and edges without a positive
+    /// finite weight are never drawn; otherwise every edge is equally likely.
+    /// Edges are streamed through a `k`-sized reservoir, so a high-degree node
+    /// is never expanded into memory. The same `seed` gives the same sample.
+    fn sample_neighbors(
+        &self,
+        src: InternalNodeId,
+        rel: Option<RelTypeId>,
+        k: usize,
+        weight_property: Option<&str>,
+        seed: u64,
+    ) -> Vec<EdgeKey> {
+        // Weighted reservoir sampling (Efraimidis-Spirakis): keep the `k`
+        // edges with the largest `ln(u) / weight` keys.
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut rng = SplitMix64(seed);
+        let mut reservoir: std::collections::BinaryHeap<std::cmp::Reverse<SampleKey>> =
+            std::collections::BinaryHeap::with_capacity(k.min(1024));
+        for edge in self.neighbors(src, rel) {
+            let weight = match weight_property {
+                None => 1.0,
+                Some(property) => match self.edge_property(edge, property) {
+                    Some(PropertyValue::Int(w)) => w as f64,
+                    Some(PropertyValue::Float(w)) => w,
+                    _ => continue,
+                },
+            };
+            if !(weight > 0.0 && weight.is_finite()) {
+                continue;
+            }
+            let key = SampleKey(rng.next_open_unit().ln() / weight, edge);
+            if reservoir.len() < k {
+                reservoir.push(std::cmp::Reverse(key));
+            } else if reservoir.peek().is_some_and(|smallest| key > smallest.0) {
+                reservoir.pop();
+                reservoir.push(std::cmp::Reverse(key));
+            }
+        }
+        let mut sample: Vec<SampleKey> = reservoir.into_iter().map(|key| key.0).collect();
+        sample.sort_by(|a, b| b.cmp(a));
+        sample.into_iter().map(|key| key.1).collect()
+    }
+
     /// Get an iterator over all non-tombstoned nodes.
     ///
     /// Returns an iterator over all internal node IDs that are not tombstoned.
@@ -364,6 +691,85 @@ pub trait GraphSnapshot {
         None
     }
 
+    /// Returns true if an equality index exists on `label.field`.
+    fn has_index(&self, _label: &str, _field: &str) -> bool {
+        false
+    }
+
+    /// Lookup nodes using a composite index on `label`.
+    ///
+    /// `predicates` are `(field, value)` equality constraints. The index whose
+    /// leading properties are most covered by `predicates` is used, so a
+    /// composite index on `(a, b)` also answers a lookup on `a` alone. Returns
+    /// `None` if no composite index applies.
+    fn lookup_composite_index(
+        &self,
+        _label: &str,
+        _predicates: &[(String, PropertyValue)],
+    ) -> Option<Vec<InternalNodeId>> {
+        None
+    }
+
+    /// Returns true if some composite index on `label` starts with one of `fields`.
+    fn has_composite_index(&self, _label: &str, _fields: &[String]) -> bool {
+        false
+    }
+
+    /// Lookup candidate nodes for `field CONTAINS needle` using a text index.
+    ///
+    /// Returns a superset of the matching nodes; callers must still evaluate
+    /// the predicate. Returns `None` if there is no text index or the needle is
+    /// too short to narrow the search.
+    fn lookup_text_index(
+        &self,
+        _label: &str,
+        _field: &str,
+        _needle: &str,
+    ) -> Option<Vec<InternalNodeId>> {
+        None
+    }
+
+    /// The `k` nodes whose stored vectors are nearest to `query` (L2), with
+    /// their distances, nearest first.
+    ///
+    /// Returns `None` if the snapshot has no vector index or the search fails.
+    fn search_vector(&self, _query: &[f32], _k: usize) -> Option<Vec<(InternalNodeId, f32)>> {
+        None
+    }
+
+    /// Names the database has used and the indexes defined on it.
+    fn schema(&self) -> Schema {
+        Schema::default()
+    }
+
+    /// Degree histograms per relationship type as of the last compaction,
+    /// sorted by type name.
+    fn degree_stats(&self) -> Vec<RelTypeDegrees> {
+        Vec::new()
+    }
+
+    /// Stored queries in the database catalog as `(name, cypher)` pairs, sorted by name.
+    fn named_queries(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Namespace prefixes and their base IRIs as `(prefix, iri)` pairs,
+    /// sorted by prefix.
+    fn namespaces(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Locale, such as `de-DE`, whose separators `toString(value, format)`
+    /// uses for numbers. `None` formats them the English way.
+    fn locale(&self) -> Option<&str> {
+        None
+    }
+
+    /// Which local files `LOAD CSV` may read.
+    fn file_import(&self) -> FileImport {
+        FileImport::Disabled
+    }
+
     /// Resolve an internal node ID to its external ID.
     ///
     /// Returns `Some(external_id)` if the node exists and has an external ID,
@@ -465,6 +871,43 @@ mod tests {
         let encoded = value.encode();
         let decoded = PropertyValue::decode(&encoded).expect("decode should succeed");
         assert_eq!(decoded, value);
+        assert_eq!(value.encoded_len(), encoded.len());
+    }
+
+    #[test]
+    fn property_value_roundtrip_temporal_types() {
+        let value = PropertyValue::List(vec![
+            PropertyValue::Date(-719_528),
+            PropertyValue::Date(19_723),
+            PropertyValue::LocalTime(13 * 3_600_000_000_000 + 5),
+            PropertyValue::Duration {
+                months: 14,
+                days: -3,
+                nanos: 1_500_000_000,
+            },
+        ]);
+
+        let encoded = value.encode();
+        let decoded = PropertyValue::decode(&encoded).expect("decode should succeed");
+        assert_eq!(decoded, value);
+        assert!(PropertyValue::decode(&[11, 0, 0]).is_err());
+        assert_eq!(value.encoded_len(), encoded.len());
+    }
+
+    #[test]
+    fn property_value_roundtrip_arrays() {
+        let value = PropertyValue::List(vec![
+            PropertyValue::IntArray(vec![3, -1, i64::MAX]),
+            PropertyValue::FloatArray(vec![0.5, -2.25]),
+            PropertyValue::IntArray(Vec::new()),
+        ]);
+
+        let encoded = value.encode();
+        let decoded = PropertyValue::decode(&encoded).expect("decode should succeed");
+        assert_eq!(decoded, value);
+        assert_eq!(value.encoded_len(), encoded.len());
+        assert_eq!(PropertyValue::FloatArray(vec![1.0; 4]).encoded_len(), 21);
+        assert!(PropertyValue::decode(&[12, 2, 0, 0, 0, 1]).is_err());
     }
 
     #[test]
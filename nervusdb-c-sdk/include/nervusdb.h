@@ -31,8 +31,15 @@
 
 #define NDB_ERR_UNSUPPORTED 1006
 
+#define NDB_ERR_CANCELLED 1007
+
 #define NDB_ERR_INTERNAL 1099
 
+/**
+ * `ndb_open_with_flags`: the handle refuses every write.
+ */
+#define NDB_OPEN_READ_ONLY 1
+
 #define NDB_ERRCAT_NONE 0
 
 #define NDB_ERRCAT_SYNTAX 1
@@ -71,6 +78,14 @@
 
 #define NDB_COL_OTHER 10
 
+#define NDB_COL_BLOB 11
+
+#define NDB_METRIC_L2 0
+
+#define NDB_METRIC_COSINE 1
+
+#define NDB_METRIC_DOT 2
+
 typedef struct ndb_db_t {
   uint8_t _private[0];
 } ndb_db_t;
@@ -79,10 +94,51 @@ typedef struct ndb_result_t {
   uint8_t _private[0];
 } ndb_result_t;
 
+/**
+ * Bytes owned by the library, such as an Arrow IPC stream from
+ * `ndb_query_arrow`. Release them with `buffer->free(buffer)` or
+ * `ndb_buffer_free(buffer)`, which resets the fields.
+ */
+typedef struct ndb_buffer_t {
+  uint8_t *data;
+  size_t len;
+  void (*free)(struct ndb_buffer_t *buffer);
+} ndb_buffer_t;
+
 typedef struct ndb_txn_t {
   uint8_t _private[0];
 } ndb_txn_t;
 
+typedef struct ndb_snapshot_t {
+  uint8_t _private[0];
+} ndb_snapshot_t;
+
+typedef struct ndb_edges_t {
+  uint8_t _private[0];
+} ndb_edges_t;
+
+typedef struct ndb_operation_t {
+  uint8_t _private[0];
+} ndb_operation_t;
+
+/**
+ * Progress callback for long maintenance operations, called with the number
+ * of items processed so far, the total, and the caller's `user_data`.
+ */
+typedef void (*ndb_progress_fn)(uint64_t done, uint64_t total, void *user_data);
+
+typedef struct ndb_follower_t {
+  uint8_t _private[0];
+} ndb_follower_t;
+
+/**
+ * Scalar function callback for `ndb_register_function`, called with the
+ * arguments as a JSON array and the caller's `user_data`. Returns the result
+ * as JSON, or NULL for null; the string must stay valid until the callback
+ * next runs on the same thread.
+ */
+typedef const char *(*ndb_function_fn)(const char *args_json, void *user_data);
+
 typedef struct ndb_stmt_t {
   uint8_t _private[0];
 } ndb_stmt_t;
@@ -93,10 +149,45 @@ int ndb_last_error_category(void);
 
 size_t ndb_last_error_message(char *buf, size_t len);
 
+/**
+ * Copies the trace ID of the last query this thread ran, such as
+ * `4f1a9c-12`, like `ndb_last_error_message`. The ID also ends the message
+ * of a query's error, and the query capture log records it. Empty before
+ * the first query.
+ */
+size_t ndb_last_trace_id(char *buf, size_t len);
+
 int ndb_open(const char *path, struct ndb_db_t **out_db);
 
+/**
+ * Opens the database at `path` like `ndb_open`. `flags` is a bitwise OR of
+ * `NDB_OPEN_*` values; unknown bits are an invalid argument.
+ *
+ * With `NDB_OPEN_READ_ONLY`, the handle rejects every write however it is
+ * issued: write statements fail before they run, whether executed directly,
+ * prepared, stored or run in a transaction, `ndb_begin_write` fails, and so
+ * do checkpoints and schema changes. Hand such a handle to code that should
+ * only read. The database must already exist, and its files are never
+ * written, not even to recover a crashed WAL.
+ */
+int ndb_open_with_flags(const char *path, int flags, struct ndb_db_t **out_db);
+
 int ndb_open_paths(const char *ndb_path, const char *wal_path, struct ndb_db_t **out_db);
 
+/**
+ * Like `ndb_open_paths`, with `NDB_OPEN_*` flags as in `ndb_open_with_flags`.
+ */
+int ndb_open_paths_with_flags(const char *ndb_path,
+                              const char *wal_path,
+                              int flags,
+                              struct ndb_db_t **out_db);
+
+/**
+ * Opens the packed image at `path`, written by `ndb_pack`, read-only.
+ * Writes, checkpoints and schema changes on it fail.
+ */
+int ndb_open_packed(const char *path, struct ndb_db_t **out_db);
+
 int ndb_close(struct ndb_db_t *db);
 
 int ndb_query(struct ndb_db_t *db,
@@ -104,6 +195,54 @@ int ndb_query(struct ndb_db_t *db,
               const char *params_json,
               struct ndb_result_t **out_result);
 
+/**
+ * Like `ndb_query`, but returns a typed envelope `{"columns": [{"name", "type"}], "rows": [[...]]}`
+ * whose rows are positional arrays in column order.
+ */
+int ndb_query_typed(struct ndb_db_t *db,
+                    const char *cypher,
+                    const char *params_json,
+                    struct ndb_result_t **out_result);
+
+/**
+ * Like `ndb_query_typed`, but writes the result to `out_buffer` as an Arrow
+ * IPC stream holding one record batch, for consumers reading columnar data.
+ * Release it with `out_buffer->free(out_buffer)`.
+ */
+int ndb_query_arrow(struct ndb_db_t *db,
+                    const char *cypher,
+                    const char *params_json,
+                    struct ndb_buffer_t *out_buffer);
+
+/**
+ * Releases the bytes of a buffer filled by the library and resets its
+ * fields; safe to call again on the reset buffer.
+ */
+void ndb_buffer_free(struct ndb_buffer_t *buffer);
+
+/**
+ * Runs a read query and writes only its row count; projected values are never built.
+ */
+int ndb_query_count(struct ndb_db_t *db,
+                    const char *cypher,
+                    const char *params_json,
+                    uint64_t *out_count);
+
+/**
+ * Runs a read query ending in `ORDER BY` one page at a time.
+ *
+ * `token` is NULL for the first page and the previous `out_next_token`
+ * afterwards. `out_next_token` receives a token to free with
+ * `ndb_string_free`, or NULL after the last page.
+ */
+int ndb_query_page(struct ndb_db_t *db,
+                   const char *cypher,
+                   const char *params_json,
+                   uint32_t page_size,
+                   const char *token,
+                   struct ndb_result_t **out_result,
+                   char **out_next_token);
+
 int ndb_execute_write(struct ndb_db_t *db,
                       const char *cypher,
                       const char *params_json,
@@ -115,14 +254,115 @@ void ndb_result_free(struct ndb_result_t *result);
 
 void ndb_string_free(char *s);
 
+/**
+ * Frees a buffer returned by `ndb_stmt_column_blob`; `len` must be the length it reported.
+ */
+void ndb_blob_free(uint8_t *ptr, size_t len);
+
 int ndb_begin_write(struct ndb_db_t *db, struct ndb_txn_t **out_txn);
 
 int ndb_txn_query(struct ndb_txn_t *txn, const char *cypher, const char *params_json);
 
+/**
+ * Runs a read or write statement inside the transaction and returns its rows, shaped like
+ * `ndb_query`. Reads see the writes staged by earlier statements of the transaction.
+ *
+ * A failed statement may leave part of its writes staged, so it aborts the transaction: a later
+ * `ndb_txn_commit` rolls back and fails. The same holds for `ndb_txn_query`.
+ */
+int ndb_txn_execute(struct ndb_txn_t *txn,
+                    const char *cypher,
+                    const char *params_json,
+                    struct ndb_result_t **out_result);
+
 int ndb_txn_commit(struct ndb_txn_t *txn);
 
+/**
+ * Commits like `ndb_txn_commit` and writes the commit's bookmark (its txid)
+ * to `out_bookmark`, for use with `ndb_wait_for_bookmark`.
+ */
+int ndb_txn_commit_bookmark(struct ndb_txn_t *txn, uint64_t *out_bookmark);
+
 int ndb_txn_rollback(struct ndb_txn_t *txn);
 
+/**
+ * Pins a read snapshot of the committed state. Queries run with `ndb_query_in_snapshot` all see
+ * this state, whatever commits in between. Release it with `ndb_snapshot_release`; the database
+ * cannot be closed while a snapshot is held.
+ */
+int ndb_begin_read(struct ndb_db_t *db, struct ndb_snapshot_t **out_snapshot);
+
+/**
+ * Runs a read query against a snapshot from `ndb_begin_read`; results are shaped like `ndb_query`.
+ */
+int ndb_query_in_snapshot(struct ndb_snapshot_t *snapshot,
+                          const char *cypher,
+                          const char *params_json,
+                          struct ndb_result_t **out_result);
+
+/**
+ * Releases a snapshot from `ndb_begin_read`. NULL is ignored.
+ */
+void ndb_snapshot_release(struct ndb_snapshot_t *snapshot);
+
+/**
+ * Opens a cursor over every edge, or only those of type `rel_type`, for
+ * bulk export with `ndb_edges_next`. Weights come from the numeric edge
+ * property `weight_property`; edges without one, and every edge when it is
+ * NULL, weigh 1.0. The cursor pins a snapshot, so the database cannot be
+ * closed until `ndb_edges_close`.
+ */
+int ndb_edges_open(struct ndb_db_t *db,
+                   const char *rel_type,
+                   const char *weight_property,
+                   struct ndb_edges_t **out_edges);
+
+/**
+ * Writes up to `capacity` edges from the cursor into the caller's arrays:
+ * external source ids to `out_src`, external target ids to `out_dst` and
+ * weights to `out_weight` (optional; weights are not read when it is NULL).
+ * `out_len` receives the number written, 0 once every edge has been returned.
+ */
+int ndb_edges_next(struct ndb_edges_t *edges,
+                   size_t capacity,
+                   uint64_t *out_src,
+                   uint64_t *out_dst,
+                   double *out_weight,
+                   size_t *out_len);
+
+/**
+ * Closes a cursor from `ndb_edges_open` and releases its snapshot. NULL is ignored.
+ */
+void ndb_edges_close(struct ndb_edges_t *edges);
+
+/**
+ * Tags the transaction with an idempotency key. `out_already_applied`, if
+ * non-null, receives 1 when a transaction with this key has already
+ * committed; committing this one then applies nothing.
+ */
+int ndb_txn_set_idempotency_key(struct ndb_txn_t *txn, const char *key, int *out_already_applied);
+
+/**
+ * Defers unique constraint checks on the transaction's later writes to
+ * commit when `deferred` is non-zero. With 0, checks the writes deferred so
+ * far, failing with a constraint violation if one breaks a constraint, and
+ * checks each later write as it is staged.
+ */
+int ndb_txn_set_constraints_deferred(struct ndb_txn_t *txn, int deferred);
+
+/**
+ * Takes a savepoint named `name` capturing the changes staged so far. A
+ * savepoint with the same name is replaced.
+ */
+int ndb_txn_savepoint(struct ndb_txn_t *txn, const char *name);
+
+/**
+ * Discards the changes staged since savepoint `name` was taken, along with
+ * the savepoints taken after it. The savepoint itself stays usable. Rolling
+ * back past a failed statement lets the transaction commit again.
+ */
+int ndb_txn_rollback_to(struct ndb_txn_t *txn, const char *name);
+
 int ndb_txn_create_node(struct ndb_txn_t *txn,
                         uint64_t external_id,
                         uint32_t label_id,
@@ -152,6 +392,12 @@ int ndb_txn_set_edge_property(struct ndb_txn_t *txn,
                               const char *key,
                               const char *value_json);
 
+/**
+ * Makes `node` expire at `at_ms` milliseconds since the Unix epoch, by
+ * setting its `expireAt` property; see `ndb_set_expiry_sweep`.
+ */
+int ndb_txn_set_node_expiry(struct ndb_txn_t *txn, uint32_t node, int64_t at_ms);
+
 int ndb_txn_remove_node_property(struct ndb_txn_t *txn, uint32_t node, const char *key);
 
 int ndb_txn_remove_edge_property(struct ndb_txn_t *txn,
@@ -164,22 +410,576 @@ int ndb_txn_set_vector(struct ndb_txn_t *txn, uint32_t node, const float *vector
 
 int ndb_compact(struct ndb_db_t *db);
 
+/**
+ * Like `ndb_compact`, reporting `(done, total)` to `progress` and to the
+ * optional `op`. Cancelling `op` fails the call with `NDB_ERR_CANCELLED`
+ * as long as the property store has not started being rewritten.
+ */
+int ndb_compact_with_progress(struct ndb_db_t *db,
+                              struct ndb_operation_t *op,
+                              ndb_progress_fn progress,
+                              void *user_data);
+
 int ndb_checkpoint(struct ndb_db_t *db);
 
+/**
+ * Checkpoints, then writes the database to `out_path` as a compressed,
+ * read-only packed image for `ndb_open_packed`. `out_report_json`
+ * (optional) receives `{"pages", "uncompressed_bytes", "packed_bytes"}`;
+ * free it with `ndb_string_free`.
+ */
+int ndb_pack(struct ndb_db_t *db, const char *out_path, char **out_report_json);
+
+/**
+ * Checkpoints automatically on a background thread every `interval_ms`
+ * milliseconds after a write, and once the WAL reaches `wal_bytes` bytes;
+ * 0 disables either trigger, and both 0 stops the thread.
+ * `io_bytes_per_sec` (0: unthrottled) caps the average checkpoint IO.
+ */
+int ndb_set_auto_checkpoint(struct ndb_db_t *db,
+                            uint64_t interval_ms,
+                            uint64_t wal_bytes,
+                            uint64_t io_bytes_per_sec);
+
+/**
+ * Holds off automatic checkpoints until `ndb_resume_checkpoints`, waiting
+ * for one in progress to finish. `ndb_checkpoint` still runs.
+ */
+int ndb_pause_checkpoints(struct ndb_db_t *db);
+
+int ndb_resume_checkpoints(struct ndb_db_t *db);
+
+/**
+ * Slows writers down while checkpoints fall behind: once the WAL reaches
+ * `delay_wal_bytes` bytes each new write transaction waits `delay_ms`
+ * milliseconds, and once it reaches `reject_wal_bytes` commits fail with
+ * `NDB_ERR_BUSY`. 0 disables either watermark; both 0 turns throttling off.
+ */
+int ndb_set_write_throttle(struct ndb_db_t *db,
+                           uint64_t delay_wal_bytes,
+                           uint64_t delay_ms,
+                           uint64_t reject_wal_bytes);
+
+/**
+ * Deletes expired nodes, with their relationships, and expired relationships
+ * on a background thread every `interval_ms` milliseconds; 0 stops the
+ * thread. Entities expire once their `expireAt` property, a datetime or
+ * milliseconds since the Unix epoch, has passed.
+ */
+int ndb_set_expiry_sweep(struct ndb_db_t *db, uint64_t interval_ms);
+
+/**
+ * Deletes what has expired now, in one commit. `out_nodes` and `out_edges`
+ * (optional) receive how many nodes and relationships were deleted.
+ */
+int ndb_sweep_expired(struct ndb_db_t *db, uint64_t *out_nodes, uint64_t *out_edges);
+
+/**
+ * Writes totals over the handle's expiry sweeps to `out_stats_json` as
+ * `{"sweeps", "nodes_purged", "edges_purged", "failures"}`. Free it with
+ * `ndb_string_free`.
+ */
+int ndb_expiry_stats(struct ndb_db_t *db, char **out_stats_json);
+
+/**
+ * Writes the bookmark of the latest commit visible to new reads.
+ */
+int ndb_last_bookmark(struct ndb_db_t *db, uint64_t *out_bookmark);
+
+/**
+ * Blocks until the commit identified by `bookmark` is visible to new reads,
+ * failing after `timeout_ms` milliseconds.
+ */
+int ndb_wait_for_bookmark(struct ndb_db_t *db, uint64_t bookmark, uint32_t timeout_ms);
+
+/**
+ * Starts replicating `primary` into `replica`, both open in this process,
+ * from a background thread. Only the follower may write to `replica`, and
+ * neither database can be closed until `ndb_follower_stop`.
+ */
+int ndb_follower_start(struct ndb_db_t *replica,
+                       struct ndb_db_t *primary,
+                       struct ndb_follower_t **out_follower);
+
+/**
+ * Writes the bookmark of the last primary commit the follower applied, to
+ * compare with the primary's commit bookmarks.
+ */
+int ndb_follower_applied_bookmark(struct ndb_follower_t *follower, uint64_t *out_bookmark);
+
+/**
+ * Blocks until the follower has applied the primary commit `bookmark`,
+ * failing after `timeout_ms` milliseconds.
+ */
+int ndb_follower_wait(struct ndb_follower_t *follower, uint64_t bookmark, uint32_t timeout_ms);
+
+/**
+ * Stops the follower once its current poll returns and frees it.
+ */
+void ndb_follower_stop(struct ndb_follower_t *follower);
+
 int ndb_create_index(struct ndb_db_t *db, const char *label, const char *property);
 
+int ndb_create_text_index(struct ndb_db_t *db, const char *label, const char *property);
+
+/**
+ * Creates an equality index over several properties of `label`.
+ * `properties_json` is a JSON array of property names in index order.
+ */
+int ndb_create_composite_index(struct ndb_db_t *db, const char *label, const char *properties_json);
+
+/**
+ * Drops the equality index on `label` over the JSON array `properties_json`
+ * (one name for a single-property index). `out_dropped` is optional.
+ */
+int ndb_drop_index(struct ndb_db_t *db,
+                   const char *label,
+                   const char *properties_json,
+                   int *out_dropped);
+
+/**
+ * Requires `property` to be unique among nodes labelled `label`. Fails if
+ * existing nodes already hold duplicate values.
+ */
+int ndb_create_unique_constraint(struct ndb_db_t *db, const char *label, const char *property);
+
+/**
+ * Drops the unique constraint on `label.property`. `out_dropped` is optional.
+ */
+int ndb_drop_unique_constraint(struct ndb_db_t *db,
+                               const char *label,
+                               const char *property,
+                               int *out_dropped);
+
+/**
+ * Restricts relationships of type `rel_type` to the label pairs in
+ * `endpoints_json`, a JSON array of `[source label, target label]` pairs,
+ * replacing any earlier restriction. Fails if an existing relationship
+ * already connects other labels.
+ */
+int ndb_create_endpoint_constraint(struct ndb_db_t *db,
+                                   const char *rel_type,
+                                   const char *endpoints_json);
+
+/**
+ * Lifts the endpoint constraint on `rel_type`. `out_dropped` is optional.
+ */
+int ndb_drop_endpoint_constraint(struct ndb_db_t *db, const char *rel_type, int *out_dropped);
+
+/**
+ * Marks `label.property` as sensitive when `sensitive` is non-zero, or
+ * removes the marker. `out_changed` is optional and set to whether the marker
+ * changed.
+ */
+int ndb_set_sensitive(struct ndb_db_t *db,
+                      const char *label,
+                      const char *property,
+                      int sensitive,
+                      int *out_changed);
+
+/**
+ * Turns redaction of sensitive properties on (non-zero) or off for queries
+ * run through this handle. Redacted values read as `"[REDACTED]"`.
+ */
+int ndb_set_redaction(struct ndb_db_t *db, int enabled);
+
+/**
+ * Turns deterministic execution on (non-zero) or off for queries run through
+ * this handle: edges are expanded and index hits returned in sorted order,
+ * so results without `ORDER BY` come out the same across storage layouts.
+ */
+int ndb_set_deterministic(struct ndb_db_t *db, int enabled);
+
+/**
+ * Turns entity references on (non-zero) or off for the JSON rows of
+ * `ndb_query`, `ndb_query_page`, `ndb_query_in_snapshot` and
+ * `ndb_query_named` on this handle. Nodes then come back as
+ * `{"type": "node_id", "value"}` and relationships as
+ * `{"type": "edge_key", "src", "rel", "dst"}`, without their labels or
+ * properties being read; pass one to `ndb_entity_get` for those. Paths are
+ * still returned whole, and reference rows bypass the result cache.
+ */
+int ndb_set_lazy_entities(struct ndb_db_t *db, int enabled);
+
+/**
+ * Reads the entity behind a reference returned with `ndb_set_lazy_entities`
+ * on, as of the latest commit. With `key` NULL, writes the whole node or
+ * relationship in the usual row encoding to `out_json`; otherwise writes
+ * just that property's value, `null` when unset. Free it with
+ * `ndb_string_free`.
+ */
+int ndb_entity_get(struct ndb_db_t *db, const char *ref_json, const char *key, char **out_json);
+
+/**
+ * Sets the locale (e.g. `de-DE`) whose separators `toString(value, format)`
+ * uses for numbers in queries run through this handle; NULL restores the
+ * English default. Unknown locales are rejected.
+ */
+int ndb_set_locale(struct ndb_db_t *db, const char *locale);
+
+/**
+ * Enables the handle's result cache for read queries run through
+ * `ndb_query`, `ndb_query_typed`, named queries and read statements, keeping
+ * up to `capacity` results (0 disables it and is the default). A result is
+ * dropped once a commit touches a label or relationship type the query
+ * reads, or after `ttl_ms` milliseconds (0 means no expiry).
+ */
+int ndb_set_result_cache(struct ndb_db_t *db, size_t capacity, uint64_t ttl_ms);
+
+/**
+ * Starts appending the queries run through `ndb_query`, `ndb_query_typed`,
+ * named queries, statements and `ndb_execute_write` on this handle to the
+ * JSON Lines file `path`, with their latency and a digest of their rows.
+ * Parameter values are kept only when `include_params` is non-zero; their
+ * shapes always are. A NULL `path` stops capturing. Replay the log with
+ * `ndb_replay_queries`.
+ */
+int ndb_set_query_capture(struct ndb_db_t *db, const char *path, int include_params);
+
+/**
+ * Runs every query in the capture log `log_path` against this database and
+ * writes `{"queries": [{"line", "cypher", "captured_rows", "replayed_rows",
+ * "results_match", "error", "captured_micros", "replayed_micros"}],
+ * "skipped"}` to `out_report_json`; free it with `ndb_string_free`. Writes
+ * in the log are committed, so replay into a copy of the database.
+ */
+int ndb_replay_queries(struct ndb_db_t *db, const char *log_path, char **out_report_json);
+
+/**
+ * Writes the labels, relationship types and property keys the database has
+ * used and its indexes to `out_schema_json` as
+ * `{"labels", "rel_types", "property_keys", "indexes": [{"name", "kind",
+ * "label", "properties", "unique"}]}`; free it with `ndb_string_free`.
+ */
+int ndb_schema(struct ndb_db_t *db, char **out_schema_json);
+
+/**
+ * Writes the degree histograms of every relationship type, as of the last
+ * compaction, to `out_stats_json` as `[{"rel_type", "relationships",
+ * "outgoing", "incoming"}]`, each histogram being `{"buckets", "max_degree",
+ * "max_node"}`; free it with `ndb_string_free`.
+ */
+int ndb_degree_stats(struct ndb_db_t *db, char **out_stats_json);
+
+/**
+ * Writes the result cache's hit and miss counts and how many results it
+ * currently holds.
+ */
+int ndb_result_cache_stats(struct ndb_db_t *db,
+                           uint64_t *out_hits,
+                           uint64_t *out_misses,
+                           size_t *out_len);
+
+/**
+ * Reads the stored properties of nodes carrying any label in the JSON array
+ * `labels_json` into the page cache. `out_loaded` (optional) receives the
+ * number of pages loaded.
+ */
+int ndb_preload(struct ndb_db_t *db, const char *labels_json, uint64_t *out_loaded);
+
+/**
+ * Reads `count` pages starting at page `first` into the page cache.
+ * `out_loaded` (optional) receives the number of pages loaded.
+ */
+int ndb_preload_pages(struct ndb_db_t *db, uint64_t first, uint64_t count, uint64_t *out_loaded);
+
+/**
+ * Moves the properties of nodes labelled `label` to the cold tier (`cold`
+ * non-zero) or back to the page store at the next checkpoint.
+ * `out_changed` (optional) receives 0 if the label was already there.
+ */
+int ndb_set_label_tier(struct ndb_db_t *db, const char *label, int cold, int *out_changed);
+
+/**
+ * Writes the cold tier to `out_tiers_json` as `{"cold_labels": [...],
+ * "nodes", "file_bytes", "live_bytes"}`; free it with `ndb_string_free`.
+ */
+int ndb_label_tiers(struct ndb_db_t *db, char **out_tiers_json);
+
+/**
+ * Binds the namespace `prefix` to the base IRI `iri`, or unbinds it when
+ * `iri` is NULL. Queries then resolve `prefix:local` references with
+ * `iri()`. `out_changed` (optional) receives 0 if the binding was already
+ * as requested.
+ */
+int ndb_set_namespace(struct ndb_db_t *db, const char *prefix, const char *iri, int *out_changed);
+
+/**
+ * Writes the namespace catalog to `out_namespaces_json` as a
+ * `{"prefix": "iri"}` object; free it with `ndb_string_free`.
+ */
+int ndb_namespaces(struct ndb_db_t *db, char **out_namespaces_json);
+
+/**
+ * Returns the graph changes committed after `since_txid` as
+ * `[{"txid", "changes": [{"op", ...}]}]`, one entry per transaction in
+ * commit order. Property values are not redacted.
+ */
+int ndb_wal_tail(struct ndb_db_t *db, uint64_t since_txid, struct ndb_result_t **out_result);
+
+/**
+ * Keeps checkpointed WAL history archived for `keep_ms` milliseconds
+ * instead of letting the next checkpoint delete it. 0 stops archiving
+ * unless a legal hold is placed.
+ */
+int ndb_set_wal_retention(struct ndb_db_t *db, uint64_t keep_ms);
+
+/**
+ * Places the legal hold `name`, which keeps every WAL archive until it is
+ * released. `out_placed` (optional) receives 0 if it was already placed.
+ */
+int ndb_place_legal_hold(struct ndb_db_t *db, const char *name, int *out_placed);
+
+/**
+ * Releases the legal hold `name`. `out_released` (optional) receives 0 if
+ * it was not placed.
+ */
+int ndb_release_legal_hold(struct ndb_db_t *db, const char *name, int *out_released);
+
+/**
+ * Writes the WAL retention policy and archives to `out_retention_json` as
+ * `{"keep_ms", "holds": [{"name", "placed_at_ms"}], "archives": [{"seq",
+ * "bytes", "archived_at_ms"}]}`, `keep_ms` being null when unset; free it
+ * with `ndb_string_free`.
+ */
+int ndb_wal_retention(struct ndb_db_t *db, char **out_retention_json);
+
+/**
+ * Registers the scalar function `name` taking `arg_count` arguments for
+ * every database in the process, replacing an earlier registration.
+ * `callback` and `user_data` must stay valid, and be safe to use from any
+ * thread, until `ndb_unregister_function`. Built-in function names are
+ * rejected.
+ */
+int ndb_register_function(const char *name,
+                          uint32_t arg_count,
+                          ndb_function_fn callback,
+                          void *user_data);
+
+/**
+ * Removes a function added with `ndb_register_function`. `out_removed`
+ * (optional) receives whether it was registered.
+ */
+int ndb_unregister_function(const char *name, int *out_removed);
+
+/**
+ * Creates a handle for following and cancelling `ndb_compact_with_progress`,
+ * `ndb_vacuum_with_progress` or `ndb_bulkload_with_progress` from another
+ * thread. Free it with `ndb_operation_free` once the call has returned.
+ */
+int ndb_operation_new(struct ndb_operation_t **out_op);
+
+/**
+ * Asks the operation to stop at its next safe point; the running call then
+ * fails with `NDB_ERR_CANCELLED`. Safe to call from any thread.
+ */
+int ndb_operation_cancel(struct ndb_operation_t *op);
+
+/**
+ * Reads the operation's last published progress. Both outputs are optional;
+ * `total` is 0 until the operation has sized its work.
+ */
+int ndb_operation_progress(struct ndb_operation_t *op, uint64_t *out_done, uint64_t *out_total);
+
+void ndb_operation_free(struct ndb_operation_t *op);
+
+/**
+ * Renames property `old_name` to `new_name` on every node labelled `label`,
+ * together with the indexes and unique constraints on it. `progress` and
+ * `out_renamed` (the number of nodes renamed) are optional.
+ */
+int ndb_rename_property(struct ndb_db_t *db,
+                        const char *label,
+                        const char *old_name,
+                        const char *new_name,
+                        ndb_progress_fn progress,
+                        void *user_data,
+                        uint64_t *out_renamed);
+
+/**
+ * Renames label `old_name` to `new_name`. `out_renamed` is optional and set
+ * to 0 when `old_name` does not exist.
+ */
+int ndb_rename_label(struct ndb_db_t *db,
+                     const char *old_name,
+                     const char *new_name,
+                     int *out_renamed);
+
+/**
+ * Stores `cypher` in the database catalog under `name`, replacing any existing entry.
+ */
+int ndb_save_query(struct ndb_db_t *db, const char *name, const char *cypher);
+
+/**
+ * Removes the stored query `name`; `out_dropped` (optional) receives 1 if it existed.
+ */
+int ndb_drop_query(struct ndb_db_t *db, const char *name, int *out_dropped);
+
+/**
+ * Runs the stored read query `name`, like `ndb_query` with the saved text.
+ */
+int ndb_query_named(struct ndb_db_t *db,
+                    const char *name,
+                    const char *params_json,
+                    struct ndb_result_t **out_result);
+
+/**
+ * Runs the stored write query `name`, like `ndb_execute_write` with the saved text.
+ */
+int ndb_execute_named(struct ndb_db_t *db,
+                      const char *name,
+                      const char *params_json,
+                      uint32_t *out_summary);
+
+/**
+ * Registers the trigger `name` on this handle, replacing one of the same
+ * name: after each commit with changes matching `event` (e.g.
+ * `node_created:User`, `node_property_set:User.email`,
+ * `relationship_deleted:KNOWS`), the write statement `cypher` runs once per
+ * change with `$id` or `$src`/`$dst`/`$type` set. Triggers are not stored in
+ * the database.
+ */
+int ndb_create_trigger(struct ndb_db_t *db,
+                       const char *name,
+                       const char *event,
+                       const char *cypher);
+
+/**
+ * Removes the trigger `name`; `out_dropped` (optional) receives 1 if it existed.
+ */
+int ndb_drop_trigger(struct ndb_db_t *db, const char *name, int *out_dropped);
+
+/**
+ * Writes the handle's triggers to `out_triggers_json` as `[{"name",
+ * "event", "cypher", "fired", "failures", "last_error"}]`, sorted by name;
+ * `cypher` is null for Rust callbacks. Free it with `ndb_string_free`.
+ */
+int ndb_triggers(struct ndb_db_t *db, char **out_triggers_json);
+
+/**
+ * Sets the capacity of the process-wide plan cache shared by all handles; `0` disables it.
+ */
+int ndb_set_plan_cache_capacity(size_t capacity);
+
 int ndb_search_vector(struct ndb_db_t *db,
                       const float *query,
                       size_t query_len,
                       uint32_t k,
                       struct ndb_result_t **out_result);
 
+/**
+ * Like `ndb_search_vector` but scans every stored vector instead of using the HNSW index.
+ */
+int ndb_search_vector_exact(struct ndb_db_t *db,
+                            const float *query,
+                            size_t query_len,
+                            uint32_t k,
+                            struct ndb_result_t **out_result);
+
+/**
+ * Like `ndb_search_vector` but ranks by `metric` (`NDB_METRIC_*`) and only returns
+ * nodes matching the filter. `label`, `property` and `value_json` may be NULL;
+ * `property` and `value_json` must be given together.
+ */
+int ndb_search_vector_filtered(struct ndb_db_t *db,
+                               const float *query,
+                               size_t query_len,
+                               uint32_t k,
+                               int metric,
+                               const char *label,
+                               const char *property,
+                               const char *value_json,
+                               struct ndb_result_t **out_result);
+
 int ndb_vacuum(const char *path);
 
+/**
+ * Like `ndb_vacuum`, reporting pages copied as `(done, total)` to
+ * `progress` and to the optional `op`. Cancelling `op` fails the call with
+ * `NDB_ERR_CANCELLED` and leaves the database file untouched.
+ */
+int ndb_vacuum_with_progress(const char *path,
+                             struct ndb_operation_t *op,
+                             ndb_progress_fn progress,
+                             void *user_data);
+
 int ndb_backup(const char *path, const char *backup_dir);
 
+/**
+ * Creates an incremental backup of the database at `path` against the
+ * latest completed backup in `backup_dir` taken at checkpoint `since_txid`.
+ * `out_info_json` (optional) receives the new backup's info as a JSON
+ * object; free it with `ndb_string_free`.
+ */
+int ndb_backup_incremental(const char *path,
+                           const char *backup_dir,
+                           uint64_t since_txid,
+                           char **out_info_json);
+
+/**
+ * Restores backup `backup_id` from `backup_dir` as a new database at
+ * `path`, applying the base chain of an incremental backup first. Fails if
+ * a database already exists at `path`.
+ */
+int ndb_restore(const char *backup_dir, const char *backup_id, const char *path);
+
+/**
+ * Verifies the backup stored at `backup_path` (`<backup_dir>/<id>`)
+ * against its manifest and, when `test_restore` is non-zero, restores it
+ * into a temporary location and opens it. `out_report_json` receives the
+ * report as a JSON object whose `problems` array is empty when the backup
+ * verified; free it with `ndb_string_free`.
+ */
+int ndb_verify_backup(const char *backup_path, int test_restore, char **out_report_json);
+
+/**
+ * Estimates per-label storage use of the database at `path` and projects
+ * its growth from the statistics history. `out_report_json` receives the
+ * report as a JSON object; free it with `ndb_string_free`.
+ */
+int ndb_capacity_report(const char *path, char **out_report_json);
+
+/**
+ * Exports the database at `path` to the file `out_path` in `format`
+ * (`"graphml"` or `"jsonl"`). `out_stats_json` (optional) receives
+ * `{"nodes": n, "relationships": n}`; free it with `ndb_string_free`.
+ */
+int ndb_export(const char *path, const char *format, const char *out_path, char **out_stats_json);
+
+/**
+ * Like `ndb_export`, scrubbing the output by `rules_json`, an object with
+ * the optional keys `salt` (string), `hash_ids` (bool), `hash_properties`
+ * and `drop_properties` (arrays of `"name"` or `"Label.name"`) and
+ * `generalize_dates` (`"year"`, `"month"` or `"day"`).
+ */
+int ndb_export_scrubbed(const char *path,
+                        const char *format,
+                        const char *out_path,
+                        const char *rules_json,
+                        char **out_stats_json);
+
+/**
+ * Imports the file `in_path` in `format` (`"graphml"` or `"jsonl"`) into
+ * the database at `path` in one transaction. The database must not be
+ * open elsewhere. `out_stats_json` (optional) receives
+ * `{"nodes": n, "relationships": n}`; free it with `ndb_string_free`.
+ */
+int ndb_import(const char *path, const char *format, const char *in_path, char **out_stats_json);
+
 int ndb_bulkload(const char *path, const char *nodes_json, const char *edges_json);
 
+/**
+ * Like `ndb_bulkload`, reporting `(done, total)` to `progress` and to the
+ * optional `op`. Cancelling `op` fails the call with `NDB_ERR_CANCELLED`
+ * and removes the partially written database.
+ */
+int ndb_bulkload_with_progress(const char *path,
+                               const char *nodes_json,
+                               const char *edges_json,
+                               struct ndb_operation_t *op,
+                               ndb_progress_fn progress,
+                               void *user_data);
+
 int ndb_prepare_read(struct ndb_db_t *db, const char *cypher, struct ndb_stmt_t **out_stmt);
 
 int ndb_prepare_write(struct ndb_db_t *db, const char *cypher, struct ndb_stmt_t **out_stmt);
@@ -202,6 +1002,21 @@ int ndb_stmt_step(struct ndb_stmt_t *stmt, int *out_state);
 
 size_t ndb_stmt_column_count(struct ndb_stmt_t *stmt);
 
+/**
+ * Number of result columns, available right after prepare unlike
+ * `ndb_stmt_column_count`, which needs a current row. Statements without
+ * `RETURN` have none. A standalone `CALL` of a built-in procedure without
+ * `YIELD` only knows its columns once a row has been stepped; until then
+ * this returns 0.
+ */
+size_t ndb_stmt_column_count_static(struct ndb_stmt_t *stmt);
+
+/**
+ * Name of result column `col` (see `ndb_stmt_column_count_static`), valid
+ * until the statement is finalized. Returns NULL for an out-of-range column.
+ */
+const char *ndb_stmt_column_name(struct ndb_stmt_t *stmt, size_t col);
+
 int ndb_stmt_column_type(struct ndb_stmt_t *stmt, size_t col);
 
 int ndb_stmt_column_int64(struct ndb_stmt_t *stmt, size_t col, int64_t *out_value);
@@ -212,6 +1027,30 @@ int ndb_stmt_column_bool(struct ndb_stmt_t *stmt, size_t col, int *out_value);
 
 int ndb_stmt_column_string(struct ndb_stmt_t *stmt, size_t col, char **out_value);
 
+/**
+ * Copies a blob column into a new buffer owned by the caller, released with
+ * `ndb_blob_free(ptr, len)`.
+ */
+int ndb_stmt_column_blob(struct ndb_stmt_t *stmt, size_t col, uint8_t **out_ptr, size_t *out_len);
+
+/**
+ * Byte length of a string (UTF-8) or blob column, for use with `ndb_stmt_column_read`.
+ */
+int ndb_stmt_column_bytes(struct ndb_stmt_t *stmt, size_t col, size_t *out_len);
+
+/**
+ * Copies up to `buf_len` bytes of a string or blob column, starting at byte `offset`,
+ * into a caller-owned buffer. Large values can be read in chunks without the library
+ * allocating a full copy; `*out_read` is 0 once `offset` reaches the end. String chunks
+ * are raw UTF-8 and may split a multi-byte character.
+ */
+int ndb_stmt_column_read(struct ndb_stmt_t *stmt,
+                         size_t col,
+                         size_t offset,
+                         uint8_t *buf,
+                         size_t buf_len,
+                         size_t *out_read);
+
 int ndb_stmt_column_json(struct ndb_stmt_t *stmt, size_t col, char **out_value);
 
 int ndb_stmt_reset(struct ndb_stmt_t *stmt);
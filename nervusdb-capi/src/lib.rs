@@ -1,13 +1,26 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use nervusdb_core as core;
-use nervusdb_query::{Params, Row, Value, ast, prepare};
+use nervusdb_core::GraphSnapshot as _;
+use nervusdb_query::evaluator::{
+    date_value_from_epoch_days, duration_value_from_parts, epoch_days_from_date_literal,
+    local_time_value_from_nanos, nanos_from_local_time_literal,
+};
+use nervusdb_query::executor::convert_api_property_to_value;
+use nervusdb_query::{
+    Params, PreparedQuery, ResultCache, ResultKey, Row, Value, prepare_cached,
+    set_plan_cache_capacity,
+};
 use serde_json::{Map as JsonMap, Value as JsonValue, json};
 use std::cell::RefCell;
 use std::collections::BTreeMap;
-use std::ffi::{CStr, CString, c_char, c_int};
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
 use std::ptr;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 pub const NDB_OK: c_int = 0;
 pub const NDB_ERR_INVALID_ARGUMENT: c_int = 1;
@@ -18,8 +31,12 @@ pub const NDB_ERR_STORAGE: c_int = 1003;
 pub const NDB_ERR_COMPATIBILITY: c_int = 1004;
 pub const NDB_ERR_BUSY: c_int = 1005;
 pub const NDB_ERR_UNSUPPORTED: c_int = 1006;
+pub const NDB_ERR_CANCELLED: c_int = 1007;
 pub const NDB_ERR_INTERNAL: c_int = 1099;
 
+/// `ndb_open_with_flags`: the handle refuses every write.
+pub const NDB_OPEN_READ_ONLY: c_int = 1;
+
 pub const NDB_ERRCAT_NONE: c_int = 0;
 pub const NDB_ERRCAT_SYNTAX: c_int = 1;
 pub const NDB_ERRCAT_EXECUTION: c_int = 2;
@@ -41,6 +58,11 @@ pub const NDB_COL_NODE: c_int = 7;
 pub const NDB_COL_RELATIONSHIP: c_int = 8;
 pub const NDB_COL_PATH: c_int = 9;
 pub const NDB_COL_OTHER: c_int = 10;
+pub const NDB_COL_BLOB: c_int = 11;
+
+pub const NDB_METRIC_L2: c_int = 0;
+pub const NDB_METRIC_COSINE: c_int = 1;
+pub const NDB_METRIC_DOT: c_int = 2;
 
 #[repr(C)]
 pub struct ndb_db_t {
@@ -62,14 +84,63 @@ pub struct ndb_result_t {
     _private: [u8; 0],
 }
 
+#[repr(C)]
+pub struct ndb_snapshot_t {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct ndb_operation_t {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct ndb_edges_t {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct ndb_follower_t {
+    _private: [u8; 0],
+}
+
+/// Bytes owned by the library, such as an Arrow IPC stream from
+/// `ndb_query_arrow`. Release them with `buffer->free(buffer)` or
+/// `ndb_buffer_free(buffer)`, which resets the fields.
+#[repr(C)]
+pub struct ndb_buffer_t {
+    pub data: *mut u8,
+    pub len: usize,
+    pub free: Option<extern "C" fn(buffer: *mut ndb_buffer_t)>,
+}
+
 struct DbHandle {
-    db: Option<core::Db>,
+    /// Shared with the followers replicating into or out of it.
+    db: Option<Arc<core::Db>>,
     active_txn_count: AtomicUsize,
+    active_snapshot_count: AtomicUsize,
+    result_cache: ResultCache,
+    /// Log that `ndb_set_query_capture` appends executed queries to.
+    query_capture: RwLock<Option<core::QueryCapture>>,
+    /// Set by `ndb_set_lazy_entities`: JSON rows carry node and relationship
+    /// references instead of reified values.
+    lazy_entities: AtomicBool,
 }
 
 struct TxnHandle {
     db: *mut ndb_db_t,
     txn: Option<core::WriteTxn<'static>>,
+    /// Set once a statement fails; the transaction can then only roll back,
+    /// or return to a savepoint taken before the failure.
+    failed: bool,
+    /// Named savepoints in the order taken, with the `failed` flag at the time.
+    savepoints: Vec<(String, core::Savepoint, bool)>,
+}
+
+/// A read snapshot pinned by `ndb_begin_read`; every query on it sees the same commits.
+struct SnapshotHandle {
+    db: *mut ndb_db_t,
+    snapshot: core::DbSnapshot,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -84,10 +155,117 @@ struct StmtHandle {
     cypher: String,
     params: BTreeMap<String, Value>,
     executed: bool,
-    rows: Vec<Row>,
-    cursor: usize,
+    rows: StmtRows,
     current: Option<Row>,
     write_count: u32,
+    /// Result column names, from the plan or else the first row stepped.
+    /// Kept until the statement is finalized so returned names stay valid.
+    columns: Option<Vec<CString>>,
+}
+
+/// Rows a statement has not stepped over yet.
+enum StmtRows {
+    Done,
+    /// Read in full up front, for the result cache and query capture.
+    Buffered(std::vec::IntoIter<Row>),
+    Streaming(RowStream),
+}
+
+impl StmtRows {
+    fn next(&mut self) -> ApiResult<Option<Row>> {
+        let next = match self {
+            StmtRows::Done => Ok(None),
+            StmtRows::Buffered(rows) => Ok(rows.next()),
+            StmtRows::Streaming(stream) => stream.next(),
+        };
+        // Release the snapshot as soon as the rows run out or fail.
+        if !matches!(next, Ok(Some(_))) {
+            *self = StmtRows::Done;
+        }
+        next
+    }
+}
+
+type RowIter = Box<dyn Iterator<Item = nervusdb_query::Result<Row>>>;
+
+/// A read query pulled one row at a time as the caller steps, reifying only
+/// the current row. The open stream pins its snapshot like
+/// `ndb_begin_read`, so the database cannot close until the rows run out or
+/// the statement is reset or finalized.
+struct RowStream {
+    /// Borrows the fields below, so it is declared (and dropped) first.
+    rows: RowIter,
+    snapshot: Arc<core::DbSnapshot>,
+    _params: Arc<Params>,
+    _query: Arc<PreparedQuery>,
+    db: *mut ndb_db_t,
+}
+
+impl RowStream {
+    fn open(db: *mut ndb_db_t, cypher: &str, params: Params) -> ApiResult<Self> {
+        let query = prepare_statement(cypher)?;
+        if query.is_write() {
+            return Err(ApiError::execution(
+                "ndb_query/read API does not accept write statements",
+            ));
+        }
+        let handle = unsafe { db_handle_ref(db)? };
+        let snapshot = Arc::new(db_ref_from_handle(handle)?.snapshot());
+        let params = Arc::new(params);
+        let rows: Box<dyn Iterator<Item = nervusdb_query::Result<Row>> + '_> =
+            Box::new(query.execute_streaming(&*snapshot, &params));
+        let rows: RowIter = unsafe {
+            // SAFETY: the iterator borrows the snapshot, params and query,
+            // which stay at stable heap addresses in the same `RowStream`
+            // and are dropped after it.
+            std::mem::transmute::<Box<dyn Iterator<Item = nervusdb_query::Result<Row>> + '_>, RowIter>(
+                rows,
+            )
+        };
+        handle.active_snapshot_count.fetch_add(1, Ordering::SeqCst);
+        Ok(Self {
+            rows,
+            snapshot,
+            _params: params,
+            _query: query,
+            db,
+        })
+    }
+
+    fn next(&mut self) -> ApiResult<Option<Row>> {
+        match self.rows.next() {
+            None => Ok(None),
+            Some(row) => {
+                let row = row.map_err(|e| ApiError::from_query_message(&e.to_string()))?;
+                reify_row(row, &self.snapshot, false).map(Some)
+            }
+        }
+    }
+}
+
+impl Drop for RowStream {
+    fn drop(&mut self) {
+        if let Ok(handle) = unsafe { db_handle_ref(self.db) } {
+            handle.active_snapshot_count.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// An edge list cursor from `ndb_edges_open`. Like a streaming statement it
+/// pins its own snapshot until `ndb_edges_close`.
+struct EdgeStream {
+    /// Borrows the snapshot below, so it is declared (and dropped) first.
+    chunks: core::EdgeChunks<'static>,
+    _snapshot: Arc<core::DbSnapshot>,
+    db: *mut ndb_db_t,
+}
+
+impl Drop for EdgeStream {
+    fn drop(&mut self) {
+        if let Ok(handle) = unsafe { db_handle_ref(self.db) } {
+            handle.active_snapshot_count.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
 }
 
 struct ResultHandle {
@@ -112,6 +290,8 @@ type ApiResult<T> = Result<T, ApiError>;
 
 thread_local! {
     static LAST_ERROR: RefCell<LastError> = RefCell::new(LastError::default());
+    /// Trace ID of the last query this thread ran, whatever its outcome.
+    static LAST_TRACE: RefCell<String> = const { RefCell::new(String::new()) };
 }
 
 impl ApiError {
@@ -163,6 +343,14 @@ impl ApiError {
         Self::new(NDB_ERR_BUSY, NDB_ERRCAT_EXECUTION, message.into())
     }
 
+    fn cancelled() -> Self {
+        Self::new(
+            NDB_ERR_CANCELLED,
+            NDB_ERRCAT_EXECUTION,
+            core::Error::Cancelled.to_string(),
+        )
+    }
+
     fn internal(message: impl Into<String>) -> Self {
         Self::new(NDB_ERR_INTERNAL, NDB_ERRCAT_EXECUTION, message.into())
     }
@@ -170,10 +358,12 @@ impl ApiError {
     fn from_core(err: core::Error) -> Self {
         match err {
             core::Error::Compatibility(msg) => Self::compatibility(msg),
+            core::Error::Storage(msg) if msg.starts_with("write throttled") => Self::busy(msg),
             core::Error::Storage(msg) => Self::storage(msg),
             core::Error::Query(msg) => Self::from_query_message(&msg),
             core::Error::Other(msg) => Self::from_query_message(&msg),
             core::Error::Io(io_err) => Self::storage(io_err.to_string()),
+            core::Error::Cancelled => Self::cancelled(),
         }
     }
 
@@ -225,6 +415,19 @@ fn set_last_error(err: &ApiError) {
     });
 }
 
+/// Runs one query under the next trace ID of `db`'s session: the ID is
+/// current while it runs, readable afterwards through `ndb_last_trace_id`,
+/// and appended to the message when it fails.
+fn traced<T>(db: &core::Db, run: impl FnOnce() -> ApiResult<T>) -> ApiResult<T> {
+    let trace = db.next_trace_id();
+    LAST_TRACE.with(|slot| *slot.borrow_mut() = trace.to_string());
+    let _scope = trace.enter();
+    run().map_err(|mut err| {
+        err.message = format!("{} [trace {trace}]", err.message);
+        err
+    })
+}
+
 fn ok_status() -> c_int {
     clear_last_error();
     NDB_OK
@@ -254,6 +457,41 @@ fn cstr_to_json_value(ptr: *const c_char, name: &str) -> ApiResult<JsonValue> {
         .map_err(|e| ApiError::invalid(format!("{name} must be valid JSON: {e}")))
 }
 
+fn parse_property_list_json(ptr: *const c_char) -> ApiResult<Vec<String>> {
+    parse_string_list_json(ptr, "properties_json")
+}
+
+fn parse_string_list_json(ptr: *const c_char, name: &str) -> ApiResult<Vec<String>> {
+    let root = cstr_to_json_value(ptr, name)?;
+    root.as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| ApiError::invalid(format!("{name} must be a JSON array of strings")))
+}
+
+fn parse_endpoint_pairs_json(ptr: *const c_char) -> ApiResult<Vec<(String, String)>> {
+    let root = cstr_to_json_value(ptr, "endpoints_json")?;
+    root.as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|item| match item.as_array()?.as_slice() {
+                    [src, dst] => Some((src.as_str()?.to_string(), dst.as_str()?.to_string())),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| {
+            ApiError::invalid(
+                "endpoints_json must be a JSON array of [source label, target label] pairs",
+            )
+        })
+}
+
 fn parse_params_json(params: *const c_char) -> ApiResult<Params> {
     let mut out = Params::new();
     if params.is_null() {
@@ -302,6 +540,28 @@ fn json_to_query_value(v: &JsonValue) -> ApiResult<Value> {
             Ok(Value::List(out))
         }
         JsonValue::Object(map) => {
+            if let Some(typed) = parse_typed_json(map)? {
+                return Ok(match typed {
+                    TypedJson::Blob(bytes) => Value::Blob(bytes),
+                    TypedJson::DateTime(ts) => Value::DateTime(ts),
+                    TypedJson::Date(days) => date_value_from_epoch_days(days),
+                    TypedJson::LocalTime(nanos) => local_time_value_from_nanos(nanos),
+                    TypedJson::IntArray(items) => {
+                        Value::List(items.into_iter().map(Value::Int).collect())
+                    }
+                    TypedJson::FloatArray(items) => Value::List(
+                        items
+                            .into_iter()
+                            .map(|f| Value::Float(f64::from(f)))
+                            .collect(),
+                    ),
+                    TypedJson::Duration {
+                        months,
+                        days,
+                        nanos,
+                    } => duration_value_from_parts(months, days, nanos),
+                });
+            }
             let mut out = BTreeMap::new();
             for (k, v) in map {
                 out.insert(k.clone(), json_to_query_value(v)?);
@@ -311,6 +571,83 @@ fn json_to_query_value(v: &JsonValue) -> ApiResult<Value> {
     }
 }
 
+/// A value in its typed JSON envelope, e.g. `{"type": "date", "value": "2024-01-31"}`.
+enum TypedJson {
+    Blob(Vec<u8>),
+    DateTime(i64),
+    Date(i32),
+    LocalTime(i64),
+    Duration { months: i64, days: i64, nanos: i64 },
+    IntArray(Vec<i64>),
+    FloatArray(Vec<f32>),
+}
+
+/// Recognizes the typed blob, temporal and packed array envelopes. Objects of any other shape, including ones
+/// that merely have a `type` key, stay plain maps.
+fn parse_typed_json(map: &JsonMap<String, JsonValue>) -> ApiResult<Option<TypedJson>> {
+    let Some(kind) = map.get("type").and_then(JsonValue::as_str) else {
+        return Ok(None);
+    };
+    let value = map.get("value");
+    let scalar_shape = map.len() == 2 && value.is_some();
+    let parsed = match kind {
+        "blob" if scalar_shape => value
+            .and_then(JsonValue::as_str)
+            .and_then(|text| BASE64.decode(text).ok())
+            .map(TypedJson::Blob)
+            .ok_or("blob value must be a base64 string"),
+        "datetime" if scalar_shape => value
+            .and_then(JsonValue::as_i64)
+            .map(TypedJson::DateTime)
+            .ok_or("datetime value must be integer microseconds since epoch"),
+        "date" if scalar_shape => value
+            .and_then(JsonValue::as_str)
+            .and_then(epoch_days_from_date_literal)
+            .map(TypedJson::Date)
+            .ok_or("date value must be an ISO YYYY-MM-DD string"),
+        "localtime" if scalar_shape => value
+            .and_then(JsonValue::as_str)
+            .and_then(nanos_from_local_time_literal)
+            .map(TypedJson::LocalTime)
+            .ok_or("localtime value must be an ISO HH:MM[:SS[.fff]] string"),
+        "int_array" if scalar_shape => value
+            .and_then(JsonValue::as_array)
+            .and_then(|items| items.iter().map(JsonValue::as_i64).collect())
+            .map(TypedJson::IntArray)
+            .ok_or("int_array value must be an array of integers"),
+        "float_array" if scalar_shape => value
+            .and_then(JsonValue::as_array)
+            .and_then(|items| {
+                items
+                    .iter()
+                    .map(|item| item.as_f64().map(|f| f as f32))
+                    .collect()
+            })
+            .map(TypedJson::FloatArray)
+            .ok_or("float_array value must be an array of numbers"),
+        "duration"
+            if map
+                .keys()
+                .all(|k| matches!(k.as_str(), "type" | "months" | "days" | "nanos" | "value")) =>
+        {
+            let part = |key: &str| match map.get(key) {
+                None => Some(0),
+                Some(v) => v.as_i64(),
+            };
+            match (part("months"), part("days"), part("nanos")) {
+                (Some(months), Some(days), Some(nanos)) => Ok(TypedJson::Duration {
+                    months,
+                    days,
+                    nanos,
+                }),
+                _ => Err("duration months/days/nanos must be integers"),
+            }
+        }
+        _ => return Ok(None),
+    };
+    parsed.map(Some).map_err(ApiError::invalid)
+}
+
 fn json_to_property_value(v: &JsonValue) -> ApiResult<core::PropertyValue> {
     match v {
         JsonValue::Null => Ok(core::PropertyValue::Null),
@@ -333,6 +670,25 @@ fn json_to_property_value(v: &JsonValue) -> ApiResult<core::PropertyValue> {
             Ok(core::PropertyValue::List(out))
         }
         JsonValue::Object(map) => {
+            if let Some(typed) = parse_typed_json(map)? {
+                return Ok(match typed {
+                    TypedJson::Blob(bytes) => core::PropertyValue::Blob(bytes),
+                    TypedJson::DateTime(ts) => core::PropertyValue::DateTime(ts),
+                    TypedJson::Date(days) => core::PropertyValue::Date(days),
+                    TypedJson::LocalTime(nanos) => core::PropertyValue::LocalTime(nanos),
+                    TypedJson::IntArray(items) => core::PropertyValue::IntArray(items),
+                    TypedJson::FloatArray(items) => core::PropertyValue::FloatArray(items),
+                    TypedJson::Duration {
+                        months,
+                        days,
+                        nanos,
+                    } => core::PropertyValue::Duration {
+                        months,
+                        days,
+                        nanos,
+                    },
+                });
+            }
             let mut out = BTreeMap::new();
             for (k, v) in map {
                 out.insert(k.clone(), json_to_property_value(v)?);
@@ -342,6 +698,29 @@ fn json_to_property_value(v: &JsonValue) -> ApiResult<core::PropertyValue> {
     }
 }
 
+/// Duration maps from the query engine become `{"type": "duration", ...}` with the ISO
+/// form in `value`, mirroring what `parse_typed_json` accepts.
+fn duration_envelope(map: &BTreeMap<String, Value>) -> Option<JsonValue> {
+    if !matches!(map.get("__kind"), Some(Value::String(kind)) if kind == "duration") {
+        return None;
+    }
+    let part = |key: &str| match map.get(key) {
+        Some(Value::Int(v)) => Some(*v),
+        _ => None,
+    };
+    let display = match map.get("__display") {
+        Some(Value::String(s)) => json!(s),
+        _ => JsonValue::Null,
+    };
+    Some(json!({
+        "type": "duration",
+        "months": part("months")?,
+        "days": part("days")?,
+        "nanos": part("nanos")?,
+        "value": display,
+    }))
+}
+
 fn value_to_json(v: Value) -> JsonValue {
     match v {
         Value::Null => JsonValue::Null,
@@ -350,9 +729,12 @@ fn value_to_json(v: Value) -> JsonValue {
         Value::Float(f) => json!(f),
         Value::String(s) => json!(s),
         Value::DateTime(ts) => json!({ "type": "datetime", "value": ts }),
-        Value::Blob(bytes) => json!({ "type": "blob", "len": bytes.len() }),
+        Value::Blob(bytes) => json!({ "type": "blob", "value": BASE64.encode(bytes) }),
         Value::List(list) => JsonValue::Array(list.into_iter().map(value_to_json).collect()),
         Value::Map(map) => {
+            if let Some(envelope) = duration_envelope(&map) {
+                return envelope;
+            }
             let mut out = JsonMap::new();
             for (k, v) in map {
                 out.insert(k, value_to_json(v));
@@ -423,32 +805,8 @@ fn row_to_json(row: Row) -> JsonValue {
     JsonValue::Object(obj)
 }
 
-fn write_query_contains_write(cypher: &str) -> ApiResult<bool> {
-    let trimmed = cypher.trim_start();
-    if trimmed.len() >= 7 && trimmed[..7].eq_ignore_ascii_case("EXPLAIN") {
-        return Ok(false);
-    }
-    let parsed =
-        nervusdb_query::parse(cypher).map_err(|e| ApiError::from_query_message(&e.to_string()))?;
-    Ok(query_contains_write(&parsed))
-}
-
-fn query_contains_write(query: &ast::Query) -> bool {
-    query.clauses.iter().any(clause_contains_write)
-}
-
-fn clause_contains_write(clause: &ast::Clause) -> bool {
-    match clause {
-        ast::Clause::Create(_)
-        | ast::Clause::Merge(_)
-        | ast::Clause::Set(_)
-        | ast::Clause::Remove(_)
-        | ast::Clause::Delete(_)
-        | ast::Clause::Foreach(_) => true,
-        ast::Clause::Call(ast::CallClause::Subquery(q)) => query_contains_write(q),
-        ast::Clause::Union(union_clause) => query_contains_write(&union_clause.query),
-        _ => false,
-    }
+fn prepare_statement(cypher: &str) -> ApiResult<Arc<PreparedQuery>> {
+    prepare_cached(cypher).map_err(|e| ApiError::from_query_message(&e.to_string()))
 }
 
 unsafe fn db_handle_mut<'a>(db: *mut ndb_db_t) -> ApiResult<&'a mut DbHandle> {
@@ -474,14 +832,14 @@ unsafe fn db_handle_ref<'a>(db: *mut ndb_db_t) -> ApiResult<&'a DbHandle> {
 fn db_ref_from_handle(handle: &DbHandle) -> ApiResult<&core::Db> {
     handle
         .db
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| ApiError::execution("database handle has been closed"))
 }
 
-fn db_ref_from_handle_mut(handle: &mut DbHandle) -> ApiResult<&mut core::Db> {
+fn db_arc_from_handle(handle: &DbHandle) -> ApiResult<Arc<core::Db>> {
     handle
         .db
-        .as_mut()
+        .clone()
         .ok_or_else(|| ApiError::execution("database handle has been closed"))
 }
 
@@ -495,6 +853,16 @@ unsafe fn txn_handle_mut<'a>(txn: *mut ndb_txn_t) -> ApiResult<&'a mut TxnHandle
     })
 }
 
+unsafe fn snapshot_handle_ref<'a>(snapshot: *mut ndb_snapshot_t) -> ApiResult<&'a SnapshotHandle> {
+    if snapshot.is_null() {
+        return Err(ApiError::null_pointer("snapshot"));
+    }
+    Ok(unsafe {
+        // SAFETY: pointer validity is ensured by FFI lifecycle; all handles are allocated by this crate.
+        &*snapshot.cast::<SnapshotHandle>()
+    })
+}
+
 unsafe fn stmt_handle_mut<'a>(stmt: *mut ndb_stmt_t) -> ApiResult<&'a mut StmtHandle> {
     if stmt.is_null() {
         return Err(ApiError::null_pointer("stmt"));
@@ -545,68 +913,418 @@ fn make_result_handle_from_rows(rows: Vec<Row>) -> ApiResult<*mut ndb_result_t>
     make_result_handle_from_json(value)
 }
 
-fn execute_read_rows(db: &core::Db, cypher: &str, params: &Params) -> ApiResult<Vec<Row>> {
-    if write_query_contains_write(cypher)? {
+/// Builds `{"columns": [{"name", "type"}], "rows": [[...]]}`. Column order and types come from
+/// the rows: a column's type is the shared type of its non-null values, `any` when they differ
+/// and `null` when there are none.
+/// Result columns in first-seen order, each with the type shared by its
+/// non-null values: `None` when all are null, `"any"` when they differ.
+fn typed_columns(rows: &[Row]) -> Vec<(String, Option<&'static str>)> {
+    let mut columns: Vec<(String, Option<&'static str>)> = Vec::new();
+    for row in rows {
+        for (name, value) in row.columns() {
+            let idx = match columns.iter().position(|(n, _)| n == name) {
+                Some(idx) => idx,
+                None => {
+                    columns.push((name.clone(), None));
+                    columns.len() - 1
+                }
+            };
+            let kind = value_type_name(value);
+            if kind == "null" {
+                continue;
+            }
+            let slot = &mut columns[idx].1;
+            *slot = match *slot {
+                None => Some(kind),
+                Some(seen) if seen == kind => Some(kind),
+                Some(_) => Some("any"),
+            };
+        }
+    }
+    columns
+}
+
+fn make_typed_result_handle_from_rows(rows: Vec<Row>) -> ApiResult<*mut ndb_result_t> {
+    let columns = typed_columns(&rows);
+    let json_rows = rows
+        .into_iter()
+        .map(|row| {
+            let mut values = vec![JsonValue::Null; columns.len()];
+            for (name, value) in row.columns().iter().cloned() {
+                if let Some(idx) = columns.iter().position(|(n, _)| *n == name) {
+                    values[idx] = value_to_json(value);
+                }
+            }
+            JsonValue::Array(values)
+        })
+        .collect();
+    let json_columns = columns
+        .into_iter()
+        .map(|(name, kind)| json!({ "name": name, "type": kind.unwrap_or("null") }))
+        .collect();
+    make_result_handle_from_json(json!({
+        "columns": JsonValue::Array(json_columns),
+        "rows": JsonValue::Array(json_rows),
+    }))
+}
+
+/// Encodes rows as an Arrow IPC stream holding one record batch.
+///
+/// Columns are typed as in `ndb_query_typed`. Booleans, integers (int64, also
+/// node and external ids), floats (float64), strings, datetimes (UTC
+/// microsecond timestamps) and blobs (binary) are native Arrow columns; a
+/// column mixing integers and floats is float64 and an all-null one has the
+/// null type. Other values are JSON text as in `ndb_result_to_json`.
+fn arrow_ipc_stream(rows: Vec<Row>) -> ApiResult<Vec<u8>> {
+    use arrow_array::{
+        ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array, NullArray, RecordBatch,
+        RecordBatchOptions, StringArray, TimestampMicrosecondArray,
+    };
+    use arrow_schema::{Field, Schema};
+
+    let columns = typed_columns(&rows);
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
+    for (name, kind) in &columns {
+        let values: Vec<Option<&Value>> = rows
+            .iter()
+            .map(|row| {
+                row.columns()
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v)
+                    .filter(|v| !matches!(v, Value::Null))
+            })
+            .collect();
+        let numeric = values
+            .iter()
+            .flatten()
+            .all(|v| matches!(v, Value::Int(_) | Value::Float(_)));
+        let array: ArrayRef = match kind {
+            None => Arc::new(NullArray::new(values.len())),
+            Some("boolean") => Arc::new(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        Some(Value::Bool(b)) => Some(*b),
+                        _ => None,
+                    })
+                    .collect::<BooleanArray>(),
+            ),
+            Some("integer" | "node_id" | "external_id") => Arc::new(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        Some(Value::Int(i)) => Some(*i),
+                        Some(Value::NodeId(id)) => Some(i64::from(*id)),
+                        Some(Value::ExternalId(id)) => Some(*id as i64),
+                        _ => None,
+                    })
+                    .collect::<Int64Array>(),
+            ),
+            Some("float") | Some("any") if numeric => Arc::new(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        Some(Value::Float(f)) => Some(*f),
+                        Some(Value::Int(i)) => Some(*i as f64),
+                        _ => None,
+                    })
+                    .collect::<Float64Array>(),
+            ),
+            Some("string") => Arc::new(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        Some(Value::String(s)) => Some(s.as_str()),
+                        _ => None,
+                    })
+                    .collect::<StringArray>(),
+            ),
+            Some("datetime") => Arc::new(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        Some(Value::DateTime(micros)) => Some(*micros),
+                        _ => None,
+                    })
+                    .collect::<TimestampMicrosecondArray>()
+                    .with_timezone("UTC"),
+            ),
+            Some("blob") => Arc::new(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        Some(Value::Blob(bytes)) => Some(bytes.as_slice()),
+                        _ => None,
+                    })
+                    .collect::<BinaryArray>(),
+            ),
+            Some(_) => Arc::new(
+                values
+                    .iter()
+                    .map(|v| v.map(|v| value_to_json(v.clone()).to_string()))
+                    .collect::<StringArray>(),
+            ),
+        };
+        fields.push(Field::new(name.as_str(), array.data_type().clone(), true));
+        arrays.push(array);
+    }
+
+    let arrow_error = |e: arrow_schema::ArrowError| ApiError::execution(e.to_string());
+    let schema = Arc::new(Schema::new(fields));
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    let batch =
+        RecordBatch::try_new_with_options(schema.clone(), arrays, &options).map_err(arrow_error)?;
+    let mut writer =
+        arrow_ipc::writer::StreamWriter::try_new(Vec::new(), &schema).map_err(arrow_error)?;
+    writer.write(&batch).map_err(arrow_error)?;
+    writer.into_inner().map_err(arrow_error)
+}
+
+/// Runs a read query on a fresh snapshot, answering from the handle's result
+/// cache when it is enabled and nothing the query reads changed since.
+fn execute_read_rows(handle: &DbHandle, cypher: &str, params: &Params) -> ApiResult<Vec<Row>> {
+    let started = Instant::now();
+    let rows = execute_read_rows_cached(handle, cypher, params)?;
+    with_query_capture(handle, |capture| {
+        capture.record_read(cypher, params, &rows, started.elapsed())
+    });
+    Ok(rows)
+}
+
+/// Hands the handle's query capture, if any, to `record`. Capture is best
+/// effort: a log that cannot be written never fails the query.
+fn handle_captures_queries(handle: &DbHandle) -> bool {
+    handle
+        .query_capture
+        .read()
+        .is_ok_and(|capture| capture.is_some())
+}
+
+fn with_query_capture(
+    handle: &DbHandle,
+    record: impl FnOnce(&core::QueryCapture) -> core::Result<()>,
+) {
+    if let Ok(capture) = handle.query_capture.read()
+        && let Some(capture) = capture.as_ref()
+    {
+        let _ = record(capture);
+    }
+}
+
+/// Runs a read query for the JSON row APIs, which return node and
+/// relationship references instead when `ndb_set_lazy_entities` is on.
+/// Reference rows bypass the result cache.
+fn execute_json_rows(handle: &DbHandle, cypher: &str, params: &Params) -> ApiResult<Vec<Row>> {
+    if !handle.lazy_entities.load(Ordering::Relaxed) {
+        return execute_read_rows(handle, cypher, params);
+    }
+    let started = Instant::now();
+    let snapshot = db_ref_from_handle(handle)?.snapshot();
+    let rows = execute_read_rows_in(&snapshot, cypher, params, true)?;
+    if handle_captures_queries(handle) {
+        // The log digests reified rows, which is what a replay compares.
+        let reified = reify_rows(rows.clone(), &snapshot, false)?;
+        with_query_capture(handle, |capture| {
+            capture.record_read(cypher, params, &reified, started.elapsed())
+        });
+    }
+    Ok(rows)
+}
+
+fn execute_read_rows_cached(
+    handle: &DbHandle,
+    cypher: &str,
+    params: &Params,
+) -> ApiResult<Vec<Row>> {
+    let db = db_ref_from_handle(handle)?;
+    let cache = &handle.result_cache;
+    let read_set = if cache.is_enabled() {
+        prepare_statement(cypher)?.read_set()
+    } else {
+        None
+    };
+    let Some(read_set) = read_set else {
+        return execute_read_rows_in(&db.snapshot(), cypher, params, false);
+    };
+    let key = ResultKey::new(cypher, params);
+    if let Some(rows) = cache.get(key, |read_set, seq| db.changed_since(seq, read_set)) {
+        return Ok(rows.as_ref().clone());
+    }
+    // Read the sequence first: a commit landing in between then only makes
+    // the entry look stale, never fresher than its snapshot.
+    let seq = db.write_seq();
+    let rows = execute_read_rows_in(&db.snapshot(), cypher, params, false)?;
+    cache.insert(key, read_set, seq, Arc::new(rows.clone()));
+    Ok(rows)
+}
+
+fn execute_read_rows_in(
+    snapshot: &core::DbSnapshot,
+    cypher: &str,
+    params: &Params,
+    references: bool,
+) -> ApiResult<Vec<Row>> {
+    let prepared = prepare_statement(cypher)?;
+    if prepared.is_write() {
         return Err(ApiError::execution(
             "ndb_query/read API does not accept write statements",
         ));
     }
-    let prepared = prepare(cypher).map_err(|e| ApiError::from_query_message(&e.to_string()))?;
-    let snapshot = db.snapshot();
     let rows = prepared
-        .execute_streaming(&snapshot, params)
+        .execute_streaming(snapshot, params)
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
+    reify_rows(rows, snapshot, references)
+}
 
-    let mut out = Vec::with_capacity(rows.len());
-    for row in rows {
-        let mut reified = Vec::with_capacity(row.columns().len());
-        for (k, v) in row.columns().iter().cloned() {
-            let rv = v
-                .reify(&snapshot)
-                .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
-            reified.push((k, rv));
+/// Reifies the rows' values; with `references`, nodes and relationships are
+/// left as ids for the caller to fetch through `ndb_entity_get`.
+fn reify_rows(
+    rows: Vec<Row>,
+    snapshot: &core::DbSnapshot,
+    references: bool,
+) -> ApiResult<Vec<Row>> {
+    rows.into_iter()
+        .map(|row| reify_row(row, snapshot, references))
+        .collect()
+}
+
+fn reify_row(row: Row, snapshot: &core::DbSnapshot, references: bool) -> ApiResult<Row> {
+    let mut reified = Vec::with_capacity(row.columns().len());
+    for (k, v) in row.columns().iter() {
+        let rv = if references {
+            reify_except_entities(v, snapshot)
+        } else {
+            v.reify(snapshot)
         }
-        out.push(Row::new(reified));
+        .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
+        reified.push((k.clone(), rv));
+    }
+    Ok(Row::new(reified))
+}
+
+/// Like `Value::reify`, but keeps node ids and edge keys, inside lists and
+/// maps too. Paths are still reified whole.
+fn reify_except_entities(
+    value: &Value,
+    snapshot: &core::DbSnapshot,
+) -> nervusdb_query::Result<Value> {
+    match value {
+        Value::NodeId(_) | Value::EdgeKey(_) => Ok(value.clone()),
+        Value::List(items) => items
+            .iter()
+            .map(|item| reify_except_entities(item, snapshot))
+            .collect::<nervusdb_query::Result<_>>()
+            .map(Value::List),
+        Value::Map(map) => map
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), reify_except_entities(v, snapshot)?)))
+            .collect::<nervusdb_query::Result<_>>()
+            .map(Value::Map),
+        _ => value.reify(snapshot),
     }
-    Ok(out)
 }
 
 fn execute_write_count(db: &core::Db, cypher: &str, params: &Params) -> ApiResult<u32> {
-    if !write_query_contains_write(cypher)? {
+    let prepared = prepare_statement(cypher)?;
+    if !prepared.is_write() {
         return Err(ApiError::execution(
             "ndb_execute_write API expects a write statement",
         ));
     }
-    let prepared = prepare(cypher).map_err(|e| ApiError::from_query_message(&e.to_string()))?;
-    let snapshot = db.snapshot();
-    let mut txn = db.begin_write();
-    let (_rows, write_count) = prepared
-        .execute_mixed(&snapshot, &mut txn, params)
-        .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
-    txn.commit().map_err(ApiError::from_core)?;
+    ensure_writable(db)?;
+    // `USING PERIODIC COMMIT` commits each batch in a transaction of its own.
+    let mut commit_error = None;
+    let write_count = prepared
+        .execute_periodic(params, |params| {
+            let snapshot = db.snapshot();
+            let mut txn = db.begin_write();
+            let (_rows, write_count) = prepared.execute_mixed(&snapshot, &mut txn, params)?;
+            txn.commit().map_err(|e| {
+                let message = e.to_string();
+                commit_error = Some(e);
+                nervusdb_query::Error::Other(message)
+            })?;
+            Ok(write_count)
+        })
+        .map_err(|e| match commit_error.take() {
+            Some(e) => ApiError::from_core(e),
+            None => ApiError::from_query_message(&e.to_string()),
+        })?;
     Ok(write_count)
 }
 
+/// Fails up front on handles that refuse writes, before a write statement
+/// stages anything.
+fn ensure_writable(db: &core::Db) -> ApiResult<()> {
+    if db.is_read_only() {
+        return Err(ApiError::storage("database is read-only"));
+    }
+    Ok(())
+}
+
+fn reject_periodic_commit_in_txn(prepared: &PreparedQuery) -> ApiResult<()> {
+    if prepared.periodic_commit().is_some() {
+        return Err(ApiError::execution(
+            "USING PERIODIC COMMIT cannot run inside an explicit transaction",
+        ));
+    }
+    Ok(())
+}
+
+fn named_query_text(db: &core::Db, name: &str) -> ApiResult<String> {
+    db.named_query(name)
+        .ok_or_else(|| ApiError::invalid(format!("no stored query named '{name}'")))
+}
+
 fn execute_write_in_txn(
     db: &core::Db,
     txn: &mut core::WriteTxn<'static>,
     cypher: &str,
     params: &Params,
 ) -> ApiResult<u32> {
-    if !write_query_contains_write(cypher)? {
+    let prepared = prepare_statement(cypher)?;
+    if !prepared.is_write() {
         return Err(ApiError::execution(
             "ndb_txn_query API expects a write statement",
         ));
     }
-    let prepared = prepare(cypher).map_err(|e| ApiError::from_query_message(&e.to_string()))?;
-    let snapshot = db.snapshot();
+    reject_periodic_commit_in_txn(&prepared)?;
+    let snapshot = db.snapshot_in(txn);
     let (_rows, write_count) = prepared
         .execute_mixed(&snapshot, txn, params)
         .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
     Ok(write_count)
 }
 
+/// Runs a read or write statement inside `txn`. It sees the writes staged by earlier statements,
+/// and returned nodes and relationships reflect the writes of this one.
+fn execute_in_txn(
+    db: &core::Db,
+    txn: &mut core::WriteTxn<'static>,
+    cypher: &str,
+    params: &Params,
+) -> ApiResult<Vec<Row>> {
+    let prepared = prepare_statement(cypher)?;
+    reject_periodic_commit_in_txn(&prepared)?;
+    let snapshot = db.snapshot_in(txn);
+    let rows = if prepared.is_write() {
+        prepared
+            .execute_mixed(&snapshot, txn, params)
+            .map(|(rows, _)| {
+                rows.into_iter()
+                    .map(|row| Row::new(row.into_iter().collect()))
+                    .collect()
+            })
+    } else {
+        prepared.execute_streaming(&snapshot, params).collect()
+    }
+    .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
+    reify_rows(rows, &db.snapshot_in(txn), false)
+}
+
 fn stmt_execute_if_needed(stmt: &mut StmtHandle) -> ApiResult<()> {
     if stmt.executed {
         return Ok(());
@@ -615,24 +1333,37 @@ fn stmt_execute_if_needed(stmt: &mut StmtHandle) -> ApiResult<()> {
     let db_handle = unsafe { db_handle_ref(stmt.db)? };
     let db = db_ref_from_handle(db_handle)?;
     let params = params_from_map(&stmt.params);
+    // Drop any previous stream before opening the next one.
+    stmt.rows = StmtRows::Done;
+    stmt.current = None;
     match stmt.mode {
         StmtMode::Read => {
-            stmt.rows = execute_read_rows(db, &stmt.cypher, &params)?;
-            stmt.cursor = 0;
-            stmt.current = None;
+            // The result cache and query capture need the whole result.
+            let capturing = handle_captures_queries(db_handle);
+            stmt.rows = if db_handle.result_cache.is_enabled() || capturing {
+                StmtRows::Buffered(execute_read_rows(db_handle, &stmt.cypher, &params)?.into_iter())
+            } else {
+                StmtRows::Streaming(RowStream::open(stmt.db, &stmt.cypher, params)?)
+            };
             stmt.write_count = 0;
         }
         StmtMode::Write => {
             stmt.write_count = execute_write_count(db, &stmt.cypher, &params)?;
-            stmt.rows.clear();
-            stmt.cursor = 0;
-            stmt.current = None;
         }
     }
     stmt.executed = true;
     Ok(())
 }
 
+fn column_cstrings(names: Vec<String>) -> ApiResult<Vec<CString>> {
+    names
+        .into_iter()
+        .map(|name| {
+            CString::new(name).map_err(|_| ApiError::invalid("column name contains interior NUL"))
+        })
+        .collect()
+}
+
 fn stmt_current_value(stmt: &StmtHandle, col: usize) -> ApiResult<&Value> {
     let row = stmt
         .current
@@ -644,6 +1375,27 @@ fn stmt_current_value(stmt: &StmtHandle, col: usize) -> ApiResult<&Value> {
         .ok_or_else(|| ApiError::invalid("column index out of range"))
 }
 
+/// Type name reported in typed result envelopes.
+fn value_type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Int(_) => "integer",
+        Value::Float(_) => "float",
+        Value::String(_) => "string",
+        Value::DateTime(_) => "datetime",
+        Value::Blob(_) => "blob",
+        Value::List(_) => "list",
+        Value::Map(map) if duration_envelope(map).is_some() => "duration",
+        Value::Map(_) => "map",
+        Value::Node(_) => "node",
+        Value::Relationship(_) => "relationship",
+        Value::Path(_) | Value::ReifiedPath(_) | Value::EdgeKey(_) => "path",
+        Value::NodeId(_) => "node_id",
+        Value::ExternalId(_) => "external_id",
+    }
+}
+
 fn value_kind(v: &Value) -> c_int {
     match v {
         Value::Null => NDB_COL_NULL,
@@ -657,7 +1409,7 @@ fn value_kind(v: &Value) -> c_int {
         Value::Node(_) => NDB_COL_NODE,
         Value::Relationship(_) => NDB_COL_RELATIONSHIP,
         Value::Path(_) | Value::ReifiedPath(_) | Value::EdgeKey(_) => NDB_COL_PATH,
-        Value::Blob(_) => NDB_COL_OTHER,
+        Value::Blob(_) => NDB_COL_BLOB,
     }
 }
 
@@ -785,21 +1537,57 @@ pub extern "C" fn ndb_last_error_message(buf: *mut c_char, len: usize) -> usize
     bytes.len()
 }
 
+/// Copies the trace ID of the last query this thread ran, such as
+/// `4f1a9c-12`, like `ndb_last_error_message`. The ID also ends the message
+/// of a query's error, and the query capture log records it. Empty before
+/// the first query.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_last_trace_id(buf: *mut c_char, len: usize) -> usize {
+    let trace = LAST_TRACE.with(|slot| slot.borrow().clone());
+    let bytes = trace.as_bytes();
+    if buf.is_null() || len == 0 {
+        return bytes.len();
+    }
+
+    let copy_len = bytes.len().min(len.saturating_sub(1));
+    unsafe {
+        // SAFETY: caller provided writable buffer of at least `len` bytes.
+        ptr::copy_nonoverlapping(bytes.as_ptr().cast::<c_char>(), buf, copy_len);
+        *buf.add(copy_len) = 0;
+    }
+    bytes.len()
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn ndb_open(path: *const c_char, out_db: *mut *mut ndb_db_t) -> c_int {
+    ndb_open_with_flags(path, 0, out_db)
+}
+
+/// Opens the database at `path` like `ndb_open`. `flags` is a bitwise OR of
+/// `NDB_OPEN_*` values; unknown bits are an invalid argument.
+///
+/// With `NDB_OPEN_READ_ONLY`, the handle rejects every write however it is
+/// issued: write statements fail before they run, whether executed directly,
+/// prepared, stored or run in a transaction, `ndb_begin_write` fails, and so
+/// do checkpoints and schema changes. Hand such a handle to code that should
+/// only read. The database must already exist, and its files are never
+/// written, not even to recover a crashed WAL.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_open_with_flags(
+    path: *const c_char,
+    flags: c_int,
+    out_db: *mut *mut ndb_db_t,
+) -> c_int {
     let result = (|| -> ApiResult<()> {
         if out_db.is_null() {
             return Err(ApiError::null_pointer("out_db"));
         }
+        let options = open_options(flags)?;
         let path = cstr_to_string(path, "path")?;
-        let db = core::Db::open(path).map_err(ApiError::from_core)?;
-        let handle = Box::new(DbHandle {
-            db: Some(db),
-            active_txn_count: AtomicUsize::new(0),
-        });
+        let db = core::Db::open_with_options(path, options).map_err(ApiError::from_core)?;
         unsafe {
             // SAFETY: out pointer validated above.
-            *out_db = Box::into_raw(handle).cast::<ndb_db_t>();
+            *out_db = new_db_handle(db);
         }
         Ok(())
     })();
@@ -815,21 +1603,75 @@ pub extern "C" fn ndb_open_paths(
     ndb_path: *const c_char,
     wal_path: *const c_char,
     out_db: *mut *mut ndb_db_t,
+) -> c_int {
+    ndb_open_paths_with_flags(ndb_path, wal_path, 0, out_db)
+}
+
+/// Like `ndb_open_paths`, with `NDB_OPEN_*` flags as in `ndb_open_with_flags`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_open_paths_with_flags(
+    ndb_path: *const c_char,
+    wal_path: *const c_char,
+    flags: c_int,
+    out_db: *mut *mut ndb_db_t,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
         if out_db.is_null() {
             return Err(ApiError::null_pointer("out_db"));
         }
+        let options = open_options(flags)?;
         let ndb_path = cstr_to_string(ndb_path, "ndb_path")?;
         let wal_path = cstr_to_string(wal_path, "wal_path")?;
-        let db = core::Db::open_paths(ndb_path, wal_path).map_err(ApiError::from_core)?;
-        let handle = Box::new(DbHandle {
-            db: Some(db),
-            active_txn_count: AtomicUsize::new(0),
-        });
+        let db = core::Db::open_paths_with_options(ndb_path, wal_path, options)
+            .map_err(ApiError::from_core)?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_db = new_db_handle(db);
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+fn open_options(flags: c_int) -> ApiResult<core::EngineOptions> {
+    if flags & !NDB_OPEN_READ_ONLY != 0 {
+        return Err(ApiError::invalid(format!("unknown open flags: {flags:#x}")));
+    }
+    Ok(core::EngineOptions {
+        read_only: flags & NDB_OPEN_READ_ONLY != 0,
+        ..Default::default()
+    })
+}
+
+fn new_db_handle(db: core::Db) -> *mut ndb_db_t {
+    let handle = Box::new(DbHandle {
+        db: Some(Arc::new(db)),
+        active_txn_count: AtomicUsize::new(0),
+        active_snapshot_count: AtomicUsize::new(0),
+        result_cache: ResultCache::default(),
+        query_capture: RwLock::new(None),
+        lazy_entities: AtomicBool::new(false),
+    });
+    Box::into_raw(handle).cast::<ndb_db_t>()
+}
+
+/// Opens the packed image at `path`, written by `ndb_pack`, read-only.
+/// Writes, checkpoints and schema changes on it fail.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_open_packed(path: *const c_char, out_db: *mut *mut ndb_db_t) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_db.is_null() {
+            return Err(ApiError::null_pointer("out_db"));
+        }
+        let path = cstr_to_string(path, "path")?;
+        let db = core::Db::open_packed(path).map_err(ApiError::from_core)?;
         unsafe {
             // SAFETY: out pointer validated above.
-            *out_db = Box::into_raw(handle).cast::<ndb_db_t>();
+            *out_db = new_db_handle(db);
         }
         Ok(())
     })();
@@ -857,14 +1699,32 @@ pub extern "C" fn ndb_close(db: *mut ndb_db_t) -> c_int {
                 "cannot close database while write transaction is active",
             ));
         }
-        if let Some(real_db) = boxed.db {
-            real_db.close().map_err(ApiError::from_core)?;
+        if boxed.active_snapshot_count.load(Ordering::SeqCst) > 0 {
+            let raw = Box::into_raw(boxed);
+            let _ = raw;
+            return Err(ApiError::busy(
+                "cannot close database while read snapshot is active",
+            ));
         }
-        Ok(())
-    })();
-
-    match result {
-        Ok(()) => ok_status(),
+        if boxed
+            .db
+            .as_ref()
+            .is_some_and(|db| Arc::strong_count(db) > 1)
+        {
+            let raw = Box::into_raw(boxed);
+            let _ = raw;
+            return Err(ApiError::busy(
+                "cannot close database while a follower replicates into or from it",
+            ));
+        }
+        if let Some(real_db) = boxed.db.and_then(Arc::into_inner) {
+            real_db.close().map_err(ApiError::from_core)?;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
         Err(e) => err_status(e),
     }
 }
@@ -883,9 +1743,218 @@ pub extern "C" fn ndb_query(
         let cypher = cstr_to_string(cypher, "cypher")?;
         let params = parse_params_json(params_json)?;
         let handle = unsafe { db_handle_ref(db)? };
+        let rows = traced(db_ref_from_handle(handle)?, || {
+            execute_json_rows(handle, &cypher, &params)
+        })?;
+        let result_ptr = make_result_handle_from_rows(rows)?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Like `ndb_query`, but returns a typed envelope `{"columns": [{"name", "type"}], "rows": [[...]]}`
+/// whose rows are positional arrays in column order.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_query_typed(
+    db: *mut ndb_db_t,
+    cypher: *const c_char,
+    params_json: *const c_char,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
+        }
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let params = parse_params_json(params_json)?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let rows = traced(db_ref_from_handle(handle)?, || {
+            execute_read_rows(handle, &cypher, &params)
+        })?;
+        let result_ptr = make_typed_result_handle_from_rows(rows)?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Like `ndb_query_typed`, but writes the result to `out_buffer` as an Arrow
+/// IPC stream holding one record batch, for consumers reading columnar data.
+/// Release it with `out_buffer->free(out_buffer)`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_query_arrow(
+    db: *mut ndb_db_t,
+    cypher: *const c_char,
+    params_json: *const c_char,
+    out_buffer: *mut ndb_buffer_t,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_buffer.is_null() {
+            return Err(ApiError::null_pointer("out_buffer"));
+        }
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let params = parse_params_json(params_json)?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let rows = traced(db_ref_from_handle(handle)?, || {
+            execute_read_rows(handle, &cypher, &params)
+        })?;
+        let bytes = arrow_ipc_stream(rows)?.into_boxed_slice();
+        let len = bytes.len();
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_buffer = ndb_buffer_t {
+                data: Box::into_raw(bytes).cast::<u8>(),
+                len,
+                free: Some(ndb_buffer_free),
+            };
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Releases the bytes of a buffer filled by the library and resets its
+/// fields; safe to call again on the reset buffer.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_buffer_free(buffer: *mut ndb_buffer_t) {
+    if buffer.is_null() {
+        return;
+    }
+    let buffer = unsafe {
+        // SAFETY: caller passes a buffer filled by this library.
+        &mut *buffer
+    };
+    if !buffer.data.is_null() {
+        drop(unsafe {
+            // SAFETY: `data` and `len` come from a boxed slice leaked by this library.
+            Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len))
+        });
+    }
+    buffer.data = ptr::null_mut();
+    buffer.len = 0;
+}
+
+/// Runs a read query and writes only its row count; projected values are never built.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_query_count(
+    db: *mut ndb_db_t,
+    cypher: *const c_char,
+    params_json: *const c_char,
+    out_count: *mut u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_count.is_null() {
+            return Err(ApiError::null_pointer("out_count"));
+        }
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let params = parse_params_json(params_json)?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let count = traced(db_ref, || {
+            let prepared = prepare_statement(&cypher)?;
+            if prepared.is_write() {
+                return Err(ApiError::execution(
+                    "ndb_query_count does not accept write statements",
+                ));
+            }
+            prepared
+                .count_rows(&db_ref.snapshot(), &params)
+                .map_err(|e| ApiError::from_query_message(&e.to_string()))
+        })?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_count = count;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Runs a read query ending in `ORDER BY` one page at a time.
+///
+/// `token` is NULL for the first page and the previous `out_next_token`
+/// afterwards. `out_next_token` receives a token to free with
+/// `ndb_string_free`, or NULL after the last page.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn ndb_query_page(
+    db: *mut ndb_db_t,
+    cypher: *const c_char,
+    params_json: *const c_char,
+    page_size: u32,
+    token: *const c_char,
+    out_result: *mut *mut ndb_result_t,
+    out_next_token: *mut *mut c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
+        }
+        if out_next_token.is_null() {
+            return Err(ApiError::null_pointer("out_next_token"));
+        }
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let params = parse_params_json(params_json)?;
+        let token = if token.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(token, "token")?)
+        };
+        let handle = unsafe { db_handle_ref(db)? };
         let db_ref = db_ref_from_handle(handle)?;
-        let rows = execute_read_rows(db_ref, &cypher, &params)?;
+        let (rows, next_token) = traced(db_ref, || {
+            let prepared = prepare_statement(&cypher)?;
+            if prepared.is_write() {
+                return Err(ApiError::execution(
+                    "ndb_query_page does not accept write statements",
+                ));
+            }
+            let snapshot = db_ref.snapshot();
+            let page = prepared
+                .execute_page(&snapshot, &params, page_size as usize, token.as_deref())
+                .map_err(|e| ApiError::from_query_message(&e.to_string()))?;
+            let references = handle.lazy_entities.load(Ordering::Relaxed);
+            Ok((
+                reify_rows(page.rows, &snapshot, references)?,
+                page.next_token,
+            ))
+        })?;
         let result_ptr = make_result_handle_from_rows(rows)?;
+        match next_token {
+            Some(next) => {
+                if let Err(e) = write_out_c_string(out_next_token, &next) {
+                    ndb_result_free(result_ptr);
+                    return Err(e);
+                }
+            }
+            None => unsafe {
+                // SAFETY: out pointer validated above.
+                *out_next_token = ptr::null_mut();
+            },
+        }
         unsafe {
             // SAFETY: out pointer validated above.
             *out_result = result_ptr;
@@ -911,7 +1980,14 @@ pub extern "C" fn ndb_execute_write(
         let params = parse_params_json(params_json)?;
         let handle = unsafe { db_handle_ref(db)? };
         let db_ref = db_ref_from_handle(handle)?;
-        let affected = execute_write_count(db_ref, &cypher, &params)?;
+        let affected = traced(db_ref, || {
+            let started = Instant::now();
+            let affected = execute_write_count(db_ref, &cypher, &params)?;
+            with_query_capture(handle, |capture| {
+                capture.record_write(&cypher, &params, affected, started.elapsed())
+            });
+            Ok(affected)
+        })?;
         if !out_summary.is_null() {
             unsafe {
                 // SAFETY: output pointer is optional and only written when non-null.
@@ -978,6 +2054,18 @@ pub extern "C" fn ndb_string_free(s: *mut c_char) {
     }
 }
 
+/// Frees a buffer returned by `ndb_stmt_column_blob`; `len` must be the length it reported.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_blob_free(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    unsafe {
+        // SAFETY: pointer and length come from a boxed slice leaked by this crate.
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn ndb_begin_write(db: *mut ndb_db_t, out_txn: *mut *mut ndb_txn_t) -> c_int {
     let result = (|| -> ApiResult<()> {
@@ -985,7 +2073,8 @@ pub extern "C" fn ndb_begin_write(db: *mut ndb_db_t, out_txn: *mut *mut ndb_txn_
             return Err(ApiError::null_pointer("out_txn"));
         }
         let handle = unsafe { db_handle_mut(db)? };
-        let db_ref = db_ref_from_handle_mut(handle)?;
+        let db_ref = db_ref_from_handle(handle)?;
+        ensure_writable(db_ref)?;
         let txn = db_ref.begin_write();
         let txn_static: core::WriteTxn<'static> = unsafe {
             // SAFETY: lifecycle is enforced by retaining parent DB handle and active-txn gate on close.
@@ -995,6 +2084,8 @@ pub extern "C" fn ndb_begin_write(db: *mut ndb_db_t, out_txn: *mut *mut ndb_txn_
         let txn_handle = Box::new(TxnHandle {
             db,
             txn: Some(txn_static),
+            failed: false,
+            savepoints: Vec::new(),
         });
         unsafe {
             // SAFETY: out pointer validated above.
@@ -1024,8 +2115,11 @@ pub extern "C" fn ndb_txn_query(
             .ok_or_else(|| ApiError::execution("transaction is not active"))?;
         let db_handle = unsafe { db_handle_ref(txn_handle.db)? };
         let db_ref = db_ref_from_handle(db_handle)?;
-        let _ = execute_write_in_txn(db_ref, inner, &cypher, &params)?;
-        Ok(())
+        let executed = traced(db_ref, || {
+            execute_write_in_txn(db_ref, inner, &cypher, &params)
+        });
+        txn_handle.failed |= executed.is_err();
+        executed.map(|_| ())
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1033,23 +2127,38 @@ pub extern "C" fn ndb_txn_query(
     }
 }
 
+/// Runs a read or write statement inside the transaction and returns its rows, shaped like
+/// `ndb_query`. Reads see the writes staged by earlier statements of the transaction.
+///
+/// A failed statement may leave part of its writes staged, so it aborts the transaction: a later
+/// `ndb_txn_commit` rolls back and fails. The same holds for `ndb_txn_query`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_commit(txn: *mut ndb_txn_t) -> c_int {
+pub extern "C" fn ndb_txn_execute(
+    txn: *mut ndb_txn_t,
+    cypher: *const c_char,
+    params_json: *const c_char,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
     let result = (|| -> ApiResult<()> {
-        if txn.is_null() {
-            return Err(ApiError::null_pointer("txn"));
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
         }
-        let mut boxed = unsafe {
-            // SAFETY: pointer validity is guaranteed by lifecycle; function takes ownership.
-            Box::from_raw(txn.cast::<TxnHandle>())
-        };
-        let db_ptr = boxed.db;
-        let tx = boxed
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let params = parse_params_json(params_json)?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
             .txn
-            .take()
+            .as_mut()
             .ok_or_else(|| ApiError::execution("transaction is not active"))?;
-        tx.commit().map_err(ApiError::from_core)?;
-        decrement_active_txn_count(db_ptr);
+        let db_handle = unsafe { db_handle_ref(txn_handle.db)? };
+        let db_ref = db_ref_from_handle(db_handle)?;
+        let executed = traced(db_ref, || execute_in_txn(db_ref, inner, &cypher, &params));
+        txn_handle.failed |= executed.is_err();
+        let result_ptr = make_result_handle_from_rows(executed?)?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
+        }
         Ok(())
     })();
     match result {
@@ -1058,6 +2167,58 @@ pub extern "C" fn ndb_txn_commit(txn: *mut ndb_txn_t) -> c_int {
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_commit(txn: *mut ndb_txn_t) -> c_int {
+    match commit_txn_handle(txn) {
+        Ok(_) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Commits like `ndb_txn_commit` and writes the commit's bookmark (its txid)
+/// to `out_bookmark`, for use with `ndb_wait_for_bookmark`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_commit_bookmark(txn: *mut ndb_txn_t, out_bookmark: *mut u64) -> c_int {
+    if out_bookmark.is_null() {
+        return err_status(ApiError::null_pointer("out_bookmark"));
+    }
+    match commit_txn_handle(txn) {
+        Ok(bookmark) => {
+            unsafe {
+                // SAFETY: checked non-null above.
+                *out_bookmark = bookmark.txid();
+            }
+            ok_status()
+        }
+        Err(e) => err_status(e),
+    }
+}
+
+fn commit_txn_handle(txn: *mut ndb_txn_t) -> ApiResult<core::Bookmark> {
+    if txn.is_null() {
+        return Err(ApiError::null_pointer("txn"));
+    }
+    let mut boxed = unsafe {
+        // SAFETY: pointer validity is guaranteed by lifecycle; function takes ownership.
+        Box::from_raw(txn.cast::<TxnHandle>())
+    };
+    let db_ptr = boxed.db;
+    let tx = boxed
+        .txn
+        .take()
+        .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+    let committed = if boxed.failed {
+        tx.rollback();
+        Err(ApiError::execution(
+            "transaction rolled back: a statement in it failed",
+        ))
+    } else {
+        tx.commit_with_bookmark().map_err(ApiError::from_core)
+    };
+    decrement_active_txn_count(db_ptr);
+    committed
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn ndb_txn_rollback(txn: *mut ndb_txn_t) -> c_int {
     let result = (|| -> ApiResult<()> {
@@ -1069,7 +2230,9 @@ pub extern "C" fn ndb_txn_rollback(txn: *mut ndb_txn_t) -> c_int {
             Box::from_raw(txn.cast::<TxnHandle>())
         };
         let db_ptr = boxed.db;
-        let _ = boxed.txn.take();
+        if let Some(txn) = boxed.txn.take() {
+            txn.rollback();
+        }
         decrement_active_txn_count(db_ptr);
         Ok(())
     })();
@@ -1079,28 +2242,25 @@ pub extern "C" fn ndb_txn_rollback(txn: *mut ndb_txn_t) -> c_int {
     }
 }
 
+/// Pins a read snapshot of the committed state. Queries run with `ndb_query_in_snapshot` all see
+/// this state, whatever commits in between. Release it with `ndb_snapshot_release`; the database
+/// cannot be closed while a snapshot is held.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_create_node(
-    txn: *mut ndb_txn_t,
-    external_id: u64,
-    label_id: u32,
-    out_node_id: *mut u32,
+pub extern "C" fn ndb_begin_read(
+    db: *mut ndb_db_t,
+    out_snapshot: *mut *mut ndb_snapshot_t,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
-        if out_node_id.is_null() {
-            return Err(ApiError::null_pointer("out_node_id"));
+        if out_snapshot.is_null() {
+            return Err(ApiError::null_pointer("out_snapshot"));
         }
-        let txn_handle = unsafe { txn_handle_mut(txn)? };
-        let inner = txn_handle
-            .txn
-            .as_mut()
-            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
-        let node_id = inner
-            .create_node(external_id, label_id)
-            .map_err(ApiError::from_core)?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let snapshot = db_ref_from_handle(handle)?.snapshot();
+        handle.active_snapshot_count.fetch_add(1, Ordering::SeqCst);
+        let snapshot_handle = Box::new(SnapshotHandle { db, snapshot });
         unsafe {
-            // SAFETY: output pointer validated above.
-            *out_node_id = node_id;
+            // SAFETY: out pointer validated above.
+            *out_snapshot = Box::into_raw(snapshot_handle).cast::<ndb_snapshot_t>();
         }
         Ok(())
     })();
@@ -1110,52 +2270,365 @@ pub extern "C" fn ndb_txn_create_node(
     }
 }
 
+/// Runs a read query against a snapshot from `ndb_begin_read`; results are shaped like `ndb_query`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_get_or_create_label(
-    txn: *mut ndb_txn_t,
-    name: *const c_char,
-    out_label_id: *mut u32,
+pub extern "C" fn ndb_query_in_snapshot(
+    snapshot: *mut ndb_snapshot_t,
+    cypher: *const c_char,
+    params_json: *const c_char,
+    out_result: *mut *mut ndb_result_t,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
-        if out_label_id.is_null() {
-            return Err(ApiError::null_pointer("out_label_id"));
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
         }
-        let name = cstr_to_string(name, "name")?;
-        let txn_handle = unsafe { txn_handle_mut(txn)? };
-        let inner = txn_handle
-            .txn
-            .as_mut()
-            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
-        let label_id = inner
-            .get_or_create_label(&name)
-            .map_err(ApiError::from_core)?;
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let params = parse_params_json(params_json)?;
+        let handle = unsafe { snapshot_handle_ref(snapshot)? };
+        let db_handle = unsafe { db_handle_ref(handle.db)? };
+        let db_ref = db_ref_from_handle(db_handle)?;
+        let references = db_handle.lazy_entities.load(Ordering::Relaxed);
+        let rows = traced(db_ref, || {
+            execute_read_rows_in(&handle.snapshot, &cypher, &params, references)
+        })?;
+        let result_ptr = make_result_handle_from_rows(rows)?;
         unsafe {
-            // SAFETY: output pointer validated above.
-            *out_label_id = label_id;
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
         }
         Ok(())
     })();
+
     match result {
         Ok(()) => ok_status(),
         Err(e) => err_status(e),
     }
 }
 
+/// Releases a snapshot from `ndb_begin_read`. NULL is ignored.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_get_or_create_rel_type(
-    txn: *mut ndb_txn_t,
-    name: *const c_char,
-    out_rel_type_id: *mut u32,
-) -> c_int {
-    let result = (|| -> ApiResult<()> {
-        if out_rel_type_id.is_null() {
-            return Err(ApiError::null_pointer("out_rel_type_id"));
-        }
-        let name = cstr_to_string(name, "name")?;
-        let txn_handle = unsafe { txn_handle_mut(txn)? };
-        let inner = txn_handle
-            .txn
-            .as_mut()
+pub extern "C" fn ndb_snapshot_release(snapshot: *mut ndb_snapshot_t) {
+    if snapshot.is_null() {
+        return;
+    }
+    let boxed = unsafe {
+        // SAFETY: pointer was allocated by this crate; function takes ownership.
+        Box::from_raw(snapshot.cast::<SnapshotHandle>())
+    };
+    let handle = unsafe {
+        // SAFETY: the database cannot be closed while this snapshot is held.
+        boxed.db.cast::<DbHandle>().as_ref()
+    };
+    if let Some(handle) = handle {
+        handle.active_snapshot_count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Opens a cursor over every edge, or only those of type `rel_type`, for
+/// bulk export with `ndb_edges_next`. Weights come from the numeric edge
+/// property `weight_property`; edges without one, and every edge when it is
+/// NULL, weigh 1.0. The cursor pins a snapshot, so the database cannot be
+/// closed until `ndb_edges_close`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_edges_open(
+    db: *mut ndb_db_t,
+    rel_type: *const c_char,
+    weight_property: *const c_char,
+    out_edges: *mut *mut ndb_edges_t,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_edges.is_null() {
+            return Err(ApiError::null_pointer("out_edges"));
+        }
+        let rel_type = if rel_type.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(rel_type, "rel_type")?)
+        };
+        let weight_property = if weight_property.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(weight_property, "weight_property")?)
+        };
+        let handle = unsafe { db_handle_ref(db)? };
+        let snapshot = Arc::new(db_ref_from_handle(handle)?.snapshot());
+        let chunks = snapshot.edges_chunked(rel_type.as_deref(), weight_property.as_deref(), 1);
+        let chunks = unsafe {
+            // SAFETY: the chunks borrow the snapshot, which stays at a stable
+            // heap address in the same `EdgeStream` and is dropped after it.
+            std::mem::transmute::<core::EdgeChunks<'_>, core::EdgeChunks<'static>>(chunks)
+        };
+        handle.active_snapshot_count.fetch_add(1, Ordering::SeqCst);
+        let stream = Box::new(EdgeStream {
+            chunks,
+            _snapshot: snapshot,
+            db,
+        });
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_edges = Box::into_raw(stream).cast::<ndb_edges_t>();
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes up to `capacity` edges from the cursor into the caller's arrays:
+/// external source ids to `out_src`, external target ids to `out_dst` and
+/// weights to `out_weight` (optional; weights are not read when it is NULL).
+/// `out_len` receives the number written, 0 once every edge has been returned.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_edges_next(
+    edges: *mut ndb_edges_t,
+    capacity: usize,
+    out_src: *mut u64,
+    out_dst: *mut u64,
+    out_weight: *mut f64,
+    out_len: *mut usize,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_len.is_null() {
+            return Err(ApiError::null_pointer("out_len"));
+        }
+        if capacity > 0 && out_src.is_null() {
+            return Err(ApiError::null_pointer("out_src"));
+        }
+        if capacity > 0 && out_dst.is_null() {
+            return Err(ApiError::null_pointer("out_dst"));
+        }
+        let stream = unsafe {
+            // SAFETY: pointer was allocated by this crate and is not shared.
+            edges.cast::<EdgeStream>().as_mut()
+        }
+        .ok_or_else(|| ApiError::null_pointer("edges"))?;
+        let len = if capacity == 0 {
+            0
+        } else {
+            let (src, dst, weight) = unsafe {
+                // SAFETY: the caller provides `capacity` writable elements in
+                // each non-null array.
+                (
+                    std::slice::from_raw_parts_mut(out_src, capacity),
+                    std::slice::from_raw_parts_mut(out_dst, capacity),
+                    (!out_weight.is_null())
+                        .then(|| std::slice::from_raw_parts_mut(out_weight, capacity)),
+                )
+            };
+            stream.chunks.fill(src, dst, weight)
+        };
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_len = len;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Closes a cursor from `ndb_edges_open` and releases its snapshot. NULL is ignored.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_edges_close(edges: *mut ndb_edges_t) {
+    if edges.is_null() {
+        return;
+    }
+    drop(unsafe {
+        // SAFETY: pointer was allocated by this crate; function takes ownership.
+        Box::from_raw(edges.cast::<EdgeStream>())
+    });
+}
+
+/// Tags the transaction with an idempotency key. `out_already_applied`, if
+/// non-null, receives 1 when a transaction with this key has already
+/// committed; committing this one then applies nothing.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_set_idempotency_key(
+    txn: *mut ndb_txn_t,
+    key: *const c_char,
+    out_already_applied: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let key = cstr_to_string(key, "key")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        let applied = inner
+            .set_idempotency_key(&key)
+            .map_err(ApiError::from_core)?;
+        if !out_already_applied.is_null() {
+            unsafe {
+                // SAFETY: checked non-null above; caller provides a writable c_int.
+                *out_already_applied = c_int::from(applied.is_some());
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Defers unique constraint checks on the transaction's later writes to
+/// commit when `deferred` is non-zero. With 0, checks the writes deferred so
+/// far, failing with a constraint violation if one breaks a constraint, and
+/// checks each later write as it is staged.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_set_constraints_deferred(txn: *mut ndb_txn_t, deferred: c_int) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        inner
+            .set_constraints_deferred(deferred != 0)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Takes a savepoint named `name` capturing the changes staged so far. A
+/// savepoint with the same name is replaced.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_savepoint(txn: *mut ndb_txn_t, name: *const c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let savepoint = txn_handle
+            .txn
+            .as_ref()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?
+            .savepoint();
+        txn_handle.savepoints.retain(|(n, _, _)| *n != name);
+        txn_handle
+            .savepoints
+            .push((name, savepoint, txn_handle.failed));
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Discards the changes staged since savepoint `name` was taken, along with
+/// the savepoints taken after it. The savepoint itself stays usable. Rolling
+/// back past a failed statement lets the transaction commit again.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_rollback_to(txn: *mut ndb_txn_t, name: *const c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let pos = txn_handle
+            .savepoints
+            .iter()
+            .position(|(n, _, _)| *n == name)
+            .ok_or_else(|| ApiError::execution(format!("no savepoint named '{name}'")))?;
+        txn_handle.savepoints.truncate(pos + 1);
+        let (_, savepoint, failed) = &txn_handle.savepoints[pos];
+        txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?
+            .rollback_to(savepoint)
+            .map_err(ApiError::from_core)?;
+        txn_handle.failed = *failed;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_create_node(
+    txn: *mut ndb_txn_t,
+    external_id: u64,
+    label_id: u32,
+    out_node_id: *mut u32,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_node_id.is_null() {
+            return Err(ApiError::null_pointer("out_node_id"));
+        }
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        let node_id = inner
+            .create_node(external_id, label_id)
+            .map_err(ApiError::from_core)?;
+        unsafe {
+            // SAFETY: output pointer validated above.
+            *out_node_id = node_id;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_get_or_create_label(
+    txn: *mut ndb_txn_t,
+    name: *const c_char,
+    out_label_id: *mut u32,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_label_id.is_null() {
+            return Err(ApiError::null_pointer("out_label_id"));
+        }
+        let name = cstr_to_string(name, "name")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        let label_id = inner
+            .get_or_create_label(&name)
+            .map_err(ApiError::from_core)?;
+        unsafe {
+            // SAFETY: output pointer validated above.
+            *out_label_id = label_id;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_get_or_create_rel_type(
+    txn: *mut ndb_txn_t,
+    name: *const c_char,
+    out_rel_type_id: *mut u32,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_rel_type_id.is_null() {
+            return Err(ApiError::null_pointer("out_rel_type_id"));
+        }
+        let name = cstr_to_string(name, "name")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
             .ok_or_else(|| ApiError::execution("transaction is not active"))?;
         let rel_type_id = inner
             .get_or_create_rel_type(&name)
@@ -1284,22 +2757,2077 @@ pub extern "C" fn ndb_txn_set_edge_property(
     }
 }
 
+/// Makes `node` expire at `at_ms` milliseconds since the Unix epoch, by
+/// setting its `expireAt` property; see `ndb_set_expiry_sweep`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_set_node_expiry(txn: *mut ndb_txn_t, node: u32, at_ms: i64) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let offset = std::time::Duration::from_millis(at_ms.unsigned_abs());
+        let at = if at_ms >= 0 {
+            std::time::UNIX_EPOCH + offset
+        } else {
+            std::time::UNIX_EPOCH - offset
+        };
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        inner
+            .set_node_expiry(node, at)
+            .map_err(ApiError::from_core)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_remove_node_property(
+    txn: *mut ndb_txn_t,
+    node: u32,
+    key: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let key = cstr_to_string(key, "key")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        inner
+            .remove_node_property(node, &key)
+            .map_err(ApiError::from_core)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_remove_edge_property(
+    txn: *mut ndb_txn_t,
+    src: u32,
+    rel: u32,
+    dst: u32,
+    key: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let key = cstr_to_string(key, "key")?;
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        inner
+            .remove_edge_property(src, rel, dst, &key)
+            .map_err(ApiError::from_core)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_txn_set_vector(
+    txn: *mut ndb_txn_t,
+    node: u32,
+    vector: *const f32,
+    len: usize,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if vector.is_null() && len > 0 {
+            return Err(ApiError::null_pointer("vector"));
+        }
+        let slice = if len == 0 {
+            &[][..]
+        } else {
+            unsafe {
+                // SAFETY: pointer validated above and caller provides `len` elements.
+                std::slice::from_raw_parts(vector, len)
+            }
+        };
+        let txn_handle = unsafe { txn_handle_mut(txn)? };
+        let inner = txn_handle
+            .txn
+            .as_mut()
+            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
+        inner
+            .set_vector(node, slice.to_vec())
+            .map_err(ApiError::from_core)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_compact(db: *mut ndb_db_t) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref.compact().map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Like `ndb_compact`, reporting `(done, total)` to `progress` and to the
+/// optional `op`. Cancelling `op` fails the call with `NDB_ERR_CANCELLED`
+/// as long as the property store has not started being rewritten.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_compact_with_progress(
+    db: *mut ndb_db_t,
+    op: *mut ndb_operation_t,
+    progress: ndb_progress_fn,
+    user_data: *mut c_void,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let operation = unsafe { operation_or_default(op) };
+        db_ref
+            .compact_with_progress(&operation, |done, total| {
+                if let Some(progress) = progress {
+                    progress(done, total, user_data);
+                }
+            })
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_checkpoint(db: *mut ndb_db_t) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref.checkpoint().map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Checkpoints, then writes the database to `out_path` as a compressed,
+/// read-only packed image for `ndb_open_packed`. `out_report_json`
+/// (optional) receives `{"pages", "uncompressed_bytes", "packed_bytes"}`;
+/// free it with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_pack(
+    db: *mut ndb_db_t,
+    out_path: *const c_char,
+    out_report_json: *mut *mut c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let out_path = cstr_to_string(out_path, "out_path")?;
+        let report = db_ref.pack(out_path).map_err(ApiError::from_core)?;
+        if !out_report_json.is_null() {
+            let text =
+                serde_json::to_string(&report).map_err(|e| ApiError::internal(e.to_string()))?;
+            write_out_c_string(out_report_json, &text)?;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Checkpoints automatically on a background thread every `interval_ms`
+/// milliseconds after a write, and once the WAL reaches `wal_bytes` bytes;
+/// 0 disables either trigger, and both 0 stops the thread.
+/// `io_bytes_per_sec` (0: unthrottled) caps the average checkpoint IO.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_auto_checkpoint(
+    db: *mut ndb_db_t,
+    interval_ms: u64,
+    wal_bytes: u64,
+    io_bytes_per_sec: u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let policy = (interval_ms > 0 || wal_bytes > 0).then(|| core::AutoCheckpoint {
+            interval: (interval_ms > 0).then(|| std::time::Duration::from_millis(interval_ms)),
+            wal_bytes,
+            io_bytes_per_sec,
+        });
+        db_ref.set_auto_checkpoint(policy);
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Holds off automatic checkpoints until `ndb_resume_checkpoints`, waiting
+/// for one in progress to finish. `ndb_checkpoint` still runs.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_pause_checkpoints(db: *mut ndb_db_t) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        db_ref_from_handle(handle)?.pause_checkpoints();
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_resume_checkpoints(db: *mut ndb_db_t) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        db_ref_from_handle(handle)?.resume_checkpoints();
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Slows writers down while checkpoints fall behind: once the WAL reaches
+/// `delay_wal_bytes` bytes each new write transaction waits `delay_ms`
+/// milliseconds, and once it reaches `reject_wal_bytes` commits fail with
+/// `NDB_ERR_BUSY`. 0 disables either watermark; both 0 turns throttling off.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_write_throttle(
+    db: *mut ndb_db_t,
+    delay_wal_bytes: u64,
+    delay_ms: u64,
+    reject_wal_bytes: u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let throttle = (delay_wal_bytes > 0 || reject_wal_bytes > 0).then(|| core::WriteThrottle {
+            delay_wal_bytes,
+            delay: std::time::Duration::from_millis(delay_ms),
+            reject_wal_bytes,
+        });
+        db_ref.set_write_throttle(throttle);
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Deletes expired nodes, with their relationships, and expired relationships
+/// on a background thread every `interval_ms` milliseconds; 0 stops the
+/// thread. Entities expire once their `expireAt` property, a datetime or
+/// milliseconds since the Unix epoch, has passed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_expiry_sweep(db: *mut ndb_db_t, interval_ms: u64) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref.set_expiry_sweep(
+            (interval_ms > 0).then(|| std::time::Duration::from_millis(interval_ms)),
+        );
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Deletes what has expired now, in one commit. `out_nodes` and `out_edges`
+/// (optional) receive how many nodes and relationships were deleted.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_sweep_expired(
+    db: *mut ndb_db_t,
+    out_nodes: *mut u64,
+    out_edges: *mut u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let report = db_ref.sweep_expired().map_err(ApiError::from_core)?;
+        unsafe {
+            // SAFETY: output pointers are optional and only written when non-null.
+            if !out_nodes.is_null() {
+                *out_nodes = report.nodes;
+            }
+            if !out_edges.is_null() {
+                *out_edges = report.edges;
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes totals over the handle's expiry sweeps to `out_stats_json` as
+/// `{"sweeps", "nodes_purged", "edges_purged", "failures"}`. Free it with
+/// `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_expiry_stats(db: *mut ndb_db_t, out_stats_json: *mut *mut c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let stats = db_ref_from_handle(handle)?.expiry_stats();
+        let json = json!({
+            "sweeps": stats.sweeps,
+            "nodes_purged": stats.nodes_purged,
+            "edges_purged": stats.edges_purged,
+            "failures": stats.failures,
+        });
+        write_out_c_string(out_stats_json, &json.to_string())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the bookmark of the latest commit visible to new reads.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_last_bookmark(db: *mut ndb_db_t, out_bookmark: *mut u64) -> c_int {
+    let result = (|| -> ApiResult<u64> {
+        if out_bookmark.is_null() {
+            return Err(ApiError::null_pointer("out_bookmark"));
+        }
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        Ok(db_ref.last_bookmark().txid())
+    })();
+    match result {
+        Ok(bookmark) => {
+            unsafe {
+                // SAFETY: checked non-null above.
+                *out_bookmark = bookmark;
+            }
+            ok_status()
+        }
+        Err(e) => err_status(e),
+    }
+}
+
+/// Blocks until the commit identified by `bookmark` is visible to new reads,
+/// failing after `timeout_ms` milliseconds.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_wait_for_bookmark(
+    db: *mut ndb_db_t,
+    bookmark: u64,
+    timeout_ms: u32,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .wait_for_bookmark(
+                bookmark.into(),
+                std::time::Duration::from_millis(u64::from(timeout_ms)),
+            )
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Starts replicating `primary` into `replica`, both open in this process,
+/// from a background thread. Only the follower may write to `replica`, and
+/// neither database can be closed until `ndb_follower_stop`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_follower_start(
+    replica: *mut ndb_db_t,
+    primary: *mut ndb_db_t,
+    out_follower: *mut *mut ndb_follower_t,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_follower.is_null() {
+            return Err(ApiError::null_pointer("out_follower"));
+        }
+        if replica == primary {
+            return Err(ApiError::invalid("a database cannot follow itself"));
+        }
+        let primary = db_arc_from_handle(unsafe { db_handle_ref(primary)? })?;
+        let replica = db_arc_from_handle(unsafe { db_handle_ref(replica)? })?;
+        ensure_writable(&replica)?;
+        let follower = core::Follower::start(replica, primary, core::FollowerOptions::default())
+            .map_err(ApiError::from_core)?;
+        unsafe {
+            // SAFETY: output pointer validated above.
+            *out_follower = Box::into_raw(Box::new(follower)).cast::<ndb_follower_t>();
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the bookmark of the last primary commit the follower applied, to
+/// compare with the primary's commit bookmarks.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_follower_applied_bookmark(
+    follower: *mut ndb_follower_t,
+    out_bookmark: *mut u64,
+) -> c_int {
+    let follower = unsafe {
+        // SAFETY: non-null pointers come from `ndb_follower_start`.
+        follower.cast::<core::Follower>().as_ref()
+    };
+    let Some(follower) = follower else {
+        return err_status(ApiError::null_pointer("follower"));
+    };
+    if out_bookmark.is_null() {
+        return err_status(ApiError::null_pointer("out_bookmark"));
+    }
+    unsafe {
+        // SAFETY: checked non-null above.
+        *out_bookmark = follower.state().applied_txid;
+    }
+    ok_status()
+}
+
+/// Blocks until the follower has applied the primary commit `bookmark`,
+/// failing after `timeout_ms` milliseconds.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_follower_wait(
+    follower: *mut ndb_follower_t,
+    bookmark: u64,
+    timeout_ms: u32,
+) -> c_int {
+    let follower = unsafe {
+        // SAFETY: non-null pointers come from `ndb_follower_start`.
+        follower.cast::<core::Follower>().as_ref()
+    };
+    let Some(follower) = follower else {
+        return err_status(ApiError::null_pointer("follower"));
+    };
+    match follower.wait_for_txid(
+        bookmark,
+        std::time::Duration::from_millis(u64::from(timeout_ms)),
+    ) {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(ApiError::from_core(e)),
+    }
+}
+
+/// Stops the follower once its current poll returns and frees it.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_follower_stop(follower: *mut ndb_follower_t) {
+    if follower.is_null() {
+        return;
+    }
+    let follower = unsafe {
+        // SAFETY: pointer was allocated by `ndb_follower_start`; function takes ownership.
+        Box::from_raw(follower.cast::<core::Follower>())
+    };
+    follower.stop();
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_create_index(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    property: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let property = cstr_to_string(property, "property")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .create_index(&label, &property)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_create_text_index(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    property: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let property = cstr_to_string(property, "property")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .create_text_index(&label, &property)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Creates an equality index over several properties of `label`.
+/// `properties_json` is a JSON array of property names in index order.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_create_composite_index(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    properties_json: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let properties = parse_property_list_json(properties_json)?;
+        let properties: Vec<&str> = properties.iter().map(String::as_str).collect();
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .create_composite_index(&label, &properties)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Drops the equality index on `label` over the JSON array `properties_json`
+/// (one name for a single-property index). `out_dropped` is optional.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_drop_index(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    properties_json: *const c_char,
+    out_dropped: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let properties = parse_property_list_json(properties_json)?;
+        let properties: Vec<&str> = properties.iter().map(String::as_str).collect();
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let dropped = db_ref
+            .drop_index(&label, &properties)
+            .map_err(ApiError::from_core)?;
+        if !out_dropped.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_dropped = c_int::from(dropped);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Requires `property` to be unique among nodes labelled `label`. Fails if
+/// existing nodes already hold duplicate values.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_create_unique_constraint(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    property: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let property = cstr_to_string(property, "property")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .create_unique_constraint(&label, &property)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Drops the unique constraint on `label.property`. `out_dropped` is optional.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_drop_unique_constraint(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    property: *const c_char,
+    out_dropped: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let property = cstr_to_string(property, "property")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let dropped = db_ref
+            .drop_unique_constraint(&label, &property)
+            .map_err(ApiError::from_core)?;
+        if !out_dropped.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_dropped = c_int::from(dropped);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Restricts relationships of type `rel_type` to the label pairs in
+/// `endpoints_json`, a JSON array of `[source label, target label]` pairs,
+/// replacing any earlier restriction. Fails if an existing relationship
+/// already connects other labels.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_create_endpoint_constraint(
+    db: *mut ndb_db_t,
+    rel_type: *const c_char,
+    endpoints_json: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let rel_type = cstr_to_string(rel_type, "rel_type")?;
+        let endpoints = parse_endpoint_pairs_json(endpoints_json)?;
+        let endpoints: Vec<(&str, &str)> = endpoints
+            .iter()
+            .map(|(src, dst)| (src.as_str(), dst.as_str()))
+            .collect();
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .create_endpoint_constraint(&rel_type, &endpoints)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Lifts the endpoint constraint on `rel_type`. `out_dropped` is optional.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_drop_endpoint_constraint(
+    db: *mut ndb_db_t,
+    rel_type: *const c_char,
+    out_dropped: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let rel_type = cstr_to_string(rel_type, "rel_type")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let dropped = db_ref
+            .drop_endpoint_constraint(&rel_type)
+            .map_err(ApiError::from_core)?;
+        if !out_dropped.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_dropped = c_int::from(dropped);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Marks `label.property` as sensitive when `sensitive` is non-zero, or
+/// removes the marker. `out_changed` is optional and set to whether the marker
+/// changed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_sensitive(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    property: *const c_char,
+    sensitive: c_int,
+    out_changed: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let property = cstr_to_string(property, "property")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let changed = if sensitive != 0 {
+            db_ref.mark_sensitive(&label, &property)
+        } else {
+            db_ref.unmark_sensitive(&label, &property)
+        }
+        .map_err(ApiError::from_core)?;
+        if !out_changed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_changed = c_int::from(changed);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Turns redaction of sensitive properties on (non-zero) or off for queries
+/// run through this handle. Redacted values read as `"[REDACTED]"`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_redaction(db: *mut ndb_db_t, enabled: c_int) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref.set_redaction(enabled != 0);
+        // Cached rows were reified under the previous setting.
+        handle.result_cache.clear();
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Turns deterministic execution on (non-zero) or off for queries run through
+/// this handle: edges are expanded and index hits returned in sorted order,
+/// so results without `ORDER BY` come out the same across storage layouts.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_deterministic(db: *mut ndb_db_t, enabled: c_int) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref.set_deterministic(enabled != 0);
+        // Cached rows keep the order they were computed in.
+        handle.result_cache.clear();
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Turns entity references on (non-zero) or off for the JSON rows of
+/// `ndb_query`, `ndb_query_page`, `ndb_query_in_snapshot` and
+/// `ndb_query_named` on this handle. Nodes then come back as
+/// `{"type": "node_id", "value"}` and relationships as
+/// `{"type": "edge_key", "src", "rel", "dst"}`, without their labels or
+/// properties being read; pass one to `ndb_entity_get` for those. Paths are
+/// still returned whole, and reference rows bypass the result cache.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_lazy_entities(db: *mut ndb_db_t, enabled: c_int) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        db_ref_from_handle(handle)?;
+        handle.lazy_entities.store(enabled != 0, Ordering::Relaxed);
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Reads the entity behind a reference returned with `ndb_set_lazy_entities`
+/// on, as of the latest commit. With `key` NULL, writes the whole node or
+/// relationship in the usual row encoding to `out_json`; otherwise writes
+/// just that property's value, `null` when unset. Free it with
+/// `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_entity_get(
+    db: *mut ndb_db_t,
+    ref_json: *const c_char,
+    key: *const c_char,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let reference: JsonValue = serde_json::from_str(&cstr_to_string(ref_json, "ref_json")?)
+            .map_err(|e| ApiError::invalid(format!("invalid entity reference: {e}")))?;
+        let entity = entity_from_reference(&reference)?;
+        let key = if key.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(key, "key")?)
+        };
+        let handle = unsafe { db_handle_ref(db)? };
+        let snapshot = db_ref_from_handle(handle)?.snapshot();
+        let value = match (key, &entity) {
+            (None, _) => entity
+                .reify(&snapshot)
+                .map_err(|e| ApiError::from_query_message(&e.to_string()))?,
+            (Some(key), Value::NodeId(id)) => snapshot
+                .node_property(*id, &key)
+                .map_or(Value::Null, |v| convert_api_property_to_value(&v)),
+            (Some(key), Value::EdgeKey(edge)) => snapshot
+                .edge_property(*edge, &key)
+                .map_or(Value::Null, |v| convert_api_property_to_value(&v)),
+            _ => Value::Null,
+        };
+        let text = serde_json::to_string(&value_to_json(value))
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        write_out_c_string(out_json, &text)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Decodes the `node_id` and `edge_key` envelopes of `value_to_json`.
+fn entity_from_reference(reference: &JsonValue) -> ApiResult<Value> {
+    let field = |name: &str| -> ApiResult<u32> {
+        reference
+            .get(name)
+            .and_then(JsonValue::as_u64)
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| ApiError::invalid(format!("entity reference needs '{name}'")))
+    };
+    match reference.get("type").and_then(JsonValue::as_str) {
+        Some("node_id") => Ok(Value::NodeId(field("value")?)),
+        Some("edge_key") => Ok(Value::EdgeKey(core::EdgeKey {
+            src: field("src")?,
+            rel: field("rel")?,
+            dst: field("dst")?,
+        })),
+        _ => Err(ApiError::invalid(
+            "entity reference must be a node_id or edge_key object",
+        )),
+    }
+}
+
+/// Sets the locale (e.g. `de-DE`) whose separators `toString(value, format)`
+/// uses for numbers in queries run through this handle; NULL restores the
+/// English default. Unknown locales are rejected.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_locale(db: *mut ndb_db_t, locale: *const c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let locale = if locale.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(locale, "locale")?)
+        };
+        db_ref
+            .set_locale(locale.as_deref())
+            .map_err(|e| ApiError::invalid(e.to_string()))?;
+        // Cached rows were formatted for the previous locale.
+        handle.result_cache.clear();
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Enables the handle's result cache for read queries run through
+/// `ndb_query`, `ndb_query_typed`, named queries and read statements, keeping
+/// up to `capacity` results (0 disables it and is the default). A result is
+/// dropped once a commit touches a label or relationship type the query
+/// reads, or after `ttl_ms` milliseconds (0 means no expiry).
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_result_cache(db: *mut ndb_db_t, capacity: usize, ttl_ms: u64) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        db_ref_from_handle(handle)?;
+        let ttl = (ttl_ms > 0).then(|| std::time::Duration::from_millis(ttl_ms));
+        handle.result_cache.set_limits(capacity, ttl);
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Starts appending the queries run through `ndb_query`, `ndb_query_typed`,
+/// named queries, statements and `ndb_execute_write` on this handle to the
+/// JSON Lines file `path`, with their latency and a digest of their rows.
+/// Parameter values are kept only when `include_params` is non-zero; their
+/// shapes always are. A NULL `path` stops capturing. Replay the log with
+/// `ndb_replay_queries`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_query_capture(
+    db: *mut ndb_db_t,
+    path: *const c_char,
+    include_params: c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        db_ref_from_handle(handle)?;
+        let capture = if path.is_null() {
+            None
+        } else {
+            let path = cstr_to_string(path, "path")?;
+            Some(
+                core::QueryCapture::create(path, include_params != 0)
+                    .map_err(ApiError::from_core)?,
+            )
+        };
+        *handle
+            .query_capture
+            .write()
+            .map_err(|_| ApiError::internal("query capture lock poisoned"))? = capture;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Runs every query in the capture log `log_path` against this database and
+/// writes `{"queries": [{"line", "cypher", "captured_rows", "replayed_rows",
+/// "results_match", "error", "captured_micros", "replayed_micros"}],
+/// "skipped"}` to `out_report_json`; free it with `ndb_string_free`. Writes
+/// in the log are committed, so replay into a copy of the database.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_replay_queries(
+    db: *mut ndb_db_t,
+    log_path: *const c_char,
+    out_report_json: *mut *mut c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let log_path = cstr_to_string(log_path, "log_path")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let report = core::replay_queries(db_ref, log_path).map_err(ApiError::from_core)?;
+        let text = serde_json::to_string(&report).map_err(|e| ApiError::internal(e.to_string()))?;
+        write_out_c_string(out_report_json, &text)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the labels, relationship types and property keys the database has
+/// used and its indexes to `out_schema_json` as
+/// `{"labels", "rel_types", "property_keys", "indexes": [{"name", "kind",
+/// "label", "properties", "unique"}]}`; free it with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_schema(db: *mut ndb_db_t, out_schema_json: *mut *mut c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let text = serde_json::to_string(&db_ref.schema())
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        write_out_c_string(out_schema_json, &text)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the degree histograms of every relationship type, as of the last
+/// compaction, to `out_stats_json` as `[{"rel_type", "relationships",
+/// "outgoing", "incoming"}]`, each histogram being `{"buckets", "max_degree",
+/// "max_node"}`; free it with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_degree_stats(db: *mut ndb_db_t, out_stats_json: *mut *mut c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let text = serde_json::to_string(&db_ref.degree_stats())
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        write_out_c_string(out_stats_json, &text)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the result cache's hit and miss counts and how many results it
+/// currently holds.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_result_cache_stats(
+    db: *mut ndb_db_t,
+    out_hits: *mut u64,
+    out_misses: *mut u64,
+    out_len: *mut usize,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_hits.is_null() {
+            return Err(ApiError::null_pointer("out_hits"));
+        }
+        if out_misses.is_null() {
+            return Err(ApiError::null_pointer("out_misses"));
+        }
+        if out_len.is_null() {
+            return Err(ApiError::null_pointer("out_len"));
+        }
+        let handle = unsafe { db_handle_ref(db)? };
+        let stats = handle.result_cache.stats();
+        unsafe {
+            // SAFETY: out pointers validated above.
+            *out_hits = stats.hits;
+            *out_misses = stats.misses;
+            *out_len = stats.len;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Reads the stored properties of nodes carrying any label in the JSON array
+/// `labels_json` into the page cache. `out_loaded` (optional) receives the
+/// number of pages loaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_preload(
+    db: *mut ndb_db_t,
+    labels_json: *const c_char,
+    out_loaded: *mut u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let labels = parse_string_list_json(labels_json, "labels_json")?;
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let loaded = db_ref
+            .preload_labels(&labels)
+            .map_err(ApiError::from_core)?;
+        if !out_loaded.is_null() {
+            unsafe {
+                // SAFETY: checked non-null above.
+                *out_loaded = loaded as u64;
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Reads `count` pages starting at page `first` into the page cache.
+/// `out_loaded` (optional) receives the number of pages loaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_preload_pages(
+    db: *mut ndb_db_t,
+    first: u64,
+    count: u64,
+    out_loaded: *mut u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let loaded = db_ref
+            .preload_pages(first, count)
+            .map_err(ApiError::from_core)?;
+        if !out_loaded.is_null() {
+            unsafe {
+                // SAFETY: checked non-null above.
+                *out_loaded = loaded as u64;
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Moves the properties of nodes labelled `label` to the cold tier (`cold`
+/// non-zero) or back to the page store at the next checkpoint.
+/// `out_changed` (optional) receives 0 if the label was already there.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_label_tier(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    cold: c_int,
+    out_changed: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let tier = if cold != 0 {
+            core::StorageTier::Cold
+        } else {
+            core::StorageTier::Hot
+        };
+        let changed = db_ref
+            .set_label_tier(&label, tier)
+            .map_err(ApiError::from_core)?;
+        if !out_changed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_changed = c_int::from(changed);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the cold tier to `out_tiers_json` as `{"cold_labels": [...],
+/// "nodes", "file_bytes", "live_bytes"}`; free it with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_label_tiers(db: *mut ndb_db_t, out_tiers_json: *mut *mut c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let stats = db_ref.cold_tier_stats();
+        let text = json!({
+            "cold_labels": db_ref.cold_labels(),
+            "nodes": stats.nodes,
+            "file_bytes": stats.file_bytes,
+            "live_bytes": stats.live_bytes,
+        })
+        .to_string();
+        write_out_c_string(out_tiers_json, &text)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Binds the namespace `prefix` to the base IRI `iri`, or unbinds it when
+/// `iri` is NULL. Queries then resolve `prefix:local` references with
+/// `iri()`. `out_changed` (optional) receives 0 if the binding was already
+/// as requested.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_namespace(
+    db: *mut ndb_db_t,
+    prefix: *const c_char,
+    iri: *const c_char,
+    out_changed: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let prefix = cstr_to_string(prefix, "prefix")?;
+        let iri = if iri.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(iri, "iri")?)
+        };
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let changed = db_ref
+            .set_namespace(&prefix, iri.as_deref())
+            .map_err(ApiError::from_core)?;
+        if changed {
+            // Cached rows resolved references against the previous binding.
+            handle.result_cache.clear();
+        }
+        if !out_changed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_changed = c_int::from(changed);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the namespace catalog to `out_namespaces_json` as a
+/// `{"prefix": "iri"}` object; free it with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_namespaces(
+    db: *mut ndb_db_t,
+    out_namespaces_json: *mut *mut c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let namespaces: JsonMap<String, JsonValue> = db_ref
+            .namespaces()
+            .into_iter()
+            .map(|(prefix, iri)| (prefix, JsonValue::String(iri)))
+            .collect();
+        write_out_c_string(
+            out_namespaces_json,
+            &JsonValue::Object(namespaces).to_string(),
+        )
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Returns the graph changes committed after `since_txid` as
+/// `[{"txid", "changes": [{"op", ...}]}]`, one entry per transaction in
+/// commit order. Property values are not redacted.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_wal_tail(
+    db: *mut ndb_db_t,
+    since_txid: u64,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
+        }
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let txns = db_ref
+            .wal_tail(since_txid)
+            .map_err(ApiError::from_core)?
+            .map(|txn| {
+                let changes: Vec<JsonValue> = txn
+                    .changes
+                    .into_iter()
+                    .filter_map(wal_change_to_json)
+                    .collect();
+                json!({ "txid": txn.txid, "changes": changes })
+            })
+            .collect();
+        let result_ptr = make_result_handle_from_json(JsonValue::Array(txns))?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Keeps checkpointed WAL history archived for `keep_ms` milliseconds
+/// instead of letting the next checkpoint delete it. 0 stops archiving
+/// unless a legal hold is placed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_wal_retention(db: *mut ndb_db_t, keep_ms: u64) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let keep_for = (keep_ms > 0).then(|| std::time::Duration::from_millis(keep_ms));
+        db_ref
+            .set_wal_retention(keep_for)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Places the legal hold `name`, which keeps every WAL archive until it is
+/// released. `out_placed` (optional) receives 0 if it was already placed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_place_legal_hold(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    out_placed: *mut c_int,
+) -> c_int {
+    set_legal_hold(db, name, true, out_placed)
+}
+
+/// Releases the legal hold `name`. `out_released` (optional) receives 0 if
+/// it was not placed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_release_legal_hold(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    out_released: *mut c_int,
+) -> c_int {
+    set_legal_hold(db, name, false, out_released)
+}
+
+fn set_legal_hold(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    place: bool,
+    out_changed: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let changed = if place {
+            db_ref.place_legal_hold(&name)
+        } else {
+            db_ref.release_legal_hold(&name)
+        }
+        .map_err(ApiError::from_core)?;
+        if !out_changed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_changed = c_int::from(changed);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the WAL retention policy and archives to `out_retention_json` as
+/// `{"keep_ms", "holds": [{"name", "placed_at_ms"}], "archives": [{"seq",
+/// "bytes", "archived_at_ms"}]}`, `keep_ms` being null when unset; free it
+/// with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_wal_retention(
+    db: *mut ndb_db_t,
+    out_retention_json: *mut *mut c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let policy = db_ref.wal_retention();
+        let archives = db_ref
+            .archived_wal_segments()
+            .map_err(ApiError::from_core)?;
+        let holds: Vec<JsonValue> = policy
+            .holds
+            .iter()
+            .map(|(name, placed_at)| json!({ "name": name, "placed_at_ms": placed_at }))
+            .collect();
+        let archives: Vec<JsonValue> = archives
+            .iter()
+            .map(|segment| {
+                json!({
+                    "seq": segment.seq,
+                    "bytes": segment.bytes,
+                    "archived_at_ms": segment.archived_at_millis,
+                })
+            })
+            .collect();
+        let text = json!({
+            "keep_ms": policy.keep_for.map(|keep| keep.as_millis() as u64),
+            "holds": holds,
+            "archives": archives,
+        })
+        .to_string();
+        write_out_c_string(out_retention_json, &text)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+fn wal_change_to_json(change: core::WalChange) -> Option<JsonValue> {
+    use core::WalChange as C;
+    let value = |v: core::PropertyValue| value_to_json(convert_api_property_to_value(&v));
+    Some(match change {
+        C::CreateNode {
+            node,
+            external_id,
+            label,
+        } => {
+            json!({ "op": "create_node", "node": node, "external_id": external_id, "label": label })
+        }
+        C::DeleteNode { node } => json!({ "op": "delete_node", "node": node }),
+        C::AddLabel { node, label } => json!({ "op": "add_label", "node": node, "label": label }),
+        C::RemoveLabel { node, label } => {
+            json!({ "op": "remove_label", "node": node, "label": label })
+        }
+        C::CreateEdge { src, rel_type, dst } => {
+            json!({ "op": "create_edge", "src": src, "rel_type": rel_type, "dst": dst })
+        }
+        C::DeleteEdge { src, rel_type, dst } => {
+            json!({ "op": "delete_edge", "src": src, "rel_type": rel_type, "dst": dst })
+        }
+        C::SetNodeProperty {
+            node,
+            key,
+            value: v,
+        } => {
+            json!({ "op": "set_node_property", "node": node, "key": key, "value": value(v) })
+        }
+        C::RemoveNodeProperty { node, key } => {
+            json!({ "op": "remove_node_property", "node": node, "key": key })
+        }
+        C::SetEdgeProperty {
+            src,
+            rel_type,
+            dst,
+            key,
+            value: v,
+        } => json!({
+            "op": "set_edge_property",
+            "src": src,
+            "rel_type": rel_type,
+            "dst": dst,
+            "key": key,
+            "value": value(v),
+        }),
+        C::RemoveEdgeProperty {
+            src,
+            rel_type,
+            dst,
+            key,
+        } => json!({
+            "op": "remove_edge_property",
+            "src": src,
+            "rel_type": rel_type,
+            "dst": dst,
+            "key": key,
+        }),
+        C::RenameLabel { from, to } => json!({ "op": "rename_label", "from": from, "to": to }),
+        _ => return None,
+    })
+}
+
+/// Scalar function callback for `ndb_register_function`, called with the
+/// arguments as a JSON array and the caller's `user_data`. Returns the result
+/// as JSON, or NULL for null; the string must stay valid until the callback
+/// next runs on the same thread.
+#[allow(non_camel_case_types)]
+pub type ndb_function_fn =
+    Option<extern "C" fn(args_json: *const c_char, user_data: *mut c_void) -> *const c_char>;
+
+/// A registered C function and its `user_data`.
+struct FunctionCallback {
+    callback: extern "C" fn(*const c_char, *mut c_void) -> *const c_char,
+    user_data: *mut c_void,
+}
+
+// SAFETY: `ndb_register_function` requires the callback and `user_data` to be
+// usable from any thread that runs queries.
+unsafe impl Send for FunctionCallback {}
+unsafe impl Sync for FunctionCallback {}
+
+impl FunctionCallback {
+    /// Calls the C function; arguments it returns no or invalid JSON for
+    /// evaluate to null.
+    fn call(&self, args: &[Value]) -> Value {
+        let args = JsonValue::Array(args.iter().cloned().map(value_to_json).collect());
+        let Ok(args) = CString::new(args.to_string()) else {
+            return Value::Null;
+        };
+        let result = (self.callback)(args.as_ptr(), self.user_data);
+        if result.is_null() {
+            return Value::Null;
+        }
+        let text = unsafe {
+            // SAFETY: the callback returns a NUL-terminated string that stays
+            // valid until its next call on this thread.
+            CStr::from_ptr(result).to_string_lossy().into_owned()
+        };
+        serde_json::from_str::<JsonValue>(&text)
+            .ok()
+            .and_then(|json| json_to_query_value(&json).ok())
+            .unwrap_or(Value::Null)
+    }
+}
+
+/// Registers the scalar function `name` taking `arg_count` arguments for
+/// every database in the process, replacing an earlier registration.
+/// `callback` and `user_data` must stay valid, and be safe to use from any
+/// thread, until `ndb_unregister_function`. Built-in function names are
+/// rejected.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_register_function(
+    name: *const c_char,
+    arg_count: u32,
+    callback: ndb_function_fn,
+    user_data: *mut c_void,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let callback = callback.ok_or_else(|| ApiError::invalid("callback is null"))?;
+        let function = FunctionCallback {
+            callback,
+            user_data,
+        };
+        let signature =
+            nervusdb_query::Signature::function((0..arg_count).map(|i| format!("arg{i}")));
+        nervusdb_query::register_function(name, signature, move |args: &[Value]| {
+            function.call(args)
+        })
+        .map_err(|e| ApiError::invalid(e.to_string()))
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Removes a function added with `ndb_register_function`. `out_removed`
+/// (optional) receives whether it was registered.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_unregister_function(name: *const c_char, out_removed: *mut c_int) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let removed = nervusdb_query::unregister_function(&name);
+        if !out_removed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_removed = c_int::from(removed);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Progress callback for long maintenance operations, called with the number
+/// of items processed so far, the total, and the caller's `user_data`.
+#[allow(non_camel_case_types)]
+pub type ndb_progress_fn = Option<extern "C" fn(done: u64, total: u64, user_data: *mut c_void)>;
+
+/// The operation behind `op`, or a fresh one nobody can cancel when `op` is
+/// null.
+unsafe fn operation_or_default(op: *mut ndb_operation_t) -> core::OperationHandle {
+    unsafe {
+        // SAFETY: non-null pointers come from `ndb_operation_new`.
+        op.cast::<core::OperationHandle>().as_ref()
+    }
+    .cloned()
+    .unwrap_or_default()
+}
+
+/// Creates a handle for following and cancelling `ndb_compact_with_progress`,
+/// `ndb_vacuum_with_progress` or `ndb_bulkload_with_progress` from another
+/// thread. Free it with `ndb_operation_free` once the call has returned.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_operation_new(out_op: *mut *mut ndb_operation_t) -> c_int {
+    if out_op.is_null() {
+        return err_status(ApiError::null_pointer("out_op"));
+    }
+    let op = Box::new(core::OperationHandle::new());
+    unsafe {
+        // SAFETY: output pointer validated above.
+        *out_op = Box::into_raw(op).cast::<ndb_operation_t>();
+    }
+    ok_status()
+}
+
+/// Asks the operation to stop at its next safe point; the running call then
+/// fails with `NDB_ERR_CANCELLED`. Safe to call from any thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_operation_cancel(op: *mut ndb_operation_t) -> c_int {
+    let operation = unsafe {
+        // SAFETY: non-null pointers come from `ndb_operation_new`.
+        op.cast::<core::OperationHandle>().as_ref()
+    };
+    match operation {
+        Some(operation) => {
+            operation.cancel();
+            ok_status()
+        }
+        None => err_status(ApiError::null_pointer("op")),
+    }
+}
+
+/// Reads the operation's last published progress. Both outputs are optional;
+/// `total` is 0 until the operation has sized its work.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_operation_progress(
+    op: *mut ndb_operation_t,
+    out_done: *mut u64,
+    out_total: *mut u64,
+) -> c_int {
+    let operation = unsafe {
+        // SAFETY: non-null pointers come from `ndb_operation_new`.
+        op.cast::<core::OperationHandle>().as_ref()
+    };
+    let Some(operation) = operation else {
+        return err_status(ApiError::null_pointer("op"));
+    };
+    let progress = operation.progress();
+    unsafe {
+        // SAFETY: output pointers are optional and only written when non-null.
+        if !out_done.is_null() {
+            *out_done = progress.done;
+        }
+        if !out_total.is_null() {
+            *out_total = progress.total;
+        }
+    }
+    ok_status()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_operation_free(op: *mut ndb_operation_t) {
+    if op.is_null() {
+        return;
+    }
+    unsafe {
+        // SAFETY: pointer was allocated by `ndb_operation_new`; function takes ownership.
+        drop(Box::from_raw(op.cast::<core::OperationHandle>()));
+    }
+}
+
+/// Renames property `old_name` to `new_name` on every node labelled `label`,
+/// together with the indexes and unique constraints on it. `progress` and
+/// `out_renamed` (the number of nodes renamed) are optional.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_rename_property(
+    db: *mut ndb_db_t,
+    label: *const c_char,
+    old_name: *const c_char,
+    new_name: *const c_char,
+    progress: ndb_progress_fn,
+    user_data: *mut c_void,
+    out_renamed: *mut u64,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let label = cstr_to_string(label, "label")?;
+        let old = cstr_to_string(old_name, "old_name")?;
+        let new = cstr_to_string(new_name, "new_name")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let renamed = db_ref
+            .rename_property_with_progress(&label, &old, &new, |done, total| {
+                if let Some(progress) = progress {
+                    progress(done as u64, total as u64, user_data);
+                }
+            })
+            .map_err(ApiError::from_core)?;
+        if !out_renamed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_renamed = renamed as u64;
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Renames label `old_name` to `new_name`. `out_renamed` is optional and set
+/// to 0 when `old_name` does not exist.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_rename_label(
+    db: *mut ndb_db_t,
+    old_name: *const c_char,
+    new_name: *const c_char,
+    out_renamed: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let old = cstr_to_string(old_name, "old_name")?;
+        let new = cstr_to_string(new_name, "new_name")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let renamed = db_ref
+            .rename_label(&old, &new)
+            .map_err(ApiError::from_core)?;
+        if !out_renamed.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_renamed = c_int::from(renamed);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Stores `cypher` in the database catalog under `name`, replacing any existing entry.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_save_query(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    cypher: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        db_ref
+            .save_query(&name, &cypher)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Removes the stored query `name`; `out_dropped` (optional) receives 1 if it existed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_drop_query(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    out_dropped: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let dropped = db_ref.drop_query(&name).map_err(ApiError::from_core)?;
+        if !out_dropped.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_dropped = c_int::from(dropped);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Runs the stored read query `name`, like `ndb_query` with the saved text.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_query_named(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    params_json: *const c_char,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
+        }
+        let name = cstr_to_string(name, "name")?;
+        let params = parse_params_json(params_json)?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let rows = traced(db_ref, || {
+            let cypher = named_query_text(db_ref, &name)?;
+            execute_json_rows(handle, &cypher, &params)
+        })?;
+        let result_ptr = make_result_handle_from_rows(rows)?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Runs the stored write query `name`, like `ndb_execute_write` with the saved text.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_execute_named(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    params_json: *const c_char,
+    out_summary: *mut u32,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let params = parse_params_json(params_json)?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let affected = traced(db_ref, || {
+            let cypher = named_query_text(db_ref, &name)?;
+            execute_write_count(db_ref, &cypher, &params)
+        })?;
+        if !out_summary.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_summary = affected;
+            }
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Registers the trigger `name` on this handle, replacing one of the same
+/// name: after each commit with changes matching `event` (e.g.
+/// `node_created:User`, `node_property_set:User.email`,
+/// `relationship_deleted:KNOWS`), the write statement `cypher` runs once per
+/// change with `$id` or `$src`/`$dst`/`$type` set. Triggers are not stored in
+/// the database.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_create_trigger(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    event: *const c_char,
+    cypher: *const c_char,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let event = cstr_to_string(event, "event")?;
+        let cypher = cstr_to_string(cypher, "cypher")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let event = event.parse().map_err(ApiError::from_core)?;
+        db_ref
+            .create_trigger(&name, event, &cypher)
+            .map_err(ApiError::from_core)
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Removes the trigger `name`; `out_dropped` (optional) receives 1 if it existed.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_drop_trigger(
+    db: *mut ndb_db_t,
+    name: *const c_char,
+    out_dropped: *mut c_int,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let name = cstr_to_string(name, "name")?;
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let dropped = db_ref.drop_trigger(&name);
+        if !out_dropped.is_null() {
+            unsafe {
+                // SAFETY: output pointer is optional and only written when non-null.
+                *out_dropped = c_int::from(dropped);
+            }
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Writes the handle's triggers to `out_triggers_json` as `[{"name",
+/// "event", "cypher", "fired", "failures", "last_error"}]`, sorted by name;
+/// `cypher` is null for Rust callbacks. Free it with `ndb_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_triggers(db: *mut ndb_db_t, out_triggers_json: *mut *mut c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let triggers: Vec<JsonValue> = db_ref
+            .triggers()
+            .into_iter()
+            .map(|t| {
+                json!({
+                    "name": t.name,
+                    "event": t.event.to_string(),
+                    "cypher": t.cypher,
+                    "fired": t.fired,
+                    "failures": t.failures,
+                    "last_error": t.last_error,
+                })
+            })
+            .collect();
+        write_out_c_string(out_triggers_json, &JsonValue::Array(triggers).to_string())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Sets the capacity of the process-wide plan cache shared by all handles; `0` disables it.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_set_plan_cache_capacity(capacity: usize) -> c_int {
+    set_plan_cache_capacity(capacity);
+    ok_status()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_search_vector(
+    db: *mut ndb_db_t,
+    query: *const f32,
+    query_len: usize,
+    k: u32,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
+    search_vector_with(db, query, query_len, k, out_result, |db, query, k| {
+        db.search_vector(query, k).map_err(ApiError::from_core)
+    })
+}
+
+/// Like `ndb_search_vector` but scans every stored vector instead of using the HNSW index.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_search_vector_exact(
+    db: *mut ndb_db_t,
+    query: *const f32,
+    query_len: usize,
+    k: u32,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
+    search_vector_with(db, query, query_len, k, out_result, |db, query, k| {
+        db.search_vector_exact(query, k)
+            .map_err(ApiError::from_core)
+    })
+}
+
+/// Like `ndb_search_vector` but ranks by `metric` (`NDB_METRIC_*`) and only returns
+/// nodes matching the filter. `label`, `property` and `value_json` may be NULL;
+/// `property` and `value_json` must be given together.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn ndb_search_vector_filtered(
+    db: *mut ndb_db_t,
+    query: *const f32,
+    query_len: usize,
+    k: u32,
+    metric: c_int,
+    label: *const c_char,
+    property: *const c_char,
+    value_json: *const c_char,
+    out_result: *mut *mut ndb_result_t,
+) -> c_int {
+    search_vector_with(db, query, query_len, k, out_result, |db, query, k| {
+        let metric = match metric {
+            NDB_METRIC_L2 => core::VectorMetric::L2,
+            NDB_METRIC_COSINE => core::VectorMetric::Cosine,
+            NDB_METRIC_DOT => core::VectorMetric::Dot,
+            other => return Err(ApiError::invalid(format!("unknown vector metric {other}"))),
+        };
+        let label = if label.is_null() {
+            None
+        } else {
+            Some(cstr_to_string(label, "label")?)
+        };
+        let property = match (property.is_null(), value_json.is_null()) {
+            (true, true) => None,
+            (false, false) => {
+                let key = cstr_to_string(property, "property")?;
+                let value = json_to_property_value(&cstr_to_json_value(value_json, "value_json")?)?;
+                Some((key, value))
+            }
+            _ => {
+                return Err(ApiError::invalid(
+                    "property and value_json must be given together",
+                ));
+            }
+        };
+        let filter = core::VectorFilter { label, property };
+        db.search_vector_filtered(query, k, metric, &filter)
+            .map_err(ApiError::from_core)
+    })
+}
+
+fn search_vector_with(
+    db: *mut ndb_db_t,
+    query: *const f32,
+    query_len: usize,
+    k: u32,
+    out_result: *mut *mut ndb_result_t,
+    search: impl FnOnce(&core::Db, &[f32], usize) -> ApiResult<Vec<(u32, f32)>>,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_result.is_null() {
+            return Err(ApiError::null_pointer("out_result"));
+        }
+        if query.is_null() && query_len > 0 {
+            return Err(ApiError::null_pointer("query"));
+        }
+        let query_slice = if query_len == 0 {
+            &[][..]
+        } else {
+            unsafe {
+                // SAFETY: pointer validated above and caller provides `query_len` items.
+                std::slice::from_raw_parts(query, query_len)
+            }
+        };
+        let handle = unsafe { db_handle_ref(db)? };
+        let db_ref = db_ref_from_handle(handle)?;
+        let rows = search(db_ref, query_slice, k as usize)?;
+        let json_rows = JsonValue::Array(
+            rows.into_iter()
+                .map(|(node_id, distance)| json!({ "node_id": node_id, "distance": distance }))
+                .collect(),
+        );
+        let result_ptr = make_result_handle_from_json(json_rows)?;
+        unsafe {
+            // SAFETY: out pointer validated above.
+            *out_result = result_ptr;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_vacuum(path: *const c_char) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let path = cstr_to_string(path, "path")?;
+        core::vacuum(path).map_err(ApiError::from_core)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Like `ndb_vacuum`, reporting pages copied as `(done, total)` to
+/// `progress` and to the optional `op`. Cancelling `op` fails the call with
+/// `NDB_ERR_CANCELLED` and leaves the database file untouched.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_vacuum_with_progress(
+    path: *const c_char,
+    op: *mut ndb_operation_t,
+    progress: ndb_progress_fn,
+    user_data: *mut c_void,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        let path = cstr_to_string(path, "path")?;
+        let operation = unsafe { operation_or_default(op) };
+        core::vacuum_with_progress(path, &operation, |done, total| {
+            if let Some(progress) = progress {
+                progress(done, total, user_data);
+            }
+        })
+        .map_err(ApiError::from_core)?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_remove_node_property(
-    txn: *mut ndb_txn_t,
-    node: u32,
-    key: *const c_char,
-) -> c_int {
+pub extern "C" fn ndb_backup(path: *const c_char, backup_dir: *const c_char) -> c_int {
     let result = (|| -> ApiResult<()> {
-        let key = cstr_to_string(key, "key")?;
-        let txn_handle = unsafe { txn_handle_mut(txn)? };
-        let inner = txn_handle
-            .txn
-            .as_mut()
-            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
-        inner
-            .remove_node_property(node, &key)
-            .map_err(ApiError::from_core)?;
+        let path = cstr_to_string(path, "path")?;
+        let backup_dir = cstr_to_string(backup_dir, "backup_dir")?;
+        core::backup(path, backup_dir).map_err(ApiError::from_core)?;
         Ok(())
     })();
     match result {
@@ -1308,24 +4836,27 @@ pub extern "C" fn ndb_txn_remove_node_property(
     }
 }
 
+/// Creates an incremental backup of the database at `path` against the
+/// latest completed backup in `backup_dir` taken at checkpoint `since_txid`.
+/// `out_info_json` (optional) receives the new backup's info as a JSON
+/// object; free it with `ndb_string_free`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_remove_edge_property(
-    txn: *mut ndb_txn_t,
-    src: u32,
-    rel: u32,
-    dst: u32,
-    key: *const c_char,
+pub extern "C" fn ndb_backup_incremental(
+    path: *const c_char,
+    backup_dir: *const c_char,
+    since_txid: u64,
+    out_info_json: *mut *mut c_char,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
-        let key = cstr_to_string(key, "key")?;
-        let txn_handle = unsafe { txn_handle_mut(txn)? };
-        let inner = txn_handle
-            .txn
-            .as_mut()
-            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
-        inner
-            .remove_edge_property(src, rel, dst, &key)
-            .map_err(ApiError::from_core)?;
+        let path = cstr_to_string(path, "path")?;
+        let backup_dir = cstr_to_string(backup_dir, "backup_dir")?;
+        let info =
+            core::backup_incremental(path, backup_dir, since_txid).map_err(ApiError::from_core)?;
+        if !out_info_json.is_null() {
+            let text =
+                serde_json::to_string(&info).map_err(|e| ApiError::internal(e.to_string()))?;
+            write_out_c_string(out_info_json, &text)?;
+        }
         Ok(())
     })();
     match result {
@@ -1334,34 +4865,25 @@ pub extern "C" fn ndb_txn_remove_edge_property(
     }
 }
 
+/// Restores backup `backup_id` from `backup_dir` as a new database at
+/// `path`, applying the base chain of an incremental backup first. Fails if
+/// a database already exists at `path`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_txn_set_vector(
-    txn: *mut ndb_txn_t,
-    node: u32,
-    vector: *const f32,
-    len: usize,
+pub extern "C" fn ndb_restore(
+    backup_dir: *const c_char,
+    backup_id: *const c_char,
+    path: *const c_char,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
-        if vector.is_null() && len > 0 {
-            return Err(ApiError::null_pointer("vector"));
-        }
-        let slice = if len == 0 {
-            &[][..]
-        } else {
-            unsafe {
-                // SAFETY: pointer validated above and caller provides `len` elements.
-                std::slice::from_raw_parts(vector, len)
-            }
-        };
-        let txn_handle = unsafe { txn_handle_mut(txn)? };
-        let inner = txn_handle
-            .txn
-            .as_mut()
-            .ok_or_else(|| ApiError::execution("transaction is not active"))?;
-        inner
-            .set_vector(node, slice.to_vec())
-            .map_err(ApiError::from_core)?;
-        Ok(())
+        let backup_dir = cstr_to_string(backup_dir, "backup_dir")?;
+        let backup_id = cstr_to_string(backup_id, "backup_id")?;
+        let path = cstr_to_string(path, "path")?;
+        let backup = core::BackupManager::list_backups(std::path::Path::new(&backup_dir))
+            .map_err(|e| ApiError::from_core(core::Error::from(e)))?
+            .into_iter()
+            .find(|info| info.id.to_string() == backup_id)
+            .ok_or_else(|| ApiError::execution(format!("no completed backup {backup_id}")))?;
+        core::restore(backup_dir, &backup, path).map_err(ApiError::from_core)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1369,12 +4891,23 @@ pub extern "C" fn ndb_txn_set_vector(
     }
 }
 
+/// Verifies the backup stored at `backup_path` (`<backup_dir>/<id>`)
+/// against its manifest and, when `test_restore` is non-zero, restores it
+/// into a temporary location and opens it. `out_report_json` receives the
+/// report as a JSON object whose `problems` array is empty when the backup
+/// verified; free it with `ndb_string_free`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_compact(db: *mut ndb_db_t) -> c_int {
+pub extern "C" fn ndb_verify_backup(
+    backup_path: *const c_char,
+    test_restore: c_int,
+    out_report_json: *mut *mut c_char,
+) -> c_int {
     let result = (|| -> ApiResult<()> {
-        let handle = unsafe { db_handle_ref(db)? };
-        let db_ref = db_ref_from_handle(handle)?;
-        db_ref.compact().map_err(ApiError::from_core)
+        let backup_path = cstr_to_string(backup_path, "backup_path")?;
+        let report =
+            core::verify_backup(backup_path, test_restore != 0).map_err(ApiError::from_core)?;
+        let text = serde_json::to_string(&report).map_err(|e| ApiError::internal(e.to_string()))?;
+        write_out_c_string(out_report_json, &text)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1382,12 +4915,19 @@ pub extern "C" fn ndb_compact(db: *mut ndb_db_t) -> c_int {
     }
 }
 
+/// Estimates per-label storage use of the database at `path` and projects
+/// its growth from the statistics history. `out_report_json` receives the
+/// report as a JSON object; free it with `ndb_string_free`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_checkpoint(db: *mut ndb_db_t) -> c_int {
+pub extern "C" fn ndb_capacity_report(
+    path: *const c_char,
+    out_report_json: *mut *mut c_char,
+) -> c_int {
     let result = (|| -> ApiResult<()> {
-        let handle = unsafe { db_handle_ref(db)? };
-        let db_ref = db_ref_from_handle(handle)?;
-        db_ref.checkpoint().map_err(ApiError::from_core)
+        let path = cstr_to_string(path, "path")?;
+        let report = core::capacity_report(path).map_err(ApiError::from_core)?;
+        let text = serde_json::to_string(&report).map_err(|e| ApiError::internal(e.to_string()))?;
+        write_out_c_string(out_report_json, &text)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1395,20 +4935,24 @@ pub extern "C" fn ndb_checkpoint(db: *mut ndb_db_t) -> c_int {
     }
 }
 
+/// Exports the database at `path` to the file `out_path` in `format`
+/// (`"graphml"` or `"jsonl"`). `out_stats_json` (optional) receives
+/// `{"nodes": n, "relationships": n}`; free it with `ndb_string_free`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_create_index(
-    db: *mut ndb_db_t,
-    label: *const c_char,
-    property: *const c_char,
+pub extern "C" fn ndb_export(
+    path: *const c_char,
+    format: *const c_char,
+    out_path: *const c_char,
+    out_stats_json: *mut *mut c_char,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
-        let label = cstr_to_string(label, "label")?;
-        let property = cstr_to_string(property, "property")?;
-        let handle = unsafe { db_handle_ref(db)? };
-        let db_ref = db_ref_from_handle(handle)?;
-        db_ref
-            .create_index(&label, &property)
-            .map_err(ApiError::from_core)
+        let path = cstr_to_string(path, "path")?;
+        let format = parse_graph_format(format)?;
+        let out_path = cstr_to_string(out_path, "out_path")?;
+        let file = std::fs::File::create(&out_path)
+            .map_err(|e| ApiError::from_core(core::Error::Io(e)))?;
+        let stats = core::export(path, format, file).map_err(ApiError::from_core)?;
+        write_transfer_stats(out_stats_json, stats)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1416,45 +4960,28 @@ pub extern "C" fn ndb_create_index(
     }
 }
 
+/// Like `ndb_export`, scrubbing the output by `rules_json`, an object with
+/// the optional keys `salt` (string), `hash_ids` (bool), `hash_properties`
+/// and `drop_properties` (arrays of `"name"` or `"Label.name"`) and
+/// `generalize_dates` (`"year"`, `"month"` or `"day"`).
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_search_vector(
-    db: *mut ndb_db_t,
-    query: *const f32,
-    query_len: usize,
-    k: u32,
-    out_result: *mut *mut ndb_result_t,
+pub extern "C" fn ndb_export_scrubbed(
+    path: *const c_char,
+    format: *const c_char,
+    out_path: *const c_char,
+    rules_json: *const c_char,
+    out_stats_json: *mut *mut c_char,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
-        if out_result.is_null() {
-            return Err(ApiError::null_pointer("out_result"));
-        }
-        if query.is_null() && query_len > 0 {
-            return Err(ApiError::null_pointer("query"));
-        }
-        let query_slice = if query_len == 0 {
-            &[][..]
-        } else {
-            unsafe {
-                // SAFETY: pointer validated above and caller provides `query_len` items.
-                std::slice::from_raw_parts(query, query_len)
-            }
-        };
-        let handle = unsafe { db_handle_ref(db)? };
-        let db_ref = db_ref_from_handle(handle)?;
-        let rows = db_ref
-            .search_vector(query_slice, k as usize)
-            .map_err(ApiError::from_core)?;
-        let json_rows = JsonValue::Array(
-            rows.into_iter()
-                .map(|(node_id, distance)| json!({ "node_id": node_id, "distance": distance }))
-                .collect(),
-        );
-        let result_ptr = make_result_handle_from_json(json_rows)?;
-        unsafe {
-            // SAFETY: out pointer validated above.
-            *out_result = result_ptr;
-        }
-        Ok(())
+        let path = cstr_to_string(path, "path")?;
+        let format = parse_graph_format(format)?;
+        let out_path = cstr_to_string(out_path, "out_path")?;
+        let rules = parse_scrub_rules(rules_json)?;
+        let file = std::fs::File::create(&out_path)
+            .map_err(|e| ApiError::from_core(core::Error::Io(e)))?;
+        let stats =
+            core::export_scrubbed(path, format, file, &rules).map_err(ApiError::from_core)?;
+        write_transfer_stats(out_stats_json, stats)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1462,12 +4989,25 @@ pub extern "C" fn ndb_search_vector(
     }
 }
 
+/// Imports the file `in_path` in `format` (`"graphml"` or `"jsonl"`) into
+/// the database at `path` in one transaction. The database must not be
+/// open elsewhere. `out_stats_json` (optional) receives
+/// `{"nodes": n, "relationships": n}`; free it with `ndb_string_free`.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_vacuum(path: *const c_char) -> c_int {
+pub extern "C" fn ndb_import(
+    path: *const c_char,
+    format: *const c_char,
+    in_path: *const c_char,
+    out_stats_json: *mut *mut c_char,
+) -> c_int {
     let result = (|| -> ApiResult<()> {
         let path = cstr_to_string(path, "path")?;
-        core::vacuum(path).map_err(ApiError::from_core)?;
-        Ok(())
+        let format = parse_graph_format(format)?;
+        let in_path = cstr_to_string(in_path, "in_path")?;
+        let file =
+            std::fs::File::open(&in_path).map_err(|e| ApiError::from_core(core::Error::Io(e)))?;
+        let stats = core::import(path, format, file).map_err(ApiError::from_core)?;
+        write_transfer_stats(out_stats_json, stats)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1475,13 +5015,70 @@ pub extern "C" fn ndb_vacuum(path: *const c_char) -> c_int {
     }
 }
 
+fn parse_graph_format(format: *const c_char) -> ApiResult<core::GraphFormat> {
+    cstr_to_string(format, "format")?
+        .parse()
+        .map_err(|e: core::Error| ApiError::invalid(e.to_string()))
+}
+
+fn parse_scrub_rules(rules_json: *const c_char) -> ApiResult<core::ScrubRules> {
+    let root = cstr_to_json_value(rules_json, "rules_json")?;
+    let obj = root
+        .as_object()
+        .ok_or_else(|| ApiError::invalid("rules_json must be a JSON object"))?;
+    let mut rules = core::ScrubRules::default();
+    for (key, value) in obj {
+        let invalid = |expected: &str| ApiError::invalid(format!("rules.{key} must be {expected}"));
+        let strings = || {
+            value
+                .as_array()
+                .and_then(|items| {
+                    items
+                        .iter()
+                        .map(|item| item.as_str().map(str::to_string))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| invalid("an array of strings"))
+        };
+        match key.as_str() {
+            "salt" => rules.salt = value.as_str().ok_or_else(|| invalid("a string"))?.into(),
+            "hash_ids" => rules.hash_ids = value.as_bool().ok_or_else(|| invalid("a boolean"))?,
+            "hash_properties" => rules.hash_properties = strings()?,
+            "drop_properties" => rules.drop_properties = strings()?,
+            "generalize_dates" if value.is_null() => rules.generalize_dates = None,
+            "generalize_dates" => {
+                let granularity = value.as_str().ok_or_else(|| invalid("a string"))?;
+                rules.generalize_dates = Some(
+                    granularity
+                        .parse()
+                        .map_err(|e: core::Error| ApiError::invalid(e.to_string()))?,
+                );
+            }
+            _ => return Err(ApiError::invalid(format!("unknown scrub rule '{key}'"))),
+        }
+    }
+    Ok(rules)
+}
+
+fn write_transfer_stats(out: *mut *mut c_char, stats: core::TransferStats) -> ApiResult<()> {
+    if out.is_null() {
+        return Ok(());
+    }
+    let text = serde_json::to_string(&stats).map_err(|e| ApiError::internal(e.to_string()))?;
+    write_out_c_string(out, &text)
+}
+
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_backup(path: *const c_char, backup_dir: *const c_char) -> c_int {
+pub extern "C" fn ndb_bulkload(
+    path: *const c_char,
+    nodes_json: *const c_char,
+    edges_json: *const c_char,
+) -> c_int {
     let result = (|| -> ApiResult<()> {
         let path = cstr_to_string(path, "path")?;
-        let backup_dir = cstr_to_string(backup_dir, "backup_dir")?;
-        core::backup(path, backup_dir).map_err(ApiError::from_core)?;
-        Ok(())
+        let nodes = parse_bulk_nodes(nodes_json)?;
+        let edges = parse_bulk_edges(edges_json)?;
+        core::bulkload(path, nodes, edges).map_err(ApiError::from_core)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1489,17 +5086,29 @@ pub extern "C" fn ndb_backup(path: *const c_char, backup_dir: *const c_char) ->
     }
 }
 
+/// Like `ndb_bulkload`, reporting `(done, total)` to `progress` and to the
+/// optional `op`. Cancelling `op` fails the call with `NDB_ERR_CANCELLED`
+/// and removes the partially written database.
 #[unsafe(no_mangle)]
-pub extern "C" fn ndb_bulkload(
+pub extern "C" fn ndb_bulkload_with_progress(
     path: *const c_char,
     nodes_json: *const c_char,
     edges_json: *const c_char,
+    op: *mut ndb_operation_t,
+    progress: ndb_progress_fn,
+    user_data: *mut c_void,
 ) -> c_int {
     let result = (|| -> ApiResult<()> {
         let path = cstr_to_string(path, "path")?;
         let nodes = parse_bulk_nodes(nodes_json)?;
         let edges = parse_bulk_edges(edges_json)?;
-        core::bulkload(path, nodes, edges).map_err(ApiError::from_core)
+        let operation = unsafe { operation_or_default(op) };
+        core::bulkload_with_progress(path, nodes, edges, &operation, |done, total| {
+            if let Some(progress) = progress {
+                progress(done, total, user_data);
+            }
+        })
+        .map_err(ApiError::from_core)
     })();
     match result {
         Ok(()) => ok_status(),
@@ -1519,21 +5128,23 @@ pub extern "C" fn ndb_prepare_read(
         }
         let _ = unsafe { db_handle_ref(db)? };
         let cypher = cstr_to_string(cypher, "cypher")?;
-        if write_query_contains_write(&cypher)? {
+        let prepared = prepare_statement(&cypher)?;
+        if prepared.is_write() {
             return Err(ApiError::execution(
                 "ndb_prepare_read does not accept write statements",
             ));
         }
+        let columns = prepared.columns().map(column_cstrings).transpose()?;
         let stmt = Box::new(StmtHandle {
             db,
             mode: StmtMode::Read,
             cypher,
             params: BTreeMap::new(),
             executed: false,
-            rows: Vec::new(),
-            cursor: 0,
+            rows: StmtRows::Done,
             current: None,
             write_count: 0,
+            columns,
         });
         unsafe {
             // SAFETY: output pointer validated above.
@@ -1557,23 +5168,26 @@ pub extern "C" fn ndb_prepare_write(
         if out_stmt.is_null() {
             return Err(ApiError::null_pointer("out_stmt"));
         }
-        let _ = unsafe { db_handle_ref(db)? };
+        let handle = unsafe { db_handle_ref(db)? };
         let cypher = cstr_to_string(cypher, "cypher")?;
-        if !write_query_contains_write(&cypher)? {
+        let prepared = prepare_statement(&cypher)?;
+        if !prepared.is_write() {
             return Err(ApiError::execution(
                 "ndb_prepare_write expects a write statement",
             ));
         }
+        ensure_writable(db_ref_from_handle(handle)?)?;
+        let columns = prepared.columns().map(column_cstrings).transpose()?;
         let stmt = Box::new(StmtHandle {
             db,
             mode: StmtMode::Write,
             cypher,
             params: BTreeMap::new(),
             executed: false,
-            rows: Vec::new(),
-            cursor: 0,
+            rows: StmtRows::Done,
             current: None,
             write_count: 0,
+            columns,
         });
         unsafe {
             // SAFETY: output pointer validated above.
@@ -1597,8 +5211,7 @@ fn stmt_bind_value(stmt: *mut ndb_stmt_t, name: *const c_char, value: Value) ->
         handle.params.insert(key, value);
         handle.executed = false;
         handle.current = None;
-        handle.rows.clear();
-        handle.cursor = 0;
+        handle.rows = StmtRows::Done;
         Ok(())
     })();
     match result {
@@ -1702,15 +5315,19 @@ pub extern "C" fn ndb_stmt_step(stmt: *mut ndb_stmt_t, out_state: *mut c_int) ->
         stmt_execute_if_needed(stmt)?;
         match stmt.mode {
             StmtMode::Read => {
-                if stmt.cursor < stmt.rows.len() {
-                    stmt.current = stmt.rows.get(stmt.cursor).cloned();
-                    stmt.cursor += 1;
+                stmt.current = stmt.rows.next()?;
+                if let Some(row) = &stmt.current
+                    && stmt.columns.is_none()
+                {
+                    let names = row.columns().iter().map(|(name, _)| name.clone());
+                    stmt.columns = Some(column_cstrings(names.collect())?);
+                }
+                if stmt.current.is_some() {
                     unsafe {
                         // SAFETY: out_state validated above.
                         *out_state = NDB_STEP_ROW;
                     }
                 } else {
-                    stmt.current = None;
                     unsafe {
                         // SAFETY: out_state validated above.
                         *out_state = NDB_STEP_DONE;
@@ -1758,6 +5375,53 @@ pub extern "C" fn ndb_stmt_column_count(stmt: *mut ndb_stmt_t) -> usize {
     }
 }
 
+/// Number of result columns, available right after prepare unlike
+/// `ndb_stmt_column_count`, which needs a current row. Statements without
+/// `RETURN` have none. A standalone `CALL` of a built-in procedure without
+/// `YIELD` only knows its columns once a row has been stepped; until then
+/// this returns 0.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_stmt_column_count_static(stmt: *mut ndb_stmt_t) -> usize {
+    let result = (|| -> ApiResult<usize> {
+        let stmt = unsafe { stmt_handle_mut(stmt)? };
+        Ok(stmt.columns.as_ref().map_or(0, Vec::len))
+    })();
+    match result {
+        Ok(v) => {
+            clear_last_error();
+            v
+        }
+        Err(e) => {
+            set_last_error(&e);
+            0
+        }
+    }
+}
+
+/// Name of result column `col` (see `ndb_stmt_column_count_static`), valid
+/// until the statement is finalized. Returns NULL for an out-of-range column.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_stmt_column_name(stmt: *mut ndb_stmt_t, col: usize) -> *const c_char {
+    let result = (|| -> ApiResult<*const c_char> {
+        let stmt = unsafe { stmt_handle_mut(stmt)? };
+        stmt.columns
+            .as_ref()
+            .and_then(|columns| columns.get(col))
+            .map(|name| name.as_ptr())
+            .ok_or_else(|| ApiError::invalid("column index out of range"))
+    })();
+    match result {
+        Ok(v) => {
+            clear_last_error();
+            v
+        }
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null()
+        }
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn ndb_stmt_column_type(stmt: *mut ndb_stmt_t, col: usize) -> c_int {
     let result = (|| -> ApiResult<c_int> {
@@ -1887,6 +5551,115 @@ pub extern "C" fn ndb_stmt_column_string(
     }
 }
 
+/// Copies a blob column into a new buffer owned by the caller, released with
+/// `ndb_blob_free(ptr, len)`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_stmt_column_blob(
+    stmt: *mut ndb_stmt_t,
+    col: usize,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_ptr.is_null() {
+            return Err(ApiError::null_pointer("out_ptr"));
+        }
+        if out_len.is_null() {
+            return Err(ApiError::null_pointer("out_len"));
+        }
+        let stmt = unsafe { stmt_handle_mut(stmt)? };
+        let value = stmt_current_value(stmt, col)?;
+        let bytes = match value {
+            Value::Blob(v) => v.clone().into_boxed_slice(),
+            _ => return Err(ApiError::execution("column type is not blob")),
+        };
+        let len = bytes.len();
+        unsafe {
+            // SAFETY: output pointers validated above.
+            *out_ptr = Box::into_raw(bytes).cast::<u8>();
+            *out_len = len;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+fn column_bytes(value: &Value) -> ApiResult<&[u8]> {
+    match value {
+        Value::String(v) => Ok(v.as_bytes()),
+        Value::Blob(v) => Ok(v),
+        _ => Err(ApiError::execution("column type is not string or blob")),
+    }
+}
+
+/// Byte length of a string (UTF-8) or blob column, for use with `ndb_stmt_column_read`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_stmt_column_bytes(
+    stmt: *mut ndb_stmt_t,
+    col: usize,
+    out_len: *mut usize,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_len.is_null() {
+            return Err(ApiError::null_pointer("out_len"));
+        }
+        let stmt = unsafe { stmt_handle_mut(stmt)? };
+        let len = column_bytes(stmt_current_value(stmt, col)?)?.len();
+        unsafe {
+            // SAFETY: output pointer validated above.
+            *out_len = len;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
+/// Copies up to `buf_len` bytes of a string or blob column, starting at byte `offset`,
+/// into a caller-owned buffer. Large values can be read in chunks without the library
+/// allocating a full copy; `*out_read` is 0 once `offset` reaches the end. String chunks
+/// are raw UTF-8 and may split a multi-byte character.
+#[unsafe(no_mangle)]
+pub extern "C" fn ndb_stmt_column_read(
+    stmt: *mut ndb_stmt_t,
+    col: usize,
+    offset: usize,
+    buf: *mut u8,
+    buf_len: usize,
+    out_read: *mut usize,
+) -> c_int {
+    let result = (|| -> ApiResult<()> {
+        if out_read.is_null() {
+            return Err(ApiError::null_pointer("out_read"));
+        }
+        if buf.is_null() && buf_len > 0 {
+            return Err(ApiError::null_pointer("buf"));
+        }
+        let stmt = unsafe { stmt_handle_mut(stmt)? };
+        let bytes = column_bytes(stmt_current_value(stmt, col)?)?;
+        let chunk = bytes.get(offset..).unwrap_or_default();
+        let n = chunk.len().min(buf_len);
+        unsafe {
+            // SAFETY: `buf` holds at least `buf_len >= n` bytes per the caller contract,
+            // and `out_read` was validated above.
+            if n > 0 {
+                ptr::copy_nonoverlapping(chunk.as_ptr(), buf, n);
+            }
+            *out_read = n;
+        }
+        Ok(())
+    })();
+    match result {
+        Ok(()) => ok_status(),
+        Err(e) => err_status(e),
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn ndb_stmt_column_json(
     stmt: *mut ndb_stmt_t,
@@ -1911,8 +5684,7 @@ pub extern "C" fn ndb_stmt_reset(stmt: *mut ndb_stmt_t) -> c_int {
     let result = (|| -> ApiResult<()> {
         let stmt = unsafe { stmt_handle_mut(stmt)? };
         stmt.executed = false;
-        stmt.rows.clear();
-        stmt.cursor = 0;
+        stmt.rows = StmtRows::Done;
         stmt.current = None;
         stmt.write_count = 0;
         Ok(())
@@ -1966,8 +5738,16 @@ mod tests {
 
     #[test]
     fn classify_write_query_detects_create() {
-        assert!(write_query_contains_write("CREATE (:User)").expect("parse"));
-        assert!(!write_query_contains_write("MATCH (n) RETURN n").expect("parse"));
+        assert!(
+            prepare_statement("CREATE (:User)")
+                .expect("parse")
+                .is_write()
+        );
+        assert!(
+            !prepare_statement("MATCH (n) RETURN n")
+                .expect("parse")
+                .is_write()
+        );
     }
 
     #[test]
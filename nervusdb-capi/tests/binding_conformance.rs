@@ -0,0 +1,277 @@
+//! Cross-binding conformance harness.
+//!
+//! Runs the scenario matrix in `examples-test/conformance/scenarios.json` through the
+//! C API in-process and, when configured, through the Python and Node bindings as
+//! subprocesses. Every step outcome is normalized to JSON and diffed against the C
+//! layer, which is the reference since both bindings sit on top of it.
+//!
+//! - `NERVUSDB_CONFORMANCE_PYTHON=<python>`: interpreter that can `import nervusdb`.
+//! - `NERVUSDB_CONFORMANCE_NODE_ADDON=<path>`: built `nervusdb_node.node`, run with `node`.
+//!
+//! `scripts/binding_conformance.sh` builds both bindings and sets these variables.
+
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::ptr;
+
+use nervusdb::{
+    NDB_ERRCAT_COMPATIBILITY, NDB_ERRCAT_EXECUTION, NDB_ERRCAT_STORAGE, NDB_ERRCAT_SYNTAX, NDB_OK,
+    ndb_begin_write, ndb_close, ndb_db_t, ndb_execute_write, ndb_last_error_category, ndb_open,
+    ndb_query, ndb_result_free, ndb_result_t, ndb_result_to_json, ndb_string_free, ndb_txn_commit,
+    ndb_txn_query, ndb_txn_rollback, ndb_txn_t,
+};
+use serde_json::{Value as JsonValue, json};
+
+fn conformance_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples-test/conformance")
+}
+
+fn load_scenarios() -> Vec<JsonValue> {
+    let path = conformance_dir().join("scenarios.json");
+    let text = std::fs::read_to_string(&path).expect("read scenarios.json");
+    let root: JsonValue = serde_json::from_str(&text).expect("parse scenarios.json");
+    root["scenarios"]
+        .as_array()
+        .expect("scenarios array")
+        .clone()
+}
+
+/// Expands `{"$repeat": [text, n]}` so large values need not live in the JSON file.
+fn expand_params(v: &JsonValue) -> JsonValue {
+    match v {
+        JsonValue::Object(map) => {
+            if let Some(JsonValue::Array(spec)) = map.get("$repeat")
+                && map.len() == 1
+            {
+                let text = spec[0].as_str().expect("$repeat text");
+                let n = spec[1].as_u64().expect("$repeat count") as usize;
+                return JsonValue::String(text.repeat(n));
+            }
+            JsonValue::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), expand_params(v)))
+                    .collect(),
+            )
+        }
+        JsonValue::Array(items) => JsonValue::Array(items.iter().map(expand_params).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Numbers compare by value: JavaScript cannot tell `1` from `1.0`.
+fn normalize(v: &JsonValue) -> JsonValue {
+    match v {
+        JsonValue::Number(n) => n
+            .as_f64()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(JsonValue::Null, JsonValue::Number),
+        JsonValue::Array(items) => JsonValue::Array(items.iter().map(normalize).collect()),
+        JsonValue::Object(map) => {
+            JsonValue::Object(map.iter().map(|(k, v)| (k.clone(), normalize(v))).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+fn last_error_outcome() -> JsonValue {
+    let category = match ndb_last_error_category() {
+        NDB_ERRCAT_SYNTAX => "syntax",
+        NDB_ERRCAT_EXECUTION => "execution",
+        NDB_ERRCAT_STORAGE => "storage",
+        NDB_ERRCAT_COMPATIBILITY => "compatibility",
+        _ => "unknown",
+    };
+    json!({ "error": category })
+}
+
+fn cstring(s: &str) -> CString {
+    CString::new(s).expect("no interior NUL")
+}
+
+fn step_params(step: &JsonValue) -> Option<CString> {
+    step.get("params")
+        .map(|p| cstring(&expand_params(p).to_string()))
+}
+
+fn run_c_scenario(scenario: &JsonValue, dir: &Path) -> Vec<JsonValue> {
+    let id = scenario["id"].as_str().expect("scenario id");
+    let path = cstring(&dir.join(id).to_string_lossy());
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK, "open {id}");
+
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    let mut outcomes = Vec::new();
+    for step in scenario["steps"].as_array().expect("steps") {
+        let query = step.get("query").and_then(JsonValue::as_str).map(cstring);
+        let query_ptr = query.as_ref().map_or(ptr::null(), |q| q.as_ptr());
+        let params = step_params(step);
+        let params_ptr = params.as_ref().map_or(ptr::null(), |p| p.as_ptr());
+
+        let outcome = match step["op"].as_str().expect("op") {
+            "write" => {
+                let mut count = 0u32;
+                if ndb_execute_write(db, query_ptr, params_ptr, &mut count) == NDB_OK {
+                    json!({ "count": count })
+                } else {
+                    last_error_outcome()
+                }
+            }
+            "query" => {
+                let mut result: *mut ndb_result_t = ptr::null_mut();
+                if ndb_query(db, query_ptr, params_ptr, &mut result) != NDB_OK {
+                    last_error_outcome()
+                } else {
+                    let mut text: *mut c_char = ptr::null_mut();
+                    assert_eq!(ndb_result_to_json(result, &mut text), NDB_OK);
+                    let rows: JsonValue = serde_json::from_str(
+                        unsafe { CStr::from_ptr(text) }.to_str().expect("utf8"),
+                    )
+                    .expect("result json");
+                    ndb_string_free(text);
+                    ndb_result_free(result);
+                    json!({ "rows": rows })
+                }
+            }
+            "begin" => {
+                if ndb_begin_write(db, &mut txn) == NDB_OK {
+                    json!({ "ok": true })
+                } else {
+                    last_error_outcome()
+                }
+            }
+            "txn_query" => {
+                if ndb_txn_query(txn, query_ptr, ptr::null()) == NDB_OK {
+                    json!({ "ok": true })
+                } else {
+                    last_error_outcome()
+                }
+            }
+            "commit" | "rollback" => {
+                let rc = if step["op"] == "commit" {
+                    ndb_txn_commit(txn)
+                } else {
+                    ndb_txn_rollback(txn)
+                };
+                txn = ptr::null_mut();
+                if rc == NDB_OK {
+                    json!({ "ok": true })
+                } else {
+                    last_error_outcome()
+                }
+            }
+            other => panic!("unknown conformance op {other:?} in {id}"),
+        };
+        outcomes.push(outcome);
+    }
+
+    assert!(txn.is_null(), "scenario {id} left a transaction open");
+    assert_eq!(ndb_close(db), NDB_OK, "close {id}");
+    outcomes
+}
+
+fn run_binding(mut cmd: Command, layer: &str) -> BTreeMap<String, Vec<JsonValue>> {
+    let output = cmd
+        .output()
+        .unwrap_or_else(|e| panic!("spawn {layer} runner: {e}"));
+    assert!(
+        output.status.success(),
+        "{layer} runner failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|e| panic!("{layer} runner printed invalid JSON: {e}"))
+}
+
+fn short(v: &JsonValue) -> String {
+    let text = v.to_string();
+    if text.chars().count() > 200 {
+        format!("{}...", text.chars().take(200).collect::<String>())
+    } else {
+        text
+    }
+}
+
+#[test]
+fn bindings_agree_with_c_api_on_scenario_matrix() {
+    let scenarios = load_scenarios();
+    let dir = tempfile::tempdir().expect("tempdir");
+
+    let mut layers: Vec<(&str, BTreeMap<String, Vec<JsonValue>>)> = Vec::new();
+    let c_dir = dir.path().join("c");
+    std::fs::create_dir_all(&c_dir).unwrap();
+    layers.push((
+        "c",
+        scenarios
+            .iter()
+            .map(|s| {
+                (
+                    s["id"].as_str().unwrap().to_string(),
+                    run_c_scenario(s, &c_dir),
+                )
+            })
+            .collect(),
+    ));
+
+    let scenarios_path = conformance_dir().join("scenarios.json");
+    if let Ok(python) = std::env::var("NERVUSDB_CONFORMANCE_PYTHON") {
+        let mut cmd = Command::new(python);
+        cmd.arg(conformance_dir().join("run_python.py"))
+            .arg(&scenarios_path)
+            .arg(dir.path().join("python"));
+        layers.push(("python", run_binding(cmd, "python")));
+    }
+    if let Ok(addon) = std::env::var("NERVUSDB_CONFORMANCE_NODE_ADDON") {
+        let mut cmd = Command::new("node");
+        cmd.arg(conformance_dir().join("run_node.js"))
+            .arg(addon)
+            .arg(&scenarios_path)
+            .arg(dir.path().join("node"));
+        layers.push(("node", run_binding(cmd, "node")));
+    }
+
+    let mut mismatches = Vec::new();
+    let reference = &layers[0].1;
+    for scenario in &scenarios {
+        let id = scenario["id"].as_str().unwrap();
+        let steps = scenario["steps"].as_array().unwrap();
+        let expected = &reference[id];
+
+        for (i, step) in steps.iter().enumerate() {
+            if let Some(expect) = step.get("expect")
+                && normalize(expect) != normalize(&expected[i])
+            {
+                mismatches.push(format!(
+                    "{id} step {i}: c gave {}, scenario expects {}",
+                    short(&expected[i]),
+                    short(expect)
+                ));
+            }
+        }
+
+        for (layer, outcomes) in &layers[1..] {
+            let Some(actual) = outcomes.get(id) else {
+                mismatches.push(format!("{id}: {layer} produced no outcomes"));
+                continue;
+            };
+            for (i, want) in expected.iter().enumerate() {
+                let got = actual.get(i).unwrap_or(&JsonValue::Null);
+                if normalize(got) != normalize(want) {
+                    mismatches.push(format!(
+                        "{id} step {i}: {layer} gave {}, c gave {}",
+                        short(got),
+                        short(want)
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "binding conformance drift:\n{}",
+        mismatches.join("\n")
+    );
+}
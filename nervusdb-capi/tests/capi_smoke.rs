@@ -1,11 +1,40 @@
-use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
+use std::ffi::{CStr, CString, c_void};
+use std::os::raw::{c_char, c_int};
 use std::ptr;
 
 use nervusdb::{
-    NDB_ERRCAT_EXECUTION, NDB_OK, ndb_begin_write, ndb_close, ndb_db_t, ndb_execute_write,
-    ndb_last_error_category, ndb_last_error_message, ndb_open, ndb_query, ndb_result_free,
-    ndb_result_t, ndb_result_to_json, ndb_string_free, ndb_txn_commit, ndb_txn_query, ndb_txn_t,
+    NDB_COL_BLOB, NDB_ERR_BUSY, NDB_ERR_CANCELLED, NDB_ERR_INVALID_ARGUMENT, NDB_ERR_STORAGE,
+    NDB_ERRCAT_EXECUTION, NDB_METRIC_DOT, NDB_OK, NDB_OPEN_READ_ONLY, NDB_STEP_DONE,
+    NDB_STEP_ERROR, NDB_STEP_ROW, ndb_backup, ndb_backup_incremental, ndb_begin_read,
+    ndb_begin_write, ndb_blob_free, ndb_buffer_free, ndb_buffer_t, ndb_bulkload_with_progress,
+    ndb_checkpoint, ndb_close, ndb_compact_with_progress, ndb_create_composite_index,
+    ndb_create_endpoint_constraint, ndb_create_trigger, ndb_create_unique_constraint, ndb_db_t,
+    ndb_drop_endpoint_constraint, ndb_drop_index, ndb_drop_query, ndb_drop_trigger,
+    ndb_drop_unique_constraint, ndb_edges_close, ndb_edges_next, ndb_edges_open, ndb_entity_get,
+    ndb_execute_named, ndb_execute_write, ndb_expiry_stats, ndb_export, ndb_export_scrubbed,
+    ndb_follower_applied_bookmark, ndb_follower_start, ndb_follower_stop, ndb_follower_t,
+    ndb_follower_wait, ndb_import, ndb_label_tiers, ndb_last_bookmark, ndb_last_error_category,
+    ndb_last_error_message, ndb_last_trace_id, ndb_namespaces, ndb_open, ndb_open_packed,
+    ndb_open_with_flags, ndb_operation_cancel, ndb_operation_free, ndb_operation_new,
+    ndb_operation_progress, ndb_operation_t, ndb_pack, ndb_pause_checkpoints, ndb_place_legal_hold,
+    ndb_prepare_read, ndb_prepare_write, ndb_query, ndb_query_arrow, ndb_query_count,
+    ndb_query_in_snapshot, ndb_query_named, ndb_query_page, ndb_query_typed, ndb_register_function,
+    ndb_release_legal_hold, ndb_rename_label, ndb_rename_property, ndb_replay_queries, ndb_restore,
+    ndb_result_cache_stats, ndb_result_free, ndb_result_t, ndb_result_to_json,
+    ndb_resume_checkpoints, ndb_save_query, ndb_search_vector_filtered, ndb_set_auto_checkpoint,
+    ndb_set_deterministic, ndb_set_expiry_sweep, ndb_set_label_tier, ndb_set_lazy_entities,
+    ndb_set_locale, ndb_set_namespace, ndb_set_query_capture, ndb_set_redaction,
+    ndb_set_result_cache, ndb_set_sensitive, ndb_set_wal_retention, ndb_set_write_throttle,
+    ndb_snapshot_release, ndb_snapshot_t, ndb_stmt_column_blob, ndb_stmt_column_bytes,
+    ndb_stmt_column_count_static, ndb_stmt_column_int64, ndb_stmt_column_name,
+    ndb_stmt_column_read, ndb_stmt_column_type, ndb_stmt_finalize, ndb_stmt_reset, ndb_stmt_step,
+    ndb_stmt_t, ndb_string_free, ndb_sweep_expired, ndb_triggers, ndb_txn_commit,
+    ndb_txn_commit_bookmark, ndb_txn_create_node, ndb_txn_execute, ndb_txn_get_or_create_label,
+    ndb_txn_query, ndb_txn_rollback, ndb_txn_rollback_to, ndb_txn_savepoint,
+    ndb_txn_set_constraints_deferred, ndb_txn_set_idempotency_key, ndb_txn_set_node_expiry,
+    ndb_txn_set_node_property, ndb_txn_set_vector, ndb_txn_t, ndb_unregister_function,
+    ndb_vacuum_with_progress, ndb_verify_backup, ndb_wait_for_bookmark, ndb_wal_retention,
+    ndb_wal_tail,
 };
 
 #[test]
@@ -95,3 +124,2525 @@ fn capi_query_api_rejects_write_statement() {
 
     assert_eq!(ndb_close(db), NDB_OK);
 }
+
+#[test]
+fn capi_blob_columns_are_readable_as_bytes_and_base64() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-blob");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    // 0x00 0xFF 'N' 0x10: interior NUL and non-UTF-8 bytes.
+    let create_sql = CString::new("CREATE (:File {data: $data})").expect("create cstr");
+    let params = CString::new(r#"{"data": {"type": "blob", "value": "AP9OEA=="}}"#).unwrap();
+    let mut write_count: u32 = 0;
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), params.as_ptr(), &mut write_count),
+        NDB_OK
+    );
+
+    let read_sql = CString::new("MATCH (f:File) RETURN f.data AS data").expect("read cstr");
+    let mut stmt: *mut ndb_stmt_t = ptr::null_mut();
+    assert_eq!(ndb_prepare_read(db, read_sql.as_ptr(), &mut stmt), NDB_OK);
+    let mut state = 0;
+    assert_eq!(ndb_stmt_step(stmt, &mut state), NDB_OK);
+    assert_eq!(state, NDB_STEP_ROW);
+    assert_eq!(ndb_stmt_column_type(stmt, 0), NDB_COL_BLOB);
+    let mut data: *mut u8 = ptr::null_mut();
+    let mut len = 0usize;
+    assert_eq!(ndb_stmt_column_blob(stmt, 0, &mut data, &mut len), NDB_OK);
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    assert_eq!(bytes, &[0x00, 0xFF, b'N', 0x10]);
+    ndb_blob_free(data, len);
+    assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, read_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .expect("json utf8")
+        .to_string();
+    assert_eq!(json, r#"[{"data":{"type":"blob","value":"AP9OEA=="}}]"#);
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_read_statements_pull_rows_lazily() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("lazy.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+
+    // A row that fails only surfaces when the caller steps to it.
+    let failing = CString::new("UNWIND [true, false, 1] AS v RETURN toBoolean(v) AS b").unwrap();
+    let mut stmt: *mut ndb_stmt_t = ptr::null_mut();
+    assert_eq!(ndb_prepare_read(db, failing.as_ptr(), &mut stmt), NDB_OK);
+    let mut state = 0;
+    for _ in 0..2 {
+        assert_eq!(ndb_stmt_step(stmt, &mut state), NDB_OK);
+        assert_eq!(state, NDB_STEP_ROW);
+    }
+    assert_ne!(ndb_stmt_step(stmt, &mut state), NDB_OK);
+    assert_eq!(state, NDB_STEP_ERROR);
+    assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+
+    let scan = CString::new("UNWIND range(1, 1000000) AS i RETURN i").unwrap();
+    assert_eq!(ndb_prepare_read(db, scan.as_ptr(), &mut stmt), NDB_OK);
+    for expected in 1..=3 {
+        assert_eq!(ndb_stmt_step(stmt, &mut state), NDB_OK);
+        assert_eq!(state, NDB_STEP_ROW);
+        let mut value = 0i64;
+        assert_eq!(ndb_stmt_column_int64(stmt, 0, &mut value), NDB_OK);
+        assert_eq!(value, expected);
+    }
+    // The open stream pins its snapshot until the statement lets go of it.
+    assert_eq!(ndb_close(db), NDB_ERR_BUSY);
+    assert_eq!(ndb_stmt_reset(stmt), NDB_OK);
+
+    let short = CString::new("UNWIND [1] AS i RETURN i").unwrap();
+    let mut done: *mut ndb_stmt_t = ptr::null_mut();
+    assert_eq!(ndb_prepare_read(db, short.as_ptr(), &mut done), NDB_OK);
+    assert_eq!(ndb_stmt_step(done, &mut state), NDB_OK);
+    assert_eq!(ndb_stmt_step(done, &mut state), NDB_OK);
+    assert_eq!(state, NDB_STEP_DONE);
+
+    // Exhausted and reset statements do not hold the database open.
+    assert_eq!(ndb_close(db), NDB_OK);
+    assert_eq!(ndb_stmt_finalize(done), NDB_OK);
+    assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+}
+
+#[test]
+fn capi_edge_cursor_fills_caller_buffers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("edges.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+    let create = CString::new(
+        "CREATE (a)-[:LINK {w: 2.5}]->(b)-[:LINK {w: 4}]->(c), (a)-[:LINK]->(c), (c)-[:TAG]->(a)",
+    )
+    .unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let link = CString::new("LINK").unwrap();
+    let w = CString::new("w").unwrap();
+    let mut edges = ptr::null_mut();
+    assert_eq!(
+        ndb_edges_open(db, link.as_ptr(), w.as_ptr(), &mut edges),
+        NDB_OK
+    );
+    let (mut src, mut dst, mut weight) = ([0u64; 2], [0u64; 2], [0f64; 2]);
+    let mut weights = Vec::new();
+    loop {
+        let mut len = usize::MAX;
+        assert_eq!(
+            ndb_edges_next(
+                edges,
+                2,
+                src.as_mut_ptr(),
+                dst.as_mut_ptr(),
+                weight.as_mut_ptr(),
+                &mut len,
+            ),
+            NDB_OK
+        );
+        if len == 0 {
+            break;
+        }
+        assert!(len <= 2);
+        assert!((0..len).all(|i| src[i] != dst[i]));
+        weights.extend_from_slice(&weight[..len]);
+    }
+    weights.sort_by(f64::total_cmp);
+    assert_eq!(weights, vec![1.0, 2.5, 4.0]);
+    // The open cursor pins its snapshot until it is closed.
+    assert_eq!(ndb_close(db), NDB_ERR_BUSY);
+    ndb_edges_close(edges);
+
+    // Without a type filter every edge is listed; weights are optional.
+    assert_eq!(
+        ndb_edges_open(db, ptr::null(), ptr::null(), &mut edges),
+        NDB_OK
+    );
+    let mut len = 0;
+    assert_eq!(
+        ndb_edges_next(
+            edges,
+            2,
+            src.as_mut_ptr(),
+            dst.as_mut_ptr(),
+            ptr::null_mut(),
+            &mut len,
+        ),
+        NDB_OK
+    );
+    let mut total = len;
+    while len > 0 {
+        assert_eq!(
+            ndb_edges_next(
+                edges,
+                2,
+                src.as_mut_ptr(),
+                dst.as_mut_ptr(),
+                ptr::null_mut(),
+                &mut len,
+            ),
+            NDB_OK
+        );
+        total += len;
+    }
+    assert_eq!(total, 4);
+    ndb_edges_close(edges);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_statement_columns_are_known_before_stepping() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("cols.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+    let names = |stmt: *mut ndb_stmt_t| -> Vec<String> {
+        (0..ndb_stmt_column_count_static(stmt))
+            .map(|col| {
+                let name = ndb_stmt_column_name(stmt, col);
+                unsafe { CStr::from_ptr(name) }
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+
+    let cases = [
+        (
+            "MATCH (n:P) RETURN n.x AS k, count(*) AS c ORDER BY c SKIP 1 LIMIT 2",
+            vec!["k", "c"],
+        ),
+        ("MATCH (n:P) RETURN DISTINCT n.name", vec!["n.name"]),
+        ("RETURN 1 AS a UNION RETURN 2 AS a", vec!["a"]),
+        ("CALL db.labels() YIELD label AS l", vec!["l"]),
+        ("EXPLAIN MATCH (n) RETURN n", vec!["plan"]),
+    ];
+    for (cypher, expected) in cases {
+        let cypher = CString::new(cypher).unwrap();
+        let mut stmt: *mut ndb_stmt_t = ptr::null_mut();
+        assert_eq!(ndb_prepare_read(db, cypher.as_ptr(), &mut stmt), NDB_OK);
+        assert_eq!(names(stmt), expected);
+        assert!(ndb_stmt_column_name(stmt, expected.len()).is_null());
+        assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+    }
+
+    let write = CString::new("CREATE (n:P {x: 1}) RETURN n.x AS x").unwrap();
+    let mut stmt: *mut ndb_stmt_t = ptr::null_mut();
+    assert_eq!(ndb_prepare_write(db, write.as_ptr(), &mut stmt), NDB_OK);
+    assert_eq!(names(stmt), vec!["x"]);
+    assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+
+    // A built-in procedure without YIELD names its columns with its first row.
+    let create = CString::new("CREATE (:P)").unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+    let call = CString::new("CALL db.labels()").unwrap();
+    assert_eq!(ndb_prepare_read(db, call.as_ptr(), &mut stmt), NDB_OK);
+    assert_eq!(ndb_stmt_column_count_static(stmt), 0);
+    let mut state = 0;
+    assert_eq!(ndb_stmt_step(stmt, &mut state), NDB_OK);
+    assert_eq!(state, NDB_STEP_ROW);
+    assert_eq!(names(stmt), vec!["label"]);
+    assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_large_string_columns_stream_in_chunks() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-large");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    let text = "日本語\u{0}🚀".repeat(200_000);
+    let create_sql = CString::new("CREATE (:Doc {text: $text})").expect("create cstr");
+    let params = CString::new(format!(
+        r#"{{"text": {}}}"#,
+        serde_json::to_string(&text).expect("json")
+    ))
+    .unwrap();
+    let mut write_count: u32 = 0;
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), params.as_ptr(), &mut write_count),
+        NDB_OK
+    );
+
+    let read_sql = CString::new("MATCH (d:Doc) RETURN d.text AS text").expect("read cstr");
+    let mut stmt: *mut ndb_stmt_t = ptr::null_mut();
+    assert_eq!(ndb_prepare_read(db, read_sql.as_ptr(), &mut stmt), NDB_OK);
+    let mut state = 0;
+    assert_eq!(ndb_stmt_step(stmt, &mut state), NDB_OK);
+    assert_eq!(state, NDB_STEP_ROW);
+
+    let mut total = 0usize;
+    assert_eq!(ndb_stmt_column_bytes(stmt, 0, &mut total), NDB_OK);
+    assert_eq!(total, text.len());
+
+    let mut out = Vec::with_capacity(total);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let mut read = 0usize;
+        assert_eq!(
+            ndb_stmt_column_read(stmt, 0, out.len(), buf.as_mut_ptr(), buf.len(), &mut read),
+            NDB_OK
+        );
+        if read == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..read]);
+    }
+    assert_eq!(String::from_utf8(out).expect("utf8"), text);
+    assert_eq!(ndb_stmt_finalize(stmt), NDB_OK);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_stored_queries_run_by_name_with_params() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-named");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    let add = CString::new("addUser").unwrap();
+    let add_sql = CString::new("CREATE (:User {name: $name})").unwrap();
+    let find = CString::new("findUser").unwrap();
+    let find_sql = CString::new("MATCH (u:User {name: $name}) RETURN u.name AS name").unwrap();
+    assert_eq!(ndb_save_query(db, add.as_ptr(), add_sql.as_ptr()), NDB_OK);
+    assert_eq!(ndb_save_query(db, find.as_ptr(), find_sql.as_ptr()), NDB_OK);
+
+    let broken = CString::new("MATCH (u RETURN u").unwrap();
+    assert_ne!(ndb_save_query(db, find.as_ptr(), broken.as_ptr()), NDB_OK);
+
+    let params = CString::new(r#"{"name":"carol"}"#).unwrap();
+    let mut affected: u32 = 0;
+    assert_eq!(
+        ndb_execute_named(db, add.as_ptr(), params.as_ptr(), &mut affected),
+        NDB_OK
+    );
+    assert_eq!(affected, 1);
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query_named(db, find.as_ptr(), params.as_ptr(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .expect("json utf8")
+        .to_string();
+    assert_eq!(json, r#"[{"name":"carol"}]"#);
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+
+    let mut dropped = 0;
+    assert_eq!(ndb_drop_query(db, find.as_ptr(), &mut dropped), NDB_OK);
+    assert_eq!(dropped, 1);
+    result = ptr::null_mut();
+    assert_ne!(
+        ndb_query_named(db, find.as_ptr(), params.as_ptr(), &mut result),
+        NDB_OK
+    );
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_profile_result_carries_operator_stats() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-profile");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new("UNWIND range(1, 4) AS i CREATE (:User {id: i})").unwrap();
+    let mut write_count: u32 = 0;
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), &mut write_count),
+        NDB_OK
+    );
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    let profile_sql = CString::new("PROFILE MATCH (u:User) RETURN u.id AS id").unwrap();
+    assert_eq!(
+        ndb_query(db, profile_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .expect("json utf8")
+        .to_string();
+    assert!(json.contains(r#""rows":4"#), "{json}");
+    assert!(json.contains(r#""operator":"NodeScan""#), "{json}");
+    assert!(json.contains("invocations=1"), "{json}");
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+
+    let write_sql = CString::new("PROFILE CREATE (:User {id: 5})").unwrap();
+    result = ptr::null_mut();
+    assert_ne!(
+        ndb_query(db, write_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_composite_index_create_and_drop() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-composite");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql =
+        CString::new("CREATE (:User {last_name: 'Lovelace', first_name: 'Ada'})").unwrap();
+    let mut write_count: u32 = 0;
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), &mut write_count),
+        NDB_OK
+    );
+
+    let label = CString::new("User").unwrap();
+    let properties = CString::new(r#"["last_name","first_name"]"#).unwrap();
+    assert_eq!(
+        ndb_create_composite_index(db, label.as_ptr(), properties.as_ptr()),
+        NDB_OK
+    );
+    let not_a_list = CString::new(r#""last_name""#).unwrap();
+    assert_ne!(
+        ndb_create_composite_index(db, label.as_ptr(), not_a_list.as_ptr()),
+        NDB_OK
+    );
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    let explain_sql =
+        CString::new("EXPLAIN MATCH (u:User {last_name: 'Lovelace', first_name: 'Ada'}) RETURN u")
+            .unwrap();
+    assert_eq!(
+        ndb_query(db, explain_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .expect("json utf8")
+        .to_string();
+    assert!(json.contains("CompositeIndexSeek"), "{json}");
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+
+    let mut dropped = 0;
+    assert_eq!(
+        ndb_drop_index(db, label.as_ptr(), properties.as_ptr(), &mut dropped),
+        NDB_OK
+    );
+    assert_eq!(dropped, 1);
+    assert_eq!(
+        ndb_drop_index(db, label.as_ptr(), properties.as_ptr(), &mut dropped),
+        NDB_OK
+    );
+    assert_eq!(dropped, 0);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_commit_bookmarks_can_be_awaited() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-bookmark");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    let create_sql = CString::new("CREATE (:User {id: 1})").unwrap();
+    assert_eq!(ndb_txn_query(txn, create_sql.as_ptr(), ptr::null()), NDB_OK);
+    let mut bookmark: u64 = 0;
+    assert_eq!(ndb_txn_commit_bookmark(txn, &mut bookmark), NDB_OK);
+    assert!(bookmark > 0);
+
+    let mut last: u64 = 0;
+    assert_eq!(ndb_last_bookmark(db, &mut last), NDB_OK);
+    assert_eq!(last, bookmark);
+    assert_eq!(ndb_wait_for_bookmark(db, bookmark, 0), NDB_OK);
+    assert_ne!(ndb_wait_for_bookmark(db, bookmark + 100, 10), NDB_OK);
+    assert_ne!(ndb_last_bookmark(db, ptr::null_mut()), NDB_OK);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_follower_applies_primary_commits() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let open = |name: &str| {
+        let path = CString::new(dir.path().join(name).to_string_lossy().to_string()).unwrap();
+        let mut db: *mut ndb_db_t = ptr::null_mut();
+        assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+        db
+    };
+    let primary = open("primary");
+    let replica = open("replica");
+
+    let mut follower: *mut ndb_follower_t = ptr::null_mut();
+    assert_ne!(ndb_follower_start(primary, primary, &mut follower), NDB_OK);
+    assert_eq!(ndb_follower_start(replica, primary, &mut follower), NDB_OK);
+
+    let create_sql = CString::new("CREATE (:User {id: 1})").unwrap();
+    let mut written: u32 = 0;
+    assert_eq!(
+        ndb_execute_write(primary, create_sql.as_ptr(), ptr::null(), &mut written),
+        NDB_OK
+    );
+    let mut bookmark: u64 = 0;
+    assert_eq!(ndb_last_bookmark(primary, &mut bookmark), NDB_OK);
+    assert_eq!(ndb_follower_wait(follower, bookmark, 5_000), NDB_OK);
+    let mut applied: u64 = 0;
+    assert_eq!(
+        ndb_follower_applied_bookmark(follower, &mut applied),
+        NDB_OK
+    );
+    assert!(applied >= bookmark);
+
+    let count_sql = CString::new("MATCH (n:User) RETURN n").unwrap();
+    let mut count: u64 = 0;
+    assert_eq!(
+        ndb_query_count(replica, count_sql.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(count, 1);
+
+    // The follower holds both databases open until it stops.
+    assert_eq!(ndb_close(primary), NDB_ERR_BUSY);
+    ndb_follower_stop(follower);
+    assert_eq!(ndb_close(replica), NDB_OK);
+    assert_eq!(ndb_close(primary), NDB_OK);
+}
+
+#[test]
+fn capi_idempotency_key_applies_a_retried_commit_once() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-idempotency");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    let key = CString::new("import-1").unwrap();
+    let create_sql = CString::new("CREATE (:User {id: 1})").unwrap();
+    let mut bookmarks = [0u64; 2];
+    for (attempt, bookmark) in bookmarks.iter_mut().enumerate() {
+        let mut txn: *mut ndb_txn_t = ptr::null_mut();
+        assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+        let mut applied: c_int = -1;
+        assert_eq!(
+            ndb_txn_set_idempotency_key(txn, key.as_ptr(), &mut applied),
+            NDB_OK
+        );
+        assert_eq!(applied, c_int::from(attempt > 0));
+        assert_eq!(ndb_txn_query(txn, create_sql.as_ptr(), ptr::null()), NDB_OK);
+        assert_eq!(ndb_txn_commit_bookmark(txn, bookmark), NDB_OK);
+    }
+    assert_eq!(bookmarks[0], bookmarks[1]);
+
+    let count_sql = CString::new("MATCH (u:User) RETURN count(u) AS c").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, count_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .expect("json utf8")
+        .to_string();
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+    assert!(json.contains("\"c\":1"), "{json}");
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_unique_constraint_rejects_duplicates() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-unique");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let label = CString::new("User").unwrap();
+    let property = CString::new("email").unwrap();
+    assert_eq!(
+        ndb_create_unique_constraint(db, label.as_ptr(), property.as_ptr()),
+        NDB_OK
+    );
+
+    let create_sql = CString::new("CREATE (:User {email: 'a@x'})").unwrap();
+    for attempt in 0..2 {
+        let mut txn: *mut ndb_txn_t = ptr::null_mut();
+        assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+        let rc = ndb_txn_query(txn, create_sql.as_ptr(), ptr::null());
+        if attempt == 0 {
+            assert_eq!(rc, NDB_OK);
+            assert_eq!(ndb_txn_commit(txn), NDB_OK);
+        } else {
+            assert_ne!(rc, NDB_OK);
+            let mut buf = vec![0 as c_char; 256];
+            assert!(ndb_last_error_message(buf.as_mut_ptr(), buf.len()) > 0);
+            let message = unsafe { CStr::from_ptr(buf.as_ptr()) }
+                .to_str()
+                .expect("error message utf8");
+            assert!(message.contains("constraint violation"), "{message}");
+            assert_eq!(ndb_txn_rollback(txn), NDB_OK);
+        }
+    }
+
+    // Deferred, the duplicate is only caught at commit.
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert_eq!(ndb_txn_set_constraints_deferred(txn, 1), NDB_OK);
+    assert_eq!(ndb_txn_query(txn, create_sql.as_ptr(), ptr::null()), NDB_OK);
+    assert_ne!(ndb_txn_commit(txn), NDB_OK);
+
+    let mut dropped: c_int = 0;
+    assert_eq!(
+        ndb_drop_unique_constraint(db, label.as_ptr(), property.as_ptr(), &mut dropped),
+        NDB_OK
+    );
+    assert_eq!(dropped, 1);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_endpoint_constraint_rejects_other_labels() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-endpoints");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let rel_type = CString::new("KNOWS").unwrap();
+    let malformed = CString::new(r#"["Person"]"#).unwrap();
+    assert_ne!(
+        ndb_create_endpoint_constraint(db, rel_type.as_ptr(), malformed.as_ptr()),
+        NDB_OK
+    );
+    let endpoints = CString::new(r#"[["Person","Person"]]"#).unwrap();
+    assert_eq!(
+        ndb_create_endpoint_constraint(db, rel_type.as_ptr(), endpoints.as_ptr()),
+        NDB_OK
+    );
+
+    let create_sql = CString::new("CREATE (:Person)-[:KNOWS]->(:City)").unwrap();
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert_eq!(ndb_txn_query(txn, create_sql.as_ptr(), ptr::null()), NDB_OK);
+    assert_ne!(ndb_txn_commit(txn), NDB_OK);
+    let mut buf = vec![0 as c_char; 256];
+    assert!(ndb_last_error_message(buf.as_mut_ptr(), buf.len()) > 0);
+    let message = unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_str()
+        .expect("error message utf8");
+    assert!(message.contains("constraint violation"), "{message}");
+
+    let mut dropped: c_int = 0;
+    assert_eq!(
+        ndb_drop_endpoint_constraint(db, rel_type.as_ptr(), &mut dropped),
+        NDB_OK
+    );
+    assert_eq!(dropped, 1);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+extern "C" fn record_progress(done: u64, total: u64, user_data: *mut c_void) {
+    // SAFETY: the test passes a live `Vec<(u64, u64)>` as user data.
+    let reports = unsafe { &mut *(user_data as *mut Vec<(u64, u64)>) };
+    reports.push((done, total));
+}
+
+thread_local! {
+    static FUNCTION_RESULT: std::cell::RefCell<CString> = std::cell::RefCell::new(CString::default());
+}
+
+/// Adds `user_data` (an `i64`) to the single integer argument.
+extern "C" fn add_offset(args_json: *const c_char, user_data: *mut c_void) -> *const c_char {
+    let args = unsafe { CStr::from_ptr(args_json) }.to_str().unwrap();
+    let Ok(arg) = args.trim_matches(['[', ']']).parse::<i64>() else {
+        return ptr::null();
+    };
+    // SAFETY: the test passes a live `i64` as user data.
+    let offset = unsafe { *(user_data as *const i64) };
+    FUNCTION_RESULT.with(|result| {
+        *result.borrow_mut() = CString::new((arg + offset).to_string()).unwrap();
+        result.borrow().as_ptr()
+    })
+}
+
+#[test]
+fn capi_registered_function_is_callable_from_cypher() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = CString::new(dir.path().join("capi-udf").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    static OFFSET: i64 = 100;
+    let name = CString::new("capi.addOffset").unwrap();
+    assert_eq!(
+        ndb_register_function(
+            name.as_ptr(),
+            1,
+            Some(add_offset),
+            &OFFSET as *const i64 as *mut c_void,
+        ),
+        NDB_OK
+    );
+    let builtin = CString::new("toUpper").unwrap();
+    assert_ne!(
+        ndb_register_function(builtin.as_ptr(), 1, Some(add_offset), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let query = CString::new("RETURN capi.addOffset(5) AS a, capi.addOffset('x') AS b").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(json.contains("\"a\":105"), "{json}");
+    assert!(json.contains("\"b\":null"), "{json}");
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+
+    let wrong_arity = CString::new("RETURN capi.addOffset(1, 2)").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_ne!(
+        ndb_query(db, wrong_arity.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+
+    let mut removed: c_int = 0;
+    assert_eq!(ndb_unregister_function(name.as_ptr(), &mut removed), NDB_OK);
+    assert_eq!(removed, 1);
+    assert_ne!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_long_operations_report_progress_and_cancel() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-operation");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql =
+        CString::new("UNWIND range(1, 300) AS i CREATE (:Item {n: i})").expect("create cstr");
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let mut op: *mut ndb_operation_t = ptr::null_mut();
+    assert_eq!(ndb_operation_new(&mut op), NDB_OK);
+    assert_eq!(ndb_operation_cancel(op), NDB_OK);
+    assert_eq!(
+        ndb_compact_with_progress(db, op, None, ptr::null_mut()),
+        NDB_ERR_CANCELLED
+    );
+    ndb_operation_free(op);
+
+    assert_eq!(ndb_operation_new(&mut op), NDB_OK);
+    let mut reports: Vec<(u64, u64)> = Vec::new();
+    assert_eq!(
+        ndb_compact_with_progress(
+            db,
+            op,
+            Some(record_progress),
+            &mut reports as *mut Vec<(u64, u64)> as *mut c_void,
+        ),
+        NDB_OK
+    );
+    let (mut done, mut total) = (0u64, 0u64);
+    assert_eq!(ndb_operation_progress(op, &mut done, &mut total), NDB_OK);
+    assert_eq!((done, total), (302, 302));
+    assert_eq!(reports.first(), Some(&(0, 302)));
+    assert_eq!(reports.last(), Some(&(302, 302)));
+    ndb_operation_free(op);
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    reports.clear();
+    assert_eq!(
+        ndb_vacuum_with_progress(
+            db_path.as_ptr(),
+            ptr::null_mut(),
+            Some(record_progress),
+            &mut reports as *mut Vec<(u64, u64)> as *mut c_void,
+        ),
+        NDB_OK
+    );
+    let &(done, total) = reports.last().expect("vacuum progress");
+    assert!(total > 0);
+    assert_eq!(done, total);
+
+    let bulk_path = dir.path().join("capi-operation-bulk");
+    let bulk_path = CString::new(bulk_path.to_string_lossy().to_string()).expect("bulk cstr");
+    let nodes = CString::new(
+        r#"[{"external_id": 1, "label": "A", "properties": {}},
+            {"external_id": 2, "label": "A", "properties": {}}]"#,
+    )
+    .unwrap();
+    let edges = CString::new(
+        r#"[{"src_external_id": 1, "rel_type": "R", "dst_external_id": 2, "properties": {}}]"#,
+    )
+    .unwrap();
+    assert_eq!(ndb_operation_new(&mut op), NDB_OK);
+    assert_eq!(ndb_operation_cancel(op), NDB_OK);
+    assert_eq!(
+        ndb_bulkload_with_progress(
+            bulk_path.as_ptr(),
+            nodes.as_ptr(),
+            edges.as_ptr(),
+            op,
+            None,
+            ptr::null_mut(),
+        ),
+        NDB_ERR_CANCELLED
+    );
+    ndb_operation_free(op);
+    reports.clear();
+    assert_eq!(
+        ndb_bulkload_with_progress(
+            bulk_path.as_ptr(),
+            nodes.as_ptr(),
+            edges.as_ptr(),
+            ptr::null_mut(),
+            Some(record_progress),
+            &mut reports as *mut Vec<(u64, u64)> as *mut c_void,
+        ),
+        NDB_OK
+    );
+    assert_eq!(reports.last(), Some(&(6, 6)));
+}
+
+#[test]
+fn capi_rename_property_and_label() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-rename");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new("CREATE (:User {mail: 'a@x'}), (:User {mail: 'b@x'})").unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let label = CString::new("User").unwrap();
+    let old = CString::new("mail").unwrap();
+    let new = CString::new("email").unwrap();
+    let mut reports: Vec<(u64, u64)> = Vec::new();
+    let mut renamed: u64 = 0;
+    assert_eq!(
+        ndb_rename_property(
+            db,
+            label.as_ptr(),
+            old.as_ptr(),
+            new.as_ptr(),
+            Some(record_progress),
+            &mut reports as *mut Vec<(u64, u64)> as *mut c_void,
+            &mut renamed,
+        ),
+        NDB_OK
+    );
+    assert_eq!(renamed, 2);
+    assert_eq!(reports, vec![(2, 2)]);
+
+    let customer = CString::new("Customer").unwrap();
+    let mut label_renamed: c_int = 0;
+    assert_eq!(
+        ndb_rename_label(db, label.as_ptr(), customer.as_ptr(), &mut label_renamed),
+        NDB_OK
+    );
+    assert_eq!(label_renamed, 1);
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    let query_sql =
+        CString::new("MATCH (c:Customer) WHERE c.email IS NOT NULL RETURN count(c) AS c").unwrap();
+    assert_eq!(
+        ndb_query(db, query_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    let text = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(text.contains("2"), "{text}");
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_redaction_masks_sensitive_properties() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-redaction");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new("CREATE (:User {ssn: '123'})").unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+    let label = CString::new("User").unwrap();
+    let property = CString::new("ssn").unwrap();
+    let mut changed: c_int = 0;
+    assert_eq!(
+        ndb_set_sensitive(db, label.as_ptr(), property.as_ptr(), 1, &mut changed),
+        NDB_OK
+    );
+    assert_eq!(changed, 1);
+
+    let query_sql = CString::new("MATCH (u:User) RETURN u.ssn AS ssn").unwrap();
+    let read_ssn = || {
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(
+            ndb_query(db, query_sql.as_ptr(), ptr::null(), &mut result),
+            NDB_OK
+        );
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        text
+    };
+    assert!(read_ssn().contains("123"));
+    assert_eq!(ndb_set_redaction(db, 1), NDB_OK);
+    let text = read_ssn();
+    assert!(
+        text.contains("[REDACTED]") && !text.contains("123"),
+        "{text}"
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_set_deterministic_sorts_expansions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("det.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+    let mut count = 0;
+    let setup = std::iter::once(
+        "CREATE (:Hub), (:Leaf {n: 1}), (:Leaf {n: 2}), (:Leaf {n: 3})".to_string(),
+    )
+    .chain([3, 1, 2].map(|n| format!("MATCH (h:Hub), (l:Leaf {{n: {n}}}) CREATE (h)-[:L]->(l)")));
+    for cypher in setup {
+        let cypher = CString::new(cypher).unwrap();
+        assert_eq!(
+            ndb_execute_write(db, cypher.as_ptr(), ptr::null(), &mut count),
+            NDB_OK
+        );
+    }
+
+    assert_eq!(ndb_set_deterministic(db, 1), NDB_OK);
+    let query = CString::new("MATCH (:Hub)-[:L]->(l) RETURN l.n AS n").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    let text = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(text, r#"[{"n":1},{"n":2},{"n":3}]"#);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+    assert_ne!(ndb_set_deterministic(ptr::null_mut(), 1), NDB_OK);
+}
+
+#[test]
+fn capi_set_locale_formats_numbers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("locale.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+    let query = CString::new("RETURN toString(1234.5, '#,##0.00') AS s").unwrap();
+    let run = |db: *mut ndb_db_t| {
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(
+            ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+            NDB_OK
+        );
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        text
+    };
+
+    assert_eq!(run(db), r#"[{"s":"1,234.50"}]"#);
+    let german = CString::new("de-DE").unwrap();
+    assert_eq!(ndb_set_locale(db, german.as_ptr()), NDB_OK);
+    assert_eq!(run(db), r#"[{"s":"1.234,50"}]"#);
+    let unknown = CString::new("tlh").unwrap();
+    assert_ne!(ndb_set_locale(db, unknown.as_ptr()), NDB_OK);
+    assert_eq!(ndb_set_locale(db, ptr::null()), NDB_OK);
+    assert_eq!(run(db), r#"[{"s":"1,234.50"}]"#);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_typed_array_envelopes_store_packed_arrays() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("arrays.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    let label = CString::new("Doc").unwrap();
+    let mut label_id = 0u32;
+    assert_eq!(
+        ndb_txn_get_or_create_label(txn, label.as_ptr(), &mut label_id),
+        NDB_OK
+    );
+    let mut node = 0u32;
+    assert_eq!(ndb_txn_create_node(txn, 1, label_id, &mut node), NDB_OK);
+    let key = CString::new("ids").unwrap();
+    let ids = CString::new(r#"{"type": "int_array", "value": [4, 5, 6]}"#).unwrap();
+    assert_eq!(
+        ndb_txn_set_node_property(txn, node, key.as_ptr(), ids.as_ptr()),
+        NDB_OK
+    );
+    let bad = CString::new(r#"{"type": "int_array", "value": [1.5]}"#).unwrap();
+    assert_ne!(
+        ndb_txn_set_node_property(txn, node, key.as_ptr(), bad.as_ptr()),
+        NDB_OK
+    );
+    assert_eq!(ndb_txn_commit(txn), NDB_OK);
+
+    let query = CString::new(
+        "MATCH (d:Doc) RETURN d.ids[1] AS second, 6 IN d.ids AS has, size(d.ids) AS n",
+    )
+    .unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    let text = unsafe { CStr::from_ptr(json) }.to_str().unwrap();
+    assert_eq!(text, r#"[{"has":true,"n":3,"second":5}]"#);
+    ndb_string_free(json);
+    ndb_result_free(result);
+
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_namespaces_resolve_curies_and_invalidate_cached_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("ns.ndb").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(path.as_ptr(), &mut db), NDB_OK);
+    assert_eq!(ndb_set_result_cache(db, 8, 0), NDB_OK);
+
+    let prefix = CString::new("ex").unwrap();
+    let old_iri = CString::new("http://old.example/").unwrap();
+    let new_iri = CString::new("https://new.example/id/").unwrap();
+    let mut changed = 0;
+    assert_eq!(
+        ndb_set_namespace(db, prefix.as_ptr(), old_iri.as_ptr(), &mut changed),
+        NDB_OK
+    );
+    assert_eq!(changed, 1);
+    let bad = CString::new("not an iri").unwrap();
+    assert_ne!(
+        ndb_set_namespace(db, prefix.as_ptr(), bad.as_ptr(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let query = CString::new("RETURN iri('ex:alice') AS iri").unwrap();
+    let read = |db: *mut ndb_db_t| {
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(
+            ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+            NDB_OK
+        );
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        text
+    };
+    assert_eq!(read(db), r#"[{"iri":"http://old.example/alice"}]"#);
+    assert_eq!(
+        ndb_set_namespace(db, prefix.as_ptr(), new_iri.as_ptr(), &mut changed),
+        NDB_OK
+    );
+    assert_eq!(read(db), r#"[{"iri":"https://new.example/id/alice"}]"#);
+
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_namespaces(db, &mut json), NDB_OK);
+    let text = unsafe { CStr::from_ptr(json) }.to_str().unwrap();
+    assert_eq!(text, r#"{"ex":"https://new.example/id/"}"#);
+    ndb_string_free(json);
+
+    assert_eq!(
+        ndb_set_namespace(db, prefix.as_ptr(), ptr::null(), &mut changed),
+        NDB_OK
+    );
+    assert_eq!(changed, 1);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_query_count_returns_row_count_only() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-count");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new("UNWIND range(1, 12) AS i CREATE (:Row {i: i})").unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let count_sql = CString::new("MATCH (r:Row) WHERE r.i > $min RETURN r LIMIT 5").unwrap();
+    let params = CString::new(r#"{"min": 9}"#).unwrap();
+    let mut count: u64 = 0;
+    assert_eq!(
+        ndb_query_count(db, count_sql.as_ptr(), params.as_ptr(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(count, 3);
+
+    let write_sql = CString::new("CREATE (:Row)").unwrap();
+    assert_ne!(
+        ndb_query_count(db, write_sql.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_last_error_category(), NDB_ERRCAT_EXECUTION);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_search_vector_filtered_applies_metric_and_filter() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-vector-filter");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    let create_sql =
+        CString::new("CREATE (:Doc {lang: 'en'}), (:Doc {lang: 'de'}), (:Doc {lang: 'en'})")
+            .unwrap();
+    assert_eq!(ndb_txn_query(txn, create_sql.as_ptr(), ptr::null()), NDB_OK);
+    assert_eq!(ndb_txn_commit(txn), NDB_OK);
+
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    for (node, vector) in [(0u32, [1.0f32, 0.0]), (1, [9.0, 0.0]), (2, [3.0, 0.0])] {
+        assert_eq!(
+            ndb_txn_set_vector(txn, node, vector.as_ptr(), vector.len()),
+            NDB_OK
+        );
+    }
+    assert_eq!(ndb_txn_commit(txn), NDB_OK);
+
+    let query = [1.0f32, 0.0];
+    let label = CString::new("Doc").unwrap();
+    let property = CString::new("lang").unwrap();
+    let value = CString::new(r#""en""#).unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_search_vector_filtered(
+            db,
+            query.as_ptr(),
+            query.len(),
+            1,
+            NDB_METRIC_DOT,
+            label.as_ptr(),
+            property.as_ptr(),
+            value.as_ptr(),
+            &mut result,
+        ),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    let text = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert!(text.contains(r#""node_id":2"#), "{text}");
+
+    assert_ne!(
+        ndb_search_vector_filtered(
+            db,
+            query.as_ptr(),
+            query.len(),
+            1,
+            42,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            &mut result,
+        ),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_query_page_follows_continuation_tokens() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-page");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new("UNWIND range(1, 5) AS i CREATE (:Row {i: i})").unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let page_sql = CString::new("MATCH (r:Row) RETURN r.i AS i ORDER BY i DESC").unwrap();
+    let mut pages = Vec::new();
+    let mut token: Option<CString> = None;
+    loop {
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        let mut next: *mut c_char = ptr::null_mut();
+        assert_eq!(
+            ndb_query_page(
+                db,
+                page_sql.as_ptr(),
+                ptr::null(),
+                2,
+                token.as_ref().map_or(ptr::null(), |t| t.as_ptr()),
+                &mut result,
+                &mut next,
+            ),
+            NDB_OK
+        );
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        pages.push(
+            unsafe { CStr::from_ptr(json) }
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        ndb_string_free(json);
+        ndb_result_free(result);
+        if next.is_null() {
+            break;
+        }
+        token = Some(unsafe { CStr::from_ptr(next) }.to_owned());
+        ndb_string_free(next);
+    }
+    assert_eq!(
+        pages,
+        [
+            r#"[{"i":5},{"i":4}]"#,
+            r#"[{"i":3},{"i":2}]"#,
+            r#"[{"i":1}]"#
+        ]
+    );
+
+    let unordered = CString::new("MATCH (r:Row) RETURN r.i").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    let mut next: *mut c_char = ptr::null_mut();
+    assert_ne!(
+        ndb_query_page(
+            db,
+            unordered.as_ptr(),
+            ptr::null(),
+            2,
+            ptr::null(),
+            &mut result,
+            &mut next,
+        ),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_query_typed_reports_column_types() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-typed");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql =
+        CString::new("CREATE (:Event {name: 'launch', at: $at, seq: 1}), (:Event {seq: 2.5})")
+            .unwrap();
+    let params =
+        CString::new(r#"{"at": {"type": "datetime", "value": 1700000000000000}}"#).unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), params.as_ptr(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let query_sql = CString::new(
+        "MATCH (e:Event) RETURN e.name AS name, e.at AS at, e.seq AS seq, e.none AS none ORDER BY seq",
+    )
+    .unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query_typed(db, query_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    let text = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(
+        text,
+        concat!(
+            r#"{"columns":[{"name":"name","type":"string"},{"name":"at","type":"datetime"},"#,
+            r#"{"name":"seq","type":"any"},{"name":"none","type":"null"}],"#,
+            r#""rows":[["launch",{"type":"datetime","value":1700000000000000},1,null],"#,
+            r#"[null,null,2.5,null]]}"#
+        )
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_query_arrow_writes_an_ipc_stream() {
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int64Type};
+    use arrow_ipc::reader::StreamReader;
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-arrow");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new(
+        "CREATE (:Event {name: 'launch', seq: 1, tags: ['a']}), (:Event {name: 'land', seq: 2.5})",
+    )
+    .unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let query_sql = CString::new(
+        "MATCH (e:Event) RETURN e.name AS name, e.seq AS seq, e.tags AS tags, size(e.name) AS len ORDER BY seq",
+    )
+    .unwrap();
+    let mut buffer = ndb_buffer_t {
+        data: ptr::null_mut(),
+        len: 0,
+        free: None,
+    };
+    assert_eq!(
+        ndb_query_arrow(db, query_sql.as_ptr(), ptr::null(), &mut buffer),
+        NDB_OK
+    );
+    let bytes = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+    let batches: Vec<_> = StreamReader::try_new(bytes, None)
+        .expect("ipc stream")
+        .collect::<Result<_, _>>()
+        .expect("record batches");
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    let names: Vec<_> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect();
+    assert_eq!(names, ["name", "seq", "tags", "len"]);
+    let name = batch.column(0).as_string::<i32>();
+    assert_eq!((name.value(0), name.value(1)), ("launch", "land"));
+    let seq = batch.column(1).as_primitive::<Float64Type>();
+    assert_eq!((seq.value(0), seq.value(1)), (1.0, 2.5));
+    let tags = batch.column(2).as_string::<i32>();
+    assert_eq!(tags.value(0), r#"["a"]"#);
+    assert!(tags.is_null(1));
+    let len = batch.column(3).as_primitive::<Int64Type>();
+    assert_eq!((len.value(0), len.value(1)), (6, 4));
+
+    let free = buffer.free.expect("free callback");
+    free(&mut buffer);
+    assert!(buffer.data.is_null());
+    ndb_buffer_free(&mut buffer);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_read_snapshot_repeats_reads_across_commits() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-snapshot");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create_sql = CString::new("CREATE (:Row {i: 1})").unwrap();
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let mut snapshot: *mut ndb_snapshot_t = ptr::null_mut();
+    assert_eq!(ndb_begin_read(db, &mut snapshot), NDB_OK);
+    assert_eq!(
+        ndb_execute_write(db, create_sql.as_ptr(), ptr::null(), ptr::null_mut()),
+        NDB_OK
+    );
+
+    let count_sql = CString::new("MATCH (r:Row) RETURN count(r) AS n").unwrap();
+    let read = |result: *mut ndb_result_t| {
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        text
+    };
+    for _ in 0..2 {
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(
+            ndb_query_in_snapshot(snapshot, count_sql.as_ptr(), ptr::null(), &mut result),
+            NDB_OK
+        );
+        assert_eq!(read(result), r#"[{"n":1}]"#);
+    }
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, count_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    assert_eq!(read(result), r#"[{"n":2}]"#);
+
+    assert_ne!(
+        ndb_query_in_snapshot(snapshot, create_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    assert_ne!(ndb_close(db), NDB_OK);
+    ndb_snapshot_release(snapshot);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_txn_execute_reads_staged_writes_and_aborts_on_failure() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-txn-execute");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let execute = |txn: *mut ndb_txn_t, cypher: &str| {
+        let cypher = CString::new(cypher).unwrap();
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        let rc = ndb_txn_execute(txn, cypher.as_ptr(), ptr::null(), &mut result);
+        if rc != NDB_OK {
+            return None;
+        }
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        Some(text)
+    };
+    let count_sql = "MATCH (r:Row) RETURN count(r) AS n";
+
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert_eq!(
+        execute(txn, "CREATE (r:Row {i: 1}) RETURN r.i AS i").as_deref(),
+        Some(r#"[{"i":1}]"#)
+    );
+    let create_sql = CString::new("CREATE (:Row {i: 2})").unwrap();
+    assert_eq!(ndb_txn_query(txn, create_sql.as_ptr(), ptr::null()), NDB_OK);
+    assert_eq!(execute(txn, count_sql).as_deref(), Some(r#"[{"n":2}]"#));
+    assert_eq!(ndb_txn_commit(txn), NDB_OK);
+
+    // A failed statement rolls back the whole transaction on commit.
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert!(execute(txn, "CREATE (:Row {i: 3})").is_some());
+    assert!(execute(txn, "CREATE (:Row {i: 4").is_none());
+    assert_ne!(ndb_txn_commit(txn), NDB_OK);
+
+    let count_sql = CString::new(count_sql).unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, count_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    assert_eq!(
+        unsafe { CStr::from_ptr(json) }.to_str().unwrap(),
+        r#"[{"n":2}]"#
+    );
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_result_cache_serves_repeat_reads_until_a_write_touches_them() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-result-cache");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    assert_eq!(ndb_set_result_cache(db, 8, 0), NDB_OK);
+    let query = |cypher: &str| {
+        let cypher = CString::new(cypher).unwrap();
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(
+            ndb_query(db, cypher.as_ptr(), ptr::null(), &mut result),
+            NDB_OK
+        );
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        text
+    };
+    let write = |cypher: &str| {
+        let cypher = CString::new(cypher).unwrap();
+        let mut txn: *mut ndb_txn_t = ptr::null_mut();
+        assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+        assert_eq!(ndb_txn_query(txn, cypher.as_ptr(), ptr::null()), NDB_OK);
+        assert_eq!(ndb_txn_commit(txn), NDB_OK);
+    };
+    let stats = || {
+        let (mut hits, mut misses, mut len) = (0u64, 0u64, 0usize);
+        assert_eq!(
+            ndb_result_cache_stats(db, &mut hits, &mut misses, &mut len),
+            NDB_OK
+        );
+        (hits, misses, len)
+    };
+    let count_sql = "MATCH (u:User) RETURN count(u) AS n";
+
+    write("CREATE (:User {name: 'ada'})");
+    assert_eq!(query(count_sql), r#"[{"n":1}]"#);
+    assert_eq!(query(count_sql), r#"[{"n":1}]"#);
+    assert_eq!(stats(), (1, 1, 1));
+
+    write("CREATE (:Order {total: 5})");
+    assert_eq!(query(count_sql), r#"[{"n":1}]"#);
+    assert_eq!(stats(), (2, 1, 1));
+
+    write("CREATE (:User {name: 'bob'})");
+    assert_eq!(query(count_sql), r#"[{"n":2}]"#);
+    assert_eq!(stats(), (2, 2, 1));
+
+    // Volatile queries are never cached.
+    query("RETURN rand() AS r");
+    assert_eq!(stats(), (2, 2, 1));
+
+    assert_eq!(ndb_set_result_cache(db, 0, 0), NDB_OK);
+    assert_eq!(stats().2, 0);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_txn_rollback_to_savepoint_recovers_from_failed_statement() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-savepoints");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let run = |txn: *mut ndb_txn_t, cypher: &str| {
+        let cypher = CString::new(cypher).unwrap();
+        ndb_txn_query(txn, cypher.as_ptr(), ptr::null())
+    };
+    let first = CString::new("first").unwrap();
+    let missing = CString::new("missing").unwrap();
+
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert_eq!(run(txn, "CREATE (:Row {i: 1})"), NDB_OK);
+    assert_eq!(ndb_txn_savepoint(txn, first.as_ptr()), NDB_OK);
+    assert_eq!(run(txn, "CREATE (:Row {i: 2})"), NDB_OK);
+    assert_ne!(run(txn, "CREATE (:Row {i: 3"), NDB_OK);
+    assert_ne!(ndb_txn_rollback_to(txn, missing.as_ptr()), NDB_OK);
+    assert_eq!(ndb_txn_rollback_to(txn, first.as_ptr()), NDB_OK);
+    assert_eq!(run(txn, "CREATE (:Row {i: 4})"), NDB_OK);
+    assert_eq!(ndb_txn_commit(txn), NDB_OK);
+
+    let count_sql = CString::new("MATCH (r:Row) RETURN collect(r.i) AS i").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, count_sql.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    assert_eq!(
+        unsafe { CStr::from_ptr(json) }.to_str().unwrap(),
+        r#"[{"i":[1,4]}]"#
+    );
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_wal_tail_returns_changes_after_a_txid() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-wal-tail");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let tail = |since: u64| {
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(ndb_wal_tail(db, since, &mut result), NDB_OK);
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        ndb_result_free(result);
+        text
+    };
+
+    let create = CString::new("CREATE (:User {name: 'ada'})").unwrap();
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert_eq!(ndb_txn_query(txn, create.as_ptr(), ptr::null()), NDB_OK);
+    let mut txid = 0u64;
+    assert_eq!(ndb_txn_commit_bookmark(txn, &mut txid), NDB_OK);
+
+    let changes = tail(0);
+    assert!(changes.starts_with(r#"[{"changes":[{"#), "{changes}");
+    assert!(changes.contains(r#""label":"User","node":0,"op":"create_node""#));
+    assert!(changes.contains(r#""key":"name","node":0,"op":"set_node_property","value":"ada""#));
+    assert!(
+        changes.ends_with(&format!(r#""txid":{txid}}}]"#)),
+        "{changes}"
+    );
+    assert_eq!(tail(txid), "[]");
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_legal_hold_keeps_wal_archives_across_checkpoints() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-retention");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let retention = || {
+        let mut json: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_wal_retention(db, &mut json), NDB_OK);
+        let text = unsafe { CStr::from_ptr(json) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json);
+        text
+    };
+
+    let hold = CString::new("case-7").unwrap();
+    let mut placed: c_int = 0;
+    assert_eq!(ndb_place_legal_hold(db, hold.as_ptr(), &mut placed), NDB_OK);
+    assert_eq!(placed, 1);
+    assert_eq!(ndb_set_wal_retention(db, 60_000), NDB_OK);
+    let create = CString::new("CREATE (:Audit {n: 1})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_checkpoint(db), NDB_OK);
+    let text = retention();
+    assert!(text.contains(r#""keep_ms":60000"#), "{text}");
+    assert!(text.contains(r#""name":"case-7""#), "{text}");
+    assert!(text.contains(r#""archives":[{"archived_at_ms":"#), "{text}");
+
+    let mut released: c_int = 0;
+    assert_eq!(
+        ndb_release_legal_hold(db, hold.as_ptr(), &mut released),
+        NDB_OK
+    );
+    assert_eq!(released, 1);
+    assert_eq!(
+        ndb_release_legal_hold(db, hold.as_ptr(), &mut released),
+        NDB_OK
+    );
+    assert_eq!(released, 0);
+    assert_eq!(ndb_set_wal_retention(db, 0), NDB_OK);
+    assert_eq!(ndb_checkpoint(db), NDB_OK);
+    assert_eq!(retention(), r#"{"archives":[],"holds":[],"keep_ms":null}"#);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_auto_checkpoint_truncates_the_wal_after_resume() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-auto-checkpoint");
+    let wal_path = dir.path().join("capi-auto-checkpoint.wal");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    assert_eq!(ndb_set_auto_checkpoint(db, 0, 4096, 0), NDB_OK);
+    assert_eq!(ndb_pause_checkpoints(db), NDB_OK);
+
+    let create =
+        CString::new("UNWIND range(1, 50) AS i CREATE (:Doc {id: i, body: 'some text'})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let grown = std::fs::metadata(&wal_path).unwrap().len();
+    assert!(grown >= 4096, "{grown}");
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), grown);
+
+    assert_eq!(ndb_resume_checkpoints(db), NDB_OK);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while std::fs::metadata(&wal_path).unwrap().len() >= grown
+        && std::time::Instant::now() < deadline
+    {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert!(std::fs::metadata(&wal_path).unwrap().len() < grown);
+
+    assert_eq!(ndb_set_auto_checkpoint(db, 0, 0, 0), NDB_OK);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_write_throttle_rejects_commits_until_checkpointed() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-throttle");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create = CString::new("CREATE (:Event {n: 1})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_checkpoint(db), NDB_OK);
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    // One transaction past the checkpoint is the limit.
+    let wal_path = dir.path().join("capi-throttle.wal");
+    let backlog = std::fs::metadata(&wal_path).unwrap().len();
+    assert_eq!(ndb_set_write_throttle(db, 0, 0, backlog), NDB_OK);
+
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_ERR_BUSY
+    );
+    let mut buf = vec![0 as c_char; 256];
+    assert!(ndb_last_error_message(buf.as_mut_ptr(), buf.len()) > 0);
+    let message = unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_str()
+        .expect("error message utf8");
+    assert!(message.contains("write throttled"), "{message}");
+
+    assert_eq!(ndb_checkpoint(db), NDB_OK);
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_set_write_throttle(db, 0, 0, 0), NDB_OK);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_cold_label_tier_keeps_properties_readable() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("capi-tiers");
+    let db_path = CString::new(db_path.to_string_lossy().to_string()).expect("db path cstr");
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create = CString::new("UNWIND range(1, 20) AS i CREATE (:Ledger {n: i})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let label = CString::new("Ledger").unwrap();
+    let mut changed: c_int = 0;
+    assert_eq!(
+        ndb_set_label_tier(db, label.as_ptr(), 1, &mut changed),
+        NDB_OK
+    );
+    assert_eq!(changed, 1);
+    assert_eq!(ndb_checkpoint(db), NDB_OK);
+
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_label_tiers(db, &mut json), NDB_OK);
+    let tiers = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    ndb_string_free(json);
+    assert!(tiers.contains(r#""cold_labels":["Ledger"]"#), "{tiers}");
+    assert!(tiers.contains(r#""nodes":20"#), "{tiers}");
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    let sum = CString::new("MATCH (l:Ledger) RETURN sum(l.n) AS total").unwrap();
+    assert_eq!(
+        ndb_query(db, sum.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let rows = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+    assert!(rows.contains(r#""total":210"#), "{rows}");
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_incremental_backup_restores_through_its_base() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = CString::new(dir.path().join("src").to_string_lossy().to_string()).unwrap();
+    let backups = CString::new(dir.path().join("backups").to_string_lossy().to_string()).unwrap();
+    let restored_path = dir.path().join("restored");
+    let restored = CString::new(restored_path.to_string_lossy().to_string()).unwrap();
+
+    let write = |cypher: &str| {
+        let mut db: *mut ndb_db_t = ptr::null_mut();
+        assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+        let cypher = CString::new(cypher).unwrap();
+        let mut count = 0u32;
+        assert_eq!(
+            ndb_execute_write(db, cypher.as_ptr(), ptr::null(), &mut count),
+            NDB_OK
+        );
+        assert_eq!(ndb_close(db), NDB_OK);
+    };
+
+    write("CREATE (:User {name: 'ada'})");
+    assert_eq!(ndb_backup(db_path.as_ptr(), backups.as_ptr()), NDB_OK);
+    write("CREATE (:User {name: 'grace'})");
+
+    let mut info: *mut c_char = ptr::null_mut();
+    assert_eq!(
+        ndb_backup_incremental(db_path.as_ptr(), backups.as_ptr(), 0, &mut info),
+        NDB_OK
+    );
+    let info_json: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(info) }.to_str().unwrap()).unwrap();
+    ndb_string_free(info);
+    assert!(info_json["base_backup"].is_string(), "{info_json}");
+    let id = CString::new(info_json["id"].as_str().unwrap()).unwrap();
+
+    assert_eq!(
+        ndb_restore(backups.as_ptr(), id.as_ptr(), restored.as_ptr()),
+        NDB_OK
+    );
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(restored.as_ptr(), &mut db), NDB_OK);
+    let query = CString::new("MATCH (u:User) RETURN count(u) AS n").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    assert_eq!(
+        unsafe { CStr::from_ptr(json) }.to_str().unwrap(),
+        r#"[{"n":2}]"#
+    );
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    assert_ne!(
+        ndb_restore(backups.as_ptr(), id.as_ptr(), restored.as_ptr()),
+        NDB_OK
+    );
+
+    let backup_path = dir
+        .path()
+        .join("backups")
+        .join(info_json["id"].as_str().unwrap());
+    let backup_path = CString::new(backup_path.to_string_lossy().to_string()).unwrap();
+    let mut report: *mut c_char = ptr::null_mut();
+    assert_eq!(
+        ndb_verify_backup(backup_path.as_ptr(), 1, &mut report),
+        NDB_OK
+    );
+    let report_json: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(report) }.to_str().unwrap()).unwrap();
+    ndb_string_free(report);
+    assert_eq!(report_json["problems"], serde_json::json!([]));
+    assert_eq!(report_json["test_restored"], true);
+}
+
+#[test]
+fn capi_export_import_round_trips_graph() {
+    let dir = tempfile::tempdir().unwrap();
+    let cstr = |path: std::path::PathBuf| CString::new(path.to_string_lossy().to_string()).unwrap();
+    let source = cstr(dir.path().join("source.ndb"));
+    let copy = cstr(dir.path().join("copy.ndb"));
+    let graphml = cstr(dir.path().join("graph.graphml"));
+    let format = CString::new("graphml").unwrap();
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(source.as_ptr(), &mut db), NDB_OK);
+    let create =
+        CString::new("CREATE (:User {name: 'ada'})-[:KNOWS {since: 2020}]->(:User {name: 'bob'})")
+            .unwrap();
+    let mut count = 0;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_checkpoint(db), NDB_OK);
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    let mut stats: *mut c_char = ptr::null_mut();
+    assert_eq!(
+        ndb_export(
+            source.as_ptr(),
+            format.as_ptr(),
+            graphml.as_ptr(),
+            &mut stats
+        ),
+        NDB_OK
+    );
+    assert_eq!(
+        unsafe { CStr::from_ptr(stats) }.to_str().unwrap(),
+        r#"{"nodes":2,"relationships":1}"#
+    );
+    ndb_string_free(stats);
+    assert_eq!(
+        ndb_import(
+            copy.as_ptr(),
+            format.as_ptr(),
+            graphml.as_ptr(),
+            ptr::null_mut()
+        ),
+        NDB_OK
+    );
+
+    assert_eq!(ndb_open(copy.as_ptr(), &mut db), NDB_OK);
+    let query = CString::new(
+        "MATCH (a:User)-[r:KNOWS]->(b:User) RETURN a.name AS a, r.since AS s, b.name AS b",
+    )
+    .unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json), NDB_OK);
+    assert_eq!(
+        unsafe { CStr::from_ptr(json) }.to_str().unwrap(),
+        r#"[{"a":"ada","b":"bob","s":2020}]"#
+    );
+    ndb_string_free(json);
+    ndb_result_free(result);
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    let bad = CString::new("xml").unwrap();
+    assert_ne!(
+        ndb_export(
+            source.as_ptr(),
+            bad.as_ptr(),
+            graphml.as_ptr(),
+            ptr::null_mut()
+        ),
+        NDB_OK
+    );
+}
+
+#[test]
+fn capi_export_scrubbed_applies_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    let cstr = |path: std::path::PathBuf| CString::new(path.to_string_lossy().to_string()).unwrap();
+    let source = cstr(dir.path().join("source.ndb"));
+    let jsonl = dir.path().join("graph.jsonl");
+    let out = cstr(jsonl.clone());
+    let format = CString::new("jsonl").unwrap();
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(source.as_ptr(), &mut db), NDB_OK);
+    let create = CString::new("CREATE (:User {email: 'ada@example.com', password: 'x'})").unwrap();
+    let mut count = 0;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    let rules = CString::new(
+        r#"{"salt": "s", "hash_properties": ["User.email"], "drop_properties": ["password"],
+            "generalize_dates": "year"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        ndb_export_scrubbed(
+            source.as_ptr(),
+            format.as_ptr(),
+            out.as_ptr(),
+            rules.as_ptr(),
+            ptr::null_mut()
+        ),
+        NDB_OK
+    );
+    let text = std::fs::read_to_string(&jsonl).unwrap();
+    assert!(!text.contains("example.com") && !text.contains("password"));
+    assert!(text.contains(r#""email":""#), "{text}");
+
+    for bad in [
+        r#"{"hash_id": true}"#,
+        r#"{"generalize_dates": "week"}"#,
+        "[]",
+    ] {
+        let bad = CString::new(bad).unwrap();
+        assert_ne!(
+            ndb_export_scrubbed(
+                source.as_ptr(),
+                format.as_ptr(),
+                out.as_ptr(),
+                bad.as_ptr(),
+                ptr::null_mut()
+            ),
+            NDB_OK
+        );
+    }
+}
+
+#[test]
+fn capi_query_capture_replays_against_another_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let cstr = |path: std::path::PathBuf| CString::new(path.to_string_lossy().to_string()).unwrap();
+    let log = cstr(dir.path().join("queries.jsonl"));
+    let create = CString::new("CREATE (:User {name: 'ada'})").unwrap();
+    let query = CString::new("MATCH (u:User {name: $name}) RETURN u.name").unwrap();
+    let params = CString::new(r#"{"name": "ada"}"#).unwrap();
+
+    let mut source: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(
+        ndb_open(cstr(dir.path().join("source.ndb")).as_ptr(), &mut source),
+        NDB_OK
+    );
+    assert_eq!(ndb_set_query_capture(source, log.as_ptr(), 1), NDB_OK);
+    let mut count = 0;
+    assert_eq!(
+        ndb_execute_write(source, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(source, query.as_ptr(), params.as_ptr(), &mut result),
+        NDB_OK
+    );
+    ndb_result_free(result);
+    assert_eq!(ndb_set_query_capture(source, ptr::null(), 0), NDB_OK);
+    // Not captured: capture is off again.
+    assert_eq!(
+        ndb_execute_write(source, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(source), NDB_OK);
+
+    let mut copy: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(
+        ndb_open(cstr(dir.path().join("copy.ndb")).as_ptr(), &mut copy),
+        NDB_OK
+    );
+    let mut report: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_replay_queries(copy, log.as_ptr(), &mut report), NDB_OK);
+    let json: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(report) }.to_str().unwrap()).unwrap();
+    ndb_string_free(report);
+    let queries = json["queries"].as_array().unwrap();
+    assert_eq!(queries.len(), 2);
+    assert!(queries.iter().all(|q| q["results_match"] == true), "{json}");
+    assert_eq!(queries[1]["replayed_rows"], 1);
+    assert_eq!(json["skipped"], 0);
+
+    let missing = cstr(dir.path().join("missing.jsonl"));
+    assert_ne!(
+        ndb_replay_queries(copy, missing.as_ptr(), &mut report),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(copy), NDB_OK);
+}
+
+#[test]
+fn capi_query_trace_ids_tag_errors_and_capture() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = CString::new(dir.path().join("traced").to_string_lossy().to_string()).unwrap();
+    let log_path = dir.path().join("queries.jsonl");
+    let log = CString::new(log_path.to_string_lossy().to_string()).unwrap();
+    let create = CString::new("CREATE (:User {name: 'ada'})").unwrap();
+    let broken = CString::new("MATCH (u:User RETURN u").unwrap();
+    let read_c = |read: extern "C" fn(*mut c_char, usize) -> usize| {
+        let mut buf = vec![0 as c_char; 256];
+        read(buf.as_mut_ptr(), buf.len());
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    };
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    assert_eq!(ndb_set_query_capture(db, log.as_ptr(), 0), NDB_OK);
+    let mut count = 0;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let first = read_c(ndb_last_trace_id);
+    let (session, seq) = first.split_once('-').unwrap();
+    assert_eq!((session.len(), seq), (6, "1"));
+
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_ne!(
+        ndb_query(db, broken.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let second = read_c(ndb_last_trace_id);
+    assert_eq!(second, format!("{session}-2"));
+    let message = read_c(ndb_last_error_message);
+    assert!(
+        message.ends_with(&format!(" [trace {second}]")),
+        "{message}"
+    );
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    let logged: serde_json::Value =
+        serde_json::from_str(std::fs::read_to_string(&log_path).unwrap().trim()).unwrap();
+    assert_eq!(logged["trace"], first.as_str());
+}
+
+#[test]
+fn capi_pack_opens_read_only() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = CString::new(dir.path().join("capi-pack").to_string_lossy().to_string()).unwrap();
+    let pack_path = CString::new(
+        dir.path()
+            .join("capi.ndbpack")
+            .to_string_lossy()
+            .to_string(),
+    )
+    .unwrap();
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create = CString::new("UNWIND range(1, 20) AS i CREATE (:Item {id: i})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let mut report: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_pack(db, pack_path.as_ptr(), &mut report), NDB_OK);
+    let text = unsafe { CStr::from_ptr(report) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(text.contains("\"packed_bytes\""), "{text}");
+    ndb_string_free(report);
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    let mut packed: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open_packed(pack_path.as_ptr(), &mut packed), NDB_OK);
+    let query = CString::new("MATCH (n:Item) RETURN count(n) AS c").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(packed, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(json.contains("\"c\":20"), "{json}");
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+
+    assert_ne!(
+        ndb_execute_write(packed, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_close(packed), NDB_OK);
+}
+
+#[test]
+fn capi_read_only_handle_rejects_every_write() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = CString::new(dir.path().join("capi-ro").to_string_lossy().to_string()).unwrap();
+    let create = CString::new("CREATE (:Item {id: 1})").unwrap();
+    let add = CString::new("add").unwrap();
+    let mut count = 0u32;
+
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    assert_eq!(ndb_save_query(db, add.as_ptr(), create.as_ptr()), NDB_OK);
+    assert_eq!(ndb_close(db), NDB_OK);
+
+    let mut ro: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(
+        ndb_open_with_flags(db_path.as_ptr(), 0x4, &mut ro),
+        NDB_ERR_INVALID_ARGUMENT
+    );
+    assert_eq!(
+        ndb_open_with_flags(db_path.as_ptr(), NDB_OPEN_READ_ONLY, &mut ro),
+        NDB_OK
+    );
+
+    let query = CString::new("MATCH (n:Item) RETURN count(n) AS c").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(ro, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    ndb_result_free(result);
+
+    assert_eq!(
+        ndb_execute_write(ro, create.as_ptr(), ptr::null(), &mut count),
+        NDB_ERR_STORAGE
+    );
+    let mut buf = vec![0 as c_char; 256];
+    assert!(ndb_last_error_message(buf.as_mut_ptr(), buf.len()) > 0);
+    let message = unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_str()
+        .expect("error message utf8");
+    assert!(message.contains("read-only"), "{message}");
+    assert_eq!(
+        ndb_execute_named(ro, add.as_ptr(), ptr::null(), &mut count),
+        NDB_ERR_STORAGE
+    );
+    let mut stmt: *mut ndb_stmt_t = ptr::null_mut();
+    assert_eq!(
+        ndb_prepare_write(ro, create.as_ptr(), &mut stmt),
+        NDB_ERR_STORAGE
+    );
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(ro, &mut txn), NDB_ERR_STORAGE);
+    assert_ne!(ndb_checkpoint(ro), NDB_OK);
+    assert_eq!(ndb_close(ro), NDB_OK);
+
+    // Nothing above reached the files.
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let json = unsafe { CStr::from_ptr(json_ptr) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(json.contains("\"c\":1"), "{json}");
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_lazy_entities_return_references_read_on_demand() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = CString::new(dir.path().join("capi-lazy").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+    let create = CString::new(
+        "CREATE (:User {name: 'ada', age: 36})-[:KNOWS {since: 2001}]->(:User {name: 'bob'})",
+    )
+    .unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+
+    let rows = |db| -> serde_json::Value {
+        let query = CString::new("MATCH (a)-[r]->(b) RETURN a, r, [b] AS bs").unwrap();
+        let mut result: *mut ndb_result_t = ptr::null_mut();
+        assert_eq!(
+            ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+            NDB_OK
+        );
+        let mut json_ptr: *mut c_char = ptr::null_mut();
+        assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+        let json = unsafe { CStr::from_ptr(json_ptr) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        ndb_string_free(json_ptr);
+        ndb_result_free(result);
+        serde_json::from_str(&json).unwrap()
+    };
+    let get = |reference: &serde_json::Value, key: Option<&str>| -> serde_json::Value {
+        let reference = CString::new(reference.to_string()).unwrap();
+        let key = key.map(|k| CString::new(k).unwrap());
+        let mut out: *mut c_char = ptr::null_mut();
+        assert_eq!(
+            ndb_entity_get(
+                db,
+                reference.as_ptr(),
+                key.as_ref().map_or(ptr::null(), |k| k.as_ptr()),
+                &mut out,
+            ),
+            NDB_OK
+        );
+        let text = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        ndb_string_free(out);
+        serde_json::from_str(&text).unwrap()
+    };
+
+    assert_eq!(rows(db)[0]["a"]["type"], "node");
+    assert_eq!(ndb_set_lazy_entities(db, 1), NDB_OK);
+    let row = rows(db)[0].clone();
+    assert_eq!(row["a"]["type"], "node_id");
+    assert_eq!(row["r"]["type"], "edge_key");
+    assert_eq!(row["bs"][0]["type"], "node_id");
+
+    assert_eq!(get(&row["a"], Some("name")), "ada");
+    assert_eq!(get(&row["a"], Some("missing")), serde_json::Value::Null);
+    assert_eq!(get(&row["r"], Some("since")), 2001);
+    let whole = get(&row["a"], None);
+    assert_eq!(whole["type"], "node");
+    assert_eq!(whole["labels"][0], "User");
+    assert_eq!(whole["properties"]["age"], 36);
+    assert_eq!(get(&row["r"], None)["rel_type"], "KNOWS");
+
+    let bogus = CString::new(r#"{"type": "node", "id": 1}"#).unwrap();
+    let mut out: *mut c_char = ptr::null_mut();
+    assert_ne!(
+        ndb_entity_get(db, bogus.as_ptr(), ptr::null(), &mut out),
+        NDB_OK
+    );
+
+    assert_eq!(ndb_set_lazy_entities(db, 0), NDB_OK);
+    assert_eq!(rows(db)[0]["a"]["type"], "node");
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_triggers_run_after_commits_and_are_listed() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = CString::new(
+        dir.path()
+            .join("capi-triggers")
+            .to_string_lossy()
+            .to_string(),
+    )
+    .unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    let name = CString::new("stamp").unwrap();
+    let event = CString::new("node_created:User").unwrap();
+    let action = CString::new("MATCH (u) WHERE id(u) = $id SET u.stamped = true").unwrap();
+    assert_eq!(
+        ndb_create_trigger(db, name.as_ptr(), event.as_ptr(), action.as_ptr()),
+        NDB_OK
+    );
+    let bad_event = CString::new("node_renamed").unwrap();
+    assert_ne!(
+        ndb_create_trigger(db, name.as_ptr(), bad_event.as_ptr(), action.as_ptr()),
+        NDB_OK
+    );
+
+    let create = CString::new("CREATE (:User {name: 'ada'}), (:Bot {name: 'r2'})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let query = CString::new("MATCH (n) WHERE n.stamped RETURN n.name AS name").unwrap();
+    let mut result: *mut ndb_result_t = ptr::null_mut();
+    assert_eq!(
+        ndb_query(db, query.as_ptr(), ptr::null(), &mut result),
+        NDB_OK
+    );
+    let mut json_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_result_to_json(result, &mut json_ptr), NDB_OK);
+    let rows: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(json_ptr) }.to_str().unwrap()).unwrap();
+    ndb_string_free(json_ptr);
+    ndb_result_free(result);
+    assert_eq!(rows, serde_json::json!([{"name": "ada"}]));
+
+    let mut list_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_triggers(db, &mut list_ptr), NDB_OK);
+    let list: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(list_ptr) }.to_str().unwrap()).unwrap();
+    ndb_string_free(list_ptr);
+    assert_eq!(
+        list,
+        serde_json::json!([{
+            "name": "stamp",
+            "event": "node_created:User",
+            "cypher": "MATCH (u) WHERE id(u) = $id SET u.stamped = true",
+            "fired": 1,
+            "failures": 0,
+            "last_error": null,
+        }])
+    );
+
+    let mut dropped = 0;
+    assert_eq!(ndb_drop_trigger(db, name.as_ptr(), &mut dropped), NDB_OK);
+    assert_eq!(dropped, 1);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
+
+#[test]
+fn capi_expired_nodes_are_swept_and_counted() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path =
+        CString::new(dir.path().join("capi-expiry").to_string_lossy().to_string()).unwrap();
+    let mut db: *mut ndb_db_t = ptr::null_mut();
+    assert_eq!(ndb_open(db_path.as_ptr(), &mut db), NDB_OK);
+
+    let create =
+        CString::new("CREATE (:Session)-[:OF]->(:User), (:Session {expireAt: 0})").unwrap();
+    let mut count = 0u32;
+    assert_eq!(
+        ndb_execute_write(db, create.as_ptr(), ptr::null(), &mut count),
+        NDB_OK
+    );
+    let mut txn: *mut ndb_txn_t = ptr::null_mut();
+    assert_eq!(ndb_begin_write(db, &mut txn), NDB_OK);
+    assert_eq!(ndb_txn_set_node_expiry(txn, 0, 1_000), NDB_OK);
+    assert_eq!(ndb_txn_commit(txn), NDB_OK);
+
+    let (mut nodes, mut edges) = (0u64, 0u64);
+    assert_eq!(ndb_sweep_expired(db, &mut nodes, &mut edges), NDB_OK);
+    assert_eq!((nodes, edges), (2, 1));
+    assert_eq!(ndb_set_expiry_sweep(db, 50), NDB_OK);
+    assert_eq!(ndb_set_expiry_sweep(db, 0), NDB_OK);
+
+    let mut stats_ptr: *mut c_char = ptr::null_mut();
+    assert_eq!(ndb_expiry_stats(db, &mut stats_ptr), NDB_OK);
+    let stats: serde_json::Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(stats_ptr) }.to_str().unwrap()).unwrap();
+    ndb_string_free(stats_ptr);
+    assert_eq!(stats["nodes_purged"], 2);
+    assert_eq!(stats["edges_purged"], 1);
+    assert_eq!(ndb_close(db), NDB_OK);
+}
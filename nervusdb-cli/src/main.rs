@@ -1,5 +1,5 @@
 use clap::{Parser, Subcommand, ValueEnum};
-use nervusdb::{Db, vacuum};
+use nervusdb::{BackupManager, Db, vacuum};
 use nervusdb_api::GraphSnapshot;
 use nervusdb_query::Value as V2Value;
 use nervusdb_query::prepare;
@@ -33,6 +33,8 @@ enum V2Commands {
     Write(V2WriteArgs),
     Repl(V2ReplArgs),
     Vacuum(V2VacuumArgs),
+    VerifyBackup(V2VerifyBackupArgs),
+    Restore(V2RestoreArgs),
 }
 
 #[derive(Parser)]
@@ -95,6 +97,32 @@ struct V2VacuumArgs {
     db: PathBuf,
 }
 
+#[derive(Parser)]
+struct V2VerifyBackupArgs {
+    /// Backup directory (`<backup_dir>/<backup-id>`)
+    #[arg(long)]
+    backup: PathBuf,
+
+    /// Also restore the backup into a temporary location and open it
+    #[arg(long)]
+    test_restore: bool,
+}
+
+#[derive(Parser)]
+struct V2RestoreArgs {
+    /// Directory holding the backups
+    #[arg(long)]
+    backup_dir: PathBuf,
+
+    /// Id of the backup to restore
+    #[arg(long)]
+    id: String,
+
+    /// Database base path to restore into; must not exist yet
+    #[arg(long)]
+    db: PathBuf,
+}
+
 fn value_to_json_v2<S: GraphSnapshot>(snapshot: &S, value: &V2Value) -> serde_json::Value {
     match value {
         V2Value::NodeId(iid) => {
@@ -262,6 +290,34 @@ fn run_v2_vacuum(args: V2VacuumArgs) -> Result<(), String> {
     Ok(())
 }
 
+fn run_v2_verify_backup(args: V2VerifyBackupArgs) -> Result<(), String> {
+    let report =
+        nervusdb::verify_backup(&args.backup, args.test_restore).map_err(|e| e.to_string())?;
+    println!(
+        "{}",
+        serde_json::to_string(&report).map_err(|e| e.to_string())?
+    );
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err(format!("backup {} failed verification", report.backup_id))
+    }
+}
+
+fn run_v2_restore(args: V2RestoreArgs) -> Result<(), String> {
+    let backup = BackupManager::list_backups(&args.backup_dir)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|info| info.id.to_string() == args.id)
+        .ok_or_else(|| format!("no completed backup {}", args.id))?;
+    nervusdb::restore(&args.backup_dir, &backup, &args.db).map_err(|e| e.to_string())?;
+    println!(
+        "{}",
+        serde_json::json!({ "restored": backup.id, "db": args.db })
+    );
+    Ok(())
+}
+
 fn main() {
     let cli = Cli::parse();
     let result = match cli.command {
@@ -270,6 +326,8 @@ fn main() {
             V2Commands::Write(args) => run_v2_write(args),
             V2Commands::Repl(args) => repl::run_repl(&args.db),
             V2Commands::Vacuum(args) => run_v2_vacuum(args),
+            V2Commands::VerifyBackup(args) => run_v2_verify_backup(args),
+            V2Commands::Restore(args) => run_v2_restore(args),
         },
     };
 
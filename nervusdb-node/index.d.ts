@@ -6,6 +6,8 @@ export interface NervusErrorPayload {
   code: string
   category: ErrorCategory
   message: string
+  /** The failed query's trace ID, for query errors. */
+  traceId?: string
 }
 
 export interface NodeValue {
@@ -41,11 +43,136 @@ export type QueryValue =
 export type QueryRow = Record<string, QueryValue>
 export type QueryParams = Record<string, QueryValue>
 
+/** What `setLazyEntities(true)` returns in place of nodes and relationships. */
+export type EntityRef =
+  | { type: 'node_id'; value: number }
+  | { type: 'edge_key'; src: number; rel: number; dst: number }
+
+export type ColumnType =
+  | 'null'
+  | 'boolean'
+  | 'integer'
+  | 'float'
+  | 'string'
+  | 'datetime'
+  | 'duration'
+  | 'blob'
+  | 'list'
+  | 'map'
+  | 'node'
+  | 'relationship'
+  | 'path'
+  | 'node_id'
+  | 'external_id'
+  | 'any'
+
+export interface TypedResult {
+  columns: { name: string; type: ColumnType }[]
+  rows: QueryValue[][]
+}
+
+export interface QueryPage {
+  rows: QueryRow[]
+  nextToken?: string | null
+}
+
 export interface VectorHit {
   nodeId: number
   distance: number
 }
 
+export interface ResultCacheStats {
+  hits: number
+  misses: number
+  len: number
+}
+
+export interface TriggerInfo {
+  name: string
+  /** e.g. `node_created:User` or `relationship_deleted:KNOWS`. */
+  event: string
+  /** `null` for a Rust callback. */
+  cypher: string | null
+  fired: number
+  failures: number
+  lastError: string | null
+}
+
+export interface ExpiryStats {
+  sweeps: number
+  nodesPurged: number
+  edgesPurged: number
+  /** Sweeps that failed and deleted nothing. */
+  failures: number
+}
+
+export interface ReplayedQuery {
+  /** 1-based line in the capture log. */
+  line: number
+  cypher: string
+  capturedRows: number
+  replayedRows: number
+  resultsMatch: boolean
+  error: string | null
+  capturedMicros: number
+  replayedMicros: number
+}
+
+export interface ReplayReport {
+  queries: ReplayedQuery[]
+  /** Entries captured without the parameter values they need. */
+  skipped: number
+}
+
+export interface WalChange {
+  op:
+    | 'create_node'
+    | 'delete_node'
+    | 'add_label'
+    | 'remove_label'
+    | 'create_edge'
+    | 'delete_edge'
+    | 'set_node_property'
+    | 'remove_node_property'
+    | 'set_edge_property'
+    | 'remove_edge_property'
+    | 'rename_label'
+  [field: string]: unknown
+}
+
+export interface WalTxn {
+  txid: number
+  changes: WalChange[]
+}
+
+export interface AutoCheckpointOptions {
+  intervalMs?: number
+  walBytes?: number
+  ioBytesPerSec?: number
+}
+
+export interface OpenOptions {
+  readOnly?: boolean
+}
+
+export interface WriteThrottleOptions {
+  delayWalBytes?: number
+  delayMs?: number
+  rejectWalBytes?: number
+}
+
+export interface QueryRequest {
+  cypher: string
+  params?: QueryParams | null
+}
+
+export interface VectorSearchOptions {
+  metric?: 'l2' | 'cosine' | 'dot'
+  label?: string
+  property?: string
+  value?: unknown
+}
+
 export interface VacuumReport {
   ndbPath: string
   backupPath: string
@@ -64,6 +191,109 @@ export interface BackupInfo {
   nervusdbVersion: string
   checkpointTxid: number
   checkpointEpoch: number
+  baseBackup?: string | null
+}
+
+export interface BackupVerification {
+  backupId: string
+  filesChecked: number
+  unverified: string[]
+  testRestored: boolean
+  problems: string[]
+}
+
+export type GraphFormat = 'graphml' | 'jsonl'
+
+export interface ScrubOptions {
+  salt?: string
+  hashIds?: boolean
+  hashProperties?: string[]
+  dropProperties?: string[]
+  generalizeDates?: 'year' | 'month' | 'day'
+}
+
+export interface TransferStats {
+  nodes: number
+  relationships: number
+}
+
+export interface IndexInfo {
+  name: string
+  kind: 'btree' | 'text' | 'composite'
+  label: string
+  properties: string[]
+  unique: boolean
+}
+
+export interface PackReport {
+  pages: number
+  uncompressedBytes: number
+  packedBytes: number
+}
+
+export interface Schema {
+  labels: string[]
+  relTypes: string[]
+  propertyKeys: string[]
+  indexes: IndexInfo[]
+}
+
+export interface DegreeHistogram {
+  /** `buckets[b]` counts the nodes with a degree in `[2^b, 2^(b+1))`. */
+  buckets: number[]
+  maxDegree: number
+  maxNode: number
+}
+
+export interface RelTypeDegrees {
+  relType: string
+  relationships: number
+  outgoing: DegreeHistogram
+  incoming: DegreeHistogram
+}
+
+export interface WalRetention {
+  /** How long checkpointed WAL history is kept, or null when unset. */
+  keepMs: number | null
+  holds: Array<{ name: string; placedAtMs: number }>
+  archives: Array<{ seq: number; bytes: number; archivedAtMs: number }>
+}
+
+export interface LabelTiers {
+  /** Labels whose nodes keep their properties in the cold tier. */
+  coldLabels: string[]
+  nodes: number
+  fileBytes: number
+  liveBytes: number
+}
+
+export interface EntityUsage {
+  name: string
+  count: number
+  properties: number
+  propertyBytes: number
+  bytesPerEntity: number
+}
+
+export interface CapacityReport {
+  fileBytes: number
+  walBytes: number
+  nodes: number
+  edges: number
+  properties: number
+  estimatedBytes: number
+  labels: EntityUsage[]
+  relTypes: EntityUsage[]
+  growth: {
+    samples: number
+    spanDays: number
+    nodesPerDay: number
+    edgesPerDay: number
+    fileBytesPerDay: number
+  } | null
+  projections: { days: number; nodes: number; edges: number; fileBytes: number }[]
+  nodeIdHeadroom: number
+  daysToNodeIdLimit: number | null
 }
 
 export interface BulkNodeInput {
@@ -80,28 +310,170 @@ export interface BulkEdgeInput {
 }
 
 export class Db {
-  static open(path: string): Db
-  static openPaths(ndbPath: string, walPath: string): Db
+  static open(path: string, options?: OpenOptions): Db
+  static openPaths(ndbPath: string, walPath: string, options?: OpenOptions): Db
+  static openPacked(path: string): Db
 
   readonly path: string
   readonly ndbPath: string
   readonly walPath: string
 
   query(cypher: string, params?: QueryParams): QueryRow[]
+  queryMany(queries: QueryRequest[]): QueryRow[][]
+  queryCount(cypher: string, params?: QueryParams): number
+  queryResult(cypher: string, params?: QueryParams): QueryResult
+  queryTyped(cypher: string, params?: QueryParams): TypedResult
+  queryPage(cypher: string, pageSize: number, token?: string | null, params?: QueryParams): QueryPage
   executeWrite(cypher: string, params?: QueryParams): number
 
+  saveQuery(name: string, cypher: string): void
+  dropQuery(name: string): boolean
+  queryNamed(name: string, params?: QueryParams): QueryRow[]
+  executeNamed(name: string, params?: QueryParams): number
+  createTrigger(name: string, event: string, cypher: string): void
+  dropTrigger(name: string): boolean
+  triggers(): TriggerInfo[]
+
   beginWrite(): WriteTxn
 
   compact(): void
   checkpoint(): void
+  pack(outPath: string): PackReport
+  setAutoCheckpoint(options?: AutoCheckpointOptions): void
+  pauseCheckpoints(): void
+  resumeCheckpoints(): void
+  setWriteThrottle(options?: WriteThrottleOptions): void
+  setExpirySweep(intervalMs?: number): void
+  sweepExpired(): { nodes: number; edges: number }
+  expiryStats(): ExpiryStats
+  lastTraceId(): string
+  lastBookmark(): number
+  waitForBookmark(bookmark: number, timeoutMs?: number): void
   createIndex(label: string, property: string): void
+  createTextIndex(label: string, property: string): void
+  createCompositeIndex(label: string, properties: string[]): void
+  dropIndex(label: string, properties: string[]): boolean
+  createUniqueConstraint(label: string, property: string): void
+  dropUniqueConstraint(label: string, property: string): boolean
+  createEndpointConstraint(relType: string, endpoints: Array<[string, string]>): void
+  dropEndpointConstraint(relType: string): boolean
+  renameProperty(label: string, old: string, new: string): number
+  renameLabel(old: string, new: string): boolean
+  markSensitive(label: string, property: string): boolean
+  unmarkSensitive(label: string, property: string): boolean
+  setRedaction(enabled: boolean): void
+  setDeterministic(enabled: boolean): void
+  setLazyEntities(enabled: boolean): void
+  entity(reference: EntityRef, key?: string | null): QueryValue
+  setLocale(locale?: string | null): void
+  preload(labels: string[]): number
+  preloadPages(first: number, count: number): number
+  setLabelTier(label: string, cold?: boolean): boolean
+  labelTiers(): LabelTiers
+  setNamespace(prefix: string, iri?: string | null): boolean
+  namespaces(): Record<string, string>
+  edgesChunked(relType?: string | null, chunkSize?: number, weightProperty?: string | null): EdgeCursor
+  walTail(sinceTxid?: number): WalTxn[]
+  setWalRetention(keepMs: number): void
+  placeLegalHold(name: string): boolean
+  releaseLegalHold(name: string): boolean
+  walRetention(): WalRetention
+  setResultCache(capacity: number, ttlMs?: number): void
+  resultCacheStats(): ResultCacheStats
+  setQueryCapture(path?: string | null, includeParams?: boolean): void
+  replayQueries(logPath: string): ReplayReport
+  schema(): Schema
+  degreeStats(): RelTypeDegrees[]
   searchVector(query: number[], k: number): VectorHit[]
+  searchVectorExact(query: number[], k: number): VectorHit[]
+  searchVectorFiltered(query: number[], k: number, options?: VectorSearchOptions): VectorHit[]
+
+  close(): void
+}
+
+/**
+ * Writes go to the primary; reads go to a replica that has applied this
+ * router's last write, or to the primary when none has.
+ */
+export class Router {
+  static open(primary: string, replicas: string[]): Router
+  readonly bookmark: number
+  query(cypher: string, params?: QueryParams): QueryRow[]
+  executeWrite(cypher: string, params?: QueryParams): number
+  close(): void
+}
 
+export interface EdgeChunk {
+  src: BigUint64Array
+  dst: BigUint64Array
+  weight: Float64Array
+}
+
+export class EdgeCursor {
+  next(): EdgeChunk | null
   close(): void
 }
 
+/** Rows from `Db.queryResult`; `key` is a column name or position. */
+export class QueryResult {
+  readonly keys: string[]
+  readonly length: number
+  rows(): QueryRow[]
+  get(index: number, key: string | number): QueryValue
+  /** Throws if the value is neither a node nor null. */
+  getNode(index: number, key: string | number): NodeValue | null
+  getRelationship(index: number, key: string | number): RelationshipValue | null
+  getPath(index: number, key: string | number): PathValue | null
+  /** Rows as neo4j-driver style records, for migrating Neo4j code. */
+  records(): Neo4jRecord[]
+}
+
+/** Node layout of the neo4j driver's `Node`, with plain numbers for ids. */
+export interface Neo4jNode {
+  identity: number
+  elementId: string
+  labels: string[]
+  properties: Record<string, unknown>
+}
+
+/** Relationships have no id of their own, so `identity` is always null. */
+export interface Neo4jRelationship {
+  identity: null
+  elementId: string
+  start: number
+  end: number
+  startNodeElementId: string
+  endNodeElementId: string
+  type: string
+  properties: Record<string, unknown>
+}
+
+export interface Neo4jPathSegment {
+  start: Neo4jNode
+  relationship: Neo4jRelationship
+  end: Neo4jNode
+}
+
+export interface Neo4jPath {
+  start: Neo4jNode
+  end: Neo4jNode
+  segments: Neo4jPathSegment[]
+  length: number
+}
+
+/** A row shaped like the neo4j driver's `Record`. */
+export class Neo4jRecord {
+  readonly keys: string[]
+  readonly length: number
+  get(key: string | number): any
+  has(key: string | number): boolean
+  values(): any[]
+  entries(): [string, any][]
+  toObject(): { [key: string]: any }
+}
+
 export class WriteTxn {
-  query(cypher: string, params?: QueryParams): void
+  query(cypher: string, params?: QueryParams): QueryRow[]
 
   createNode(external_id: number, label_id: number): number
   getOrCreateLabel(name: string): number
@@ -110,15 +482,27 @@ export class WriteTxn {
   tombstoneNode(node: number): void
   tombstoneEdge(src: number, rel: number, dst: number): void
   setNodeProperty(node: number, key: string, value: QueryValue): void
+  setNodeExpiry(node: number, atMs: number): void
   setEdgeProperty(src: number, rel: number, dst: number, key: string, value: QueryValue): void
   removeNodeProperty(node: number, key: string): void
   removeEdgeProperty(src: number, rel: number, dst: number, key: string): void
   setVector(node: number, vector: number[]): void
+  setIdempotencyKey(key: string): boolean
+  setConstraintsDeferred(deferred: boolean): void
+  savepoint(name: string): void
+  rollbackTo(name: string): void
 
   commit(): number
   rollback(): void
 }
 
+export function setPlanCacheCapacity(capacity: number): void
 export function vacuum(path: string): VacuumReport
 export function backup(path: string, backupDir: string): BackupInfo
+export function backupIncremental(path: string, backupDir: string, sinceTxid: number): BackupInfo
+export function restore(backupDir: string, backupId: string, path: string): void
+export function verifyBackup(backupPath: string, testRestore?: boolean): BackupVerification
+export function capacityReport(path: string): CapacityReport
 export function bulkload(path: string, nodes: BulkNodeInput[], edges: BulkEdgeInput[]): void
+export function exportGraph(path: string, format: GraphFormat, outPath: string, scrub?: ScrubOptions): TransferStats
+export function importGraph(path: string, format: GraphFormat, inPath: string): TransferStats
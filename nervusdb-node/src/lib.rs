@@ -1,14 +1,14 @@
-use napi::bindgen_prelude::Result;
+use napi::bindgen_prelude::{BigUint64Array, Either, Float64Array, Result};
 use napi::Error;
 use napi_derive::napi;
 use nervusdb_capi as capi;
 use serde_json::{json, Value as JsonValue};
-use std::ffi::{c_char, CStr, CString};
+use std::ffi::{c_char, c_int, CStr, CString};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::ptr;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, RwLock};
 
 fn error_payload(code: &str, category: &str, message: impl ToString) -> String {
     json!({
@@ -98,11 +98,33 @@ fn map_error_payload(category: i32, message: &str) -> (&'static str, &'static st
     }
 }
 
+fn read_last_trace_id() -> String {
+    let mut buf = vec![0 as c_char; 64];
+    let _ = capi::ndb_last_trace_id(buf.as_mut_ptr(), buf.len());
+    unsafe {
+        // SAFETY: C API guarantees null-terminated output when len > 0.
+        CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
+    }
+}
+
 fn napi_last_error() -> Error {
     let category = capi::ndb_last_error_category();
     let message = read_last_error_message();
     let (code, category) = map_error_payload(category, &message);
-    Error::from_reason(error_payload(code, category, message))
+    // Query errors end with the query's trace ID; expose it as `traceId`.
+    let trace_id = message
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once(" [trace "))
+        .map(|(_, trace)| trace.to_string());
+    let mut payload = json!({
+        "code": code,
+        "category": category,
+        "message": message,
+    });
+    if let Some(trace_id) = trace_id {
+        payload["traceId"] = trace_id.into();
+    }
+    Error::from_reason(payload.to_string())
 }
 
 fn capi_status(rc: i32) -> Result<()> {
@@ -113,6 +135,32 @@ fn capi_status(rc: i32) -> Result<()> {
     }
 }
 
+/// JS has no integer type and napi hands integral numbers above `u32::MAX`
+/// over as floats. Send every integral number in the safe-integer range as an
+/// integer so Cypher sees the same types as the other bindings.
+fn integralize_js_numbers(value: JsonValue) -> JsonValue {
+    const MAX_SAFE_INT: f64 = 9_007_199_254_740_991.0; // 2^53 - 1
+    match value {
+        JsonValue::Number(n) if !n.is_i64() && !n.is_u64() => match n.as_f64() {
+            Some(f) if f.trunc() == f && f.abs() <= MAX_SAFE_INT => JsonValue::from(f as i64),
+            _ => JsonValue::Number(n),
+        },
+        JsonValue::Array(items) => {
+            JsonValue::Array(items.into_iter().map(integralize_js_numbers).collect())
+        }
+        JsonValue::Object(map) => JsonValue::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, integralize_js_numbers(v)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+fn encode_json_value(value: JsonValue) -> Result<String> {
+    serde_json::to_string(&integralize_js_numbers(value)).map_err(napi_err)
+}
+
 fn encode_params(params: Option<JsonValue>) -> Result<Option<CString>> {
     let Some(params) = params else {
         return Ok(None);
@@ -120,10 +168,18 @@ fn encode_params(params: Option<JsonValue>) -> Result<Option<CString>> {
     if !params.is_object() {
         return Err(napi_err("params must be an object"));
     }
-    let encoded = serde_json::to_string(&params).map_err(napi_err)?;
+    let encoded = encode_json_value(params)?;
     Ok(Some(to_cstring(&encoded, "params")?))
 }
 
+/// How long `waitForBookmark` blocks when no timeout is given.
+const DEFAULT_BOOKMARK_TIMEOUT_MS: u32 = 5_000;
+
+fn encode_property_list(properties: &[String]) -> Result<CString> {
+    let encoded = serde_json::to_string(properties).map_err(napi_err)?;
+    to_cstring(&encoded, "properties")
+}
+
 fn parse_json_array(json_text: &str) -> Result<Vec<JsonValue>> {
     let value: JsonValue = serde_json::from_str(json_text).map_err(napi_err)?;
     let arr = value
@@ -133,6 +189,10 @@ fn parse_json_array(json_text: &str) -> Result<Vec<JsonValue>> {
 }
 
 fn result_to_json_rows(result_ptr: *mut capi::ndb_result_t) -> Result<Vec<JsonValue>> {
+    parse_json_array(&result_to_json_text(result_ptr)?)
+}
+
+fn result_to_json_text(result_ptr: *mut capi::ndb_result_t) -> Result<String> {
     let mut json_ptr: *mut c_char = ptr::null_mut();
     let rc = capi::ndb_result_to_json(result_ptr, &mut json_ptr);
     let _ = capi::ndb_result_free(result_ptr);
@@ -146,7 +206,7 @@ fn result_to_json_rows(result_ptr: *mut capi::ndb_result_t) -> Result<Vec<JsonVa
         CStr::from_ptr(json_ptr).to_string_lossy().into_owned()
     };
     capi::ndb_string_free(json_ptr);
-    parse_json_array(&json_text)
+    Ok(json_text)
 }
 
 #[napi(object)]
@@ -164,9 +224,74 @@ pub struct BulkEdgeInput {
     pub properties: Option<JsonValue>,
 }
 
+/// One batch of edges from `EdgeCursor.next`: external source and target
+/// ids with their weights, as parallel typed arrays.
+#[napi(object)]
+pub struct EdgeChunk {
+    pub src: BigUint64Array,
+    pub dst: BigUint64Array,
+    pub weight: Float64Array,
+}
+
+/// One read query of a `Db.queryMany` batch.
+#[napi(object)]
+pub struct QueryRequest {
+    pub cypher: String,
+    pub params: Option<JsonValue>,
+}
+
+#[napi(object)]
+pub struct QueryPage {
+    pub rows: Vec<JsonValue>,
+    pub next_token: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct AutoCheckpointOptions {
+    pub interval_ms: Option<i64>,
+    pub wal_bytes: Option<i64>,
+    pub io_bytes_per_sec: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct OpenOptions {
+    pub read_only: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct WriteThrottleOptions {
+    pub delay_wal_bytes: Option<i64>,
+    pub delay_ms: Option<i64>,
+    pub reject_wal_bytes: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ScrubOptions {
+    pub salt: Option<String>,
+    pub hash_ids: Option<bool>,
+    pub hash_properties: Option<Vec<String>>,
+    pub drop_properties: Option<Vec<String>>,
+    pub generalize_dates: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct VectorSearchOptions {
+    pub metric: Option<String>,
+    pub label: Option<String>,
+    pub property: Option<String>,
+    pub value: Option<JsonValue>,
+}
+
 #[napi]
 pub struct Db {
-    raw: Arc<Mutex<Option<*mut capi::ndb_db_t>>>,
+    /// Calls share the handle; only `close` takes it exclusively. Reads run on
+    /// their own snapshots, so they never wait on each other or on commits.
+    raw: Arc<RwLock<Option<*mut capi::ndb_db_t>>>,
     path: String,
     ndb_path: String,
     wal_path: String,
@@ -177,8 +302,8 @@ impl Db {
     fn with_db_ptr<T>(&self, f: impl FnOnce(*mut capi::ndb_db_t) -> Result<T>) -> Result<T> {
         let guard = self
             .raw
-            .lock()
-            .map_err(|_| napi_err("database mutex poisoned"))?;
+            .read()
+            .map_err(|_| napi_err("database lock poisoned"))?;
         let raw = guard
             .as_ref()
             .copied()
@@ -186,189 +311,1484 @@ impl Db {
         f(raw)
     }
 
-    fn make_open(ndb_path: String, wal_path: String, logical_path: String) -> Result<Self> {
+    fn set_sensitive(&self, label: &str, property: &str, sensitive: bool) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(label, "label")?;
+            let property_c = to_cstring(property, "property")?;
+            let mut changed: c_int = 0;
+            capi_status(capi::ndb_set_sensitive(
+                raw,
+                label_c.as_ptr(),
+                property_c.as_ptr(),
+                c_int::from(sensitive),
+                &mut changed,
+            ))?;
+            Ok(changed != 0)
+        })
+    }
+
+    fn make_open(
+        ndb_path: String,
+        wal_path: String,
+        logical_path: String,
+        options: Option<OpenOptions>,
+    ) -> Result<Self> {
         let ndb_c = to_cstring(&ndb_path, "ndb_path")?;
         let wal_c = to_cstring(&wal_path, "wal_path")?;
+        let options = options.unwrap_or_default();
+        let flags = if options.read_only.unwrap_or(false) {
+            capi::NDB_OPEN_READ_ONLY
+        } else {
+            0
+        };
         let mut raw: *mut capi::ndb_db_t = ptr::null_mut();
-        capi_status(capi::ndb_open_paths(
+        capi_status(capi::ndb_open_paths_with_flags(
             ndb_c.as_ptr(),
             wal_c.as_ptr(),
+            flags,
             &mut raw,
         ))?;
         if raw.is_null() {
-            return Err(napi_err("ndb_open_paths returned null db handle"));
+            return Err(napi_err(
+                "ndb_open_paths_with_flags returned null db handle",
+            ));
         }
 
         Ok(Self {
-            raw: Arc::new(Mutex::new(Some(raw))),
+            raw: Arc::new(RwLock::new(Some(raw))),
             path: logical_path,
             ndb_path,
             wal_path,
             active_write_txns: Arc::new(AtomicU64::new(0)),
         })
     }
+
+    /// Runs one of the `ndb_search_vector*` calls on `query` and decodes its hits.
+    fn vector_hits(
+        &self,
+        query: Vec<f64>,
+        search: impl FnOnce(*mut capi::ndb_db_t, &[f32], &mut *mut capi::ndb_result_t) -> c_int,
+    ) -> Result<Vec<JsonValue>> {
+        self.with_db_ptr(|raw| {
+            let query_f32: Vec<f32> = query.into_iter().map(|v| v as f32).collect();
+            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+            capi_status(search(raw, &query_f32, &mut result_ptr))?;
+            if result_ptr.is_null() {
+                return Err(napi_err("vector search returned null result"));
+            }
+            let rows = result_to_json_rows(result_ptr)?;
+            Ok(rows
+                .into_iter()
+                .map(|r| {
+                    let node_id = r.get("node_id").cloned().unwrap_or(JsonValue::Null);
+                    let distance = r.get("distance").cloned().unwrap_or(JsonValue::Null);
+                    json!({"nodeId": node_id, "distance": distance})
+                })
+                .collect())
+        })
+    }
+}
+
+#[napi]
+impl Db {
+    /// Opens the database at `path`. With `readOnly`, the handle refuses
+    /// every write, including checkpoints and schema changes; the database
+    /// must already exist, and its files are left untouched.
+    #[napi(factory)]
+    pub fn open(path: String, options: Option<OpenOptions>) -> Result<Self> {
+        let (ndb_path, wal_path) = derive_paths(Path::new(&path));
+        Self::make_open(
+            ndb_path.to_string_lossy().to_string(),
+            wal_path.to_string_lossy().to_string(),
+            path,
+            options,
+        )
+    }
+
+    #[napi(factory, js_name = "openPaths")]
+    pub fn open_paths_factory(
+        ndb_path: String,
+        wal_path: String,
+        options: Option<OpenOptions>,
+    ) -> Result<Self> {
+        let logical_path = ndb_path.clone();
+        Self::make_open(ndb_path, wal_path, logical_path, options)
+    }
+
+    /// Opens a packed image written by `pack`, read-only.
+    #[napi(factory, js_name = "openPacked")]
+    pub fn open_packed(path: String) -> Result<Self> {
+        let path_c = to_cstring(&path, "path")?;
+        let mut raw: *mut capi::ndb_db_t = ptr::null_mut();
+        capi_status(capi::ndb_open_packed(path_c.as_ptr(), &mut raw))?;
+        if raw.is_null() {
+            return Err(napi_err("ndb_open_packed returned null db handle"));
+        }
+
+        Ok(Self {
+            raw: Arc::new(RwLock::new(Some(raw))),
+            path: path.clone(),
+            ndb_path: path.clone(),
+            wal_path: path,
+            active_write_txns: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    #[napi(getter, js_name = "ndbPath")]
+    pub fn ndb_path(&self) -> String {
+        self.ndb_path.clone()
+    }
+
+    #[napi(getter, js_name = "walPath")]
+    pub fn wal_path(&self) -> String {
+        self.wal_path.clone()
+    }
+
+    #[napi]
+    pub fn query(&self, cypher: String, params: Option<JsonValue>) -> Result<Vec<JsonValue>> {
+        self.with_db_ptr(|raw| {
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+            capi_status(capi::ndb_query(
+                raw,
+                cypher_c.as_ptr(),
+                params_ptr,
+                &mut result_ptr,
+            ))?;
+            if result_ptr.is_null() {
+                return Err(napi_err("ndb_query returned null result handle"));
+            }
+            result_to_json_rows(result_ptr)
+        })
+    }
+
+    /// Runs read queries against one pinned snapshot, so all results see the
+    /// same commits, and returns their rows in order.
+    #[napi(js_name = "queryMany")]
+    pub fn query_many(&self, queries: Vec<QueryRequest>) -> Result<Vec<Vec<JsonValue>>> {
+        self.with_db_ptr(|raw| {
+            let mut snapshot: *mut capi::ndb_snapshot_t = ptr::null_mut();
+            capi_status(capi::ndb_begin_read(raw, &mut snapshot))?;
+            let results = queries
+                .into_iter()
+                .map(|query| {
+                    let cypher_c = to_cstring(&query.cypher, "cypher")?;
+                    let params_c = encode_params(query.params)?;
+                    let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+                    let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+                    capi_status(capi::ndb_query_in_snapshot(
+                        snapshot,
+                        cypher_c.as_ptr(),
+                        params_ptr,
+                        &mut result_ptr,
+                    ))?;
+                    result_to_json_rows(result_ptr)
+                })
+                .collect();
+            capi::ndb_snapshot_release(snapshot);
+            results
+        })
+    }
+
+    #[napi(js_name = "queryCount")]
+    pub fn query_count(&self, cypher: String, params: Option<JsonValue>) -> Result<i64> {
+        self.with_db_ptr(|raw| {
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+            let mut count: u64 = 0;
+            capi_status(capi::ndb_query_count(
+                raw,
+                cypher_c.as_ptr(),
+                params_ptr,
+                &mut count,
+            ))?;
+            Ok(count as i64)
+        })
+    }
+
+    #[napi(js_name = "queryTyped")]
+    pub fn query_typed(&self, cypher: String, params: Option<JsonValue>) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+            capi_status(capi::ndb_query_typed(
+                raw,
+                cypher_c.as_ptr(),
+                params_ptr,
+                &mut result_ptr,
+            ))?;
+            serde_json::from_str(&result_to_json_text(result_ptr)?).map_err(napi_err)
+        })
+    }
+
+    /// Runs a read query and wraps its rows in a `QueryResult` with typed
+    /// accessors.
+    #[napi(js_name = "queryResult")]
+    pub fn query_result(&self, cypher: String, params: Option<JsonValue>) -> Result<QueryResult> {
+        QueryResult::from_typed(self.query_typed(cypher, params)?)
+    }
+
+    #[napi(js_name = "queryPage")]
+    pub fn query_page(
+        &self,
+        cypher: String,
+        page_size: u32,
+        token: Option<String>,
+        params: Option<JsonValue>,
+    ) -> Result<QueryPage> {
+        self.with_db_ptr(|raw| {
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            let token_c = token
+                .as_deref()
+                .map(|t| to_cstring(t, "token"))
+                .transpose()?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+            let mut next_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_query_page(
+                raw,
+                cypher_c.as_ptr(),
+                params_ptr,
+                page_size,
+                token_c.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                &mut result_ptr,
+                &mut next_ptr,
+            ))?;
+            let next_token = (!next_ptr.is_null()).then(|| {
+                let text = unsafe {
+                    // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+                    CStr::from_ptr(next_ptr).to_string_lossy().into_owned()
+                };
+                capi::ndb_string_free(next_ptr);
+                text
+            });
+            Ok(QueryPage {
+                rows: result_to_json_rows(result_ptr)?,
+                next_token,
+            })
+        })
+    }
+
+    #[napi]
+    pub fn execute_write(&self, cypher: String, params: Option<JsonValue>) -> Result<u32> {
+        self.with_db_ptr(|raw| {
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut affected: u32 = 0;
+            capi_status(capi::ndb_execute_write(
+                raw,
+                cypher_c.as_ptr(),
+                params_ptr,
+                &mut affected,
+            ))?;
+            Ok(affected)
+        })
+    }
+
+    #[napi(js_name = "saveQuery")]
+    pub fn save_query(&self, name: String, cypher: String) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            capi_status(capi::ndb_save_query(
+                raw,
+                name_c.as_ptr(),
+                cypher_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "dropQuery")]
+    pub fn drop_query(&self, name: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let mut dropped: c_int = 0;
+            capi_status(capi::ndb_drop_query(raw, name_c.as_ptr(), &mut dropped))?;
+            Ok(dropped != 0)
+        })
+    }
+
+    #[napi(js_name = "queryNamed")]
+    pub fn query_named(&self, name: String, params: Option<JsonValue>) -> Result<Vec<JsonValue>> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+            capi_status(capi::ndb_query_named(
+                raw,
+                name_c.as_ptr(),
+                params_ptr,
+                &mut result_ptr,
+            ))?;
+            if result_ptr.is_null() {
+                return Err(napi_err("ndb_query_named returned null result handle"));
+            }
+            result_to_json_rows(result_ptr)
+        })
+    }
+
+    #[napi(js_name = "executeNamed")]
+    pub fn execute_named(&self, name: String, params: Option<JsonValue>) -> Result<u32> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let params_c = encode_params(params)?;
+            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+            let mut affected: u32 = 0;
+            capi_status(capi::ndb_execute_named(
+                raw,
+                name_c.as_ptr(),
+                params_ptr,
+                &mut affected,
+            ))?;
+            Ok(affected)
+        })
+    }
+
+    /// Runs the write statement `cypher` after each commit through this
+    /// handle whose changes match `event`, e.g. `"node_created:User"`.
+    #[napi(js_name = "createTrigger")]
+    pub fn create_trigger(&self, name: String, event: String, cypher: String) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let event_c = to_cstring(&event, "event")?;
+            let cypher_c = to_cstring(&cypher, "cypher")?;
+            capi_status(capi::ndb_create_trigger(
+                raw,
+                name_c.as_ptr(),
+                event_c.as_ptr(),
+                cypher_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "dropTrigger")]
+    pub fn drop_trigger(&self, name: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let mut dropped: c_int = 0;
+            capi_status(capi::ndb_drop_trigger(raw, name_c.as_ptr(), &mut dropped))?;
+            Ok(dropped != 0)
+        })
+    }
+
+    #[napi]
+    pub fn triggers(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut triggers_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_triggers(raw, &mut triggers_ptr))?;
+            transfer_stats(triggers_ptr, "ndb_triggers").map(camel_case_keys)
+        })
+    }
+
+    #[napi]
+    pub fn begin_write(&self) -> Result<WriteTxn> {
+        self.with_db_ptr(|raw| {
+            let mut txn_raw: *mut capi::ndb_txn_t = ptr::null_mut();
+            self.active_write_txns.fetch_add(1, Ordering::SeqCst);
+            if let Err(err) = capi_status(capi::ndb_begin_write(raw, &mut txn_raw)) {
+                self.active_write_txns.fetch_sub(1, Ordering::SeqCst);
+                return Err(err);
+            }
+            if txn_raw.is_null() {
+                self.active_write_txns.fetch_sub(1, Ordering::SeqCst);
+                return Err(napi_err("ndb_begin_write returned null transaction"));
+            }
+            Ok(WriteTxn {
+                raw: Some(txn_raw),
+                active_write_txns: self.active_write_txns.clone(),
+                affected: 0,
+                finished: false,
+            })
+        })
+    }
+
+    /// Streams every edge, or only those of type `rel_type`, in chunks of up
+    /// to `chunk_size` (default 65536). Weights come from the numeric edge
+    /// property `weight_property`, defaulting to 1.0.
+    #[napi(js_name = "edgesChunked")]
+    pub fn edges_chunked(
+        &self,
+        rel_type: Option<String>,
+        chunk_size: Option<u32>,
+        weight_property: Option<String>,
+    ) -> Result<EdgeCursor> {
+        let rel_type_c = rel_type.map(|r| to_cstring(&r, "relType")).transpose()?;
+        let weight_c = weight_property
+            .map(|w| to_cstring(&w, "weightProperty"))
+            .transpose()?;
+        self.with_db_ptr(|raw| {
+            let mut edges: *mut capi::ndb_edges_t = ptr::null_mut();
+            capi_status(capi::ndb_edges_open(
+                raw,
+                rel_type_c.as_ref().map_or(ptr::null(), |r| r.as_ptr()),
+                weight_c.as_ref().map_or(ptr::null(), |w| w.as_ptr()),
+                &mut edges,
+            ))?;
+            Ok(EdgeCursor {
+                raw: Some(edges),
+                chunk_size: chunk_size.unwrap_or(65536).max(1) as usize,
+            })
+        })
+    }
+
+    #[napi]
+    pub fn compact(&self) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_compact(raw)))
+    }
+
+    #[napi]
+    pub fn checkpoint(&self) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_checkpoint(raw)))
+    }
+
+    /// Checkpoints, then writes a compressed, read-only packed image to
+    /// `out_path` for `Db.openPacked`.
+    #[napi]
+    pub fn pack(&self, out_path: String) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let out_c = to_cstring(&out_path, "out_path")?;
+            let mut report_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_pack(raw, out_c.as_ptr(), &mut report_ptr))?;
+            transfer_stats(report_ptr, "ndb_pack").map(camel_case_keys)
+        })
+    }
+
+    #[napi(js_name = "setAutoCheckpoint")]
+    pub fn set_auto_checkpoint(&self, options: Option<AutoCheckpointOptions>) -> Result<()> {
+        let options = options.unwrap_or_default();
+        let to_u64 = |v: Option<i64>| u64::try_from(v.unwrap_or(0)).map_err(napi_err);
+        let (interval_ms, wal_bytes, io_bytes_per_sec) = (
+            to_u64(options.interval_ms)?,
+            to_u64(options.wal_bytes)?,
+            to_u64(options.io_bytes_per_sec)?,
+        );
+        self.with_db_ptr(|raw| {
+            capi_status(capi::ndb_set_auto_checkpoint(
+                raw,
+                interval_ms,
+                wal_bytes,
+                io_bytes_per_sec,
+            ))
+        })
+    }
+
+    #[napi(js_name = "pauseCheckpoints")]
+    pub fn pause_checkpoints(&self) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_pause_checkpoints(raw)))
+    }
+
+    #[napi(js_name = "resumeCheckpoints")]
+    pub fn resume_checkpoints(&self) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_resume_checkpoints(raw)))
+    }
+
+    /// Slows writers down while the WAL is backlogged; without options
+    /// throttling is turned off. Throttled commits throw a busy error.
+    #[napi(js_name = "setWriteThrottle")]
+    pub fn set_write_throttle(&self, options: Option<WriteThrottleOptions>) -> Result<()> {
+        let options = options.unwrap_or_default();
+        let to_u64 = |v: Option<i64>| u64::try_from(v.unwrap_or(0)).map_err(napi_err);
+        let (delay_wal_bytes, delay_ms, reject_wal_bytes) = (
+            to_u64(options.delay_wal_bytes)?,
+            to_u64(options.delay_ms)?,
+            to_u64(options.reject_wal_bytes)?,
+        );
+        self.with_db_ptr(|raw| {
+            capi_status(capi::ndb_set_write_throttle(
+                raw,
+                delay_wal_bytes,
+                delay_ms,
+                reject_wal_bytes,
+            ))
+        })
+    }
+
+    /// Deletes expired nodes, with their relationships, and expired
+    /// relationships every `intervalMs` on a background thread; 0 or no
+    /// interval stops it.
+    #[napi(js_name = "setExpirySweep")]
+    pub fn set_expiry_sweep(&self, interval_ms: Option<i64>) -> Result<()> {
+        let interval_ms = u64::try_from(interval_ms.unwrap_or(0)).map_err(napi_err)?;
+        self.with_db_ptr(|raw| capi_status(capi::ndb_set_expiry_sweep(raw, interval_ms)))
+    }
+
+    #[napi(js_name = "sweepExpired")]
+    pub fn sweep_expired(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let (mut nodes, mut edges) = (0u64, 0u64);
+            capi_status(capi::ndb_sweep_expired(raw, &mut nodes, &mut edges))?;
+            Ok(json!({ "nodes": nodes, "edges": edges }))
+        })
+    }
+
+    #[napi(js_name = "expiryStats")]
+    pub fn expiry_stats(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut stats_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_expiry_stats(raw, &mut stats_ptr))?;
+            transfer_stats(stats_ptr, "ndb_expiry_stats").map(camel_case_keys)
+        })
+    }
+
+    /// Trace ID of the last query this thread ran, such as `"4f1a9c-12"`.
+    /// Errors thrown by a query carry theirs as `traceId`.
+    #[napi(js_name = "lastTraceId")]
+    pub fn last_trace_id(&self) -> String {
+        read_last_trace_id()
+    }
+
+    #[napi(js_name = "lastBookmark")]
+    pub fn last_bookmark(&self) -> Result<i64> {
+        self.with_db_ptr(|raw| {
+            let mut bookmark: u64 = 0;
+            capi_status(capi::ndb_last_bookmark(raw, &mut bookmark))?;
+            i64::try_from(bookmark).map_err(napi_err)
+        })
+    }
+
+    #[napi(js_name = "waitForBookmark")]
+    pub fn wait_for_bookmark(&self, bookmark: i64, timeout_ms: Option<u32>) -> Result<()> {
+        let bookmark = u64::try_from(bookmark).map_err(napi_err)?;
+        self.with_db_ptr(|raw| {
+            capi_status(capi::ndb_wait_for_bookmark(
+                raw,
+                bookmark,
+                timeout_ms.unwrap_or(DEFAULT_BOOKMARK_TIMEOUT_MS),
+            ))
+        })
+    }
+
+    #[napi(js_name = "createIndex")]
+    pub fn create_index(&self, label: String, property: String) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let property_c = to_cstring(&property, "property")?;
+            capi_status(capi::ndb_create_index(
+                raw,
+                label_c.as_ptr(),
+                property_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "createTextIndex")]
+    pub fn create_text_index(&self, label: String, property: String) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let property_c = to_cstring(&property, "property")?;
+            capi_status(capi::ndb_create_text_index(
+                raw,
+                label_c.as_ptr(),
+                property_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "createCompositeIndex")]
+    pub fn create_composite_index(&self, label: String, properties: Vec<String>) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let properties_c = encode_property_list(&properties)?;
+            capi_status(capi::ndb_create_composite_index(
+                raw,
+                label_c.as_ptr(),
+                properties_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "dropIndex")]
+    pub fn drop_index(&self, label: String, properties: Vec<String>) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let properties_c = encode_property_list(&properties)?;
+            let mut dropped: c_int = 0;
+            capi_status(capi::ndb_drop_index(
+                raw,
+                label_c.as_ptr(),
+                properties_c.as_ptr(),
+                &mut dropped,
+            ))?;
+            Ok(dropped != 0)
+        })
+    }
+
+    #[napi(js_name = "createUniqueConstraint")]
+    pub fn create_unique_constraint(&self, label: String, property: String) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let property_c = to_cstring(&property, "property")?;
+            capi_status(capi::ndb_create_unique_constraint(
+                raw,
+                label_c.as_ptr(),
+                property_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "dropUniqueConstraint")]
+    pub fn drop_unique_constraint(&self, label: String, property: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let property_c = to_cstring(&property, "property")?;
+            let mut dropped: c_int = 0;
+            capi_status(capi::ndb_drop_unique_constraint(
+                raw,
+                label_c.as_ptr(),
+                property_c.as_ptr(),
+                &mut dropped,
+            ))?;
+            Ok(dropped != 0)
+        })
+    }
+
+    #[napi(js_name = "createEndpointConstraint")]
+    pub fn create_endpoint_constraint(
+        &self,
+        rel_type: String,
+        endpoints: Vec<Vec<String>>,
+    ) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            let rel_type_c = to_cstring(&rel_type, "rel_type")?;
+            let encoded = serde_json::to_string(&endpoints).map_err(napi_err)?;
+            let endpoints_c = to_cstring(&encoded, "endpoints")?;
+            capi_status(capi::ndb_create_endpoint_constraint(
+                raw,
+                rel_type_c.as_ptr(),
+                endpoints_c.as_ptr(),
+            ))
+        })
+    }
+
+    #[napi(js_name = "dropEndpointConstraint")]
+    pub fn drop_endpoint_constraint(&self, rel_type: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let rel_type_c = to_cstring(&rel_type, "rel_type")?;
+            let mut dropped: c_int = 0;
+            capi_status(capi::ndb_drop_endpoint_constraint(
+                raw,
+                rel_type_c.as_ptr(),
+                &mut dropped,
+            ))?;
+            Ok(dropped != 0)
+        })
+    }
+
+    #[napi(js_name = "renameProperty")]
+    pub fn rename_property(&self, label: String, old: String, new: String) -> Result<u32> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let old_c = to_cstring(&old, "old")?;
+            let new_c = to_cstring(&new, "new")?;
+            let mut renamed: u64 = 0;
+            capi_status(capi::ndb_rename_property(
+                raw,
+                label_c.as_ptr(),
+                old_c.as_ptr(),
+                new_c.as_ptr(),
+                None,
+                ptr::null_mut(),
+                &mut renamed,
+            ))?;
+            Ok(renamed as u32)
+        })
+    }
+
+    #[napi(js_name = "renameLabel")]
+    pub fn rename_label(&self, old: String, new: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let old_c = to_cstring(&old, "old")?;
+            let new_c = to_cstring(&new, "new")?;
+            let mut renamed: c_int = 0;
+            capi_status(capi::ndb_rename_label(
+                raw,
+                old_c.as_ptr(),
+                new_c.as_ptr(),
+                &mut renamed,
+            ))?;
+            Ok(renamed != 0)
+        })
+    }
+
+    #[napi(js_name = "markSensitive")]
+    pub fn mark_sensitive(&self, label: String, property: String) -> Result<bool> {
+        self.set_sensitive(&label, &property, true)
+    }
+
+    #[napi(js_name = "unmarkSensitive")]
+    pub fn unmark_sensitive(&self, label: String, property: String) -> Result<bool> {
+        self.set_sensitive(&label, &property, false)
+    }
+
+    #[napi(js_name = "setRedaction")]
+    pub fn set_redaction(&self, enabled: bool) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_set_redaction(raw, c_int::from(enabled))))
+    }
+
+    #[napi(js_name = "setDeterministic")]
+    pub fn set_deterministic(&self, enabled: bool) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_set_deterministic(raw, c_int::from(enabled))))
+    }
+
+    /// With lazy entities on, `query` and `queryNamed` return nodes as
+    /// `{type: "node_id"}` and relationships as `{type: "edge_key"}`
+    /// references; read them with `entity`.
+    #[napi(js_name = "setLazyEntities")]
+    pub fn set_lazy_entities(&self, enabled: bool) -> Result<()> {
+        self.with_db_ptr(|raw| capi_status(capi::ndb_set_lazy_entities(raw, c_int::from(enabled))))
+    }
+
+    /// Reads the node or relationship behind a lazy reference, or just its
+    /// `key` property (`null` when unset), as of the latest commit.
+    #[napi]
+    pub fn entity(&self, reference: JsonValue, key: Option<String>) -> Result<JsonValue> {
+        let reference_c = to_cstring(&reference.to_string(), "reference")?;
+        let key_c = key.as_deref().map(|k| to_cstring(k, "key")).transpose()?;
+        self.with_db_ptr(|raw| {
+            let mut json_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_entity_get(
+                raw,
+                reference_c.as_ptr(),
+                key_c.as_ref().map_or(ptr::null(), |k| k.as_ptr()),
+                &mut json_ptr,
+            ))?;
+            transfer_stats(json_ptr, "ndb_entity_get")
+        })
+    }
+
+    #[napi(js_name = "setLocale")]
+    pub fn set_locale(&self, locale: Option<String>) -> Result<()> {
+        let locale_c = locale
+            .as_deref()
+            .map(|l| to_cstring(l, "locale"))
+            .transpose()?;
+        self.with_db_ptr(|raw| {
+            capi_status(capi::ndb_set_locale(
+                raw,
+                locale_c.as_ref().map_or(ptr::null(), |l| l.as_ptr()),
+            ))
+        })
+    }
+
+    #[napi]
+    pub fn preload(&self, labels: Vec<String>) -> Result<i64> {
+        let labels_json = serde_json::to_string(&labels).map_err(napi_err)?;
+        let labels_c = to_cstring(&labels_json, "labels")?;
+        self.with_db_ptr(|raw| {
+            let mut loaded: u64 = 0;
+            capi_status(capi::ndb_preload(raw, labels_c.as_ptr(), &mut loaded))?;
+            i64::try_from(loaded).map_err(napi_err)
+        })
+    }
+
+    #[napi(js_name = "preloadPages")]
+    pub fn preload_pages(&self, first: i64, count: i64) -> Result<i64> {
+        let first = u64::try_from(first).map_err(napi_err)?;
+        let count = u64::try_from(count).map_err(napi_err)?;
+        self.with_db_ptr(|raw| {
+            let mut loaded: u64 = 0;
+            capi_status(capi::ndb_preload_pages(raw, first, count, &mut loaded))?;
+            i64::try_from(loaded).map_err(napi_err)
+        })
+    }
+
+    #[napi(js_name = "setLabelTier")]
+    pub fn set_label_tier(&self, label: String, cold: Option<bool>) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let label_c = to_cstring(&label, "label")?;
+            let mut changed: c_int = 0;
+            capi_status(capi::ndb_set_label_tier(
+                raw,
+                label_c.as_ptr(),
+                c_int::from(cold.unwrap_or(true)),
+                &mut changed,
+            ))?;
+            Ok(changed != 0)
+        })
+    }
+
+    #[napi(js_name = "labelTiers")]
+    pub fn label_tiers(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut tiers_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_label_tiers(raw, &mut tiers_ptr))?;
+            transfer_stats(tiers_ptr, "ndb_label_tiers").map(camel_case_keys)
+        })
+    }
+
+    #[napi(js_name = "setNamespace")]
+    pub fn set_namespace(&self, prefix: String, iri: Option<String>) -> Result<bool> {
+        let prefix_c = to_cstring(&prefix, "prefix")?;
+        let iri_c = iri.as_deref().map(|i| to_cstring(i, "iri")).transpose()?;
+        self.with_db_ptr(|raw| {
+            let mut changed: c_int = 0;
+            capi_status(capi::ndb_set_namespace(
+                raw,
+                prefix_c.as_ptr(),
+                iri_c.as_ref().map_or(ptr::null(), |i| i.as_ptr()),
+                &mut changed,
+            ))?;
+            Ok(changed != 0)
+        })
+    }
+
+    #[napi]
+    pub fn namespaces(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut namespaces_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_namespaces(raw, &mut namespaces_ptr))?;
+            transfer_stats(namespaces_ptr, "ndb_namespaces")
+        })
+    }
+
+    #[napi(js_name = "walTail")]
+    pub fn wal_tail(&self, since_txid: Option<i64>) -> Result<Vec<JsonValue>> {
+        let since_txid = u64::try_from(since_txid.unwrap_or(0)).map_err(napi_err)?;
+        self.with_db_ptr(|raw| {
+            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+            capi_status(capi::ndb_wal_tail(raw, since_txid, &mut result_ptr))?;
+            if result_ptr.is_null() {
+                return Err(napi_err("ndb_wal_tail returned null result"));
+            }
+            result_to_json_rows(result_ptr)
+        })
+    }
+
+    #[napi(js_name = "setWalRetention")]
+    pub fn set_wal_retention(&self, keep_ms: i64) -> Result<()> {
+        let keep_ms = u64::try_from(keep_ms).map_err(napi_err)?;
+        self.with_db_ptr(|raw| capi_status(capi::ndb_set_wal_retention(raw, keep_ms)))
+    }
+
+    #[napi(js_name = "placeLegalHold")]
+    pub fn place_legal_hold(&self, name: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let mut placed: c_int = 0;
+            capi_status(capi::ndb_place_legal_hold(
+                raw,
+                name_c.as_ptr(),
+                &mut placed,
+            ))?;
+            Ok(placed != 0)
+        })
+    }
+
+    #[napi(js_name = "releaseLegalHold")]
+    pub fn release_legal_hold(&self, name: String) -> Result<bool> {
+        self.with_db_ptr(|raw| {
+            let name_c = to_cstring(&name, "name")?;
+            let mut released: c_int = 0;
+            capi_status(capi::ndb_release_legal_hold(
+                raw,
+                name_c.as_ptr(),
+                &mut released,
+            ))?;
+            Ok(released != 0)
+        })
+    }
+
+    #[napi(js_name = "walRetention")]
+    pub fn wal_retention(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut retention_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_wal_retention(raw, &mut retention_ptr))?;
+            transfer_stats(retention_ptr, "ndb_wal_retention").map(camel_case_keys)
+        })
+    }
+
+    #[napi(js_name = "setResultCache")]
+    pub fn set_result_cache(&self, capacity: u32, ttl_ms: Option<u32>) -> Result<()> {
+        self.with_db_ptr(|raw| {
+            capi_status(capi::ndb_set_result_cache(
+                raw,
+                capacity as usize,
+                u64::from(ttl_ms.unwrap_or(0)),
+            ))
+        })
+    }
+
+    #[napi(js_name = "setQueryCapture")]
+    pub fn set_query_capture(
+        &self,
+        path: Option<String>,
+        include_params: Option<bool>,
+    ) -> Result<()> {
+        let path_c = path.as_deref().map(|p| to_cstring(p, "path")).transpose()?;
+        self.with_db_ptr(|raw| {
+            capi_status(capi::ndb_set_query_capture(
+                raw,
+                path_c.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                c_int::from(include_params.unwrap_or(true)),
+            ))
+        })
+    }
+
+    #[napi(js_name = "replayQueries")]
+    pub fn replay_queries(&self, log_path: String) -> Result<JsonValue> {
+        let log_path_c = to_cstring(&log_path, "logPath")?;
+        self.with_db_ptr(|raw| {
+            let mut report_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_replay_queries(
+                raw,
+                log_path_c.as_ptr(),
+                &mut report_ptr,
+            ))?;
+            transfer_stats(report_ptr, "ndb_replay_queries").map(camel_case_keys)
+        })
+    }
+
+    #[napi]
+    pub fn schema(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut schema_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_schema(raw, &mut schema_ptr))?;
+            transfer_stats(schema_ptr, "ndb_schema").map(camel_case_keys)
+        })
+    }
+
+    #[napi(js_name = "degreeStats")]
+    pub fn degree_stats(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let mut stats_ptr: *mut c_char = ptr::null_mut();
+            capi_status(capi::ndb_degree_stats(raw, &mut stats_ptr))?;
+            transfer_stats(stats_ptr, "ndb_degree_stats").map(camel_case_keys)
+        })
+    }
+
+    #[napi(js_name = "resultCacheStats")]
+    pub fn result_cache_stats(&self) -> Result<JsonValue> {
+        self.with_db_ptr(|raw| {
+            let (mut hits, mut misses, mut len) = (0u64, 0u64, 0usize);
+            capi_status(capi::ndb_result_cache_stats(
+                raw,
+                &mut hits,
+                &mut misses,
+                &mut len,
+            ))?;
+            Ok(json!({ "hits": hits, "misses": misses, "len": len }))
+        })
+    }
+
+    #[napi(js_name = "searchVector")]
+    pub fn search_vector(&self, query: Vec<f64>, k: u32) -> Result<Vec<JsonValue>> {
+        self.vector_hits(query, |raw, query, out| {
+            capi::ndb_search_vector(raw, query.as_ptr(), query.len(), k, out)
+        })
+    }
+
+    #[napi(js_name = "searchVectorExact")]
+    pub fn search_vector_exact(&self, query: Vec<f64>, k: u32) -> Result<Vec<JsonValue>> {
+        self.vector_hits(query, |raw, query, out| {
+            capi::ndb_search_vector_exact(raw, query.as_ptr(), query.len(), k, out)
+        })
+    }
+
+    #[napi(js_name = "searchVectorFiltered")]
+    pub fn search_vector_filtered(
+        &self,
+        query: Vec<f64>,
+        k: u32,
+        options: Option<VectorSearchOptions>,
+    ) -> Result<Vec<JsonValue>> {
+        let options = options.unwrap_or_default();
+        let metric = match options.metric.as_deref().unwrap_or("l2") {
+            "l2" => capi::NDB_METRIC_L2,
+            "cosine" => capi::NDB_METRIC_COSINE,
+            "dot" => capi::NDB_METRIC_DOT,
+            other => {
+                return Err(napi_err(format!(
+                    "unknown vector metric '{other}' (expected l2, cosine or dot)"
+                )));
+            }
+        };
+        let label_c = options
+            .label
+            .as_deref()
+            .map(|l| to_cstring(l, "label"))
+            .transpose()?;
+        let property_c = options
+            .property
+            .as_deref()
+            .map(|p| to_cstring(p, "property"))
+            .transpose()?;
+        let value_c = options
+            .value
+            .map(|v| to_cstring(&v.to_string(), "value"))
+            .transpose()?;
+        self.vector_hits(query, |raw, query, out| {
+            capi::ndb_search_vector_filtered(
+                raw,
+                query.as_ptr(),
+                query.len(),
+                k,
+                metric,
+                label_c.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                property_c.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                value_c.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                out,
+            )
+        })
+    }
+
+    #[napi]
+    pub fn close(&self) -> Result<()> {
+        if self.active_write_txns.load(Ordering::SeqCst) != 0 {
+            return Err(napi_err(
+                "cannot close database: write transaction in progress",
+            ));
+        }
+
+        let mut guard = self
+            .raw
+            .write()
+            .map_err(|_| napi_err("database lock poisoned"))?;
+        if let Some(raw) = guard.take() {
+            capi_status(capi::ndb_close(raw))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends writes to a primary and reads to read replicas kept current by
+/// followers, as one session: a read only goes to a replica that has applied
+/// this router's last write, and to the primary otherwise.
+#[napi]
+pub struct Router {
+    primary: Db,
+    replicas: Vec<(Db, *mut capi::ndb_follower_t)>,
+    /// Where the next read starts looking for a replica.
+    next: usize,
+    /// Primary bookmark of this router's last write.
+    bookmark: u64,
+}
+
+impl Router {
+    /// The first replica, round robin, that has applied `self.bookmark`.
+    fn read_db(&mut self) -> Result<&Db> {
+        let count = self.replicas.len();
+        for offset in 0..count {
+            let index = (self.next + offset) % count;
+            let mut applied: u64 = 0;
+            capi_status(capi::ndb_follower_applied_bookmark(
+                self.replicas[index].1,
+                &mut applied,
+            ))?;
+            if applied >= self.bookmark {
+                self.next = index + 1;
+                return Ok(&self.replicas[index].0);
+            }
+        }
+        Ok(&self.primary)
+    }
+
+    fn stop_followers(&mut self) {
+        for (_, follower) in &mut self.replicas {
+            capi::ndb_follower_stop(std::mem::replace(follower, ptr::null_mut()));
+        }
+    }
+}
+
+impl Drop for Router {
+    fn drop(&mut self) {
+        self.stop_followers();
+    }
+}
+
+#[napi]
+impl Router {
+    /// Opens the primary at `primary` and a replica at each of `replicas`,
+    /// and starts replicating into the replicas. Replicas start empty or as
+    /// restored backups of the primary, and take no other writes.
+    #[napi(factory)]
+    pub fn open(primary: String, replicas: Vec<String>) -> Result<Self> {
+        let mut router = Self {
+            primary: Db::open(primary, None)?,
+            replicas: Vec::with_capacity(replicas.len()),
+            next: 0,
+            bookmark: 0,
+        };
+        for path in replicas {
+            let replica = Db::open(path, None)?;
+            let mut follower: *mut capi::ndb_follower_t = ptr::null_mut();
+            router.primary.with_db_ptr(|primary| {
+                replica.with_db_ptr(|raw| {
+                    capi_status(capi::ndb_follower_start(raw, primary, &mut follower))
+                })
+            })?;
+            if follower.is_null() {
+                return Err(napi_err("ndb_follower_start returned null follower handle"));
+            }
+            router.replicas.push((replica, follower));
+        }
+        Ok(router)
+    }
+
+    /// Runs a read query on a replica that has seen this router's writes.
+    #[napi]
+    pub fn query(&mut self, cypher: String, params: Option<JsonValue>) -> Result<Vec<JsonValue>> {
+        self.read_db()?.query(cypher, params)
+    }
+
+    /// Runs a write query on the primary and remembers its bookmark.
+    #[napi(js_name = "executeWrite")]
+    pub fn execute_write(&mut self, cypher: String, params: Option<JsonValue>) -> Result<u32> {
+        let affected = self.primary.execute_write(cypher, params)?;
+        let mut bookmark: u64 = 0;
+        self.primary
+            .with_db_ptr(|raw| capi_status(capi::ndb_last_bookmark(raw, &mut bookmark)))?;
+        self.bookmark = bookmark;
+        Ok(affected)
+    }
+
+    /// Primary bookmark of this router's last write; 0 before the first.
+    #[napi(getter)]
+    pub fn bookmark(&self) -> Result<i64> {
+        i64::try_from(self.bookmark).map_err(napi_err)
+    }
+
+    /// Stops replicating and closes the replicas and the primary.
+    #[napi]
+    pub fn close(&mut self) -> Result<()> {
+        self.stop_followers();
+        for (replica, _) in self.replicas.drain(..) {
+            replica.close()?;
+        }
+        self.primary.close()
+    }
+}
+
+/// Rows from `Db.queryResult`, addressed by row index and column name or
+/// position. The `get*` accessors check the value's type, so TypeScript
+/// callers get `NodeValue` and friends without casting.
+#[napi]
+pub struct QueryResult {
+    keys: Vec<String>,
+    rows: Vec<Vec<JsonValue>>,
+}
+
+impl QueryResult {
+    fn from_typed(typed: JsonValue) -> Result<Self> {
+        let keys = typed["columns"]
+            .as_array()
+            .ok_or_else(|| napi_err("typed result has no columns"))?
+            .iter()
+            .map(|column| column["name"].as_str().unwrap_or_default().to_string())
+            .collect();
+        let rows = typed["rows"]
+            .as_array()
+            .ok_or_else(|| napi_err("typed result has no rows"))?
+            .iter()
+            .map(|row| row.as_array().cloned().unwrap_or_default())
+            .collect();
+        Ok(Self { keys, rows })
+    }
+
+    fn value(&self, index: u32, key: &Either<String, u32>) -> Result<&JsonValue> {
+        let row = self
+            .rows
+            .get(index as usize)
+            .ok_or_else(|| napi_err(format!("row {index} out of range")))?;
+        let column = column_position(&self.keys, key)?;
+        Ok(&row[column])
+    }
+
+    /// The value if it is null or an envelope of one of `types`.
+    fn typed_value(
+        &self,
+        index: u32,
+        key: Either<String, u32>,
+        types: &[&str],
+    ) -> Result<JsonValue> {
+        let value = self.value(index, &key)?;
+        match value["type"].as_str() {
+            _ if value.is_null() => Ok(JsonValue::Null),
+            Some(ty) if types.contains(&ty) => Ok(value.clone()),
+            _ => Err(napi_err(format!(
+                "column {} of row {index} is not a {}",
+                column_label(&key),
+                types[0]
+            ))),
+        }
+    }
 }
 
 #[napi]
-impl Db {
-    #[napi(factory)]
-    pub fn open(path: String) -> Result<Self> {
-        let (ndb_path, wal_path) = derive_paths(Path::new(&path));
-        Self::make_open(
-            ndb_path.to_string_lossy().to_string(),
-            wal_path.to_string_lossy().to_string(),
-            path,
-        )
+impl QueryResult {
+    #[napi(getter)]
+    pub fn keys(&self) -> Vec<String> {
+        self.keys.clone()
     }
 
-    #[napi(factory, js_name = "openPaths")]
-    pub fn open_paths_factory(ndb_path: String, wal_path: String) -> Result<Self> {
-        let logical_path = ndb_path.clone();
-        Self::make_open(ndb_path, wal_path, logical_path)
+    #[napi(getter)]
+    pub fn length(&self) -> u32 {
+        self.rows.len() as u32
     }
 
-    #[napi(getter)]
-    pub fn path(&self) -> String {
-        self.path.clone()
+    /// Rows as objects keyed by column, as `Db.query` returns them.
+    #[napi]
+    pub fn rows(&self) -> Vec<JsonValue> {
+        self.rows
+            .iter()
+            .map(|row| {
+                JsonValue::Object(self.keys.iter().cloned().zip(row.iter().cloned()).collect())
+            })
+            .collect()
     }
 
-    #[napi(getter, js_name = "ndbPath")]
-    pub fn ndb_path(&self) -> String {
-        self.ndb_path.clone()
+    #[napi]
+    pub fn get(&self, index: u32, key: Either<String, u32>) -> Result<JsonValue> {
+        self.value(index, &key).cloned()
     }
 
-    #[napi(getter, js_name = "walPath")]
-    pub fn wal_path(&self) -> String {
-        self.wal_path.clone()
+    #[napi(js_name = "getNode")]
+    pub fn get_node(&self, index: u32, key: Either<String, u32>) -> Result<JsonValue> {
+        self.typed_value(index, key, &["node"])
     }
 
-    #[napi]
-    pub fn query(&self, cypher: String, params: Option<JsonValue>) -> Result<Vec<JsonValue>> {
-        self.with_db_ptr(|raw| {
-            let cypher_c = to_cstring(&cypher, "cypher")?;
-            let params_c = encode_params(params)?;
-            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+    #[napi(js_name = "getRelationship")]
+    pub fn get_relationship(&self, index: u32, key: Either<String, u32>) -> Result<JsonValue> {
+        self.typed_value(index, key, &["relationship"])
+    }
 
-            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
-            capi_status(capi::ndb_query(
-                raw,
-                cypher_c.as_ptr(),
-                params_ptr,
-                &mut result_ptr,
-            ))?;
-            if result_ptr.is_null() {
-                return Err(napi_err("ndb_query returned null result handle"));
-            }
-            result_to_json_rows(result_ptr)
-        })
+    #[napi(js_name = "getPath")]
+    pub fn get_path(&self, index: u32, key: Either<String, u32>) -> Result<JsonValue> {
+        self.typed_value(index, key, &["path", "path_legacy"])
     }
 
+    /// Rows as neo4j-driver style `Record`s, for code migrating from Neo4j.
     #[napi]
-    pub fn execute_write(&self, cypher: String, params: Option<JsonValue>) -> Result<u32> {
-        self.with_db_ptr(|raw| {
-            let cypher_c = to_cstring(&cypher, "cypher")?;
-            let params_c = encode_params(params)?;
-            let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+    pub fn records(&self) -> Vec<Neo4jRecord> {
+        self.rows
+            .iter()
+            .map(|row| Neo4jRecord {
+                keys: self.keys.clone(),
+                values: row.iter().cloned().map(to_neo4j_value).collect(),
+            })
+            .collect()
+    }
+}
 
-            let mut affected: u32 = 0;
-            capi_status(capi::ndb_execute_write(
-                raw,
-                cypher_c.as_ptr(),
-                params_ptr,
-                &mut affected,
-            ))?;
-            Ok(affected)
-        })
+/// A row shaped like the neo4j driver's `Record`. Graph values inside it use
+/// the driver's field names (`identity`, `start`, `end`, `segments`, ...)
+/// with plain numbers in place of driver `Integer`s.
+#[napi]
+pub struct Neo4jRecord {
+    keys: Vec<String>,
+    values: Vec<JsonValue>,
+}
+
+#[napi]
+impl Neo4jRecord {
+    #[napi(getter)]
+    pub fn keys(&self) -> Vec<String> {
+        self.keys.clone()
+    }
+
+    #[napi(getter)]
+    pub fn length(&self) -> u32 {
+        self.keys.len() as u32
     }
 
     #[napi]
-    pub fn begin_write(&self) -> Result<WriteTxn> {
-        self.with_db_ptr(|raw| {
-            let mut txn_raw: *mut capi::ndb_txn_t = ptr::null_mut();
-            self.active_write_txns.fetch_add(1, Ordering::SeqCst);
-            if let Err(err) = capi_status(capi::ndb_begin_write(raw, &mut txn_raw)) {
-                self.active_write_txns.fetch_sub(1, Ordering::SeqCst);
-                return Err(err);
-            }
-            if txn_raw.is_null() {
-                self.active_write_txns.fetch_sub(1, Ordering::SeqCst);
-                return Err(napi_err("ndb_begin_write returned null transaction"));
-            }
-            Ok(WriteTxn {
-                raw: Some(txn_raw),
-                active_write_txns: self.active_write_txns.clone(),
-                affected: 0,
-                finished: false,
-            })
-        })
+    pub fn get(&self, key: Either<String, u32>) -> Result<JsonValue> {
+        Ok(self.values[column_position(&self.keys, &key)?].clone())
     }
 
     #[napi]
-    pub fn compact(&self) -> Result<()> {
-        self.with_db_ptr(|raw| capi_status(capi::ndb_compact(raw)))
+    pub fn has(&self, key: Either<String, u32>) -> bool {
+        column_position(&self.keys, &key).is_ok()
     }
 
     #[napi]
-    pub fn checkpoint(&self) -> Result<()> {
-        self.with_db_ptr(|raw| capi_status(capi::ndb_checkpoint(raw)))
+    pub fn values(&self) -> Vec<JsonValue> {
+        self.values.clone()
     }
 
-    #[napi(js_name = "createIndex")]
-    pub fn create_index(&self, label: String, property: String) -> Result<()> {
-        self.with_db_ptr(|raw| {
-            let label_c = to_cstring(&label, "label")?;
-            let property_c = to_cstring(&property, "property")?;
-            capi_status(capi::ndb_create_index(
-                raw,
-                label_c.as_ptr(),
-                property_c.as_ptr(),
-            ))
-        })
+    #[napi]
+    pub fn entries(&self) -> Vec<Vec<JsonValue>> {
+        self.keys
+            .iter()
+            .zip(&self.values)
+            .map(|(key, value)| vec![JsonValue::String(key.clone()), value.clone()])
+            .collect()
     }
 
-    #[napi(js_name = "searchVector")]
-    pub fn search_vector(&self, query: Vec<f64>, k: u32) -> Result<Vec<JsonValue>> {
-        self.with_db_ptr(|raw| {
-            let query_f32: Vec<f32> = query.into_iter().map(|v| v as f32).collect();
-            let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
-            capi_status(capi::ndb_search_vector(
-                raw,
-                query_f32.as_ptr(),
-                query_f32.len(),
-                k,
-                &mut result_ptr,
-            ))?;
-            if result_ptr.is_null() {
-                return Err(napi_err("ndb_search_vector returned null result"));
-            }
-            let rows = result_to_json_rows(result_ptr)?;
-            Ok(rows
-                .into_iter()
-                .map(|r| {
-                    let node_id = r.get("node_id").cloned().unwrap_or(JsonValue::Null);
-                    let distance = r.get("distance").cloned().unwrap_or(JsonValue::Null);
-                    json!({"nodeId": node_id, "distance": distance})
+    #[napi(js_name = "toObject")]
+    pub fn to_object(&self) -> JsonValue {
+        JsonValue::Object(
+            self.keys
+                .iter()
+                .cloned()
+                .zip(self.values.iter().cloned())
+                .collect(),
+        )
+    }
+}
+
+fn column_position(keys: &[String], key: &Either<String, u32>) -> Result<usize> {
+    match key {
+        Either::A(name) => keys.iter().position(|k| k == name),
+        Either::B(index) => Some(*index as usize).filter(|&i| i < keys.len()),
+    }
+    .ok_or_else(|| napi_err(format!("no column {}", column_label(key))))
+}
+
+fn column_label(key: &Either<String, u32>) -> String {
+    match key {
+        Either::A(name) => format!("'{name}'"),
+        Either::B(index) => index.to_string(),
+    }
+}
+
+/// Reshapes node, relationship and path envelopes into the neo4j driver's
+/// object layout, recursing through lists and maps. Relationships have no
+/// id of their own, so `identity` is null and `elementId` names the edge.
+fn to_neo4j_value(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Array(items) => {
+            JsonValue::Array(items.into_iter().map(to_neo4j_value).collect())
+        }
+        JsonValue::Object(map) => match map.get("type").and_then(JsonValue::as_str) {
+            Some("node") => json!({
+                "identity": map["id"],
+                "elementId": map["id"].to_string(),
+                "labels": map["labels"],
+                "properties": map["properties"],
+            }),
+            Some("relationship") => json!({
+                "identity": null,
+                "elementId": format!(
+                    "{}-{}-{}",
+                    map["src"],
+                    map["rel_type"].as_str().unwrap_or_default(),
+                    map["dst"]
+                ),
+                "start": map["src"],
+                "end": map["dst"],
+                "startNodeElementId": map["src"].to_string(),
+                "endNodeElementId": map["dst"].to_string(),
+                "type": map["rel_type"],
+                "properties": map["properties"],
+            }),
+            Some("path") => {
+                let nodes: Vec<JsonValue> = map["nodes"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(to_neo4j_value)
+                    .collect();
+                let relationships: Vec<JsonValue> = map["relationships"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(to_neo4j_value)
+                    .collect();
+                // A relationship may be walked against its direction, so each
+                // segment takes its ends from the node sequence.
+                let segments: Vec<JsonValue> = relationships
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, relationship)| {
+                        json!({
+                            "start": nodes[i],
+                            "relationship": relationship,
+                            "end": nodes[i + 1],
+                        })
+                    })
+                    .collect();
+                json!({
+                    "start": nodes.first(),
+                    "end": nodes.last(),
+                    "length": segments.len(),
+                    "segments": segments,
                 })
-                .collect())
-        })
+            }
+            Some(_) => JsonValue::Object(map),
+            None => JsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k, to_neo4j_value(v)))
+                    .collect(),
+            ),
+        },
+        other => other,
     }
+}
 
-    #[napi]
-    pub fn close(&self) -> Result<()> {
-        if self.active_write_txns.load(Ordering::SeqCst) != 0 {
-            return Err(napi_err(
-                "cannot close database: write transaction in progress",
-            ));
+/// Edge list cursor from `Db.edgesChunked`. It pins a snapshot, so the
+/// database cannot be closed until it is exhausted or closed.
+#[napi]
+pub struct EdgeCursor {
+    raw: Option<*mut capi::ndb_edges_t>,
+    chunk_size: usize,
+}
+
+impl Drop for EdgeCursor {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[napi]
+impl EdgeCursor {
+    /// The next chunk of edges, or `null` once every edge has been returned.
+    #[napi(js_name = "next")]
+    pub fn next_chunk(&mut self) -> Result<Option<EdgeChunk>> {
+        let Some(raw) = self.raw else {
+            return Ok(None);
+        };
+        let mut src = vec![0u64; self.chunk_size];
+        let mut dst = vec![0u64; self.chunk_size];
+        let mut weight = vec![0f64; self.chunk_size];
+        let mut len = 0usize;
+        capi_status(capi::ndb_edges_next(
+            raw,
+            self.chunk_size,
+            src.as_mut_ptr(),
+            dst.as_mut_ptr(),
+            weight.as_mut_ptr(),
+            &mut len,
+        ))?;
+        if len == 0 {
+            self.close();
+            return Ok(None);
         }
+        src.truncate(len);
+        dst.truncate(len);
+        weight.truncate(len);
+        Ok(Some(EdgeChunk {
+            src: BigUint64Array::new(src),
+            dst: BigUint64Array::new(dst),
+            weight: Float64Array::new(weight),
+        }))
+    }
 
-        let mut guard = self
-            .raw
-            .lock()
-            .map_err(|_| napi_err("database mutex poisoned"))?;
-        if let Some(raw) = guard.take() {
-            capi_status(capi::ndb_close(raw))?;
+    #[napi]
+    pub fn close(&mut self) {
+        if let Some(raw) = self.raw.take() {
+            capi::ndb_edges_close(raw);
         }
-        Ok(())
     }
 }
 
@@ -412,17 +1832,29 @@ impl Drop for WriteTxn {
 
 #[napi]
 impl WriteTxn {
+    /// Runs a statement in this transaction and returns its rows. Reads see
+    /// the transaction's uncommitted writes; after a failed statement the
+    /// transaction can only roll back.
     #[napi]
-    pub fn query(&mut self, cypher: String, params: Option<JsonValue>) -> Result<()> {
+    pub fn query(&mut self, cypher: String, params: Option<JsonValue>) -> Result<Vec<JsonValue>> {
         let cypher_c = to_cstring(&cypher, "cypher")?;
         let params_c = encode_params(params)?;
         let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
 
+        let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
         self.with_txn_ptr(|raw| {
-            capi_status(capi::ndb_txn_query(raw, cypher_c.as_ptr(), params_ptr))
+            capi_status(capi::ndb_txn_execute(
+                raw,
+                cypher_c.as_ptr(),
+                params_ptr,
+                &mut result_ptr,
+            ))
         })?;
+        if result_ptr.is_null() {
+            return Err(napi_err("ndb_txn_execute returned null result handle"));
+        }
         self.affected = self.affected.saturating_add(1);
-        Ok(())
+        result_to_json_rows(result_ptr)
     }
 
     #[napi(js_name = "createNode")]
@@ -495,10 +1927,7 @@ impl WriteTxn {
     #[napi(js_name = "setNodeProperty")]
     pub fn set_node_property(&mut self, node: u32, key: String, value: JsonValue) -> Result<()> {
         let key_c = to_cstring(&key, "key")?;
-        let value_c = to_cstring(
-            &serde_json::to_string(&value).map_err(napi_err)?,
-            "value_json",
-        )?;
+        let value_c = to_cstring(&encode_json_value(value)?, "value_json")?;
         self.with_txn_ptr(|raw| {
             capi_status(capi::ndb_txn_set_node_property(
                 raw,
@@ -511,6 +1940,14 @@ impl WriteTxn {
         Ok(())
     }
 
+    /// Makes `node` expire at `atMs` milliseconds since the Unix epoch.
+    #[napi(js_name = "setNodeExpiry")]
+    pub fn set_node_expiry(&mut self, node: u32, at_ms: i64) -> Result<()> {
+        self.with_txn_ptr(|raw| capi_status(capi::ndb_txn_set_node_expiry(raw, node, at_ms)))?;
+        self.affected = self.affected.saturating_add(1);
+        Ok(())
+    }
+
     #[napi(js_name = "setEdgeProperty")]
     pub fn set_edge_property(
         &mut self,
@@ -521,10 +1958,7 @@ impl WriteTxn {
         value: JsonValue,
     ) -> Result<()> {
         let key_c = to_cstring(&key, "key")?;
-        let value_c = to_cstring(
-            &serde_json::to_string(&value).map_err(napi_err)?,
-            "value_json",
-        )?;
+        let value_c = to_cstring(&encode_json_value(value)?, "value_json")?;
         self.with_txn_ptr(|raw| {
             capi_status(capi::ndb_txn_set_edge_property(
                 raw,
@@ -590,6 +2024,47 @@ impl WriteTxn {
         Ok(())
     }
 
+    /// Returns true when a transaction with this key already committed; this
+    /// one then applies nothing on commit.
+    #[napi(js_name = "setIdempotencyKey")]
+    pub fn set_idempotency_key(&mut self, key: String) -> Result<bool> {
+        let key_c = to_cstring(&key, "key")?;
+        let mut applied: c_int = 0;
+        self.with_txn_ptr(|raw| {
+            capi_status(capi::ndb_txn_set_idempotency_key(
+                raw,
+                key_c.as_ptr(),
+                &mut applied,
+            ))
+        })?;
+        Ok(applied != 0)
+    }
+
+    /// Defers unique constraint checks on later writes to commit. With
+    /// false, checks the writes deferred so far and throws if one breaks a
+    /// constraint.
+    #[napi(js_name = "setConstraintsDeferred")]
+    pub fn set_constraints_deferred(&mut self, deferred: bool) -> Result<()> {
+        self.with_txn_ptr(|raw| {
+            capi_status(capi::ndb_txn_set_constraints_deferred(
+                raw,
+                c_int::from(deferred),
+            ))
+        })
+    }
+
+    #[napi]
+    pub fn savepoint(&mut self, name: String) -> Result<()> {
+        let name_c = to_cstring(&name, "name")?;
+        self.with_txn_ptr(|raw| capi_status(capi::ndb_txn_savepoint(raw, name_c.as_ptr())))
+    }
+
+    #[napi(js_name = "rollbackTo")]
+    pub fn rollback_to(&mut self, name: String) -> Result<()> {
+        let name_c = to_cstring(&name, "name")?;
+        self.with_txn_ptr(|raw| capi_status(capi::ndb_txn_rollback_to(raw, name_c.as_ptr())))
+    }
+
     #[napi]
     pub fn rollback(&mut self) -> Result<()> {
         if self.finished {
@@ -608,14 +2083,20 @@ impl WriteTxn {
         if self.finished {
             return Ok(self.affected);
         }
-        if let Some(raw) = self.raw.take() {
-            capi_status(capi::ndb_txn_commit(raw))?;
-        }
+        let committed = match self.raw.take() {
+            Some(raw) => capi_status(capi::ndb_txn_commit(raw)),
+            None => Ok(()),
+        };
         self.finish();
-        Ok(self.affected)
+        committed.map(|()| self.affected)
     }
 }
 
+#[napi(js_name = "setPlanCacheCapacity")]
+pub fn set_plan_cache_capacity(capacity: u32) -> Result<()> {
+    capi_status(capi::ndb_set_plan_cache_capacity(capacity as usize))
+}
+
 #[napi]
 pub fn vacuum(path: String) -> Result<JsonValue> {
     let path_c = to_cstring(&path, "path")?;
@@ -666,17 +2147,196 @@ pub fn backup(path: String, backup_dir: String) -> Result<JsonValue> {
         }
     }
 
+    let checkpoint = &read_backup_manifest(&latest_path)["checkpoint"];
     Ok(json!({
         "id": id,
         "createdAt": format!("{:?}", std::time::SystemTime::now()),
         "sizeBytes": size_bytes,
         "fileCount": file_count,
         "nervusdbVersion": "1.0.0",
-        "checkpointTxid": 0,
-        "checkpointEpoch": 0,
+        "checkpointTxid": checkpoint["txid"].as_u64().unwrap_or(0),
+        "checkpointEpoch": checkpoint["epoch"].as_u64().unwrap_or(0),
+    }))
+}
+
+fn read_backup_manifest(dir: &Path) -> JsonValue {
+    fs::read(dir.join("backup_manifest.json"))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or(JsonValue::Null)
+}
+
+#[napi(js_name = "backupIncremental")]
+pub fn backup_incremental(path: String, backup_dir: String, since_txid: i64) -> Result<JsonValue> {
+    let path_c = to_cstring(&path, "path")?;
+    let backup_dir_c = to_cstring(&backup_dir, "backup_dir")?;
+    let since_txid = u64::try_from(since_txid).map_err(napi_err)?;
+    let mut info_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_backup_incremental(
+        path_c.as_ptr(),
+        backup_dir_c.as_ptr(),
+        since_txid,
+        &mut info_ptr,
+    ))?;
+    if info_ptr.is_null() {
+        return Err(napi_err("ndb_backup_incremental returned null"));
+    }
+    let text = unsafe {
+        // SAFETY: pointer returned by C API is valid until freed by `ndb_string_free`.
+        CStr::from_ptr(info_ptr).to_string_lossy().into_owned()
+    };
+    capi::ndb_string_free(info_ptr);
+    let info: JsonValue = serde_json::from_str(&text).map_err(napi_err)?;
+    Ok(json!({
+        "id": info["id"],
+        "createdAt": info["created_at"],
+        "sizeBytes": info["size_bytes"],
+        "fileCount": info["file_count"],
+        "nervusdbVersion": info["nervusdb_version"],
+        "checkpointTxid": info["checkpoint_txid"],
+        "checkpointEpoch": info["checkpoint_epoch"],
+        "baseBackup": info["base_backup"],
+    }))
+}
+
+#[napi]
+pub fn restore(backup_dir: String, backup_id: String, path: String) -> Result<()> {
+    let backup_dir_c = to_cstring(&backup_dir, "backup_dir")?;
+    let backup_id_c = to_cstring(&backup_id, "backup_id")?;
+    let path_c = to_cstring(&path, "path")?;
+    capi_status(capi::ndb_restore(
+        backup_dir_c.as_ptr(),
+        backup_id_c.as_ptr(),
+        path_c.as_ptr(),
+    ))
+}
+
+#[napi(js_name = "verifyBackup")]
+pub fn verify_backup(backup_path: String, test_restore: Option<bool>) -> Result<JsonValue> {
+    let backup_path_c = to_cstring(&backup_path, "backup_path")?;
+    let mut report_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_verify_backup(
+        backup_path_c.as_ptr(),
+        i32::from(test_restore.unwrap_or(false)),
+        &mut report_ptr,
+    ))?;
+    if report_ptr.is_null() {
+        return Err(napi_err("ndb_verify_backup returned null"));
+    }
+    let text = unsafe {
+        // SAFETY: pointer returned by C API is valid until freed by `ndb_string_free`.
+        CStr::from_ptr(report_ptr).to_string_lossy().into_owned()
+    };
+    capi::ndb_string_free(report_ptr);
+    let report: JsonValue = serde_json::from_str(&text).map_err(napi_err)?;
+    Ok(json!({
+        "backupId": report["backup_id"],
+        "filesChecked": report["files_checked"],
+        "unverified": report["unverified"],
+        "testRestored": report["test_restored"],
+        "problems": report["problems"],
     }))
 }
 
+#[napi(js_name = "exportGraph")]
+pub fn export_graph(
+    path: String,
+    format: String,
+    out_path: String,
+    scrub: Option<ScrubOptions>,
+) -> Result<JsonValue> {
+    let path_c = to_cstring(&path, "path")?;
+    let format_c = to_cstring(&format, "format")?;
+    let out_path_c = to_cstring(&out_path, "out_path")?;
+    let mut stats_ptr: *mut c_char = ptr::null_mut();
+    let Some(scrub) = scrub else {
+        capi_status(capi::ndb_export(
+            path_c.as_ptr(),
+            format_c.as_ptr(),
+            out_path_c.as_ptr(),
+            &mut stats_ptr,
+        ))?;
+        return transfer_stats(stats_ptr, "ndb_export");
+    };
+    let rules = json!({
+        "salt": scrub.salt.unwrap_or_default(),
+        "hash_ids": scrub.hash_ids.unwrap_or(false),
+        "hash_properties": scrub.hash_properties.unwrap_or_default(),
+        "drop_properties": scrub.drop_properties.unwrap_or_default(),
+        "generalize_dates": scrub.generalize_dates,
+    });
+    let rules_c = to_cstring(&rules.to_string(), "scrub")?;
+    capi_status(capi::ndb_export_scrubbed(
+        path_c.as_ptr(),
+        format_c.as_ptr(),
+        out_path_c.as_ptr(),
+        rules_c.as_ptr(),
+        &mut stats_ptr,
+    ))?;
+    transfer_stats(stats_ptr, "ndb_export_scrubbed")
+}
+
+#[napi(js_name = "importGraph")]
+pub fn import_graph(path: String, format: String, in_path: String) -> Result<JsonValue> {
+    let path_c = to_cstring(&path, "path")?;
+    let format_c = to_cstring(&format, "format")?;
+    let in_path_c = to_cstring(&in_path, "in_path")?;
+    let mut stats_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_import(
+        path_c.as_ptr(),
+        format_c.as_ptr(),
+        in_path_c.as_ptr(),
+        &mut stats_ptr,
+    ))?;
+    transfer_stats(stats_ptr, "ndb_import")
+}
+
+fn transfer_stats(stats_ptr: *mut c_char, api: &str) -> Result<JsonValue> {
+    if stats_ptr.is_null() {
+        return Err(napi_err(format!("{api} returned null")));
+    }
+    let text = unsafe {
+        // SAFETY: pointer returned by C API is valid until freed by `ndb_string_free`.
+        CStr::from_ptr(stats_ptr).to_string_lossy().into_owned()
+    };
+    capi::ndb_string_free(stats_ptr);
+    serde_json::from_str(&text).map_err(napi_err)
+}
+
+#[napi(js_name = "capacityReport")]
+pub fn capacity_report(path: String) -> Result<JsonValue> {
+    let path_c = to_cstring(&path, "path")?;
+    let mut report_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_capacity_report(path_c.as_ptr(), &mut report_ptr))?;
+    transfer_stats(report_ptr, "ndb_capacity_report").map(camel_case_keys)
+}
+
+/// Renames the snake_case keys of a C API report to JavaScript's camelCase.
+fn camel_case_keys(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(map) => JsonValue::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let mut parts = key.split('_');
+                    let mut camel = parts.next().unwrap_or_default().to_string();
+                    for part in parts {
+                        let mut chars = part.chars();
+                        if let Some(first) = chars.next() {
+                            camel.extend(first.to_uppercase());
+                            camel.push_str(chars.as_str());
+                        }
+                    }
+                    (camel, camel_case_keys(value))
+                })
+                .collect(),
+        ),
+        JsonValue::Array(items) => {
+            JsonValue::Array(items.into_iter().map(camel_case_keys).collect())
+        }
+        other => other,
+    }
+}
+
 #[napi]
 pub fn bulkload(path: String, nodes: Vec<BulkNodeInput>, edges: Vec<BulkEdgeInput>) -> Result<()> {
     let path_c = to_cstring(&path, "path")?;
@@ -707,11 +2367,11 @@ pub fn bulkload(path: String, nodes: Vec<BulkNodeInput>, edges: Vec<BulkEdgeInpu
     }
 
     let nodes_c = to_cstring(
-        &serde_json::to_string(&node_items).map_err(napi_err)?,
+        &encode_json_value(JsonValue::Array(node_items))?,
         "nodes_json",
     )?;
     let edges_c = to_cstring(
-        &serde_json::to_string(&edge_items).map_err(napi_err)?,
+        &encode_json_value(JsonValue::Array(edge_items))?,
         "edges_json",
     )?;
 
@@ -724,8 +2384,8 @@ pub fn bulkload(path: String, nodes: Vec<BulkNodeInput>, edges: Vec<BulkEdgeInpu
 
 #[cfg(test)]
 mod tests {
-    use super::{classify_err_message, napi_err};
-    use serde_json::Value;
+    use super::{classify_err_message, integralize_js_numbers, napi_err, to_neo4j_value};
+    use serde_json::{json, Value};
 
     fn parse_payload(reason: &str) -> Value {
         serde_json::from_str(reason).expect("napi reason should be valid json payload")
@@ -788,4 +2448,58 @@ mod tests {
         assert_eq!(code, "NERVUS_RESOURCE_LIMIT");
         assert_eq!(category, "execution");
     }
+
+    #[test]
+    fn integral_js_numbers_are_sent_as_integers() {
+        let value = json!({
+            "big": 9007199254740991.0,
+            "neg": -4294967297.0,
+            "frac": 2.5,
+            "unsafe": 9007199254740992.0,
+            "nested": [1099511627777.0, {"x": 3.0}]
+        });
+        assert_eq!(
+            integralize_js_numbers(value).to_string(),
+            json!({
+                "big": 9007199254740991i64,
+                "neg": -4294967297i64,
+                "frac": 2.5,
+                "unsafe": 9007199254740992.0,
+                "nested": [1099511627777i64, {"x": 3}]
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn neo4j_values_use_driver_field_names() {
+        let node = |id: u32, name: &str| json!({"type": "node", "id": id, "labels": ["P"], "properties": {"name": name}});
+        let rel = json!({
+            "type": "relationship", "src": 1, "dst": 0, "rel_type": "K", "properties": {}
+        });
+        // The path walks `K` backwards, from node 0 to node 1.
+        let path = json!({
+            "type": "path",
+            "nodes": [node(0, "a"), node(1, "b")],
+            "relationships": [rel.clone()],
+        });
+        let converted = to_neo4j_value(json!([node(0, "a"), {"rel": rel}, path]));
+
+        assert_eq!(converted[0]["identity"], 0);
+        assert_eq!(converted[0]["elementId"], "0");
+        assert_eq!(converted[0]["properties"]["name"], "a");
+        assert_eq!(converted[1]["rel"]["type"], "K");
+        assert_eq!(converted[1]["rel"]["start"], 1);
+        assert_eq!(converted[1]["rel"]["endNodeElementId"], "0");
+        assert_eq!(converted[1]["rel"]["elementId"], "1-K-0");
+        let path = &converted[2];
+        assert_eq!(path["length"], 1);
+        assert_eq!(path["start"]["identity"], 0);
+        assert_eq!(path["end"]["identity"], 1);
+        assert_eq!(path["segments"][0]["start"]["identity"], 0);
+        assert_eq!(path["segments"][0]["relationship"]["start"], 1);
+        // Other envelopes pass through untouched.
+        let datetime = json!({"type": "datetime", "value": 5});
+        assert_eq!(to_neo4j_value(datetime.clone()), datetime);
+    }
 }
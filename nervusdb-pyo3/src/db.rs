@@ -1,23 +1,40 @@
-use super::types::{json_to_py, py_to_json};
+use super::types::{json_to_py, json_to_py_in, py_to_json};
 use super::WriteTxn;
-use crate::{capi_status, classify_nervus_error, QueryStream};
+use crate::operation::{progress_callback, Operation};
+use crate::{capi_status, classify_nervus_error, EdgeChunkStream, QueryStream};
+use arrow::pyarrow::ToPyArrow;
 use nervusdb_capi as capi;
 use pyo3::prelude::*;
-use pyo3::types::PyType;
+use pyo3::types::{PyDict, PyType};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
-use std::ffi::{c_char, CStr, CString};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
 use std::path::{Path, PathBuf};
 use std::ptr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+/// One query row as handed to Python.
+type PyRow = HashMap<String, Py<PyAny>>;
+/// One `(query, params)` entry of a `query_many` batch.
+type QueryRequest = (String, Option<HashMap<String, Py<PyAny>>>);
+
 #[pyclass(unsendable)]
 pub struct Db {
     pub(crate) raw: Option<*mut capi::ndb_db_t>,
     ndb_path: PathBuf,
     wal_path: PathBuf,
     active_write_txns: Arc<AtomicUsize>,
+    /// Set by `set_lazy_entities`; mirrors the C handle's flag.
+    lazy_entities: bool,
+}
+
+fn open_flags(read_only: bool) -> c_int {
+    if read_only {
+        capi::NDB_OPEN_READ_ONLY
+    } else {
+        0
+    }
 }
 
 impl Db {
@@ -29,11 +46,28 @@ impl Db {
         }
     }
 
-    fn raw_ptr(&self) -> PyResult<*mut capi::ndb_db_t> {
+    pub(crate) fn raw_ptr(&self) -> PyResult<*mut capi::ndb_db_t> {
         self.raw
             .ok_or_else(|| classify_nervus_error("database is closed"))
     }
 
+    fn set_sensitive(&self, label: &str, property: &str, sensitive: bool) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let property_c = CString::new(property)
+            .map_err(|_| classify_nervus_error("property contains interior NUL"))?;
+        let mut changed: c_int = 0;
+        capi_status(capi::ndb_set_sensitive(
+            raw,
+            label_c.as_ptr(),
+            property_c.as_ptr(),
+            c_int::from(sensitive),
+            &mut changed,
+        ))?;
+        Ok(changed != 0)
+    }
+
     fn encode_params(
         params: Option<HashMap<String, Py<PyAny>>>,
         py: Python<'_>,
@@ -53,6 +87,85 @@ impl Db {
             .map_err(|_| classify_nervus_error("params contains interior NUL"))
     }
 
+    fn encode_property_list(properties: &[String]) -> PyResult<CString> {
+        let encoded =
+            serde_json::to_string(properties).map_err(|e| classify_nervus_error(e.to_string()))?;
+        CString::new(encoded).map_err(|_| classify_nervus_error("properties contain interior NUL"))
+    }
+
+    /// Runs one of the `ndb_search_vector*` calls and decodes its hits.
+    fn vector_hits(
+        &self,
+        search: impl FnOnce(*mut capi::ndb_db_t, &mut *mut capi::ndb_result_t) -> c_int,
+    ) -> PyResult<Vec<(u32, f32)>> {
+        let raw = self.raw_ptr()?;
+        let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+        capi_status(search(raw, &mut result_ptr))?;
+        if result_ptr.is_null() {
+            return Err(classify_nervus_error(
+                "vector search returned null result handle",
+            ));
+        }
+
+        let value = Self::result_json(result_ptr)?;
+        let rows = value
+            .as_array()
+            .ok_or_else(|| classify_nervus_error("vector result must be array"))?;
+
+        let mut out = Vec::with_capacity(rows.len());
+        for row in rows {
+            let obj = row
+                .as_object()
+                .ok_or_else(|| classify_nervus_error("vector row must be object"))?;
+            let node_id = obj
+                .get("node_id")
+                .and_then(JsonValue::as_u64)
+                .ok_or_else(|| classify_nervus_error("vector row.node_id missing"))?
+                as u32;
+            let distance = obj
+                .get("distance")
+                .and_then(JsonValue::as_f64)
+                .ok_or_else(|| classify_nervus_error("vector row.distance missing"))?
+                as f32;
+            out.push((node_id, distance));
+        }
+        Ok(out)
+    }
+
+    /// Reads a node or relationship reference through `ndb_entity_get`:
+    /// one property with `key`, else the whole entity.
+    pub(crate) fn entity_json(
+        &self,
+        reference: &JsonValue,
+        key: Option<&str>,
+    ) -> PyResult<JsonValue> {
+        let raw = self.raw_ptr()?;
+        let reference_c = CString::new(reference.to_string())
+            .map_err(|_| classify_nervus_error("reference contains interior NUL"))?;
+        let key_c = key
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("key contains interior NUL"))?;
+        let mut json_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_entity_get(
+            raw,
+            reference_c.as_ptr(),
+            key_c.as_ref().map_or(ptr::null(), |k| k.as_ptr()),
+            &mut json_ptr,
+        ))?;
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(json_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(json_ptr);
+        serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))
+    }
+
+    /// The handle to build lazy entities on, when `set_lazy_entities` is on.
+    fn entity_refs(slf: &Bound<'_, Self>) -> Option<Py<Db>> {
+        slf.borrow().lazy_entities.then(|| slf.clone().unbind())
+    }
+
     fn result_json(result_ptr: *mut capi::ndb_result_t) -> PyResult<JsonValue> {
         let mut json_ptr: *mut c_char = ptr::null_mut();
         let rc = capi::ndb_result_to_json(result_ptr, &mut json_ptr);
@@ -71,10 +184,11 @@ impl Db {
         serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))
     }
 
-    fn execute_query_rows(
+    pub(crate) fn execute_query_rows(
         &self,
         query: &str,
         params: Option<HashMap<String, Py<PyAny>>>,
+        refs: Option<&Py<Db>>,
         py: Python<'_>,
     ) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
         let raw = self.raw_ptr()?;
@@ -95,7 +209,40 @@ impl Db {
                 "ndb_query returned null result handle",
             ));
         }
+        Self::result_rows(result_ptr, refs, py)
+    }
+
+    /// Runs a read query through `ndb_query_typed` and returns its
+    /// `{"columns", "rows"}` envelope.
+    fn typed_result_json(
+        &self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<JsonValue> {
+        let raw = self.raw_ptr()?;
+        let query_c = CString::new(query)
+            .map_err(|_| classify_nervus_error("query contains interior NUL"))?;
+        let params_c = Self::encode_params(params, py)?;
+        let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
 
+        let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+        capi_status(capi::ndb_query_typed(
+            raw,
+            query_c.as_ptr(),
+            params_ptr,
+            &mut result_ptr,
+        ))?;
+        Self::result_json(result_ptr)
+    }
+
+    /// Decodes JSON rows; with `refs`, node and relationship references
+    /// become lazy objects reading from that database.
+    fn result_rows(
+        result_ptr: *mut capi::ndb_result_t,
+        refs: Option<&Py<Db>>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
         let value = Self::result_json(result_ptr)?;
         let rows = value
             .as_array()
@@ -108,7 +255,7 @@ impl Db {
                 .ok_or_else(|| classify_nervus_error("query row must be object"))?;
             let mut mapped = HashMap::with_capacity(obj.len());
             for (k, v) in obj {
-                mapped.insert(k.clone(), json_to_py(v.clone(), py));
+                mapped.insert(k.clone(), json_to_py_in(v.clone(), py, refs));
             }
             out.push(mapped);
         }
@@ -118,8 +265,12 @@ impl Db {
 
 #[pymethods]
 impl Db {
+    /// Opens the database at `path`. With `read_only`, the handle refuses
+    /// every write, including checkpoints and schema changes; the database
+    /// must already exist, and its files are left untouched.
     #[new]
-    pub(crate) fn new(path: &str) -> PyResult<Self> {
+    #[pyo3(signature = (path, read_only=false))]
+    pub(crate) fn new(path: &str, read_only: bool) -> PyResult<Self> {
         let (ndb_path, wal_path) = Self::derive_paths(Path::new(path));
         let ndb_c = CString::new(ndb_path.to_string_lossy().to_string())
             .map_err(|_| classify_nervus_error("ndb_path contains interior NUL"))?;
@@ -127,14 +278,15 @@ impl Db {
             .map_err(|_| classify_nervus_error("wal_path contains interior NUL"))?;
 
         let mut raw: *mut capi::ndb_db_t = ptr::null_mut();
-        capi_status(capi::ndb_open_paths(
+        capi_status(capi::ndb_open_paths_with_flags(
             ndb_c.as_ptr(),
             wal_c.as_ptr(),
+            open_flags(read_only),
             &mut raw,
         ))?;
         if raw.is_null() {
             return Err(classify_nervus_error(
-                "ndb_open_paths returned null db handle",
+                "ndb_open_paths_with_flags returned null db handle",
             ));
         }
 
@@ -143,26 +295,33 @@ impl Db {
             ndb_path,
             wal_path,
             active_write_txns: Arc::new(AtomicUsize::new(0)),
+            lazy_entities: false,
         })
     }
 
     #[classmethod]
-    #[pyo3(signature = (ndb_path, wal_path))]
-    fn open_paths(_cls: &Bound<'_, PyType>, ndb_path: &str, wal_path: &str) -> PyResult<Self> {
+    #[pyo3(signature = (ndb_path, wal_path, read_only=false))]
+    fn open_paths(
+        _cls: &Bound<'_, PyType>,
+        ndb_path: &str,
+        wal_path: &str,
+        read_only: bool,
+    ) -> PyResult<Self> {
         let ndb_c = CString::new(ndb_path)
             .map_err(|_| classify_nervus_error("ndb_path contains interior NUL"))?;
         let wal_c = CString::new(wal_path)
             .map_err(|_| classify_nervus_error("wal_path contains interior NUL"))?;
 
         let mut raw: *mut capi::ndb_db_t = ptr::null_mut();
-        capi_status(capi::ndb_open_paths(
+        capi_status(capi::ndb_open_paths_with_flags(
             ndb_c.as_ptr(),
             wal_c.as_ptr(),
+            open_flags(read_only),
             &mut raw,
         ))?;
         if raw.is_null() {
             return Err(classify_nervus_error(
-                "ndb_open_paths returned null db handle",
+                "ndb_open_paths_with_flags returned null db handle",
             ));
         }
 
@@ -171,32 +330,234 @@ impl Db {
             ndb_path: PathBuf::from(ndb_path),
             wal_path: PathBuf::from(wal_path),
             active_write_txns: Arc::new(AtomicUsize::new(0)),
+            lazy_entities: false,
+        })
+    }
+
+    /// Opens a packed image written by `pack`, read-only.
+    #[classmethod]
+    #[pyo3(signature = (path))]
+    fn open_packed(_cls: &Bound<'_, PyType>, path: &str) -> PyResult<Self> {
+        let path_c =
+            CString::new(path).map_err(|_| classify_nervus_error("path contains interior NUL"))?;
+
+        let mut raw: *mut capi::ndb_db_t = ptr::null_mut();
+        capi_status(capi::ndb_open_packed(path_c.as_ptr(), &mut raw))?;
+        if raw.is_null() {
+            return Err(classify_nervus_error(
+                "ndb_open_packed returned null db handle",
+            ));
+        }
+
+        Ok(Self {
+            raw: Some(raw),
+            ndb_path: PathBuf::from(path),
+            wal_path: PathBuf::from(path),
+            active_write_txns: Arc::new(AtomicUsize::new(0)),
+            lazy_entities: false,
         })
     }
 
     #[pyo3(signature = (query, params=None))]
     fn query(
-        &self,
+        slf: &Bound<'_, Self>,
         query: &str,
         params: Option<HashMap<String, Py<PyAny>>>,
         py: Python<'_>,
     ) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
-        self.execute_query_rows(query, params, py)
+        let refs = Self::entity_refs(slf);
+        slf.borrow()
+            .execute_query_rows(query, params, refs.as_ref(), py)
     }
 
     #[pyo3(signature = (query, params=None))]
     fn query_stream(
-        &self,
+        slf: &Bound<'_, Self>,
         query: &str,
         params: Option<HashMap<String, Py<PyAny>>>,
         py: Python<'_>,
     ) -> PyResult<QueryStream> {
-        let rows = self.execute_query_rows(query, params, py)?;
+        let refs = Self::entity_refs(slf);
+        let rows = slf
+            .borrow()
+            .execute_query_rows(query, params, refs.as_ref(), py)?;
         Ok(QueryStream::new(rows))
     }
 
+    /// Runs `(query, params)` read queries against one pinned snapshot, so
+    /// all results see the same commits, and returns their rows in order.
+    fn query_many(
+        slf: &Bound<'_, Self>,
+        queries: Vec<QueryRequest>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<Vec<PyRow>>> {
+        let refs = Self::entity_refs(slf);
+        let raw = slf.borrow().raw_ptr()?;
+        let mut snapshot: *mut capi::ndb_snapshot_t = ptr::null_mut();
+        capi_status(capi::ndb_begin_read(raw, &mut snapshot))?;
+        let results = queries
+            .into_iter()
+            .map(|(query, params)| {
+                let query_c = CString::new(query)
+                    .map_err(|_| classify_nervus_error("query contains interior NUL"))?;
+                let params_c = Self::encode_params(params, py)?;
+                let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+                let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+                capi_status(capi::ndb_query_in_snapshot(
+                    snapshot,
+                    query_c.as_ptr(),
+                    params_ptr,
+                    &mut result_ptr,
+                ))?;
+                Self::result_rows(result_ptr, refs.as_ref(), py)
+            })
+            .collect();
+        capi::ndb_snapshot_release(snapshot);
+        results
+    }
+
+    #[pyo3(signature = (query, params=None))]
+    fn query_count(
+        &self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<u64> {
+        let raw = self.raw_ptr()?;
+        let query_c = CString::new(query)
+            .map_err(|_| classify_nervus_error("query contains interior NUL"))?;
+        let params_c = Self::encode_params(params, py)?;
+        let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+        let mut count: u64 = 0;
+        capi_status(capi::ndb_query_count(
+            raw,
+            query_c.as_ptr(),
+            params_ptr,
+            &mut count,
+        ))?;
+        Ok(count)
+    }
+
+    /// Returns `{"columns": [{"name", "type"}], "rows": [[...]]}` so callers
+    /// can hydrate values by column type instead of guessing.
     #[pyo3(signature = (query, params=None))]
-    fn execute_write(
+    pub(crate) fn query_typed(
+        &self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<Py<PyAny>> {
+        let value = self.typed_result_json(query, params, py)?;
+        let malformed = || classify_nervus_error("typed result must have columns and rows");
+        let columns = value
+            .get("columns")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(malformed)?;
+        let rows = value
+            .get("rows")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(malformed)?;
+
+        let mut py_columns: Vec<Py<PyAny>> = Vec::with_capacity(columns.len());
+        for column in columns {
+            let dict = PyDict::new_bound(py);
+            dict.set_item("name", column.get("name").and_then(JsonValue::as_str))?;
+            dict.set_item("type", column.get("type").and_then(JsonValue::as_str))?;
+            py_columns.push(dict.into());
+        }
+        let mut py_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            let values = row.as_array().ok_or_else(malformed)?;
+            py_rows.push(
+                values
+                    .iter()
+                    .map(|v| json_to_py(v.clone(), py))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let out = PyDict::new_bound(py);
+        out.set_item("columns", py_columns)?;
+        out.set_item("rows", py_rows)?;
+        Ok(out.into())
+    }
+
+    /// Returns the result as a `pyarrow.RecordBatch`, built column by column
+    /// without creating a Python object per value. Requires `pyarrow`.
+    #[pyo3(signature = (query, params=None))]
+    fn query_arrow(
+        &self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<PyObject> {
+        let value = self.typed_result_json(query, params, py)?;
+        crate::arrow_batch::record_batch(&value)
+            .map_err(classify_nervus_error)?
+            .to_pyarrow(py)
+    }
+
+    /// Returns the result as a `pandas.DataFrame`, via `query_arrow`.
+    #[pyo3(signature = (query, params=None))]
+    fn query_df(
+        &self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<PyObject> {
+        let batch = self.query_arrow(query, params, py)?;
+        batch.call_method0(py, "to_pandas")
+    }
+
+    /// Returns `(rows, next_token)` for one page of an `ORDER BY` query;
+    /// pass `next_token` back to get the following page. It is `None` after
+    /// the last page.
+    #[pyo3(signature = (query, page_size, token=None, params=None))]
+    fn query_page(
+        slf: &Bound<'_, Self>,
+        query: &str,
+        page_size: u32,
+        token: Option<&str>,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<(Vec<PyRow>, Option<String>)> {
+        let refs = Self::entity_refs(slf);
+        let raw = slf.borrow().raw_ptr()?;
+        let query_c = CString::new(query)
+            .map_err(|_| classify_nervus_error("query contains interior NUL"))?;
+        let token_c = token
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("token contains interior NUL"))?;
+        let params_c = Self::encode_params(params, py)?;
+        let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
+        let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+        let mut next_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_query_page(
+            raw,
+            query_c.as_ptr(),
+            params_ptr,
+            page_size,
+            token_c.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            &mut result_ptr,
+            &mut next_ptr,
+        ))?;
+        let next_token = (!next_ptr.is_null()).then(|| {
+            let text = unsafe {
+                // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+                CStr::from_ptr(next_ptr).to_string_lossy().into_owned()
+            };
+            capi::ndb_string_free(next_ptr);
+            text
+        });
+        Ok((
+            Self::result_rows(result_ptr, refs.as_ref(), py)?,
+            next_token,
+        ))
+    }
+
+    #[pyo3(signature = (query, params=None))]
+    pub(crate) fn execute_write(
         &self,
         query: &str,
         params: Option<HashMap<String, Py<PyAny>>>,
@@ -218,50 +579,208 @@ impl Db {
         Ok(affected)
     }
 
-    fn search_vector(&self, query: Vec<f32>, k: usize) -> PyResult<Vec<(u32, f32)>> {
+    fn save_query(&self, name: &str, query: &str) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let query_c = CString::new(query)
+            .map_err(|_| classify_nervus_error("query contains interior NUL"))?;
+        capi_status(capi::ndb_save_query(raw, name_c.as_ptr(), query_c.as_ptr()))
+    }
+
+    fn drop_query(&self, name: &str) -> PyResult<bool> {
         let raw = self.raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let mut dropped: c_int = 0;
+        capi_status(capi::ndb_drop_query(raw, name_c.as_ptr(), &mut dropped))?;
+        Ok(dropped != 0)
+    }
+
+    #[pyo3(signature = (name, params=None))]
+    fn query_named(
+        slf: &Bound<'_, Self>,
+        name: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+        let refs = Self::entity_refs(slf);
+        let raw = slf.borrow().raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let params_c = Self::encode_params(params, py)?;
+        let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+
         let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
-        capi_status(capi::ndb_search_vector(
+        capi_status(capi::ndb_query_named(
             raw,
-            query.as_ptr(),
-            query.len(),
-            k as u32,
+            name_c.as_ptr(),
+            params_ptr,
             &mut result_ptr,
         ))?;
         if result_ptr.is_null() {
             return Err(classify_nervus_error(
-                "ndb_search_vector returned null result handle",
+                "ndb_query_named returned null result handle",
             ));
         }
+        Self::result_rows(result_ptr, refs.as_ref(), py)
+    }
 
-        let value = Self::result_json(result_ptr)?;
-        let rows = value
-            .as_array()
-            .ok_or_else(|| classify_nervus_error("vector result must be array"))?;
+    #[pyo3(signature = (name, params=None))]
+    fn execute_named(
+        &self,
+        name: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<u32> {
+        let raw = self.raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let params_c = Self::encode_params(params, py)?;
+        let params_ptr = params_c.as_ref().map_or(ptr::null(), |s| s.as_ptr());
 
-        let mut out = Vec::with_capacity(rows.len());
-        for row in rows {
-            let obj = row
-                .as_object()
-                .ok_or_else(|| classify_nervus_error("vector row must be object"))?;
-            let node_id = obj
-                .get("node_id")
-                .and_then(JsonValue::as_u64)
-                .ok_or_else(|| classify_nervus_error("vector row.node_id missing"))?
-                as u32;
-            let distance = obj
-                .get("distance")
-                .and_then(JsonValue::as_f64)
-                .ok_or_else(|| classify_nervus_error("vector row.distance missing"))?
-                as f32;
-            out.push((node_id, distance));
-        }
-        Ok(out)
+        let mut affected: u32 = 0;
+        capi_status(capi::ndb_execute_named(
+            raw,
+            name_c.as_ptr(),
+            params_ptr,
+            &mut affected,
+        ))?;
+        Ok(affected)
     }
 
-    fn compact(&self) -> PyResult<()> {
+    /// Runs the write statement `query` after each commit through this
+    /// handle whose changes match `event`, e.g. `"node_created:User"`.
+    fn create_trigger(&self, name: &str, event: &str, query: &str) -> PyResult<()> {
         let raw = self.raw_ptr()?;
-        capi_status(capi::ndb_compact(raw))
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let event_c = CString::new(event)
+            .map_err(|_| classify_nervus_error("event contains interior NUL"))?;
+        let query_c = CString::new(query)
+            .map_err(|_| classify_nervus_error("query contains interior NUL"))?;
+        capi_status(capi::ndb_create_trigger(
+            raw,
+            name_c.as_ptr(),
+            event_c.as_ptr(),
+            query_c.as_ptr(),
+        ))
+    }
+
+    fn drop_trigger(&self, name: &str) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let mut dropped: c_int = 0;
+        capi_status(capi::ndb_drop_trigger(raw, name_c.as_ptr(), &mut dropped))?;
+        Ok(dropped != 0)
+    }
+
+    fn triggers(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut triggers_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_triggers(raw, &mut triggers_ptr))?;
+        if triggers_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_triggers returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(triggers_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(triggers_ptr);
+        let triggers: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(triggers, py))
+    }
+
+    fn search_vector(&self, query: Vec<f32>, k: usize) -> PyResult<Vec<(u32, f32)>> {
+        self.vector_hits(|raw, out| {
+            capi::ndb_search_vector(raw, query.as_ptr(), query.len(), k as u32, out)
+        })
+    }
+
+    fn search_vector_exact(&self, query: Vec<f32>, k: usize) -> PyResult<Vec<(u32, f32)>> {
+        self.vector_hits(|raw, out| {
+            capi::ndb_search_vector_exact(raw, query.as_ptr(), query.len(), k as u32, out)
+        })
+    }
+
+    #[pyo3(signature = (query, k, metric="l2", label=None, property=None, value=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn search_vector_filtered(
+        &self,
+        query: Vec<f32>,
+        k: usize,
+        metric: &str,
+        label: Option<&str>,
+        property: Option<&str>,
+        value: Option<Py<PyAny>>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<(u32, f32)>> {
+        let metric = match metric {
+            "l2" => capi::NDB_METRIC_L2,
+            "cosine" => capi::NDB_METRIC_COSINE,
+            "dot" => capi::NDB_METRIC_DOT,
+            other => {
+                return Err(classify_nervus_error(format!(
+                    "unknown vector metric '{other}' (expected l2, cosine or dot)"
+                )));
+            }
+        };
+        let label_c = label
+            .map(|l| {
+                CString::new(l).map_err(|_| classify_nervus_error("label contains interior NUL"))
+            })
+            .transpose()?;
+        let property_c = property
+            .map(|p| {
+                CString::new(p).map_err(|_| classify_nervus_error("property contains interior NUL"))
+            })
+            .transpose()?;
+        let value_c = value
+            .map(|v| {
+                let encoded = serde_json::to_string(&py_to_json(v.bind(py))?)
+                    .map_err(|e| classify_nervus_error(e.to_string()))?;
+                CString::new(encoded)
+                    .map_err(|_| classify_nervus_error("value contains interior NUL"))
+            })
+            .transpose()?;
+        self.vector_hits(|raw, out| {
+            capi::ndb_search_vector_filtered(
+                raw,
+                query.as_ptr(),
+                query.len(),
+                k as u32,
+                metric,
+                label_c.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                property_c.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                value_c.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                out,
+            )
+        })
+    }
+
+    /// `progress` is called with `(done, total)`; `operation` can cancel the
+    /// compaction until it starts rewriting the property store.
+    #[pyo3(signature = (progress=None, operation=None))]
+    fn compact(
+        &self,
+        py: Python<'_>,
+        progress: Option<PyObject>,
+        operation: Option<PyRef<'_, Operation>>,
+    ) -> PyResult<()> {
+        let raw_addr = self.raw_ptr()? as usize;
+        let op_addr = operation.as_ref().map_or(0, |op| op.as_ptr() as usize);
+        let (callback, user_data) = progress_callback(&progress);
+        // Release the GIL so another Python thread can poll or cancel.
+        py.allow_threads(move || {
+            capi_status(capi::ndb_compact_with_progress(
+                raw_addr as *mut capi::ndb_db_t,
+                op_addr as *mut capi::ndb_operation_t,
+                callback,
+                user_data as *mut c_void,
+            ))
+        })
     }
 
     fn checkpoint(&self) -> PyResult<()> {
@@ -269,6 +788,188 @@ impl Db {
         capi_status(capi::ndb_checkpoint(raw))
     }
 
+    /// Checkpoints, then writes a compressed, read-only packed image to
+    /// `out_path` for `Db.open_packed`. Returns the pack report as a dict.
+    fn pack(&self, py: Python<'_>, out_path: &str) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let out_c = CString::new(out_path)
+            .map_err(|_| classify_nervus_error("out_path contains interior NUL"))?;
+        let mut report_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_pack(raw, out_c.as_ptr(), &mut report_ptr))?;
+        if report_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_pack returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(report_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(report_ptr);
+        let report: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(report, py))
+    }
+
+    /// Checkpoints on a background thread every `interval_ms` after a write
+    /// and once the WAL reaches `wal_bytes`; 0 disables either trigger and
+    /// both 0 stops the thread. `io_bytes_per_sec` caps checkpoint IO.
+    #[pyo3(signature = (interval_ms=0, wal_bytes=0, io_bytes_per_sec=0))]
+    fn set_auto_checkpoint(
+        &self,
+        interval_ms: u64,
+        wal_bytes: u64,
+        io_bytes_per_sec: u64,
+    ) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_auto_checkpoint(
+            raw,
+            interval_ms,
+            wal_bytes,
+            io_bytes_per_sec,
+        ))
+    }
+
+    fn pause_checkpoints(&self) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_pause_checkpoints(raw))
+    }
+
+    fn resume_checkpoints(&self) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_resume_checkpoints(raw))
+    }
+
+    /// Slows writers down while the WAL is backlogged: past
+    /// `delay_wal_bytes` each write transaction waits `delay_ms` first, past
+    /// `reject_wal_bytes` commits fail. 0 disables either; all 0 turns it off.
+    #[pyo3(signature = (delay_wal_bytes=0, delay_ms=0, reject_wal_bytes=0))]
+    fn set_write_throttle(
+        &self,
+        delay_wal_bytes: u64,
+        delay_ms: u64,
+        reject_wal_bytes: u64,
+    ) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_write_throttle(
+            raw,
+            delay_wal_bytes,
+            delay_ms,
+            reject_wal_bytes,
+        ))
+    }
+
+    /// Deletes expired nodes, with their relationships, and expired
+    /// relationships every `interval_ms` on a background thread; 0 stops it.
+    /// Entities expire once their `expireAt` property, a datetime or epoch
+    /// milliseconds, has passed.
+    fn set_expiry_sweep(&self, interval_ms: u64) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_expiry_sweep(raw, interval_ms))
+    }
+
+    /// Deletes what has expired now; returns `(nodes, relationships)` deleted.
+    fn sweep_expired(&self) -> PyResult<(u64, u64)> {
+        let raw = self.raw_ptr()?;
+        let (mut nodes, mut edges) = (0u64, 0u64);
+        capi_status(capi::ndb_sweep_expired(raw, &mut nodes, &mut edges))?;
+        Ok((nodes, edges))
+    }
+
+    fn expiry_stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut stats_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_expiry_stats(raw, &mut stats_ptr))?;
+        if stats_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_expiry_stats returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(stats_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(stats_ptr);
+        let stats: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(stats, py))
+    }
+
+    /// Moves the properties of nodes labelled `label` to the cold tier, a
+    /// compressed file read without the page cache, or back with
+    /// `cold=False`. They move at the next checkpoint. Returns `False` if
+    /// the label was already there.
+    #[pyo3(signature = (label, cold=true))]
+    fn set_label_tier(&self, label: &str, cold: bool) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let mut changed: c_int = 0;
+        capi_status(capi::ndb_set_label_tier(
+            raw,
+            label_c.as_ptr(),
+            c_int::from(cold),
+            &mut changed,
+        ))?;
+        Ok(changed != 0)
+    }
+
+    /// The cold labels and the size of the cold tier, as a dict.
+    fn label_tiers(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut tiers_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_label_tiers(raw, &mut tiers_ptr))?;
+        if tiers_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_label_tiers returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(tiers_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(tiers_ptr);
+        let tiers: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(tiers, py))
+    }
+
+    /// Binds the namespace `prefix` to the base IRI `iri`, or unbinds it
+    /// with `iri=None`. Queries resolve `prefix:local` references with
+    /// `iri()`. Returns `False` if the binding was already as requested.
+    #[pyo3(signature = (prefix, iri=None))]
+    fn set_namespace(&self, prefix: &str, iri: Option<&str>) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let prefix_c = CString::new(prefix)
+            .map_err(|_| classify_nervus_error("prefix contains interior NUL"))?;
+        let iri_c = iri
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("iri contains interior NUL"))?;
+        let mut changed: c_int = 0;
+        capi_status(capi::ndb_set_namespace(
+            raw,
+            prefix_c.as_ptr(),
+            iri_c.as_ref().map_or(ptr::null(), |i| i.as_ptr()),
+            &mut changed,
+        ))?;
+        Ok(changed != 0)
+    }
+
+    /// The namespace catalog as a `{prefix: iri}` dict.
+    fn namespaces(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut namespaces_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_namespaces(raw, &mut namespaces_ptr))?;
+        if namespaces_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_namespaces returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(namespaces_ptr)
+                .to_string_lossy()
+                .into_owned()
+        };
+        capi::ndb_string_free(namespaces_ptr);
+        let namespaces: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(namespaces, py))
+    }
+
     fn create_index(&self, label: &str, property: &str) -> PyResult<()> {
         let raw = self.raw_ptr()?;
         let label_c = CString::new(label)
@@ -282,6 +983,452 @@ impl Db {
         ))
     }
 
+    fn create_text_index(&self, label: &str, property: &str) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let property_c = CString::new(property)
+            .map_err(|_| classify_nervus_error("property contains interior NUL"))?;
+        capi_status(capi::ndb_create_text_index(
+            raw,
+            label_c.as_ptr(),
+            property_c.as_ptr(),
+        ))
+    }
+
+    /// Trace ID of the last query this thread ran, such as `"4f1a9c-12"`.
+    /// Errors raised by a query carry theirs as `trace_id`.
+    fn last_trace_id(&self) -> String {
+        crate::last_trace_id()
+    }
+
+    fn last_bookmark(&self) -> PyResult<u64> {
+        let raw = self.raw_ptr()?;
+        let mut bookmark: u64 = 0;
+        capi_status(capi::ndb_last_bookmark(raw, &mut bookmark))?;
+        Ok(bookmark)
+    }
+
+    #[pyo3(signature = (bookmark, timeout_ms=5000))]
+    fn wait_for_bookmark(&self, bookmark: u64, timeout_ms: u32, py: Python<'_>) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        // Release the GIL so a Python thread can commit the awaited write.
+        let raw_addr = raw as usize;
+        py.allow_threads(move || {
+            capi_status(capi::ndb_wait_for_bookmark(
+                raw_addr as *mut capi::ndb_db_t,
+                bookmark,
+                timeout_ms,
+            ))
+        })
+    }
+
+    fn create_composite_index(&self, label: &str, properties: Vec<String>) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let properties_c = Self::encode_property_list(&properties)?;
+        capi_status(capi::ndb_create_composite_index(
+            raw,
+            label_c.as_ptr(),
+            properties_c.as_ptr(),
+        ))
+    }
+
+    fn drop_index(&self, label: &str, properties: Vec<String>) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let properties_c = Self::encode_property_list(&properties)?;
+        let mut dropped: c_int = 0;
+        capi_status(capi::ndb_drop_index(
+            raw,
+            label_c.as_ptr(),
+            properties_c.as_ptr(),
+            &mut dropped,
+        ))?;
+        Ok(dropped != 0)
+    }
+
+    fn create_unique_constraint(&self, label: &str, property: &str) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let property_c = CString::new(property)
+            .map_err(|_| classify_nervus_error("property contains interior NUL"))?;
+        capi_status(capi::ndb_create_unique_constraint(
+            raw,
+            label_c.as_ptr(),
+            property_c.as_ptr(),
+        ))
+    }
+
+    fn drop_unique_constraint(&self, label: &str, property: &str) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let property_c = CString::new(property)
+            .map_err(|_| classify_nervus_error("property contains interior NUL"))?;
+        let mut dropped: c_int = 0;
+        capi_status(capi::ndb_drop_unique_constraint(
+            raw,
+            label_c.as_ptr(),
+            property_c.as_ptr(),
+            &mut dropped,
+        ))?;
+        Ok(dropped != 0)
+    }
+
+    fn create_endpoint_constraint(
+        &self,
+        rel_type: &str,
+        endpoints: Vec<(String, String)>,
+    ) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let rel_type_c = CString::new(rel_type)
+            .map_err(|_| classify_nervus_error("rel_type contains interior NUL"))?;
+        let encoded =
+            serde_json::to_string(&endpoints).map_err(|e| classify_nervus_error(e.to_string()))?;
+        let endpoints_c = CString::new(encoded)
+            .map_err(|_| classify_nervus_error("endpoints contain interior NUL"))?;
+        capi_status(capi::ndb_create_endpoint_constraint(
+            raw,
+            rel_type_c.as_ptr(),
+            endpoints_c.as_ptr(),
+        ))
+    }
+
+    fn drop_endpoint_constraint(&self, rel_type: &str) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let rel_type_c = CString::new(rel_type)
+            .map_err(|_| classify_nervus_error("rel_type contains interior NUL"))?;
+        let mut dropped: c_int = 0;
+        capi_status(capi::ndb_drop_endpoint_constraint(
+            raw,
+            rel_type_c.as_ptr(),
+            &mut dropped,
+        ))?;
+        Ok(dropped != 0)
+    }
+
+    #[pyo3(signature = (label, old, new, progress=None))]
+    fn rename_property(
+        &self,
+        label: &str,
+        old: &str,
+        new: &str,
+        progress: Option<PyObject>,
+    ) -> PyResult<u64> {
+        let raw = self.raw_ptr()?;
+        let label_c = CString::new(label)
+            .map_err(|_| classify_nervus_error("label contains interior NUL"))?;
+        let old_c =
+            CString::new(old).map_err(|_| classify_nervus_error("old contains interior NUL"))?;
+        let new_c =
+            CString::new(new).map_err(|_| classify_nervus_error("new contains interior NUL"))?;
+        let (callback, user_data) = progress_callback(&progress);
+        let mut renamed: u64 = 0;
+        capi_status(capi::ndb_rename_property(
+            raw,
+            label_c.as_ptr(),
+            old_c.as_ptr(),
+            new_c.as_ptr(),
+            callback,
+            user_data as *mut c_void,
+            &mut renamed,
+        ))?;
+        Ok(renamed)
+    }
+
+    fn rename_label(&self, old: &str, new: &str) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let old_c =
+            CString::new(old).map_err(|_| classify_nervus_error("old contains interior NUL"))?;
+        let new_c =
+            CString::new(new).map_err(|_| classify_nervus_error("new contains interior NUL"))?;
+        let mut renamed: c_int = 0;
+        capi_status(capi::ndb_rename_label(
+            raw,
+            old_c.as_ptr(),
+            new_c.as_ptr(),
+            &mut renamed,
+        ))?;
+        Ok(renamed != 0)
+    }
+
+    fn mark_sensitive(&self, label: &str, property: &str) -> PyResult<bool> {
+        self.set_sensitive(label, property, true)
+    }
+
+    fn unmark_sensitive(&self, label: &str, property: &str) -> PyResult<bool> {
+        self.set_sensitive(label, property, false)
+    }
+
+    fn set_redaction(&self, enabled: bool) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_redaction(raw, c_int::from(enabled)))
+    }
+
+    /// Expands edges and returns index hits in sorted order, so results
+    /// without `ORDER BY` are the same across storage layouts.
+    fn set_deterministic(&self, enabled: bool) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_deterministic(raw, c_int::from(enabled)))
+    }
+
+    /// Returns nodes and relationships from `query`, `query_stream`,
+    /// `query_many`, `query_page` and `query_named` as `NodeRef` and
+    /// `RelationshipRef` objects, which read labels and properties only
+    /// when asked for them.
+    fn set_lazy_entities(&mut self, enabled: bool) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_lazy_entities(raw, c_int::from(enabled)))?;
+        self.lazy_entities = enabled;
+        Ok(())
+    }
+
+    /// Sets the locale (e.g. `"de-DE"`) whose separators
+    /// `toString(value, format)` uses for numbers; `None` restores English.
+    #[pyo3(signature = (locale=None))]
+    fn set_locale(&self, locale: Option<&str>) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let locale_c = locale
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("locale contains interior NUL"))?;
+        capi_status(capi::ndb_set_locale(
+            raw,
+            locale_c.as_ref().map_or(ptr::null(), |l| l.as_ptr()),
+        ))
+    }
+
+    /// Reads the stored properties of nodes with any of `labels` into the
+    /// page cache. Returns the number of pages loaded.
+    fn preload(&self, labels: Vec<String>) -> PyResult<u64> {
+        let raw = self.raw_ptr()?;
+        let labels_json =
+            serde_json::to_string(&labels).map_err(|e| classify_nervus_error(e.to_string()))?;
+        let labels_c = CString::new(labels_json)
+            .map_err(|_| classify_nervus_error("labels contain interior NUL"))?;
+        let mut loaded: u64 = 0;
+        capi_status(capi::ndb_preload(raw, labels_c.as_ptr(), &mut loaded))?;
+        Ok(loaded)
+    }
+
+    /// Reads `count` pages starting at page `first` into the page cache.
+    /// Returns the number of pages loaded.
+    fn preload_pages(&self, first: u64, count: u64) -> PyResult<u64> {
+        let raw = self.raw_ptr()?;
+        let mut loaded: u64 = 0;
+        capi_status(capi::ndb_preload_pages(raw, first, count, &mut loaded))?;
+        Ok(loaded)
+    }
+
+    /// Graph changes committed after `since_txid`, as a list of
+    /// `{"txid", "changes"}` dicts in commit order. Values are not redacted.
+    #[pyo3(signature = (since_txid=0))]
+    fn wal_tail(&self, since_txid: u64, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let raw = self.raw_ptr()?;
+        let mut result_ptr: *mut capi::ndb_result_t = ptr::null_mut();
+        capi_status(capi::ndb_wal_tail(raw, since_txid, &mut result_ptr))?;
+        if result_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_wal_tail returned null result"));
+        }
+        Ok(json_to_py(Self::result_json(result_ptr)?, py))
+    }
+
+    /// Keeps checkpointed WAL history archived for `keep_ms` milliseconds
+    /// instead of letting the next checkpoint delete it. 0 stops archiving
+    /// unless a legal hold is placed.
+    fn set_wal_retention(&self, keep_ms: u64) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_wal_retention(raw, keep_ms))
+    }
+
+    /// Places the legal hold `name`, which keeps every WAL archive until it
+    /// is released. Returns `False` if it was already placed.
+    fn place_legal_hold(&self, name: &str) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let mut placed: c_int = 0;
+        capi_status(capi::ndb_place_legal_hold(
+            raw,
+            name_c.as_ptr(),
+            &mut placed,
+        ))?;
+        Ok(placed != 0)
+    }
+
+    /// Releases the legal hold `name`, returning whether it was placed.
+    fn release_legal_hold(&self, name: &str) -> PyResult<bool> {
+        let raw = self.raw_ptr()?;
+        let name_c =
+            CString::new(name).map_err(|_| classify_nervus_error("name contains interior NUL"))?;
+        let mut released: c_int = 0;
+        capi_status(capi::ndb_release_legal_hold(
+            raw,
+            name_c.as_ptr(),
+            &mut released,
+        ))?;
+        Ok(released != 0)
+    }
+
+    /// The WAL retention period, placed legal holds and kept archives, as a
+    /// dict.
+    fn wal_retention(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut retention_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_wal_retention(raw, &mut retention_ptr))?;
+        if retention_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_wal_retention returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(retention_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(retention_ptr);
+        let retention: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(retention, py))
+    }
+
+    /// Caches up to `capacity` read results until a commit touches a label
+    /// or relationship type they read, or `ttl_ms` passes (0: no expiry).
+    /// A capacity of 0 turns the cache off.
+    #[pyo3(signature = (capacity, ttl_ms=0))]
+    fn set_result_cache(&self, capacity: usize, ttl_ms: u64) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        capi_status(capi::ndb_set_result_cache(raw, capacity, ttl_ms))
+    }
+
+    /// Appends every query run on this handle to the JSON Lines file `path`
+    /// with its latency and a digest of its rows. Without `include_params`
+    /// only parameter shapes are logged. `None` stops capturing.
+    #[pyo3(signature = (path=None, include_params=true))]
+    fn set_query_capture(&self, path: Option<&str>, include_params: bool) -> PyResult<()> {
+        let raw = self.raw_ptr()?;
+        let path_c = path
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("path contains interior NUL"))?;
+        capi_status(capi::ndb_set_query_capture(
+            raw,
+            path_c.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+            c_int::from(include_params),
+        ))
+    }
+
+    /// Runs the queries captured in `log_path` against this database and
+    /// returns a dict with per-query row counts, whether results match and
+    /// both latencies. Captured writes are committed.
+    fn replay_queries(&self, log_path: &str, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let log_path_c = CString::new(log_path)
+            .map_err(|_| classify_nervus_error("log_path contains interior NUL"))?;
+        let mut report_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_replay_queries(
+            raw,
+            log_path_c.as_ptr(),
+            &mut report_ptr,
+        ))?;
+        if report_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_replay_queries returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(report_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(report_ptr);
+        let report: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(report, py))
+    }
+
+    /// Labels, relationship types and property keys the database has used,
+    /// and its indexes, as a dict.
+    fn schema(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut schema_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_schema(raw, &mut schema_ptr))?;
+        if schema_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_schema returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(schema_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(schema_ptr);
+        let schema: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(schema, py))
+    }
+
+    /// Out- and in-degree histograms of every relationship type as of the
+    /// last compaction, as a list of dicts.
+    fn degree_stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let raw = self.raw_ptr()?;
+        let mut stats_ptr: *mut c_char = ptr::null_mut();
+        capi_status(capi::ndb_degree_stats(raw, &mut stats_ptr))?;
+        if stats_ptr.is_null() {
+            return Err(classify_nervus_error("ndb_degree_stats returned null"));
+        }
+        let text = unsafe {
+            // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+            CStr::from_ptr(stats_ptr).to_string_lossy().into_owned()
+        };
+        capi::ndb_string_free(stats_ptr);
+        let stats: JsonValue =
+            serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+        Ok(json_to_py(stats, py))
+    }
+
+    fn result_cache_stats(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let raw = self.raw_ptr()?;
+        let (mut hits, mut misses, mut len) = (0u64, 0u64, 0usize);
+        capi_status(capi::ndb_result_cache_stats(
+            raw,
+            &mut hits,
+            &mut misses,
+            &mut len,
+        ))?;
+        let out = PyDict::new_bound(py);
+        out.set_item("hits", hits)?;
+        out.set_item("misses", misses)?;
+        out.set_item("len", len)?;
+        Ok(out.into())
+    }
+
+    /// Streams every edge, or only those of type `rel_type`, as `EdgeChunk`s
+    /// of up to `chunk_size` rows. Weights come from the numeric edge
+    /// property `weight_property`, defaulting to 1.0.
+    #[pyo3(signature = (rel_type=None, chunk_size=65536, weight_property=None))]
+    fn edges_chunked(
+        slf: Py<Db>,
+        py: Python<'_>,
+        rel_type: Option<&str>,
+        chunk_size: usize,
+        weight_property: Option<&str>,
+    ) -> PyResult<EdgeChunkStream> {
+        let raw = slf.borrow(py).raw_ptr()?;
+        let rel_type_c = rel_type
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("rel_type contains interior NUL"))?;
+        let weight_c = weight_property
+            .map(CString::new)
+            .transpose()
+            .map_err(|_| classify_nervus_error("weight_property contains interior NUL"))?;
+        let mut edges: *mut capi::ndb_edges_t = ptr::null_mut();
+        capi_status(capi::ndb_edges_open(
+            raw,
+            rel_type_c.as_ref().map_or(ptr::null(), |r| r.as_ptr()),
+            weight_c.as_ref().map_or(ptr::null(), |w| w.as_ptr()),
+            &mut edges,
+        ))?;
+        Ok(EdgeChunkStream::new(edges, slf, chunk_size))
+    }
+
     pub(crate) fn begin_write(slf: Py<Db>, py: Python<'_>) -> PyResult<WriteTxn> {
         let db_ref = slf.borrow_mut(py);
         let raw = db_ref
@@ -0,0 +1,512 @@
+//! `nervusdb.dbapi`: a PEP 249 (DB-API 2.0) layer over `Db`, for tooling
+//! that speaks `connect()` / `cursor()` / `execute()` / `fetchall()`.
+//!
+//! Statements commit as they run, as in autocommit mode. Parameters use the
+//! `pyformat` style (`%(name)s` with a mapping, `%s` with a sequence) and
+//! are rewritten to Cypher `$` parameters; `$name` itself also works with a
+//! mapping.
+
+use crate::db::Db;
+use crate::{capi_status, CompatibilityError, NervusError, StorageError, SyntaxError};
+use nervusdb_capi as capi;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyMapping, PyString, PyTuple};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::CString;
+use std::ptr;
+
+/// Cypher `$` parameters by name.
+type Params = HashMap<String, Py<PyAny>>;
+
+create_exception!(nervusdb.dbapi, Warning, PyException);
+create_exception!(nervusdb.dbapi, Error, NervusError);
+create_exception!(nervusdb.dbapi, InterfaceError, Error);
+create_exception!(nervusdb.dbapi, DatabaseError, Error);
+create_exception!(nervusdb.dbapi, DataError, DatabaseError);
+create_exception!(nervusdb.dbapi, OperationalError, DatabaseError);
+create_exception!(nervusdb.dbapi, IntegrityError, DatabaseError);
+create_exception!(nervusdb.dbapi, InternalError, DatabaseError);
+create_exception!(nervusdb.dbapi, ProgrammingError, DatabaseError);
+create_exception!(nervusdb.dbapi, NotSupportedError, DatabaseError);
+
+/// Re-raises a binding error as the matching DB-API exception, chained to
+/// the original.
+fn dbapi_error(py: Python<'_>, err: PyErr) -> PyErr {
+    if err.is_instance_of::<Error>(py) {
+        return err;
+    }
+    let msg = err.value_bound(py).to_string();
+    let mapped = if err.is_instance_of::<SyntaxError>(py) {
+        ProgrammingError::new_err(msg)
+    } else if err.is_instance_of::<StorageError>(py) {
+        OperationalError::new_err(msg)
+    } else if err.is_instance_of::<CompatibilityError>(py) {
+        NotSupportedError::new_err(msg)
+    } else if msg.contains("constraint violation") {
+        IntegrityError::new_err(msg)
+    } else if err.is_instance_of::<NervusError>(py) {
+        DatabaseError::new_err(msg)
+    } else {
+        InterfaceError::new_err(msg)
+    };
+    mapped.set_cause(py, Some(err));
+    mapped
+}
+
+/// A query with its `pyformat` placeholders rewritten to Cypher parameters.
+#[derive(Debug, PartialEq)]
+struct Rewritten {
+    query: String,
+    /// How many `%s` placeholders were replaced by `$p1`, `$p2`, ...
+    positional: usize,
+    /// Whether any `%(name)s` placeholder was seen.
+    named: bool,
+}
+
+/// Rewrites `%(name)s` to `$name`, the n-th `%s` to `$pn` and `%%` to `%`,
+/// leaving string literals and backquoted names untouched.
+fn rewrite_placeholders(query: &str) -> Result<Rewritten, String> {
+    let mut out = String::with_capacity(query.len());
+    let mut positional = 0;
+    let mut named = false;
+    let mut quote: Option<char> = None;
+    let mut chars = query.chars();
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' && q != '`' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            '%' => match chars.next() {
+                Some('%') => out.push('%'),
+                Some('s') => {
+                    positional += 1;
+                    out.push_str(&format!("$p{positional}"));
+                }
+                Some('(') => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(')') => break,
+                            Some(c) => name.push(c),
+                            None => return Err("unterminated %(name)s placeholder".into()),
+                        }
+                    }
+                    if chars.next() != Some('s') {
+                        return Err(format!("placeholder %({name}) must end in 's'"));
+                    }
+                    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        return Err(format!("invalid parameter name {name:?}"));
+                    }
+                    named = true;
+                    out.push('$');
+                    out.push_str(&name);
+                }
+                _ => {
+                    return Err(
+                        "unsupported placeholder; use %s, %(name)s, or %% for a literal %".into(),
+                    )
+                }
+            },
+            _ => out.push(c),
+        }
+    }
+    Ok(Rewritten {
+        query: out,
+        positional,
+        named,
+    })
+}
+
+/// Turns `operation` and DB-API `parameters` into a Cypher query and its
+/// `$` parameters. Without parameters the query is passed through as is.
+fn bind_parameters(
+    operation: &str,
+    parameters: Option<&Bound<'_, PyAny>>,
+) -> PyResult<(String, Option<Params>)> {
+    let Some(parameters) = parameters.filter(|p| !p.is_none()) else {
+        return Ok((operation.to_string(), None));
+    };
+    let rewritten = rewrite_placeholders(operation).map_err(ProgrammingError::new_err)?;
+    let mut params = HashMap::new();
+    if let Ok(mapping) = parameters.downcast::<PyMapping>() {
+        if rewritten.positional > 0 {
+            return Err(ProgrammingError::new_err(
+                "%s placeholders need a sequence of parameters, not a mapping",
+            ));
+        }
+        for item in mapping.items()?.iter()? {
+            let (key, value): (String, Py<PyAny>) = item?.extract()?;
+            params.insert(key, value);
+        }
+    } else if parameters.is_instance_of::<PyString>() {
+        return Err(ProgrammingError::new_err(
+            "parameters must be a mapping or a sequence, not a string",
+        ));
+    } else {
+        let values: Vec<Py<PyAny>> = parameters
+            .extract()
+            .map_err(|_| ProgrammingError::new_err("parameters must be a mapping or a sequence"))?;
+        if rewritten.named {
+            return Err(ProgrammingError::new_err(
+                "%(name)s placeholders need a mapping of parameters, not a sequence",
+            ));
+        }
+        if values.len() != rewritten.positional {
+            return Err(ProgrammingError::new_err(format!(
+                "the query has {} %s placeholders but {} parameters were given",
+                rewritten.positional,
+                values.len()
+            )));
+        }
+        for (i, value) in values.into_iter().enumerate() {
+            params.insert(format!("p{}", i + 1), value);
+        }
+    }
+    Ok((rewritten.query, Some(params)))
+}
+
+/// Whether `query` is a write statement, by preparing it without running it.
+fn is_write_statement(db: &Db, query: &str) -> PyResult<bool> {
+    let raw = db
+        .raw
+        .ok_or_else(|| ProgrammingError::new_err("cannot operate on a closed connection"))?;
+    let query_c = CString::new(query)
+        .map_err(|_| ProgrammingError::new_err("query contains interior NUL"))?;
+    let mut stmt: *mut capi::ndb_stmt_t = ptr::null_mut();
+    if capi::ndb_prepare_read(raw, query_c.as_ptr(), &mut stmt) == capi::NDB_OK {
+        capi::ndb_stmt_finalize(stmt);
+        return Ok(false);
+    }
+    capi_status(capi::ndb_prepare_write(raw, query_c.as_ptr(), &mut stmt))?;
+    capi::ndb_stmt_finalize(stmt);
+    Ok(true)
+}
+
+/// Opens the database at `database` as a DB-API connection; with
+/// `read_only`, writes through it fail.
+#[pyfunction]
+#[pyo3(signature = (database, read_only=false))]
+fn connect(py: Python<'_>, database: &str, read_only: bool) -> PyResult<Connection> {
+    let db = Db::new(database, read_only).map_err(|e| dbapi_error(py, e))?;
+    Ok(Connection {
+        db: Some(Py::new(py, db)?),
+    })
+}
+
+/// A DB-API connection owning one `Db`; closing it closes the database.
+#[pyclass(unsendable, module = "nervusdb.dbapi")]
+pub struct Connection {
+    db: Option<Py<Db>>,
+}
+
+impl Connection {
+    fn db(&self, py: Python<'_>) -> PyResult<Py<Db>> {
+        self.db
+            .as_ref()
+            .map(|db| db.clone_ref(py))
+            .ok_or_else(|| ProgrammingError::new_err("cannot operate on a closed connection"))
+    }
+}
+
+#[pymethods]
+impl Connection {
+    fn cursor(slf: &Bound<'_, Self>) -> PyResult<Cursor> {
+        slf.borrow().db(slf.py())?;
+        Ok(Cursor::new(slf.clone().unbind()))
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        if let Some(db) = self.db.take() {
+            db.borrow_mut(py).close().map_err(|e| dbapi_error(py, e))?;
+        }
+        Ok(())
+    }
+
+    /// Statements commit as they run, so there is nothing left to commit.
+    fn commit(&self, py: Python<'_>) -> PyResult<()> {
+        self.db(py).map(|_| ())
+    }
+
+    /// Statements commit as they run, so there is never anything to roll back.
+    fn rollback(&self, py: Python<'_>) -> PyResult<()> {
+        self.db(py).map(|_| ())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, py: Python<'_>, _args: &Bound<'_, PyTuple>) -> PyResult<()> {
+        self.close(py)
+    }
+}
+
+/// A DB-API cursor. Rows are fetched as tuples in `description` order;
+/// a read materializes its result set when it is executed.
+#[pyclass(unsendable, module = "nervusdb.dbapi")]
+pub struct Cursor {
+    connection: Py<Connection>,
+    rows: VecDeque<Py<PyTuple>>,
+    /// `(name, type_code, None, None, None, None, None)` per column, or
+    /// `None` when the last statement returned no result set.
+    description: Option<Vec<Py<PyTuple>>>,
+    rowcount: i64,
+    #[pyo3(get, set)]
+    arraysize: usize,
+    closed: bool,
+}
+
+impl Cursor {
+    fn new(connection: Py<Connection>) -> Self {
+        Self {
+            connection,
+            rows: VecDeque::new(),
+            description: None,
+            rowcount: -1,
+            arraysize: 1,
+            closed: false,
+        }
+    }
+
+    fn db(&self, py: Python<'_>) -> PyResult<Py<Db>> {
+        if self.closed {
+            return Err(ProgrammingError::new_err(
+                "cannot operate on a closed cursor",
+            ));
+        }
+        self.connection.borrow(py).db(py)
+    }
+
+    fn run(
+        &mut self,
+        py: Python<'_>,
+        operation: &str,
+        parameters: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let db = self.db(py)?;
+        let db = db.borrow(py);
+        let (query, params) = bind_parameters(operation, parameters)?;
+        self.rows.clear();
+        self.description = None;
+        self.rowcount = -1;
+        if is_write_statement(&db, &query).map_err(|e| dbapi_error(py, e))? {
+            let affected = db
+                .execute_write(&query, params, py)
+                .map_err(|e| dbapi_error(py, e))?;
+            self.rowcount = i64::from(affected);
+            return Ok(());
+        }
+        let typed = db
+            .query_typed(&query, params, py)
+            .map_err(|e| dbapi_error(py, e))?;
+        let typed = typed.downcast_bound::<PyDict>(py)?;
+        let columns = typed
+            .get_item("columns")?
+            .ok_or_else(|| InternalError::new_err("typed result has no columns"))?;
+        let mut description = Vec::new();
+        for column in columns.downcast::<PyList>()?.iter() {
+            let name = column.get_item("name")?;
+            let type_code = column.get_item("type")?;
+            let none = py.None();
+            description.push(
+                PyTuple::new_bound(
+                    py,
+                    [
+                        name.unbind(),
+                        type_code.unbind(),
+                        none.clone_ref(py),
+                        none.clone_ref(py),
+                        none.clone_ref(py),
+                        none.clone_ref(py),
+                        none,
+                    ],
+                )
+                .unbind(),
+            );
+        }
+        let rows = typed
+            .get_item("rows")?
+            .ok_or_else(|| InternalError::new_err("typed result has no rows"))?;
+        for row in rows.downcast::<PyList>()?.iter() {
+            self.rows
+                .push_back(row.downcast::<PyList>()?.to_tuple().unbind());
+        }
+        self.rowcount = self.rows.len() as i64;
+        self.description = Some(description);
+        Ok(())
+    }
+
+    fn result_set(&self) -> PyResult<()> {
+        if self.closed {
+            return Err(ProgrammingError::new_err(
+                "cannot operate on a closed cursor",
+            ));
+        }
+        if self.description.is_none() {
+            return Err(ProgrammingError::new_err(
+                "no result set; the last statement did not return rows",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Cursor {
+    #[getter]
+    fn description(&self, py: Python<'_>) -> Option<Vec<Py<PyTuple>>> {
+        self.description
+            .as_ref()
+            .map(|columns| columns.iter().map(|c| c.clone_ref(py)).collect())
+    }
+
+    /// Rows returned by the last read, or nodes and relationships touched by
+    /// the last write; -1 before any statement.
+    #[getter]
+    fn rowcount(&self) -> i64 {
+        self.rowcount
+    }
+
+    #[getter]
+    fn connection(&self, py: Python<'_>) -> Py<Connection> {
+        self.connection.clone_ref(py)
+    }
+
+    #[pyo3(signature = (operation, parameters=None))]
+    fn execute<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        operation: &str,
+        parameters: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let py = slf.py();
+        slf.run(py, operation, parameters)?;
+        Ok(slf)
+    }
+
+    /// Runs `operation` once per parameter set; `rowcount` is the total of
+    /// the writes, and no result set is kept.
+    fn executemany<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        operation: &str,
+        seq_of_parameters: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let py = slf.py();
+        let mut total = 0;
+        for parameters in seq_of_parameters.iter()? {
+            slf.run(py, operation, Some(&parameters?))?;
+            total += slf.rowcount.max(0);
+        }
+        slf.rows.clear();
+        slf.description = None;
+        slf.rowcount = total;
+        Ok(slf)
+    }
+
+    fn fetchone(&mut self) -> PyResult<Option<Py<PyTuple>>> {
+        self.result_set()?;
+        Ok(self.rows.pop_front())
+    }
+
+    #[pyo3(signature = (size=None))]
+    fn fetchmany(&mut self, size: Option<usize>) -> PyResult<Vec<Py<PyTuple>>> {
+        self.result_set()?;
+        let size = size.unwrap_or(self.arraysize).min(self.rows.len());
+        Ok(self.rows.drain(..size).collect())
+    }
+
+    fn fetchall(&mut self) -> PyResult<Vec<Py<PyTuple>>> {
+        self.result_set()?;
+        Ok(self.rows.drain(..).collect())
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        self.rows.clear();
+        self.description = None;
+    }
+
+    fn setinputsizes(&self, _sizes: &Bound<'_, PyAny>) {}
+
+    #[pyo3(signature = (_size, _column=None))]
+    fn setoutputsize(&self, _size: &Bound<'_, PyAny>, _column: Option<&Bound<'_, PyAny>>) {}
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<Py<PyTuple>>> {
+        self.fetchone()
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, _args: &Bound<'_, PyTuple>) {
+        self.close();
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("apilevel", "2.0")?;
+    m.add("threadsafety", 1)?;
+    m.add("paramstyle", "pyformat")?;
+    m.add_function(wrap_pyfunction!(connect, m)?)?;
+    m.add_class::<Connection>()?;
+    m.add_class::<Cursor>()?;
+    m.add("Warning", py.get_type_bound::<Warning>())?;
+    m.add("Error", py.get_type_bound::<Error>())?;
+    m.add("InterfaceError", py.get_type_bound::<InterfaceError>())?;
+    m.add("DatabaseError", py.get_type_bound::<DatabaseError>())?;
+    m.add("DataError", py.get_type_bound::<DataError>())?;
+    m.add("OperationalError", py.get_type_bound::<OperationalError>())?;
+    m.add("IntegrityError", py.get_type_bound::<IntegrityError>())?;
+    m.add("InternalError", py.get_type_bound::<InternalError>())?;
+    m.add("ProgrammingError", py.get_type_bound::<ProgrammingError>())?;
+    m.add(
+        "NotSupportedError",
+        py.get_type_bound::<NotSupportedError>(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rewrite_placeholders, Rewritten};
+
+    #[test]
+    fn rewrites_pyformat_placeholders_outside_literals() {
+        assert_eq!(
+            rewrite_placeholders(
+                "MATCH (n {name: %(name)s}) WHERE n.tag = '%s \\' %(x)s' RETURN n.v %% 2, %s"
+            ),
+            Ok(Rewritten {
+                query: "MATCH (n {name: $name}) WHERE n.tag = '%s \\' %(x)s' RETURN n.v % 2, $p1"
+                    .into(),
+                positional: 1,
+                named: true,
+            })
+        );
+        // A bare `%` is Cypher's modulo, but must be written `%%` here.
+        assert!(rewrite_placeholders("RETURN 7 % 2").is_err());
+        assert!(rewrite_placeholders("RETURN %(bad name)s").is_err());
+        assert!(rewrite_placeholders("RETURN %(open").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::capi_status;
+use crate::db::Db;
+use nervusdb_capi as capi;
+use pyo3::exceptions::PyBufferError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::ffi::{c_int, c_void, CStr};
+use std::ptr;
+
+/// Edge list iterator from `Db.edges_chunked`, yielding `EdgeChunk`s.
+///
+/// Holds a snapshot open until exhausted or garbage collected, so the
+/// database cannot be closed while it is live.
+#[pyclass(unsendable)]
+pub struct EdgeChunkStream {
+    raw: Option<*mut capi::ndb_edges_t>,
+    _db: Py<Db>,
+    chunk_size: usize,
+}
+
+impl EdgeChunkStream {
+    pub(crate) fn new(raw: *mut capi::ndb_edges_t, db: Py<Db>, chunk_size: usize) -> Self {
+        Self {
+            raw: Some(raw),
+            _db: db,
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    fn release(&mut self) {
+        if let Some(raw) = self.raw.take() {
+            capi::ndb_edges_close(raw);
+        }
+    }
+}
+
+impl Drop for EdgeChunkStream {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[pymethods]
+impl EdgeChunkStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<EdgeChunk>> {
+        let Some(raw) = self.raw else {
+            return Ok(None);
+        };
+        let mut src = vec![0u64; self.chunk_size];
+        let mut dst = vec![0u64; self.chunk_size];
+        let mut weight = vec![0f64; self.chunk_size];
+        let mut len = 0usize;
+        capi_status(capi::ndb_edges_next(
+            raw,
+            self.chunk_size,
+            src.as_mut_ptr(),
+            dst.as_mut_ptr(),
+            weight.as_mut_ptr(),
+            &mut len,
+        ))?;
+        if len == 0 {
+            self.release();
+            return Ok(None);
+        }
+        src.truncate(len);
+        dst.truncate(len);
+        weight.truncate(len);
+        Ok(Some(EdgeChunk {
+            src: Py::new(py, EdgeColumn::new(ColumnData::U64(src)))?,
+            dst: Py::new(py, EdgeColumn::new(ColumnData::U64(dst)))?,
+            weight: Py::new(py, EdgeColumn::new(ColumnData::F64(weight)))?,
+            len,
+        }))
+    }
+}
+
+/// One batch of edges as `src`, `dst` (external node ids, `uint64`) and
+/// `weight` (`float64`) columns of equal length.
+#[pyclass(frozen)]
+pub struct EdgeChunk {
+    #[pyo3(get)]
+    src: Py<EdgeColumn>,
+    #[pyo3(get)]
+    dst: Py<EdgeColumn>,
+    #[pyo3(get)]
+    weight: Py<EdgeColumn>,
+    len: usize,
+}
+
+#[pymethods]
+impl EdgeChunk {
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    /// The chunk as a `pyarrow.RecordBatch` sharing the column buffers.
+    fn to_arrow(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let pa = py.import_bound("pyarrow")?;
+        let column = |data: &Py<EdgeColumn>, ty: &str| -> PyResult<Bound<'_, PyAny>> {
+            let buffer = pa.call_method1("py_buffer", (data.clone_ref(py),))?;
+            pa.getattr("Array")?.call_method1(
+                "from_buffers",
+                (
+                    pa.call_method0(ty)?,
+                    self.len,
+                    vec![py.None(), buffer.unbind()],
+                ),
+            )
+        };
+        let arrays = vec![
+            column(&self.src, "uint64")?,
+            column(&self.dst, "uint64")?,
+            column(&self.weight, "float64")?,
+        ];
+        let batch = pa
+            .getattr("RecordBatch")?
+            .call_method1("from_arrays", (arrays, vec!["src", "dst", "weight"]))?;
+        Ok(batch.unbind())
+    }
+}
+
+enum ColumnData {
+    U64(Vec<u64>),
+    F64(Vec<f64>),
+}
+
+/// A read-only numeric column exposed through the buffer protocol, so
+/// `memoryview`, `numpy.frombuffer` and `pyarrow.py_buffer` use its memory
+/// without copying.
+#[pyclass(frozen)]
+pub struct EdgeColumn {
+    data: ColumnData,
+    shape: [ffi::Py_ssize_t; 1],
+}
+
+impl EdgeColumn {
+    fn new(data: ColumnData) -> Self {
+        let len = match &data {
+            ColumnData::U64(values) => values.len(),
+            ColumnData::F64(values) => values.len(),
+        };
+        Self {
+            data,
+            shape: [len as ffi::Py_ssize_t],
+        }
+    }
+}
+
+#[pymethods]
+impl EdgeColumn {
+    fn __len__(&self) -> usize {
+        self.shape[0] as usize
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("view is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("edge columns are read-only"));
+        }
+        let column = slf.get();
+        let (buf, itemsize, format): (*const c_void, usize, &'static CStr) = match &column.data {
+            ColumnData::U64(values) => (values.as_ptr().cast(), 8, c"Q"),
+            ColumnData::F64(values) => (values.as_ptr().cast(), 8, c"d"),
+        };
+        // SAFETY: `view` is non-null and owned by the caller; the column is
+        // frozen, so `buf` and `shape` stay valid while `obj` holds it.
+        unsafe {
+            (*view).obj = slf.clone().into_any().into_ptr();
+            (*view).buf = buf as *mut c_void;
+            (*view).len = column.shape[0] * itemsize as ffi::Py_ssize_t;
+            (*view).readonly = 1;
+            (*view).itemsize = itemsize as ffi::Py_ssize_t;
+            (*view).format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT {
+                format.as_ptr() as *mut _
+            } else {
+                ptr::null_mut()
+            };
+            (*view).ndim = 1;
+            (*view).shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND {
+                column.shape.as_ptr() as *mut _
+            } else {
+                ptr::null_mut()
+            };
+            (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+                &mut (*view).itemsize
+            } else {
+                ptr::null_mut()
+            };
+            (*view).suboffsets = ptr::null_mut();
+            (*view).internal = ptr::null_mut();
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
@@ -8,17 +8,24 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use serde_json::json;
 use serde_json::Value as JsonValue;
-use std::ffi::{c_char, CStr, CString};
+use std::ffi::{c_char, c_void, CStr, CString};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::ptr;
 
+mod arrow_batch;
 mod db;
+mod dbapi;
+mod edges;
+mod operation;
+mod router;
 mod stream;
 mod txn;
 mod types;
 
 pub use db::Db;
+pub use edges::{EdgeChunk, EdgeChunkStream, EdgeColumn};
+pub use operation::Operation;
 pub use stream::QueryStream;
 pub use txn::WriteTxn;
 
@@ -104,15 +111,34 @@ fn last_error_message() -> String {
     }
 }
 
+/// Trace ID of the last query this thread ran through the C API.
+pub(crate) fn last_trace_id() -> String {
+    let mut buf = vec![0 as c_char; 64];
+    let _ = capi::ndb_last_trace_id(buf.as_mut_ptr(), buf.len());
+    unsafe {
+        // SAFETY: C API guarantees null-terminated output when len > 0.
+        CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
+    }
+}
+
 pub(crate) fn capi_last_error() -> PyErr {
     let msg = last_error_message();
     let category = capi::ndb_last_error_category();
-    match classify_capi_category(category, &msg) {
+    // Query errors end with the query's trace ID; expose it as `trace_id`.
+    let trace_id = msg
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once(" [trace "))
+        .map(|(_, trace)| trace.to_string());
+    let err = match classify_capi_category(category, &msg) {
         ErrorClass::Syntax => SyntaxError::new_err(msg),
         ErrorClass::Compatibility => CompatibilityError::new_err(msg),
         ErrorClass::Storage => StorageError::new_err(msg),
         ErrorClass::Execution => ExecutionError::new_err(msg),
-    }
+    };
+    Python::with_gil(|py| {
+        let _ = err.value_bound(py).setattr("trace_id", trace_id);
+    });
+    err
 }
 
 pub(crate) fn capi_status(rc: i32) -> PyResult<()> {
@@ -132,17 +158,31 @@ fn derive_paths(path: &Path) -> (PathBuf, PathBuf) {
 }
 
 #[pyfunction]
-#[pyo3(signature = (path))]
-fn open(path: &str) -> PyResult<Db> {
-    Db::new(path)
+#[pyo3(signature = (path, read_only=false))]
+fn open(path: &str, read_only: bool) -> PyResult<Db> {
+    Db::new(path, read_only)
 }
 
 #[pyfunction]
-#[pyo3(signature = (path))]
-fn vacuum(py: Python<'_>, path: &str) -> PyResult<PyObject> {
+#[pyo3(signature = (path, progress=None, operation=None))]
+fn vacuum(
+    py: Python<'_>,
+    path: &str,
+    progress: Option<PyObject>,
+    operation: Option<PyRef<'_, Operation>>,
+) -> PyResult<PyObject> {
     let path_c =
         CString::new(path).map_err(|_| classify_nervus_error("path contains interior NUL"))?;
-    capi_status(capi::ndb_vacuum(path_c.as_ptr()))?;
+    let op_addr = operation.as_ref().map_or(0, |op| op.as_ptr() as usize);
+    let (callback, user_data) = operation::progress_callback(&progress);
+    py.allow_threads(|| {
+        capi_status(capi::ndb_vacuum_with_progress(
+            path_c.as_ptr(),
+            op_addr as *mut capi::ndb_operation_t,
+            callback,
+            user_data as *mut c_void,
+        ))
+    })?;
 
     let (ndb_path, _) = derive_paths(Path::new(path));
     let meta = fs::metadata(&ndb_path).map_err(classify_nervus_error)?;
@@ -199,15 +239,134 @@ fn backup(py: Python<'_>, path: &str, backup_dir: &str) -> PyResult<PyObject> {
     out.set_item("created_at", format!("{:?}", std::time::SystemTime::now()))?;
     out.set_item("size_bytes", size_bytes)?;
     out.set_item("file_count", file_count)?;
+    let checkpoint = &read_backup_manifest(&latest_path)["checkpoint"];
     out.set_item("nervusdb_version", "1.0.0")?;
-    out.set_item("checkpoint_txid", 0)?;
-    out.set_item("checkpoint_epoch", 0)?;
+    out.set_item("checkpoint_txid", checkpoint["txid"].as_u64().unwrap_or(0))?;
+    out.set_item(
+        "checkpoint_epoch",
+        checkpoint["epoch"].as_u64().unwrap_or(0),
+    )?;
     Ok(out.into())
 }
 
+fn read_backup_manifest(dir: &Path) -> JsonValue {
+    fs::read(dir.join("backup_manifest.json"))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or(JsonValue::Null)
+}
+
+/// Backs up only what changed since the backup in `backup_dir` taken at
+/// checkpoint `since_txid` (its `checkpoint_txid`).
+#[pyfunction]
+#[pyo3(signature = (path, backup_dir, since_txid))]
+fn backup_incremental(
+    py: Python<'_>,
+    path: &str,
+    backup_dir: &str,
+    since_txid: u64,
+) -> PyResult<PyObject> {
+    let path_c =
+        CString::new(path).map_err(|_| classify_nervus_error("path contains interior NUL"))?;
+    let backup_dir_c = CString::new(backup_dir)
+        .map_err(|_| classify_nervus_error("backup_dir contains interior NUL"))?;
+    let mut info_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_backup_incremental(
+        path_c.as_ptr(),
+        backup_dir_c.as_ptr(),
+        since_txid,
+        &mut info_ptr,
+    ))?;
+    if info_ptr.is_null() {
+        return Err(classify_nervus_error(
+            "ndb_backup_incremental returned null",
+        ));
+    }
+    let text = unsafe {
+        // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+        CStr::from_ptr(info_ptr).to_string_lossy().into_owned()
+    };
+    capi::ndb_string_free(info_ptr);
+    let info: JsonValue =
+        serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+    Ok(types::json_to_py(info, py))
+}
+
+/// Restores backup `backup_id` as a new database at `path`, applying the
+/// base chain of an incremental backup first.
+#[pyfunction]
+#[pyo3(signature = (backup_dir, backup_id, path))]
+fn restore(backup_dir: &str, backup_id: &str, path: &str) -> PyResult<()> {
+    let backup_dir_c = CString::new(backup_dir)
+        .map_err(|_| classify_nervus_error("backup_dir contains interior NUL"))?;
+    let backup_id_c = CString::new(backup_id)
+        .map_err(|_| classify_nervus_error("backup_id contains interior NUL"))?;
+    let path_c =
+        CString::new(path).map_err(|_| classify_nervus_error("path contains interior NUL"))?;
+    capi_status(capi::ndb_restore(
+        backup_dir_c.as_ptr(),
+        backup_id_c.as_ptr(),
+        path_c.as_ptr(),
+    ))
+}
+
+/// Verifies the backup at `backup_path` against its manifest, optionally
+/// test-restoring it; returns the report dict (`problems` is empty when ok).
 #[pyfunction]
-#[pyo3(signature = (path, nodes, edges))]
-fn bulkload(path: &str, nodes: &Bound<'_, PyList>, edges: &Bound<'_, PyList>) -> PyResult<()> {
+#[pyo3(signature = (backup_path, test_restore=false))]
+fn verify_backup(py: Python<'_>, backup_path: &str, test_restore: bool) -> PyResult<PyObject> {
+    let backup_path_c = CString::new(backup_path)
+        .map_err(|_| classify_nervus_error("backup_path contains interior NUL"))?;
+    let mut report_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_verify_backup(
+        backup_path_c.as_ptr(),
+        i32::from(test_restore),
+        &mut report_ptr,
+    ))?;
+    if report_ptr.is_null() {
+        return Err(classify_nervus_error("ndb_verify_backup returned null"));
+    }
+    let text = unsafe {
+        // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+        CStr::from_ptr(report_ptr).to_string_lossy().into_owned()
+    };
+    capi::ndb_string_free(report_ptr);
+    let report: JsonValue =
+        serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+    Ok(types::json_to_py(report, py))
+}
+
+/// Estimates per-label storage use of the database at `path` and projects
+/// its growth; returns the report dict.
+#[pyfunction]
+fn capacity_report(py: Python<'_>, path: &str) -> PyResult<PyObject> {
+    let path_c =
+        CString::new(path).map_err(|_| classify_nervus_error("path contains interior NUL"))?;
+    let mut report_ptr: *mut c_char = ptr::null_mut();
+    capi_status(capi::ndb_capacity_report(path_c.as_ptr(), &mut report_ptr))?;
+    if report_ptr.is_null() {
+        return Err(classify_nervus_error("ndb_capacity_report returned null"));
+    }
+    let text = unsafe {
+        // SAFETY: pointer comes from C API and is valid until freed by `ndb_string_free`.
+        CStr::from_ptr(report_ptr).to_string_lossy().into_owned()
+    };
+    capi::ndb_string_free(report_ptr);
+    let report: JsonValue =
+        serde_json::from_str(&text).map_err(|e| classify_nervus_error(e.to_string()))?;
+    Ok(types::json_to_py(report, py))
+}
+
+#[pyfunction]
+#[pyo3(signature = (path, nodes, edges, progress=None, operation=None))]
+fn bulkload(
+    py: Python<'_>,
+    path: &str,
+    nodes: &Bound<'_, PyList>,
+    edges: &Bound<'_, PyList>,
+    progress: Option<PyObject>,
+    operation: Option<PyRef<'_, Operation>>,
+) -> PyResult<()> {
     let mut parsed_nodes = Vec::with_capacity(nodes.len());
     for item in nodes.iter() {
         let dict = item.downcast::<PyDict>().map_err(|_| {
@@ -280,11 +439,18 @@ fn bulkload(path: &str, nodes: &Bound<'_, PyList>, edges: &Bound<'_, PyList>) ->
     )
     .map_err(|_| classify_nervus_error("edges payload contains interior NUL"))?;
 
-    capi_status(capi::ndb_bulkload(
-        path_c.as_ptr(),
-        nodes_c.as_ptr(),
-        edges_c.as_ptr(),
-    ))
+    let op_addr = operation.as_ref().map_or(0, |op| op.as_ptr() as usize);
+    let (callback, user_data) = operation::progress_callback(&progress);
+    py.allow_threads(|| {
+        capi_status(capi::ndb_bulkload_with_progress(
+            path_c.as_ptr(),
+            nodes_c.as_ptr(),
+            edges_c.as_ptr(),
+            op_addr as *mut capi::ndb_operation_t,
+            callback,
+            user_data as *mut c_void,
+        ))
+    })
 }
 
 #[pymodule]
@@ -293,12 +459,23 @@ fn nervusdb_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(open, m)?)?;
     m.add_function(wrap_pyfunction!(vacuum, m)?)?;
     m.add_function(wrap_pyfunction!(backup, m)?)?;
+    m.add_function(wrap_pyfunction!(backup_incremental, m)?)?;
+    m.add_function(wrap_pyfunction!(restore, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_backup, m)?)?;
+    m.add_function(wrap_pyfunction!(capacity_report, m)?)?;
     m.add_function(wrap_pyfunction!(bulkload, m)?)?;
     m.add_class::<Db>()?;
+    m.add_class::<Operation>()?;
     m.add_class::<WriteTxn>()?;
+    m.add_class::<router::Router>()?;
     m.add_class::<QueryStream>()?;
+    m.add_class::<EdgeChunkStream>()?;
+    m.add_class::<EdgeChunk>()?;
+    m.add_class::<EdgeColumn>()?;
     m.add_class::<types::Node>()?;
     m.add_class::<types::Relationship>()?;
+    m.add_class::<types::NodeRef>()?;
+    m.add_class::<types::RelationshipRef>()?;
     m.add_class::<types::Path>()?;
 
     m.add("NervusError", m.py().get_type_bound::<NervusError>())?;
@@ -310,6 +487,15 @@ fn nervusdb_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
         m.py().get_type_bound::<CompatibilityError>(),
     )?;
 
+    let dbapi = PyModule::new_bound(m.py(), "dbapi")?;
+    dbapi::register(&dbapi)?;
+    m.add_submodule(&dbapi)?;
+    // Makes `import nervusdb.dbapi` find the submodule.
+    m.py()
+        .import_bound("sys")?
+        .getattr("modules")?
+        .set_item("nervusdb.dbapi", &dbapi)?;
+
     m.add("__version__", "2.0.0")?;
     Ok(())
 }
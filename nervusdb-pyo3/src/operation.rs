@@ -0,0 +1,85 @@
+//! Progress and cancellation for long-running maintenance calls.
+
+use crate::capi_status;
+use nervusdb_capi as capi;
+use pyo3::prelude::*;
+use std::ffi::c_void;
+use std::ptr;
+
+/// Handle for following and cancelling `Db.compact`, `vacuum` or `bulkload`
+/// from another thread. Those calls release the GIL while they run.
+#[pyclass]
+pub struct Operation {
+    /// `ndb_operation_t` address; the handle itself is thread-safe.
+    raw: usize,
+}
+
+#[pymethods]
+impl Operation {
+    #[new]
+    fn new() -> PyResult<Self> {
+        let mut raw: *mut capi::ndb_operation_t = ptr::null_mut();
+        capi_status(capi::ndb_operation_new(&mut raw))?;
+        Ok(Self { raw: raw as usize })
+    }
+
+    /// Asks the operation to stop at its next safe point; the running call
+    /// then raises an error saying the operation was cancelled.
+    fn cancel(&self) -> PyResult<()> {
+        capi_status(capi::ndb_operation_cancel(self.as_ptr()))
+    }
+
+    /// `(done, total)` as last published; `total` is 0 until known.
+    #[getter]
+    fn progress(&self) -> PyResult<(u64, u64)> {
+        let (mut done, mut total) = (0u64, 0u64);
+        capi_status(capi::ndb_operation_progress(
+            self.as_ptr(),
+            &mut done,
+            &mut total,
+        ))?;
+        Ok((done, total))
+    }
+
+    #[getter]
+    fn percent(&self) -> PyResult<f64> {
+        let (done, total) = self.progress()?;
+        Ok(if total == 0 {
+            0.0
+        } else {
+            done as f64 * 100.0 / total as f64
+        })
+    }
+}
+
+impl Operation {
+    pub(crate) fn as_ptr(&self) -> *mut capi::ndb_operation_t {
+        self.raw as *mut capi::ndb_operation_t
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        capi::ndb_operation_free(self.as_ptr());
+    }
+}
+
+/// The C callback and `user_data` address for an optional Python callable.
+pub(crate) fn progress_callback(progress: &Option<PyObject>) -> (capi::ndb_progress_fn, usize) {
+    match progress {
+        Some(callable) => (Some(report_progress), callable as *const PyObject as usize),
+        None => (None, 0),
+    }
+}
+
+/// Forwards progress to the Python callable passed as `user_data`.
+/// Exceptions it raises are reported as unraisable; the operation continues.
+extern "C" fn report_progress(done: u64, total: u64, user_data: *mut c_void) {
+    // SAFETY: callers pass a callable that outlives the C call.
+    let callable = unsafe { &*(user_data as *const PyObject) };
+    Python::with_gil(|py| {
+        if let Err(err) = callable.call1(py, (done, total)) {
+            err.write_unraisable_bound(py, Some(callable.bind(py)));
+        }
+    });
+}
@@ -0,0 +1,134 @@
+use crate::db::Db;
+use crate::{capi_status, classify_nervus_error};
+use nervusdb_capi as capi;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::ptr;
+
+/// Sends writes to a primary and reads to read replicas kept current by
+/// followers, as one session: a read only goes to a replica that has
+/// applied this router's last write, and to the primary otherwise.
+#[pyclass(unsendable)]
+pub struct Router {
+    primary: Py<Db>,
+    replicas: Vec<(Py<Db>, *mut capi::ndb_follower_t)>,
+    /// Where the next read starts looking for a replica.
+    next: usize,
+    /// Primary bookmark of this router's last write.
+    bookmark: u64,
+}
+
+impl Router {
+    /// The first replica, round robin, that has applied `self.bookmark`.
+    fn read_db(&mut self, py: Python<'_>) -> PyResult<Py<Db>> {
+        let count = self.replicas.len();
+        for offset in 0..count {
+            let index = (self.next + offset) % count;
+            let (replica, follower) = &self.replicas[index];
+            let mut applied: u64 = 0;
+            capi_status(capi::ndb_follower_applied_bookmark(*follower, &mut applied))?;
+            if applied >= self.bookmark {
+                self.next = index + 1;
+                return Ok(replica.clone_ref(py));
+            }
+        }
+        Ok(self.primary.clone_ref(py))
+    }
+
+    fn stop_followers(&mut self) {
+        for (_, follower) in &mut self.replicas {
+            capi::ndb_follower_stop(std::mem::replace(follower, ptr::null_mut()));
+        }
+    }
+}
+
+impl Drop for Router {
+    fn drop(&mut self) {
+        self.stop_followers();
+    }
+}
+
+#[pymethods]
+impl Router {
+    /// Opens the primary at `primary` and a replica at each of `replicas`,
+    /// and starts replicating into the replicas. Replicas start empty or as
+    /// restored backups of the primary, and take no other writes.
+    #[new]
+    fn new(primary: &str, replicas: Vec<String>, py: Python<'_>) -> PyResult<Self> {
+        let primary = Py::new(py, Db::new(primary, false)?)?;
+        let mut router = Self {
+            primary,
+            replicas: Vec::with_capacity(replicas.len()),
+            next: 0,
+            bookmark: 0,
+        };
+        let primary_raw = router.primary.borrow(py).raw_ptr()?;
+        for path in replicas {
+            let replica = Py::new(py, Db::new(&path, false)?)?;
+            let mut follower: *mut capi::ndb_follower_t = ptr::null_mut();
+            capi_status(capi::ndb_follower_start(
+                replica.borrow(py).raw_ptr()?,
+                primary_raw,
+                &mut follower,
+            ))?;
+            if follower.is_null() {
+                return Err(classify_nervus_error(
+                    "ndb_follower_start returned null follower handle",
+                ));
+            }
+            router.replicas.push((replica, follower));
+        }
+        Ok(router)
+    }
+
+    /// Runs a read query on a replica that has seen this router's writes.
+    #[pyo3(signature = (query, params=None))]
+    fn query(
+        &mut self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+        let db = self.read_db(py)?;
+        let db = db.borrow(py);
+        db.execute_query_rows(query, params, None, py)
+    }
+
+    /// Runs a write query on the primary and remembers its bookmark.
+    #[pyo3(signature = (query, params=None))]
+    fn execute_write(
+        &mut self,
+        query: &str,
+        params: Option<HashMap<String, Py<PyAny>>>,
+        py: Python<'_>,
+    ) -> PyResult<u32> {
+        let primary = self.primary.borrow(py);
+        let affected = primary.execute_write(query, params, py)?;
+        capi_status(capi::ndb_last_bookmark(
+            primary.raw_ptr()?,
+            &mut self.bookmark,
+        ))?;
+        Ok(affected)
+    }
+
+    /// Primary bookmark of this router's last write; 0 before the first.
+    #[getter]
+    fn bookmark(&self) -> u64 {
+        self.bookmark
+    }
+
+    /// The primary, for writes the router does not run itself.
+    #[getter]
+    fn primary(&self, py: Python<'_>) -> Py<Db> {
+        self.primary.clone_ref(py)
+    }
+
+    /// Stops replicating and closes the replicas and the primary.
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.stop_followers();
+        for (replica, _) in self.replicas.drain(..) {
+            replica.borrow_mut(py).close()?;
+        }
+        self.primary.borrow_mut(py).close()
+    }
+}
@@ -3,7 +3,7 @@ use crate::db::Db;
 use crate::types::py_to_json;
 use nervusdb_capi as capi;
 use pyo3::prelude::*;
-use std::ffi::CString;
+use std::ffi::{c_int, CString};
 use std::ptr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -79,13 +79,100 @@ impl WriteTxn {
             .take()
             .ok_or_else(|| classify_nervus_error("Transaction already finished"))?;
         let rc = capi::ndb_txn_commit(raw);
+        // The handle is consumed whether or not the commit succeeds.
+        self.finish();
         if rc != capi::NDB_OK {
             return Err(crate::capi_last_error());
         }
-        self.finish();
         Ok(())
     }
 
+    /// Tags the transaction with an idempotency key. Returns True when a
+    /// transaction with this key already committed; this one then applies
+    /// nothing on commit.
+    fn set_idempotency_key(&mut self, key: &str) -> PyResult<bool> {
+        let key_c = CString::new(key)
+            .map_err(|_| classify_nervus_error("idempotency key contains interior NUL"))?;
+        let mut applied: c_int = 0;
+        self.with_txn_ptr(|raw| {
+            let rc = capi::ndb_txn_set_idempotency_key(raw, key_c.as_ptr(), &mut applied);
+            if rc == capi::NDB_OK {
+                Ok(())
+            } else {
+                Err(crate::capi_last_error())
+            }
+        })?;
+        Ok(applied != 0)
+    }
+
+    /// Defers unique constraint checks on later writes to commit. With
+    /// False, checks the writes deferred so far and raises if one breaks a
+    /// constraint.
+    #[pyo3(signature = (deferred=true))]
+    fn set_constraints_deferred(&mut self, deferred: bool) -> PyResult<()> {
+        self.with_txn_ptr(|raw| {
+            let rc = capi::ndb_txn_set_constraints_deferred(raw, c_int::from(deferred));
+            if rc == capi::NDB_OK {
+                Ok(())
+            } else {
+                Err(crate::capi_last_error())
+            }
+        })
+    }
+
+    /// Takes a savepoint named `name`, replacing any earlier one of that name.
+    fn savepoint(&mut self, name: &str) -> PyResult<()> {
+        let name_c = CString::new(name)
+            .map_err(|_| classify_nervus_error("savepoint name contains interior NUL"))?;
+        self.with_txn_ptr(|raw| {
+            let rc = capi::ndb_txn_savepoint(raw, name_c.as_ptr());
+            if rc == capi::NDB_OK {
+                Ok(())
+            } else {
+                Err(crate::capi_last_error())
+            }
+        })
+    }
+
+    /// Discards every change made since savepoint `name`, including property
+    /// and edge mutations. The transaction stays open, and a statement that
+    /// failed after the savepoint no longer blocks the commit.
+    fn rollback_to(&mut self, name: &str) -> PyResult<()> {
+        let name_c = CString::new(name)
+            .map_err(|_| classify_nervus_error("savepoint name contains interior NUL"))?;
+        self.with_txn_ptr(|raw| {
+            let rc = capi::ndb_txn_rollback_to(raw, name_c.as_ptr());
+            if rc == capi::NDB_OK {
+                Ok(())
+            } else {
+                Err(crate::capi_last_error())
+            }
+        })
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Commits when the block exits cleanly and rolls back when it raises.
+    /// A transaction already finished inside the block is left alone.
+    fn __exit__(
+        &mut self,
+        exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        if !self.active {
+            return Ok(false);
+        }
+        if !exc_type.is_none() {
+            self.rollback()?;
+        } else {
+            self.commit()?;
+        }
+        Ok(false)
+    }
+
     fn set_vector(&mut self, node_id: u32, vector: Vec<f32>) -> PyResult<()> {
         self.with_txn_ptr(|raw| {
             let rc = capi::ndb_txn_set_vector(raw, node_id, vector.as_ptr(), vector.len());
@@ -230,6 +317,19 @@ impl WriteTxn {
         })
     }
 
+    /// Makes `node` expire at `at_ms` milliseconds since the Unix epoch, by
+    /// setting its `expireAt` property.
+    fn set_node_expiry(&mut self, node: u32, at_ms: i64) -> PyResult<()> {
+        self.with_txn_ptr(|raw| {
+            let rc = capi::ndb_txn_set_node_expiry(raw, node, at_ms);
+            if rc == capi::NDB_OK {
+                Ok(())
+            } else {
+                Err(crate::capi_last_error())
+            }
+        })
+    }
+
     fn remove_node_property(&mut self, node: u32, key: &str) -> PyResult<()> {
         let key_c = CString::new(key)
             .map_err(|_| classify_nervus_error("property key contains interior NUL"))?;
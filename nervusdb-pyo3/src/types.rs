@@ -1,5 +1,10 @@
+use crate::db::Db;
+use pyo3::exceptions::PyKeyError;
 use pyo3::prelude::*;
-use pyo3::types::{PyAny, PyDict, PyList};
+use pyo3::types::{
+    PyAny, PyDate, PyDateAccess, PyDateTime, PyDelta, PyDeltaAccess, PyDict, PyList, PyTime,
+    PyTimeAccess,
+};
 use serde_json::{Map as JsonMap, Number as JsonNumber, Value as JsonValue};
 use std::collections::BTreeMap;
 
@@ -97,6 +102,193 @@ impl Path {
     }
 }
 
+/// A node returned with `set_lazy_entities(True)`: only its id is known up
+/// front. `get` and `[key]` read single properties; `labels`, `properties`
+/// and `fetch()` read the whole node once and keep it.
+#[pyclass(unsendable)]
+pub struct NodeRef {
+    #[pyo3(get)]
+    pub id: u64,
+    reference: JsonValue,
+    db: Py<Db>,
+    fetched: Option<Node>,
+}
+
+#[pymethods]
+impl NodeRef {
+    #[getter]
+    fn labels(&mut self, py: Python<'_>) -> PyResult<Vec<String>> {
+        Ok(self.node(py)?.labels.clone())
+    }
+
+    #[getter]
+    fn properties(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        self.node(py)?.properties(py)
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn get(&self, key: &str, default: Option<PyObject>, py: Python<'_>) -> PyResult<PyObject> {
+        let value = match &self.fetched {
+            Some(node) => node.properties.get(key).map(|v| v.clone_ref(py)),
+            None => property(&self.db, &self.reference, key, py)?,
+        };
+        Ok(value.or(default).unwrap_or_else(|| py.None()))
+    }
+
+    fn __getitem__(&self, key: &str, py: Python<'_>) -> PyResult<PyObject> {
+        let value = match &self.fetched {
+            Some(node) => node.properties.get(key).map(|v| v.clone_ref(py)),
+            None => property(&self.db, &self.reference, key, py)?,
+        };
+        value.ok_or_else(|| PyKeyError::new_err(key.to_string()))
+    }
+
+    /// The whole node, as a query would have returned it without references.
+    fn fetch(&mut self, py: Python<'_>) -> PyResult<Node> {
+        Ok(self.node(py)?.clone_ref(py))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("NodeRef(id={})", self.id)
+    }
+}
+
+impl NodeRef {
+    fn node(&mut self, py: Python<'_>) -> PyResult<&Node> {
+        if self.fetched.is_none() {
+            let value = self.db.borrow(py).entity_json(&self.reference, None)?;
+            let node = match value {
+                JsonValue::Object(obj) => node_from_json(&obj, py),
+                _ => Node {
+                    id: self.id,
+                    labels: Vec::new(),
+                    properties: BTreeMap::new(),
+                },
+            };
+            self.fetched = Some(node);
+        }
+        Ok(self.fetched.as_ref().expect("fetched above"))
+    }
+}
+
+/// A relationship returned with `set_lazy_entities(True)`; its endpoints
+/// are known up front and the rest is read as for [`NodeRef`].
+#[pyclass(unsendable)]
+pub struct RelationshipRef {
+    #[pyo3(get)]
+    pub id: Option<u64>,
+    #[pyo3(get)]
+    pub start_node_id: u64,
+    #[pyo3(get)]
+    pub end_node_id: u64,
+    reference: JsonValue,
+    db: Py<Db>,
+    fetched: Option<Relationship>,
+}
+
+#[pymethods]
+impl RelationshipRef {
+    #[getter]
+    fn rel_type(&mut self, py: Python<'_>) -> PyResult<String> {
+        Ok(self.relationship(py)?.rel_type.clone())
+    }
+
+    #[getter]
+    fn properties(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        self.relationship(py)?.properties(py)
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn get(&self, key: &str, default: Option<PyObject>, py: Python<'_>) -> PyResult<PyObject> {
+        let value = match &self.fetched {
+            Some(rel) => rel.properties.get(key).map(|v| v.clone_ref(py)),
+            None => property(&self.db, &self.reference, key, py)?,
+        };
+        Ok(value.or(default).unwrap_or_else(|| py.None()))
+    }
+
+    fn __getitem__(&self, key: &str, py: Python<'_>) -> PyResult<PyObject> {
+        let value = match &self.fetched {
+            Some(rel) => rel.properties.get(key).map(|v| v.clone_ref(py)),
+            None => property(&self.db, &self.reference, key, py)?,
+        };
+        value.ok_or_else(|| PyKeyError::new_err(key.to_string()))
+    }
+
+    /// The whole relationship, as a query would have returned it without
+    /// references.
+    fn fetch(&mut self, py: Python<'_>) -> PyResult<Relationship> {
+        Ok(self.relationship(py)?.clone_ref(py))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RelationshipRef(start_node_id={}, end_node_id={})",
+            self.start_node_id, self.end_node_id
+        )
+    }
+}
+
+impl RelationshipRef {
+    fn relationship(&mut self, py: Python<'_>) -> PyResult<&Relationship> {
+        if self.fetched.is_none() {
+            let value = self.db.borrow(py).entity_json(&self.reference, None)?;
+            let JsonValue::Object(obj) = value else {
+                return Err(crate::classify_nervus_error(
+                    "relationship reference did not resolve",
+                ));
+            };
+            self.fetched = Some(relationship_from_json(&obj, py));
+        }
+        Ok(self.fetched.as_ref().expect("fetched above"))
+    }
+}
+
+/// One property of a referenced entity; `None` when it is unset.
+fn property(
+    db: &Py<Db>,
+    reference: &JsonValue,
+    key: &str,
+    py: Python<'_>,
+) -> PyResult<Option<PyObject>> {
+    match db.borrow(py).entity_json(reference, Some(key))? {
+        JsonValue::Null => Ok(None),
+        value => Ok(Some(json_to_py(value, py))),
+    }
+}
+
+impl Node {
+    fn clone_ref(&self, py: Python<'_>) -> Node {
+        Node {
+            id: self.id,
+            labels: self.labels.clone(),
+            properties: clone_properties(&self.properties, py),
+        }
+    }
+}
+
+impl Relationship {
+    fn clone_ref(&self, py: Python<'_>) -> Relationship {
+        Relationship {
+            id: self.id,
+            start_node_id: self.start_node_id,
+            end_node_id: self.end_node_id,
+            rel_type: self.rel_type.clone(),
+            properties: clone_properties(&self.properties, py),
+        }
+    }
+}
+
+fn clone_properties(
+    properties: &BTreeMap<String, PyObject>,
+    py: Python<'_>,
+) -> BTreeMap<String, PyObject> {
+    properties
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone_ref(py)))
+        .collect()
+}
+
 pub fn py_to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
     if obj.is_none() {
         return Ok(JsonValue::Null);
@@ -120,6 +312,10 @@ pub fn py_to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
         return Ok(JsonValue::String(s));
     }
 
+    if let Some(temporal) = temporal_to_json(obj)? {
+        return Ok(temporal);
+    }
+
     if let Ok(list) = obj.downcast::<PyList>() {
         let mut out = Vec::with_capacity(list.len());
         for item in list.iter() {
@@ -144,10 +340,86 @@ pub fn py_to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
     ))
 }
 
-fn json_to_py_map(map: JsonMap<String, JsonValue>, py: Python<'_>) -> Py<PyAny> {
+/// Days from 1970-01-01 to the given proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn delta_micros(delta: &Bound<'_, PyDelta>) -> i64 {
+    (i64::from(delta.get_days()) * 86_400 + i64::from(delta.get_seconds())) * 1_000_000
+        + i64::from(delta.get_microseconds())
+}
+
+/// Encodes `datetime` values in the C API's typed temporal envelopes. Naive datetimes
+/// are taken as UTC; aware ones are shifted by their offset.
+fn temporal_to_json(obj: &Bound<'_, PyAny>) -> PyResult<Option<JsonValue>> {
+    if let Ok(dt) = obj.downcast::<PyDateTime>() {
+        let days = days_from_civil(
+            i64::from(dt.get_year()),
+            i64::from(dt.get_month()),
+            i64::from(dt.get_day()),
+        );
+        let seconds = days * 86_400
+            + i64::from(dt.get_hour()) * 3_600
+            + i64::from(dt.get_minute()) * 60
+            + i64::from(dt.get_second());
+        let mut micros = seconds * 1_000_000 + i64::from(dt.get_microsecond());
+        let offset = dt.call_method0("utcoffset")?;
+        if let Ok(offset) = offset.downcast::<PyDelta>() {
+            micros -= delta_micros(offset);
+        }
+        return Ok(Some(
+            serde_json::json!({ "type": "datetime", "value": micros }),
+        ));
+    }
+    if let Ok(date) = obj.downcast::<PyDate>() {
+        let iso = format!(
+            "{:04}-{:02}-{:02}",
+            date.get_year(),
+            date.get_month(),
+            date.get_day()
+        );
+        return Ok(Some(serde_json::json!({ "type": "date", "value": iso })));
+    }
+    if let Ok(time) = obj.downcast::<PyTime>() {
+        let iso = format!(
+            "{:02}:{:02}:{:02}.{:06}",
+            time.get_hour(),
+            time.get_minute(),
+            time.get_second(),
+            time.get_microsecond()
+        );
+        return Ok(Some(
+            serde_json::json!({ "type": "localtime", "value": iso }),
+        ));
+    }
+    if let Ok(delta) = obj.downcast::<PyDelta>() {
+        let micros =
+            i64::from(delta.get_seconds()) * 1_000_000 + i64::from(delta.get_microseconds());
+        return Ok(Some(serde_json::json!({
+            "type": "duration",
+            "months": 0,
+            "days": delta.get_days(),
+            "nanos": micros * 1_000,
+        })));
+    }
+    Ok(None)
+}
+
+fn json_to_py_map(
+    map: JsonMap<String, JsonValue>,
+    py: Python<'_>,
+    refs: Option<&Py<Db>>,
+) -> Py<PyAny> {
     let dict = PyDict::new_bound(py);
     for (k, v) in map {
-        let _ = dict.set_item(k, json_to_py(v, py));
+        let _ = dict.set_item(k, json_to_py_in(v, py, refs));
     }
     dict.into()
 }
@@ -215,7 +487,7 @@ fn relationship_from_json(obj: &JsonMap<String, JsonValue>, py: Python<'_>) -> R
         .unwrap_or_default();
 
     Relationship {
-        id: Some(src ^ dst ^ 0x0102_0304_0506_0708),
+        id: Some(relationship_id(src, dst)),
         start_node_id: src,
         end_node_id: dst,
         rel_type,
@@ -223,6 +495,10 @@ fn relationship_from_json(obj: &JsonMap<String, JsonValue>, py: Python<'_>) -> R
     }
 }
 
+fn relationship_id(src: u64, dst: u64) -> u64 {
+    src ^ dst ^ 0x0102_0304_0506_0708
+}
+
 fn path_from_json(obj: &JsonMap<String, JsonValue>, py: Python<'_>) -> Path {
     let nodes = obj
         .get("nodes")
@@ -253,6 +529,13 @@ fn path_from_json(obj: &JsonMap<String, JsonValue>, py: Python<'_>) -> Path {
 }
 
 pub fn json_to_py(val: JsonValue, py: Python<'_>) -> Py<PyAny> {
+    json_to_py_in(val, py, None)
+}
+
+/// Like [`json_to_py`], but with `refs` set, the `node_id` and `edge_key`
+/// references of lazy rows become [`NodeRef`] and [`RelationshipRef`]
+/// objects reading from that database.
+pub(crate) fn json_to_py_in(val: JsonValue, py: Python<'_>, refs: Option<&Py<Db>>) -> Py<PyAny> {
     match val {
         JsonValue::Null => py.None(),
         JsonValue::Bool(b) => b.into_py(py),
@@ -269,7 +552,8 @@ pub fn json_to_py(val: JsonValue, py: Python<'_>) -> Py<PyAny> {
         }
         JsonValue::String(s) => s.into_py(py),
         JsonValue::Array(arr) => {
-            let py_list = PyList::new_bound(py, arr.into_iter().map(|v| json_to_py(v, py)));
+            let py_list =
+                PyList::new_bound(py, arr.into_iter().map(|v| json_to_py_in(v, py, refs)));
             py_list.into()
         }
         JsonValue::Object(obj) => {
@@ -278,6 +562,10 @@ pub fn json_to_py(val: JsonValue, py: Python<'_>) -> Py<PyAny> {
                     "node" => return node_from_json(&obj, py).into_py(py),
                     "relationship" => return relationship_from_json(&obj, py).into_py(py),
                     "path" => return path_from_json(&obj, py).into_py(py),
+                    "node_id" | "edge_key" if refs.is_some() => {
+                        let db = refs.expect("guarded above");
+                        return entity_ref(JsonValue::Object(obj), db, py);
+                    }
                     "node_id" | "external_id" => {
                         let value = obj.get("value").cloned().unwrap_or(JsonValue::Null);
                         return json_to_py(value, py);
@@ -285,7 +573,35 @@ pub fn json_to_py(val: JsonValue, py: Python<'_>) -> Py<PyAny> {
                     _ => {}
                 }
             }
-            json_to_py_map(obj, py)
+            json_to_py_map(obj, py, refs)
         }
     }
 }
+
+fn entity_ref(reference: JsonValue, db: &Py<Db>, py: Python<'_>) -> Py<PyAny> {
+    let field = |name: &str| {
+        reference
+            .get(name)
+            .and_then(JsonValue::as_u64)
+            .unwrap_or_default()
+    };
+    if reference.get("type").and_then(JsonValue::as_str) == Some("node_id") {
+        let node = NodeRef {
+            id: field("value"),
+            reference,
+            db: db.clone_ref(py),
+            fetched: None,
+        };
+        return node.into_py(py);
+    }
+    let (src, dst) = (field("src"), field("dst"));
+    RelationshipRef {
+        id: Some(relationship_id(src, dst)),
+        start_node_id: src,
+        end_node_id: dst,
+        reference,
+        db: db.clone_ref(py),
+        fetched: None,
+    }
+    .into_py(py)
+}
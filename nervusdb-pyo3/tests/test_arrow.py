@@ -0,0 +1,39 @@
+#!/usr/bin/env python3
+"""Arrow and pandas export for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def test_query_arrow_and_query_df():
+    try:
+        import pyarrow as pa
+    except ImportError:
+        return
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "arrow.ndb"))
+        db.execute_write(
+            "CREATE (:Item {name: 'a', qty: 2, price: 1.5, tags: ['x']}), (:Item {name: 'b', qty: 3})"
+        )
+        query = (
+            "MATCH (i:Item) RETURN i.name AS name, i.qty AS qty, i.price AS price, "
+            "i.tags AS tags ORDER BY name"
+        )
+
+        batch = db.query_arrow(query)
+        assert batch.schema.names == ["name", "qty", "price", "tags"]
+        assert batch.schema.field("qty").type == pa.int64()
+        assert batch.schema.field("price").type == pa.float64()
+        assert batch.column(0).to_pylist() == ["a", "b"]
+        assert batch.column(2).to_pylist() == [1.5, None]
+        assert batch.column(3).to_pylist() == ['["x"]', None]
+
+        try:
+            import pandas  # noqa: F401
+        except ImportError:
+            return
+        df = db.query_df("MATCH (i:Item) WHERE i.qty > $min RETURN sum(i.qty) AS total", {"min": 0})
+        assert df["total"].tolist() == [5]
+        db.close()
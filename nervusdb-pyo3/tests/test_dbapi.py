@@ -0,0 +1,69 @@
+#!/usr/bin/env python3
+"""DB-API 2.0 layer for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+import nervusdb.dbapi as dbapi
+
+
+def test_module_globals():
+    assert dbapi.apilevel == "2.0"
+    assert dbapi.threadsafety == 1
+    assert dbapi.paramstyle == "pyformat"
+    assert issubclass(dbapi.ProgrammingError, dbapi.DatabaseError)
+    assert issubclass(dbapi.Error, nervusdb.NervusError)
+
+
+def test_execute_and_fetch_with_pyformat_parameters():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        with dbapi.connect(os.path.join(tmpdir, "dbapi.ndb")) as conn:
+            cur = conn.cursor()
+            cur.executemany(
+                "CREATE (:Person {name: %(name)s, age: %(age)s})",
+                [{"name": "Ada", "age": 36}, {"name": "Alan", "age": 41}, {"name": "Grace", "age": 85}],
+            )
+            assert cur.rowcount == 3
+            assert cur.description is None
+            conn.commit()
+
+            cur.execute(
+                "MATCH (p:Person) WHERE p.age > %s RETURN p.name AS name, p.age %% 2 AS odd ORDER BY name",
+                (40,),
+            )
+            assert [c[:2] for c in cur.description] == [("name", "string"), ("odd", "integer")]
+            assert cur.rowcount == 2
+            assert cur.fetchone() == ("Alan", 1)
+            assert cur.fetchmany(5) == [("Grace", 1)]
+            assert cur.fetchall() == []
+
+            cur.execute("MATCH (p:Person {name: $name}) RETURN p.age", {"name": "Ada"})
+            assert list(cur) == [(36,)]
+            # Without parameters the query is passed through untouched.
+            assert cur.execute("RETURN 7 % 2 AS m").fetchall() == [(1,)]
+
+
+def _raises(exc, fn, *args):
+    try:
+        fn(*args)
+    except exc as e:
+        return e
+    raise AssertionError(f"expected {exc.__name__}")
+
+
+def test_errors_follow_the_dbapi_hierarchy():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        conn = dbapi.connect(os.path.join(tmpdir, "dbapi.ndb"))
+        cur = conn.cursor()
+        err = _raises(dbapi.ProgrammingError, cur.execute, "MATCH (")
+        assert isinstance(err.__cause__, nervusdb.SyntaxError)
+        _raises(dbapi.ProgrammingError, cur.execute, "RETURN %s, %s", (1,))
+        _raises(dbapi.ProgrammingError, cur.execute, "RETURN %(a)s", (1,))
+
+        cur.execute("CREATE (n)")
+        _raises(dbapi.ProgrammingError, cur.fetchall)
+
+        conn.close()
+        _raises(dbapi.ProgrammingError, cur.execute, "RETURN 1")
+        _raises(dbapi.ProgrammingError, conn.cursor)
@@ -0,0 +1,72 @@
+#!/usr/bin/env python3
+"""Edge list streaming for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def _graph(db):
+    txn = db.begin_write()
+    txn.query(
+        "CREATE (a)-[:LINK {w: 2.5}]->(b)-[:LINK {w: 4}]->(c), (a)-[:LINK]->(c), (c)-[:TAG]->(a)"
+    )
+    txn.commit()
+
+
+def test_edges_chunked_shares_numeric_buffers():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "edges.ndb"))
+        _graph(db)
+
+        chunks = list(db.edges_chunked("LINK", chunk_size=2, weight_property="w"))
+        assert [len(chunk) for chunk in chunks] == [2, 1]
+        weights = []
+        for chunk in chunks:
+            src, dst, weight = (memoryview(c) for c in (chunk.src, chunk.dst, chunk.weight))
+            assert (src.format, dst.format, weight.format) == ("Q", "Q", "d")
+            assert src.readonly and src.shape == (len(chunk),)
+            assert all(s != d for s, d in zip(src.tolist(), dst.tolist()))
+            weights += weight.tolist()
+        assert sorted(weights) == [1.0, 2.5, 4.0]
+
+        assert sum(len(chunk) for chunk in db.edges_chunked()) == 4
+        assert list(db.edges_chunked("MISSING")) == []
+        db.close()
+
+
+def test_open_edge_stream_keeps_database_open():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "edges.ndb"))
+        _graph(db)
+
+        stream = db.edges_chunked()
+        try:
+            db.close()
+            raise AssertionError("close should fail while an edge stream is open")
+        except nervusdb.NervusError:
+            pass
+        assert sum(len(chunk) for chunk in stream) == 4
+        db.close()
+
+
+def test_edge_chunk_to_arrow():
+    try:
+        import pyarrow  # noqa: F401
+    except ImportError:
+        return
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "edges.ndb"))
+        _graph(db)
+
+        batch = next(iter(db.edges_chunked("LINK", weight_property="w"))).to_arrow()
+        assert batch.schema.names == ["src", "dst", "weight"]
+        assert sorted(batch.column("weight").to_pylist()) == [1.0, 2.5, 4.0]
+        db.close()
+
+
+if __name__ == "__main__":
+    test_edges_chunked_shares_numeric_buffers()
+    test_open_edge_stream_keeps_database_open()
+    test_edge_chunk_to_arrow()
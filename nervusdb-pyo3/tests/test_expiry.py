@@ -0,0 +1,28 @@
+#!/usr/bin/env python3
+"""Node expiry sweeps for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def test_expired_nodes_are_swept():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "expiry.ndb"))
+        db.execute_write(
+            "CREATE (:Session {name: 'old', expireAt: 1000})-[:OF]->(:User), "
+            "(:Session {name: 'live'})"
+        )
+        [row] = db.query("MATCH (s:Session {name: 'live'}) RETURN id(s) AS id")
+        with db.begin_write() as txn:
+            txn.set_node_expiry(row["id"], 2000)
+
+        assert db.sweep_expired() == (2, 1)
+        assert db.query("MATCH (s:Session) RETURN count(s) AS n") == [{"n": 0}]
+        stats = db.expiry_stats()
+        assert (stats["sweeps"], stats["nodes_purged"], stats["edges_purged"]) == (1, 2, 1)
+
+        db.set_expiry_sweep(50)
+        db.set_expiry_sweep(0)
+        db.close()
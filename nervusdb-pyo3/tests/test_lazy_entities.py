@@ -0,0 +1,51 @@
+#!/usr/bin/env python3
+"""Lazily read nodes and relationships for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def test_lazy_entities_read_properties_on_access():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "lazy.ndb"))
+        db.execute_write(
+            "CREATE (:User {name: 'ada', age: 36})-[:KNOWS {since: 2001}]->(:User {name: 'bob'})"
+        )
+        cypher = "MATCH (a)-[r]->(b) RETURN a, r, [b] AS bs"
+        assert isinstance(db.query(cypher)[0]["a"], nervusdb.Node)
+
+        db.set_lazy_entities(True)
+        row = db.query(cypher)[0]
+        a, r, b = row["a"], row["r"], row["bs"][0]
+        assert isinstance(a, nervusdb.NodeRef)
+        assert isinstance(r, nervusdb.RelationshipRef)
+        assert isinstance(b, nervusdb.NodeRef)
+
+        assert a["name"] == "ada"
+        assert a.get("missing") is None
+        assert a.get("missing", 7) == 7
+        try:
+            a["missing"]
+            assert False, "unset properties raise KeyError"
+        except KeyError:
+            pass
+        assert r["since"] == 2001
+        assert (r.start_node_id, r.end_node_id) == (a.id, b.id)
+
+        assert a.labels == ["User"]
+        assert a.properties == {"name": "ada", "age": 36}
+        assert r.rel_type == "KNOWS"
+        whole = b.fetch()
+        assert isinstance(whole, nervusdb.Node)
+        assert whole.properties == {"name": "bob"}
+
+        page, _ = db.query_page("MATCH (u:User) RETURN u ORDER BY u.name", 1)
+        assert page[0]["u"]["name"] == "ada"
+        (many,) = db.query_many([("MATCH (u:User {name: 'bob'}) RETURN u", None)])
+        assert many[0]["u"].get("name") == "bob"
+
+        db.set_lazy_entities(False)
+        assert isinstance(db.query(cypher)[0]["a"], nervusdb.Node)
+        db.close()
@@ -0,0 +1,34 @@
+#!/usr/bin/env python3
+"""Batched reads on one snapshot for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def test_query_many_groups_results():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "many.ndb"))
+        db.execute_write("CREATE (:Item {n: 1}), (:Item {n: 2}), (:Tag {name: 'x'})")
+
+        items, tags, empty = db.query_many(
+            [
+                ("MATCH (i:Item) WHERE i.n >= $min RETURN i.n AS n ORDER BY n", {"min": 1}),
+                ("MATCH (t:Tag) RETURN t.name AS name", None),
+                ("MATCH (i:Item) WHERE i.n > 5 RETURN i.n AS n", None),
+            ]
+        )
+        assert items == [{"n": 1}, {"n": 2}]
+        assert tags == [{"name": "x"}]
+        assert empty == []
+        assert db.query_many([]) == []
+
+        try:
+            db.query_many([("RETURN 1 AS one", None), ("CREATE (:Item)", None)])
+            assert False, "writes are rejected"
+        except nervusdb.NervusError:
+            pass
+        assert db.query("MATCH (i:Item) RETURN count(i) AS c") == [{"c": 2}]
+        # A failed batch still releases its snapshot.
+        db.close()
@@ -0,0 +1,31 @@
+#!/usr/bin/env python3
+"""Read replica routing test for nervusdb Python bindings."""
+
+import nervusdb
+import tempfile
+import os
+
+def test_router_reads_its_own_writes():
+    """Writes go to the primary; reads see them, from a replica or the primary."""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        router = nervusdb.Router(
+            os.path.join(tmpdir, "primary.ndb"),
+            [os.path.join(tmpdir, "replica1.ndb"), os.path.join(tmpdir, "replica2.ndb")],
+        )
+        assert router.bookmark == 0
+
+        for i in range(5):
+            router.execute_write("CREATE (:Person {n: $n})", {"n": i})
+            rows = router.query("MATCH (p:Person) RETURN count(p) AS c")
+            assert rows == [{"c": i + 1}]
+        assert router.bookmark == router.primary.last_bookmark()
+        print("✓ Every read saw the router's earlier writes")
+
+        router.close()
+        replica = nervusdb.open(os.path.join(tmpdir, "replica1.ndb"))
+        assert replica.query("MATCH (p:Person) RETURN count(p) AS c") == [{"c": 5}]
+        replica.close()
+        print("✓ The replicas received the writes")
+
+if __name__ == "__main__":
+    test_router_reads_its_own_writes()
@@ -0,0 +1,24 @@
+#!/usr/bin/env python3
+"""Query trace IDs for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def test_errors_carry_the_query_trace_id():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "traced.ndb"))
+        db.execute_write("CREATE (:Item {n: 1})")
+        session, seq = db.last_trace_id().split("-")
+        assert len(session) == 6 and seq == "1"
+
+        try:
+            db.query("MATCH (i:Item RETURN i")
+            assert False, "the query is malformed"
+        except nervusdb.SyntaxError as err:
+            assert err.trace_id == f"{session}-2"
+            assert str(err).endswith(f"[trace {session}-2]")
+        assert db.last_trace_id() == f"{session}-2"
+        db.close()
@@ -0,0 +1,33 @@
+#!/usr/bin/env python3
+"""Commit triggers for the Python bindings."""
+
+import os
+import tempfile
+
+import nervusdb
+
+
+def test_trigger_runs_after_matching_commits():
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "triggers.ndb"))
+        db.create_trigger(
+            "follow_count",
+            "relationship_created:FOLLOWS",
+            "MATCH (n) WHERE id(n) = $dst SET n.followers = coalesce(n.followers, 0) + 1",
+        )
+        db.execute_write(
+            "CREATE (a:User {name: 'ada'}), (b:User {name: 'bob'}), "
+            "(a)-[:FOLLOWS]->(b), (a)-[:LIKES]->(b)"
+        )
+        rows = db.query("MATCH (u:User {name: 'bob'}) RETURN u.followers AS f")
+        assert rows == [{"f": 1}]
+
+        [info] = db.triggers()
+        assert info["name"] == "follow_count"
+        assert info["event"] == "relationship_created:FOLLOWS"
+        assert (info["fired"], info["failures"]) == (1, 0)
+
+        assert db.drop_trigger("follow_count")
+        assert not db.drop_trigger("follow_count")
+        assert db.triggers() == []
+        db.close()
@@ -0,0 +1,79 @@
+#!/usr/bin/env python3
+"""Write transaction savepoint and context manager test for nervusdb Python bindings."""
+
+import nervusdb
+import tempfile
+import os
+
+def names(db):
+    rows = db.query("MATCH (p:Person) RETURN p.name AS name ORDER BY name")
+    return [row["name"] for row in rows]
+
+def test_savepoints_and_context_manager():
+    """Test partial rollback and auto-commit / auto-rollback."""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "txn.ndb"))
+
+        with db.begin_write() as txn:
+            txn.query("CREATE (:Person {name: 'Alice', age: 30})")
+            txn.savepoint("before_bob")
+            txn.query("CREATE (:Person {name: 'Bob'})")
+            txn.query("MATCH (a:Person {name: 'Alice'}) SET a.age = 31")
+            txn.query("MATCH (a:Person {name: 'Alice'}), (b:Person {name: 'Bob'}) CREATE (a)-[:KNOWS]->(b)")
+            txn.rollback_to("before_bob")
+        assert names(db) == ["Alice"]
+        assert db.query("MATCH (a:Person) RETURN a.age AS age") == [{"age": 30}]
+        assert db.query("MATCH ()-[k:KNOWS]->() RETURN count(k) AS n") == [{"n": 0}]
+        print("✓ rollback_to undid the node, property and edge changes")
+
+        try:
+            with db.begin_write() as txn:
+                txn.query("CREATE (:Person {name: 'Carol'})")
+                raise RuntimeError("abort")
+        except RuntimeError:
+            pass
+        assert names(db) == ["Alice"]
+        print("✓ An exception rolled the transaction back")
+
+        with db.begin_write() as txn:
+            txn.savepoint("ok")
+            try:
+                txn.query("CREATE (:Person {name: 'Dave'")
+            except Exception:
+                txn.rollback_to("ok")
+            txn.query("CREATE (:Person {name: 'Erin'})")
+        assert names(db) == ["Alice", "Erin"]
+        print("✓ Rolling back past a failed statement lets the commit go through")
+
+        db.close()
+
+    print("\n🎉 Transaction tests passed!")
+
+def test_deferred_constraints():
+    """Unique checks deferred to commit let a swap pass through a violation."""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        db = nervusdb.open(os.path.join(tmpdir, "txn.ndb"))
+        db.execute_write("CREATE (:Person {name: 'ada'}), (:Person {name: 'bob'})")
+        db.create_unique_constraint("Person", "name")
+        swap = "MATCH (a:Person {name: 'ada'}), (b:Person {name: 'bob'}) SET a.name = 'bob', b.name = 'ada'"
+
+        with db.begin_write() as txn:
+            txn.set_constraints_deferred()
+            txn.query(swap)
+        assert names(db) == ["ada", "bob"]
+
+        txn = db.begin_write()
+        txn.set_constraints_deferred(True)
+        txn.query("CREATE (:Person {name: 'ada'})")
+        try:
+            txn.set_constraints_deferred(False)
+            raise AssertionError("the duplicate should be caught when checks resume")
+        except nervusdb.NervusError:
+            pass
+        txn.rollback()
+        db.close()
+        print("✓ Deferred unique checks let a swap through and catch a duplicate at the end")
+
+if __name__ == "__main__":
+    test_savepoints_and_context_manager()
+    test_deferred_constraints()
@@ -73,6 +73,13 @@ def test_vector_search():
         results3 = db.search_vector(query, 1)
         print(f"✓ Re-Search query {query}: {results3}")
         assert results3[0][0] == n1, "Persistence check: Node 1 should still be closest"
+
+        exact = db.search_vector_exact(query, 2)
+        assert [hit[0] for hit in exact] == [hit[0] for hit in results]
+
+        dot = db.search_vector_filtered([1.0, 1.0], 1, metric="dot", label="Point")
+        assert dot[0][0] == n4, "Dot product should favour the longest vector"
+        assert db.search_vector_filtered(query, 1, label="Missing") == []
         
     print("\n🎉 Vector search tests passed!")
 
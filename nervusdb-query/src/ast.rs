@@ -11,6 +11,7 @@ pub enum Clause {
     Create(CreateClause),
     Merge(MergeClause),
     Unwind(UnwindClause),
+    LoadCsv(LoadCsvClause),
     Call(CallClause),
     Return(ReturnClause),
     Where(WhereClause),
@@ -44,6 +45,16 @@ pub struct UnwindClause {
     pub alias: String,
 }
 
+/// `LOAD CSV [WITH HEADERS] FROM <url> AS <alias> [FIELDTERMINATOR <char>]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoadCsvClause {
+    /// Rows bind as maps keyed by the header line instead of as lists.
+    pub with_headers: bool,
+    pub source: Expression,
+    pub alias: String,
+    pub field_terminator: Option<char>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum CallClause {
     Subquery(Query),
@@ -229,6 +240,7 @@ pub enum Expression {
     FunctionCall(FunctionCall),
     Case(Box<CaseExpression>),
     Exists(Box<ExistsExpression>),
+    Subquery(Box<SubqueryExpression>),
     List(Vec<Expression>),
     ListComprehension(Box<ListComprehension>),
     PatternComprehension(Box<PatternComprehension>),
@@ -242,6 +254,22 @@ pub enum ExistsExpression {
     Subquery(Query),
 }
 
+/// `COUNT { ... }` or `CALL { ... }` used as a value. Evaluated per outer
+/// row; the subquery sees the outer row's variables.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubqueryExpression {
+    pub kind: SubqueryKind,
+    pub query: Query,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SubqueryKind {
+    /// `COUNT { ... }`: the number of rows the subquery returns.
+    Count,
+    /// `CALL { ... RETURN x }`: the single column of the first row, or null.
+    Scalar,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ListComprehension {
     pub variable: String,
@@ -291,6 +319,7 @@ pub enum BinaryOperator {
     StartsWith,
     EndsWith,
     Contains,
+    RegexMatch,
     HasLabel,
     IsNull,
     IsNotNull,
@@ -331,6 +360,11 @@ pub enum AggregateFunction {
     CollectDistinct(Expression),
     PercentileDisc(Expression, Expression),
     PercentileCont(Expression, Expression),
+    /// `countDistinctApprox(expr, precision)`; the precision is optional.
+    CountDistinctApprox(Expression, Option<Expression>),
+    /// `hllSketch(expr, precision)`; the precision is optional.
+    HllSketch(Expression, Option<Expression>),
+    HllMerge(Expression),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -10,6 +10,7 @@ pub enum ResourceLimitKind {
     CollectionItems,
     Timeout,
     ApplyRowsPerOuter,
+    MemoryBytes,
 }
 
 #[derive(Debug)]
@@ -5,6 +5,7 @@ use chrono::{
     DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike,
 };
 mod evaluator_arithmetic;
+mod evaluator_arrays;
 mod evaluator_collections;
 mod evaluator_compare;
 mod evaluator_comprehension;
@@ -13,12 +14,14 @@ mod evaluator_duration;
 mod evaluator_duration_between;
 mod evaluator_duration_core;
 mod evaluator_equality;
+mod evaluator_format;
 mod evaluator_graph_functions;
 mod evaluator_large_temporal;
-mod evaluator_materialize;
 mod evaluator_membership;
+mod evaluator_namespaces;
 mod evaluator_numeric;
 mod evaluator_pattern;
+mod evaluator_regex;
 mod evaluator_scalars;
 mod evaluator_temporal_format;
 mod evaluator_temporal_functions;
@@ -30,20 +33,28 @@ mod evaluator_temporal_shift;
 mod evaluator_temporal_truncate;
 mod evaluator_timezone;
 use evaluator_arithmetic::{add_values, divide_values, multiply_values, subtract_values};
+pub(crate) use evaluator_arrays::{array_argument_error, array_element};
+use evaluator_arrays::{array_contains, array_size, to_array};
 use evaluator_collections::evaluate_collection_function;
 use evaluator_compare::{compare_values, order_compare_non_null};
 use evaluator_comprehension::{evaluate_list_comprehension, evaluate_quantifier, evaluate_reduce};
 use evaluator_duration::duration_from_value;
 use evaluator_duration_core::build_duration_parts;
 use evaluator_equality::cypher_equals;
+pub(crate) use evaluator_format::format_value;
+pub use evaluator_format::is_known_locale;
 use evaluator_graph_functions::evaluate_graph_function;
 use evaluator_membership::{in_list, string_predicate};
+pub(crate) use evaluator_namespaces::namespace_argument_error;
+use evaluator_namespaces::{compact_iri, expand_iri};
 use evaluator_numeric::{
     cast_to_boolean, cast_to_float, cast_to_integer, numeric_mod, numeric_pow,
 };
 use evaluator_pattern::{
     evaluate_has_label, evaluate_pattern_comprehension, evaluate_pattern_exists,
 };
+pub(crate) use evaluator_regex::compile_regex;
+use evaluator_regex::regex_match;
 use evaluator_scalars::evaluate_scalar_function;
 use evaluator_temporal_functions::evaluate_temporal_function;
 use evaluator_temporal_shift::{
@@ -142,7 +153,7 @@ pub fn evaluate_expression_value<S: GraphSnapshot>(
         }
         Expression::Parameter(name) => {
             // Get from params
-            params.get(name).cloned().unwrap_or(Value::Null)
+            params.value(name).unwrap_or(Value::Null)
         }
         Expression::List(items) => Value::List(
             items
@@ -181,6 +192,11 @@ pub fn evaluate_expression_value<S: GraphSnapshot>(
         }
         Expression::Binary(b) => {
             let left = evaluate_expression_value(&b.left, row, snapshot, params);
+            if b.operator == BinaryOperator::In
+                && let Some(value) = array_contains(&left, &b.right, row, snapshot)
+            {
+                return value;
+            }
             let right = evaluate_expression_value(&b.right, row, snapshot, params);
 
             match b.operator {
@@ -240,6 +256,7 @@ pub fn evaluate_expression_value<S: GraphSnapshot>(
                 }
                 BinaryOperator::EndsWith => string_predicate(&left, &right, |l, r| l.ends_with(r)),
                 BinaryOperator::Contains => string_predicate(&left, &right, |l, r| l.contains(r)),
+                BinaryOperator::RegexMatch => regex_match(&left, &right),
                 BinaryOperator::HasLabel => evaluate_has_label(&left, &right, snapshot),
                 BinaryOperator::IsNull => Value::Bool(matches!(left, Value::Null)),
                 BinaryOperator::IsNotNull => Value::Bool(!matches!(left, Value::Null)),
@@ -283,6 +300,10 @@ pub fn evaluate_expression_value<S: GraphSnapshot>(
                 }
             }
         },
+        Expression::Subquery(subquery) => {
+            crate::query_api::evaluate_subquery(subquery, row, snapshot, params)
+                .unwrap_or(Value::Null)
+        }
         Expression::PatternComprehension(pattern_comp) => {
             evaluate_pattern_comprehension(pattern_comp, row, snapshot, params)
         }
@@ -296,6 +317,21 @@ fn evaluate_function<S: GraphSnapshot>(
     params: &Params,
 ) -> Value {
     let name = call.name.to_lowercase();
+    // Index, size and containment on stored arrays skip building the list.
+    match (name.as_str(), call.args.as_slice()) {
+        ("__index", [list, index]) => {
+            let index = evaluate_expression_value(index, row, snapshot, params);
+            if let Some(value) = array_element(list, &index, row, snapshot) {
+                return value;
+            }
+        }
+        ("size", [list]) => {
+            if let Some(value) = array_size(list, row, snapshot) {
+                return value;
+            }
+        }
+        _ => {}
+    }
     let args: Vec<Value> = call
         .args
         .iter()
@@ -305,6 +341,10 @@ fn evaluate_function<S: GraphSnapshot>(
     if let Some(value) = evaluate_collection_function(&name, &args, snapshot) {
         return value;
     }
+    if name == "tostring" && args.len() > 1 {
+        // Invalid formats are rejected before evaluation.
+        return format_value(&args, snapshot.locale()).unwrap_or(Value::Null);
+    }
     if let Some(value) = evaluate_scalar_function(&name, &args) {
         return value;
     }
@@ -317,9 +357,15 @@ fn evaluate_function<S: GraphSnapshot>(
 
     match name.as_str() {
         "tointeger" => cast_to_integer(args.first()),
+        "tointegerarray" | "tofloatarray" => to_array(&name, args.first()),
         "tofloat" => cast_to_float(args.first()),
+        "iri" => expand_iri(args.first(), &snapshot.namespaces()),
+        "curie" => compact_iri(args.first(), &snapshot.namespaces()),
         "toboolean" => cast_to_boolean(args.first()),
-        _ => Value::Null, // Unknown function
+        _ => match crate::executor::custom_function(&name) {
+            Some((_, function)) => function(&args),
+            None => Value::Null, // Unknown function
+        },
     }
 }
 
@@ -377,6 +423,70 @@ pub fn order_compare(left: &Value, right: &Value) -> Ordering {
     }
 }
 
+/// Query-layer form of a stored `PropertyValue::Date` (days since epoch): the same
+/// ISO `YYYY-MM-DD` string that `date()` produces.
+pub fn date_value_from_epoch_days(days: i32) -> Value {
+    NaiveDate::from_num_days_from_ce_opt(days.saturating_add(719_163))
+        .map(|d| Value::String(d.format("%Y-%m-%d").to_string()))
+        .unwrap_or(Value::Null)
+}
+
+/// Query-layer form of a stored `PropertyValue::LocalTime` (nanoseconds since midnight),
+/// formatted like `localtime()`.
+pub fn local_time_value_from_nanos(nanos: i64) -> Value {
+    let (secs, frac) = (
+        nanos.div_euclid(1_000_000_000),
+        nanos.rem_euclid(1_000_000_000),
+    );
+    u32::try_from(secs)
+        .ok()
+        .and_then(|secs| NaiveTime::from_num_seconds_from_midnight_opt(secs, frac as u32))
+        .map(|t| {
+            let include_seconds = t.second() != 0 || t.nanosecond() != 0;
+            Value::String(evaluator_temporal_format::format_time_literal(
+                t,
+                include_seconds,
+            ))
+        })
+        .unwrap_or(Value::Null)
+}
+
+/// Days since epoch for an ISO `YYYY-MM-DD` date, the inverse of
+/// [`date_value_from_epoch_days`].
+pub fn epoch_days_from_date_literal(s: &str) -> Option<i32> {
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+    i32::try_from(date.num_days_from_ce() as i64 - 719_163).ok()
+}
+
+/// Nanoseconds since midnight for an ISO `HH:MM[:SS[.fffffffff]]` local time.
+pub fn nanos_from_local_time_literal(s: &str) -> Option<i64> {
+    let time = NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .ok()?;
+    Some(i64::from(time.num_seconds_from_midnight()) * 1_000_000_000 + i64::from(time.nanosecond()))
+}
+
+/// Query-layer form of a stored `PropertyValue::Duration`: the duration map built by
+/// `duration()`.
+pub fn duration_value_from_parts(months: i64, days: i64, nanos: i64) -> Value {
+    evaluator_duration::duration_value_wide(months, days, nanos)
+}
+
+/// Splits a duration map back into `(months, days, nanos)`; `None` for any other value.
+pub fn duration_parts_from_value(value: &Value) -> Option<(i64, i64, i64)> {
+    let Value::Map(map) = value else {
+        return None;
+    };
+    if !matches!(map.get("__kind"), Some(Value::String(kind)) if kind == "duration") {
+        return None;
+    }
+    let part = |key: &str| match map.get(key) {
+        Some(Value::Int(v)) => Some(*v),
+        _ => None,
+    };
+    Some((part("months")?, part("days")?, part("nanos")?))
+}
+
 #[derive(Debug, Clone, Default)]
 struct DurationParts {
     months: i32,
@@ -0,0 +1,142 @@
+use super::Value;
+use crate::ast::{Expression, PropertyAccess};
+use nervusdb_api::{GraphSnapshot, PropertyValue};
+
+/// `toIntegerArray(list)` / `toFloatArray(list)`: the list with every
+/// element converted, floats truncated towards zero for integer arrays and
+/// rounded to 32 bits for float arrays. Stored through `SET` or `CREATE`, the
+/// result becomes a packed array property. Lists with anything but numbers
+/// are rejected before evaluation and only reach here as null.
+pub(super) fn to_array(name: &str, arg: Option<&Value>) -> Value {
+    let Some(Value::List(items)) = arg else {
+        return Value::Null;
+    };
+    let converted: Option<Vec<Value>> = items
+        .iter()
+        .map(|item| match (name, item) {
+            ("tointegerarray", Value::Int(i)) => Some(Value::Int(*i)),
+            ("tointegerarray", Value::Float(f)) if f.is_finite() => Some(Value::Int(*f as i64)),
+            ("tofloatarray", Value::Int(i)) => Some(Value::Float(f64::from(*i as f32))),
+            ("tofloatarray", Value::Float(f)) => Some(Value::Float(f64::from(*f as f32))),
+            _ => None,
+        })
+        .collect();
+    converted.map_or(Value::Null, Value::List)
+}
+
+/// Why `toIntegerArray()` / `toFloatArray()` cannot convert `arg`.
+pub(crate) fn array_argument_error(name: &str, arg: &Value) -> Option<String> {
+    let valid = match arg {
+        Value::Null => true,
+        Value::List(items) => items.iter().all(|item| match item {
+            Value::Int(_) => true,
+            Value::Float(f) => name == "tofloatarray" || f.is_finite(),
+            _ => false,
+        }),
+        _ => false,
+    };
+    (!valid).then(|| format!("{name}() takes a list of numbers"))
+}
+
+/// The stored array behind `var.key` when `var` is a node or relationship
+/// read from the snapshot. Other bindings, and properties that are not
+/// packed arrays, return `None` so the caller evaluates them as usual.
+fn stored_array<S: GraphSnapshot>(
+    access: &PropertyAccess,
+    row: &crate::executor::Row,
+    snapshot: &S,
+) -> Option<PropertyValue> {
+    let value = match row.get(&access.variable)? {
+        Value::NodeId(node) => snapshot.node_property(*node, &access.property),
+        Value::EdgeKey(edge) => snapshot.edge_property(*edge, &access.property),
+        _ => None,
+    }?;
+    matches!(
+        value,
+        PropertyValue::IntArray(_) | PropertyValue::FloatArray(_)
+    )
+    .then_some(value)
+}
+
+/// `var.key[index]` on a stored array, without building the whole list.
+pub(crate) fn array_element<S: GraphSnapshot>(
+    list: &Expression,
+    index: &Value,
+    row: &crate::executor::Row,
+    snapshot: &S,
+) -> Option<Value> {
+    let Expression::PropertyAccess(access) = list else {
+        return None;
+    };
+    let array = stored_array(access, row, snapshot)?;
+    let Value::Int(index) = index else {
+        return Some(Value::Null);
+    };
+    let position = |len: usize| {
+        let at = if *index < 0 {
+            len as i64 + index
+        } else {
+            *index
+        };
+        usize::try_from(at).ok().filter(|at| *at < len)
+    };
+    Some(match array {
+        PropertyValue::IntArray(items) => position(items.len())
+            .map(|at| Value::Int(items[at]))
+            .unwrap_or(Value::Null),
+        PropertyValue::FloatArray(items) => position(items.len())
+            .map(|at| Value::Float(f64::from(items[at])))
+            .unwrap_or(Value::Null),
+        _ => unreachable!("stored_array returns arrays"),
+    })
+}
+
+/// `needle IN var.key` on a stored array. Arrays hold no nulls, so the
+/// answer is null only for a null needle.
+pub(super) fn array_contains<S: GraphSnapshot>(
+    needle: &Value,
+    list: &Expression,
+    row: &crate::executor::Row,
+    snapshot: &S,
+) -> Option<Value> {
+    let Expression::PropertyAccess(access) = list else {
+        return None;
+    };
+    let array = stored_array(access, row, snapshot)?;
+    let empty = match &array {
+        PropertyValue::IntArray(items) => items.is_empty(),
+        PropertyValue::FloatArray(items) => items.is_empty(),
+        _ => unreachable!("stored_array returns arrays"),
+    };
+    Some(match (needle, array) {
+        (_, _) if empty => Value::Bool(false),
+        (Value::Null, _) => Value::Null,
+        (Value::Int(n), PropertyValue::IntArray(items)) => Value::Bool(items.contains(n)),
+        (Value::Float(f), PropertyValue::IntArray(items)) => {
+            Value::Bool(items.iter().any(|i| *i as f64 == *f))
+        }
+        (Value::Int(n), PropertyValue::FloatArray(items)) => {
+            Value::Bool(items.iter().any(|x| f64::from(*x) == *n as f64))
+        }
+        (Value::Float(f), PropertyValue::FloatArray(items)) => {
+            Value::Bool(items.iter().any(|x| f64::from(*x) == *f))
+        }
+        _ => Value::Bool(false),
+    })
+}
+
+/// `size(var.key)` on a stored array.
+pub(super) fn array_size<S: GraphSnapshot>(
+    list: &Expression,
+    row: &crate::executor::Row,
+    snapshot: &S,
+) -> Option<Value> {
+    let Expression::PropertyAccess(access) = list else {
+        return None;
+    };
+    Some(match stored_array(access, row, snapshot)? {
+        PropertyValue::IntArray(items) => Value::Int(items.len() as i64),
+        PropertyValue::FloatArray(items) => Value::Int(items.len() as i64),
+        _ => unreachable!("stored_array returns arrays"),
+    })
+}
@@ -21,6 +21,7 @@ pub(super) fn evaluate_collection_function<S: GraphSnapshot>(
         "__slice" => Some(evaluate_slice(args)),
         "__getprop" => Some(evaluate_getprop(args, snapshot)),
         "properties" => Some(evaluate_properties(args, snapshot)),
+        "__map_projection" => Some(evaluate_map_projection(args, snapshot)),
         _ => None,
     }
 }
@@ -314,8 +315,37 @@ fn evaluate_slice(args: &[Value]) -> Value {
 #[allow(clippy::items_after_test_module)]
 #[cfg(test)]
 mod tests {
-    use super::{evaluate_range, evaluate_slice};
+    use super::{evaluate_map_projection, evaluate_range, evaluate_slice};
     use crate::evaluator::Value;
+    use nervusdb_api::{EdgeKey, GraphSnapshot, InternalNodeId, PropertyValue, RelTypeId};
+    use std::collections::BTreeMap;
+
+    /// A node with many properties that may only be read one key at a time.
+    struct WideNode;
+
+    impl GraphSnapshot for WideNode {
+        type Neighbors<'a> = std::iter::Empty<EdgeKey>;
+
+        fn neighbors(&self, _src: InternalNodeId, _rel: Option<RelTypeId>) -> Self::Neighbors<'_> {
+            std::iter::empty()
+        }
+
+        fn incoming_neighbors(
+            &self,
+            _dst: InternalNodeId,
+            _rel: Option<RelTypeId>,
+        ) -> Self::Neighbors<'_> {
+            std::iter::empty()
+        }
+
+        fn node_property(&self, _iid: InternalNodeId, key: &str) -> Option<PropertyValue> {
+            Some(PropertyValue::String(key.to_uppercase()))
+        }
+
+        fn node_properties(&self, _iid: InternalNodeId) -> Option<BTreeMap<String, PropertyValue>> {
+            panic!("the whole property map was read")
+        }
+    }
 
     #[test]
     fn slice_returns_null_for_explicit_null_lower_bound() {
@@ -356,6 +386,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn map_projection_without_star_skips_the_property_map() {
+        let entries = BTreeMap::from([("name".to_string(), Value::String("NAME".into()))]);
+        let result = evaluate_map_projection(
+            &[
+                Value::NodeId(7),
+                Value::Map(entries.clone()),
+                Value::Bool(false),
+            ],
+            &WideNode,
+        );
+        assert_eq!(result, Value::Map(entries));
+    }
+
     #[test]
     fn range_default_step_returns_empty_when_start_greater_than_end() {
         let result = evaluate_range(&[Value::Int(0), Value::Int(-2)]);
@@ -400,6 +444,28 @@ fn evaluate_getprop<S: GraphSnapshot>(args: &[Value], snapshot: &S) -> Value {
     }
 }
 
+/// `n {.*, key: value}`: `args` are the subject, the listed entries and
+/// whether `.*` asked for all of the subject's properties.
+fn evaluate_map_projection<S: GraphSnapshot>(args: &[Value], snapshot: &S) -> Value {
+    let [subject, Value::Map(entries), Value::Bool(all_properties)] = args else {
+        return Value::Null;
+    };
+    if matches!(subject, Value::Null) {
+        return Value::Null;
+    }
+    // Without `.*` the listed entries were already read key by key.
+    let mut out = if *all_properties {
+        match evaluate_properties(std::slice::from_ref(subject), snapshot) {
+            Value::Map(properties) => properties,
+            _ => std::collections::BTreeMap::new(),
+        }
+    } else {
+        std::collections::BTreeMap::new()
+    };
+    out.extend(entries.clone());
+    Value::Map(out)
+}
+
 fn evaluate_properties<S: GraphSnapshot>(args: &[Value], snapshot: &S) -> Value {
     match args.first() {
         Some(Value::Map(map)) => Value::Map(map.clone()),
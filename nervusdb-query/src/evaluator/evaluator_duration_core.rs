@@ -199,10 +199,7 @@ fn calendar_months_and_remainder_with_offsets(
             pivot_local = add_months_to_naive_datetime(lhs, months)?;
             pivot_dt = lhs_offset.from_local_datetime(&pivot_local).single()?;
         }
-        loop {
-            let Some(next_local) = add_months_to_naive_datetime(lhs, months + 1) else {
-                break;
-            };
+        while let Some(next_local) = add_months_to_naive_datetime(lhs, months + 1) {
             let Some(next_dt) = lhs_offset.from_local_datetime(&next_local).single() else {
                 break;
             };
@@ -219,10 +216,7 @@ fn calendar_months_and_remainder_with_offsets(
             pivot_local = add_months_to_naive_datetime(lhs, months)?;
             pivot_dt = lhs_offset.from_local_datetime(&pivot_local).single()?;
         }
-        loop {
-            let Some(next_local) = add_months_to_naive_datetime(lhs, months - 1) else {
-                break;
-            };
+        while let Some(next_local) = add_months_to_naive_datetime(lhs, months - 1) {
             let Some(next_dt) = lhs_offset.from_local_datetime(&next_local).single() else {
                 break;
             };
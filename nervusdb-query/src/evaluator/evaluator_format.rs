@@ -0,0 +1,274 @@
+use super::evaluator_temporal_parse::parse_temporal_string;
+use super::{TemporalValue, Value};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use std::fmt::Write as _;
+
+/// `toString(value, format [, locale])`. Numbers take a decimal pattern such
+/// as `#,##0.00` and print with the separators of `locale`, falling back to
+/// `default_locale` (the database's) and then English; temporal values take a
+/// strftime pattern such as `%Y-%m-%d`. Null in, null out.
+pub(crate) fn format_value(args: &[Value], default_locale: Option<&str>) -> Result<Value, String> {
+    let (value, format) = match (args.first(), args.get(1)) {
+        (Some(Value::Null), _) | (_, Some(Value::Null)) => return Ok(Value::Null),
+        (Some(value), Some(Value::String(format))) => (value, format),
+        _ => return Err("toString() format must be a string".to_string()),
+    };
+    let locale = match args.get(2) {
+        None | Some(Value::Null) => default_locale.unwrap_or("en"),
+        Some(Value::String(locale)) => locale,
+        Some(_) => return Err("toString() locale must be a string".to_string()),
+    };
+    let (group, decimal) =
+        number_separators(locale).ok_or_else(|| format!("unknown locale '{locale}'"))?;
+
+    let text = match value {
+        Value::Int(_) | Value::Float(_) => {
+            format_number(value, &NumberPattern::parse(format)?, group, decimal)
+        }
+        Value::DateTime(micros) => {
+            let at = DateTime::<Utc>::from_timestamp_micros(*micros)
+                .ok_or_else(|| "datetime out of range".to_string())?;
+            format_temporal(format, |items, out| {
+                write!(out, "{}", at.format_with_items(items))
+            })?
+        }
+        Value::String(text) => match parse_temporal_string(text) {
+            Some(TemporalValue::Date(d)) => format_temporal(format, |items, out| {
+                write!(out, "{}", d.format_with_items(items))
+            })?,
+            Some(TemporalValue::LocalTime(t) | TemporalValue::Time { time: t, .. }) => {
+                format_temporal(format, |items, out| {
+                    write!(out, "{}", t.format_with_items(items))
+                })?
+            }
+            Some(TemporalValue::LocalDateTime(dt)) => format_temporal(format, |items, out| {
+                write!(out, "{}", dt.format_with_items(items))
+            })?,
+            Some(TemporalValue::DateTime(dt)) => format_temporal(format, |items, out| {
+                write!(out, "{}", dt.format_with_items(items))
+            })?,
+            None => return Err("toString() formats numbers and temporal values".to_string()),
+        },
+        _ => return Err("toString() formats numbers and temporal values".to_string()),
+    };
+    Ok(Value::String(text))
+}
+
+/// Whether `toString()` knows the separators of `locale`, a BCP 47 tag
+/// such as `de` or `pt-BR`.
+pub fn is_known_locale(locale: &str) -> bool {
+    number_separators(locale).is_some()
+}
+
+/// Group and decimal separators of `locale`.
+fn number_separators(locale: &str) -> Option<(char, char)> {
+    let tag = locale.to_ascii_lowercase().replace('_', "-");
+    let (language, region) = tag.split_once('-').unwrap_or((&tag, ""));
+    let separators = match (language, region) {
+        ("de" | "fr" | "it", "ch") => ('\u{2019}', '.'),
+        ("es", "mx" | "us") => (',', '.'),
+        ("en" | "ja" | "zh" | "ko" | "he" | "th" | "hi" | "ms", _) => (',', '.'),
+        (
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+            | "sr" | "vi",
+            _,
+        ) => ('.', ','),
+        (
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "uk" | "hu" | "bg"
+            | "lt" | "lv" | "et",
+            _,
+        ) => ('\u{a0}', ','),
+        _ => return None,
+    };
+    Some(separators)
+}
+
+/// A decimal pattern: an optional literal prefix and suffix around digits
+/// (`0` always shown, `#` only when significant), `,` marking the group
+/// size and `.` the decimal point. A `%` in the prefix or suffix scales the
+/// value by 100.
+#[derive(Debug)]
+struct NumberPattern {
+    prefix: String,
+    suffix: String,
+    min_integer: usize,
+    group_size: Option<usize>,
+    min_fraction: usize,
+    max_fraction: usize,
+    percent: bool,
+}
+
+impl NumberPattern {
+    fn parse(pattern: &str) -> Result<Self, String> {
+        let is_body = |c: char| matches!(c, '#' | '0' | ',' | '.');
+        let invalid = || format!("invalid number format '{pattern}'");
+        let start = pattern.find(is_body).ok_or_else(invalid)?;
+        let end = pattern.rfind(is_body).ok_or_else(invalid)? + 1;
+        let (prefix, body, suffix) = (&pattern[..start], &pattern[start..end], &pattern[end..]);
+        if body.chars().any(|c| !is_body(c)) {
+            return Err(invalid());
+        }
+        let (integer, fraction) = body.split_once('.').unwrap_or((body, ""));
+        if fraction.contains(['.', ',']) || fraction.trim_start_matches('0').contains('0') {
+            return Err(invalid());
+        }
+        let digits = integer.replace(',', "");
+        if digits.trim_start_matches('#').contains('#') || digits.len() + fraction.len() == 0 {
+            return Err(invalid());
+        }
+        let group_size = match integer.rfind(',') {
+            Some(at) if at + 1 == integer.len() => return Err(invalid()),
+            Some(at) => Some(integer.len() - at - 1),
+            None => None,
+        };
+        Ok(Self {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            min_integer: digits.matches('0').count(),
+            group_size,
+            min_fraction: fraction.matches('0').count(),
+            max_fraction: fraction.len(),
+            percent: prefix.contains('%') || suffix.contains('%'),
+        })
+    }
+}
+
+fn format_number(value: &Value, pattern: &NumberPattern, group: char, decimal: char) -> String {
+    let scale = if pattern.percent { 100.0 } else { 1.0 };
+    let (negative, digits) = match *value {
+        Value::Int(n) if !pattern.percent || n.checked_mul(100).is_some() => {
+            let n = if pattern.percent { n * 100 } else { n };
+            (n < 0, n.unsigned_abs().to_string())
+        }
+        Value::Int(n) => (
+            n < 0,
+            format!("{:.*}", pattern.max_fraction, n as f64 * scale),
+        ),
+        Value::Float(f) if f.is_nan() => return "NaN".to_string(),
+        Value::Float(f) if f.is_infinite() => {
+            return format!(
+                "{}{}\u{221e}{}",
+                if f < 0.0 { "-" } else { "" },
+                pattern.prefix,
+                pattern.suffix
+            );
+        }
+        Value::Float(f) => (
+            f.is_sign_negative(),
+            format!("{:.*}", pattern.max_fraction, (f * scale).abs()),
+        ),
+        _ => unreachable!("format_number takes numbers"),
+    };
+    let digits = digits.trim_start_matches('-');
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let mut fraction = fraction.to_string();
+    while fraction.len() > pattern.min_fraction && fraction.ends_with('0') {
+        fraction.pop();
+    }
+
+    let mut integer = integer.trim_start_matches('0').to_string();
+    if integer.len() < pattern.min_integer {
+        integer = format!(
+            "{}{integer}",
+            "0".repeat(pattern.min_integer - integer.len())
+        );
+    }
+    if integer.is_empty() && fraction.is_empty() {
+        integer.push('0');
+    }
+    if let Some(size) = pattern.group_size.filter(|size| *size > 0) {
+        let mut grouped = String::new();
+        for (i, c) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % size == 0 {
+                grouped.push(group);
+            }
+            grouped.push(c);
+        }
+        integer = grouped;
+    }
+
+    let is_zero = !integer
+        .chars()
+        .chain(fraction.chars())
+        .any(|c| c.is_ascii_digit() && c != '0');
+    let mut out = String::new();
+    if negative && !is_zero {
+        out.push('-');
+    }
+    out.push_str(&pattern.prefix);
+    out.push_str(&integer);
+    if !fraction.is_empty() {
+        out.push(decimal);
+        out.push_str(&fraction);
+    }
+    out.push_str(&pattern.suffix);
+    out
+}
+
+/// Runs `write` with the parsed strftime `format`, turning specifiers the
+/// value has no field for (`%H` on a date) into an error.
+fn format_temporal(
+    format: &str,
+    write: impl FnOnce(std::slice::Iter<'_, Item<'_>>, &mut String) -> std::fmt::Result,
+) -> Result<String, String> {
+    let items: Vec<Item<'_>> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("invalid temporal format '{format}'"));
+    }
+    let mut out = String::new();
+    write(items.iter(), &mut out)
+        .map_err(|_| format!("temporal format '{format}' does not apply to this value"))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_value;
+    use crate::executor::Value;
+
+    fn format(value: Value, pattern: &str, locale: Option<&str>) -> String {
+        match format_value(&[value, Value::String(pattern.into())], locale) {
+            Ok(Value::String(text)) => text,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn numbers_follow_the_pattern_and_locale() {
+        assert_eq!(format(Value::Int(1234567), "#,##0", None), "1,234,567");
+        assert_eq!(format(Value::Float(1234.5), "#,##0.00", None), "1,234.50");
+        assert_eq!(
+            format(Value::Float(1234.5), "#,##0.00", Some("de-DE")),
+            "1.234,50"
+        );
+        assert_eq!(format(Value::Float(0.125), "0.0#%", None), "12.5%");
+        assert_eq!(format(Value::Float(-0.001), "0.00", None), "0.00");
+        assert_eq!(format(Value::Int(-42), "$000", None), "-$042");
+        assert_eq!(format(Value::Float(0.5), "#.##", None), ".5");
+        assert!(format_value(&[Value::Int(1), Value::String("abc".into())], None).is_err());
+        assert!(
+            format_value(
+                &[
+                    Value::Int(1),
+                    Value::String("0.0".into()),
+                    Value::String("xx".into())
+                ],
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn temporal_values_take_strftime_patterns() {
+        let date = Value::String("2024-03-17".into());
+        assert_eq!(format(date.clone(), "%d/%m/%Y", None), "17/03/2024");
+        assert_eq!(
+            format(Value::DateTime(0), "%Y-%m-%d %H:%M", None),
+            "1970-01-01 00:00"
+        );
+        assert!(format_value(&[date.clone(), Value::String("%H".into())], None).is_err());
+        assert!(format_value(&[date, Value::String("%Q".into())], None).is_err());
+    }
+}
@@ -1,6 +1,5 @@
-use super::evaluator_materialize::materialize_node_from_row_or_snapshot;
 use super::{Row, Value};
-use nervusdb_api::GraphSnapshot;
+use nervusdb_api::{GraphSnapshot, InternalNodeId};
 
 pub(super) fn evaluate_graph_function<S: GraphSnapshot>(
     name: &str,
@@ -9,8 +8,8 @@ pub(super) fn evaluate_graph_function<S: GraphSnapshot>(
     snapshot: &S,
 ) -> Option<Value> {
     match name {
-        "startnode" => Some(evaluate_start_node(args, row, snapshot)),
-        "endnode" => Some(evaluate_end_node(args, row, snapshot)),
+        "startnode" => Some(evaluate_start_node(args, row)),
+        "endnode" => Some(evaluate_end_node(args, row)),
         "labels" => Some(evaluate_labels(args, snapshot)),
         "type" => Some(evaluate_type(args, snapshot)),
         "id" => Some(evaluate_id(args)),
@@ -18,26 +17,30 @@ pub(super) fn evaluate_graph_function<S: GraphSnapshot>(
     }
 }
 
-fn evaluate_start_node<S: GraphSnapshot>(args: &[Value], row: &Row, snapshot: &S) -> Value {
+/// The endpoint as the row already holds it, else as a bare id: property
+/// access then fetches single keys, and only a returned node is reified.
+fn node_from_row_or_id(row: &Row, node_id: InternalNodeId) -> Value {
+    row.columns()
+        .iter()
+        .find_map(|(_, v)| match v {
+            Value::Node(node) if node.id == node_id => Some(v.clone()),
+            _ => None,
+        })
+        .unwrap_or(Value::NodeId(node_id))
+}
+
+fn evaluate_start_node(args: &[Value], row: &Row) -> Value {
     match args.first() {
-        Some(Value::EdgeKey(edge_key)) => {
-            materialize_node_from_row_or_snapshot(row, snapshot, edge_key.src)
-        }
-        Some(Value::Relationship(rel)) => {
-            materialize_node_from_row_or_snapshot(row, snapshot, rel.key.src)
-        }
+        Some(Value::EdgeKey(edge_key)) => node_from_row_or_id(row, edge_key.src),
+        Some(Value::Relationship(rel)) => node_from_row_or_id(row, rel.key.src),
         _ => Value::Null,
     }
 }
 
-fn evaluate_end_node<S: GraphSnapshot>(args: &[Value], row: &Row, snapshot: &S) -> Value {
+fn evaluate_end_node(args: &[Value], row: &Row) -> Value {
     match args.first() {
-        Some(Value::EdgeKey(edge_key)) => {
-            materialize_node_from_row_or_snapshot(row, snapshot, edge_key.dst)
-        }
-        Some(Value::Relationship(rel)) => {
-            materialize_node_from_row_or_snapshot(row, snapshot, rel.key.dst)
-        }
+        Some(Value::EdgeKey(edge_key)) => node_from_row_or_id(row, edge_key.dst),
+        Some(Value::Relationship(rel)) => node_from_row_or_id(row, rel.key.dst),
         _ => Value::Null,
     }
 }
@@ -122,10 +122,7 @@ pub(super) fn large_months_and_days_between(lhs: LargeDate, rhs: LargeDate) -> O
             months -= 1;
             pivot = add_months_large_date(lhs, months)?;
         }
-        loop {
-            let Some(next) = add_months_large_date(lhs, months + 1) else {
-                break;
-            };
+        while let Some(next) = add_months_large_date(lhs, months + 1) {
             if (next.year, next.month, next.day) <= (rhs.year, rhs.month, rhs.day) {
                 months += 1;
                 pivot = next;
@@ -138,10 +135,7 @@ pub(super) fn large_months_and_days_between(lhs: LargeDate, rhs: LargeDate) -> O
             months += 1;
             pivot = add_months_large_date(lhs, months)?;
         }
-        loop {
-            let Some(next) = add_months_large_date(lhs, months - 1) else {
-                break;
-            };
+        while let Some(next) = add_months_large_date(lhs, months - 1) {
             if (next.year, next.month, next.day) >= (rhs.year, rhs.month, rhs.day) {
                 months -= 1;
                 pivot = next;
@@ -0,0 +1,62 @@
+use super::Value;
+
+/// `iri(curie)`: the full IRI for a compact `prefix:local` reference,
+/// resolved through the namespace catalog. Null stays null; unbound prefixes
+/// and non-strings are rejected before evaluation and only reach here as
+/// null.
+pub(super) fn expand_iri(arg: Option<&Value>, namespaces: &[(String, String)]) -> Value {
+    match arg {
+        Some(value) => iri_result(value, namespaces).unwrap_or(Value::Null),
+        None => Value::Null,
+    }
+}
+
+/// `curie(iri)`: `iri` shortened to `prefix:local` with the longest bound
+/// base IRI it starts with, or null if none matches. When two prefixes share
+/// that base the alphabetically first wins.
+pub(super) fn compact_iri(arg: Option<&Value>, namespaces: &[(String, String)]) -> Value {
+    let Some(Value::String(iri)) = arg else {
+        return Value::Null;
+    };
+    namespaces
+        .iter()
+        .filter(|(_, base)| iri.starts_with(base.as_str()))
+        .fold(None::<&(String, String)>, |best, candidate| match best {
+            Some(best) if best.1.len() >= candidate.1.len() => Some(best),
+            _ => Some(candidate),
+        })
+        .map_or(Value::Null, |(prefix, base)| {
+            Value::String(format!("{prefix}:{}", &iri[base.len()..]))
+        })
+}
+
+/// Why `iri()` / `curie()` cannot take `arg`.
+pub(crate) fn namespace_argument_error(
+    name: &str,
+    arg: &Value,
+    namespaces: &[(String, String)],
+) -> Option<String> {
+    match (name, arg) {
+        (_, Value::Null) | ("curie", Value::String(_)) => None,
+        ("iri", value) => iri_result(value, namespaces).err(),
+        _ => Some(format!("{name}() takes a string")),
+    }
+}
+
+fn iri_result(value: &Value, namespaces: &[(String, String)]) -> Result<Value, String> {
+    let curie = match value {
+        Value::Null => return Ok(Value::Null),
+        Value::String(curie) => curie,
+        _ => return Err("iri() takes a string".to_string()),
+    };
+    let Some((prefix, local)) = curie.split_once(':') else {
+        return Err(format!(
+            "iri() takes a prefix:local reference, got '{curie}'"
+        ));
+    };
+    namespaces
+        .iter()
+        .find(|(bound, _)| bound == prefix)
+        .map(|(_, base)| Value::String(format!("{base}{local}")))
+        .ok_or_else(|| format!("namespace prefix '{prefix}' is not bound"))
+}
@@ -0,0 +1,41 @@
+use super::Value;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Distinct patterns kept compiled; the cache is emptied when it fills up.
+const REGEX_CACHE_CAPACITY: usize = 256;
+
+fn regex_cache() -> &'static Mutex<HashMap<String, Arc<Regex>>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Arc<Regex>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Compiles `pattern` to match whole strings, as `=~` does, reusing the
+/// compiled form for patterns seen before.
+pub(crate) fn compile_regex(pattern: &str) -> Result<Arc<Regex>, regex::Error> {
+    let mut cache = regex_cache()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(regex) = cache.get(pattern) {
+        return Ok(Arc::clone(regex));
+    }
+    let regex = Arc::new(Regex::new(&format!("^(?:{pattern})$"))?);
+    if cache.len() >= REGEX_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), Arc::clone(&regex));
+    Ok(regex)
+}
+
+/// `left =~ right`: null unless both sides are strings. Invalid patterns are
+/// rejected before evaluation, so they only reach here as null.
+pub(super) fn regex_match(left: &Value, right: &Value) -> Value {
+    match (left, right) {
+        (Value::String(text), Value::String(pattern)) => match compile_regex(pattern) {
+            Ok(regex) => Value::Bool(regex.is_match(text)),
+            Err(_) => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
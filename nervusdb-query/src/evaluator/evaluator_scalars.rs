@@ -28,6 +28,7 @@ pub(super) fn evaluate_scalar_function(name: &str, args: &[Value]) -> Option<Val
         "log" => Some(evaluate_log(args)),
         "e" => Some(evaluate_e(args)),
         "pi" => Some(evaluate_pi(args)),
+        "hllcount" => Some(evaluate_hll_count(args)),
         _ => None,
     }
 }
@@ -62,6 +63,15 @@ fn evaluate_abs(args: &[Value]) -> Value {
     }
 }
 
+/// Estimated distinct count of a sketch from `hllSketch` or `hllMerge`.
+fn evaluate_hll_count(args: &[Value]) -> Value {
+    match args.first() {
+        Some(Value::Blob(bytes)) => crate::sketch::HyperLogLog::from_bytes(bytes)
+            .map_or(Value::Null, |sketch| Value::Int(sketch.estimate() as i64)),
+        _ => Value::Null,
+    }
+}
+
 fn evaluate_to_lower(args: &[Value]) -> Value {
     if let Some(Value::String(s)) = args.first() {
         Value::String(s.to_lowercase())
@@ -5,17 +5,21 @@ use crate::error::{Error, Result};
 use crate::evaluator::evaluate_expression_value;
 mod binding_utils;
 mod core_types;
+mod cost_model;
 mod create_delete_ops;
 mod foreach_ops;
+mod graph_algorithms;
 mod index_seek_plan;
 mod join_apply;
 mod label_constraint;
+mod load_csv;
 mod match_bound_rel_plan;
 mod match_in_undirected_plan;
 mod match_out_plan;
 mod merge_execution;
 mod merge_helpers;
 mod merge_overlay;
+mod motif;
 mod path_usage;
 mod plan_dispatch;
 mod plan_head;
@@ -26,8 +30,11 @@ mod plan_types;
 mod procedure_registry;
 mod projection_sort;
 mod property_bridge;
+mod query_catalog;
 mod read_path;
 mod runtime_limits;
+mod spill;
+mod traversal_program;
 mod txn_engine_impl;
 mod write_dispatch;
 mod write_forwarders;
@@ -37,7 +44,7 @@ mod write_support;
 use binding_utils::{
     apply_optional_unbinds_row, row_contains_all_bindings, row_matches_node_binding,
 };
-use join_apply::{ApplyIter, ProcedureCallIter};
+use join_apply::{ApplyIter, HashJoinIter, ProcedureCallIter};
 use label_constraint::{LabelConstraint, node_matches_label_constraint, resolve_label_constraint};
 use merge_overlay::{MergeOverlayEdge, MergeOverlayNode, MergeOverlayState};
 pub use nervusdb_api::LabelId;
@@ -49,8 +56,8 @@ use property_bridge::{
     api_property_map_to_storage, merge_props_to_values, merge_storage_property_to_api,
 };
 use write_forwarders::{
-    convert_executor_value_to_property, execute_create, execute_create_write_rows, execute_delete,
-    execute_delete_on_rows, execute_foreach, execute_merge_create_from_rows,
+    convert_expression_value_to_property, execute_create, execute_create_write_rows,
+    execute_delete, execute_delete_on_rows, execute_foreach, execute_merge_create_from_rows,
 };
 use write_path::{
     apply_label_overlay_to_rows, apply_removed_property_overlay_to_rows,
@@ -62,10 +69,21 @@ const UNLABELED_LABEL_ID: LabelId = LabelId::MAX;
 pub use core_types::{NodeValue, PathValue, ReifiedPathValue, RelationshipValue, Row, Value};
 pub use plan_types::{Plan, PlanIterator};
 pub use procedure_registry::{
-    ErasedSnapshot, Procedure, ProcedureRegistry, TestProcedureField, TestProcedureFixture,
-    TestProcedureType, clear_test_procedure_fixtures, get_procedure_registry,
-    get_test_procedure_fixture, register_test_procedure_fixture,
+    ErasedSnapshot, Procedure, ProcedureRegistry, ScalarFunction, Signature, TestProcedureField,
+    TestProcedureFixture, TestProcedureType, clear_test_procedure_fixtures, get_procedure_registry,
+    get_test_procedure_fixture, register_function, register_procedure,
+    register_test_procedure_fixture, unregister_function, unregister_procedure,
 };
+pub(crate) use procedure_registry::{custom_function, custom_procedure_signature};
+pub(crate) use query_catalog::is_write_procedure;
+pub use query_catalog::validate_stored_query;
+pub use traversal_program::TraversalProgram;
+pub(crate) use traversal_program::compile_traversals;
+
+/// The operator name used for runtime-limit errors and `PROFILE` output.
+pub(crate) fn plan_operator_name(plan: &Plan) -> &'static str {
+    plan_dispatch::plan_stage_name(plan)
+}
 
 pub fn execute_plan<'a, S: GraphSnapshot + 'a>(
     snapshot: &'a S,
@@ -196,6 +214,16 @@ pub trait WriteableGraph {
     fn staged_created_nodes_with_labels(&self) -> Vec<(InternalNodeId, Vec<String>)> {
         Vec::new()
     }
+
+    /// Stages `cypher` as the stored query `name`, replacing any existing one.
+    fn save_named_query(&mut self, _name: &str, _cypher: &str) -> Result<()> {
+        Err(Error::NotImplemented("stored queries"))
+    }
+
+    /// Stages removal of the stored query `name`, returning whether it existed.
+    fn drop_named_query(&mut self, _name: &str) -> Result<bool> {
+        Err(Error::NotImplemented("stored queries"))
+    }
 }
 
 pub use nervusdb_storage::property::PropertyValue;
@@ -217,6 +245,21 @@ pub fn convert_api_property_to_value(api_value: &nervusdb_api::PropertyValue) ->
                 .map(|(k, v)| (k.clone(), convert_api_property_to_value(v)))
                 .collect(),
         ),
+        nervusdb_api::PropertyValue::Date(d) => crate::evaluator::date_value_from_epoch_days(*d),
+        nervusdb_api::PropertyValue::LocalTime(t) => {
+            crate::evaluator::local_time_value_from_nanos(*t)
+        }
+        nervusdb_api::PropertyValue::Duration {
+            months,
+            days,
+            nanos,
+        } => crate::evaluator::duration_value_from_parts(*months, *days, *nanos),
+        nervusdb_api::PropertyValue::IntArray(items) => {
+            Value::List(items.iter().map(|i| Value::Int(*i)).collect())
+        }
+        nervusdb_api::PropertyValue::FloatArray(items) => {
+            Value::List(items.iter().map(|f| Value::Float(f64::from(*f))).collect())
+        }
     }
 }
 
@@ -0,0 +1,287 @@
+//! Cardinality estimates for picking among the alternatives of a
+//! [`Plan::CostChoice`].
+//!
+//! The model is deliberately coarse: scans cost the label's node count,
+//! expansions multiply by the average fan-out of the relationship types, and
+//! filters keep a fixed fraction of their input. It only has to rank anchors
+//! and join orders, not predict row counts.
+
+use super::{GraphSnapshot, Plan, RelationshipDirection};
+
+/// Fraction of rows assumed to pass a `Filter`.
+const FILTER_SELECTIVITY: f64 = 0.5;
+/// Fraction of a label assumed to match one indexed value.
+const INDEX_SEEK_SELECTIVITY: f64 = 0.01;
+/// Hops assumed for an unbounded variable-length expansion.
+const UNBOUNDED_VAR_LEN_HOPS: u32 = 3;
+
+#[derive(Debug, Clone, Copy)]
+struct Estimate {
+    rows: f64,
+    cost: f64,
+}
+
+/// Picks the alternative with the lowest estimated cost. Without statistics
+/// (no compaction yet) every estimate is zero, so the first alternative, the
+/// textual order, wins; ties also go to the earlier alternative.
+pub(super) fn choose_alternative<'p, S: GraphSnapshot>(
+    snapshot: &S,
+    alternatives: &'p [Plan],
+) -> Option<&'p Plan> {
+    let first = alternatives.first()?;
+    if snapshot.node_count(None) == 0 {
+        return Some(first);
+    }
+    let mut best = (first, estimate(snapshot, first).cost);
+    for alternative in &alternatives[1..] {
+        let cost = estimate(snapshot, alternative).cost;
+        if cost < best.1 {
+            best = (alternative, cost);
+        }
+    }
+    Some(best.0)
+}
+
+fn estimate<S: GraphSnapshot>(snapshot: &S, plan: &Plan) -> Estimate {
+    match plan {
+        Plan::NodeScan { label, .. } => {
+            let rows = label_rows(snapshot, label.as_deref());
+            Estimate { rows, cost: rows }
+        }
+        Plan::IndexSeek {
+            label,
+            field,
+            fallback,
+            ..
+        } => {
+            if snapshot.has_index(label, field) {
+                let rows = (label_rows(snapshot, Some(label)) * INDEX_SEEK_SELECTIVITY).max(1.0);
+                Estimate { rows, cost: rows }
+            } else {
+                estimate(snapshot, fallback)
+            }
+        }
+        Plan::TextIndexSeek { fallback, .. } => estimate(snapshot, fallback),
+        Plan::CompositeIndexSeek {
+            label,
+            predicates,
+            fallback,
+            ..
+        } => {
+            let fields: Vec<String> = predicates.iter().map(|(field, _)| field.clone()).collect();
+            if snapshot.has_composite_index(label, &fields) {
+                let rows = (label_rows(snapshot, Some(label)) * INDEX_SEEK_SELECTIVITY).max(1.0);
+                Estimate { rows, cost: rows }
+            } else {
+                estimate(snapshot, fallback)
+            }
+        }
+        Plan::Filter { input, .. } => {
+            let input = estimate(snapshot, input);
+            Estimate {
+                rows: input.rows * FILTER_SELECTIVITY,
+                cost: input.cost + input.rows,
+            }
+        }
+        Plan::Project { input, .. } => estimate(snapshot, input),
+        Plan::MatchOut {
+            input,
+            rels,
+            dst_labels,
+            ..
+        } => expand(snapshot, input.as_deref(), rels, dst_labels, 1.0),
+        Plan::MatchIn {
+            input,
+            rels,
+            dst_labels,
+            ..
+        } => expand(snapshot, input.as_deref(), rels, dst_labels, 1.0),
+        Plan::MatchUndirected {
+            input,
+            rels,
+            dst_labels,
+            ..
+        } => expand(snapshot, input.as_deref(), rels, dst_labels, 2.0),
+        Plan::MatchOutVarLen {
+            input,
+            rels,
+            dst_labels,
+            direction,
+            max_hops,
+            ..
+        } => {
+            let per_hop = if matches!(direction, RelationshipDirection::Undirected) {
+                2.0
+            } else {
+                1.0
+            };
+            let hops = max_hops.unwrap_or(UNBOUNDED_VAR_LEN_HOPS).max(1);
+            let mut out = expand(snapshot, input.as_deref(), rels, &[], per_hop);
+            for _ in 1..hops {
+                let next = expand_from(snapshot, out, rels, &[], per_hop);
+                out = Estimate {
+                    rows: out.rows + next.rows,
+                    cost: next.cost,
+                };
+            }
+            out.rows *= label_fraction(snapshot, dst_labels);
+            out
+        }
+        Plan::CartesianProduct { left, right } => {
+            let left = estimate(snapshot, left);
+            let right = estimate(snapshot, right);
+            Estimate {
+                rows: left.rows * right.rows,
+                cost: left.cost + left.rows * right.cost,
+            }
+        }
+        Plan::HashJoin { left, right, .. } => {
+            // Every key is a node or relationship both sides bind, so each
+            // probe matches about as many right rows as one node fans out to.
+            let left = estimate(snapshot, left);
+            let right = estimate(snapshot, right);
+            let nodes = (snapshot.node_count(None) as f64).max(1.0);
+            Estimate {
+                rows: left.rows * (right.rows / nodes).max(1.0).min(right.rows),
+                cost: left.cost + right.cost + left.rows,
+            }
+        }
+        Plan::CostChoice { alternatives } => alternatives
+            .iter()
+            .map(|alternative| estimate(snapshot, alternative))
+            .min_by(|a, b| a.cost.total_cmp(&b.cost))
+            .unwrap_or(Estimate {
+                rows: 0.0,
+                cost: 0.0,
+            }),
+        _ => {
+            let rows = snapshot.node_count(None) as f64;
+            Estimate { rows, cost: rows }
+        }
+    }
+}
+
+fn expand<S: GraphSnapshot>(
+    snapshot: &S,
+    input: Option<&Plan>,
+    rels: &[String],
+    dst_labels: &[String],
+    per_hop: f64,
+) -> Estimate {
+    let input = input.map_or(
+        Estimate {
+            rows: 1.0,
+            cost: 0.0,
+        },
+        |plan| estimate(snapshot, plan),
+    );
+    expand_from(snapshot, input, rels, dst_labels, per_hop)
+}
+
+fn expand_from<S: GraphSnapshot>(
+    snapshot: &S,
+    input: Estimate,
+    rels: &[String],
+    dst_labels: &[String],
+    per_hop: f64,
+) -> Estimate {
+    let nodes = (snapshot.node_count(None) as f64).max(1.0);
+    let fan_out = rel_rows(snapshot, rels) / nodes * per_hop;
+    let rows = input.rows * fan_out * label_fraction(snapshot, dst_labels);
+    Estimate {
+        rows,
+        cost: input.cost + input.rows + rows,
+    }
+}
+
+fn label_rows<S: GraphSnapshot>(snapshot: &S, label: Option<&str>) -> f64 {
+    match label {
+        None => snapshot.node_count(None) as f64,
+        Some(name) => snapshot
+            .resolve_label_id(name)
+            .map_or(0.0, |id| snapshot.node_count(Some(id)) as f64),
+    }
+}
+
+fn rel_rows<S: GraphSnapshot>(snapshot: &S, rels: &[String]) -> f64 {
+    if rels.is_empty() {
+        return snapshot.edge_count(None) as f64;
+    }
+    rels.iter()
+        .filter_map(|name| snapshot.resolve_rel_type_id(name))
+        .map(|id| snapshot.edge_count(Some(id)) as f64)
+        .sum()
+}
+
+/// Fraction of all nodes carrying every label in `labels`, taking the rarest.
+fn label_fraction<S: GraphSnapshot>(snapshot: &S, labels: &[String]) -> f64 {
+    let nodes = (snapshot.node_count(None) as f64).max(1.0);
+    labels
+        .iter()
+        .map(|label| label_rows(snapshot, Some(label)) / nodes)
+        .fold(1.0, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::choose_alternative;
+    use crate::executor::Plan;
+    use nervusdb_api::{EdgeKey, GraphSnapshot, InternalNodeId, LabelId, RelTypeId};
+
+    struct Stats;
+
+    impl GraphSnapshot for Stats {
+        type Neighbors<'a> = std::iter::Empty<EdgeKey>;
+
+        fn neighbors(&self, _src: InternalNodeId, _rel: Option<RelTypeId>) -> Self::Neighbors<'_> {
+            std::iter::empty()
+        }
+
+        fn incoming_neighbors(
+            &self,
+            _dst: InternalNodeId,
+            _rel: Option<RelTypeId>,
+        ) -> Self::Neighbors<'_> {
+            std::iter::empty()
+        }
+
+        fn resolve_label_id(&self, name: &str) -> Option<LabelId> {
+            match name {
+                "Big" => Some(1),
+                "Small" => Some(2),
+                _ => None,
+            }
+        }
+
+        fn node_count(&self, label: Option<LabelId>) -> u64 {
+            match label {
+                None => 1_010,
+                Some(1) => 1_000,
+                Some(2) => 10,
+                Some(_) => 0,
+            }
+        }
+    }
+
+    fn scan(label: &str) -> Plan {
+        Plan::NodeScan {
+            alias: "n".to_string(),
+            label: Some(label.to_string()),
+            optional: false,
+        }
+    }
+
+    #[test]
+    fn picks_the_smaller_label_as_anchor() {
+        let alternatives = vec![scan("Big"), scan("Small")];
+        let chosen = choose_alternative(&Stats, &alternatives).unwrap();
+        assert!(matches!(chosen, Plan::NodeScan { label: Some(l), .. } if l == "Small"));
+    }
+
+    #[test]
+    fn keeps_the_first_alternative_on_ties() {
+        let alternatives = vec![scan("Small"), scan("Small")];
+        let chosen = choose_alternative(&Stats, &alternatives).unwrap();
+        assert!(std::ptr::eq(chosen, &alternatives[0]));
+    }
+}
@@ -1,7 +1,7 @@
 use super::{
     EdgeKey, Error, ExternalId, GraphSnapshot, InternalNodeId, NodeValue, PathElement, Pattern,
     Plan, RelationshipValue, Result, Row, UNLABELED_LABEL_ID, Value, WriteableGraph,
-    convert_executor_value_to_property, evaluate_expression_value, execute_plan,
+    convert_expression_value_to_property, evaluate_expression_value, execute_plan,
 };
 use crate::ast::Expression;
 
@@ -72,7 +72,7 @@ pub(super) fn execute_create_from_rows<S: GraphSnapshot>(
                     if matches!(val, Value::Null) {
                         continue;
                     }
-                    let prop_val = convert_executor_value_to_property(&val)?;
+                    let prop_val = convert_expression_value_to_property(&prop.value, &val)?;
                     txn.set_node_property(node_id, prop.key.clone(), prop_val)?;
                     node_props.insert(prop.key.clone(), val);
                 }
@@ -153,7 +153,7 @@ pub(super) fn execute_create_from_rows<S: GraphSnapshot>(
                     if matches!(val, Value::Null) {
                         continue;
                     }
-                    let prop_val = convert_executor_value_to_property(&val)?;
+                    let prop_val = convert_expression_value_to_property(&prop.value, &val)?;
                     txn.set_edge_property(src_id, rel_type, dst_id, prop.key.clone(), prop_val)?;
                     rel_props.insert(prop.key.clone(), val);
                 }
@@ -62,8 +62,11 @@ fn inject_rows(plan: &mut Plan, rows: Vec<super::Row>) {
         | Plan::OrderBy { input, .. }
         | Plan::Distinct { input }
         | Plan::Unwind { input, .. }
+        | Plan::LoadCsv { input, .. }
         | Plan::Aggregate { input, .. } => inject_rows(input, rows),
-        Plan::CartesianProduct { left, .. } | Plan::Union { left, .. } => inject_rows(left, rows),
+        Plan::CartesianProduct { left, .. }
+        | Plan::HashJoin { left, .. }
+        | Plan::Union { left, .. } => inject_rows(left, rows),
         Plan::Apply { input, .. } => inject_rows(input, rows),
         _ => {}
     }
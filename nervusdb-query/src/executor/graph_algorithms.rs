@@ -0,0 +1,768 @@
+//! Graph algorithms behind the `algo.*` procedures.
+//!
+//! `algo.dijkstra`, `algo.pageRank`, `algo.wcc`, `algo.triangleCount`,
+//! `algo.louvain` and `algo.sampleNeighbors` are read procedures in the
+//! registry. `algo.pageRank.write` and `algo.wcc.write` store their results
+//! as a node property, so like `query.save` they are dispatched from the
+//! write path and stage their changes on the write transaction.
+
+use super::procedure_registry::{ErasedSnapshot, Procedure};
+use super::{
+    EdgeKey, Error, InternalNodeId, PathValue, PropertyValue, Result, Row, Value, WriteableGraph,
+};
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+pub(super) const PAGE_RANK_WRITE: &str = "algo.pageRank.write";
+pub(super) const WCC_WRITE: &str = "algo.wcc.write";
+
+const DEFAULT_ITERATIONS: i64 = 20;
+const DEFAULT_DAMPING_FACTOR: f64 = 0.85;
+const DEFAULT_LOUVAIN_ITERATIONS: i64 = 10;
+const DEFAULT_LOUVAIN_TOLERANCE: f64 = 0.0001;
+
+/// A node reached at `cost`, ordered so `BinaryHeap` pops the cheapest first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Frontier {
+    cost: f64,
+    node: InternalNodeId,
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra's algorithm along outgoing relationships from `start` to `end`,
+/// weighting each relationship by its numeric `weight` property.
+///
+/// Relationships without a numeric `weight` are not traversed; a negative or
+/// non-finite weight is an error. Returns `None` when `end` is unreachable.
+pub(super) fn dijkstra(
+    snapshot: &dyn ErasedSnapshot,
+    start: InternalNodeId,
+    end: InternalNodeId,
+    weight: &str,
+) -> Result<Option<(PathValue, f64)>> {
+    let mut best: HashMap<InternalNodeId, f64> = HashMap::from([(start, 0.0)]);
+    let mut via: HashMap<InternalNodeId, EdgeKey> = HashMap::new();
+    let mut frontier = BinaryHeap::from([Frontier {
+        cost: 0.0,
+        node: start,
+    }]);
+
+    while let Some(Frontier { cost, node }) = frontier.pop() {
+        if node == end {
+            return Ok(Some((trace_path(&via, start, end), cost)));
+        }
+        if best.get(&node).is_some_and(|&known| cost > known) {
+            continue;
+        }
+        for edge in snapshot.neighbors_erased(node, None) {
+            let edge_weight = match snapshot.edge_property_erased(edge, weight) {
+                Some(PropertyValue::Int(w)) => w as f64,
+                Some(PropertyValue::Float(w)) => w,
+                _ => continue,
+            };
+            if !(edge_weight >= 0.0 && edge_weight.is_finite()) {
+                return Err(Error::Other(format!(
+                    "algo.dijkstra requires non-negative weights, got {edge_weight} for '{weight}'"
+                )));
+            }
+            let next = cost + edge_weight;
+            if best.get(&edge.dst).map_or(true, |&known| next < known) {
+                best.insert(edge.dst, next);
+                via.insert(edge.dst, edge);
+                frontier.push(Frontier {
+                    cost: next,
+                    node: edge.dst,
+                });
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Walks the `via` edges back from `end` to `start`.
+fn trace_path(
+    via: &HashMap<InternalNodeId, EdgeKey>,
+    start: InternalNodeId,
+    end: InternalNodeId,
+) -> PathValue {
+    let mut nodes = vec![end];
+    let mut edges = Vec::new();
+    let mut node = end;
+    while node != start {
+        let edge = via[&node];
+        edges.push(edge);
+        nodes.push(edge.src);
+        node = edge.src;
+    }
+    nodes.reverse();
+    edges.reverse();
+    PathValue { nodes, edges }
+}
+
+/// PageRank over every node, following relationships in their direction.
+///
+/// Scores start at `1 / n` and sum to 1; the rank of nodes without outgoing
+/// relationships is spread evenly over all nodes. Returned in node order.
+pub(super) fn page_rank(
+    snapshot: &dyn ErasedSnapshot,
+    iterations: u32,
+    damping: f64,
+) -> Vec<(InternalNodeId, f64)> {
+    let nodes: Vec<InternalNodeId> = snapshot.nodes_erased().collect();
+    if nodes.is_empty() {
+        return Vec::new();
+    }
+    let position: HashMap<InternalNodeId, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &node)| (node, i))
+        .collect();
+    let out_edges: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|&node| {
+            snapshot
+                .neighbors_erased(node, None)
+                .filter_map(|edge| position.get(&edge.dst).copied())
+                .collect()
+        })
+        .collect();
+
+    let n = nodes.len() as f64;
+    let mut scores = vec![1.0 / n; nodes.len()];
+    for _ in 0..iterations {
+        let dangling: f64 = out_edges
+            .iter()
+            .zip(&scores)
+            .filter(|(targets, _)| targets.is_empty())
+            .map(|(_, score)| score)
+            .sum();
+        let base = (1.0 - damping) / n + damping * dangling / n;
+        let mut next = vec![base; nodes.len()];
+        for (targets, score) in out_edges.iter().zip(&scores) {
+            let share = damping * score / targets.len() as f64;
+            for &target in targets {
+                next[target] += share;
+            }
+        }
+        scores = next;
+    }
+    nodes.into_iter().zip(scores).collect()
+}
+
+/// Weakly connected components: nodes joined by relationships in either
+/// direction share a component, identified by its lowest node id. Returned in
+/// node order.
+pub(super) fn weakly_connected_components(
+    snapshot: &dyn ErasedSnapshot,
+) -> Vec<(InternalNodeId, InternalNodeId)> {
+    fn root(
+        parent: &mut HashMap<InternalNodeId, InternalNodeId>,
+        node: InternalNodeId,
+    ) -> InternalNodeId {
+        let mut root = node;
+        while parent[&root] != root {
+            root = parent[&root];
+        }
+        let mut node = node;
+        while node != root {
+            node = parent.insert(node, root).unwrap_or(root);
+        }
+        root
+    }
+
+    let nodes: Vec<InternalNodeId> = snapshot.nodes_erased().collect();
+    let mut parent: HashMap<InternalNodeId, InternalNodeId> =
+        nodes.iter().map(|&node| (node, node)).collect();
+    for &node in &nodes {
+        for edge in snapshot.neighbors_erased(node, None) {
+            if !parent.contains_key(&edge.dst) {
+                continue;
+            }
+            let (a, b) = (root(&mut parent, edge.src), root(&mut parent, edge.dst));
+            // Keep the lower id as the root so it names the component.
+            match a.cmp(&b) {
+                Ordering::Less => parent.insert(b, a),
+                Ordering::Greater => parent.insert(a, b),
+                Ordering::Equal => None,
+            };
+        }
+    }
+    nodes
+        .into_iter()
+        .map(|node| (node, root(&mut parent, node)))
+        .collect()
+}
+
+/// Triangles through each node and its local clustering coefficient,
+/// treating relationships as undirected and ignoring self-loops and parallel
+/// relationships. Returned in node order.
+///
+/// The adjacency is read from the snapshot first; the per-node counts then
+/// run in parallel on it.
+pub(super) fn triangle_count(snapshot: &dyn ErasedSnapshot) -> Vec<(InternalNodeId, u64, f64)> {
+    let nodes: Vec<InternalNodeId> = snapshot.nodes_erased().collect();
+    let position: HashMap<InternalNodeId, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &node)| (node, i))
+        .collect();
+    let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (i, &node) in nodes.iter().enumerate() {
+        for edge in snapshot.neighbors_erased(node, None) {
+            match position.get(&edge.dst) {
+                Some(&j) if j != i => {
+                    adjacent[i].push(j);
+                    adjacent[j].push(i);
+                }
+                _ => {}
+            }
+        }
+    }
+    for neighbors in &mut adjacent {
+        neighbors.sort_unstable();
+        neighbors.dedup();
+    }
+
+    let counts: Vec<(u64, f64)> = adjacent
+        .par_iter()
+        .map(|neighbors| {
+            // Each triangle through this node is seen from both other corners.
+            let closed: u64 = neighbors
+                .iter()
+                .map(|&other| shared(neighbors, &adjacent[other]))
+                .sum();
+            let triangles = closed / 2;
+            let degree = neighbors.len() as u64;
+            let coefficient = if degree < 2 {
+                0.0
+            } else {
+                (2 * triangles) as f64 / (degree * (degree - 1)) as f64
+            };
+            (triangles, coefficient)
+        })
+        .collect();
+    nodes
+        .into_iter()
+        .zip(counts)
+        .map(|(node, (triangles, coefficient))| (node, triangles, coefficient))
+        .collect()
+}
+
+/// Louvain community detection, treating each relationship as an undirected
+/// edge of weight 1. Returns each node with its community, identified by its
+/// lowest node id, in node order.
+///
+/// Each level moves nodes between neighboring communities for at most
+/// `max_iterations` sweeps, then merges every community into a single node for
+/// the next level. Levels stop once no node moves or modularity improves by
+/// no more than `tolerance`.
+pub(super) fn louvain(
+    snapshot: &dyn ErasedSnapshot,
+    max_iterations: u32,
+    tolerance: f64,
+) -> Vec<(InternalNodeId, InternalNodeId)> {
+    let nodes: Vec<InternalNodeId> = snapshot.nodes_erased().collect();
+    let position: HashMap<InternalNodeId, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &node)| (node, i))
+        .collect();
+    // Symmetric weights: a self-loop adds 2 to its diagonal entry, so every
+    // row sums to the node's degree.
+    let mut weights: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); nodes.len()];
+    for (i, &node) in nodes.iter().enumerate() {
+        for edge in snapshot.neighbors_erased(node, None) {
+            if let Some(&j) = position.get(&edge.dst) {
+                *weights[i].entry(j).or_default() += 1.0;
+                *weights[j].entry(i).or_default() += 1.0;
+            }
+        }
+    }
+
+    // `membership[i]` is the level node holding original node `i`.
+    let mut membership: Vec<usize> = (0..nodes.len()).collect();
+    let mut modularity = louvain_modularity(&weights, &(0..weights.len()).collect::<Vec<_>>());
+    while let Some(community) = louvain_local_moves(&weights, max_iterations) {
+        let gained = louvain_modularity(&weights, &community) - modularity;
+        modularity += gained;
+        let count = community.iter().max().map_or(0, |&c| c + 1);
+        let mut merged: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); count];
+        for (i, row) in weights.iter().enumerate() {
+            for (&j, &w) in row {
+                *merged[community[i]].entry(community[j]).or_default() += w;
+            }
+        }
+        weights = merged;
+        for level_node in &mut membership {
+            *level_node = community[*level_node];
+        }
+        if gained <= tolerance {
+            break;
+        }
+    }
+
+    let mut lowest: HashMap<usize, InternalNodeId> = HashMap::new();
+    for (&node, &community) in nodes.iter().zip(&membership) {
+        let id = lowest.entry(community).or_insert(node);
+        *id = (*id).min(node);
+    }
+    nodes
+        .into_iter()
+        .zip(membership)
+        .map(|(node, community)| (node, lowest[&community]))
+        .collect()
+}
+
+/// One Louvain level: moves each node to the neighboring community with the
+/// largest modularity gain until a sweep moves nothing or `max_iterations`
+/// sweeps have run. Returns communities numbered from 0, or `None` if no node
+/// moved.
+fn louvain_local_moves(
+    weights: &[BTreeMap<usize, f64>],
+    max_iterations: u32,
+) -> Option<Vec<usize>> {
+    let degree: Vec<f64> = weights.iter().map(|row| row.values().sum()).collect();
+    let total: f64 = degree.iter().sum();
+    if total == 0.0 {
+        return None;
+    }
+    let mut community: Vec<usize> = (0..weights.len()).collect();
+    let mut community_degree = degree.clone();
+    let mut moved_any = false;
+    for _ in 0..max_iterations {
+        let mut moved = false;
+        for (i, row) in weights.iter().enumerate() {
+            let current = community[i];
+            community_degree[current] -= degree[i];
+            let mut links: BTreeMap<usize, f64> = BTreeMap::from([(current, 0.0)]);
+            for (&j, &w) in row {
+                if j != i {
+                    *links.entry(community[j]).or_default() += w;
+                }
+            }
+            let gain = |c: usize, link: f64| link - community_degree[c] * degree[i] / total;
+            let mut best = (current, gain(current, links[&current]));
+            for (&c, &link) in &links {
+                let candidate = gain(c, link);
+                if candidate > best.1 {
+                    best = (c, candidate);
+                }
+            }
+            community[i] = best.0;
+            community_degree[best.0] += degree[i];
+            if best.0 != current {
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+        moved_any = true;
+    }
+    if !moved_any {
+        return None;
+    }
+    let mut renumbered: HashMap<usize, usize> = HashMap::new();
+    for c in &mut community {
+        let next = renumbered.len();
+        *c = *renumbered.entry(*c).or_insert(next);
+    }
+    Some(community)
+}
+
+/// Modularity of the partition `community` over symmetric `weights`.
+fn louvain_modularity(weights: &[BTreeMap<usize, f64>], community: &[usize]) -> f64 {
+    let total: f64 = weights.iter().flat_map(|row| row.values()).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let mut inside: HashMap<usize, f64> = HashMap::new();
+    let mut degree: HashMap<usize, f64> = HashMap::new();
+    for (i, row) in weights.iter().enumerate() {
+        for (&j, &w) in row {
+            *degree.entry(community[i]).or_default() += w;
+            if community[i] == community[j] {
+                *inside.entry(community[i]).or_default() += w;
+            }
+        }
+    }
+    degree
+        .iter()
+        .map(|(c, d)| inside.get(c).copied().unwrap_or(0.0) / total - (d / total).powi(2))
+        .sum()
+}
+
+/// Number of entries two sorted lists have in common.
+fn shared(a: &[usize], b: &[usize]) -> u64 {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+/// The optional configuration map of an `algo.*` procedure, rejecting keys
+/// other than `allowed`.
+pub(super) fn config(
+    procedure: &str,
+    args: &[Value],
+    allowed: &[&str],
+) -> Result<BTreeMap<String, Value>> {
+    let config = match args {
+        [] | [Value::Null] => BTreeMap::new(),
+        [Value::Map(config)] => config.clone(),
+        _ => {
+            return Err(Error::Other(format!(
+                "{procedure} takes an optional configuration map"
+            )));
+        }
+    };
+    if let Some(key) = config.keys().find(|key| !allowed.contains(&key.as_str())) {
+        return Err(Error::Other(format!(
+            "{procedure}: unknown configuration key '{key}'"
+        )));
+    }
+    Ok(config)
+}
+
+/// `iterations` and `dampingFactor` from a PageRank configuration.
+fn page_rank_settings(procedure: &str, config: &BTreeMap<String, Value>) -> Result<(u32, f64)> {
+    let iterations = match config.get("iterations") {
+        None => DEFAULT_ITERATIONS,
+        Some(Value::Int(iterations)) if (0..=u32::MAX as i64).contains(iterations) => *iterations,
+        Some(_) => {
+            return Err(Error::Other(format!(
+                "{procedure}: iterations must be a non-negative integer"
+            )));
+        }
+    };
+    let damping = match config.get("dampingFactor") {
+        None => DEFAULT_DAMPING_FACTOR,
+        Some(Value::Float(d)) if (0.0..1.0).contains(d) => *d,
+        Some(Value::Int(0)) => 0.0,
+        Some(_) => {
+            return Err(Error::Other(format!(
+                "{procedure}: dampingFactor must be a number in [0, 1)"
+            )));
+        }
+    };
+    Ok((iterations as u32, damping))
+}
+
+fn write_property(procedure: &str, config: &BTreeMap<String, Value>) -> Result<String> {
+    match config.get("writeProperty") {
+        Some(Value::String(property)) if !property.is_empty() => Ok(property.clone()),
+        _ => Err(Error::Other(format!(
+            "{procedure} requires a writeProperty name"
+        ))),
+    }
+}
+
+/// `algo.dijkstra(start, end, weightProperty)`: the cheapest path from
+/// `start` to `end` along outgoing relationships, weighted by
+/// `weightProperty`, as one `path`, `cost` row. No row when `end` is
+/// unreachable.
+pub(super) struct DijkstraProcedure;
+
+impl Procedure for DijkstraProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        if args.len() != 3 {
+            return Err(Error::Other(
+                "algo.dijkstra requires 3 arguments".to_string(),
+            ));
+        }
+        let node = |value: &Value| match value {
+            Value::NodeId(id) => Ok(Some(*id)),
+            Value::Node(node) => Ok(Some(node.id)),
+            Value::Null => Ok(None),
+            _ => Err(Error::Other(
+                "algo.dijkstra requires start and end nodes".to_string(),
+            )),
+        };
+        let (Some(start), Some(end)) = (node(&args[0])?, node(&args[1])?) else {
+            return Ok(Vec::new());
+        };
+        let Value::String(weight) = &args[2] else {
+            return Err(Error::Other(
+                "algo.dijkstra requires a weight property name".to_string(),
+            ));
+        };
+        Ok(dijkstra(snapshot, start, end, weight)?
+            .into_iter()
+            .map(|(path, cost)| {
+                Row::new(vec![
+                    ("path".to_string(), Value::Path(path)),
+                    ("cost".to_string(), Value::Float(cost)),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `algo.pageRank({iterations, dampingFactor})`: one `node`, `score` row per
+/// node.
+pub(super) struct PageRankProcedure;
+
+impl Procedure for PageRankProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        let config = config("algo.pageRank", &args, &["iterations", "dampingFactor"])?;
+        let (iterations, damping) = page_rank_settings("algo.pageRank", &config)?;
+        Ok(page_rank(snapshot, iterations, damping)
+            .into_iter()
+            .map(|(node, score)| {
+                Row::new(vec![
+                    ("node".to_string(), Value::NodeId(node)),
+                    ("score".to_string(), Value::Float(score)),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `algo.wcc()`: one `node`, `componentId` row per node.
+pub(super) struct WccProcedure;
+
+impl Procedure for WccProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        config("algo.wcc", &args, &[])?;
+        Ok(weakly_connected_components(snapshot)
+            .into_iter()
+            .map(|(node, component)| {
+                Row::new(vec![
+                    ("node".to_string(), Value::NodeId(node)),
+                    ("componentId".to_string(), Value::Int(i64::from(component))),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `algo.triangleCount()`: one `nodeId`, `triangles`, `coefficient` row per
+/// node.
+pub(super) struct TriangleCountProcedure;
+
+impl Procedure for TriangleCountProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        config("algo.triangleCount", &args, &[])?;
+        Ok(triangle_count(snapshot)
+            .into_iter()
+            .map(|(node, triangles, coefficient)| {
+                Row::new(vec![
+                    ("nodeId".to_string(), Value::Int(i64::from(node))),
+                    ("triangles".to_string(), Value::Int(triangles as i64)),
+                    ("coefficient".to_string(), Value::Float(coefficient)),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `algo.louvain(maxIterations, tolerance)`: one `nodeId`, `communityId` row
+/// per node. Both arguments are optional.
+pub(super) struct LouvainProcedure;
+
+impl Procedure for LouvainProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        if args.len() > 2 {
+            return Err(Error::Other(
+                "algo.louvain takes at most 2 arguments".to_string(),
+            ));
+        }
+        let max_iterations = match args.first() {
+            None | Some(Value::Null) => DEFAULT_LOUVAIN_ITERATIONS,
+            Some(Value::Int(n)) if (1..=u32::MAX as i64).contains(n) => *n,
+            Some(_) => {
+                return Err(Error::Other(
+                    "algo.louvain: maxIterations must be a positive integer".to_string(),
+                ));
+            }
+        };
+        let tolerance = match args.get(1) {
+            None | Some(Value::Null) => DEFAULT_LOUVAIN_TOLERANCE,
+            Some(Value::Float(t)) if *t >= 0.0 => *t,
+            Some(Value::Int(t)) if *t >= 0 => *t as f64,
+            Some(_) => {
+                return Err(Error::Other(
+                    "algo.louvain: tolerance must be a non-negative number".to_string(),
+                ));
+            }
+        };
+        Ok(louvain(snapshot, max_iterations as u32, tolerance)
+            .into_iter()
+            .map(|(node, community)| {
+                Row::new(vec![
+                    ("nodeId".to_string(), Value::Int(i64::from(node))),
+                    ("communityId".to_string(), Value::Int(i64::from(community))),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `algo.sampleNeighbors(node, k, {relationshipType, weightProperty, seed})`:
+/// up to `k` outgoing relationships of `node` drawn at random, one
+/// `relationship`, `neighbor` row each, in draw order. With `weightProperty`
+/// relationships are drawn in proportion to that property. Without a `seed`
+/// each call draws afresh.
+pub(super) struct SampleNeighborsProcedure;
+
+impl Procedure for SampleNeighborsProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        if !(2..=3).contains(&args.len()) {
+            return Err(Error::Other(
+                "algo.sampleNeighbors requires a node, a sample size and an optional configuration map"
+                    .to_string(),
+            ));
+        }
+        let node = match &args[0] {
+            Value::NodeId(id) => *id,
+            Value::Node(node) => node.id,
+            Value::Null => return Ok(Vec::new()),
+            _ => {
+                return Err(Error::Other(
+                    "algo.sampleNeighbors requires a node".to_string(),
+                ));
+            }
+        };
+        let k = match &args[1] {
+            Value::Int(k) if *k >= 0 => *k as usize,
+            _ => {
+                return Err(Error::Other(
+                    "algo.sampleNeighbors: k must be a non-negative integer".to_string(),
+                ));
+            }
+        };
+        let config = config(
+            "algo.sampleNeighbors",
+            &args[2..],
+            &["relationshipType", "weightProperty", "seed"],
+        )?;
+        let rel = match config.get("relationshipType") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(name)) => match snapshot.resolve_rel_type_id_erased(name) {
+                Some(rel) => Some(rel),
+                None => return Ok(Vec::new()),
+            },
+            Some(_) => {
+                return Err(Error::Other(
+                    "algo.sampleNeighbors: relationshipType must be a string".to_string(),
+                ));
+            }
+        };
+        let weight = match config.get("weightProperty") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(property)) => Some(property.as_str()),
+            Some(_) => {
+                return Err(Error::Other(
+                    "algo.sampleNeighbors: weightProperty must be a string".to_string(),
+                ));
+            }
+        };
+        let seed = match config.get("seed") {
+            None | Some(Value::Null) => {
+                use std::hash::{BuildHasher, Hasher};
+                std::collections::hash_map::RandomState::new()
+                    .build_hasher()
+                    .finish()
+            }
+            Some(Value::Int(seed)) => *seed as u64,
+            Some(_) => {
+                return Err(Error::Other(
+                    "algo.sampleNeighbors: seed must be an integer".to_string(),
+                ));
+            }
+        };
+        Ok(snapshot
+            .sample_neighbors_erased(node, rel, k, weight, seed)
+            .into_iter()
+            .map(|edge| {
+                Row::new(vec![
+                    ("relationship".to_string(), Value::EdgeKey(edge)),
+                    ("neighbor".to_string(), Value::NodeId(edge.dst)),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// Runs `algo.pageRank.write` or `algo.wcc.write`, staging one property per
+/// node on `txn`. Returns the number of properties written and the
+/// procedure's row.
+pub(super) fn execute_write_algorithm(
+    procedure: &str,
+    snapshot: &dyn ErasedSnapshot,
+    args: &[Value],
+    txn: &mut dyn WriteableGraph,
+) -> Result<(u32, Row)> {
+    if procedure == PAGE_RANK_WRITE {
+        let config = config(
+            procedure,
+            args,
+            &["iterations", "dampingFactor", "writeProperty"],
+        )?;
+        let (iterations, damping) = page_rank_settings(procedure, &config)?;
+        let property = write_property(procedure, &config)?;
+        let scores = page_rank(snapshot, iterations, damping);
+        for &(node, score) in &scores {
+            txn.set_node_property(node, property.clone(), PropertyValue::Float(score))?;
+        }
+        let written = scores.len() as u32;
+        return Ok((
+            written,
+            Row::new(vec![(
+                "nodePropertiesWritten".to_string(),
+                Value::Int(i64::from(written)),
+            )]),
+        ));
+    }
+
+    let config = config(procedure, args, &["writeProperty"])?;
+    let property = write_property(procedure, &config)?;
+    let components = weakly_connected_components(snapshot);
+    for &(node, component) in &components {
+        txn.set_node_property(
+            node,
+            property.clone(),
+            PropertyValue::Int(i64::from(component)),
+        )?;
+    }
+    let mut ids: Vec<InternalNodeId> = components.iter().map(|&(_, id)| id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let written = components.len() as u32;
+    Ok((
+        written,
+        Row::new(vec![
+            (
+                "nodePropertiesWritten".to_string(),
+                Value::Int(i64::from(written)),
+            ),
+            ("componentCount".to_string(), Value::Int(ids.len() as i64)),
+        ]),
+    ))
+}
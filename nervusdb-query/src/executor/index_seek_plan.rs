@@ -20,27 +20,105 @@ pub(super) fn execute_index_seek<'a, S: GraphSnapshot + 'a>(
         return PlanIterator::Dynamic(Box::new(std::iter::once(Err(err))));
     }
     let val = evaluate_expression_value(value_expr, &Row::default(), snapshot, params);
+    let Some(prop_val) = seek_value(val) else {
+        return execute_plan(snapshot, fallback, params);
+    };
+
+    if let Some(node_ids) = snapshot.lookup_index(label, field, &prop_val) {
+        seek_rows(alias, node_ids)
+    } else {
+        execute_plan(snapshot, fallback, params)
+    }
+}
+
+pub(super) fn execute_composite_index_seek<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    alias: &str,
+    label: &str,
+    predicates: &'a [(String, crate::ast::Expression)],
+    fallback: &'a Plan,
+    params: &'a crate::query_api::Params,
+) -> PlanIterator<'a, S> {
+    let mut bound = Vec::with_capacity(predicates.len());
+    for (field, value_expr) in predicates {
+        if let Err(err) = super::plan_mid::ensure_runtime_expression_compatible(
+            value_expr,
+            &Row::default(),
+            snapshot,
+            params,
+        ) {
+            return PlanIterator::Dynamic(Box::new(std::iter::once(Err(err))));
+        }
+        let val = evaluate_expression_value(value_expr, &Row::default(), snapshot, params);
+        let Some(prop_val) = seek_value(val) else {
+            return execute_plan(snapshot, fallback, params);
+        };
+        bound.push((field.clone(), prop_val));
+    }
+
+    if let Some(node_ids) = snapshot.lookup_composite_index(label, &bound) {
+        seek_rows(alias, node_ids)
+    } else {
+        execute_plan(snapshot, fallback, params)
+    }
+}
 
-    let prop_val = match val {
+/// Converts a seek key to a storable value; lists, maps and graph values
+/// cannot be index keys, so their seeks use the fallback plan.
+fn seek_value(val: Value) -> Option<nervusdb_api::PropertyValue> {
+    Some(match val {
         Value::Null => nervusdb_api::PropertyValue::Null,
         Value::Bool(b) => nervusdb_api::PropertyValue::Bool(b),
         Value::Int(i) => nervusdb_api::PropertyValue::Int(i),
         Value::Float(f) => nervusdb_api::PropertyValue::Float(f),
         Value::String(s) => nervusdb_api::PropertyValue::String(s),
-        _ => {
-            return execute_plan(snapshot, fallback, params);
-        }
+        _ => return None,
+    })
+}
+
+fn seek_rows<'a, S: GraphSnapshot + 'a>(
+    alias: &str,
+    mut node_ids: Vec<nervusdb_api::InternalNodeId>,
+) -> PlanIterator<'a, S> {
+    node_ids.sort();
+    let alias = alias.to_string();
+    PlanIterator::Dynamic(Box::new(
+        node_ids
+            .into_iter()
+            .map(move |iid| Ok(Row::default().with(alias.clone(), Value::NodeId(iid)))),
+    ))
+}
+
+pub(super) fn execute_text_index_seek<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    alias: &str,
+    label: &str,
+    field: &str,
+    needle_expr: &'a crate::ast::Expression,
+    fallback: &'a Plan,
+    params: &'a crate::query_api::Params,
+) -> PlanIterator<'a, S> {
+    let Value::String(needle) =
+        evaluate_expression_value(needle_expr, &Row::default(), snapshot, params)
+    else {
+        return execute_plan(snapshot, fallback, params);
+    };
+    let (Some(label_id), Some(candidates)) = (
+        snapshot.resolve_label_id(label),
+        snapshot.lookup_text_index(label, field, &needle),
+    ) else {
+        return execute_plan(snapshot, fallback, params);
     };
 
-    if let Some(mut node_ids) = snapshot.lookup_index(label, field, &prop_val) {
-        node_ids.sort();
-        let alias = alias.to_string();
-        PlanIterator::Dynamic(Box::new(
-            node_ids
-                .into_iter()
-                .map(move |iid| Ok(Row::default().with(alias.clone(), Value::NodeId(iid)))),
-        ))
-    } else {
-        execute_plan(snapshot, fallback, params)
-    }
+    let alias = alias.to_string();
+    PlanIterator::Dynamic(Box::new(
+        candidates
+            .into_iter()
+            .filter(move |iid| {
+                snapshot
+                    .resolve_node_labels(*iid)
+                    .is_some_and(|labels| labels.contains(&label_id))
+            })
+            .map(move |iid| Ok(Row::default().with(alias.clone(), Value::NodeId(iid)))),
+    ))
 }
@@ -1,10 +1,11 @@
 use super::{
-    ErasedSnapshot, Error, Plan, PlanIterator, Result, Row, execute_plan, get_procedure_registry,
-    get_test_procedure_fixture,
+    ErasedSnapshot, Error, Plan, PlanIterator, Result, Row, Value, execute_plan,
+    get_procedure_registry, get_test_procedure_fixture,
 };
 use crate::ast::Expression;
 use crate::evaluator::evaluate_expression_value;
 use nervusdb_api::GraphSnapshot;
+use std::collections::HashMap;
 
 pub struct ApplyIter<'a, S: GraphSnapshot> {
     pub(super) input_iter: Box<PlanIterator<'a, S>>,
@@ -202,3 +203,79 @@ impl<'a, S: GraphSnapshot + 'a> Iterator for ProcedureCallIter<'a, S> {
         }
     }
 }
+
+/// Joins the left input with the right plan on the values of `keys`. The
+/// right plan runs once and is held in a hash table; rows with a missing or
+/// null key never match.
+pub struct HashJoinIter<'a, S: GraphSnapshot> {
+    pub(super) left_iter: Box<PlanIterator<'a, S>>,
+    pub(super) right_plan: &'a Plan,
+    pub(super) keys: &'a [String],
+    pub(super) snapshot: &'a S,
+    pub(super) params: &'a crate::query_api::Params,
+    pub(super) table: Option<HashMap<Vec<Value>, Vec<Row>>>,
+    pub(super) pending: std::vec::IntoIter<Row>,
+}
+
+impl<'a, S: GraphSnapshot> HashJoinIter<'a, S> {
+    fn build(&self) -> Result<HashMap<Vec<Value>, Vec<Row>>> {
+        let mut table: HashMap<Vec<Value>, Vec<Row>> = HashMap::new();
+        for row in execute_plan(self.snapshot, self.right_plan, self.params) {
+            self.params.check_timeout("HashJoin.build")?;
+            let row = row?;
+            if let Some(key) = join_key(&row, self.keys) {
+                // The key columns are already on the left row.
+                let rest = row
+                    .cols
+                    .into_iter()
+                    .filter(|(name, _)| !self.keys.contains(name))
+                    .collect();
+                table.entry(key).or_default().push(Row::new(rest));
+            }
+        }
+        Ok(table)
+    }
+}
+
+impl<'a, S: GraphSnapshot> Iterator for HashJoinIter<'a, S> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.table.is_none() {
+            match self.build() {
+                Ok(table) => self.table = Some(table),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        loop {
+            if let Some(row) = self.pending.next() {
+                return Some(Ok(row));
+            }
+            let left = match self.left_iter.next()? {
+                Ok(row) => row,
+                Err(err) => return Some(Err(err)),
+            };
+            let Some(matches) = join_key(&left, self.keys)
+                .and_then(|key| self.table.as_ref().and_then(|table| table.get(&key)))
+            else {
+                continue;
+            };
+            self.pending = matches
+                .iter()
+                .map(|right| left.join(right))
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
+
+fn join_key(row: &Row, keys: &[String]) -> Option<Vec<Value>> {
+    keys.iter()
+        .map(|key| match row.get(key)? {
+            Value::Null => None,
+            Value::Node(node) => Some(Value::NodeId(node.id)),
+            Value::Relationship(rel) => Some(Value::EdgeKey(rel.key)),
+            value => Some(value.clone()),
+        })
+        .collect()
+}
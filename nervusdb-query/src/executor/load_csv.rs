@@ -0,0 +1,208 @@
+use super::{Error, GraphSnapshot, Plan, PlanIterator, Row, Value, execute_plan};
+use crate::query_api::Params;
+use nervusdb_api::FileImport;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Streams the records of the CSV file each input row names, binding each
+/// record to `alias`: a map keyed by the header line `with_headers`, a list
+/// of strings otherwise. Only the current record is held in memory.
+pub(super) fn execute_load_csv<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    input: &'a Plan,
+    source: &'a crate::ast::Expression,
+    alias: &'a str,
+    with_headers: bool,
+    field_terminator: u8,
+    params: &'a Params,
+) -> PlanIterator<'a, S> {
+    let input_iter = execute_plan(snapshot, input, params);
+    let alias = alias.to_string();
+    let params = params.clone();
+
+    PlanIterator::Dynamic(Box::new(input_iter.flat_map(
+        move |result| -> Box<dyn Iterator<Item = super::Result<Row>>> {
+            let row = match result {
+                Ok(row) => row,
+                Err(e) => return Box::new(std::iter::once(Err(e))),
+            };
+            let url = crate::evaluator::evaluate_expression_value(source, &row, snapshot, &params);
+            let Value::String(url) = url else {
+                return Box::new(std::iter::once(Err(Error::Other(format!(
+                    "LOAD CSV source must be a file:// URL string, got {url:?}"
+                )))));
+            };
+            let records = match CsvRecords::open(
+                &url,
+                &snapshot.file_import(),
+                with_headers,
+                field_terminator,
+                params.clone(),
+            ) {
+                Ok(records) => records,
+                Err(e) => return Box::new(std::iter::once(Err(e))),
+            };
+            let alias = alias.clone();
+            Box::new(records.map(move |record| Ok(row.clone().with(alias.clone(), record?))))
+        },
+    )))
+}
+
+struct CsvRecords {
+    path: String,
+    reader: csv::Reader<File>,
+    headers: Option<Vec<String>>,
+    /// Offset of the reader's start in the file.
+    base: u64,
+    /// Records left in the current `USING PERIODIC COMMIT` batch.
+    remaining: Option<usize>,
+    record: csv::StringRecord,
+    params: Params,
+    done: bool,
+}
+
+impl CsvRecords {
+    fn open(
+        url: &str,
+        access: &FileImport,
+        with_headers: bool,
+        field_terminator: u8,
+        params: Params,
+    ) -> super::Result<Self> {
+        let Some(path) = url.strip_prefix("file://") else {
+            return Err(Error::Other(format!(
+                "LOAD CSV supports file:// URLs only, got '{url}'"
+            )));
+        };
+        let file = resolve(path, access)?;
+        let offset = params.csv_batch().map_or(0, |batch| batch.offset);
+        let open = || File::open(&file).map_err(|e| Error::Other(format!("LOAD CSV {path}: {e}")));
+        let builder = || {
+            let mut builder = csv::ReaderBuilder::new();
+            builder
+                .has_headers(false)
+                .flexible(true)
+                .delimiter(field_terminator);
+            builder
+        };
+
+        let mut reader = builder().from_reader(open()?);
+        let mut headers = None;
+        if with_headers {
+            let mut record = csv::StringRecord::new();
+            if reader
+                .read_record(&mut record)
+                .map_err(|e| Error::Other(format!("LOAD CSV {path}: {e}")))?
+            {
+                headers = Some(record.iter().map(str::to_string).collect());
+            }
+        }
+        // A later batch reads the header line above, then skips ahead.
+        if offset > 0 {
+            let mut file = open()?;
+            file.seek(SeekFrom::Start(offset))
+                .map_err(|e| Error::Other(format!("LOAD CSV {path}: {e}")))?;
+            reader = builder().from_reader(file);
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            reader,
+            headers,
+            base: offset,
+            remaining: params.csv_batch().map(|batch| batch.rows),
+            record: csv::StringRecord::new(),
+            params,
+            done: false,
+        })
+    }
+
+    fn value(&self) -> Value {
+        match &self.headers {
+            // Missing and empty fields read as null, as in other Cypher stores.
+            Some(headers) => Value::Map(
+                headers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, name)| {
+                        let value = match self.record.get(i) {
+                            Some(field) if !field.is_empty() => Value::String(field.to_string()),
+                            _ => Value::Null,
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect::<BTreeMap<_, _>>(),
+            ),
+            None => Value::List(
+                self.record
+                    .iter()
+                    .map(|field| Value::String(field.to_string()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The file `path` names, if the snapshot lets `LOAD CSV` read it.
+fn resolve(path: &str, access: &FileImport) -> super::Result<PathBuf> {
+    match access {
+        FileImport::Disabled => Err(Error::Other(
+            "LOAD CSV file import is disabled on this database handle".into(),
+        )),
+        FileImport::Anywhere => Ok(PathBuf::from(path)),
+        FileImport::Under(root) => {
+            // Canonical, so `..` and symlinks cannot lead out of the root.
+            let file = root
+                .join(Path::new(path))
+                .canonicalize()
+                .map_err(|e| Error::Other(format!("LOAD CSV {path}: {e}")))?;
+            if file.starts_with(root) {
+                Ok(file)
+            } else {
+                Err(Error::Other(format!(
+                    "LOAD CSV {path}: outside the import root {}",
+                    root.display()
+                )))
+            }
+        }
+    }
+}
+
+impl Iterator for CsvRecords {
+    type Item = super::Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.remaining == Some(0) {
+            self.done = true;
+            self.params
+                .set_csv_resume_at(Some(self.base + self.reader.position().byte()));
+            return None;
+        }
+        if let Err(err) = self.params.check_timeout("LoadCsv.read") {
+            self.done = true;
+            return Some(Err(err));
+        }
+        match self.reader.read_record(&mut self.record) {
+            Ok(true) => {
+                if let Some(remaining) = &mut self.remaining {
+                    *remaining -= 1;
+                }
+                Some(Ok(self.value()))
+            }
+            Ok(false) => {
+                self.done = true;
+                self.params.set_csv_resume_at(None);
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(Error::Other(format!("LOAD CSV {}: {e}", self.path))))
+            }
+        }
+    }
+}
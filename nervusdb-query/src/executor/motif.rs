@@ -0,0 +1,540 @@
+//! Motif matching behind `algo.motif` and `algo.motif.count`.
+//!
+//! A motif is a small pattern such as `(a)-->(b)-->(c)<--(a)`. Its
+//! occurrences are found with a generic join: motif nodes are bound one at a
+//! time, and the candidates for each are the intersection of the sorted
+//! adjacency lists of its already-bound neighbors. Walking the shortest list
+//! and probing the others keeps the work within the worst-case-optimal bound
+//! for cyclic motifs such as triangles and 4-cliques, where joining one
+//! relationship at a time can blow up on high-degree nodes.
+
+use super::graph_algorithms::config;
+use super::procedure_registry::{ErasedSnapshot, Procedure};
+use super::{Error, InternalNodeId, RelTypeId, Result, Row, Value};
+use crate::ast::{Clause, PathElement, RelationshipDirection, RelationshipPattern};
+use crate::parser::Parser;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// A node of the motif: its variable, if named, and the labels it needs.
+struct MotifNode {
+    name: Option<String>,
+    labels: Vec<String>,
+}
+
+/// A relationship of the motif. `types` are alternatives; none means any.
+struct MotifEdge {
+    from: usize,
+    to: usize,
+    types: Vec<String>,
+    directed: bool,
+}
+
+impl MotifEdge {
+    fn new(left: usize, right: usize, rel: &RelationshipPattern) -> Self {
+        let (from, to) = match rel.direction {
+            RelationshipDirection::RightToLeft => (right, left),
+            _ => (left, right),
+        };
+        Self {
+            from,
+            to,
+            types: rel.types.clone(),
+            directed: rel.direction != RelationshipDirection::Undirected,
+        }
+    }
+}
+
+struct Motif {
+    nodes: Vec<MotifNode>,
+    edges: Vec<MotifEdge>,
+}
+
+impl Motif {
+    /// Parses one or more comma-separated patterns. Properties,
+    /// variable-length relationships and relationship or path variables are
+    /// rejected: a motif is pure structure.
+    fn parse(procedure: &str, pattern: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::Other(format!("{procedure}: {reason}"));
+        let query = Parser::parse(&format!("MATCH {pattern} RETURN 1"))
+            .map_err(|e| invalid(&format!("invalid motif pattern: {e}")))?;
+        let patterns = match query.clauses.as_slice() {
+            [Clause::Match(clause), Clause::Return(_)] if !clause.optional => &clause.patterns,
+            _ => return Err(invalid("the motif must be a node and relationship pattern")),
+        };
+
+        let mut motif = Motif {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        let mut named: HashMap<&str, usize> = HashMap::new();
+        for pattern in patterns {
+            if pattern.variable.is_some() {
+                return Err(invalid("motif patterns cannot bind a path variable"));
+            }
+            let mut previous = None;
+            let mut pending: Option<&RelationshipPattern> = None;
+            for element in &pattern.elements {
+                match element {
+                    PathElement::Node(node) => {
+                        if node.properties.is_some() {
+                            return Err(invalid("motif nodes cannot have properties"));
+                        }
+                        let name = node.variable.as_deref();
+                        let index = match name.and_then(|name| named.get(name)) {
+                            Some(&index) => index,
+                            None => {
+                                motif.nodes.push(MotifNode {
+                                    name: node.variable.clone(),
+                                    labels: Vec::new(),
+                                });
+                                let index = motif.nodes.len() - 1;
+                                if let Some(name) = name {
+                                    named.insert(name, index);
+                                }
+                                index
+                            }
+                        };
+                        for label in &node.labels {
+                            if !motif.nodes[index].labels.contains(label) {
+                                motif.nodes[index].labels.push(label.clone());
+                            }
+                        }
+                        if let (Some(left), Some(rel)) = (previous, pending.take()) {
+                            motif.edges.push(MotifEdge::new(left, index, rel));
+                        }
+                        previous = Some(index);
+                    }
+                    PathElement::Relationship(rel) => {
+                        if rel.variable.is_some() {
+                            return Err(invalid("motif relationships cannot be named"));
+                        }
+                        if rel.properties.is_some() {
+                            return Err(invalid("motif relationships cannot have properties"));
+                        }
+                        if rel.variable_length.is_some() {
+                            return Err(invalid("motif relationships must have a fixed length"));
+                        }
+                        pending = Some(rel);
+                    }
+                }
+            }
+        }
+        Ok(motif)
+    }
+
+    /// Positions of the named motif nodes, in order of first appearance.
+    fn named(&self) -> Vec<(String, usize)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, node)| node.name.clone().map(|name| (name, i)))
+            .collect()
+    }
+}
+
+/// Sorted, deduplicated neighbor positions of every node over one set of
+/// relationship types.
+struct Adjacency {
+    out: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+    both: Vec<Vec<usize>>,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Out,
+    Incoming,
+    Both,
+}
+
+/// A candidate for a motif node must appear in the `side` list of the node
+/// bound to `earlier`.
+struct Probe {
+    earlier: usize,
+    adjacency: usize,
+    side: Side,
+}
+
+/// One motif node in binding order, with what its candidates must satisfy.
+struct Step {
+    node: usize,
+    probes: Vec<Probe>,
+    /// Adjacencies in which the candidate must have a self-loop.
+    loops: Vec<usize>,
+    /// Nodes carrying the motif node's labels, when it has any.
+    allowed: Option<Vec<bool>>,
+}
+
+/// A motif compiled against a snapshot.
+struct Matcher {
+    nodes: Vec<InternalNodeId>,
+    adjacency: Vec<Adjacency>,
+    steps: Vec<Step>,
+    width: usize,
+}
+
+impl Matcher {
+    /// Reads the adjacency the motif needs and plans the join. `None` when
+    /// a label or relationship type of the motif does not exist, so it
+    /// cannot occur.
+    fn new(snapshot: &dyn ErasedSnapshot, motif: &Motif) -> Option<Self> {
+        let nodes: Vec<InternalNodeId> = snapshot.nodes_erased().collect();
+        let position: HashMap<InternalNodeId, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| (node, i))
+            .collect();
+
+        // One adjacency per distinct set of relationship types.
+        let mut filters: Vec<Option<Vec<RelTypeId>>> = Vec::new();
+        let mut edge_adjacency = Vec::with_capacity(motif.edges.len());
+        for edge in &motif.edges {
+            let filter = if edge.types.is_empty() {
+                None
+            } else {
+                let mut types: Vec<RelTypeId> = edge
+                    .types
+                    .iter()
+                    .filter_map(|name| snapshot.resolve_rel_type_id_erased(name))
+                    .collect();
+                if types.is_empty() {
+                    return None;
+                }
+                types.sort_unstable();
+                types.dedup();
+                Some(types)
+            };
+            let index = match filters.iter().position(|known| *known == filter) {
+                Some(index) => index,
+                None => {
+                    filters.push(filter);
+                    filters.len() - 1
+                }
+            };
+            edge_adjacency.push(index);
+        }
+        let mut adjacency: Vec<Adjacency> = filters
+            .iter()
+            .map(|_| Adjacency {
+                out: vec![Vec::new(); nodes.len()],
+                incoming: vec![Vec::new(); nodes.len()],
+                both: vec![Vec::new(); nodes.len()],
+            })
+            .collect();
+        for (i, &node) in nodes.iter().enumerate() {
+            for edge in snapshot.neighbors_erased(node, None) {
+                let Some(&j) = position.get(&edge.dst) else {
+                    continue;
+                };
+                for (filter, lists) in filters.iter().zip(&mut adjacency) {
+                    if filter
+                        .as_ref()
+                        .is_some_and(|types| !types.contains(&edge.rel))
+                    {
+                        continue;
+                    }
+                    lists.out[i].push(j);
+                    lists.incoming[j].push(i);
+                    lists.both[i].push(j);
+                    if i != j {
+                        lists.both[j].push(i);
+                    }
+                }
+            }
+        }
+        for lists in &mut adjacency {
+            for list in lists
+                .out
+                .iter_mut()
+                .chain(&mut lists.incoming)
+                .chain(&mut lists.both)
+            {
+                list.sort_unstable();
+                list.dedup();
+            }
+        }
+
+        let mut allowed = Vec::with_capacity(motif.nodes.len());
+        for node in &motif.nodes {
+            if node.labels.is_empty() {
+                allowed.push(None);
+                continue;
+            }
+            let labels = node
+                .labels
+                .iter()
+                .map(|name| snapshot.resolve_label_id_erased(name))
+                .collect::<Option<Vec<_>>>()?;
+            allowed.push(Some(
+                nodes
+                    .iter()
+                    .map(|&iid| {
+                        snapshot
+                            .resolve_node_labels_erased(iid)
+                            .is_some_and(|has| labels.iter().all(|label| has.contains(label)))
+                    })
+                    .collect(),
+            ));
+        }
+
+        let steps = plan(motif, &edge_adjacency, allowed);
+        Some(Self {
+            nodes,
+            adjacency,
+            steps,
+            width: motif.nodes.len(),
+        })
+    }
+
+    /// Whether `candidate` can bind the motif node of `steps[depth]`, given
+    /// the nodes bound before it in `bound`.
+    fn accepts(&self, depth: usize, bound: &[usize], candidate: usize) -> bool {
+        let step = &self.steps[depth];
+        step.allowed
+            .as_ref()
+            .map_or(true, |allowed| allowed[candidate])
+            && step.loops.iter().all(|&a| {
+                self.adjacency[a].out[candidate]
+                    .binary_search(&candidate)
+                    .is_ok()
+            })
+            && self.steps[..depth]
+                .iter()
+                .all(|earlier| bound[earlier.node] != candidate)
+    }
+
+    fn list(&self, probe: &Probe, bound: &[usize]) -> &[usize] {
+        let lists = &self.adjacency[probe.adjacency];
+        let node = bound[probe.earlier];
+        match probe.side {
+            Side::Out => &lists.out[node],
+            Side::Incoming => &lists.incoming[node],
+            Side::Both => &lists.both[node],
+        }
+    }
+
+    /// Binds `steps[depth..]` in every way consistent with `bound`, calling
+    /// `visit` with each full binding until it returns `false`. Returns
+    /// `false` once stopped.
+    fn extend(
+        &self,
+        depth: usize,
+        bound: &mut [usize],
+        visit: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        let Some(step) = self.steps.get(depth) else {
+            return visit(bound);
+        };
+        let mut try_candidate = |candidate: usize, bound: &mut [usize]| {
+            if !self.accepts(depth, bound, candidate) {
+                return true;
+            }
+            bound[step.node] = candidate;
+            self.extend(depth + 1, bound, visit)
+        };
+        let lists: Vec<&[usize]> = step.probes.iter().map(|p| self.list(p, bound)).collect();
+        let Some(shortest) = (0..lists.len()).min_by_key(|&i| lists[i].len()) else {
+            return (0..self.nodes.len()).all(|candidate| try_candidate(candidate, bound));
+        };
+        lists[shortest].iter().all(|&candidate| {
+            let shared = lists
+                .iter()
+                .enumerate()
+                .all(|(i, list)| i == shortest || list.binary_search(&candidate).is_ok());
+            !shared || try_candidate(candidate, bound)
+        })
+    }
+
+    /// Bindings that start with the first motif node at `start`.
+    fn bind_from(&self, start: usize, visit: &mut dyn FnMut(&[usize]) -> bool) -> bool {
+        let mut bound = vec![usize::MAX; self.width];
+        if !self.accepts(0, &bound, start) {
+            return true;
+        }
+        bound[self.steps[0].node] = start;
+        self.extend(1, &mut bound, visit)
+    }
+
+    fn count(&self) -> u64 {
+        (0..self.nodes.len())
+            .into_par_iter()
+            .map(|start| {
+                let mut found = 0u64;
+                self.bind_from(start, &mut |_| {
+                    found += 1;
+                    true
+                });
+                found
+            })
+            .sum()
+    }
+
+    /// Occurrences as node positions per motif node, at most `limit` of them.
+    /// Without a limit the search runs in parallel across start nodes.
+    fn occurrences(&self, limit: Option<usize>) -> Vec<Vec<usize>> {
+        let Some(limit) = limit else {
+            return (0..self.nodes.len())
+                .into_par_iter()
+                .flat_map_iter(|start| {
+                    let mut found = Vec::new();
+                    self.bind_from(start, &mut |bound| {
+                        found.push(bound.to_vec());
+                        true
+                    });
+                    found
+                })
+                .collect();
+        };
+        let mut found = Vec::new();
+        for start in 0..self.nodes.len() {
+            if found.len() >= limit {
+                break;
+            }
+            self.bind_from(start, &mut |bound| {
+                found.push(bound.to_vec());
+                found.len() < limit
+            });
+        }
+        found
+    }
+}
+
+/// Orders the motif nodes for binding: each next node is the one with the
+/// most relationships to nodes already bound, then the most relationships
+/// overall, then labelled before unlabelled, then first in the pattern.
+fn plan(motif: &Motif, edge_adjacency: &[usize], allowed: Vec<Option<Vec<bool>>>) -> Vec<Step> {
+    let width = motif.nodes.len();
+    let degree: Vec<usize> = (0..width)
+        .map(|v| {
+            motif
+                .edges
+                .iter()
+                .filter(|e| e.from != e.to && (e.from == v || e.to == v))
+                .count()
+        })
+        .collect();
+    let mut bound = vec![false; width];
+    let mut order = Vec::with_capacity(width);
+    while order.len() < width {
+        let links = |v: usize| {
+            motif
+                .edges
+                .iter()
+                .filter(|e| (e.from == v && bound[e.to]) || (e.to == v && bound[e.from]))
+                .count()
+        };
+        let next = (0..width)
+            .rev()
+            .filter(|&v| !bound[v])
+            .max_by_key(|&v| (links(v), degree[v], allowed[v].is_some()))
+            .expect("an unbound motif node remains");
+        bound[next] = true;
+        order.push(next);
+    }
+
+    let mut allowed = allowed;
+    let mut placed = vec![false; width];
+    order
+        .into_iter()
+        .map(|node| {
+            let mut probes = Vec::new();
+            let mut loops = Vec::new();
+            for (edge, &adjacency) in motif.edges.iter().zip(edge_adjacency) {
+                if edge.from == node && edge.to == node {
+                    loops.push(adjacency);
+                } else if edge.to == node && placed[edge.from] {
+                    let side = if edge.directed { Side::Out } else { Side::Both };
+                    probes.push(Probe {
+                        earlier: edge.from,
+                        adjacency,
+                        side,
+                    });
+                } else if edge.from == node && placed[edge.to] {
+                    let side = if edge.directed {
+                        Side::Incoming
+                    } else {
+                        Side::Both
+                    };
+                    probes.push(Probe {
+                        earlier: edge.to,
+                        adjacency,
+                        side,
+                    });
+                }
+            }
+            placed[node] = true;
+            Step {
+                node,
+                probes,
+                loops,
+                allowed: allowed[node].take(),
+            }
+        })
+        .collect()
+}
+
+fn motif_argument(procedure: &str, args: &[Value]) -> Result<Motif> {
+    match args.first() {
+        Some(Value::String(pattern)) => Motif::parse(procedure, pattern),
+        _ => Err(Error::Other(format!(
+            "{procedure} requires a motif pattern string"
+        ))),
+    }
+}
+
+/// `algo.motif(pattern, {limit})`: one row per occurrence of `pattern`, with
+/// a column per named node of the motif.
+pub(super) struct MotifProcedure;
+
+impl Procedure for MotifProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        let motif = motif_argument("algo.motif", &args)?;
+        let config = config("algo.motif", &args[1..], &["limit"])?;
+        let limit = match config.get("limit") {
+            None | Some(Value::Null) => None,
+            Some(Value::Int(limit)) if *limit >= 0 => Some(*limit as usize),
+            Some(_) => {
+                return Err(Error::Other(
+                    "algo.motif: limit must be a non-negative integer".to_string(),
+                ));
+            }
+        };
+        let Some(matcher) = Matcher::new(snapshot, &motif) else {
+            return Ok(Vec::new());
+        };
+        let named = motif.named();
+        Ok(matcher
+            .occurrences(limit)
+            .into_iter()
+            .map(|bound| {
+                Row::new(
+                    named
+                        .iter()
+                        .map(|(name, i)| (name.clone(), Value::NodeId(matcher.nodes[bound[*i]])))
+                        .collect(),
+                )
+            })
+            .collect())
+    }
+}
+
+/// `algo.motif.count(pattern)`: one `count` row with the number of
+/// occurrences of `pattern`.
+pub(super) struct MotifCountProcedure;
+
+impl Procedure for MotifCountProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        if args.len() != 1 {
+            return Err(Error::Other(
+                "algo.motif.count takes a single motif pattern".to_string(),
+            ));
+        }
+        let motif = motif_argument("algo.motif.count", &args)?;
+        let count = Matcher::new(snapshot, &motif).map_or(0, |matcher| matcher.count());
+        Ok(vec![Row::new(vec![(
+            "count".to_string(),
+            Value::Int(count as i64),
+        )])])
+    }
+}
@@ -1,6 +1,7 @@
 use super::{
-    GraphSnapshot, Plan, PlanIterator, Row, index_seek_plan, match_bound_rel_plan,
-    match_in_undirected_plan, match_out_plan, plan_head, plan_mid, plan_tail, runtime_limits,
+    GraphSnapshot, Plan, PlanIterator, Row, cost_model, index_seek_plan, load_csv,
+    match_bound_rel_plan, match_in_undirected_plan, match_out_plan, plan_head, plan_mid, plan_tail,
+    runtime_limits, traversal_program,
 };
 
 pub(super) fn execute_plan<'a, S: GraphSnapshot + 'a>(
@@ -9,11 +10,40 @@ pub(super) fn execute_plan<'a, S: GraphSnapshot + 'a>(
     params: &'a crate::query_api::Params,
 ) -> PlanIterator<'a, S> {
     let stage = plan_stage_name(plan);
-    let iter = match plan {
+    let opened_at = params.profile_clock();
+    // PROFILE reports rows per operator, so it always runs the interpreter.
+    let program = match plan {
+        Plan::MatchOut {
+            program: Some(program),
+            ..
+        }
+        | Plan::MatchIn {
+            program: Some(program),
+            ..
+        } if opened_at.is_none() && params.execute_options().compiled_traversals => Some(program),
+        _ => None,
+    };
+    let iter = match program {
+        Some(program) => traversal_program::execute(snapshot, program, params),
+        None => interpret_plan(snapshot, plan, params),
+    };
+
+    runtime_limits::wrap_plan_iterator(iter, params, stage, plan, opened_at)
+}
+
+fn interpret_plan<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    plan: &'a Plan,
+    params: &'a crate::query_api::Params,
+) -> PlanIterator<'a, S> {
+    match plan {
         Plan::ReturnOne => PlanIterator::ReturnOne(std::iter::once(Ok(Row::default()))),
         Plan::CartesianProduct { left, right } => {
             plan_head::execute_cartesian_product(snapshot, left, right, params)
         }
+        Plan::HashJoin { left, right, keys } => {
+            plan_head::execute_hash_join(snapshot, left, right, keys, params)
+        }
         Plan::Apply {
             input,
             subquery,
@@ -45,6 +75,7 @@ pub(super) fn execute_plan<'a, S: GraphSnapshot + 'a>(
             optional,
             optional_unbind,
             path_alias,
+            program: _,
         } => match_out_plan::execute_match_out(
             snapshot,
             input,
@@ -107,6 +138,7 @@ pub(super) fn execute_plan<'a, S: GraphSnapshot + 'a>(
             optional,
             optional_unbind,
             path_alias,
+            program: _,
         } => match_in_undirected_plan::execute_match_in(
             snapshot,
             input,
@@ -204,6 +236,21 @@ pub(super) fn execute_plan<'a, S: GraphSnapshot + 'a>(
             expression,
             alias,
         } => plan_tail::execute_unwind(snapshot, input, expression, alias, params),
+        Plan::LoadCsv {
+            input,
+            source,
+            alias,
+            with_headers,
+            field_terminator,
+        } => load_csv::execute_load_csv(
+            snapshot,
+            input,
+            source,
+            alias,
+            *with_headers,
+            *field_terminator,
+            params,
+        ),
         Plan::Union { left, right, all } => {
             plan_tail::execute_union(snapshot, left, right, *all, params)
         }
@@ -228,13 +275,40 @@ pub(super) fn execute_plan<'a, S: GraphSnapshot + 'a>(
         } => index_seek_plan::execute_index_seek(
             snapshot, alias, label, field, value_expr, fallback, params,
         ),
+        Plan::TextIndexSeek {
+            alias,
+            label,
+            field,
+            needle_expr,
+            fallback,
+        } => index_seek_plan::execute_text_index_seek(
+            snapshot,
+            alias,
+            label,
+            field,
+            needle_expr,
+            fallback,
+            params,
+        ),
+        Plan::CompositeIndexSeek {
+            alias,
+            label,
+            predicates,
+            fallback,
+        } => index_seek_plan::execute_composite_index_seek(
+            snapshot, alias, label, predicates, fallback, params,
+        ),
         Plan::Values { rows } => plan_tail::execute_values(rows),
-    };
-
-    runtime_limits::wrap_plan_iterator(iter, params, stage)
+        Plan::CostChoice { alternatives } => {
+            match cost_model::choose_alternative(snapshot, alternatives) {
+                Some(chosen) => execute_plan(snapshot, chosen, params),
+                None => plan_tail::execute_values(&[]),
+            }
+        }
+    }
 }
 
-fn plan_stage_name(plan: &Plan) -> &'static str {
+pub(super) fn plan_stage_name(plan: &Plan) -> &'static str {
     match plan {
         Plan::ReturnOne => "ReturnOne",
         Plan::NodeScan { .. } => "NodeScan",
@@ -252,6 +326,7 @@ fn plan_stage_name(plan: &Plan) -> &'static str {
         Plan::Limit { .. } => "Limit",
         Plan::Distinct { .. } => "Distinct",
         Plan::Unwind { .. } => "Unwind",
+        Plan::LoadCsv { .. } => "LoadCsv",
         Plan::Union { .. } => "Union",
         Plan::Delete { .. } => "Delete",
         Plan::SetProperty { .. } => "SetProperty",
@@ -260,7 +335,11 @@ fn plan_stage_name(plan: &Plan) -> &'static str {
         Plan::RemoveProperty { .. } => "RemoveProperty",
         Plan::RemoveLabels { .. } => "RemoveLabels",
         Plan::IndexSeek { .. } => "IndexSeek",
+        Plan::TextIndexSeek { .. } => "TextIndexSeek",
+        Plan::CompositeIndexSeek { .. } => "CompositeIndexSeek",
+        Plan::CostChoice { .. } => "CostChoice",
         Plan::CartesianProduct { .. } => "CartesianProduct",
+        Plan::HashJoin { .. } => "HashJoin",
         Plan::Apply { .. } => "Apply",
         Plan::ProcedureCall { .. } => "ProcedureCall",
         Plan::Foreach { .. } => "Foreach",
@@ -1,5 +1,5 @@
 use super::{
-    ApplyIter, CartesianProductIter, GraphSnapshot, NodeScanIter, Plan, PlanIterator,
+    ApplyIter, CartesianProductIter, GraphSnapshot, HashJoinIter, NodeScanIter, Plan, PlanIterator,
     ProcedureCallIter, Row, Value, execute_plan,
 };
 use crate::ast::Expression;
@@ -21,6 +21,25 @@ pub(super) fn execute_cartesian_product<'a, S: GraphSnapshot + 'a>(
     }))
 }
 
+pub(super) fn execute_hash_join<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    left: &'a Plan,
+    right: &'a Plan,
+    keys: &'a [String],
+    params: &'a crate::query_api::Params,
+) -> PlanIterator<'a, S> {
+    let left_iter = execute_plan(snapshot, left, params);
+    PlanIterator::HashJoin(Box::new(HashJoinIter {
+        left_iter: Box::new(left_iter),
+        right_plan: right,
+        keys,
+        snapshot,
+        params,
+        table: None,
+        pending: Vec::new().into_iter(),
+    }))
+}
+
 pub(super) fn execute_apply<'a, S: GraphSnapshot + 'a>(
     snapshot: &'a S,
     input: &'a Plan,
@@ -1,8 +1,9 @@
+use super::spill::{self, MemoryReservation, SpillFile};
 use super::{
     Direction, Error, FilterIter, GraphSnapshot, Plan, PlanIterator, Result, Row, Value,
     execute_aggregate as execute_aggregate_impl, execute_plan, row_contains_all_bindings,
 };
-use crate::ast::Expression;
+use crate::ast::{BinaryOperator, Expression};
 
 fn runtime_type_error(code: &str) -> Error {
     Error::Other(format!("runtime error: {code}"))
@@ -25,10 +26,15 @@ fn ensure_runtime_function_call_compatible<S: GraphSnapshot>(
     let name = call.name.to_ascii_lowercase();
     match name.as_str() {
         "__index" if call.args.len() == 2 => {
-            let container =
-                crate::evaluator::evaluate_expression_value(&call.args[0], row, snapshot, params);
             let index =
                 crate::evaluator::evaluate_expression_value(&call.args[1], row, snapshot, params);
+            if matches!(index, Value::Int(_))
+                && crate::evaluator::array_element(&call.args[0], &index, row, snapshot).is_some()
+            {
+                return Ok(());
+            }
+            let container =
+                crate::evaluator::evaluate_expression_value(&call.args[0], row, snapshot, params);
 
             if matches!(container, Value::Null) || matches!(index, Value::Null) {
                 return Ok(());
@@ -125,6 +131,37 @@ fn ensure_runtime_function_call_compatible<S: GraphSnapshot>(
             let observed = estimate_range_len(start, end, step);
             params.check_collection_size("Function(range)", observed)
         }
+        "tostring" if call.args.len() > 1 => {
+            let args: Vec<Value> = call
+                .args
+                .iter()
+                .map(|arg| crate::evaluator::evaluate_expression_value(arg, row, snapshot, params))
+                .collect();
+            crate::evaluator::format_value(&args, snapshot.locale())
+                .map(|_| ())
+                .map_err(|e| Error::Other(format!("runtime error: InvalidArgumentValue: {e}")))
+        }
+        "tointegerarray" | "tofloatarray" if call.args.len() == 1 => {
+            let value =
+                crate::evaluator::evaluate_expression_value(&call.args[0], row, snapshot, params);
+            match crate::evaluator::array_argument_error(&name, &value) {
+                Some(e) => Err(Error::Other(format!(
+                    "runtime error: InvalidArgumentValue: {e}"
+                ))),
+                None => Ok(()),
+            }
+        }
+        "iri" | "curie" if call.args.len() == 1 => {
+            let value =
+                crate::evaluator::evaluate_expression_value(&call.args[0], row, snapshot, params);
+            let namespaces = snapshot.namespaces();
+            match crate::evaluator::namespace_argument_error(&name, &value, &namespaces) {
+                Some(e) => Err(Error::Other(format!(
+                    "runtime error: InvalidArgumentValue: {e}"
+                ))),
+                None => Ok(()),
+            }
+        }
         "tostring" if call.args.len() == 1 => {
             let value =
                 crate::evaluator::evaluate_expression_value(&call.args[0], row, snapshot, params);
@@ -172,7 +209,21 @@ pub(super) fn ensure_runtime_expression_compatible<S: GraphSnapshot>(
         }
         Expression::Binary(binary) => {
             ensure_runtime_expression_compatible(&binary.left, row, snapshot, params)?;
-            ensure_runtime_expression_compatible(&binary.right, row, snapshot, params)
+            ensure_runtime_expression_compatible(&binary.right, row, snapshot, params)?;
+            if matches!(binary.operator, BinaryOperator::RegexMatch)
+                && let Value::String(pattern) = crate::evaluator::evaluate_expression_value(
+                    &binary.right,
+                    row,
+                    snapshot,
+                    params,
+                )
+                && let Err(e) = crate::evaluator::compile_regex(&pattern)
+            {
+                return Err(Error::Other(format!(
+                    "runtime error: InvalidArgumentValue: invalid regular expression: {e}"
+                )));
+            }
+            Ok(())
         }
         Expression::FunctionCall(call) => {
             for arg in &call.args {
@@ -369,57 +420,123 @@ pub(super) fn execute_order_by<'a, S: GraphSnapshot + 'a>(
     items: &[(Expression, Direction)],
     params: &'a crate::query_api::Params,
 ) -> PlanIterator<'a, S> {
-    let input_iter = execute_plan(snapshot, input, params);
-    let mut rows: Vec<Result<Row>> = Vec::new();
-    for item in input_iter {
-        if let Err(err) = params.check_timeout("OrderBy.collect") {
-            return PlanIterator::Dynamic(Box::new(std::iter::once(Err(err))));
+    match sort_rows(snapshot, input, items, params) {
+        Ok(rows) => PlanIterator::Dynamic(Box::new(rows)),
+        Err(err) => PlanIterator::Dynamic(Box::new(std::iter::once(Err(err)))),
+    }
+}
+
+/// Sorts the input in memory or, once it outgrows the memory budget or the
+/// collection limit, as sorted runs spilled to disk and merged.
+fn sort_rows<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    input: &'a Plan,
+    items: &[(Expression, Direction)],
+    params: &'a crate::query_api::Params,
+) -> Result<MergeRuns<'a>> {
+    let directions: Vec<Direction> = items.iter().map(|(_, dir)| dir.clone()).collect();
+    let mut memory = MemoryReservation::new(params);
+    let mut buffer: Vec<(Vec<Value>, Row)> = Vec::new();
+    let mut runs: Vec<Box<dyn Iterator<Item = Result<SortRecord>> + 'a>> = Vec::new();
+    for item in execute_plan(snapshot, input, params) {
+        params.check_timeout("OrderBy.collect")?;
+        let row = item?;
+        for (expr, _) in items {
+            ensure_runtime_expression_compatible(expr, &row, snapshot, params)?;
         }
-        rows.push(item);
-        if let Err(err) = params.check_collection_size("OrderBy.collect", rows.len()) {
-            return PlanIterator::Dynamic(Box::new(std::iter::once(Err(err))));
+        let keys: Vec<Value> = items
+            .iter()
+            .map(|(expr, _)| {
+                crate::evaluator::evaluate_expression_value(expr, &row, snapshot, params)
+            })
+            .collect();
+        let bytes = spill::row_bytes(&row) + keys.iter().map(spill::value_bytes).sum::<usize>();
+        let within_budget = memory.grow("OrderBy.collect", bytes)?;
+        buffer.push((keys, row));
+        let full = match params.check_collection_size("OrderBy.collect", buffer.len()) {
+            Ok(()) => false,
+            Err(_) if params.execute_options().spill_to_disk => true,
+            Err(err) => return Err(err),
+        };
+        if !within_budget || full {
+            buffer.sort_by(|a, b| compare_sort_keys(&a.0, &b.0, &directions));
+            let mut run = SpillFile::create(params)?;
+            for (keys, row) in buffer.drain(..) {
+                run.write(&keys, &row)?;
+            }
+            runs.push(Box::new(run.into_reader()?));
+            memory.clear();
         }
     }
-    #[allow(clippy::type_complexity)]
-    let mut sortable: Vec<(Result<Row>, Vec<(Value, Direction)>)> = rows
-        .into_iter()
-        .map(|row| match &row {
-            Ok(r) => {
-                for (expr, _) in items {
-                    if let Err(err) =
-                        ensure_runtime_expression_compatible(expr, r, snapshot, params)
-                    {
-                        return (Err(err), vec![]);
-                    }
-                }
-                let sort_keys: Vec<(Value, Direction)> = items
-                    .iter()
-                    .map(|(expr, dir)| {
-                        let val =
-                            crate::evaluator::evaluate_expression_value(expr, r, snapshot, params);
-                        (val, dir.clone())
-                    })
-                    .collect();
-                (row, sort_keys)
-            }
-            Err(_) => (row, vec![]),
-        })
-        .collect();
+    buffer.sort_by(|a, b| compare_sort_keys(&a.0, &b.0, &directions));
+    runs.push(Box::new(buffer.into_iter().map(Ok)));
+    Ok(MergeRuns {
+        heads: runs.iter().map(|_| None).collect(),
+        exhausted: vec![false; runs.len()],
+        runs,
+        directions,
+        _memory: memory,
+    })
+}
 
-    sortable.sort_by(|a, b| {
-        for ((val_a, dir_a), (val_b, _)) in a.1.iter().zip(b.1.iter()) {
-            let order = crate::evaluator::order_compare(val_a, val_b);
-            if order == std::cmp::Ordering::Equal {
-                continue;
-            }
-            return if *dir_a == Direction::Ascending {
+type SortRecord = (Vec<Value>, Row);
+
+fn compare_sort_keys(a: &[Value], b: &[Value], directions: &[Direction]) -> std::cmp::Ordering {
+    for ((a, b), dir) in a.iter().zip(b).zip(directions) {
+        let order = crate::evaluator::order_compare(a, b);
+        if order != std::cmp::Ordering::Equal {
+            return if *dir == Direction::Ascending {
                 order
             } else {
                 order.reverse()
             };
         }
-        std::cmp::Ordering::Equal
-    });
+    }
+    std::cmp::Ordering::Equal
+}
 
-    PlanIterator::Dynamic(Box::new(sortable.into_iter().map(|(row, _)| row)))
+/// Merges sorted runs in input order, taking from the earliest run on ties
+/// so the sort stays stable.
+struct MergeRuns<'a> {
+    runs: Vec<Box<dyn Iterator<Item = Result<SortRecord>> + 'a>>,
+    heads: Vec<Option<SortRecord>>,
+    exhausted: Vec<bool>,
+    directions: Vec<Direction>,
+    _memory: MemoryReservation<'a>,
+}
+
+impl Iterator for MergeRuns<'_> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (i, run) in self.runs.iter_mut().enumerate() {
+            if self.heads[i].is_none() && !self.exhausted[i] {
+                match run.next() {
+                    Some(Ok(record)) => self.heads[i] = Some(record),
+                    Some(Err(err)) => {
+                        self.exhausted[i] = true;
+                        return Some(Err(err));
+                    }
+                    None => self.exhausted[i] = true,
+                }
+            }
+        }
+        let mut best: Option<usize> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            let Some((keys, _)) = head else {
+                continue;
+            };
+            let better = match best.and_then(|b| self.heads[b].as_ref()) {
+                Some((best_keys, _)) => {
+                    compare_sort_keys(keys, best_keys, &self.directions).is_lt()
+                }
+                None => true,
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+        let (_, row) = self.heads[best?].take()?;
+        Some(Ok(row))
+    }
 }
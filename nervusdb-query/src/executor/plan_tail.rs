@@ -1,20 +1,34 @@
+use super::spill::{MemoryReservation, SpillFile};
 use super::{Error, GraphSnapshot, Plan, PlanIterator, Row, Value, execute_plan};
+use std::hash::{Hash, Hasher};
 
+/// Evaluates the count of a `SKIP` or `LIMIT` (`clause`), which may use
+/// parameters but no row variables.
 fn evaluate_row_window_expression<S: GraphSnapshot>(
     snapshot: &S,
+    clause: &str,
     expr: &crate::ast::Expression,
     params: &crate::query_api::Params,
 ) -> super::Result<usize> {
     let value =
         crate::evaluator::evaluate_expression_value(expr, &Row::default(), snapshot, params);
+    let invalid = |code: &str, got: String| {
+        Error::Other(format!(
+            "syntax error: {code}: {clause} must be a non-negative integer, got {got}"
+        ))
+    };
     match value {
-        Value::Int(v) if v >= 0 => usize::try_from(v)
-            .map_err(|_| Error::Other("syntax error: InvalidArgumentType".to_string())),
-        Value::Int(_) => Err(Error::Other(
-            "syntax error: NegativeIntegerArgument".to_string(),
-        )),
-        _ => Err(Error::Other(
-            "syntax error: InvalidArgumentType".to_string(),
+        Value::Int(v) if v >= 0 => {
+            usize::try_from(v).map_err(|_| invalid("InvalidArgumentType", v.to_string()))
+        }
+        Value::Int(v) => Err(invalid("NegativeIntegerArgument", v.to_string())),
+        Value::Float(v) => Err(invalid("InvalidArgumentType", v.to_string())),
+        Value::Null => Err(invalid("InvalidArgumentType", "null".to_string())),
+        Value::Bool(v) => Err(invalid("InvalidArgumentType", v.to_string())),
+        Value::String(_) => Err(invalid("InvalidArgumentType", "a string".to_string())),
+        _ => Err(invalid(
+            "InvalidArgumentType",
+            "a non-numeric value".to_string(),
         )),
     }
 }
@@ -25,7 +39,7 @@ pub(super) fn execute_skip<'a, S: GraphSnapshot + 'a>(
     skip: &'a crate::ast::Expression,
     params: &'a crate::query_api::Params,
 ) -> PlanIterator<'a, S> {
-    let skip = match evaluate_row_window_expression(snapshot, skip, params) {
+    let skip = match evaluate_row_window_expression(snapshot, "SKIP", skip, params) {
         Ok(value) => value,
         Err(err) => return PlanIterator::Dynamic(Box::new(std::iter::once(Err(err)))),
     };
@@ -39,7 +53,7 @@ pub(super) fn execute_limit<'a, S: GraphSnapshot + 'a>(
     limit: &'a crate::ast::Expression,
     params: &'a crate::query_api::Params,
 ) -> PlanIterator<'a, S> {
-    let limit = match evaluate_row_window_expression(snapshot, limit, params) {
+    let limit = match evaluate_row_window_expression(snapshot, "LIMIT", limit, params) {
         Ok(value) => value,
         Err(err) => return PlanIterator::Dynamic(Box::new(std::iter::once(Err(err)))),
     };
@@ -53,21 +67,127 @@ pub(super) fn execute_distinct<'a, S: GraphSnapshot + 'a>(
     params: &'a crate::query_api::Params,
 ) -> PlanIterator<'a, S> {
     let input_iter = execute_plan(snapshot, input, params);
-    let mut seen = std::collections::HashSet::new();
-    PlanIterator::Dynamic(Box::new(input_iter.filter(move |result| {
-        if let Ok(row) = result {
-            let key = row
-                .columns()
-                .iter()
-                .map(|(_, v)| format!("{:?}", v))
-                .collect::<Vec<_>>()
-                .join(",");
-            if seen.insert(key) {
-                return true;
+    PlanIterator::Dynamic(Box::new(SpillingDistinct::new(
+        Box::new(input_iter),
+        params,
+        0,
+    )))
+}
+
+/// Partitions a spilling `DISTINCT` splits its overflow into.
+const DISTINCT_PARTITIONS: usize = 16;
+/// Rounds of repartitioning before an oversized partition fails the query.
+const MAX_DISTINCT_SPILL_DEPTH: u32 = 4;
+
+/// Hash `DISTINCT` that emits first occurrences as they arrive. Once its set
+/// of seen rows outgrows the memory budget, rows it has not seen go to hash
+/// partitions on disk, each deduplicated on its own after the input ends.
+struct SpillingDistinct<'a> {
+    input: Box<dyn Iterator<Item = super::Result<Row>> + 'a>,
+    params: &'a crate::query_api::Params,
+    depth: u32,
+    seen: std::collections::HashSet<String>,
+    memory: MemoryReservation<'a>,
+    partitions: Vec<SpillFile>,
+    input_done: bool,
+    current: Option<Box<SpillingDistinct<'a>>>,
+}
+
+impl<'a> SpillingDistinct<'a> {
+    fn new(
+        input: Box<dyn Iterator<Item = super::Result<Row>> + 'a>,
+        params: &'a crate::query_api::Params,
+        depth: u32,
+    ) -> Self {
+        Self {
+            input,
+            params,
+            depth,
+            seen: std::collections::HashSet::new(),
+            memory: MemoryReservation::new(params),
+            partitions: Vec::new(),
+            input_done: false,
+            current: None,
+        }
+    }
+
+    /// Keeps `row` for later if it is new, returning it if it can be
+    /// emitted now.
+    fn admit(&mut self, row: Row) -> super::Result<Option<Row>> {
+        let key = row
+            .columns()
+            .iter()
+            .map(|(_, v)| format!("{:?}", v))
+            .collect::<Vec<_>>()
+            .join(",");
+        if self.seen.contains(&key) {
+            return Ok(None);
+        }
+        if self.partitions.is_empty() {
+            let bytes = std::mem::size_of::<String>() * 2 + key.len();
+            let admitted = if self.depth >= MAX_DISTINCT_SPILL_DEPTH {
+                self.memory.require("Distinct", bytes)?;
+                true
+            } else {
+                self.memory.grow("Distinct", bytes)?
+            };
+            if admitted {
+                self.seen.insert(key);
+                return Ok(Some(row));
+            }
+            for _ in 0..DISTINCT_PARTITIONS {
+                self.partitions.push(SpillFile::create(self.params)?);
+            }
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (self.depth, &key).hash(&mut hasher);
+        let partition = hasher.finish() as usize % DISTINCT_PARTITIONS;
+        self.partitions[partition].write(&[], &row)?;
+        Ok(None)
+    }
+}
+
+impl Iterator for SpillingDistinct<'_> {
+    type Item = super::Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(current) = &mut self.current {
+                match current.next() {
+                    Some(item) => return Some(item),
+                    None => self.current = None,
+                }
+            }
+            if !self.input_done {
+                match self.input.next() {
+                    Some(Ok(row)) => match self.admit(row) {
+                        Ok(Some(row)) => return Some(Ok(row)),
+                        Ok(None) => continue,
+                        Err(err) => return Some(Err(err)),
+                    },
+                    Some(Err(err)) => return Some(Err(err)),
+                    None => {
+                        // Partitions only hold rows that are not in `seen`.
+                        self.input_done = true;
+                        self.seen = std::collections::HashSet::new();
+                        self.memory.clear();
+                    }
+                }
+            }
+            let partition = self.partitions.pop()?;
+            match partition.into_reader() {
+                Ok(reader) => {
+                    let rows = reader.map(|record| record.map(|(_, row)| row));
+                    self.current = Some(Box::new(SpillingDistinct::new(
+                        Box::new(rows),
+                        self.params,
+                        self.depth + 1,
+                    )));
+                }
+                Err(err) => return Some(Err(err)),
             }
         }
-        false
-    })))
+    }
 }
 
 pub(super) fn execute_unwind<'a, S: GraphSnapshot + 'a>(
@@ -1,7 +1,9 @@
 use super::{
     AggregateFunction, ApplyIter, CartesianProductIter, Direction, Expression, FilterIter,
-    GraphSnapshot, NodeScanIter, Pattern, ProcedureCallIter, RelationshipDirection, Result, Row,
+    GraphSnapshot, HashJoinIter, NodeScanIter, Pattern, ProcedureCallIter, RelationshipDirection,
+    Result, Row, TraversalProgram,
 };
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub enum Plan {
@@ -30,6 +32,8 @@ pub enum Plan {
         optional: bool,
         optional_unbind: Vec<String>,
         path_alias: Option<String>,
+        /// The hop chain ending here, compiled by `prepare` when it qualifies.
+        program: Option<Arc<TraversalProgram>>,
     },
     /// `MATCH (a)-[:rel*min..max]->(b) RETURN ...` (variable length)
     MatchOutVarLen {
@@ -62,6 +66,8 @@ pub enum Plan {
         optional: bool,
         optional_unbind: Vec<String>,
         path_alias: Option<String>,
+        /// The hop chain ending here, compiled by `prepare` when it qualifies.
+        program: Option<Arc<TraversalProgram>>,
     },
     MatchUndirected {
         input: Option<Box<Plan>>,
@@ -136,6 +142,14 @@ pub enum Plan {
         expression: Expression,
         alias: String,
     },
+    /// `LOAD CSV` - stream the records of a CSV file, one row each
+    LoadCsv {
+        input: Box<Plan>,
+        source: Expression,
+        alias: String,
+        with_headers: bool,
+        field_terminator: u8,
+    },
     /// `UNION` / `UNION ALL` - combine results from two queries
     Union {
         left: Box<Plan>,
@@ -182,11 +196,41 @@ pub enum Plan {
         value_expr: Expression,
         fallback: Box<Plan>,
     },
+    /// `TextIndexSeek` - candidate nodes for `field CONTAINS needle` from a text index,
+    /// else fallback. The WHERE filter above still checks each candidate.
+    TextIndexSeek {
+        alias: String,
+        label: String,
+        field: String,
+        needle_expr: Expression,
+        fallback: Box<Plan>,
+    },
+    /// `CompositeIndexSeek` - equality predicates on several properties answered
+    /// by a composite index covering their leading fields, else fallback.
+    CompositeIndexSeek {
+        alias: String,
+        label: String,
+        predicates: Vec<(String, Expression)>,
+        fallback: Box<Plan>,
+    },
+    /// `CostChoice` - equivalent plans for one MATCH (e.g. a chain anchored at
+    /// either end); the executor runs the cheapest under the snapshot's
+    /// label/rel-type statistics, or the first when there are none.
+    CostChoice {
+        alternatives: Vec<Plan>,
+    },
     /// `CartesianProduct` - multiply two plans (join without shared variables)
     CartesianProduct {
         left: Box<Plan>,
         right: Box<Plan>,
     },
+    /// `HashJoin` - join two plans on the variables in `keys`, which both
+    /// bind; `right` is built into a hash table and `left` probes it
+    HashJoin {
+        left: Box<Plan>,
+        right: Box<Plan>,
+        keys: Vec<String>,
+    },
     /// `Apply` - execute subquery for each row (Correlated Subquery)
     Apply {
         input: Box<Plan>,
@@ -222,6 +266,7 @@ pub enum PlanIterator<'a, S: GraphSnapshot> {
     NodeScan(NodeScanIter<'a, S>),
     Filter(FilterIter<'a, S>),
     CartesianProduct(Box<CartesianProductIter<'a, S>>),
+    HashJoin(Box<HashJoinIter<'a, S>>),
     Apply(Box<ApplyIter<'a, S>>),
     ProcedureCall(Box<ProcedureCallIter<'a, S>>),
 
@@ -237,6 +282,7 @@ impl<'a, S: GraphSnapshot> Iterator for PlanIterator<'a, S> {
             PlanIterator::NodeScan(iter) => iter.next(),
             PlanIterator::Filter(iter) => iter.next(),
             PlanIterator::CartesianProduct(iter) => iter.next(),
+            PlanIterator::HashJoin(iter) => iter.next(),
             PlanIterator::Apply(iter) => iter.next(),
             PlanIterator::ProcedureCall(iter) => iter.next(),
 
@@ -1,3 +1,8 @@
+use super::graph_algorithms::{
+    DijkstraProcedure, LouvainProcedure, PageRankProcedure, SampleNeighborsProcedure,
+    TriangleCountProcedure, WccProcedure,
+};
+use super::motif::{MotifCountProcedure, MotifProcedure};
 use super::{
     EdgeKey, Error, GraphSnapshot, InternalNodeId, LabelId, RelTypeId, Result, Row, Value,
 };
@@ -8,6 +13,48 @@ pub trait Procedure: Send + Sync {
     fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>>;
 }
 
+impl<F> Procedure for F
+where
+    F: Fn(&dyn ErasedSnapshot, Vec<Value>) -> Result<Vec<Row>> + Send + Sync,
+{
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        self(snapshot, args)
+    }
+}
+
+/// Scalar function registered with [`register_function`]. Like the built-in
+/// functions it returns null for arguments it cannot handle.
+pub type ScalarFunction = Arc<dyn Fn(&[Value]) -> Value + Send + Sync>;
+
+/// Argument and output names of a procedure or function registered by the
+/// embedding application. `prepare()` rejects calls with another number of
+/// arguments and `YIELD`s of columns not in `outputs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Signature {
+    pub inputs: Vec<String>,
+    /// Columns a procedure's rows carry; unused for functions.
+    pub outputs: Vec<String>,
+}
+
+impl Signature {
+    pub fn function<I: Into<String>>(inputs: impl IntoIterator<Item = I>) -> Self {
+        Self {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn procedure<I: Into<String>, O: Into<String>>(
+        inputs: impl IntoIterator<Item = I>,
+        outputs: impl IntoIterator<Item = O>,
+    ) -> Self {
+        Self {
+            outputs: outputs.into_iter().map(Into::into).collect(),
+            ..Self::function(inputs)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TestProcedureType {
     Any,
@@ -33,6 +80,7 @@ pub struct TestProcedureFixture {
 }
 
 pub trait ErasedSnapshot {
+    fn nodes_erased(&self) -> Box<dyn Iterator<Item = InternalNodeId> + '_>;
     fn neighbors_erased(
         &self,
         src: InternalNodeId,
@@ -48,7 +96,17 @@ pub trait ErasedSnapshot {
         iid: InternalNodeId,
         key: &str,
     ) -> Option<nervusdb_api::PropertyValue>;
+    fn sample_neighbors_erased(
+        &self,
+        src: InternalNodeId,
+        rel: Option<RelTypeId>,
+        k: usize,
+        weight_property: Option<&str>,
+        seed: u64,
+    ) -> Vec<EdgeKey>;
+    fn resolve_label_id_erased(&self, name: &str) -> Option<LabelId>;
     fn resolve_label_name_erased(&self, id: LabelId) -> Option<String>;
+    fn resolve_rel_type_id_erased(&self, name: &str) -> Option<RelTypeId>;
     fn resolve_rel_type_name_erased(&self, id: RelTypeId) -> Option<String>;
     fn resolve_node_labels_erased(&self, iid: InternalNodeId) -> Option<Vec<LabelId>>;
     fn node_properties_erased(
@@ -59,9 +117,20 @@ pub trait ErasedSnapshot {
         &self,
         key: EdgeKey,
     ) -> Option<std::collections::BTreeMap<String, nervusdb_api::PropertyValue>>;
+    fn edge_property_erased(&self, key: EdgeKey, name: &str)
+    -> Option<nervusdb_api::PropertyValue>;
+    fn named_queries_erased(&self) -> Vec<(String, String)>;
+    fn namespaces_erased(&self) -> Vec<(String, String)>;
+    fn schema_erased(&self) -> nervusdb_api::Schema;
+    fn degree_stats_erased(&self) -> Vec<nervusdb_api::RelTypeDegrees>;
+    fn search_vector_erased(&self, query: &[f32], k: usize) -> Option<Vec<(InternalNodeId, f32)>>;
 }
 
 impl<S: GraphSnapshot> ErasedSnapshot for S {
+    fn nodes_erased(&self) -> Box<dyn Iterator<Item = InternalNodeId> + '_> {
+        self.nodes()
+    }
+
     fn neighbors_erased(
         &self,
         src: InternalNodeId,
@@ -86,10 +155,29 @@ impl<S: GraphSnapshot> ErasedSnapshot for S {
         self.node_property(iid, key)
     }
 
+    fn sample_neighbors_erased(
+        &self,
+        src: InternalNodeId,
+        rel: Option<RelTypeId>,
+        k: usize,
+        weight_property: Option<&str>,
+        seed: u64,
+    ) -> Vec<EdgeKey> {
+        self.sample_neighbors(src, rel, k, weight_property, seed)
+    }
+
+    fn resolve_label_id_erased(&self, name: &str) -> Option<LabelId> {
+        self.resolve_label_id(name)
+    }
+
     fn resolve_label_name_erased(&self, id: LabelId) -> Option<String> {
         self.resolve_label_name(id)
     }
 
+    fn resolve_rel_type_id_erased(&self, name: &str) -> Option<RelTypeId> {
+        self.resolve_rel_type_id(name)
+    }
+
     fn resolve_rel_type_name_erased(&self, id: RelTypeId) -> Option<String> {
         self.resolve_rel_type_name(id)
     }
@@ -111,6 +199,34 @@ impl<S: GraphSnapshot> ErasedSnapshot for S {
     ) -> Option<std::collections::BTreeMap<String, nervusdb_api::PropertyValue>> {
         self.edge_properties(key)
     }
+
+    fn edge_property_erased(
+        &self,
+        key: EdgeKey,
+        name: &str,
+    ) -> Option<nervusdb_api::PropertyValue> {
+        self.edge_property(key, name)
+    }
+
+    fn named_queries_erased(&self) -> Vec<(String, String)> {
+        self.named_queries()
+    }
+
+    fn namespaces_erased(&self) -> Vec<(String, String)> {
+        self.namespaces()
+    }
+
+    fn schema_erased(&self) -> nervusdb_api::Schema {
+        self.schema()
+    }
+
+    fn degree_stats_erased(&self) -> Vec<nervusdb_api::RelTypeDegrees> {
+        self.degree_stats()
+    }
+
+    fn search_vector_erased(&self, query: &[f32], k: usize) -> Option<Vec<(InternalNodeId, f32)>> {
+        self.search_vector(query, k)
+    }
 }
 
 pub struct ProcedureRegistry {
@@ -126,8 +242,42 @@ impl Default for ProcedureRegistry {
 impl ProcedureRegistry {
     pub fn new() -> Self {
         let mut handlers: HashMap<String, Arc<dyn Procedure>> = HashMap::new();
+        handlers.insert("algo.dijkstra".to_string(), Arc::new(DijkstraProcedure));
+        handlers.insert("algo.pageRank".to_string(), Arc::new(PageRankProcedure));
+        handlers.insert("algo.wcc".to_string(), Arc::new(WccProcedure));
+        handlers.insert("algo.louvain".to_string(), Arc::new(LouvainProcedure));
+        handlers.insert("algo.motif".to_string(), Arc::new(MotifProcedure));
+        handlers.insert(
+            "algo.motif.count".to_string(),
+            Arc::new(MotifCountProcedure),
+        );
+        handlers.insert(
+            "algo.sampleNeighbors".to_string(),
+            Arc::new(SampleNeighborsProcedure),
+        );
+        handlers.insert(
+            "algo.triangleCount".to_string(),
+            Arc::new(TriangleCountProcedure),
+        );
         handlers.insert("db.info".to_string(), Arc::new(DbInfoProcedure));
+        handlers.insert("db.labels".to_string(), Arc::new(DbLabelsProcedure));
+        handlers.insert(
+            "db.relationshipTypes".to_string(),
+            Arc::new(DbRelationshipTypesProcedure),
+        );
+        handlers.insert(
+            "db.propertyKeys".to_string(),
+            Arc::new(DbPropertyKeysProcedure),
+        );
+        handlers.insert("db.indexes".to_string(), Arc::new(DbIndexesProcedure));
+        handlers.insert("db.namespaces".to_string(), Arc::new(DbNamespacesProcedure));
+        handlers.insert(
+            "db.stats.degrees".to_string(),
+            Arc::new(DbStatsDegreesProcedure),
+        );
         handlers.insert("math.add".to_string(), Arc::new(MathAddProcedure));
+        handlers.insert("query.list".to_string(), Arc::new(QueryListProcedure));
+        handlers.insert("vector.search".to_string(), Arc::new(VectorSearchProcedure));
         handlers.insert(
             "test.doNothing".to_string(),
             Arc::new(TestFixtureProcedure {
@@ -149,8 +299,14 @@ impl ProcedureRegistry {
         Self { handlers }
     }
 
+    /// The built-in procedure `name`, else one registered with
+    /// [`register_procedure`].
     pub fn get(&self, name: &str) -> Option<Arc<dyn Procedure>> {
-        self.handlers.get(name).cloned()
+        if let Some(handler) = self.handlers.get(name) {
+            return Some(handler.clone());
+        }
+        let custom = custom_procedures().read().ok()?;
+        custom.get(name).map(|(_, procedure)| procedure.clone())
     }
 }
 
@@ -160,6 +316,97 @@ pub fn get_procedure_registry() -> &'static ProcedureRegistry {
     GLOBAL_PROCEDURE_REGISTRY.get_or_init(ProcedureRegistry::new)
 }
 
+type CustomProcedures = HashMap<String, (Signature, Arc<dyn Procedure>)>;
+type CustomFunctions = HashMap<String, (Signature, ScalarFunction)>;
+
+static CUSTOM_PROCEDURES: OnceLock<RwLock<CustomProcedures>> = OnceLock::new();
+static CUSTOM_FUNCTIONS: OnceLock<RwLock<CustomFunctions>> = OnceLock::new();
+
+fn custom_procedures() -> &'static RwLock<CustomProcedures> {
+    CUSTOM_PROCEDURES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn custom_functions() -> &'static RwLock<CustomFunctions> {
+    CUSTOM_FUNCTIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Makes `CALL name(...)` run `procedure` in every database of the process,
+/// replacing an earlier registration of `name`. Each returned row should
+/// carry the columns in `signature.outputs`. Fails if a built-in procedure
+/// has that name.
+pub fn register_procedure(
+    name: impl Into<String>,
+    signature: Signature,
+    procedure: impl Procedure + 'static,
+) -> Result<()> {
+    let name = name.into();
+    if name.is_empty() || get_procedure_registry().handlers.contains_key(&name) {
+        return Err(Error::Other(format!(
+            "cannot register procedure '{name}': the name is taken by a built-in procedure"
+        )));
+    }
+    custom_procedures()
+        .write()
+        .unwrap()
+        .insert(name, (signature, Arc::new(procedure)));
+    crate::plan_cache::invalidate_plan_cache();
+    Ok(())
+}
+
+/// Removes a procedure added with [`register_procedure`]; returns whether
+/// it was registered.
+pub fn unregister_procedure(name: &str) -> bool {
+    let removed = custom_procedures().write().unwrap().remove(name).is_some();
+    crate::plan_cache::invalidate_plan_cache();
+    removed
+}
+
+/// Makes `name(...)` call `function` in every database of the process,
+/// replacing an earlier registration of `name`. Function names are
+/// case-insensitive like the built-in ones, which cannot be replaced.
+pub fn register_function(
+    name: impl Into<String>,
+    signature: Signature,
+    function: impl Fn(&[Value]) -> Value + Send + Sync + 'static,
+) -> Result<()> {
+    let name = name.into();
+    if name.is_empty() || crate::query_api::is_builtin_function(&name) {
+        return Err(Error::Other(format!(
+            "cannot register function '{name}': the name is taken by a built-in function"
+        )));
+    }
+    custom_functions()
+        .write()
+        .unwrap()
+        .insert(name.to_lowercase(), (signature, Arc::new(function)));
+    crate::plan_cache::invalidate_plan_cache();
+    Ok(())
+}
+
+/// Removes a function added with [`register_function`]; returns whether it
+/// was registered.
+pub fn unregister_function(name: &str) -> bool {
+    let removed = custom_functions()
+        .write()
+        .unwrap()
+        .remove(&name.to_lowercase())
+        .is_some();
+    crate::plan_cache::invalidate_plan_cache();
+    removed
+}
+
+/// Signature of the registered procedure `name`.
+pub(crate) fn custom_procedure_signature(name: &str) -> Option<Signature> {
+    let custom = custom_procedures().read().ok()?;
+    custom.get(name).map(|(signature, _)| signature.clone())
+}
+
+/// The registered function `name` (lowercase) and its signature.
+pub(crate) fn custom_function(name: &str) -> Option<(Signature, ScalarFunction)> {
+    let custom = custom_functions().read().ok()?;
+    custom.get(name).cloned()
+}
+
 static TEST_PROCEDURE_FIXTURES: OnceLock<RwLock<HashMap<String, TestProcedureFixture>>> =
     OnceLock::new();
 
@@ -306,6 +553,229 @@ impl Procedure for DbInfoProcedure {
     }
 }
 
+/// One row per name, in a column called `column`.
+fn name_rows(column: &str, names: Vec<String>) -> Vec<Row> {
+    names
+        .into_iter()
+        .map(|name| Row::new(vec![(column.to_string(), Value::String(name))]))
+        .collect()
+}
+
+/// `db.labels()`: every label the database has used, as `label` rows.
+struct DbLabelsProcedure;
+
+impl Procedure for DbLabelsProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        Ok(name_rows("label", snapshot.schema_erased().labels))
+    }
+}
+
+/// `db.relationshipTypes()`: every relationship type, as `relationshipType` rows.
+struct DbRelationshipTypesProcedure;
+
+impl Procedure for DbRelationshipTypesProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        Ok(name_rows(
+            "relationshipType",
+            snapshot.schema_erased().rel_types,
+        ))
+    }
+}
+
+/// `db.propertyKeys()`: every property key, as `propertyKey` rows.
+struct DbPropertyKeysProcedure;
+
+impl Procedure for DbPropertyKeysProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        Ok(name_rows(
+            "propertyKey",
+            snapshot.schema_erased().property_keys,
+        ))
+    }
+}
+
+/// `db.indexes()`: one row per index with `name`, `type` (`BTREE`, `TEXT`
+/// or `COMPOSITE`), `labelsOrTypes`, `properties` and `uniqueness`.
+struct DbIndexesProcedure;
+
+impl Procedure for DbIndexesProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        let strings =
+            |items: Vec<String>| Value::List(items.into_iter().map(Value::String).collect());
+        Ok(snapshot
+            .schema_erased()
+            .indexes
+            .into_iter()
+            .map(|index| {
+                let uniqueness = if index.unique { "UNIQUE" } else { "NONUNIQUE" };
+                Row::new(vec![
+                    ("name".to_string(), Value::String(index.name)),
+                    (
+                        "type".to_string(),
+                        Value::String(index.kind.as_str().to_uppercase()),
+                    ),
+                    ("labelsOrTypes".to_string(), strings(vec![index.label])),
+                    ("properties".to_string(), strings(index.properties)),
+                    (
+                        "uniqueness".to_string(),
+                        Value::String(uniqueness.to_string()),
+                    ),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `db.stats.degrees()`: two rows per relationship type, one per
+/// `direction` (`OUTGOING` counts source nodes, `INCOMING` target nodes),
+/// with `relType`, `relationships`, `nodes`, `meanDegree`, `maxDegree`,
+/// `maxNode` and `buckets`, where `buckets[b]` counts the nodes with a
+/// degree in `[2^b, 2^(b+1))`. Reflects the last compaction.
+struct DbStatsDegreesProcedure;
+
+impl Procedure for DbStatsDegreesProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        let mut rows = Vec::new();
+        for degrees in snapshot.degree_stats_erased() {
+            for (direction, histogram) in [
+                ("OUTGOING", degrees.outgoing),
+                ("INCOMING", degrees.incoming),
+            ] {
+                let nodes = histogram.nodes();
+                let mean = if nodes == 0 {
+                    0.0
+                } else {
+                    degrees.relationships as f64 / nodes as f64
+                };
+                let max_node = if nodes == 0 {
+                    Value::Null
+                } else {
+                    Value::NodeId(histogram.max_node)
+                };
+                rows.push(Row::new(vec![
+                    (
+                        "relType".to_string(),
+                        Value::String(degrees.rel_type.clone()),
+                    ),
+                    (
+                        "direction".to_string(),
+                        Value::String(direction.to_string()),
+                    ),
+                    (
+                        "relationships".to_string(),
+                        Value::Int(degrees.relationships as i64),
+                    ),
+                    ("nodes".to_string(), Value::Int(nodes as i64)),
+                    ("meanDegree".to_string(), Value::Float(mean)),
+                    (
+                        "maxDegree".to_string(),
+                        Value::Int(histogram.max_degree as i64),
+                    ),
+                    ("maxNode".to_string(), max_node),
+                    (
+                        "buckets".to_string(),
+                        Value::List(
+                            histogram
+                                .buckets
+                                .into_iter()
+                                .map(|count| Value::Int(count as i64))
+                                .collect(),
+                        ),
+                    ),
+                ]));
+            }
+        }
+        Ok(rows)
+    }
+}
+
+/// `db.namespaces()`: the namespace catalog as `prefix`, `iri` rows.
+struct DbNamespacesProcedure;
+
+impl Procedure for DbNamespacesProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        Ok(snapshot
+            .namespaces_erased()
+            .into_iter()
+            .map(|(prefix, iri)| {
+                Row::new(vec![
+                    ("prefix".to_string(), Value::String(prefix)),
+                    ("iri".to_string(), Value::String(iri)),
+                ])
+            })
+            .collect())
+    }
+}
+
+struct QueryListProcedure;
+
+impl Procedure for QueryListProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, _args: Vec<Value>) -> Result<Vec<Row>> {
+        Ok(snapshot
+            .named_queries_erased()
+            .into_iter()
+            .map(|(name, query)| {
+                Row::new(vec![
+                    ("name".to_string(), Value::String(name)),
+                    ("query".to_string(), Value::String(query)),
+                ])
+            })
+            .collect())
+    }
+}
+
+/// `vector.search(embedding, k)`: the `k` nearest nodes as `node`, `score`
+/// rows, where `score` is the L2 distance (lower is closer).
+struct VectorSearchProcedure;
+
+impl Procedure for VectorSearchProcedure {
+    fn execute(&self, snapshot: &dyn ErasedSnapshot, args: Vec<Value>) -> Result<Vec<Row>> {
+        if args.len() != 2 {
+            return Err(Error::Other(
+                "vector.search requires 2 arguments".to_string(),
+            ));
+        }
+        let query = match &args[0] {
+            Value::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Int(i) => Ok(*i as f32),
+                    Value::Float(f) => Ok(*f as f32),
+                    _ => Err(Error::Other(
+                        "vector.search requires a list of numbers".to_string(),
+                    )),
+                })
+                .collect::<Result<Vec<f32>>>()?,
+            Value::Null => return Ok(Vec::new()),
+            _ => {
+                return Err(Error::Other(
+                    "vector.search requires a list of numbers".to_string(),
+                ));
+            }
+        };
+        let k = match &args[1] {
+            Value::Int(k) if *k >= 0 => *k as usize,
+            _ => {
+                return Err(Error::Other(
+                    "vector.search requires a non-negative integer k".to_string(),
+                ));
+            }
+        };
+        let hits = snapshot
+            .search_vector_erased(&query, k)
+            .ok_or_else(|| Error::Other("vector.search failed".to_string()))?;
+        Ok(hits
+            .into_iter()
+            .map(|(node, distance)| {
+                Row::new(vec![
+                    ("node".to_string(), Value::NodeId(node)),
+                    ("score".to_string(), Value::Float(f64::from(distance))),
+                ])
+            })
+            .collect())
+    }
+}
+
 struct MathAddProcedure;
 
 impl Procedure for MathAddProcedure {
@@ -1,6 +1,8 @@
+use super::spill::{MemoryReservation, row_bytes};
 use super::{Result, Row, Value};
 use crate::ast::AggregateFunction;
 use crate::evaluator::{evaluate_expression_value, order_compare};
+use crate::sketch::{DEFAULT_PRECISION, HyperLogLog};
 use nervusdb_api::GraphSnapshot;
 
 pub(super) fn execute_aggregate<'a, S: GraphSnapshot + 'a>(
@@ -10,10 +12,13 @@ pub(super) fn execute_aggregate<'a, S: GraphSnapshot + 'a>(
     aggregates: Vec<(AggregateFunction, String)>,
     params: &'a crate::query_api::Params,
 ) -> Box<dyn Iterator<Item = Result<Row>> + 'a> {
-    // Collect all rows and group them
-    let mut groups: std::collections::HashMap<Vec<Value>, Vec<Row>> =
+    // Collect all rows and group them. Groups are emitted in the order their
+    // first row arrived, so ordered input gives ordered output.
+    let mut group_index: std::collections::HashMap<Vec<Value>, usize> =
         std::collections::HashMap::new();
+    let mut groups: Vec<(Vec<Value>, Vec<Row>)> = Vec::new();
     let mut total_rows: usize = 0;
+    let mut memory = MemoryReservation::new(params);
 
     for item in input {
         if let Err(err) = params.check_timeout("Aggregate.collect") {
@@ -40,7 +45,16 @@ pub(super) fn execute_aggregate<'a, S: GraphSnapshot + 'a>(
             })
             .collect();
 
-        groups.entry(key).or_default().push(row);
+        if let Err(err) = memory.require("Aggregate.rows", row_bytes(&row)) {
+            return Box::new(std::iter::once(Err(err)));
+        }
+        match group_index.get(&key) {
+            Some(&index) => groups[index].1.push(row),
+            None => {
+                group_index.insert(key.clone(), groups.len());
+                groups.push((key, vec![row]));
+            }
+        }
         total_rows = total_rows.saturating_add(1);
         if let Err(err) = params.check_collection_size("Aggregate.groups", groups.len()) {
             return Box::new(std::iter::once(Err(err)));
@@ -52,7 +66,7 @@ pub(super) fn execute_aggregate<'a, S: GraphSnapshot + 'a>(
 
     // Cypher aggregate semantics: no grouping keys still yields one row on empty input.
     if groups.is_empty() && group_by.is_empty() {
-        groups.insert(Vec::new(), Vec::new());
+        groups.push((Vec::new(), Vec::new()));
     }
 
     // Convert to result rows
@@ -323,6 +337,18 @@ pub(super) fn execute_aggregate<'a, S: GraphSnapshot + 'a>(
                             params,
                         )?
                     }
+                    AggregateFunction::CountDistinctApprox(expr, precision) => {
+                        let sketch =
+                            sketch_values(&rows, expr, precision.as_ref(), snapshot, params)?;
+                        Value::Int(sketch.estimate() as i64)
+                    }
+                    AggregateFunction::HllSketch(expr, precision) => Value::Blob(
+                        sketch_values(&rows, expr, precision.as_ref(), snapshot, params)?
+                            .to_bytes(),
+                    ),
+                    AggregateFunction::HllMerge(expr) => {
+                        merge_sketches(&rows, expr, snapshot, params)?
+                    }
                 };
                 result = result.with(alias, value);
             }
@@ -369,6 +395,18 @@ fn validate_aggregate_runtime_expressions<S: GraphSnapshot>(
                     params,
                 )?;
             }
+            AggregateFunction::CountDistinctApprox(expr, precision)
+            | AggregateFunction::HllSketch(expr, precision) => {
+                super::plan_mid::ensure_runtime_expression_compatible(expr, row, snapshot, params)?;
+                if let Some(precision) = precision {
+                    super::plan_mid::ensure_runtime_expression_compatible(
+                        precision, row, snapshot, params,
+                    )?;
+                }
+            }
+            AggregateFunction::HllMerge(expr) => {
+                super::plan_mid::ensure_runtime_expression_compatible(expr, row, snapshot, params)?
+            }
         }
     }
     Ok(())
@@ -454,6 +492,63 @@ fn collect_numeric_values<S: GraphSnapshot>(
         .collect()
 }
 
+/// A sketch of the non-null values of `expr` over `rows`.
+fn sketch_values<S: GraphSnapshot>(
+    rows: &[Row],
+    expr: &crate::ast::Expression,
+    precision_expr: Option<&crate::ast::Expression>,
+    snapshot: &S,
+    params: &crate::query_api::Params,
+) -> Result<HyperLogLog> {
+    let empty = Row::default();
+    let precision = match precision_expr
+        .map(|e| evaluate_expression_value(e, rows.first().unwrap_or(&empty), snapshot, params))
+    {
+        None | Some(Value::Null) => DEFAULT_PRECISION,
+        Some(Value::Int(p)) => u8::try_from(p).unwrap_or(u8::MAX),
+        Some(_) => {
+            return Err(crate::error::Error::Other(
+                "HyperLogLog precision must be an integer".to_string(),
+            ));
+        }
+    };
+    let mut sketch = HyperLogLog::new(precision)?;
+    for row in rows {
+        let value = evaluate_expression_value(expr, row, snapshot, params);
+        if value != Value::Null {
+            sketch.insert(&value);
+        }
+    }
+    Ok(sketch)
+}
+
+/// The union of the sketches `expr` yields over `rows`, as a sketch blob;
+/// null when there are none.
+fn merge_sketches<S: GraphSnapshot>(
+    rows: &[Row],
+    expr: &crate::ast::Expression,
+    snapshot: &S,
+    params: &crate::query_api::Params,
+) -> Result<Value> {
+    let mut merged: Option<HyperLogLog> = None;
+    for row in rows {
+        let sketch = match evaluate_expression_value(expr, row, snapshot, params) {
+            Value::Null => continue,
+            Value::Blob(bytes) => HyperLogLog::from_bytes(&bytes)?,
+            _ => {
+                return Err(crate::error::Error::Other(
+                    "hllMerge expects sketches made by hllSketch".to_string(),
+                ));
+            }
+        };
+        match &mut merged {
+            Some(merged) => merged.merge(&sketch),
+            None => merged = Some(sketch),
+        }
+    }
+    Ok(merged.map_or(Value::Null, |sketch| Value::Blob(sketch.to_bytes())))
+}
+
 fn resolve_percentile<S: GraphSnapshot>(
     rows: &[Row],
     percentile_expr: &crate::ast::Expression,
@@ -1,4 +1,7 @@
 use super::{PropertyValue, Value};
+use crate::evaluator::{
+    date_value_from_epoch_days, duration_value_from_parts, local_time_value_from_nanos,
+};
 use std::collections::BTreeMap;
 
 pub(super) fn merge_props_to_values(
@@ -27,6 +30,19 @@ pub(super) fn merge_storage_property_to_api(v: &PropertyValue) -> nervusdb_api::
                 .map(|(k, vv)| (k.clone(), merge_storage_property_to_api(vv)))
                 .collect(),
         ),
+        PropertyValue::Date(d) => nervusdb_api::PropertyValue::Date(*d),
+        PropertyValue::LocalTime(t) => nervusdb_api::PropertyValue::LocalTime(*t),
+        PropertyValue::Duration {
+            months,
+            days,
+            nanos,
+        } => nervusdb_api::PropertyValue::Duration {
+            months: *months,
+            days: *days,
+            nanos: *nanos,
+        },
+        PropertyValue::IntArray(items) => nervusdb_api::PropertyValue::IntArray(items.clone()),
+        PropertyValue::FloatArray(items) => nervusdb_api::PropertyValue::FloatArray(items.clone()),
     }
 }
 
@@ -47,6 +63,19 @@ fn api_property_to_storage(v: &nervusdb_api::PropertyValue) -> PropertyValue {
                 .map(|(k, vv)| (k.clone(), api_property_to_storage(vv)))
                 .collect(),
         ),
+        nervusdb_api::PropertyValue::Date(d) => PropertyValue::Date(*d),
+        nervusdb_api::PropertyValue::LocalTime(t) => PropertyValue::LocalTime(*t),
+        nervusdb_api::PropertyValue::Duration {
+            months,
+            days,
+            nanos,
+        } => PropertyValue::Duration {
+            months: *months,
+            days: *days,
+            nanos: *nanos,
+        },
+        nervusdb_api::PropertyValue::IntArray(items) => PropertyValue::IntArray(items.clone()),
+        nervusdb_api::PropertyValue::FloatArray(items) => PropertyValue::FloatArray(items.clone()),
     }
 }
 
@@ -75,6 +104,19 @@ pub(super) fn merge_storage_property_to_value(v: &PropertyValue) -> Value {
                 .map(|(k, vv)| (k.clone(), merge_storage_property_to_value(vv)))
                 .collect(),
         ),
+        PropertyValue::Date(d) => date_value_from_epoch_days(*d),
+        PropertyValue::LocalTime(t) => local_time_value_from_nanos(*t),
+        PropertyValue::Duration {
+            months,
+            days,
+            nanos,
+        } => duration_value_from_parts(*months, *days, *nanos),
+        PropertyValue::IntArray(items) => {
+            Value::List(items.iter().map(|i| Value::Int(*i)).collect())
+        }
+        PropertyValue::FloatArray(items) => {
+            Value::List(items.iter().map(|f| Value::Float(f64::from(*f))).collect())
+        }
     }
 }
 
@@ -115,6 +157,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn storage_temporal_properties_project_to_cypher_values() {
+        assert_eq!(
+            merge_storage_property_to_value(&PropertyValue::Date(0)),
+            Value::String("1970-01-01".to_string())
+        );
+        assert_eq!(
+            merge_storage_property_to_value(&PropertyValue::LocalTime(9 * 3_600_000_000_000)),
+            Value::String("09:00".to_string())
+        );
+        let Value::Map(duration) = merge_storage_property_to_value(&PropertyValue::Duration {
+            months: 1,
+            days: 2,
+            nanos: 3_000_000_000,
+        }) else {
+            panic!("duration should project to a duration map");
+        };
+        assert_eq!(
+            duration.get("__display"),
+            Some(&Value::String("P1M2DT3S".to_string()))
+        );
+    }
+
     #[test]
     fn api_map_to_storage_and_props_to_values_roundtrip_shape() {
         let api_props = BTreeMap::from([(
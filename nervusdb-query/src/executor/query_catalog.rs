@@ -0,0 +1,111 @@
+//! `query.save` / `query.drop`: procedures that change the stored query catalog.
+//!
+//! Registered procedures only see a read snapshot, so these two are dispatched
+//! from the write path instead and stage their change on the write transaction.
+//! `query.list` is an ordinary read procedure in the registry. The `algo.*.write`
+//! procedures of [`graph_algorithms`](super::graph_algorithms) go through the
+//! same dispatch.
+
+use super::graph_algorithms::{PAGE_RANK_WRITE, WCC_WRITE, execute_write_algorithm};
+use super::procedure_registry::ErasedSnapshot;
+use super::{Error, GraphSnapshot, Result, Row, Value, WriteableGraph};
+use crate::ast::Expression;
+use crate::evaluator::evaluate_expression_value;
+
+const QUERY_SAVE: &str = "query.save";
+const QUERY_DROP: &str = "query.drop";
+
+pub(crate) fn is_write_procedure(name: &[String]) -> bool {
+    let name = name.join(".");
+    [QUERY_SAVE, QUERY_DROP, PAGE_RANK_WRITE, WCC_WRITE].contains(&name.as_str())
+}
+
+/// Runs a catalog procedure once per input row, returning the number of
+/// catalog changes and the rows joined with the procedure's output.
+pub(super) fn execute_write_procedure<S: GraphSnapshot>(
+    snapshot: &S,
+    input_rows: Vec<Row>,
+    name: &[String],
+    args: &[Expression],
+    yields: &[(String, Option<String>)],
+    txn: &mut dyn WriteableGraph,
+    params: &crate::query_api::Params,
+) -> Result<(u32, Vec<Row>)> {
+    let proc_name = name.join(".");
+    let mut changes = 0;
+    let mut out = Vec::with_capacity(input_rows.len());
+    for row in input_rows {
+        let values: Vec<Value> = args
+            .iter()
+            .map(|arg| evaluate_expression_value(arg, &row, snapshot, params))
+            .collect();
+        let result = match (proc_name.as_str(), values.as_slice()) {
+            (PAGE_RANK_WRITE | WCC_WRITE, args) => {
+                let (written, result) = execute_write_algorithm(
+                    &proc_name,
+                    snapshot as &dyn ErasedSnapshot,
+                    args,
+                    txn,
+                )?;
+                changes += written;
+                result
+            }
+            (QUERY_SAVE, [Value::String(query_name), Value::String(cypher)]) => {
+                validate_stored_query(query_name, cypher)?;
+                txn.save_named_query(query_name, cypher)?;
+                changes += 1;
+                Row::new(vec![(
+                    "name".to_string(),
+                    Value::String(query_name.clone()),
+                )])
+            }
+            (QUERY_DROP, [Value::String(query_name)]) => {
+                let dropped = txn.drop_named_query(query_name)?;
+                if dropped {
+                    changes += 1;
+                }
+                Row::new(vec![
+                    ("name".to_string(), Value::String(query_name.clone())),
+                    ("dropped".to_string(), Value::Bool(dropped)),
+                ])
+            }
+            (QUERY_SAVE, _) => {
+                return Err(Error::Other(
+                    "query.save requires a name and a query string".to_string(),
+                ));
+            }
+            _ => {
+                return Err(Error::Other(
+                    "query.drop requires a query name string".to_string(),
+                ));
+            }
+        };
+
+        let mut joined = row;
+        if yields.is_empty() {
+            for (k, v) in result.cols {
+                joined = joined.with(k, v);
+            }
+        } else {
+            for (field, alias) in yields {
+                if let Some(val) = result.get(field) {
+                    joined = joined.with(alias.as_ref().unwrap_or(field), val.clone());
+                }
+            }
+        }
+        out.push(joined);
+    }
+    Ok((changes, out))
+}
+
+/// Checks that `name` is usable as a catalog key and that `cypher` compiles.
+pub fn validate_stored_query(name: &str, cypher: &str) -> Result<()> {
+    if name.is_empty() || name.chars().any(char::is_control) {
+        return Err(Error::Other(format!(
+            "query.save: invalid query name {name:?}"
+        )));
+    }
+    crate::query_api::prepare(cypher)
+        .map_err(|e| Error::Other(format!("query.save: '{name}' does not compile: {e}")))?;
+    Ok(())
+}
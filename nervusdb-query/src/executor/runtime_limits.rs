@@ -1,14 +1,25 @@
-use super::{GraphSnapshot, PlanIterator, Result, Row};
+use super::{GraphSnapshot, Plan, PlanIterator, Result, Row};
+use std::time::Instant;
 
 pub(super) fn wrap_plan_iterator<'a, S: GraphSnapshot + 'a>(
     iter: PlanIterator<'a, S>,
     params: &'a crate::query_api::Params,
     stage: &'static str,
+    plan: &'a Plan,
+    opened_at: Option<Instant>,
 ) -> PlanIterator<'a, S> {
+    // Under PROFILE, operators are keyed by the address of their plan node,
+    // which stays put for the whole run.
+    let profile_key = opened_at.map(|opened| {
+        let key = plan as *const Plan as usize;
+        params.record_operator_open(key, opened.elapsed());
+        key
+    });
     PlanIterator::Dynamic(Box::new(RuntimeGuardIter {
         inner: Box::new(iter),
         params,
         stage,
+        profile_key,
     }))
 }
 
@@ -16,6 +27,7 @@ struct RuntimeGuardIter<'a> {
     inner: Box<dyn Iterator<Item = Result<Row>> + 'a>,
     params: &'a crate::query_api::Params,
     stage: &'static str,
+    profile_key: Option<usize>,
 }
 
 impl<'a> Iterator for RuntimeGuardIter<'a> {
@@ -26,7 +38,15 @@ impl<'a> Iterator for RuntimeGuardIter<'a> {
             return Some(Err(err));
         }
 
-        match self.inner.next() {
+        let started = self.profile_key.map(|_| Instant::now());
+        let next = self.inner.next();
+        if let (Some(key), Some(started)) = (self.profile_key, started) {
+            let produced = matches!(next, Some(Ok(_)));
+            self.params
+                .record_operator_step(key, produced, started.elapsed());
+        }
+
+        match next {
             Some(Ok(row)) => {
                 if let Err(err) = self.params.note_emitted_row(self.stage) {
                     return Some(Err(err));
@@ -0,0 +1,493 @@
+//! Memory accounting and spill files for buffering operators.
+//!
+//! `ORDER BY`, `DISTINCT` and aggregation hold rows until their input ends.
+//! Each reserves an estimate of what it buffers against the query's
+//! `ExecuteOptions::max_memory_bytes`; past it, `ORDER BY` writes sorted runs
+//! and `DISTINCT` hash partitions to spill files, which are removed when
+//! dropped. Rows are written in a private binary encoding that, unlike the
+//! JSON form of [`Value`], reads back exactly.
+
+use super::{
+    EdgeKey, Error, NodeValue, PathValue, ReifiedPathValue, RelationshipValue, Result, Row, Value,
+};
+use crate::query_api::Params;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bytes of the query's memory budget held by one operator, released when
+/// cleared or dropped.
+pub(super) struct MemoryReservation<'a> {
+    params: &'a Params,
+    bytes: usize,
+}
+
+impl<'a> MemoryReservation<'a> {
+    pub(super) fn new(params: &'a Params) -> Self {
+        Self { params, bytes: 0 }
+    }
+
+    /// Reserves `bytes` more. Returns `Ok(false)`, reserving nothing, when
+    /// the budget is spent and the operator should spill; fails instead
+    /// when spilling is disabled.
+    pub(super) fn grow(&mut self, stage: &str, bytes: usize) -> Result<bool> {
+        match self.params.reserve_memory(bytes) {
+            Ok(()) => {
+                self.bytes += bytes;
+                Ok(true)
+            }
+            Err(_) if self.params.execute_options().spill_to_disk => Ok(false),
+            Err(observed) => Err(self.params.memory_limit_exceeded(stage, observed)),
+        }
+    }
+
+    /// Reserves `bytes` more for an operator that cannot spill.
+    pub(super) fn require(&mut self, stage: &str, bytes: usize) -> Result<()> {
+        match self.params.reserve_memory(bytes) {
+            Ok(()) => {
+                self.bytes += bytes;
+                Ok(())
+            }
+            Err(observed) => Err(self.params.memory_limit_exceeded(stage, observed)),
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.params.release_memory(std::mem::take(&mut self.bytes));
+    }
+}
+
+impl Drop for MemoryReservation<'_> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Estimated heap and inline bytes of a buffered row.
+pub(super) fn row_bytes(row: &Row) -> usize {
+    std::mem::size_of::<Row>()
+        + row
+            .cols
+            .iter()
+            .map(|(name, value)| std::mem::size_of::<String>() + name.len() + value_bytes(value))
+            .sum::<usize>()
+}
+
+pub(super) fn value_bytes(value: &Value) -> usize {
+    let properties = |map: &BTreeMap<String, Value>| -> usize {
+        map.iter()
+            .map(|(k, v)| std::mem::size_of::<String>() + k.len() + value_bytes(v))
+            .sum()
+    };
+    let node = |node: &NodeValue| -> usize {
+        node.labels.iter().map(|l| l.len() + 24).sum::<usize>() + properties(&node.properties)
+    };
+    std::mem::size_of::<Value>()
+        + match value {
+            Value::String(s) => s.len(),
+            Value::Blob(b) => b.len(),
+            Value::List(items) => items.iter().map(value_bytes).sum(),
+            Value::Map(map) => properties(map),
+            Value::Path(path) => {
+                path.nodes.len() * std::mem::size_of::<u32>()
+                    + path.edges.len() * std::mem::size_of::<EdgeKey>()
+            }
+            Value::Node(n) => node(n),
+            Value::Relationship(rel) => rel.rel_type.len() + properties(&rel.properties),
+            Value::ReifiedPath(path) => {
+                path.nodes.iter().map(node).sum::<usize>()
+                    + path
+                        .relationships
+                        .iter()
+                        .map(|rel| rel.rel_type.len() + properties(&rel.properties))
+                        .sum::<usize>()
+            }
+            _ => 0,
+        }
+}
+
+static NEXT_SPILL_FILE: AtomicU64 = AtomicU64::new(0);
+
+/// A temporary file of `(sort keys, row)` records, written once and then
+/// read back in order.
+pub(super) struct SpillFile {
+    path: RemoveOnDrop,
+    writer: BufWriter<File>,
+}
+
+impl SpillFile {
+    pub(super) fn create(params: &Params) -> Result<Self> {
+        let dir = params
+            .execute_options()
+            .spill_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir);
+        let path = dir.join(format!(
+            "nervusdb-spill-{}-{}.tmp",
+            std::process::id(),
+            NEXT_SPILL_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self {
+            path: RemoveOnDrop(path),
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub(super) fn write(&mut self, keys: &[Value], row: &Row) -> Result<()> {
+        let mut buf = Vec::new();
+        put_len(&mut buf, keys.len());
+        for key in keys {
+            encode_value(key, &mut buf);
+        }
+        put_len(&mut buf, row.cols.len());
+        for (name, value) in &row.cols {
+            put_bytes(&mut buf, name.as_bytes());
+            encode_value(value, &mut buf);
+        }
+        self.writer.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Finishes writing and reads the records back in the order written.
+    pub(super) fn into_reader(self) -> Result<SpillReader> {
+        let Self { path, writer } = self;
+        let mut file = writer
+            .into_inner()
+            .map_err(|err| Error::Io(err.into_error()))?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(SpillReader {
+            reader: BufReader::new(file),
+            _path: path,
+        })
+    }
+}
+
+struct RemoveOnDrop(PathBuf);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+pub(super) struct SpillReader {
+    reader: BufReader<File>,
+    _path: RemoveOnDrop,
+}
+
+impl Iterator for SpillReader {
+    type Item = Result<(Vec<Value>, Row)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.fill_buf() {
+            Ok([]) => None,
+            Ok(_) => Some(read_record(&mut self.reader)),
+            Err(err) => Some(Err(err.into())),
+        }
+    }
+}
+
+fn read_record(reader: &mut impl Read) -> Result<(Vec<Value>, Row)> {
+    let keys = (0..get_len(reader)?)
+        .map(|_| decode_value(reader))
+        .collect::<Result<Vec<_>>>()?;
+    let col_count = get_len(reader)?;
+    let mut cols = Vec::with_capacity(col_count);
+    for _ in 0..col_count {
+        let name = get_string(reader)?;
+        cols.push((name, decode_value(reader)?));
+    }
+    Ok((keys, Row::new(cols)))
+}
+
+fn put_len(buf: &mut Vec<u8>, len: usize) {
+    buf.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    put_len(buf, bytes.len());
+    buf.extend_from_slice(bytes);
+}
+
+fn put_edge(buf: &mut Vec<u8>, edge: &EdgeKey) {
+    for part in [edge.src, edge.rel, edge.dst] {
+        buf.extend_from_slice(&part.to_le_bytes());
+    }
+}
+
+fn put_properties(buf: &mut Vec<u8>, map: &BTreeMap<String, Value>) {
+    put_len(buf, map.len());
+    for (key, value) in map {
+        put_bytes(buf, key.as_bytes());
+        encode_value(value, buf);
+    }
+}
+
+fn put_node(buf: &mut Vec<u8>, node: &NodeValue) {
+    buf.extend_from_slice(&node.id.to_le_bytes());
+    put_len(buf, node.labels.len());
+    for label in &node.labels {
+        put_bytes(buf, label.as_bytes());
+    }
+    put_properties(buf, &node.properties);
+}
+
+fn put_relationship(buf: &mut Vec<u8>, rel: &RelationshipValue) {
+    put_edge(buf, &rel.key);
+    put_bytes(buf, rel.rel_type.as_bytes());
+    put_properties(buf, &rel.properties);
+}
+
+fn encode_value(value: &Value, buf: &mut Vec<u8>) {
+    match value {
+        Value::Null => buf.push(0),
+        Value::Bool(b) => buf.extend_from_slice(&[1, u8::from(*b)]),
+        Value::Int(i) => {
+            buf.push(2);
+            buf.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::Float(f) => {
+            buf.push(3);
+            buf.extend_from_slice(&f.to_bits().to_le_bytes());
+        }
+        Value::String(s) => {
+            buf.push(4);
+            put_bytes(buf, s.as_bytes());
+        }
+        Value::List(items) => {
+            buf.push(5);
+            put_len(buf, items.len());
+            for item in items {
+                encode_value(item, buf);
+            }
+        }
+        Value::DateTime(micros) => {
+            buf.push(6);
+            buf.extend_from_slice(&micros.to_le_bytes());
+        }
+        Value::Blob(bytes) => {
+            buf.push(7);
+            put_bytes(buf, bytes);
+        }
+        Value::Map(map) => {
+            buf.push(8);
+            put_properties(buf, map);
+        }
+        Value::NodeId(id) => {
+            buf.push(9);
+            buf.extend_from_slice(&id.to_le_bytes());
+        }
+        Value::ExternalId(id) => {
+            buf.push(10);
+            buf.extend_from_slice(&id.to_le_bytes());
+        }
+        Value::EdgeKey(edge) => {
+            buf.push(11);
+            put_edge(buf, edge);
+        }
+        Value::Path(path) => {
+            buf.push(12);
+            put_len(buf, path.nodes.len());
+            for node in &path.nodes {
+                buf.extend_from_slice(&node.to_le_bytes());
+            }
+            put_len(buf, path.edges.len());
+            for edge in &path.edges {
+                put_edge(buf, edge);
+            }
+        }
+        Value::Node(node) => {
+            buf.push(13);
+            put_node(buf, node);
+        }
+        Value::Relationship(rel) => {
+            buf.push(14);
+            put_relationship(buf, rel);
+        }
+        Value::ReifiedPath(path) => {
+            buf.push(15);
+            put_len(buf, path.nodes.len());
+            for node in &path.nodes {
+                put_node(buf, node);
+            }
+            put_len(buf, path.relationships.len());
+            for rel in &path.relationships {
+                put_relationship(buf, rel);
+            }
+        }
+    }
+}
+
+fn get_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn get_u32(reader: &mut impl Read) -> Result<u32> {
+    Ok(u32::from_le_bytes(get_array(reader)?))
+}
+
+fn get_i64(reader: &mut impl Read) -> Result<i64> {
+    Ok(i64::from_le_bytes(get_array(reader)?))
+}
+
+fn get_len(reader: &mut impl Read) -> Result<usize> {
+    Ok(u64::from_le_bytes(get_array(reader)?) as usize)
+}
+
+fn get_bytes(reader: &mut impl Read) -> Result<Vec<u8>> {
+    let len = get_len(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(corrupt());
+    }
+    Ok(bytes)
+}
+
+fn get_string(reader: &mut impl Read) -> Result<String> {
+    String::from_utf8(get_bytes(reader)?).map_err(|_| corrupt())
+}
+
+fn get_edge(reader: &mut impl Read) -> Result<EdgeKey> {
+    Ok(EdgeKey {
+        src: get_u32(reader)?,
+        rel: get_u32(reader)?,
+        dst: get_u32(reader)?,
+    })
+}
+
+fn get_properties(reader: &mut impl Read) -> Result<BTreeMap<String, Value>> {
+    let len = get_len(reader)?;
+    let mut map = BTreeMap::new();
+    for _ in 0..len {
+        let key = get_string(reader)?;
+        map.insert(key, decode_value(reader)?);
+    }
+    Ok(map)
+}
+
+fn get_node(reader: &mut impl Read) -> Result<NodeValue> {
+    let id = get_u32(reader)?;
+    let labels = (0..get_len(reader)?)
+        .map(|_| get_string(reader))
+        .collect::<Result<_>>()?;
+    Ok(NodeValue {
+        id,
+        labels,
+        properties: get_properties(reader)?,
+    })
+}
+
+fn get_relationship(reader: &mut impl Read) -> Result<RelationshipValue> {
+    Ok(RelationshipValue {
+        key: get_edge(reader)?,
+        rel_type: get_string(reader)?,
+        properties: get_properties(reader)?,
+    })
+}
+
+fn decode_value(reader: &mut impl Read) -> Result<Value> {
+    let [tag] = get_array(reader)?;
+    Ok(match tag {
+        0 => Value::Null,
+        1 => Value::Bool(get_array::<1>(reader)?[0] != 0),
+        2 => Value::Int(get_i64(reader)?),
+        3 => Value::Float(f64::from_bits(u64::from_le_bytes(get_array(reader)?))),
+        4 => Value::String(get_string(reader)?),
+        5 => Value::List(
+            (0..get_len(reader)?)
+                .map(|_| decode_value(reader))
+                .collect::<Result<_>>()?,
+        ),
+        6 => Value::DateTime(get_i64(reader)?),
+        7 => Value::Blob(get_bytes(reader)?),
+        8 => Value::Map(get_properties(reader)?),
+        9 => Value::NodeId(get_u32(reader)?),
+        10 => Value::ExternalId(u64::from_le_bytes(get_array(reader)?)),
+        11 => Value::EdgeKey(get_edge(reader)?),
+        12 => {
+            let nodes = (0..get_len(reader)?)
+                .map(|_| get_u32(reader))
+                .collect::<Result<_>>()?;
+            let edges = (0..get_len(reader)?)
+                .map(|_| get_edge(reader))
+                .collect::<Result<_>>()?;
+            Value::Path(PathValue { nodes, edges })
+        }
+        13 => Value::Node(get_node(reader)?),
+        14 => Value::Relationship(get_relationship(reader)?),
+        15 => {
+            let nodes = (0..get_len(reader)?)
+                .map(|_| get_node(reader))
+                .collect::<Result<_>>()?;
+            let relationships = (0..get_len(reader)?)
+                .map(|_| get_relationship(reader))
+                .collect::<Result<_>>()?;
+            Value::ReifiedPath(ReifiedPathValue {
+                nodes,
+                relationships,
+            })
+        }
+        _ => return Err(corrupt()),
+    })
+}
+
+fn corrupt() -> Error {
+    Error::Other("corrupt spill file".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip_through_a_spill_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let params = Params::with_execute_options(crate::query_api::ExecuteOptions {
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        });
+        let node = NodeValue {
+            id: 7,
+            labels: vec!["User".into()],
+            properties: BTreeMap::from([("name".into(), Value::String("ada".into()))]),
+        };
+        let rows = vec![
+            Row::new(vec![
+                ("n".into(), Value::Node(node.clone())),
+                (
+                    "xs".into(),
+                    Value::List(vec![Value::Float(1.5), Value::Null]),
+                ),
+            ]),
+            Row::new(vec![(
+                "p".into(),
+                Value::ReifiedPath(ReifiedPathValue {
+                    nodes: vec![node],
+                    relationships: vec![],
+                }),
+            )]),
+        ];
+
+        let mut file = SpillFile::create(&params).unwrap();
+        for row in &rows {
+            file.write(&[Value::Int(1), Value::Blob(vec![0, 255])], row)
+                .unwrap();
+        }
+        let read: Vec<_> = file.into_reader().unwrap().map(Result::unwrap).collect();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].0, vec![Value::Int(1), Value::Blob(vec![0, 255])]);
+        assert_eq!(read[0].1, rows[0]);
+        assert_eq!(read[1].1, rows[1]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
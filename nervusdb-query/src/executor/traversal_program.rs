@@ -0,0 +1,523 @@
+//! Compiled traversal programs for chained pattern expansions.
+//!
+//! A `NodeScan` followed by fixed-length `MatchOut` / `MatchIn` hops is
+//! lowered to a flat opcode array over node and relationship registers and
+//! run by a small backtracking machine. Intermediate bindings live in the
+//! registers instead of per-hop `Row` clones and path rebuilds; a row is only
+//! built when the last hop matches, with its columns in the order the
+//! interpreter would have produced them.
+//!
+//! `prepare` compiles each chain once and caches the program on the plan;
+//! every execution only binds its label and relationship type names to the
+//! snapshot's ids.
+//!
+//! Anything the compiler does not recognise (optional hops, variable-length
+//! hops, other inputs, alias clashes) returns `None` and keeps the
+//! operator-by-operator interpreter.
+
+use super::label_constraint::{LabelConstraint, node_matches_label_constraint};
+use super::{
+    EdgeKey, GraphSnapshot, InternalNodeId, LabelId, Plan, PlanIterator, RelTypeId, Result, Row,
+    Value, edge_multiplicity, resolve_label_constraint,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+/// One instruction, bound to a snapshot. `Scan` is always the first and
+/// only scan; every later instruction is an `Expand` whose relationship
+/// register is its index - 1.
+#[derive(Debug)]
+enum Op {
+    Scan {
+        dst: usize,
+        label: Option<LabelId>,
+    },
+    Expand {
+        src: usize,
+        dst: usize,
+        /// Whether `dst` was bound by an earlier instruction, so the hop
+        /// only checks it instead of writing it.
+        dst_bound: bool,
+        direction: Direction,
+        rels: Option<Vec<RelTypeId>>,
+        dst_labels: LabelConstraint,
+    },
+}
+
+/// An `Expand` before binding, with its types and labels still names.
+#[derive(Debug)]
+struct Expand {
+    src: usize,
+    dst: usize,
+    dst_bound: bool,
+    direction: Direction,
+    rels: Vec<String>,
+    dst_labels: Vec<String>,
+}
+
+/// Column names an instruction binds when a row is built.
+#[derive(Debug)]
+struct Output {
+    node_alias: String,
+    edge_alias: Option<String>,
+}
+
+/// A hop chain compiled once at prepare time.
+///
+/// Label and relationship type ids can differ between snapshots, so they
+/// are kept as names and resolved each time the program runs.
+#[derive(Debug)]
+pub struct TraversalProgram {
+    scan_label: Option<String>,
+    expands: Vec<Expand>,
+    outputs: Vec<Output>,
+    node_registers: usize,
+    /// Shared path alias of the hops; also turns on relationship uniqueness.
+    path_alias: Option<String>,
+    limit: Option<usize>,
+}
+
+struct Hop<'p> {
+    direction: Direction,
+    src_alias: &'p str,
+    rels: &'p [String],
+    edge_alias: Option<&'p str>,
+    dst_alias: &'p str,
+    dst_labels: &'p [String],
+    path_alias: Option<&'p str>,
+}
+
+/// Attaches a program to every hop chain in `plan` that compiles.
+pub(crate) fn compile_traversals(plan: &mut Plan) {
+    if let Some(compiled) = compile(plan) {
+        if let Plan::MatchOut { program, .. } | Plan::MatchIn { program, .. } = plan {
+            *program = Some(Arc::new(compiled));
+        }
+        return;
+    }
+    match plan {
+        Plan::MatchOut { input, .. }
+        | Plan::MatchIn { input, .. }
+        | Plan::MatchUndirected { input, .. }
+        | Plan::MatchOutVarLen { input, .. } => {
+            if let Some(input) = input {
+                compile_traversals(input);
+            }
+        }
+        Plan::MatchBoundRel { input, .. }
+        | Plan::Filter { input, .. }
+        | Plan::Project { input, .. }
+        | Plan::Aggregate { input, .. }
+        | Plan::OrderBy { input, .. }
+        | Plan::Skip { input, .. }
+        | Plan::Limit { input, .. }
+        | Plan::Distinct { input }
+        | Plan::Unwind { input, .. }
+        | Plan::LoadCsv { input, .. }
+        | Plan::Delete { input, .. }
+        | Plan::SetProperty { input, .. }
+        | Plan::SetPropertiesFromMap { input, .. }
+        | Plan::SetLabels { input, .. }
+        | Plan::RemoveProperty { input, .. }
+        | Plan::RemoveLabels { input, .. }
+        | Plan::ProcedureCall { input, .. }
+        | Plan::Create { input, .. } => compile_traversals(input),
+        Plan::IndexSeek { fallback, .. }
+        | Plan::TextIndexSeek { fallback, .. }
+        | Plan::CompositeIndexSeek { fallback, .. } => compile_traversals(fallback),
+        Plan::CostChoice { alternatives } => alternatives.iter_mut().for_each(compile_traversals),
+        Plan::OptionalWhereFixup {
+            outer: left,
+            filtered: right,
+            ..
+        }
+        | Plan::Apply {
+            input: left,
+            subquery: right,
+            ..
+        }
+        | Plan::Foreach {
+            input: left,
+            sub_plan: right,
+            ..
+        }
+        | Plan::CartesianProduct { left, right }
+        | Plan::HashJoin { left, right, .. }
+        | Plan::Union { left, right, .. } => {
+            compile_traversals(left);
+            compile_traversals(right);
+        }
+        Plan::ReturnOne | Plan::NodeScan { .. } | Plan::Values { .. } => {}
+    }
+}
+
+/// Compiles `plan` when it is a chain of fixed-length hops over a node scan.
+fn compile(plan: &Plan) -> Option<TraversalProgram> {
+    let limit = match plan {
+        Plan::MatchOut { limit, .. } => limit.map(|n| n as usize),
+        Plan::MatchIn { .. } => None,
+        _ => return None,
+    };
+
+    let mut hops = Vec::new();
+    let mut current = plan;
+    let (root_alias, root_label) = loop {
+        match current {
+            Plan::MatchOut {
+                input: Some(input),
+                src_alias,
+                rels,
+                edge_alias,
+                dst_alias,
+                dst_labels,
+                limit,
+                optional: false,
+                path_alias,
+                ..
+            } if limit.is_none() || hops.is_empty() => {
+                hops.push(Hop {
+                    direction: Direction::Outgoing,
+                    src_alias,
+                    rels,
+                    edge_alias: edge_alias.as_deref(),
+                    dst_alias,
+                    dst_labels,
+                    path_alias: path_alias.as_deref(),
+                });
+                current = input;
+            }
+            Plan::MatchIn {
+                input: Some(input),
+                src_alias,
+                rels,
+                edge_alias,
+                dst_alias,
+                dst_labels,
+                optional: false,
+                path_alias,
+                ..
+            } => {
+                hops.push(Hop {
+                    direction: Direction::Incoming,
+                    src_alias,
+                    rels,
+                    edge_alias: edge_alias.as_deref(),
+                    dst_alias,
+                    dst_labels,
+                    path_alias: path_alias.as_deref(),
+                });
+                current = input;
+            }
+            Plan::NodeScan {
+                alias,
+                label,
+                optional: false,
+            } => break (alias.as_str(), label.clone()),
+            _ => return None,
+        }
+    };
+    hops.reverse();
+
+    let path_alias = hops[0].path_alias;
+    if hops.iter().any(|hop| hop.path_alias != path_alias) || path_alias == Some(root_alias) {
+        return None;
+    }
+
+    let mut node_registers: HashMap<&str, usize> = HashMap::from([(root_alias, 0)]);
+    let mut edge_aliases: Vec<&str> = Vec::new();
+    let mut expands = Vec::with_capacity(hops.len());
+    let mut outputs = vec![Output {
+        node_alias: root_alias.to_string(),
+        edge_alias: None,
+    }];
+    for hop in hops {
+        let src = *node_registers.get(hop.src_alias)?;
+        if let Some(edge_alias) = hop.edge_alias
+            && (node_registers.contains_key(edge_alias)
+                || edge_aliases.contains(&edge_alias)
+                || Some(edge_alias) == path_alias
+                || edge_alias == hop.dst_alias)
+        {
+            return None;
+        }
+        if edge_aliases.contains(&hop.dst_alias) || Some(hop.dst_alias) == path_alias {
+            return None;
+        }
+        let next = node_registers.len();
+        let dst = *node_registers.entry(hop.dst_alias).or_insert(next);
+        edge_aliases.extend(hop.edge_alias);
+        expands.push(Expand {
+            src,
+            dst,
+            dst_bound: dst != next,
+            direction: hop.direction,
+            rels: hop.rels.to_vec(),
+            dst_labels: hop.dst_labels.to_vec(),
+        });
+        outputs.push(Output {
+            node_alias: hop.dst_alias.to_string(),
+            edge_alias: hop.edge_alias.map(str::to_string),
+        });
+    }
+
+    Some(TraversalProgram {
+        scan_label: root_label,
+        expands,
+        outputs,
+        node_registers: node_registers.len(),
+        path_alias: path_alias.map(str::to_string),
+        limit,
+    })
+}
+
+/// Resolves the names of `program` against `snapshot`, or `None` when the
+/// scan label is unknown there and nothing can match.
+fn bind<S: GraphSnapshot>(snapshot: &S, program: &TraversalProgram) -> Option<Vec<Op>> {
+    let label = match &program.scan_label {
+        Some(name) => Some(snapshot.resolve_label_id(name)?),
+        None => None,
+    };
+    let mut ops = Vec::with_capacity(program.expands.len() + 1);
+    ops.push(Op::Scan { dst: 0, label });
+    ops.extend(program.expands.iter().map(|expand| Op::Expand {
+        src: expand.src,
+        dst: expand.dst,
+        dst_bound: expand.dst_bound,
+        direction: expand.direction,
+        rels: resolve_rel_ids(snapshot, &expand.rels),
+        dst_labels: resolve_label_constraint(snapshot, &expand.dst_labels),
+    }));
+    Some(ops)
+}
+
+fn resolve_rel_ids<S: GraphSnapshot>(snapshot: &S, rels: &[String]) -> Option<Vec<RelTypeId>> {
+    if rels.is_empty() {
+        return None;
+    }
+    Some(
+        rels.iter()
+            .filter_map(|rel| snapshot.resolve_rel_type_id(rel))
+            .collect(),
+    )
+}
+
+pub(super) fn execute<'a, S: GraphSnapshot + 'a>(
+    snapshot: &'a S,
+    program: &'a TraversalProgram,
+    params: &'a crate::query_api::Params,
+) -> PlanIterator<'a, S> {
+    let Some(ops) = bind(snapshot, program) else {
+        return PlanIterator::Dynamic(Box::new(std::iter::empty()));
+    };
+    let hops = ops.len() - 1;
+    PlanIterator::Dynamic(Box::new(TraversalIter {
+        snapshot,
+        params,
+        scan: Box::new(snapshot.nodes()),
+        cursors: (0..hops).map(|_| None).collect(),
+        nodes: vec![InternalNodeId::default(); program.node_registers],
+        edges: Vec::with_capacity(hops),
+        pc: 0,
+        emitted: 0,
+        program,
+        ops,
+    }))
+}
+
+struct TraversalIter<'a, S: GraphSnapshot + 'a> {
+    snapshot: &'a S,
+    params: &'a crate::query_api::Params,
+    program: &'a TraversalProgram,
+    ops: Vec<Op>,
+    scan: Box<dyn Iterator<Item = InternalNodeId> + 'a>,
+    /// Candidate relationships of each `Expand`, open while it is bound.
+    cursors: Vec<Option<Box<dyn Iterator<Item = EdgeKey> + 'a>>>,
+    nodes: Vec<InternalNodeId>,
+    /// Relationship registers of the bound `Expand`s.
+    edges: Vec<EdgeKey>,
+    /// Instruction to advance next.
+    pc: usize,
+    emitted: usize,
+}
+
+impl<'a, S: GraphSnapshot + 'a> TraversalIter<'a, S> {
+    /// Counts an intermediate binding against the runtime limits, as the
+    /// interpreter's per-operator guard would. The last hop is counted by
+    /// the guard around the whole program.
+    fn note_binding(&self, stage: &'static str) -> Result<()> {
+        self.params.check_timeout(stage)?;
+        self.params.note_emitted_row(stage)
+    }
+
+    fn scan_next(&mut self, dst: usize, label: Option<LabelId>) -> Option<InternalNodeId> {
+        let snapshot = self.snapshot;
+        for iid in self.scan.by_ref() {
+            if snapshot.is_tombstoned_node(iid) {
+                continue;
+            }
+            if let Some(lid) = label {
+                let matches_label = snapshot
+                    .resolve_node_labels(iid)
+                    .map(|labels| labels.contains(&lid))
+                    .unwrap_or_else(|| snapshot.node_label(iid) == Some(lid));
+                if !matches_label {
+                    continue;
+                }
+            }
+            self.nodes[dst] = iid;
+            return Some(iid);
+        }
+        None
+    }
+
+    fn open(&mut self, pc: usize) {
+        let Op::Expand {
+            src,
+            direction,
+            rels,
+            ..
+        } = &self.ops[pc]
+        else {
+            return;
+        };
+        let (snapshot, direction, rels) = (self.snapshot, *direction, rels.clone());
+        let node = self.nodes[*src];
+        let neighbors = move |rel: Option<RelTypeId>| -> Box<dyn Iterator<Item = EdgeKey> + 'a> {
+            match direction {
+                Direction::Outgoing => Box::new(snapshot.neighbors(node, rel)),
+                Direction::Incoming => Box::new(snapshot.incoming_neighbors(node, rel)),
+            }
+        };
+        let cursor: Box<dyn Iterator<Item = EdgeKey> + 'a> = match rels {
+            Some(rels) => Box::new(rels.into_iter().flat_map(move |rel| neighbors(Some(rel)))),
+            None => neighbors(None),
+        };
+        self.cursors[pc - 1] = Some(cursor);
+    }
+
+    /// Advances the `Expand` at `pc` to its next matching relationship.
+    fn expand_next(&mut self, pc: usize) -> bool {
+        let Op::Expand {
+            dst,
+            dst_bound,
+            direction,
+            dst_labels,
+            ..
+        } = &self.ops[pc]
+        else {
+            return false;
+        };
+        let hop = pc - 1;
+        self.edges.truncate(hop);
+        let Some(cursor) = self.cursors[hop].as_mut() else {
+            return false;
+        };
+        for edge in cursor.by_ref() {
+            let node = match direction {
+                Direction::Outgoing => edge.dst,
+                Direction::Incoming => edge.src,
+            };
+            if self.program.path_alias.is_some() {
+                let used = self.edges.iter().filter(|used| **used == edge).count();
+                if used > 0 && used >= edge_multiplicity(self.snapshot, edge) {
+                    continue;
+                }
+            }
+            if *dst_bound && self.nodes[*dst] != node {
+                continue;
+            }
+            if !node_matches_label_constraint(self.snapshot, node, dst_labels) {
+                continue;
+            }
+            self.nodes[*dst] = node;
+            self.edges.push(edge);
+            return true;
+        }
+        self.cursors[hop] = None;
+        false
+    }
+
+    fn build_row(&self) -> Row {
+        let root = &self.program.outputs[0];
+        let mut row = Row::default().with(root.node_alias.as_str(), Value::NodeId(self.nodes[0]));
+        for (pc, output) in self.program.outputs.iter().enumerate().skip(1) {
+            let Op::Expand { dst, direction, .. } = &self.ops[pc] else {
+                continue;
+            };
+            let edge = self.edges[pc - 1];
+            let node = Value::NodeId(self.nodes[*dst]);
+            let (from, to) = match direction {
+                Direction::Outgoing => {
+                    if let Some(alias) = &output.edge_alias {
+                        row = row.with(alias.as_str(), Value::EdgeKey(edge));
+                    }
+                    row = row.with(output.node_alias.as_str(), node);
+                    (edge.src, edge.dst)
+                }
+                Direction::Incoming => {
+                    row = row.with(output.node_alias.as_str(), node);
+                    if let Some(alias) = &output.edge_alias {
+                        row = row.with(alias.as_str(), Value::EdgeKey(edge));
+                    }
+                    (edge.dst, edge.src)
+                }
+            };
+            if let Some(path_alias) = &self.program.path_alias {
+                row.join_path(path_alias, from, edge, to);
+            }
+        }
+        row
+    }
+}
+
+impl<'a, S: GraphSnapshot + 'a> Iterator for TraversalIter<'a, S> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.ops.len() - 1;
+        if self
+            .program
+            .limit
+            .is_some_and(|limit| self.emitted >= limit)
+        {
+            return None;
+        }
+        loop {
+            match self.ops[self.pc] {
+                Op::Scan { dst, label } => {
+                    self.scan_next(dst, label)?;
+                    if let Err(err) = self.note_binding("NodeScan") {
+                        return Some(Err(err));
+                    }
+                    self.open(1);
+                    self.pc = 1;
+                }
+                Op::Expand { direction, .. } => {
+                    if !self.expand_next(self.pc) {
+                        self.pc -= 1;
+                        continue;
+                    }
+                    if self.pc == last {
+                        self.emitted += 1;
+                        return Some(Ok(self.build_row()));
+                    }
+                    let stage = match direction {
+                        Direction::Outgoing => "MatchOut",
+                        Direction::Incoming => "MatchIn",
+                    };
+                    if let Err(err) = self.note_binding(stage) {
+                        return Some(Err(err));
+                    }
+                    self.pc += 1;
+                    self.open(self.pc);
+                }
+            }
+        }
+    }
+}
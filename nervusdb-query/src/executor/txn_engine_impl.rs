@@ -39,8 +39,8 @@ impl<'a> WriteableGraph for EngineWriteTxn<'a> {
         key: String,
         value: PropertyValue,
     ) -> Result<()> {
-        EngineWriteTxn::set_node_property(self, node, key, value);
-        Ok(())
+        EngineWriteTxn::set_node_property(self, node, key, value)
+            .map_err(|e| Error::Other(e.to_string()))
     }
 
     fn set_edge_property(
@@ -51,13 +51,13 @@ impl<'a> WriteableGraph for EngineWriteTxn<'a> {
         key: String,
         value: PropertyValue,
     ) -> Result<()> {
-        EngineWriteTxn::set_edge_property(self, src, rel, dst, key, value);
-        Ok(())
+        EngineWriteTxn::set_edge_property(self, src, rel, dst, key, value)
+            .map_err(|e| Error::Other(e.to_string()))
     }
 
     fn remove_node_property(&mut self, node: InternalNodeId, key: &str) -> Result<()> {
-        EngineWriteTxn::remove_node_property(self, node, key);
-        Ok(())
+        EngineWriteTxn::remove_node_property(self, node, key)
+            .map_err(|e| Error::Other(e.to_string()))
     }
 
     fn remove_edge_property(
@@ -67,8 +67,8 @@ impl<'a> WriteableGraph for EngineWriteTxn<'a> {
         dst: InternalNodeId,
         key: &str,
     ) -> Result<()> {
-        EngineWriteTxn::remove_edge_property(self, src, rel, dst, key);
-        Ok(())
+        EngineWriteTxn::remove_edge_property(self, src, rel, dst, key)
+            .map_err(|e| Error::Other(e.to_string()))
     }
 
     fn tombstone_node(&mut self, node: InternalNodeId) -> Result<()> {
@@ -97,4 +97,13 @@ impl<'a> WriteableGraph for EngineWriteTxn<'a> {
     fn staged_created_nodes_with_labels(&self) -> Vec<(InternalNodeId, Vec<String>)> {
         EngineWriteTxn::staged_created_nodes_with_labels(self)
     }
+
+    fn save_named_query(&mut self, name: &str, cypher: &str) -> Result<()> {
+        EngineWriteTxn::save_query(self, name, cypher);
+        Ok(())
+    }
+
+    fn drop_named_query(&mut self, name: &str) -> Result<bool> {
+        Ok(EngineWriteTxn::drop_query(self, name))
+    }
 }
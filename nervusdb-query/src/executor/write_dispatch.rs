@@ -1,7 +1,7 @@
 use super::{
     Error, GraphSnapshot, Plan, Result, WriteableGraph, execute_create, execute_delete,
     execute_foreach, execute_remove, execute_remove_labels, execute_set, execute_set_from_maps,
-    execute_set_labels,
+    execute_set_labels, is_write_procedure, write_orchestration::execute_write_with_rows,
 };
 
 pub(super) fn execute_write<S: GraphSnapshot>(
@@ -43,13 +43,24 @@ pub(super) fn execute_write<S: GraphSnapshot>(
         | Plan::OrderBy { input, .. }
         | Plan::Distinct { input }
         | Plan::Unwind { input, .. }
-        | Plan::Aggregate { input, .. }
-        | Plan::ProcedureCall { input, .. } => execute_write(input, snapshot, txn, params),
+        | Plan::LoadCsv { input, .. }
+        | Plan::Aggregate { input, .. } => execute_write(input, snapshot, txn, params),
+        Plan::ProcedureCall { input, name, .. } => {
+            if is_write_procedure(name) {
+                execute_write_with_rows(plan, snapshot, txn, params).map(|(count, _)| count)
+            } else {
+                execute_write(input, snapshot, txn, params)
+            }
+        }
         Plan::OptionalWhereFixup {
             outer, filtered, ..
         } => execute_write(outer, snapshot, txn, params)
             .or_else(|_| execute_write(filtered, snapshot, txn, params)),
-        Plan::IndexSeek { fallback, .. } => execute_write(fallback, snapshot, txn, params),
+        Plan::IndexSeek { fallback, .. }
+        | Plan::TextIndexSeek { fallback, .. }
+        | Plan::CompositeIndexSeek { fallback, .. } => {
+            execute_write(fallback, snapshot, txn, params)
+        }
         Plan::MatchOut { input, .. }
         | Plan::MatchIn { input, .. }
         | Plan::MatchUndirected { input, .. }
@@ -87,6 +87,9 @@ pub(super) fn execute_delete<S: GraphSnapshot>(
     create_delete_ops::execute_delete(snapshot, input, txn, detach, expressions, params)
 }
 
-pub(super) fn convert_executor_value_to_property(value: &Value) -> Result<PropertyValue> {
-    write_path::convert_executor_value_to_property(value)
+pub(super) fn convert_expression_value_to_property(
+    expr: &Expression,
+    value: &Value,
+) -> Result<PropertyValue> {
+    write_path::convert_expression_value_to_property(expr, value)
 }
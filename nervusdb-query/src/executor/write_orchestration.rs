@@ -1,5 +1,6 @@
 #![allow(clippy::collapsible_match, clippy::too_many_arguments)]
 
+use super::query_catalog::execute_write_procedure;
 use super::write_support::merge_eval_props_on_row;
 use super::{
     EdgeKey, GraphSnapshot, InternalNodeId, MergeOverlayNode, MergeOverlayState, Plan, Result, Row,
@@ -7,6 +8,7 @@ use super::{
     apply_set_map_overlay_to_rows, apply_set_property_overlay_to_rows, execute_create_write_rows,
     execute_delete_on_rows, execute_foreach, execute_merge_create_from_rows, execute_plan,
     execute_remove, execute_remove_labels, execute_set, execute_set_from_maps, execute_set_labels,
+    is_write_procedure,
 };
 use crate::ast::{Expression, PathElement};
 use crate::evaluator::evaluate_expression_value;
@@ -166,6 +168,24 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
             let out_rows = execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
             Ok((mods, out_rows))
         }
+        Plan::LoadCsv {
+            input,
+            source,
+            alias,
+            with_headers,
+            field_terminator,
+        } => {
+            let (mods, rows) = execute_write_with_rows(input, snapshot, txn, params)?;
+            let staged = Plan::LoadCsv {
+                input: Box::new(Plan::Values { rows }),
+                source: source.clone(),
+                alias: alias.clone(),
+                with_headers: *with_headers,
+                field_terminator: *field_terminator,
+            };
+            let out_rows = execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
+            Ok((mods, out_rows))
+        }
         Plan::ProcedureCall {
             input,
             name,
@@ -173,6 +193,11 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
             yields,
         } => {
             let (mods, rows) = execute_write_with_rows(input, snapshot, txn, params)?;
+            if is_write_procedure(name) {
+                let (changes, out_rows) =
+                    execute_write_procedure(snapshot, rows, name, args, yields, txn, params)?;
+                return Ok((mods + changes, out_rows));
+            }
             let staged = Plan::ProcedureCall {
                 input: Box::new(Plan::Values { rows }),
                 name: name.clone(),
@@ -196,6 +221,7 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
             optional,
             optional_unbind,
             path_alias,
+            program: _,
         } => {
             if let Some(inner) = input {
                 let (mods, rows) = execute_write_with_rows(inner, snapshot, txn, params)?;
@@ -213,6 +239,7 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
                     optional: *optional,
                     optional_unbind: optional_unbind.clone(),
                     path_alias: path_alias.clone(),
+                    program: None,
                 };
                 let out_rows =
                     execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
@@ -280,6 +307,7 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
             optional,
             optional_unbind,
             path_alias,
+            program: _,
         } => {
             if let Some(inner) = input {
                 let (mods, rows) = execute_write_with_rows(inner, snapshot, txn, params)?;
@@ -295,6 +323,7 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
                     optional: *optional,
                     optional_unbind: optional_unbind.clone(),
                     path_alias: path_alias.clone(),
+                    program: None,
                 };
                 let out_rows =
                     execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
@@ -405,6 +434,11 @@ pub(super) fn execute_write_with_rows<S: GraphSnapshot>(
             let out_rows = execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
             Ok((left_mods + right_mods, out_rows))
         }
+        // Writes keep the textual order so that staged creates stay visible
+        // to the scans they were written against.
+        Plan::CostChoice { alternatives } if !alternatives.is_empty() => {
+            execute_write_with_rows(&alternatives[0], snapshot, txn, params)
+        }
         _ => {
             let out_rows = execute_plan(snapshot, plan, params).collect::<Result<Vec<_>>>()?;
             Ok((0, out_rows))
@@ -887,6 +921,36 @@ fn execute_merge_with_rows_inner<S: GraphSnapshot>(
             let out_rows = execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
             Ok((mods, out_rows))
         }
+        Plan::LoadCsv {
+            input,
+            source,
+            alias,
+            with_headers,
+            field_terminator,
+        } => {
+            let (mods, rows) = execute_merge_with_rows_inner(
+                input,
+                snapshot,
+                txn,
+                params,
+                on_create_items,
+                on_create_map_items,
+                on_match_items,
+                on_match_map_items,
+                on_create_labels,
+                on_match_labels,
+                overlay,
+            )?;
+            let staged = Plan::LoadCsv {
+                input: Box::new(Plan::Values { rows }),
+                source: source.clone(),
+                alias: alias.clone(),
+                with_headers: *with_headers,
+                field_terminator: *field_terminator,
+            };
+            let out_rows = execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
+            Ok((mods, out_rows))
+        }
         Plan::ProcedureCall {
             input,
             name,
@@ -929,6 +993,7 @@ fn execute_merge_with_rows_inner<S: GraphSnapshot>(
             optional,
             optional_unbind,
             path_alias,
+            program: _,
         } => {
             if let Some(inner) = input {
                 let (mods, rows) = execute_merge_with_rows_inner(
@@ -958,6 +1023,7 @@ fn execute_merge_with_rows_inner<S: GraphSnapshot>(
                     optional: *optional,
                     optional_unbind: optional_unbind.clone(),
                     path_alias: path_alias.clone(),
+                    program: None,
                 };
                 let out_rows =
                     execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
@@ -1037,6 +1103,7 @@ fn execute_merge_with_rows_inner<S: GraphSnapshot>(
             optional,
             optional_unbind,
             path_alias,
+            program: _,
         } => {
             if let Some(inner) = input {
                 let (mods, rows) = execute_merge_with_rows_inner(
@@ -1064,6 +1131,7 @@ fn execute_merge_with_rows_inner<S: GraphSnapshot>(
                     optional: *optional,
                     optional_unbind: optional_unbind.clone(),
                     path_alias: path_alias.clone(),
+                    program: None,
                 };
                 let out_rows =
                     execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
@@ -1258,6 +1326,21 @@ fn execute_merge_with_rows_inner<S: GraphSnapshot>(
             let out_rows = execute_plan(snapshot, &staged, params).collect::<Result<Vec<_>>>()?;
             Ok((left_mods + right_mods, out_rows))
         }
+        Plan::CostChoice { alternatives } if !alternatives.is_empty() => {
+            execute_merge_with_rows_inner(
+                &alternatives[0],
+                snapshot,
+                txn,
+                params,
+                on_create_items,
+                on_create_map_items,
+                on_match_items,
+                on_match_map_items,
+                on_create_labels,
+                on_match_labels,
+                overlay,
+            )
+        }
         _ => {
             let out_rows = execute_plan(snapshot, plan, params).collect::<Result<Vec<_>>>()?;
             Ok((0, out_rows))
@@ -1278,7 +1361,7 @@ fn bind_plan_input_rows(plan: &mut Plan, rows: &[Row]) {
             *input = Some(Box::new(values()));
         }
         Plan::MatchBoundRel { input, .. } => {
-            *input = Box::new(values());
+            **input = values();
         }
         Plan::Filter { input, .. }
         | Plan::Project { input, .. }
@@ -1287,7 +1370,8 @@ fn bind_plan_input_rows(plan: &mut Plan, rows: &[Row]) {
         | Plan::Skip { input, .. }
         | Plan::Limit { input, .. }
         | Plan::Distinct { input }
-        | Plan::Unwind { input, .. } => bind_plan_input_rows(input, rows),
+        | Plan::Unwind { input, .. }
+        | Plan::LoadCsv { input, .. } => bind_plan_input_rows(input, rows),
         _ => {
             *plan = values();
         }
@@ -4,7 +4,7 @@ use super::{
 };
 use crate::ast::Expression;
 use crate::error::Error;
-use crate::evaluator::evaluate_expression_value;
+use crate::evaluator::{duration_parts_from_value, evaluate_expression_value};
 use nervusdb_api::GraphSnapshot;
 
 pub(super) fn execute_set<S: GraphSnapshot>(
@@ -24,7 +24,7 @@ pub(super) fn execute_set<S: GraphSnapshot>(
             let val = evaluate_expression_value(expr, &row, snapshot, params);
 
             // Convert value to PropertyValue
-            let prop_val = convert_executor_value_to_property(&val)?;
+            let prop_val = convert_expression_value_to_property(expr, &val)?;
             let is_remove = matches!(prop_val, PropertyValue::Null);
 
             if let Some(node_id) = row.get_node(var) {
@@ -659,6 +659,34 @@ pub(super) fn apply_removed_property_overlay_to_rows<S: GraphSnapshot>(
         .collect()
 }
 
+/// Like [`convert_executor_value_to_property`], but a `toIntegerArray()` or
+/// `toFloatArray()` call stores its list as a packed array.
+pub(super) fn convert_expression_value_to_property(
+    expr: &Expression,
+    value: &Value,
+) -> Result<PropertyValue> {
+    if let (Expression::FunctionCall(call), Value::List(items)) = (expr, value) {
+        if call.name.eq_ignore_ascii_case("tointegerarray") {
+            let ints = items.iter().map(|item| match item {
+                Value::Int(i) => Some(*i),
+                _ => None,
+            });
+            if let Some(ints) = ints.collect() {
+                return Ok(PropertyValue::IntArray(ints));
+            }
+        } else if call.name.eq_ignore_ascii_case("tofloatarray") {
+            let floats = items.iter().map(|item| match item {
+                Value::Float(f) => Some(*f as f32),
+                _ => None,
+            });
+            if let Some(floats) = floats.collect() {
+                return Ok(PropertyValue::FloatArray(floats));
+            }
+        }
+    }
+    convert_executor_value_to_property(value)
+}
+
 pub(super) fn convert_executor_value_to_property(value: &Value) -> Result<PropertyValue> {
     match value {
         Value::Null => Ok(PropertyValue::Null),
@@ -694,6 +722,13 @@ pub(super) fn convert_executor_value_to_property(value: &Value) -> Result<Proper
             Ok(PropertyValue::List(list))
         }
         Value::Map(m) => {
+            if let Some((months, days, nanos)) = duration_parts_from_value(value) {
+                return Ok(PropertyValue::Duration {
+                    months,
+                    days,
+                    nanos,
+                });
+            }
             let mut map = std::collections::BTreeMap::new();
             for (k, v) in m {
                 map.insert(k.clone(), convert_executor_value_to_property(v)?);
@@ -752,10 +787,13 @@ mod tests {
         match converted {
             PropertyValue::List(items) => {
                 assert_eq!(items.len(), 3);
-                assert!(
-                    items
-                        .iter()
-                        .all(|item| matches!(item, PropertyValue::Map(_)))
+                assert_eq!(
+                    items[0],
+                    PropertyValue::Duration {
+                        months: 0,
+                        days: 0,
+                        nanos: 13_000_000_000,
+                    }
                 );
             }
             other => panic!("expected list property, got {other:?}"),
@@ -1,6 +1,6 @@
 use super::{
     Plan, PropertyValue, Row, Value, WriteableGraph, apply_set_map_overlay_to_rows,
-    convert_executor_value_to_property, execute_set_from_maps,
+    convert_expression_value_to_property, execute_set_from_maps,
 };
 use crate::ast::Expression;
 use crate::error::{Error, Result};
@@ -17,7 +17,7 @@ pub(super) fn merge_apply_set_items<S: GraphSnapshot>(
     for (var, key, expr) in items {
         super::plan_mid::ensure_runtime_expression_compatible(expr, row, snapshot, params)?;
         let val = evaluate_expression_value(expr, row, snapshot, params);
-        let prop_val = convert_executor_value_to_property(&val)?;
+        let prop_val = convert_expression_value_to_property(expr, &val)?;
         let is_remove = matches!(prop_val, PropertyValue::Null);
         if let Some(node_id) = row.get_node(var) {
             if is_remove {
@@ -144,7 +144,10 @@ pub(super) fn merge_eval_props_on_row<S: GraphSnapshot>(
                 params,
             )?;
             let v = evaluate_expression_value(&pair.value, row, snapshot, params);
-            out.insert(pair.key.clone(), convert_executor_value_to_property(&v)?);
+            out.insert(
+                pair.key.clone(),
+                convert_expression_value_to_property(&pair.value, &v)?,
+            );
         }
     }
     Ok(out)
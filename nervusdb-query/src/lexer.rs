@@ -82,6 +82,7 @@ pub enum TokenType {
     // Operators
     Equals,
     NotEquals,
+    RegexMatch,
     LessThan,
     LessEqual,
     GreaterThan,
@@ -250,7 +251,14 @@ impl<'a> Lexer<'a> {
                     TokenType::GreaterThan
                 }
             }
-            '=' => TokenType::Equals,
+            '=' => {
+                if let Some(&'~') = self.chars.peek() {
+                    self.advance();
+                    TokenType::RegexMatch
+                } else {
+                    TokenType::Equals
+                }
+            }
             '+' => TokenType::Plus,
             '*' => TokenType::Asterisk,
             '/' => TokenType::Divide,
@@ -366,18 +374,16 @@ impl<'a> Lexer<'a> {
                         };
                         value.push(decoded);
                     }
+                    // `\\` is one backslash, so `'a\\.b'` is the text `a\.b`;
+                    // other escapes are kept as written.
+                    Some('\\') => {
+                        value.push('\\');
+                        self.advance();
+                    }
                     Some(next) => {
-                        if next == '\\' {
-                            value.push('\\');
-                            value.push('\\');
-                            value.push('\\');
-                            value.push('\\');
-                            self.advance();
-                        } else {
-                            value.push('\\');
-                            value.push(next);
-                            self.advance();
-                        }
+                        value.push('\\');
+                        value.push(next);
+                        self.advance();
                     }
                     None => {
                         return Err("Unterminated string literal".to_string());
@@ -654,3 +660,27 @@ impl<'a> Lexer<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(input: &str) -> String {
+        match Lexer::new(input).tokenize().unwrap().remove(0).token_type {
+            TokenType::String(value) => value,
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(string(r"'a\\b'"), r"a\b");
+        assert_eq!(string(r"'\\\\'"), r"\\");
+        assert_eq!(string(r"'a\u00e9'"), "aé");
+        assert_eq!(string("'it''s'"), "it's");
+        // Other escapes reach the evaluator as written.
+        assert_eq!(string(r"'a\.b\n'"), r"a\.b\n");
+        assert_eq!(string(r#""\"""#), r#"\""#);
+        assert!(Lexer::new(r"'a\").tokenize().is_err());
+    }
+}
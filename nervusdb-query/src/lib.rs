@@ -37,15 +37,27 @@ pub mod executor;
 pub mod facade;
 pub mod lexer;
 pub mod parser;
+pub mod plan_cache;
 pub mod query_api;
+pub mod result_cache;
+pub mod sketch;
 
 pub use error::{Error, ResourceLimitKind, Result};
-pub use executor::{Row, Value, WriteableGraph};
+pub use executor::{
+    ErasedSnapshot, Procedure, Row, Signature, Value, WriteableGraph, register_function,
+    register_procedure, unregister_function, unregister_procedure, validate_stored_query,
+};
 pub use facade::{
     EdgeKey, ExternalId, GraphSnapshot, GraphStore, InternalNodeId, LabelId, PropertyValue,
     QueryExt, RelTypeId, query_collect,
 };
-pub use query_api::{ExecuteOptions, Params, PreparedQuery, prepare};
+pub use plan_cache::{
+    DEFAULT_PLAN_CACHE_CAPACITY, PlanCache, PlanCacheStats, invalidate_plan_cache, prepare_cached,
+    set_plan_cache_capacity,
+};
+pub use query_api::{ExecuteOptions, Page, Params, PreparedQuery, ReadSet, Scope, prepare};
+pub use result_cache::{ResultCache, ResultCacheStats, ResultKey};
+pub use sketch::HyperLogLog;
 
 /// Parses a Cypher query string into an AST.
 ///
@@ -162,6 +162,11 @@ impl TokenParser {
         if self.match_token(&TokenType::Unwind) {
             return Ok(Some(Clause::Unwind(self.parse_unwind()?)));
         }
+        if self.check_word("LOAD") && self.check_next_word("CSV") {
+            self.advance();
+            self.advance();
+            return Ok(Some(Clause::LoadCsv(self.parse_load_csv()?)));
+        }
         if self.match_token(&TokenType::Call) {
             return Ok(Some(Clause::Call(self.parse_call()?)));
         }
@@ -338,6 +343,51 @@ impl TokenParser {
         Ok(UnwindClause { expression, alias })
     }
 
+    fn parse_load_csv(&mut self) -> Result<LoadCsvClause, Error> {
+        let with_headers = if self.match_token(&TokenType::With) {
+            if !self.match_word("HEADERS") {
+                return Err(Error::Other("Expected HEADERS after LOAD CSV WITH".into()));
+            }
+            true
+        } else {
+            false
+        };
+        if !self.match_word("FROM") {
+            return Err(Error::Other("Expected FROM after LOAD CSV".into()));
+        }
+        let source = self.parse_expression()?;
+        self.consume(&TokenType::As, "Expected AS after LOAD CSV source")?;
+        let alias = self.parse_identifier("LOAD CSV alias")?;
+
+        let field_terminator = if self.match_word("FIELDTERMINATOR") {
+            let TokenType::String(text) = &self.advance().token_type else {
+                return Err(Error::Other(
+                    "Expected a string after FIELDTERMINATOR".into(),
+                ));
+            };
+            // The lexer keeps escapes other than `\u` as written.
+            let text = if text == "\\t" { "\t" } else { text.as_str() };
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii() => Some(c),
+                _ => {
+                    return Err(Error::Other(
+                        "FIELDTERMINATOR must be a single ASCII character".into(),
+                    ));
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(LoadCsvClause {
+            with_headers,
+            source,
+            alias,
+            field_terminator,
+        })
+    }
+
     fn parse_return(&mut self) -> Result<ReturnClause, Error> {
         let distinct = self.match_token(&TokenType::Distinct);
         let mut items = Vec::new();
@@ -813,6 +863,55 @@ impl TokenParser {
         Ok(PropertyMap { properties })
     }
 
+    /// Parses `{.key, .*, key: expr, var}` after `variable`. Desugared to
+    /// `__map_projection(variable, {key: value, ...}, all_properties)`:
+    /// null for a null subject, otherwise its properties (with `.*`)
+    /// overlaid with the listed entries.
+    fn parse_map_projection(&mut self, variable: String) -> Result<Expression, Error> {
+        self.ensure_budget()?;
+        self.consume(&TokenType::LeftBrace, "Expected '{'")?;
+        let mut properties = Vec::new();
+        let mut all_properties = false;
+
+        while !self.check(&TokenType::RightBrace) {
+            if self.match_token(&TokenType::Dot) {
+                if self.match_token(&TokenType::Asterisk) {
+                    all_properties = true;
+                } else {
+                    let key = self.parse_property_key()?;
+                    let value = Expression::PropertyAccess(PropertyAccess {
+                        variable: variable.clone(),
+                        property: key.clone(),
+                    });
+                    properties.push(PropertyPair { key, value });
+                }
+            } else if self.check_next(&TokenType::Colon) {
+                let key = self.parse_property_key()?;
+                self.consume(&TokenType::Colon, "Expected ':' in map projection")?;
+                let value = self.parse_expression()?;
+                properties.push(PropertyPair { key, value });
+            } else {
+                let key = self.parse_identifier("map projection variable")?;
+                let value = Expression::Variable(key.clone());
+                properties.push(PropertyPair { key, value });
+            }
+
+            if !self.match_token(&TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.consume(&TokenType::RightBrace, "Expected '}' after map projection")?;
+        Ok(Expression::FunctionCall(FunctionCall {
+            name: "__map_projection".to_string(),
+            args: vec![
+                Expression::Variable(variable),
+                Expression::Map(PropertyMap { properties }),
+                Expression::Literal(Literal::Boolean(all_properties)),
+            ],
+        }))
+    }
+
     fn parse_order_by(&mut self) -> Result<OrderByClause, Error> {
         let mut items = Vec::new();
         loop {
@@ -1019,10 +1118,7 @@ impl TokenParser {
             lhs = Self::binary_expr(lhs, op, Expression::Literal(Literal::Null));
         }
 
-        loop {
-            let Some((op, lbp, rbp, needs_with)) = self.peek_infix_operator() else {
-                break;
-            };
+        while let Some((op, lbp, rbp, needs_with)) = self.peek_infix_operator() {
             if lbp < min_bp {
                 break;
             }
@@ -1181,6 +1277,11 @@ impl TokenParser {
                 if name.eq_ignore_ascii_case("null") {
                     return Ok(Expression::Literal(Literal::Null));
                 }
+                if name.eq_ignore_ascii_case("count") && self.check(&TokenType::LeftBrace) {
+                    return Ok(Expression::Subquery(Box::new(
+                        self.parse_subquery_expression(SubqueryKind::Count)?,
+                    )));
+                }
 
                 let mut function_name = name.clone();
                 let mut is_function = false;
@@ -1253,6 +1354,19 @@ impl TokenParser {
 
                     let has_distinct_arg = self.match_token(&TokenType::Distinct);
                     let mut args = self.parse_function_arguments()?;
+                    // size((n)-->()) counts the paths the pattern expression
+                    // matches, which is what COUNT { (n)-->() } returns.
+                    if quant_name == "size"
+                        && !has_distinct_arg
+                        && let [Expression::Exists(exists)] = args.as_slice()
+                        && let ExistsExpression::Pattern(pattern) = exists.as_ref()
+                    {
+                        let query = self.build_exists_pattern_subquery(pattern.clone(), None);
+                        return Ok(Expression::Subquery(Box::new(SubqueryExpression {
+                            kind: SubqueryKind::Count,
+                            query,
+                        })));
+                    }
                     if has_distinct_arg {
                         if args.len() != 1 {
                             return Err(Error::Other(
@@ -1323,6 +1437,12 @@ impl TokenParser {
                 self.advance(); // 'EXISTS'
                 Expression::Exists(Box::new(self.parse_exists_expression()?))
             }
+            TokenType::Call => {
+                self.advance(); // 'CALL'
+                Expression::Subquery(Box::new(
+                    self.parse_subquery_expression(SubqueryKind::Scalar)?,
+                ))
+            }
             _ => return Err(Error::NotImplemented("expression")),
         };
 
@@ -1390,6 +1510,13 @@ impl TokenParser {
                 continue;
             }
 
+            if let Expression::Variable(variable) = &expr
+                && self.check(&TokenType::LeftBrace)
+            {
+                expr = self.parse_map_projection(variable.clone())?;
+                continue;
+            }
+
             break;
         }
 
@@ -1494,6 +1621,52 @@ impl TokenParser {
         Ok(ExistsExpression::Pattern(pattern))
     }
 
+    /// Parses the `{ ... }` of `COUNT { ... }` or `CALL { ... }` used as a
+    /// value. `COUNT` also takes a bare pattern with an optional `WHERE`.
+    fn parse_subquery_expression(
+        &mut self,
+        kind: SubqueryKind,
+    ) -> Result<SubqueryExpression, Error> {
+        self.ensure_budget()?;
+        self.consume(&TokenType::LeftBrace, "Expected '{' to open subquery")?;
+        let query = if self.check(&TokenType::Match)
+            || self.check(&TokenType::With)
+            || self.check(&TokenType::Return)
+            || self.check(&TokenType::Unwind)
+            || self.check(&TokenType::Call)
+        {
+            self.parse_query()?
+        } else if kind == SubqueryKind::Count {
+            let pattern = self.parse_pattern()?;
+            let predicate = if self.match_token(&TokenType::Where) {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+            self.build_exists_pattern_subquery(pattern, predicate)
+        } else {
+            return Err(Error::Other(
+                "syntax error: Expected a query inside CALL { }".to_string(),
+            ));
+        };
+        self.consume(&TokenType::RightBrace, "Expected '}' after subquery")?;
+        self.validate_exists_subquery_clauses(&query)?;
+
+        if kind == SubqueryKind::Scalar {
+            let single_column = matches!(
+                query.clauses.last(),
+                Some(Clause::Return(ret)) if ret.items.len() == 1
+            );
+            if !single_column {
+                return Err(Error::Other(
+                    "syntax error: CALL subquery used as a value must RETURN exactly one column"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(SubqueryExpression { kind, query })
+    }
+
     fn validate_exists_subquery_clauses(&self, subquery: &Query) -> Result<(), Error> {
         for clause in &subquery.clauses {
             if matches!(
@@ -1634,6 +1807,12 @@ impl TokenParser {
                 Self::BP_PRED + 1,
                 false,
             )),
+            TokenType::RegexMatch => Some((
+                BinaryOperator::RegexMatch,
+                Self::BP_PRED,
+                Self::BP_PRED + 1,
+                false,
+            )),
             TokenType::Starts => {
                 if self.check_next(&TokenType::With) {
                     Some((
@@ -1848,6 +2027,28 @@ impl TokenParser {
             && matches!(self.tokens[idx].token_type, TokenType::LeftParen)
     }
 
+    /// Whether the current token is the contextual keyword `word`, which the
+    /// lexer leaves as an identifier.
+    fn check_word(&self, word: &str) -> bool {
+        matches!(&self.peek().token_type, TokenType::Identifier(name) if name.eq_ignore_ascii_case(word))
+    }
+
+    fn check_next_word(&self, word: &str) -> bool {
+        matches!(
+            self.tokens.get(self.position + 1).map(|t| &t.token_type),
+            Some(TokenType::Identifier(name)) if name.eq_ignore_ascii_case(word)
+        )
+    }
+
+    fn match_word(&mut self, word: &str) -> bool {
+        if self.check_word(word) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
     fn check_next(&self, token_type: &TokenType) -> bool {
         if self.position + 1 >= self.tokens.len() {
             return false;
@@ -0,0 +1,332 @@
+//! LRU cache of prepared queries keyed by query text.
+//!
+//! Parsing and planning dominate the cost of short queries, so callers that
+//! see the same Cypher text over and over (the facade, the C ABI and the
+//! bindings built on it) go through [`prepare_cached`] instead of
+//! [`prepare`](crate::prepare). Plans are cached per exact query text;
+//! parameters are bound at execution time, so `$param` queries share one entry.
+//! Texts that differ only in literals compared against properties in `MATCH`
+//! and its `WHERE` also share one plan: the literals are lifted into slots and
+//! bound per text, like parameters.
+//!
+//! Compiled plans do not bake in which indexes exist (index seeks fall back to
+//! scans at runtime), but schema changes still clear the cache so the next
+//! prepare always sees the current catalog.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use crate::error::Result;
+use crate::query_api::{PreparedQuery, parameterize, prepare};
+
+/// Capacity of the process-wide cache used by [`prepare_cached`].
+pub const DEFAULT_PLAN_CACHE_CAPACITY: usize = 256;
+
+/// Counters reported by [`PlanCache::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlanCacheStats {
+    pub hits: u64,
+    /// New query texts served by the plan of a cached query that differs
+    /// only in literal values.
+    pub template_hits: u64,
+    pub misses: u64,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+/// A bounded, least-recently-used map from query text to prepared plan.
+///
+/// A capacity of zero disables caching; every lookup then prepares afresh.
+#[derive(Debug)]
+pub struct PlanCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    capacity: usize,
+    clock: u64,
+    generation: u64,
+    entries: HashMap<String, CacheEntry>,
+    /// Plans with literal slots, keyed by query shape.
+    templates: HashMap<String, CacheEntry>,
+    hits: u64,
+    template_hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    query: Arc<PreparedQuery>,
+    last_used: u64,
+}
+
+impl PlanCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(CacheState {
+                capacity,
+                clock: 0,
+                generation: 0,
+                entries: HashMap::new(),
+                templates: HashMap::new(),
+                hits: 0,
+                template_hits: 0,
+                misses: 0,
+            }),
+        }
+    }
+
+    /// Returns the cached plan for `cypher`, preparing and caching it on a miss.
+    ///
+    /// A text seen for the first time reuses the template of a cached query
+    /// that differs only in literal values, binding this text's values
+    /// instead of planning again. Errors are not cached; a query that fails
+    /// to prepare is re-parsed next time.
+    pub fn prepare(&self, cypher: &str) -> Result<Arc<PreparedQuery>> {
+        let generation = {
+            let mut state = self.lock();
+            state.clock += 1;
+            let now = state.clock;
+            if let Some(entry) = state.entries.get_mut(cypher) {
+                entry.last_used = now;
+                let query = Arc::clone(&entry.query);
+                state.hits += 1;
+                return Ok(query);
+            }
+            state.generation
+        };
+
+        // Parse and plan without holding the lock so one slow plan does not
+        // stall other threads' cache hits.
+        let parameterized = parameterize(cypher)?;
+        if let Some(parameterized) = &parameterized {
+            let template = {
+                let mut state = self.lock();
+                state.clock += 1;
+                let now = state.clock;
+                state.templates.get_mut(&parameterized.shape).map(|entry| {
+                    entry.last_used = now;
+                    Arc::clone(&entry.query)
+                })
+            };
+            if let Some(template) = template {
+                let query = Arc::new(template.with_literals(parameterized.literals.clone())?);
+                let mut state = self.lock();
+                state.template_hits += 1;
+                if state.generation == generation {
+                    state.insert(cypher, &query);
+                }
+                return Ok(query);
+            }
+        }
+
+        // The first text of a shape runs its own plan, which also reports
+        // compile-time errors the template would only show as null.
+        let query = Arc::new(prepare(cypher)?);
+        let template = parameterized.and_then(|parameterized| {
+            Some((parameterized.shape.clone(), parameterized.compile().ok()?))
+        });
+
+        let mut state = self.lock();
+        state.misses += 1;
+        if state.generation == generation {
+            state.insert(cypher, &query);
+            if let Some((shape, template)) = template {
+                state.insert_template(shape, template);
+            }
+        }
+        Ok(query)
+    }
+
+    /// Changes the capacity, evicting least-recently-used plans if it shrank.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.lock();
+        state.capacity = capacity;
+        while state.entries.len() > capacity {
+            state.evict_lru();
+        }
+        while state.templates.len() > capacity {
+            evict_lru(&mut state.templates);
+        }
+    }
+
+    /// Drops every cached plan. Plans being prepared concurrently are not cached.
+    pub fn invalidate(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.templates.clear();
+        state.generation += 1;
+    }
+
+    pub fn stats(&self) -> PlanCacheStats {
+        let state = self.lock();
+        PlanCacheStats {
+            hits: state.hits,
+            template_hits: state.template_hits,
+            misses: state.misses,
+            len: state.entries.len(),
+            capacity: state.capacity,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        // The state is a plain map; a panic mid-update cannot leave it inconsistent.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for PlanCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PLAN_CACHE_CAPACITY)
+    }
+}
+
+impl CacheState {
+    fn insert(&mut self, cypher: &str, query: &Arc<PreparedQuery>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(cypher) {
+            self.evict_lru();
+        }
+        self.clock += 1;
+        let now = self.clock;
+        self.entries.insert(
+            cypher.to_string(),
+            CacheEntry {
+                query: Arc::clone(query),
+                last_used: now,
+            },
+        );
+    }
+
+    fn insert_template(&mut self, shape: String, template: PreparedQuery) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.templates.len() >= self.capacity && !self.templates.contains_key(&shape) {
+            evict_lru(&mut self.templates);
+        }
+        self.clock += 1;
+        let now = self.clock;
+        self.templates.insert(
+            shape,
+            CacheEntry {
+                query: Arc::new(template),
+                last_used: now,
+            },
+        );
+    }
+
+    fn evict_lru(&mut self) {
+        evict_lru(&mut self.entries);
+    }
+}
+
+fn evict_lru(entries: &mut HashMap<String, CacheEntry>) {
+    let oldest = entries
+        .iter()
+        .min_by_key(|(_, entry)| entry.last_used)
+        .map(|(key, _)| key.clone());
+    if let Some(key) = oldest {
+        entries.remove(&key);
+    }
+}
+
+static GLOBAL_PLAN_CACHE: OnceLock<PlanCache> = OnceLock::new();
+
+/// The process-wide cache shared by every database handle.
+pub fn global_plan_cache() -> &'static PlanCache {
+    GLOBAL_PLAN_CACHE.get_or_init(PlanCache::default)
+}
+
+/// Prepares `cypher` through the process-wide plan cache.
+pub fn prepare_cached(cypher: &str) -> Result<Arc<PreparedQuery>> {
+    global_plan_cache().prepare(cypher)
+}
+
+/// Sets the capacity of the process-wide plan cache; `0` disables it.
+pub fn set_plan_cache_capacity(capacity: usize) {
+    global_plan_cache().set_capacity(capacity);
+}
+
+/// Clears the process-wide plan cache. Called on index and schema changes.
+pub fn invalidate_plan_cache() {
+    global_plan_cache().invalidate();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_text_hits_the_cache() {
+        let cache = PlanCache::new(4);
+        let first = cache.prepare("RETURN 1 AS x").unwrap();
+        let second = cache.prepare("RETURN 1 AS x").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            cache.stats(),
+            PlanCacheStats {
+                hits: 1,
+                template_hits: 0,
+                misses: 1,
+                len: 1,
+                capacity: 4
+            }
+        );
+    }
+
+    #[test]
+    fn least_recently_used_plan_is_evicted() {
+        let cache = PlanCache::new(2);
+        let a = cache.prepare("RETURN 1 AS a").unwrap();
+        cache.prepare("RETURN 2 AS b").unwrap();
+        cache.prepare("RETURN 1 AS a").unwrap();
+        cache.prepare("RETURN 3 AS c").unwrap();
+
+        assert_eq!(cache.stats().len, 2);
+        assert!(Arc::ptr_eq(&a, &cache.prepare("RETURN 1 AS a").unwrap()));
+        let misses = cache.stats().misses;
+        cache.prepare("RETURN 2 AS b").unwrap();
+        assert_eq!(cache.stats().misses, misses + 1);
+    }
+
+    #[test]
+    fn invalidate_and_zero_capacity_drop_plans() {
+        let cache = PlanCache::new(2);
+        let a = cache.prepare("RETURN 1 AS a").unwrap();
+        cache.invalidate();
+        assert_eq!(cache.stats().len, 0);
+        assert!(!Arc::ptr_eq(&a, &cache.prepare("RETURN 1 AS a").unwrap()));
+
+        cache.set_capacity(0);
+        cache.prepare("RETURN 2 AS b").unwrap();
+        assert_eq!(cache.stats().len, 0);
+    }
+
+    #[test]
+    fn texts_differing_in_literals_share_a_template() {
+        let cache = PlanCache::new(4);
+        cache
+            .prepare("MATCH (n:User {name: 'ada'}) WHERE n.age > 30 RETURN n")
+            .unwrap();
+        cache
+            .prepare("MATCH (n:User {name: 'bob'}) WHERE n.age > 40 RETURN n")
+            .unwrap();
+        // Another literal kind may type-check differently, so it plans anew.
+        cache
+            .prepare("MATCH (n:User {name: 'cy'}) WHERE n.age > 4.5 RETURN n")
+            .unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.template_hits, stats.misses, stats.len), (1, 2, 3));
+    }
+
+    #[test]
+    fn prepare_errors_are_not_cached() {
+        let cache = PlanCache::new(2);
+        assert!(cache.prepare("RETURN").is_err());
+        assert_eq!(cache.stats().len, 0);
+    }
+}
@@ -2,20 +2,25 @@ use crate::ast::{BinaryOperator, CallClause, Clause, Expression, Literal, Query}
 use crate::error::{Error, Result};
 use crate::executor::{Plan, Row, Value, execute_plan, execute_write};
 use nervusdb_api::GraphSnapshot;
-use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 mod aggregate_parse;
 mod ast_walk;
 mod binding_analysis;
 mod compile_core;
 mod explain;
+mod explain_indexes;
 mod foreach_compile;
 mod internal_alias;
+mod literal_slots;
 mod match_anchor;
 mod match_compile;
 mod merge_set;
+mod pagination;
+mod param_types;
 mod pattern_predicate;
 mod plan;
 mod plan_introspection;
@@ -23,47 +28,60 @@ mod plan_render;
 mod planner;
 mod prepare_entry;
 mod prepared_query_impl;
+mod profile;
 mod projection_alias;
 mod projection_compile;
+mod read_set;
+mod result_columns;
 mod return_with;
+mod subquery;
 mod type_validation;
 mod where_validation;
 mod write_compile;
 mod write_create_merge;
 mod write_validation;
 use aggregate_parse::parse_aggregate_function;
-use ast_walk::{extract_predicates, extract_variables_from_expr};
+use ast_walk::{extract_predicates, extract_text_predicates, extract_variables_from_expr};
 use binding_analysis::{
     extract_output_var_kinds, infer_expression_binding_kind, validate_match_pattern_bindings,
     variable_already_bound_error,
 };
 use compile_core::compile_m3_plan;
-use explain::strip_explain_prefix;
+use explain::{strip_explain_prefix, strip_periodic_commit_prefix, strip_profile_prefix};
 use foreach_compile::compile_foreach_plan;
 use internal_alias::{alloc_internal_path_alias, is_internal_path_alias};
+pub(crate) use literal_slots::parameterize;
 use match_anchor::{
     build_optional_unbind_aliases, first_relationship_is_bound, maybe_reanchor_pattern,
-    pattern_has_bound_relationship,
+    pattern_has_bound_relationship, reverse_pattern,
 };
 use match_compile::compile_match_plan;
 use merge_set::{compile_merge_set_items, extract_merge_pattern_vars};
 use pattern_predicate::ensure_no_pattern_predicate;
 use plan_introspection::plan_contains_write;
-use plan_render::render_plan;
+use plan_render::{render_plan, render_plan_annotated};
+use profile::OperatorStats;
 use projection_alias::{default_aggregate_alias, default_projection_alias};
 use projection_compile::{
     compile_order_by_items, compile_projection_aggregation, contains_aggregate_expression,
     rewrite_order_expression, validate_order_by_aggregate_semantics, validate_order_by_scope,
 };
 use return_with::{compile_return_plan, compile_with_plan};
+use subquery::CachedSubquery;
+pub(crate) use subquery::evaluate_subquery;
+pub(crate) use type_validation::is_supported_function_name as is_builtin_function;
 use type_validation::validate_expression_types;
 use where_validation::validate_where_expression_bindings;
 use write_compile::{
-    compile_delete_plan_v2, compile_remove_plan_v2, compile_set_plan_v2, compile_unwind_plan,
+    compile_delete_plan_v2, compile_load_csv_plan, compile_remove_plan_v2, compile_set_plan_v2,
+    compile_unwind_plan,
 };
 use write_create_merge::{compile_create_plan, compile_merge_plan};
 use write_validation::validate_create_property_vars;
 
+pub use pagination::Page;
+pub use read_set::{ReadSet, Scope};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum WriteSemantics {
     Default,
@@ -91,6 +109,17 @@ pub struct ExecuteOptions {
     pub max_collection_items: usize,
     pub soft_timeout_ms: u64,
     pub max_apply_rows_per_outer: usize,
+    /// Bytes `ORDER BY`, `DISTINCT` and aggregation may buffer per query;
+    /// `0` disables the budget.
+    pub max_memory_bytes: usize,
+    /// Whether `ORDER BY` and `DISTINCT` spill to disk past
+    /// `max_memory_bytes` instead of failing. Aggregation always fails.
+    pub spill_to_disk: bool,
+    /// Directory for spill files; the system temporary directory if unset.
+    pub spill_dir: Option<PathBuf>,
+    /// Whether chains of fixed-length hops over a node scan run as compiled
+    /// traversal programs instead of one operator per hop.
+    pub compiled_traversals: bool,
 }
 
 impl Default for ExecuteOptions {
@@ -100,6 +129,10 @@ impl Default for ExecuteOptions {
             max_collection_items: 200_000,
             soft_timeout_ms: 5_000,
             max_apply_rows_per_outer: 200_000,
+            max_memory_bytes: 256 * 1024 * 1024,
+            spill_to_disk: true,
+            spill_dir: None,
+            compiled_traversals: true,
         }
     }
 }
@@ -108,11 +141,30 @@ impl Default for ExecuteOptions {
 struct ExecutionRuntimeState {
     started_at: Option<Instant>,
     emitted_rows: usize,
+    /// Byte offset the next `USING PERIODIC COMMIT` batch starts at; `None`
+    /// once `LOAD CSV` reached the end of the file.
+    csv_resume_at: Option<u64>,
+    /// Values of uncorrelated `COUNT { }` / `CALL { }` subqueries.
+    subqueries: Vec<CachedSubquery>,
+    /// Literal slot values of the running plan.
+    literals: Arc<[Value]>,
+    /// Bytes buffering operators hold against `max_memory_bytes`.
+    memory_used: usize,
+}
+
+/// The slice of the CSV file one `USING PERIODIC COMMIT` batch loads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CsvBatch {
+    /// Byte offset of the batch's first record.
+    pub(crate) offset: u64,
+    pub(crate) rows: usize,
 }
 
 #[derive(Debug, Default)]
 struct ExecutionRuntime {
     state: Mutex<ExecutionRuntimeState>,
+    /// Per-operator counters, present only for `PROFILE` runs.
+    profile: Option<Mutex<HashMap<usize, OperatorStats>>>,
 }
 
 /// Query parameters for parameterized Cypher queries.
@@ -129,6 +181,7 @@ pub struct Params {
     inner: BTreeMap<String, Value>,
     execute_options: ExecuteOptions,
     runtime: Arc<ExecutionRuntime>,
+    csv_batch: Option<CsvBatch>,
 }
 
 impl Params {
@@ -156,6 +209,19 @@ impl Params {
         self.inner.get(name)
     }
 
+    /// Iterates over the parameters in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.inner.iter()
+    }
+
+    /// Hash of the parameter values, for keying cached results.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        format!("{:?}", self.inner).hash(&mut hasher);
+        hasher.finish()
+    }
+
     /// Returns execution options associated with this parameter bag.
     pub fn execute_options(&self) -> &ExecuteOptions {
         &self.execute_options
@@ -166,10 +232,119 @@ impl Params {
         self.execute_options = options;
     }
 
-    pub(crate) fn begin_execution(&self) {
+    /// Returns a copy of these parameters with a fresh runtime that records
+    /// per-operator statistics.
+    fn profiling(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            execute_options: self.execute_options.clone(),
+            runtime: Arc::new(ExecutionRuntime {
+                state: Mutex::default(),
+                profile: Some(Mutex::default()),
+            }),
+            csv_batch: self.csv_batch,
+        }
+    }
+
+    /// Returns a copy of these parameters, with a fresh runtime, that loads
+    /// only `batch` of the CSV file.
+    pub(super) fn csv_batch_of(&self, batch: CsvBatch) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            execute_options: self.execute_options.clone(),
+            runtime: Arc::default(),
+            csv_batch: Some(batch),
+        }
+    }
+
+    pub(crate) fn csv_batch(&self) -> Option<CsvBatch> {
+        self.csv_batch
+    }
+
+    pub(crate) fn set_csv_resume_at(&self, offset: Option<u64>) {
+        if let Ok(mut state) = self.runtime.state.lock() {
+            state.csv_resume_at = offset;
+        }
+    }
+
+    pub(super) fn csv_resume_at(&self) -> Option<u64> {
+        self.runtime
+            .state
+            .lock()
+            .ok()
+            .and_then(|state| state.csv_resume_at)
+    }
+
+    /// Starts the clock for opening an operator, when profiling.
+    pub(crate) fn profile_clock(&self) -> Option<Instant> {
+        self.runtime.profile.as_ref().map(|_| Instant::now())
+    }
+
+    pub(crate) fn record_operator_open(&self, key: usize, elapsed: Duration) {
+        if let Some(profile) = &self.runtime.profile
+            && let Ok(mut stats) = profile.lock()
+        {
+            let entry = stats.entry(key).or_default();
+            entry.invocations += 1;
+            entry.elapsed += elapsed;
+        }
+    }
+
+    pub(crate) fn record_operator_step(&self, key: usize, produced: bool, elapsed: Duration) {
+        if let Some(profile) = &self.runtime.profile
+            && let Ok(mut stats) = profile.lock()
+        {
+            let entry = stats.entry(key).or_default();
+            if produced {
+                entry.rows += 1;
+            }
+            entry.elapsed += elapsed;
+        }
+    }
+
+    fn operator_stats(&self) -> HashMap<usize, OperatorStats> {
+        self.runtime
+            .profile
+            .as_ref()
+            .and_then(|profile| profile.lock().ok().map(|stats| stats.clone()))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn begin_execution(&self, literals: &Arc<[Value]>) {
         if let Ok(mut state) = self.runtime.state.lock() {
             state.started_at = Some(Instant::now());
             state.emitted_rows = 0;
+            state.subqueries.clear();
+            state.literals = Arc::clone(literals);
+            state.memory_used = 0;
+        }
+    }
+
+    /// Value of `$name`, or of a literal slot of the running plan.
+    pub(crate) fn value(&self, name: &str) -> Option<Value> {
+        let Some(slot) = literal_slots::slot_index(name) else {
+            return self.inner.get(name).cloned();
+        };
+        let state = self.runtime.state.lock().ok()?;
+        state.literals.get(slot).cloned()
+    }
+
+    fn cached_subquery(
+        &self,
+        key: (usize, usize),
+        subquery: &crate::ast::SubqueryExpression,
+    ) -> Option<Value> {
+        let state = self.runtime.state.lock().ok()?;
+        state
+            .subqueries
+            .iter()
+            .find(|cached| cached.key == key && cached.subquery == *subquery)
+            .map(|cached| cached.value.clone())
+    }
+
+    fn cache_subquery(&self, cached: CachedSubquery) {
+        if let Ok(mut state) = self.runtime.state.lock() {
+            state.subqueries.push(cached);
         }
     }
 
@@ -256,6 +431,36 @@ impl Params {
         Ok(())
     }
 
+    /// Adds `bytes` to the memory buffering operators hold, unless that
+    /// would exceed `max_memory_bytes`; then returns the total it would reach.
+    pub(crate) fn reserve_memory(&self, bytes: usize) -> std::result::Result<(), usize> {
+        let limit = self.execute_options.max_memory_bytes;
+        let Ok(mut state) = self.runtime.state.lock() else {
+            return Ok(());
+        };
+        let observed = state.memory_used.saturating_add(bytes);
+        if limit != 0 && observed > limit {
+            return Err(observed);
+        }
+        state.memory_used = observed;
+        Ok(())
+    }
+
+    pub(crate) fn release_memory(&self, bytes: usize) {
+        if let Ok(mut state) = self.runtime.state.lock() {
+            state.memory_used = state.memory_used.saturating_sub(bytes);
+        }
+    }
+
+    pub(crate) fn memory_limit_exceeded(&self, stage: &str, observed: usize) -> Error {
+        Error::resource_limit_exceeded(
+            crate::error::ResourceLimitKind::MemoryBytes,
+            self.execute_options.max_memory_bytes,
+            observed,
+            stage,
+        )
+    }
+
     pub(crate) fn check_apply_rows_per_outer(&self, stage: &str, observed: usize) -> Result<()> {
         let limit = self.execute_options.max_apply_rows_per_outer;
         if observed > limit {
@@ -278,6 +483,9 @@ impl Params {
 pub struct PreparedQuery {
     plan: Plan,
     explain: Option<String>,
+    profile: bool,
+    /// Rows per transaction for `USING PERIODIC COMMIT`.
+    periodic_commit: Option<usize>,
     write: WriteSemantics,
     merge_on_create_items: Vec<(String, String, Expression)>,
     merge_on_create_map_items: Vec<(String, Expression, bool)>,
@@ -285,6 +493,10 @@ pub struct PreparedQuery {
     merge_on_match_map_items: Vec<(String, Expression, bool)>,
     merge_on_create_labels: Vec<(String, Vec<String>)>,
     merge_on_match_labels: Vec<(String, Vec<String>)>,
+    /// Values of the `#N` literal slots of a plan-cache template.
+    literals: Arc<[Value]>,
+    /// Parameters whose values must fit how the query uses them.
+    param_uses: Arc<[(String, param_types::ParamUse)]>,
 }
 
 /// Parses and prepares a Cypher query for execution.
@@ -298,6 +510,8 @@ pub struct PreparedQuery {
 /// - `CREATE (a)-[:1]->(b)` - Create edges
 /// - `MATCH (n)-[:1]->(m) DELETE n` / `DETACH DELETE n` - Delete nodes/edges
 /// - `EXPLAIN <query>` - Show compiled plan (no execution)
+/// - `PROFILE <query>` / `EXPLAIN ANALYZE <query>` - Run a read query and show
+///   the plan annotated with per-operator rows, invocations and time
 ///
 /// Returns an error for unsupported Cypher constructs.
 pub fn prepare(cypher: &str) -> Result<PreparedQuery> {
@@ -114,6 +114,29 @@ pub(super) fn parse_aggregate_function(
                 call.args[1].clone(),
             )))
         }
+        "countdistinctapprox" | "hllsketch" => {
+            if !(1..=2).contains(&call.args.len()) {
+                return Err(Error::Other(format!(
+                    "{} takes 1 or 2 arguments",
+                    name.to_uppercase()
+                )));
+            }
+            let (arg, _) = unwrap_distinct_argument(&call.args[0]);
+            let precision = call.args.get(1).cloned();
+            Ok(Some(if name == "hllsketch" {
+                crate::ast::AggregateFunction::HllSketch(arg, precision)
+            } else {
+                crate::ast::AggregateFunction::CountDistinctApprox(arg, precision)
+            }))
+        }
+        "hllmerge" => {
+            if call.args.len() != 1 {
+                return Err(Error::Other("HLLMERGE takes exactly 1 argument".into()));
+            }
+            Ok(Some(crate::ast::AggregateFunction::HllMerge(
+                call.args[0].clone(),
+            )))
+        }
         _ => Ok(None),
     }
 }
@@ -28,6 +28,25 @@ pub(super) fn extract_predicates(
     }
 }
 
+/// Collects `n.prop CONTAINS <literal|$param>` conjuncts, first one per variable.
+pub(super) fn extract_text_predicates(
+    expr: &Expression,
+    map: &mut BTreeMap<String, (String, Expression)>,
+) {
+    if let Expression::Binary(bin) = expr {
+        if matches!(bin.operator, BinaryOperator::And) {
+            extract_text_predicates(&bin.left, map);
+            extract_text_predicates(&bin.right, map);
+        } else if matches!(bin.operator, BinaryOperator::Contains)
+            && let Expression::PropertyAccess(pa) = &bin.left
+            && matches!(bin.right, Expression::Literal(_) | Expression::Parameter(_))
+        {
+            map.entry(pa.variable.clone())
+                .or_insert_with(|| (pa.property.clone(), bin.right.clone()));
+        }
+    }
+}
+
 pub(super) fn extract_variables_from_expr(expr: &Expression, vars: &mut HashSet<String>) {
     match expr {
         Expression::Variable(v) => {
@@ -89,7 +108,7 @@ pub(super) fn extract_variables_from_expr(expr: &Expression, vars: &mut HashSet<
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_predicates, extract_variables_from_expr};
+    use super::{extract_predicates, extract_text_predicates, extract_variables_from_expr};
     use crate::ast::{
         BinaryExpression, BinaryOperator, Expression, FunctionCall, Literal, PropertyAccess,
     };
@@ -151,4 +170,30 @@ mod tests {
         assert!(n_map.contains_key("name"));
         assert!(n_map.contains_key("age"));
     }
+
+    #[test]
+    fn extract_text_predicates_collects_contains_conjuncts() {
+        let contains = |variable: &str, needle: &str| {
+            Expression::Binary(Box::new(BinaryExpression {
+                left: Expression::PropertyAccess(PropertyAccess {
+                    variable: variable.to_string(),
+                    property: "body".to_string(),
+                }),
+                operator: BinaryOperator::Contains,
+                right: Expression::Literal(Literal::String(needle.to_string())),
+            }))
+        };
+        let expr = Expression::Binary(Box::new(BinaryExpression {
+            left: contains("d", "needle"),
+            operator: BinaryOperator::And,
+            right: contains("d", "other"),
+        }));
+
+        let mut map = BTreeMap::new();
+        extract_text_predicates(&expr, &mut map);
+
+        let (field, needle) = map.get("d").expect("d predicate");
+        assert_eq!(field, "body");
+        assert!(matches!(needle, Expression::Literal(Literal::String(s)) if s == "needle"));
+    }
 }
@@ -318,7 +318,7 @@ pub(super) fn extract_output_var_kinds(plan: &Plan, vars: &mut BTreeMap<String,
             }
             vars.retain(|name, _| output_names.contains(name));
         }
-        Plan::Unwind { input, alias, .. } => {
+        Plan::Unwind { input, alias, .. } | Plan::LoadCsv { input, alias, .. } => {
             extract_output_var_kinds(input, vars);
             vars.insert(alias.clone(), BindingKind::Unknown);
         }
@@ -326,7 +326,7 @@ pub(super) fn extract_output_var_kinds(plan: &Plan, vars: &mut BTreeMap<String,
             extract_output_var_kinds(left, vars);
             extract_output_var_kinds(right, vars);
         }
-        Plan::CartesianProduct { left, right } => {
+        Plan::CartesianProduct { left, right } | Plan::HashJoin { left, right, .. } => {
             extract_output_var_kinds(left, vars);
             extract_output_var_kinds(right, vars);
         }
@@ -358,11 +358,23 @@ pub(super) fn extract_output_var_kinds(plan: &Plan, vars: &mut BTreeMap<String,
         }
         Plan::IndexSeek {
             alias, fallback, ..
+        }
+        | Plan::TextIndexSeek {
+            alias, fallback, ..
+        }
+        | Plan::CompositeIndexSeek {
+            alias, fallback, ..
         } => {
             extract_output_var_kinds(fallback, vars);
             merge_binding_kind(vars, alias.clone(), BindingKind::Node);
         }
         Plan::Foreach { input, .. } => extract_output_var_kinds(input, vars),
+        // Every alternative binds the same variables.
+        Plan::CostChoice { alternatives } => {
+            if let Some(first) = alternatives.first() {
+                extract_output_var_kinds(first, vars);
+            }
+        }
         Plan::Values { rows } => {
             for row in rows {
                 for (name, value) in row.columns() {
@@ -435,6 +447,7 @@ mod tests {
             optional: false,
             optional_unbind: vec![],
             path_alias: None,
+            program: None,
         };
 
         let mut vars = BTreeMap::new();
@@ -1,11 +1,13 @@
 use super::{
     BTreeMap, BTreeSet, BindingKind, CallClause, Clause, Error, Expression, Plan, Query, Result,
     VecDeque, WriteSemantics, compile_create_plan, compile_delete_plan_v2, compile_foreach_plan,
-    compile_match_plan, compile_merge_plan, compile_merge_set_items, compile_remove_plan_v2,
-    compile_return_plan, compile_set_plan_v2, compile_unwind_plan, compile_with_plan,
-    contains_aggregate_expression, extract_merge_pattern_vars, extract_output_var_kinds,
-    extract_predicates, validate_expression_types, validate_where_expression_bindings,
+    compile_load_csv_plan, compile_match_plan, compile_merge_plan, compile_merge_set_items,
+    compile_remove_plan_v2, compile_return_plan, compile_set_plan_v2, compile_unwind_plan,
+    compile_with_plan, contains_aggregate_expression, extract_merge_pattern_vars,
+    extract_output_var_kinds, extract_predicates, extract_text_predicates,
+    validate_expression_types, validate_where_expression_bindings,
 };
+use crate::executor::custom_procedure_signature;
 
 pub(crate) struct CompiledQuery {
     pub(crate) plan: Plan,
@@ -44,8 +46,10 @@ pub(crate) fn compile_m3_plan(
             Clause::Match(m) => {
                 // Check ahead for WHERE to optimize immediately
                 let mut predicates = BTreeMap::new();
+                let mut text_predicates = BTreeMap::new();
                 if let Some(Clause::Where(w)) = clauses.peek() {
                     extract_predicates(&w.expression, &mut predicates);
+                    extract_text_predicates(&w.expression, &mut text_predicates);
                 }
 
                 let previous_plan = plan.clone().unwrap_or(Plan::ReturnOne);
@@ -65,6 +69,7 @@ pub(crate) fn compile_m3_plan(
                     plan,
                     compiled_match,
                     &predicates,
+                    &text_predicates,
                     &mut next_anon_id,
                 )?);
 
@@ -197,6 +202,23 @@ pub(crate) fn compile_m3_plan(
                         }
                     }
 
+                    let proc_name = proc_call.name.join(".");
+                    if let Some(signature) = custom_procedure_signature(&proc_name) {
+                        if signature.inputs.len() != proc_call.arguments.len() {
+                            return Err(Error::Other(
+                                "syntax error: InvalidNumberOfArguments".to_string(),
+                            ));
+                        }
+                        if let Some((field, _)) = yields
+                            .iter()
+                            .find(|(field, _)| !signature.outputs.contains(field))
+                        {
+                            return Err(Error::Other(format!(
+                                "procedure {proc_name} has no output '{field}'"
+                            )));
+                        }
+                    }
+
                     if yield_all {
                         // openCypher allows `YIELD *` only for standalone CALL.
                         if clauses.peek().is_some() {
@@ -289,6 +311,10 @@ pub(crate) fn compile_m3_plan(
                 let input = plan.unwrap_or(Plan::ReturnOne);
                 plan = Some(compile_unwind_plan(input, u.clone()));
             }
+            Clause::LoadCsv(l) => {
+                let input = plan.unwrap_or(Plan::ReturnOne);
+                plan = Some(compile_load_csv_plan(input, l.clone()));
+            }
             Clause::Union(u) => {
                 // UNION logic: current plan is the "left" side; the clause's nested query is the "right" side
                 let left_plan =
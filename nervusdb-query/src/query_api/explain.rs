@@ -1,11 +1,35 @@
 pub(super) fn strip_explain_prefix(input: &str) -> Option<&str> {
+    strip_keyword(input, "EXPLAIN")
+}
+
+/// Accepts both `PROFILE <query>` and `EXPLAIN ANALYZE <query>`.
+pub(super) fn strip_profile_prefix(input: &str) -> Option<&str> {
+    strip_keyword(input, "PROFILE")
+        .or_else(|| strip_explain_prefix(input).and_then(|rest| strip_keyword(rest, "ANALYZE")))
+}
+
+/// Accepts `USING PERIODIC COMMIT [rows] <query>`, returning the row count
+/// when one was given.
+pub(super) fn strip_periodic_commit_prefix(input: &str) -> Option<(Option<usize>, &str)> {
+    let rest = strip_keyword(input, "USING")?;
+    let rest = strip_keyword(rest, "PERIODIC")?;
+    let rest = strip_keyword(rest, "COMMIT")?;
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return Some((None, rest));
+    }
+    let rows = rest[..digits].parse().ok()?;
+    Some((Some(rows), rest[digits..].trim_start()))
+}
+
+fn strip_keyword<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
     let trimmed = input.trim_start();
-    let prefix_len = "EXPLAIN".len();
+    let prefix_len = keyword.len();
     if trimmed.len() < prefix_len {
         return None;
     }
     let head = trimmed.get(..prefix_len)?;
-    if !head.eq_ignore_ascii_case("EXPLAIN") {
+    if !head.eq_ignore_ascii_case(keyword) {
         return None;
     }
     let tail = trimmed.get(prefix_len..)?;
@@ -20,7 +44,7 @@ pub(super) fn strip_explain_prefix(input: &str) -> Option<&str> {
 
 #[cfg(test)]
 mod tests {
-    use super::strip_explain_prefix;
+    use super::{strip_explain_prefix, strip_periodic_commit_prefix, strip_profile_prefix};
 
     #[test]
     fn accepts_explain_case_insensitive() {
@@ -42,4 +66,30 @@ mod tests {
         assert_eq!(strip_explain_prefix("EXPLAIN"), Some(""));
         assert_eq!(strip_explain_prefix("   EXPLAIN   "), Some(""));
     }
+
+    #[test]
+    fn accepts_profile_and_explain_analyze() {
+        assert_eq!(strip_profile_prefix("PROFILE RETURN 1"), Some("RETURN 1"));
+        assert_eq!(
+            strip_profile_prefix("explain analyze MATCH (n) RETURN n"),
+            Some("MATCH (n) RETURN n")
+        );
+        assert_eq!(strip_profile_prefix("EXPLAIN RETURN 1"), None);
+        assert_eq!(strip_profile_prefix("PROFILED RETURN 1"), None);
+        assert_eq!(strip_profile_prefix("EXPLAIN ANALYZED RETURN 1"), None);
+    }
+
+    #[test]
+    fn accepts_periodic_commit_with_optional_rows() {
+        assert_eq!(
+            strip_periodic_commit_prefix("USING PERIODIC COMMIT 500 LOAD CSV FROM 'x' AS r"),
+            Some((Some(500), "LOAD CSV FROM 'x' AS r"))
+        );
+        assert_eq!(
+            strip_periodic_commit_prefix("using periodic commit LOAD CSV FROM 'x' AS r"),
+            Some((None, "LOAD CSV FROM 'x' AS r"))
+        );
+        assert_eq!(strip_periodic_commit_prefix("USING PERIODIC LOAD"), None);
+        assert_eq!(strip_periodic_commit_prefix("LOAD CSV FROM 'x' AS r"), None);
+    }
 }
@@ -0,0 +1,327 @@
+//! Index selection report for `EXPLAIN`.
+//!
+//! The planner wraps a pattern's start scan in index seeks for every
+//! equality (or `CONTAINS`) predicate; whether a seek or its fallback scan
+//! runs is only decided against a snapshot's indexes. Executing `EXPLAIN`
+//! resolves that choice and says why each node pattern is or isn't served by
+//! an index.
+
+use super::{
+    BTreeMap, BinaryOperator, Expression, GraphSnapshot, Plan, PreparedQuery, Row, Value,
+    render_plan_annotated,
+};
+use crate::ast::{PropertyAccess, UnaryOperator};
+use nervusdb_api::{IndexKind, Schema};
+
+/// What actually runs for one node pattern, and why.
+struct Selection {
+    operator: &'static str,
+    reasons: Vec<String>,
+}
+
+/// A property a `WHERE` predicate reads, and how it is used.
+struct PropertyUse {
+    property: String,
+    /// `None` for `prop = <literal|$param>`, which an index can answer.
+    non_sargable: Option<&'static str>,
+}
+
+/// Executes an `EXPLAIN` query: one row with the plan (`plan`), each scan
+/// or seek suffixed with the operator that runs and why.
+pub(super) fn execute_explain<S: GraphSnapshot>(query: &PreparedQuery, snapshot: &S) -> Row {
+    let schema = snapshot.schema();
+    let mut filters: BTreeMap<String, Vec<PropertyUse>> = BTreeMap::new();
+    let plan = render_plan_annotated(&query.plan, &mut |node, _| {
+        // Filters print above the scans they apply to, so they are seen first.
+        if let Plan::Filter { predicate, .. } = node {
+            collect_property_uses(predicate, None, &mut filters);
+        }
+        match select(snapshot, &schema, &filters, node, Vec::new(), Vec::new()) {
+            Some(selection) => format!(
+                " [{}: {}]",
+                selection.operator,
+                selection.reasons.join("; ")
+            ),
+            None => String::new(),
+        }
+    });
+
+    Row::default().with("plan", Value::String(plan))
+}
+
+/// Resolves the seek chain rooted at `node` down to the operator that runs.
+/// `missing` holds why the seeks already passed over were skipped and
+/// `seek_fields` the properties they covered.
+fn select<S: GraphSnapshot>(
+    snapshot: &S,
+    schema: &Schema,
+    filters: &BTreeMap<String, Vec<PropertyUse>>,
+    node: &Plan,
+    mut missing: Vec<String>,
+    mut seek_fields: Vec<String>,
+) -> Option<Selection> {
+    match node {
+        Plan::NodeScan { alias, label, .. } => Some(scan(
+            snapshot,
+            schema,
+            filters,
+            alias,
+            label.as_deref(),
+            missing,
+            &seek_fields,
+        )),
+        Plan::IndexSeek {
+            alias,
+            label,
+            field,
+            fallback,
+            ..
+        } => {
+            if snapshot.has_index(label, field) {
+                let index = equality_index_name(schema, label, field);
+                return Some(Selection {
+                    operator: "IndexSeek",
+                    reasons: vec![format!("equality on {alias}.{field} uses index {index}")],
+                });
+            }
+            missing.push(format!("no index on :{label}({field})"));
+            seek_fields.push(field.clone());
+            select(snapshot, schema, filters, fallback, missing, seek_fields)
+        }
+        Plan::CompositeIndexSeek {
+            alias,
+            label,
+            predicates,
+            fallback,
+        } => {
+            let fields: Vec<String> = predicates.iter().map(|(field, _)| field.clone()).collect();
+            if snapshot.has_composite_index(label, &fields) {
+                let index = schema
+                    .indexes
+                    .iter()
+                    .find(|index| {
+                        index.kind == IndexKind::Composite
+                            && index.label == *label
+                            && index
+                                .properties
+                                .first()
+                                .is_some_and(|first| fields.contains(first))
+                    })
+                    .map(|index| index.name.clone())
+                    .unwrap_or_else(|| format!("{label}({})", fields.join(", ")));
+                let covered: Vec<String> = fields
+                    .iter()
+                    .map(|field| format!("{alias}.{field}"))
+                    .collect();
+                return Some(Selection {
+                    operator: "CompositeIndexSeek",
+                    reasons: vec![format!(
+                        "equality on {} uses composite index {index}",
+                        covered.join(", ")
+                    )],
+                });
+            }
+            missing.push(format!(
+                "no composite index on :{label}({})",
+                fields.join(", ")
+            ));
+            seek_fields.extend(fields);
+            select(snapshot, schema, filters, fallback, missing, seek_fields)
+        }
+        Plan::TextIndexSeek {
+            alias,
+            label,
+            field,
+            fallback,
+            ..
+        } => {
+            let text_index = schema.indexes.iter().find(|index| {
+                index.kind == IndexKind::Text
+                    && index.label == *label
+                    && index.properties.len() == 1
+                    && index.properties[0] == *field
+            });
+            if let Some(index) = text_index {
+                return Some(Selection {
+                    operator: "TextIndexSeek",
+                    reasons: vec![format!(
+                        "CONTAINS on {alias}.{field} uses text index {}",
+                        index.name
+                    )],
+                });
+            }
+            missing.push(format!("no text index on :{label}({field})"));
+            seek_fields.push(field.clone());
+            select(snapshot, schema, filters, fallback, missing, seek_fields)
+        }
+        _ => None,
+    }
+}
+
+fn scan<S: GraphSnapshot>(
+    snapshot: &S,
+    schema: &Schema,
+    filters: &BTreeMap<String, Vec<PropertyUse>>,
+    alias: &str,
+    label: Option<&str>,
+    mut reasons: Vec<String>,
+    seek_fields: &[String],
+) -> Selection {
+    let uses = filters.get(alias).map(Vec::as_slice).unwrap_or_default();
+    let Some(label) = label else {
+        reasons.push("node pattern has no label; indexes are per label".to_string());
+        return Selection {
+            operator: "NodeScan",
+            reasons,
+        };
+    };
+
+    for property_use in uses {
+        let property = &property_use.property;
+        if seek_fields.contains(property) {
+            continue;
+        }
+        let reason = match property_use.non_sargable {
+            Some(shape) => {
+                let mut reason = format!("{shape} on {alias}.{property} is not sargable");
+                if snapshot.has_index(label, property) {
+                    let index = equality_index_name(schema, label, property);
+                    reason.push_str(&format!("; index {index} only answers equality"));
+                }
+                reason
+            }
+            None => format!(
+                "equality on {alias}.{property} is applied after joining earlier bindings, so it cannot seek"
+            ),
+        };
+        if !reasons.contains(&reason) {
+            reasons.push(reason);
+        }
+    }
+    if uses.is_empty() && seek_fields.is_empty() {
+        reasons.push(format!("no property predicate on {alias}"));
+    }
+
+    let estimated_rows = snapshot
+        .resolve_label_id(label)
+        .map(|id| snapshot.node_count(Some(id)))
+        .filter(|&rows| rows > 0);
+    match estimated_rows {
+        Some(rows) => reasons.push(format!("scans about {rows} rows")),
+        None if schema.labels.iter().any(|known| known == label) => reasons.push(format!(
+            "statistics missing for :{label} until the next compaction; scan cost is unknown"
+        )),
+        None => {}
+    }
+    Selection {
+        operator: "NodeScan",
+        reasons,
+    }
+}
+
+/// The index `snapshot.has_index` found: the single-property index, else a
+/// unique constraint's index led by `field`.
+fn equality_index_name(schema: &Schema, label: &str, field: &str) -> String {
+    let on_label = || schema.indexes.iter().filter(|index| index.label == label);
+    on_label()
+        .find(|index| index.kind == IndexKind::BTree && index.properties == [field])
+        .or_else(|| {
+            on_label().find(|index| {
+                index.unique && index.properties.first().is_some_and(|first| first == field)
+            })
+        })
+        .map(|index| index.name.clone())
+        .unwrap_or_else(|| format!("{label}.{field}"))
+}
+
+/// Records every `alias.property` `expr` reads. `context` describes the
+/// enclosing construct that already makes the read non-sargable.
+fn collect_property_uses(
+    expr: &Expression,
+    context: Option<&'static str>,
+    out: &mut BTreeMap<String, Vec<PropertyUse>>,
+) {
+    match expr {
+        Expression::PropertyAccess(pa) => {
+            record(out, pa, Some(context.unwrap_or("boolean property test")));
+        }
+        Expression::Binary(binary) => {
+            let shape = match binary.operator {
+                BinaryOperator::And => {
+                    collect_property_uses(&binary.left, context, out);
+                    collect_property_uses(&binary.right, context, out);
+                    return;
+                }
+                BinaryOperator::Or | BinaryOperator::Xor => {
+                    let context = Some(context.unwrap_or("OR branch"));
+                    collect_property_uses(&binary.left, context, out);
+                    collect_property_uses(&binary.right, context, out);
+                    return;
+                }
+                BinaryOperator::Equals => "comparison with a non-constant value",
+                BinaryOperator::NotEquals
+                | BinaryOperator::LessThan
+                | BinaryOperator::LessEqual
+                | BinaryOperator::GreaterThan
+                | BinaryOperator::GreaterEqual => "range comparison",
+                BinaryOperator::In => "IN list",
+                BinaryOperator::StartsWith | BinaryOperator::EndsWith => "prefix/suffix match",
+                BinaryOperator::Contains => "CONTAINS",
+                BinaryOperator::RegexMatch => "regular expression match",
+                BinaryOperator::IsNull | BinaryOperator::IsNotNull => "null check",
+                BinaryOperator::HasLabel => return,
+                BinaryOperator::Add
+                | BinaryOperator::Subtract
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo
+                | BinaryOperator::Power => "arithmetic expression",
+            };
+            let constant = |side: &Expression| {
+                matches!(side, Expression::Literal(_) | Expression::Parameter(_))
+            };
+            for (side, other) in [(&binary.left, &binary.right), (&binary.right, &binary.left)] {
+                match side {
+                    Expression::PropertyAccess(pa) => {
+                        let non_sargable = if context.is_none()
+                            && matches!(binary.operator, BinaryOperator::Equals)
+                            && constant(other)
+                        {
+                            None
+                        } else {
+                            Some(context.unwrap_or(shape))
+                        };
+                        record(out, pa, non_sargable);
+                    }
+                    _ => collect_property_uses(side, context, out),
+                }
+            }
+        }
+        Expression::Unary(unary) => {
+            let shape = match unary.operator {
+                UnaryOperator::Not => "negation",
+                UnaryOperator::Negate => "arithmetic expression",
+            };
+            collect_property_uses(&unary.operand, Some(context.unwrap_or(shape)), out);
+        }
+        Expression::FunctionCall(call) => {
+            for arg in &call.args {
+                collect_property_uses(arg, Some(context.unwrap_or("function call")), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn record(
+    out: &mut BTreeMap<String, Vec<PropertyUse>>,
+    pa: &PropertyAccess,
+    non_sargable: Option<&'static str>,
+) {
+    out.entry(pa.variable.clone())
+        .or_default()
+        .push(PropertyUse {
+            property: pa.property.clone(),
+            non_sargable,
+        });
+}
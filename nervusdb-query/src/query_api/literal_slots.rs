@@ -0,0 +1,162 @@
+//! Literal auto-parameterization for plan reuse.
+//!
+//! `MATCH (n:User {name: 'ada'})` and `MATCH (n:User {name: 'bob'})` differ
+//! only in a literal, yet each text would be parsed and planned on its own.
+//! [`parameterize`] lifts literals compared against properties in `MATCH`
+//! patterns and the `WHERE` that follows them into slots named `#0`, `#1`, ...
+//! (names `$params` cannot take), so the plan cache can compile one template
+//! per query shape and bind each text's values at execution.
+
+use super::{Error, PreparedQuery, Result};
+use crate::ast::{BinaryOperator, Clause, Expression, Literal, PathElement, PropertyMap, Query};
+use crate::executor::Value;
+use crate::parser::MergeSubclauses;
+
+const SLOT_PREFIX: char = '#';
+
+/// A query with its literals lifted into slots.
+pub(crate) struct Parameterized {
+    /// Identifies the template: the parameterized query plus the kind of
+    /// every literal, since compile-time type checks depend on those.
+    pub(crate) shape: String,
+    pub(crate) literals: Vec<Value>,
+    query: Query,
+    merge_subclauses: Vec<MergeSubclauses>,
+}
+
+impl Parameterized {
+    /// Compiles the template, whose slots are bound by
+    /// [`PreparedQuery::with_literals`].
+    pub(crate) fn compile(self) -> Result<PreparedQuery> {
+        let mut template = super::prepare_entry::build(self.query, self.merge_subclauses)?;
+        template.literals = self.literals.into();
+        Ok(template)
+    }
+}
+
+/// Parses `cypher` and lifts its literals into slots. Returns `None` for
+/// queries with nothing to lift and for `EXPLAIN`, `PROFILE` and
+/// `USING PERIODIC COMMIT` queries, which are cached by text only.
+pub(crate) fn parameterize(cypher: &str) -> Result<Option<Parameterized>> {
+    if super::strip_profile_prefix(cypher).is_some()
+        || super::strip_explain_prefix(cypher).is_some()
+        || super::strip_periodic_commit_prefix(cypher).is_some()
+    {
+        return Ok(None);
+    }
+    let (mut query, merge_subclauses) = crate::parser::Parser::parse_with_merge_subclauses(cypher)?;
+    let mut literals = Vec::new();
+    let mut after_match = false;
+    for clause in &mut query.clauses {
+        match clause {
+            Clause::Match(m) => {
+                for pattern in &mut m.patterns {
+                    for element in &mut pattern.elements {
+                        let properties = match element {
+                            PathElement::Node(node) => &mut node.properties,
+                            PathElement::Relationship(rel) => &mut rel.properties,
+                        };
+                        if let Some(map) = properties {
+                            lift_property_map(map, &mut literals);
+                        }
+                    }
+                }
+                after_match = true;
+                continue;
+            }
+            Clause::Where(w) if after_match => lift_predicates(&mut w.expression, &mut literals),
+            _ => {}
+        }
+        after_match = false;
+    }
+    if literals.is_empty() {
+        return Ok(None);
+    }
+    let kinds: Vec<&str> = literals.iter().map(value_kind).collect();
+    let shape = format!("{query:?}|{merge_subclauses:?}|{kinds:?}");
+    Ok(Some(Parameterized {
+        shape,
+        literals,
+        query,
+        merge_subclauses,
+    }))
+}
+
+/// Slot index of a `#N` parameter name.
+pub(crate) fn slot_index(name: &str) -> Option<usize> {
+    name.strip_prefix(SLOT_PREFIX)?.parse().ok()
+}
+
+fn lift_property_map(map: &mut PropertyMap, literals: &mut Vec<Value>) {
+    for pair in &mut map.properties {
+        lift(&mut pair.value, literals);
+    }
+}
+
+fn lift_predicates(expr: &mut Expression, literals: &mut Vec<Value>) {
+    let Expression::Binary(bin) = expr else {
+        return;
+    };
+    match bin.operator {
+        BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor => {
+            lift_predicates(&mut bin.left, literals);
+            lift_predicates(&mut bin.right, literals);
+        }
+        BinaryOperator::Equals
+        | BinaryOperator::NotEquals
+        | BinaryOperator::LessThan
+        | BinaryOperator::LessEqual
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::GreaterEqual
+        | BinaryOperator::StartsWith
+        | BinaryOperator::EndsWith
+        | BinaryOperator::Contains
+        | BinaryOperator::RegexMatch => {
+            if matches!(bin.left, Expression::PropertyAccess(_)) {
+                lift(&mut bin.right, literals);
+            } else if matches!(bin.right, Expression::PropertyAccess(_)) {
+                lift(&mut bin.left, literals);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces a non-null scalar literal with the next slot.
+fn lift(expr: &mut Expression, literals: &mut Vec<Value>) {
+    let value = match expr {
+        Expression::Literal(Literal::String(s)) => Value::String(std::mem::take(s)),
+        Expression::Literal(Literal::Integer(n)) => Value::Int(*n),
+        Expression::Literal(Literal::Float(n)) => Value::Float(*n),
+        Expression::Literal(Literal::Boolean(b)) => Value::Bool(*b),
+        _ => return,
+    };
+    *expr = Expression::Parameter(format!("{SLOT_PREFIX}{}", literals.len()));
+    literals.push(value);
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Int(_) => "int",
+        Value::Float(_) => "float",
+        Value::Bool(_) => "bool",
+        _ => "other",
+    }
+}
+
+impl PreparedQuery {
+    /// A copy of this template with its slots bound to `literals`.
+    pub(crate) fn with_literals(&self, literals: Vec<Value>) -> Result<Self> {
+        if literals.len() != self.literals.len() {
+            return Err(Error::Other(format!(
+                "plan template has {} literal slots, got {}",
+                self.literals.len(),
+                literals.len()
+            )));
+        }
+        let mut bound = self.clone();
+        bound.literals = literals.into();
+        Ok(bound)
+    }
+}
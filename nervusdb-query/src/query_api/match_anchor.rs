@@ -104,6 +104,11 @@ pub(super) fn maybe_reanchor_pattern(
         return pattern;
     }
 
+    reverse_pattern(pattern)
+}
+
+/// Reverses a Node-(Rel-Node)* chain, flipping each relationship direction.
+pub(super) fn reverse_pattern(pattern: crate::ast::Pattern) -> crate::ast::Pattern {
     let mut reversed_elements = Vec::with_capacity(pattern.elements.len());
     for element in pattern.elements.into_iter().rev() {
         match element {
@@ -1,14 +1,65 @@
 use super::{
     BTreeMap, BTreeSet, BindingKind, Error, Expression, Plan, Result, alloc_internal_path_alias,
     build_optional_unbind_aliases, extract_output_var_kinds, first_relationship_is_bound,
-    maybe_reanchor_pattern, pattern_has_bound_relationship, validate_match_pattern_bindings,
+    maybe_reanchor_pattern, pattern_has_bound_relationship, reverse_pattern,
+    validate_match_pattern_bindings,
 };
 use crate::query_api::ast_walk::extract_variables_from_expr;
 
+/// Most patterns of one MATCH clause that are tried in every order.
+const MAX_REORDERED_PATTERNS: usize = 3;
+
 pub(super) fn compile_match_plan(
     input: Option<Plan>,
     m: crate::ast::MatchClause,
     predicates: &BTreeMap<String, BTreeMap<String, Expression>>,
+    text_predicates: &BTreeMap<String, (String, Expression)>,
+    next_anon_id: &mut u32,
+) -> Result<Plan> {
+    let reorder = input.is_none()
+        && !m.optional
+        && (2..=MAX_REORDERED_PATTERNS).contains(&m.patterns.len())
+        && m.patterns.iter().all(inline_properties_are_constant);
+    let first_anon_id = *next_anon_id;
+    let textual = compile_match_patterns(
+        input,
+        m.patterns.clone(),
+        m.optional,
+        predicates,
+        text_predicates,
+        next_anon_id,
+    )?;
+    if !reorder {
+        return Ok(textual);
+    }
+
+    // Let the executor start from whichever pattern its statistics say is
+    // cheapest. Orders that do not compile are simply not offered.
+    let mut alternatives = vec![textual];
+    for order in permutations(m.patterns.len()).into_iter().skip(1) {
+        let mut anon_id = first_anon_id;
+        let patterns = order.iter().map(|&i| m.patterns[i].clone()).collect();
+        if let Ok(plan) = compile_match_patterns(
+            None,
+            patterns,
+            false,
+            predicates,
+            text_predicates,
+            &mut anon_id,
+        ) {
+            alternatives.push(plan);
+            *next_anon_id = (*next_anon_id).max(anon_id);
+        }
+    }
+    Ok(Plan::CostChoice { alternatives })
+}
+
+fn compile_match_patterns(
+    input: Option<Plan>,
+    patterns: Vec<crate::ast::Pattern>,
+    optional: bool,
+    predicates: &BTreeMap<String, BTreeMap<String, Expression>>,
+    text_predicates: &BTreeMap<String, (String, Expression)>,
     next_anon_id: &mut u32,
 ) -> Result<Plan> {
     let mut plan = input;
@@ -17,7 +68,7 @@ pub(super) fn compile_match_plan(
         extract_output_var_kinds(p, &mut known_bindings);
     }
 
-    for raw_pattern in m.patterns {
+    for raw_pattern in patterns {
         let pattern = maybe_reanchor_pattern(raw_pattern, &known_bindings);
         if pattern.elements.is_empty() {
             return Err(Error::Other("pattern cannot be empty".into()));
@@ -52,24 +103,62 @@ pub(super) fn compile_match_plan(
                 plan,
                 &pattern,
                 predicates,
-                m.optional,
+                text_predicates,
+                optional,
                 &known_bindings,
                 next_anon_id,
             )?);
         } else {
-            // Start a new component
-            let sub_plan = compile_pattern_chain(
+            // Start a new component. Variables it shares with earlier
+            // patterns away from its start node are matched independently
+            // and joined on afterwards.
+            let shared = shared_variables(&pattern, &known_bindings);
+            let component_bindings = if shared.is_empty() {
+                known_bindings.clone()
+            } else {
+                BTreeMap::new()
+            };
+            let first_anon_id = *next_anon_id;
+            let mut sub_plan = compile_pattern_chain(
                 None,
                 &pattern,
                 predicates,
-                m.optional,
-                &known_bindings,
+                text_predicates,
+                optional,
+                &component_bindings,
                 next_anon_id,
             )?;
+            // A chain can be anchored at either end; offer the reversed one
+            // so the executor can start from the rarer label.
+            if !optional && let Some(reversed) = reverse_free_chain(&pattern, &component_bindings) {
+                let mut anon_id = first_anon_id;
+                if let Ok(reversed_plan) = compile_pattern_chain(
+                    None,
+                    &reversed,
+                    predicates,
+                    text_predicates,
+                    optional,
+                    &component_bindings,
+                    &mut anon_id,
+                ) {
+                    *next_anon_id = (*next_anon_id).max(anon_id);
+                    sub_plan = Plan::CostChoice {
+                        alternatives: vec![sub_plan, reversed_plan],
+                    };
+                }
+            }
             if let Some(existing) = plan {
-                plan = Some(Plan::CartesianProduct {
-                    left: Box::new(existing),
-                    right: Box::new(sub_plan),
+                plan = Some(if shared.is_empty() {
+                    Plan::CartesianProduct {
+                        left: Box::new(existing),
+                        right: Box::new(sub_plan),
+                    }
+                } else {
+                    Plan::HashJoin {
+                        left: Box::new(existing),
+                        right: Box::new(sub_plan),
+                        keys: shared,
+                    }
                 });
             } else {
                 plan = Some(sub_plan);
@@ -90,6 +179,7 @@ fn compile_pattern_chain(
     input: Option<Plan>,
     pattern: &crate::ast::Pattern,
     predicates: &BTreeMap<String, BTreeMap<String, Expression>>,
+    text_predicates: &BTreeMap<String, (String, Expression)>,
     optional: bool,
     known_bindings: &BTreeMap<String, BindingKind>,
     next_anon_id: &mut u32,
@@ -177,16 +267,42 @@ fn compile_pattern_chain(
             optional,
         };
 
-        // Try IndexSeek optimization
+        // Try IndexSeek optimization, then a text index for CONTAINS. Each
+        // equality predicate gets a seek, falling back to the next field's,
+        // so whichever field is indexed is used.
         if let Some(label_name) = &src_label
             && let Some(var_preds) = local_predicates.get(&src_alias)
-            && let Some((field, val_expr)) = var_preds.iter().next()
+            && !var_preds.is_empty()
+        {
+            for (field, val_expr) in var_preds.iter().rev() {
+                start_plan = Plan::IndexSeek {
+                    alias: src_alias.clone(),
+                    label: label_name.clone(),
+                    field: field.clone(),
+                    value_expr: val_expr.clone(),
+                    fallback: Box::new(start_plan),
+                };
+            }
+            // Several equality predicates may be covered by one composite index.
+            if var_preds.len() > 1 {
+                start_plan = Plan::CompositeIndexSeek {
+                    alias: src_alias.clone(),
+                    label: label_name.clone(),
+                    predicates: var_preds
+                        .iter()
+                        .map(|(field, val_expr)| (field.clone(), val_expr.clone()))
+                        .collect(),
+                    fallback: Box::new(start_plan),
+                };
+            }
+        } else if let Some(label_name) = &src_label
+            && let Some((field, needle_expr)) = text_predicates.get(&src_alias)
         {
-            start_plan = Plan::IndexSeek {
+            start_plan = Plan::TextIndexSeek {
                 alias: src_alias.clone(),
                 label: label_name.clone(),
                 field: field.clone(),
-                value_expr: val_expr.clone(),
+                needle_expr: needle_expr.clone(),
                 fallback: Box::new(start_plan),
             };
         }
@@ -298,6 +414,7 @@ fn compile_pattern_chain(
                         optional,
                         optional_unbind: optional_unbind.clone(),
                         path_alias: path_alias.clone(),
+                        program: None,
                     };
                 }
                 crate::ast::RelationshipDirection::RightToLeft => {
@@ -313,6 +430,7 @@ fn compile_pattern_chain(
                         optional,
                         optional_unbind: optional_unbind.clone(),
                         path_alias: path_alias.clone(),
+                        program: None,
                     };
                 }
                 crate::ast::RelationshipDirection::Undirected => {
@@ -398,6 +516,90 @@ fn compile_pattern_chain(
     Ok(plan)
 }
 
+/// Node and relationship variables of `pattern` that earlier patterns
+/// already bind, in pattern order.
+fn shared_variables(
+    pattern: &crate::ast::Pattern,
+    known_bindings: &BTreeMap<String, BindingKind>,
+) -> Vec<String> {
+    let mut shared: Vec<String> = Vec::new();
+    for element in &pattern.elements {
+        let variable = match element {
+            crate::ast::PathElement::Node(node) => &node.variable,
+            crate::ast::PathElement::Relationship(rel) => &rel.variable,
+        };
+        if let Some(name) = variable
+            && known_bindings.contains_key(name)
+            && !shared.contains(name)
+        {
+            shared.push(name.clone());
+        }
+    }
+    shared
+}
+
+/// Returns `pattern` anchored at its other end when that binds the same
+/// values: a chain of at least one hop with no path variable, no named
+/// variable-length relationship (its list order would flip), nothing bound
+/// from earlier clauses and only constant inline properties.
+fn reverse_free_chain(
+    pattern: &crate::ast::Pattern,
+    known_bindings: &BTreeMap<String, BindingKind>,
+) -> Option<crate::ast::Pattern> {
+    if pattern.variable.is_some()
+        || pattern.elements.len() < 3
+        || pattern.elements.len() % 2 == 0
+        || !inline_properties_are_constant(pattern)
+    {
+        return None;
+    }
+    let reversible = pattern.elements.iter().all(|element| match element {
+        crate::ast::PathElement::Node(node) => node
+            .variable
+            .as_ref()
+            .map_or(true, |name| !known_bindings.contains_key(name)),
+        crate::ast::PathElement::Relationship(rel) => match &rel.variable {
+            Some(name) => rel.variable_length.is_none() && !known_bindings.contains_key(name),
+            None => true,
+        },
+    });
+    reversible.then(|| reverse_pattern(pattern.clone()))
+}
+
+/// True when no inline property value refers to a variable, so the pattern's
+/// filters do not depend on which of its elements is bound first.
+fn inline_properties_are_constant(pattern: &crate::ast::Pattern) -> bool {
+    pattern.elements.iter().all(|element| {
+        let properties = match element {
+            crate::ast::PathElement::Node(node) => &node.properties,
+            crate::ast::PathElement::Relationship(rel) => &rel.properties,
+        };
+        properties.as_ref().map_or(true, |props| {
+            props.properties.iter().all(|pair| {
+                let mut refs = std::collections::HashSet::new();
+                extract_variables_from_expr(&pair.value, &mut refs);
+                refs.is_empty()
+            })
+        })
+    })
+}
+
+/// All orderings of `0..n`, starting with the identity.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut out = Vec::new();
+    for first in 0..n {
+        for rest in permutations(n - 1) {
+            let mut order = vec![first];
+            order.extend(rest.into_iter().map(|i| if i >= first { i + 1 } else { i }));
+            out.push(order);
+        }
+    }
+    out
+}
+
 fn build_var_len_rel_properties_predicate(
     path_alias: &str,
     rel_props: &crate::ast::PropertyMap,
@@ -0,0 +1,329 @@
+//! Keyset pagination for queries ending in `ORDER BY`.
+//!
+//! A continuation token records the sort keys of the last row returned and
+//! how many rows with exactly those keys were returned so far. The next page
+//! drops every row ordered before the keys instead of sorting and skipping
+//! them, and keeps only the best `page_size + 1` candidates while scanning.
+//! Rows with tied keys resume by their position among the ties, so sort keys
+//! that are unique per row give stable pages even across writes.
+
+use super::{
+    Error, Expression, GraphSnapshot, Params, Plan, PreparedQuery, Result, Row, Value, execute_plan,
+};
+use crate::ast::Direction;
+use crate::evaluator::{evaluate_expression_value, order_compare};
+use std::cmp::Ordering;
+
+const TOKEN_VERSION: u8 = 1;
+
+/// One page of rows from [`PreparedQuery::execute_page`].
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    pub rows: Vec<Row>,
+    /// Resumes after the last row of `rows`; `None` on the last page.
+    pub next_token: Option<String>,
+}
+
+/// Position after the last row of a page.
+#[derive(Debug, Clone, PartialEq)]
+struct Cursor {
+    keys: Vec<Value>,
+    ties: u64,
+}
+
+/// A row waiting for the page, with its sort keys and scan position.
+type Candidate = (Vec<Value>, usize, Row);
+
+pub(super) fn execute_page<S: GraphSnapshot>(
+    query: &PreparedQuery,
+    snapshot: &S,
+    params: &Params,
+    page_size: usize,
+    token: Option<&str>,
+) -> Result<Page> {
+    if page_size == 0 {
+        return Err(Error::Other("page size must be positive".to_string()));
+    }
+    let (projections, items, input) = (query.explain.is_none() && !query.profile)
+        .then(|| split_order_by(&query.plan))
+        .flatten()
+        .ok_or_else(|| {
+            Error::Other(
+                "keyset pagination requires a read query ending in ORDER BY without SKIP or LIMIT"
+                    .to_string(),
+            )
+        })?;
+    let fingerprint = fingerprint(items);
+    let cursor = token.map(|t| decode_token(t, fingerprint)).transpose()?;
+
+    let order = |a: &Candidate, b: &Candidate| compare_keys(&a.0, &b.0, items).then(a.1.cmp(&b.1));
+    let keep = page_size.saturating_add(1);
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut skipped_ties = 0u64;
+    query.bind(params)?;
+    for (seq, row) in execute_plan(snapshot, input, params).enumerate() {
+        params.check_timeout("Page.collect")?;
+        let row = row?;
+        let keys: Vec<Value> = items
+            .iter()
+            .map(|(expr, _)| evaluate_expression_value(expr, &row, snapshot, params))
+            .collect();
+        if let Some(cursor) = &cursor {
+            match compare_keys(&keys, &cursor.keys, items) {
+                Ordering::Less => continue,
+                Ordering::Equal if skipped_ties < cursor.ties => {
+                    skipped_ties += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        candidates.push((keys, seq, row));
+        if candidates.len() >= keep.saturating_mul(2) {
+            candidates.select_nth_unstable_by(keep - 1, order);
+            candidates.truncate(keep);
+        }
+    }
+    candidates.sort_by(order);
+    let has_more = candidates.len() > page_size;
+    candidates.truncate(page_size);
+
+    let next_token = match candidates.last() {
+        Some((last, _, _)) if has_more => {
+            let mut ties = candidates
+                .iter()
+                .filter(|(keys, _, _)| compare_keys(keys, last, items) == Ordering::Equal)
+                .count() as u64;
+            if let Some(cursor) = &cursor
+                && compare_keys(&cursor.keys, last, items) == Ordering::Equal
+            {
+                ties += cursor.ties;
+            }
+            Some(encode_token(
+                &Cursor {
+                    keys: last.clone(),
+                    ties,
+                },
+                fingerprint,
+            )?)
+        }
+        _ => None,
+    };
+
+    let rows = candidates
+        .into_iter()
+        .map(|(_, _, row)| match projections {
+            Some(projections) => Row::new(
+                projections
+                    .iter()
+                    .map(|(alias, expr)| {
+                        (
+                            alias.clone(),
+                            evaluate_expression_value(expr, &row, snapshot, params),
+                        )
+                    })
+                    .collect(),
+            ),
+            None => row,
+        })
+        .collect();
+    Ok(Page { rows, next_token })
+}
+
+/// Splits a plan ending in `ORDER BY` into the projection applied after
+/// sorting (present when `ORDER BY` uses unprojected variables), the sort
+/// items and the sorted input.
+#[allow(clippy::type_complexity)]
+fn split_order_by(
+    plan: &Plan,
+) -> Option<(
+    Option<&[(String, Expression)]>,
+    &[(Expression, Direction)],
+    &Plan,
+)> {
+    match plan {
+        Plan::OrderBy { input, items } => Some((None, items, input)),
+        Plan::Project { input, projections } => match &**input {
+            Plan::OrderBy { input, items } => Some((Some(projections), items, input)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn compare_keys(a: &[Value], b: &[Value], items: &[(Expression, Direction)]) -> Ordering {
+    for ((left, right), (_, direction)) in a.iter().zip(b).zip(items) {
+        let order = order_compare(left, right);
+        if order != Ordering::Equal {
+            return if *direction == Direction::Ascending {
+                order
+            } else {
+                order.reverse()
+            };
+        }
+    }
+    Ordering::Equal
+}
+
+/// Ties a token to the `ORDER BY` it was produced for (FNV-1a).
+fn fingerprint(items: &[(Expression, Direction)]) -> u64 {
+    format!("{items:?}")
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn encode_token(cursor: &Cursor, fingerprint: u64) -> Result<String> {
+    let mut buf = vec![TOKEN_VERSION];
+    buf.extend_from_slice(&fingerprint.to_be_bytes());
+    buf.extend_from_slice(&cursor.ties.to_be_bytes());
+    encode_value(&Value::List(cursor.keys.clone()), &mut buf)?;
+    Ok(buf.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+fn decode_token(token: &str, fingerprint: u64) -> Result<Cursor> {
+    let invalid = || Error::Other("invalid continuation token".to_string());
+    if token.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let bytes = (0..token.len())
+        .step_by(2)
+        .map(|i| {
+            token
+                .get(i..i + 2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    let mut reader = Reader { bytes: &bytes };
+    if reader.take(1).ok_or_else(invalid)? != [TOKEN_VERSION] {
+        return Err(invalid());
+    }
+    if reader.u64().ok_or_else(invalid)? != fingerprint {
+        return Err(Error::Other(
+            "continuation token belongs to a different query".to_string(),
+        ));
+    }
+    let ties = reader.u64().ok_or_else(invalid)?;
+    let Some(Value::List(keys)) = reader.value() else {
+        return Err(invalid());
+    };
+    if !reader.bytes.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Cursor { keys, ties })
+}
+
+fn encode_value(value: &Value, buf: &mut Vec<u8>) -> Result<()> {
+    match value {
+        Value::Null => buf.push(b'n'),
+        Value::Bool(b) => buf.extend_from_slice(&[b'b', u8::from(*b)]),
+        Value::Int(i) => {
+            buf.push(b'i');
+            buf.extend_from_slice(&i.to_be_bytes());
+        }
+        Value::Float(f) => {
+            buf.push(b'f');
+            buf.extend_from_slice(&f.to_bits().to_be_bytes());
+        }
+        Value::DateTime(t) => {
+            buf.push(b'd');
+            buf.extend_from_slice(&t.to_be_bytes());
+        }
+        Value::NodeId(id) => {
+            buf.push(b'x');
+            buf.extend_from_slice(&id.to_be_bytes());
+        }
+        Value::ExternalId(id) => {
+            buf.push(b'e');
+            buf.extend_from_slice(&id.to_be_bytes());
+        }
+        Value::String(s) => {
+            buf.push(b's');
+            buf.extend_from_slice(&(s.len() as u64).to_be_bytes());
+            buf.extend_from_slice(s.as_bytes());
+        }
+        Value::List(items) => {
+            buf.push(b'l');
+            buf.extend_from_slice(&(items.len() as u64).to_be_bytes());
+            for item in items {
+                encode_value(item, buf)?;
+            }
+        }
+        other => {
+            return Err(Error::Other(format!(
+                "cannot paginate on ORDER BY value {other:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        let tag = self.take(1)?[0];
+        Some(match tag {
+            b'n' => Value::Null,
+            b'b' => Value::Bool(self.take(1)?[0] != 0),
+            b'i' => Value::Int(self.u64()? as i64),
+            b'f' => Value::Float(f64::from_bits(self.u64()?)),
+            b'd' => Value::DateTime(self.u64()? as i64),
+            b'x' => Value::NodeId(u32::from_be_bytes(self.take(4)?.try_into().ok()?)),
+            b'e' => Value::ExternalId(self.u64()?),
+            b's' => {
+                let len = usize::try_from(self.u64()?).ok()?;
+                Value::String(String::from_utf8(self.take(len)?.to_vec()).ok()?)
+            }
+            b'l' => {
+                let len = usize::try_from(self.u64()?).ok()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.value()?);
+                }
+                Value::List(items)
+            }
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_round_trip_and_reject_other_queries() {
+        let cursor = Cursor {
+            keys: vec![
+                Value::String("ada".to_string()),
+                Value::Int(-3),
+                Value::Null,
+                Value::List(vec![Value::Float(1.5), Value::NodeId(7)]),
+            ],
+            ties: 2,
+        };
+        let token = encode_token(&cursor, 42).unwrap();
+        assert_eq!(decode_token(&token, 42).unwrap(), cursor);
+        assert!(decode_token(&token, 43).is_err());
+        assert!(decode_token(&token[..token.len() - 2], 42).is_err());
+        assert!(decode_token("zz", 42).is_err());
+    }
+}
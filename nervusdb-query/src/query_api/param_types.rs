@@ -0,0 +1,216 @@
+//! Checks parameter values against how the query uses them, before it runs.
+//!
+//! Uses are read off the AST: `$p` as a `SKIP` or `LIMIT` count, as the list
+//! of `IN`, `FOREACH` or a list comprehension, and as the map of `SET n = $p`
+//! / `SET n += $p`. A value that cannot serve there fails the execution up
+//! front with the parameter's name, instead of as an empty result or an error
+//! raised only once rows reach the operator.
+
+use super::{Error, Params, Result, literal_slots};
+use crate::ast::{
+    BinaryOperator, CallClause, Clause, ExistsExpression, Expression, Query, SubqueryExpression,
+};
+use crate::executor::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum ParamUse {
+    Skip,
+    Limit,
+    List,
+    PropertyMap,
+}
+
+impl ParamUse {
+    fn describe(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Skip => ("in SKIP", "a non-negative integer"),
+            Self::Limit => ("in LIMIT", "a non-negative integer"),
+            Self::List => ("as a list", "a list or null"),
+            Self::PropertyMap => ("as the map of a SET", "a map or null"),
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            Self::Skip | Self::Limit => matches!(value, Value::Int(v) if *v >= 0),
+            Self::List => matches!(value, Value::List(_) | Value::Null),
+            Self::PropertyMap => !matches!(
+                value,
+                Value::Bool(_)
+                    | Value::Int(_)
+                    | Value::Float(_)
+                    | Value::String(_)
+                    | Value::List(_)
+            ),
+        }
+    }
+}
+
+/// The parameters `query` constrains, sorted by name.
+pub(super) fn infer(query: &Query) -> Vec<(String, ParamUse)> {
+    let mut uses = Vec::new();
+    walk_query(query, &mut uses);
+    uses.sort();
+    uses.dedup();
+    uses
+}
+
+/// Fails on the first parameter in `uses` whose value in `params` does not
+/// fit; missing parameters count as null.
+pub(super) fn check(uses: &[(String, ParamUse)], params: &Params) -> Result<()> {
+    for (name, used) in uses {
+        let value = params.get(name).cloned().unwrap_or(Value::Null);
+        if used.accepts(&value) {
+            continue;
+        }
+        let (place, expected) = used.describe();
+        let got = match &value {
+            Value::Int(v) => v.to_string(),
+            Value::Null if params.get(name).is_none() => "nothing (it was not given)".to_string(),
+            other => value_kind(other).to_string(),
+        };
+        return Err(Error::Other(format!(
+            "parameter ${name} is used {place} and must be {expected}, got {got}"
+        )));
+    }
+    Ok(())
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Int(_) => "an integer",
+        Value::Float(_) => "a float",
+        Value::String(_) => "a string",
+        Value::List(_) => "a list",
+        Value::Map(_) => "a map",
+        _ => "a graph value",
+    }
+}
+
+fn walk_query(query: &Query, uses: &mut Vec<(String, ParamUse)>) {
+    for clause in &query.clauses {
+        walk_clause(clause, uses);
+    }
+}
+
+fn walk_clause(clause: &Clause, uses: &mut Vec<(String, ParamUse)>) {
+    let window = |expr: &Option<Expression>, used: ParamUse, uses: &mut Vec<_>| {
+        if let Some(expr) = expr {
+            note(expr, used, uses);
+            walk_expr(expr, uses);
+        }
+    };
+    match clause {
+        Clause::Return(ret) => {
+            window(&ret.skip, ParamUse::Skip, uses);
+            window(&ret.limit, ParamUse::Limit, uses);
+            ret.items
+                .iter()
+                .for_each(|i| walk_expr(&i.expression, uses));
+            for item in ret.order_by.iter().flat_map(|o| &o.items) {
+                walk_expr(&item.expression, uses);
+            }
+        }
+        Clause::With(with) => {
+            window(&with.skip, ParamUse::Skip, uses);
+            window(&with.limit, ParamUse::Limit, uses);
+            with.items
+                .iter()
+                .for_each(|i| walk_expr(&i.expression, uses));
+            if let Some(w) = &with.where_clause {
+                walk_expr(&w.expression, uses);
+            }
+            for item in with.order_by.iter().flat_map(|o| &o.items) {
+                walk_expr(&item.expression, uses);
+            }
+        }
+        Clause::Where(w) => walk_expr(&w.expression, uses),
+        Clause::Unwind(unwind) => walk_expr(&unwind.expression, uses),
+        Clause::Set(set) => {
+            set.items.iter().for_each(|i| walk_expr(&i.value, uses));
+            for item in &set.map_items {
+                note(&item.value, ParamUse::PropertyMap, uses);
+                walk_expr(&item.value, uses);
+            }
+        }
+        Clause::Delete(delete) => delete.expressions.iter().for_each(|e| walk_expr(e, uses)),
+        Clause::Foreach(foreach) => {
+            note(&foreach.list, ParamUse::List, uses);
+            walk_expr(&foreach.list, uses);
+            foreach.updates.iter().for_each(|c| walk_clause(c, uses));
+        }
+        Clause::Call(CallClause::Subquery(query)) => walk_query(query, uses),
+        Clause::Call(CallClause::Procedure(call)) => {
+            call.arguments.iter().for_each(|e| walk_expr(e, uses))
+        }
+        Clause::Union(union) => walk_query(&union.query, uses),
+        Clause::Match(_)
+        | Clause::Create(_)
+        | Clause::Merge(_)
+        | Clause::LoadCsv(_)
+        | Clause::Remove(_) => {}
+    }
+}
+
+fn walk_expr(expr: &Expression, uses: &mut Vec<(String, ParamUse)>) {
+    match expr {
+        Expression::Binary(bin) => {
+            if matches!(bin.operator, BinaryOperator::In) {
+                note(&bin.right, ParamUse::List, uses);
+            }
+            walk_expr(&bin.left, uses);
+            walk_expr(&bin.right, uses);
+        }
+        Expression::Unary(unary) => walk_expr(&unary.operand, uses),
+        Expression::FunctionCall(call) => call.args.iter().for_each(|e| walk_expr(e, uses)),
+        Expression::Case(case) => {
+            let arms = case.when_clauses.iter().flat_map(|(w, t)| [w, t]);
+            for e in case
+                .expression
+                .iter()
+                .chain(arms)
+                .chain(&case.else_expression)
+            {
+                walk_expr(e, uses);
+            }
+        }
+        Expression::List(items) => items.iter().for_each(|e| walk_expr(e, uses)),
+        Expression::Map(map) => map
+            .properties
+            .iter()
+            .for_each(|p| walk_expr(&p.value, uses)),
+        Expression::ListComprehension(lc) => {
+            note(&lc.list, ParamUse::List, uses);
+            let parts = [&lc.list].into_iter().chain(&lc.where_expression);
+            for e in parts.chain(&lc.map_expression) {
+                walk_expr(e, uses);
+            }
+        }
+        Expression::Exists(exists) => {
+            if let ExistsExpression::Subquery(query) = exists.as_ref() {
+                walk_query(query, uses);
+            }
+        }
+        Expression::Subquery(sub) => {
+            let SubqueryExpression { query, .. } = sub.as_ref();
+            walk_query(query, uses);
+        }
+        Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::PropertyAccess(_)
+        | Expression::PatternComprehension(_)
+        | Expression::Parameter(_) => {}
+    }
+}
+
+/// Records `expr` as a use of its parameter, unless it is a plan-cache
+/// literal slot, whose value was checked when the query was compiled.
+fn note(expr: &Expression, used: ParamUse, uses: &mut Vec<(String, ParamUse)>) {
+    if let Expression::Parameter(name) = expr
+        && literal_slots::slot_index(name).is_none()
+    {
+        uses.push((name.clone(), used));
+    }
+}
@@ -17,13 +17,19 @@ pub(super) fn plan_contains_write(plan: &Plan) -> bool {
         | Plan::OrderBy { input, .. }
         | Plan::Distinct { input }
         | Plan::Unwind { input, .. }
+        | Plan::LoadCsv { input, .. }
         | Plan::Aggregate { input, .. }
-        | Plan::ProcedureCall { input, .. }
         | Plan::MatchBoundRel { input, .. } => plan_contains_write(input),
+        Plan::ProcedureCall { input, name, .. } => {
+            crate::executor::is_write_procedure(name) || plan_contains_write(input)
+        }
         Plan::OptionalWhereFixup {
             outer, filtered, ..
         } => plan_contains_write(outer) || plan_contains_write(filtered),
-        Plan::IndexSeek { fallback, .. } => plan_contains_write(fallback),
+        Plan::IndexSeek { fallback, .. }
+        | Plan::TextIndexSeek { fallback, .. }
+        | Plan::CompositeIndexSeek { fallback, .. } => plan_contains_write(fallback),
+        Plan::CostChoice { alternatives } => alternatives.iter().any(plan_contains_write),
         Plan::MatchOut { input, .. }
         | Plan::MatchIn { input, .. }
         | Plan::MatchUndirected { input, .. }
@@ -31,7 +37,9 @@ pub(super) fn plan_contains_write(plan: &Plan) -> bool {
         Plan::Apply {
             input, subquery, ..
         } => plan_contains_write(input) || plan_contains_write(subquery),
-        Plan::CartesianProduct { left, right } | Plan::Union { left, right, .. } => {
+        Plan::CartesianProduct { left, right }
+        | Plan::HashJoin { left, right, .. }
+        | Plan::Union { left, right, .. } => {
             plan_contains_write(left) || plan_contains_write(right)
         }
         Plan::NodeScan { .. } | Plan::ReturnOne | Plan::Values { .. } => false,
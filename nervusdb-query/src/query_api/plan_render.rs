@@ -2,12 +2,28 @@ use crate::executor::Plan;
 use std::fmt::Write as _;
 
 pub(super) fn render_plan(plan: &Plan) -> String {
+    render_plan_annotated(plan, &mut |_, _| String::new())
+}
+
+/// Renders `plan` like [`render_plan`], appending `annotate(node, depth)` to
+/// each operator's line. Nodes are visited in the order they are printed.
+pub(super) fn render_plan_annotated(
+    plan: &Plan,
+    annotate: &mut dyn FnMut(&Plan, usize) -> String,
+) -> String {
     fn indent(n: usize) -> String {
         "  ".repeat(n)
     }
 
-    fn go(out: &mut String, plan: &Plan, depth: usize) {
+    fn go(
+        out: &mut String,
+        plan: &Plan,
+        depth: usize,
+        annotate: &mut dyn FnMut(&Plan, usize) -> String,
+    ) {
         let pad = indent(depth);
+        let start = out.len();
+        let note = annotate(plan, depth);
         match plan {
             Plan::ReturnOne => {
                 let _ = writeln!(out, "{pad}ReturnOne");
@@ -21,7 +37,7 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 merge,
             } => {
                 let _ = writeln!(out, "{pad}Create(merge={merge}, pattern={pattern:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Foreach {
                 input,
@@ -30,9 +46,9 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 sub_plan,
             } => {
                 let _ = writeln!(out, "{pad}Foreach(var={variable}, list={list:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
                 let _ = writeln!(out, "{pad}  SubPlan:");
-                go(out, sub_plan, depth + 2);
+                go(out, sub_plan, depth + 2, annotate);
             }
 
             Plan::NodeScan {
@@ -57,6 +73,7 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 optional,
                 optional_unbind: _,
                 path_alias,
+                program: _,
             } => {
                 let opt_str = if *optional { " OPTIONAL" } else { "" };
                 let path_str = if let Some(p) = path_alias {
@@ -110,6 +127,7 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 optional,
                 optional_unbind: _,
                 path_alias,
+                program: _,
             } => {
                 let opt_str = if *optional { " OPTIONAL" } else { "" };
                 let path_str = if let Some(p) = path_alias {
@@ -122,7 +140,7 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     "{pad}MatchIn{opt_str}(src={src_alias}, rels={rels:?}, edge={edge_alias:?}, dst={dst_alias}, limit={limit:?}{path_str})"
                 );
                 if let Some(p) = input {
-                    go(out, p, depth + 1);
+                    go(out, p, depth + 1, annotate);
                 }
             }
             Plan::MatchUndirected {
@@ -149,7 +167,7 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     "{pad}MatchUndirected{opt_str}(src={src_alias}, rels={rels:?}, edge={edge_alias:?}, dst={dst_alias}, limit={limit:?}{path_str})"
                 );
                 if let Some(p) = input {
-                    go(out, p, depth + 1);
+                    go(out, p, depth + 1, annotate);
                 }
             }
             Plan::MatchBoundRel {
@@ -175,11 +193,11 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     out,
                     "{pad}MatchBoundRel{opt_str}(rel={rel_alias}, src={src_alias}, rels={rels:?}, dst={dst_alias}, dir={direction:?}{path_str})"
                 );
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Filter { input, predicate } => {
                 let _ = writeln!(out, "{pad}Filter(predicate={predicate:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::OptionalWhereFixup {
                 outer,
@@ -191,13 +209,13 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     "{pad}OptionalWhereFixup(null_aliases={null_aliases:?})"
                 );
                 let _ = writeln!(out, "{pad}  Outer:");
-                go(out, outer, depth + 2);
+                go(out, outer, depth + 2, annotate);
                 let _ = writeln!(out, "{pad}  Filtered:");
-                go(out, filtered, depth + 2);
+                go(out, filtered, depth + 2, annotate);
             }
             Plan::Project { input, projections } => {
                 let _ = writeln!(out, "{pad}Project(len={})", projections.len());
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Aggregate {
                 input,
@@ -208,24 +226,36 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     out,
                     "{pad}Aggregate(group_by={group_by:?}, aggregates={aggregates:?})"
                 );
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::OrderBy { input, items } => {
                 let _ = writeln!(out, "{pad}OrderBy(items={items:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Skip { input, skip } => {
                 let _ = writeln!(out, "{pad}Skip(skip={skip:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Limit { input, limit } => {
                 let _ = writeln!(out, "{pad}Limit(limit={limit:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
+            }
+            Plan::CostChoice { alternatives } => {
+                let _ = writeln!(out, "{pad}CostChoice(alternatives={})", alternatives.len());
+                for alternative in alternatives {
+                    let _ = writeln!(out, "{pad}  Alternative:");
+                    go(out, alternative, depth + 2, annotate);
+                }
             }
             Plan::CartesianProduct { left, right } => {
                 let _ = writeln!(out, "{pad}CartesianProduct");
-                go(out, left, depth + 1);
-                go(out, right, depth + 1);
+                go(out, left, depth + 1, annotate);
+                go(out, right, depth + 1, annotate);
+            }
+            Plan::HashJoin { left, right, keys } => {
+                let _ = writeln!(out, "{pad}HashJoin(keys={keys:?})");
+                go(out, left, depth + 1, annotate);
+                go(out, right, depth + 1, annotate);
             }
             Plan::Apply {
                 input,
@@ -233,9 +263,9 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 alias,
             } => {
                 let _ = writeln!(out, "{pad}Apply(alias={alias:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
                 let _ = writeln!(out, "{pad}  Subquery:");
-                go(out, subquery, depth + 2);
+                go(out, subquery, depth + 2, annotate);
             }
             Plan::ProcedureCall {
                 input,
@@ -261,11 +291,11 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     name.join("."),
                     yields_str
                 );
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Distinct { input } => {
                 let _ = writeln!(out, "{pad}Distinct");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
 
             Plan::Delete {
@@ -277,7 +307,7 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                     out,
                     "{pad}Delete(detach={detach}, expressions={expressions:?})"
                 );
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::Unwind {
                 input,
@@ -285,32 +315,46 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 alias,
             } => {
                 let _ = writeln!(out, "{pad}Unwind(alias={alias}, expression={expression:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
+            }
+            Plan::LoadCsv {
+                input,
+                source,
+                alias,
+                with_headers,
+                field_terminator,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{pad}LoadCsv(alias={alias}, with_headers={with_headers}, field_terminator={:?}, source={source:?})",
+                    *field_terminator as char
+                );
+                go(out, input, depth + 1, annotate);
             }
             Plan::Union { left, right, all } => {
                 let _ = writeln!(out, "{pad}Union(all={all})");
-                go(out, left, depth + 1);
-                go(out, right, depth + 1);
+                go(out, left, depth + 1, annotate);
+                go(out, right, depth + 1, annotate);
             }
             Plan::SetProperty { input, items } => {
                 let _ = writeln!(out, "{pad}SetProperty(items={items:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::SetPropertiesFromMap { input, items } => {
                 let _ = writeln!(out, "{pad}SetPropertiesFromMap(items={items:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::SetLabels { input, items } => {
                 let _ = writeln!(out, "{pad}SetLabels(items={items:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::RemoveProperty { input, items } => {
                 let _ = writeln!(out, "{pad}RemoveProperty(items={items:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::RemoveLabels { input, items } => {
                 let _ = writeln!(out, "{pad}RemoveLabels(items={items:?})");
-                go(out, input, depth + 1);
+                go(out, input, depth + 1, annotate);
             }
             Plan::IndexSeek {
                 alias,
@@ -325,11 +369,39 @@ pub(super) fn render_plan(plan: &Plan) -> String {
                 );
                 // We don't render fallback to avoid noise, as it's just the unoptimized plan
             }
+            Plan::TextIndexSeek {
+                alias,
+                label,
+                field,
+                needle_expr,
+                fallback: _fallback,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{pad}TextIndexSeek(alias={alias}, label={label}, field={field}, needle={needle_expr:?})"
+                );
+            }
+            Plan::CompositeIndexSeek {
+                alias,
+                label,
+                predicates,
+                fallback: _fallback,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{pad}CompositeIndexSeek(alias={alias}, label={label}, predicates={predicates:?})"
+                );
+            }
+        }
+        if !note.is_empty()
+            && let Some(line_end) = out[start..].find('\n')
+        {
+            out.insert_str(start + line_end, &note);
         }
     }
 
     let mut out = String::new();
-    go(&mut out, plan, 0);
+    go(&mut out, plan, 0, annotate);
     out.trim_end().to_string()
 }
 
@@ -1,35 +1,87 @@
-use super::{Error, PreparedQuery, Result, VecDeque, render_plan, strip_explain_prefix};
+use super::{
+    Error, PreparedQuery, Result, VecDeque, plan_contains_write, render_plan, strip_explain_prefix,
+    strip_periodic_commit_prefix, strip_profile_prefix,
+};
+use crate::ast::Clause;
+use std::sync::Arc;
+
+/// Rows per transaction for `USING PERIODIC COMMIT` without a count.
+const DEFAULT_PERIODIC_COMMIT_ROWS: usize = 1000;
 
 pub(super) fn prepare(cypher: &str) -> Result<PreparedQuery> {
+    // `EXPLAIN ANALYZE` also starts with `EXPLAIN`, so check it first.
+    if let Some(inner) = strip_profile_prefix(cypher) {
+        if inner.is_empty() {
+            return Err(Error::Other("PROFILE requires a query".into()));
+        }
+        let mut prepared = compile(inner)?;
+        if plan_contains_write(&prepared.plan) {
+            return Err(Error::Other("PROFILE supports read queries only".into()));
+        }
+        prepared.profile = true;
+        return Ok(prepared);
+    }
+
     if let Some(inner) = strip_explain_prefix(cypher) {
         if inner.is_empty() {
             return Err(Error::Other("EXPLAIN requires a query".into()));
         }
+        let mut prepared = compile(inner)?;
+        prepared.explain = Some(render_plan(&prepared.plan));
+        return Ok(prepared);
+    }
+
+    if let Some((rows, inner)) = strip_periodic_commit_prefix(cypher) {
+        let rows = rows.unwrap_or(DEFAULT_PERIODIC_COMMIT_ROWS);
+        if rows == 0 {
+            return Err(Error::Other(
+                "USING PERIODIC COMMIT needs a positive row count".into(),
+            ));
+        }
         let (query, merge_subclauses) = crate::parser::Parser::parse_with_merge_subclauses(inner)?;
-        let logical = super::planner::build_logical(query, VecDeque::from(merge_subclauses));
-        let optimized = super::plan::optimizer::optimize(logical);
-        let physical = super::planner::build_physical(optimized)?;
-        let explain = Some(render_plan(&physical.plan));
-        return Ok(PreparedQuery {
-            plan: physical.plan,
-            explain,
-            write: physical.write,
-            merge_on_create_items: physical.merge_on_create_items,
-            merge_on_create_map_items: physical.merge_on_create_map_items,
-            merge_on_match_items: physical.merge_on_match_items,
-            merge_on_match_map_items: physical.merge_on_match_map_items,
-            merge_on_create_labels: physical.merge_on_create_labels,
-            merge_on_match_labels: physical.merge_on_match_labels,
-        });
+        // Batches are slices of one file, so it must be the only source.
+        let loads = query
+            .clauses
+            .iter()
+            .filter(|clause| matches!(clause, Clause::LoadCsv(_)))
+            .count();
+        if loads != 1 || !matches!(query.clauses.first(), Some(Clause::LoadCsv(_))) {
+            return Err(Error::Other(
+                "USING PERIODIC COMMIT requires a query starting with its only LOAD CSV".into(),
+            ));
+        }
+        let mut prepared = build(query, merge_subclauses)?;
+        if !plan_contains_write(&prepared.plan) {
+            return Err(Error::Other(
+                "USING PERIODIC COMMIT supports write queries only".into(),
+            ));
+        }
+        prepared.periodic_commit = Some(rows);
+        return Ok(prepared);
     }
 
+    compile(cypher)
+}
+
+fn compile(cypher: &str) -> Result<PreparedQuery> {
     let (query, merge_subclauses) = crate::parser::Parser::parse_with_merge_subclauses(cypher)?;
+    build(query, merge_subclauses)
+}
+
+pub(super) fn build(
+    query: crate::ast::Query,
+    merge_subclauses: Vec<crate::parser::MergeSubclauses>,
+) -> Result<PreparedQuery> {
+    let param_uses = super::param_types::infer(&query);
     let logical = super::planner::build_logical(query, VecDeque::from(merge_subclauses));
     let optimized = super::plan::optimizer::optimize(logical);
-    let physical = super::planner::build_physical(optimized)?;
+    let mut physical = super::planner::build_physical(optimized)?;
+    crate::executor::compile_traversals(&mut physical.plan);
     Ok(PreparedQuery {
         plan: physical.plan,
         explain: None,
+        profile: false,
+        periodic_commit: None,
         write: physical.write,
         merge_on_create_items: physical.merge_on_create_items,
         merge_on_create_map_items: physical.merge_on_create_map_items,
@@ -37,5 +89,7 @@ pub(super) fn prepare(cypher: &str) -> Result<PreparedQuery> {
         merge_on_match_map_items: physical.merge_on_match_map_items,
         merge_on_create_labels: physical.merge_on_create_labels,
         merge_on_match_labels: physical.merge_on_match_labels,
+        literals: Arc::from([]),
+        param_uses: param_uses.into(),
     })
 }
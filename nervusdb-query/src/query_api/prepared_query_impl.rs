@@ -1,9 +1,17 @@
 use super::{
-    Error, GraphSnapshot, Params, PreparedQuery, Result, Row, Value, WriteSemantics, execute_plan,
-    execute_write, plan_contains_write,
+    CsvBatch, Error, GraphSnapshot, Params, PreparedQuery, Result, Row, WriteSemantics,
+    execute_plan, execute_write, plan_contains_write,
 };
 
 impl PreparedQuery {
+    /// Checks `params` against the query's parameter uses and starts an
+    /// execution with them.
+    pub(super) fn bind(&self, params: &Params) -> Result<()> {
+        super::param_types::check(&self.param_uses, params)?;
+        params.begin_execution(&self.literals);
+        Ok(())
+    }
+
     fn should_clear_write_rows(plan: &crate::executor::Plan) -> bool {
         matches!(
             plan,
@@ -24,6 +32,12 @@ impl PreparedQuery {
     /// represents a result record. Errors can occur during execution
     /// (e.g., type mismatches, missing variables).
     ///
+    /// `PROFILE` queries run to completion and yield a single row with the
+    /// annotated plan (`plan`), the result row count (`rows`) and per-operator
+    /// statistics (`operators`). `EXPLAIN` queries yield a single row with the
+    /// plan (`plan`), noting per node pattern whether an index will be used
+    /// and why, resolved against `snapshot`.
+    ///
     /// # Example
     ///
     /// ```ignore
@@ -38,16 +52,92 @@ impl PreparedQuery {
         snapshot: &'a S,
         params: &'a Params,
     ) -> impl Iterator<Item = Result<Row>> + 'a {
-        if let Some(plan) = &self.explain {
+        if self.explain.is_some() {
             let it: Box<dyn Iterator<Item = Result<Row>> + 'a> = Box::new(std::iter::once(Ok(
-                Row::default().with("plan", Value::String(plan.clone())),
+                super::explain_indexes::execute_explain(self, snapshot),
             )));
             return it;
         }
-        params.begin_execution();
+        if self.profile {
+            let it: Box<dyn Iterator<Item = Result<Row>> + 'a> = Box::new(std::iter::once(
+                super::profile::execute_profiled(self, snapshot, params),
+            ));
+            return it;
+        }
+        if let Err(err) = self.bind(params) {
+            return Box::new(std::iter::once(Err(err)));
+        }
         Box::new(execute_plan(snapshot, &self.plan, params))
     }
 
+    /// Executes a read query and returns only the number of result rows.
+    ///
+    /// The final `RETURN` projection (and any `SKIP`/`LIMIT` above it) is
+    /// kept for row counting but its expressions are never evaluated, so
+    /// nothing is materialized per row. Errors those expressions would raise
+    /// are therefore not reported. Projections under `ORDER BY`, `DISTINCT`
+    /// or aggregation still run because they shape the row set.
+    pub fn count_rows<S: GraphSnapshot>(&self, snapshot: &S, params: &Params) -> Result<u64> {
+        if self.explain.is_some() || self.profile {
+            return self
+                .execute_streaming(snapshot, params)
+                .try_fold(0u64, |count, row| row.map(|_| count + 1));
+        }
+        self.bind(params)?;
+        let plan = Self::without_final_projection(&self.plan);
+        execute_plan(snapshot, plan.as_ref().unwrap_or(&self.plan), params)
+            .try_fold(0u64, |count, row| row.map(|_| count + 1))
+    }
+
+    /// Executes a read query ending in `ORDER BY` one page at a time.
+    ///
+    /// Pass `None` for the first page, then each page's `next_token` to get
+    /// the rows after it. Unlike `SKIP`, resuming never sorts or returns the
+    /// rows of earlier pages. The query must not have its own `SKIP` or
+    /// `LIMIT`, and a token is only accepted by the query that produced it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let query = prepare("MATCH (u:User) RETURN u.name ORDER BY u.name").unwrap();
+    /// let first = query.execute_page(&snapshot, &Params::new(), 50, None)?;
+    /// let second = query.execute_page(&snapshot, &Params::new(), 50, first.next_token.as_deref())?;
+    /// ```
+    pub fn execute_page<S: GraphSnapshot>(
+        &self,
+        snapshot: &S,
+        params: &Params,
+        page_size: usize,
+        token: Option<&str>,
+    ) -> Result<super::Page> {
+        super::pagination::execute_page(self, snapshot, params, page_size, token)
+    }
+
+    /// The labels and relationship types this query's result depends on, or
+    /// `None` when its result must not be cached: writes, procedure calls,
+    /// `EXPLAIN`/`PROFILE` and calls like `rand()` that differ per run.
+    pub fn read_set(&self) -> Option<super::ReadSet> {
+        super::read_set::read_set(self)
+    }
+
+    /// Returns `plan` with its top-level projection removed, looking through
+    /// `SKIP`/`LIMIT`, or `None` when there is nothing to remove.
+    fn without_final_projection(plan: &crate::executor::Plan) -> Option<crate::executor::Plan> {
+        use crate::executor::Plan;
+        match plan {
+            Plan::Project { input, .. } => Some((**input).clone()),
+            Plan::Skip { input, skip } => Some(Plan::Skip {
+                input: Box::new(Self::without_final_projection(input)?),
+                skip: skip.clone(),
+            }),
+            Plan::Limit { input, limit } => Some(Plan::Limit {
+                input: Box::new(Self::without_final_projection(input)?),
+                limit: limit.clone(),
+            }),
+            _ => None,
+        }
+    }
+
     /// Executes a write query (CREATE/DELETE) with a write transaction.
     ///
     /// Returns the number of entities created/deleted.
@@ -71,7 +161,12 @@ impl PreparedQuery {
                 "EXPLAIN cannot be executed as a write query".into(),
             ));
         }
-        params.begin_execution();
+        if self.profile {
+            return Err(Error::Other(
+                "PROFILE cannot be executed as a write query".into(),
+            ));
+        }
+        self.bind(params)?;
         match self.write {
             WriteSemantics::Default => execute_write(&self.plan, snapshot, txn, params),
             WriteSemantics::Merge => crate::executor::execute_merge(
@@ -89,6 +184,46 @@ impl PreparedQuery {
         }
     }
 
+    /// Runs a `USING PERIODIC COMMIT` query one batch of CSV records at a
+    /// time. `run_batch` gets the parameters of each batch and should run
+    /// the query with them, through [`Self::execute_mixed`] or
+    /// [`Self::execute_write`], in a write transaction of its own that it
+    /// commits. Returns the total write count. A failing batch stops the
+    /// load; the batches before it stay committed.
+    ///
+    /// Any other query runs as a single batch.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let query = prepare("USING PERIODIC COMMIT 500 LOAD CSV FROM 'file:///x.csv' AS r CREATE (:Row {v: r[0]})")?;
+    /// let written = query.execute_periodic(&Params::new(), |params| {
+    ///     let mut txn = db.begin_write();
+    ///     let (_, count) = query.execute_mixed(&db.snapshot(), &mut txn, params)?;
+    ///     txn.commit()?;
+    ///     Ok(count)
+    /// })?;
+    /// ```
+    pub fn execute_periodic(
+        &self,
+        params: &Params,
+        mut run_batch: impl FnMut(&Params) -> Result<u32>,
+    ) -> Result<u32> {
+        let Some(rows) = self.periodic_commit else {
+            return run_batch(params);
+        };
+        let mut total = 0u32;
+        let mut offset = 0;
+        loop {
+            let batch = params.csv_batch_of(CsvBatch { offset, rows });
+            total = total.saturating_add(run_batch(&batch)?);
+            match batch.csv_resume_at() {
+                Some(next) => offset = next,
+                None => return Ok(total),
+            }
+        }
+    }
+
     pub fn execute_mixed<S: GraphSnapshot>(
         &self,
         snapshot: &S,
@@ -103,7 +238,11 @@ impl PreparedQuery {
                 "EXPLAIN cannot be executed as a mixed query".into(),
             ));
         }
-        params.begin_execution();
+        if self.profile {
+            let row = super::profile::execute_profiled(self, snapshot, params)?;
+            return Ok((vec![row.columns().iter().cloned().collect()], 0));
+        }
+        self.bind(params)?;
 
         if plan_contains_write(&self.plan) {
             return match self.write {
@@ -179,10 +318,35 @@ impl PreparedQuery {
         Ok((results, 0))
     }
 
+    /// The names of the columns each result row carries, in order, available
+    /// before the query runs. Queries without `RETURN` have none. `None`
+    /// means the columns are only known from the rows: a standalone `CALL`
+    /// of a built-in procedure without `YIELD`.
+    pub fn columns(&self) -> Option<Vec<String>> {
+        super::result_columns::result_columns(self)
+    }
+
+    /// Returns true if executing the plan would write. `EXPLAIN` and
+    /// `PROFILE` never write.
+    pub fn is_write(&self) -> bool {
+        self.explain.is_none() && !self.profile && plan_contains_write(&self.plan)
+    }
+
     pub fn is_explain(&self) -> bool {
         self.explain.is_some()
     }
 
+    /// Rows per transaction of a `USING PERIODIC COMMIT` query; `None` for
+    /// other queries.
+    pub fn periodic_commit(&self) -> Option<usize> {
+        self.periodic_commit
+    }
+
+    /// Returns true for `PROFILE` / `EXPLAIN ANALYZE` queries.
+    pub fn is_profile(&self) -> bool {
+        self.profile
+    }
+
     /// Returns the explained plan string if this query was an EXPLAIN query.
     pub fn explain_string(&self) -> Option<&str> {
         self.explain.as_deref()
@@ -0,0 +1,69 @@
+//! `PROFILE` / `EXPLAIN ANALYZE`: runs a read query to completion and reports
+//! what each plan operator actually did.
+
+use super::{
+    Duration, GraphSnapshot, Params, Plan, PreparedQuery, Result, Row, Value, execute_plan,
+    render_plan_annotated,
+};
+use crate::executor::plan_operator_name;
+use std::collections::BTreeMap;
+
+/// Counters for one plan operator during a profiled run.
+///
+/// `elapsed` is inclusive: it covers the time spent pulling rows from the
+/// operator's inputs as well.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct OperatorStats {
+    pub(crate) rows: u64,
+    pub(crate) invocations: u64,
+    pub(crate) elapsed: Duration,
+}
+
+/// Executes `query` with per-operator instrumentation and returns a single
+/// row with the annotated plan (`plan`), the number of result rows (`rows`)
+/// and one map per operator (`operators`).
+pub(super) fn execute_profiled<S: GraphSnapshot>(
+    query: &PreparedQuery,
+    snapshot: &S,
+    params: &Params,
+) -> Result<Row> {
+    let profiled = params.profiling();
+    query.bind(&profiled)?;
+    let mut result_rows = 0i64;
+    for row in execute_plan(snapshot, &query.plan, &profiled) {
+        row?;
+        result_rows += 1;
+    }
+
+    let stats = profiled.operator_stats();
+    let mut operators = Vec::new();
+    let plan = render_plan_annotated(&query.plan, &mut |node, depth| {
+        let node_stats = stats
+            .get(&(node as *const Plan as usize))
+            .copied()
+            .unwrap_or_default();
+        let elapsed_us = node_stats.elapsed.as_micros() as i64;
+        operators.push(Value::Map(BTreeMap::from([
+            (
+                "operator".to_string(),
+                Value::String(plan_operator_name(node).to_string()),
+            ),
+            ("depth".to_string(), Value::Int(depth as i64)),
+            ("rows".to_string(), Value::Int(node_stats.rows as i64)),
+            (
+                "invocations".to_string(),
+                Value::Int(node_stats.invocations as i64),
+            ),
+            ("elapsed_us".to_string(), Value::Int(elapsed_us)),
+        ])));
+        format!(
+            " [rows={}, invocations={}, time={}us]",
+            node_stats.rows, node_stats.invocations, elapsed_us
+        )
+    });
+
+    Ok(Row::default()
+        .with("plan", Value::String(plan))
+        .with("rows", Value::Int(result_rows))
+        .with("operators", Value::List(operators)))
+}
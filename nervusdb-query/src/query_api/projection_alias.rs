@@ -21,6 +21,7 @@ fn binary_operator_symbol(operator: &BinaryOperator) -> &'static str {
         BinaryOperator::StartsWith => "STARTS WITH",
         BinaryOperator::EndsWith => "ENDS WITH",
         BinaryOperator::Contains => "CONTAINS",
+        BinaryOperator::RegexMatch => "=~",
         BinaryOperator::HasLabel => ":",
         BinaryOperator::IsNull => "IS NULL",
         BinaryOperator::IsNotNull => "IS NOT NULL",
@@ -49,6 +50,7 @@ fn binary_precedence(operator: &BinaryOperator) -> u8 {
         | BinaryOperator::StartsWith
         | BinaryOperator::EndsWith
         | BinaryOperator::Contains
+        | BinaryOperator::RegexMatch
         | BinaryOperator::HasLabel
         | BinaryOperator::IsNull
         | BinaryOperator::IsNotNull => 4,
@@ -144,6 +146,30 @@ fn expression_alias_fragment(expr: &Expression) -> String {
                 }
                 return format!("{base}[{}]", expression_alias_fragment(&call.args[1]));
             }
+            if call.name == "__map_projection"
+                && let [
+                    Expression::Variable(subject),
+                    Expression::Map(map),
+                    Expression::Literal(Literal::Boolean(all)),
+                ] = call.args.as_slice()
+            {
+                let mut items = Vec::new();
+                if *all {
+                    items.push(".*".to_string());
+                }
+                for pair in &map.properties {
+                    items.push(match &pair.value {
+                        Expression::PropertyAccess(pa)
+                            if pa.variable == *subject && pa.property == pair.key =>
+                        {
+                            format!(".{}", pair.key)
+                        }
+                        Expression::Variable(name) if *name == pair.key => name.clone(),
+                        value => format!("{}: {}", pair.key, expression_alias_fragment(value)),
+                    });
+                }
+                return format!("{subject} {{{}}}", items.join(", "));
+            }
             let args = call
                 .args
                 .iter()
@@ -198,6 +224,10 @@ fn expression_alias_fragment(expr: &Expression) -> String {
         }
         Expression::Case(_) => "case(...)".to_string(),
         Expression::Exists(_) => "exists(...)".to_string(),
+        Expression::Subquery(subquery) => match subquery.kind {
+            crate::ast::SubqueryKind::Count => "count{...}".to_string(),
+            crate::ast::SubqueryKind::Scalar => "call{...}".to_string(),
+        },
         _ => "...".to_string(),
     }
 }
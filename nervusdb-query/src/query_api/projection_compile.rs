@@ -122,15 +122,29 @@ fn resolve_projection_source_expr<'a>(plan: &'a Plan, variable: &str) -> Option<
                 resolve_projection_source_expr(input, variable)
             }
         }
-        Plan::IndexSeek { fallback, .. } => resolve_projection_source_expr(fallback, variable),
+        Plan::LoadCsv { input, alias, .. } => {
+            if alias == variable {
+                None
+            } else {
+                resolve_projection_source_expr(input, variable)
+            }
+        }
+        Plan::IndexSeek { fallback, .. }
+        | Plan::TextIndexSeek { fallback, .. }
+        | Plan::CompositeIndexSeek { fallback, .. } => {
+            resolve_projection_source_expr(fallback, variable)
+        }
+        Plan::CostChoice { alternatives } => alternatives
+            .first()
+            .and_then(|first| resolve_projection_source_expr(first, variable)),
         Plan::Apply {
             input, subquery, ..
         } => resolve_projection_source_expr(subquery, variable)
             .or_else(|| resolve_projection_source_expr(input, variable)),
-        Plan::CartesianProduct { left, right } | Plan::Union { left, right, .. } => {
-            resolve_projection_source_expr(right, variable)
-                .or_else(|| resolve_projection_source_expr(left, variable))
-        }
+        Plan::CartesianProduct { left, right }
+        | Plan::HashJoin { left, right, .. }
+        | Plan::Union { left, right, .. } => resolve_projection_source_expr(right, variable)
+            .or_else(|| resolve_projection_source_expr(left, variable)),
         Plan::ProcedureCall { input, .. } | Plan::MatchBoundRel { input, .. } => {
             resolve_projection_source_expr(input, variable)
         }
@@ -470,6 +484,9 @@ fn validate_projection_expression_bindings(
                 // Subquery variables are validated in nested query compilation.
             }
         },
+        Expression::Subquery(_) => {
+            // Same as EXISTS subqueries: validated in nested query compilation.
+        }
         Expression::Parameter(_) | Expression::Literal(_) => {}
     }
     Ok(())
@@ -624,32 +641,10 @@ fn validate_projection_expression_semantics(
                 }
             }
             crate::ast::ExistsExpression::Subquery(subquery) => {
-                for clause in &subquery.clauses {
-                    match clause {
-                        crate::ast::Clause::Where(w) => {
-                            validate_projection_expression_semantics(&w.expression, vars)?
-                        }
-                        crate::ast::Clause::With(w) => {
-                            for item in &w.items {
-                                validate_projection_expression_semantics(&item.expression, vars)?;
-                            }
-                            if let Some(where_clause) = &w.where_clause {
-                                validate_projection_expression_semantics(
-                                    &where_clause.expression,
-                                    vars,
-                                )?;
-                            }
-                        }
-                        crate::ast::Clause::Return(r) => {
-                            for item in &r.items {
-                                validate_projection_expression_semantics(&item.expression, vars)?;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+                validate_subquery_semantics(subquery, vars)?;
             }
         },
+        Expression::Subquery(subquery) => validate_subquery_semantics(&subquery.query, vars)?,
         Expression::Literal(_)
         | Expression::Variable(_)
         | Expression::PropertyAccess(_)
@@ -659,6 +654,34 @@ fn validate_projection_expression_semantics(
     Ok(())
 }
 
+fn validate_subquery_semantics(
+    subquery: &crate::ast::Query,
+    vars: &BTreeMap<String, BindingKind>,
+) -> Result<()> {
+    for clause in &subquery.clauses {
+        match clause {
+            crate::ast::Clause::Where(w) => {
+                validate_projection_expression_semantics(&w.expression, vars)?
+            }
+            crate::ast::Clause::With(w) => {
+                for item in &w.items {
+                    validate_projection_expression_semantics(&item.expression, vars)?;
+                }
+                if let Some(where_clause) = &w.where_clause {
+                    validate_projection_expression_semantics(&where_clause.expression, vars)?;
+                }
+            }
+            crate::ast::Clause::Return(r) => {
+                for item in &r.items {
+                    validate_projection_expression_semantics(&item.expression, vars)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 fn expression_uses_allowed_group_refs(
     expr: &Expression,
     grouping_keys: &[(Expression, String)],
@@ -1598,6 +1621,25 @@ pub(super) fn compile_projection_aggregation(
         }
     }
 
+    // `n {.name, friends: collect(f)}` groups by its subject, as if `n` were
+    // projected next to it.
+    for (expr, _, contains_agg) in &resolved_items {
+        if let Expression::FunctionCall(call) = expr
+            && *contains_agg
+            && call.name == "__map_projection"
+            && let Some(Expression::Variable(subject)) = call.args.first()
+            && !grouping_aliases.contains(subject)
+            && input_bindings.contains_key(subject)
+        {
+            if projected_aliases.insert(subject.clone()) {
+                pre_projections.push((subject.clone(), Expression::Variable(subject.clone())));
+            }
+            group_by.push(subject.clone());
+            grouping_aliases.insert(subject.clone());
+            grouping_keys.push((Expression::Variable(subject.clone()), subject.clone()));
+        }
+    }
+
     let mut aggregate_exprs: Vec<(Expression, crate::ast::AggregateFunction, String)> = Vec::new();
     for (expr, _alias, contains_agg) in &resolved_items {
         if !*contains_agg {
@@ -1626,7 +1668,10 @@ pub(super) fn compile_projection_aggregation(
             | crate::ast::AggregateFunction::Max(expr)
             | crate::ast::AggregateFunction::MaxDistinct(expr)
             | crate::ast::AggregateFunction::Collect(expr)
-            | crate::ast::AggregateFunction::CollectDistinct(expr) => {
+            | crate::ast::AggregateFunction::CollectDistinct(expr)
+            | crate::ast::AggregateFunction::CountDistinctApprox(expr, None)
+            | crate::ast::AggregateFunction::HllSketch(expr, None)
+            | crate::ast::AggregateFunction::HllMerge(expr) => {
                 let mut deps = std::collections::HashSet::new();
                 extract_variables_from_expr(expr, &mut deps);
                 for dep in deps {
@@ -1637,7 +1682,12 @@ pub(super) fn compile_projection_aggregation(
                 }
             }
             crate::ast::AggregateFunction::PercentileDisc(value_expr, percentile_expr)
-            | crate::ast::AggregateFunction::PercentileCont(value_expr, percentile_expr) => {
+            | crate::ast::AggregateFunction::PercentileCont(value_expr, percentile_expr)
+            | crate::ast::AggregateFunction::CountDistinctApprox(
+                value_expr,
+                Some(percentile_expr),
+            )
+            | crate::ast::AggregateFunction::HllSketch(value_expr, Some(percentile_expr)) => {
                 let mut deps = std::collections::HashSet::new();
                 extract_variables_from_expr(value_expr, &mut deps);
                 extract_variables_from_expr(percentile_expr, &mut deps);
@@ -1835,6 +1885,9 @@ pub(super) fn contains_aggregate_expression(expr: &Expression) -> bool {
                     | "collect"
                     | "percentiledisc"
                     | "percentilecont"
+                    | "countdistinctapprox"
+                    | "hllsketch"
+                    | "hllmerge"
             ) {
                 return true;
             }
@@ -0,0 +1,342 @@
+//! The labels and relationship types a read query's result depends on.
+//!
+//! Result caches use the read set to decide which writes invalidate a cached
+//! result. It is conservative: a node pattern without a label reads every
+//! label, and a relationship pattern without a type reads every type.
+
+use super::{Expression, Plan, PreparedQuery};
+use crate::ast::{AggregateFunction, ExistsExpression};
+use std::collections::BTreeSet;
+
+/// Names of the labels (or relationship types) a query reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    All,
+    Only(BTreeSet<String>),
+}
+
+impl Scope {
+    /// The names read, or `None` for all of them.
+    pub fn names(&self) -> Option<&BTreeSet<String>> {
+        match self {
+            Scope::All => None,
+            Scope::Only(names) => Some(names),
+        }
+    }
+
+    fn add(&mut self, name: &str) {
+        if let Scope::Only(names) = self {
+            names.insert(name.to_string());
+        }
+    }
+
+    fn extend_or_all(&mut self, names: &[String]) {
+        if names.is_empty() {
+            *self = Scope::All;
+        } else {
+            names.iter().for_each(|name| self.add(name));
+        }
+    }
+}
+
+/// What a cached result of a read query has to be invalidated by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadSet {
+    pub labels: Scope,
+    pub rel_types: Scope,
+}
+
+/// Functions whose result differs between calls with the same arguments.
+const VOLATILE_FUNCTIONS: &[&str] = &["rand", "randomuuid", "timestamp"];
+/// Temporal constructors, which read the clock when called without arguments.
+const CLOCK_FUNCTIONS: &[&str] = &["date", "datetime", "localdatetime", "localtime", "time"];
+
+pub(super) fn read_set(query: &PreparedQuery) -> Option<ReadSet> {
+    if query.explain.is_some() || query.profile {
+        return None;
+    }
+    let mut set = ReadSet {
+        labels: Scope::Only(BTreeSet::new()),
+        rel_types: Scope::Only(BTreeSet::new()),
+    };
+    collect_plan(&query.plan, &mut set).then_some(set)
+}
+
+/// Adds what `plan` reads to `set`; false when its result cannot be cached.
+fn collect_plan(plan: &Plan, set: &mut ReadSet) -> bool {
+    match plan {
+        Plan::ReturnOne | Plan::Values { .. } => true,
+        Plan::NodeScan { label, .. } => {
+            match label {
+                Some(label) => set.labels.add(label),
+                None => set.labels = Scope::All,
+            }
+            true
+        }
+        Plan::MatchOut {
+            input,
+            rels,
+            dst_labels,
+            src_prebound,
+            ..
+        }
+        | Plan::MatchOutVarLen {
+            input,
+            rels,
+            dst_labels,
+            src_prebound,
+            ..
+        }
+        | Plan::MatchIn {
+            input,
+            rels,
+            dst_labels,
+            src_prebound,
+            ..
+        }
+        | Plan::MatchUndirected {
+            input,
+            rels,
+            dst_labels,
+            src_prebound,
+            ..
+        } => {
+            set.rel_types.extend_or_all(rels);
+            set.labels.extend_or_all(dst_labels);
+            match input {
+                Some(input) => collect_plan(input, set),
+                None => {
+                    if !src_prebound {
+                        set.labels = Scope::All;
+                    }
+                    true
+                }
+            }
+        }
+        Plan::MatchBoundRel {
+            input,
+            rels,
+            dst_labels,
+            ..
+        } => {
+            set.rel_types.extend_or_all(rels);
+            set.labels.extend_or_all(dst_labels);
+            collect_plan(input, set)
+        }
+        Plan::Filter { input, predicate } => {
+            collect_expression(predicate, set) && collect_plan(input, set)
+        }
+        Plan::OptionalWhereFixup {
+            outer, filtered, ..
+        } => collect_plan(outer, set) && collect_plan(filtered, set),
+        Plan::Project { input, projections } => {
+            projections
+                .iter()
+                .all(|(_, expr)| collect_expression(expr, set))
+                && collect_plan(input, set)
+        }
+        Plan::Aggregate {
+            input, aggregates, ..
+        } => {
+            aggregates
+                .iter()
+                .flat_map(|(function, _)| aggregate_arguments(function))
+                .all(|expr| collect_expression(expr, set))
+                && collect_plan(input, set)
+        }
+        Plan::OrderBy { input, items } => {
+            items.iter().all(|(expr, _)| collect_expression(expr, set)) && collect_plan(input, set)
+        }
+        Plan::Skip { input, skip: expr }
+        | Plan::Limit { input, limit: expr }
+        | Plan::Unwind {
+            input,
+            expression: expr,
+            ..
+        } => collect_expression(expr, set) && collect_plan(input, set),
+        Plan::Distinct { input } => collect_plan(input, set),
+        // The file may change under the cache.
+        Plan::LoadCsv { .. } => false,
+        Plan::Union { left, right, .. }
+        | Plan::CartesianProduct { left, right }
+        | Plan::HashJoin { left, right, .. } => collect_plan(left, set) && collect_plan(right, set),
+        Plan::Apply {
+            input, subquery, ..
+        } => collect_plan(input, set) && collect_plan(subquery, set),
+        Plan::IndexSeek {
+            label,
+            value_expr: expr,
+            fallback,
+            ..
+        }
+        | Plan::TextIndexSeek {
+            label,
+            needle_expr: expr,
+            fallback,
+            ..
+        } => {
+            set.labels.add(label);
+            collect_expression(expr, set) && collect_plan(fallback, set)
+        }
+        Plan::CompositeIndexSeek {
+            label,
+            predicates,
+            fallback,
+            ..
+        } => {
+            set.labels.add(label);
+            predicates
+                .iter()
+                .all(|(_, expr)| collect_expression(expr, set))
+                && collect_plan(fallback, set)
+        }
+        Plan::CostChoice { alternatives } => {
+            alternatives.iter().all(|plan| collect_plan(plan, set))
+        }
+        // Procedures may read anything, including state outside the graph.
+        Plan::ProcedureCall { .. }
+        | Plan::Create { .. }
+        | Plan::Delete { .. }
+        | Plan::SetProperty { .. }
+        | Plan::SetPropertiesFromMap { .. }
+        | Plan::SetLabels { .. }
+        | Plan::RemoveProperty { .. }
+        | Plan::RemoveLabels { .. }
+        | Plan::Foreach { .. } => false,
+    }
+}
+
+fn collect_expression(expr: &Expression, set: &mut ReadSet) -> bool {
+    match expr {
+        Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::PropertyAccess(_)
+        | Expression::Parameter(_) => true,
+        Expression::Binary(binary) => {
+            collect_expression(&binary.left, set) && collect_expression(&binary.right, set)
+        }
+        Expression::Unary(unary) => collect_expression(&unary.operand, set),
+        Expression::FunctionCall(call) => {
+            let name = call.name.to_ascii_lowercase();
+            let base = name.split('.').next().unwrap_or_default();
+            // `date()` and `date.statement()` read the clock; `date('...')` does not.
+            let volatile = VOLATILE_FUNCTIONS.contains(&name.as_str())
+                || (CLOCK_FUNCTIONS.contains(&base) && (call.args.is_empty() || name != base));
+            !volatile && call.args.iter().all(|arg| collect_expression(arg, set))
+        }
+        Expression::Case(case) => case
+            .expression
+            .iter()
+            .chain(case.else_expression.iter())
+            .chain(
+                case.when_clauses
+                    .iter()
+                    .flat_map(|(when, then)| [when, then]),
+            )
+            .all(|expr| collect_expression(expr, set)),
+        Expression::List(items) => items.iter().all(|item| collect_expression(item, set)),
+        Expression::ListComprehension(comprehension) => {
+            collect_expression(&comprehension.list, set)
+                && comprehension
+                    .where_expression
+                    .iter()
+                    .chain(comprehension.map_expression.iter())
+                    .all(|expr| collect_expression(expr, set))
+        }
+        Expression::Map(map) => map
+            .properties
+            .iter()
+            .all(|pair| collect_expression(&pair.value, set)),
+        // Patterns inside expressions may reach any node.
+        Expression::Exists(exists) => match **exists {
+            ExistsExpression::Pattern(_) => {
+                set.labels = Scope::All;
+                set.rel_types = Scope::All;
+                true
+            }
+            ExistsExpression::Subquery(_) => false,
+        },
+        Expression::Subquery(_) => false,
+        Expression::PatternComprehension(comprehension) => {
+            set.labels = Scope::All;
+            set.rel_types = Scope::All;
+            collect_expression(&comprehension.projection, set)
+                && comprehension
+                    .where_expression
+                    .iter()
+                    .all(|expr| collect_expression(expr, set))
+        }
+    }
+}
+
+fn aggregate_arguments(function: &AggregateFunction) -> Vec<&Expression> {
+    match function {
+        AggregateFunction::Count(expr) => expr.iter().collect(),
+        AggregateFunction::CountDistinct(expr)
+        | AggregateFunction::Sum(expr)
+        | AggregateFunction::SumDistinct(expr)
+        | AggregateFunction::Avg(expr)
+        | AggregateFunction::AvgDistinct(expr)
+        | AggregateFunction::Min(expr)
+        | AggregateFunction::MinDistinct(expr)
+        | AggregateFunction::Max(expr)
+        | AggregateFunction::MaxDistinct(expr)
+        | AggregateFunction::Collect(expr)
+        | AggregateFunction::CollectDistinct(expr) => vec![expr],
+        AggregateFunction::PercentileDisc(expr, percentile)
+        | AggregateFunction::PercentileCont(expr, percentile) => vec![expr, percentile],
+        AggregateFunction::CountDistinctApprox(expr, precision)
+        | AggregateFunction::HllSketch(expr, precision) => {
+            std::iter::once(expr).chain(precision).collect()
+        }
+        AggregateFunction::HllMerge(expr) => vec![expr],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_api::prepare;
+
+    fn names(scope: &Scope) -> Option<Vec<&str>> {
+        scope
+            .names()
+            .map(|names| names.iter().map(String::as_str).collect())
+    }
+
+    #[test]
+    fn read_sets_name_labels_and_rel_types() {
+        let set = prepare("MATCH (u:User)-[:KNOWS]->(f:User) RETURN f.name")
+            .unwrap()
+            .read_set()
+            .unwrap();
+        assert_eq!(names(&set.labels), Some(vec!["User"]));
+        assert_eq!(names(&set.rel_types), Some(vec!["KNOWS"]));
+
+        let set = prepare("MATCH (u:User)-->(f) RETURN count(f)")
+            .unwrap()
+            .read_set()
+            .unwrap();
+        assert_eq!(set.labels, Scope::All);
+        assert_eq!(set.rel_types, Scope::All);
+
+        for uncacheable in [
+            "CREATE (:User)",
+            "MATCH (u:User) RETURN rand() AS r",
+            "RETURN datetime() AS now",
+            "CALL db.labels() YIELD label RETURN label",
+            "EXPLAIN MATCH (u:User) RETURN u",
+        ] {
+            assert!(
+                prepare(uncacheable).unwrap().read_set().is_none(),
+                "{uncacheable}"
+            );
+        }
+        assert!(
+            prepare("RETURN datetime('2024-01-01T00:00:00Z') AS t")
+                .unwrap()
+                .read_set()
+                .is_some()
+        );
+    }
+}
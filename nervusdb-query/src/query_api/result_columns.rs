@@ -0,0 +1,48 @@
+//! The columns a query's rows carry, known before it runs.
+
+use super::{Plan, PreparedQuery};
+
+pub(super) fn result_columns(query: &PreparedQuery) -> Option<Vec<String>> {
+    if query.explain.is_some() {
+        return Some(names(&["plan"]));
+    }
+    if query.profile {
+        return Some(names(&["plan", "rows", "operators"]));
+    }
+    plan_columns(&query.plan)
+}
+
+fn plan_columns(plan: &Plan) -> Option<Vec<String>> {
+    match plan {
+        Plan::Project { projections, .. } => {
+            Some(projections.iter().map(|(alias, _)| alias.clone()).collect())
+        }
+        // Both sides of a UNION return the same columns.
+        Plan::Skip { input, .. }
+        | Plan::Limit { input, .. }
+        | Plan::OrderBy { input, .. }
+        | Plan::Distinct { input }
+        | Plan::Union { left: input, .. } => plan_columns(input),
+        Plan::ProcedureCall { name, yields, .. } => {
+            if !yields.is_empty() {
+                return Some(
+                    yields
+                        .iter()
+                        .map(|(field, alias)| alias.as_ref().unwrap_or(field).clone())
+                        .collect(),
+                );
+            }
+            // Without YIELD the rows carry every output of the procedure,
+            // which only registered procedures declare.
+            crate::executor::custom_procedure_signature(&name.join("."))
+                .map(|signature| signature.outputs)
+        }
+        // Without RETURN a query yields no rows to describe.
+        _ if super::plan_contains_write(plan) => Some(Vec::new()),
+        _ => None,
+    }
+}
+
+fn names(columns: &[&str]) -> Vec<String> {
+    columns.iter().map(|column| column.to_string()).collect()
+}
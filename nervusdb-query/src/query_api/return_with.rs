@@ -47,8 +47,14 @@ fn validate_skip_or_limit_expression(expr: &Expression) -> Result<()> {
                 "syntax error: NegativeIntegerArgument".to_string(),
             ));
         }
+        // Other expressions, such as `$offset` or `($page - 1) * $size`, are
+        // checked when the query runs.
+        Expression::Unary(unary) if matches!(unary.operand, Expression::Literal(_)) => {
+            return Err(Error::Other(
+                "syntax error: InvalidArgumentType".to_string(),
+            ));
+        }
         Expression::Literal(Literal::Float(_))
-        | Expression::Unary(_)
         | Expression::Literal(Literal::Boolean(_) | Literal::String(_) | Literal::Null)
         | Expression::Map(_)
         | Expression::List(_) => {
@@ -0,0 +1,245 @@
+//! `COUNT { ... }` and `CALL { ... }` subqueries used as values.
+//!
+//! The subquery is compiled on top of the outer row, like an `EXISTS`
+//! subquery, and run once per outer row. A subquery that mentions none of
+//! the outer row's variables gives the same value for every row, so its
+//! value is cached for the rest of the execution.
+
+use super::{Params, compile_m3_plan, plan_contains_write};
+use crate::ast::{
+    CallClause, Clause, ExistsExpression, Expression, PathElement, Pattern, PropertyMap, Query,
+    SubqueryExpression, SubqueryKind,
+};
+use crate::error::{Error, Result};
+use crate::executor::{Plan, Row, Value, execute_plan};
+use nervusdb_api::GraphSnapshot;
+use std::collections::{HashSet, VecDeque};
+
+/// A cached value of an uncorrelated subquery. The subquery is kept to tell
+/// apart expressions that came to share an address.
+#[derive(Debug)]
+pub(super) struct CachedSubquery {
+    pub(super) key: (usize, usize),
+    pub(super) subquery: SubqueryExpression,
+    pub(super) value: Value,
+}
+
+pub(crate) fn evaluate_subquery<S: GraphSnapshot>(
+    subquery: &SubqueryExpression,
+    outer_row: &Row,
+    snapshot: &S,
+    params: &Params,
+) -> Result<Value> {
+    let correlated = is_correlated(&subquery.query, outer_row);
+    let key = (
+        subquery as *const SubqueryExpression as usize,
+        snapshot as *const S as usize,
+    );
+    if !correlated && let Some(value) = params.cached_subquery(key, subquery) {
+        return Ok(value);
+    }
+
+    let mut merge_subclauses = VecDeque::new();
+    let compiled = compile_m3_plan(
+        subquery.query.clone(),
+        &mut merge_subclauses,
+        Some(Plan::Values {
+            rows: vec![outer_row.clone()],
+        }),
+    )?;
+    if plan_contains_write(&compiled.plan) {
+        return Err(Error::Other(
+            "syntax error: InvalidClauseComposition".to_string(),
+        ));
+    }
+
+    let mut rows = execute_plan(snapshot, &compiled.plan, params);
+    let value = match subquery.kind {
+        SubqueryKind::Count => {
+            let mut count = 0i64;
+            for row in rows {
+                row?;
+                count += 1;
+            }
+            Value::Int(count)
+        }
+        SubqueryKind::Scalar => match rows.next() {
+            Some(row) => scalar_column(&row?, &subquery.query),
+            None => Value::Null,
+        },
+    };
+
+    if !correlated {
+        params.cache_subquery(CachedSubquery {
+            key,
+            subquery: subquery.clone(),
+            value: value.clone(),
+        });
+    }
+    Ok(value)
+}
+
+/// The value a scalar subquery returns: its only column, which the parser
+/// guarantees the final `RETURN` has.
+fn scalar_column(row: &Row, query: &Query) -> Value {
+    let alias = match query.clauses.last() {
+        Some(Clause::Return(ret)) => ret.items.first().and_then(|item| item.alias.as_deref()),
+        _ => None,
+    };
+    alias
+        .and_then(|alias| row.get(alias))
+        .or_else(|| row.columns().last().map(|(_, value)| value))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+/// Whether `query` may read any of the outer row's variables. Names bound
+/// inside the subquery count too, so this errs towards correlated.
+fn is_correlated(query: &Query, outer_row: &Row) -> bool {
+    if outer_row.columns().is_empty() {
+        return false;
+    }
+    let mut names = HashSet::new();
+    collect_query_names(query, &mut names);
+    outer_row
+        .columns()
+        .iter()
+        .any(|(name, _)| names.contains(name))
+}
+
+/// Collects every variable name `query` mentions. Write clauses are skipped:
+/// a subquery containing one fails to compile and is never cached.
+fn collect_query_names(query: &Query, names: &mut HashSet<String>) {
+    for clause in &query.clauses {
+        match clause {
+            Clause::Match(m) => {
+                for pattern in &m.patterns {
+                    collect_pattern_names(pattern, names);
+                }
+            }
+            Clause::Unwind(u) => {
+                collect_expr_names(&u.expression, names);
+                names.insert(u.alias.clone());
+            }
+            Clause::LoadCsv(l) => {
+                collect_expr_names(&l.source, names);
+                names.insert(l.alias.clone());
+            }
+            Clause::Where(w) => collect_expr_names(&w.expression, names),
+            Clause::With(w) => {
+                for item in &w.items {
+                    collect_expr_names(&item.expression, names);
+                }
+                if let Some(where_clause) = &w.where_clause {
+                    collect_expr_names(&where_clause.expression, names);
+                }
+                for item in w.order_by.iter().flat_map(|o| &o.items) {
+                    collect_expr_names(&item.expression, names);
+                }
+            }
+            Clause::Return(r) => {
+                for item in &r.items {
+                    collect_expr_names(&item.expression, names);
+                }
+                for item in r.order_by.iter().flat_map(|o| &o.items) {
+                    collect_expr_names(&item.expression, names);
+                }
+            }
+            Clause::Call(CallClause::Subquery(inner)) => collect_query_names(inner, names),
+            Clause::Call(CallClause::Procedure(call)) => {
+                for arg in &call.arguments {
+                    collect_expr_names(arg, names);
+                }
+            }
+            Clause::Union(u) => collect_query_names(&u.query, names),
+            Clause::Create(_)
+            | Clause::Merge(_)
+            | Clause::Set(_)
+            | Clause::Remove(_)
+            | Clause::Delete(_)
+            | Clause::Foreach(_) => {}
+        }
+    }
+}
+
+fn collect_pattern_names(pattern: &Pattern, names: &mut HashSet<String>) {
+    names.extend(pattern.variable.clone());
+    for element in &pattern.elements {
+        let (variable, properties) = match element {
+            PathElement::Node(node) => (&node.variable, &node.properties),
+            PathElement::Relationship(rel) => (&rel.variable, &rel.properties),
+        };
+        names.extend(variable.clone());
+        if let Some(properties) = properties {
+            collect_map_names(properties, names);
+        }
+    }
+}
+
+fn collect_map_names(map: &PropertyMap, names: &mut HashSet<String>) {
+    for pair in &map.properties {
+        collect_expr_names(&pair.value, names);
+    }
+}
+
+fn collect_expr_names(expr: &Expression, names: &mut HashSet<String>) {
+    match expr {
+        Expression::Variable(name) => {
+            names.insert(name.clone());
+        }
+        Expression::PropertyAccess(pa) => {
+            names.insert(pa.variable.clone());
+        }
+        Expression::Binary(b) => {
+            collect_expr_names(&b.left, names);
+            collect_expr_names(&b.right, names);
+        }
+        Expression::Unary(u) => collect_expr_names(&u.operand, names),
+        Expression::FunctionCall(f) => {
+            for arg in &f.args {
+                collect_expr_names(arg, names);
+            }
+        }
+        Expression::Case(case) => {
+            if let Some(test) = &case.expression {
+                collect_expr_names(test, names);
+            }
+            for (when, then) in &case.when_clauses {
+                collect_expr_names(when, names);
+                collect_expr_names(then, names);
+            }
+            if let Some(otherwise) = &case.else_expression {
+                collect_expr_names(otherwise, names);
+            }
+        }
+        Expression::Exists(exists) => match exists.as_ref() {
+            ExistsExpression::Pattern(pattern) => collect_pattern_names(pattern, names),
+            ExistsExpression::Subquery(query) => collect_query_names(query, names),
+        },
+        Expression::Subquery(inner) => collect_query_names(&inner.query, names),
+        Expression::List(items) => {
+            for item in items {
+                collect_expr_names(item, names);
+            }
+        }
+        Expression::ListComprehension(lc) => {
+            names.insert(lc.variable.clone());
+            collect_expr_names(&lc.list, names);
+            if let Some(where_expr) = &lc.where_expression {
+                collect_expr_names(where_expr, names);
+            }
+            if let Some(map_expr) = &lc.map_expression {
+                collect_expr_names(map_expr, names);
+            }
+        }
+        Expression::PatternComprehension(pc) => {
+            collect_pattern_names(&pc.pattern, names);
+            if let Some(where_expr) = &pc.where_expression {
+                collect_expr_names(where_expr, names);
+            }
+            collect_expr_names(&pc.projection, names);
+        }
+        Expression::Map(map) => collect_map_names(map, names),
+        Expression::Literal(_) | Expression::Parameter(_) => {}
+    }
+}
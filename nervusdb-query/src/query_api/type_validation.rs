@@ -1,4 +1,5 @@
 use super::{BinaryOperator, Clause, Error, Expression, Literal, Result};
+use crate::executor::custom_function;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum StaticScalarKind {
@@ -30,6 +31,7 @@ fn is_definitely_non_boolean(expr: &Expression) -> bool {
             | BinaryOperator::StartsWith
             | BinaryOperator::EndsWith
             | BinaryOperator::Contains
+            | BinaryOperator::RegexMatch
             | BinaryOperator::HasLabel
             | BinaryOperator::IsNull
             | BinaryOperator::IsNotNull => false,
@@ -46,6 +48,7 @@ fn is_definitely_non_boolean(expr: &Expression) -> bool {
         | Expression::FunctionCall(_)
         | Expression::Case(_)
         | Expression::Exists(_)
+        | Expression::Subquery(_)
         | Expression::ListComprehension(_)
         | Expression::PatternComprehension(_) => false,
     }
@@ -144,7 +147,10 @@ fn expression_references_variable(expr: &Expression, variable: &str) -> bool {
                     .as_ref()
                     .is_some_and(|expr| expression_references_variable(expr, variable))
         }
-        Expression::Exists(_) | Expression::Parameter(_) | Expression::Literal(_) => false,
+        Expression::Exists(_)
+        | Expression::Subquery(_)
+        | Expression::Parameter(_)
+        | Expression::Literal(_) => false,
     }
 }
 
@@ -214,6 +220,7 @@ fn expression_uses_variable_in_numeric_context(expr: &Expression, variable: &str
                     .is_some_and(|expr| expression_uses_variable_in_numeric_context(expr, variable))
         }
         Expression::Exists(_)
+        | Expression::Subquery(_)
         | Expression::Parameter(_)
         | Expression::Variable(_)
         | Expression::PropertyAccess(_)
@@ -273,7 +280,7 @@ fn validate_reduce_arguments(call: &crate::ast::FunctionCall) -> Result<()> {
     Ok(())
 }
 
-fn is_supported_function_name(name: &str) -> bool {
+pub(crate) fn is_supported_function_name(name: &str) -> bool {
     let lower = name.to_ascii_lowercase();
     if lower.starts_with("__quant_") {
         return true;
@@ -290,6 +297,9 @@ fn is_supported_function_name(name: &str) -> bool {
             | "collect"
             | "percentiledisc"
             | "percentilecont"
+            | "countdistinctapprox"
+            | "hllsketch"
+            | "hllmerge"
             // Collections / list / map helpers
             | "size"
             | "head"
@@ -325,8 +335,13 @@ fn is_supported_function_name(name: &str) -> bool {
             | "log"
             | "e"
             | "pi"
+            | "hllcount"
             | "tointeger"
             | "tofloat"
+            | "tointegerarray"
+            | "tofloatarray"
+            | "iri"
+            | "curie"
             | "toboolean"
             // Graph helpers
             | "startnode"
@@ -371,6 +386,7 @@ fn is_supported_function_name(name: &str) -> bool {
             | "__index"
             | "__slice"
             | "__getprop"
+            | "__map_projection"
             | "__distinct"
             | "__reduce"
             | "__nervus_singleton_path"
@@ -415,7 +431,14 @@ pub(super) fn validate_expression_types(expr: &Expression) -> Result<()> {
                 validate_expression_types(arg)?;
             }
             if !is_supported_function_name(&call.name) {
-                return Err(Error::Other("syntax error: UnknownFunction".to_string()));
+                let Some((signature, _)) = custom_function(&call.name.to_lowercase()) else {
+                    return Err(Error::Other("syntax error: UnknownFunction".to_string()));
+                };
+                if signature.inputs.len() != call.args.len() {
+                    return Err(Error::Other(
+                        "syntax error: InvalidNumberOfArguments".to_string(),
+                    ));
+                }
             }
             validate_quantifier_argument_types(call)?;
             validate_reduce_arguments(call)?;
@@ -522,33 +545,39 @@ pub(super) fn validate_expression_types(expr: &Expression) -> Result<()> {
                     }
                 }
                 crate::ast::ExistsExpression::Subquery(subquery) => {
-                    for clause in &subquery.clauses {
-                        match clause {
-                            Clause::Where(w) => validate_expression_types(&w.expression)?,
-                            Clause::With(w) => {
-                                for item in &w.items {
-                                    validate_expression_types(&item.expression)?;
-                                }
-                                if let Some(where_clause) = &w.where_clause {
-                                    validate_expression_types(&where_clause.expression)?;
-                                }
-                            }
-                            Clause::Return(r) => {
-                                for item in &r.items {
-                                    validate_expression_types(&item.expression)?;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
+                    validate_subquery_types(subquery)?;
                 }
             }
             Ok(())
         }
+        Expression::Subquery(subquery) => validate_subquery_types(&subquery.query),
         _ => Ok(()),
     }
 }
 
+fn validate_subquery_types(subquery: &crate::ast::Query) -> Result<()> {
+    for clause in &subquery.clauses {
+        match clause {
+            Clause::Where(w) => validate_expression_types(&w.expression)?,
+            Clause::With(w) => {
+                for item in &w.items {
+                    validate_expression_types(&item.expression)?;
+                }
+                if let Some(where_clause) = &w.where_clause {
+                    validate_expression_types(&where_clause.expression)?;
+                }
+            }
+            Clause::Return(r) => {
+                for item in &r.items {
+                    validate_expression_types(&item.expression)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::validate_expression_types;
@@ -134,6 +134,7 @@ fn ensure_no_aggregation_functions(expr: &Expression) -> Result<()> {
                 // `EXISTS { ... WITH n, count(*) AS c WHERE c > 0 ... }`.
             }
         },
+        Expression::Subquery(_) => {}
         Expression::Variable(_)
         | Expression::PropertyAccess(_)
         | Expression::Parameter(_)
@@ -314,7 +315,10 @@ fn validate_where_expression_variables(
 
             local_scopes.pop();
         }
-        Expression::Exists(_) | Expression::Parameter(_) | Expression::Literal(_) => {}
+        Expression::Exists(_)
+        | Expression::Subquery(_)
+        | Expression::Parameter(_)
+        | Expression::Literal(_) => {}
     }
     Ok(())
 }
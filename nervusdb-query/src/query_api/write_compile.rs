@@ -145,3 +145,13 @@ pub(super) fn compile_delete_plan_v2(
         expressions: delete.expressions,
     })
 }
+
+pub(super) fn compile_load_csv_plan(input: Plan, load: crate::ast::LoadCsvClause) -> Plan {
+    Plan::LoadCsv {
+        input: Box::new(input),
+        source: load.source,
+        alias: load.alias,
+        with_headers: load.with_headers,
+        field_terminator: load.field_terminator.map_or(b',', |c| c as u8),
+    }
+}
@@ -0,0 +1,265 @@
+//! Cache of read query results keyed by query text and parameters.
+//!
+//! Each entry keeps the [`ReadSet`] of its query and the write sequence the
+//! store was at before the result was computed. On lookup the caller checks
+//! with the store whether a later commit touched anything in the read set,
+//! so a write to `:Order` nodes leaves cached `:User` dashboards alone.
+//! Entries also expire after an optional TTL, and the least recently used
+//! are evicted beyond the capacity. A capacity of zero disables the cache.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::executor::Row;
+use crate::query_api::{Params, ReadSet};
+
+/// Identifies a cached result: hashes of the query text and of the
+/// parameter values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResultKey {
+    query: u64,
+    params: u64,
+}
+
+impl ResultKey {
+    pub fn new(cypher: &str, params: &Params) -> Self {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        cypher.hash(&mut hasher);
+        Self {
+            query: hasher.finish(),
+            params: params.fingerprint(),
+        }
+    }
+}
+
+/// Counters reported by [`ResultCache::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResultCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Lookups that found an entry invalidated by a write or expired.
+    pub invalidations: u64,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+/// A bounded, least-recently-used map from [`ResultKey`] to result rows.
+#[derive(Debug)]
+pub struct ResultCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    capacity: usize,
+    ttl: Option<Duration>,
+    clock: u64,
+    entries: HashMap<ResultKey, CacheEntry>,
+    hits: u64,
+    misses: u64,
+    invalidations: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    rows: Arc<Vec<Row>>,
+    read_set: ReadSet,
+    write_seq: u64,
+    created: Instant,
+    last_used: u64,
+}
+
+impl ResultCache {
+    /// A cache holding up to `capacity` results, each for at most `ttl`
+    /// (`None` keeps them until a write invalidates them).
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            state: Mutex::new(CacheState {
+                capacity,
+                ttl,
+                clock: 0,
+                entries: HashMap::new(),
+                hits: 0,
+                misses: 0,
+                invalidations: 0,
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock().capacity > 0
+    }
+
+    /// Returns the rows cached under `key` unless they expired or
+    /// `changed_since(read_set, write_seq)` reports a write they depend on.
+    pub fn get(
+        &self,
+        key: ResultKey,
+        changed_since: impl FnOnce(&ReadSet, u64) -> bool,
+    ) -> Option<Arc<Vec<Row>>> {
+        let mut state = self.lock();
+        state.clock += 1;
+        let now = state.clock;
+        let ttl = state.ttl;
+        let Some(entry) = state.entries.get_mut(&key) else {
+            state.misses += 1;
+            return None;
+        };
+        let expired = ttl.is_some_and(|ttl| entry.created.elapsed() >= ttl);
+        if expired || changed_since(&entry.read_set, entry.write_seq) {
+            state.entries.remove(&key);
+            state.invalidations += 1;
+            state.misses += 1;
+            return None;
+        }
+        entry.last_used = now;
+        let rows = Arc::clone(&entry.rows);
+        state.hits += 1;
+        Some(rows)
+    }
+
+    /// Caches `rows`, computed from a snapshot taken after the store was at
+    /// `write_seq`, for a query reading `read_set`.
+    pub fn insert(&self, key: ResultKey, read_set: ReadSet, write_seq: u64, rows: Arc<Vec<Row>>) {
+        let mut state = self.lock();
+        if state.capacity == 0 {
+            return;
+        }
+        if state.entries.len() >= state.capacity && !state.entries.contains_key(&key) {
+            state.evict_lru();
+        }
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(
+            key,
+            CacheEntry {
+                rows,
+                read_set,
+                write_seq,
+                created: Instant::now(),
+                last_used,
+            },
+        );
+    }
+
+    /// Changes the limits, evicting least-recently-used results if the
+    /// capacity shrank.
+    pub fn set_limits(&self, capacity: usize, ttl: Option<Duration>) {
+        let mut state = self.lock();
+        state.capacity = capacity;
+        state.ttl = ttl;
+        while state.entries.len() > capacity {
+            state.evict_lru();
+        }
+    }
+
+    /// Drops every cached result.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    pub fn stats(&self) -> ResultCacheStats {
+        let state = self.lock();
+        ResultCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            invalidations: state.invalidations,
+            len: state.entries.len(),
+            capacity: state.capacity,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        // The state is a plain map; a panic mid-update cannot leave it inconsistent.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for ResultCache {
+    /// A disabled cache.
+    fn default() -> Self {
+        Self::new(0, None)
+    }
+}
+
+impl CacheState {
+    fn evict_lru(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+    use crate::query_api::prepare;
+
+    fn rows(n: i64) -> Arc<Vec<Row>> {
+        Arc::new(vec![Row::new(vec![("n".to_string(), Value::Int(n))])])
+    }
+
+    #[test]
+    fn entries_are_keyed_by_params_and_dropped_when_stale() {
+        let cache = ResultCache::new(2, None);
+        let cypher = "MATCH (u:User) WHERE u.age > $age RETURN count(u) AS n";
+        let read_set = prepare(cypher).unwrap().read_set().unwrap();
+        let mut young = Params::new();
+        young.insert("age", Value::Int(18));
+        let mut old = Params::new();
+        old.insert("age", Value::Int(65));
+
+        let key = ResultKey::new(cypher, &young);
+        assert_ne!(key, ResultKey::new(cypher, &old));
+        cache.insert(key, read_set.clone(), 7, rows(3));
+        assert_eq!(cache.get(key, |_, seq| seq != 7), Some(rows(3)));
+        assert!(
+            cache
+                .get(ResultKey::new(cypher, &old), |_, _| false)
+                .is_none()
+        );
+        assert!(cache.get(key, |_, _| true).is_none());
+        assert!(cache.get(key, |_, _| false).is_none());
+        assert_eq!(
+            cache.stats(),
+            ResultCacheStats {
+                hits: 1,
+                misses: 3,
+                invalidations: 1,
+                len: 0,
+                capacity: 2
+            }
+        );
+    }
+
+    #[test]
+    fn capacity_and_ttl_bound_the_cache() {
+        let cache = ResultCache::new(2, None);
+        let read_set = prepare("RETURN 1 AS n").unwrap().read_set().unwrap();
+        let keys: Vec<_> = ["RETURN 1", "RETURN 2", "RETURN 3"]
+            .iter()
+            .map(|q| ResultKey::new(q, &Params::new()))
+            .collect();
+        cache.insert(keys[0], read_set.clone(), 0, rows(1));
+        cache.insert(keys[1], read_set.clone(), 0, rows(2));
+        assert!(cache.get(keys[0], |_, _| false).is_some());
+        cache.insert(keys[2], read_set.clone(), 0, rows(3));
+        assert!(cache.get(keys[1], |_, _| false).is_none());
+        assert_eq!(cache.stats().len, 2);
+
+        cache.set_limits(2, Some(Duration::ZERO));
+        assert!(cache.get(keys[0], |_, _| false).is_none());
+        cache.set_limits(0, None);
+        cache.insert(keys[0], read_set, 0, rows(1));
+        assert_eq!(cache.stats().len, 0);
+        assert!(!cache.is_enabled());
+    }
+}
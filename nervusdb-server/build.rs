@@ -0,0 +1,69 @@
+//! Generates the gRPC client and server stubs for `proto/nervusdb.proto`.
+//!
+//! The messages are written out in `src/grpc/pb.rs`, so building needs no
+//! `protoc`; only the service is described here, method for method.
+//! `tests/proto_sync.rs` fails when either drifts from the .proto.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    fn method(
+        name: &str,
+        route: &str,
+        input: &str,
+        output: &str,
+    ) -> tonic_build::manual::MethodBuilder {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("crate::grpc::pb::{input}"))
+            .output_type(format!("crate::grpc::pb::{output}"))
+            .codec_path("tonic_prost::ProstCodec")
+    }
+
+    pub fn generate() {
+        let service = Service::builder()
+            .name("NervusDb")
+            .package("nervusdb.v1")
+            .method(method("open", "Open", "OpenRequest", "OpenResponse").build())
+            .method(
+                method("query", "Query", "QueryRequest", "ResultFrame")
+                    .server_streaming()
+                    .build(),
+            )
+            .method(
+                method(
+                    "execute_write",
+                    "ExecuteWrite",
+                    "QueryRequest",
+                    "ResultFrame",
+                )
+                .server_streaming()
+                .build(),
+            )
+            .method(
+                method("bulk_load", "BulkLoad", "BulkLoadBatch", "BulkLoadSummary")
+                    .client_streaming()
+                    .build(),
+            )
+            .method(
+                method(
+                    "vector_search",
+                    "VectorSearch",
+                    "VectorSearchRequest",
+                    "VectorHit",
+                )
+                .server_streaming()
+                .build(),
+            )
+            .build();
+        Builder::new().compile(&[service]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Query execution shared by the Bolt and HTTP front ends.
+
+use nervusdb::{Bookmark, Db};
+use nervusdb_query::{Params, PreparedQuery, Row, Value};
+use std::collections::HashMap;
+
+/// What an engine error is about, judged by its message as the C API does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorKind {
+    Syntax,
+    Constraint,
+    ResourceLimit,
+    Execution,
+}
+
+impl ErrorKind {
+    pub(crate) fn of(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        if lower.contains("syntax")
+            || lower.contains("parse")
+            || lower.contains("unexpected token")
+            || lower.contains("unexpected character")
+            || lower.starts_with("expected ")
+        {
+            ErrorKind::Syntax
+        } else if lower.contains("constraint violation") {
+            ErrorKind::Constraint
+        } else if lower.contains("resourcelimitexceeded") {
+            ErrorKind::ResourceLimit
+        } else {
+            ErrorKind::Execution
+        }
+    }
+}
+
+/// Runs a write statement in auto-commit mode and returns its reified rows,
+/// how many writes it made and the bookmark of its (last) commit. `USING PERIODIC COMMIT` commits each batch
+/// on its own and returns no rows; anything else runs in one transaction.
+pub(crate) fn auto_commit_write(
+    db: &Db,
+    prepared: &PreparedQuery,
+    params: &Params,
+) -> Result<(Vec<Row>, u32, Bookmark), String> {
+    let mut rows = Vec::new();
+    let mut commit_error = None;
+    let mut committed = None;
+    let writes = prepared
+        .execute_periodic(params, |params| {
+            let snapshot = db.snapshot();
+            let mut txn = db.begin_write();
+            let (batch_rows, write_count) = prepared.execute_mixed(&snapshot, &mut txn, params)?;
+            if prepared.periodic_commit().is_none() {
+                let staged = db.snapshot_in(&txn);
+                rows = mixed_rows(batch_rows, prepared)
+                    .iter()
+                    .map(|row| row.reify(&staged))
+                    .collect::<nervusdb_query::Result<_>>()?;
+            }
+            let bookmark = txn.commit_with_bookmark().map_err(|e| {
+                let message = e.to_string();
+                commit_error = Some(message.clone());
+                nervusdb_query::Error::Other(message)
+            })?;
+            committed = Some(bookmark);
+            Ok(write_count)
+        })
+        .map_err(|e| commit_error.take().unwrap_or(e.to_string()))?;
+    // A periodic commit over no input commits nothing.
+    Ok((
+        rows,
+        writes,
+        committed.unwrap_or_else(|| db.last_bookmark()),
+    ))
+}
+
+/// Rows of `execute_mixed`, which come back as maps, in column order.
+pub(crate) fn mixed_rows(rows: Vec<HashMap<String, Value>>, prepared: &PreparedQuery) -> Vec<Row> {
+    let columns = prepared.columns();
+    rows.into_iter()
+        .map(|mut row| match &columns {
+            Some(columns) => Row::new(
+                columns
+                    .iter()
+                    .map(|name| (name.clone(), row.remove(name).unwrap_or(Value::Null)))
+                    .collect(),
+            ),
+            None => Row::new(row.into_iter().collect()),
+        })
+        .collect()
+}
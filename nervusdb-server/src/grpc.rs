@@ -0,0 +1,527 @@
+//! A gRPC service for NervusDB (the `grpc` feature).
+//!
+//! `proto/nervusdb.proto` defines the service; generate clients for other
+//! languages from it. [`pb`] holds its messages along with the generated
+//! `NervusDbServer` and, for Rust callers, `NervusDbClient`.
+//!
+//! - `Open` reports the server version and the last committed txid.
+//! - `Query` streams a read statement's rows from a snapshot as they
+//!   execute. `ExecuteWrite` commits first, then streams what the statement
+//!   returned. Both send a header with the column names, the rows, and a
+//!   summary.
+//! - `BulkLoad` takes a stream of node and edge batches and commits each in
+//!   a write transaction of its own. Batches committed before a failure stay
+//!   committed.
+//! - `VectorSearch` streams the nearest nodes, optionally filtered by label
+//!   and property.
+//!
+//! Given an [`Auth`], every call must carry its credentials as
+//! `authorization: Basic ...` metadata; calls without them fail with
+//! `UNAUTHENTICATED` before they reach the database.
+//!
+//! Failures end the call with a status: `INVALID_ARGUMENT` for syntax
+//! errors and malformed requests, `FAILED_PRECONDITION` for constraint
+//! violations, `RESOURCE_EXHAUSTED` for resource limits, and `UNKNOWN` for
+//! other execution errors.
+//!
+//! ```no_run
+//! use nervusdb::Db;
+//! use std::sync::Arc;
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let db = Arc::new(Db::open("/tmp/graph.ndb").unwrap());
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:50051").await?;
+//! nervusdb_server::grpc::serve(listener, db, None).await
+//! # }
+//! ```
+
+pub mod pb;
+
+use crate::Auth;
+use crate::exec::{ErrorKind, auto_commit_write};
+use nervusdb::{Db, GraphSnapshot, PropertyValue, VectorFilter, VectorMetric};
+use nervusdb_query::executor::{NodeValue, RelationshipValue};
+use nervusdb_query::{Params, PreparedQuery, Row, Value, prepare};
+use pb::nervus_db_server::{NervusDb, NervusDbServer};
+use pb::result_frame::Frame;
+use pb::value::Kind;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::service::Interceptor;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status, Streaming};
+
+/// Frames buffered ahead of a slow client before the query waits for it.
+const STREAM_BUFFER: usize = 64;
+
+type FrameStream<T> = ReceiverStream<Result<T, Status>>;
+
+/// Serves the service on `listener` until it fails.
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    db: Arc<Db>,
+    auth: Option<Auth>,
+) -> io::Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(service(db, auth))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+        .map_err(io::Error::other)
+}
+
+/// The service, for adding to an existing tonic server. `auth`, when set,
+/// is required of every call.
+pub fn service(
+    db: Arc<Db>,
+    auth: Option<Auth>,
+) -> InterceptedService<NervusDbServer<NervusDbService>, AuthInterceptor> {
+    NervusDbServer::with_interceptor(
+        NervusDbService { db },
+        AuthInterceptor {
+            auth: auth.map(Arc::new),
+        },
+    )
+}
+
+/// Rejects calls whose `authorization` metadata lacks the server's
+/// credentials; lets every call through when there are none.
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    auth: Option<Arc<Auth>>,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(auth) = &self.auth else {
+            return Ok(request);
+        };
+        let accepted = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| auth.accepts_basic(value));
+        if !accepted {
+            return Err(Status::unauthenticated("missing or wrong credentials"));
+        }
+        Ok(request)
+    }
+}
+
+/// Implements the `NervusDb` service over one database.
+pub struct NervusDbService {
+    db: Arc<Db>,
+}
+
+#[tonic::async_trait]
+impl NervusDb for NervusDbService {
+    type QueryStream = FrameStream<pb::ResultFrame>;
+    type ExecuteWriteStream = FrameStream<pb::ResultFrame>;
+    type VectorSearchStream = FrameStream<pb::VectorHit>;
+
+    async fn open(
+        &self,
+        _request: Request<pb::OpenRequest>,
+    ) -> Result<Response<pb::OpenResponse>, Status> {
+        Ok(Response::new(pb::OpenResponse {
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+            bookmark: self.db.last_bookmark().txid(),
+            read_only: self.db.is_read_only(),
+        }))
+    }
+
+    async fn query(
+        &self,
+        request: Request<pb::QueryRequest>,
+    ) -> Result<Response<Self::QueryStream>, Status> {
+        let (prepared, params) = prepare_request(request.into_inner())?;
+        if prepared.is_write() {
+            return Err(Status::invalid_argument(
+                "Query does not accept write statements; use ExecuteWrite",
+            ));
+        }
+        let (frames, receiver) = mpsc::channel(STREAM_BUFFER);
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || {
+            let snapshot = db.snapshot();
+            let mut frames = FrameSender::new(frames, prepared.columns());
+            for row in prepared.execute_streaming(&snapshot, &params) {
+                let row = match row.and_then(|row| row.reify(&snapshot)) {
+                    Ok(row) => row,
+                    Err(e) => return frames.fail(status(e.to_string())),
+                };
+                // A closed channel means the client went away; stop reading.
+                if !frames.row(row) {
+                    return;
+                }
+            }
+            frames.finish(0, db.last_bookmark().txid());
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn execute_write(
+        &self,
+        request: Request<pb::QueryRequest>,
+    ) -> Result<Response<Self::ExecuteWriteStream>, Status> {
+        let (prepared, params) = prepare_request(request.into_inner())?;
+        let db = Arc::clone(&self.db);
+        let (rows, writes, bookmark, columns) = tokio::task::spawn_blocking(move || {
+            let (rows, writes, bookmark) =
+                auto_commit_write(&db, &prepared, &params).map_err(status)?;
+            Ok::<_, Status>((rows, writes, bookmark.txid(), prepared.columns()))
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))??;
+
+        let (frames, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let mut frames = FrameSender::new(frames, columns);
+            for row in rows {
+                if !frames.row(row) {
+                    return;
+                }
+            }
+            frames.finish(u64::from(writes), bookmark);
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn bulk_load(
+        &self,
+        request: Request<Streaming<pb::BulkLoadBatch>>,
+    ) -> Result<Response<pb::BulkLoadSummary>, Status> {
+        let mut batches = request.into_inner();
+        let mut summary = pb::BulkLoadSummary::default();
+        while let Some(batch) = batches.message().await? {
+            let (nodes, edges) = (batch.nodes.len() as u64, batch.edges.len() as u64);
+            let db = Arc::clone(&self.db);
+            tokio::task::spawn_blocking(move || load_batch(&db, batch))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| {
+                    Status::new(
+                        e.code(),
+                        format!("batch {}: {}", summary.batches, e.message()),
+                    )
+                })?;
+            summary.nodes += nodes;
+            summary.edges += edges;
+            summary.batches += 1;
+        }
+        summary.bookmark = self.db.last_bookmark().txid();
+        Ok(Response::new(summary))
+    }
+
+    async fn vector_search(
+        &self,
+        request: Request<pb::VectorSearchRequest>,
+    ) -> Result<Response<Self::VectorSearchStream>, Status> {
+        let request = request.into_inner();
+        let metric = match pb::VectorMetric::try_from(request.metric) {
+            Ok(pb::VectorMetric::L2) => VectorMetric::L2,
+            Ok(pb::VectorMetric::Cosine) => VectorMetric::Cosine,
+            Ok(pb::VectorMetric::Dot) => VectorMetric::Dot,
+            Err(_) => return Err(Status::invalid_argument("unknown vector metric")),
+        };
+        let property = match request.property.as_str() {
+            "" => None,
+            _ => Some((
+                request.property,
+                property_from_proto(request.value.unwrap_or_default())?,
+            )),
+        };
+        let filter = VectorFilter {
+            label: (!request.label.is_empty()).then_some(request.label),
+            property,
+        };
+        let db = Arc::clone(&self.db);
+        let hits = tokio::task::spawn_blocking(move || {
+            db.search_vector_filtered(&request.vector, request.k as usize, metric, &filter)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| status(e.to_string()))?;
+
+        let (frames, receiver) = mpsc::channel(STREAM_BUFFER);
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || {
+            let snapshot = db.snapshot();
+            for (iid, distance) in hits {
+                let node = match Value::NodeId(iid).reify(&snapshot) {
+                    Ok(Value::Node(node)) => Some(node_to_proto(node)),
+                    Ok(_) => None,
+                    Err(e) => {
+                        let _ = frames.blocking_send(Err(status(e.to_string())));
+                        return;
+                    }
+                };
+                let hit = pb::VectorHit {
+                    node,
+                    external_id: snapshot.resolve_external(iid).unwrap_or_default(),
+                    distance,
+                };
+                if frames.blocking_send(Ok(hit)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+/// Sends a statement's header, rows and summary. The header goes out
+/// before the first row, naming the statement's columns or, when those are
+/// only known at run time, the first row's.
+struct FrameSender {
+    frames: mpsc::Sender<Result<pb::ResultFrame, Status>>,
+    columns: Option<Vec<String>>,
+    header_sent: bool,
+    rows: u64,
+}
+
+impl FrameSender {
+    fn new(
+        frames: mpsc::Sender<Result<pb::ResultFrame, Status>>,
+        columns: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            frames,
+            columns,
+            header_sent: false,
+            rows: 0,
+        }
+    }
+
+    /// Sends `row`; false once the client has gone away.
+    fn row(&mut self, row: Row) -> bool {
+        let names = || row.columns().iter().map(|(name, _)| name.clone()).collect();
+        if !self.header(names) {
+            return false;
+        }
+        self.rows += 1;
+        let values = row
+            .columns()
+            .iter()
+            .map(|(_, value)| value_to_proto(value.clone()))
+            .collect();
+        self.send(Frame::Row(pb::Row { values }))
+    }
+
+    fn finish(mut self, writes: u64, bookmark: u64) {
+        if self.header(Vec::new) {
+            self.send(Frame::Summary(pb::Summary {
+                rows: self.rows,
+                writes,
+                bookmark,
+            }));
+        }
+    }
+
+    fn fail(self, status: Status) {
+        let _ = self.frames.blocking_send(Err(status));
+    }
+
+    fn header(&mut self, names: impl FnOnce() -> Vec<String>) -> bool {
+        if self.header_sent {
+            return true;
+        }
+        self.header_sent = true;
+        let columns = self.columns.take().unwrap_or_else(names);
+        self.send(Frame::Header(pb::Header { columns }))
+    }
+
+    fn send(&self, frame: Frame) -> bool {
+        self.frames
+            .blocking_send(Ok(pb::ResultFrame { frame: Some(frame) }))
+            .is_ok()
+    }
+}
+
+/// Classifies an engine error by its message.
+fn status(message: String) -> Status {
+    match ErrorKind::of(&message) {
+        ErrorKind::Syntax => Status::invalid_argument(message),
+        ErrorKind::Constraint => Status::failed_precondition(message),
+        ErrorKind::ResourceLimit => Status::resource_exhausted(message),
+        ErrorKind::Execution => Status::unknown(message),
+    }
+}
+
+fn prepare_request(request: pb::QueryRequest) -> Result<(PreparedQuery, Params), Status> {
+    let prepared = prepare(&request.query).map_err(|e| status(e.to_string()))?;
+    let mut params = Params::new();
+    for (name, value) in request.params {
+        params.insert(name, value_from_proto(value)?);
+    }
+    Ok((prepared, params))
+}
+
+/// Creates a batch's nodes, then its edges, in one transaction.
+fn load_batch(db: &Db, batch: pb::BulkLoadBatch) -> Result<(), Status> {
+    let engine = |e: nervusdb::Error| status(e.to_string());
+    let mut txn = db.begin_write();
+    for node in batch.nodes {
+        let id = node.external_id;
+        let Some((label, extra_labels)) = node.labels.split_first() else {
+            return Err(Status::invalid_argument(format!("node {id} has no label")));
+        };
+        if txn.lookup_node(id).is_some() {
+            return Err(Status::already_exists(format!("node {id} already exists")));
+        }
+        let label = txn.get_or_create_label(label).map_err(engine)?;
+        let iid = txn.create_node(id, label).map_err(engine)?;
+        for label in extra_labels {
+            let label = txn.get_or_create_label(label).map_err(engine)?;
+            txn.add_node_label(iid, label).map_err(engine)?;
+        }
+        for (key, value) in node.properties {
+            txn.set_node_property(iid, key, property_from_proto(value)?)
+                .map_err(engine)?;
+        }
+        if !node.vector.is_empty() {
+            txn.set_vector(iid, node.vector).map_err(engine)?;
+        }
+    }
+    for edge in batch.edges {
+        let endpoint = |id| {
+            txn.lookup_node(id)
+                .ok_or_else(|| Status::not_found(format!("no node {id}")))
+        };
+        let (src, dst) = (
+            endpoint(edge.src_external_id)?,
+            endpoint(edge.dst_external_id)?,
+        );
+        let rel = txn.get_or_create_rel_type(&edge.rel_type).map_err(engine)?;
+        txn.create_edge(src, rel, dst);
+        for (key, value) in edge.properties {
+            txn.set_edge_property(src, rel, dst, key, property_from_proto(value)?)
+                .map_err(engine)?;
+        }
+    }
+    txn.commit().map_err(engine)
+}
+
+fn graph_element() -> Status {
+    Status::invalid_argument("nodes, relationships and paths cannot be sent as values")
+}
+
+fn value_from_proto(value: pb::Value) -> Result<Value, Status> {
+    Ok(match value.kind {
+        None | Some(Kind::Null(_)) => Value::Null,
+        Some(Kind::BoolValue(b)) => Value::Bool(b),
+        Some(Kind::IntValue(i)) => Value::Int(i),
+        Some(Kind::FloatValue(f)) => Value::Float(f),
+        Some(Kind::StringValue(s)) => Value::String(s),
+        Some(Kind::BytesValue(bytes)) => Value::Blob(bytes),
+        Some(Kind::DatetimeValue(micros)) => Value::DateTime(micros),
+        Some(Kind::ListValue(list)) => Value::List(
+            list.values
+                .into_iter()
+                .map(value_from_proto)
+                .collect::<Result<_, _>>()?,
+        ),
+        Some(Kind::MapValue(map)) => Value::Map(
+            map.entries
+                .into_iter()
+                .map(|(key, value)| Ok((key, value_from_proto(value)?)))
+                .collect::<Result<_, Status>>()?,
+        ),
+        Some(_) => return Err(graph_element()),
+    })
+}
+
+fn property_from_proto(value: pb::Value) -> Result<PropertyValue, Status> {
+    Ok(match value.kind {
+        None | Some(Kind::Null(_)) => PropertyValue::Null,
+        Some(Kind::BoolValue(b)) => PropertyValue::Bool(b),
+        Some(Kind::IntValue(i)) => PropertyValue::Int(i),
+        Some(Kind::FloatValue(f)) => PropertyValue::Float(f),
+        Some(Kind::StringValue(s)) => PropertyValue::String(s),
+        Some(Kind::BytesValue(bytes)) => PropertyValue::Blob(bytes),
+        Some(Kind::DatetimeValue(micros)) => PropertyValue::DateTime(micros),
+        Some(Kind::ListValue(list)) => PropertyValue::List(
+            list.values
+                .into_iter()
+                .map(property_from_proto)
+                .collect::<Result<_, _>>()?,
+        ),
+        Some(Kind::MapValue(map)) => PropertyValue::Map(
+            map.entries
+                .into_iter()
+                .map(|(key, value)| Ok((key, property_from_proto(value)?)))
+                .collect::<Result<_, Status>>()?,
+        ),
+        Some(_) => return Err(graph_element()),
+    })
+}
+
+fn value_to_proto(value: Value) -> pb::Value {
+    let kind = match value {
+        Value::Null => Kind::Null(pb::Null {}),
+        Value::Bool(b) => Kind::BoolValue(b),
+        Value::Int(i) => Kind::IntValue(i),
+        Value::Float(f) => Kind::FloatValue(f),
+        Value::String(s) => Kind::StringValue(s),
+        Value::Blob(bytes) => Kind::BytesValue(bytes),
+        Value::DateTime(micros) => Kind::DatetimeValue(micros),
+        Value::List(items) => Kind::ListValue(pb::List {
+            values: items.into_iter().map(value_to_proto).collect(),
+        }),
+        Value::Map(map) => Kind::MapValue(pb::Map {
+            entries: properties_to_proto(map),
+        }),
+        Value::Node(node) => Kind::Node(node_to_proto(node)),
+        Value::Relationship(rel) => Kind::Relationship(relationship_to_proto(rel)),
+        Value::ReifiedPath(path) => Kind::Path(pb::Path {
+            nodes: path.nodes.into_iter().map(node_to_proto).collect(),
+            relationships: path
+                .relationships
+                .into_iter()
+                .map(relationship_to_proto)
+                .collect(),
+        }),
+        Value::NodeId(id) => Kind::NodeId(u64::from(id)),
+        Value::ExternalId(id) => Kind::ExternalId(id),
+        Value::EdgeKey(key) => Kind::EdgeKey(pb::EdgeKey {
+            src: u64::from(key.src),
+            rel: key.rel,
+            dst: u64::from(key.dst),
+        }),
+        Value::Path(path) => Kind::ListValue(pb::List {
+            values: path
+                .nodes
+                .into_iter()
+                .map(|id| pb::Value {
+                    kind: Some(Kind::NodeId(u64::from(id))),
+                })
+                .collect(),
+        }),
+    };
+    pb::Value { kind: Some(kind) }
+}
+
+fn node_to_proto(node: NodeValue) -> pb::Node {
+    pb::Node {
+        id: u64::from(node.id),
+        labels: node.labels,
+        properties: properties_to_proto(node.properties),
+    }
+}
+
+fn relationship_to_proto(rel: RelationshipValue) -> pb::Relationship {
+    pb::Relationship {
+        src: u64::from(rel.key.src),
+        dst: u64::from(rel.key.dst),
+        rel_type: rel.rel_type,
+        properties: properties_to_proto(rel.properties),
+    }
+}
+
+fn properties_to_proto(map: BTreeMap<String, Value>) -> BTreeMap<String, pb::Value> {
+    map.into_iter()
+        .map(|(key, value)| (key, value_to_proto(value)))
+        .collect()
+}
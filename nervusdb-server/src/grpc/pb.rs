@@ -0,0 +1,245 @@
+//! The messages of `proto/nervusdb.proto`, plus the client and server
+//! stubs `build.rs` generates for its service.
+
+use std::collections::BTreeMap;
+
+include!(concat!(env!("OUT_DIR"), "/nervusdb.v1.NervusDb.rs"));
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OpenRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OpenResponse {
+    #[prost(string, tag = "1")]
+    pub server_version: String,
+    #[prost(uint64, tag = "2")]
+    pub bookmark: u64,
+    #[prost(bool, tag = "3")]
+    pub read_only: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryRequest {
+    #[prost(string, tag = "1")]
+    pub query: String,
+    #[prost(btree_map = "string, message", tag = "2")]
+    pub params: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ResultFrame {
+    #[prost(oneof = "result_frame::Frame", tags = "1, 2, 3")]
+    pub frame: Option<result_frame::Frame>,
+}
+
+pub mod result_frame {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Frame {
+        #[prost(message, tag = "1")]
+        Header(super::Header),
+        #[prost(message, tag = "2")]
+        Row(super::Row),
+        #[prost(message, tag = "3")]
+        Summary(super::Summary),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Header {
+    #[prost(string, repeated, tag = "1")]
+    pub columns: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Row {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Summary {
+    #[prost(uint64, tag = "1")]
+    pub rows: u64,
+    #[prost(uint64, tag = "2")]
+    pub writes: u64,
+    #[prost(uint64, tag = "3")]
+    pub bookmark: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Value {
+    #[prost(
+        oneof = "value::Kind",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15"
+    )]
+    pub kind: Option<value::Kind>,
+}
+
+pub mod value {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        Null(super::Null),
+        #[prost(bool, tag = "2")]
+        BoolValue(bool),
+        #[prost(int64, tag = "3")]
+        IntValue(i64),
+        #[prost(double, tag = "4")]
+        FloatValue(f64),
+        #[prost(string, tag = "5")]
+        StringValue(String),
+        #[prost(bytes = "vec", tag = "6")]
+        BytesValue(Vec<u8>),
+        #[prost(int64, tag = "7")]
+        DatetimeValue(i64),
+        #[prost(message, tag = "8")]
+        ListValue(super::List),
+        #[prost(message, tag = "9")]
+        MapValue(super::Map),
+        #[prost(message, tag = "10")]
+        Node(super::Node),
+        #[prost(message, tag = "11")]
+        Relationship(super::Relationship),
+        #[prost(message, tag = "12")]
+        Path(super::Path),
+        #[prost(uint64, tag = "13")]
+        NodeId(u64),
+        #[prost(uint64, tag = "14")]
+        ExternalId(u64),
+        #[prost(message, tag = "15")]
+        EdgeKey(super::EdgeKey),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Null {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct List {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Map {
+    #[prost(btree_map = "string, message", tag = "1")]
+    pub entries: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Node {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, repeated, tag = "2")]
+    pub labels: Vec<String>,
+    #[prost(btree_map = "string, message", tag = "3")]
+    pub properties: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Relationship {
+    #[prost(uint64, tag = "1")]
+    pub src: u64,
+    #[prost(uint64, tag = "2")]
+    pub dst: u64,
+    #[prost(string, tag = "3")]
+    pub rel_type: String,
+    #[prost(btree_map = "string, message", tag = "4")]
+    pub properties: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Path {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: Vec<Node>,
+    #[prost(message, repeated, tag = "2")]
+    pub relationships: Vec<Relationship>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EdgeKey {
+    #[prost(uint64, tag = "1")]
+    pub src: u64,
+    #[prost(uint32, tag = "2")]
+    pub rel: u32,
+    #[prost(uint64, tag = "3")]
+    pub dst: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BulkLoadBatch {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: Vec<BulkNode>,
+    #[prost(message, repeated, tag = "2")]
+    pub edges: Vec<BulkEdge>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BulkNode {
+    #[prost(uint64, tag = "1")]
+    pub external_id: u64,
+    #[prost(string, repeated, tag = "2")]
+    pub labels: Vec<String>,
+    #[prost(btree_map = "string, message", tag = "3")]
+    pub properties: BTreeMap<String, Value>,
+    #[prost(float, repeated, tag = "4")]
+    pub vector: Vec<f32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BulkEdge {
+    #[prost(uint64, tag = "1")]
+    pub src_external_id: u64,
+    #[prost(string, tag = "2")]
+    pub rel_type: String,
+    #[prost(uint64, tag = "3")]
+    pub dst_external_id: u64,
+    #[prost(btree_map = "string, message", tag = "4")]
+    pub properties: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BulkLoadSummary {
+    #[prost(uint64, tag = "1")]
+    pub nodes: u64,
+    #[prost(uint64, tag = "2")]
+    pub edges: u64,
+    #[prost(uint64, tag = "3")]
+    pub batches: u64,
+    #[prost(uint64, tag = "4")]
+    pub bookmark: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum VectorMetric {
+    L2 = 0,
+    Cosine = 1,
+    Dot = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VectorSearchRequest {
+    #[prost(float, repeated, tag = "1")]
+    pub vector: Vec<f32>,
+    #[prost(uint32, tag = "2")]
+    pub k: u32,
+    #[prost(enumeration = "VectorMetric", tag = "3")]
+    pub metric: i32,
+    #[prost(string, tag = "4")]
+    pub label: String,
+    #[prost(string, tag = "5")]
+    pub property: String,
+    #[prost(message, optional, tag = "6")]
+    pub value: Option<Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VectorHit {
+    #[prost(message, optional, tag = "1")]
+    pub node: Option<Node>,
+    #[prost(uint64, tag = "2")]
+    pub external_id: u64,
+    #[prost(float, tag = "3")]
+    pub distance: f32,
+}
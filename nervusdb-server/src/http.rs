@@ -0,0 +1,414 @@
+//! An HTTP/JSON endpoint for NervusDB (the `http` feature).
+//!
+//! - `POST /query` runs a read statement and `POST /write` a write, each
+//!   taking `{"query": "...", "params": {...}}`.
+//! - `GET /health` reports liveness and the last committed txid.
+//! - `GET /metrics` reports request and cache counters in the Prometheus
+//!   text format.
+//!
+//! Given an [`Auth`], every route but `/health` requires its credentials as
+//! `Authorization: Basic ...` and answers `401 Unauthorized` without them.
+//!
+//! Results stream back as frames: `row` frames, then one `summary` frame,
+//! or an `error` frame if the statement fails midway. By default each frame
+//! is a line of JSON (`{"row": {...}}`) sent with chunked transfer; clients
+//! that accept `text/event-stream` get server-sent events named after the
+//! frame. Reads stream from a snapshot as they execute. Writes commit
+//! before the response starts, so their errors come back as a plain status
+//! and the summary carries the commit's bookmark.
+//!
+//! ```no_run
+//! use nervusdb::Db;
+//! use std::sync::Arc;
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let db = Arc::new(Db::open("/tmp/graph.ndb").unwrap());
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:7474").await?;
+//! nervusdb_server::http::serve(listener, db, None).await
+//! # }
+//! ```
+
+use crate::Auth;
+use crate::exec::{ErrorKind, auto_commit_write};
+use crate::json::{params_from_json, row_to_json};
+use axum::Router;
+use axum::body::Body;
+use axum::extract::{Json, Request, State};
+use axum::http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use nervusdb::Db;
+use nervusdb_query::{Params, PreparedQuery, prepare};
+use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Frames buffered ahead of a slow client before the query waits for it.
+const STREAM_BUFFER: usize = 64;
+
+/// Serves the endpoint on `listener` until it fails.
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    db: Arc<Db>,
+    auth: Option<Auth>,
+) -> io::Result<()> {
+    axum::serve(listener, router(db, auth)).await
+}
+
+/// The endpoint's routes, for mounting into an existing axum application.
+/// `auth`, when set, guards every route but `/health`.
+pub fn router(db: Arc<Db>, auth: Option<Auth>) -> Router {
+    let state = Arc::new(AppState {
+        db,
+        auth,
+        metrics: Metrics::default(),
+    });
+    Router::new()
+        .route("/query", post(query))
+        .route("/write", post(write))
+        .route("/metrics", get(metrics))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            require_auth,
+        ))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+struct AppState {
+    db: Arc<Db>,
+    auth: Option<Auth>,
+    metrics: Metrics,
+}
+
+#[derive(Default)]
+struct Metrics {
+    queries: AtomicU64,
+    writes: AtomicU64,
+    errors: AtomicU64,
+    rows: AtomicU64,
+    in_flight: AtomicU64,
+}
+
+#[derive(Deserialize)]
+struct QueryRequest {
+    query: String,
+    #[serde(default)]
+    params: JsonMap<String, JsonValue>,
+}
+
+/// One unit of a streamed result.
+enum Frame {
+    Row(JsonValue),
+    Summary(JsonValue),
+    Error(JsonValue),
+}
+
+impl Frame {
+    fn parts(self) -> (&'static str, JsonValue) {
+        match self {
+            Frame::Row(row) => ("row", row),
+            Frame::Summary(summary) => ("summary", summary),
+            Frame::Error(error) => ("error", error),
+        }
+    }
+}
+
+/// A failed request: its status and `{"code", "message"}` body.
+struct Failure {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl Failure {
+    fn unauthorized() -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: "unauthorized",
+            message: "missing or wrong credentials".into(),
+        }
+    }
+
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "invalid_request",
+            message: message.into(),
+        }
+    }
+
+    /// Classifies an engine error by its message.
+    fn query(message: String) -> Self {
+        let (status, code) = match ErrorKind::of(&message) {
+            ErrorKind::Syntax => (StatusCode::BAD_REQUEST, "syntax_error"),
+            ErrorKind::Constraint => (StatusCode::CONFLICT, "constraint_violation"),
+            ErrorKind::ResourceLimit => (StatusCode::SERVICE_UNAVAILABLE, "resource_limit"),
+            ErrorKind::Execution => (StatusCode::UNPROCESSABLE_ENTITY, "execution_error"),
+        };
+        Self {
+            status,
+            code,
+            message,
+        }
+    }
+
+    fn body(&self) -> JsonValue {
+        json!({ "code": self.code, "message": self.message })
+    }
+}
+
+impl IntoResponse for Failure {
+    fn into_response(self) -> Response {
+        (self.status, axum::Json(json!({ "error": self.body() }))).into_response()
+    }
+}
+
+/// Counts a request in flight until dropped.
+struct InFlight<'a>(&'a AtomicU64);
+
+impl<'a> InFlight<'a> {
+    fn enter(gauge: &'a AtomicU64) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Lets a request through when the server needs no credentials or it
+/// carries the right ones.
+async fn require_auth(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth) = &state.auth else {
+        return next.run(request).await;
+    };
+    let accepted = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| auth.accepts_basic(value));
+    if accepted {
+        return next.run(request).await;
+    }
+    let mut response = Failure::unauthorized().into_response();
+    response.headers_mut().insert(
+        WWW_AUTHENTICATE,
+        axum::http::HeaderValue::from_static("Basic realm=\"nervusdb\""),
+    );
+    response
+}
+
+fn prepare_request(request: QueryRequest) -> Result<(PreparedQuery, Params), Failure> {
+    let prepared = prepare(&request.query).map_err(|e| Failure::query(e.to_string()))?;
+    let params = params_from_json(request.params).map_err(Failure::invalid)?;
+    Ok((prepared, params))
+}
+
+async fn query(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<QueryRequest>,
+) -> Response {
+    state.metrics.queries.fetch_add(1, Ordering::Relaxed);
+    let (prepared, params) = match prepare_request(request) {
+        Ok((prepared, _)) if prepared.is_write() => {
+            state.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            return Failure::invalid("POST /query does not accept write statements; use /write")
+                .into_response();
+        }
+        Ok(prepared) => prepared,
+        Err(failure) => {
+            state.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            return failure.into_response();
+        }
+    };
+
+    let (frames, receiver) = mpsc::channel(STREAM_BUFFER);
+    let streaming = Arc::clone(&state);
+    tokio::task::spawn_blocking(move || {
+        let metrics = &streaming.metrics;
+        let _in_flight = InFlight::enter(&metrics.in_flight);
+        let snapshot = streaming.db.snapshot();
+        let mut rows = 0u64;
+        for row in prepared.execute_streaming(&snapshot, &params) {
+            let frame = match row.and_then(|row| row.reify(&snapshot)) {
+                Ok(row) => Frame::Row(row_to_json(row)),
+                Err(e) => {
+                    metrics.errors.fetch_add(1, Ordering::Relaxed);
+                    let _ =
+                        frames.blocking_send(Frame::Error(Failure::query(e.to_string()).body()));
+                    return;
+                }
+            };
+            // A closed channel means the client went away; stop reading.
+            if frames.blocking_send(frame).is_err() {
+                return;
+            }
+            rows += 1;
+            metrics.rows.fetch_add(1, Ordering::Relaxed);
+        }
+        let _ = frames.blocking_send(Frame::Summary(json!({ "rows": rows })));
+    });
+    stream_response(&headers, receiver)
+}
+
+async fn write(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<QueryRequest>,
+) -> Response {
+    state.metrics.writes.fetch_add(1, Ordering::Relaxed);
+    let executing = Arc::clone(&state);
+    let outcome = tokio::task::spawn_blocking(move || {
+        let _in_flight = InFlight::enter(&executing.metrics.in_flight);
+        let (prepared, params) = prepare_request(request)?;
+        let (rows, writes, bookmark) =
+            auto_commit_write(&executing.db, &prepared, &params).map_err(Failure::query)?;
+        Ok::<_, Failure>((rows, writes, bookmark.txid()))
+    })
+    .await
+    .unwrap_or_else(|e| Err(Failure::query(e.to_string())));
+    let (rows, writes, bookmark) = match outcome {
+        Ok(outcome) => outcome,
+        Err(failure) => {
+            state.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            return failure.into_response();
+        }
+    };
+
+    state
+        .metrics
+        .rows
+        .fetch_add(rows.len() as u64, Ordering::Relaxed);
+    let summary = json!({ "rows": rows.len(), "writes": writes, "bookmark": bookmark });
+    let (frames, receiver) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        for row in rows {
+            if frames.send(Frame::Row(row_to_json(row))).await.is_err() {
+                return;
+            }
+        }
+        let _ = frames.send(Frame::Summary(summary)).await;
+    });
+    stream_response(&headers, receiver)
+}
+
+/// Streams frames as server-sent events when the client accepts them,
+/// otherwise as newline-delimited JSON.
+fn stream_response(headers: &HeaderMap, receiver: mpsc::Receiver<Frame>) -> Response {
+    let frames = ReceiverStream::new(receiver);
+    let wants_events = headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+    if wants_events {
+        let events = frames.map(|frame| {
+            let (name, data) = frame.parts();
+            Ok::<_, Infallible>(Event::default().event(name).data(data.to_string()))
+        });
+        return Sse::new(events).into_response();
+    }
+    let lines = frames.map(|frame| {
+        let (name, data) = frame.parts();
+        let mut line = json!({ name: data }).to_string();
+        line.push('\n');
+        Ok::<_, Infallible>(line)
+    });
+    (
+        [(CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    axum::Json(json!({
+        "status": "ok",
+        "bookmark": state.db.last_bookmark().txid(),
+    }))
+}
+
+async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let metrics = &state.metrics;
+    let cache = state.db.page_cache_stats();
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: u64| {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        let _ = writeln!(out, "{name} {value}");
+    };
+    metric(
+        "nervusdb_http_queries_total",
+        "counter",
+        "Requests to POST /query.",
+        load(&metrics.queries),
+    );
+    metric(
+        "nervusdb_http_writes_total",
+        "counter",
+        "Requests to POST /write.",
+        load(&metrics.writes),
+    );
+    metric(
+        "nervusdb_http_errors_total",
+        "counter",
+        "Queries and writes that failed.",
+        load(&metrics.errors),
+    );
+    metric(
+        "nervusdb_http_rows_total",
+        "counter",
+        "Result rows sent.",
+        load(&metrics.rows),
+    );
+    metric(
+        "nervusdb_http_in_flight",
+        "gauge",
+        "Statements executing now.",
+        load(&metrics.in_flight),
+    );
+    metric(
+        "nervusdb_page_cache_hits_total",
+        "counter",
+        "Page cache hits.",
+        cache.hits,
+    );
+    metric(
+        "nervusdb_page_cache_misses_total",
+        "counter",
+        "Page cache misses.",
+        cache.misses,
+    );
+    metric(
+        "nervusdb_page_cache_pages",
+        "gauge",
+        "Pages held in the page cache.",
+        cache.len as u64,
+    );
+    metric(
+        "nervusdb_last_committed_txid",
+        "gauge",
+        "The last committed transaction id.",
+        state.db.last_bookmark().txid(),
+    );
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4")], out)
+}
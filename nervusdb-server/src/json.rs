@@ -0,0 +1,122 @@
+//! Query values as JSON, in the envelopes the C API uses: scalars, lists and
+//! maps as themselves, everything else as an object with a `type` field.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use nervusdb_query::{Params, Row, Value};
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+pub(crate) fn row_to_json(row: Row) -> JsonValue {
+    JsonValue::Object(
+        row.columns()
+            .iter()
+            .cloned()
+            .map(|(name, value)| (name, value_to_json(value)))
+            .collect(),
+    )
+}
+
+pub(crate) fn value_to_json(value: Value) -> JsonValue {
+    match value {
+        Value::Null => JsonValue::Null,
+        Value::Bool(b) => json!(b),
+        Value::Int(i) => json!(i),
+        Value::Float(f) => json!(f),
+        Value::String(s) => json!(s),
+        Value::DateTime(micros) => json!({ "type": "datetime", "value": micros }),
+        Value::Blob(bytes) => json!({ "type": "blob", "value": BASE64.encode(bytes) }),
+        Value::List(items) => JsonValue::Array(items.into_iter().map(value_to_json).collect()),
+        Value::Map(map) => properties(map),
+        Value::Node(node) => json!({
+            "type": "node",
+            "id": node.id,
+            "labels": node.labels,
+            "properties": properties(node.properties),
+        }),
+        Value::Relationship(rel) => json!({
+            "type": "relationship",
+            "src": rel.key.src,
+            "dst": rel.key.dst,
+            "rel_type": rel.rel_type,
+            "properties": properties(rel.properties),
+        }),
+        Value::ReifiedPath(path) => json!({
+            "type": "path",
+            "nodes": path.nodes.into_iter().map(Value::Node).map(value_to_json).collect::<Vec<_>>(),
+            "relationships": path
+                .relationships
+                .into_iter()
+                .map(Value::Relationship)
+                .map(value_to_json)
+                .collect::<Vec<_>>(),
+        }),
+        Value::NodeId(id) => json!({ "type": "node_id", "value": id }),
+        Value::ExternalId(id) => json!({ "type": "external_id", "value": id }),
+        Value::EdgeKey(key) => {
+            json!({ "type": "edge_key", "src": key.src, "rel": key.rel, "dst": key.dst })
+        }
+        Value::Path(path) => json!({
+            "type": "path_legacy",
+            "nodes": path.nodes,
+            "edges": path
+                .edges
+                .into_iter()
+                .map(|e| json!({ "src": e.src, "rel": e.rel, "dst": e.dst }))
+                .collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn properties(map: impl IntoIterator<Item = (String, Value)>) -> JsonValue {
+    JsonValue::Object(
+        map.into_iter()
+            .map(|(key, value)| (key, value_to_json(value)))
+            .collect(),
+    )
+}
+
+/// Converts request parameters. `{"type": "datetime" | "blob", "value"}`
+/// objects decode to those values; other objects become maps.
+pub(crate) fn params_from_json(params: JsonMap<String, JsonValue>) -> Result<Params, String> {
+    let mut out = Params::new();
+    for (name, value) in params {
+        out.insert(name, json_to_value(value)?);
+    }
+    Ok(out)
+}
+
+fn json_to_value(value: JsonValue) -> Result<Value, String> {
+    Ok(match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(b) => Value::Bool(b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::Float(n.as_f64().ok_or("unsupported numeric value")?),
+        },
+        JsonValue::String(s) => Value::String(s),
+        JsonValue::Array(items) => Value::List(
+            items
+                .into_iter()
+                .map(json_to_value)
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(map) => match (
+            map.get("type").and_then(JsonValue::as_str),
+            map.get("value"),
+        ) {
+            (Some("datetime"), Some(JsonValue::Number(micros))) if map.len() == 2 => {
+                Value::DateTime(micros.as_i64().ok_or("datetime value must be an integer")?)
+            }
+            (Some("blob"), Some(JsonValue::String(encoded))) if map.len() == 2 => Value::Blob(
+                BASE64
+                    .decode(encoded)
+                    .map_err(|_| "blob value must be base64")?,
+            ),
+            _ => Value::Map(
+                map.into_iter()
+                    .map(|(key, value)| Ok((key, json_to_value(value)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+        },
+    })
+}
@@ -0,0 +1,296 @@
+//! A Bolt protocol server for NervusDB.
+//!
+//! Speaks Bolt 4.4 and 5.0 over TCP so Neo4j drivers (Java, Go, JavaScript,
+//! Python, ...) can connect to a [`Db`] directly. Each connection is served
+//! on a thread of its own; an explicit write transaction holds the
+//! database's single writer until it commits or rolls back, while read
+//! transactions (`BEGIN` with `mode: "r"`) only pin a snapshot.
+//!
+//! With the `http` feature (on by default), [`http`] serves the same
+//! database as a JSON endpoint with streamed results; with the `grpc`
+//! feature (also on by default), [`grpc`] serves it as a gRPC service.
+//!
+//! ```no_run
+//! use nervusdb::Db;
+//! use nervusdb_server::{Server, ServerConfig};
+//! use std::sync::Arc;
+//!
+//! let db = Arc::new(Db::open("/tmp/graph.ndb")?);
+//! let server = Server::bind(db, "127.0.0.1:7687", ServerConfig::default())?;
+//! server.serve()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod exec;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "http")]
+mod json;
+pub mod packstream;
+mod session;
+mod values;
+
+use nervusdb::Db;
+use packstream::PackValue;
+use session::Session;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use values::Version;
+
+/// The four bytes a Bolt client opens with.
+const MAGIC: [u8; 4] = [0x60, 0x60, 0xB0, 0x17];
+/// Upper bound on one request, so a bad length cannot exhaust memory.
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Credentials `HELLO` must present, and that HTTP and gRPC requests must
+/// carry as `Authorization: Basic ...`.
+#[derive(Debug, Clone)]
+pub struct Auth {
+    pub user: String,
+    pub password: String,
+}
+
+impl Auth {
+    /// Whether `user` and `password` are these credentials. Both are always
+    /// compared, in time independent of where they differ.
+    pub(crate) fn accepts(&self, user: &str, password: &str) -> bool {
+        let user = constant_time_eq(user.as_bytes(), self.user.as_bytes());
+        let password = constant_time_eq(password.as_bytes(), self.password.as_bytes());
+        user & password
+    }
+
+    /// Whether an `Authorization` header value is `Basic` with these
+    /// credentials.
+    #[cfg(any(feature = "http", feature = "grpc"))]
+    pub(crate) fn accepts_basic(&self, authorization: &str) -> bool {
+        use base64::Engine;
+        use base64::engine::general_purpose::STANDARD as BASE64;
+
+        let Some((scheme, encoded)) = authorization.trim().split_once(' ') else {
+            return false;
+        };
+        if !scheme.eq_ignore_ascii_case("basic") {
+            return false;
+        }
+        let Ok(decoded) = BASE64.decode(encoded.trim()) else {
+            return false;
+        };
+        let Ok(decoded) = String::from_utf8(decoded) else {
+            return false;
+        };
+        decoded
+            .split_once(':')
+            .is_some_and(|(user, password)| self.accepts(user, password))
+    }
+}
+
+/// Byte equality without an early exit, so the time taken does not reveal
+/// how much of a guess was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let (x, y) = (a.get(i).copied(), b.get(i).copied());
+        diff |= usize::from(x.unwrap_or(0) ^ y.unwrap_or(0));
+    }
+    std::hint::black_box(diff) == 0
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    /// Required credentials; `None` accepts every client.
+    pub auth: Option<Auth>,
+}
+
+/// A bound Bolt listener.
+pub struct Server {
+    listener: TcpListener,
+    db: Arc<Db>,
+    config: Arc<ServerConfig>,
+}
+
+impl Server {
+    pub fn bind(db: Arc<Db>, addr: impl ToSocketAddrs, config: ServerConfig) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            db,
+            config: Arc::new(config),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections until the listener fails, serving each on its
+    /// own thread.
+    pub fn serve(self) -> io::Result<()> {
+        let connections = AtomicU64::new(0);
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let db = Arc::clone(&self.db);
+            let config = Arc::clone(&self.config);
+            let id = connections.fetch_add(1, Ordering::Relaxed);
+            std::thread::spawn(move || {
+                // A broken connection only ends its own thread.
+                let _ = serve_connection(&db, &config, stream, format!("bolt-{id}"));
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Serves one client until it says `GOODBYE`, disconnects or breaks the
+/// protocol.
+pub fn serve_connection(
+    db: &Db,
+    config: &ServerConfig,
+    stream: TcpStream,
+    connection_id: String,
+) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a Bolt client",
+        ));
+    }
+    let mut proposals = [0; 16];
+    reader.read_exact(&mut proposals)?;
+    let Some((version, reply)) = negotiate(&proposals) else {
+        writer.write_all(&[0; 4])?;
+        return writer.flush();
+    };
+    writer.write_all(&reply)?;
+    writer.flush()?;
+
+    let mut session = Session::new(db, config, version, connection_id);
+    while !session.is_closed() {
+        let Some(message) = read_message(&mut reader)? else {
+            break;
+        };
+        let mut input = message.as_slice();
+        let request = packstream::decode(&mut input)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for response in session.handle(request) {
+            write_message(&mut writer, &response)?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Picks the first of the client's four proposals we speak. Each is
+/// `[0, range, minor, major]` and covers `major.minor` down to
+/// `major.(minor - range)`.
+fn negotiate(proposals: &[u8; 16]) -> Option<(Version, [u8; 4])> {
+    proposals.chunks_exact(4).find_map(|proposal| {
+        let (range, minor, major) = (proposal[1], proposal[2], proposal[3]);
+        let covers = |wanted: u8| minor >= wanted && minor.saturating_sub(range) <= wanted;
+        match major {
+            5 if covers(0) => Some((Version::V5_0, [0, 0, 0, 5])),
+            4 if covers(4) => Some((Version::V4_4, [0, 0, 4, 4])),
+            _ => None,
+        }
+    })
+}
+
+/// Reads one chunked message: 16-bit sized chunks up to an empty chunk.
+/// An empty chunk before any data is a keep-alive and is skipped. Returns
+/// `None` when the client hangs up between messages.
+pub fn read_message(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut message = Vec::new();
+    loop {
+        let mut header = [0; 2];
+        if let Err(err) = reader.read_exact(&mut header) {
+            if message.is_empty() && err.kind() == io::ErrorKind::UnexpectedEof {
+                return Ok(None);
+            }
+            return Err(err);
+        }
+        let len = u16::from_be_bytes(header) as usize;
+        if len == 0 {
+            if message.is_empty() {
+                continue;
+            }
+            return Ok(Some(message));
+        }
+        if message.len() + len > MAX_MESSAGE_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "message exceeds the size limit",
+            ));
+        }
+        let start = message.len();
+        message.resize(start + len, 0);
+        reader.read_exact(&mut message[start..])?;
+    }
+}
+
+/// Writes `value` as one chunked message.
+pub fn write_message(writer: &mut impl Write, value: &PackValue) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    packstream::encode(value, &mut bytes);
+    for chunk in bytes.chunks(u16::MAX as usize) {
+        writer.write_all(&(chunk.len() as u16).to_be_bytes())?;
+        writer.write_all(chunk)?;
+    }
+    writer.write_all(&[0, 0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Version, constant_time_eq, negotiate};
+
+    #[cfg(any(feature = "http", feature = "grpc"))]
+    #[test]
+    fn basic_auth_matches_user_and_password() {
+        let auth = super::Auth {
+            user: "neo".into(),
+            password: "s3cret:x".into(),
+        };
+        // "neo:s3cret:x"
+        assert!(auth.accepts_basic("Basic bmVvOnMzY3JldDp4"));
+        assert!(auth.accepts_basic("basic bmVvOnMzY3JldDp4"));
+        // "neo:wrong"
+        assert!(!auth.accepts_basic("Basic bmVvOndyb25n"));
+        assert!(!auth.accepts_basic("Bearer bmVvOnMzY3JldDp4"));
+        assert!(!auth.accepts_basic("Basic !!!"));
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_inputs() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"\0"));
+    }
+
+    #[test]
+    fn negotiation_picks_the_first_supported_proposal() {
+        let proposals = |list: [[u8; 4]; 4]| {
+            let mut bytes = [0; 16];
+            for (i, p) in list.iter().enumerate() {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(p);
+            }
+            bytes
+        };
+        let v5 = negotiate(&proposals([[0, 4, 4, 5], [0, 0, 4, 4], [0; 4], [0; 4]]));
+        assert_eq!(v5, Some((Version::V5_0, [0, 0, 0, 5])));
+        let v4 = negotiate(&proposals([[0, 0, 2, 5], [0, 2, 4, 4], [0; 4], [0; 4]]));
+        assert_eq!(v4, Some((Version::V4_4, [0, 0, 4, 4])));
+        assert_eq!(
+            negotiate(&proposals([[0, 0, 3, 4], [0, 0, 0, 3], [0; 4], [0; 4]])),
+            None
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use clap::Parser;
+use nervusdb::{Db, FileImport};
+use nervusdb_server::{Auth, Server, ServerConfig};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Environment variable read for the password when there is no
+/// `--password-file`.
+const PASSWORD_ENV: &str = "NERVUSDB_PASSWORD";
+
+/// Serves a NervusDB database over the Bolt protocol, over HTTP/JSON with
+/// `--http`, and over gRPC with `--grpc`.
+#[derive(Parser)]
+#[command(name = "nervusdb-server", version)]
+struct Args {
+    /// Database base path (`<path>.ndb` / `<path>.wal`)
+    db: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7687")]
+    listen: String,
+
+    /// Address to serve the HTTP/JSON endpoint on
+    #[cfg(feature = "http")]
+    #[arg(long)]
+    http: Option<String>,
+
+    /// Address to serve the gRPC service on
+    #[cfg(feature = "grpc")]
+    #[arg(long)]
+    grpc: Option<String>,
+
+    /// User clients must authenticate as, on every endpoint. The password
+    /// comes from `--password-file` or `NERVUSDB_PASSWORD`
+    #[arg(long)]
+    user: Option<String>,
+
+    /// File holding the password clients must authenticate with
+    #[arg(long, requires = "user")]
+    password_file: Option<PathBuf>,
+
+    /// Directory `LOAD CSV` may read files from; without it, clients
+    /// cannot read server files
+    #[arg(long)]
+    import_root: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let auth = match args.user {
+        Some(user) => Some(Auth {
+            user,
+            password: password(args.password_file.as_deref())?,
+        }),
+        None => None,
+    };
+    let db = Db::open(&args.db)?;
+    db.set_file_import(
+        args.import_root
+            .map_or(FileImport::Disabled, FileImport::Under),
+    )?;
+    let db = Arc::new(db);
+    #[cfg(feature = "http")]
+    if let Some(addr) = &args.http {
+        let runtime = tokio::runtime::Runtime::new()?;
+        let listener = runtime.block_on(tokio::net::TcpListener::bind(addr))?;
+        eprintln!(
+            "nervusdb-server listening on http://{}",
+            listener.local_addr()?
+        );
+        let db = Arc::clone(&db);
+        let auth = auth.clone();
+        std::thread::spawn(move || {
+            runtime.block_on(nervusdb_server::http::serve(listener, db, auth))
+        });
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(addr) = &args.grpc {
+        let runtime = tokio::runtime::Runtime::new()?;
+        let listener = runtime.block_on(tokio::net::TcpListener::bind(addr))?;
+        eprintln!(
+            "nervusdb-server listening on grpc://{}",
+            listener.local_addr()?
+        );
+        let db = Arc::clone(&db);
+        let auth = auth.clone();
+        std::thread::spawn(move || {
+            runtime.block_on(nervusdb_server::grpc::serve(listener, db, auth))
+        });
+    }
+    let server = Server::bind(db, &args.listen, ServerConfig { auth })?;
+    eprintln!(
+        "nervusdb-server listening on bolt://{}",
+        server.local_addr()?
+    );
+    server.serve()?;
+    Ok(())
+}
+
+/// The server password. It is never taken from the command line, where any
+/// local user could read it with `ps`.
+fn password(file: Option<&Path>) -> Result<String, Box<dyn std::error::Error>> {
+    let Some(file) = file else {
+        return std::env::var(PASSWORD_ENV).map_err(|_| {
+            format!("--user needs a password in --password-file or {PASSWORD_ENV}").into()
+        });
+    };
+    let password = std::fs::read_to_string(file)?;
+    // Files written by `echo` end with a newline that is not part of it.
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,312 @@
+//! PackStream, the binary value encoding Bolt messages are made of.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A PackStream value. Structures carry a one-byte tag and their fields;
+/// Bolt messages are structures too.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+    String(String),
+    List(Vec<PackValue>),
+    Map(BTreeMap<String, PackValue>),
+    Struct { tag: u8, fields: Vec<PackValue> },
+}
+
+impl PackValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PackValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            PackValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&BTreeMap<String, PackValue>> {
+        match self {
+            PackValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for PackValue {
+    fn from(s: &str) -> Self {
+        PackValue::String(s.to_string())
+    }
+}
+
+impl From<String> for PackValue {
+    fn from(s: String) -> Self {
+        PackValue::String(s)
+    }
+}
+
+impl From<i64> for PackValue {
+    fn from(i: i64) -> Self {
+        PackValue::Int(i)
+    }
+}
+
+impl From<bool> for PackValue {
+    fn from(b: bool) -> Self {
+        PackValue::Bool(b)
+    }
+}
+
+/// Malformed PackStream input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError(pub String);
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid PackStream data: {}", self.0)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Appends the encoding of `value` to `out`, using the smallest marker that
+/// holds each integer, string and container size.
+pub fn encode(value: &PackValue, out: &mut Vec<u8>) {
+    match value {
+        PackValue::Null => out.push(0xC0),
+        PackValue::Bool(false) => out.push(0xC2),
+        PackValue::Bool(true) => out.push(0xC3),
+        PackValue::Int(i) => encode_int(*i, out),
+        PackValue::Float(f) => {
+            out.push(0xC1);
+            out.extend_from_slice(&f.to_be_bytes());
+        }
+        PackValue::Bytes(bytes) => {
+            encode_size(bytes.len(), None, [0xCC, 0xCD, 0xCE], out);
+            out.extend_from_slice(bytes);
+        }
+        PackValue::String(s) => {
+            encode_size(s.len(), Some(0x80), [0xD0, 0xD1, 0xD2], out);
+            out.extend_from_slice(s.as_bytes());
+        }
+        PackValue::List(items) => {
+            encode_size(items.len(), Some(0x90), [0xD4, 0xD5, 0xD6], out);
+            for item in items {
+                encode(item, out);
+            }
+        }
+        PackValue::Map(map) => {
+            encode_size(map.len(), Some(0xA0), [0xD8, 0xD9, 0xDA], out);
+            for (key, item) in map {
+                encode(&PackValue::String(key.clone()), out);
+                encode(item, out);
+            }
+        }
+        PackValue::Struct { tag, fields } => {
+            // Structures hold at most 15 fields; every Bolt structure fits.
+            debug_assert!(fields.len() < 16);
+            out.push(0xB0 | fields.len() as u8);
+            out.push(*tag);
+            for field in fields {
+                encode(field, out);
+            }
+        }
+    }
+}
+
+fn encode_int(i: i64, out: &mut Vec<u8>) {
+    if (-16..128).contains(&i) {
+        out.push(i as i8 as u8);
+    } else if let Ok(i) = i8::try_from(i) {
+        out.push(0xC8);
+        out.push(i as u8);
+    } else if let Ok(i) = i16::try_from(i) {
+        out.push(0xC9);
+        out.extend_from_slice(&i.to_be_bytes());
+    } else if let Ok(i) = i32::try_from(i) {
+        out.push(0xCA);
+        out.extend_from_slice(&i.to_be_bytes());
+    } else {
+        out.push(0xCB);
+        out.extend_from_slice(&i.to_be_bytes());
+    }
+}
+
+/// Writes a size as a tiny marker (`tiny | len` below 16, when the type has
+/// one) or as one of the 8/16/32-bit `markers` followed by the size.
+fn encode_size(len: usize, tiny: Option<u8>, markers: [u8; 3], out: &mut Vec<u8>) {
+    match tiny {
+        Some(tiny) if len < 16 => out.push(tiny | len as u8),
+        _ if len <= u8::MAX as usize => out.extend_from_slice(&[markers[0], len as u8]),
+        _ if len <= u16::MAX as usize => {
+            out.push(markers[1]);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(markers[2]);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+/// How deeply lists, maps and structures may nest, so hostile input cannot
+/// exhaust the stack.
+const MAX_DEPTH: usize = 256;
+
+/// Decodes one value from the front of `input`, advancing it past the value.
+pub fn decode(input: &mut &[u8]) -> Result<PackValue, DecodeError> {
+    decode_nested(input, 0)
+}
+
+fn decode_nested(input: &mut &[u8], depth: usize) -> Result<PackValue, DecodeError> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeError("values are nested too deeply".to_string()));
+    }
+    let marker = take(input, 1)?[0];
+    match marker {
+        0x00..=0x7F => Ok(PackValue::Int(marker as i64)),
+        0xF0..=0xFF => Ok(PackValue::Int(marker as i8 as i64)),
+        0x80..=0x8F => decode_string(input, (marker & 0x0F) as usize),
+        0x90..=0x9F => decode_list(input, (marker & 0x0F) as usize, depth),
+        0xA0..=0xAF => decode_map(input, (marker & 0x0F) as usize, depth),
+        0xB0..=0xBF => {
+            let tag = take(input, 1)?[0];
+            let fields = (0..marker & 0x0F)
+                .map(|_| decode_nested(input, depth + 1))
+                .collect::<Result<_, _>>()?;
+            Ok(PackValue::Struct { tag, fields })
+        }
+        0xC0 => Ok(PackValue::Null),
+        0xC1 => Ok(PackValue::Float(f64::from_be_bytes(array(input)?))),
+        0xC2 => Ok(PackValue::Bool(false)),
+        0xC3 => Ok(PackValue::Bool(true)),
+        0xC8 => Ok(PackValue::Int(i8::from_be_bytes(array(input)?) as i64)),
+        0xC9 => Ok(PackValue::Int(i16::from_be_bytes(array(input)?) as i64)),
+        0xCA => Ok(PackValue::Int(i32::from_be_bytes(array(input)?) as i64)),
+        0xCB => Ok(PackValue::Int(i64::from_be_bytes(array(input)?))),
+        0xCC..=0xCE => {
+            let len = decode_size(input, marker - 0xCC)?;
+            Ok(PackValue::Bytes(take(input, len)?.to_vec()))
+        }
+        0xD0..=0xD2 => {
+            let len = decode_size(input, marker - 0xD0)?;
+            decode_string(input, len)
+        }
+        0xD4..=0xD6 => {
+            let len = decode_size(input, marker - 0xD4)?;
+            decode_list(input, len, depth)
+        }
+        0xD8..=0xDA => {
+            let len = decode_size(input, marker - 0xD8)?;
+            decode_map(input, len, depth)
+        }
+        _ => Err(DecodeError(format!("unknown marker 0x{marker:02X}"))),
+    }
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if input.len() < len {
+        return Err(DecodeError("unexpected end of data".to_string()));
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Ok(head)
+}
+
+fn array<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(take(input, N)?);
+    Ok(bytes)
+}
+
+/// Reads an 8, 16 or 32-bit size (`width` 0, 1 or 2).
+fn decode_size(input: &mut &[u8], width: u8) -> Result<usize, DecodeError> {
+    Ok(match width {
+        0 => u8::from_be_bytes(array(input)?) as usize,
+        1 => u16::from_be_bytes(array(input)?) as usize,
+        _ => u32::from_be_bytes(array(input)?) as usize,
+    })
+}
+
+fn decode_string(input: &mut &[u8], len: usize) -> Result<PackValue, DecodeError> {
+    let bytes = take(input, len)?;
+    String::from_utf8(bytes.to_vec())
+        .map(PackValue::String)
+        .map_err(|_| DecodeError("string is not valid UTF-8".to_string()))
+}
+
+fn decode_list(input: &mut &[u8], len: usize, depth: usize) -> Result<PackValue, DecodeError> {
+    // Every item takes at least a byte, which bounds what a length claims.
+    let mut items = Vec::with_capacity(len.min(input.len()));
+    for _ in 0..len {
+        items.push(decode_nested(input, depth + 1)?);
+    }
+    Ok(PackValue::List(items))
+}
+
+fn decode_map(input: &mut &[u8], len: usize, depth: usize) -> Result<PackValue, DecodeError> {
+    let mut map = BTreeMap::new();
+    for _ in 0..len {
+        let PackValue::String(key) = decode_nested(input, depth + 1)? else {
+            return Err(DecodeError("map keys must be strings".to_string()));
+        };
+        map.insert(key, decode_nested(input, depth + 1)?);
+    }
+    Ok(PackValue::Map(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackValue, decode, encode};
+    use std::collections::BTreeMap;
+
+    fn round_trip(value: PackValue) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        encode(&value, &mut bytes);
+        let mut input = bytes.as_slice();
+        assert_eq!(decode(&mut input).unwrap(), value);
+        assert!(input.is_empty());
+        bytes
+    }
+
+    #[test]
+    fn values_round_trip_with_the_smallest_markers() {
+        assert_eq!(round_trip(PackValue::Int(-16)), [0xF0]);
+        assert_eq!(round_trip(PackValue::Int(-17)), [0xC8, 0xEF]);
+        assert_eq!(round_trip(PackValue::Int(200)), [0xC9, 0x00, 0xC8]);
+        assert_eq!(round_trip(PackValue::Int(i64::MIN)).len(), 9);
+        assert_eq!(round_trip("a".into()), [0x81, b'a']);
+        assert_eq!(round_trip(PackValue::String("x".repeat(300))).len(), 303);
+        round_trip(PackValue::Float(1.5));
+        round_trip(PackValue::Bytes(vec![1, 2, 3]));
+        round_trip(PackValue::List((0..20).map(PackValue::Int).collect()));
+        round_trip(PackValue::Map(BTreeMap::from([
+            ("k".to_string(), PackValue::Null),
+            ("b".to_string(), PackValue::Bool(true)),
+        ])));
+        assert_eq!(
+            round_trip(PackValue::Struct {
+                tag: 0x3F,
+                fields: vec![PackValue::Map(BTreeMap::new())],
+            }),
+            [0xB1, 0x3F, 0xA0]
+        );
+    }
+
+    #[test]
+    fn truncated_and_unknown_input_is_rejected() {
+        assert!(decode(&mut [0xC9, 0x00].as_slice()).is_err());
+        assert!(decode(&mut [0x85, b'a'].as_slice()).is_err());
+        assert!(decode(&mut [0xA1, 0x01, 0x01].as_slice()).is_err());
+        assert!(decode(&mut [0xE0].as_slice()).is_err());
+        assert!(decode(&mut [0x91; 100_000].as_slice()).is_err());
+    }
+}
@@ -0,0 +1,586 @@
+//! The per-connection Bolt state machine.
+//!
+//! Reads are executed as `PULL` asks for records: `RUN` opens a cursor over
+//! its own snapshot, which stays pinned until the result is pulled to the
+//! end or discarded. Writes run to completion at `RUN`, and auto-commit
+//! writes commit there, so their bookmark is known when the stream ends.
+
+use crate::ServerConfig;
+use crate::exec::{ErrorKind, auto_commit_write, mixed_rows};
+use crate::packstream::PackValue;
+use crate::values::{Version, from_pack, to_pack};
+use nervusdb::{Bookmark, Db, DbSnapshot, WriteTxn};
+use nervusdb_query::{Params, PreparedQuery, Row, Value, prepare};
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::sync::Arc;
+use std::time::Duration;
+
+const HELLO: u8 = 0x01;
+const GOODBYE: u8 = 0x02;
+const RESET: u8 = 0x0F;
+const RUN: u8 = 0x10;
+const BEGIN: u8 = 0x11;
+const COMMIT: u8 = 0x12;
+const ROLLBACK: u8 = 0x13;
+const DISCARD: u8 = 0x2F;
+const PULL: u8 = 0x3F;
+
+const SUCCESS: u8 = 0x70;
+const RECORD: u8 = 0x71;
+const IGNORED: u8 = 0x7E;
+const FAILURE: u8 = 0x7F;
+
+/// Bookmarks are `nervusdb:<txid>`.
+const BOOKMARK_PREFIX: &str = "nervusdb:";
+/// How long `BEGIN` and `RUN` wait for the commits their bookmarks name.
+const BOOKMARK_WAIT: Duration = Duration::from_secs(30);
+
+/// A Bolt `FAILURE`: a Neo4j status code and a message.
+struct Failure {
+    code: &'static str,
+    message: String,
+}
+
+impl Failure {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn request(message: impl Into<String>) -> Self {
+        Self::new("Neo.ClientError.Request.Invalid", message)
+    }
+
+    fn query(message: String) -> Self {
+        let code = match ErrorKind::of(&message) {
+            ErrorKind::Syntax => "Neo.ClientError.Statement.SyntaxError",
+            ErrorKind::Constraint => "Neo.ClientError.Schema.ConstraintValidationFailed",
+            ErrorKind::ResourceLimit => "Neo.TransientError.General.MemoryPoolOutOfMemoryError",
+            ErrorKind::Execution => "Neo.ClientError.Statement.ExecutionFailed",
+        };
+        Self::new(code, message)
+    }
+}
+
+// A session holds at most one, so the size gap between variants is moot.
+#[allow(clippy::large_enum_variant)]
+enum Transaction<'db> {
+    /// `BEGIN` with `mode: "r"` reads one snapshot and takes no write lock.
+    Read(Arc<DbSnapshot>),
+    Write(WriteTxn<'db>),
+}
+
+type RowIter = Box<dyn Iterator<Item = nervusdb_query::Result<Row>>>;
+
+/// The rows of a result, produced one at a time.
+struct Cursor {
+    /// Borrows the fields below, so it is declared (and dropped) first.
+    rows: Peekable<RowIter>,
+    /// Reifies rows as they are pulled; `None` when they already are.
+    snapshot: Option<Arc<DbSnapshot>>,
+    _params: Option<Arc<Params>>,
+    _query: Option<Arc<PreparedQuery>>,
+}
+
+impl Cursor {
+    /// Executes the read `query` lazily over `snapshot`.
+    fn read(query: Arc<PreparedQuery>, snapshot: Arc<DbSnapshot>, params: Params) -> Self {
+        let params = Arc::new(params);
+        let rows: Box<dyn Iterator<Item = nervusdb_query::Result<Row>> + '_> =
+            Box::new(query.execute_streaming(&*snapshot, &params));
+        let rows: RowIter = unsafe {
+            // SAFETY: the iterator borrows the snapshot, params and query,
+            // which stay at stable heap addresses in the same `Cursor` and
+            // are dropped after it.
+            std::mem::transmute::<Box<dyn Iterator<Item = nervusdb_query::Result<Row>> + '_>, RowIter>(
+                rows,
+            )
+        };
+        Self {
+            rows: rows.peekable(),
+            snapshot: Some(snapshot),
+            _params: Some(params),
+            _query: Some(query),
+        }
+    }
+
+    /// Hands out rows a write already produced and reified.
+    fn materialized(rows: Vec<Row>) -> Self {
+        let rows: RowIter = Box::new(rows.into_iter().map(Ok));
+        Self {
+            rows: rows.peekable(),
+            snapshot: None,
+            _params: None,
+            _query: None,
+        }
+    }
+
+    /// The columns of the first row, without consuming it.
+    fn first_columns(&mut self) -> Result<Vec<String>, Failure> {
+        match self.rows.peek() {
+            None => Ok(Vec::new()),
+            Some(Ok(row)) => Ok(row.columns().iter().map(|(k, _)| k.clone()).collect()),
+            Some(Err(_)) => Err(self.next().err().unwrap()),
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Row>, Failure> {
+        let Some(row) = self.rows.next() else {
+            return Ok(None);
+        };
+        let row = row.map_err(|e| Failure::query(e.to_string()))?;
+        match &self.snapshot {
+            Some(snapshot) => row
+                .reify(&**snapshot)
+                .map(Some)
+                .map_err(|e| Failure::query(e.to_string())),
+            None => Ok(Some(row)),
+        }
+    }
+
+    fn has_more(&mut self) -> bool {
+        self.rows.peek().is_some()
+    }
+}
+
+/// A result waiting to be pulled.
+struct Stream {
+    cursor: Cursor,
+    /// Record fields, in order.
+    names: Vec<String>,
+    /// Entries of the final `SUCCESS`.
+    summary: BTreeMap<String, PackValue>,
+}
+
+enum State {
+    /// Waiting for `HELLO`.
+    Connected,
+    Ready,
+    /// A request failed; everything but `RESET` is ignored.
+    Failed,
+    /// `GOODBYE`, or a failure the connection cannot recover from.
+    Closed,
+}
+
+pub(crate) struct Session<'db> {
+    db: &'db Db,
+    config: &'db ServerConfig,
+    version: Version,
+    connection_id: String,
+    state: State,
+    txn: Option<Transaction<'db>>,
+    stream: Option<Stream>,
+    /// Id of the last result opened in the explicit transaction.
+    qid: i64,
+}
+
+impl<'db> Session<'db> {
+    pub(crate) fn new(
+        db: &'db Db,
+        config: &'db ServerConfig,
+        version: Version,
+        connection_id: String,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            version,
+            connection_id,
+            state: State::Connected,
+            txn: None,
+            stream: None,
+            qid: -1,
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        matches!(self.state, State::Closed)
+    }
+
+    /// Handles one request and returns the responses to send, in order.
+    pub(crate) fn handle(&mut self, message: PackValue) -> Vec<PackValue> {
+        let PackValue::Struct { tag, fields } = message else {
+            self.state = State::Closed;
+            return vec![failure(&Failure::request("message is not a structure"))];
+        };
+        match (&self.state, tag) {
+            (State::Closed, _) => return Vec::new(),
+            (_, GOODBYE) => {
+                self.state = State::Closed;
+                return Vec::new();
+            }
+            (State::Connected, HELLO) => {}
+            (State::Connected, _) => {
+                self.state = State::Closed;
+                return vec![failure(&Failure::request("expected HELLO"))];
+            }
+            (_, RESET) => {
+                self.txn = None;
+                self.stream = None;
+                self.state = State::Ready;
+                return vec![success(BTreeMap::new())];
+            }
+            (State::Failed, _) => return vec![response(IGNORED, Vec::new())],
+            (State::Ready, _) => {}
+        }
+
+        let mut responses = Vec::new();
+        let result = match tag {
+            HELLO => self.hello(&fields),
+            RUN => self.run(&fields),
+            PULL => self.pull(&fields, &mut responses),
+            DISCARD => self.discard(&fields),
+            BEGIN => self.begin(&fields),
+            COMMIT => self.commit(),
+            ROLLBACK => self.rollback(),
+            _ => Err(Failure::request(format!("unsupported message 0x{tag:02X}"))),
+        };
+        match result {
+            Ok(metadata) => responses.push(success(metadata)),
+            Err(err) => {
+                if matches!(self.state, State::Connected) {
+                    self.state = State::Closed;
+                } else {
+                    self.state = State::Failed;
+                }
+                self.stream = None;
+                responses.push(failure(&err));
+            }
+        }
+        responses
+    }
+
+    fn hello(&mut self, fields: &[PackValue]) -> Result<BTreeMap<String, PackValue>, Failure> {
+        if !matches!(self.state, State::Connected) {
+            return Err(Failure::request("HELLO was already sent"));
+        }
+        let extra = fields.first().and_then(PackValue::as_map);
+        if let Some(auth) = &self.config.auth {
+            let field = |name| extra.and_then(|extra| extra.get(name)?.as_str());
+            if !auth.accepts(
+                field("principal").unwrap_or_default(),
+                field("credentials").unwrap_or_default(),
+            ) {
+                return Err(Failure::new(
+                    "Neo.ClientError.Security.Unauthorized",
+                    "the client is unauthorized due to authentication failure",
+                ));
+            }
+        }
+        self.state = State::Ready;
+        // Drivers parse the agent as `Neo4j/<version>` before anything else,
+        // so our own name rides along as a version suffix.
+        Ok(BTreeMap::from([
+            (
+                "server".to_string(),
+                format!("Neo4j/5.0.0-nervusdb-{}", env!("CARGO_PKG_VERSION")).into(),
+            ),
+            (
+                "connection_id".to_string(),
+                self.connection_id.clone().into(),
+            ),
+        ]))
+    }
+
+    fn run(&mut self, fields: &[PackValue]) -> Result<BTreeMap<String, PackValue>, Failure> {
+        let [PackValue::String(query), parameters, rest @ ..] = fields else {
+            return Err(Failure::request("RUN expects a query and parameters"));
+        };
+        if self.stream.is_some() && self.txn.is_none() {
+            return Err(Failure::request(
+                "the previous result must be pulled or discarded first",
+            ));
+        }
+        let params = params(parameters)?;
+        let prepared = Arc::new(prepare(query).map_err(|e| Failure::query(e.to_string()))?);
+
+        let (mut cursor, summary) = match &mut self.txn {
+            None => {
+                let extra = rest.first().and_then(PackValue::as_map);
+                self.wait_for_bookmarks(extra)?;
+                let read_only = extra
+                    .and_then(|extra| extra.get("mode")?.as_str())
+                    .is_some_and(|mode| mode == "r");
+                self.run_auto_commit(&prepared, params, read_only)?
+            }
+            Some(Transaction::Read(snapshot)) => {
+                if prepared.is_write() {
+                    return Err(write_in_read_mode());
+                }
+                let cursor = Cursor::read(Arc::clone(&prepared), Arc::clone(snapshot), params);
+                (cursor, summary_of("r"))
+            }
+            Some(Transaction::Write(txn)) => {
+                if prepared.periodic_commit().is_some() {
+                    return Err(Failure::query(
+                        "USING PERIODIC COMMIT cannot run inside an explicit transaction".into(),
+                    ));
+                }
+                if prepared.is_write() {
+                    let snapshot = self.db.snapshot_in(txn);
+                    let (rows, _) = prepared
+                        .execute_mixed(&snapshot, txn, &params)
+                        .map_err(|e| Failure::query(e.to_string()))?;
+                    let rows = reify(mixed_rows(rows, &prepared), &self.db.snapshot_in(txn))?;
+                    (Cursor::materialized(rows), summary_of("rw"))
+                } else {
+                    let snapshot = Arc::new(self.db.snapshot_in(txn));
+                    let cursor = Cursor::read(Arc::clone(&prepared), snapshot, params);
+                    (cursor, summary_of("r"))
+                }
+            }
+        };
+
+        let names = match prepared.columns() {
+            Some(names) => names,
+            None => cursor.first_columns()?,
+        };
+        self.stream = Some(Stream {
+            cursor,
+            names: names.clone(),
+            summary,
+        });
+
+        let mut metadata = BTreeMap::from([
+            (
+                "fields".to_string(),
+                PackValue::List(names.into_iter().map(PackValue::String).collect()),
+            ),
+            ("t_first".to_string(), PackValue::Int(0)),
+        ]);
+        if self.txn.is_some() {
+            self.qid += 1;
+            metadata.insert("qid".to_string(), PackValue::Int(self.qid));
+        }
+        Ok(metadata)
+    }
+
+    fn run_auto_commit(
+        &self,
+        prepared: &Arc<PreparedQuery>,
+        params: Params,
+        read_only: bool,
+    ) -> Result<(Cursor, BTreeMap<String, PackValue>), Failure> {
+        if !prepared.is_write() {
+            let cursor = Cursor::read(Arc::clone(prepared), Arc::new(self.db.snapshot()), params);
+            return Ok((cursor, summary_of("r")));
+        }
+        if read_only {
+            return Err(write_in_read_mode());
+        }
+        let (rows, _, committed) =
+            auto_commit_write(self.db, prepared, &params).map_err(Failure::query)?;
+        let mut summary = summary_of("w");
+        summary.insert("bookmark".to_string(), bookmark(committed).into());
+        Ok((Cursor::materialized(rows), summary))
+    }
+
+    fn pull(
+        &mut self,
+        fields: &[PackValue],
+        responses: &mut Vec<PackValue>,
+    ) -> Result<BTreeMap<String, PackValue>, Failure> {
+        let n = fetch_size(fields);
+        let version = self.version;
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| Failure::request("there is no result to pull"))?;
+        let mut pulled = 0;
+        while n < 0 || pulled < n {
+            let Some(row) = stream.cursor.next()? else {
+                break;
+            };
+            let record = stream
+                .names
+                .iter()
+                .map(|name| to_pack(row.get(name).cloned().unwrap_or(Value::Null), version))
+                .collect();
+            responses.push(response(RECORD, vec![PackValue::List(record)]));
+            pulled += 1;
+        }
+        if stream.cursor.has_more() {
+            return Ok(BTreeMap::from([(
+                "has_more".to_string(),
+                PackValue::Bool(true),
+            )]));
+        }
+        let mut summary = self.stream.take().map(|s| s.summary).unwrap_or_default();
+        summary.insert("t_last".to_string(), PackValue::Int(0));
+        Ok(summary)
+    }
+
+    fn discard(&mut self, fields: &[PackValue]) -> Result<BTreeMap<String, PackValue>, Failure> {
+        let n = fetch_size(fields);
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| Failure::request("there is no result to discard"))?;
+        if n >= 0 {
+            // Skipped rows are still produced, so their errors surface.
+            for _ in 0..n {
+                if let Some(Err(e)) = stream.cursor.rows.next() {
+                    return Err(Failure::query(e.to_string()));
+                }
+            }
+            if stream.cursor.has_more() {
+                return Ok(BTreeMap::from([(
+                    "has_more".to_string(),
+                    PackValue::Bool(true),
+                )]));
+            }
+        }
+        let mut summary = self.stream.take().map(|s| s.summary).unwrap_or_default();
+        summary.insert("t_last".to_string(), PackValue::Int(0));
+        Ok(summary)
+    }
+
+    fn begin(&mut self, fields: &[PackValue]) -> Result<BTreeMap<String, PackValue>, Failure> {
+        if self.txn.is_some() {
+            return Err(Failure::request("a transaction is already open"));
+        }
+        if self.stream.is_some() {
+            return Err(Failure::request(
+                "the previous result must be pulled or discarded first",
+            ));
+        }
+        let extra = fields.first().and_then(PackValue::as_map);
+        self.wait_for_bookmarks(extra)?;
+        let read_only = extra
+            .and_then(|extra| extra.get("mode")?.as_str())
+            .is_some_and(|mode| mode == "r");
+        self.txn = Some(if read_only {
+            Transaction::Read(Arc::new(self.db.snapshot()))
+        } else {
+            Transaction::Write(self.db.begin_write())
+        });
+        self.qid = -1;
+        Ok(BTreeMap::new())
+    }
+
+    fn commit(&mut self) -> Result<BTreeMap<String, PackValue>, Failure> {
+        self.stream = None;
+        let bookmark = match self.txn.take() {
+            None => return Err(Failure::request("there is no transaction to commit")),
+            Some(Transaction::Read(_)) => self.db.last_bookmark(),
+            Some(Transaction::Write(txn)) => txn
+                .commit_with_bookmark()
+                .map_err(|e| Failure::query(e.to_string()))?,
+        };
+        Ok(BTreeMap::from([(
+            "bookmark".to_string(),
+            self::bookmark(bookmark).into(),
+        )]))
+    }
+
+    fn rollback(&mut self) -> Result<BTreeMap<String, PackValue>, Failure> {
+        self.stream = None;
+        match self.txn.take() {
+            None => Err(Failure::request("there is no transaction to roll back")),
+            Some(Transaction::Read(_)) => Ok(BTreeMap::new()),
+            Some(Transaction::Write(txn)) => {
+                txn.rollback();
+                Ok(BTreeMap::new())
+            }
+        }
+    }
+
+    /// Waits until the commits named by `extra.bookmarks` are visible.
+    /// Bookmarks from other servers are not ours to wait for and are skipped.
+    fn wait_for_bookmarks(
+        &self,
+        extra: Option<&BTreeMap<String, PackValue>>,
+    ) -> Result<(), Failure> {
+        let Some(PackValue::List(bookmarks)) = extra.and_then(|extra| extra.get("bookmarks"))
+        else {
+            return Ok(());
+        };
+        for bookmark in bookmarks {
+            let Some(txid) = bookmark
+                .as_str()
+                .and_then(|b| b.strip_prefix(BOOKMARK_PREFIX))
+                .and_then(|txid| txid.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            self.db
+                .wait_for_bookmark(Bookmark::from(txid), BOOKMARK_WAIT)
+                .map_err(|e| {
+                    Failure::new(
+                        "Neo.TransientError.Transaction.BookmarkTimeout",
+                        e.to_string(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+}
+
+fn bookmark(bookmark: Bookmark) -> String {
+    format!("{BOOKMARK_PREFIX}{}", bookmark.txid())
+}
+
+fn write_in_read_mode() -> Failure {
+    Failure::new(
+        "Neo.ClientError.Statement.AccessMode",
+        "writes are not allowed in read access mode",
+    )
+}
+
+fn params(parameters: &PackValue) -> Result<Params, Failure> {
+    let mut params = Params::new();
+    match parameters {
+        PackValue::Null => {}
+        PackValue::Map(map) => {
+            for (name, value) in map {
+                let value = from_pack(value.clone()).map_err(|message| {
+                    Failure::new("Neo.ClientError.Statement.TypeError", message)
+                })?;
+                params.insert(name.clone(), value);
+            }
+        }
+        _ => return Err(Failure::request("RUN parameters must be a map")),
+    }
+    Ok(params)
+}
+
+/// The `n` of `PULL` / `DISCARD`; `-1`, and a missing `n`, mean all.
+fn fetch_size(fields: &[PackValue]) -> i64 {
+    fields
+        .first()
+        .and_then(PackValue::as_map)
+        .and_then(|extra| extra.get("n")?.as_int())
+        .unwrap_or(-1)
+}
+
+fn summary_of(kind: &str) -> BTreeMap<String, PackValue> {
+    BTreeMap::from([("type".to_string(), kind.into())])
+}
+
+fn reify(rows: Vec<Row>, snapshot: &DbSnapshot) -> Result<Vec<Row>, Failure> {
+    rows.iter()
+        .map(|row| row.reify(snapshot))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Failure::query(e.to_string()))
+}
+
+fn response(tag: u8, fields: Vec<PackValue>) -> PackValue {
+    PackValue::Struct { tag, fields }
+}
+
+fn success(metadata: BTreeMap<String, PackValue>) -> PackValue {
+    response(SUCCESS, vec![PackValue::Map(metadata)])
+}
+
+fn failure(failure: &Failure) -> PackValue {
+    response(
+        FAILURE,
+        vec![PackValue::Map(BTreeMap::from([
+            ("code".to_string(), failure.code.into()),
+            ("message".to_string(), failure.message.clone().into()),
+        ]))],
+    )
+}
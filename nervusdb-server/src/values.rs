@@ -0,0 +1,208 @@
+//! Conversions between query values and their PackStream form.
+
+use crate::packstream::PackValue;
+use nervusdb_query::{EdgeKey, Value};
+use std::collections::BTreeMap;
+
+const NODE: u8 = 0x4E;
+const RELATIONSHIP: u8 = 0x52;
+const UNBOUND_RELATIONSHIP: u8 = 0x72;
+const PATH: u8 = 0x50;
+/// `DateTime` with UTC seconds, Bolt 5.
+const DATE_TIME_UTC: u8 = 0x49;
+/// `DateTime` with local seconds, Bolt 4.
+const DATE_TIME_LOCAL: u8 = 0x46;
+
+/// The negotiated protocol version, as far as values are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Version {
+    V4_4,
+    V5_0,
+}
+
+impl Version {
+    /// Bolt 5 adds string element ids to nodes and relationships.
+    fn element_ids(self) -> bool {
+        self == Version::V5_0
+    }
+}
+
+/// The numeric id of a relationship. Relationships are identified by their
+/// endpoints and type, so parallel relationships of different types share
+/// one; their element ids tell them apart.
+fn relationship_id(key: EdgeKey) -> i64 {
+    ((key.src as i64) << 32) | key.dst as i64
+}
+
+fn relationship_element_id(key: EdgeKey) -> String {
+    format!("{}:{}:{}", key.src, key.rel, key.dst)
+}
+
+/// Encodes a reified query value. Node ids, relationship keys and paths not
+/// reified against a snapshot go out as their bare ids.
+pub(crate) fn to_pack(value: Value, version: Version) -> PackValue {
+    match value {
+        Value::Null => PackValue::Null,
+        Value::Bool(b) => PackValue::Bool(b),
+        Value::Int(i) => PackValue::Int(i),
+        Value::Float(f) => PackValue::Float(f),
+        Value::String(s) => PackValue::String(s),
+        Value::Blob(bytes) => PackValue::Bytes(bytes),
+        Value::NodeId(id) => PackValue::Int(id as i64),
+        Value::ExternalId(id) => PackValue::Int(id as i64),
+        Value::EdgeKey(key) => PackValue::String(relationship_element_id(key)),
+        Value::DateTime(micros) => {
+            let seconds = micros.div_euclid(1_000_000);
+            let nanos = micros.rem_euclid(1_000_000) * 1_000;
+            let tag = match version {
+                Version::V5_0 => DATE_TIME_UTC,
+                Version::V4_4 => DATE_TIME_LOCAL,
+            };
+            PackValue::Struct {
+                tag,
+                fields: vec![seconds.into(), nanos.into(), 0.into()],
+            }
+        }
+        Value::List(items) => PackValue::List(
+            items
+                .into_iter()
+                .map(|item| to_pack(item, version))
+                .collect(),
+        ),
+        Value::Map(map) => map_to_pack(map, version),
+        Value::Path(path) => PackValue::List(
+            path.nodes
+                .into_iter()
+                .map(|id| PackValue::Int(id as i64))
+                .collect(),
+        ),
+        Value::Node(node) => {
+            let mut fields = vec![
+                PackValue::Int(node.id as i64),
+                PackValue::List(node.labels.into_iter().map(PackValue::String).collect()),
+                map_to_pack(node.properties, version),
+            ];
+            if version.element_ids() {
+                fields.push(node.id.to_string().into());
+            }
+            PackValue::Struct { tag: NODE, fields }
+        }
+        Value::Relationship(rel) => {
+            let key = rel.key;
+            let mut fields = vec![
+                relationship_id(key).into(),
+                PackValue::Int(key.src as i64),
+                PackValue::Int(key.dst as i64),
+                rel.rel_type.into(),
+                map_to_pack(rel.properties, version),
+            ];
+            if version.element_ids() {
+                fields.push(relationship_element_id(key).into());
+                fields.push(key.src.to_string().into());
+                fields.push(key.dst.to_string().into());
+            }
+            PackValue::Struct {
+                tag: RELATIONSHIP,
+                fields,
+            }
+        }
+        Value::ReifiedPath(path) => {
+            // Nodes and relationships are listed once each; `indices`
+            // alternates a 1-based relationship index, negative when walked
+            // against its direction, with the 0-based index of the next node.
+            let mut node_ids = Vec::new();
+            let mut nodes = Vec::new();
+            for node in &path.nodes {
+                if !node_ids.contains(&node.id) {
+                    node_ids.push(node.id);
+                    nodes.push(to_pack(Value::Node(node.clone()), version));
+                }
+            }
+            let mut rel_keys = Vec::new();
+            let mut rels = Vec::new();
+            let mut indices = Vec::new();
+            for (i, rel) in path.relationships.into_iter().enumerate() {
+                let key = rel.key;
+                if !rel_keys.contains(&key) {
+                    rel_keys.push(key);
+                    let mut fields = vec![
+                        relationship_id(key).into(),
+                        rel.rel_type.into(),
+                        map_to_pack(rel.properties, version),
+                    ];
+                    if version.element_ids() {
+                        fields.push(relationship_element_id(key).into());
+                    }
+                    rels.push(PackValue::Struct {
+                        tag: UNBOUND_RELATIONSHIP,
+                        fields,
+                    });
+                }
+                let index = rel_keys.iter().position(|seen| *seen == key).unwrap_or(0) as i64 + 1;
+                let forward = path.nodes.get(i).is_some_and(|node| node.id == key.src);
+                indices.push(PackValue::Int(if forward { index } else { -index }));
+                let next = path
+                    .nodes
+                    .get(i + 1)
+                    .and_then(|node| node_ids.iter().position(|id| *id == node.id))
+                    .unwrap_or(0);
+                indices.push(PackValue::Int(next as i64));
+            }
+            PackValue::Struct {
+                tag: PATH,
+                fields: vec![
+                    PackValue::List(nodes),
+                    PackValue::List(rels),
+                    PackValue::List(indices),
+                ],
+            }
+        }
+    }
+}
+
+fn map_to_pack(map: BTreeMap<String, Value>, version: Version) -> PackValue {
+    PackValue::Map(
+        map.into_iter()
+            .map(|(key, value)| (key, to_pack(value, version)))
+            .collect(),
+    )
+}
+
+/// Decodes a query parameter. Of the structures only date-times are
+/// accepted; nodes, relationships and paths cannot be passed back in.
+pub(crate) fn from_pack(value: PackValue) -> Result<Value, String> {
+    Ok(match value {
+        PackValue::Null => Value::Null,
+        PackValue::Bool(b) => Value::Bool(b),
+        PackValue::Int(i) => Value::Int(i),
+        PackValue::Float(f) => Value::Float(f),
+        PackValue::String(s) => Value::String(s),
+        PackValue::Bytes(bytes) => Value::Blob(bytes),
+        PackValue::List(items) => {
+            Value::List(items.into_iter().map(from_pack).collect::<Result<_, _>>()?)
+        }
+        PackValue::Map(map) => Value::Map(
+            map.into_iter()
+                .map(|(key, value)| Ok((key, from_pack(value)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        PackValue::Struct { tag, fields } => match (tag, fields.as_slice()) {
+            (
+                DATE_TIME_UTC | DATE_TIME_LOCAL,
+                [
+                    PackValue::Int(seconds),
+                    PackValue::Int(nanos),
+                    PackValue::Int(offset),
+                ],
+            ) => {
+                let seconds = if tag == DATE_TIME_LOCAL {
+                    seconds - offset
+                } else {
+                    *seconds
+                };
+                Value::DateTime(seconds * 1_000_000 + nanos / 1_000)
+            }
+            _ => return Err(format!("unsupported parameter structure 0x{tag:02X}")),
+        },
+    })
+}
@@ -0,0 +1,309 @@
+use nervusdb::Db;
+use nervusdb_server::packstream::PackValue;
+use nervusdb_server::{Auth, Server, ServerConfig, read_message, write_message};
+use std::collections::BTreeMap;
+use std::io::{BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+
+const HELLO: u8 = 0x01;
+const RESET: u8 = 0x0F;
+const RUN: u8 = 0x10;
+const BEGIN: u8 = 0x11;
+const COMMIT: u8 = 0x12;
+const ROLLBACK: u8 = 0x13;
+const DISCARD: u8 = 0x2F;
+const PULL: u8 = 0x3F;
+const SUCCESS: u8 = 0x70;
+const RECORD: u8 = 0x71;
+const IGNORED: u8 = 0x7E;
+const FAILURE: u8 = 0x7F;
+
+const V5: [u8; 4] = [0, 0, 0, 5];
+const V4: [u8; 4] = [0, 0, 4, 4];
+
+fn start(config: ServerConfig) -> (tempfile::TempDir, SocketAddr) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("bolt.ndb")).unwrap());
+    let server = Server::bind(db, "127.0.0.1:0", config).unwrap();
+    let addr = server.local_addr().unwrap();
+    std::thread::spawn(move || server.serve());
+    (dir, addr)
+}
+
+fn map(entries: &[(&str, PackValue)]) -> PackValue {
+    PackValue::Map(
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+    )
+}
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    /// Connects proposing `version` only and returns the agreed version.
+    fn connect(addr: SocketAddr, version: [u8; 4]) -> (Self, [u8; 4]) {
+        let mut writer = TcpStream::connect(addr).unwrap();
+        writer.write_all(&[0x60, 0x60, 0xB0, 0x17]).unwrap();
+        writer.write_all(&version).unwrap();
+        writer.write_all(&[0; 12]).unwrap();
+        let mut reader = BufReader::new(writer.try_clone().unwrap());
+        let mut agreed = [0; 4];
+        reader.read_exact(&mut agreed).unwrap();
+        (Self { reader, writer }, agreed)
+    }
+
+    fn open(addr: SocketAddr, version: [u8; 4]) -> Self {
+        let (mut client, agreed) = Self::connect(addr, version);
+        assert_eq!(agreed, version);
+        let (tag, _) = client.request(HELLO, vec![map(&[("user_agent", "test/1".into())])]);
+        assert_eq!(tag, SUCCESS);
+        client
+    }
+
+    fn send(&mut self, tag: u8, fields: Vec<PackValue>) {
+        write_message(&mut self.writer, &PackValue::Struct { tag, fields }).unwrap();
+    }
+
+    fn receive(&mut self) -> Option<(u8, Vec<PackValue>)> {
+        let bytes = read_message(&mut self.reader).unwrap()?;
+        match nervusdb_server::packstream::decode(&mut bytes.as_slice()).unwrap() {
+            PackValue::Struct { tag, fields } => Some((tag, fields)),
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    /// Sends a request and returns its summary, collecting records.
+    fn request(&mut self, tag: u8, fields: Vec<PackValue>) -> (u8, BTreeMap<String, PackValue>) {
+        self.send(tag, fields);
+        let (tag, fields) = self.receive().expect("a response");
+        let metadata = fields
+            .into_iter()
+            .next()
+            .and_then(|m| m.as_map().cloned())
+            .unwrap_or_default();
+        (tag, metadata)
+    }
+
+    fn run(&mut self, query: &str, params: PackValue) -> (u8, BTreeMap<String, PackValue>) {
+        self.request(RUN, vec![query.into(), params, map(&[])])
+    }
+
+    /// Pulls `n` records; returns them with the summary.
+    fn pull(&mut self, n: i64) -> (Vec<Vec<PackValue>>, u8, BTreeMap<String, PackValue>) {
+        self.send(PULL, vec![map(&[("n", n.into())])]);
+        let mut records = Vec::new();
+        loop {
+            let (tag, fields) = self.receive().expect("a response");
+            match (tag, fields.into_iter().next()) {
+                (RECORD, Some(PackValue::List(values))) => records.push(values),
+                (tag, metadata) => {
+                    let metadata = metadata
+                        .and_then(|m| m.as_map().cloned())
+                        .unwrap_or_default();
+                    return (records, tag, metadata);
+                }
+            }
+        }
+    }
+
+    fn query(&mut self, query: &str) -> Vec<Vec<PackValue>> {
+        let (tag, metadata) = self.run(query, map(&[]));
+        assert_eq!(tag, SUCCESS, "{metadata:?}");
+        let (records, tag, metadata) = self.pull(-1);
+        assert_eq!(tag, SUCCESS, "{metadata:?}");
+        records
+    }
+}
+
+#[test]
+fn auto_commit_runs_stream_records_in_batches() {
+    let (_dir, addr) = start(ServerConfig::default());
+    let mut client = Client::open(addr, V5);
+
+    let (tag, metadata) = client.run(
+        "UNWIND $names AS name CREATE (:Person {name: name})",
+        map(&[(
+            "names",
+            PackValue::List(vec!["Ada".into(), "Alan".into(), "Grace".into()]),
+        )]),
+    );
+    assert_eq!(tag, SUCCESS);
+    assert_eq!(metadata["fields"], PackValue::List(Vec::new()));
+    let (records, tag, summary) = client.pull(-1);
+    assert_eq!((records.len(), tag), (0, SUCCESS));
+    assert_eq!(summary["type"], "w".into());
+    assert!(
+        summary["bookmark"]
+            .as_str()
+            .unwrap()
+            .starts_with("nervusdb:")
+    );
+
+    let (_, metadata) = client.run(
+        "MATCH (p:Person) RETURN p.name AS name, p ORDER BY name",
+        map(&[]),
+    );
+    assert_eq!(
+        metadata["fields"],
+        PackValue::List(vec!["name".into(), "p".into()])
+    );
+    let (records, _, summary) = client.pull(2);
+    assert_eq!(summary["has_more"], PackValue::Bool(true));
+    assert_eq!(records[0][0], "Ada".into());
+    let PackValue::Struct { tag: 0x4E, fields } = &records[1][1] else {
+        panic!("expected a node, got {:?}", records[1][1]);
+    };
+    assert_eq!(fields.len(), 4, "Bolt 5 nodes carry an element id");
+    assert_eq!(fields[1], PackValue::List(vec!["Person".into()]));
+    assert_eq!(fields[2], map(&[("name", "Alan".into())]));
+    let (records, _, summary) = client.pull(2);
+    assert_eq!(records.len(), 1);
+    assert_eq!(summary["type"], "r".into());
+}
+
+#[test]
+fn pulls_produce_records_as_they_are_asked_for() {
+    let (_dir, addr) = start(ServerConfig::default());
+    let mut client = Client::open(addr, V5);
+
+    // Ten billion rows: only a cursor can answer this.
+    let (tag, metadata) = client.run(
+        "UNWIND range(1, 100000) AS a UNWIND range(1, 100000) AS b RETURN a, b",
+        map(&[]),
+    );
+    assert_eq!(tag, SUCCESS, "{metadata:?}");
+    let (records, _, summary) = client.pull(2);
+    assert_eq!(
+        records,
+        [
+            vec![PackValue::Int(1), PackValue::Int(1)],
+            vec![PackValue::Int(1), PackValue::Int(2)]
+        ]
+    );
+    assert_eq!(summary["has_more"], PackValue::Bool(true));
+    let (tag, summary) = client.request(DISCARD, vec![map(&[("n", 3.into())])]);
+    assert_eq!(
+        (tag, &summary["has_more"]),
+        (SUCCESS, &PackValue::Bool(true))
+    );
+    let (records, _, _) = client.pull(1);
+    assert_eq!(records, [vec![PackValue::Int(1), PackValue::Int(6)]]);
+    let (tag, summary) = client.request(DISCARD, vec![map(&[("n", (-1).into())])]);
+    assert_eq!((tag, summary["type"].clone()), (SUCCESS, "r".into()));
+
+    // The same inside a read transaction, whose snapshot the cursor shares.
+    assert_eq!(
+        client.request(BEGIN, vec![map(&[("mode", "r".into())])]).0,
+        SUCCESS
+    );
+    client.run(
+        "UNWIND range(1, 100000) AS a UNWIND range(1, 100000) AS b RETURN a",
+        map(&[]),
+    );
+    let (records, _, summary) = client.pull(1);
+    assert_eq!(records, [vec![PackValue::Int(1)]]);
+    assert_eq!(summary["has_more"], PackValue::Bool(true));
+    assert_eq!(client.request(COMMIT, Vec::new()).0, SUCCESS);
+}
+
+#[test]
+fn explicit_transactions_commit_or_roll_back() {
+    let (_dir, addr) = start(ServerConfig::default());
+    let mut client = Client::open(addr, V4);
+
+    assert_eq!(client.request(BEGIN, vec![map(&[])]).0, SUCCESS);
+    client.query("CREATE (:Item {n: 1})");
+    assert_eq!(
+        client.query("MATCH (i:Item) RETURN count(i)"),
+        vec![vec![PackValue::Int(1)]]
+    );
+    assert_eq!(client.request(ROLLBACK, Vec::new()).0, SUCCESS);
+    assert_eq!(
+        client.query("MATCH (i:Item) RETURN count(i)"),
+        vec![vec![PackValue::Int(0)]]
+    );
+
+    assert_eq!(client.request(BEGIN, vec![map(&[])]).0, SUCCESS);
+    let records = client.query("CREATE (i:Item {n: 2}) RETURN i");
+    let PackValue::Struct { tag: 0x4E, fields } = &records[0][0] else {
+        panic!("expected a node, got {:?}", records[0][0]);
+    };
+    assert_eq!(fields.len(), 3, "Bolt 4 nodes have no element id");
+    let (tag, metadata) = client.request(COMMIT, Vec::new());
+    assert_eq!(tag, SUCCESS);
+    let bookmark = metadata["bookmark"].clone();
+
+    let mut reader = Client::open(addr, V5);
+    let begin = map(&[
+        ("mode", "r".into()),
+        ("bookmarks", PackValue::List(vec![bookmark])),
+    ]);
+    assert_eq!(reader.request(BEGIN, vec![begin]).0, SUCCESS);
+    assert_eq!(
+        reader.query("MATCH (i:Item) RETURN i.n"),
+        vec![vec![PackValue::Int(2)]]
+    );
+    let (tag, metadata) = reader.run("CREATE (:Item)", map(&[]));
+    assert_eq!(tag, FAILURE);
+    assert_eq!(
+        metadata["code"],
+        "Neo.ClientError.Statement.AccessMode".into()
+    );
+}
+
+#[test]
+fn failures_ignore_requests_until_reset() {
+    let (_dir, addr) = start(ServerConfig::default());
+    let mut client = Client::open(addr, V5);
+
+    let (tag, metadata) = client.run("MATCH (", map(&[]));
+    assert_eq!(tag, FAILURE);
+    assert_eq!(
+        metadata["code"],
+        "Neo.ClientError.Statement.SyntaxError".into()
+    );
+    assert_eq!(client.run("RETURN 1", map(&[])).0, IGNORED);
+    assert_eq!(client.request(RESET, Vec::new()).0, SUCCESS);
+    assert_eq!(client.query("RETURN 1 + 1"), vec![vec![PackValue::Int(2)]]);
+}
+
+#[test]
+fn credentials_are_checked_and_versions_negotiated() {
+    let config = ServerConfig {
+        auth: Some(Auth {
+            user: "neo4j".to_string(),
+            password: "secret".to_string(),
+        }),
+    };
+    let (_dir, addr) = start(config);
+
+    let (_, agreed) = Client::connect(addr, [0, 0, 0, 3]);
+    assert_eq!(agreed, [0; 4]);
+
+    let (mut client, _) = Client::connect(addr, V5);
+    let hello = |password: &str| {
+        vec![map(&[
+            ("scheme", "basic".into()),
+            ("principal", "neo4j".into()),
+            ("credentials", password.into()),
+        ])]
+    };
+    let (tag, metadata) = client.request(HELLO, hello("wrong"));
+    assert_eq!(tag, FAILURE);
+    assert_eq!(
+        metadata["code"],
+        "Neo.ClientError.Security.Unauthorized".into()
+    );
+    assert!(client.receive().is_none(), "the server hangs up");
+
+    let (mut client, _) = Client::connect(addr, V5);
+    let (tag, metadata) = client.request(HELLO, hello("secret"));
+    assert_eq!(tag, SUCCESS);
+    assert!(metadata["server"].as_str().unwrap().starts_with("Neo4j/"));
+}
@@ -0,0 +1,335 @@
+#![cfg(feature = "grpc")]
+
+use nervusdb::Db;
+use nervusdb_server::Auth;
+use nervusdb_server::grpc::pb::nervus_db_client::NervusDbClient;
+use nervusdb_server::grpc::pb::result_frame::Frame;
+use nervusdb_server::grpc::pb::value::Kind;
+use nervusdb_server::grpc::pb::{
+    BulkEdge, BulkLoadBatch, BulkNode, OpenRequest, QueryRequest, ResultFrame, Value, VectorMetric,
+    VectorSearchRequest,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Status};
+
+async fn client() -> (tempfile::TempDir, NervusDbClient<Channel>) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("grpc.ndb")).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(nervusdb_server::grpc::serve(listener, db, None));
+    let client = NervusDbClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    (dir, client)
+}
+
+fn value(kind: Kind) -> Value {
+    Value { kind: Some(kind) }
+}
+
+fn string(s: &str) -> Value {
+    value(Kind::StringValue(s.to_string()))
+}
+
+fn request(query: &str, params: impl IntoIterator<Item = (&'static str, Value)>) -> QueryRequest {
+    QueryRequest {
+        query: query.to_string(),
+        params: params
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
+
+fn node(id: u64, name: &str, vector: Vec<f32>) -> BulkNode {
+    BulkNode {
+        external_id: id,
+        labels: vec!["Person".to_string()],
+        properties: BTreeMap::from([("name".to_string(), string(name))]),
+        vector,
+    }
+}
+
+fn edge(src: u64, dst: u64) -> BulkEdge {
+    BulkEdge {
+        src_external_id: src,
+        rel_type: "KNOWS".to_string(),
+        dst_external_id: dst,
+        properties: BTreeMap::from([("since".to_string(), value(Kind::IntValue(2020)))]),
+    }
+}
+
+async fn frames(
+    stream: Result<tonic::Response<tonic::Streaming<ResultFrame>>, tonic::Status>,
+) -> Vec<Frame> {
+    let mut stream = stream.unwrap().into_inner();
+    let mut frames = Vec::new();
+    while let Some(frame) = stream.message().await.unwrap() {
+        frames.push(frame.frame.unwrap());
+    }
+    frames
+}
+
+#[tokio::test]
+async fn bulk_load_then_query_and_write() {
+    let (_dir, mut client) = client().await;
+    let opened = client.open(OpenRequest {}).await.unwrap().into_inner();
+    assert_eq!(opened.server_version, env!("CARGO_PKG_VERSION"));
+    assert!(!opened.read_only);
+
+    // The second batch links nodes of the first to one of its own.
+    let batches = vec![
+        BulkLoadBatch {
+            nodes: vec![node(1, "Ada", vec![]), node(2, "Alan", vec![])],
+            edges: vec![edge(1, 2)],
+        },
+        BulkLoadBatch {
+            nodes: vec![node(3, "Grace", vec![])],
+            edges: vec![edge(2, 3), edge(3, 1)],
+        },
+    ];
+    let summary = client
+        .bulk_load(tokio_stream::iter(batches))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!((summary.nodes, summary.edges, summary.batches), (3, 3, 2));
+    assert!(summary.bookmark > opened.bookmark);
+
+    let read = frames(
+        client
+            .query(request(
+                "MATCH (a:Person)-[r:KNOWS]->(b) WHERE r.since = $since \
+                 RETURN a.name AS a, b.name AS b ORDER BY a",
+                [("since", value(Kind::IntValue(2020)))],
+            ))
+            .await,
+    )
+    .await;
+    let Frame::Header(header) = &read[0] else {
+        panic!("expected a header, got {read:?}");
+    };
+    assert_eq!(header.columns, ["a", "b"]);
+    let pairs: Vec<_> = read[1..4]
+        .iter()
+        .map(|frame| match frame {
+            Frame::Row(row) => (row.values[0].clone(), row.values[1].clone()),
+            other => panic!("expected a row, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            (string("Ada"), string("Alan")),
+            (string("Alan"), string("Grace")),
+            (string("Grace"), string("Ada")),
+        ]
+    );
+    assert!(
+        matches!(&read[4], Frame::Summary(summary) if summary.rows == 3 && summary.writes == 0)
+    );
+
+    let written = frames(
+        client
+            .execute_write(request(
+                "CREATE (p:Person {name: $name, tags: $tags}) RETURN p",
+                [
+                    ("name", string("Edsger")),
+                    (
+                        "tags",
+                        value(Kind::ListValue(nervusdb_server::grpc::pb::List {
+                            values: vec![string("go-to")],
+                        })),
+                    ),
+                ],
+            ))
+            .await,
+    )
+    .await;
+    let Frame::Row(row) = &written[1] else {
+        panic!("expected a row, got {written:?}");
+    };
+    let Some(Kind::Node(created)) = &row.values[0].kind else {
+        panic!("expected a node, got {row:?}");
+    };
+    assert_eq!(created.labels, ["Person"]);
+    assert_eq!(created.properties["name"], string("Edsger"));
+    let Frame::Summary(summary) = &written[2] else {
+        panic!("expected a summary, got {written:?}");
+    };
+    assert!(summary.writes > 0);
+    assert_eq!(
+        summary.bookmark,
+        client
+            .open(OpenRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .bookmark
+    );
+}
+
+#[tokio::test]
+async fn failures_map_to_status_codes() {
+    let (_dir, mut client) = client().await;
+    let status = client
+        .query(request("CREATE (:Person)", []))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(status.message().contains("ExecuteWrite"));
+    let status = client
+        .query(request("MATCH (n RETURN n", []))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    // The first batch commits; the second names a node that does not exist.
+    let status = client
+        .bulk_load(tokio_stream::iter(vec![
+            BulkLoadBatch {
+                nodes: vec![node(1, "Ada", vec![])],
+                edges: vec![],
+            },
+            BulkLoadBatch {
+                nodes: vec![],
+                edges: vec![edge(1, 9)],
+            },
+        ]))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+    assert!(status.message().starts_with("batch 1:"), "{status:?}");
+    let status = client
+        .bulk_load(tokio_stream::iter(vec![BulkLoadBatch {
+            nodes: vec![node(1, "Ada again", vec![])],
+            edges: vec![],
+        }]))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::AlreadyExists);
+
+    let counted = frames(
+        client
+            .query(request("MATCH (n:Person) RETURN count(n) AS n", []))
+            .await,
+    )
+    .await;
+    assert!(matches!(
+        &counted[1],
+        Frame::Row(row) if row.values[0] == value(Kind::IntValue(1))
+    ));
+}
+
+#[tokio::test]
+async fn vector_search_streams_nearest_nodes() {
+    let (_dir, mut client) = client().await;
+    client
+        .bulk_load(tokio_stream::iter(vec![BulkLoadBatch {
+            nodes: vec![
+                node(10, "east", vec![1.0, 0.0]),
+                node(11, "north", vec![0.0, 1.0]),
+                node(12, "north-east", vec![0.7, 0.7]),
+            ],
+            edges: vec![],
+        }]))
+        .await
+        .unwrap();
+
+    let search = |request: VectorSearchRequest| {
+        let mut client = client.clone();
+        async move {
+            let mut hits = client.vector_search(request).await.unwrap().into_inner();
+            let mut found = Vec::new();
+            while let Some(hit) = hits.message().await.unwrap() {
+                found.push((
+                    hit.external_id,
+                    hit.node.unwrap().properties["name"].clone(),
+                ));
+            }
+            found
+        }
+    };
+    let nearest = search(VectorSearchRequest {
+        vector: vec![0.1, 0.9],
+        k: 2,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(nearest, [(11, string("north")), (12, string("north-east"))]);
+
+    let filtered = search(VectorSearchRequest {
+        vector: vec![1.0, 1.0],
+        k: 3,
+        metric: VectorMetric::Cosine as i32,
+        label: "Person".to_string(),
+        property: "name".to_string(),
+        value: Some(string("east")),
+    })
+    .await;
+    assert_eq!(filtered, [(10, string("east"))]);
+}
+
+#[tokio::test]
+async fn credentials_are_required_when_configured() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("grpc.ndb")).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let auth = Auth {
+        user: "neo".to_string(),
+        password: "secret".to_string(),
+    };
+    tokio::spawn(nervusdb_server::grpc::serve(
+        listener,
+        Arc::clone(&db),
+        Some(auth),
+    ));
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let with_credentials = |credentials: &'static str| {
+        move |mut request: Request<()>| {
+            let header = format!("Basic {credentials}");
+            request
+                .metadata_mut()
+                .insert("authorization", MetadataValue::try_from(header).unwrap());
+            Ok::<_, Status>(request)
+        }
+    };
+
+    let mut anonymous = NervusDbClient::new(channel.clone());
+    let err = anonymous
+        .execute_write(request("CREATE (:N)", []))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let batch = BulkLoadBatch {
+        nodes: vec![node(1, "Ada", vec![])],
+        edges: vec![],
+    };
+    let err = anonymous
+        .bulk_load(tokio_stream::iter(vec![batch]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    // "neo:wrong"
+    let mut wrong =
+        NervusDbClient::with_interceptor(channel.clone(), with_credentials("bmVvOndyb25n"));
+    let err = wrong.open(OpenRequest {}).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    assert_eq!(db.last_bookmark().txid(), 0);
+
+    // "neo:secret"
+    let mut client =
+        NervusDbClient::with_interceptor(channel, with_credentials("bmVvOnNlY3JldA=="));
+    let written = frames(client.execute_write(request("CREATE (:N)", [])).await).await;
+    assert!(!written.is_empty());
+    assert!(db.last_bookmark().txid() > 0);
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "http")]
+
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode, header};
+use nervusdb::Db;
+use nervusdb_server::Auth;
+use nervusdb_server::http::router;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn app() -> (tempfile::TempDir, Router) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("http.ndb")).unwrap());
+    (dir, router(db, None))
+}
+
+async fn post(app: &Router, path: &str, body: Value, accept: &str) -> (StatusCode, String) {
+    let request = Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, accept)
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+async fn get(app: &Router, path: &str) -> String {
+    let request = Request::get(path).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+fn frames(body: &str) -> Vec<Value> {
+    body.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn writes_commit_and_reads_stream_rows() {
+    let (_dir, app) = app();
+    let (status, body) = post(
+        &app,
+        "/write",
+        json!({
+            "query": "UNWIND $names AS name CREATE (p:Person {name: name}) RETURN p.name AS name",
+            "params": {"names": ["Ada", "Alan"]},
+        }),
+        "*/*",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let written = frames(&body);
+    assert_eq!(written[0], json!({"row": {"name": "Ada"}}));
+    let summary = &written[2]["summary"];
+    assert_eq!(summary["rows"], 2);
+    assert_eq!(summary["writes"], 2);
+    assert!(summary["bookmark"].as_u64().unwrap() > 0);
+
+    let (status, body) = post(
+        &app,
+        "/query",
+        json!({"query": "MATCH (p:Person) WHERE p.name <> $skip RETURN p ORDER BY p.name", "params": {"skip": "Ada"}}),
+        "application/x-ndjson",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = frames(&body);
+    assert_eq!(rows.len(), 2);
+    let node = &rows[0]["row"]["p"];
+    assert_eq!(node["type"], "node");
+    assert_eq!(node["labels"], json!(["Person"]));
+    assert_eq!(node["properties"], json!({"name": "Alan"}));
+    assert_eq!(rows[1], json!({"summary": {"rows": 1}}));
+}
+
+#[tokio::test]
+async fn server_sent_events_name_each_frame() {
+    let (_dir, app) = app();
+    let (status, body) = post(
+        &app,
+        "/query",
+        json!({"query": "UNWIND [1, 2] AS x RETURN x"}),
+        "text/event-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let events: Vec<(&str, Value)> = body
+        .split("\n\n")
+        .filter(|event| !event.is_empty())
+        .map(|event| {
+            let mut lines = event.lines();
+            let name = lines.next().unwrap().strip_prefix("event: ").unwrap();
+            let data = lines.next().unwrap().strip_prefix("data: ").unwrap();
+            (name, serde_json::from_str(data).unwrap())
+        })
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            ("row", json!({"x": 1})),
+            ("row", json!({"x": 2})),
+            ("summary", json!({"rows": 2})),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failures_report_a_status_or_an_error_frame() {
+    let (_dir, app) = app();
+    let (status, body) = post(&app, "/query", json!({"query": "MATCH ("}), "*/*").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(error["error"]["code"], "syntax_error");
+
+    let (status, _) = post(&app, "/query", json!({"query": "CREATE (:X)"}), "*/*").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The second `x` is not a node, which only fails once rows are flowing.
+    let query = "CREATE (:N)-[:R]->(:M {v: 1})";
+    post(&app, "/write", json!({ "query": query }), "*/*").await;
+    let query = "MATCH (n:N) UNWIND [n, 1] AS x MATCH (x)-[:R]->(m) RETURN m.v AS v";
+    let (status, body) = post(&app, "/query", json!({ "query": query }), "*/*").await;
+    assert_eq!(status, StatusCode::OK);
+    let streamed = frames(&body);
+    assert_eq!(streamed[0], json!({"row": {"v": 1}}));
+    assert_eq!(streamed[1]["error"]["code"], "execution_error");
+}
+
+#[tokio::test]
+async fn health_and_metrics_report_state() {
+    let (_dir, app) = app();
+    post(&app, "/write", json!({"query": "CREATE (:N)"}), "*/*").await;
+    post(
+        &app,
+        "/query",
+        json!({"query": "MATCH (n:N) RETURN n"}),
+        "*/*",
+    )
+    .await;
+
+    let health: Value = serde_json::from_str(&get(&app, "/health").await).unwrap();
+    assert_eq!(health["status"], "ok");
+    assert!(health["bookmark"].as_u64().unwrap() > 0);
+
+    let metrics = get(&app, "/metrics").await;
+    assert!(metrics.contains("nervusdb_http_queries_total 1\n"));
+    assert!(metrics.contains("nervusdb_http_writes_total 1\n"));
+    assert!(metrics.contains("nervusdb_http_rows_total 1\n"));
+    assert!(metrics.contains("# TYPE nervusdb_page_cache_hits_total counter\n"));
+}
+
+#[tokio::test]
+async fn credentials_guard_everything_but_health() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("http.ndb")).unwrap());
+    let auth = Auth {
+        user: "neo".into(),
+        password: "secret".into(),
+    };
+    let app = router(Arc::clone(&db), Some(auth));
+    let send = |path: &str, authorization: Option<&str>| {
+        let mut request = Request::post(path).header(header::CONTENT_TYPE, "application/json");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        let request = request
+            .body(Body::from(json!({"query": "CREATE (:N)"}).to_string()))
+            .unwrap();
+        app.clone().oneshot(request)
+    };
+
+    let response = send("/write", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.headers()[header::WWW_AUTHENTICATE],
+        "Basic realm=\"nervusdb\""
+    );
+    // "neo:wrong"
+    let response = send("/write", Some("Basic bmVvOndyb25n")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let metrics = Request::get("/metrics").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(metrics).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(db.last_bookmark().txid(), 0);
+
+    get(&app, "/health").await;
+    // "neo:secret"
+    let response = send("/write", Some("Basic bmVvOnNlY3JldA=="))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(db.last_bookmark().txid() > 0);
+}
@@ -0,0 +1,232 @@
+#![cfg(feature = "grpc")]
+
+//! `src/grpc/pb.rs` and the service in `build.rs` are written by hand to
+//! avoid needing `protoc`; this checks them against `proto/nervusdb.proto`
+//! so the three cannot drift apart.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+fn read(relative: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(relative);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+fn snake_case(camel: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in camel.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn camel_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+            })
+        })
+        .collect()
+}
+
+/// What `.proto` declares, as `Message.field = tag kind` lines, `Enum.Variant
+/// = n` lines and `rpc Name(In) returns (Out)` lines.
+fn proto_shape(proto: &str) -> BTreeSet<String> {
+    let lines: Vec<&str> = proto
+        .lines()
+        .map(|line| line.split("//").next().unwrap().trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let enums: BTreeSet<&str> = lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("enum "))
+        .map(|rest| rest.trim_end_matches('{').trim())
+        .collect();
+    let kind = |ty: &str| {
+        if let Some(map) = ty.strip_prefix("map<") {
+            let (key, value) = map.trim_end_matches('>').split_once(',').unwrap();
+            let value = value.trim();
+            let value = if is_scalar(value) { value } else { "message" };
+            format!("map<{}, {value}>", key.trim())
+        } else if enums.contains(ty) {
+            format!("enumeration = {ty}")
+        } else if is_scalar(ty) {
+            ty.to_string()
+        } else {
+            "message".to_string()
+        }
+    };
+
+    let mut shape = BTreeSet::new();
+    let mut scope: Option<(&str, &str)> = None;
+    for line in &lines {
+        if let Some(rest) = line.strip_prefix("rpc ") {
+            shape.insert(format!("rpc {}", rest.trim_end_matches(';').trim()));
+        } else if let Some(rest) = line.strip_prefix("message ") {
+            let name = rest.trim_end_matches("{}").trim_end_matches('{').trim();
+            scope = Some(("message", name));
+        } else if let Some(rest) = line.strip_prefix("enum ") {
+            scope = Some(("enum", rest.trim_end_matches('{').trim()));
+        } else if line.starts_with("service ") {
+            scope = None;
+        } else if line.starts_with("oneof ") || *line == "}" {
+            continue;
+        } else if let Some(("message", message)) = scope {
+            let (decl, tag) = line.trim_end_matches(';').split_once('=').unwrap();
+            let mut words: Vec<&str> = decl.split_whitespace().collect();
+            let name = words.pop().unwrap();
+            let repeated = words.first() == Some(&"repeated");
+            let ty = words.join(" ");
+            let ty = ty.trim_start_matches("repeated ");
+            let label = if repeated { "repeated " } else { "" };
+            shape.insert(format!(
+                "{message}.{name} = {} {label}{}",
+                tag.trim(),
+                kind(ty)
+            ));
+        } else if let Some(("enum", name)) = scope {
+            let (variant, value) = line.trim_end_matches(';').split_once('=').unwrap();
+            let prefix = format!("{}_", snake_case(name).to_ascii_uppercase());
+            let variant = variant.trim().strip_prefix(&prefix).unwrap();
+            shape.insert(format!("{name}.{} = {}", camel_case(variant), value.trim()));
+        }
+    }
+    shape
+}
+
+fn is_scalar(ty: &str) -> bool {
+    matches!(
+        ty,
+        "bool" | "bytes" | "double" | "float" | "int32" | "int64" | "string" | "uint32" | "uint64"
+    )
+}
+
+/// The same shape, read from the prost attributes in `pb.rs`.
+fn pb_shape(pb: &str) -> BTreeSet<String> {
+    let mut shape = BTreeSet::new();
+    // The message a field belongs to: a struct, or the message of a oneof
+    // module (`result_frame` for `ResultFrame`).
+    let mut message = String::new();
+    // Set inside a plain enum, as opposed to a oneof.
+    let mut enumeration: Option<String> = None;
+    let mut repr_i32 = false;
+    let mut attribute: Option<String> = None;
+    for line in pb.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("pub struct ") {
+            message = rest.split_whitespace().next().unwrap().to_string();
+        } else if let Some(rest) = line.strip_prefix("pub mod ") {
+            message = camel_case(rest.trim_end_matches('{').trim());
+        } else if line == "#[repr(i32)]" {
+            repr_i32 = true;
+        } else if let Some(rest) = line.strip_prefix("pub enum ") {
+            enumeration = repr_i32.then(|| rest.trim_end_matches('{').trim().to_string());
+            repr_i32 = false;
+        } else if line == "}" {
+            enumeration = None;
+        } else if let Some(name) = &enumeration {
+            let (variant, value) = line.trim_end_matches(',').split_once(" = ").unwrap();
+            shape.insert(format!("{name}.{variant} = {value}"));
+        } else if let Some(rest) = line.strip_prefix("#[prost(") {
+            attribute = Some(rest.trim_end_matches(")]").to_string());
+        } else if let Some(attr) = attribute.take() {
+            // Oneof containers; their variants are checked one by one.
+            if attr.is_empty() || attr.starts_with("oneof") {
+                continue;
+            }
+            let name = match line.strip_prefix("pub ") {
+                Some(field) => field.split(':').next().unwrap().to_string(),
+                None => snake_case(line.split('(').next().unwrap()),
+            };
+            let parts: Vec<&str> = attr.split(", ").collect();
+            let tag = parts
+                .iter()
+                .find_map(|part| part.strip_prefix("tag = "))
+                .unwrap()
+                .trim_matches('"');
+            let kind = if let Some(map) = attr.strip_prefix("btree_map = \"") {
+                format!("map<{}>", map.split('"').next().unwrap())
+            } else if let Some(ty) = attr.strip_prefix("enumeration = \"") {
+                format!("enumeration = {}", ty.split('"').next().unwrap())
+            } else {
+                parts[0].split(" = ").next().unwrap().to_string()
+            };
+            let label = if parts.contains(&"repeated") {
+                "repeated "
+            } else {
+                ""
+            };
+            shape.insert(format!("{message}.{name} = {tag} {label}{kind}"));
+        }
+    }
+    shape
+}
+
+/// The service `build.rs` describes, as `rpc` lines.
+fn build_shape(build: &str) -> BTreeSet<String> {
+    let compact: String = build.chars().filter(|c| !c.is_whitespace()).collect();
+    compact
+        .split(".method(method(")
+        .skip(1)
+        .map(|method| {
+            let (args, rest) = method.split_once(')').unwrap();
+            let args: Vec<&str> = args
+                .split(',')
+                .map(|arg| arg.trim_matches('"'))
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            let modifiers = rest.split(".build()").next().unwrap();
+            let stream = |yes: bool| if yes { "stream " } else { "" };
+            format!(
+                "rpc {}({}{}) returns ({}{})",
+                args[1],
+                stream(modifiers.contains("client_streaming")),
+                args[2],
+                stream(modifiers.contains("server_streaming")),
+                args[3]
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn messages_match_the_proto() {
+    let proto: BTreeSet<String> = proto_shape(&read("proto/nervusdb.proto"))
+        .into_iter()
+        .filter(|line| !line.starts_with("rpc "))
+        .collect();
+    let pb = pb_shape(&read("src/grpc/pb.rs"));
+    assert!(
+        proto.len() > 50,
+        "parsed too little of the proto: {proto:#?}"
+    );
+    assert_eq!(
+        proto.difference(&pb).collect::<Vec<_>>(),
+        Vec::<&String>::new(),
+        "in nervusdb.proto but not in pb.rs"
+    );
+    assert_eq!(
+        pb.difference(&proto).collect::<Vec<_>>(),
+        Vec::<&String>::new(),
+        "in pb.rs but not in nervusdb.proto"
+    );
+}
+
+#[test]
+fn service_matches_the_proto() {
+    let proto: BTreeSet<String> = proto_shape(&read("proto/nervusdb.proto"))
+        .into_iter()
+        .filter(|line| line.starts_with("rpc "))
+        .collect();
+    assert_eq!(proto.len(), 5);
+    assert_eq!(build_shape(&read("build.rs")), proto);
+}
@@ -1,8 +1,14 @@
-use crate::engine::GraphEngine;
+use crate::cold_store::ColdStore;
+use crate::engine::{GraphEngine, NativeHnsw, search_vector_index};
 use crate::idmap::I2eRecord;
 use crate::index::btree::BTree;
 use crate::index::catalog::IndexCatalog;
+use crate::index::composite::{best_composite_index, lookup_prefix};
 use crate::index::ordered_key::encode_ordered_value;
+use crate::index::text::{TEXT_INDEX_SUFFIX, lookup_candidates, text_index_name};
+use crate::index::unique::unique_constraint_name;
+use crate::index::vector::VectorMetric;
+use crate::names::is_system_property;
 use crate::pager::Pager;
 use crate::read_path_api_stats::{edge_count_from_stats, node_count_from_stats};
 use crate::read_path_convert::{
@@ -16,8 +22,8 @@ use crate::read_path_property_store::{
 use crate::read_path_tombstones::collect_tombstoned_nodes;
 use crate::snapshot;
 use nervusdb_api::{
-    EdgeKey, ExternalId, GraphSnapshot, GraphStore, InternalNodeId, LabelId, PropertyValue,
-    RelTypeId,
+    EdgeKey, ExternalId, GraphSnapshot, GraphStore, IndexInfo, IndexKind, InternalNodeId, LabelId,
+    PropertyValue, RelTypeDegrees, RelTypeId, Schema,
 };
 use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, Mutex, RwLock};
@@ -28,11 +34,39 @@ pub struct StorageSnapshot {
     i2e: Arc<Vec<I2eRecord>>,
     tombstoned_nodes: Arc<HashSet<InternalNodeId>>,
     pager: Arc<RwLock<Pager>>,
+    cold_store: Arc<ColdStore>,
     index_catalog: Arc<Mutex<IndexCatalog>>,
+    named_queries: Arc<BTreeMap<String, String>>,
+    namespaces: Arc<BTreeMap<String, String>>,
+    vector_index: Arc<Mutex<NativeHnsw>>,
+    exact_vector_search: bool,
     stats_cache: Mutex<Option<crate::stats::GraphStatistics>>,
+    schema: Arc<crate::schema::SchemaCatalog>,
 }
 
 impl StorageSnapshot {
+    pub(crate) fn from_parts(
+        engine: &GraphEngine,
+        inner: snapshot::Snapshot,
+        i2e: Vec<I2eRecord>,
+    ) -> Self {
+        let tombstoned_nodes: HashSet<InternalNodeId> = collect_tombstoned_nodes(inner.runs());
+        StorageSnapshot {
+            inner,
+            i2e: Arc::new(i2e),
+            tombstoned_nodes: Arc::new(tombstoned_nodes),
+            pager: engine.get_pager(),
+            cold_store: engine.get_cold_store(),
+            index_catalog: engine.get_index_catalog(),
+            named_queries: engine.named_queries(),
+            namespaces: engine.namespaces(),
+            vector_index: engine.get_vector_index(),
+            exact_vector_search: engine.exact_vector_search(),
+            stats_cache: Mutex::new(None),
+            schema: engine.schema(),
+        }
+    }
+
     fn ensure_stats_cache_loaded(&self) {
         let mut cache = self.stats_cache.lock().unwrap();
         if cache.is_none() {
@@ -47,23 +81,19 @@ impl StorageSnapshot {
         self.ensure_stats_cache_loaded();
         self.stats_cache.lock().unwrap().clone()
     }
+
+    /// Statistics written by the last compaction, including its size history.
+    pub fn statistics(&self) -> Option<crate::stats::GraphStatistics> {
+        self.cached_stats_clone()
+    }
 }
 
 impl GraphStore for GraphEngine {
     type Snapshot = StorageSnapshot;
 
     fn snapshot(&self) -> Self::Snapshot {
-        let i2e = Arc::new(self.scan_i2e_records());
-        let inner = self.begin_read();
-        let tombstoned_nodes: HashSet<InternalNodeId> = collect_tombstoned_nodes(inner.runs());
-        StorageSnapshot {
-            inner,
-            i2e,
-            tombstoned_nodes: Arc::new(tombstoned_nodes),
-            pager: self.get_pager(),
-            index_catalog: self.get_index_catalog(),
-            stats_cache: Mutex::new(None),
-        }
+        let i2e = self.scan_i2e_records();
+        StorageSnapshot::from_parts(self, self.begin_read(), i2e)
     }
 }
 
@@ -99,12 +129,9 @@ impl GraphSnapshot for StorageSnapshot {
         field: &str,
         value: &PropertyValue,
     ) -> Option<Vec<InternalNodeId>> {
-        // MVP Convention: Index name = "Label.Property"
-        let index_name = format!("{}.{}", label, field);
-
         let def = {
             let catalog = self.index_catalog.lock().unwrap();
-            catalog.get(&index_name)?.clone()
+            equality_index(&catalog, label, field)?.clone()
         };
         let tree = BTree::load(def.root);
 
@@ -143,6 +170,143 @@ impl GraphSnapshot for StorageSnapshot {
         }
     }
 
+    fn has_index(&self, label: &str, field: &str) -> bool {
+        let catalog = self.index_catalog.lock().unwrap();
+        equality_index(&catalog, label, field).is_some()
+    }
+
+    fn lookup_composite_index(
+        &self,
+        label: &str,
+        predicates: &[(String, PropertyValue)],
+    ) -> Option<Vec<InternalNodeId>> {
+        let (def, values) = {
+            let catalog = self.index_catalog.lock().unwrap();
+            let (def, bound) = best_composite_index(&catalog, label, predicates)?;
+            (def.clone(), bound)
+        };
+        let tree = BTree::load(def.root);
+        let values: Vec<_> = values
+            .into_iter()
+            .map(convert_property_to_storage)
+            .collect();
+        let pager = self.pager.read().unwrap();
+        let nodes =
+            lookup_prefix(&tree, &pager, def.id, &values.iter().collect::<Vec<_>>()).ok()?;
+
+        // The index is shared across snapshots; drop nodes this snapshot cannot see.
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .filter(|iid| (*iid as usize) < self.i2e.len())
+            .filter(|iid| !self.tombstoned_nodes.contains(iid))
+            .collect();
+        if nodes.is_empty() { None } else { Some(nodes) }
+    }
+
+    fn has_composite_index(&self, label: &str, fields: &[String]) -> bool {
+        let catalog = self.index_catalog.lock().unwrap();
+        catalog
+            .composites_for_label(label)
+            .any(|(_, _, key)| fields.contains(&key.properties[0]))
+    }
+
+    fn lookup_text_index(
+        &self,
+        label: &str,
+        field: &str,
+        needle: &str,
+    ) -> Option<Vec<InternalNodeId>> {
+        let def = {
+            let catalog = self.index_catalog.lock().unwrap();
+            catalog.get(&text_index_name(label, field))?.clone()
+        };
+        let tree = BTree::load(def.root);
+        let pager = self.pager.read().unwrap();
+        let candidates = lookup_candidates(&tree, &pager, def.id, needle).ok()??;
+
+        // The index is shared across snapshots; drop nodes this snapshot cannot see.
+        Some(
+            candidates
+                .into_iter()
+                .filter(|iid| (*iid as usize) < self.i2e.len())
+                .filter(|iid| !self.tombstoned_nodes.contains(iid))
+                .collect(),
+        )
+    }
+
+    fn search_vector(&self, query: &[f32], k: usize) -> Option<Vec<(InternalNodeId, f32)>> {
+        // The index is shared across snapshots; skip nodes this snapshot cannot see.
+        search_vector_index(
+            &self.pager,
+            &self.vector_index,
+            self.exact_vector_search,
+            query,
+            k,
+            VectorMetric::L2,
+            &mut |iid| (iid as usize) < self.i2e.len() && !self.tombstoned_nodes.contains(&iid),
+        )
+        .ok()
+    }
+
+    fn schema(&self) -> Schema {
+        let names = |ids: &std::collections::BTreeSet<u32>| {
+            let mut names: Vec<String> = ids
+                .iter()
+                .filter_map(|&id| self.resolve_label_name(id))
+                .collect();
+            names.sort();
+            names
+        };
+        let indexes = self
+            .index_catalog
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .filter_map(|(name, def)| index_info(name, def))
+            .collect();
+        Schema {
+            labels: names(&self.schema.labels),
+            rel_types: names(&self.schema.rel_types),
+            property_keys: self.schema.property_keys.iter().cloned().collect(),
+            indexes,
+        }
+    }
+
+    fn degree_stats(&self) -> Vec<RelTypeDegrees> {
+        let Some(stats) = self.cached_stats_clone() else {
+            return Vec::new();
+        };
+        let mut degrees: Vec<RelTypeDegrees> = stats
+            .degrees
+            .into_iter()
+            .filter_map(|(rel, degrees)| {
+                Some(RelTypeDegrees {
+                    rel_type: self.resolve_rel_type_name(rel)?,
+                    relationships: stats.edge_counts_by_type.get(&rel).copied().unwrap_or(0),
+                    outgoing: degrees.outgoing,
+                    incoming: degrees.incoming,
+                })
+            })
+            .collect();
+        degrees.sort_by(|a, b| a.rel_type.cmp(&b.rel_type));
+        degrees
+    }
+
+    fn named_queries(&self) -> Vec<(String, String)> {
+        self.named_queries
+            .iter()
+            .map(|(name, cypher)| (name.clone(), cypher.clone()))
+            .collect()
+    }
+
+    fn namespaces(&self) -> Vec<(String, String)> {
+        self.namespaces
+            .iter()
+            .map(|(prefix, iri)| (prefix.clone(), iri.clone()))
+            .collect()
+    }
+
     fn nodes(&self) -> Box<dyn Iterator<Item = InternalNodeId> + '_> {
         let len = self.i2e.len();
         let tombstoned = self.tombstoned_nodes.clone();
@@ -181,14 +345,17 @@ impl GraphSnapshot for StorageSnapshot {
             return Some(convert_property_to_api(v));
         }
 
-        if self.inner.properties_root == 0 {
-            return None;
+        if self.inner.properties_root != 0 {
+            let pager = self.pager.read().unwrap();
+            if let Some(storage_val) =
+                read_node_property_from_store(&pager, self.inner.properties_root, iid, key)
+            {
+                return Some(convert_property_to_api(storage_val));
+            }
         }
 
-        let pager = self.pager.read().unwrap();
-        let storage_val =
-            read_node_property_from_store(&pager, self.inner.properties_root, iid, key)?;
-        Some(convert_property_to_api(storage_val))
+        let mut cold = self.cold_store.get(iid).ok()??;
+        cold.remove(key).map(convert_property_to_api)
     }
 
     fn edge_property(&self, edge: EdgeKey, key: &str) -> Option<PropertyValue> {
@@ -214,6 +381,13 @@ impl GraphSnapshot for StorageSnapshot {
             let pager = self.pager.read().unwrap();
             extend_node_properties_from_store(&pager, self.inner.properties_root, iid, &mut props)?;
         }
+        if let Ok(Some(cold)) = self.cold_store.get(iid) {
+            for (key, value) in cold {
+                props.entry(key).or_insert(value);
+            }
+        }
+        // System properties are read by key only.
+        props.retain(|key, _| !is_system_property(key));
 
         if props.is_empty() {
             None
@@ -238,6 +412,7 @@ impl GraphSnapshot for StorageSnapshot {
                 &mut props,
             )?;
         }
+        props.retain(|key, _| !is_system_property(key));
 
         if props.is_empty() {
             None
@@ -272,3 +447,46 @@ impl GraphSnapshot for StorageSnapshot {
         edge_count_from_stats(stats.as_ref(), rel)
     }
 }
+
+/// The index answering `label.field = value`: the single-property index
+/// (named "Label.Property" by convention), else a unique constraint's index,
+/// whose keys share the same prefix layout.
+fn equality_index<'c>(
+    catalog: &'c IndexCatalog,
+    label: &str,
+    field: &str,
+) -> Option<&'c crate::index::catalog::IndexDef> {
+    catalog.get(&format!("{}.{}", label, field)).or_else(|| {
+        catalog
+            .get(&unique_constraint_name(label, field))
+            .filter(|def| def.composite.as_ref().is_some_and(|key| key.unique))
+    })
+}
+
+/// Describes a user index catalog entry; `None` for the engine's own entries.
+fn index_info(name: &str, def: &crate::index::catalog::IndexDef) -> Option<IndexInfo> {
+    if name.starts_with("__") {
+        return None;
+    }
+    if let Some(key) = &def.composite {
+        return Some(IndexInfo {
+            name: name.to_string(),
+            kind: IndexKind::Composite,
+            label: key.label.clone(),
+            properties: key.properties.clone(),
+            unique: key.unique,
+        });
+    }
+    let (kind, spec) = match name.strip_suffix(TEXT_INDEX_SUFFIX) {
+        Some(spec) => (IndexKind::Text, spec),
+        None => (IndexKind::BTree, name),
+    };
+    let (label, property) = spec.split_once('.')?;
+    Some(IndexInfo {
+        name: name.to_string(),
+        kind,
+        label: label.to_string(),
+        properties: vec![property.to_string()],
+        unique: false,
+    })
+}
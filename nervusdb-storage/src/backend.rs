@@ -0,0 +1,602 @@
+//! Byte storage underneath the [`Pager`](crate::pager::Pager).
+//!
+//! The pager only ever reads and writes whole pages at page-aligned offsets,
+//! grows the store with [`StorageBackend::set_size`] and flushes it with
+//! [`StorageBackend::sync`]. Anything that can do that can hold a database:
+//! a local file ([`FileBackend`], the default), a buffer ([`MemoryBackend`]),
+//! another backend with every page encrypted ([`EncryptedBackend`]), a
+//! remote object read by range ([`ReadOnlyBackend`]) or another backend that
+//! is never written to ([`OverlayBackend`]).
+
+use crate::PAGE_SIZE;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(unix)]
+use std::os::unix::fs::FileExt as _;
+#[cfg(windows)]
+use std::os::windows::fs::FileExt as _;
+
+/// Random-access byte store a pager keeps its pages in.
+pub trait StorageBackend: Send + Sync + fmt::Debug {
+    /// Fills `buf` with the bytes at `offset`. Reading past the end fails
+    /// with [`io::ErrorKind::UnexpectedEof`].
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Writes all of `buf` at `offset`.
+    fn write_all_at(&self, offset: u64, buf: &[u8]) -> io::Result<()>;
+
+    /// Current size in bytes.
+    fn size(&self) -> io::Result<u64>;
+
+    /// Grows or truncates the store to `size` bytes; new bytes read as zero.
+    fn set_size(&self, size: u64) -> io::Result<()>;
+
+    /// Makes every completed write durable.
+    fn sync(&self) -> io::Result<()>;
+
+    /// The local file this backend writes pages to directly, if any. The
+    /// pager keeps its page LSNs beside it, see [`crate::page_lsn`].
+    fn local_file(&self) -> Option<&Path> {
+        None
+    }
+}
+
+impl<B: StorageBackend + ?Sized> StorageBackend for Box<B> {
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read_exact_at(offset, buf)
+    }
+
+    fn write_all_at(&self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all_at(offset, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+
+    fn set_size(&self, size: u64) -> io::Result<()> {
+        (**self).set_size(size)
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        (**self).sync()
+    }
+
+    fn local_file(&self) -> Option<&Path> {
+        (**self).local_file()
+    }
+}
+
+/// A local file, read and written with positioned I/O.
+#[derive(Debug)]
+pub struct FileBackend {
+    file: File,
+    /// Set when opened by path for writing.
+    path: Option<PathBuf>,
+}
+
+impl FileBackend {
+    /// Opens `path` for reading and writing, creating it when missing.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.as_ref())?;
+        Ok(Self {
+            file,
+            path: Some(path.as_ref().to_path_buf()),
+        })
+    }
+
+    /// Opens an existing `path` for reading only; fails when it is missing.
+    pub fn open_read_only(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self { file, path: None })
+    }
+
+    pub fn new(file: File) -> Self {
+        Self { file, path: None }
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn read_exact_at(&self, mut offset: u64, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            let n = read_at(&self.file, offset, buf)?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "read_at returned 0 bytes",
+                ));
+            }
+            offset = offset.saturating_add(n as u64);
+            buf = &mut buf[n..];
+        }
+        Ok(())
+    }
+
+    fn write_all_at(&self, mut offset: u64, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            let n = write_at(&self.file, offset, buf)?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "write_at returned 0 bytes",
+                ));
+            }
+            offset = offset.saturating_add(n as u64);
+            buf = &buf[n..];
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    fn set_size(&self, size: u64) -> io::Result<()> {
+        self.file.set_len(size)
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn local_file(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+    file.read_at(buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+    file.seek_read(buf, offset)
+}
+
+#[cfg(unix)]
+fn write_at(file: &File, offset: u64, buf: &[u8]) -> io::Result<usize> {
+    file.write_at(buf, offset)
+}
+
+#[cfg(windows)]
+fn write_at(file: &File, offset: u64, buf: &[u8]) -> io::Result<usize> {
+    file.seek_write(buf, offset)
+}
+
+/// An in-memory buffer. Nothing survives the process; useful for tests and
+/// scratch databases.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    bytes: RwLock<Vec<u8>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from an existing image, e.g. a `.ndb` file read into memory.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes: RwLock::new(bytes),
+        }
+    }
+
+    /// Copy of the current contents.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.read().clone()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Vec<u8>> {
+        // Writes never leave the buffer half-resized; a poisoned lock is still usable.
+        self.bytes.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Vec<u8>> {
+        self.bytes.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        buf.copy_from_slice(range(&self.read(), offset, buf.len())?);
+        Ok(())
+    }
+
+    fn write_all_at(&self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let mut bytes = self.write();
+        let end = end_offset(offset, buf.len())?;
+        if bytes.len() < end {
+            bytes.resize(end, 0);
+        }
+        bytes[end - buf.len()..end].copy_from_slice(buf);
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.read().len() as u64)
+    }
+
+    fn set_size(&self, size: u64) -> io::Result<()> {
+        let size = usize::try_from(size)
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "size exceeds memory"))?;
+        self.write().resize(size, 0);
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Encrypts and decrypts single pages for [`EncryptedBackend`].
+///
+/// Ciphers must keep the length of the page unchanged, so tweakable block
+/// ciphers such as AES-XTS keyed on the page id fit; the page id is passed
+/// so equal pages do not encrypt to equal bytes.
+pub trait PageCipher: Send + Sync + fmt::Debug {
+    fn encrypt(&self, page_id: u64, page: &mut [u8]);
+    fn decrypt(&self, page_id: u64, page: &mut [u8]);
+}
+
+/// Encrypts every page written to `inner` with `cipher`.
+///
+/// Only whole, page-aligned reads and writes are accepted, which is all the
+/// pager issues. Pages that were allocated but never written read back as
+/// zeros rather than as the decryption of zeros.
+#[derive(Debug)]
+pub struct EncryptedBackend<B, C> {
+    inner: B,
+    cipher: C,
+}
+
+impl<B: StorageBackend, C: PageCipher> EncryptedBackend<B, C> {
+    pub fn new(inner: B, cipher: C) -> Self {
+        Self { inner, cipher }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: StorageBackend, C: PageCipher> StorageBackend for EncryptedBackend<B, C> {
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let first_page = page_aligned(offset, buf.len())?;
+        self.inner.read_exact_at(offset, buf)?;
+        for (page_id, page) in (first_page..).zip(buf.chunks_exact_mut(PAGE_SIZE)) {
+            if page.iter().any(|&b| b != 0) {
+                self.cipher.decrypt(page_id, page);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_all_at(&self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let first_page = page_aligned(offset, buf.len())?;
+        let mut encrypted = buf.to_vec();
+        for (page_id, page) in (first_page..).zip(encrypted.chunks_exact_mut(PAGE_SIZE)) {
+            self.cipher.encrypt(page_id, page);
+        }
+        self.inner.write_all_at(offset, &encrypted)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        self.inner.size()
+    }
+
+    fn set_size(&self, size: u64) -> io::Result<()> {
+        self.inner.set_size(size)
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.inner.sync()
+    }
+}
+
+/// Reads pages from `inner` but keeps every write in memory, so `inner` is
+/// never changed.
+///
+/// Backs databases opened with
+/// [`EngineOptions::read_only`](crate::engine::EngineOptions::read_only):
+/// recovery still stages pages, and they are dropped with the handle. Only
+/// whole, page-aligned reads and writes are accepted.
+#[derive(Debug)]
+pub struct OverlayBackend<B> {
+    inner: B,
+    overlay: RwLock<Overlay>,
+}
+
+#[derive(Debug)]
+struct Overlay {
+    pages: HashMap<u64, Box<[u8]>>,
+    size: u64,
+    /// Bytes of `inner` still visible; a truncation hides the rest.
+    inner_len: u64,
+}
+
+impl<B: StorageBackend> OverlayBackend<B> {
+    pub fn new(inner: B) -> io::Result<Self> {
+        let size = inner.size()?;
+        Ok(Self {
+            inner,
+            overlay: RwLock::new(Overlay {
+                pages: HashMap::new(),
+                size,
+                inner_len: size,
+            }),
+        })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Overlay> {
+        self.overlay.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Overlay> {
+        self.overlay.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<B: StorageBackend> StorageBackend for OverlayBackend<B> {
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let first_page = page_aligned(offset, buf.len())?;
+        let overlay = self.read();
+        if offset + buf.len() as u64 > overlay.size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read past the end of storage",
+            ));
+        }
+        for (page_id, page) in (first_page..).zip(buf.chunks_exact_mut(PAGE_SIZE)) {
+            if let Some(written) = overlay.pages.get(&page_id) {
+                page.copy_from_slice(written);
+                continue;
+            }
+            let start = page_id * PAGE_SIZE as u64;
+            let from_inner = overlay
+                .inner_len
+                .saturating_sub(start)
+                .min(PAGE_SIZE as u64) as usize;
+            self.inner.read_exact_at(start, &mut page[..from_inner])?;
+            page[from_inner..].fill(0);
+        }
+        Ok(())
+    }
+
+    fn write_all_at(&self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let first_page = page_aligned(offset, buf.len())?;
+        let mut overlay = self.write();
+        for (page_id, page) in (first_page..).zip(buf.chunks_exact(PAGE_SIZE)) {
+            overlay.pages.insert(page_id, page.into());
+        }
+        overlay.size = overlay.size.max(offset + buf.len() as u64);
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.read().size)
+    }
+
+    fn set_size(&self, size: u64) -> io::Result<()> {
+        let mut overlay = self.write();
+        if size < overlay.size {
+            overlay
+                .pages
+                .retain(|&page_id, _| page_id * (PAGE_SIZE as u64) < size);
+            overlay.inner_len = overlay.inner_len.min(size);
+        }
+        overlay.size = size;
+        Ok(())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Bytes fetched by range from somewhere the database cannot write to, such
+/// as an object in an object store.
+pub trait RangeSource: Send + Sync + fmt::Debug {
+    /// Total size of the object in bytes.
+    fn size(&self) -> io::Result<u64>;
+
+    /// Fills `buf` with the bytes at `offset`.
+    fn read_range(&self, offset: u64, buf: &mut [u8]) -> io::Result<()>;
+}
+
+impl RangeSource for Vec<u8> {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_range(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        buf.copy_from_slice(range(self, offset, buf.len())?);
+        Ok(())
+    }
+}
+
+/// Serves reads from a [`RangeSource`] and rejects every write with
+/// [`io::ErrorKind::PermissionDenied`].
+///
+/// A database opened on it, together with a copy of the WAL that was
+/// checkpointed alongside the image, answers queries; commits fail.
+#[derive(Debug)]
+pub struct ReadOnlyBackend<S> {
+    source: S,
+}
+
+impl<S: RangeSource> ReadOnlyBackend<S> {
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+}
+
+impl<S: RangeSource> StorageBackend for ReadOnlyBackend<S> {
+    fn read_exact_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.source.read_range(offset, buf)
+    }
+
+    fn write_all_at(&self, _offset: u64, _buf: &[u8]) -> io::Result<()> {
+        Err(read_only())
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        self.source.size()
+    }
+
+    fn set_size(&self, size: u64) -> io::Result<()> {
+        if size == self.source.size()? {
+            return Ok(());
+        }
+        Err(read_only())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn read_only() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "storage backend is read-only",
+    )
+}
+
+fn end_offset(offset: u64, len: usize) -> io::Result<usize> {
+    usize::try_from(offset)
+        .ok()
+        .and_then(|offset| offset.checked_add(len))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset out of range"))
+}
+
+fn range(bytes: &[u8], offset: u64, len: usize) -> io::Result<&[u8]> {
+    let end = end_offset(offset, len)?;
+    bytes
+        .get(end - len..end)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "read past the end of storage"))
+}
+
+/// First page id of a page-aligned range of whole pages.
+fn page_aligned(offset: u64, len: usize) -> io::Result<u64> {
+    if offset % PAGE_SIZE as u64 != 0 || len % PAGE_SIZE != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "storage needs whole, page-aligned pages",
+        ));
+    }
+    Ok(offset / PAGE_SIZE as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct XorCipher(u8);
+
+    impl PageCipher for XorCipher {
+        fn encrypt(&self, page_id: u64, page: &mut [u8]) {
+            for b in page {
+                *b ^= self.0 ^ page_id as u8;
+            }
+        }
+
+        fn decrypt(&self, page_id: u64, page: &mut [u8]) {
+            self.encrypt(page_id, page);
+        }
+    }
+
+    #[test]
+    fn memory_backend_grows_on_write_and_rejects_reads_past_end() {
+        let backend = MemoryBackend::new();
+        backend.write_all_at(4, b"abc").unwrap();
+        assert_eq!(backend.to_bytes(), b"\0\0\0\0abc");
+
+        let mut buf = [0u8; 2];
+        backend.read_exact_at(5, &mut buf).unwrap();
+        assert_eq!(&buf, b"bc");
+        let err = backend.read_exact_at(6, &mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        backend.set_size(2).unwrap();
+        assert_eq!(backend.size().unwrap(), 2);
+    }
+
+    #[test]
+    fn encrypted_backend_stores_ciphertext_and_reads_unwritten_pages_as_zero() {
+        let backend = EncryptedBackend::new(MemoryBackend::new(), XorCipher(0x5A));
+        backend.set_size(3 * PAGE_SIZE as u64).unwrap();
+        let page = [7u8; PAGE_SIZE];
+        backend.write_all_at(PAGE_SIZE as u64, &page).unwrap();
+
+        let mut buf = [0u8; PAGE_SIZE];
+        backend.read_exact_at(PAGE_SIZE as u64, &mut buf).unwrap();
+        assert_eq!(buf, page);
+        backend
+            .read_exact_at(2 * PAGE_SIZE as u64, &mut buf)
+            .unwrap();
+        assert_eq!(buf, [0u8; PAGE_SIZE]);
+
+        let stored = backend.into_inner().to_bytes();
+        assert_eq!(stored[PAGE_SIZE], 7 ^ 0x5A ^ 1);
+
+        let backend = EncryptedBackend::new(MemoryBackend::new(), XorCipher(0));
+        let err = backend.write_all_at(1, &page).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn overlay_backend_keeps_writes_off_the_inner_backend() {
+        let mut image = vec![1u8; PAGE_SIZE];
+        image.extend([2u8; PAGE_SIZE]);
+        let backend = OverlayBackend::new(MemoryBackend::from_bytes(image.clone())).unwrap();
+        backend.write_all_at(0, &[9u8; PAGE_SIZE]).unwrap();
+        backend
+            .write_all_at(2 * PAGE_SIZE as u64, &[3u8; PAGE_SIZE])
+            .unwrap();
+        assert_eq!(backend.size().unwrap(), 3 * PAGE_SIZE as u64);
+
+        let mut buf = vec![0u8; 3 * PAGE_SIZE];
+        backend.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(buf[0], 9);
+        assert_eq!(buf[PAGE_SIZE], 2);
+        assert_eq!(buf[2 * PAGE_SIZE], 3);
+
+        // Truncated pages come back as zeros, not as the inner bytes.
+        backend.set_size(PAGE_SIZE as u64).unwrap();
+        backend.set_size(2 * PAGE_SIZE as u64).unwrap();
+        backend
+            .read_exact_at(PAGE_SIZE as u64, &mut buf[..PAGE_SIZE])
+            .unwrap();
+        assert_eq!(&buf[..PAGE_SIZE], &[0u8; PAGE_SIZE][..]);
+        assert_eq!(backend.inner.to_bytes(), image);
+    }
+
+    #[test]
+    fn read_only_backend_rejects_writes() {
+        let backend = ReadOnlyBackend::new(b"ndb".to_vec());
+        let mut buf = [0u8; 2];
+        backend.read_exact_at(1, &mut buf).unwrap();
+        assert_eq!(&buf, b"db");
+        backend.set_size(3).unwrap();
+
+        for err in [
+            backend.write_all_at(0, b"x").unwrap_err(),
+            backend.set_size(8).unwrap_err(),
+        ] {
+            assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        }
+    }
+}
@@ -2,12 +2,31 @@
 //!
 //! This module provides online backup functionality that allows creating
 //! consistent backups while the database is running.
+//!
+//! Backups are full or incremental. Every backup records the SHA-256 of
+//! each data page (`page_digests.bin`) and the highest page LSN it saw (see
+//! [`crate::page_lsn`]). An incremental backup reads only the pages written
+//! since its base's LSN and stores those that differ from the base, in a
+//! page delta file. When the LSNs cannot vouch for the base, because the
+//! data file was replaced or was last written by a handle that kept none,
+//! every page is read and compared by digest instead. Restoring an
+//! incremental backup restores its base chain first and then applies the
+//! deltas in order.
+//!
+//! The manifest records the size and CRC32 of every file the backup stores,
+//! so [`BackupManager::verify_backup`] can check a backup without restoring
+//! it, and optionally restore it into a scratch location as a final check.
 
-use crate::Result;
+use crate::cold_store;
+use crate::engine::GraphEngine;
 use crate::error::Error;
-use crate::wal::Wal;
+use crate::page_lsn::{self, PageLsns};
+use crate::wal::{Wal, sealed_segment_path, sealed_segments};
+use crate::{PAGE_SIZE, Result};
 use serde::{Deserialize, Serialize};
-use std::fs::File;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -66,6 +85,32 @@ pub struct BackupInfo {
     pub nervusdb_version: String,
     pub checkpoint_txid: u64,
     pub checkpoint_epoch: u64,
+    /// The backup this one only stores the changes since; `None` for a full
+    /// backup.
+    #[serde(default)]
+    pub base_backup: Option<Uuid>,
+}
+
+/// Outcome of [`BackupManager::verify_backup`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub backup_id: Uuid,
+    /// Files compared against their manifest, base backups included.
+    pub files_checked: usize,
+    /// Checked files whose manifest predates checksums, so only their size
+    /// was compared.
+    #[serde(default)]
+    pub unverified: Vec<String>,
+    /// A test restore was done and the restored database opened.
+    pub test_restored: bool,
+    /// What is wrong with the backup; empty when it verified.
+    pub problems: Vec<String>,
+}
+
+impl BackupVerification {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
 }
 
 /// Backup manifest that describes a complete backup.
@@ -79,6 +124,19 @@ pub struct BackupManifest {
     pub checkpoint: CheckpointInfo,
     pub files: Vec<BackupFileInfo>,
     pub status: ManifestStatus,
+    #[serde(default)]
+    pub base_backup: Option<Uuid>,
+    /// The page LSNs of the data file as the backup read it; `None` when
+    /// the file had none.
+    #[serde(default)]
+    pub page_lsns: Option<PageLsnMark>,
+}
+
+/// Which page LSNs a backup saw: those with this id, up to `mark`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageLsnMark {
+    pub id: u64,
+    pub mark: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +152,15 @@ pub struct BackupFileInfo {
     pub checksum: String,
     pub is_wal: bool,
     pub wal_start_offset: Option<u64>,
+    /// The file holds only the data pages changed since the base backup.
+    #[serde(default)]
+    pub page_delta: bool,
+    /// The file is unchanged since the base backup and only stored there.
+    #[serde(default)]
+    pub in_base: bool,
+    /// The file is the cold storage tier, see [`crate::cold_store`].
+    #[serde(default)]
+    pub cold_tier: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,9 +183,25 @@ pub struct BackupManager {
     active_backup: RwLock<Option<ActiveBackup>>,
 }
 
+/// What an incremental backup compares against.
+struct BaseBackup {
+    id: Uuid,
+    pages: BasePages,
+    files: Vec<BackupFileInfo>,
+}
+
+/// The data pages of a base backup.
+#[derive(Debug)]
+struct BasePages {
+    digests: Vec<PageDigest>,
+    lsns: Option<PageLsnMark>,
+}
+
 #[derive(Debug)]
 struct ActiveBackup {
     id: Uuid,
+    /// The base backup's data pages, for incremental backups.
+    base_pages: Option<BasePages>,
     manifest: BackupManifest,
     progress: AtomicU64,
     total_bytes: AtomicU64,
@@ -146,6 +229,44 @@ impl BackupManager {
     /// - A backup is already in progress
     /// - The database files cannot be read
     pub fn begin_backup(&self) -> Result<BackupHandle> {
+        self.begin(None)
+    }
+
+    /// Begin an incremental backup that stores only the data pages changed
+    /// since the backup `base_id` in this manager's backup directory, which
+    /// may itself be incremental. Of the WAL, sealed segments the base
+    /// already holds are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`Self::begin_backup`], and when the base backup is
+    /// missing, did not complete, or predates page digests.
+    pub fn begin_incremental_backup(&self, base_id: Uuid) -> Result<BackupHandle> {
+        let base_dir = self.backup_path.join(base_id.to_string());
+        let manifest = self
+            .read_manifest(&base_dir)
+            .map_err(|_| Error::BackupProtocol(format!("base backup {base_id} not found")))?;
+        if !matches!(manifest.status, ManifestStatus::Completed { .. }) {
+            return Err(Error::BackupProtocol(format!(
+                "base backup {base_id} did not complete"
+            )));
+        }
+        let digests = read_page_digests(&base_dir).map_err(|_| {
+            Error::BackupProtocol(format!(
+                "base backup {base_id} has no page digests; take a full backup"
+            ))
+        })?;
+        self.begin(Some(BaseBackup {
+            id: base_id,
+            pages: BasePages {
+                digests,
+                lsns: manifest.page_lsns,
+            },
+            files: manifest.files,
+        }))
+    }
+
+    fn begin(&self, base: Option<BaseBackup>) -> Result<BackupHandle> {
         // Check if backup already in progress
         if self.active_backup.read().unwrap().is_some() {
             return Err(Error::BackupProtocol(
@@ -168,8 +289,16 @@ impl BackupManager {
         // Read current checkpoint info from WAL
         let checkpoint_info = self.get_checkpoint_info()?;
 
+        let base_backup = base.as_ref().map(|b| b.id);
+        let ndb_name = self
+            .db_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
         // Create initial manifest
-        let manifest = BackupManifest {
+        let mut manifest = BackupManifest {
             backup_id,
             created_at: timestamp,
             nervusdb_version: env!("CARGO_PKG_VERSION").to_string(),
@@ -181,16 +310,18 @@ impl BackupManager {
             },
             files: vec![
                 BackupFileInfo {
-                    name: self
-                        .db_path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .into_owned(),
+                    name: if base_backup.is_some() {
+                        format!("{ndb_name}.{PAGE_DELTA_EXTENSION}")
+                    } else {
+                        ndb_name
+                    },
                     size: ndb_size,
-                    checksum: String::new(), // Will be calculated during copy
+                    checksum: String::new(), // Filled in once copied
                     is_wal: false,
                     wal_start_offset: None,
+                    page_delta: base_backup.is_some(),
+                    in_base: false,
+                    cold_tier: false,
                 },
                 BackupFileInfo {
                     name: self
@@ -203,9 +334,64 @@ impl BackupManager {
                     checksum: String::new(),
                     is_wal: true,
                     wal_start_offset: Some(checkpoint_info.wal_offset),
+                    page_delta: false,
+                    in_base: false,
+                    cold_tier: false,
                 },
             ],
             status: ManifestStatus::InProgress,
+            base_backup,
+            page_lsns: None,
+        };
+        // Sealed segments never change, but their names are reused once a
+        // checkpoint deletes them; an incremental backup skips those whose
+        // name, size and checksum match a segment of the base.
+        for (_, segment) in sealed_segments(&self.wal_path())? {
+            let name = segment
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let size = self.get_file_size(&segment)?;
+            let checksum = format!("{:08x}", file_checksum(&segment)?);
+            let in_base = base.as_ref().is_some_and(|base| {
+                base.files
+                    .iter()
+                    .any(|f| f.is_wal && f.name == name && f.size == size && f.checksum == checksum)
+            });
+            manifest.files.push(BackupFileInfo {
+                name,
+                size,
+                checksum,
+                is_wal: true,
+                wal_start_offset: Some(0),
+                page_delta: false,
+                in_base,
+                cold_tier: false,
+            });
+        }
+        // The cold tier is small next to the data file and rewritten as a
+        // whole, so incremental backups copy all of it too.
+        let cold_path = cold_store::cold_path(&self.db_path);
+        let cold_size = if cold_path.exists() {
+            let size = self.get_file_size(&cold_path)?;
+            manifest.files.push(BackupFileInfo {
+                name: cold_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                size,
+                checksum: String::new(),
+                is_wal: false,
+                wal_start_offset: None,
+                page_delta: false,
+                in_base: false,
+                cold_tier: true,
+            });
+            size
+        } else {
+            0
         };
 
         // Write initial manifest
@@ -214,9 +400,10 @@ impl BackupManager {
         // Store active backup
         let active = ActiveBackup {
             id: backup_id,
+            base_pages: base.map(|b| b.pages),
             manifest: manifest.clone(),
             progress: AtomicU64::new(0),
-            total_bytes: AtomicU64::new(ndb_size + wal_size),
+            total_bytes: AtomicU64::new(ndb_size + wal_size + cold_size),
         };
         *self.active_backup.write().unwrap() = Some(active);
 
@@ -269,6 +456,7 @@ impl BackupManager {
                         nervusdb_version: manifest.nervusdb_version,
                         checkpoint_txid: manifest.checkpoint.txid,
                         checkpoint_epoch: manifest.checkpoint.epoch,
+                        base_backup: manifest.base_backup,
                     })),
                     ManifestStatus::Failed { error } => Ok(BackupStatus::Failed { error }),
                     ManifestStatus::InProgress => {
@@ -287,6 +475,9 @@ impl BackupManager {
         // Copy .ndb file
         self.copy_ndb_file(handle)?;
 
+        // Copy .cold file, if the database has a cold tier
+        self.copy_cold_file(handle)?;
+
         // Copy .wal file (from checkpoint position)
         self.copy_wal_file(handle)?;
 
@@ -296,9 +487,11 @@ impl BackupManager {
             if let Some(ref mut backup) = *active
                 && backup.id == handle.id
             {
+                // What the backup stores, which for an incremental backup is
+                // less than what was read.
                 backup.manifest.status = ManifestStatus::Completed {
                     completed_at: chrono::Utc::now(),
-                    total_bytes: backup.total_bytes.load(Ordering::Relaxed),
+                    total_bytes: backup.manifest.files.iter().map(|f| f.size).sum(),
                 };
                 self.write_manifest(&handle.backup_dir, &backup.manifest)?;
                 *active = None;
@@ -355,6 +548,7 @@ impl BackupManager {
                         nervusdb_version: manifest.nervusdb_version,
                         checkpoint_txid: manifest.checkpoint.txid,
                         checkpoint_epoch: manifest.checkpoint.epoch,
+                        base_backup: manifest.base_backup,
                     });
                 }
             }
@@ -363,7 +557,8 @@ impl BackupManager {
         Ok(backups)
     }
 
-    /// Restore a database from a backup.
+    /// Restore a database from a backup. An incremental backup restores its
+    /// base chain first, then applies each page delta on top.
     pub fn restore_from_backup(
         backup_dir: &Path,
         backup_id: Uuid,
@@ -386,18 +581,161 @@ impl BackupManager {
             }
         }
 
+        if let Some(base_id) = manifest.base_backup {
+            Self::restore_from_backup(backup_dir, base_id, target_db_path)?;
+            // Keep only the base's WAL segments this backup still refers to.
+            let target_wal = target_db_path.with_extension("wal");
+            for (_, segment) in sealed_segments(&target_wal)? {
+                let name = segment.file_name().unwrap_or_default().to_string_lossy();
+                if !manifest.files.iter().any(|f| f.in_base && f.name == name) {
+                    std::fs::remove_file(&segment).map_err(Error::Io)?;
+                }
+            }
+        }
+
         // Copy files back
         for file in &manifest.files {
+            if file.in_base {
+                continue;
+            }
             let src = backup_path.join(&file.name);
+            if file.page_delta {
+                apply_page_delta(&src, target_db_path)?;
+                continue;
+            }
             let dst = if file.is_wal {
-                target_db_path.with_extension("wal")
+                let target_wal = target_db_path.with_extension("wal");
+                match sealed_segment_seq(&file.name) {
+                    Some(seq) => sealed_segment_path(&target_wal, seq),
+                    None => target_wal,
+                }
+            } else if file.cold_tier {
+                cold_store::cold_path(target_db_path)
             } else {
                 target_db_path.to_path_buf()
             };
 
             std::fs::copy(&src, &dst).map_err(Error::Io)?;
         }
+        // Whatever LSNs lay beside the target belong to another data file.
+        page_lsn::remove(target_db_path)?;
+
+        Ok(())
+    }
+
+    /// Checks the backup stored at `backup_path` (`<backup_dir>/<id>`):
+    /// that it completed, and that every file it stores, across its base
+    /// chain, has the size and checksum its manifest records. With
+    /// `test_restore` a backup that passes is also restored into a scratch
+    /// directory under the system temp dir, its data pages compared with
+    /// the recorded page digests, and the result opened; the scratch copy
+    /// is removed afterwards. Files whose manifest records no checksum are
+    /// listed in [`BackupVerification::unverified`].
+    ///
+    /// Fails only if the manifest cannot be read; anything wrong with the
+    /// backup itself is reported in [`BackupVerification::problems`].
+    pub fn verify_backup(backup_path: &Path, test_restore: bool) -> Result<BackupVerification> {
+        let manifest = Self::read_manifest_from_path(&backup_path.join("backup_manifest.json"))?;
+        let mut verification = BackupVerification {
+            backup_id: manifest.backup_id,
+            files_checked: 0,
+            unverified: Vec::new(),
+            test_restored: false,
+            problems: Vec::new(),
+        };
+        Self::verify_files(backup_path, &manifest, "", &mut verification);
+
+        if test_restore && verification.is_ok() {
+            let scratch = std::env::temp_dir().join(format!("nervusdb-verify-{}", Uuid::new_v4()));
+            match Self::test_restore(backup_path, &manifest, &scratch) {
+                Ok(()) => verification.test_restored = true,
+                Err(e) => verification
+                    .problems
+                    .push(format!("test restore failed: {e}")),
+            }
+            let _ = std::fs::remove_dir_all(&scratch);
+        }
+        Ok(verification)
+    }
+
+    fn verify_files(
+        backup_path: &Path,
+        manifest: &BackupManifest,
+        prefix: &str,
+        verification: &mut BackupVerification,
+    ) {
+        let problems = &mut verification.problems;
+        if !matches!(manifest.status, ManifestStatus::Completed { .. }) {
+            problems.push(format!("{prefix}backup did not complete"));
+        }
+        for file in manifest.files.iter().filter(|f| !f.in_base) {
+            verification.files_checked += 1;
+            let path = backup_path.join(&file.name);
+            let size = match std::fs::metadata(&path) {
+                Ok(meta) => meta.len(),
+                Err(_) => {
+                    problems.push(format!("{prefix}{} is missing", file.name));
+                    continue;
+                }
+            };
+            if size != file.size {
+                problems.push(format!(
+                    "{prefix}{}: size {size}, manifest says {}",
+                    file.name, file.size
+                ));
+                continue;
+            }
+            // Manifests written before checksums were recorded leave it empty.
+            if file.checksum.is_empty() {
+                verification
+                    .unverified
+                    .push(format!("{prefix}{}", file.name));
+                continue;
+            }
+            match file_checksum(&path) {
+                Ok(checksum) if format!("{checksum:08x}") == file.checksum => {}
+                Ok(checksum) => problems.push(format!(
+                    "{prefix}{}: checksum {checksum:08x}, manifest says {}",
+                    file.name, file.checksum
+                )),
+                Err(e) => problems.push(format!("{prefix}{}: {e}", file.name)),
+            }
+        }
+
+        let Some(base_id) = manifest.base_backup else {
+            return;
+        };
+        let base_path = backup_path.with_file_name(base_id.to_string());
+        match Self::read_manifest_from_path(&base_path.join("backup_manifest.json")) {
+            Ok(base) => {
+                let prefix = format!("{prefix}base backup {base_id}: ");
+                Self::verify_files(&base_path, &base, &prefix, verification);
+            }
+            Err(_) => problems.push(format!("{prefix}base backup {base_id} not found")),
+        }
+    }
+
+    fn test_restore(backup_path: &Path, manifest: &BackupManifest, scratch: &Path) -> Result<()> {
+        std::fs::create_dir_all(scratch).map_err(Error::Io)?;
+        let ndb_path = scratch.join("verify.ndb");
+        let backup_dir = backup_path.parent().unwrap_or(Path::new("."));
+        Self::restore_from_backup(backup_dir, manifest.backup_id, &ndb_path)?;
+
+        if let Ok(expected) = read_page_digests(backup_path) {
+            let mut restored = BufReader::new(File::open(&ndb_path).map_err(Error::Io)?);
+            let mut page = vec![0u8; PAGE_SIZE];
+            for (page_id, expected) in expected.iter().enumerate() {
+                let len = read_page(&mut restored, &mut page)?;
+                if len == 0 || page_digest(&page[..len]) != *expected {
+                    return Err(Error::BackupProtocol(format!(
+                        "restored page {page_id} does not match the backup"
+                    )));
+                }
+            }
+        }
 
+        let engine = GraphEngine::open(&ndb_path, ndb_path.with_extension("wal"))?;
+        drop(engine.begin_read());
         Ok(())
     }
 
@@ -413,11 +751,14 @@ impl BackupManager {
 
     fn get_wal_size(&self) -> Result<u64> {
         let wal_path = self.wal_path();
+        let mut total = 0;
+        for (_, segment) in sealed_segments(&wal_path)? {
+            total += self.get_file_size(&segment)?;
+        }
         if wal_path.exists() {
-            self.get_file_size(&wal_path)
-        } else {
-            Ok(0)
+            total += self.get_file_size(&wal_path)?;
         }
+        Ok(total)
     }
 
     fn get_checkpoint_info(&self) -> Result<WalCheckpointInfo> {
@@ -444,36 +785,152 @@ impl BackupManager {
     }
 
     fn copy_ndb_file(&self, handle: &BackupHandle) -> Result<()> {
-        let src = &self.db_path;
-        let dst = handle.backup_dir.join(
-            src.file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned(),
-        );
+        let (name, base) = {
+            let mut active = self.active_backup.write().unwrap();
+            let Some(backup) = active.as_mut() else {
+                return Err(Error::BackupProtocol("No backup in progress".to_string()));
+            };
+            (
+                backup.manifest.files[0].name.clone(),
+                backup.base_pages.take(),
+            )
+        };
+        let dst = handle.backup_dir.join(name);
+
+        // Read before any page, so pages written during the copy get
+        // higher LSNs than the mark and are copied again next time.
+        let lsns = PageLsns::read(&self.db_path)?;
+        let mark = lsns.as_ref().map(|lsns| PageLsnMark {
+            id: lsns.id(),
+            mark: lsns.mark(),
+        });
+        // Pages the LSNs vouch for as unchanged since the base are not read.
+        let unchanged = |page_id: u64| match (&base, &lsns) {
+            (Some(base), Some(lsns)) => base.lsns.is_some_and(|since| {
+                since.id == lsns.id()
+                    && page_id >= 2
+                    && (page_id as usize) < base.digests.len()
+                    && !lsns.written_since(page_id, since.mark)
+            }),
+            _ => false,
+        };
 
-        let mut src_file = File::open(src).map_err(Error::Io)?;
-        let mut dst_file = File::create(&dst).map_err(Error::Io)?;
+        let src = File::open(&self.db_path).map_err(Error::Io)?;
+        let total = src.metadata().map_err(Error::Io)?.len();
+        let mut src_file = BufReader::new(src);
+        let mut dst_file = BufWriter::new(File::create(&dst).map_err(Error::Io)?);
+        if base.is_some() {
+            dst_file.write_all(PAGE_DELTA_MAGIC).map_err(Error::Io)?;
+            dst_file
+                .write_all(&total.to_le_bytes())
+                .map_err(Error::Io)?;
+        }
 
-        let total = std::io::copy(&mut src_file, &mut dst_file).map_err(Error::Io)?;
+        // Full backups copy every page; incremental ones only those whose
+        // digest differs from the base, each prefixed with its page id.
+        let mut digests = Vec::new();
+        let mut position = 0u64;
+        let mut page = vec![0u8; PAGE_SIZE];
+        for page_id in 0..total.div_ceil(PAGE_SIZE as u64) {
+            let offset = page_id * PAGE_SIZE as u64;
+            let len = if unchanged(page_id) {
+                digests.push(base.as_ref().unwrap().digests[page_id as usize]);
+                (total - offset).min(PAGE_SIZE as u64) as usize
+            } else {
+                if position != offset {
+                    src_file.seek(SeekFrom::Start(offset)).map_err(Error::Io)?;
+                }
+                let len = read_page(&mut src_file, &mut page)?;
+                position = offset + len as u64;
+                if len == 0 {
+                    break;
+                }
+                let digest = page_digest(&page[..len]);
+                digests.push(digest);
+                match &base {
+                    None => dst_file.write_all(&page[..len]).map_err(Error::Io)?,
+                    Some(base) if base.digests.get(page_id as usize) != Some(&digest) => {
+                        page[len..].fill(0);
+                        dst_file
+                            .write_all(&page_id.to_le_bytes())
+                            .map_err(Error::Io)?;
+                        dst_file.write_all(&page).map_err(Error::Io)?;
+                    }
+                    Some(_) => {}
+                }
+                len
+            };
 
-        // Update progress
-        {
+            // Update progress
             let active = self.active_backup.read().unwrap();
             if let Some(ref backup) = *active {
-                backup.progress.fetch_add(total, Ordering::Relaxed);
+                backup.progress.fetch_add(len as u64, Ordering::Relaxed);
             }
         }
-
+        dst_file.flush().map_err(Error::Io)?;
+        drop(dst_file);
+
+        write_page_digests(&handle.backup_dir, &digests)?;
+        let size = self.get_file_size(&dst)?;
+        let checksum = format!("{:08x}", file_checksum(&dst)?);
+        if let Some(backup) = self.active_backup.write().unwrap().as_mut() {
+            backup.manifest.files[0].size = size;
+            backup.manifest.files[0].checksum = checksum;
+            backup.manifest.page_lsns = mark;
+        }
         Ok(())
     }
 
     fn copy_wal_file(&self, handle: &BackupHandle) -> Result<()> {
         let src = self.wal_path();
         if !src.exists() {
+            // Nothing to copy, so nothing for restore to look for either.
+            if let Some(backup) = self.active_backup.write().unwrap().as_mut() {
+                backup.manifest.files.retain(|f| !f.is_wal);
+            }
+            return Ok(());
+        }
+
+        let in_base: Vec<(String, u64)> = match self.active_backup.read().unwrap().as_ref() {
+            Some(backup) => backup
+                .manifest
+                .files
+                .iter()
+                .filter(|f| f.in_base)
+                .map(|f| (f.name.clone(), f.size))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // Sealed segments first, so every tx in the copied active file has its
+        // predecessors in the backup as well.
+        for (_, segment) in sealed_segments(&src)? {
+            let name = segment.file_name().unwrap_or_default().to_string_lossy();
+            if let Some((_, size)) = in_base.iter().find(|(n, _)| *n == name) {
+                let active = self.active_backup.read().unwrap();
+                if let Some(ref backup) = *active {
+                    backup.progress.fetch_add(*size, Ordering::Relaxed);
+                }
+                continue;
+            }
+            self.copy_wal_segment(handle, &segment)?;
+        }
+        self.copy_wal_segment(handle, &src)
+    }
+
+    fn copy_cold_file(&self, handle: &BackupHandle) -> Result<()> {
+        let src = cold_store::cold_path(&self.db_path);
+        if !src.exists() {
+            if let Some(backup) = self.active_backup.write().unwrap().as_mut() {
+                backup.manifest.files.retain(|f| !f.cold_tier);
+            }
             return Ok(());
         }
+        self.copy_wal_segment(handle, &src)
+    }
 
+    /// Copies `src` whole, recording the size and checksum of the copy.
+    fn copy_wal_segment(&self, handle: &BackupHandle, src: &Path) -> Result<()> {
         let dst = handle.backup_dir.join(
             src.file_name()
                 .unwrap_or_default()
@@ -481,16 +938,21 @@ impl BackupManager {
                 .into_owned(),
         );
 
-        let mut src_file = File::open(&src).map_err(Error::Io)?;
+        let mut src_file = File::open(src).map_err(Error::Io)?;
         let mut dst_file = File::create(&dst).map_err(Error::Io)?;
 
         let total = std::io::copy(&mut src_file, &mut dst_file).map_err(Error::Io)?;
+        drop(dst_file);
 
-        // Update progress
-        {
-            let active = self.active_backup.read().unwrap();
-            if let Some(ref backup) = *active {
-                backup.progress.fetch_add(total, Ordering::Relaxed);
+        // The active WAL keeps growing after `begin`; record what was copied.
+        let checksum = format!("{:08x}", file_checksum(&dst)?);
+        let name = dst.file_name().unwrap_or_default().to_string_lossy();
+        let mut active = self.active_backup.write().unwrap();
+        if let Some(ref mut backup) = *active {
+            backup.progress.fetch_add(total, Ordering::Relaxed);
+            if let Some(file) = backup.manifest.files.iter_mut().find(|f| f.name == name) {
+                file.size = total;
+                file.checksum = checksum;
             }
         }
 
@@ -514,6 +976,98 @@ impl BackupManager {
     }
 }
 
+/// Extension appended to the data file name for a page delta.
+const PAGE_DELTA_EXTENSION: &str = "pages";
+
+/// Header of a page delta: magic, then the data file length as `u64` LE,
+/// then `(page_id: u64 LE, page)` records.
+const PAGE_DELTA_MAGIC: &[u8; 8] = b"NDBPDLT1";
+
+const PAGE_DIGESTS_FILE: &str = "page_digests.bin";
+
+type PageDigest = [u8; 32];
+
+fn page_digest(page: &[u8]) -> PageDigest {
+    Sha256::digest(page).into()
+}
+
+/// Fills `page` from `src` as far as the file allows; returns the bytes read.
+fn read_page(src: &mut impl Read, page: &mut [u8]) -> Result<usize> {
+    let mut len = 0;
+    while len < page.len() {
+        match src.read(&mut page[len..]).map_err(Error::Io)? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+fn file_checksum(path: &Path) -> Result<u32> {
+    let mut file = BufReader::new(File::open(path).map_err(Error::Io)?);
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0u8; PAGE_SIZE];
+    loop {
+        match file.read(&mut buf).map_err(Error::Io)? {
+            0 => return Ok(hasher.finalize()),
+            n => hasher.update(&buf[..n]),
+        }
+    }
+}
+
+fn write_page_digests(dir: &Path, digests: &[PageDigest]) -> Result<()> {
+    std::fs::write(dir.join(PAGE_DIGESTS_FILE), digests.concat()).map_err(Error::Io)
+}
+
+fn read_page_digests(dir: &Path) -> Result<Vec<PageDigest>> {
+    let bytes = std::fs::read(dir.join(PAGE_DIGESTS_FILE)).map_err(Error::Io)?;
+    Ok(bytes
+        .chunks_exact(size_of::<PageDigest>())
+        .map(|c| c.try_into().unwrap())
+        .collect())
+}
+
+/// Writes the pages of the delta at `src` into the data file at `target`
+/// and sets its length to the one recorded in the delta.
+fn apply_page_delta(src: &Path, target: &Path) -> Result<()> {
+    let mut delta = BufReader::new(File::open(src).map_err(Error::Io)?);
+    let mut header = [0u8; 16];
+    delta.read_exact(&mut header).map_err(Error::Io)?;
+    if &header[..8] != PAGE_DELTA_MAGIC {
+        return Err(Error::BackupProtocol(format!(
+            "{} is not a page delta",
+            src.display()
+        )));
+    }
+    let file_len = u64::from_le_bytes(header[8..].try_into().unwrap());
+
+    let mut dst = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(target)
+        .map_err(Error::Io)?;
+    let mut id = [0u8; 8];
+    let mut page = vec![0u8; PAGE_SIZE];
+    while read_page(&mut delta, &mut id)? == id.len() {
+        delta.read_exact(&mut page).map_err(Error::Io)?;
+        let offset = u64::from_le_bytes(id) * PAGE_SIZE as u64;
+        dst.seek(SeekFrom::Start(offset)).map_err(Error::Io)?;
+        dst.write_all(&page).map_err(Error::Io)?;
+    }
+    dst.set_len(file_len).map_err(Error::Io)?;
+    dst.sync_all().map_err(Error::Io)
+}
+
+/// Sequence number of a sealed WAL segment file name (`graph.wal.000003`).
+fn sealed_segment_seq(name: &str) -> Option<u64> {
+    let (_, suffix) = name.rsplit_once('.')?;
+    if suffix.len() < 6 || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    suffix.parse().ok()
+}
+
 /// Helper struct for reading checkpoint info from WAL
 struct WalCheckpointInfo {
     txid: u64,
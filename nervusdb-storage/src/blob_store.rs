@@ -46,6 +46,12 @@ impl BlobStore {
         Ok(last_pid)
     }
 
+    /// Bytes of page store a blob of `len` bytes occupies; every blob takes
+    /// at least one page.
+    pub fn stored_bytes(len: usize) -> u64 {
+        (len.div_ceil(MAX_DATA_PER_PAGE).max(1) * crate::PAGE_SIZE) as u64
+    }
+
     /// Reads a blob starting from the given page ID.
     pub fn read(pager: &Pager, page_id: u64) -> Result<Vec<u8>> {
         Self::read_direct(pager, page_id)
@@ -1,7 +1,12 @@
 use crate::csr::CsrSegment;
-use crate::idmap::{ExternalId, InternalNodeId, LabelId};
+use crate::engine::EndpointConstraints;
+use crate::idmap::{ExternalId, InternalNodeId, LabelId, internal_node_id};
+use crate::index::unique::unique_constraint_name;
 use crate::label_interner::LabelInterner;
+use crate::names::{NameKind, NameLimits};
+use crate::operation::{OperationHandle, Tracker};
 use crate::property::PropertyValue;
+use crate::snapshot::EdgeKey;
 use crate::wal::SegmentPointer;
 use crate::{Error, Result};
 use std::collections::BTreeMap;
@@ -56,6 +61,8 @@ pub struct BulkLoader {
     wal_path: PathBuf,
     nodes: Vec<BulkNode>,
     edges: Vec<BulkEdge>,
+    unique_constraints: Vec<(String, String)>,
+    endpoint_constraints: EndpointConstraints,
 }
 
 impl BulkLoader {
@@ -79,6 +86,8 @@ impl BulkLoader {
             wal_path,
             nodes: Vec::new(),
             edges: Vec::new(),
+            unique_constraints: Vec::new(),
+            endpoint_constraints: EndpointConstraints::new(),
         })
     }
 
@@ -88,8 +97,11 @@ impl BulkLoader {
     ///
     /// # Errors
     ///
-    /// Returns an error if the external_id is not unique.
+    /// Returns an error if the external_id is not unique, a property value is over
+    /// [`crate::property::MAX_PROPERTY_VALUE_BYTES`], or the label or a property key
+    /// breaks the default [`NameLimits`].
     pub fn add_node(&mut self, node: BulkNode) -> Result<()> {
+        check_row(NameKind::Label, &node.label, &node.properties)?;
         // Uniqueness and referential integrity are validated in `commit()` to allow
         // streaming ingestion without requiring nodes/edges ordering constraints here.
         self.nodes.push(node);
@@ -102,21 +114,64 @@ impl BulkLoader {
     ///
     /// # Errors
     ///
-    /// Returns an error if src or dst external_id doesn't reference a node.
+    /// Returns an error if src or dst external_id doesn't reference a node, a
+    /// property value is over [`crate::property::MAX_PROPERTY_VALUE_BYTES`], or the
+    /// relationship type or a property key breaks the default [`NameLimits`].
     pub fn add_edge(&mut self, edge: BulkEdge) -> Result<()> {
+        check_row(NameKind::RelType, &edge.rel_type, &edge.properties)?;
         // Referential integrity is validated in `commit()` to allow loading edges
         // before all nodes have been buffered.
         self.edges.push(edge);
         Ok(())
     }
 
+    /// Declares a unique constraint on `label.property` for the new database.
+    ///
+    /// `commit()` rejects the load if two nodes with that label share a
+    /// non-null value, and otherwise creates the constraint as
+    /// `GraphEngine::create_unique_constraint` would.
+    pub fn add_unique_constraint(&mut self, label: &str, property: &str) -> Result<()> {
+        if property.is_empty() || property.contains([',', '(', ')']) {
+            return Err(Error::InvalidIndex(format!(
+                "invalid property {property:?}"
+            )));
+        }
+        let constraint = (label.to_string(), property.to_string());
+        if !self.unique_constraints.contains(&constraint) {
+            self.unique_constraints.push(constraint);
+        }
+        Ok(())
+    }
+
+    /// Allows relationships of type `rel_type` to run from nodes labelled
+    /// `src_label` to nodes labelled `dst_label`. Once a type has an allowed
+    /// pair, `commit()` rejects the load if one of its edges connects any
+    /// other labels, and otherwise creates the constraint as
+    /// `GraphEngine::create_endpoint_constraint` would.
+    pub fn add_endpoint_constraint(
+        &mut self,
+        rel_type: &str,
+        src_label: &str,
+        dst_label: &str,
+    ) -> Result<()> {
+        let limits = NameLimits::default();
+        limits.check(NameKind::RelType, rel_type)?;
+        limits.check(NameKind::Label, src_label)?;
+        limits.check(NameKind::Label, dst_label)?;
+        self.endpoint_constraints
+            .entry(rel_type.to_string())
+            .or_default()
+            .insert((src_label.to_string(), dst_label.to_string()));
+        Ok(())
+    }
+
     /// Commits the bulk load, writing all data to disk.
     ///
     /// This performs the following steps:
-    /// 1. Validates all data (uniqueness, referential integrity)
+    /// 1. Validates all data (uniqueness, unique constraints, referential integrity)
     /// 2. Assigns internal IDs to all nodes
     /// 3. Generates L1 Segments from edges
-    /// 4. Writes properties to B-Tree
+    /// 4. Writes properties to B-Tree and unique constraint indexes
     /// 5. Initializes WAL with manifest
     ///
     /// # Errors
@@ -126,8 +181,37 @@ impl BulkLoader {
     /// - Disk I/O fails
     /// - Database constraints are violated
     pub fn commit(self) -> Result<()> {
+        self.commit_with_progress(&OperationHandle::new(), &mut |_, _| {})
+    }
+
+    /// Like [`BulkLoader::commit`], publishing progress to `handle` and
+    /// calling `progress` with `(done, total)`. Every node and edge counts
+    /// twice: once when its id or segment entry is written and once when its
+    /// properties are.
+    ///
+    /// Cancellation is checked between rows. A cancelled load removes the
+    /// files it created, leaving no database behind.
+    pub fn commit_with_progress(
+        self,
+        handle: &OperationHandle,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<()> {
+        let mut tracker = Tracker::new(handle, progress);
+        let result = self.write_all(&mut tracker);
+        if matches!(result, Err(Error::Cancelled)) {
+            let _ = std::fs::remove_file(&self.db_path);
+            let _ = std::fs::remove_file(&self.wal_path);
+        }
+        result
+    }
+
+    fn write_all(&self, tracker: &mut Tracker<'_>) -> Result<()> {
+        tracker.check()?;
+        tracker.start(2 * (self.nodes.len() + self.edges.len()) as u64);
+
         // Step 1: Validate data
         self.validate()?;
+        tracker.check()?;
 
         // Step 2: Create Pager
         let mut pager = crate::pager::Pager::open(&self.db_path)?;
@@ -138,7 +222,7 @@ impl BulkLoader {
 
         // Step 4: Build IdMap and node label IDs
         let (external_to_internal, node_label_ids) =
-            self.build_idmap_and_labels(&mut pager, &mut label_interner)?;
+            self.build_idmap_and_labels(&mut pager, &mut label_interner, tracker)?;
 
         // Step 5: Generate L1 Segments
         let segments = self.build_segments(&external_to_internal, &label_interner)?;
@@ -147,11 +231,15 @@ impl BulkLoader {
         let segments_for_stats = segments.clone();
 
         // Step 6: Write segments to pager and get segment pointers
+        tracker.check()?;
         let segment_pointers = self.write_segments(&mut pager, segments)?;
+        tracker.advance(self.edges.len() as u64);
 
         // Step 7: Write properties and get properties_root
         let properties_root =
-            self.write_properties(&mut pager, &external_to_internal, &label_interner)?;
+            self.write_properties(&mut pager, &external_to_internal, &label_interner, tracker)?;
+        self.write_unique_constraints(&mut pager, &external_to_internal)?;
+        tracker.check()?;
 
         // Step 8: Collect statistics and get stats_root
         let stats_root = self.write_statistics(&mut pager, &node_label_ids, &segments_for_stats)?;
@@ -163,6 +251,7 @@ impl BulkLoader {
             stats_root,
             &label_interner,
         )?;
+        tracker.finish();
 
         Ok(())
     }
@@ -188,6 +277,24 @@ impl BulkLoader {
             }
         }
 
+        for (label, property) in &self.unique_constraints {
+            let mut seen = std::collections::HashSet::new();
+            for node in self.nodes.iter().filter(|n| &n.label == label) {
+                let Some(value) = node.properties.get(property) else {
+                    continue;
+                };
+                if matches!(value, PropertyValue::Null) {
+                    continue;
+                }
+                if !seen.insert(crate::index::ordered_key::encode_ordered_value(value)) {
+                    return Err(Error::ConstraintViolation(format!(
+                        "{} is unique, bulk load has duplicate {property} = {value:?}",
+                        unique_constraint_name(label, property)
+                    )));
+                }
+            }
+        }
+
         // Build set of valid external IDs for edge validation
         let valid_ids: BTreeMap<ExternalId, ()> =
             self.nodes.iter().map(|n| (n.external_id, ())).collect();
@@ -206,6 +313,27 @@ impl BulkLoader {
             }
         }
 
+        if !self.endpoint_constraints.is_empty() {
+            let labels: BTreeMap<ExternalId, &str> = self
+                .nodes
+                .iter()
+                .map(|n| (n.external_id, n.label.as_str()))
+                .collect();
+            for edge in &self.edges {
+                let Some(allowed) = self.endpoint_constraints.get(&edge.rel_type) else {
+                    continue;
+                };
+                let src = labels[&edge.src_external_id];
+                let dst = labels[&edge.dst_external_id];
+                if !allowed.iter().any(|(s, d)| s == src && d == dst) {
+                    return Err(Error::ConstraintViolation(format!(
+                        "bulk load edge {}-[:{}]->{} connects (:{src})->(:{dst}), which {} does not allow",
+                        edge.src_external_id, edge.rel_type, edge.dst_external_id, edge.rel_type
+                    )));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -214,6 +342,7 @@ impl BulkLoader {
         &self,
         pager: &mut crate::pager::Pager,
         label_interner: &mut LabelInterner,
+        tracker: &mut Tracker<'_>,
     ) -> Result<(BTreeMap<ExternalId, InternalNodeId>, Vec<LabelId>)> {
         use crate::idmap::IdMap;
 
@@ -226,7 +355,8 @@ impl BulkLoader {
 
         // Assign internal IDs and register labels
         for (idx, node) in self.nodes.iter().enumerate() {
-            let internal_id = idx as InternalNodeId;
+            tracker.check()?;
+            let internal_id = internal_node_id(idx as u64)?;
             let label_id = label_interner.get_or_create(&node.label);
 
             external_to_internal.insert(node.external_id, internal_id);
@@ -234,6 +364,7 @@ impl BulkLoader {
 
             // Write to IdMap
             idmap.apply_create_node(pager, node.external_id, label_id, internal_id)?;
+            tracker.advance(1);
         }
 
         // We don't persist label interner snapshot to pager metadata. The canonical mapping
@@ -248,9 +379,6 @@ impl BulkLoader {
         external_to_internal: &BTreeMap<ExternalId, InternalNodeId>,
         label_interner: &LabelInterner,
     ) -> Result<Vec<CsrSegment>> {
-        use crate::csr::{EdgeRecord, SegmentId};
-        use crate::snapshot::EdgeKey;
-
         // Build edge list with internal IDs
         let mut edges: Vec<EdgeKey> = Vec::with_capacity(self.edges.len());
 
@@ -268,64 +396,7 @@ impl BulkLoader {
         // Sort edges by (src, rel, dst)
         edges.sort();
 
-        if edges.is_empty() {
-            // Return empty segment
-            return Ok(vec![CsrSegment {
-                id: SegmentId(0),
-                meta_page_id: 0,
-                min_src: 0,
-                max_src: 0,
-                min_dst: 0,
-                max_dst: 0,
-                offsets: vec![0, 0],
-                edges: Vec::new(),
-                in_offsets: Vec::new(),
-                in_edges: Vec::new(),
-            }]);
-        }
-
-        // Group edges by source node
-        let (min_src, max_src) = edges.iter().fold((u32::MAX, 0u32), |(min_s, max_s), e| {
-            (min_s.min(e.src), max_s.max(e.src))
-        });
-
-        let range = (max_src - min_src) as usize + 2;
-        let mut offsets = vec![0u64; range];
-        let mut edges_by_src: BTreeMap<InternalNodeId, Vec<EdgeRecord>> = BTreeMap::new();
-
-        for e in edges {
-            edges_by_src.entry(e.src).or_default().push(EdgeRecord {
-                rel: e.rel,
-                dst: e.dst,
-            });
-        }
-
-        // Build CSR format
-        let mut edge_vec: Vec<EdgeRecord> = Vec::new();
-        let mut cursor = 0u64;
-        for src in min_src..=max_src {
-            let idx = (src - min_src) as usize;
-            offsets[idx] = cursor;
-            if let Some(mut list) = edges_by_src.remove(&src) {
-                list.sort_by_key(|r| (r.rel, r.dst));
-                cursor += list.len() as u64;
-                edge_vec.extend(list);
-            }
-        }
-        offsets[(max_src - min_src) as usize + 1] = cursor;
-
-        Ok(vec![CsrSegment {
-            id: SegmentId(0),
-            meta_page_id: 0,
-            min_src,
-            max_src,
-            min_dst: 0,
-            max_dst: 0,
-            offsets,
-            edges: edge_vec,
-            in_offsets: Vec::new(),
-            in_edges: Vec::new(),
-        }])
+        Ok(vec![csr_segment(&edges)])
     }
 
     /// Writes segments to pager and returns segment pointers.
@@ -337,11 +408,7 @@ impl BulkLoader {
         let mut pointers = Vec::with_capacity(segments.len());
 
         for seg in segments.iter_mut() {
-            seg.persist(pager)?;
-            pointers.push(SegmentPointer {
-                id: seg.id.0,
-                meta_page_id: seg.meta_page_id,
-            });
+            pointers.push(persist_segment(pager, seg)?);
         }
 
         Ok(pointers)
@@ -353,6 +420,7 @@ impl BulkLoader {
         pager: &mut crate::pager::Pager,
         external_to_internal: &BTreeMap<ExternalId, InternalNodeId>,
         label_interner: &LabelInterner,
+        tracker: &mut Tracker<'_>,
     ) -> Result<u64> {
         use crate::index::btree::BTree;
 
@@ -361,22 +429,18 @@ impl BulkLoader {
 
         // Write node properties (Tag 0)
         for node in &self.nodes {
+            tracker.check()?;
             let internal_id = external_to_internal[&node.external_id];
             for (key, value) in &node.properties {
-                let mut btree_key = Vec::with_capacity(1 + 4 + 4 + key.len());
-                btree_key.push(0u8); // Tag 0: Node Property
-                btree_key.extend_from_slice(&internal_id.to_be_bytes());
-                btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
-                btree_key.extend_from_slice(key.as_bytes());
-
-                let encoded_val = value.encode();
-                let blob_id = crate::blob_store::BlobStore::write_direct(pager, &encoded_val)?;
-                tree.insert(pager, &btree_key, blob_id)?;
+                let btree_key = node_property_key(internal_id, key);
+                write_property(&mut tree, pager, &btree_key, value)?;
             }
+            tracker.advance(1);
         }
 
         // Write edge properties (Tag 1)
         for edge in &self.edges {
+            tracker.check()?;
             let src = external_to_internal[&edge.src_external_id];
             let dst = external_to_internal[&edge.dst_external_id];
             let rel = label_interner
@@ -386,23 +450,61 @@ impl BulkLoader {
                 ))?;
 
             for (key, value) in &edge.properties {
-                let mut btree_key = Vec::with_capacity(1 + 4 + 4 + 4 + 4 + key.len());
-                btree_key.push(1u8); // Tag 1: Edge Property
-                btree_key.extend_from_slice(&src.to_be_bytes());
-                btree_key.extend_from_slice(&rel.to_be_bytes());
-                btree_key.extend_from_slice(&dst.to_be_bytes());
-                btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
-                btree_key.extend_from_slice(key.as_bytes());
-
-                let encoded_val = value.encode();
-                let blob_id = crate::blob_store::BlobStore::write_direct(pager, &encoded_val)?;
-                tree.insert(pager, &btree_key, blob_id)?;
+                let btree_key = edge_property_key(EdgeKey { src, rel, dst }, key);
+                write_property(&mut tree, pager, &btree_key, value)?;
             }
+            tracker.advance(1);
         }
 
         Ok(tree.root().as_u64())
     }
 
+    /// Registers each declared unique constraint in the index catalog and
+    /// indexes the loaded nodes under it.
+    fn write_unique_constraints(
+        &self,
+        pager: &mut crate::pager::Pager,
+        external_to_internal: &BTreeMap<ExternalId, InternalNodeId>,
+    ) -> Result<()> {
+        use crate::index::btree::BTree;
+        use crate::index::catalog::{CompositeKey, IndexCatalog};
+        use crate::index::composite::update_composite;
+
+        if self.unique_constraints.is_empty() {
+            return Ok(());
+        }
+        let mut catalog = IndexCatalog::open_or_create(pager)?;
+        for (label, property) in &self.unique_constraints {
+            let name = unique_constraint_name(label, property);
+            let key = CompositeKey {
+                label: label.clone(),
+                properties: vec![property.clone()],
+                unique: true,
+            };
+            let def = catalog.get_or_create_composite(pager, &name, key)?;
+            let mut tree = BTree::load(def.root);
+            for node in self.nodes.iter().filter(|n| &n.label == label) {
+                let Some(value) = node.properties.get(property) else {
+                    continue;
+                };
+                if matches!(value, PropertyValue::Null) {
+                    continue;
+                }
+                let internal_id = external_to_internal[&node.external_id];
+                update_composite(
+                    &mut tree,
+                    pager,
+                    def.id,
+                    internal_id,
+                    None,
+                    Some(std::slice::from_ref(value)),
+                )?;
+            }
+            catalog.update_root(pager, &name, tree.root())?;
+        }
+        Ok(())
+    }
+
     /// Collects statistics and writes them to blob store.
     fn write_statistics(
         &self,
@@ -426,6 +528,7 @@ impl BulkLoader {
                 *stats.edge_counts_by_type.entry(edge.rel).or_default() += 1;
             }
         }
+        stats.degrees = crate::stats::degree_stats(segments.iter().flat_map(|seg| seg.edge_keys()));
 
         // Write stats to blob store
         let encoded_stats = stats.encode();
@@ -442,52 +545,571 @@ impl BulkLoader {
         stats_root: u64,
         label_interner: &LabelInterner,
     ) -> Result<()> {
-        use crate::wal::{Wal, WalRecord};
+        initialize_wal(
+            &self.wal_path,
+            segment_pointers,
+            properties_root,
+            stats_root,
+            label_interner,
+            &self.endpoint_constraints,
+        )
+    }
+}
 
-        let mut wal = Wal::open(&self.wal_path)?;
+/// Default number of nodes or edges [`BulkLoaderBuilder`] holds in memory
+/// before spilling a sorted run to disk.
+pub const DEFAULT_BULK_RUN_RECORDS: usize = 1 << 20;
+
+/// Counts reported by [`BulkLoaderBuilder::load`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct BulkLoadStats {
+    pub nodes: u64,
+    pub edges: u64,
+    /// Node rows replaced by a later row with the same external id.
+    pub duplicate_nodes: u64,
+    /// Edge rows replaced by a later row with the same source, type and
+    /// target.
+    pub duplicate_edges: u64,
+    /// Sorted runs written to the temporary directory.
+    pub spilled_runs: u64,
+}
 
-        let txid = 0; // First transaction
+/// Streaming bulk loader for inputs too large to hold in memory.
+///
+/// Unlike [`BulkLoader`], nodes and edges are consumed from iterators and
+/// sorted externally: every `run_records` rows are sorted and spilled to a
+/// run file, and the runs are merged while the database is written, so the
+/// IdMap, property tree and CSR segment are built in ascending key order.
+/// Rows repeating an external id (or, for edges, the same source, type and
+/// target) are deduplicated, the last row winning. Edges may only reference
+/// nodes from the node iterator.
+///
+/// # Example
+///
+/// ```ignore
+/// let stats = BulkLoaderBuilder::new(PathBuf::from("db.ndb"))
+///     .run_records(100_000)
+///     .load(nodes, edges)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BulkLoaderBuilder {
+    db_path: PathBuf,
+    temp_dir: Option<PathBuf>,
+    run_records: usize,
+}
 
-        // Begin transaction
-        wal.append(&WalRecord::BeginTx { txid })?;
+impl BulkLoaderBuilder {
+    /// Creates a builder for a new database at `db_path`, which MUST NOT
+    /// exist yet.
+    pub fn new(db_path: PathBuf) -> Self {
+        Self {
+            db_path,
+            temp_dir: None,
+            run_records: DEFAULT_BULK_RUN_RECORDS,
+        }
+    }
 
-        // Write label definitions using snapshot's iter_ids
-        let snapshot = label_interner.snapshot();
-        for id in snapshot.iter_ids() {
-            if let Some(name) = snapshot.get_name(id) {
-                wal.append(&WalRecord::CreateLabel {
-                    name: name.to_string(),
-                    label_id: id,
-                })?;
+    /// Directory for sorted runs. Defaults to the database's directory.
+    pub fn temp_dir(mut self, dir: PathBuf) -> Self {
+        self.temp_dir = Some(dir);
+        self
+    }
+
+    /// Rows held in memory before a sorted run is spilled to disk.
+    pub fn run_records(mut self, records: usize) -> Self {
+        self.run_records = records.max(1);
+        self
+    }
+
+    /// Loads `nodes` and then `edges` into the new database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database exists, a row breaks the name or
+    /// property size limits, an edge references an unknown external id, or
+    /// disk I/O fails.
+    pub fn load<N, E>(self, nodes: N, edges: E) -> Result<BulkLoadStats>
+    where
+        N: IntoIterator<Item = BulkNode>,
+        E: IntoIterator<Item = BulkEdge>,
+    {
+        use crate::external_sort::{ExternalSorter, RunReader, RunWriter};
+        use crate::idmap::IdMap;
+        use crate::index::btree::BTree;
+
+        if self.db_path.exists() {
+            return Err(Error::WalProtocol(
+                "Database file already exists. BulkLoader only works with new databases.",
+            ));
+        }
+        let file_name = self
+            .db_path
+            .file_name()
+            .ok_or(Error::WalProtocol("bulk load path has no file name"))?
+            .to_string_lossy()
+            .into_owned();
+        let temp_dir = match self.temp_dir {
+            Some(dir) => dir,
+            None => self.db_path.parent().map(PathBuf::from).unwrap_or_default(),
+        };
+        let spill = SpillDir::create(temp_dir.join(format!("{file_name}.bulk-sort")))?;
+
+        let mut stats = BulkLoadStats::default();
+        let mut label_interner = LabelInterner::new();
+
+        let mut node_runs = ExternalSorter::new(spill.0.clone(), "nodes", self.run_records);
+        for (seq, node) in nodes.into_iter().enumerate() {
+            check_row(NameKind::Label, &node.label, &node.properties)?;
+            node_runs.push(NodeRow {
+                external_id: node.external_id,
+                seq: seq as u64,
+                label: label_interner.get_or_create(&node.label),
+                properties: node.properties.into_iter().collect(),
+            })?;
+        }
+
+        // Sorted and deduplicated, so an external id's internal id is its
+        // position here. Properties wait in a run file so the IdMap pages
+        // are written first, in one contiguous stretch.
+        let mut external_ids: Vec<ExternalId> = Vec::new();
+        let mut node_labels: Vec<LabelId> = Vec::new();
+        let mut node_properties = RunWriter::create(spill.0.join("node-properties.run"))?;
+        let node_rows = node_runs.finish()?;
+        stats.spilled_runs += node_rows.spilled_runs() as u64;
+        stats.duplicate_nodes = keep_last(
+            node_rows,
+            |row| row.external_id,
+            |row| {
+                external_ids.push(row.external_id);
+                node_labels.push(row.label);
+                node_properties.write(&row)
+            },
+        )?;
+        let node_properties = node_properties.finish()?;
+        stats.nodes = external_ids.len() as u64;
+        if let Some(last) = stats.nodes.checked_sub(1) {
+            internal_node_id(last)?;
+        }
+
+        let internal_id = |external_id: ExternalId, missing: &'static str| {
+            external_ids
+                .binary_search(&external_id)
+                .map(|idx| idx as InternalNodeId)
+                .map_err(|_| Error::WalProtocol(missing))
+        };
+        let mut edge_runs = ExternalSorter::new(spill.0.clone(), "edges", self.run_records);
+        for (seq, edge) in edges.into_iter().enumerate() {
+            check_row(NameKind::RelType, &edge.rel_type, &edge.properties)?;
+            let key = EdgeKey {
+                src: internal_id(
+                    edge.src_external_id,
+                    "Edge src_external_id references non-existent node",
+                )?,
+                rel: label_interner.get_or_create(&edge.rel_type),
+                dst: internal_id(
+                    edge.dst_external_id,
+                    "Edge dst_external_id references non-existent node",
+                )?,
+            };
+            edge_runs.push(EdgeRow {
+                key,
+                seq: seq as u64,
+                properties: edge.properties.into_iter().collect(),
+            })?;
+        }
+
+        let mut pager = crate::pager::Pager::open(&self.db_path)?;
+        let mut graph_stats = crate::stats::GraphStatistics::default();
+        let mut idmap = IdMap::load(&mut pager)?;
+        for (idx, (&external_id, &label)) in external_ids.iter().zip(&node_labels).enumerate() {
+            idmap.apply_create_node(&mut pager, external_id, label, idx as InternalNodeId)?;
+            *graph_stats.node_counts_by_label.entry(label).or_default() += 1;
+        }
+        drop(idmap);
+        drop(node_labels);
+
+        let mut tree = BTree::create(&mut pager)?;
+        for (idx, row) in RunReader::<NodeRow>::open(&node_properties)?.enumerate() {
+            for (key, value) in &row?.properties {
+                let btree_key = node_property_key(idx as InternalNodeId, key);
+                write_property(&mut tree, &mut pager, &btree_key, value)?;
             }
         }
 
-        // Write manifest switch
-        wal.append(&WalRecord::ManifestSwitch {
-            epoch: 0,
-            segments: segment_pointers.to_vec(),
-            properties_root,
-            stats_root,
-        })?;
+        let mut edge_keys: Vec<EdgeKey> = Vec::new();
+        let edge_rows = edge_runs.finish()?;
+        stats.spilled_runs += edge_rows.spilled_runs() as u64;
+        stats.duplicate_edges = keep_last(
+            edge_rows,
+            |row| row.key,
+            |row| {
+                for (key, value) in &row.properties {
+                    let btree_key = edge_property_key(row.key, key);
+                    write_property(&mut tree, &mut pager, &btree_key, value)?;
+                }
+                *graph_stats
+                    .edge_counts_by_type
+                    .entry(row.key.rel)
+                    .or_default() += 1;
+                edge_keys.push(row.key);
+                Ok(())
+            },
+        )?;
+        stats.edges = edge_keys.len() as u64;
+        graph_stats.degrees = crate::stats::degree_stats(edge_keys.iter().copied());
 
-        // Write checkpoint
-        wal.append(&WalRecord::Checkpoint {
-            up_to_txid: 0,
-            epoch: 0,
-            properties_root,
+        let mut segment = csr_segment(&edge_keys);
+        drop(edge_keys);
+        let segment_pointers = vec![persist_segment(&mut pager, &mut segment)?];
+
+        graph_stats.total_nodes = stats.nodes;
+        graph_stats.total_edges = stats.edges;
+        let stats_root =
+            crate::blob_store::BlobStore::write_direct(&mut pager, &graph_stats.encode())?;
+
+        initialize_wal(
+            &self.db_path.with_extension("wal"),
+            &segment_pointers,
+            tree.root().as_u64(),
             stats_root,
-        })?;
+            &label_interner,
+            &EndpointConstraints::new(),
+        )?;
+        Ok(stats)
+    }
+}
+
+/// Passes on the last of each stretch of sorted rows sharing a key and
+/// returns how many rows were dropped.
+fn keep_last<R, K: PartialEq>(
+    rows: impl Iterator<Item = Result<R>>,
+    key: impl Fn(&R) -> K,
+    mut keep: impl FnMut(R) -> Result<()>,
+) -> Result<u64> {
+    let mut dropped = 0;
+    let mut pending: Option<R> = None;
+    for row in rows {
+        let row = row?;
+        if let Some(prev) = pending.replace(row) {
+            if pending.as_ref().map(&key) == Some(key(&prev)) {
+                dropped += 1;
+                continue;
+            }
+            keep(prev)?;
+        }
+    }
+    if let Some(last) = pending {
+        keep(last)?;
+    }
+    Ok(dropped)
+}
 
-        // Commit transaction
-        wal.append(&WalRecord::CommitTx { txid })?;
+/// A node in a sorted run, ordered by external id and then input position.
+struct NodeRow {
+    external_id: ExternalId,
+    seq: u64,
+    label: LabelId,
+    properties: Vec<(String, PropertyValue)>,
+}
 
-        // Fsync WAL for durability
-        wal.fsync()?;
+/// An edge in a sorted run, ordered by CSR key and then input position.
+struct EdgeRow {
+    key: EdgeKey,
+    seq: u64,
+    properties: Vec<(String, PropertyValue)>,
+}
 
-        Ok(())
+impl NodeRow {
+    fn sort_key(&self) -> (ExternalId, u64) {
+        (self.external_id, self.seq)
+    }
+}
+
+impl EdgeRow {
+    fn sort_key(&self) -> (EdgeKey, u64) {
+        (self.key, self.seq)
+    }
+}
+
+macro_rules! order_by_sort_key {
+    ($row:ty) => {
+        impl PartialEq for $row {
+            fn eq(&self, other: &Self) -> bool {
+                self.sort_key() == other.sort_key()
+            }
+        }
+
+        impl Eq for $row {}
+
+        impl PartialOrd for $row {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $row {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.sort_key().cmp(&other.sort_key())
+            }
+        }
+    };
+}
+
+order_by_sort_key!(NodeRow);
+order_by_sort_key!(EdgeRow);
+
+impl crate::external_sort::RunRecord for NodeRow {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.external_id.to_le_bytes());
+        out.extend_from_slice(&self.seq.to_le_bytes());
+        out.extend_from_slice(&self.label.to_le_bytes());
+        encode_run_properties(&self.properties, out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut fields = crate::external_sort::FieldReader::new(bytes);
+        Ok(Self {
+            external_id: fields.u64()?,
+            seq: fields.u64()?,
+            label: fields.u32()?,
+            properties: decode_run_properties(&mut fields)?,
+        })
+    }
+}
+
+impl crate::external_sort::RunRecord for EdgeRow {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.key.src.to_le_bytes());
+        out.extend_from_slice(&self.key.rel.to_le_bytes());
+        out.extend_from_slice(&self.key.dst.to_le_bytes());
+        out.extend_from_slice(&self.seq.to_le_bytes());
+        encode_run_properties(&self.properties, out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut fields = crate::external_sort::FieldReader::new(bytes);
+        Ok(Self {
+            key: EdgeKey {
+                src: fields.u32()?,
+                rel: fields.u32()?,
+                dst: fields.u32()?,
+            },
+            seq: fields.u64()?,
+            properties: decode_run_properties(&mut fields)?,
+        })
     }
 }
 
+fn encode_run_properties(properties: &[(String, PropertyValue)], out: &mut Vec<u8>) {
+    use crate::external_sort::put_bytes;
+
+    out.extend_from_slice(&(properties.len() as u32).to_le_bytes());
+    for (key, value) in properties {
+        put_bytes(out, key.as_bytes());
+        put_bytes(out, &value.encode());
+    }
+}
+
+fn decode_run_properties(
+    fields: &mut crate::external_sort::FieldReader<'_>,
+) -> Result<Vec<(String, PropertyValue)>> {
+    let len = fields.u32()? as usize;
+    let mut properties = Vec::with_capacity(len);
+    for _ in 0..len {
+        let key = fields.string()?;
+        let value = PropertyValue::decode(fields.bytes()?)
+            .map_err(|_| Error::StorageCorrupted("invalid property in sort run"))?;
+        properties.push((key, value));
+    }
+    Ok(properties)
+}
+
+/// Scratch directory for sorted runs, removed when the load ends.
+struct SpillDir(PathBuf);
+
+impl SpillDir {
+    fn create(path: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Checks a row's label or relationship type and properties against the
+/// default [`NameLimits`] and the property size limit.
+fn check_row(
+    kind: NameKind,
+    name: &str,
+    properties: &BTreeMap<String, PropertyValue>,
+) -> Result<()> {
+    let limits = NameLimits::default();
+    limits.check(kind, name)?;
+    for (key, value) in properties {
+        limits.check(NameKind::PropertyKey, key)?;
+        crate::property::check_property_value_size(value)?;
+    }
+    Ok(())
+}
+
+/// Builds a CSR segment from edges sorted by (src, rel, dst).
+fn csr_segment(edges: &[EdgeKey]) -> CsrSegment {
+    use crate::csr::{EdgeRecord, SegmentId};
+
+    let (Some(first), Some(last)) = (edges.first(), edges.last()) else {
+        return CsrSegment {
+            id: SegmentId(0),
+            meta_page_id: 0,
+            min_src: 0,
+            max_src: 0,
+            min_dst: 0,
+            max_dst: 0,
+            offsets: vec![0, 0],
+            edges: Vec::new(),
+            in_offsets: Vec::new(),
+            in_edges: Vec::new(),
+        };
+    };
+    let (min_src, max_src) = (first.src, last.src);
+
+    let mut offsets = vec![0u64; (max_src - min_src) as usize + 2];
+    let mut cursor = 0usize;
+    for src in min_src..=max_src {
+        offsets[(src - min_src) as usize] = cursor as u64;
+        while cursor < edges.len() && edges[cursor].src == src {
+            cursor += 1;
+        }
+    }
+    offsets[(max_src - min_src) as usize + 1] = cursor as u64;
+
+    CsrSegment {
+        id: SegmentId(0),
+        meta_page_id: 0,
+        min_src,
+        max_src,
+        min_dst: 0,
+        max_dst: 0,
+        offsets,
+        edges: edges
+            .iter()
+            .map(|e| EdgeRecord {
+                rel: e.rel,
+                dst: e.dst,
+            })
+            .collect(),
+        in_offsets: Vec::new(),
+        in_edges: Vec::new(),
+    }
+}
+
+fn persist_segment(
+    pager: &mut crate::pager::Pager,
+    segment: &mut CsrSegment,
+) -> Result<SegmentPointer> {
+    segment.persist(pager)?;
+    Ok(SegmentPointer {
+        id: segment.id.0,
+        meta_page_id: segment.meta_page_id,
+    })
+}
+
+fn node_property_key(internal_id: InternalNodeId, key: &str) -> Vec<u8> {
+    let mut btree_key = Vec::with_capacity(1 + 4 + 4 + key.len());
+    btree_key.push(0u8); // Tag 0: Node Property
+    btree_key.extend_from_slice(&internal_id.to_be_bytes());
+    btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    btree_key.extend_from_slice(key.as_bytes());
+    btree_key
+}
+
+fn edge_property_key(edge: EdgeKey, key: &str) -> Vec<u8> {
+    let mut btree_key = Vec::with_capacity(1 + 4 + 4 + 4 + 4 + key.len());
+    btree_key.push(1u8); // Tag 1: Edge Property
+    btree_key.extend_from_slice(&edge.src.to_be_bytes());
+    btree_key.extend_from_slice(&edge.rel.to_be_bytes());
+    btree_key.extend_from_slice(&edge.dst.to_be_bytes());
+    btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    btree_key.extend_from_slice(key.as_bytes());
+    btree_key
+}
+
+fn write_property(
+    tree: &mut crate::index::btree::BTree,
+    pager: &mut crate::pager::Pager,
+    btree_key: &[u8],
+    value: &PropertyValue,
+) -> Result<()> {
+    let encoded_val = crate::property::encode_stored_value(value);
+    let blob_id = crate::blob_store::BlobStore::write_direct(pager, &encoded_val)?;
+    tree.insert(pager, btree_key, blob_id)?;
+    Ok(())
+}
+
+/// Initializes WAL with manifest, label definitions and endpoint constraints.
+fn initialize_wal(
+    wal_path: &std::path::Path,
+    segment_pointers: &[SegmentPointer],
+    properties_root: u64,
+    stats_root: u64,
+    label_interner: &LabelInterner,
+    endpoint_constraints: &EndpointConstraints,
+) -> Result<()> {
+    use crate::wal::{Wal, WalRecord};
+
+    let mut wal = Wal::open(wal_path)?;
+
+    let txid = 0; // First transaction
+
+    // Begin transaction
+    wal.append(&WalRecord::BeginTx { txid })?;
+
+    // Write label definitions using snapshot's iter_ids
+    let snapshot = label_interner.snapshot();
+    for id in snapshot.iter_ids() {
+        if let Some(name) = snapshot.get_name(id) {
+            wal.append(&WalRecord::CreateLabel {
+                name: name.to_string(),
+                label_id: id,
+            })?;
+        }
+    }
+
+    for (rel_type, endpoints) in endpoint_constraints {
+        wal.append(&WalRecord::SetEndpoints {
+            rel_type: rel_type.clone(),
+            endpoints: endpoints.iter().cloned().collect(),
+        })?;
+    }
+
+    // Write manifest switch
+    wal.append(&WalRecord::ManifestSwitch {
+        epoch: 0,
+        segments: segment_pointers.to_vec(),
+        properties_root,
+        stats_root,
+    })?;
+
+    // Write checkpoint
+    wal.append(&WalRecord::Checkpoint {
+        up_to_txid: 0,
+        epoch: 0,
+        properties_root,
+        stats_root,
+    })?;
+
+    // Commit transaction
+    wal.append(&WalRecord::CommitTx { txid })?;
+
+    // Fsync WAL for durability
+    wal.fsync()?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -618,4 +1240,109 @@ mod tests {
             assert_eq!(engine.get_label_name(1), Some("KNOWS".to_string()));
         }
     }
+
+    #[test]
+    fn test_bulkloader_unique_constraints() {
+        use crate::engine::GraphEngine;
+
+        let user = |external_id, email: &str| BulkNode {
+            external_id,
+            label: "User".to_string(),
+            properties: BTreeMap::from([(
+                "email".to_string(),
+                PropertyValue::String(email.to_string()),
+            )]),
+        };
+        let dir = tempdir().unwrap();
+
+        let mut loader = BulkLoader::new(dir.path().join("dup.ndb")).unwrap();
+        loader.add_unique_constraint("User", "email").unwrap();
+        loader.add_node(user(1, "a@x")).unwrap();
+        loader.add_node(user(2, "a@x")).unwrap();
+        assert!(matches!(
+            loader.commit(),
+            Err(Error::ConstraintViolation(_))
+        ));
+
+        let ndb = dir.path().join("ok.ndb");
+        let mut loader = BulkLoader::new(ndb.clone()).unwrap();
+        loader.add_unique_constraint("User", "email").unwrap();
+        loader.add_node(user(1, "a@x")).unwrap();
+        loader.add_node(user(2, "b@x")).unwrap();
+        loader.commit().unwrap();
+
+        let engine = GraphEngine::open(&ndb, dir.path().join("ok.wal")).unwrap();
+        assert_eq!(
+            engine.unique_constraints(),
+            vec![("User".to_string(), "email".to_string())]
+        );
+        let node = engine.lookup_internal_id(2).unwrap();
+        let mut txn = engine.begin_write();
+        assert!(matches!(
+            txn.set_node_property(
+                node,
+                "email".to_string(),
+                PropertyValue::String("a@x".to_string())
+            ),
+            Err(Error::ConstraintViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_bulkloader_endpoint_constraints() {
+        use crate::engine::GraphEngine;
+
+        let node = |external_id, label: &str| BulkNode {
+            external_id,
+            label: label.to_string(),
+            properties: BTreeMap::new(),
+        };
+        let knows = |src, dst| BulkEdge {
+            src_external_id: src,
+            rel_type: "KNOWS".to_string(),
+            dst_external_id: dst,
+            properties: BTreeMap::new(),
+        };
+        let dir = tempdir().unwrap();
+
+        let mut loader = BulkLoader::new(dir.path().join("bad.ndb")).unwrap();
+        loader
+            .add_endpoint_constraint("KNOWS", "Person", "Person")
+            .unwrap();
+        loader.add_node(node(1, "Person")).unwrap();
+        loader.add_node(node(2, "City")).unwrap();
+        loader.add_edge(knows(1, 2)).unwrap();
+        assert!(matches!(
+            loader.commit(),
+            Err(Error::ConstraintViolation(_))
+        ));
+
+        let ndb = dir.path().join("ok.ndb");
+        let mut loader = BulkLoader::new(ndb.clone()).unwrap();
+        loader
+            .add_endpoint_constraint("KNOWS", "Person", "Person")
+            .unwrap();
+        loader.add_node(node(1, "Person")).unwrap();
+        loader.add_node(node(2, "Person")).unwrap();
+        loader.add_node(node(3, "City")).unwrap();
+        loader.add_edge(knows(1, 2)).unwrap();
+        loader.commit().unwrap();
+
+        let engine = GraphEngine::open(&ndb, dir.path().join("ok.wal")).unwrap();
+        assert_eq!(
+            engine.endpoint_constraints().get("KNOWS"),
+            Some(&std::collections::BTreeSet::from([(
+                "Person".to_string(),
+                "Person".to_string()
+            )]))
+        );
+        let (person, city) = (
+            engine.lookup_internal_id(1).unwrap(),
+            engine.lookup_internal_id(3).unwrap(),
+        );
+        let knows = engine.get_label_id("KNOWS").unwrap();
+        let mut txn = engine.begin_write();
+        txn.create_edge(person, knows, city);
+        assert!(matches!(txn.commit(), Err(Error::ConstraintViolation(_))));
+    }
 }
@@ -0,0 +1,198 @@
+//! Background thread that checkpoints a [`GraphEngine`] on its own.
+//!
+//! Without it the WAL only shrinks when [`GraphEngine::checkpoint`] is called
+//! or the database is closed. A [`Checkpointer`] checks the engine every
+//! [`POLL_INTERVAL`] and checkpoints once the configured time has passed or
+//! the WAL has grown past the configured size. It holds the engine weakly
+//! and stops when dropped or when the engine goes away.
+//!
+//! [`GraphEngine`]: crate::engine::GraphEngine
+//! [`GraphEngine::checkpoint`]: crate::engine::GraphEngine::checkpoint
+
+use crate::engine::GraphEngine;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How often the thread looks at the WAL size; shorter intervals win.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// When a [`Checkpointer`] checkpoints. It does so when either trigger fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoCheckpoint {
+    /// Checkpoint this long after the previous one, if anything was written
+    /// since. `None` disables the timer.
+    pub interval: Option<Duration>,
+    /// Checkpoint once the WAL, sealed segments included, reaches this many
+    /// bytes. `0` disables the size trigger.
+    pub wal_bytes: u64,
+    /// Caps the average checkpoint IO: after checkpointing a WAL of `n`
+    /// bytes, the next checkpoint waits at least `n / io_bytes_per_sec`
+    /// seconds, whatever the triggers say. `0` means unthrottled.
+    pub io_bytes_per_sec: u64,
+}
+
+impl Default for AutoCheckpoint {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_secs(60)),
+            wal_bytes: 64 * 1024 * 1024,
+            io_bytes_per_sec: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    paused: bool,
+    stop: bool,
+    /// A checkpoint is in progress on the thread.
+    running: bool,
+    checkpoints: u64,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+/// Handle to the background checkpoint thread. Dropping it stops the thread,
+/// waiting for a checkpoint in progress to finish.
+#[derive(Debug)]
+pub struct Checkpointer {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Checkpointer {
+    pub fn spawn(engine: Weak<GraphEngine>, policy: AutoCheckpoint) -> Self {
+        let shared = Arc::new(Shared::default());
+        // Measured here rather than on the thread, so that commits made
+        // before the thread first runs count towards the triggers.
+        let baseline = engine
+            .upgrade()
+            .map_or(0, |engine| engine.wal_bytes().unwrap_or(0));
+        let thread = {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("nervusdb-checkpoint".into())
+                .spawn(move || run(&engine, policy, baseline, &shared))
+                .expect("spawn checkpoint thread")
+        };
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops new background checkpoints and waits for one in progress to
+    /// finish, so the data and WAL files stay put until [`Self::resume`].
+    /// Explicit checkpoints are not affected.
+    pub fn pause(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.paused = true;
+        while state.running {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+    }
+
+    pub fn resume(&self) {
+        self.shared.state.lock().unwrap().paused = false;
+        self.shared.changed.notify_all();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.state.lock().unwrap().paused
+    }
+
+    /// Number of checkpoints the thread has completed.
+    pub fn checkpoints(&self) -> u64 {
+        self.shared.state.lock().unwrap().checkpoints
+    }
+}
+
+impl Drop for Checkpointer {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().stop = true;
+        self.shared.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// `baseline` is the WAL size right after the last checkpoint; neither
+/// trigger fires until something has been written on top of it.
+fn run(engine: &Weak<GraphEngine>, policy: AutoCheckpoint, mut baseline: u64, shared: &Shared) {
+    let poll = policy
+        .interval
+        .map_or(POLL_INTERVAL, |interval| interval.min(POLL_INTERVAL));
+    let mut last = Instant::now();
+    let mut not_before = last;
+
+    loop {
+        {
+            let state = shared.state.lock().unwrap();
+            let (state, _) = shared.changed.wait_timeout(state, poll).unwrap();
+            if state.stop {
+                return;
+            }
+            if state.paused {
+                continue;
+            }
+        }
+        let Some(engine) = engine.upgrade() else {
+            return;
+        };
+        let now = Instant::now();
+        if now < not_before {
+            continue;
+        }
+        // A failed stat or checkpoint leaves the WAL as it was; try again
+        // on the next poll.
+        let Ok(wal_bytes) = engine.wal_bytes() else {
+            continue;
+        };
+        if wal_bytes <= baseline {
+            continue;
+        }
+        let by_size = policy.wal_bytes > 0 && wal_bytes >= policy.wal_bytes;
+        let by_time = policy
+            .interval
+            .is_some_and(|interval| now.duration_since(last) >= interval);
+        if !by_size && !by_time {
+            continue;
+        }
+
+        {
+            let mut state = shared.state.lock().unwrap();
+            if state.stop || state.paused {
+                continue;
+            }
+            state.running = true;
+        }
+        let done = engine.checkpoint().is_ok();
+        {
+            let mut state = shared.state.lock().unwrap();
+            state.running = false;
+            if done {
+                state.checkpoints += 1;
+            }
+        }
+        shared.changed.notify_all();
+        // A failed checkpoint wrote nothing to throttle; retry on the next poll.
+        if !done {
+            continue;
+        }
+
+        last = Instant::now();
+        if let Ok(after) = engine.wal_bytes() {
+            baseline = after;
+        }
+        if policy.io_bytes_per_sec > 0 {
+            not_before =
+                last + Duration::from_secs_f64(wal_bytes as f64 / policy.io_bytes_per_sec as f64);
+        }
+    }
+}
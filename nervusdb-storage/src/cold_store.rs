@@ -0,0 +1,364 @@
+//! Cold storage tier for the properties of archival nodes.
+//!
+//! Labels can be moved to the cold tier with
+//! [`GraphEngine::set_label_tier`](crate::engine::GraphEngine::set_label_tier).
+//! Compaction then takes the properties of nodes carrying such a label out
+//! of the page store and appends them, one LZ4-compressed record per node,
+//! to a file beside the database (`<name>.cold`). Reads of those properties
+//! go straight to that file and bypass the page cache, so archival data
+//! neither fills the cache nor inflates the main file.
+//!
+//! Record layout, little-endian:
+//! `[node: u32][payload_len: u32][crc32: u32][payload]`, where the payload is
+//! the node's property map encoded as a [`PropertyValue::Map`] and
+//! compressed. The newest record of a node wins. A torn record left at the
+//! end by a crash is cut off on open, and the file is rewritten without
+//! superseded records once they take more room than the live ones.
+
+use crate::idmap::InternalNodeId;
+use crate::property::PropertyValue;
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const RECORD_HEADER_BYTES: u64 = 12;
+
+/// Which tier keeps the properties of a label's nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageTier {
+    /// The page store, read through the page cache.
+    #[default]
+    Hot,
+    /// The compressed cold file, read without caching.
+    Cold,
+}
+
+/// Size of the cold tier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColdTierStats {
+    /// Nodes whose properties are kept in the cold file.
+    pub nodes: u64,
+    /// Bytes of the cold file, superseded records included.
+    pub file_bytes: u64,
+    /// Bytes of the records still in use.
+    pub live_bytes: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct ColdStore {
+    path: PathBuf,
+    read_only: bool,
+    inner: Mutex<ColdInner>,
+}
+
+#[derive(Debug, Default)]
+struct ColdInner {
+    /// Opened on first use; absent while the file does not exist.
+    file: Option<File>,
+    /// Offset of each node's newest record and the record's length.
+    index: HashMap<InternalNodeId, (u64, u64)>,
+    len: u64,
+    live_bytes: u64,
+}
+
+impl ColdStore {
+    /// Opens the cold file at `path` if there is one, indexing its records.
+    pub(crate) fn open(path: PathBuf, read_only: bool) -> Result<Self> {
+        let mut inner = ColdInner::default();
+        if path.exists() {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(!read_only)
+                .open(&path)?;
+            let file_len = file.metadata()?.len();
+            let mut offset = 0u64;
+            let mut header = [0u8; RECORD_HEADER_BYTES as usize];
+            while offset + RECORD_HEADER_BYTES <= file_len {
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut header)?;
+                let node = u32::from_le_bytes(header[0..4].try_into().unwrap());
+                let payload_len = u64::from(u32::from_le_bytes(header[4..8].try_into().unwrap()));
+                let record_len = RECORD_HEADER_BYTES + payload_len;
+                if offset + record_len > file_len {
+                    break;
+                }
+                if let Some((_, old_len)) = inner.index.insert(node, (offset, record_len)) {
+                    inner.live_bytes -= old_len;
+                }
+                inner.live_bytes += record_len;
+                offset += record_len;
+            }
+            if offset < file_len && !read_only {
+                file.set_len(offset)?;
+                file.sync_all()?;
+            }
+            inner.len = offset;
+            inner.file = Some(file);
+        }
+        Ok(Self {
+            path,
+            read_only,
+            inner: Mutex::new(inner),
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().index.is_empty()
+    }
+
+    /// Nodes with a record, in no particular order.
+    pub(crate) fn nodes(&self) -> Vec<InternalNodeId> {
+        self.inner.lock().unwrap().index.keys().copied().collect()
+    }
+
+    pub(crate) fn stats(&self) -> ColdTierStats {
+        let inner = self.inner.lock().unwrap();
+        ColdTierStats {
+            nodes: inner.index.len() as u64,
+            file_bytes: inner.len,
+            live_bytes: inner.live_bytes,
+        }
+    }
+
+    /// The properties kept for `node`, if it has a record.
+    pub(crate) fn get(
+        &self,
+        node: InternalNodeId,
+    ) -> Result<Option<BTreeMap<String, PropertyValue>>> {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(&(offset, record_len)) = inner.index.get(&node) else {
+            return Ok(None);
+        };
+        let file = inner
+            .file
+            .as_mut()
+            .ok_or(Error::StorageCorrupted("cold tier file missing"))?;
+        let mut record = vec![0u8; record_len as usize];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut record)?;
+        decode_record(&record).map(Some)
+    }
+
+    /// Appends a record for each `(node, properties)` pair and syncs the
+    /// file, replacing the nodes' earlier records.
+    pub(crate) fn put(
+        &self,
+        records: &[(InternalNodeId, BTreeMap<String, PropertyValue>)],
+    ) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.file.is_none() {
+            inner.file = Some(
+                OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(&self.path)?,
+            );
+        }
+        let start = inner.len;
+        let mut encoded = Vec::with_capacity(records.len());
+        {
+            let file = inner.file.as_mut().unwrap();
+            file.seek(SeekFrom::Start(start))?;
+            let mut out = BufWriter::new(&mut *file);
+            let mut offset = start;
+            for (node, props) in records {
+                let record = encode_record(*node, props)?;
+                out.write_all(&record)?;
+                encoded.push((*node, offset, record.len() as u64));
+                offset += record.len() as u64;
+            }
+            out.flush()?;
+            drop(out);
+            file.sync_data()?;
+        }
+        for (node, offset, record_len) in encoded {
+            if let Some((_, old_len)) = inner.index.insert(node, (offset, record_len)) {
+                inner.live_bytes -= old_len;
+            }
+            inner.live_bytes += record_len;
+            inner.len = offset + record_len;
+        }
+        Ok(())
+    }
+
+    /// Drops `node`'s record from the index once its properties are back in
+    /// the page store. The bytes are reclaimed by [`ColdStore::rewrite_if_sparse`].
+    pub(crate) fn forget(&self, node: InternalNodeId) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some((_, record_len)) = inner.index.remove(&node) {
+            inner.live_bytes -= record_len;
+        }
+    }
+
+    /// Rewrites the file with only its live records once superseded ones
+    /// take more room than they do. An emptied file is deleted.
+    pub(crate) fn rewrite_if_sparse(&self) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.len - inner.live_bytes <= inner.live_bytes {
+            return Ok(());
+        }
+        if inner.index.is_empty() {
+            inner.file = None;
+            inner.len = 0;
+            std::fs::remove_file(&self.path)?;
+            return Ok(());
+        }
+
+        let mut live: Vec<(InternalNodeId, u64, u64)> = inner
+            .index
+            .iter()
+            .map(|(&node, &(offset, record_len))| (node, offset, record_len))
+            .collect();
+        live.sort_unstable_by_key(|&(_, offset, _)| offset);
+
+        let tmp_path = rewrite_path(&self.path);
+        let mut index = HashMap::with_capacity(live.len());
+        let mut written = 0u64;
+        {
+            let src = inner.file.as_mut().unwrap();
+            let mut out = BufWriter::new(File::create(&tmp_path)?);
+            let mut record = Vec::new();
+            for (node, offset, record_len) in live {
+                record.resize(record_len as usize, 0);
+                src.seek(SeekFrom::Start(offset))?;
+                src.read_exact(&mut record)?;
+                out.write_all(&record)?;
+                index.insert(node, (written, record_len));
+                written += record_len;
+            }
+            out.into_inner()
+                .map_err(|err| Error::Io(err.into_error()))?
+                .sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+        inner.file = Some(OpenOptions::new().read(true).write(true).open(&self.path)?);
+        inner.index = index;
+        inner.len = written;
+        inner.live_bytes = written;
+        Ok(())
+    }
+}
+
+/// Path of the cold file kept beside the database at `ndb_path`.
+pub(crate) fn cold_path(ndb_path: &Path) -> PathBuf {
+    ndb_path.with_extension("cold")
+}
+
+fn rewrite_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+fn encode_record(node: InternalNodeId, props: &BTreeMap<String, PropertyValue>) -> Result<Vec<u8>> {
+    let encoded = PropertyValue::Map(props.clone()).encode();
+    let payload = lz4_flex::compress_prepend_size(&encoded);
+    let payload_len = u32::try_from(payload.len()).map_err(|_| Error::PropertyValueTooLarge {
+        len: payload.len(),
+        max: u32::MAX as usize,
+    })?;
+    let mut record = Vec::with_capacity(RECORD_HEADER_BYTES as usize + payload.len());
+    record.extend_from_slice(&node.to_le_bytes());
+    record.extend_from_slice(&payload_len.to_le_bytes());
+    record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+fn decode_record(record: &[u8]) -> Result<BTreeMap<String, PropertyValue>> {
+    let crc = u32::from_le_bytes(record[8..12].try_into().unwrap());
+    let payload = &record[RECORD_HEADER_BYTES as usize..];
+    if crc32fast::hash(payload) != crc {
+        return Err(Error::StorageCorrupted(
+            "cold tier record checksum mismatch",
+        ));
+    }
+    let encoded = lz4_flex::decompress_size_prepended(payload)
+        .map_err(|_| Error::StorageCorrupted("invalid cold tier record"))?;
+    match PropertyValue::decode(&encoded) {
+        Ok(PropertyValue::Map(props)) => Ok(props),
+        _ => Err(Error::StorageCorrupted("invalid cold tier record")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColdStore, PropertyValue};
+    use std::collections::BTreeMap;
+
+    fn props(value: i64) -> BTreeMap<String, PropertyValue> {
+        BTreeMap::from([("v".to_string(), PropertyValue::Int(value))])
+    }
+
+    #[test]
+    fn newest_record_wins_and_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.cold");
+        let store = ColdStore::open(path.clone(), false).unwrap();
+        store.put(&[(1, props(1)), (2, props(2))]).unwrap();
+        store.put(&[(1, props(10))]).unwrap();
+        assert_eq!(store.get(1).unwrap(), Some(props(10)));
+        drop(store);
+
+        let store = ColdStore::open(path, false).unwrap();
+        assert_eq!(store.get(1).unwrap(), Some(props(10)));
+        assert_eq!(store.get(2).unwrap(), Some(props(2)));
+        assert_eq!(store.get(3).unwrap(), None);
+        assert_eq!(store.stats().nodes, 2);
+    }
+
+    #[test]
+    fn torn_tail_is_cut_off_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.cold");
+        let store = ColdStore::open(path.clone(), false).unwrap();
+        store.put(&[(1, props(1))]).unwrap();
+        let good_len = store.stats().file_bytes;
+        store.put(&[(2, props(2))]).unwrap();
+        drop(store);
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(good_len + 5).unwrap();
+        drop(file);
+
+        let store = ColdStore::open(path.clone(), false).unwrap();
+        assert_eq!(store.get(1).unwrap(), Some(props(1)));
+        assert_eq!(store.get(2).unwrap(), None);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), good_len);
+    }
+
+    #[test]
+    fn sparse_file_is_rewritten_with_live_records_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.cold");
+        let store = ColdStore::open(path.clone(), false).unwrap();
+        store
+            .put(&[(1, props(1)), (2, props(2)), (3, props(3))])
+            .unwrap();
+        store.forget(1);
+        store.forget(2);
+        store.rewrite_if_sparse().unwrap();
+        let stats = store.stats();
+        assert_eq!(stats.file_bytes, stats.live_bytes);
+        assert_eq!(store.nodes(), vec![3]);
+        assert_eq!(store.get(3).unwrap(), Some(props(3)));
+
+        store.forget(3);
+        store.rewrite_if_sparse().unwrap();
+        assert!(store.is_empty());
+        assert!(!path.exists());
+    }
+}
@@ -79,6 +79,22 @@ impl CsrSegment {
         )
     }
 
+    /// Every edge in the segment, ordered by source.
+    pub fn edge_keys(&self) -> impl Iterator<Item = EdgeKey> + '_ {
+        self.offsets
+            .windows(2)
+            .zip(self.min_src..)
+            .flat_map(move |(range, src)| {
+                self.edges[range[0] as usize..range[1] as usize]
+                    .iter()
+                    .map(move |e| EdgeKey {
+                        src,
+                        rel: e.rel,
+                        dst: e.dst,
+                    })
+            })
+    }
+
     pub fn load(pager: &mut Pager, meta_page_id: u64) -> Result<Self> {
         let page = pager.read_page(PageId::new(meta_page_id))?;
         let mut seg = decode_segment(&page, pager)?;
@@ -252,11 +268,45 @@ fn encode_meta(
     Ok(())
 }
 
-fn decode_segment(meta_page: &[u8; PAGE_SIZE], pager: &mut Pager) -> Result<CsrSegment> {
+/// Data pages of the segment whose meta page is `meta_page`: out-offsets,
+/// out-edges, in-offsets and in-edges, in that order.
+fn decode_page_lists(meta_page: &[u8; PAGE_SIZE]) -> Result<[Vec<u64>; 4]> {
     if meta_page[0..8] != META_MAGIC {
         return Err(Error::WalProtocol("invalid csr meta magic"));
     }
+    let counts: [usize; 4] = std::array::from_fn(|i| {
+        let at = 64 + i * 4;
+        u32::from_le_bytes(meta_page[at..at + 4].try_into().unwrap()) as usize
+    });
+    if 80usize + counts.iter().sum::<usize>() * 8 > PAGE_SIZE {
+        return Err(Error::WalProtocol("csr meta page overflow"));
+    }
 
+    let mut offset = 80;
+    Ok(counts.map(|count| {
+        let pages = (0..count)
+            .map(|i| {
+                let at = offset + i * 8;
+                u64::from_le_bytes(meta_page[at..at + 8].try_into().unwrap())
+            })
+            .collect();
+        offset += count * 8;
+        pages
+    }))
+}
+
+/// Every page a segment occupies, its meta page included, for vacuum and
+/// packing to keep.
+pub(crate) fn segment_page_ids(pager: &Pager, meta_page_id: PageId) -> Result<Vec<u64>> {
+    let meta = pager.read_page(meta_page_id)?;
+    let mut pages = vec![meta_page_id.as_u64()];
+    pages.extend(decode_page_lists(&meta)?.into_iter().flatten());
+    Ok(pages)
+}
+
+fn decode_segment(meta_page: &[u8; PAGE_SIZE], pager: &mut Pager) -> Result<CsrSegment> {
+    let [offsets_pages, edges_pages, in_offsets_pages, in_edges_pages] =
+        decode_page_lists(meta_page)?;
     let id = u64::from_le_bytes(meta_page[8..16].try_into().unwrap());
     let min_src = u32::from_le_bytes(meta_page[16..20].try_into().unwrap());
     let max_src = u32::from_le_bytes(meta_page[20..24].try_into().unwrap());
@@ -266,46 +316,6 @@ fn decode_segment(meta_page: &[u8; PAGE_SIZE], pager: &mut Pager) -> Result<CsrS
     let edges_len = u64::from_le_bytes(meta_page[40..48].try_into().unwrap()) as usize;
     let in_offsets_len = u64::from_le_bytes(meta_page[48..56].try_into().unwrap()) as usize;
     let in_edges_len = u64::from_le_bytes(meta_page[56..64].try_into().unwrap()) as usize;
-    let offsets_page_count = u32::from_le_bytes(meta_page[64..68].try_into().unwrap()) as usize;
-    let edges_page_count = u32::from_le_bytes(meta_page[68..72].try_into().unwrap()) as usize;
-    let in_offsets_page_count = u32::from_le_bytes(meta_page[72..76].try_into().unwrap()) as usize;
-    let in_edges_page_count = u32::from_le_bytes(meta_page[76..80].try_into().unwrap()) as usize;
-
-    let needed = 80usize
-        + (offsets_page_count + edges_page_count + in_offsets_page_count + in_edges_page_count) * 8;
-    if needed > PAGE_SIZE {
-        return Err(Error::WalProtocol("csr meta page overflow"));
-    }
-
-    let mut offset = 80;
-    let mut offsets_pages = Vec::with_capacity(offsets_page_count);
-    for _ in 0..offsets_page_count {
-        offsets_pages.push(u64::from_le_bytes(
-            meta_page[offset..offset + 8].try_into().unwrap(),
-        ));
-        offset += 8;
-    }
-    let mut edges_pages = Vec::with_capacity(edges_page_count);
-    for _ in 0..edges_page_count {
-        edges_pages.push(u64::from_le_bytes(
-            meta_page[offset..offset + 8].try_into().unwrap(),
-        ));
-        offset += 8;
-    }
-    let mut in_offsets_pages = Vec::with_capacity(in_offsets_page_count);
-    for _ in 0..in_offsets_page_count {
-        in_offsets_pages.push(u64::from_le_bytes(
-            meta_page[offset..offset + 8].try_into().unwrap(),
-        ));
-        offset += 8;
-    }
-    let mut in_edges_pages = Vec::with_capacity(in_edges_page_count);
-    for _ in 0..in_edges_page_count {
-        in_edges_pages.push(u64::from_le_bytes(
-            meta_page[offset..offset + 8].try_into().unwrap(),
-        ));
-        offset += 8;
-    }
 
     let offsets_bytes = read_blob_pages(pager, &offsets_pages)?;
     let edges_bytes = read_blob_pages(pager, &edges_pages)?;
@@ -1,13 +1,26 @@
+use crate::backend::{FileBackend, OverlayBackend, ReadOnlyBackend, StorageBackend};
+use crate::checkpointer::AutoCheckpoint;
+use crate::cold_store::{self, ColdStore, ColdTierStats, StorageTier};
 use crate::csr::{CsrSegment, EdgeRecord, SegmentId};
-use crate::idmap::{ExternalId, I2eRecord, IdMap, InternalNodeId, LabelId};
+use crate::idmap::{ExternalId, I2eRecord, IdMap, InternalNodeId, LabelId, internal_node_id};
 use crate::index::btree::BTree;
-use crate::index::catalog::IndexCatalog;
+use crate::index::catalog::{CompositeKey, IndexCatalog, Rename};
+use crate::index::composite::{
+    composite_index_name, indexable_tuple, lookup_prefix, update_composite,
+};
 use crate::index::hnsw::HnswIndex;
 use crate::index::hnsw::params::HnswParams;
 use crate::index::hnsw::storage::{PersistentGraphStorage, PersistentVectorStorage};
 use crate::index::ordered_key::encode_ordered_value;
+use crate::index::text::{text_index_name, update_text};
+use crate::index::unique::{UniqueClaims, unique_constraint_name};
+use crate::index::vector::VectorMetric;
 use crate::label_interner::{LabelInterner, LabelSnapshot};
 use crate::memtable::MemTable;
+use crate::names::{NameKind, NameLimits};
+use crate::operation::{OperationHandle, Tracker};
+use crate::packed::{PackReport, PackedImage};
+use crate::page_cache::{DEFAULT_PAGE_CACHE_PAGES, PageCacheStats};
 use crate::pager::{PageId, Pager};
 use crate::read_path_engine_idmap::{
     lookup_internal_node_id, read_i2e_snapshot, read_i2l_snapshot,
@@ -18,16 +31,22 @@ use crate::read_path_engine_labels::{
 use crate::read_path_engine_view::{
     build_snapshot_from_published, load_properties_and_stats_roots,
 };
+use crate::schema::SchemaCatalog;
 use crate::snapshot::{L0Run, RelTypeId, Snapshot};
-use crate::wal::{CommittedTx, SegmentPointer, Wal, WalRecord};
+use crate::wal::{CommittedTx, DEFAULT_WAL_SEGMENT_BYTES, SegmentPointer, Wal, WalRecord};
+use crate::wal_archive::{self, ArchivedSegment, RetentionPolicy};
+use crate::wal_tail::{self, WalTail, WalTxn};
+use crate::write_stamps::{Touched, WriteStamps};
+use crate::write_throttle::WriteThrottle;
 use crate::{Error, Result};
 use nervusdb_api::{GraphSnapshot, GraphStore};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
-type NativeHnsw = HnswIndex<PersistentVectorStorage, PersistentGraphStorage>;
+pub(crate) type NativeHnsw = HnswIndex<PersistentVectorStorage, PersistentGraphStorage>;
 
 fn parse_hnsw_env_usize(name: &str, default_value: usize) -> usize {
     std::env::var(name)
@@ -45,6 +64,66 @@ fn load_hnsw_params_from_env() -> HnswParams {
     }
 }
 
+/// How many candidates per requested result a filtered L2 search pulls from
+/// the HNSW index before falling back to a full scan.
+const FILTERED_SEARCH_OVERFETCH: usize = 8;
+
+/// Tunables applied when opening a [`GraphEngine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineOptions {
+    /// Size in bytes at which the active WAL file is sealed into a numbered
+    /// segment. `0` disables rotation.
+    pub wal_segment_bytes: u64,
+    /// HNSW vector index tunables. `m` and `ef_construction` shape vectors
+    /// inserted from now on; `ef_search` applies to every search. Defaults
+    /// come from `NERVUSDB_HNSW_*` environment variables when set.
+    pub hnsw: HnswParams,
+    /// Answer [`GraphEngine::search_vector`] by brute force instead of the
+    /// HNSW index: exact results at linear cost.
+    pub exact_vector_search: bool,
+    /// Data pages kept in the in-memory page cache. `0` disables it.
+    pub page_cache_pages: usize,
+    /// Checkpoint automatically from a background thread. `None` (the
+    /// default) leaves checkpointing to [`GraphEngine::checkpoint`]. The
+    /// thread is run by the database handle, see
+    /// [`Checkpointer`](crate::checkpointer::Checkpointer).
+    pub auto_checkpoint: Option<AutoCheckpoint>,
+    /// Limits on new label, relationship type and property key names.
+    pub name_limits: NameLimits,
+    /// Stamp [`CREATED_TX`](crate::names::CREATED_TX),
+    /// [`UPDATED_TX`](crate::names::UPDATED_TX) and
+    /// [`UPDATED_AT`](crate::names::UPDATED_AT) on every node and
+    /// relationship a transaction creates or changes.
+    pub system_properties: bool,
+    /// Slow down or refuse writers while the WAL is backlogged. `None` (the
+    /// default) lets it grow; see [`GraphEngine::set_write_throttle`].
+    pub write_throttle: Option<WriteThrottle>,
+    /// Refuse writes, checkpoints and schema changes, like a packed image:
+    /// they fail with [`Error::ReadOnly`]. The database must already exist;
+    /// it is recovered in memory, and its files are never written.
+    pub read_only: bool,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            wal_segment_bytes: DEFAULT_WAL_SEGMENT_BYTES,
+            hnsw: load_hnsw_params_from_env(),
+            exact_vector_search: false,
+            page_cache_pages: DEFAULT_PAGE_CACHE_PAGES,
+            auto_checkpoint: None,
+            name_limits: NameLimits::default(),
+            system_properties: false,
+            write_throttle: None,
+            read_only: false,
+        }
+    }
+}
+
+/// Allowed `(source label, target label)` pairs per constrained relationship
+/// type.
+pub type EndpointConstraints = BTreeMap<String, BTreeSet<(String, String)>>;
+
 #[derive(Debug)]
 pub struct GraphEngine {
     ndb_path: PathBuf,
@@ -58,13 +137,42 @@ pub struct GraphEngine {
 
     // T203: Vector Search Index
     vector_index: Arc<Mutex<NativeHnsw>>,
+    exact_vector_search: bool,
+    name_limits: NameLimits,
+    system_properties: bool,
+    /// Opened from a packed image: commits and checkpoints are refused.
+    read_only: bool,
 
     published_runs: RwLock<Arc<Vec<Arc<L0Run>>>>,
     published_segments: RwLock<Arc<Vec<Arc<CsrSegment>>>>,
     published_labels: RwLock<Arc<LabelSnapshot>>,
     published_node_labels: RwLock<Arc<Vec<Vec<LabelId>>>>,
+    published_named_queries: RwLock<Arc<BTreeMap<String, String>>>,
+    /// Committed idempotency keys and the txid each was committed under.
+    idempotency_keys: RwLock<BTreeMap<String, u64>>,
+    /// `(label, property)` pairs marked sensitive, redacted for sessions
+    /// without access to them.
+    published_sensitive: RwLock<Arc<BTreeSet<(String, String)>>>,
+    published_endpoints: RwLock<Arc<EndpointConstraints>>,
+    /// Retention period and legal holds for checkpointed WAL history.
+    published_retention: RwLock<Arc<RetentionPolicy>>,
+    /// Labels whose nodes keep their properties in `cold_store`.
+    published_cold_labels: RwLock<Arc<BTreeSet<String>>>,
+    /// Namespace prefixes and the base IRIs they are bound to.
+    published_namespaces: RwLock<Arc<BTreeMap<String, String>>>,
+    cold_store: Arc<ColdStore>,
+    /// The next compaction checks every node with a cold label, not just
+    /// those it sinks properties for.
+    tier_pass_pending: AtomicBool,
+    published_schema: RwLock<Arc<SchemaCatalog>>,
+    /// Which labels and relationship types each commit touched.
+    write_stamps: Mutex<WriteStamps>,
+    write_throttle: RwLock<Option<WriteThrottle>>,
     write_lock: Mutex<()>,
     next_txid: AtomicU64,
+    /// Highest txid whose writes are visible to new snapshots.
+    visible_txid: Mutex<u64>,
+    visible_txid_changed: Condvar,
     next_segment_id: AtomicU64,
     manifest_epoch: AtomicU64,
     checkpoint_txid: AtomicU64,
@@ -74,11 +182,77 @@ pub struct GraphEngine {
 
 impl GraphEngine {
     pub fn open(ndb_path: impl AsRef<Path>, wal_path: impl AsRef<Path>) -> Result<Self> {
-        let ndb_path = ndb_path.as_ref().to_path_buf();
-        let wal_path = wal_path.as_ref().to_path_buf();
+        Self::open_with_options(ndb_path, wal_path, EngineOptions::default())
+    }
+
+    pub fn open_with_options(
+        ndb_path: impl AsRef<Path>,
+        wal_path: impl AsRef<Path>,
+        options: EngineOptions,
+    ) -> Result<Self> {
+        let backend = if options.read_only {
+            FileBackend::open_read_only(ndb_path.as_ref())?
+        } else {
+            FileBackend::open(ndb_path.as_ref())?
+        };
+        Self::open_with_backend(ndb_path, wal_path, options, Box::new(backend))
+    }
 
-        let mut pager = Pager::open(&ndb_path)?;
-        let wal = Wal::open(&wal_path)?;
+    /// Opens an engine whose pages live in `backend` instead of the file at
+    /// `ndb_path`. The WAL stays a local file, and `ndb_path` still locates
+    /// the files kept beside the data, such as the warm page list.
+    ///
+    /// With [`EngineOptions::read_only`], neither the WAL nor `backend` is
+    /// written: the WAL must exist and keeps any torn tail, and the pages
+    /// recovery stages stay in memory (see [`OverlayBackend`]).
+    pub fn open_with_backend(
+        ndb_path: impl AsRef<Path>,
+        wal_path: impl AsRef<Path>,
+        options: EngineOptions,
+        backend: Box<dyn StorageBackend>,
+    ) -> Result<Self> {
+        if options.read_only {
+            let wal = Wal::open_read_only(wal_path)?;
+            let backend = OverlayBackend::new(backend)?;
+            return Self::open_parts(
+                ndb_path.as_ref().to_path_buf(),
+                options,
+                Box::new(backend),
+                wal,
+            );
+        }
+        let mut wal = Wal::open(wal_path)?;
+        wal.set_segment_bytes(options.wal_segment_bytes);
+        // Drop any torn tail from a crash so new commits stay reachable on replay.
+        wal.truncate_torn_tail()?;
+        Self::open_parts(ndb_path.as_ref().to_path_buf(), options, backend, wal)
+    }
+
+    /// Opens the packed image `bytes`, written by [`GraphEngine::pack`],
+    /// read-only and entirely in memory. `path` is only reported back by
+    /// [`GraphEngine::ndb_path`] and [`GraphEngine::wal_path`].
+    pub fn open_packed(
+        path: impl AsRef<Path>,
+        bytes: &[u8],
+        options: EngineOptions,
+    ) -> Result<Self> {
+        let image = PackedImage::decode(bytes)?;
+        let wal = Wal::frozen(path.as_ref(), image.committed);
+        let backend = ReadOnlyBackend::new(image.pages);
+        Self::open_parts(path.as_ref().to_path_buf(), options, Box::new(backend), wal)
+    }
+
+    fn open_parts(
+        ndb_path: PathBuf,
+        options: EngineOptions,
+        backend: Box<dyn StorageBackend>,
+        mut wal: Wal,
+    ) -> Result<Self> {
+        let wal_path = wal.path().to_path_buf();
+        let frozen = wal.is_frozen();
+        let read_only = frozen || options.read_only;
+        let mut pager = Pager::open_with_backend(&ndb_path, backend)?;
+        pager.set_page_cache_capacity(options.page_cache_pages);
 
         let mut idmap = IdMap::load(&mut pager)?;
         let mut index_catalog = IndexCatalog::open_or_create(&mut pager)?;
@@ -90,9 +264,8 @@ impl GraphEngine {
 
         let v_store = PersistentVectorStorage::new(BTree::load(vec_def.root));
         let g_store = PersistentGraphStorage::new(BTree::load(graph_def.root));
-        let params = load_hnsw_params_from_env();
         // HnswIndex::load needs generic Ctx = &mut Pager
-        let vector_index = HnswIndex::load(params, v_store, g_store, &mut pager)?;
+        let vector_index = HnswIndex::load(options.hnsw, v_store, g_store, &mut pager)?;
 
         let committed = wal.replay_committed()?;
         let state = scan_recovery_state(&committed);
@@ -111,6 +284,15 @@ impl GraphEngine {
         // Build label interner from recovered state (first, before graph transactions)
         let mut label_interner = LabelInterner::new();
         replay_label_transactions(&committed, &mut label_interner)?;
+        let named_queries = replay_named_queries(&committed);
+        let idempotency_keys = replay_idempotency_keys(&committed);
+        let sensitive = replay_sensitive_properties(&committed);
+        let endpoints = replay_endpoint_constraints(&committed);
+        let retention = replay_retention(&committed);
+        wal.set_archive(retention.archives());
+        let cold_labels = replay_label_tiers(&committed);
+        let namespaces = replay_namespaces(&committed);
+        let cold_store = ColdStore::open(cold_store::cold_path(&ndb_path), read_only)?;
 
         let mut runs = Vec::new();
         replay_graph_transactions(
@@ -120,13 +302,36 @@ impl GraphEngine {
             state.checkpoint_txid,
             &mut runs,
         )?;
+        let mut vector_index = vector_index;
+        // A packed image is taken under the write lock, so its pages already
+        // hold every vector its log mentions.
+        if !frozen {
+            replay_vectors(
+                &mut pager,
+                &mut index_catalog,
+                &mut vector_index,
+                &committed,
+                state.checkpoint_txid,
+            )?;
+        }
 
         runs.reverse(); // newest first for read path
 
         let label_snapshot = label_interner.snapshot();
         let node_labels_snapshot = idmap.get_i2l_snapshot();
+        let stored_schema =
+            crate::read_path_stats::read_statistics(&pager, state.stats_root)?.schema;
+        let scan_schema = stored_schema.is_none();
+        let mut schema = stored_schema.unwrap_or_default();
+        for run in &runs {
+            schema.record_run(run);
+        }
+        schema.record_labels(node_labels_snapshot.iter().flatten().copied());
+        if !frozen {
+            restore_warm_pages(&pager, &warm_pages_path(&ndb_path));
+        }
 
-        Ok(Self {
+        let engine = Self {
             ndb_path,
             wal_path,
             pager: Arc::new(RwLock::new(pager)),
@@ -135,18 +340,43 @@ impl GraphEngine {
             label_interner: Mutex::new(label_interner),
             index_catalog: Arc::new(Mutex::new(index_catalog)),
             vector_index: Arc::new(Mutex::new(vector_index)),
+            exact_vector_search: options.exact_vector_search,
+            name_limits: options.name_limits,
+            system_properties: options.system_properties,
+            read_only,
             published_runs: RwLock::new(Arc::new(runs)),
             published_segments: RwLock::new(Arc::new(segments)),
             published_labels: RwLock::new(Arc::new(label_snapshot)),
             published_node_labels: RwLock::new(Arc::new(node_labels_snapshot)),
+            published_named_queries: RwLock::new(Arc::new(named_queries)),
+            idempotency_keys: RwLock::new(idempotency_keys),
+            published_sensitive: RwLock::new(Arc::new(sensitive)),
+            published_endpoints: RwLock::new(Arc::new(endpoints)),
+            published_retention: RwLock::new(Arc::new(retention)),
+            tier_pass_pending: AtomicBool::new(!cold_labels.is_empty()),
+            published_cold_labels: RwLock::new(Arc::new(cold_labels)),
+            published_namespaces: RwLock::new(Arc::new(namespaces)),
+            cold_store: Arc::new(cold_store),
+            published_schema: RwLock::new(Arc::new(schema)),
+            write_stamps: Mutex::new(WriteStamps::default()),
+            write_throttle: RwLock::new(options.write_throttle),
             write_lock: Mutex::new(()),
             next_txid: AtomicU64::new(state.max_txid.saturating_add(1).max(1)),
+            visible_txid: Mutex::new(state.max_txid),
+            visible_txid_changed: Condvar::new(),
             next_segment_id: AtomicU64::new(max_seg_id.saturating_add(1).max(1)),
             manifest_epoch: AtomicU64::new(state.manifest_epoch),
             checkpoint_txid: AtomicU64::new(state.checkpoint_txid),
             properties_root: AtomicU64::new(state.properties_root),
             stats_root: AtomicU64::new(state.stats_root),
-        })
+        };
+        if scan_schema {
+            // Checkpointed data predates the catalog, or came from the bulk
+            // loader; the next compaction stores what the scan finds.
+            let scanned = crate::schema::scan(&engine.snapshot());
+            engine.extend_schema(scanned);
+        }
+        Ok(engine)
     }
 
     #[inline]
@@ -159,6 +389,20 @@ impl GraphEngine {
         &self.wal_path
     }
 
+    /// Whether this engine refuses writes: it serves a packed image, or was
+    /// opened with [`EngineOptions::read_only`].
+    #[inline]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_pager(&self) -> Arc<RwLock<Pager>> {
         self.pager.clone()
     }
@@ -167,12 +411,21 @@ impl GraphEngine {
         self.index_catalog.clone()
     }
 
+    pub(crate) fn get_vector_index(&self) -> Arc<Mutex<NativeHnsw>> {
+        self.vector_index.clone()
+    }
+
+    pub(crate) fn exact_vector_search(&self) -> bool {
+        self.exact_vector_search
+    }
+
     /// Creates a B-Tree index for the given label and property.
     ///
     /// If the index already exists, this is a no-op.
     /// Note: This MVP does not backfill existing data. The index will only track
     /// valid data inserted *after* index creation.
     pub fn create_index(&self, label: &str, field: &str) -> Result<()> {
+        self.ensure_writable()?;
         let mut catalog = self.index_catalog.lock().unwrap();
         let name = format!("{}.{}", label, field);
         if catalog.get(&name).is_some() {
@@ -185,6 +438,686 @@ impl GraphEngine {
         Ok(())
     }
 
+    /// Creates a trigram text index for `CONTAINS` lookups on the given label and property.
+    ///
+    /// Unlike [`Self::create_index`], existing string values are backfilled, so the
+    /// index can be used for substring search immediately. Non-string values are ignored.
+    pub fn create_text_index(&self, label: &str, field: &str) -> Result<()> {
+        self.ensure_writable()?;
+        let name = text_index_name(label, field);
+        if self.index_catalog.lock().unwrap().get(&name).is_some() {
+            return Ok(());
+        }
+
+        // Block commits so no write slips between the backfill and index maintenance.
+        let _guard = self.write_lock.lock().unwrap();
+        let def = {
+            let mut catalog = self.index_catalog.lock().unwrap();
+            let mut pager = self.pager.write().unwrap();
+            catalog.get_or_create(&mut pager, &name)?
+        };
+        let mut tree = BTree::load(def.root);
+
+        if let Some(label_id) = self.get_label_id(label) {
+            let snapshot = self.snapshot();
+            for node in snapshot.nodes() {
+                let has_label = snapshot
+                    .resolve_node_labels(node)
+                    .is_some_and(|labels| labels.contains(&label_id));
+                if !has_label {
+                    continue;
+                }
+                if let Some(nervusdb_api::PropertyValue::String(text)) =
+                    snapshot.node_property(node, field)
+                {
+                    let mut pager = self.pager.write().unwrap();
+                    update_text(&mut tree, &mut pager, def.id, node, None, Some(&text))?;
+                }
+            }
+        }
+
+        let mut catalog = self.index_catalog.lock().unwrap();
+        let mut pager = self.pager.write().unwrap();
+        catalog.update_root(&mut pager, &name, tree.root())
+    }
+
+    /// Creates an equality index over several properties of `label`, in the
+    /// given order. Existing nodes are backfilled; nodes missing any of the
+    /// properties are not indexed.
+    ///
+    /// If the index already exists, this is a no-op.
+    pub fn create_composite_index(&self, label: &str, properties: &[&str]) -> Result<()> {
+        self.ensure_writable()?;
+        validate_composite_properties(properties)?;
+        let name = composite_index_name(label, properties);
+        if self.index_catalog.lock().unwrap().get(&name).is_some() {
+            return Ok(());
+        }
+
+        // Block commits so no write slips between the backfill and index maintenance.
+        let _guard = self.write_lock.lock().unwrap();
+        let key = CompositeKey {
+            label: label.to_string(),
+            properties: properties.iter().map(|p| p.to_string()).collect(),
+            unique: false,
+        };
+        let def = {
+            let mut catalog = self.index_catalog.lock().unwrap();
+            let mut pager = self.pager.write().unwrap();
+            catalog.get_or_create_composite(&mut pager, &name, key)?
+        };
+        let mut tree = BTree::load(def.root);
+
+        if let Some(label_id) = self.get_label_id(label) {
+            let snapshot = self.snapshot();
+            for node in snapshot.nodes() {
+                let has_label = snapshot
+                    .resolve_node_labels(node)
+                    .is_some_and(|labels| labels.contains(&label_id));
+                if !has_label {
+                    continue;
+                }
+                let values = properties
+                    .iter()
+                    .map(|field| snapshot.node_property(node, field))
+                    .collect();
+                if let Some(tuple) = indexable_tuple(values) {
+                    let mut pager = self.pager.write().unwrap();
+                    update_composite(&mut tree, &mut pager, def.id, node, None, Some(&tuple))?;
+                }
+            }
+        }
+
+        let mut catalog = self.index_catalog.lock().unwrap();
+        let mut pager = self.pager.write().unwrap();
+        catalog.update_root(&mut pager, &name, tree.root())
+    }
+
+    /// Drops the equality index on `label` over `properties` (one property for
+    /// an index made by [`Self::create_index`], several for a composite one).
+    ///
+    /// Returns whether an index was removed. Its pages are reclaimed by the
+    /// next vacuum.
+    pub fn drop_index(&self, label: &str, properties: &[&str]) -> Result<bool> {
+        self.ensure_writable()?;
+        let name = match properties {
+            [] => return Err(Error::InvalidIndex("no properties given".to_string())),
+            [field] => format!("{}.{}", label, field),
+            _ => composite_index_name(label, properties),
+        };
+        // Wait for in-flight commits, which update index roots in place.
+        let _guard = self.write_lock.lock().unwrap();
+        let mut catalog = self.index_catalog.lock().unwrap();
+        let mut pager = self.pager.write().unwrap();
+        Ok(catalog.remove(&mut pager, &name)?.is_some())
+    }
+
+    /// Creates a unique constraint: no two live nodes labelled `label` may
+    /// hold the same non-null `property` value. Existing nodes are checked and
+    /// indexed first, so creation fails if they already hold duplicates.
+    ///
+    /// If the constraint already exists, this is a no-op.
+    pub fn create_unique_constraint(&self, label: &str, property: &str) -> Result<()> {
+        self.ensure_writable()?;
+        if property.is_empty() || property.contains([',', '(', ')']) {
+            return Err(Error::InvalidIndex(format!(
+                "invalid property {property:?}"
+            )));
+        }
+        let name = unique_constraint_name(label, property);
+        if self.index_catalog.lock().unwrap().get(&name).is_some() {
+            return Ok(());
+        }
+
+        // Block commits so no write slips between the check and enforcement.
+        let _guard = self.write_lock.lock().unwrap();
+        let mut entries = Vec::new();
+        if let Some(label_id) = self.get_label_id(label) {
+            use crate::read_path_convert::convert_property_to_storage as to_storage;
+            let snapshot = self.snapshot();
+            let mut seen = std::collections::HashMap::new();
+            for node in snapshot.nodes() {
+                let has_label = snapshot
+                    .resolve_node_labels(node)
+                    .is_some_and(|labels| labels.contains(&label_id));
+                if !has_label {
+                    continue;
+                }
+                let Some(value) = snapshot.node_property(node, property).map(to_storage) else {
+                    continue;
+                };
+                if matches!(value, crate::property::PropertyValue::Null) {
+                    continue;
+                }
+                if let Some(other) = seen.insert(encode_ordered_value(&value), node) {
+                    return Err(Error::ConstraintViolation(format!(
+                        "cannot create {name}: nodes {other} and {node} share {property} = {value:?}"
+                    )));
+                }
+                entries.push((node, value));
+            }
+        }
+
+        let key = CompositeKey {
+            label: label.to_string(),
+            properties: vec![property.to_string()],
+            unique: true,
+        };
+        let mut catalog = self.index_catalog.lock().unwrap();
+        let mut pager = self.pager.write().unwrap();
+        let def = catalog.get_or_create_composite(&mut pager, &name, key)?;
+        let mut tree = BTree::load(def.root);
+        for (node, value) in entries {
+            update_composite(&mut tree, &mut pager, def.id, node, None, Some(&[value]))?;
+        }
+        catalog.update_root(&mut pager, &name, tree.root())
+    }
+
+    /// Drops the unique constraint on `label.property`, returning whether one
+    /// existed.
+    pub fn drop_unique_constraint(&self, label: &str, property: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let name = unique_constraint_name(label, property);
+        let _guard = self.write_lock.lock().unwrap();
+        let mut catalog = self.index_catalog.lock().unwrap();
+        let is_unique = catalog
+            .get(&name)
+            .and_then(|def| def.composite.as_ref())
+            .is_some_and(|key| key.unique);
+        if !is_unique {
+            return Ok(false);
+        }
+        let mut pager = self.pager.write().unwrap();
+        Ok(catalog.remove(&mut pager, &name)?.is_some())
+    }
+
+    /// Returns every unique constraint as `(label, property)`.
+    pub fn unique_constraints(&self) -> Vec<(String, String)> {
+        self.index_catalog
+            .lock()
+            .unwrap()
+            .entries
+            .values()
+            .filter_map(|def| def.composite.as_ref())
+            .filter(|key| key.unique)
+            .map(|key| (key.label.clone(), key.properties[0].clone()))
+            .collect()
+    }
+
+    /// Restricts relationships of type `rel_type` to the given `(source label,
+    /// target label)` pairs, replacing any earlier restriction on the type.
+    /// Existing relationships are checked first, so creation fails if one
+    /// already connects other labels.
+    ///
+    /// The restriction is checked when relationships are created; changing a
+    /// node's labels later does not revisit the relationships it already has.
+    pub fn create_endpoint_constraint(
+        &self,
+        rel_type: &str,
+        endpoints: &[(String, String)],
+    ) -> Result<()> {
+        self.ensure_writable()?;
+        if endpoints.is_empty() {
+            return Err(Error::SchemaConflict(format!(
+                "endpoint constraint on {rel_type} needs at least one label pair"
+            )));
+        }
+        self.name_limits.check(NameKind::RelType, rel_type)?;
+        for (src, dst) in endpoints {
+            self.name_limits.check(NameKind::Label, src)?;
+            self.name_limits.check(NameKind::Label, dst)?;
+        }
+        let allowed: BTreeSet<(String, String)> = endpoints.iter().cloned().collect();
+
+        // Block commits so no write slips between the check and enforcement.
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(rel) = self.get_label_id(rel_type) {
+            let snapshot = self.begin_read();
+            let names = |node| -> Vec<String> {
+                snapshot
+                    .node_labels(node)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|label| snapshot.resolve_label_name(label))
+                    .collect()
+            };
+            for src in snapshot.nodes() {
+                for edge in snapshot.neighbors(src, Some(rel)) {
+                    let (src_labels, dst_labels) = (names(edge.src), names(edge.dst));
+                    if !endpoints_allowed(&allowed, &src_labels, &dst_labels) {
+                        return Err(Error::ConstraintViolation(format!(
+                            "cannot constrain {rel_type}: {}",
+                            endpoint_violation(rel_type, &allowed, edge, &src_labels, &dst_labels)
+                        )));
+                    }
+                }
+            }
+        }
+        self.append_endpoints(rel_type, Some(allowed))
+    }
+
+    /// Lifts the endpoint constraint on `rel_type`, returning whether one
+    /// existed.
+    pub fn drop_endpoint_constraint(&self, rel_type: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let _guard = self.write_lock.lock().unwrap();
+        if !self.endpoint_constraints().contains_key(rel_type) {
+            return Ok(false);
+        }
+        self.append_endpoints(rel_type, None)?;
+        Ok(true)
+    }
+
+    /// Returns the allowed `(source label, target label)` pairs of every
+    /// constrained relationship type.
+    pub fn endpoint_constraints(&self) -> Arc<EndpointConstraints> {
+        self.published_endpoints.read().unwrap().clone()
+    }
+
+    /// Logs and publishes the endpoints of `rel_type` in a transaction of
+    /// its own. The caller holds the write lock.
+    fn append_endpoints(
+        &self,
+        rel_type: &str,
+        allowed: Option<BTreeSet<(String, String)>>,
+    ) -> Result<()> {
+        let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut wal = self.wal.lock().unwrap();
+            wal.append(&WalRecord::BeginTx { txid })?;
+            wal.append(&WalRecord::SetEndpoints {
+                rel_type: rel_type.to_string(),
+                endpoints: allowed.iter().flatten().cloned().collect(),
+            })?;
+            wal.append(&WalRecord::CommitTx { txid })?;
+            wal.fsync()?;
+            wal.rotate_if_full()?;
+        }
+        let mut published = self.published_endpoints.write().unwrap();
+        let mut constraints = (**published).clone();
+        match allowed {
+            Some(allowed) => constraints.insert(rel_type.to_string(), allowed),
+            None => constraints.remove(rel_type),
+        };
+        *published = Arc::new(constraints);
+        Ok(())
+    }
+
+    /// Keeps checkpointed WAL history for `keep_for` instead of letting the
+    /// next checkpoint delete it: checkpoints archive the log they replace
+    /// (see [`crate::wal_archive`]) and delete archives once they are older
+    /// than this. `None` stops archiving unless a legal hold is placed.
+    pub fn set_wal_retention(&self, keep_for: Option<Duration>) -> Result<()> {
+        self.ensure_writable()?;
+        let _guard = self.write_lock.lock().unwrap();
+        let keep_millis = keep_for.map(|keep| u64::try_from(keep.as_millis()).unwrap_or(u64::MAX));
+        let mut policy = (*self.wal_retention()).clone();
+        policy.keep_for = keep_millis.map(Duration::from_millis);
+        self.append_retention(WalRecord::SetRetention { keep_millis }, policy)
+    }
+
+    /// Places the legal hold `name`: until it is released, checkpoints
+    /// archive the WAL and no archive is deleted, whatever the retention
+    /// period. Returns `false` if the hold was already placed.
+    pub fn place_legal_hold(&self, name: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let _guard = self.write_lock.lock().unwrap();
+        let mut policy = (*self.wal_retention()).clone();
+        if policy.holds.contains_key(name) {
+            return Ok(false);
+        }
+        let placed_at_millis = wal_archive::unix_millis(SystemTime::now());
+        policy.holds.insert(name.to_string(), placed_at_millis);
+        self.append_retention(
+            WalRecord::SetLegalHold {
+                name: name.to_string(),
+                placed_at_millis,
+                held: true,
+            },
+            policy,
+        )?;
+        Ok(true)
+    }
+
+    /// Releases the legal hold `name`, returning whether it was placed.
+    /// Archives it pinned are deleted by the next checkpoint once no hold
+    /// remains and they are past the retention period.
+    pub fn release_legal_hold(&self, name: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let _guard = self.write_lock.lock().unwrap();
+        let mut policy = (*self.wal_retention()).clone();
+        let Some(placed_at_millis) = policy.holds.remove(name) else {
+            return Ok(false);
+        };
+        self.append_retention(
+            WalRecord::SetLegalHold {
+                name: name.to_string(),
+                placed_at_millis,
+                held: false,
+            },
+            policy,
+        )?;
+        Ok(true)
+    }
+
+    /// The retention period and placed legal holds.
+    pub fn wal_retention(&self) -> Arc<RetentionPolicy> {
+        self.published_retention.read().unwrap().clone()
+    }
+
+    /// The WAL archives kept by the retention policy, oldest first.
+    pub fn archived_wal_segments(&self) -> Result<Vec<ArchivedSegment>> {
+        wal_archive::archived_segments(&self.wal_path)
+    }
+
+    /// Moves the properties of nodes labelled `label` to `tier`. They move
+    /// at the next compaction, which also moves the properties of nodes that
+    /// gain the label once they are written. A node with both a cold and a
+    /// hot label is kept cold. Returns `false` if `label` was already in
+    /// `tier`.
+    pub fn set_label_tier(&self, label: &str, tier: StorageTier) -> Result<bool> {
+        self.ensure_writable()?;
+        let _guard = self.write_lock.lock().unwrap();
+        let cold = tier == StorageTier::Cold;
+        let mut labels = (*self.cold_labels()).clone();
+        let changed = if cold {
+            labels.insert(label.to_string())
+        } else {
+            labels.remove(label)
+        };
+        if !changed {
+            return Ok(false);
+        }
+        let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut wal = self.wal.lock().unwrap();
+            wal.append(&WalRecord::BeginTx { txid })?;
+            wal.append(&WalRecord::SetLabelTier {
+                label: label.to_string(),
+                cold,
+            })?;
+            wal.append(&WalRecord::CommitTx { txid })?;
+            wal.fsync()?;
+            wal.rotate_if_full()?;
+        }
+        *self.published_cold_labels.write().unwrap() = Arc::new(labels);
+        self.tier_pass_pending.store(true, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    pub fn label_tier(&self, label: &str) -> StorageTier {
+        if self.cold_labels().contains(label) {
+            StorageTier::Cold
+        } else {
+            StorageTier::Hot
+        }
+    }
+
+    /// Labels assigned to the cold tier.
+    pub fn cold_labels(&self) -> Arc<BTreeSet<String>> {
+        self.published_cold_labels.read().unwrap().clone()
+    }
+
+    /// Binds `prefix` to the base IRI `iri`, or unbinds it when `iri` is
+    /// `None`. Values stored as `prefix:local` resolve through the catalog,
+    /// so rebinding a prefix moves every reference to the new base. Returns
+    /// `false` if the binding was already as requested.
+    pub fn set_namespace(&self, prefix: &str, iri: Option<&str>) -> Result<bool> {
+        self.ensure_writable()?;
+        crate::names::check_namespace_prefix(prefix)?;
+        if let Some(iri) = iri {
+            crate::names::check_namespace_iri(iri)?;
+        }
+        let _guard = self.write_lock.lock().unwrap();
+        let mut namespaces = (*self.namespaces()).clone();
+        let changed = match iri {
+            Some(iri) => {
+                namespaces
+                    .insert(prefix.to_string(), iri.to_string())
+                    .as_deref()
+                    != Some(iri)
+            }
+            None => namespaces.remove(prefix).is_some(),
+        };
+        if !changed {
+            return Ok(false);
+        }
+        let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut wal = self.wal.lock().unwrap();
+            wal.append(&WalRecord::BeginTx { txid })?;
+            wal.append(&WalRecord::SetNamespace {
+                prefix: prefix.to_string(),
+                iri: iri.map(str::to_string),
+            })?;
+            wal.append(&WalRecord::CommitTx { txid })?;
+            wal.fsync()?;
+            wal.rotate_if_full()?;
+        }
+        *self.published_namespaces.write().unwrap() = Arc::new(namespaces);
+        Ok(true)
+    }
+
+    /// Namespace prefixes and their base IRIs.
+    pub fn namespaces(&self) -> Arc<BTreeMap<String, String>> {
+        self.published_namespaces.read().unwrap().clone()
+    }
+
+    pub fn cold_tier_stats(&self) -> ColdTierStats {
+        self.cold_store.stats()
+    }
+
+    pub(crate) fn get_cold_store(&self) -> Arc<ColdStore> {
+        self.cold_store.clone()
+    }
+
+    /// Logs `record` in a transaction of its own and publishes `policy`.
+    /// The caller holds the write lock.
+    fn append_retention(&self, record: WalRecord, policy: RetentionPolicy) -> Result<()> {
+        let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut wal = self.wal.lock().unwrap();
+            wal.append(&WalRecord::BeginTx { txid })?;
+            wal.append(&record)?;
+            wal.append(&WalRecord::CommitTx { txid })?;
+            wal.fsync()?;
+            wal.rotate_if_full()?;
+            wal.set_archive(policy.archives());
+        }
+        *self.published_retention.write().unwrap() = Arc::new(policy);
+        Ok(())
+    }
+
+    /// Renames property `old` to `new` on every node labelled `label`, in a
+    /// single write transaction. Equality, text and composite indexes, unique
+    /// constraints and the sensitive marker on the property follow it without
+    /// being rebuilt.
+    ///
+    /// `progress` is called with `(renamed, total)` as nodes are staged.
+    /// Fails with [`Error::SchemaConflict`] if one of those nodes already has
+    /// `new` or an index under the new name exists. Returns the number of
+    /// nodes renamed.
+    pub fn rename_property(
+        &self,
+        label: &str,
+        old: &str,
+        new: &str,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<usize> {
+        self.ensure_writable()?;
+        if old == new {
+            return Ok(0);
+        }
+        if new.is_empty() || new.contains([',', '(', ')']) {
+            return Err(Error::SchemaConflict(format!("invalid property {new:?}")));
+        }
+        self.name_limits.check(NameKind::PropertyKey, new)?;
+        crate::names::check_writable_property(old)?;
+        crate::names::check_writable_property(new)?;
+
+        let mut txn = self.begin_write();
+        let renames = self
+            .index_catalog
+            .lock()
+            .unwrap()
+            .property_renames(label, old, new)?;
+
+        let mut nodes = Vec::new();
+        if let Some(label_id) = self.get_label_id(label) {
+            use crate::read_path_convert::convert_property_to_storage as to_storage;
+            let snapshot = self.snapshot();
+            for node in snapshot.nodes() {
+                let has_label = snapshot
+                    .resolve_node_labels(node)
+                    .is_some_and(|labels| labels.contains(&label_id));
+                if !has_label {
+                    continue;
+                }
+                let Some(value) = snapshot.node_property(node, old) else {
+                    continue;
+                };
+                if snapshot.node_property(node, new).is_some() {
+                    return Err(Error::SchemaConflict(format!(
+                        "node {node} already has property {new}"
+                    )));
+                }
+                nodes.push((node, to_storage(value)));
+            }
+        }
+
+        let total = nodes.len();
+        for (done, (node, value)) in nodes.into_iter().enumerate() {
+            txn.set_node_property(node, new.to_string(), value)?;
+            txn.remove_node_property(node, old)?;
+            if (done + 1) % RENAME_PROGRESS_STEP == 0 {
+                progress(done + 1, total);
+            }
+        }
+        if total % RENAME_PROGRESS_STEP != 0 {
+            progress(total, total);
+        }
+        if self
+            .sensitive_properties()
+            .contains(&(label.to_string(), old.to_string()))
+        {
+            txn.set_sensitive(label, old, false);
+            txn.set_sensitive(label, new, true);
+        }
+        txn.pending_index_renames = renames;
+        txn.commit()?;
+        Ok(total)
+    }
+
+    /// Renames label `old` to `new`. Nodes keep their label ID, so only the
+    /// name table, the index catalog, sensitive markers and endpoint constraints
+    /// change. Labels and relationship types share one name table, so a
+    /// relationship type named `old` is renamed too.
+    ///
+    /// Returns whether `old` existed. Fails with [`Error::SchemaConflict`] if
+    /// `new` is already in use.
+    pub fn rename_label(&self, old: &str, new: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let _guard = self.write_lock.lock().unwrap();
+        if self.get_label_id(old).is_none() {
+            return Ok(false);
+        }
+        if old == new {
+            return Ok(true);
+        }
+        self.name_limits.check(NameKind::Label, new)?;
+        let renames = self.index_catalog.lock().unwrap().label_renames(old, new)?;
+
+        {
+            let mut interner = self.label_interner.lock().unwrap();
+            let Some(label_id) = interner.get_id(old) else {
+                return Ok(false);
+            };
+            if interner.contains(new) {
+                return Err(Error::SchemaConflict(format!("label {new} already exists")));
+            }
+            let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
+            let mut wal = self.wal.lock().unwrap();
+            wal.append(&WalRecord::BeginTx { txid })?;
+            wal.append(&WalRecord::RenameLabel {
+                label_id,
+                name: new.to_string(),
+            })?;
+            let moved: Vec<(String, String, bool)> = self
+                .sensitive_properties()
+                .iter()
+                .filter(|(label, _)| label == old)
+                .flat_map(|(_, property)| {
+                    [
+                        (old.to_string(), property.clone(), false),
+                        (new.to_string(), property.clone(), true),
+                    ]
+                })
+                .collect();
+            for (label, property, sensitive) in &moved {
+                wal.append(&WalRecord::SetSensitive {
+                    label: label.clone(),
+                    property: property.clone(),
+                    sensitive: *sensitive,
+                })?;
+            }
+            let cold = self.cold_labels().contains(old);
+            if cold {
+                wal.append(&WalRecord::SetLabelTier {
+                    label: old.to_string(),
+                    cold: false,
+                })?;
+                wal.append(&WalRecord::SetLabelTier {
+                    label: new.to_string(),
+                    cold: true,
+                })?;
+            }
+            let endpoints = self.endpoint_constraints();
+            let renamed_endpoints = rename_in_endpoints(&endpoints, old, new);
+            for rel_type in endpoints.keys().chain(renamed_endpoints.keys()) {
+                if endpoints.get(rel_type) != renamed_endpoints.get(rel_type) {
+                    wal.append(&WalRecord::SetEndpoints {
+                        rel_type: rel_type.clone(),
+                        endpoints: renamed_endpoints
+                            .get(rel_type)
+                            .map(|pairs| pairs.iter().cloned().collect())
+                            .unwrap_or_default(),
+                    })?;
+                }
+            }
+            wal.append(&WalRecord::CommitTx { txid })?;
+            wal.fsync()?;
+            wal.rotate_if_full()?;
+            interner.rename(label_id, new);
+            *self.published_labels.write().unwrap() = Arc::new(interner.snapshot());
+            self.write_stamps.lock().unwrap().record(&Touched {
+                everything: true,
+                ..Touched::default()
+            });
+            if !moved.is_empty() {
+                let mut published = self.published_sensitive.write().unwrap();
+                let mut sensitive = (**published).clone();
+                apply_sensitive_ops(&mut sensitive, moved);
+                *published = Arc::new(sensitive);
+            }
+            if cold {
+                let mut published = self.published_cold_labels.write().unwrap();
+                let mut labels = (**published).clone();
+                labels.remove(old);
+                labels.insert(new.to_string());
+                *published = Arc::new(labels);
+            }
+            if renamed_endpoints != *endpoints {
+                *self.published_endpoints.write().unwrap() = Arc::new(renamed_endpoints);
+            }
+        }
+
+        let mut catalog = self.index_catalog.lock().unwrap();
+        let detached = catalog.detach(renames);
+        let mut pager = self.pager.write().unwrap();
+        catalog.attach(&mut pager, detached)?;
+        Ok(true)
+    }
+
     pub fn begin_read(&self) -> Snapshot {
         let runs = self.published_runs.read().unwrap().clone();
         let segments = self.published_segments.read().unwrap().clone();
@@ -203,6 +1136,10 @@ impl GraphEngine {
     }
 
     pub fn begin_write(&self) -> WriteTxn<'_> {
+        // Wait before taking the lock, which checkpoints need too.
+        if let Some(delay) = self.write_throttle_delay() {
+            std::thread::sleep(delay);
+        }
         let guard = self.write_lock.lock().unwrap();
         let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
         WriteTxn {
@@ -212,11 +1149,280 @@ impl GraphEngine {
             created_nodes: Vec::new(),
             pending_label_additions: Vec::new(),
             pending_label_removals: Vec::new(),
-            created_external_ids: std::collections::HashSet::new(),
+            created_external_ids: std::collections::HashMap::new(),
+            pending_vectors: Vec::new(),
+            pending_named_queries: Vec::new(),
+            pending_sensitive: Vec::new(),
+            idempotency_key: None,
+            unique_claims: UniqueClaims::default(),
+            constraints_deferred: false,
+            deferred_unique: BTreeSet::new(),
+            pending_index_renames: Vec::new(),
             memtable: MemTable::default(),
         }
     }
 
+    /// Reads the on-disk property pages of every node carrying one of
+    /// `labels` into the page cache, returning how many pages were loaded.
+    /// Unknown labels are skipped. Loading more pages than the cache holds
+    /// evicts the earliest ones.
+    pub fn preload_labels(&self, labels: &[&str]) -> Result<usize> {
+        let wanted: BTreeSet<LabelId> = labels
+            .iter()
+            .filter_map(|name| self.get_label_id(name))
+            .collect();
+        if wanted.is_empty() || self.properties_root.load(Ordering::Relaxed) == 0 {
+            return Ok(0);
+        }
+        let before = self.page_cache_stats().misses;
+        let snapshot = self.snapshot();
+        for node in snapshot.nodes() {
+            let labels = snapshot.resolve_node_labels(node).unwrap_or_default();
+            if labels.iter().any(|label| wanted.contains(label)) {
+                let _ = snapshot.node_properties(node);
+            }
+        }
+        Ok((self.page_cache_stats().misses - before) as usize)
+    }
+
+    /// Reads up to `count` pages starting at `first` into the page cache,
+    /// returning how many were loaded. Unallocated and already cached pages
+    /// are skipped.
+    pub fn preload_pages(&self, first: u64, count: u64) -> Result<usize> {
+        let pager = self.pager.read().unwrap();
+        let mut loaded = 0;
+        for id in first..first.saturating_add(count) {
+            if pager.preload_page(PageId::new(id))? {
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Changes how many pages the page cache keeps, evicting the least
+    /// recently used if it shrinks.
+    pub fn set_page_cache_capacity(&self, pages: usize) {
+        self.pager.read().unwrap().set_page_cache_capacity(pages);
+    }
+
+    pub fn page_cache_stats(&self) -> PageCacheStats {
+        self.pager.read().unwrap().page_cache_stats()
+    }
+
+    /// Bytes the WAL takes on disk, sealed segments included. A checkpoint
+    /// shrinks it back to a single snapshot transaction.
+    pub fn wal_bytes(&self) -> Result<u64> {
+        self.wal.lock().unwrap().total_bytes()
+    }
+
+    /// Replaces the write throttle set through
+    /// [`EngineOptions::write_throttle`]; `None` turns it off.
+    pub fn set_write_throttle(&self, throttle: Option<WriteThrottle>) {
+        *self.write_throttle.write().unwrap() = throttle;
+    }
+
+    pub fn write_throttle(&self) -> Option<WriteThrottle> {
+        *self.write_throttle.read().unwrap()
+    }
+
+    /// How long a new write transaction waits for checkpoints to catch up.
+    /// A WAL whose size cannot be read is not waited on.
+    fn write_throttle_delay(&self) -> Option<Duration> {
+        let throttle = self.write_throttle()?;
+        throttle.delay_at(self.wal_bytes().ok()?)
+    }
+
+    /// Refuses a commit while the WAL is past the rejection watermark.
+    fn check_write_backlog(&self) -> Result<()> {
+        let Some(throttle) = self.write_throttle() else {
+            return Ok(());
+        };
+        if throttle.reject_wal_bytes == 0 {
+            return Ok(());
+        }
+        let wal_bytes = self.wal_bytes()?;
+        if throttle.rejects_at(wal_bytes) {
+            return Err(Error::WriteThrottled {
+                wal_bytes,
+                limit: throttle.reject_wal_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Graph changes committed after `since_txid`, decoded from the WAL in
+    /// commit order. Pass 0 for everything still in the log, then the last
+    /// [`WalTxn::txid`](crate::wal_tail::WalTxn::txid) seen to resume.
+    ///
+    /// A checkpoint rewrites the log; unless the retention policy archived
+    /// them (see [`GraphEngine::set_wal_retention`]), asking for changes from
+    /// before it fails with [`Error::WalHistoryUnavailable`], and the
+    /// consumer has to rebuild from a snapshot and resume from the
+    /// [`visible_txid`](Self::visible_txid) read before taking it.
+    pub fn wal_tail(&self, since_txid: u64) -> Result<WalTail> {
+        self.wal_tail_limited(since_txid, usize::MAX)
+    }
+
+    /// Like [`GraphEngine::wal_tail`], but stops decoding after `max_txns`
+    /// transactions.
+    ///
+    /// The live log is read from the first transaction after `since_txid`
+    /// on; only history from before the last checkpoint is replayed whole,
+    /// from the archive.
+    pub fn wal_tail_limited(&self, since_txid: u64, max_txns: usize) -> Result<WalTail> {
+        let committed = {
+            let mut wal = self.wal.lock().unwrap();
+            if !wal.is_frozen() {
+                let live = wal.tail_after(since_txid)?;
+                if since_txid >= live.start {
+                    // Read just enough; decoding waits until the lock is
+                    // released, as it looks up label names.
+                    let mut committed = Vec::new();
+                    let mut wanted = max_txns;
+                    if let Some(mut reader) = live.reader {
+                        while wanted > 0
+                            && let Some(tx) = reader.next_tx()?
+                        {
+                            if tx.txid > since_txid && wal_tail::has_changes(&tx) {
+                                wanted -= 1;
+                            }
+                            committed.push(tx);
+                        }
+                    }
+                    drop(wal);
+                    return Ok(wal_tail::decode(
+                        committed,
+                        live.names,
+                        since_txid,
+                        max_txns,
+                        |id| self.get_label_name(id),
+                    ));
+                }
+            }
+            let mut committed = if wal.is_frozen() {
+                Vec::new()
+            } else {
+                wal_archive::replay_archived(&self.wal_path)?
+            };
+            committed.extend(wal.replay_committed()?);
+            wal_archive::dedup_txids(committed)
+        };
+        let oldest = wal_tail::tail_start(&committed);
+        if since_txid < oldest {
+            return Err(Error::WalHistoryUnavailable {
+                since: since_txid,
+                oldest,
+            });
+        }
+        Ok(wal_tail::decode(
+            committed,
+            HashMap::new(),
+            since_txid,
+            max_txns,
+            |id| self.get_label_name(id),
+        ))
+    }
+
+    /// Sequence number of the latest commit that changed graph data. Read it
+    /// before taking a snapshot and pass it to [`GraphEngine::changed_since`]
+    /// later to learn whether results computed on that snapshot went stale.
+    pub fn write_seq(&self) -> u64 {
+        self.write_stamps.lock().unwrap().seq()
+    }
+
+    /// Whether a commit after `seq` touched nodes with one of `labels` or
+    /// edges with one of `rel_types`. `None` stands for every label or every
+    /// relationship type.
+    pub fn changed_since(
+        &self,
+        seq: u64,
+        labels: Option<&BTreeSet<String>>,
+        rel_types: Option<&BTreeSet<String>>,
+    ) -> bool {
+        let ids = |names: &BTreeSet<String>| -> Vec<Option<LabelId>> {
+            names.iter().map(|name| self.get_label_id(name)).collect()
+        };
+        let labels = labels.map(ids);
+        let rel_types = rel_types.map(ids);
+        self.write_stamps.lock().unwrap().changed_since(
+            seq,
+            labels.as_deref(),
+            rel_types.as_deref(),
+        )
+    }
+
+    /// Returns the txid of the latest commit visible to new snapshots.
+    pub fn visible_txid(&self) -> u64 {
+        *self.visible_txid.lock().unwrap()
+    }
+
+    /// Blocks until the commit with `txid` is visible to new snapshots or
+    /// `timeout` elapses. Returns whether it became visible.
+    pub fn wait_for_txid(&self, txid: u64, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut visible = self.visible_txid.lock().unwrap();
+        while *visible < txid {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            visible = self
+                .visible_txid_changed
+                .wait_timeout(visible, deadline - now)
+                .unwrap()
+                .0;
+        }
+        true
+    }
+
+    fn publish_visible_txid(&self, txid: u64) {
+        let mut visible = self.visible_txid.lock().unwrap();
+        if txid > *visible {
+            *visible = txid;
+            self.visible_txid_changed.notify_all();
+        }
+    }
+
+    /// Returns the text of the stored query registered as `name`.
+    pub fn named_query(&self, name: &str) -> Option<String> {
+        self.published_named_queries
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+    }
+
+    /// Returns the txid of the committed transaction that carried the
+    /// idempotency key `key`, if any.
+    pub fn idempotency_key_txid(&self, key: &str) -> Option<u64> {
+        self.idempotency_keys.read().unwrap().get(key).copied()
+    }
+
+    /// Returns every stored query, keyed by name.
+    pub fn named_queries(&self) -> Arc<BTreeMap<String, String>> {
+        self.published_named_queries.read().unwrap().clone()
+    }
+
+    /// Returns every label, relationship type and property key in use so far.
+    pub fn schema(&self) -> Arc<SchemaCatalog> {
+        self.published_schema.read().unwrap().clone()
+    }
+
+    fn extend_schema(&self, names: SchemaCatalog) {
+        if self.schema().contains(&names) {
+            return;
+        }
+        let mut published = self.published_schema.write().unwrap();
+        let mut schema = (**published).clone();
+        schema.merge(names);
+        *published = Arc::new(schema);
+    }
+
+    /// Returns the `(label, property)` pairs marked sensitive.
+    pub fn sensitive_properties(&self) -> Arc<BTreeSet<(String, String)>> {
+        self.published_sensitive.read().unwrap().clone()
+    }
+
     pub fn lookup_internal_id(&self, external_id: ExternalId) -> Option<InternalNodeId> {
         lookup_internal_node_id(&self.idmap, external_id)
     }
@@ -225,6 +1431,23 @@ impl GraphEngine {
     ///
     /// This is a write operation and must be called within a write transaction.
     pub fn get_or_create_label(&self, name: &str) -> Result<LabelId> {
+        self.get_or_create_name(NameKind::Label, name)
+    }
+
+    /// Limits new label, relationship type and property key names must meet.
+    pub fn name_limits(&self) -> NameLimits {
+        self.name_limits
+    }
+
+    /// Whether commits stamp system properties, see
+    /// [`EngineOptions::system_properties`].
+    pub fn system_properties(&self) -> bool {
+        self.system_properties
+    }
+
+    /// Interns a label or relationship type name, which share one table.
+    /// Only names not yet in the table are checked against the limits.
+    fn get_or_create_name(&self, kind: NameKind, name: &str) -> Result<LabelId> {
         // Optimistic read
         {
             let interner = self.label_interner.lock().unwrap();
@@ -242,6 +1465,8 @@ impl GraphEngine {
         }
 
         // It's a new label.
+        self.ensure_writable()?;
+        self.name_limits.check(kind, name)?;
         // We update memory first to get the authoritative ID.
         let returned_id = interner.get_or_create(name);
 
@@ -257,6 +1482,7 @@ impl GraphEngine {
             })?;
             wal.append(&WalRecord::CommitTx { txid })?;
             wal.fsync()?;
+            wal.rotate_if_full()?;
         }
 
         // Update Published Snapshot
@@ -291,18 +1517,70 @@ impl GraphEngine {
     }
 
     // T203: HNSW Public API
+    /// Inserts `vector` into the HNSW index right away, outside any
+    /// transaction and without logging it; [`WriteTxn::set_vector`] is the
+    /// logged, transactional way.
     pub fn insert_vector(&self, id: InternalNodeId, vector: Vec<f32>) -> Result<()> {
+        self.ensure_writable()?;
         let mut pager = self.pager.write().unwrap();
         let mut idx = self.vector_index.lock().unwrap();
-        idx.insert(&mut *pager, id, vector)
+        let mut catalog = self.index_catalog.lock().unwrap();
+        apply_vector(&mut pager, &mut catalog, &mut idx, id, vector)
     }
 
+    /// Returns the `k` nearest vectors to `query` with their distances,
+    /// nearest first. Uses the HNSW index unless the engine was opened with
+    /// [`EngineOptions::exact_vector_search`].
     pub fn search_vector(&self, query: &[f32], k: usize) -> Result<Vec<(InternalNodeId, f32)>> {
+        if self.exact_vector_search {
+            return self.search_vector_exact(query, k);
+        }
         let mut pager = self.pager.write().unwrap();
         let mut idx = self.vector_index.lock().unwrap();
         idx.search(&mut *pager, query, k)
     }
 
+    /// Like [`Self::search_vector`] but always scans every stored vector.
+    pub fn search_vector_exact(
+        &self,
+        query: &[f32],
+        k: usize,
+    ) -> Result<Vec<(InternalNodeId, f32)>> {
+        let mut pager = self.pager.write().unwrap();
+        let mut idx = self.vector_index.lock().unwrap();
+        idx.search_exact(&mut *pager, query, k, VectorMetric::L2)
+    }
+
+    /// Returns the `k` nodes nearest to `query` under `metric` for which
+    /// `accept` holds, nearest first.
+    ///
+    /// `accept` runs without engine locks held, so it may read a snapshot.
+    /// L2 searches probe the HNSW index with an enlarged `k` and fall back to
+    /// a full scan when too few candidates pass; other metrics always scan,
+    /// as the index is built for L2.
+    pub fn search_vector_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        accept: &mut dyn FnMut(InternalNodeId) -> bool,
+    ) -> Result<Vec<(InternalNodeId, f32)>> {
+        search_vector_index(
+            &self.pager,
+            &self.vector_index,
+            self.exact_vector_search,
+            query,
+            k,
+            metric,
+            accept,
+        )
+    }
+
+    /// The HNSW tunables the engine was opened with.
+    pub fn hnsw_params(&self) -> HnswParams {
+        *self.vector_index.lock().unwrap().params()
+    }
+
     pub fn scan_i2e_records(&self) -> Vec<I2eRecord> {
         read_i2e_snapshot(&self.idmap)
     }
@@ -321,36 +1599,48 @@ impl GraphEngine {
     /// - Writes CSR segment pages to `.ndb` and fsyncs before publishing the manifest in WAL.
     /// - Writes `ManifestSwitch` + `Checkpoint` as a committed WAL tx to make the switch atomic.
     pub fn compact(&self) -> Result<()> {
+        self.compact_with_progress(&OperationHandle::new(), &mut |_, _| {})
+    }
+
+    /// Like [`GraphEngine::compact`], publishing progress to `handle` and
+    /// calling `progress` with `(done, total)`. The units are one for the
+    /// segment, one per property sunk and one for the manifest switch.
+    ///
+    /// Cancellation is honoured before the segment is written and before
+    /// properties are sunk; the property B-Tree is updated in place, so
+    /// from then on the compaction runs to completion. Pages of a segment
+    /// written by a cancelled compaction stay allocated until the next vacuum.
+    pub fn compact_with_progress(
+        &self,
+        handle: &OperationHandle,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<()> {
+        let mut tracker = Tracker::new(handle, progress);
+        tracker.check()?;
+        self.ensure_writable()?;
         let _guard = self.write_lock.lock().unwrap();
 
         let runs = self.published_runs.read().unwrap().clone();
+        let tier_pass = self.tier_pass_pending.load(Ordering::SeqCst);
 
         if runs.is_empty() {
+            if tier_pass {
+                let root = self.properties_root.load(Ordering::SeqCst);
+                let new_root = {
+                    let mut pager = self.pager.write().unwrap();
+                    self.apply_label_tiers(&mut pager, root, &BTreeSet::new(), true)?
+                };
+                if new_root != root {
+                    self.switch_properties_root(new_root)?;
+                }
+                self.tier_pass_pending.store(false, Ordering::SeqCst);
+            }
+            tracker.start(0);
             return Ok(());
         }
 
         let has_properties = runs.iter().any(|r| r.has_properties());
 
-        let seg_id = SegmentId(self.next_segment_id.fetch_add(1, Ordering::Relaxed));
-        let mut seg = build_segment_from_runs(seg_id, &runs);
-
-        {
-            let mut pager = self.pager.write().unwrap();
-            seg.persist(&mut pager)?;
-            pager.sync()?;
-        }
-
-        let up_to_txid = runs.iter().map(|r| r.txid()).max().unwrap_or(0);
-        let epoch = self.manifest_epoch.load(Ordering::Relaxed) + 1;
-
-        let new_segments = {
-            let current = self.published_segments.read().unwrap().clone();
-            let mut next = Vec::with_capacity(current.len() + 1);
-            next.push(Arc::new(seg));
-            next.extend(current.iter().cloned());
-            Arc::new(next)
-        };
-
         // Property Sinking: Persist properties from L0Runs into the B-Tree Property Store.
         let mut sink_node_props = BTreeMap::new();
         let mut sink_edge_props = BTreeMap::new();
@@ -370,6 +1660,32 @@ impl GraphEngine {
                 }
             }
         }
+        tracker.start(2 + (sink_node_props.len() + sink_edge_props.len()) as u64);
+        let sunk_nodes: BTreeSet<InternalNodeId> =
+            sink_node_props.keys().map(|(node, _)| *node).collect();
+
+        let seg_id = SegmentId(self.next_segment_id.fetch_add(1, Ordering::Relaxed));
+        let mut seg = build_segment_from_runs(seg_id, &runs);
+        tracker.check()?;
+
+        {
+            let mut pager = self.pager.write().unwrap();
+            seg.persist(&mut pager)?;
+            pager.sync()?;
+        }
+        tracker.advance(1);
+
+        let up_to_txid = runs.iter().map(|r| r.txid()).max().unwrap_or(0);
+        let epoch = self.manifest_epoch.load(Ordering::Relaxed) + 1;
+
+        let new_segments = {
+            let current = self.published_segments.read().unwrap().clone();
+            let mut next = Vec::with_capacity(current.len() + 1);
+            next.push(Arc::new(seg));
+            next.extend(current.iter().cloned());
+            Arc::new(next)
+        };
+        tracker.check()?;
 
         let mut current_root = self.properties_root.load(Ordering::SeqCst);
         if !sink_node_props.is_empty() || !sink_edge_props.is_empty() {
@@ -388,9 +1704,10 @@ impl GraphEngine {
                 btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
                 btree_key.extend_from_slice(key.as_bytes());
 
-                let encoded_val = value.encode();
+                let encoded_val = crate::property::encode_stored_value(&value);
                 let blob_id = crate::blob_store::BlobStore::write(&mut pager, &encoded_val)?;
                 tree.insert(&mut pager, &btree_key, blob_id)?;
+                tracker.advance(1);
             }
 
             // Sink Edge Properties (Tag 1)
@@ -403,13 +1720,19 @@ impl GraphEngine {
                 btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
                 btree_key.extend_from_slice(key.as_bytes());
 
-                let encoded_val = value.encode();
+                let encoded_val = crate::property::encode_stored_value(&value);
                 let blob_id = crate::blob_store::BlobStore::write(&mut pager, &encoded_val)?;
                 tree.insert(&mut pager, &btree_key, blob_id)?;
+                tracker.advance(1);
             }
 
             current_root = tree.root().as_u64();
         }
+        {
+            let mut pager = self.pager.write().unwrap();
+            current_root =
+                self.apply_label_tiers(&mut pager, current_root, &sunk_nodes, tier_pass)?;
+        }
 
         // Statistics Collection - read directly from IdMap for accuracy
         let mut stats = crate::stats::GraphStatistics::default();
@@ -432,10 +1755,26 @@ impl GraphEngine {
                 *stats.edge_counts_by_type.entry(edge.rel).or_default() += 1;
             }
         }
+        stats.degrees =
+            crate::stats::degree_stats(new_segments.iter().flat_map(|seg| seg.edge_keys()));
 
         let stats_root;
         {
             let mut pager = self.pager.write().unwrap();
+            stats.history = crate::read_path_stats::read_statistics(
+                &pager,
+                self.stats_root.load(Ordering::SeqCst),
+            )?
+            .history;
+            stats.schema = Some((*self.schema()).clone());
+            stats.record_sample(crate::stats::StatsSample {
+                unix_millis: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis() as u64),
+                total_nodes: stats.total_nodes,
+                total_edges: stats.total_edges,
+                file_bytes: pager.file_bytes()?,
+            });
             let encoded_stats = stats.encode();
             stats_root = crate::blob_store::BlobStore::write(&mut pager, &encoded_stats)?;
         }
@@ -467,6 +1806,7 @@ impl GraphEngine {
             })?;
             wal.append(&WalRecord::CommitTx { txid: system_txid })?;
             wal.fsync()?;
+            wal.rotate_if_full()?;
         }
 
         // 4. Update memory state
@@ -486,20 +1826,222 @@ impl GraphEngine {
         if !has_properties {
             self.checkpoint_txid.store(up_to_txid, Ordering::Relaxed);
         }
+        if tier_pass {
+            self.tier_pass_pending.store(false, Ordering::SeqCst);
+        }
+        tracker.finish();
+        Ok(())
+    }
+
+    /// Moves node properties between the page store rooted at `root` and
+    /// the cold tier to match the label tiers, returning the new root.
+    /// Nodes with a cold label are checked if their properties were just
+    /// sunk (`sunk_nodes`), or all of them with `full_pass`; every node in
+    /// the cold tier is checked for having lost its cold labels.
+    ///
+    /// Records reach the cold file, synced, before the page store entries
+    /// they replace are deleted, and moved-back properties are synced to the
+    /// page store before the cold file drops them, so a crash at any point
+    /// leaves every property readable. Where both hold a property, the page
+    /// store wins.
+    fn apply_label_tiers(
+        &self,
+        pager: &mut Pager,
+        root: u64,
+        sunk_nodes: &BTreeSet<InternalNodeId>,
+        full_pass: bool,
+    ) -> Result<u64> {
+        let cold_labels = self.cold_labels();
+        if cold_labels.is_empty() && self.cold_store.is_empty() {
+            return Ok(root);
+        }
+        let cold_ids: BTreeSet<LabelId> = cold_labels
+            .iter()
+            .filter_map(|name| self.get_label_id(name))
+            .collect();
+        let node_labels = self.published_node_labels.read().unwrap().clone();
+        let is_cold = |node: InternalNodeId| {
+            node_labels
+                .get(node as usize)
+                .is_some_and(|labels| labels.iter().any(|label| cold_ids.contains(label)))
+        };
+
+        // Page store -> cold tier.
+        let candidates: Vec<InternalNodeId> = if full_pass {
+            (0..node_labels.len() as InternalNodeId)
+                .filter(|&node| is_cold(node))
+                .collect()
+        } else {
+            sunk_nodes
+                .iter()
+                .copied()
+                .filter(|&node| is_cold(node))
+                .collect()
+        };
+        let mut records = Vec::new();
+        let mut hot_entries = Vec::new();
+        for node in candidates {
+            let entries = crate::read_path_property_store::node_property_blobs(pager, root, node)?;
+            if entries.is_empty() {
+                continue;
+            }
+            let mut props = self.cold_store.get(node)?.unwrap_or_default();
+            for (_, key, blob_id) in &entries {
+                let bytes = crate::blob_store::BlobStore::read(pager, *blob_id)?;
+                props.insert(key.clone(), crate::property::decode_stored_value(&bytes)?);
+            }
+            records.push((node, props));
+            hot_entries.extend(entries);
+        }
+        self.cold_store.put(&records)?;
+        let mut tree = BTree::load(PageId::new(root));
+        for (btree_key, _, blob_id) in hot_entries {
+            tree.delete(pager, &btree_key, blob_id)?;
+            crate::blob_store::BlobStore::delete(pager, blob_id)?;
+        }
+
+        // Cold tier -> page store, for nodes no longer carrying a cold label.
+        let mut moved_back = Vec::new();
+        for node in self.cold_store.nodes() {
+            if is_cold(node) {
+                continue;
+            }
+            let Some(props) = self.cold_store.get(node)? else {
+                continue;
+            };
+            if root == 0 && tree.root().as_u64() == 0 {
+                tree = BTree::create(pager)?;
+            }
+            let present: BTreeSet<String> = crate::read_path_property_store::node_property_blobs(
+                pager,
+                tree.root().as_u64(),
+                node,
+            )?
+            .into_iter()
+            .map(|(_, key, _)| key)
+            .collect();
+            for (key, value) in props {
+                if present.contains(&key) {
+                    continue;
+                }
+                let mut btree_key = Vec::with_capacity(1 + 4 + 4 + key.len());
+                btree_key.push(0u8); // Tag 0: Node Property
+                btree_key.extend_from_slice(&node.to_be_bytes());
+                btree_key.extend_from_slice(&(key.len() as u32).to_be_bytes());
+                btree_key.extend_from_slice(key.as_bytes());
+                let encoded_val = crate::property::encode_stored_value(&value);
+                let blob_id = crate::blob_store::BlobStore::write(pager, &encoded_val)?;
+                tree.insert(pager, &btree_key, blob_id)?;
+            }
+            moved_back.push(node);
+        }
+        if !moved_back.is_empty() {
+            pager.sync()?;
+            for node in moved_back {
+                self.cold_store.forget(node);
+            }
+        }
+        self.cold_store.rewrite_if_sparse()?;
+        Ok(tree.root().as_u64())
+    }
+
+    /// Logs a manifest that only changes the property root, for
+    /// compactions that had no runs to turn into a segment.
+    fn switch_properties_root(&self, properties_root: u64) -> Result<()> {
+        let segments: Vec<SegmentPointer> = self
+            .published_segments
+            .read()
+            .unwrap()
+            .iter()
+            .map(|s| SegmentPointer {
+                id: s.id.0,
+                meta_page_id: s.meta_page_id,
+            })
+            .collect();
+        let epoch = self.manifest_epoch.load(Ordering::Relaxed) + 1;
+        let stats_root = self.stats_root.load(Ordering::SeqCst);
+        let txid = self.next_txid.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut wal = self.wal.lock().unwrap();
+            wal.append(&WalRecord::BeginTx { txid })?;
+            wal.append(&WalRecord::ManifestSwitch {
+                epoch,
+                segments,
+                properties_root,
+                stats_root,
+            })?;
+            wal.append(&WalRecord::CommitTx { txid })?;
+            wal.fsync()?;
+            wal.rotate_if_full()?;
+        }
+        self.properties_root
+            .store(properties_root, Ordering::SeqCst);
+        self.manifest_epoch.store(epoch, Ordering::Relaxed);
         Ok(())
     }
 
+    /// Full checkpoint: compacts L0 runs into a segment, then rewrites the WAL
+    /// as a snapshot, which also deletes every sealed WAL segment unless the
+    /// retention policy archives them.
+    ///
+    /// If a writer commits between the two steps, the WAL rewrite is skipped
+    /// (see [`GraphEngine::checkpoint_on_close`]) and the next checkpoint
+    /// picks it up.
+    pub fn checkpoint(&self) -> Result<()> {
+        self.compact()?;
+        self.checkpoint_on_close()
+    }
+
+    /// Checkpoints, then writes every page the checkpoint still uses,
+    /// together with the checkpointed WAL, as one compressed packed image at
+    /// `out_path`. Open it with [`GraphEngine::open_packed`].
+    pub fn pack(&self, out_path: impl AsRef<Path>) -> Result<PackReport> {
+        loop {
+            self.checkpoint()?;
+            let _guard = self.write_lock.lock().unwrap();
+            // The image holds pages only; the cold tier would be left behind.
+            if !self.cold_store.is_empty() {
+                return Err(Error::Unsupported(
+                    "cannot pack a database with a cold storage tier",
+                ));
+            }
+            // A commit that lands mid-checkpoint stays WAL-only; checkpoint again.
+            if !self.published_runs.read().unwrap().is_empty() {
+                continue;
+            }
+            let committed = self.wal.lock().unwrap().replay_committed()?;
+            let pager = self.pager.read().unwrap();
+            return crate::packed::write_image(&pager, &committed, out_path.as_ref());
+        }
+    }
+
     /// T106: Checkpoint-on-Close (WAL compaction).
     ///
     /// Safety rule:
     /// - Only allowed when there are no published L0 runs (otherwise we'd lose data that only exists in WAL).
     ///
     /// The resulting WAL contains a single committed tx that replays:
-    /// - label mappings (`CreateLabel`) and
+    /// - label mappings (`CreateLabel`),
+    /// - stored queries (`SaveQuery`),
+    /// - committed idempotency keys (`IdempotencyKey`),
+    /// - sensitive property markers (`SetSensitive`),
+    /// - relationship endpoint constraints (`SetEndpoints`),
+    /// - the WAL retention policy (`SetRetention`, `SetLegalHold`),
+    /// - cold storage tier labels (`SetLabelTier`),
+    /// - namespace bindings (`SetNamespace`) and
     /// - the current manifest (`ManifestSwitch`) plus
     /// - a `Checkpoint` that allows recovery to skip older graph tx.
     pub fn checkpoint_on_close(&self) -> Result<()> {
+        if self.read_only {
+            // Nothing was written, and there is no WAL file to rewrite.
+            return Ok(());
+        }
         let _guard = self.write_lock.lock().unwrap();
+        // The warm page set is only a hint; failing to save it must not fail the close.
+        let _ = save_warm_pages(
+            &self.pager.read().unwrap(),
+            &warm_pages_path(&self.ndb_path),
+        );
 
         let runs = self.published_runs.read().unwrap().clone();
         if !runs.is_empty() {
@@ -550,6 +2092,59 @@ impl GraphEngine {
             }
         }
 
+        for (name, cypher) in self.named_queries().iter() {
+            ops.push(WalRecord::SaveQuery {
+                name: name.clone(),
+                cypher: cypher.clone(),
+            });
+        }
+        for (key, txid) in self.idempotency_keys.read().unwrap().iter() {
+            ops.push(WalRecord::IdempotencyKey {
+                key: key.clone(),
+                txid: *txid,
+            });
+        }
+        for (label, property) in self.sensitive_properties().iter() {
+            ops.push(WalRecord::SetSensitive {
+                label: label.clone(),
+                property: property.clone(),
+                sensitive: true,
+            });
+        }
+        for (rel_type, endpoints) in self.endpoint_constraints().iter() {
+            ops.push(WalRecord::SetEndpoints {
+                rel_type: rel_type.clone(),
+                endpoints: endpoints.iter().cloned().collect(),
+            });
+        }
+
+        let retention = self.wal_retention();
+        if let Some(keep_for) = retention.keep_for {
+            ops.push(WalRecord::SetRetention {
+                keep_millis: Some(u64::try_from(keep_for.as_millis()).unwrap_or(u64::MAX)),
+            });
+        }
+        for (name, placed_at_millis) in &retention.holds {
+            ops.push(WalRecord::SetLegalHold {
+                name: name.clone(),
+                placed_at_millis: *placed_at_millis,
+                held: true,
+            });
+        }
+
+        for label in self.cold_labels().iter() {
+            ops.push(WalRecord::SetLabelTier {
+                label: label.clone(),
+                cold: true,
+            });
+        }
+        for (prefix, iri) in self.namespaces().iter() {
+            ops.push(WalRecord::SetNamespace {
+                prefix: prefix.clone(),
+                iri: Some(iri.clone()),
+            });
+        }
+
         let (properties_root, stats_root) =
             load_properties_and_stats_roots(&self.properties_root, &self.stats_root);
         ops.push(WalRecord::ManifestSwitch {
@@ -570,11 +2165,55 @@ impl GraphEngine {
             wal.rewrite_as_snapshot(system_txid, ops)?;
             wal.fsync()?;
         }
+        wal_archive::purge(&self.wal_path, &retention, SystemTime::now())?;
 
         Ok(())
     }
 }
 
+/// Body of [`GraphEngine::search_vector_filtered`], shared with read
+/// snapshots, which hold the index but not the engine.
+pub(crate) fn search_vector_index(
+    pager: &RwLock<Pager>,
+    index: &Mutex<NativeHnsw>,
+    exact: bool,
+    query: &[f32],
+    k: usize,
+    metric: VectorMetric,
+    accept: &mut dyn FnMut(InternalNodeId) -> bool,
+) -> Result<Vec<(InternalNodeId, f32)>> {
+    if k == 0 {
+        return Ok(Vec::new());
+    }
+    if metric == VectorMetric::L2 && !exact {
+        let fetch = k.saturating_mul(FILTERED_SEARCH_OVERFETCH).max(64);
+        let hits = {
+            let mut pager = pager.write().unwrap();
+            let mut idx = index.lock().unwrap();
+            idx.search(&mut *pager, query, fetch)?
+        };
+        let exhausted = hits.len() < fetch;
+        let accepted: Vec<_> = hits
+            .into_iter()
+            .filter(|(id, _)| accept(*id))
+            .take(k)
+            .collect();
+        if accepted.len() == k || exhausted {
+            return Ok(accepted);
+        }
+    }
+    let ranked = {
+        let mut pager = pager.write().unwrap();
+        let mut idx = index.lock().unwrap();
+        idx.search_exact(&mut *pager, query, usize::MAX, metric)?
+    };
+    Ok(ranked
+        .into_iter()
+        .filter(|(id, _)| accept(*id))
+        .take(k)
+        .collect())
+}
+
 fn build_segment_from_runs(seg_id: SegmentId, runs: &Arc<Vec<Arc<L0Run>>>) -> CsrSegment {
     // Apply the same semantics as snapshot merge: newest->oldest, key-based tombstones.
     use std::collections::{BTreeMap, HashSet};
@@ -663,11 +2302,85 @@ pub struct WriteTxn<'a> {
     created_nodes: Vec<(ExternalId, LabelId, InternalNodeId)>,
     pending_label_additions: Vec<(InternalNodeId, LabelId)>,
     pending_label_removals: Vec<(InternalNodeId, LabelId)>,
-    created_external_ids: std::collections::HashSet<ExternalId>,
+    created_external_ids: std::collections::HashMap<ExternalId, InternalNodeId>,
+    pending_vectors: Vec<(InternalNodeId, Vec<f32>)>,
+    pending_named_queries: Vec<(String, Option<String>)>,
+    /// `(label, property, sensitive)` changes to the sensitive property set.
+    pending_sensitive: Vec<(String, String, bool)>,
+    idempotency_key: Option<String>,
+    unique_claims: UniqueClaims,
+    /// Whether unique constraints are checked at commit instead of per write.
+    constraints_deferred: bool,
+    /// `(node, property)` pairs written while checks were deferred.
+    deferred_unique: BTreeSet<(InternalNodeId, String)>,
+    /// Catalog entries renamed along with the properties this transaction moves.
+    pending_index_renames: Vec<Rename>,
+    memtable: MemTable,
+}
+
+/// The staged changes of a [`WriteTxn`] at one point, captured by
+/// [`WriteTxn::savepoint`] and restored by [`WriteTxn::rollback_to`].
+#[derive(Debug, Clone)]
+pub struct Savepoint {
+    txid: u64,
+    created_nodes: Vec<(ExternalId, LabelId, InternalNodeId)>,
+    pending_label_additions: Vec<(InternalNodeId, LabelId)>,
+    pending_label_removals: Vec<(InternalNodeId, LabelId)>,
+    created_external_ids: std::collections::HashMap<ExternalId, InternalNodeId>,
+    pending_vectors: Vec<(InternalNodeId, Vec<f32>)>,
+    pending_named_queries: Vec<(String, Option<String>)>,
+    pending_sensitive: Vec<(String, String, bool)>,
+    unique_claims: UniqueClaims,
+    deferred_unique: BTreeSet<(InternalNodeId, String)>,
+    pending_index_renames: Vec<Rename>,
     memtable: MemTable,
 }
 
 impl<'a> WriteTxn<'a> {
+    /// Captures the changes staged so far. Cost is proportional to their size.
+    pub fn savepoint(&self) -> Savepoint {
+        Savepoint {
+            txid: self.txid,
+            created_nodes: self.created_nodes.clone(),
+            pending_label_additions: self.pending_label_additions.clone(),
+            pending_label_removals: self.pending_label_removals.clone(),
+            created_external_ids: self.created_external_ids.clone(),
+            pending_vectors: self.pending_vectors.clone(),
+            pending_named_queries: self.pending_named_queries.clone(),
+            pending_sensitive: self.pending_sensitive.clone(),
+            unique_claims: self.unique_claims.clone(),
+            deferred_unique: self.deferred_unique.clone(),
+            pending_index_renames: self.pending_index_renames.clone(),
+            memtable: self.memtable.clone(),
+        }
+    }
+
+    /// Discards every change staged after `savepoint` was taken, keeping the
+    /// ones before it. The idempotency key, if set, is kept.
+    ///
+    /// # Errors
+    /// Fails if `savepoint` was taken in another transaction.
+    pub fn rollback_to(&mut self, savepoint: &Savepoint) -> Result<()> {
+        if savepoint.txid != self.txid {
+            return Err(Error::WalProtocol(
+                "savepoint belongs to another transaction",
+            ));
+        }
+        let savepoint = savepoint.clone();
+        self.created_nodes = savepoint.created_nodes;
+        self.pending_label_additions = savepoint.pending_label_additions;
+        self.pending_label_removals = savepoint.pending_label_removals;
+        self.created_external_ids = savepoint.created_external_ids;
+        self.pending_vectors = savepoint.pending_vectors;
+        self.pending_named_queries = savepoint.pending_named_queries;
+        self.pending_sensitive = savepoint.pending_sensitive;
+        self.unique_claims = savepoint.unique_claims;
+        self.deferred_unique = savepoint.deferred_unique;
+        self.pending_index_renames = savepoint.pending_index_renames;
+        self.memtable = savepoint.memtable;
+        Ok(())
+    }
+
     pub fn create_node(
         &mut self,
         external_id: ExternalId,
@@ -677,22 +2390,61 @@ impl<'a> WriteTxn<'a> {
             return Err(Error::WalProtocol("external id already exists"));
         }
 
-        if !self.created_external_ids.insert(external_id) {
+        if self.created_external_ids.contains_key(&external_id) {
             return Err(Error::WalProtocol("duplicate external id in same tx"));
         }
 
         let base_next = {
             let idmap = self.engine.idmap.lock().unwrap();
-            idmap.next_internal_id()
+            idmap.len()
         };
-        let internal_id = base_next + self.created_nodes.len() as u32;
+        let internal_id = internal_node_id(base_next + self.created_nodes.len() as u64)?;
 
+        self.created_external_ids.insert(external_id, internal_id);
         self.created_nodes
             .push((external_id, label_id, internal_id));
         Ok(internal_id)
     }
 
+    /// The node with `external_id`, whether committed or created earlier in
+    /// this transaction.
+    pub fn lookup_internal_id(&self, external_id: ExternalId) -> Option<InternalNodeId> {
+        self.created_external_ids
+            .get(&external_id)
+            .copied()
+            .or_else(|| self.engine.lookup_internal_id(external_id))
+    }
+
     pub fn add_node_label(&mut self, node: InternalNodeId, label_id: LabelId) -> Result<()> {
+        let properties: Vec<String> = match self.engine.get_label_name(label_id) {
+            Some(label) => self
+                .engine
+                .index_catalog
+                .lock()
+                .unwrap()
+                .unique_for_label(&label)
+                .map(|(_, _, key)| key.properties[0].clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        if self.constraints_deferred {
+            self.deferred_unique
+                .extend(properties.into_iter().map(|property| (node, property)));
+        } else if !properties.is_empty() {
+            use crate::read_path_convert::convert_property_to_storage as to_storage;
+            let snapshot = self.engine.snapshot();
+            let is_new = self.created_nodes.iter().any(|(_, _, iid)| *iid == node);
+            for property in properties {
+                let value = match self.memtable.staged_node_property(node, &property) {
+                    Some(staged) => staged.cloned(),
+                    None if is_new => None,
+                    None => snapshot.node_property(node, &property).map(to_storage),
+                };
+                if let Some(value) = value {
+                    self.claim_unique(node, &property, &value, Some(label_id))?;
+                }
+            }
+        }
         self.pending_label_additions.push((node, label_id));
         Ok(())
     }
@@ -712,10 +2464,11 @@ impl<'a> WriteTxn<'a> {
 
     pub fn get_or_create_rel_type(&self, name: &str) -> Result<RelTypeId> {
         // Reuse label interner for relationship types for now
-        self.engine.get_or_create_label(name)
+        self.engine.get_or_create_name(NameKind::RelType, name)
     }
 
     pub fn tombstone_node(&mut self, node: InternalNodeId) {
+        self.unique_claims.release_node(node);
         self.memtable.tombstone_node(node);
     }
 
@@ -723,13 +2476,24 @@ impl<'a> WriteTxn<'a> {
         self.memtable.tombstone_edge(src, rel, dst);
     }
 
+    /// Stages a node property, failing with [`Error::ConstraintViolation`] if
+    /// the value would break a unique constraint (at commit, while checks
+    /// are deferred).
     pub fn set_node_property(
         &mut self,
         node: InternalNodeId,
         key: String,
         value: crate::property::PropertyValue,
-    ) {
+    ) -> Result<()> {
+        self.engine.name_limits.check(NameKind::PropertyKey, &key)?;
+        crate::names::check_writable_property(&key)?;
+        if self.constraints_deferred {
+            self.deferred_unique.insert((node, key.clone()));
+        } else {
+            self.claim_unique(node, &key, &value, None)?;
+        }
         self.memtable.set_node_property(node, key, value);
+        Ok(())
     }
 
     pub fn set_edge_property(
@@ -739,12 +2503,46 @@ impl<'a> WriteTxn<'a> {
         dst: InternalNodeId,
         key: String,
         value: crate::property::PropertyValue,
-    ) {
+    ) -> Result<()> {
+        self.engine.name_limits.check(NameKind::PropertyKey, &key)?;
+        crate::names::check_writable_property(&key)?;
+        self.memtable.set_edge_property(src, rel, dst, key, value);
+        Ok(())
+    }
+
+    /// Stages a system property copied from another database's log, as a
+    /// follower replays a primary's `__updated_tx`. Fails unless `key` is a
+    /// system property; user keys go through [`WriteTxn::set_node_property`].
+    pub fn set_system_node_property(
+        &mut self,
+        node: InternalNodeId,
+        key: String,
+        value: crate::property::PropertyValue,
+    ) -> Result<()> {
+        crate::names::check_system_property(&key)?;
+        self.memtable.set_node_property(node, key, value);
+        Ok(())
+    }
+
+    /// The edge counterpart of [`WriteTxn::set_system_node_property`].
+    pub fn set_system_edge_property(
+        &mut self,
+        src: InternalNodeId,
+        rel: RelTypeId,
+        dst: InternalNodeId,
+        key: String,
+        value: crate::property::PropertyValue,
+    ) -> Result<()> {
+        crate::names::check_system_property(&key)?;
         self.memtable.set_edge_property(src, rel, dst, key, value);
+        Ok(())
     }
 
-    pub fn remove_node_property(&mut self, node: InternalNodeId, key: &str) {
+    pub fn remove_node_property(&mut self, node: InternalNodeId, key: &str) -> Result<()> {
+        crate::names::check_writable_property(key)?;
+        self.unique_claims.release(node, key);
         self.memtable.remove_node_property(node, key);
+        Ok(())
     }
 
     pub fn remove_edge_property(
@@ -753,8 +2551,261 @@ impl<'a> WriteTxn<'a> {
         rel: RelTypeId,
         dst: InternalNodeId,
         key: &str,
-    ) {
+    ) -> Result<()> {
+        crate::names::check_writable_property(key)?;
         self.memtable.remove_edge_property(src, rel, dst, key);
+        Ok(())
+    }
+
+    /// Labels `node` carries once this transaction commits.
+    fn staged_labels(&self, node: InternalNodeId, snapshot: &Snapshot) -> Vec<LabelId> {
+        let mut labels = match self.created_nodes.iter().find(|(_, _, iid)| *iid == node) {
+            Some((_, label, _)) => vec![*label],
+            None => snapshot.node_labels(node).unwrap_or_default(),
+        };
+        labels.extend(
+            self.pending_label_additions
+                .iter()
+                .filter(|(n, _)| *n == node)
+                .map(|(_, l)| *l),
+        );
+        labels.retain(|label| !self.pending_label_removals.contains(&(node, *label)));
+        labels
+    }
+
+    /// Checks the relationships created in this transaction against the
+    /// endpoint constraints on their types.
+    fn check_endpoints(&self) -> Result<()> {
+        let constraints = self.engine.endpoint_constraints();
+        if constraints.is_empty() {
+            return Ok(());
+        }
+        let snapshot = self.engine.begin_read();
+        let names = |node| -> Vec<String> {
+            self.staged_labels(node, &snapshot)
+                .into_iter()
+                .filter_map(|label| self.engine.get_label_name(label))
+                .collect()
+        };
+        for edge in self.memtable.created_edges() {
+            let Some(rel_type) = self.engine.get_label_name(edge.rel) else {
+                continue;
+            };
+            let Some(allowed) = constraints.get(&rel_type) else {
+                continue;
+            };
+            let (src_labels, dst_labels) = (names(edge.src), names(edge.dst));
+            if !endpoints_allowed(allowed, &src_labels, &dst_labels) {
+                return Err(Error::ConstraintViolation(endpoint_violation(
+                    &rel_type,
+                    allowed,
+                    edge,
+                    &src_labels,
+                    &dst_labels,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Defers unique constraint checks on the writes staged from now on to
+    /// commit, or with `false`, checks the writes deferred so far and goes
+    /// back to checking each write as it is staged.
+    ///
+    /// While deferred, a multi-step change such as swapping two nodes'
+    /// unique values may pass through states that break a constraint, as
+    /// long as the state it commits does not.
+    pub fn set_constraints_deferred(&mut self, deferred: bool) -> Result<()> {
+        self.constraints_deferred = deferred;
+        if deferred {
+            Ok(())
+        } else {
+            self.check_deferred_unique()
+        }
+    }
+
+    /// Checks the final values of the writes whose unique checks were
+    /// deferred and claims them. On a violation they stay deferred, so
+    /// commit fails too.
+    fn check_deferred_unique(&mut self) -> Result<()> {
+        use crate::read_path_convert::convert_property_to_storage as to_storage;
+        if self.deferred_unique.is_empty() {
+            return Ok(());
+        }
+        let deferred = std::mem::take(&mut self.deferred_unique);
+        for (node, property) in &deferred {
+            self.unique_claims.release(*node, property);
+        }
+        let snapshot = self.engine.snapshot();
+        for (node, property) in &deferred {
+            if self.memtable.is_node_tombstoned(*node) {
+                continue;
+            }
+            let is_new = self.created_nodes.iter().any(|(_, _, iid)| iid == node);
+            let value = match self.memtable.staged_node_property(*node, property) {
+                Some(staged) => staged.cloned(),
+                None if is_new => None,
+                None => snapshot.node_property(*node, property).map(to_storage),
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            if let Err(e) = self.claim_unique(*node, property, &value, None) {
+                self.deferred_unique = deferred;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks `node.property = value` against the unique constraints on
+    /// `node`'s labels (or only on `only_label`) and claims the value,
+    /// replacing what `node` held for `property` unless `only_label` is set.
+    ///
+    /// A committed holder of the same value only conflicts while it is alive,
+    /// keeps the label and has not had the property changed in this transaction.
+    fn claim_unique(
+        &mut self,
+        node: InternalNodeId,
+        property: &str,
+        value: &crate::property::PropertyValue,
+        only_label: Option<LabelId>,
+    ) -> Result<()> {
+        let claims = if matches!(value, crate::property::PropertyValue::Null) {
+            Vec::new()
+        } else {
+            self.check_unique(node, property, value, only_label)?
+        };
+        if only_label.is_none() {
+            self.unique_claims.release(node, property);
+        }
+        let encoded = encode_ordered_value(value);
+        for name in claims {
+            self.unique_claims
+                .claim(&name, node, property, encoded.clone());
+        }
+        Ok(())
+    }
+
+    /// Returns the constraints `node.property = value` falls under, or the
+    /// violation it would cause.
+    fn check_unique(
+        &self,
+        node: InternalNodeId,
+        property: &str,
+        value: &crate::property::PropertyValue,
+        only_label: Option<LabelId>,
+    ) -> Result<Vec<String>> {
+        let constraints: Vec<(String, String, crate::index::catalog::IndexDef)> = self
+            .engine
+            .index_catalog
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .filter_map(|(name, def)| {
+                let key = def.composite.as_ref()?;
+                (key.unique && key.properties[0] == property)
+                    .then(|| (name.clone(), key.label.clone(), def.clone()))
+            })
+            .collect();
+        if constraints.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let snapshot = self.engine.begin_read();
+        let labels = self.staged_labels(node, &snapshot);
+        let encoded = encode_ordered_value(value);
+        let mut claims = Vec::new();
+        for (name, label, def) in constraints {
+            let Some(label_id) = self.engine.get_label_id(&label) else {
+                continue;
+            };
+            let applies = match only_label {
+                Some(only) => only == label_id,
+                None => labels.contains(&label_id),
+            };
+            if !applies {
+                continue;
+            }
+            let violation = || {
+                Error::ConstraintViolation(format!(
+                    "{name} is unique, another node already has {property} = {value:?}"
+                ))
+            };
+            if self
+                .unique_claims
+                .owner(&name, &encoded)
+                .is_some_and(|owner| owner != node)
+            {
+                return Err(violation());
+            }
+            let holders = {
+                let pager = self.engine.pager.read().unwrap();
+                lookup_prefix(&BTree::load(def.root), &pager, def.id, &[value])?
+            };
+            for holder in holders {
+                let conflicts = holder != node
+                    && !snapshot.is_tombstoned_node(holder)
+                    && !self.memtable.is_node_tombstoned(holder)
+                    && self
+                        .memtable
+                        .staged_node_property(holder, property)
+                        .is_none()
+                    && self.staged_labels(holder, &snapshot).contains(&label_id);
+                if conflicts {
+                    return Err(violation());
+                }
+            }
+            claims.push(name);
+        }
+        Ok(claims)
+    }
+
+    /// A snapshot of the committed state with this transaction's staged
+    /// graph changes applied, so statements later in the transaction read
+    /// its earlier writes. Staged vectors stay unsearchable until commit.
+    ///
+    /// It copies the staged changes; take a new one after further writes.
+    pub fn staged_snapshot(&self) -> crate::api::StorageSnapshot {
+        let mut runs = vec![Arc::new(self.memtable.clone().freeze_into_run(self.txid))];
+        runs.extend(self.engine.published_runs.read().unwrap().iter().cloned());
+        let segments = self.engine.published_segments.read().unwrap().clone();
+        let labels = self.engine.published_labels.read().unwrap().clone();
+        let mut node_labels = (**self.engine.published_node_labels.read().unwrap()).clone();
+        let mut i2e = self.engine.scan_i2e_records();
+        for (external_id, label_id, _) in &self.created_nodes {
+            node_labels.push(vec![*label_id]);
+            i2e.push(I2eRecord {
+                external_id: *external_id,
+                label_id: *label_id,
+                flags: 0,
+            });
+        }
+        for (node, label_id) in &self.pending_label_additions {
+            if let Some(labels) = node_labels.get_mut(*node as usize)
+                && !labels.contains(label_id)
+            {
+                labels.push(*label_id);
+                labels.sort_unstable();
+            }
+        }
+        for (node, label_id) in &self.pending_label_removals {
+            if let Some(labels) = node_labels.get_mut(*node as usize) {
+                labels.retain(|l| l != label_id);
+            }
+        }
+        let (properties_root, stats_root) =
+            load_properties_and_stats_roots(&self.engine.properties_root, &self.engine.stats_root);
+        let inner = build_snapshot_from_published(
+            Arc::new(runs),
+            segments,
+            labels,
+            Arc::new(node_labels),
+            properties_root,
+            stats_root,
+        );
+        crate::api::StorageSnapshot::from_parts(self.engine, inner, i2e)
     }
 
     pub fn staged_created_nodes_with_labels(&self) -> Vec<(InternalNodeId, Vec<String>)> {
@@ -802,98 +2853,288 @@ impl<'a> WriteTxn<'a> {
     }
 
     // T203: HNSW Support
+    /// Stages a vector for `id`; it is logged with the commit and reaches
+    /// the HNSW index once the commit is durable.
     pub fn set_vector(&mut self, id: InternalNodeId, vector: Vec<f32>) -> Result<()> {
-        self.engine.insert_vector(id, vector)
+        self.pending_vectors.push((id, vector));
+        Ok(())
+    }
+
+    /// Stages `cypher` under `name` in the stored query catalog, replacing any
+    /// previous query with that name. The text is stored as given; callers
+    /// validate it before saving.
+    pub fn save_query(&mut self, name: &str, cypher: &str) {
+        self.pending_named_queries
+            .push((name.to_string(), Some(cypher.to_string())));
+    }
+
+    /// Stages removal of the stored query `name`, returning whether it existed.
+    pub fn drop_query(&mut self, name: &str) -> bool {
+        let existed = match self
+            .pending_named_queries
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+        {
+            Some((_, staged)) => staged.is_some(),
+            None => self.engine.named_query(name).is_some(),
+        };
+        if existed {
+            self.pending_named_queries.push((name.to_string(), None));
+        }
+        existed
+    }
+
+    /// Stages marking (or unmarking) `label.property` as sensitive, returning
+    /// whether that changes the set.
+    pub fn set_sensitive(&mut self, label: &str, property: &str, sensitive: bool) -> bool {
+        let current = match self
+            .pending_sensitive
+            .iter()
+            .rev()
+            .find(|(l, p, _)| l == label && p == property)
+        {
+            Some((_, _, staged)) => *staged,
+            None => self
+                .engine
+                .sensitive_properties()
+                .contains(&(label.to_string(), property.to_string())),
+        };
+        if current == sensitive {
+            return false;
+        }
+        self.pending_sensitive
+            .push((label.to_string(), property.to_string(), sensitive));
+        true
+    }
+
+    /// Tags the transaction with a client-supplied idempotency key, recorded
+    /// durably on commit.
+    ///
+    /// Returns the txid of an earlier committed transaction with the same key;
+    /// in that case `commit` discards this transaction's changes and reports
+    /// the earlier txid, so a retried request is applied at most once.
+    pub fn set_idempotency_key(&mut self, key: &str) -> Option<u64> {
+        self.idempotency_key = Some(key.to_string());
+        self.engine.idempotency_key_txid(key)
+    }
+
+    /// The txid this transaction commits under; it becomes the engine's
+    /// [`GraphEngine::visible_txid`] once `commit` returns.
+    pub fn txid(&self) -> u64 {
+        self.txid
     }
 
     pub fn commit(self) -> Result<()> {
+        self.commit_with_txid().map(|_| ())
+    }
+
+    /// Commits and returns the txid the changes are visible under: this
+    /// transaction's, or the earlier one's when its idempotency key was
+    /// already committed (the changes are then discarded).
+    pub fn commit_with_txid(self) -> Result<u64> {
+        self.commit_recording(false).map(|(txid, _)| txid)
+    }
+
+    /// Commits like [`WriteTxn::commit_with_txid`] and also returns the
+    /// graph changes it wrote, decoded as [`GraphEngine::wal_tail`] would.
+    /// The changes are empty when the idempotency key was already committed.
+    pub fn commit_with_changes(self) -> Result<WalTxn> {
+        let engine = self.engine;
+        let (txid, ops) = self.commit_recording(true)?;
+        let committed = vec![CommittedTx { txid, ops }];
+        let tail = wal_tail::decode(committed, HashMap::new(), 0, usize::MAX, |id| {
+            engine.get_label_name(id)
+        });
+        Ok(tail.into_iter().next().unwrap_or(WalTxn {
+            txid,
+            changes: Vec::new(),
+        }))
+    }
+
+    fn commit_recording(mut self, record: bool) -> Result<(u64, Vec<WalRecord>)> {
+        self.engine.ensure_writable()?;
+        self.engine.check_write_backlog()?;
+        let txid = self.txid;
+        if let Some(key) = &self.idempotency_key
+            && let Some(applied) = self.engine.idempotency_key_txid(key)
+        {
+            return Ok((applied, Vec::new()));
+        }
+
+        if self.engine.system_properties {
+            self.stamp_system_properties();
+        }
+        self.check_deferred_unique()?;
+        self.check_endpoints()?;
+
         // Extract property data before freezing (since freeze consumes memtable)
         let node_properties = self.memtable.node_properties_for_wal();
         let edge_properties = self.memtable.edge_properties_for_wal();
         let removed_node_props = self.memtable.removed_node_properties_for_wal();
         let removed_edge_props = self.memtable.removed_edge_properties_for_wal();
 
-        let run = self.memtable.freeze_into_run(self.txid);
+        let run = std::mem::take(&mut self.memtable).freeze_into_run(self.txid);
+        let touched = self.touched(&run);
 
+        let mut recorded = Vec::new();
         // 1) Append WAL and fsync (durability Full by default).
         {
             let mut wal = self.engine.wal.lock().unwrap();
             wal.append(&WalRecord::BeginTx { txid: self.txid })?;
+            // Graph changes are also kept for `commit_with_changes`.
+            let mut append = |wal: &mut Wal, op: WalRecord| -> Result<()> {
+                wal.append(&op)?;
+                if record {
+                    recorded.push(op);
+                }
+                Ok(())
+            };
 
             for (external_id, label_id, internal_id) in &self.created_nodes {
-                wal.append(&WalRecord::CreateNode {
-                    external_id: *external_id,
-                    label_id: *label_id,
-                    internal_id: *internal_id,
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::CreateNode {
+                        external_id: *external_id,
+                        label_id: *label_id,
+                        internal_id: *internal_id,
+                    },
+                )?;
             }
             for (node, label_id) in &self.pending_label_additions {
-                wal.append(&WalRecord::AddNodeLabel {
-                    node: *node,
-                    label_id: *label_id,
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::AddNodeLabel {
+                        node: *node,
+                        label_id: *label_id,
+                    },
+                )?;
             }
             for (node, label_id) in &self.pending_label_removals {
-                wal.append(&WalRecord::RemoveNodeLabel {
-                    node: *node,
-                    label_id: *label_id,
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::RemoveNodeLabel {
+                        node: *node,
+                        label_id: *label_id,
+                    },
+                )?;
             }
 
             for edge in run.iter_edges() {
-                wal.append(&WalRecord::CreateEdge {
-                    src: edge.src,
-                    rel: edge.rel,
-                    dst: edge.dst,
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::CreateEdge {
+                        src: edge.src,
+                        rel: edge.rel,
+                        dst: edge.dst,
+                    },
+                )?;
             }
             for node in run.iter_tombstoned_nodes() {
-                wal.append(&WalRecord::TombstoneNode { node })?;
+                append(&mut wal, WalRecord::TombstoneNode { node })?;
             }
             for edge in run.iter_tombstoned_edges() {
-                wal.append(&WalRecord::TombstoneEdge {
-                    src: edge.src,
-                    rel: edge.rel,
-                    dst: edge.dst,
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::TombstoneEdge {
+                        src: edge.src,
+                        rel: edge.rel,
+                        dst: edge.dst,
+                    },
+                )?;
             }
 
             // Write property operations
             // Write property operations
             for (node, key, value) in &node_properties {
-                wal.append(&WalRecord::SetNodeProperty {
-                    node: *node,
-                    key: key.clone(),
-                    value: value.clone(),
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::SetNodeProperty {
+                        node: *node,
+                        key: key.clone(),
+                        value: value.clone(),
+                    },
+                )?;
             }
             // Removed Node properties
             for (node, key) in &removed_node_props {
-                wal.append(&WalRecord::RemoveNodeProperty {
-                    node: *node,
-                    key: key.clone(),
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::RemoveNodeProperty {
+                        node: *node,
+                        key: key.clone(),
+                    },
+                )?;
             }
 
             for (src, rel, dst, key, value) in edge_properties {
-                wal.append(&WalRecord::SetEdgeProperty {
-                    src,
-                    rel,
-                    dst,
-                    key,
-                    value,
-                })?;
+                append(
+                    &mut wal,
+                    WalRecord::SetEdgeProperty {
+                        src,
+                        rel,
+                        dst,
+                        key,
+                        value,
+                    },
+                )?;
             }
             // Removed Edge properties
             for (src, rel, dst, key) in &removed_edge_props {
-                wal.append(&WalRecord::RemoveEdgeProperty {
-                    src: *src,
-                    rel: *rel,
-                    dst: *dst,
+                append(
+                    &mut wal,
+                    WalRecord::RemoveEdgeProperty {
+                        src: *src,
+                        rel: *rel,
+                        dst: *dst,
+                        key: key.clone(),
+                    },
+                )?;
+            }
+
+            if let Some(key) = &self.idempotency_key {
+                wal.append(&WalRecord::IdempotencyKey {
                     key: key.clone(),
+                    txid: self.txid,
+                })?;
+            }
+
+            for (label, property, sensitive) in &self.pending_sensitive {
+                wal.append(&WalRecord::SetSensitive {
+                    label: label.clone(),
+                    property: property.clone(),
+                    sensitive: *sensitive,
+                })?;
+            }
+
+            for (name, cypher) in &self.pending_named_queries {
+                wal.append(&match cypher {
+                    Some(cypher) => WalRecord::SaveQuery {
+                        name: name.clone(),
+                        cypher: cypher.clone(),
+                    },
+                    None => WalRecord::DropQuery { name: name.clone() },
+                })?;
+            }
+
+            // Replayed on open, so a vector the index never received before
+            // a crash is not lost.
+            for (node, vector) in &self.pending_vectors {
+                wal.append(&WalRecord::SetVector {
+                    node: *node,
+                    vector: vector.clone(),
                 })?;
             }
 
+            // Renamed indexes already describe the committed state; keep them
+            // out of maintenance and re-attach them under their new names.
+            let renamed_indexes = if self.pending_index_renames.is_empty() {
+                Vec::new()
+            } else {
+                let renames = self.pending_index_renames;
+                self.engine.index_catalog.lock().unwrap().detach(renames)
+            };
+
             // T107/T108: Update Indexes
             // We separate Read (Old Values) phase from Write (Index Update) phase to avoid deadlocks
             // caused by holding pager lock during property lookup.
@@ -905,6 +3146,16 @@ impl<'a> WriteTxn<'a> {
                     crate::property::PropertyValue,
                 ),
                 Remove(String, Option<crate::property::PropertyValue>),
+                Text(
+                    String,
+                    Option<crate::property::PropertyValue>,
+                    Option<crate::property::PropertyValue>,
+                ),
+                Composite(
+                    String,
+                    Option<Vec<crate::property::PropertyValue>>,
+                    Option<Vec<crate::property::PropertyValue>>,
+                ),
             }
             let mut index_ops = Vec::new();
 
@@ -958,6 +3209,26 @@ impl<'a> WriteTxn<'a> {
                                 ));
                             }
                         }
+
+                        let text_index = text_index_name(&label_name, key);
+                        let has_text_index = self
+                            .engine
+                            .index_catalog
+                            .lock()
+                            .unwrap()
+                            .get(&text_index)
+                            .is_some();
+                        if has_text_index {
+                            let old_value = if is_new {
+                                None
+                            } else {
+                                snapshot.node_property(*node, key).map(to_storage)
+                            };
+                            index_ops.push((
+                                IndexOp::Text(text_index, old_value, Some(value.clone())),
+                                *node,
+                            ));
+                        }
                     }
                 }
             }
@@ -995,6 +3266,115 @@ impl<'a> WriteTxn<'a> {
                             let old_value = snapshot.node_property(*node, key).map(to_storage);
                             index_ops.push((IndexOp::Remove(index_name, old_value), *node));
                         }
+
+                        let text_index = text_index_name(&label_name, key);
+                        let has_text_index = self
+                            .engine
+                            .index_catalog
+                            .lock()
+                            .unwrap()
+                            .get(&text_index)
+                            .is_some();
+                        if has_text_index {
+                            let old_value = snapshot.node_property(*node, key).map(to_storage);
+                            index_ops.push((IndexOp::Text(text_index, old_value, None), *node));
+                        }
+                    }
+                }
+            }
+
+            // Composite indexes: recompute the full tuple of every touched node
+            // that carries (or gains or loses) the index's label.
+            let composites: Vec<(String, LabelId, Vec<String>)> = self
+                .engine
+                .index_catalog
+                .lock()
+                .unwrap()
+                .entries
+                .iter()
+                .filter_map(|(name, def)| {
+                    let key = def.composite.as_ref()?;
+                    let label_id = self.engine.get_label_id(&key.label)?;
+                    Some((name.clone(), label_id, key.properties.clone()))
+                })
+                .collect();
+            if !composites.is_empty() {
+                let mut touched: BTreeMap<InternalNodeId, BTreeMap<&str, Option<&_>>> =
+                    BTreeMap::new();
+                for (node, key, value) in &node_properties {
+                    touched
+                        .entry(*node)
+                        .or_default()
+                        .insert(key.as_str(), Some(value));
+                }
+                for (node, key) in &removed_node_props {
+                    touched.entry(*node).or_default().insert(key.as_str(), None);
+                }
+                for (node, _) in self
+                    .pending_label_additions
+                    .iter()
+                    .chain(&self.pending_label_removals)
+                {
+                    touched.entry(*node).or_default();
+                }
+
+                for (node, changes) in &touched {
+                    let created_label = self
+                        .created_nodes
+                        .iter()
+                        .find(|(_, _, iid)| iid == node)
+                        .map(|(_, l, _)| *l);
+                    let is_new = created_label.is_some();
+                    let stored_labels = if is_new {
+                        Vec::new()
+                    } else {
+                        snapshot.resolve_node_labels(*node).unwrap_or_default()
+                    };
+                    let mut labels = stored_labels.clone();
+                    labels.extend(created_label);
+                    labels.extend(
+                        self.pending_label_additions
+                            .iter()
+                            .filter(|(n, _)| n == node)
+                            .map(|(_, l)| *l),
+                    );
+                    labels.retain(|label| !self.pending_label_removals.contains(&(*node, *label)));
+
+                    let stored = |field: &str| {
+                        if is_new {
+                            None
+                        } else {
+                            snapshot.node_property(*node, field).map(to_storage)
+                        }
+                    };
+                    for (name, label_id, properties) in &composites {
+                        let had_label = stored_labels.contains(label_id);
+                        let has_label = labels.contains(label_id);
+                        if had_label == has_label
+                            && (!has_label
+                                || !properties.iter().any(|p| changes.contains_key(p.as_str())))
+                        {
+                            continue;
+                        }
+                        let old = if had_label {
+                            indexable_tuple(properties.iter().map(|p| stored(p)).collect())
+                        } else {
+                            None
+                        };
+                        let new = if has_label {
+                            indexable_tuple(
+                                properties
+                                    .iter()
+                                    .map(|p| match changes.get(p.as_str()) {
+                                        Some(change) => change.cloned(),
+                                        None => stored(p),
+                                    })
+                                    .collect(),
+                            )
+                        } else {
+                            None
+                        };
+                        index_ops.push((IndexOp::Composite(name.clone(), old, new), *node));
                     }
                 }
             }
@@ -1053,17 +3433,64 @@ impl<'a> WriteTxn<'a> {
                                 }
                             }
                         }
+                        IndexOp::Text(name, old_val, new_val) => {
+                            if let Some(re) = catalog.entries.get_mut(&name) {
+                                use crate::property::PropertyValue::String as Text;
+                                let old_text = match &old_val {
+                                    Some(Text(s)) => Some(s.as_str()),
+                                    _ => None,
+                                };
+                                let new_text = match &new_val {
+                                    Some(Text(s)) => Some(s.as_str()),
+                                    _ => None,
+                                };
+                                let mut tree = crate::index::btree::BTree::load(re.root);
+                                update_text(
+                                    &mut tree, &mut pager, re.id, node_id, old_text, new_text,
+                                )?;
+                                re.root = tree.root();
+                            }
+                        }
+                        IndexOp::Composite(name, old, new) => {
+                            if let Some(re) = catalog.entries.get_mut(&name) {
+                                let mut tree = crate::index::btree::BTree::load(re.root);
+                                update_composite(
+                                    &mut tree,
+                                    &mut pager,
+                                    re.id,
+                                    node_id,
+                                    old.as_deref(),
+                                    new.as_deref(),
+                                )?;
+                                re.root = tree.root();
+                            }
+                        }
                     }
                 }
                 catalog.flush(&mut pager)?;
             }
+            if !renamed_indexes.is_empty() {
+                let mut catalog = self.engine.index_catalog.lock().unwrap();
+                let mut pager = self.engine.pager.write().unwrap();
+                catalog.attach(&mut pager, renamed_indexes)?;
+            }
 
             // Flush WAL
             // wal.append calls flush internally, we just need fsync at end of commit
             wal.append(&WalRecord::CommitTx { txid: self.txid })?;
             wal.fsync()?;
+            wal.rotate_if_full()?;
         }
 
+        let mut names = SchemaCatalog::default();
+        names.record_run(&run);
+        names.record_labels(
+            self.created_nodes
+                .iter()
+                .map(|(_, label, _)| *label)
+                .chain(self.pending_label_additions.iter().map(|(_, label)| *label)),
+        );
+
         let has_new_nodes = !self.created_nodes.is_empty();
         let has_label_additions = !self.pending_label_additions.is_empty();
         let has_label_removals = !self.pending_label_removals.is_empty();
@@ -1091,13 +3518,141 @@ impl<'a> WriteTxn<'a> {
         if !run.is_empty() {
             self.engine.publish_run(Arc::new(run));
         }
+        self.engine.extend_schema(names);
+
+        // Logged above; if an insert fails here, the next open replays it.
+        for (id, vector) in self.pending_vectors {
+            self.engine.insert_vector(id, vector)?;
+        }
+
+        if !self.pending_named_queries.is_empty() {
+            let mut published = self.engine.published_named_queries.write().unwrap();
+            let mut queries = (**published).clone();
+            apply_named_query_ops(&mut queries, self.pending_named_queries);
+            *published = Arc::new(queries);
+        }
+
+        if !self.pending_sensitive.is_empty() {
+            let mut published = self.engine.published_sensitive.write().unwrap();
+            let mut sensitive = (**published).clone();
+            apply_sensitive_ops(&mut sensitive, self.pending_sensitive);
+            *published = Arc::new(sensitive);
+        }
+
+        if let Some(key) = self.idempotency_key {
+            self.engine
+                .idempotency_keys
+                .write()
+                .unwrap()
+                .insert(key, txid);
+        }
+
+        // Stamp after publishing, so a result computed from a snapshot taken
+        // before the stamp is never mistaken for a fresh one.
+        self.engine.write_stamps.lock().unwrap().record(&touched);
 
         self.engine.next_txid.fetch_add(1, Ordering::Relaxed);
+        self.engine.publish_visible_txid(txid);
 
-        Ok(())
+        Ok((txid, recorded))
+    }
+
+    /// Stages the system properties of every node and edge this transaction
+    /// creates or changes, bypassing the reserved-key check.
+    fn stamp_system_properties(&mut self) {
+        use crate::names::{CREATED_TX, UPDATED_AT, UPDATED_TX};
+        use crate::property::PropertyValue;
+
+        let txid = PropertyValue::Int(self.txid as i64);
+        let now = PropertyValue::DateTime(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_micros() as i64),
+        );
+
+        let created: BTreeSet<InternalNodeId> =
+            self.created_nodes.iter().map(|(_, _, iid)| *iid).collect();
+        let mut nodes = self.memtable.nodes_with_property_changes();
+        nodes.extend(&created);
+        nodes.extend(self.pending_label_additions.iter().map(|(node, _)| *node));
+        nodes.extend(self.pending_label_removals.iter().map(|(node, _)| *node));
+        for node in nodes {
+            if self.memtable.is_node_tombstoned(node) {
+                continue;
+            }
+            if created.contains(&node) {
+                self.memtable
+                    .set_node_property(node, CREATED_TX.to_string(), txid.clone());
+            }
+            self.memtable
+                .set_node_property(node, UPDATED_TX.to_string(), txid.clone());
+            self.memtable
+                .set_node_property(node, UPDATED_AT.to_string(), now.clone());
+        }
+
+        let created = self.memtable.created_edges();
+        let mut edges = self.memtable.edges_with_property_changes();
+        edges.extend(&created);
+        for edge in edges {
+            if self.memtable.is_edge_tombstoned(edge)
+                || self.memtable.is_node_tombstoned(edge.src)
+                || self.memtable.is_node_tombstoned(edge.dst)
+            {
+                continue;
+            }
+            let crate::snapshot::EdgeKey { src, rel, dst } = edge;
+            if created.contains(&edge) {
+                self.memtable.set_edge_property(
+                    src,
+                    rel,
+                    dst,
+                    CREATED_TX.to_string(),
+                    txid.clone(),
+                );
+            }
+            self.memtable
+                .set_edge_property(src, rel, dst, UPDATED_TX.to_string(), txid.clone());
+            self.memtable
+                .set_edge_property(src, rel, dst, UPDATED_AT.to_string(), now.clone());
+        }
+    }
+
+    /// Labels and relationship types the staged changes in `run` and the
+    /// label changes of this transaction touch, for [`WriteStamps`].
+    fn touched(&self, run: &L0Run) -> Touched {
+        let mut nodes: BTreeSet<InternalNodeId> = run.node_properties.keys().copied().collect();
+        nodes.extend(run.tombstoned_node_properties.keys());
+        nodes.extend(run.iter_tombstoned_nodes());
+        nodes.extend(self.pending_label_additions.iter().map(|(node, _)| *node));
+        nodes.extend(self.pending_label_removals.iter().map(|(node, _)| *node));
+
+        let published = self.engine.published_node_labels.read().unwrap().clone();
+        let mut labels: BTreeSet<LabelId> = self.created_nodes.iter().map(|(_, l, _)| *l).collect();
+        for node in nodes {
+            if let Some(node_labels) = published.get(node as usize) {
+                labels.extend(node_labels);
+            }
+        }
+        labels.extend(self.pending_label_additions.iter().map(|(_, l)| *l));
+        labels.extend(self.pending_label_removals.iter().map(|(_, l)| *l));
+
+        let mut rel_types: BTreeSet<LabelId> = run.iter_edges().map(|e| e.rel).collect();
+        rel_types.extend(run.iter_tombstoned_edges().map(|e| e.rel));
+        rel_types.extend(run.edge_properties.keys().map(|e| e.rel));
+        rel_types.extend(run.tombstoned_edge_properties.keys().map(|e| e.rel));
+
+        Touched {
+            labels,
+            rel_types,
+            all_rel_types: run.iter_tombstoned_nodes().next().is_some(),
+            everything: !self.pending_sensitive.is_empty(),
+        }
     }
 }
 
+/// Nodes staged between progress reports of [`GraphEngine::rename_property`].
+const RENAME_PROGRESS_STEP: usize = 1024;
+
 fn replay_graph_transactions(
     pager: &mut Pager,
     idmap: &mut IdMap,
@@ -1161,6 +3716,17 @@ fn replay_graph_transactions(
                 | WalRecord::PageWrite { .. }
                 | WalRecord::PageFree { .. }
                 | WalRecord::CreateLabel { .. }
+                | WalRecord::RenameLabel { .. }
+                | WalRecord::SetSensitive { .. }
+                | WalRecord::SetEndpoints { .. }
+                | WalRecord::SetRetention { .. }
+                | WalRecord::SetLegalHold { .. }
+                | WalRecord::SetLabelTier { .. }
+                | WalRecord::SetNamespace { .. }
+                | WalRecord::SaveQuery { .. }
+                | WalRecord::DropQuery { .. }
+                | WalRecord::IdempotencyKey { .. }
+                | WalRecord::SetVector { .. }
                 | WalRecord::ManifestSwitch { .. }
                 | WalRecord::Checkpoint { .. } => {}
             }
@@ -1175,6 +3741,293 @@ fn replay_graph_transactions(
     Ok(())
 }
 
+/// Inserts `vector` for `id` into `index` and keeps the catalog pointing at
+/// its trees, which move when a root splits, so the index survives reopening.
+fn apply_vector(
+    pager: &mut Pager,
+    catalog: &mut IndexCatalog,
+    index: &mut NativeHnsw,
+    id: InternalNodeId,
+    vector: Vec<f32>,
+) -> Result<()> {
+    index.insert(pager, id, vector)?;
+    let roots = [
+        ("__sys_hnsw_vec", index.vector_store().root()),
+        ("__sys_hnsw_graph", index.graph_store().root()),
+    ];
+    for (name, root) in roots {
+        if catalog.get(name).is_some_and(|def| def.root != root) {
+            catalog.update_root(pager, name, root)?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-applies the `SetVector` records committed after `checkpoint_txid`.
+///
+/// Vectors reach the index only after their commit is durable, so a crash
+/// in between, or pages that never reached the disk, can leave one out. A
+/// vector the index already holds unchanged is skipped.
+fn replay_vectors(
+    pager: &mut Pager,
+    catalog: &mut IndexCatalog,
+    index: &mut NativeHnsw,
+    committed: &[CommittedTx],
+    checkpoint_txid: u64,
+) -> Result<()> {
+    for tx in committed.iter().filter(|tx| tx.txid > checkpoint_txid) {
+        for op in &tx.ops {
+            let WalRecord::SetVector { node, vector } = op else {
+                continue;
+            };
+            if index
+                .get_vector(pager, *node)
+                .is_ok_and(|stored| stored == *vector)
+            {
+                continue;
+            }
+            apply_vector(pager, catalog, index, *node, vector.clone())?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_composite_properties(properties: &[&str]) -> Result<()> {
+    if properties.len() < 2 {
+        return Err(Error::InvalidIndex(
+            "a composite index needs at least two properties".to_string(),
+        ));
+    }
+    if properties
+        .iter()
+        .any(|p| p.is_empty() || p.contains([',', '(', ')']))
+    {
+        return Err(Error::InvalidIndex(format!(
+            "invalid property list {properties:?}"
+        )));
+    }
+    let distinct: std::collections::BTreeSet<_> = properties.iter().collect();
+    if distinct.len() != properties.len() {
+        return Err(Error::InvalidIndex(format!(
+            "duplicate property in {properties:?}"
+        )));
+    }
+    Ok(())
+}
+
+/// Rebuilds the stored query catalog from committed `SaveQuery`/`DropQuery` records.
+///
+/// Unlike graph data these records are never skipped by a checkpoint, since
+/// `checkpoint_on_close` re-emits the live catalog into the rewritten WAL.
+fn replay_named_queries(committed: &[CommittedTx]) -> BTreeMap<String, String> {
+    let mut queries = BTreeMap::new();
+    for tx in committed {
+        let ops = tx.ops.iter().filter_map(|op| match op {
+            WalRecord::SaveQuery { name, cypher } => Some((name.clone(), Some(cypher.clone()))),
+            WalRecord::DropQuery { name } => Some((name.clone(), None)),
+            _ => None,
+        });
+        apply_named_query_ops(&mut queries, ops);
+    }
+    queries
+}
+
+/// Rebuilds the committed idempotency keys; like stored queries they survive
+/// checkpoints because `checkpoint_on_close` re-emits them.
+fn replay_idempotency_keys(committed: &[CommittedTx]) -> BTreeMap<String, u64> {
+    committed
+        .iter()
+        .flat_map(|tx| &tx.ops)
+        .filter_map(|op| match op {
+            WalRecord::IdempotencyKey { key, txid } => Some((key.clone(), *txid)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rebuilds the sensitive property set; it survives checkpoints because
+/// `checkpoint_on_close` re-emits it.
+fn replay_sensitive_properties(committed: &[CommittedTx]) -> BTreeSet<(String, String)> {
+    let mut sensitive = BTreeSet::new();
+    let ops = committed
+        .iter()
+        .flat_map(|tx| &tx.ops)
+        .filter_map(|op| match op {
+            WalRecord::SetSensitive {
+                label,
+                property,
+                sensitive,
+            } => Some((label.clone(), property.clone(), *sensitive)),
+            _ => None,
+        });
+    apply_sensitive_ops(&mut sensitive, ops);
+    sensitive
+}
+
+/// Rebuilds the relationship endpoint constraints; they survive checkpoints
+/// because `checkpoint_on_close` re-emits them.
+fn replay_endpoint_constraints(committed: &[CommittedTx]) -> EndpointConstraints {
+    let mut constraints = EndpointConstraints::new();
+    for op in committed.iter().flat_map(|tx| &tx.ops) {
+        if let WalRecord::SetEndpoints {
+            rel_type,
+            endpoints,
+        } = op
+        {
+            if endpoints.is_empty() {
+                constraints.remove(rel_type);
+            } else {
+                constraints.insert(rel_type.clone(), endpoints.iter().cloned().collect());
+            }
+        }
+    }
+    constraints
+}
+
+/// Rebuilds the WAL retention policy; it survives checkpoints because
+/// `checkpoint_on_close` re-emits it.
+fn replay_retention(committed: &[CommittedTx]) -> RetentionPolicy {
+    let mut policy = RetentionPolicy::default();
+    for op in committed.iter().flat_map(|tx| &tx.ops) {
+        match op {
+            WalRecord::SetRetention { keep_millis } => {
+                policy.keep_for = keep_millis.map(Duration::from_millis);
+            }
+            WalRecord::SetLegalHold {
+                name,
+                placed_at_millis,
+                held,
+            } => {
+                if *held {
+                    policy.holds.insert(name.clone(), *placed_at_millis);
+                } else {
+                    policy.holds.remove(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    policy
+}
+
+/// Rebuilds the set of cold labels; it survives checkpoints because
+/// `checkpoint_on_close` re-emits it.
+fn replay_label_tiers(committed: &[CommittedTx]) -> BTreeSet<String> {
+    let mut labels = BTreeSet::new();
+    for op in committed.iter().flat_map(|tx| &tx.ops) {
+        if let WalRecord::SetLabelTier { label, cold } = op {
+            if *cold {
+                labels.insert(label.clone());
+            } else {
+                labels.remove(label);
+            }
+        }
+    }
+    labels
+}
+
+/// Rebuilds the namespace catalog; it survives checkpoints because
+/// `checkpoint_on_close` re-emits it.
+fn replay_namespaces(committed: &[CommittedTx]) -> BTreeMap<String, String> {
+    let mut namespaces = BTreeMap::new();
+    for op in committed.iter().flat_map(|tx| &tx.ops) {
+        if let WalRecord::SetNamespace { prefix, iri } = op {
+            match iri {
+                Some(iri) => namespaces.insert(prefix.clone(), iri.clone()),
+                None => namespaces.remove(prefix),
+            };
+        }
+    }
+    namespaces
+}
+
+/// `constraints` with label (or relationship type) `old` renamed to `new`.
+fn rename_in_endpoints(
+    constraints: &EndpointConstraints,
+    old: &str,
+    new: &str,
+) -> EndpointConstraints {
+    let rename = |name: &String| {
+        if name == old {
+            new.to_string()
+        } else {
+            name.clone()
+        }
+    };
+    constraints
+        .iter()
+        .map(|(rel_type, pairs)| {
+            let pairs = pairs.iter().map(|(src, dst)| (rename(src), rename(dst)));
+            (rename(rel_type), pairs.collect())
+        })
+        .collect()
+}
+
+/// Whether an edge from a node labelled `src` to one labelled `dst` matches
+/// one of the `allowed` pairs.
+fn endpoints_allowed(allowed: &BTreeSet<(String, String)>, src: &[String], dst: &[String]) -> bool {
+    allowed
+        .iter()
+        .any(|(s, d)| src.contains(s) && dst.contains(d))
+}
+
+fn endpoint_violation(
+    rel_type: &str,
+    allowed: &BTreeSet<(String, String)>,
+    edge: crate::snapshot::EdgeKey,
+    src: &[String],
+    dst: &[String],
+) -> String {
+    let node = |labels: &[String]| {
+        if labels.is_empty() {
+            "()".to_string()
+        } else {
+            format!("(:{})", labels.join(":"))
+        }
+    };
+    let allowed: Vec<String> = allowed
+        .iter()
+        .map(|(s, d)| format!("(:{s})->(:{d})"))
+        .collect();
+    format!(
+        "relationship {}-[:{rel_type}]->{} connects {}->{}, but {rel_type} only allows {}",
+        edge.src,
+        edge.dst,
+        node(src),
+        node(dst),
+        allowed.join(", ")
+    )
+}
+
+fn apply_sensitive_ops(
+    sensitive: &mut BTreeSet<(String, String)>,
+    ops: impl IntoIterator<Item = (String, String, bool)>,
+) {
+    for (label, property, mark) in ops {
+        if mark {
+            sensitive.insert((label, property));
+        } else {
+            sensitive.remove(&(label, property));
+        }
+    }
+}
+
+fn apply_named_query_ops(
+    queries: &mut BTreeMap<String, String>,
+    ops: impl IntoIterator<Item = (String, Option<String>)>,
+) {
+    for (name, cypher) in ops {
+        match cypher {
+            Some(cypher) => {
+                queries.insert(name, cypher);
+            }
+            None => {
+                queries.remove(&name);
+            }
+        }
+    }
+}
+
 /// Replay label creation transactions from WAL.
 fn replay_label_transactions(
     committed: &[CommittedTx],
@@ -1203,6 +4056,11 @@ fn replay_label_transactions(
                     }
                 }
             }
+            if let WalRecord::RenameLabel { label_id, name } = op
+                && !interner.rename(*label_id, name)
+            {
+                return Err(Error::WalProtocol("label rename conflict"));
+            }
         }
     }
     Ok(())
@@ -1229,26 +4087,22 @@ fn scan_recovery_state(committed: &[CommittedTx]) -> RecoveryState {
                     segments,
                     properties_root,
                     stats_root,
-                } => {
-                    if *epoch >= state.manifest_epoch {
-                        state.manifest_epoch = *epoch;
-                        state.manifest_segments = segments.clone();
-                        state.checkpoint_txid = 0;
-                        state.properties_root = *properties_root;
-                        state.stats_root = *stats_root;
-                    }
+                } if *epoch >= state.manifest_epoch => {
+                    state.manifest_epoch = *epoch;
+                    state.manifest_segments = segments.clone();
+                    state.checkpoint_txid = 0;
+                    state.properties_root = *properties_root;
+                    state.stats_root = *stats_root;
                 }
                 WalRecord::Checkpoint {
                     up_to_txid,
                     epoch,
                     properties_root,
                     stats_root,
-                } => {
-                    if *epoch == state.manifest_epoch {
-                        state.checkpoint_txid = state.checkpoint_txid.max(*up_to_txid);
-                        state.properties_root = *properties_root;
-                        state.stats_root = *stats_root;
-                    }
+                } if *epoch == state.manifest_epoch => {
+                    state.checkpoint_txid = state.checkpoint_txid.max(*up_to_txid);
+                    state.properties_root = *properties_root;
+                    state.stats_root = *stats_root;
                 }
                 _ => {}
             }
@@ -1257,6 +4111,42 @@ fn scan_recovery_state(committed: &[CommittedTx]) -> RecoveryState {
     state
 }
 
+/// Sidecar file listing the page cache's pages at close, most recent first.
+fn warm_pages_path(ndb_path: &Path) -> PathBuf {
+    ndb_path.with_extension("warm")
+}
+
+fn save_warm_pages(pager: &Pager, path: &Path) -> Result<()> {
+    let bytes: Vec<u8> = pager
+        .cached_page_ids()
+        .iter()
+        .flat_map(|id| id.as_u64().to_le_bytes())
+        .collect();
+    let tmp = path.with_extension("warm.tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reloads the pages saved by [`save_warm_pages`], oldest first so recency
+/// order survives. Missing or stale entries are ignored.
+fn restore_warm_pages(pager: &Pager, path: &Path) {
+    let Ok(bytes) = std::fs::read(path) else {
+        return;
+    };
+    let capacity = pager.page_cache_stats().capacity;
+    let ids: Vec<u64> = bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("8-byte chunk")))
+        .take(capacity)
+        .collect();
+    for id in ids.into_iter().rev() {
+        if pager.preload_page(PageId::new(id)).is_err() {
+            break;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1372,6 +4262,78 @@ mod tests {
         assert_eq!(snap.neighbors(a, Some(7)).count(), 2);
     }
 
+    #[test]
+    fn reopen_after_torn_wal_tail_keeps_later_commits() {
+        use std::io::Write;
+
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("torn.ndb");
+        let wal = dir.path().join("torn.wal");
+
+        {
+            let engine = GraphEngine::open(&ndb, &wal).unwrap();
+            let mut tx = engine.begin_write();
+            let a = tx.create_node(1, 1).unwrap();
+            let b = tx.create_node(2, 1).unwrap();
+            tx.create_edge(a, 7, b);
+            tx.commit().unwrap();
+        }
+
+        // Simulate a crash in the middle of appending the next record.
+        {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&wal).unwrap();
+            file.write_all(&[0x40, 0x00, 0x00, 0x00, 0xde, 0xad])
+                .unwrap();
+        }
+
+        {
+            let engine = GraphEngine::open(&ndb, &wal).unwrap();
+            let a = engine.lookup_internal_id(1).unwrap();
+            let mut tx = engine.begin_write();
+            let c = tx.create_node(3, 1).unwrap();
+            tx.create_edge(a, 7, c);
+            tx.commit().unwrap();
+        }
+
+        let engine = GraphEngine::open(&ndb, &wal).unwrap();
+        let a = engine.lookup_internal_id(1).unwrap();
+        assert!(engine.lookup_internal_id(3).is_some());
+        assert_eq!(engine.begin_read().neighbors(a, Some(7)).count(), 2);
+    }
+
+    #[test]
+    fn wal_rotates_into_segments_and_checkpoint_prunes_them() {
+        use crate::wal::sealed_segments;
+
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("seg.ndb");
+        let wal = dir.path().join("seg.wal");
+        let options = EngineOptions {
+            wal_segment_bytes: 256,
+            ..EngineOptions::default()
+        };
+
+        {
+            let engine = GraphEngine::open_with_options(&ndb, &wal, options).unwrap();
+            for i in 1..=20u64 {
+                let mut tx = engine.begin_write();
+                tx.create_node(i, 1).unwrap();
+                tx.commit().unwrap();
+            }
+            assert!(!sealed_segments(&wal).unwrap().is_empty());
+        }
+
+        let engine = GraphEngine::open_with_options(&ndb, &wal, options).unwrap();
+        assert!((1..=20u64).all(|i| engine.lookup_internal_id(i).is_some()));
+
+        engine.checkpoint().unwrap();
+        assert!(sealed_segments(&wal).unwrap().is_empty());
+        drop(engine);
+
+        let engine = GraphEngine::open(&ndb, &wal).unwrap();
+        assert!((1..=20u64).all(|i| engine.lookup_internal_id(i).is_some()));
+    }
+
     #[test]
     fn t103_compaction_checkpoints_even_with_properties() {
         use crate::api::StorageSnapshot;
@@ -1390,7 +4352,8 @@ mod tests {
                 node,
                 "age".to_string(),
                 crate::property::PropertyValue::Int(30),
-            );
+            )
+            .unwrap();
             tx.commit().unwrap();
             internal_id = node;
 
@@ -25,15 +25,59 @@ pub enum Error {
     #[error("wal record too large: {0}")]
     WalRecordTooLarge(u32),
 
+    #[error("property value too large: {len} bytes encoded, limit is {max}")]
+    PropertyValueTooLarge { len: usize, max: usize },
+
+    #[error("invalid {kind} \"{name}\": {reason}")]
+    InvalidName {
+        kind: crate::names::NameKind,
+        name: String,
+        reason: String,
+    },
+
     #[error("wal checksum mismatch at offset {offset}")]
     WalChecksumMismatch { offset: u64 },
 
+    #[error("wal corrupted at offset {offset}: {reason}")]
+    WalCorrupted { offset: u64, reason: &'static str },
+
     #[error("wal protocol error: {0}")]
     WalProtocol(&'static str),
 
     #[error("storage corrupted: {0}")]
     StorageCorrupted(&'static str),
 
+    #[error("invalid index definition: {0}")]
+    InvalidIndex(String),
+
+    #[error("constraint violation: {0}")]
+    ConstraintViolation(String),
+
+    #[error("schema conflict: {0}")]
+    SchemaConflict(String),
+
+    #[error(
+        "wal history unavailable: asked for changes after txid {since}, log starts at txid {oldest}"
+    )]
+    WalHistoryUnavailable { since: u64, oldest: u64 },
+
+    #[error("database is read-only")]
+    ReadOnly,
+
+    #[error(
+        "node id space exhausted: at most {limit} nodes can be created, and ids of deleted nodes are not reused"
+    )]
+    NodeIdSpaceExhausted { limit: u64 },
+
+    #[error("write throttled: wal backlog is {wal_bytes} bytes, limit is {limit}")]
+    WriteThrottled { wal_bytes: u64, limit: u64 },
+
+    #[error("unsupported: {0}")]
+    Unsupported(&'static str),
+
+    #[error("operation cancelled")]
+    Cancelled,
+
     #[error("backup protocol error: {0}")]
     BackupProtocol(String),
 
@@ -0,0 +1,226 @@
+//! Deletes nodes and relationships whose `expireAt` property has passed.
+//!
+//! A node or relationship expires once its [`EXPIRE_AT`] property is at or
+//! before the current time. The property is a `DateTime`, or an integer
+//! count of milliseconds since the Unix epoch; other values never expire.
+//! [`sweep`] deletes everything expired in one write transaction, nodes
+//! together with their relationships like `DETACH DELETE`. An
+//! [`ExpirySweeper`] runs it on a background thread at a fixed interval. It
+//! holds the engine weakly and stops when dropped or when the engine goes
+//! away.
+//!
+//! Expired entities stay visible until the sweep that deletes them.
+
+use crate::engine::GraphEngine;
+use crate::{Error, Result};
+use nervusdb_api::{EdgeKey, GraphSnapshot, GraphStore, PropertyValue};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Property holding the time a node or relationship expires: a `DateTime`,
+/// or an `Int` of milliseconds (not seconds) since the Unix epoch.
+pub const EXPIRE_AT: &str = "expireAt";
+
+/// What one [`sweep`] deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SweepReport {
+    pub nodes: u64,
+    /// Relationships, expired themselves or attached to an expired node.
+    pub edges: u64,
+}
+
+/// Totals over the sweeps of one handle, background and explicit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExpiryStats {
+    pub sweeps: u64,
+    pub nodes_purged: u64,
+    pub edges_purged: u64,
+    /// Sweeps that failed; they deleted nothing.
+    pub failures: u64,
+}
+
+/// Collects [`ExpiryStats`] from the sweeps that report to it.
+#[derive(Debug, Default)]
+pub struct ExpiryMetrics {
+    stats: Mutex<ExpiryStats>,
+}
+
+impl ExpiryMetrics {
+    pub fn record(&self, outcome: &Result<SweepReport>) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.sweeps += 1;
+        match outcome {
+            Ok(report) => {
+                stats.nodes_purged += report.nodes;
+                stats.edges_purged += report.edges;
+            }
+            Err(_) => stats.failures += 1,
+        }
+    }
+
+    pub fn stats(&self) -> ExpiryStats {
+        *self.stats.lock().unwrap()
+    }
+}
+
+/// Microseconds since the Unix epoch at which `value` expires, if it is an
+/// expiry time at all.
+pub fn expires_at_micros(value: &PropertyValue) -> Option<i64> {
+    match value {
+        PropertyValue::DateTime(micros) => Some(*micros),
+        PropertyValue::Int(millis) => Some(millis.saturating_mul(1000)),
+        _ => None,
+    }
+}
+
+/// The current time in microseconds since the Unix epoch.
+pub fn now_micros() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_micros()).unwrap_or(i64::MAX))
+}
+
+/// Deletes the nodes and relationships that expired by `now` (microseconds
+/// since the Unix epoch) in one commit.
+///
+/// Every node and relationship of a snapshot is read without the write
+/// lock, so writers are not held up by the scan. Only the candidates it
+/// found are read again under the lock, so a commit that moved an expiry
+/// into the future or deleted a candidate in between is respected. Nothing
+/// is committed when none expired.
+pub fn sweep(engine: &GraphEngine, now: i64) -> Result<SweepReport> {
+    if engine.is_read_only() {
+        return Err(Error::ReadOnly);
+    }
+    let expired = |value: Option<PropertyValue>| {
+        value
+            .as_ref()
+            .and_then(expires_at_micros)
+            .is_some_and(|at| at <= now)
+    };
+
+    let (candidate_nodes, candidate_edges) = {
+        let snapshot = engine.snapshot();
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for node in snapshot.nodes() {
+            if expired(snapshot.node_property(node, EXPIRE_AT)) {
+                nodes.push(node);
+                continue;
+            }
+            edges.extend(
+                snapshot
+                    .neighbors(node, None)
+                    .filter(|edge| expired(snapshot.edge_property(*edge, EXPIRE_AT))),
+            );
+        }
+        (nodes, edges)
+    };
+    if candidate_nodes.is_empty() && candidate_edges.is_empty() {
+        return Ok(SweepReport::default());
+    }
+
+    let mut txn = engine.begin_write();
+    let snapshot = engine.snapshot();
+    let mut nodes = Vec::new();
+    let mut edges: BTreeSet<EdgeKey> = BTreeSet::new();
+    for node in candidate_nodes {
+        if snapshot.is_tombstoned_node(node) || !expired(snapshot.node_property(node, EXPIRE_AT)) {
+            continue;
+        }
+        nodes.push(node);
+        // Relationships attached since the scan go with the node too.
+        edges.extend(snapshot.incoming_neighbors(node, None));
+        edges.extend(snapshot.neighbors(node, None));
+    }
+    edges.extend(candidate_edges.into_iter().filter(|edge| {
+        snapshot
+            .neighbors(edge.src, Some(edge.rel))
+            .any(|live| live == *edge)
+            && expired(snapshot.edge_property(*edge, EXPIRE_AT))
+    }));
+    if nodes.is_empty() && edges.is_empty() {
+        return Ok(SweepReport::default());
+    }
+
+    for edge in &edges {
+        txn.tombstone_edge(edge.src, edge.rel, edge.dst);
+    }
+    for &node in &nodes {
+        txn.tombstone_node(node);
+    }
+    txn.commit()?;
+    Ok(SweepReport {
+        nodes: nodes.len() as u64,
+        edges: edges.len() as u64,
+    })
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    stop: Mutex<bool>,
+    changed: Condvar,
+}
+
+/// Handle to the background sweep thread. Dropping it stops the thread,
+/// waiting for a sweep in progress to finish.
+#[derive(Debug)]
+pub struct ExpirySweeper {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ExpirySweeper {
+    /// Sweeps `engine` every `interval`, first one `interval` from now, and
+    /// reports each sweep to `metrics`.
+    pub fn spawn(
+        engine: Weak<GraphEngine>,
+        interval: Duration,
+        metrics: Arc<ExpiryMetrics>,
+    ) -> Self {
+        let shared = Arc::new(Shared::default());
+        let thread = {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("nervusdb-expiry".into())
+                .spawn(move || run(&engine, interval, &metrics, &shared))
+                .expect("spawn expiry thread")
+        };
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for ExpirySweeper {
+    fn drop(&mut self) {
+        *self.shared.stop.lock().unwrap() = true;
+        self.shared.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(engine: &Weak<GraphEngine>, interval: Duration, metrics: &ExpiryMetrics, shared: &Shared) {
+    loop {
+        {
+            let stop = shared.stop.lock().unwrap();
+            let (stop, _) = shared
+                .changed
+                .wait_timeout_while(stop, interval, |stop| !*stop)
+                .unwrap();
+            if *stop {
+                return;
+            }
+        }
+        let Some(engine) = engine.upgrade() else {
+            return;
+        };
+        // A failed sweep deletes nothing; the next one tries again.
+        metrics.record(&sweep(&engine, now_micros()));
+    }
+}
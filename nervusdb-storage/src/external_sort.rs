@@ -0,0 +1,274 @@
+//! Sorts record streams larger than memory by spilling sorted runs to disk
+//! and merging them back.
+
+use crate::Result;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// A record that can be written to and read back from a sorted run.
+pub(crate) trait RunRecord: Ord + Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> Result<Self>;
+}
+
+/// Buffers up to `run_len` records in memory, spilling each full buffer to
+/// `dir` as a sorted run file.
+pub(crate) struct ExternalSorter<R> {
+    dir: PathBuf,
+    prefix: &'static str,
+    run_len: usize,
+    buffer: Vec<R>,
+    runs: Vec<PathBuf>,
+}
+
+impl<R: RunRecord> ExternalSorter<R> {
+    pub(crate) fn new(dir: PathBuf, prefix: &'static str, run_len: usize) -> Self {
+        Self {
+            dir,
+            prefix,
+            run_len: run_len.max(1),
+            buffer: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, record: R) -> Result<()> {
+        self.buffer.push(record);
+        if self.buffer.len() >= self.run_len {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Returns every pushed record in ascending order.
+    ///
+    /// Records that never left memory are sorted in place; otherwise the
+    /// remaining buffer is spilled and all runs are merged.
+    pub(crate) fn finish(mut self) -> Result<SortedRecords<R>> {
+        if self.runs.is_empty() {
+            self.buffer.sort();
+            return Ok(SortedRecords::Memory(self.buffer.into_iter()));
+        }
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+        let mut readers = Vec::with_capacity(self.runs.len());
+        let mut heap = BinaryHeap::with_capacity(self.runs.len());
+        for (idx, path) in self.runs.iter().enumerate() {
+            let mut reader = RunReader::open(path)?;
+            if let Some(record) = reader.next().transpose()? {
+                heap.push(Reverse((record, idx)));
+            }
+            readers.push(reader);
+        }
+        Ok(SortedRecords::Merge { readers, heap })
+    }
+
+    fn spill(&mut self) -> Result<()> {
+        self.buffer.sort();
+        let path = self
+            .dir
+            .join(format!("{}-{:06}.run", self.prefix, self.runs.len()));
+        let mut out = RunWriter::create(path.clone())?;
+        for record in self.buffer.drain(..) {
+            out.write(&record)?;
+        }
+        out.finish()?;
+        self.runs.push(path);
+        Ok(())
+    }
+}
+
+/// Ascending records from [`ExternalSorter::finish`].
+pub(crate) enum SortedRecords<R> {
+    Memory(std::vec::IntoIter<R>),
+    Merge {
+        readers: Vec<RunReader<R>>,
+        heap: BinaryHeap<Reverse<(R, usize)>>,
+    },
+}
+
+impl<R> SortedRecords<R> {
+    /// Number of runs spilled to disk; zero when sorted in memory.
+    pub(crate) fn spilled_runs(&self) -> usize {
+        match self {
+            SortedRecords::Memory(_) => 0,
+            SortedRecords::Merge { readers, .. } => readers.len(),
+        }
+    }
+}
+
+impl<R: RunRecord> Iterator for SortedRecords<R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Result<R>> {
+        match self {
+            SortedRecords::Memory(records) => records.next().map(Ok),
+            SortedRecords::Merge { readers, heap } => {
+                let Reverse((record, idx)) = heap.pop()?;
+                match readers[idx].next().transpose() {
+                    Ok(Some(next)) => heap.push(Reverse((next, idx))),
+                    Ok(None) => {}
+                    Err(err) => return Some(Err(err)),
+                }
+                Some(Ok(record))
+            }
+        }
+    }
+}
+
+/// Appends records to a run file in the order given.
+pub(crate) struct RunWriter {
+    path: PathBuf,
+    out: BufWriter<File>,
+    bytes: Vec<u8>,
+}
+
+impl RunWriter {
+    pub(crate) fn create(path: PathBuf) -> Result<Self> {
+        let out = BufWriter::new(File::create(&path)?);
+        Ok(Self {
+            path,
+            out,
+            bytes: Vec::new(),
+        })
+    }
+
+    pub(crate) fn write<R: RunRecord>(&mut self, record: &R) -> Result<()> {
+        self.bytes.clear();
+        record.encode(&mut self.bytes);
+        self.out
+            .write_all(&(self.bytes.len() as u32).to_le_bytes())?;
+        self.out.write_all(&self.bytes)?;
+        Ok(())
+    }
+
+    /// Flushes the file and returns its path for [`RunReader::open`].
+    pub(crate) fn finish(mut self) -> Result<PathBuf> {
+        self.out.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// Reads back the records of a run file in file order.
+pub(crate) struct RunReader<R> {
+    reader: BufReader<File>,
+    _record: PhantomData<R>,
+}
+
+impl<R: RunRecord> RunReader<R> {
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            _record: PhantomData,
+        })
+    }
+}
+
+impl<R: RunRecord> Iterator for RunReader<R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Result<R>> {
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return None,
+            Err(err) => return Some(Err(err.into())),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        if let Err(err) = self.reader.read_exact(&mut bytes) {
+            return Some(Err(err.into()));
+        }
+        Some(R::decode(&bytes))
+    }
+}
+
+/// Reads the length-prefixed fields written by [`put_bytes`].
+pub(crate) struct FieldReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FieldReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String> {
+        String::from_utf8(self.bytes()?.to_vec())
+            .map_err(|_| crate::Error::StorageCorrupted("sort run string is not utf-8"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(crate::Error::StorageCorrupted("truncated sort run record"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+}
+
+pub(crate) fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExternalSorter, FieldReader, RunRecord};
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Num(u64);
+
+    impl RunRecord for Num {
+        fn encode(&self, out: &mut Vec<u8>) {
+            out.extend_from_slice(&self.0.to_le_bytes());
+        }
+
+        fn decode(bytes: &[u8]) -> crate::Result<Self> {
+            FieldReader::new(bytes).u64().map(Num)
+        }
+    }
+
+    #[test]
+    fn merges_spilled_runs_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sorter = ExternalSorter::new(dir.path().to_path_buf(), "num", 3);
+        for n in [9u64, 4, 7, 1, 8, 2, 6, 3, 5, 0] {
+            sorter.push(Num(n)).unwrap();
+        }
+        let records = sorter.finish().unwrap();
+        assert_eq!(records.spilled_runs(), 4);
+        let sorted: Vec<u64> = records.map(|r| r.unwrap().0).collect();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sorts_in_memory_without_spilling() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sorter = ExternalSorter::new(dir.path().to_path_buf(), "num", 100);
+        for n in [3u64, 1, 2] {
+            sorter.push(Num(n)).unwrap();
+        }
+        let sorted: Vec<u64> = sorter.finish().unwrap().map(|r| r.unwrap().0).collect();
+        assert_eq!(sorted, vec![1, 2, 3]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
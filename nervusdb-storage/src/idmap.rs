@@ -6,6 +6,28 @@ pub type ExternalId = u64;
 pub type InternalNodeId = u32;
 pub type LabelId = u32;
 
+/// How many internal node ids a database can ever hand out. Ids are dense
+/// `u32`s and are not reused after a node is deleted; `u32::MAX` itself is
+/// never allocated because [`IdMap::next_internal_id`] saturates there.
+///
+/// Reuse would need a deletion that is eventually forgotten: today a node
+/// tombstone lives in the L0 runs and is logged again by every checkpoint,
+/// while the I2E record of the node stays, so a recycled id would still be
+/// dead to every reader. Widening the id instead changes the CSR, WAL, I2E
+/// and index layouts and the public `nervusdb_api::InternalNodeId`.
+pub const MAX_INTERNAL_NODE_IDS: u64 = u32::MAX as u64;
+
+/// The internal id of the `index`-th node ever created, or
+/// [`Error::NodeIdSpaceExhausted`] past [`MAX_INTERNAL_NODE_IDS`].
+pub fn internal_node_id(index: u64) -> Result<InternalNodeId> {
+    if index >= MAX_INTERNAL_NODE_IDS {
+        return Err(Error::NodeIdSpaceExhausted {
+            limit: MAX_INTERNAL_NODE_IDS,
+        });
+    }
+    Ok(index as InternalNodeId)
+}
+
 const I2E_RECORD_SIZE: usize = 16;
 const I2E_RECORDS_PER_PAGE: usize = PAGE_SIZE / I2E_RECORD_SIZE;
 
@@ -150,15 +172,7 @@ impl IdMap {
         labels.sort_unstable();
         labels.dedup();
 
-        let start = match self.i2e_start {
-            Some(p) => p,
-            None => {
-                let p = pager.allocate_page()?;
-                pager.set_i2e_start_page(Some(p))?;
-                self.i2e_start = Some(p);
-                p
-            }
-        };
+        let start = self.i2e_start_for(pager, internal_id as u64)?;
 
         // For now, only persist first label in I2E (backward compat)
         let first_label = labels.first().copied().unwrap_or(0);
@@ -206,6 +220,40 @@ impl IdMap {
         Ok(())
     }
 
+    /// Returns the first I2E page, making room for record `index`.
+    ///
+    /// The table is a contiguous page run. When `index` starts a new page and
+    /// the page after the run already belongs to something else, the table is
+    /// moved to a fresh run at the end of the file.
+    fn i2e_start_for(&mut self, pager: &mut Pager, index: u64) -> Result<PageId> {
+        let Some(start) = self.i2e_start else {
+            let p = pager.allocate_page()?;
+            pager.set_i2e_start_page(Some(p))?;
+            self.i2e_start = Some(p);
+            return Ok(p);
+        };
+        let per_page = I2E_RECORDS_PER_PAGE as u64;
+        let pages = index / per_page;
+        if index % per_page != 0
+            || pages == 0
+            || !pager.is_page_allocated(PageId::new(start.as_u64() + pages))
+        {
+            return Ok(start);
+        }
+
+        let moved = pager.allocate_run(pages + 1)?;
+        for i in 0..pages {
+            let page = pager.read_page(PageId::new(start.as_u64() + i))?;
+            pager.write_page(PageId::new(moved.as_u64() + i), &page)?;
+        }
+        pager.set_i2e_start_page(Some(moved))?;
+        for i in 0..pages {
+            pager.free_page(PageId::new(start.as_u64() + i))?;
+        }
+        self.i2e_start = Some(moved);
+        Ok(moved)
+    }
+
     /// Remove a label from an existing node.
     pub fn apply_remove_label(
         &mut self,
@@ -258,6 +306,19 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn internal_node_ids_stop_short_of_the_saturated_next_id() {
+        assert_eq!(internal_node_id(0).unwrap(), 0);
+        assert_eq!(
+            internal_node_id(MAX_INTERNAL_NODE_IDS - 1).unwrap(),
+            u32::MAX - 1
+        );
+        assert!(matches!(
+            internal_node_id(MAX_INTERNAL_NODE_IDS),
+            Err(Error::NodeIdSpaceExhausted { limit }) if limit == MAX_INTERNAL_NODE_IDS
+        ));
+    }
+
     #[test]
     fn idmap_persists_i2e_and_rebuilds_e2i() {
         let dir = tempdir().unwrap();
@@ -278,4 +339,35 @@ mod tests {
         assert_eq!(idmap2.lookup(200), Some(1));
         assert_eq!(idmap2.len(), 2);
     }
+
+    #[test]
+    fn i2e_table_moves_instead_of_overwriting_neighbour_pages() {
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("test.ndb");
+        let mut pager = Pager::open(&ndb).unwrap();
+        let mut idmap = IdMap::load(&mut pager).unwrap();
+
+        let count = I2E_RECORDS_PER_PAGE as u32;
+        for id in 0..count {
+            idmap
+                .apply_create_node(&mut pager, u64::from(id) + 1, 1, id)
+                .unwrap();
+        }
+        let neighbour = pager.allocate_page().unwrap();
+        pager.write_page(neighbour, &[7u8; PAGE_SIZE]).unwrap();
+        for id in count..count * 2 + 1 {
+            idmap
+                .apply_create_node(&mut pager, u64::from(id) + 1, 1, id)
+                .unwrap();
+        }
+        assert_eq!(pager.read_page(neighbour).unwrap(), [7u8; PAGE_SIZE]);
+        drop(pager);
+
+        let mut pager = Pager::open(&ndb).unwrap();
+        let idmap = IdMap::load(&mut pager).unwrap();
+        assert_eq!(idmap.len(), u64::from(count) * 2 + 1);
+        for id in 0..count * 2 + 1 {
+            assert_eq!(idmap.lookup(u64::from(id) + 1), Some(id));
+        }
+    }
 }
@@ -1,18 +1,43 @@
 use crate::PAGE_SIZE;
 use crate::error::{Error, Result};
 use crate::index::btree::BTree;
+use crate::index::composite::composite_index_name;
+use crate::index::text::text_index_name;
 use crate::pager::{PageId, Pager};
 use std::collections::BTreeMap;
 
-const MAGIC: [u8; 8] = *b"NDBXCAT1";
+/// Catalog pages written before composite indexes; still readable.
+const MAGIC_V1: [u8; 8] = *b"NDBXCAT1";
+/// Composite keys without the flags byte; still readable.
+const MAGIC_V2: [u8; 8] = *b"NDBXCAT2";
+/// Every record carries its composite key definition (empty for other indexes).
+const MAGIC: [u8; 8] = *b"NDBXCAT3";
+/// Composite key flag: the index backs a unique constraint.
+const FLAG_UNIQUE: u8 = 1;
 const HEADER_SIZE: usize = 16;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IndexDef {
     pub id: u32,
     pub root: PageId,
+    /// Set for composite indexes; single-property, text and system indexes
+    /// are identified by their name alone.
+    pub composite: Option<CompositeKey>,
 }
 
+/// The label and ordered properties a composite index is keyed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositeKey {
+    pub label: String,
+    pub properties: Vec<String>,
+    /// The index enforces a unique constraint on its properties.
+    pub unique: bool,
+}
+
+/// A catalog entry moving from one name to another, with its updated
+/// composite key (`None` for single-property and text indexes).
+pub(crate) type Rename = (String, String, Option<CompositeKey>);
+
 /// Index catalog persisted inside the pager.
 ///
 /// MVP: single-page catalog that is rewritten atomically on update.
@@ -64,12 +89,170 @@ impl IndexCatalog {
         let def = IndexDef {
             id,
             root: tree.root(),
+            composite: None,
+        };
+        self.entries.insert(name.to_string(), def.clone());
+        self.flush(pager)?;
+        Ok(def)
+    }
+
+    /// Registers a composite index under `name`, or returns the existing entry.
+    pub fn get_or_create_composite(
+        &mut self,
+        pager: &mut Pager,
+        name: &str,
+        key: CompositeKey,
+    ) -> Result<IndexDef> {
+        if let Some(def) = self.entries.get(name) {
+            return Ok(def.clone());
+        }
+
+        let id = pager.allocate_index_id()?;
+        let tree = BTree::create(pager)?;
+        let def = IndexDef {
+            id,
+            root: tree.root(),
+            composite: Some(key),
         };
         self.entries.insert(name.to_string(), def.clone());
         self.flush(pager)?;
         Ok(def)
     }
 
+    /// Composite indexes defined on `label`, with their catalog names.
+    pub fn composites_for_label<'a>(
+        &'a self,
+        label: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a IndexDef, &'a CompositeKey)> + 'a {
+        self.entries.iter().filter_map(move |(name, def)| {
+            let key = def.composite.as_ref()?;
+            (key.label == label).then_some((name.as_str(), def, key))
+        })
+    }
+
+    /// Unique constraints defined on `label`, with their catalog names.
+    pub fn unique_for_label<'a>(
+        &'a self,
+        label: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a IndexDef, &'a CompositeKey)> + 'a {
+        self.composites_for_label(label)
+            .filter(|(_, _, key)| key.unique)
+    }
+
+    /// Removes the entry for `name`. Its tree pages become unreachable and are
+    /// reclaimed by the next vacuum.
+    pub fn remove(&mut self, pager: &mut Pager, name: &str) -> Result<Option<IndexDef>> {
+        let removed = self.entries.remove(name);
+        if removed.is_some() {
+            self.flush(pager)?;
+        }
+        Ok(removed)
+    }
+
+    /// Entries that must follow `label`'s property `old` when it is renamed
+    /// to `new`: its equality and text indexes and every composite index or
+    /// unique constraint on `label` that includes it.
+    pub(crate) fn property_renames(
+        &self,
+        label: &str,
+        old: &str,
+        new: &str,
+    ) -> Result<Vec<Rename>> {
+        let mut renames = Vec::new();
+        for (from, to) in [
+            (format!("{label}.{old}"), format!("{label}.{new}")),
+            (text_index_name(label, old), text_index_name(label, new)),
+        ] {
+            if self.entries.contains_key(&from) {
+                renames.push((from, to, None));
+            }
+        }
+        for (name, _, key) in self.composites_for_label(label) {
+            if !key.properties.iter().any(|p| p == old) {
+                continue;
+            }
+            if key.properties.iter().any(|p| p == new) {
+                return Err(Error::SchemaConflict(format!(
+                    "index {name} already covers {new}"
+                )));
+            }
+            let mut key = key.clone();
+            for property in &mut key.properties {
+                if property == old {
+                    *property = new.to_string();
+                }
+            }
+            let properties: Vec<&str> = key.properties.iter().map(String::as_str).collect();
+            let to = composite_index_name(label, &properties);
+            renames.push((name.to_string(), to, Some(key)));
+        }
+        self.check_renames(&renames)?;
+        Ok(renames)
+    }
+
+    /// Entries that must follow label `old` when it is renamed to `new`.
+    pub(crate) fn label_renames(&self, old: &str, new: &str) -> Result<Vec<Rename>> {
+        let prefix = format!("{old}.");
+        let mut renames = Vec::new();
+        for (name, def) in &self.entries {
+            match &def.composite {
+                Some(key) if key.label == old => {
+                    let properties: Vec<&str> = key.properties.iter().map(String::as_str).collect();
+                    let to = composite_index_name(new, &properties);
+                    let key = CompositeKey {
+                        label: new.to_string(),
+                        ..key.clone()
+                    };
+                    renames.push((name.clone(), to, Some(key)));
+                }
+                None if name.starts_with(&prefix) => {
+                    let to = format!("{new}.{}", &name[prefix.len()..]);
+                    renames.push((name.clone(), to, None));
+                }
+                _ => {}
+            }
+        }
+        self.check_renames(&renames)?;
+        Ok(renames)
+    }
+
+    fn check_renames(&self, renames: &[Rename]) -> Result<()> {
+        match renames
+            .iter()
+            .find(|(_, to, _)| self.entries.contains_key(to))
+        {
+            Some((from, to, _)) => Err(Error::SchemaConflict(format!(
+                "cannot rename index {from}: {to} already exists"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the entries named by `renames` without flushing, returning
+    /// them under their new names for [`Self::attach`].
+    pub(crate) fn detach(&mut self, renames: Vec<Rename>) -> Vec<(String, IndexDef)> {
+        renames
+            .into_iter()
+            .filter_map(|(from, to, key)| {
+                let mut def = self.entries.remove(&from)?;
+                if key.is_some() {
+                    def.composite = key;
+                }
+                Some((to, def))
+            })
+            .collect()
+    }
+
+    /// Re-inserts detached entries and persists the catalog.
+    pub(crate) fn attach(
+        &mut self,
+        pager: &mut Pager,
+        entries: Vec<(String, IndexDef)>,
+    ) -> Result<()> {
+        self.entries.extend(entries);
+        self.flush(pager)
+    }
+
     pub fn update_root(&mut self, pager: &mut Pager, name: &str, new_root: PageId) -> Result<()> {
         let Some(def) = self.entries.get_mut(name) else {
             return Err(Error::WalProtocol("index catalog: missing entry"));
@@ -93,9 +276,12 @@ fn init_empty_catalog_page(buf: &mut [u8; PAGE_SIZE]) {
 }
 
 fn decode_catalog_page(buf: &[u8; PAGE_SIZE]) -> Result<BTreeMap<String, IndexDef>> {
-    if buf[0..8] != MAGIC {
-        return Err(Error::WalProtocol("index catalog: bad magic"));
-    }
+    let version = match &buf[0..8] {
+        m if m == MAGIC => 3,
+        m if m == MAGIC_V2 => 2,
+        m if m == MAGIC_V1 => 1,
+        _ => return Err(Error::WalProtocol("index catalog: bad magic")),
+    };
     let count = u16::from_le_bytes(buf[8..10].try_into().unwrap()) as usize;
     let mut off = HEADER_SIZE;
     let mut entries = BTreeMap::new();
@@ -116,11 +302,17 @@ fn decode_catalog_page(buf: &[u8; PAGE_SIZE]) -> Result<BTreeMap<String, IndexDe
         off += 4;
         let root = u64::from_le_bytes(buf[off..off + 8].try_into().unwrap());
         off += 8;
+        let composite = if version >= 2 {
+            decode_composite_key(buf, &mut off, version >= 3)?
+        } else {
+            None
+        };
         entries.insert(
             name,
             IndexDef {
                 id,
                 root: PageId::new(root),
+                composite,
             },
         );
     }
@@ -142,7 +334,8 @@ fn encode_catalog_page(
         let name_bytes = name.as_bytes();
         let name_len = u16::try_from(name_bytes.len())
             .map_err(|_| Error::WalProtocol("index catalog: name too long"))?;
-        let rec_len = 2 + name_bytes.len() + 4 + 8;
+        let key_bytes = encode_composite_key(def.composite.as_ref())?;
+        let rec_len = 2 + name_bytes.len() + 4 + 8 + key_bytes.len();
         if off + rec_len > PAGE_SIZE {
             return Err(Error::WalProtocol("index catalog: page full"));
         }
@@ -154,10 +347,74 @@ fn encode_catalog_page(
         off += 4;
         out[off..off + 8].copy_from_slice(&def.root.as_u64().to_le_bytes());
         off += 8;
+        out[off..off + key_bytes.len()].copy_from_slice(&key_bytes);
+        off += key_bytes.len();
     }
     Ok(())
 }
 
+/// `[prop_count: u8]` then, when non-zero, `[flags: u8]` and the label and
+/// each property as `[len: u16][utf8]`.
+fn encode_composite_key(key: Option<&CompositeKey>) -> Result<Vec<u8>> {
+    let Some(key) = key else {
+        return Ok(vec![0]);
+    };
+    let count = u8::try_from(key.properties.len())
+        .map_err(|_| Error::WalProtocol("index catalog: too many properties"))?;
+    let flags = if key.unique { FLAG_UNIQUE } else { 0 };
+    let mut out = vec![count, flags];
+    for part in std::iter::once(&key.label).chain(&key.properties) {
+        let len = u16::try_from(part.len())
+            .map_err(|_| Error::WalProtocol("index catalog: name too long"))?;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(part.as_bytes());
+    }
+    Ok(out)
+}
+
+fn decode_composite_key(
+    buf: &[u8; PAGE_SIZE],
+    off: &mut usize,
+    has_flags: bool,
+) -> Result<Option<CompositeKey>> {
+    let Some(&count) = buf.get(*off) else {
+        return Err(Error::WalProtocol("index catalog: truncated"));
+    };
+    *off += 1;
+    if count == 0 {
+        return Ok(None);
+    }
+    let mut flags = 0;
+    if has_flags {
+        let Some(&byte) = buf.get(*off) else {
+            return Err(Error::WalProtocol("index catalog: truncated"));
+        };
+        flags = byte;
+        *off += 1;
+    }
+    let mut parts = Vec::with_capacity(count as usize + 1);
+    for _ in 0..=count {
+        if *off + 2 > PAGE_SIZE {
+            return Err(Error::WalProtocol("index catalog: truncated"));
+        }
+        let len = u16::from_le_bytes(buf[*off..*off + 2].try_into().unwrap()) as usize;
+        *off += 2;
+        if *off + len > PAGE_SIZE {
+            return Err(Error::WalProtocol("index catalog: truncated"));
+        }
+        let part = std::str::from_utf8(&buf[*off..*off + len])
+            .map_err(|_| Error::WalProtocol("index catalog: invalid utf8"))?;
+        parts.push(part.to_string());
+        *off += len;
+    }
+    let label = parts.remove(0);
+    Ok(Some(CompositeKey {
+        label,
+        properties: parts,
+        unique: flags & FLAG_UNIQUE != 0,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,6 +441,177 @@ mod tests {
         assert!(cat.get("name").is_some());
     }
 
+    #[test]
+    fn renames_follow_properties_and_labels() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cat_rename.ndb");
+        let mut pager = Pager::open(&path).unwrap();
+        let mut cat = IndexCatalog::open_or_create(&mut pager).unwrap();
+        cat.get_or_create(&mut pager, "User.mail").unwrap();
+        cat.get_or_create(&mut pager, &text_index_name("User", "mail"))
+            .unwrap();
+        let key = CompositeKey {
+            label: "User".to_string(),
+            properties: vec!["mail".to_string(), "name".to_string()],
+            unique: false,
+        };
+        cat.get_or_create_composite(&mut pager, "User(mail,name)", key)
+            .unwrap();
+        cat.get_or_create(&mut pager, "Post.mail").unwrap();
+
+        let renames = cat.property_renames("User", "mail", "email").unwrap();
+        let detached = cat.detach(renames);
+        cat.attach(&mut pager, detached).unwrap();
+        let names: Vec<&str> = cat.entries.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            [
+                "Post.mail",
+                "User(email,name)",
+                "User.email",
+                "User.email#text"
+            ]
+        );
+        assert_eq!(
+            cat.get("User(email,name)")
+                .unwrap()
+                .composite
+                .as_ref()
+                .unwrap()
+                .properties,
+            ["email", "name"]
+        );
+        assert!(matches!(
+            cat.property_renames("User", "name", "email"),
+            Err(Error::SchemaConflict(_))
+        ));
+
+        let renames = cat.label_renames("User", "Customer").unwrap();
+        let detached = cat.detach(renames);
+        cat.attach(&mut pager, detached).unwrap();
+        let names: Vec<&str> = cat.entries.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            [
+                "Customer(email,name)",
+                "Customer.email",
+                "Customer.email#text",
+                "Post.mail"
+            ]
+        );
+        assert_eq!(
+            cat.get("Customer(email,name)")
+                .unwrap()
+                .composite
+                .as_ref()
+                .unwrap()
+                .label,
+            "Customer"
+        );
+        cat.get_or_create(&mut pager, "Post.email").unwrap();
+        assert!(matches!(
+            cat.label_renames("Post", "Customer"),
+            Err(Error::SchemaConflict(_))
+        ));
+    }
+
+    #[test]
+    fn composite_definitions_survive_reopen_and_drop() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cat3.ndb");
+        let key = CompositeKey {
+            label: "User".to_string(),
+            properties: vec!["last_name".to_string(), "first_name".to_string()],
+            unique: false,
+        };
+        let unique = CompositeKey {
+            label: "User".to_string(),
+            properties: vec!["handle".to_string()],
+            unique: true,
+        };
+        {
+            let mut pager = Pager::open(&path).unwrap();
+            let mut cat = IndexCatalog::open_or_create(&mut pager).unwrap();
+            cat.get_or_create(&mut pager, "User.email").unwrap();
+            cat.get_or_create_composite(&mut pager, "User(last_name,first_name)", key.clone())
+                .unwrap();
+            cat.get_or_create_composite(&mut pager, "User(handle)", unique.clone())
+                .unwrap();
+        }
+
+        let mut pager = Pager::open(&path).unwrap();
+        let mut cat = IndexCatalog::open_or_create(&mut pager).unwrap();
+        assert_eq!(cat.get("User.email").unwrap().composite, None);
+        let found: Vec<_> = cat.composites_for_label("User").collect();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].2, &key);
+        let unique_found: Vec<_> = cat.unique_for_label("User").collect();
+        assert_eq!(unique_found.len(), 1);
+        assert_eq!(unique_found[0].2, &unique);
+
+        assert!(
+            cat.remove(&mut pager, "User(last_name,first_name)")
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            cat.remove(&mut pager, "User(last_name,first_name)")
+                .unwrap()
+                .is_none()
+        );
+        drop(cat);
+        let cat = IndexCatalog::open_or_create(&mut pager).unwrap();
+        assert_eq!(cat.composites_for_label("User").count(), 1);
+        assert!(cat.get("User.email").is_some());
+    }
+
+    #[test]
+    fn version_one_pages_are_still_readable() {
+        let mut buf = [0u8; PAGE_SIZE];
+        buf[0..8].copy_from_slice(&MAGIC_V1);
+        buf[8..10].copy_from_slice(&1u16.to_le_bytes());
+        let mut off = HEADER_SIZE;
+        buf[off..off + 2].copy_from_slice(&3u16.to_le_bytes());
+        off += 2;
+        buf[off..off + 3].copy_from_slice(b"a.b");
+        off += 3;
+        buf[off..off + 4].copy_from_slice(&7u32.to_le_bytes());
+        off += 4;
+        buf[off..off + 8].copy_from_slice(&9u64.to_le_bytes());
+
+        let entries = decode_catalog_page(&buf).unwrap();
+        let def = &entries["a.b"];
+        assert_eq!(
+            (def.id, def.root, def.composite.clone()),
+            (7, PageId::new(9), None)
+        );
+    }
+
+    #[test]
+    fn version_two_composite_keys_decode_without_flags() {
+        let mut buf = [0u8; PAGE_SIZE];
+        buf[0..8].copy_from_slice(&MAGIC_V2);
+        buf[8..10].copy_from_slice(&1u16.to_le_bytes());
+        let mut off = HEADER_SIZE;
+        buf[off..off + 2].copy_from_slice(&6u16.to_le_bytes());
+        off += 2;
+        buf[off..off + 6].copy_from_slice(b"L(a,b)");
+        off += 6 + 4 + 8;
+        buf[off] = 2;
+        off += 1;
+        for part in [&b"L"[..], b"a", b"b"] {
+            buf[off..off + 2].copy_from_slice(&(part.len() as u16).to_le_bytes());
+            off += 2;
+            buf[off..off + part.len()].copy_from_slice(part);
+            off += part.len();
+        }
+
+        let entries = decode_catalog_page(&buf).unwrap();
+        let key = entries["L(a,b)"].composite.clone().unwrap();
+        assert_eq!(key.properties, vec!["a", "b"]);
+        assert!(!key.unique);
+    }
+
     #[test]
     #[allow(deprecated)]
     fn equality_seek_and_delete_via_rebuild() {
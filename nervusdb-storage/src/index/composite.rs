@@ -0,0 +1,173 @@
+//! Equality indexes over several properties of one label.
+//!
+//! Each indexed node is stored under
+//! `[index_id: u32 BE][value_1]...[value_n]` with the node id as payload, where
+//! every value uses the prefix-free ordered encoding. A lookup that binds the
+//! first `k` properties therefore becomes a prefix scan, so an index on
+//! `(last_name, first_name)` also answers predicates on `last_name` alone.
+//!
+//! Nodes missing any of the indexed properties (or holding `null`) are not
+//! indexed, matching how equality predicates treat missing values.
+
+use crate::Result;
+use crate::idmap::InternalNodeId;
+use crate::index::btree::BTree;
+use crate::index::catalog::{IndexCatalog, IndexDef};
+use crate::index::ordered_key::encode_ordered_value;
+use crate::pager::Pager;
+use crate::property::PropertyValue;
+
+/// Catalog name of a composite index, e.g. `User(last_name,first_name)`.
+pub fn composite_index_name(label: &str, properties: &[&str]) -> String {
+    format!("{label}({})", properties.join(","))
+}
+
+/// Encodes the key prefix for the leading `values` of a composite index.
+pub(crate) fn composite_key(index_id: u32, values: &[&PropertyValue]) -> Vec<u8> {
+    let mut key = index_id.to_be_bytes().to_vec();
+    for value in values {
+        key.extend_from_slice(&encode_ordered_value(value));
+    }
+    key
+}
+
+/// Returns the indexable tuple, or `None` when any component is missing or null.
+pub(crate) fn indexable_tuple(values: Vec<Option<PropertyValue>>) -> Option<Vec<PropertyValue>> {
+    values
+        .into_iter()
+        .map(|value| value.filter(|v| !matches!(v, PropertyValue::Null)))
+        .collect()
+}
+
+/// Moves `node` from the entry for `old` to the one for `new`.
+pub(crate) fn update_composite(
+    tree: &mut BTree,
+    pager: &mut Pager,
+    index_id: u32,
+    node: InternalNodeId,
+    old: Option<&[PropertyValue]>,
+    new: Option<&[PropertyValue]>,
+) -> Result<()> {
+    if old == new {
+        return Ok(());
+    }
+    if let Some(old) = old {
+        let key = composite_key(index_id, &old.iter().collect::<Vec<_>>());
+        tree.delete(pager, &key, u64::from(node))?;
+    }
+    if let Some(new) = new {
+        let key = composite_key(index_id, &new.iter().collect::<Vec<_>>());
+        tree.insert(pager, &key, u64::from(node))?;
+    }
+    Ok(())
+}
+
+/// Picks the composite index on `label` with the longest run of leading
+/// properties bound by `predicates`, returning it with those bound values in
+/// index order. Ties go to the index with fewer properties.
+pub(crate) fn best_composite_index<'c>(
+    catalog: &'c IndexCatalog,
+    label: &'c str,
+    predicates: &[(String, PropertyValue)],
+) -> Option<(&'c IndexDef, Vec<PropertyValue>)> {
+    let mut best: Option<(&IndexDef, Vec<PropertyValue>, usize)> = None;
+    for (_, def, key) in catalog.composites_for_label(label) {
+        let bound: Vec<PropertyValue> = key
+            .properties
+            .iter()
+            .map_while(|field| {
+                predicates
+                    .iter()
+                    .find(|(name, _)| name == field)
+                    .map(|(_, value)| value.clone())
+            })
+            .collect();
+        if bound.is_empty() {
+            continue;
+        }
+        let better = best.as_ref().map_or(true, |(_, values, width)| {
+            bound.len() > values.len()
+                || (bound.len() == values.len() && key.properties.len() < *width)
+        });
+        if better {
+            best = Some((def, bound, key.properties.len()));
+        }
+    }
+    best.map(|(def, values, _)| (def, values))
+}
+
+/// Returns the nodes whose leading indexed values equal `values`.
+pub(crate) fn lookup_prefix(
+    tree: &BTree,
+    pager: &Pager,
+    index_id: u32,
+    values: &[&PropertyValue],
+) -> Result<Vec<InternalNodeId>> {
+    let prefix = composite_key(index_id, values);
+    let mut cursor = tree.cursor_lower_bound(pager, &prefix)?;
+    let mut nodes = Vec::new();
+    while cursor.is_valid()? {
+        if !cursor.key()?.starts_with(&prefix) {
+            break;
+        }
+        nodes.push(cursor.payload()? as InternalNodeId);
+        if !cursor.advance()? {
+            break;
+        }
+    }
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn prefix_lookup_matches_leading_properties() {
+        let dir = tempdir().unwrap();
+        let mut pager = Pager::open(dir.path().join("composite.ndb")).unwrap();
+        let mut tree = BTree::create(&mut pager).unwrap();
+        let s = |v: &str| PropertyValue::String(v.to_string());
+        let rows = [
+            (1, vec![s("Lovelace"), s("Ada")]),
+            (2, vec![s("Lovelace"), s("Byron")]),
+            (3, vec![s("Love"), s("Ada")]),
+        ];
+        for (node, tuple) in &rows {
+            update_composite(&mut tree, &mut pager, 4, *node, None, Some(tuple)).unwrap();
+        }
+
+        let both = lookup_prefix(&tree, &pager, 4, &[&s("Lovelace"), &s("Ada")]).unwrap();
+        assert_eq!(both, vec![1]);
+        let mut last = lookup_prefix(&tree, &pager, 4, &[&s("Lovelace")]).unwrap();
+        last.sort();
+        assert_eq!(last, vec![1, 2]);
+
+        update_composite(
+            &mut tree,
+            &mut pager,
+            4,
+            1,
+            Some(&rows[0].1),
+            Some(&[s("Lovelace"), s("Augusta")]),
+        )
+        .unwrap();
+        assert!(
+            lookup_prefix(&tree, &pager, 4, &[&s("Lovelace"), &s("Ada")])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn tuples_with_missing_or_null_values_are_not_indexed() {
+        let int = |i| Some(PropertyValue::Int(i));
+        assert!(indexable_tuple(vec![int(1), None]).is_none());
+        assert!(indexable_tuple(vec![int(1), Some(PropertyValue::Null)]).is_none());
+        assert_eq!(
+            indexable_tuple(vec![int(1), int(2)]),
+            Some(vec![PropertyValue::Int(1), PropertyValue::Int(2)])
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use super::params::HnswParams;
 use super::storage::{GraphStorage, VectorStorage};
 use crate::Result;
-use crate::index::vector::euclidean_distance;
+use crate::index::vector::{VectorMetric, euclidean_distance};
 use ordered_float::OrderedFloat;
 use rand::Rng;
 use std::cmp::Reverse;
@@ -50,6 +50,18 @@ impl<V, G> HnswIndex<V, G> {
         })
     }
 
+    pub fn params(&self) -> &HnswParams {
+        &self.params
+    }
+
+    pub fn vector_store(&self) -> &V {
+        &self.vector_store
+    }
+
+    pub fn graph_store(&self) -> &G {
+        &self.graph_store
+    }
+
     fn random_level(&self) -> u8 {
         let mut rng = rand::thread_rng();
         let ml = 1.0 / (self.params.m as f64).ln();
@@ -233,6 +245,14 @@ impl<V, G> HnswIndex<V, G> {
         Ok(())
     }
 
+    /// The stored vector of `id`; fails when it has none.
+    pub fn get_vector<Ctx>(&mut self, ctx: &mut Ctx, id: u32) -> Result<Vec<f32>>
+    where
+        V: VectorStorage<Ctx>,
+    {
+        self.vector_store.get_vector(ctx, id)
+    }
+
     pub fn search<Ctx>(&mut self, ctx: &mut Ctx, query: &[f32], k: usize) -> Result<Vec<(u32, f32)>>
     where
         V: VectorStorage<Ctx>,
@@ -268,8 +288,9 @@ impl<V, G> HnswIndex<V, G> {
             }
         }
 
-        // Search base layer with ef_search
-        let mut candidates = self.search_layer(ctx, query, &[curr_ep], self.params.ef_search, 0)?;
+        // Search base layer with ef_search, widened so it can return k results.
+        let ef = self.params.ef_search.max(k);
+        let mut candidates = self.search_layer(ctx, query, &[curr_ep], ef, 0)?;
 
         let mut results = Vec::new();
         while let Some(Reverse((dist, id))) = candidates.pop() {
@@ -280,4 +301,36 @@ impl<V, G> HnswIndex<V, G> {
         }
         Ok(results)
     }
+
+    /// Brute-force k-NN over every stored vector under `metric`: exact, but
+    /// linear in the number of vectors. Pass `usize::MAX` as `k` to rank
+    /// every vector.
+    pub fn search_exact<Ctx>(
+        &mut self,
+        ctx: &mut Ctx,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+    ) -> Result<Vec<(u32, f32)>>
+    where
+        V: VectorStorage<Ctx>,
+    {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut heap: BinaryHeap<(OrderedFloat<f32>, u32)> =
+            BinaryHeap::with_capacity(k.saturating_add(1).min(4096));
+        for id in self.vector_store.vector_ids(ctx)? {
+            let vector = self.vector_store.get_vector(ctx, id)?;
+            heap.push((OrderedFloat(metric.distance(query, &vector)), id));
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+        Ok(heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(dist, id)| (id, dist.into_inner()))
+            .collect())
+    }
 }
@@ -1,4 +1,5 @@
-#[derive(Clone, Debug)]
+/// Tunables of the HNSW vector index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct HnswParams {
     /// Max number of connections per element in all layers.
     pub m: usize,
@@ -1,6 +1,6 @@
 use crate::blob_store::BlobStore;
 use crate::index::btree::BTree;
-use crate::pager::Pager;
+use crate::pager::{PageId, Pager};
 use crate::{Error, Result};
 use std::collections::{HashMap, VecDeque};
 
@@ -8,6 +8,8 @@ use std::collections::{HashMap, VecDeque};
 pub trait VectorStorage<Ctx> {
     fn insert_vector(&mut self, ctx: &mut Ctx, id: u32, vector: &[f32]) -> Result<()>;
     fn get_vector(&mut self, ctx: &mut Ctx, id: u32) -> Result<Vec<f32>>;
+    /// Ids of every stored vector, in ascending order.
+    fn vector_ids(&mut self, ctx: &mut Ctx) -> Result<Vec<u32>>;
 }
 
 /// Trait for storing the HNSW graph structure.
@@ -37,6 +39,11 @@ impl PersistentVectorStorage {
             cache: VectorCache::new(DEFAULT_VECTOR_CACHE_CAP),
         }
     }
+
+    /// Current root of the backing tree; it moves when the root splits.
+    pub fn root(&self) -> PageId {
+        self.btree.root()
+    }
 }
 
 impl VectorStorage<Pager> for PersistentVectorStorage {
@@ -84,6 +91,23 @@ impl VectorStorage<Pager> for PersistentVectorStorage {
         self.cache.put(id, vector.clone());
         Ok(vector)
     }
+
+    fn vector_ids(&mut self, pager: &mut Pager) -> Result<Vec<u32>> {
+        let mut ids = Vec::new();
+        let mut cursor = self.btree.cursor_lower_bound(pager, &[TAG_VECTOR])?;
+        while cursor.is_valid()? {
+            let key = cursor.key()?;
+            if key.len() != 5 || key[0] != TAG_VECTOR {
+                break;
+            }
+            let id = u32::from_be_bytes(key[1..5].try_into().unwrap());
+            if ids.last() != Some(&id) {
+                ids.push(id);
+            }
+            cursor.advance()?;
+        }
+        Ok(ids)
+    }
 }
 
 const DEFAULT_VECTOR_CACHE_CAP: usize = 1024;
@@ -139,6 +163,11 @@ impl PersistentGraphStorage {
     pub fn new(btree: BTree) -> Self {
         Self { btree }
     }
+
+    /// Current root of the backing tree; it moves when the root splits.
+    pub fn root(&self) -> PageId {
+        self.btree.root()
+    }
 }
 
 impl GraphStorage<Pager> for PersistentGraphStorage {
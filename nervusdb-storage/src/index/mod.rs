@@ -1,5 +1,8 @@
 pub mod btree;
 pub mod catalog;
+pub mod composite;
 pub mod hnsw;
 pub mod ordered_key;
+pub mod text;
+pub mod unique;
 pub mod vector;
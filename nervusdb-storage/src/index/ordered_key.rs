@@ -73,14 +73,43 @@ pub fn encode_ordered_value(v: &PropertyValue) -> Vec<u8> {
             out.push(0x00);
             out
         }
-        PropertyValue::List(_) => {
+        PropertyValue::List(_) | PropertyValue::IntArray(_) | PropertyValue::FloatArray(_) => {
             // For MVP: Lists only sort by tag. Full sorting is complex.
+            // Arrays are lists to queries, so they share the tag.
             vec![0x07]
         }
         PropertyValue::Map(_) => {
             // For MVP: Maps only sort by tag.
             vec![0x08]
         }
+        PropertyValue::Date(d) => {
+            let mut out = Vec::with_capacity(1 + 4);
+            out.push(0x09);
+            let u = (*d as u32) ^ 0x8000_0000;
+            out.extend_from_slice(&u.to_be_bytes());
+            out
+        }
+        PropertyValue::LocalTime(t) => {
+            let mut out = Vec::with_capacity(1 + 8);
+            out.push(0x0A);
+            let u = (*t as u64) ^ 0x8000_0000_0000_0000;
+            out.extend_from_slice(&u.to_be_bytes());
+            out
+        }
+        PropertyValue::Duration {
+            months,
+            days,
+            nanos,
+        } => {
+            // Component-wise order; durations have no total order across months and days.
+            let mut out = Vec::with_capacity(1 + 24);
+            out.push(0x0B);
+            for part in [months, days, nanos] {
+                let u = (*part as u64) ^ 0x8000_0000_0000_0000;
+                out.extend_from_slice(&u.to_be_bytes());
+            }
+            out
+        }
     }
 }
 
@@ -148,6 +177,27 @@ mod tests {
         assert_eq!(encoded, sorted);
     }
 
+    #[test]
+    fn ordered_temporal_values() {
+        assert_ordered(vec![
+            PropertyValue::Date(-1),
+            PropertyValue::Date(0),
+            PropertyValue::Date(19_723),
+            PropertyValue::LocalTime(0),
+            PropertyValue::LocalTime(86_399_999_999_999),
+            PropertyValue::Duration {
+                months: -1,
+                days: 40,
+                nanos: 0,
+            },
+            PropertyValue::Duration {
+                months: 0,
+                days: 1,
+                nanos: -5,
+            },
+        ]);
+    }
+
     #[test]
     fn composite_key_orders_by_index_then_value_then_id() {
         let k1 = encode_index_key(1, &PropertyValue::Int(7), 10);
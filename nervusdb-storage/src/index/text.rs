@@ -0,0 +1,148 @@
+//! Trigram side index for substring search over text properties.
+//!
+//! Every distinct 3-byte window of an indexed string is stored as a B-Tree key
+//! `[index_id: u32 BE][trigram: 3B][node: u32 BE]`. Because a `CONTAINS` match
+//! must contain every trigram of the needle, intersecting the needle's postings
+//! yields a candidate superset; callers still evaluate the predicate on those
+//! candidates, but no longer decode every value in a label scan.
+//!
+//! Trigrams are taken over UTF-8 bytes, so byte-level substring containment
+//! (which is what `CONTAINS` means for valid UTF-8) is preserved.
+
+use crate::Result;
+use crate::idmap::InternalNodeId;
+use crate::index::btree::BTree;
+use crate::pager::Pager;
+use std::collections::BTreeSet;
+
+/// Catalog names of text indexes end with this suffix, e.g. `Doc.body#text`.
+pub const TEXT_INDEX_SUFFIX: &str = "#text";
+
+/// Needles shorter than this cannot be answered from the index.
+pub const MIN_NEEDLE_BYTES: usize = 3;
+
+pub fn text_index_name(label: &str, field: &str) -> String {
+    format!("{label}.{field}{TEXT_INDEX_SUFFIX}")
+}
+
+pub(crate) fn trigrams(text: &str) -> BTreeSet<[u8; 3]> {
+    text.as_bytes()
+        .windows(3)
+        .map(|w| [w[0], w[1], w[2]])
+        .collect()
+}
+
+fn posting_prefix(index_id: u32, gram: &[u8; 3]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(4 + 3 + 4);
+    key.extend_from_slice(&index_id.to_be_bytes());
+    key.extend_from_slice(gram);
+    key
+}
+
+fn posting_key(index_id: u32, gram: &[u8; 3], node: InternalNodeId) -> Vec<u8> {
+    let mut key = posting_prefix(index_id, gram);
+    key.extend_from_slice(&node.to_be_bytes());
+    key
+}
+
+/// Moves `node` from the postings of `old` to those of `new`, touching only the
+/// trigrams that differ.
+pub(crate) fn update_text(
+    tree: &mut BTree,
+    pager: &mut Pager,
+    index_id: u32,
+    node: InternalNodeId,
+    old: Option<&str>,
+    new: Option<&str>,
+) -> Result<()> {
+    let old_grams = old.map(trigrams).unwrap_or_default();
+    let new_grams = new.map(trigrams).unwrap_or_default();
+    for gram in old_grams.difference(&new_grams) {
+        tree.delete(pager, &posting_key(index_id, gram, node), u64::from(node))?;
+    }
+    for gram in new_grams.difference(&old_grams) {
+        tree.insert(pager, &posting_key(index_id, gram, node), u64::from(node))?;
+    }
+    Ok(())
+}
+
+/// Returns the sorted nodes whose indexed text contains every trigram of `needle`,
+/// or `None` when the needle is too short to narrow the search.
+pub(crate) fn lookup_candidates(
+    tree: &BTree,
+    pager: &Pager,
+    index_id: u32,
+    needle: &str,
+) -> Result<Option<Vec<InternalNodeId>>> {
+    if needle.len() < MIN_NEEDLE_BYTES {
+        return Ok(None);
+    }
+
+    let mut candidates: Option<BTreeSet<InternalNodeId>> = None;
+    for gram in trigrams(needle) {
+        let prefix = posting_prefix(index_id, &gram);
+        let mut postings = BTreeSet::new();
+        let mut cursor = tree.cursor_lower_bound(pager, &prefix)?;
+        while cursor.is_valid()? {
+            if !cursor.key()?.starts_with(&prefix) {
+                break;
+            }
+            let node = cursor.payload()? as InternalNodeId;
+            if candidates.as_ref().map_or(true, |c| c.contains(&node)) {
+                postings.insert(node);
+            }
+            if !cursor.advance()? {
+                break;
+            }
+        }
+        let empty = postings.is_empty();
+        candidates = Some(postings);
+        if empty {
+            break;
+        }
+    }
+
+    Ok(candidates.map(|c| c.into_iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_are_nodes_holding_every_needle_trigram() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pager = Pager::open(dir.path().join("text.ndb")).unwrap();
+        let mut tree = BTree::create(&mut pager).unwrap();
+
+        let docs = [
+            (1, "the quick brown fox"),
+            (2, "a quick fix"),
+            (3, "東京タワー"),
+        ];
+        for (node, text) in docs {
+            update_text(&mut tree, &mut pager, 9, node, None, Some(text)).unwrap();
+        }
+
+        let lookup = |tree: &BTree, pager: &Pager, needle| {
+            lookup_candidates(tree, pager, 9, needle).unwrap()
+        };
+        assert_eq!(lookup(&tree, &pager, "quick"), Some(vec![1, 2]));
+        assert_eq!(lookup(&tree, &pager, "brown"), Some(vec![1]));
+        assert_eq!(lookup(&tree, &pager, "京タ"), Some(vec![3]));
+        assert_eq!(lookup(&tree, &pager, "zebra"), Some(vec![]));
+        assert_eq!(lookup(&tree, &pager, "qu"), None);
+
+        update_text(
+            &mut tree,
+            &mut pager,
+            9,
+            2,
+            Some("a quick fix"),
+            Some("slow"),
+        )
+        .unwrap();
+        assert_eq!(lookup(&tree, &pager, "quick"), Some(vec![1]));
+        assert_eq!(lookup(&tree, &pager, "slow"), Some(vec![2]));
+    }
+}
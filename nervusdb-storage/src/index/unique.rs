@@ -0,0 +1,97 @@
+//! Unique constraints on a single node property.
+//!
+//! A constraint is a one-property composite index flagged as unique in the
+//! catalog, so commits maintain it like any other composite index. Writes are
+//! checked as they are staged: against the committed index, and against the
+//! values other nodes claimed earlier in the same transaction.
+
+use crate::idmap::InternalNodeId;
+use crate::index::composite::composite_index_name;
+use std::collections::{BTreeMap, HashMap};
+
+/// Catalog name of the unique constraint on `label.property`, e.g. `User(email)`.
+pub fn unique_constraint_name(label: &str, property: &str) -> String {
+    composite_index_name(label, &[property])
+}
+
+/// A claimed value: `(constraint name, encoded value)`.
+type Claim = (String, Vec<u8>);
+
+/// Values claimed by nodes of one write transaction, per constraint.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct UniqueClaims {
+    /// Claim -> claiming node.
+    owners: HashMap<Claim, InternalNodeId>,
+    /// `(node, property)` -> the claims it holds.
+    held: BTreeMap<(InternalNodeId, String), Vec<Claim>>,
+}
+
+impl UniqueClaims {
+    /// Returns the node already holding `value` under `constraint`, if any.
+    pub(crate) fn owner(&self, constraint: &str, value: &[u8]) -> Option<InternalNodeId> {
+        self.owners
+            .get(&(constraint.to_string(), value.to_vec()))
+            .copied()
+    }
+
+    /// Records that `node` now holds `value` for `property` under `constraint`.
+    pub(crate) fn claim(
+        &mut self,
+        constraint: &str,
+        node: InternalNodeId,
+        property: &str,
+        value: Vec<u8>,
+    ) {
+        let key = (constraint.to_string(), value);
+        self.owners.insert(key.clone(), node);
+        self.held
+            .entry((node, property.to_string()))
+            .or_default()
+            .push(key);
+    }
+
+    /// Releases every value `node` holds for `property`.
+    pub(crate) fn release(&mut self, node: InternalNodeId, property: &str) {
+        if let Some(keys) = self.held.remove(&(node, property.to_string())) {
+            for key in keys {
+                self.owners.remove(&key);
+            }
+        }
+    }
+
+    /// Releases every value `node` holds.
+    pub(crate) fn release_node(&mut self, node: InternalNodeId) {
+        let properties: Vec<String> = self
+            .held
+            .range((node, String::new())..)
+            .take_while(|((n, _), _)| *n == node)
+            .map(|((_, property), _)| property.clone())
+            .collect();
+        for property in properties {
+            self.release(node, &property);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_are_released_per_property_and_per_node() {
+        let mut claims = UniqueClaims::default();
+        claims.claim("User(email)", 1, "email", b"a".to_vec());
+        claims.claim("User(handle)", 1, "handle", b"h".to_vec());
+        assert_eq!(claims.owner("User(email)", b"a"), Some(1));
+        assert_eq!(claims.owner("User(handle)", b"a"), None);
+
+        claims.release(1, "email");
+        assert_eq!(claims.owner("User(email)", b"a"), None);
+        assert_eq!(claims.owner("User(handle)", b"h"), Some(1));
+
+        claims.claim("User(email)", 2, "email", b"a".to_vec());
+        claims.release_node(1);
+        assert_eq!(claims.owner("User(handle)", b"h"), None);
+        assert_eq!(claims.owner("User(email)", b"a"), Some(2));
+    }
+}
@@ -9,6 +9,39 @@ pub fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
         .sqrt()
 }
 
+/// How vector search ranks stored vectors; smaller distances rank first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VectorMetric {
+    /// Euclidean distance. This is the metric the HNSW index is built with.
+    #[default]
+    L2,
+    /// `1 - cosine similarity`; zero vectors are at distance 1 from everything.
+    Cosine,
+    /// Negated dot product, so the largest product ranks first.
+    Dot,
+}
+
+impl VectorMetric {
+    pub fn distance(self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::L2 => euclidean_distance(a, b),
+            Self::Cosine => {
+                let norms = dot(a, a).sqrt() * dot(b, b).sqrt();
+                if norms == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot(a, b) / norms
+                }
+            }
+            Self::Dot => -dot(a, b),
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
 /// Interface for vector Similarity Search.
 pub trait VectorIndex {
     /// Inserts a vector for the given internal node ID.
@@ -74,4 +107,17 @@ mod tests {
         assert_eq!(results[0].0, 3); // Origin is closest
         assert_eq!(results.len(), 3);
     }
+
+    #[test]
+    fn metrics_rank_smaller_distances_first() {
+        let q = [1.0, 0.0];
+        assert_eq!(VectorMetric::L2.distance(&q, &[4.0, 4.0]), 5.0);
+        assert!(VectorMetric::Cosine.distance(&q, &[5.0, 0.0]).abs() < 1e-6);
+        assert!((VectorMetric::Cosine.distance(&q, &[0.0, 2.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(VectorMetric::Cosine.distance(&q, &[0.0, 0.0]), 1.0);
+        assert!(
+            VectorMetric::Dot.distance(&q, &[3.0, 0.0])
+                < VectorMetric::Dot.distance(&q, &[1.0, 9.0])
+        );
+    }
 }
@@ -170,6 +170,21 @@ impl LabelInterner {
         self.i2s.len() as LabelId
     }
 
+    /// Give the label `id` the name `name`, keeping its ID.
+    ///
+    /// Returns false if `id` is unknown or `name` belongs to another label.
+    pub fn rename(&mut self, id: LabelId, name: &str) -> bool {
+        match self.s2i.get(name) {
+            Some(existing) => return *existing == id,
+            None if id as usize >= self.i2s.len() => return false,
+            None => {}
+        }
+        let old = std::mem::replace(&mut self.i2s[id as usize], name.to_string());
+        self.s2i.remove(&old);
+        self.s2i.insert(name.to_string(), id);
+        true
+    }
+
     /// Merge another interner's labels into this one.
     ///
     /// Returns the number of labels that were added.
@@ -296,4 +311,21 @@ mod tests {
         assert_eq!(added, 1); // Only Comment was added
         assert_eq!(interner1.len(), 3);
     }
+
+    #[test]
+    fn test_rename() {
+        let mut interner = LabelInterner::new();
+        let user = interner.get_or_create("User");
+        let post = interner.get_or_create("Post");
+
+        assert!(interner.rename(user, "Customer"));
+        assert_eq!(interner.get_id("Customer"), Some(user));
+        assert_eq!(interner.get_id("User"), None);
+        assert_eq!(interner.get_name(user), Some("Customer"));
+
+        assert!(!interner.rename(user, "Post"));
+        assert!(!interner.rename(7, "Ghost"));
+        assert!(interner.rename(post, "Post"));
+        assert_eq!(interner.len(), 2);
+    }
 }
@@ -1,14 +1,24 @@
 pub mod api;
+pub mod backend;
 pub mod backup;
 pub mod blob_store;
 pub mod bulkload;
+pub mod checkpointer;
+pub mod cold_store;
 pub mod csr;
 pub mod engine;
 mod error;
+pub mod expiry;
+mod external_sort;
 pub mod idmap;
 pub mod index;
 pub mod label_interner;
 pub mod memtable;
+pub mod names;
+pub mod operation;
+pub mod packed;
+pub mod page_cache;
+pub mod page_lsn;
 pub mod pager;
 pub mod property;
 mod read_path_api_iter;
@@ -32,10 +42,15 @@ mod read_path_run_state;
 mod read_path_stats;
 mod read_path_symbols;
 mod read_path_tombstones;
+pub mod schema;
 pub mod snapshot;
 pub mod stats;
 pub mod vacuum;
 pub mod wal;
+pub mod wal_archive;
+pub mod wal_tail;
+mod write_stamps;
+pub mod write_throttle;
 
 pub use crate::error::{Error, Result};
 
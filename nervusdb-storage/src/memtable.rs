@@ -3,7 +3,7 @@ use crate::property::PropertyValue;
 use crate::snapshot::{EdgeKey, L0Run, RelTypeId};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct MemTable {
     out: HashMap<InternalNodeId, Vec<EdgeKey>>,
     in_: HashMap<InternalNodeId, Vec<EdgeKey>>,
@@ -110,6 +110,53 @@ impl MemTable {
             .insert(key.to_string());
     }
 
+    /// Returns the change staged for `node`'s `key`: `Some(Some(value))` when
+    /// set, `Some(None)` when removed and `None` when untouched.
+    pub fn staged_node_property(
+        &self,
+        node: InternalNodeId,
+        key: &str,
+    ) -> Option<Option<&PropertyValue>> {
+        if let Some(value) = self.node_properties.get(&node).and_then(|p| p.get(key)) {
+            return Some(Some(value));
+        }
+        self.removed_node_properties
+            .get(&node)
+            .is_some_and(|keys| keys.contains(key))
+            .then_some(None)
+    }
+
+    pub fn is_node_tombstoned(&self, node: InternalNodeId) -> bool {
+        self.tombstoned_nodes.contains(&node)
+    }
+
+    pub fn is_edge_tombstoned(&self, edge: EdgeKey) -> bool {
+        self.tombstoned_edges.contains(&edge)
+    }
+
+    /// Edges created in this transaction and not tombstoned since.
+    pub fn created_edges(&self) -> BTreeSet<EdgeKey> {
+        self.out.values().flatten().copied().collect()
+    }
+
+    /// Nodes with properties set or removed in this transaction.
+    pub fn nodes_with_property_changes(&self) -> BTreeSet<InternalNodeId> {
+        self.node_properties
+            .keys()
+            .chain(self.removed_node_properties.keys())
+            .copied()
+            .collect()
+    }
+
+    /// Edges with properties set or removed in this transaction.
+    pub fn edges_with_property_changes(&self) -> BTreeSet<EdgeKey> {
+        self.edge_properties
+            .keys()
+            .chain(self.removed_edge_properties.keys())
+            .copied()
+            .collect()
+    }
+
     /// Get removed node properties for WAL writing.
     pub fn removed_node_properties_for_wal(&self) -> Vec<(InternalNodeId, String)> {
         self.removed_node_properties
@@ -0,0 +1,290 @@
+use std::fmt;
+
+/// Default longest label, relationship type or property key, in bytes.
+pub const DEFAULT_MAX_NAME_BYTES: usize = 256;
+
+/// Prefix reserved for the system properties the engine maintains. Users
+/// can read such keys but not write them.
+pub const SYSTEM_PROPERTY_PREFIX: &str = "__";
+
+/// Txid of the transaction that created a node or relationship.
+pub const CREATED_TX: &str = "__created_tx";
+/// Txid of the last transaction that wrote a node or relationship.
+pub const UPDATED_TX: &str = "__updated_tx";
+/// Commit time (`DateTime`) of the last transaction that wrote a node or
+/// relationship.
+pub const UPDATED_AT: &str = "__updated_at";
+
+/// Whether `key` is in the reserved system property namespace.
+pub fn is_system_property(key: &str) -> bool {
+    key.starts_with(SYSTEM_PROPERTY_PREFIX)
+}
+
+/// Fails with [`crate::Error::InvalidName`] if `key` is a system property,
+/// which only the engine may write.
+pub fn check_writable_property(key: &str) -> crate::Result<()> {
+    if !is_system_property(key) {
+        return Ok(());
+    }
+    Err(crate::Error::InvalidName {
+        kind: NameKind::PropertyKey,
+        name: preview(key),
+        reason: format!("the {SYSTEM_PROPERTY_PREFIX} prefix is reserved for system properties"),
+    })
+}
+
+/// Fails with [`crate::Error::InvalidName`] unless `key` is a system
+/// property; the inverse of [`check_writable_property`].
+pub fn check_system_property(key: &str) -> crate::Result<()> {
+    if is_system_property(key) {
+        return Ok(());
+    }
+    Err(crate::Error::InvalidName {
+        kind: NameKind::PropertyKey,
+        name: preview(key),
+        reason: format!("only {SYSTEM_PROPERTY_PREFIX} keys are system properties"),
+    })
+}
+
+/// Fails with [`crate::Error::InvalidName`] unless `prefix` can be bound
+/// to a namespace: a letter or `_`, then letters, digits, `_`, `-` or `.`,
+/// not ending in `.`.
+pub fn check_namespace_prefix(prefix: &str) -> crate::Result<()> {
+    let mut chars = prefix.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !prefix.ends_with('.');
+    if valid {
+        return Ok(());
+    }
+    Err(crate::Error::InvalidName {
+        kind: NameKind::NamespacePrefix,
+        name: preview(prefix),
+        reason: "a prefix is a letter or _ followed by letters, digits, _, - or .".to_string(),
+    })
+}
+
+/// Fails with [`crate::Error::InvalidName`] unless `iri` is absolute: a
+/// scheme such as `https:` and no whitespace, `<`, `>` or `"`.
+pub fn check_namespace_iri(iri: &str) -> crate::Result<()> {
+    let reason = match iri.split_once(':') {
+        None => Some("the IRI has no scheme"),
+        Some((scheme, _))
+            if !scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                || !scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) =>
+        {
+            Some("the IRI has no scheme")
+        }
+        Some(_)
+            if iri
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '<' | '>' | '"')) =>
+        {
+            Some("the IRI contains whitespace, <, > or \"")
+        }
+        Some(_) => None,
+    };
+    match reason {
+        None => Ok(()),
+        Some(reason) => Err(crate::Error::InvalidName {
+            kind: NameKind::NamespaceIri,
+            name: preview(iri),
+            reason: reason.to_string(),
+        }),
+    }
+}
+
+/// What a name is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameKind {
+    Label,
+    RelType,
+    PropertyKey,
+    NamespacePrefix,
+    NamespaceIri,
+}
+
+impl fmt::Display for NameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NameKind::Label => "label",
+            NameKind::RelType => "relationship type",
+            NameKind::PropertyKey => "property key",
+            NameKind::NamespacePrefix => "namespace prefix",
+            NameKind::NamespaceIri => "namespace IRI",
+        })
+    }
+}
+
+/// Rules new label, relationship type and property key names must meet.
+///
+/// Names already in the database keep working when the limits tighten;
+/// [`NameLimits::usage`] reports them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameLimits {
+    /// Longest name in UTF-8 bytes.
+    pub max_bytes: usize,
+    /// Accept control characters such as tabs and newlines. NUL is always
+    /// rejected.
+    pub allow_control_chars: bool,
+}
+
+impl Default for NameLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_NAME_BYTES,
+            allow_control_chars: false,
+        }
+    }
+}
+
+/// A name that is close to or past the limits, from
+/// [`NameLimits::usage`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct NameUsage {
+    pub kind: NameKind,
+    pub name: String,
+    /// Length in UTF-8 bytes.
+    pub bytes: usize,
+    /// Why the name would be rejected today, if it would be.
+    pub problem: Option<String>,
+}
+
+impl NameLimits {
+    /// Fails with [`crate::Error::InvalidName`] unless `name` may be used as
+    /// a new `kind`.
+    pub fn check(&self, kind: NameKind, name: &str) -> crate::Result<()> {
+        match self.problem(name) {
+            None => Ok(()),
+            Some(reason) => Err(crate::Error::InvalidName {
+                kind,
+                name: preview(name),
+                reason,
+            }),
+        }
+    }
+
+    /// Reports `name` when it fails [`NameLimits::check`] or uses at least
+    /// three quarters of `max_bytes`.
+    pub fn usage(&self, kind: NameKind, name: &str) -> Option<NameUsage> {
+        let problem = self.problem(name);
+        (problem.is_some() || name.len() * 4 >= self.max_bytes * 3).then(|| NameUsage {
+            kind,
+            name: name.to_string(),
+            bytes: name.len(),
+            problem,
+        })
+    }
+
+    fn problem(&self, name: &str) -> Option<String> {
+        if name.len() > self.max_bytes {
+            return Some(format!(
+                "name is {} bytes, limit is {}",
+                name.len(),
+                self.max_bytes
+            ));
+        }
+        if name.contains('\0') {
+            return Some("name contains a NUL byte".to_string());
+        }
+        if !self.allow_control_chars
+            && let Some(c) = name.chars().find(|c| c.is_control())
+        {
+            return Some(format!(
+                "name contains control character U+{:04X}",
+                c as u32
+            ));
+        }
+        None
+    }
+}
+
+/// Shortens `name` for error messages, which may be shown for megabyte
+/// inputs.
+fn preview(name: &str) -> String {
+    const PREVIEW_CHARS: usize = 40;
+    match name.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}...", &name[..end]),
+        None => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NameKind, NameLimits, check_namespace_iri, check_namespace_prefix, check_writable_property,
+    };
+
+    #[test]
+    fn check_rejects_long_and_control_names() {
+        let limits = NameLimits {
+            max_bytes: 8,
+            allow_control_chars: false,
+        };
+        assert!(limits.check(NameKind::Label, "Person").is_ok());
+        assert!(limits.check(NameKind::Label, "Persönli").is_err());
+
+        let err = limits
+            .check(NameKind::PropertyKey, &"k".repeat(1 << 20))
+            .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("property key"), "{msg}");
+        assert!(msg.contains("limit is 8"), "{msg}");
+        assert!(msg.len() < 200, "{msg}");
+
+        let err = limits.check(NameKind::RelType, "a\0b").unwrap_err();
+        assert!(err.to_string().contains("NUL"), "{err}");
+        assert!(limits.check(NameKind::Label, "a\tb").is_err());
+
+        let lenient = NameLimits {
+            allow_control_chars: true,
+            ..limits
+        };
+        assert!(lenient.check(NameKind::Label, "a\tb").is_ok());
+        assert!(lenient.check(NameKind::Label, "a\0b").is_err());
+    }
+
+    #[test]
+    fn usage_reports_names_near_or_past_the_limit() {
+        let limits = NameLimits {
+            max_bytes: 8,
+            allow_control_chars: false,
+        };
+        assert_eq!(limits.usage(NameKind::Label, "short"), None);
+        let near = limits.usage(NameKind::Label, "sixchr").unwrap();
+        assert_eq!((near.bytes, near.problem), (6, None));
+        let over = limits.usage(NameKind::Label, "ninechars").unwrap();
+        assert!(over.problem.unwrap().contains("limit is 8"));
+    }
+
+    #[test]
+    fn system_property_keys_are_not_writable() {
+        assert!(check_writable_property("created").is_ok());
+        assert!(check_writable_property("_private").is_ok());
+        let err = check_writable_property("__created_tx").unwrap_err();
+        assert!(err.to_string().contains("reserved"), "{err}");
+    }
+
+    #[test]
+    fn namespace_prefixes_and_iris_are_checked() {
+        for prefix in ["foaf", "_x", "schema-org", "v1.2"] {
+            assert!(check_namespace_prefix(prefix).is_ok(), "{prefix}");
+        }
+        for prefix in ["", "1x", "a:b", "a.", "a b"] {
+            assert!(check_namespace_prefix(prefix).is_err(), "{prefix}");
+        }
+        for iri in ["http://xmlns.com/foaf/0.1/", "urn:isbn:", "tag:x.org,2026:"] {
+            assert!(check_namespace_iri(iri).is_ok(), "{iri}");
+        }
+        for iri in ["", "no-scheme", "1ab:x", "http://a b/", "http://<x>/"] {
+            assert!(check_namespace_iri(iri).is_err(), "{iri}");
+        }
+        let err = check_namespace_iri("relative/path").unwrap_err();
+        assert!(err.to_string().contains("namespace IRI"), "{err}");
+    }
+}
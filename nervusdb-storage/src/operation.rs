@@ -0,0 +1,166 @@
+//! Progress and cooperative cancellation for long-running maintenance
+//! (compaction, vacuum, bulk load).
+//!
+//! An [`OperationHandle`] is shared between the thread doing the work and any
+//! thread watching it. The worker publishes `(done, total)` as it goes and
+//! only stops at points where giving up leaves the database as it was; a
+//! cancelled operation fails with [`Error::Cancelled`]. Units are specific to
+//! each operation (properties sunk, pages copied, rows loaded), so they are
+//! only meaningful relative to `total`.
+
+use crate::{Error, Result};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Where an operation is, as last published by the worker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub done: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    /// `done` as a percentage of `total`; 0 until the total is known.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+/// Cloneable handle to poll or cancel a running operation from another
+/// thread. One handle can be reused; each run restarts its progress.
+#[derive(Debug, Clone, Default)]
+pub struct OperationHandle {
+    state: Arc<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    done: AtomicU64,
+    total: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl OperationHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the operation to stop at its next safe point. Cancelling before
+    /// the operation starts makes it fail straight away.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            done: self.state.done.load(Ordering::Relaxed),
+            total: self.state.total.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Worker side of an operation: publishes progress to the handle and calls
+/// the caller's `(done, total)` callback about once per percent.
+pub(crate) struct Tracker<'a> {
+    handle: &'a OperationHandle,
+    callback: &'a mut dyn FnMut(u64, u64),
+    done: u64,
+    total: u64,
+    reported: u64,
+}
+
+impl<'a> Tracker<'a> {
+    pub(crate) fn new(handle: &'a OperationHandle, callback: &'a mut dyn FnMut(u64, u64)) -> Self {
+        Self {
+            handle,
+            callback,
+            done: 0,
+            total: 0,
+            reported: 0,
+        }
+    }
+
+    /// Sets the amount of work once it is known and reports `(0, total)`.
+    pub(crate) fn start(&mut self, total: u64) {
+        self.total = total;
+        self.done = 0;
+        self.reported = 0;
+        self.handle.state.total.store(total, Ordering::Relaxed);
+        self.handle.state.done.store(0, Ordering::Relaxed);
+        (self.callback)(0, total);
+    }
+
+    pub(crate) fn advance(&mut self, units: u64) {
+        self.done = self.done.saturating_add(units).min(self.total);
+        self.handle.state.done.store(self.done, Ordering::Relaxed);
+        let step = (self.total / 100).max(1);
+        if self.done == self.total || self.done - self.reported >= step {
+            self.reported = self.done;
+            (self.callback)(self.done, self.total);
+        }
+    }
+
+    pub(crate) fn finish(&mut self) {
+        if self.done < self.total {
+            self.advance(self.total - self.done);
+        }
+    }
+
+    /// A safe point: fails with [`Error::Cancelled`] if cancellation was
+    /// requested.
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.handle.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_reports_about_once_per_percent() {
+        let handle = OperationHandle::new();
+        let mut calls = Vec::new();
+        let mut callback = |done, total| calls.push((done, total));
+        let mut tracker = Tracker::new(&handle, &mut callback);
+        tracker.start(1000);
+        for _ in 0..1000 {
+            tracker.advance(1);
+        }
+        tracker.finish();
+        assert_eq!(
+            handle.progress(),
+            Progress {
+                done: 1000,
+                total: 1000
+            }
+        );
+        assert_eq!(handle.progress().percent(), 100.0);
+        assert_eq!(calls.len(), 101);
+        assert_eq!(calls.first(), Some(&(0, 1000)));
+        assert_eq!(calls.last(), Some(&(1000, 1000)));
+    }
+
+    #[test]
+    fn cancelled_handle_fails_the_next_check() {
+        let handle = OperationHandle::new();
+        let watcher = handle.clone();
+        let mut callback = |_, _| {};
+        let tracker = Tracker::new(&handle, &mut callback);
+        assert!(tracker.check().is_ok());
+        watcher.cancel();
+        assert!(matches!(tracker.check(), Err(Error::Cancelled)));
+        assert_eq!(Progress::default().percent(), 0.0);
+    }
+}
@@ -0,0 +1,154 @@
+//! Least-recently-used cache of data pages read through the [`Pager`].
+//!
+//! Reads of property B-trees, blobs and index pages hit the file on every
+//! lookup otherwise. The cache keeps the hottest pages in memory; its page
+//! set, most recent first, is what [`GraphEngine`] saves on close to warm
+//! the cache again after a restart.
+//!
+//! [`Pager`]: crate::pager::Pager
+//! [`GraphEngine`]: crate::engine::GraphEngine
+
+use crate::PAGE_SIZE;
+use std::collections::{BTreeMap, HashMap};
+
+/// Pages kept by default: 32 MiB at the 8 KiB page size.
+pub const DEFAULT_PAGE_CACHE_PAGES: usize = 4096;
+
+/// Counters reported by [`GraphEngine::page_cache_stats`](crate::engine::GraphEngine::page_cache_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct PageCache {
+    capacity: usize,
+    clock: u64,
+    pages: HashMap<u64, (Box<[u8; PAGE_SIZE]>, u64)>,
+    /// Last-use stamp -> page id, oldest first.
+    recency: BTreeMap<u64, u64>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PageCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            pages: HashMap::new(),
+            recency: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub(crate) fn contains(&self, page_id: u64) -> bool {
+        self.pages.contains_key(&page_id)
+    }
+
+    /// Returns a copy of the cached page, counting a hit or a miss.
+    pub(crate) fn get(&mut self, page_id: u64) -> Option<[u8; PAGE_SIZE]> {
+        self.clock += 1;
+        let stamp = self.clock;
+        let Some((page, used)) = self.pages.get_mut(&page_id) else {
+            self.misses += 1;
+            return None;
+        };
+        self.recency.remove(used);
+        *used = stamp;
+        self.recency.insert(stamp, page_id);
+        self.hits += 1;
+        Some(**page)
+    }
+
+    pub(crate) fn insert(&mut self, page_id: u64, page: &[u8; PAGE_SIZE]) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(page_id);
+        while self.pages.len() >= self.capacity {
+            self.evict_oldest();
+        }
+        self.clock += 1;
+        self.recency.insert(self.clock, page_id);
+        self.pages.insert(page_id, (Box::new(*page), self.clock));
+    }
+
+    /// Refreshes a cached page after a write, without caching new ones.
+    pub(crate) fn update(&mut self, page_id: u64, page: &[u8; PAGE_SIZE]) {
+        if let Some((cached, _)) = self.pages.get_mut(&page_id) {
+            **cached = *page;
+        }
+    }
+
+    pub(crate) fn remove(&mut self, page_id: u64) {
+        if let Some((_, used)) = self.pages.remove(&page_id) {
+            self.recency.remove(&used);
+        }
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.pages.len() > capacity {
+            self.evict_oldest();
+        }
+    }
+
+    /// Cached page ids, most recently used first.
+    pub(crate) fn page_ids(&self) -> Vec<u64> {
+        self.recency.values().rev().copied().collect()
+    }
+
+    pub(crate) fn stats(&self) -> PageCacheStats {
+        PageCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            len: self.pages.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, page_id)) = self.recency.pop_first() {
+            self.pages.remove(&page_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_pages() {
+        let mut cache = PageCache::new(2);
+        cache.insert(3, &[3; PAGE_SIZE]);
+        cache.insert(4, &[4; PAGE_SIZE]);
+        assert_eq!(cache.get(3).map(|p| p[0]), Some(3));
+        cache.insert(5, &[5; PAGE_SIZE]);
+        assert!(!cache.contains(4));
+        assert_eq!(cache.page_ids(), vec![5, 3]);
+
+        cache.update(3, &[9; PAGE_SIZE]);
+        cache.update(4, &[9; PAGE_SIZE]);
+        assert_eq!(cache.get(3).map(|p| p[0]), Some(9));
+        assert!(cache.get(4).is_none());
+        assert_eq!(
+            cache.stats(),
+            PageCacheStats {
+                hits: 2,
+                misses: 1,
+                len: 2,
+                capacity: 2
+            }
+        );
+
+        cache.set_capacity(0);
+        cache.insert(6, &[6; PAGE_SIZE]);
+        assert_eq!(cache.stats().len, 0);
+    }
+}
@@ -0,0 +1,225 @@
+//! Page LSNs: which data pages were written since a given point.
+//!
+//! A pager over a local file keeps, beside it (`graph.lsn`), the sequence
+//! number of the last write to every page. The numbers only grow, so an
+//! incremental backup that recorded the highest one its base saw can tell
+//! the pages written since without reading the others; see
+//! [`crate::backup`].
+//!
+//! A page's number is written after the page, so whoever sees the number
+//! sees the page too. A handle that is not closed cleanly may lose numbers
+//! whose pages reached the disk; the next open then counts every page as
+//! written (see [`PageLsns::reset`]). The numbers belong to one data file:
+//! a new data file gets a new [`PageLsns::id`], and vacuum and restore,
+//! which replace the file, remove the old numbers.
+
+use crate::Result;
+use crate::backend::{FileBackend, StorageBackend};
+use crate::pager::PageId;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"NDBLSN01";
+
+/// Magic, id, reset, flags; the per-page numbers follow as `u64` LE.
+const HEADER_LEN: u64 = 32;
+const FLAGS_OFFSET: u64 = 24;
+
+/// Set while no handle has the numbers open, cleared by the one that does.
+const CLEAN: u64 = 1;
+
+/// The page LSN file of the data file at `ndb_path`.
+pub fn lsn_path(ndb_path: &Path) -> PathBuf {
+    ndb_path.with_extension("lsn")
+}
+
+/// Removes the page LSNs of `ndb_path`, for a data file that was replaced.
+pub(crate) fn remove(ndb_path: &Path) -> Result<()> {
+    match std::fs::remove_file(lsn_path(ndb_path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// The page LSNs of a data file as read from disk.
+#[derive(Debug, Clone)]
+pub struct PageLsns {
+    id: u64,
+    reset: u64,
+    clean: bool,
+    lsns: Vec<u64>,
+}
+
+impl PageLsns {
+    /// Reads the page LSNs kept beside `ndb_path`; `None` if there are none.
+    pub fn read(ndb_path: &Path) -> Result<Option<Self>> {
+        match std::fs::read(lsn_path(ndb_path)) {
+            Ok(bytes) => Ok(Self::decode(&bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN as usize || &bytes[..8] != MAGIC {
+            return None;
+        }
+        let word = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        Some(Self {
+            id: word(8),
+            reset: word(16),
+            clean: word(FLAGS_OFFSET as usize) & CLEAN != 0,
+            lsns: bytes[HEADER_LEN as usize..]
+                .chunks_exact(8)
+                .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        })
+    }
+
+    /// Random id of the data file these numbers belong to.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Every page counts as written at this number; set when a handle
+    /// was not closed cleanly.
+    pub fn reset(&self) -> u64 {
+        self.reset
+    }
+
+    /// The highest number so far. Pages written after reading it get
+    /// higher ones.
+    pub fn mark(&self) -> u64 {
+        self.lsns.iter().copied().fold(self.reset, u64::max)
+    }
+
+    /// Whether `page_id` was written after `mark`, an earlier [`Self::mark`]
+    /// of the same [`Self::id`].
+    pub fn written_since(&self, page_id: u64, mark: u64) -> bool {
+        self.reset > mark
+            || self
+                .lsns
+                .get(page_id as usize)
+                .is_some_and(|&lsn| lsn > mark)
+    }
+}
+
+/// The page LSNs a writable pager keeps up to date.
+#[derive(Debug)]
+pub(crate) struct PageLsnWriter {
+    file: FileBackend,
+    next: u64,
+}
+
+impl PageLsnWriter {
+    /// Opens the page LSNs of `ndb_path`. `fresh` starts new ones with a
+    /// new id, for a data file that was just created.
+    pub(crate) fn open(ndb_path: &Path, fresh: bool) -> Result<Self> {
+        let existing = if fresh {
+            None
+        } else {
+            PageLsns::read(ndb_path)?
+        };
+        let file = FileBackend::open(lsn_path(ndb_path))?;
+        let (id, reset, next) = match existing {
+            Some(lsns) if lsns.clean => (lsns.id, lsns.reset, lsns.mark()),
+            // Numbers of pages written before the crash may be missing.
+            Some(lsns) => {
+                let reset = lsns.mark() + 1;
+                (lsns.id, reset, reset)
+            }
+            None => {
+                file.set_size(0)?;
+                (rand::random(), 0, 0)
+            }
+        };
+        let mut header = [0u8; HEADER_LEN as usize];
+        header[..8].copy_from_slice(MAGIC);
+        header[8..16].copy_from_slice(&id.to_le_bytes());
+        header[16..24].copy_from_slice(&reset.to_le_bytes());
+        file.write_all_at(0, &header)?;
+        file.sync()?;
+        Ok(Self { file, next })
+    }
+
+    /// Records a write of `page_id`, after the page itself was written.
+    pub(crate) fn record(&mut self, page_id: PageId) -> Result<()> {
+        self.next += 1;
+        let offset = HEADER_LEN + page_id.as_u64() * 8;
+        self.file.write_all_at(offset, &self.next.to_le_bytes())?;
+        Ok(())
+    }
+
+    pub(crate) fn sync(&self) -> Result<()> {
+        self.file.sync()?;
+        Ok(())
+    }
+}
+
+impl Drop for PageLsnWriter {
+    fn drop(&mut self) {
+        if self.file.sync().is_ok() {
+            let _ = self
+                .file
+                .write_all_at(FLAGS_OFFSET, &CLEAN.to_le_bytes())
+                .and_then(|()| self.file.sync());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn pages_written_after_a_mark_are_written_since_it() {
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("graph.ndb");
+        let mut writer = PageLsnWriter::open(&ndb, true).unwrap();
+        writer.record(PageId::new(2)).unwrap();
+        let mark = PageLsns::read(&ndb).unwrap().unwrap().mark();
+        writer.record(PageId::new(3)).unwrap();
+        drop(writer);
+
+        let lsns = PageLsns::read(&ndb).unwrap().unwrap();
+        assert!(!lsns.written_since(2, mark));
+        assert!(lsns.written_since(3, mark));
+        assert!(!lsns.written_since(4, mark));
+
+        // Reopened cleanly: same id, numbers carry on.
+        let mut writer = PageLsnWriter::open(&ndb, false).unwrap();
+        writer.record(PageId::new(4)).unwrap();
+        let reopened = PageLsns::read(&ndb).unwrap().unwrap();
+        assert_eq!(reopened.id(), lsns.id());
+        assert!(!reopened.written_since(2, mark));
+        assert!(reopened.written_since(4, lsns.mark()));
+    }
+
+    #[test]
+    fn reopening_after_a_crash_counts_every_page_as_written() {
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("graph.ndb");
+        let mut writer = PageLsnWriter::open(&ndb, true).unwrap();
+        writer.record(PageId::new(2)).unwrap();
+        let mark = PageLsns::read(&ndb).unwrap().unwrap().mark();
+        // No clean close.
+        std::mem::forget(writer);
+
+        drop(PageLsnWriter::open(&ndb, false).unwrap());
+        let lsns = PageLsns::read(&ndb).unwrap().unwrap();
+        assert!(lsns.written_since(2, mark));
+        assert!(lsns.written_since(7, mark));
+        assert!(!lsns.written_since(7, lsns.mark()));
+    }
+
+    #[test]
+    fn a_fresh_data_file_gets_a_new_id() {
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("graph.ndb");
+        drop(PageLsnWriter::open(&ndb, true).unwrap());
+        let first = PageLsns::read(&ndb).unwrap().unwrap().id();
+        drop(PageLsnWriter::open(&ndb, true).unwrap());
+        assert_ne!(PageLsns::read(&ndb).unwrap().unwrap().id(), first);
+    }
+}
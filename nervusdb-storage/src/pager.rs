@@ -1,15 +1,13 @@
+use crate::backend::{FileBackend, StorageBackend};
+use crate::operation::Tracker;
+use crate::page_cache::{DEFAULT_PAGE_CACHE_PAGES, PageCache, PageCacheStats};
+use crate::page_lsn::PageLsnWriter;
 use crate::{
     Error, FILE_MAGIC, PAGE_SIZE, Result, STORAGE_FORMAT_EPOCH, VERSION_MAJOR, VERSION_MINOR,
 };
 use std::collections::BTreeSet;
-use std::fs::{File, OpenOptions};
-use std::io;
 use std::path::{Path, PathBuf};
-
-#[cfg(unix)]
-use std::os::unix::fs::FileExt as _;
-#[cfg(windows)]
-use std::os::windows::fs::FileExt as _;
+use std::sync::{Mutex, MutexGuard};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PageId(u64);
@@ -180,9 +178,12 @@ impl Bitmap {
 #[derive(Debug)]
 pub struct Pager {
     path: PathBuf,
-    file: File,
+    backend: Box<dyn StorageBackend>,
     meta: Meta,
     bitmap: Bitmap,
+    cache: Mutex<PageCache>,
+    /// Kept for local files only; see [`crate::page_lsn`].
+    lsns: Option<PageLsnWriter>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -196,56 +197,73 @@ pub(crate) struct VacuumCopyStats {
 
 impl Pager {
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let backend = FileBackend::open(path.as_ref())?;
+        Self::open_with_backend(path, Box::new(backend))
+    }
+
+    /// Opens the pages kept in `backend`, initialising an empty one.
+    ///
+    /// `path` only names the database; no file is opened there.
+    pub fn open_with_backend(
+        path: impl AsRef<Path>,
+        backend: Box<dyn StorageBackend>,
+    ) -> Result<Self> {
         let path = path.as_ref().to_path_buf();
-        let existed = path.exists();
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(false)
-            .open(&path)?;
-
-        if !existed || file.metadata()?.len() == 0 {
+        let size = backend.size()?;
+        let lsns = match backend.local_file() {
+            Some(file) => Some(PageLsnWriter::open(file, size == 0)?),
+            None => None,
+        };
+
+        if size == 0 {
             let meta = Meta::new();
             let bitmap = Bitmap::new();
-            file.set_len((PAGE_SIZE * 2) as u64)?;
+            backend.set_size((PAGE_SIZE * 2) as u64)?;
 
             let mut pager = Self {
                 path,
-                file,
+                backend,
                 meta,
                 bitmap,
+                cache: Mutex::new(PageCache::new(DEFAULT_PAGE_CACHE_PAGES)),
+                lsns,
             };
             pager.flush_meta_and_bitmap()?;
             return Ok(pager);
         }
 
-        if file.metadata()?.len() < (PAGE_SIZE * 2) as u64 {
+        if size < (PAGE_SIZE * 2) as u64 {
             return Err(Error::WalProtocol("ndb file too small"));
         }
 
         let mut meta_page = [0u8; PAGE_SIZE];
-        read_page_raw(&file, META_PAGE_ID, &mut meta_page)?;
+        read_page_raw(backend.as_ref(), META_PAGE_ID, &mut meta_page)?;
         let meta = Meta::decode_page(&meta_page)?;
 
         let mut bitmap_page = [0u8; PAGE_SIZE];
-        read_page_raw(&file, BITMAP_PAGE_ID, &mut bitmap_page)?;
+        read_page_raw(backend.as_ref(), BITMAP_PAGE_ID, &mut bitmap_page)?;
         let bitmap = Bitmap { data: bitmap_page };
 
         Ok(Self {
             path,
-            file,
+            backend,
             meta,
             bitmap,
+            cache: Mutex::new(PageCache::new(DEFAULT_PAGE_CACHE_PAGES)),
+            lsns,
         })
     }
 
+    /// Writes the pages in `reachable` into the empty `out`, keeping their
+    /// ids, with a meta and bitmap that allocate only those pages. Progress
+    /// is counted in data pages copied, with a cancellation check per page.
     pub(crate) fn write_vacuum_copy(
         &self,
-        target_path: &Path,
+        out: &dyn StorageBackend,
         reachable: &BTreeSet<PageId>,
+        tracker: &mut Tracker<'_>,
     ) -> Result<VacuumCopyStats> {
-        let old_file_pages = self.file.metadata()?.len() / PAGE_SIZE as u64;
+        let old_file_pages = self.backend.size()? / PAGE_SIZE as u64;
         let old_next_page_id = self.meta.next_page_id;
 
         let mut max_page_id = BITMAP_PAGE_ID.as_u64();
@@ -264,6 +282,7 @@ impl Pager {
         let new_next_page_id = max_page_id
             .saturating_add(1)
             .max(FIRST_DATA_PAGE_ID.as_u64());
+        tracker.start(copied_data_pages);
 
         let mut meta = self.meta;
         meta.next_page_id = new_next_page_id;
@@ -275,27 +294,23 @@ impl Pager {
             }
         }
 
-        let out = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .truncate(false)
-            .open(target_path)?;
-
-        out.set_len(new_next_page_id.saturating_mul(PAGE_SIZE as u64))?;
+        out.set_size(new_next_page_id.saturating_mul(PAGE_SIZE as u64))?;
 
         let meta_page = meta.encode_page();
-        write_page_raw(&out, META_PAGE_ID, &meta_page)?;
-        write_page_raw(&out, BITMAP_PAGE_ID, &bitmap.data)?;
+        write_page_raw(out, META_PAGE_ID, &meta_page)?;
+        write_page_raw(out, BITMAP_PAGE_ID, &bitmap.data)?;
 
         for p in reachable {
             if p.as_u64() < FIRST_DATA_PAGE_ID.as_u64() {
                 continue;
             }
+            tracker.check()?;
             let page = self.read_page(*p)?;
-            write_page_raw(&out, *p, &page)?;
+            write_page_raw(out, *p, &page)?;
+            tracker.advance(1);
         }
 
-        out.sync_data()?;
+        out.sync()?;
 
         Ok(VacuumCopyStats {
             old_next_page_id,
@@ -394,6 +409,23 @@ impl Pager {
         Ok(PageId::new(candidate))
     }
 
+    /// Allocates `count` contiguous pages past the current end of the file.
+    pub(crate) fn allocate_run(&mut self, count: u64) -> Result<PageId> {
+        let start = self.meta.next_page_id;
+        let end = start.saturating_add(count);
+        if end > BITMAP_BITS {
+            return Err(Error::PageIdOutOfRange(end - 1));
+        }
+        for page_id in start..end {
+            self.ensure_allocated(PageId::new(page_id))?;
+        }
+        Ok(PageId::new(start))
+    }
+
+    pub(crate) fn is_page_allocated(&self, page_id: PageId) -> bool {
+        page_id.as_u64() < BITMAP_BITS && self.bitmap.is_allocated(page_id)
+    }
+
     pub fn free_page(&mut self, page_id: PageId) -> Result<()> {
         self.validate_data_page_id(page_id)?;
         if !self.bitmap.is_allocated(page_id) {
@@ -401,6 +433,7 @@ impl Pager {
         }
 
         self.bitmap.set_allocated(page_id, false);
+        self.cache().remove(page_id.as_u64());
         self.flush_meta_and_bitmap()?;
         Ok(())
     }
@@ -410,9 +443,13 @@ impl Pager {
         if !self.bitmap.is_allocated(page_id) {
             return Err(Error::PageNotAllocated(page_id.as_u64()));
         }
+        if let Some(page) = self.cache().get(page_id.as_u64()) {
+            return Ok(page);
+        }
 
         let mut page = [0u8; PAGE_SIZE];
-        read_page_raw(&self.file, page_id, &mut page)?;
+        read_page_raw(self.backend.as_ref(), page_id, &mut page)?;
+        self.cache().insert(page_id.as_u64(), &page);
         Ok(page)
     }
 
@@ -422,15 +459,64 @@ impl Pager {
             return Err(Error::PageNotAllocated(page_id.as_u64()));
         }
 
-        write_page_raw(&self.file, page_id, page)?;
+        write_page_raw(self.backend.as_ref(), page_id, page)?;
+        self.cache().update(page_id.as_u64(), page);
+        if let Some(lsns) = &mut self.lsns {
+            lsns.record(page_id)?;
+        }
         Ok(())
     }
 
+    /// Reads `page_id` into the page cache unless it is cached already or
+    /// not an allocated data page. Returns whether it was loaded.
+    pub(crate) fn preload_page(&self, page_id: PageId) -> Result<bool> {
+        if page_id < FIRST_DATA_PAGE_ID
+            || !self.is_page_allocated(page_id)
+            || self.cache().contains(page_id.as_u64())
+        {
+            return Ok(false);
+        }
+        let mut page = [0u8; PAGE_SIZE];
+        read_page_raw(self.backend.as_ref(), page_id, &mut page)?;
+        self.cache().insert(page_id.as_u64(), &page);
+        Ok(true)
+    }
+
+    /// Ids of the cached pages, most recently used first.
+    pub(crate) fn cached_page_ids(&self) -> Vec<PageId> {
+        self.cache()
+            .page_ids()
+            .into_iter()
+            .map(PageId::new)
+            .collect()
+    }
+
+    pub(crate) fn set_page_cache_capacity(&self, pages: usize) {
+        self.cache().set_capacity(pages);
+    }
+
+    pub(crate) fn page_cache_stats(&self) -> PageCacheStats {
+        self.cache().stats()
+    }
+
+    fn cache(&self) -> MutexGuard<'_, PageCache> {
+        // The cache only holds copies of file pages; a poisoned one is still usable.
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     pub fn sync(&mut self) -> Result<()> {
-        self.file.sync_data()?;
+        self.backend.sync()?;
+        if let Some(lsns) = &self.lsns {
+            lsns.sync()?;
+        }
         Ok(())
     }
 
+    /// Size of the backing store in bytes.
+    pub fn file_bytes(&self) -> Result<u64> {
+        Ok(self.backend.size()?)
+    }
+
     pub(crate) fn ensure_allocated(&mut self, page_id: PageId) -> Result<()> {
         self.validate_data_page_id(page_id)?;
 
@@ -443,9 +529,9 @@ impl Pager {
         }
 
         let required_bytes = (page_id.as_u64() + 1) * PAGE_SIZE as u64;
-        let current_len = self.file.metadata()?.len();
+        let current_len = self.backend.size()?;
         if current_len < required_bytes {
-            self.file.set_len(required_bytes)?;
+            self.backend.set_size(required_bytes)?;
         }
 
         self.flush_meta_and_bitmap()
@@ -460,77 +546,35 @@ impl Pager {
 
     fn flush_meta_and_bitmap(&mut self) -> Result<()> {
         let meta_page = self.meta.encode_page();
-        write_page_raw(&self.file, META_PAGE_ID, &meta_page)?;
-        write_page_raw(&self.file, BITMAP_PAGE_ID, &self.bitmap.data)?;
+        write_page_raw(self.backend.as_ref(), META_PAGE_ID, &meta_page)?;
+        write_page_raw(self.backend.as_ref(), BITMAP_PAGE_ID, &self.bitmap.data)?;
         // Ensure meta + bitmap durability. WAL replay can recover data pages, but
         // durable metadata reduces recovery work and avoids pathological re-scan.
-        self.file.sync_data()?;
+        self.backend.sync()?;
         Ok(())
     }
 }
 
-fn read_page_raw(file: &File, page_id: PageId, buf: &mut [u8; PAGE_SIZE]) -> Result<()> {
+fn read_page_raw(
+    backend: &dyn StorageBackend,
+    page_id: PageId,
+    buf: &mut [u8; PAGE_SIZE],
+) -> Result<()> {
     let offset = page_id.as_u64() * PAGE_SIZE as u64;
-    read_exact_at(file, offset, buf).map_err(Error::Io)?;
+    backend.read_exact_at(offset, buf).map_err(Error::Io)?;
     Ok(())
 }
 
-fn write_page_raw(file: &File, page_id: PageId, buf: &[u8; PAGE_SIZE]) -> Result<()> {
+fn write_page_raw(
+    backend: &dyn StorageBackend,
+    page_id: PageId,
+    buf: &[u8; PAGE_SIZE],
+) -> Result<()> {
     let offset = page_id.as_u64() * PAGE_SIZE as u64;
-    write_all_at(file, offset, buf).map_err(Error::Io)?;
-    Ok(())
-}
-
-fn read_exact_at(file: &File, mut offset: u64, mut buf: &mut [u8]) -> io::Result<()> {
-    while !buf.is_empty() {
-        let n = read_at(file, offset, buf)?;
-        if n == 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "read_at returned 0 bytes",
-            ));
-        }
-        offset = offset.saturating_add(n as u64);
-        buf = &mut buf[n..];
-    }
-    Ok(())
-}
-
-fn write_all_at(file: &File, mut offset: u64, mut buf: &[u8]) -> io::Result<()> {
-    while !buf.is_empty() {
-        let n = write_at(file, offset, buf)?;
-        if n == 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::WriteZero,
-                "write_at returned 0 bytes",
-            ));
-        }
-        offset = offset.saturating_add(n as u64);
-        buf = &buf[n..];
-    }
+    backend.write_all_at(offset, buf).map_err(Error::Io)?;
     Ok(())
 }
 
-#[cfg(unix)]
-fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
-    file.read_at(buf, offset)
-}
-
-#[cfg(windows)]
-fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
-    file.seek_read(buf, offset)
-}
-
-#[cfg(unix)]
-fn write_at(file: &File, offset: u64, buf: &[u8]) -> io::Result<usize> {
-    file.write_at(buf, offset)
-}
-
-#[cfg(windows)]
-fn write_at(file: &File, offset: u64, buf: &[u8]) -> io::Result<usize> {
-    file.seek_write(buf, offset)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,8 +1,73 @@
 pub use nervusdb_api::{DecodeError, PropertyValue};
 
+/// Largest encoded size of a single property value (16 MiB).
+///
+/// Values above ~1 MiB are split across several WAL records and, once checkpointed,
+/// across a chain of pages, so anything up to this limit round-trips.
+pub const MAX_PROPERTY_VALUE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Rejects values whose encoding exceeds [`MAX_PROPERTY_VALUE_BYTES`].
+pub fn check_property_value_size(value: &PropertyValue) -> crate::Result<()> {
+    let len = value.encoded_len();
+    if len > MAX_PROPERTY_VALUE_BYTES {
+        return Err(crate::Error::PropertyValueTooLarge {
+            len,
+            max: MAX_PROPERTY_VALUE_BYTES,
+        });
+    }
+    Ok(())
+}
+
+/// Strings at least this long are LZ4-compressed when checkpointed into the page store.
+pub const COMPRESS_TEXT_MIN_BYTES: usize = 4 * 1024;
+
+/// Page-store tag for a compressed string; above every `PropertyValue` encode tag.
+const COMPRESSED_STRING_TAG: u8 = 0x80;
+
+/// Encodes a value for the page store, compressing long strings when that saves space.
+///
+/// WAL records keep the plain encoding; only checkpointed blobs are compressed.
+pub(crate) fn encode_stored_value(value: &PropertyValue) -> Vec<u8> {
+    if let PropertyValue::String(text) = value
+        && text.len() >= COMPRESS_TEXT_MIN_BYTES
+    {
+        let compressed = lz4_flex::compress_prepend_size(text.as_bytes());
+        if compressed.len() + 1 < value.encoded_len() {
+            let mut out = Vec::with_capacity(1 + compressed.len());
+            out.push(COMPRESSED_STRING_TAG);
+            out.extend_from_slice(&compressed);
+            return out;
+        }
+    }
+    value.encode()
+}
+
+/// Bytes `value` occupies as a checkpointed blob, before page rounding.
+pub fn stored_len(value: &PropertyValue) -> usize {
+    encode_stored_value(value).len()
+}
+
+/// Decodes a page-store blob written by [`encode_stored_value`].
+pub(crate) fn decode_stored_value(bytes: &[u8]) -> crate::Result<PropertyValue> {
+    match bytes.split_first() {
+        Some((&COMPRESSED_STRING_TAG, compressed)) => {
+            let raw = lz4_flex::decompress_size_prepended(compressed)
+                .map_err(|_| crate::Error::StorageCorrupted("invalid compressed property"))?;
+            String::from_utf8(raw)
+                .map(PropertyValue::String)
+                .map_err(|_| crate::Error::StorageCorrupted("compressed property is not utf-8"))
+        }
+        _ => PropertyValue::decode(bytes)
+            .map_err(|_| crate::Error::StorageCorrupted("invalid property encoding")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PropertyValue;
+    use super::{
+        COMPRESS_TEXT_MIN_BYTES, MAX_PROPERTY_VALUE_BYTES, PropertyValue,
+        check_property_value_size, decode_stored_value, encode_stored_value,
+    };
     use std::collections::BTreeMap;
 
     #[test]
@@ -17,6 +82,34 @@ mod tests {
         assert_eq!(decoded, value);
     }
 
+    #[test]
+    fn property_value_size_limit_counts_encoded_bytes() {
+        let at_limit = PropertyValue::Blob(vec![0; MAX_PROPERTY_VALUE_BYTES - 5]);
+        assert!(check_property_value_size(&at_limit).is_ok());
+
+        let over = PropertyValue::String("é".repeat(MAX_PROPERTY_VALUE_BYTES / 2));
+        let err = check_property_value_size(&over).expect_err("over the limit");
+        assert!(
+            err.to_string().contains("property value too large"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn long_strings_are_compressed_in_the_page_store() {
+        let text = PropertyValue::String("lorem ipsum dolor ".repeat(1_000));
+        let stored = encode_stored_value(&text);
+        assert!(stored.len() < text.encoded_len() / 4);
+        assert_eq!(decode_stored_value(&stored).unwrap(), text);
+
+        let short = PropertyValue::String("x".repeat(COMPRESS_TEXT_MIN_BYTES - 1));
+        assert_eq!(encode_stored_value(&short), short.encode());
+        assert_eq!(
+            decode_stored_value(&encode_stored_value(&PropertyValue::Int(7))).unwrap(),
+            PropertyValue::Int(7)
+        );
+    }
+
     #[test]
     fn storage_property_as_float_is_preserved() {
         assert_eq!(PropertyValue::Float(2.5).as_float(), Some(2.5));
@@ -180,9 +180,43 @@ pub(crate) fn extend_edge_properties_from_store(
     Some(())
 }
 
+/// Every property of `node` in the store, as `(B-Tree key, property key,
+/// blob id)`, for moving them elsewhere.
+pub(crate) fn node_property_blobs(
+    pager: &Pager,
+    properties_root: u64,
+    node: InternalNodeId,
+) -> crate::Result<Vec<(Vec<u8>, String, u64)>> {
+    let mut out = Vec::new();
+    if properties_root == 0 {
+        return Ok(out);
+    }
+
+    let tree = BTree::load(PageId::new(properties_root));
+    let mut prefix = Vec::with_capacity(5);
+    prefix.push(0u8);
+    prefix.extend_from_slice(&node.to_be_bytes());
+
+    let mut cursor = tree.cursor_lower_bound(pager, &prefix)?;
+    while cursor.is_valid()? {
+        let key = cursor.key()?;
+        if !key.starts_with(&prefix) || key.len() < 9 {
+            break;
+        }
+        let key_len = u32::from_be_bytes(key[5..9].try_into().unwrap()) as usize;
+        let key_name = String::from_utf8(key[9..9 + key_len].to_vec())
+            .map_err(|_| crate::Error::StorageCorrupted("property key is not utf-8"))?;
+        out.push((key, key_name, cursor.payload()?));
+        if !cursor.advance()? {
+            break;
+        }
+    }
+    Ok(out)
+}
+
 fn decode_property_blob(pager: &Pager, blob_id: u64) -> Option<PropertyValue> {
     let bytes = crate::blob_store::BlobStore::read(pager, blob_id).ok()?;
-    crate::property::PropertyValue::decode(&bytes).ok()
+    crate::property::decode_stored_value(&bytes).ok()
 }
 
 #[cfg(test)]
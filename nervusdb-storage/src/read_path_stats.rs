@@ -17,7 +17,9 @@ mod tests {
     use super::read_statistics;
     use crate::blob_store::BlobStore;
     use crate::pager::Pager;
-    use crate::stats::GraphStatistics;
+    use crate::schema::SchemaCatalog;
+    use crate::stats::{DegreeStats, GraphStatistics, StatsSample};
+    use nervusdb_api::DegreeHistogram;
     use std::collections::BTreeMap;
     use tempfile::tempdir;
 
@@ -45,6 +47,31 @@ mod tests {
             edge_counts_by_type: BTreeMap::from([(7, 3)]),
             total_nodes: 11,
             total_edges: 13,
+            history: vec![StatsSample {
+                unix_millis: 1_700_000_000_000,
+                total_nodes: 5,
+                total_edges: 8,
+                file_bytes: 4096,
+            }],
+            schema: Some(SchemaCatalog {
+                labels: [1].into(),
+                ..Default::default()
+            }),
+            degrees: BTreeMap::from([(
+                7,
+                DegreeStats {
+                    outgoing: DegreeHistogram {
+                        buckets: vec![1, 1],
+                        max_degree: 2,
+                        max_node: 4,
+                    },
+                    incoming: DegreeHistogram {
+                        buckets: vec![3],
+                        max_degree: 1,
+                        max_node: 0,
+                    },
+                },
+            )]),
         };
 
         let root = BlobStore::write(&mut pager, &expected.encode()).expect("write blob");
@@ -54,6 +81,43 @@ mod tests {
         assert_eq!(actual.total_edges, expected.total_edges);
         assert_eq!(actual.node_counts_by_label.get(&1), Some(&2));
         assert_eq!(actual.edge_counts_by_type.get(&7), Some(&3));
+        assert_eq!(actual.history, expected.history);
+        assert_eq!(actual.schema, expected.schema);
+        assert_eq!(actual.degrees, expected.degrees);
+    }
+
+    #[test]
+    fn read_statistics_decodes_degrees_without_schema() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("stats_degrees.ndb");
+        let mut pager = Pager::open(&path).expect("open pager");
+
+        let mut expected = GraphStatistics::default();
+        expected.degrees.insert(2, DegreeStats::default());
+        let root = BlobStore::write(&mut pager, &expected.encode()).expect("write blob");
+        let actual = read_statistics(&pager, root).expect("read statistics");
+
+        assert_eq!(actual.schema, None);
+        assert_eq!(actual.degrees, expected.degrees);
+    }
+
+    #[test]
+    fn read_statistics_accepts_payload_without_history() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("stats_legacy.ndb");
+        let mut pager = Pager::open(&path).expect("open pager");
+
+        let mut legacy = GraphStatistics {
+            total_nodes: 3,
+            ..Default::default()
+        }
+        .encode();
+        legacy.truncate(legacy.len() - 4);
+        let root = BlobStore::write(&mut pager, &legacy).expect("write blob");
+        let actual = read_statistics(&pager, root).expect("read statistics");
+
+        assert_eq!(actual.total_nodes, 3);
+        assert!(actual.history.is_empty());
     }
 
     #[test]
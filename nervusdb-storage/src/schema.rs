@@ -0,0 +1,136 @@
+//! Every label, relationship type and property key a database has used.
+//!
+//! Names are only ever added: like tokens in other graph databases they stay
+//! listed after the last node or relationship using them is gone. The
+//! catalog is checkpointed with the statistics, and commits replayed from
+//! the WAL are folded in on open.
+
+use crate::api::StorageSnapshot;
+use crate::idmap::LabelId;
+use crate::names::is_system_property;
+use crate::snapshot::{L0Run, RelTypeId};
+use nervusdb_api::GraphSnapshot;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaCatalog {
+    pub labels: BTreeSet<LabelId>,
+    pub rel_types: BTreeSet<RelTypeId>,
+    pub property_keys: BTreeSet<String>,
+}
+
+impl SchemaCatalog {
+    /// Adds the relationship types and property keys `run` writes.
+    pub(crate) fn record_run(&mut self, run: &L0Run) {
+        self.rel_types.extend(run.iter_edges().map(|edge| edge.rel));
+        for properties in run.node_properties.values() {
+            self.add_keys(properties.keys());
+        }
+        for properties in run.edge_properties.values() {
+            self.add_keys(properties.keys());
+        }
+    }
+
+    /// Adds node labels, skipping the "no label" marker.
+    pub(crate) fn record_labels(&mut self, labels: impl IntoIterator<Item = LabelId>) {
+        self.labels
+            .extend(labels.into_iter().filter(|&label| label != LabelId::MAX));
+    }
+
+    /// Adds property keys, skipping system properties.
+    pub(crate) fn add_keys<'a>(&mut self, keys: impl IntoIterator<Item = &'a String>) {
+        for key in keys {
+            if !is_system_property(key) && !self.property_keys.contains(key) {
+                self.property_keys.insert(key.clone());
+            }
+        }
+    }
+
+    /// Whether every name in `other` is already listed.
+    pub(crate) fn contains(&self, other: &SchemaCatalog) -> bool {
+        other.labels.is_subset(&self.labels)
+            && other.rel_types.is_subset(&self.rel_types)
+            && other.property_keys.is_subset(&self.property_keys)
+    }
+
+    pub(crate) fn merge(&mut self, other: SchemaCatalog) {
+        self.labels.extend(other.labels);
+        self.rel_types.extend(other.rel_types);
+        self.property_keys.extend(other.property_keys);
+    }
+
+    pub(crate) fn encode_into(&self, bytes: &mut Vec<u8>) {
+        for ids in [&self.labels, &self.rel_types] {
+            bytes.extend_from_slice(&(ids.len() as u32).to_le_bytes());
+            for id in ids {
+                bytes.extend_from_slice(&id.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&(self.property_keys.len() as u32).to_le_bytes());
+        for key in &self.property_keys {
+            bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(key.as_bytes());
+        }
+    }
+
+    /// Decodes a catalog written by [`Self::encode_into`] at `*pos`,
+    /// advancing `pos` past it.
+    pub(crate) fn decode_from(bytes: &[u8], pos: &mut usize) -> Option<Self> {
+        let u32_at = |pos: &mut usize| -> Option<u32> {
+            let value = u32::from_le_bytes(bytes.get(*pos..*pos + 4)?.try_into().ok()?);
+            *pos += 4;
+            Some(value)
+        };
+        let mut catalog = SchemaCatalog::default();
+        for _ in 0..u32_at(pos)? {
+            catalog.labels.insert(u32_at(pos)?);
+        }
+        for _ in 0..u32_at(pos)? {
+            catalog.rel_types.insert(u32_at(pos)?);
+        }
+        for _ in 0..u32_at(pos)? {
+            let len = u32_at(pos)? as usize;
+            let key = std::str::from_utf8(bytes.get(*pos..*pos + len)?).ok()?;
+            *pos += len;
+            catalog.property_keys.insert(key.to_string());
+        }
+        Some(catalog)
+    }
+}
+
+/// Builds the catalog from every live node and relationship in `snapshot`.
+pub(crate) fn scan(snapshot: &StorageSnapshot) -> SchemaCatalog {
+    let mut catalog = SchemaCatalog::default();
+    for node in snapshot.nodes() {
+        catalog.record_labels(snapshot.resolve_node_labels(node).unwrap_or_default());
+        catalog.add_keys(snapshot.node_properties(node).unwrap_or_default().keys());
+        for edge in snapshot.neighbors(node, None) {
+            catalog.rel_types.insert(edge.rel);
+            catalog.add_keys(snapshot.edge_properties(edge).unwrap_or_default().keys());
+        }
+    }
+    catalog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SchemaCatalog;
+
+    #[test]
+    fn encoding_roundtrips() {
+        let catalog = SchemaCatalog {
+            labels: [1, 4].into(),
+            rel_types: [2].into(),
+            property_keys: ["name".to_string(), "since".to_string()].into(),
+        };
+        let mut bytes = vec![0xAA];
+        catalog.encode_into(&mut bytes);
+        let mut pos = 1;
+        assert_eq!(SchemaCatalog::decode_from(&bytes, &mut pos), Some(catalog));
+        assert_eq!(pos, bytes.len());
+        assert_eq!(
+            SchemaCatalog::decode_from(&bytes[..bytes.len() - 1], &mut 1),
+            None
+        );
+    }
+}
@@ -1,6 +1,11 @@
 use crate::idmap::LabelId;
-use crate::snapshot::RelTypeId;
-use std::collections::BTreeMap;
+use crate::schema::SchemaCatalog;
+use crate::snapshot::{EdgeKey, RelTypeId};
+use nervusdb_api::{DegreeHistogram, InternalNodeId};
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of [`StatsSample`]s kept in [`GraphStatistics::history`].
+pub const MAX_STATS_HISTORY: usize = 64;
 
 #[derive(Debug, Clone, Default)]
 pub struct GraphStatistics {
@@ -8,8 +13,57 @@ pub struct GraphStatistics {
     pub edge_counts_by_type: BTreeMap<RelTypeId, u64>,
     pub total_nodes: u64,
     pub total_edges: u64,
+    /// Totals recorded at past compactions, oldest first.
+    pub history: Vec<StatsSample>,
+    /// Names in use at the compaction; `None` in statistics written before
+    /// the catalog existed or by the bulk loader.
+    pub schema: Option<SchemaCatalog>,
+    /// Degree histograms per relationship type at the compaction; empty in
+    /// statistics written before they were collected.
+    pub degrees: BTreeMap<RelTypeId, DegreeStats>,
+}
+
+/// Out- and in-degree histograms of one relationship type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DegreeStats {
+    pub outgoing: DegreeHistogram,
+    pub incoming: DegreeHistogram,
+}
+
+/// Builds the degree histograms of every relationship type in `edges`.
+pub fn degree_stats(edges: impl IntoIterator<Item = EdgeKey>) -> BTreeMap<RelTypeId, DegreeStats> {
+    let mut outgoing: HashMap<(RelTypeId, InternalNodeId), u64> = HashMap::new();
+    let mut incoming: HashMap<(RelTypeId, InternalNodeId), u64> = HashMap::new();
+    for edge in edges {
+        *outgoing.entry((edge.rel, edge.src)).or_default() += 1;
+        *incoming.entry((edge.rel, edge.dst)).or_default() += 1;
+    }
+    let mut stats: BTreeMap<RelTypeId, DegreeStats> = BTreeMap::new();
+    for ((rel, node), degree) in outgoing {
+        stats.entry(rel).or_default().outgoing.record(node, degree);
+    }
+    for ((rel, node), degree) in incoming {
+        stats.entry(rel).or_default().incoming.record(node, degree);
+    }
+    stats
 }
 
+/// Graph size at one compaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsSample {
+    pub unix_millis: u64,
+    pub total_nodes: u64,
+    pub total_edges: u64,
+    pub file_bytes: u64,
+}
+
+const SAMPLE_SIZE: usize = 32;
+
+/// Starts the degree section. It cannot be mistaken for the label count
+/// that starts a schema, so the section can follow the schema or stand in
+/// its place.
+const DEGREES_TAG: u32 = u32::MAX;
+
 impl GraphStatistics {
     pub fn encode(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
@@ -32,9 +86,48 @@ impl GraphStatistics {
             bytes.extend_from_slice(&count.to_le_bytes());
         }
 
+        // History; older readers stop before this section.
+        bytes.extend_from_slice(&(self.history.len() as u32).to_le_bytes());
+        for sample in &self.history {
+            bytes.extend_from_slice(&sample.unix_millis.to_le_bytes());
+            bytes.extend_from_slice(&sample.total_nodes.to_le_bytes());
+            bytes.extend_from_slice(&sample.total_edges.to_le_bytes());
+            bytes.extend_from_slice(&sample.file_bytes.to_le_bytes());
+        }
+
+        if let Some(schema) = &self.schema {
+            schema.encode_into(&mut bytes);
+        }
+
+        // Degrees; older readers stop before this section when a schema
+        // precedes it.
+        if !self.degrees.is_empty() {
+            bytes.extend_from_slice(&DEGREES_TAG.to_le_bytes());
+            bytes.extend_from_slice(&(self.degrees.len() as u32).to_le_bytes());
+            for (rel, stats) in &self.degrees {
+                bytes.extend_from_slice(&rel.to_le_bytes());
+                for histogram in [&stats.outgoing, &stats.incoming] {
+                    bytes.extend_from_slice(&histogram.max_degree.to_le_bytes());
+                    bytes.extend_from_slice(&histogram.max_node.to_le_bytes());
+                    bytes.extend_from_slice(&(histogram.buckets.len() as u32).to_le_bytes());
+                    for count in &histogram.buckets {
+                        bytes.extend_from_slice(&count.to_le_bytes());
+                    }
+                }
+            }
+        }
+
         bytes
     }
 
+    /// Appends `sample`, dropping the oldest samples beyond
+    /// [`MAX_STATS_HISTORY`].
+    pub fn record_sample(&mut self, sample: StatsSample) {
+        self.history.push(sample);
+        let excess = self.history.len().saturating_sub(MAX_STATS_HISTORY);
+        self.history.drain(..excess);
+    }
+
     pub fn decode(bytes: &[u8]) -> Option<Self> {
         if bytes.len() < 16 {
             return None;
@@ -68,11 +161,70 @@ impl GraphStatistics {
             edge_counts_by_type.insert(rel, count);
         }
 
+        // Statistics written before the history section have no samples.
+        let mut history = Vec::new();
+        if let Some(len) = bytes.get(pos..pos + 4) {
+            let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+            pos += 4;
+            for _ in 0..len {
+                let field = |i: usize| -> Option<u64> {
+                    let at = pos + i * 8;
+                    Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+                };
+                history.push(StatsSample {
+                    unix_millis: field(0)?,
+                    total_nodes: field(1)?,
+                    total_edges: field(2)?,
+                    file_bytes: field(3)?,
+                });
+                pos += SAMPLE_SIZE;
+            }
+        }
+
+        let u32_at = |pos: &mut usize| -> Option<u32> {
+            let value = u32::from_le_bytes(bytes.get(*pos..*pos + 4)?.try_into().ok()?);
+            *pos += 4;
+            Some(value)
+        };
+        let u64_at = |pos: &mut usize| -> Option<u64> {
+            let value = u64::from_le_bytes(bytes.get(*pos..*pos + 8)?.try_into().ok()?);
+            *pos += 8;
+            Some(value)
+        };
+        let at_degrees = |pos: usize| bytes.get(pos..pos + 4) == Some(&DEGREES_TAG.to_le_bytes());
+
+        let schema = if pos < bytes.len() && !at_degrees(pos) {
+            Some(SchemaCatalog::decode_from(bytes, &mut pos)?)
+        } else {
+            None
+        };
+
+        let mut degrees = BTreeMap::new();
+        if at_degrees(pos) {
+            pos += 4;
+            for _ in 0..u32_at(&mut pos)? {
+                let rel = u32_at(&mut pos)?;
+                let mut histograms = [DegreeHistogram::default(), DegreeHistogram::default()];
+                for histogram in &mut histograms {
+                    histogram.max_degree = u64_at(&mut pos)?;
+                    histogram.max_node = u32_at(&mut pos)?;
+                    for _ in 0..u32_at(&mut pos)? {
+                        histogram.buckets.push(u64_at(&mut pos)?);
+                    }
+                }
+                let [outgoing, incoming] = histograms;
+                degrees.insert(rel, DegreeStats { outgoing, incoming });
+            }
+        }
+
         Some(Self {
             node_counts_by_label,
             edge_counts_by_type,
             total_nodes,
             total_edges,
+            history,
+            schema,
+            degrees,
         })
     }
 }
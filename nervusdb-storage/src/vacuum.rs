@@ -1,9 +1,12 @@
+use crate::backend::FileBackend;
 use crate::index::btree::BTree;
 use crate::index::catalog::IndexCatalog;
+use crate::operation::{OperationHandle, Tracker};
 use crate::pager::{PageId, Pager};
 use crate::wal::{CommittedTx, SegmentPointer, WalRecord};
 use crate::{Error, PAGE_SIZE, Result};
 use std::collections::BTreeSet;
+use std::fs::OpenOptions;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
@@ -21,6 +24,23 @@ pub fn vacuum_in_place(
     ndb_path: impl AsRef<Path>,
     wal_path: impl AsRef<Path>,
 ) -> Result<VacuumReport> {
+    vacuum_in_place_with_progress(ndb_path, wal_path, &OperationHandle::new(), &mut |_, _| {})
+}
+
+/// Like [`vacuum_in_place`], publishing progress in data pages copied to
+/// `handle` and calling `progress` with `(done, total)`.
+///
+/// The copy goes to a temporary file and only replaces the database at the
+/// end, so cancelling at any point before that removes the temporary file and
+/// leaves the database untouched.
+pub fn vacuum_in_place_with_progress(
+    ndb_path: impl AsRef<Path>,
+    wal_path: impl AsRef<Path>,
+    handle: &OperationHandle,
+    progress: &mut dyn FnMut(u64, u64),
+) -> Result<VacuumReport> {
+    let mut tracker = Tracker::new(handle, progress);
+    tracker.check()?;
     let ndb_path = ndb_path.as_ref();
     let wal_path = wal_path.as_ref();
 
@@ -49,8 +69,26 @@ pub fn vacuum_in_place(
     let tmp_path = ndb_path.with_extension(format!("ndb.vacuum.tmp.{pid}.{nonce}"));
     let backup_path = ndb_path.with_extension(format!("ndb.bak.{pid}.{nonce}"));
 
-    let stats = pager.write_vacuum_copy(&tmp_path, &reachable)?;
+    let out = FileBackend::new(
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .truncate(false)
+            .open(&tmp_path)?,
+    );
+    let stats = pager
+        .write_vacuum_copy(&out, &reachable, &mut tracker)
+        .and_then(|stats| tracker.check().map(|()| stats));
+    drop(out);
     drop(pager);
+    let stats = match stats {
+        Ok(stats) => stats,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    };
 
     std::fs::rename(ndb_path, &backup_path).map_err(Error::Io)?;
     if let Err(e) = std::fs::rename(&tmp_path, ndb_path) {
@@ -58,6 +96,8 @@ pub fn vacuum_in_place(
         let _ = std::fs::remove_file(&tmp_path);
         return Err(Error::Io(e));
     }
+    // The page LSNs described the replaced file.
+    crate::page_lsn::remove(ndb_path)?;
 
     Ok(VacuumReport {
         ndb_path: ndb_path.to_path_buf(),
@@ -71,14 +111,14 @@ pub fn vacuum_in_place(
 }
 
 #[derive(Debug, Default)]
-struct WalRoots {
+pub(crate) struct WalRoots {
     manifest_epoch: u64,
     segments: Vec<SegmentPointer>,
     properties_root: u64,
     stats_root: u64,
 }
 
-fn scan_wal_roots(committed: &[CommittedTx]) -> WalRoots {
+pub(crate) fn scan_wal_roots(committed: &[CommittedTx]) -> WalRoots {
     let mut state = WalRoots::default();
     for tx in committed {
         for op in &tx.ops {
@@ -88,24 +128,20 @@ fn scan_wal_roots(committed: &[CommittedTx]) -> WalRoots {
                     segments,
                     properties_root,
                     stats_root,
-                } => {
-                    if *epoch >= state.manifest_epoch {
-                        state.manifest_epoch = *epoch;
-                        state.segments = segments.clone();
-                        state.properties_root = *properties_root;
-                        state.stats_root = *stats_root;
-                    }
+                } if *epoch >= state.manifest_epoch => {
+                    state.manifest_epoch = *epoch;
+                    state.segments = segments.clone();
+                    state.properties_root = *properties_root;
+                    state.stats_root = *stats_root;
                 }
                 WalRecord::Checkpoint {
                     epoch,
                     properties_root,
                     stats_root,
                     ..
-                } => {
-                    if *epoch == state.manifest_epoch {
-                        state.properties_root = *properties_root;
-                        state.stats_root = *stats_root;
-                    }
+                } if *epoch == state.manifest_epoch => {
+                    state.properties_root = *properties_root;
+                    state.stats_root = *stats_root;
                 }
                 _ => {}
             }
@@ -114,7 +150,7 @@ fn scan_wal_roots(committed: &[CommittedTx]) -> WalRoots {
     state
 }
 
-fn mark_reachable_pages(pager: &Pager, roots: &WalRoots) -> Result<BTreeSet<PageId>> {
+pub(crate) fn mark_reachable_pages(pager: &Pager, roots: &WalRoots) -> Result<BTreeSet<PageId>> {
     let mut reachable: BTreeSet<PageId> = BTreeSet::new();
     // Always keep meta + bitmap pages.
     reachable.insert(PageId::new(0));
@@ -178,9 +214,8 @@ fn mark_reachable_pages(pager: &Pager, roots: &WalRoots) -> Result<BTreeSet<Page
         if seg.meta_page_id == 0 {
             continue;
         }
-        let meta = PageId::new(seg.meta_page_id);
-        reachable.insert(meta);
-        mark_csr_segment_pages(pager, meta, &mut reachable)?;
+        let pages = crate::csr::segment_page_ids(pager, PageId::new(seg.meta_page_id))?;
+        reachable.extend(pages.into_iter().filter(|&id| id != 0).map(PageId::new));
     }
 
     Ok(reachable)
@@ -213,45 +248,6 @@ fn mark_blob_chain(
     Ok(())
 }
 
-fn mark_csr_segment_pages(
-    pager: &Pager,
-    meta_page_id: PageId,
-    reachable: &mut BTreeSet<PageId>,
-) -> Result<()> {
-    const META_MAGIC: [u8; 8] = *b"NDBCSRv1";
-
-    let meta = pager.read_page(meta_page_id)?;
-    if meta[0..8] != META_MAGIC {
-        return Err(Error::WalProtocol("invalid csr meta magic"));
-    }
-
-    let offsets_page_count = u32::from_le_bytes(meta[40..44].try_into().unwrap()) as usize;
-    let edges_page_count = u32::from_le_bytes(meta[44..48].try_into().unwrap()) as usize;
-
-    let needed = 48usize + (offsets_page_count + edges_page_count) * 8;
-    if needed > PAGE_SIZE {
-        return Err(Error::WalProtocol("csr meta page overflow"));
-    }
-
-    let mut off = 48usize;
-    for _ in 0..offsets_page_count {
-        let id = u64::from_le_bytes(meta[off..off + 8].try_into().unwrap());
-        off += 8;
-        if id != 0 {
-            reachable.insert(PageId::new(id));
-        }
-    }
-    for _ in 0..edges_page_count {
-        let id = u64::from_le_bytes(meta[off..off + 8].try_into().unwrap());
-        off += 8;
-        if id != 0 {
-            reachable.insert(PageId::new(id));
-        }
-    }
-
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -298,4 +294,81 @@ mod tests {
         let pid = pager.allocate_page().unwrap();
         assert_eq!(pid.as_u64(), orphan_blob_id);
     }
+
+    #[test]
+    fn vacuum_keeps_compacted_csr_segments() {
+        use crate::engine::GraphEngine;
+
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("graph.ndb");
+        let wal = dir.path().join("graph.wal");
+        {
+            let engine = GraphEngine::open(&ndb, &wal).unwrap();
+            let mut tx = engine.begin_write();
+            let a = tx.create_node(10, 1).unwrap();
+            let b = tx.create_node(20, 1).unwrap();
+            tx.create_edge(a, 7, b);
+            tx.commit().unwrap();
+            engine.checkpoint().unwrap();
+        }
+
+        vacuum_in_place(&ndb, &wal).unwrap();
+
+        let engine = GraphEngine::open(&ndb, &wal).unwrap();
+        let a = engine.lookup_internal_id(10).unwrap();
+        assert_eq!(engine.begin_read().neighbors(a, Some(7)).count(), 1);
+    }
+
+    #[test]
+    fn cancelled_vacuum_leaves_the_database_in_place() {
+        use crate::engine::GraphEngine;
+
+        let dir = tempdir().unwrap();
+        let ndb = dir.path().join("graph.ndb");
+        let wal = dir.path().join("graph.wal");
+        {
+            let engine = GraphEngine::open(&ndb, &wal).unwrap();
+            let mut tx = engine.begin_write();
+            for id in 0..50 {
+                let node = tx.create_node(id, 1).unwrap();
+                tx.set_node_property(node, "n".to_string(), PropertyValue::Int(id as i64))
+                    .unwrap();
+            }
+            tx.commit().unwrap();
+            engine.checkpoint().unwrap();
+        }
+        let before = std::fs::read(&ndb).unwrap();
+
+        let handle = OperationHandle::new();
+        let watcher = handle.clone();
+        let err = vacuum_in_place_with_progress(&ndb, &wal, &handle, &mut |done, _| {
+            if done > 0 {
+                watcher.cancel();
+            }
+        })
+        .unwrap_err();
+        assert!(matches!(err, Error::Cancelled), "{err}");
+        assert!(handle.progress().done < handle.progress().total);
+        assert_eq!(std::fs::read(&ndb).unwrap(), before);
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.contains(".tmp.") || name.contains(".bak."))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+
+        let mut calls = 0;
+        let report = vacuum_in_place_with_progress(
+            &ndb,
+            &wal,
+            &OperationHandle::new(),
+            &mut |done, total| {
+                calls += 1;
+                assert!(done <= total);
+            },
+        )
+        .unwrap();
+        assert!(calls > 1);
+        assert!(report.copied_data_pages > 0);
+    }
 }
@@ -2,6 +2,7 @@ use crate::pager::{PageId, Pager};
 use crate::property::PropertyValue;
 use crate::{Error, PAGE_SIZE, Result};
 use crc32fast::Hasher;
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
@@ -85,6 +86,64 @@ pub enum WalRecord {
         dst: u32,
         key: String,
     },
+    SaveQuery {
+        name: String,
+        cypher: String,
+    },
+    DropQuery {
+        name: String,
+    },
+    /// Client-supplied key of the transaction it appears in, committed as `txid`.
+    IdempotencyKey {
+        key: String,
+        txid: u64,
+    },
+    /// Gives the label (or relationship type) `label_id` the name `name`.
+    RenameLabel {
+        label_id: u32,
+        name: String,
+    },
+    /// Marks (or unmarks) `label.property` as sensitive for redaction.
+    SetSensitive {
+        label: String,
+        property: String,
+        sensitive: bool,
+    },
+    /// Restricts relationships of type `rel_type` to the `(source label,
+    /// target label)` pairs in `endpoints`; an empty list lifts the restriction.
+    SetEndpoints {
+        rel_type: String,
+        endpoints: Vec<(String, String)>,
+    },
+    /// Keeps checkpointed WAL history archived for `keep_millis`; `None`
+    /// stops keeping it.
+    SetRetention {
+        keep_millis: Option<u64>,
+    },
+    /// Places (or releases) the legal hold `name`, placed at Unix time
+    /// `placed_at_millis`.
+    SetLegalHold {
+        name: String,
+        placed_at_millis: u64,
+        held: bool,
+    },
+    /// Moves the properties of nodes labelled `label` to the cold tier (or
+    /// back to the hot one) at the next compaction.
+    SetLabelTier {
+        label: String,
+        cold: bool,
+    },
+    /// Binds the namespace `prefix` to the base `iri`, or unbinds it when
+    /// `iri` is `None`.
+    SetNamespace {
+        prefix: String,
+        iri: Option<String>,
+    },
+    /// Stores `vector` as the embedding of `node` in the vector index.
+    SetVector {
+        node: u32,
+        vector: Vec<f32>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -113,6 +172,17 @@ impl WalRecord {
             WalRecord::SetEdgeProperty { .. } => 12,
             WalRecord::RemoveNodeProperty { .. } => 13,
             WalRecord::RemoveEdgeProperty { .. } => 14,
+            WalRecord::SaveQuery { .. } => 19,
+            WalRecord::DropQuery { .. } => 20,
+            WalRecord::IdempotencyKey { .. } => 21,
+            WalRecord::RenameLabel { .. } => 22,
+            WalRecord::SetSensitive { .. } => 23,
+            WalRecord::SetEndpoints { .. } => 24,
+            WalRecord::SetRetention { .. } => 25,
+            WalRecord::SetLegalHold { .. } => 26,
+            WalRecord::SetLabelTier { .. } => 27,
+            WalRecord::SetNamespace { .. } => 28,
+            WalRecord::SetVector { .. } => 29,
         }
     }
 
@@ -237,6 +307,113 @@ impl WalRecord {
                 out.extend_from_slice(&key_len.to_le_bytes());
                 out.extend_from_slice(key_bytes);
             }
+            WalRecord::SaveQuery { name, cypher } => {
+                for text in [name, cypher] {
+                    let bytes = text.as_bytes();
+                    let len = u32::try_from(bytes.len())
+                        .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                    out.extend_from_slice(&len.to_le_bytes());
+                    out.extend_from_slice(bytes);
+                }
+            }
+            WalRecord::DropQuery { name } => {
+                let name_bytes = name.as_bytes();
+                let name_len = u32::try_from(name_bytes.len())
+                    .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&name_len.to_le_bytes());
+                out.extend_from_slice(name_bytes);
+            }
+            WalRecord::IdempotencyKey { key, txid } => {
+                out.extend_from_slice(&txid.to_le_bytes());
+                let key_bytes = key.as_bytes();
+                let key_len = u32::try_from(key_bytes.len())
+                    .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&key_len.to_le_bytes());
+                out.extend_from_slice(key_bytes);
+            }
+            WalRecord::RenameLabel { label_id, name } => {
+                out.extend_from_slice(&label_id.to_le_bytes());
+                let name_bytes = name.as_bytes();
+                let name_len = u32::try_from(name_bytes.len())
+                    .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&name_len.to_le_bytes());
+                out.extend_from_slice(name_bytes);
+            }
+            WalRecord::SetSensitive {
+                label,
+                property,
+                sensitive,
+            } => {
+                out.push(u8::from(*sensitive));
+                for text in [label, property] {
+                    let bytes = text.as_bytes();
+                    let len = u32::try_from(bytes.len())
+                        .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                    out.extend_from_slice(&len.to_le_bytes());
+                    out.extend_from_slice(bytes);
+                }
+            }
+            WalRecord::SetEndpoints {
+                rel_type,
+                endpoints,
+            } => {
+                let count = u32::try_from(endpoints.len())
+                    .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&count.to_le_bytes());
+                let texts = std::iter::once(rel_type)
+                    .chain(endpoints.iter().flat_map(|(src, dst)| [src, dst]));
+                for text in texts {
+                    let bytes = text.as_bytes();
+                    let len = u32::try_from(bytes.len())
+                        .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                    out.extend_from_slice(&len.to_le_bytes());
+                    out.extend_from_slice(bytes);
+                }
+            }
+            WalRecord::SetRetention { keep_millis } => {
+                out.push(u8::from(keep_millis.is_some()));
+                out.extend_from_slice(&keep_millis.unwrap_or(0).to_le_bytes());
+            }
+            WalRecord::SetLegalHold {
+                name,
+                placed_at_millis,
+                held,
+            } => {
+                out.push(u8::from(*held));
+                out.extend_from_slice(&placed_at_millis.to_le_bytes());
+                let name_bytes = name.as_bytes();
+                let name_len = u32::try_from(name_bytes.len())
+                    .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&name_len.to_le_bytes());
+                out.extend_from_slice(name_bytes);
+            }
+            WalRecord::SetLabelTier { label, cold } => {
+                out.push(u8::from(*cold));
+                let label_bytes = label.as_bytes();
+                let label_len = u32::try_from(label_bytes.len())
+                    .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&label_len.to_le_bytes());
+                out.extend_from_slice(label_bytes);
+            }
+            WalRecord::SetNamespace { prefix, iri } => {
+                out.push(u8::from(iri.is_some()));
+                for text in std::iter::once(prefix).chain(iri) {
+                    let bytes = text.as_bytes();
+                    let len = u32::try_from(bytes.len())
+                        .map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                    out.extend_from_slice(&len.to_le_bytes());
+                    out.extend_from_slice(bytes);
+                }
+            }
+            WalRecord::SetVector { node, vector } => {
+                let count =
+                    u32::try_from(vector.len()).map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+                out.extend_from_slice(&node.to_le_bytes());
+                out.extend_from_slice(&count.to_le_bytes());
+                for value in vector {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
         }
         Ok(out)
     }
@@ -464,15 +641,196 @@ impl WalRecord {
                     .map_err(|_| Error::WalProtocol("invalid UTF-8 in key"))?;
                 Ok(WalRecord::RemoveEdgeProperty { src, rel, dst, key })
             }
+            19 => {
+                // SaveQuery: [name_len: u32][name: bytes][cypher_len: u32][cypher: bytes]
+                let (name, rest) = read_len_prefixed_str(payload, "invalid SaveQuery payload")?;
+                let (cypher, rest) = read_len_prefixed_str(rest, "invalid SaveQuery payload")?;
+                if !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid SaveQuery payload"));
+                }
+                Ok(WalRecord::SaveQuery { name, cypher })
+            }
+            20 => {
+                // DropQuery: [name_len: u32][name: bytes]
+                let (name, rest) = read_len_prefixed_str(payload, "invalid DropQuery payload")?;
+                if !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid DropQuery payload"));
+                }
+                Ok(WalRecord::DropQuery { name })
+            }
+            21 => {
+                // IdempotencyKey: [txid: u64][key_len: u32][key: bytes]
+                if payload.len() < 8 {
+                    return Err(Error::WalProtocol("invalid IdempotencyKey payload"));
+                }
+                let txid = u64::from_le_bytes(payload[0..8].try_into().unwrap());
+                let (key, rest) =
+                    read_len_prefixed_str(&payload[8..], "invalid IdempotencyKey payload")?;
+                if !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid IdempotencyKey payload"));
+                }
+                Ok(WalRecord::IdempotencyKey { key, txid })
+            }
+            22 => {
+                // RenameLabel: [label_id: u32][name_len: u32][name: bytes]
+                if payload.len() < 4 {
+                    return Err(Error::WalProtocol("invalid RenameLabel payload"));
+                }
+                let label_id = u32::from_le_bytes(payload[0..4].try_into().unwrap());
+                let (name, rest) =
+                    read_len_prefixed_str(&payload[4..], "invalid RenameLabel payload")?;
+                if !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid RenameLabel payload"));
+                }
+                Ok(WalRecord::RenameLabel { label_id, name })
+            }
+            23 => {
+                // SetSensitive: [sensitive: u8][label_len: u32][label][property_len: u32][property]
+                let (&flag, rest) = payload
+                    .split_first()
+                    .ok_or(Error::WalProtocol("invalid SetSensitive payload"))?;
+                let (label, rest) = read_len_prefixed_str(rest, "invalid SetSensitive payload")?;
+                let (property, rest) = read_len_prefixed_str(rest, "invalid SetSensitive payload")?;
+                if flag > 1 || !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid SetSensitive payload"));
+                }
+                Ok(WalRecord::SetSensitive {
+                    label,
+                    property,
+                    sensitive: flag == 1,
+                })
+            }
+            24 => {
+                // SetEndpoints: [count: u32][rel_type_len: u32][rel_type]
+                // then count x ([src_len: u32][src][dst_len: u32][dst])
+                const WHAT: &str = "invalid SetEndpoints payload";
+                if payload.len() < 4 {
+                    return Err(Error::WalProtocol(WHAT));
+                }
+                let count = u32::from_le_bytes(payload[0..4].try_into().unwrap());
+                let (rel_type, mut rest) = read_len_prefixed_str(&payload[4..], WHAT)?;
+                let mut endpoints = Vec::new();
+                for _ in 0..count {
+                    let (src, after_src) = read_len_prefixed_str(rest, WHAT)?;
+                    let (dst, after_dst) = read_len_prefixed_str(after_src, WHAT)?;
+                    endpoints.push((src, dst));
+                    rest = after_dst;
+                }
+                if !rest.is_empty() {
+                    return Err(Error::WalProtocol(WHAT));
+                }
+                Ok(WalRecord::SetEndpoints {
+                    rel_type,
+                    endpoints,
+                })
+            }
+            25 => {
+                // SetRetention: [present: u8][keep_millis: u64]
+                if payload.len() != 9 || payload[0] > 1 {
+                    return Err(Error::WalProtocol("invalid SetRetention payload"));
+                }
+                let keep_millis = u64::from_le_bytes(payload[1..9].try_into().unwrap());
+                Ok(WalRecord::SetRetention {
+                    keep_millis: (payload[0] == 1).then_some(keep_millis),
+                })
+            }
+            26 => {
+                // SetLegalHold: [held: u8][placed_at_millis: u64][name_len: u32][name]
+                if payload.len() < 9 || payload[0] > 1 {
+                    return Err(Error::WalProtocol("invalid SetLegalHold payload"));
+                }
+                let placed_at_millis = u64::from_le_bytes(payload[1..9].try_into().unwrap());
+                let (name, rest) =
+                    read_len_prefixed_str(&payload[9..], "invalid SetLegalHold payload")?;
+                if !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid SetLegalHold payload"));
+                }
+                Ok(WalRecord::SetLegalHold {
+                    name,
+                    placed_at_millis,
+                    held: payload[0] == 1,
+                })
+            }
+            27 => {
+                // SetLabelTier: [cold: u8][label_len: u32][label]
+                let (&flag, rest) = payload
+                    .split_first()
+                    .ok_or(Error::WalProtocol("invalid SetLabelTier payload"))?;
+                let (label, rest) = read_len_prefixed_str(rest, "invalid SetLabelTier payload")?;
+                if flag > 1 || !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid SetLabelTier payload"));
+                }
+                Ok(WalRecord::SetLabelTier {
+                    label,
+                    cold: flag == 1,
+                })
+            }
+            28 => {
+                // SetNamespace: [bound: u8][prefix_len: u32][prefix][iri_len: u32][iri]?
+                let (&flag, rest) = payload
+                    .split_first()
+                    .ok_or(Error::WalProtocol("invalid SetNamespace payload"))?;
+                let (prefix, mut rest) =
+                    read_len_prefixed_str(rest, "invalid SetNamespace payload")?;
+                let iri = if flag == 1 {
+                    let (iri, after) = read_len_prefixed_str(rest, "invalid SetNamespace payload")?;
+                    rest = after;
+                    Some(iri)
+                } else {
+                    None
+                };
+                if flag > 1 || !rest.is_empty() {
+                    return Err(Error::WalProtocol("invalid SetNamespace payload"));
+                }
+                Ok(WalRecord::SetNamespace { prefix, iri })
+            }
+            29 => {
+                // SetVector: [node: u32][count: u32] then count x f32
+                if payload.len() < 8 {
+                    return Err(Error::WalProtocol("invalid SetVector payload"));
+                }
+                let node = u32::from_le_bytes(payload[0..4].try_into().unwrap());
+                let count = u32::from_le_bytes(payload[4..8].try_into().unwrap()) as usize;
+                let values = &payload[8..];
+                if values.len() != count.saturating_mul(4) {
+                    return Err(Error::WalProtocol("invalid SetVector payload"));
+                }
+                let vector = values
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect();
+                Ok(WalRecord::SetVector { node, vector })
+            }
             _ => Err(Error::WalProtocol("unknown record type")),
         }
     }
 }
 
+/// Default size at which the active WAL file is sealed into a numbered segment.
+pub const DEFAULT_WAL_SEGMENT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Write-ahead log made of sealed segments plus one active file.
+///
+/// The active file lives at `path`; once it grows past the segment limit it is
+/// renamed to `<path>.<seq>` (see [`sealed_segment_path`]) at a transaction
+/// boundary and a fresh active file is started. Readers replay sealed segments
+/// in sequence order followed by the active file. Checkpointing rewrites the
+/// active file as a snapshot and deletes every sealed segment; with
+/// [`Wal::set_archive`] on, the old log is first copied into the archive (see
+/// [`crate::wal_archive`]).
+///
+/// A [`Wal::frozen`] log has no file at all: it serves a fixed list of
+/// committed transactions, e.g. from a packed image, and refuses appends.
 #[derive(Debug)]
 pub struct Wal {
     path: PathBuf,
     file: Option<File>,
+    segment_bytes: u64,
+    archive: bool,
+    frozen: Option<Vec<CommittedTx>>,
+    /// Built by the first [`Wal::tail_after`], then kept up to date by
+    /// appends and rotations; a checkpoint drops it.
+    tail_index: Option<TailIndex>,
 }
 
 impl Wal {
@@ -487,32 +845,181 @@ impl Wal {
         Ok(Self {
             path,
             file: Some(file),
+            segment_bytes: DEFAULT_WAL_SEGMENT_BYTES,
+            archive: false,
+            frozen: None,
+            tail_index: None,
+        })
+    }
+
+    /// Opens an existing log for reading only: nothing is created, and
+    /// appends and rewrites fail. A torn tail is left in place; replay
+    /// stops before it.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            segment_bytes: 0,
+            archive: false,
+            frozen: None,
+            tail_index: None,
         })
     }
 
+    /// A read-only log replaying `committed` without touching the disk.
+    /// `path` is only reported back by [`Wal::path`].
+    pub fn frozen(path: impl AsRef<Path>, committed: Vec<CommittedTx>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            file: None,
+            segment_bytes: 0,
+            archive: false,
+            frozen: Some(committed),
+            tail_index: None,
+        }
+    }
+
+    /// Whether this log was built by [`Wal::frozen`].
+    #[inline]
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
     #[inline]
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Sets the size at which [`Wal::rotate_if_full`] seals the active file.
+    ///
+    /// `0` disables rotation.
+    pub fn set_segment_bytes(&mut self, bytes: u64) {
+        self.segment_bytes = bytes;
+    }
+
+    /// Whether [`Wal::rewrite_as_snapshot`] archives the log it replaces
+    /// instead of deleting it.
+    pub fn set_archive(&mut self, archive: bool) {
+        self.archive = archive;
+    }
+
+    /// Seals the active file into the next numbered segment once it reaches the
+    /// configured size. Returns `true` if a rotation happened.
+    ///
+    /// Must only be called at a transaction boundary, after `fsync`, so that no
+    /// transaction ever spans two segments.
+    pub fn rotate_if_full(&mut self) -> Result<bool> {
+        if self.segment_bytes == 0 {
+            return Ok(false);
+        }
+        let Some(file) = self.file.as_mut() else {
+            return Err(Error::WalProtocol("wal file is closed"));
+        };
+        if file.metadata()?.len() < self.segment_bytes {
+            return Ok(false);
+        }
+        file.sync_all()?;
+
+        let seq = sealed_segments(&self.path)?
+            .last()
+            .map(|(seq, _)| seq + 1)
+            .unwrap_or(1);
+        let _ = self.file.take();
+        std::fs::rename(&self.path, sealed_segment_path(&self.path, seq))?;
+        if let Some(index) = &mut self.tail_index {
+            index.sealed(seq);
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        self.file = Some(file);
+        Ok(true)
+    }
+
+    /// Truncates a torn tail left behind by a crash mid-append.
+    ///
+    /// Replay already stops at the first incomplete or checksum-failing trailing
+    /// record, but new appends would land *after* those bytes and become
+    /// unreachable on the next open. Only the exclusive owner of the WAL (the
+    /// engine on open) may call this; readers such as backup must not.
+    ///
+    /// A zero length prefix is a torn tail only when nothing but zeros
+    /// follows it; otherwise this fails with [`Error::WalCorrupted`] and
+    /// leaves the file alone.
+    pub fn truncate_torn_tail(&mut self) -> Result<WalRecoveryReport> {
+        if self.is_frozen() {
+            return Ok(WalRecoveryReport::default());
+        }
+        let Some(file) = self.file.as_mut() else {
+            return Err(Error::WalProtocol("wal file is closed"));
+        };
+
+        let file_len = file.metadata()?.len();
+        let mut reader = WalReader::open(&self.path)?;
+        let mut records = 0u64;
+        while reader.next_record()?.is_some() {
+            records += 1;
+        }
+        let valid_len = reader.offset;
+
+        let truncated_bytes = file_len.saturating_sub(valid_len);
+        if truncated_bytes > 0 {
+            file.set_len(valid_len)?;
+            file.sync_all()?;
+            self.tail_index = None;
+        }
+
+        Ok(WalRecoveryReport {
+            records,
+            valid_len,
+            truncated_bytes,
+        })
+    }
+
     pub fn append(&mut self, record: &WalRecord) -> Result<u64> {
+        if self.is_frozen() {
+            return Err(Error::ReadOnly);
+        }
         let Some(file) = self.file.as_mut() else {
             return Err(Error::WalProtocol("wal file is closed"));
         };
         let body = record.encode_body()?;
-        let len = u32::try_from(body.len()).map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
-        let crc = crc32(&body);
 
         let offset = file.metadata()?.len();
         file.seek(SeekFrom::End(0))?;
-        file.write_all(&len.to_le_bytes())?;
-        file.write_all(&crc.to_le_bytes())?;
-        file.write_all(&body)?;
+        write_frames(file, body)?;
         file.flush()?;
+        if let Some(index) = &mut self.tail_index {
+            index.observe(None, offset, record);
+        }
         Ok(offset)
     }
 
+    /// Size on disk of the active file plus every sealed segment.
+    pub fn total_bytes(&self) -> Result<u64> {
+        if self.is_frozen() {
+            return Ok(0);
+        }
+        let Some(file) = self.file.as_ref() else {
+            return Err(Error::WalProtocol("wal file is closed"));
+        };
+        let mut total = file.metadata()?.len();
+        for (_, segment) in sealed_segments(&self.path)? {
+            total += std::fs::metadata(segment)?.len();
+        }
+        Ok(total)
+    }
+
     pub fn fsync(&mut self) -> Result<()> {
+        if self.is_frozen() {
+            return Ok(());
+        }
         let Some(file) = self.file.as_mut() else {
             return Err(Error::WalProtocol("wal file is closed"));
         };
@@ -521,8 +1028,12 @@ impl Wal {
     }
 
     pub fn rewrite_as_snapshot(&mut self, txid: u64, ops: Vec<WalRecord>) -> Result<()> {
+        if self.is_frozen() {
+            return Err(Error::ReadOnly);
+        }
         // Close the current file handle so we can replace it safely.
         let _ = self.file.take();
+        self.tail_index = None;
 
         let tmp = {
             let pid = std::process::id();
@@ -537,14 +1048,7 @@ impl Wal {
                 .open(&tmp)?;
 
             fn append_to(file: &mut File, record: &WalRecord) -> Result<()> {
-                let body = record.encode_body()?;
-                let len =
-                    u32::try_from(body.len()).map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
-                let crc = crc32(&body);
-                file.write_all(&len.to_le_bytes())?;
-                file.write_all(&crc.to_le_bytes())?;
-                file.write_all(&body)?;
-                Ok(())
+                write_frames(file, record.encode_body()?)
             }
 
             append_to(&mut tmp_file, &WalRecord::BeginTx { txid })?;
@@ -556,6 +1060,10 @@ impl Wal {
             tmp_file.sync_data()?;
         }
 
+        if self.archive {
+            crate::wal_archive::archive_log(&self.path)?;
+        }
+
         // Best-effort replace (POSIX: rename overwrites; Windows: needs remove first).
         if std::fs::rename(&tmp, &self.path).is_err() {
             if self.path.exists() {
@@ -564,6 +1072,12 @@ impl Wal {
             std::fs::rename(&tmp, &self.path)?;
         }
 
+        // The snapshot supersedes every sealed segment. A crash before this loop
+        // finishes only leaves older tx behind the snapshot's checkpoint.
+        for (_, segment) in sealed_segments(&self.path)? {
+            std::fs::remove_file(segment)?;
+        }
+
         let file = OpenOptions::new()
             .read(true)
             .write(true)
@@ -575,7 +1089,10 @@ impl Wal {
     }
 
     pub fn replay_into(&self, pager: &mut Pager) -> Result<ReplayStats> {
-        let mut reader = WalReader::open(&self.path)?;
+        if self.is_frozen() {
+            return Err(Error::ReadOnly);
+        }
+        let mut reader = WalReader::open_segmented(&self.path)?;
         let mut stats = ReplayStats::default();
 
         let mut current_txid: Option<u64> = None;
@@ -617,46 +1134,71 @@ impl Wal {
     }
 
     pub fn replay_committed(&self) -> Result<Vec<CommittedTx>> {
+        if let Some(committed) = &self.frozen {
+            return Ok(committed.clone());
+        }
         Self::replay_committed_from_path(&self.path)
     }
 
+    /// Replays committed transactions from every segment of the WAL at `path`.
     pub fn replay_committed_from_path(path: impl AsRef<Path>) -> Result<Vec<CommittedTx>> {
-        let mut reader = WalReader::open(path.as_ref())?;
-        let mut out: Vec<CommittedTx> = Vec::new();
-
-        let mut current_txid: Option<u64> = None;
-        let mut pending: Vec<WalRecord> = Vec::new();
+        let mut reader = CommittedReader::new(WalReader::open_segmented(path.as_ref())?);
+        let mut out = Vec::new();
+        while let Some(tx) = reader.next_tx()? {
+            out.push(tx);
+        }
+        Ok(out)
+    }
 
-        while let Some((_offset, record)) = reader.next_record()? {
-            match record {
-                WalRecord::BeginTx { txid } => {
-                    current_txid = Some(txid);
-                    pending.clear();
-                }
-                WalRecord::CommitTx { txid } => {
-                    if current_txid != Some(txid) {
-                        return Err(Error::WalProtocol("CommitTx without matching BeginTx"));
-                    }
-                    out.push(CommittedTx {
-                        txid,
-                        ops: std::mem::take(&mut pending),
-                    });
-                    current_txid = None;
-                }
-                other => {
-                    if current_txid.is_none() {
-                        return Err(Error::WalProtocol("op outside tx"));
-                    }
-                    pending.push(other);
-                }
+    /// Reads the live log from the first committed transaction with a txid
+    /// above `since_txid`, without replaying what comes before it. The
+    /// index of transaction positions this needs is built on first use.
+    pub(crate) fn tail_after(&mut self, since_txid: u64) -> Result<LiveTail> {
+        if self.is_frozen() {
+            return Err(Error::ReadOnly);
+        }
+        let index = match &mut self.tail_index {
+            Some(index) => index,
+            slot => slot.insert(TailIndex::build(&self.path)?),
+        };
+        // Txids are taken at `BeginTx` but logged at commit, so the log is
+        // only sorted by the running maximum.
+        let first = index.txns.partition_point(|tx| tx.max_txid <= since_txid);
+        let mut names = HashMap::new();
+        for (tx, label_id, name) in &index.labels {
+            if *tx >= first {
+                break;
             }
+            names.insert(*label_id, name.clone());
         }
-
-        Ok(out)
+        let reader = match index.txns.get(first) {
+            Some(tx) => Some(CommittedReader::new(WalReader::open_segmented_at(
+                &self.path, tx.segment, tx.offset,
+            )?)),
+            None => None,
+        };
+        Ok(LiveTail {
+            start: index.start,
+            names,
+            reader,
+        })
     }
 
     pub(crate) fn latest_checkpoint_info(&self) -> Result<Option<(u64, u64)>> {
-        let mut reader = WalReader::open(&self.path)?;
+        if let Some(committed) = &self.frozen {
+            let last = committed
+                .iter()
+                .rev()
+                .flat_map(|tx| tx.ops.iter().rev())
+                .find_map(|op| match op {
+                    WalRecord::Checkpoint {
+                        up_to_txid, epoch, ..
+                    } => Some((*up_to_txid, *epoch)),
+                    _ => None,
+                });
+            return Ok(last);
+        }
+        let mut reader = WalReader::open_segmented(&self.path)?;
         let mut last: Option<(u64, u64)> = None;
         while let Some((_offset, record)) = reader.next_record()? {
             if let WalRecord::Checkpoint {
@@ -676,6 +1218,219 @@ pub struct CommittedTx {
     pub ops: Vec<WalRecord>,
 }
 
+/// The live log from a position found by [`Wal::tail_after`].
+pub(crate) struct LiveTail {
+    /// Oldest txid the live log can be tailed from, see
+    /// [`crate::wal_tail::tail_start`].
+    pub start: u64,
+    /// Label names as of the position.
+    pub names: HashMap<u32, String>,
+    /// Committed transactions from the position on; `None` when there are
+    /// none.
+    pub reader: Option<CommittedReader>,
+}
+
+/// Positions of the committed transactions in the live log.
+#[derive(Debug, Default)]
+struct TailIndex {
+    /// In log order.
+    txns: Vec<TxPosition>,
+    /// Label names set by committed transactions, with the index in `txns`
+    /// of the transaction setting them.
+    labels: Vec<(usize, u32, String)>,
+    start: u64,
+    /// The transaction being read or appended.
+    open: Option<OpenTx>,
+}
+
+#[derive(Debug)]
+struct TxPosition {
+    /// Sealed segment sequence number; `None` for the active file.
+    segment: Option<u64>,
+    offset: u64,
+    /// Highest txid committed up to and including this transaction.
+    max_txid: u64,
+}
+
+#[derive(Debug)]
+struct OpenTx {
+    txid: u64,
+    segment: Option<u64>,
+    offset: u64,
+    labels: Vec<(u32, String)>,
+    checkpoint: Option<u64>,
+}
+
+impl TailIndex {
+    fn build(path: &Path) -> Result<Self> {
+        let mut reader = WalReader::open_segmented(path)?;
+        let mut index = Self::default();
+        while let Some((offset, record)) = reader.next_record()? {
+            index.observe(reader.current_seq, offset, &record);
+        }
+        Ok(index)
+    }
+
+    /// Takes in `record`, read or appended at `offset` of `segment`.
+    fn observe(&mut self, segment: Option<u64>, offset: u64, record: &WalRecord) {
+        match record {
+            WalRecord::BeginTx { txid } => {
+                self.open = Some(OpenTx {
+                    txid: *txid,
+                    segment,
+                    offset,
+                    labels: Vec::new(),
+                    checkpoint: None,
+                });
+            }
+            WalRecord::CommitTx { txid } => {
+                let Some(tx) = self.open.take().filter(|tx| tx.txid == *txid) else {
+                    return;
+                };
+                if self.txns.is_empty() {
+                    self.start = tx.checkpoint.unwrap_or(0);
+                }
+                let at = self.txns.len();
+                let max_txid = self.txns.last().map_or(0, |last| last.max_txid);
+                self.txns.push(TxPosition {
+                    segment: tx.segment,
+                    offset: tx.offset,
+                    max_txid: max_txid.max(tx.txid),
+                });
+                self.labels
+                    .extend(tx.labels.into_iter().map(|(id, name)| (at, id, name)));
+            }
+            WalRecord::CreateLabel { name, label_id }
+            | WalRecord::RenameLabel { label_id, name } => {
+                if let Some(tx) = &mut self.open {
+                    tx.labels.push((*label_id, name.clone()));
+                }
+            }
+            WalRecord::Checkpoint { up_to_txid, .. } => {
+                if let Some(tx) = &mut self.open {
+                    tx.checkpoint = Some(*up_to_txid);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The active file was sealed as segment `seq`.
+    fn sealed(&mut self, seq: u64) {
+        let open = self.open.iter_mut().map(|tx| &mut tx.segment);
+        for segment in self.txns.iter_mut().map(|tx| &mut tx.segment).chain(open) {
+            if segment.is_none() {
+                *segment = Some(seq);
+            }
+        }
+    }
+}
+
+/// Groups the records of a log into committed transactions.
+pub(crate) struct CommittedReader {
+    reader: SegmentedReader,
+    current_txid: Option<u64>,
+    pending: Vec<WalRecord>,
+}
+
+impl CommittedReader {
+    fn new(reader: SegmentedReader) -> Self {
+        Self {
+            reader,
+            current_txid: None,
+            pending: Vec::new(),
+        }
+    }
+
+    pub(crate) fn next_tx(&mut self) -> Result<Option<CommittedTx>> {
+        while let Some((_offset, record)) = self.reader.next_record()? {
+            match record {
+                WalRecord::BeginTx { txid } => {
+                    self.current_txid = Some(txid);
+                    self.pending.clear();
+                }
+                WalRecord::CommitTx { txid } => {
+                    if self.current_txid != Some(txid) {
+                        return Err(Error::WalProtocol("CommitTx without matching BeginTx"));
+                    }
+                    self.current_txid = None;
+                    return Ok(Some(CommittedTx {
+                        txid,
+                        ops: std::mem::take(&mut self.pending),
+                    }));
+                }
+                other => {
+                    if self.current_txid.is_none() {
+                        return Err(Error::WalProtocol("op outside tx"));
+                    }
+                    self.pending.push(other);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Encodes `committed` as u32-length-prefixed record bodies, each tx
+/// bracketed by `BeginTx`/`CommitTx`. Unlike the log itself this has no
+/// per-frame checksums; callers checksum the whole buffer.
+pub(crate) fn encode_committed(committed: &[CommittedTx]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut push = |record: &WalRecord| -> Result<()> {
+        let body = record.encode_body()?;
+        let len = u32::try_from(body.len()).map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&body);
+        Ok(())
+    };
+    for tx in committed {
+        push(&WalRecord::BeginTx { txid: tx.txid })?;
+        for op in &tx.ops {
+            push(op)?;
+        }
+        push(&WalRecord::CommitTx { txid: tx.txid })?;
+    }
+    Ok(out)
+}
+
+/// Decodes transactions written by [`encode_committed`].
+pub(crate) fn decode_committed(mut bytes: &[u8]) -> Result<Vec<CommittedTx>> {
+    let mut out = Vec::new();
+    let mut current: Option<CommittedTx> = None;
+    while !bytes.is_empty() {
+        if bytes.len() < 4 {
+            return Err(Error::WalProtocol("truncated record length"));
+        }
+        let (len, rest) = bytes.split_at(4);
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        if rest.len() < len {
+            return Err(Error::WalProtocol("truncated record"));
+        }
+        let (body, rest) = rest.split_at(len);
+        bytes = rest;
+        match WalRecord::decode_body(body)? {
+            WalRecord::BeginTx { txid } => {
+                current = Some(CommittedTx {
+                    txid,
+                    ops: Vec::new(),
+                });
+            }
+            WalRecord::CommitTx { txid } => match current.take() {
+                Some(tx) if tx.txid == txid => out.push(tx),
+                _ => return Err(Error::WalProtocol("CommitTx without matching BeginTx")),
+            },
+            op => match current.as_mut() {
+                Some(tx) => tx.ops.push(op),
+                None => return Err(Error::WalProtocol("op outside tx")),
+            },
+        }
+    }
+    if current.is_some() {
+        return Err(Error::WalProtocol("unterminated tx"));
+    }
+    Ok(out)
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct ReplayStats {
     pub records: u64,
@@ -683,6 +1438,17 @@ pub struct ReplayStats {
     pub last_offset: u64,
 }
 
+/// Outcome of [`Wal::truncate_torn_tail`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WalRecoveryReport {
+    /// Number of intact records kept.
+    pub records: u64,
+    /// Byte length of the WAL after recovery.
+    pub valid_len: u64,
+    /// Bytes of torn tail that were discarded.
+    pub truncated_bytes: u64,
+}
+
 fn apply_op(pager: &mut Pager, op: WalRecord) -> Result<()> {
     match op {
         WalRecord::PageWrite { page_id, page } => {
@@ -699,26 +1465,178 @@ fn apply_op(pager: &mut Pager, op: WalRecord) -> Result<()> {
     }
 }
 
+/// Path of the sealed segment with sequence number `seq` for the WAL at `path`.
+pub fn sealed_segment_path(path: &Path, seq: u64) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{seq:06}"));
+    PathBuf::from(name)
+}
+
+/// Lists the sealed segments of the WAL at `path`, ordered by sequence number.
+pub fn sealed_segments(path: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return Ok(Vec::new());
+    };
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Io(e)),
+    };
+
+    let mut out = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(suffix) = name
+            .to_str()
+            .and_then(|n| n.strip_prefix(file_name))
+            .and_then(|n| n.strip_prefix('.'))
+        else {
+            continue;
+        };
+        if suffix.len() < 6 || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(seq) = suffix.parse::<u64>() {
+            out.push((seq, entry.path()));
+        }
+    }
+    out.sort_by_key(|(seq, _)| *seq);
+    Ok(out)
+}
+
+/// Reads sealed segments in order, then the active file.
+struct SegmentedReader {
+    sealed: std::vec::IntoIter<(u64, PathBuf)>,
+    active: Option<PathBuf>,
+    current: Option<WalReader>,
+    current_sealed: bool,
+    /// Sequence number of the sealed segment being read; `None` for the
+    /// active file.
+    current_seq: Option<u64>,
+}
+
+impl SegmentedReader {
+    fn next_record(&mut self) -> Result<Option<(u64, WalRecord)>> {
+        loop {
+            if let Some(reader) = self.current.as_mut() {
+                if let Some(item) = reader.next_record()? {
+                    return Ok(Some(item));
+                }
+                // Sealed segments were fsynced before rotation; a short read there
+                // would silently drop tx that later segments depend on.
+                if self.current_sealed && reader.offset != reader.file_len {
+                    return Err(Error::WalProtocol("truncated sealed wal segment"));
+                }
+                self.current = None;
+            }
+
+            if let Some((seq, path)) = self.sealed.next() {
+                self.current = Some(WalReader::open(&path)?);
+                self.current_sealed = true;
+                self.current_seq = Some(seq);
+            } else if let Some(path) = self.active.take() {
+                if !path.exists() {
+                    return Ok(None);
+                }
+                self.current = Some(WalReader::open(&path)?);
+                self.current_sealed = false;
+                self.current_seq = None;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+}
+
 struct WalReader {
     file: File,
+    file_len: u64,
     offset: u64,
 }
 
 impl WalReader {
     fn open(path: &Path) -> Result<Self> {
         let file = OpenOptions::new().read(true).open(path)?;
-        Ok(Self { file, offset: 0 })
+        let file_len = file.metadata()?.len();
+        Ok(Self {
+            file,
+            file_len,
+            offset: 0,
+        })
     }
 
-    fn next_record(&mut self) -> Result<Option<(u64, WalRecord)>> {
+    fn open_segmented(path: &Path) -> Result<SegmentedReader> {
+        Ok(SegmentedReader {
+            sealed: sealed_segments(path)?.into_iter(),
+            active: Some(path.to_path_buf()),
+            current: None,
+            current_sealed: false,
+            current_seq: None,
+        })
+    }
+
+    /// Like [`Self::open_segmented`], starting at `offset` of the sealed
+    /// segment `segment`, or of the active file when that is `None`.
+    fn open_segmented_at(
+        path: &Path,
+        segment: Option<u64>,
+        offset: u64,
+    ) -> Result<SegmentedReader> {
+        let mut sealed = sealed_segments(path)?;
+        let first = match segment {
+            Some(seq) => {
+                sealed.retain(|(s, _)| *s > seq);
+                sealed_segment_path(path, seq)
+            }
+            None => {
+                sealed.clear();
+                path.to_path_buf()
+            }
+        };
+        let mut reader = Self::open(&first)?;
+        reader.file.seek(SeekFrom::Start(offset))?;
+        reader.offset = offset;
+        Ok(SegmentedReader {
+            sealed: sealed.into_iter(),
+            active: segment.is_some().then(|| path.to_path_buf()),
+            current: Some(reader),
+            current_sealed: segment.is_some(),
+            current_seq: segment,
+        })
+    }
+
+    /// Reads one on-disk frame and returns its offset and body.
+    fn next_frame(&mut self) -> Result<Option<(u64, Vec<u8>)>> {
         let record_offset = self.offset;
 
         let Some(len) = self.try_read_u32()? else {
             return Ok(None);
         };
 
-        const MAX_WAL_RECORD_LEN: u32 = 1024 * 1024; // 1MB
-        if len > MAX_WAL_RECORD_LEN {
+        // Every record carries at least its type byte, so a zero length can only
+        // come from a zero-filled tail that was allocated but never written.
+        // Anything written after it means the log itself is damaged.
+        if len == 0 {
+            if self.rest_is_zero()? {
+                return Ok(None);
+            }
+            return Err(Error::WalCorrupted {
+                offset: record_offset,
+                reason: "zero-length record followed by data",
+            });
+        }
+
+        let record_end = record_offset + 4 + 4 + len as u64;
+        if len as usize > MAX_WAL_RECORD_LEN {
+            if record_end > self.file_len {
+                // A torn length prefix pointing past EOF is a crash artifact.
+                return Ok(None);
+            }
             return Err(Error::WalRecordTooLarge(len));
         }
 
@@ -737,14 +1655,65 @@ impl WalReader {
         let got_crc = crc32(&body);
         if got_crc != crc {
             // In crash scenarios, it's acceptable to have a torn final record.
-            // Treat CRC mismatch as end-of-log and ignore the tail.
-            return Ok(None);
+            // A mismatch with intact data after it is real corruption, though.
+            if record_end >= self.file_len {
+                return Ok(None);
+            }
+            return Err(Error::WalChecksumMismatch {
+                offset: record_offset,
+            });
         }
 
         self.offset += 4 + 4 + len as u64;
+        Ok(Some((record_offset, body)))
+    }
+
+    fn next_record(&mut self) -> Result<Option<(u64, WalRecord)>> {
+        let Some((record_offset, body)) = self.next_frame()? else {
+            return Ok(None);
+        };
+        if body.first() != Some(&FRAGMENT_RECORD_TYPE) {
+            return WalRecord::decode_body(&body).map(|record| Some((record_offset, record)));
+        }
+
+        let mut assembled = Vec::new();
+        let mut frame = body;
+        loop {
+            let [_, last, data @ ..] = frame.as_slice() else {
+                return Err(Error::WalProtocol("invalid wal fragment"));
+            };
+            if assembled.len() + data.len() > MAX_FRAGMENTED_RECORD_LEN {
+                return Err(Error::WalRecordTooLarge(u32::MAX));
+            }
+            assembled.extend_from_slice(data);
+            if *last != 0 {
+                break;
+            }
+            match self.next_frame()? {
+                Some((_, next)) if next.first() == Some(&FRAGMENT_RECORD_TYPE) => frame = next,
+                Some(_) => return Err(Error::WalProtocol("wal fragment run interrupted")),
+                None => {
+                    // A run cut short by a crash is a torn tail: drop all of it.
+                    self.offset = record_offset;
+                    return Ok(None);
+                }
+            }
+        }
+        WalRecord::decode_body(&assembled).map(|record| Some((record_offset, record)))
+    }
 
-        let record = WalRecord::decode_body(&body)?;
-        Ok(Some((record_offset, record)))
+    /// Whether every byte from the read position to the end of the file is zero.
+    fn rest_is_zero(&mut self) -> Result<bool> {
+        let mut buf = [0u8; 8192];
+        loop {
+            let n = self.file.read(&mut buf)?;
+            if n == 0 {
+                return Ok(true);
+            }
+            if buf[..n].iter().any(|&b| b != 0) {
+                return Ok(false);
+            }
+        }
     }
 
     fn try_read_u32(&mut self) -> Result<Option<u32>> {
@@ -757,6 +1726,40 @@ impl WalReader {
     }
 }
 
+/// Longest frame body the reader accepts. Larger record bodies are split into a run of
+/// fragment frames, `[FRAGMENT_RECORD_TYPE][last: u8][data]`, stitched back on replay.
+const MAX_WAL_RECORD_LEN: usize = 1024 * 1024;
+const FRAGMENT_RECORD_TYPE: u8 = 18;
+const FRAGMENT_DATA_LEN: usize = MAX_WAL_RECORD_LEN - 2;
+/// Cap on a reassembled record: the largest property value plus room for its key.
+const MAX_FRAGMENTED_RECORD_LEN: usize =
+    crate::property::MAX_PROPERTY_VALUE_BYTES + MAX_WAL_RECORD_LEN;
+
+fn write_frames(file: &mut File, body: Vec<u8>) -> Result<()> {
+    let frames = if body.len() <= MAX_WAL_RECORD_LEN {
+        vec![body]
+    } else {
+        let count = body.len().div_ceil(FRAGMENT_DATA_LEN);
+        body.chunks(FRAGMENT_DATA_LEN)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut frame = Vec::with_capacity(2 + chunk.len());
+                frame.push(FRAGMENT_RECORD_TYPE);
+                frame.push(u8::from(i + 1 == count));
+                frame.extend_from_slice(chunk);
+                frame
+            })
+            .collect()
+    };
+    for frame in frames {
+        let len = u32::try_from(frame.len()).map_err(|_| Error::WalRecordTooLarge(u32::MAX))?;
+        file.write_all(&len.to_le_bytes())?;
+        file.write_all(&crc32(&frame).to_le_bytes())?;
+        file.write_all(&frame)?;
+    }
+    Ok(())
+}
+
 fn crc32(bytes: &[u8]) -> u32 {
     let mut hasher = Hasher::new();
     hasher.update(bytes);
@@ -770,11 +1773,121 @@ fn read_u64(payload: &[u8]) -> Result<u64> {
     Ok(u64::from_le_bytes(payload.try_into().unwrap()))
 }
 
+fn read_len_prefixed_str<'a>(payload: &'a [u8], what: &'static str) -> Result<(String, &'a [u8])> {
+    if payload.len() < 4 {
+        return Err(Error::WalProtocol(what));
+    }
+    let len = u32::from_le_bytes(payload[0..4].try_into().unwrap()) as usize;
+    if payload.len() < 4 + len {
+        return Err(Error::WalProtocol(what));
+    }
+    let text = String::from_utf8(payload[4..4 + len].to_vec())
+        .map_err(|_| Error::WalProtocol("invalid UTF-8 in WAL string"))?;
+    Ok((text, &payload[4 + len..]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn named_query_records_roundtrip() {
+        for record in [
+            WalRecord::SaveQuery {
+                name: "topUsers".to_string(),
+                cypher: "MATCH (u:User) RETURN u.name LIMIT $n".to_string(),
+            },
+            WalRecord::DropQuery {
+                name: "topUsers".to_string(),
+            },
+            WalRecord::IdempotencyKey {
+                key: "import-2024-05-01".to_string(),
+                txid: 42,
+            },
+            WalRecord::RenameLabel {
+                label_id: 3,
+                name: "Customer".to_string(),
+            },
+            WalRecord::SetSensitive {
+                label: "User".to_string(),
+                property: "ssn".to_string(),
+                sensitive: true,
+            },
+            WalRecord::SetEndpoints {
+                rel_type: "KNOWS".to_string(),
+                endpoints: vec![
+                    ("Person".to_string(), "Person".to_string()),
+                    ("Person".to_string(), "Bot".to_string()),
+                ],
+            },
+            WalRecord::SetEndpoints {
+                rel_type: "KNOWS".to_string(),
+                endpoints: Vec::new(),
+            },
+            WalRecord::SetRetention {
+                keep_millis: Some(86_400_000),
+            },
+            WalRecord::SetRetention { keep_millis: None },
+            WalRecord::SetLegalHold {
+                name: "case-1138".to_string(),
+                placed_at_millis: 1_700_000_000_000,
+                held: true,
+            },
+            WalRecord::SetLabelTier {
+                label: "Invoice".to_string(),
+                cold: true,
+            },
+            WalRecord::SetNamespace {
+                prefix: "foaf".to_string(),
+                iri: Some("http://xmlns.com/foaf/0.1/".to_string()),
+            },
+            WalRecord::SetNamespace {
+                prefix: "foaf".to_string(),
+                iri: None,
+            },
+            WalRecord::SetVector {
+                node: 7,
+                vector: vec![0.5, -1.25, 3.0],
+            },
+        ] {
+            let body = record.encode_body().unwrap();
+            assert_eq!(WalRecord::decode_body(&body).unwrap(), record);
+            assert!(WalRecord::decode_body(&body[..body.len() - 1]).is_err());
+        }
+    }
+
+    #[test]
+    fn frozen_wal_replays_encoded_committed_txs() {
+        let committed = vec![CommittedTx {
+            txid: 7,
+            ops: vec![
+                WalRecord::CreateLabel {
+                    name: "City".to_string(),
+                    label_id: 0,
+                },
+                WalRecord::Checkpoint {
+                    up_to_txid: 6,
+                    epoch: 2,
+                    properties_root: 9,
+                    stats_root: 0,
+                },
+            ],
+        }];
+        let bytes = encode_committed(&committed).unwrap();
+        assert!(decode_committed(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut wal = Wal::frozen("packed", decode_committed(&bytes).unwrap());
+        let replayed = wal.replay_committed().unwrap();
+        assert_eq!(replayed[0].txid, 7);
+        assert_eq!(replayed[0].ops, committed[0].ops);
+        assert_eq!(wal.latest_checkpoint_info().unwrap(), Some((6, 2)));
+        assert!(matches!(
+            wal.append(&WalRecord::BeginTx { txid: 8 }),
+            Err(Error::ReadOnly)
+        ));
+    }
+
     #[test]
     fn replay_applies_only_committed_tx() {
         let dir = tempdir().unwrap();
@@ -958,4 +2071,239 @@ mod tests {
         let page = pager.read_page(PageId::new(2)).unwrap();
         assert_eq!(page[0], 0xCC);
     }
+
+    #[test]
+    fn truncate_torn_tail_discards_partial_record() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let intact_len;
+        {
+            let mut wal = Wal::open(&wal_path).unwrap();
+            wal.append(&WalRecord::BeginTx { txid: 1 }).unwrap();
+            wal.append(&WalRecord::CreateEdge {
+                src: 1,
+                rel: 1,
+                dst: 2,
+            })
+            .unwrap();
+            wal.append(&WalRecord::CommitTx { txid: 1 }).unwrap();
+            wal.fsync().unwrap();
+            intact_len = std::fs::metadata(&wal_path).unwrap().len();
+
+            let mut file = OpenOptions::new().append(true).open(&wal_path).unwrap();
+            file.write_all(&[0x10, 0x00, 0x00, 0x00, 0xAB]).unwrap();
+        }
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        let report = wal.truncate_torn_tail().unwrap();
+        assert_eq!(report.records, 3);
+        assert_eq!(report.valid_len, intact_len);
+        assert_eq!(report.truncated_bytes, 5);
+
+        // Appends after recovery must be visible to replay.
+        wal.append(&WalRecord::BeginTx { txid: 2 }).unwrap();
+        wal.append(&WalRecord::CommitTx { txid: 2 }).unwrap();
+        let txs = wal.replay_committed().unwrap();
+        assert_eq!(txs.iter().map(|tx| tx.txid).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn large_records_are_fragmented_and_reassembled() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+        let value = PropertyValue::String("ж🚀".repeat(500_000));
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        wal.append(&WalRecord::BeginTx { txid: 1 }).unwrap();
+        wal.append(&WalRecord::SetNodeProperty {
+            node: 7,
+            key: "text".to_string(),
+            value: value.clone(),
+        })
+        .unwrap();
+        wal.append(&WalRecord::CommitTx { txid: 1 }).unwrap();
+
+        let txs = wal.replay_committed().unwrap();
+        assert_eq!(txs.len(), 1);
+        match &txs[0].ops[..] {
+            [
+                WalRecord::SetNodeProperty {
+                    node: 7,
+                    key,
+                    value: got,
+                },
+            ] => {
+                assert_eq!(key, "text");
+                assert_eq!(got, &value);
+            }
+            other => panic!("unexpected ops: {other:?}"),
+        }
+        assert_eq!(wal.truncate_torn_tail().unwrap().records, 3);
+    }
+
+    #[test]
+    fn truncate_torn_tail_drops_partial_fragment_run() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let intact_len;
+        {
+            let mut wal = Wal::open(&wal_path).unwrap();
+            wal.append(&WalRecord::BeginTx { txid: 1 }).unwrap();
+            wal.append(&WalRecord::CommitTx { txid: 1 }).unwrap();
+            intact_len = std::fs::metadata(&wal_path).unwrap().len();
+            wal.append(&WalRecord::BeginTx { txid: 2 }).unwrap();
+            wal.append(&WalRecord::SetNodeProperty {
+                node: 1,
+                key: "blob".to_string(),
+                value: PropertyValue::Blob(vec![7; 3 * MAX_WAL_RECORD_LEN]),
+            })
+            .unwrap();
+        }
+        // Cut the run inside its second fragment.
+        let file = OpenOptions::new().write(true).open(&wal_path).unwrap();
+        let begin2_len = 4 + 4 + 9;
+        file.set_len(intact_len + begin2_len + MAX_WAL_RECORD_LEN as u64 + 100)
+            .unwrap();
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        let report = wal.truncate_torn_tail().unwrap();
+        assert_eq!(report.records, 3);
+        assert_eq!(report.valid_len, intact_len + begin2_len);
+
+        wal.append(&WalRecord::CommitTx { txid: 2 }).unwrap();
+        let txs = wal.replay_committed().unwrap();
+        assert_eq!(txs.iter().map(|tx| tx.txid).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(txs[1].ops.is_empty());
+    }
+
+    #[test]
+    fn replay_rejects_checksum_mismatch_before_intact_records() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let begin_offset;
+        {
+            let mut wal = Wal::open(&wal_path).unwrap();
+            begin_offset = wal.append(&WalRecord::BeginTx { txid: 1 }).unwrap();
+            wal.append(&WalRecord::CommitTx { txid: 1 }).unwrap();
+            wal.fsync().unwrap();
+        }
+        {
+            let mut file = OpenOptions::new().write(true).open(&wal_path).unwrap();
+            file.seek(SeekFrom::Start(begin_offset + 4)).unwrap();
+            file.write_all(&0u32.to_le_bytes()).unwrap();
+        }
+
+        let wal = Wal::open(&wal_path).unwrap();
+        let err = wal.replay_committed().unwrap_err();
+        assert!(matches!(err, Error::WalChecksumMismatch { offset: 0 }));
+    }
+
+    #[test]
+    fn zero_length_record_ends_the_log_only_before_zeros() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let intact_len;
+        {
+            let mut wal = Wal::open(&wal_path).unwrap();
+            wal.append(&WalRecord::BeginTx { txid: 1 }).unwrap();
+            wal.append(&WalRecord::CommitTx { txid: 1 }).unwrap();
+            wal.fsync().unwrap();
+            intact_len = std::fs::metadata(&wal_path).unwrap().len();
+        }
+        // Space allocated by a crash but never written.
+        let file = OpenOptions::new().write(true).open(&wal_path).unwrap();
+        file.set_len(intact_len + 64).unwrap();
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        assert_eq!(wal.replay_committed().unwrap().len(), 1);
+        let report = wal.truncate_torn_tail().unwrap();
+        assert_eq!(report.valid_len, intact_len);
+        assert_eq!(report.truncated_bytes, 64);
+        drop(wal);
+
+        // A zeroed length with data after it is damage, not a torn tail.
+        {
+            let mut wal = Wal::open(&wal_path).unwrap();
+            wal.append(&WalRecord::BeginTx { txid: 2 }).unwrap();
+            wal.append(&WalRecord::CommitTx { txid: 2 }).unwrap();
+            wal.fsync().unwrap();
+        }
+        let mut file = OpenOptions::new().write(true).open(&wal_path).unwrap();
+        file.seek(SeekFrom::Start(intact_len)).unwrap();
+        file.write_all(&0u32.to_le_bytes()).unwrap();
+        drop(file);
+        let before = std::fs::metadata(&wal_path).unwrap().len();
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        let err = wal.replay_committed().unwrap_err();
+        assert!(
+            matches!(err, Error::WalCorrupted { offset, .. } if offset == intact_len),
+            "{err}"
+        );
+        assert!(wal.truncate_torn_tail().is_err());
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), before);
+    }
+
+    #[test]
+    fn rotation_seals_segments_and_snapshot_prunes_them() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        wal.set_segment_bytes(1);
+        for txid in 1..=3 {
+            wal.append(&WalRecord::BeginTx { txid }).unwrap();
+            wal.append(&WalRecord::CommitTx { txid }).unwrap();
+            wal.fsync().unwrap();
+            assert!(wal.rotate_if_full().unwrap());
+        }
+
+        let sealed = sealed_segments(&wal_path).unwrap();
+        assert_eq!(
+            sealed.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(sealed[0].1, sealed_segment_path(&wal_path, 1));
+
+        wal.append(&WalRecord::BeginTx { txid: 4 }).unwrap();
+        wal.append(&WalRecord::CommitTx { txid: 4 }).unwrap();
+        let txs = Wal::replay_committed_from_path(&wal_path).unwrap();
+        assert_eq!(
+            txs.iter().map(|tx| tx.txid).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        wal.rewrite_as_snapshot(5, Vec::new()).unwrap();
+        assert!(sealed_segments(&wal_path).unwrap().is_empty());
+        let txs = wal.replay_committed().unwrap();
+        assert_eq!(txs.iter().map(|tx| tx.txid).collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[test]
+    fn replay_rejects_truncated_sealed_segment() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+
+        let mut wal = Wal::open(&wal_path).unwrap();
+        wal.set_segment_bytes(1);
+        wal.append(&WalRecord::BeginTx { txid: 1 }).unwrap();
+        wal.append(&WalRecord::CommitTx { txid: 1 }).unwrap();
+        wal.fsync().unwrap();
+        wal.rotate_if_full().unwrap();
+
+        let sealed = sealed_segment_path(&wal_path, 1);
+        let len = std::fs::metadata(&sealed).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&sealed)
+            .unwrap()
+            .set_len(len - 2)
+            .unwrap();
+
+        assert!(matches!(wal.replay_committed(), Err(Error::WalProtocol(_))));
+    }
 }
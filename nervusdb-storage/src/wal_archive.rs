@@ -0,0 +1,238 @@
+//! Retention of checkpointed WAL history.
+//!
+//! A checkpoint rewrites the log as a snapshot and normally deletes what came
+//! before it. While a retention period is set or a legal hold is placed, the
+//! old log (sealed segments, then the active file) is first copied into one
+//! numbered file under `<wal>.archive/`. Every archive starts where the
+//! previous checkpoint left off, so the archives followed by the live log
+//! form one continuous history that
+//! [`GraphEngine::wal_tail`](crate::engine::GraphEngine::wal_tail) reads.
+//!
+//! Each checkpoint then deletes the archives that have outlived the retention
+//! period, oldest first, so the history left is never missing a piece. No
+//! archive is deleted while any legal hold is placed.
+
+use crate::wal::{CommittedTx, Wal, sealed_segments};
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long checkpointed WAL history is kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Archives are deleted once they are older than this; `None` keeps
+    /// nothing beyond what a hold pins.
+    pub keep_for: Option<Duration>,
+    /// Placed legal holds by name, with the Unix time in milliseconds each
+    /// was placed at. While any is placed, no archive is deleted.
+    pub holds: BTreeMap<String, u64>,
+}
+
+impl RetentionPolicy {
+    /// Whether checkpoints archive the log instead of deleting it.
+    pub fn archives(&self) -> bool {
+        self.keep_for.is_some() || !self.holds.is_empty()
+    }
+}
+
+/// One archived stretch of the WAL, as listed by [`archived_segments`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedSegment {
+    pub seq: u64,
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Unix time in milliseconds the checkpoint archived it at.
+    pub archived_at_millis: u64,
+}
+
+/// Directory holding the archives of the WAL at `wal_path`.
+pub fn archive_dir(wal_path: &Path) -> PathBuf {
+    let mut name = wal_path.as_os_str().to_os_string();
+    name.push(".archive");
+    PathBuf::from(name)
+}
+
+/// Lists the archives of the WAL at `wal_path`, oldest first.
+pub fn archived_segments(wal_path: &Path) -> Result<Vec<ArchivedSegment>> {
+    let entries = match std::fs::read_dir(archive_dir(wal_path)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Io(e)),
+    };
+    let mut out = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(seq) = name
+            .to_str()
+            .and_then(|n| n.strip_suffix(".wal"))
+            .filter(|n| n.len() >= 6 && n.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|n| n.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let metadata = entry.metadata()?;
+        out.push(ArchivedSegment {
+            seq,
+            path: entry.path(),
+            bytes: metadata.len(),
+            archived_at_millis: metadata.modified().map_or(0, unix_millis),
+        });
+    }
+    out.sort_by_key(|segment| segment.seq);
+    Ok(out)
+}
+
+/// Copies the sealed segments and the active file of the WAL at `wal_path`,
+/// in replay order, into the next archive. The copy is synced and renamed
+/// into place before the caller replaces the log.
+pub(crate) fn archive_log(wal_path: &Path) -> Result<()> {
+    let dir = archive_dir(wal_path);
+    std::fs::create_dir_all(&dir)?;
+    let seq = archived_segments(wal_path)?
+        .last()
+        .map_or(1, |segment| segment.seq + 1);
+    let target = dir.join(format!("{seq:06}.wal"));
+    let tmp = dir.join(format!("{seq:06}.wal.tmp"));
+
+    {
+        let mut out = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp)?;
+        let sources = sealed_segments(wal_path)?
+            .into_iter()
+            .map(|(_, path)| path)
+            .chain(wal_path.exists().then(|| wal_path.to_path_buf()));
+        for source in sources {
+            std::io::copy(&mut File::open(source)?, &mut out)?;
+        }
+        out.sync_all()?;
+    }
+    std::fs::rename(&tmp, &target)?;
+    Ok(())
+}
+
+/// Deletes archives older than `policy.keep_for` as of `now`, oldest first,
+/// stopping at the first one still kept. Deletes nothing while a hold is
+/// placed. Returns how many were deleted.
+pub(crate) fn purge(wal_path: &Path, policy: &RetentionPolicy, now: SystemTime) -> Result<usize> {
+    if !policy.holds.is_empty() {
+        return Ok(0);
+    }
+    let now = unix_millis(now);
+    let keep_millis = policy.keep_for.map_or(0, |keep| {
+        u64::try_from(keep.as_millis()).unwrap_or(u64::MAX)
+    });
+    let mut purged = 0;
+    for segment in archived_segments(wal_path)? {
+        if policy.keep_for.is_some() && segment.archived_at_millis.saturating_add(keep_millis) > now
+        {
+            break;
+        }
+        std::fs::remove_file(&segment.path)?;
+        purged += 1;
+    }
+    Ok(purged)
+}
+
+/// Committed transactions of every archive, oldest first. A transaction
+/// archived twice, after a crash between archiving and rewriting the log,
+/// is returned once.
+pub(crate) fn replay_archived(wal_path: &Path) -> Result<Vec<CommittedTx>> {
+    let mut out = Vec::new();
+    for segment in archived_segments(wal_path)? {
+        out.extend(Wal::replay_committed_from_path(&segment.path)?);
+    }
+    Ok(dedup_txids(out))
+}
+
+/// Drops every repeat of a txid after its first occurrence. Txids are taken
+/// at `BeginTx` but logged at commit, so the log is not sorted by txid.
+pub(crate) fn dedup_txids(committed: Vec<CommittedTx>) -> Vec<CommittedTx> {
+    let mut seen = HashSet::new();
+    committed
+        .into_iter()
+        .filter(|tx| seen.insert(tx.txid))
+        .collect()
+}
+
+/// `time` as milliseconds since the Unix epoch, 0 before it.
+pub(crate) fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wal::WalRecord;
+    use tempfile::tempdir;
+
+    fn commit(wal: &mut Wal, txid: u64) {
+        wal.append(&WalRecord::BeginTx { txid }).unwrap();
+        wal.append(&WalRecord::CommitTx { txid }).unwrap();
+    }
+
+    #[test]
+    fn archiving_wal_keeps_every_checkpointed_tx() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+        let mut wal = Wal::open(&wal_path).unwrap();
+        wal.set_segment_bytes(1);
+        wal.set_archive(true);
+        commit(&mut wal, 1);
+        wal.rotate_if_full().unwrap();
+        commit(&mut wal, 2);
+        wal.rewrite_as_snapshot(3, Vec::new()).unwrap();
+        commit(&mut wal, 4);
+        wal.rewrite_as_snapshot(5, Vec::new()).unwrap();
+
+        let archived = archived_segments(&wal_path).unwrap();
+        assert_eq!(
+            archived.iter().map(|s| s.seq).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let txids: Vec<u64> = replay_archived(&wal_path)
+            .unwrap()
+            .iter()
+            .map(|tx| tx.txid)
+            .collect();
+        assert_eq!(txids, vec![1, 2, 3, 4]);
+
+        // Archiving stops with the policy; what is archived stays.
+        wal.set_archive(false);
+        commit(&mut wal, 6);
+        wal.rewrite_as_snapshot(7, Vec::new()).unwrap();
+        assert_eq!(archived_segments(&wal_path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn purge_stops_at_the_first_kept_archive_and_respects_holds() {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("test.wal");
+        let mut wal = Wal::open(&wal_path).unwrap();
+        wal.set_archive(true);
+        for txid in [1, 3] {
+            commit(&mut wal, txid);
+            wal.rewrite_as_snapshot(txid + 1, Vec::new()).unwrap();
+        }
+        let now = SystemTime::now();
+        let mut policy = RetentionPolicy {
+            keep_for: Some(Duration::from_secs(3600)),
+            holds: BTreeMap::new(),
+        };
+        assert_eq!(purge(&wal_path, &policy, now).unwrap(), 0);
+
+        policy.holds.insert("audit".to_string(), 0);
+        let later = now + Duration::from_secs(7200);
+        assert_eq!(purge(&wal_path, &policy, later).unwrap(), 0);
+
+        policy.holds.clear();
+        assert_eq!(purge(&wal_path, &policy, later).unwrap(), 2);
+        assert!(archived_segments(&wal_path).unwrap().is_empty());
+    }
+}
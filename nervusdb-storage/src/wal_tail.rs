@@ -0,0 +1,243 @@
+//! Decoded view of committed WAL transactions for external consumers.
+//!
+//! [`GraphEngine::wal_tail`](crate::engine::GraphEngine::wal_tail) turns the
+//! raw log into [`WalTxn`]s of [`WalChange`]s: graph mutations only, with
+//! label and relationship type names resolved. Page images, manifests and
+//! other bookkeeping records are left out, so the shape stays stable when the
+//! on-disk format changes. Search indexes and caches can follow the database
+//! by remembering the last txid they applied and tailing from there.
+
+use crate::idmap::{ExternalId, InternalNodeId, LabelId};
+use crate::property::PropertyValue;
+use crate::wal::{CommittedTx, WalRecord};
+use std::collections::HashMap;
+
+/// One committed transaction's graph changes, in the order the log holds
+/// them: created nodes and edges come before property changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalTxn {
+    pub txid: u64,
+    pub changes: Vec<WalChange>,
+}
+
+/// A graph mutation recorded in the WAL.
+///
+/// New variants may be added; match with a wildcard arm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum WalChange {
+    CreateNode {
+        node: InternalNodeId,
+        external_id: ExternalId,
+        label: Option<String>,
+    },
+    DeleteNode {
+        node: InternalNodeId,
+    },
+    AddLabel {
+        node: InternalNodeId,
+        label: String,
+    },
+    RemoveLabel {
+        node: InternalNodeId,
+        label: String,
+    },
+    CreateEdge {
+        src: InternalNodeId,
+        rel_type: String,
+        dst: InternalNodeId,
+    },
+    DeleteEdge {
+        src: InternalNodeId,
+        rel_type: String,
+        dst: InternalNodeId,
+    },
+    SetNodeProperty {
+        node: InternalNodeId,
+        key: String,
+        value: PropertyValue,
+    },
+    RemoveNodeProperty {
+        node: InternalNodeId,
+        key: String,
+    },
+    SetEdgeProperty {
+        src: InternalNodeId,
+        rel_type: String,
+        dst: InternalNodeId,
+        key: String,
+        value: PropertyValue,
+    },
+    RemoveEdgeProperty {
+        src: InternalNodeId,
+        rel_type: String,
+        dst: InternalNodeId,
+        key: String,
+    },
+    /// A label or relationship type was renamed; earlier changes used `from`.
+    RenameLabel {
+        from: String,
+        to: String,
+    },
+}
+
+/// Iterator over the [`WalTxn`]s committed after a given txid.
+#[derive(Debug)]
+pub struct WalTail {
+    txns: std::vec::IntoIter<WalTxn>,
+}
+
+impl Iterator for WalTail {
+    type Item = WalTxn;
+
+    fn next(&mut self) -> Option<WalTxn> {
+        self.txns.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.txns.size_hint()
+    }
+}
+
+/// The oldest txid `committed` can be tailed from: a WAL rewritten by a
+/// checkpoint starts with a transaction carrying its `Checkpoint` record,
+/// and the history up to that point is gone.
+pub(crate) fn tail_start(committed: &[CommittedTx]) -> u64 {
+    committed
+        .first()
+        .and_then(|tx| {
+            tx.ops.iter().find_map(|op| match op {
+                WalRecord::Checkpoint { up_to_txid, .. } => Some(*up_to_txid),
+                _ => None,
+            })
+        })
+        .unwrap_or(0)
+}
+
+/// Whether [`decode`] turns `tx` into a [`WalTxn`], when past `since_txid`.
+pub(crate) fn has_changes(tx: &CommittedTx) -> bool {
+    tx.ops.iter().any(|op| {
+        matches!(
+            op,
+            WalRecord::RenameLabel { .. }
+                | WalRecord::CreateNode { .. }
+                | WalRecord::TombstoneNode { .. }
+                | WalRecord::AddNodeLabel { .. }
+                | WalRecord::RemoveNodeLabel { .. }
+                | WalRecord::CreateEdge { .. }
+                | WalRecord::TombstoneEdge { .. }
+                | WalRecord::SetNodeProperty { .. }
+                | WalRecord::RemoveNodeProperty { .. }
+                | WalRecord::SetEdgeProperty { .. }
+                | WalRecord::RemoveEdgeProperty { .. }
+        )
+    })
+}
+
+/// Decodes up to `max_txns` transactions after `since_txid`. Names come
+/// from `names`, the label names as of the first transaction, and the label
+/// records in the log as of each change, falling back to `current_name`.
+pub(crate) fn decode(
+    committed: Vec<CommittedTx>,
+    mut names: HashMap<LabelId, String>,
+    since_txid: u64,
+    max_txns: usize,
+    current_name: impl Fn(LabelId) -> Option<String>,
+) -> WalTail {
+    let mut txns = Vec::new();
+    for tx in committed {
+        if txns.len() >= max_txns {
+            break;
+        }
+        let emit = tx.txid > since_txid;
+        let mut changes = Vec::new();
+        for op in tx.ops {
+            let name = |names: &HashMap<LabelId, String>, id: LabelId| {
+                names
+                    .get(&id)
+                    .cloned()
+                    .or_else(|| current_name(id))
+                    .unwrap_or_else(|| format!("#{id}"))
+            };
+            let change = match op {
+                WalRecord::CreateLabel { name, label_id } => {
+                    names.insert(label_id, name);
+                    None
+                }
+                WalRecord::RenameLabel { label_id, name: to } => {
+                    let from = name(&names, label_id);
+                    names.insert(label_id, to.clone());
+                    Some(WalChange::RenameLabel { from, to })
+                }
+                WalRecord::CreateNode {
+                    external_id,
+                    label_id,
+                    internal_id,
+                } => Some(WalChange::CreateNode {
+                    node: internal_id,
+                    external_id,
+                    label: (label_id != LabelId::MAX).then(|| name(&names, label_id)),
+                }),
+                WalRecord::TombstoneNode { node } => Some(WalChange::DeleteNode { node }),
+                WalRecord::AddNodeLabel { node, label_id } => Some(WalChange::AddLabel {
+                    node,
+                    label: name(&names, label_id),
+                }),
+                WalRecord::RemoveNodeLabel { node, label_id } => Some(WalChange::RemoveLabel {
+                    node,
+                    label: name(&names, label_id),
+                }),
+                WalRecord::CreateEdge { src, rel, dst } => Some(WalChange::CreateEdge {
+                    src,
+                    rel_type: name(&names, rel),
+                    dst,
+                }),
+                WalRecord::TombstoneEdge { src, rel, dst } => Some(WalChange::DeleteEdge {
+                    src,
+                    rel_type: name(&names, rel),
+                    dst,
+                }),
+                WalRecord::SetNodeProperty { node, key, value } => {
+                    Some(WalChange::SetNodeProperty { node, key, value })
+                }
+                WalRecord::RemoveNodeProperty { node, key } => {
+                    Some(WalChange::RemoveNodeProperty { node, key })
+                }
+                WalRecord::SetEdgeProperty {
+                    src,
+                    rel,
+                    dst,
+                    key,
+                    value,
+                } => Some(WalChange::SetEdgeProperty {
+                    src,
+                    rel_type: name(&names, rel),
+                    dst,
+                    key,
+                    value,
+                }),
+                WalRecord::RemoveEdgeProperty { src, rel, dst, key } => {
+                    Some(WalChange::RemoveEdgeProperty {
+                        src,
+                        rel_type: name(&names, rel),
+                        dst,
+                        key,
+                    })
+                }
+                _ => None,
+            };
+            if emit && let Some(change) = change {
+                changes.push(change);
+            }
+        }
+        if emit && !changes.is_empty() {
+            txns.push(WalTxn {
+                txid: tx.txid,
+                changes,
+            });
+        }
+    }
+    WalTail {
+        txns: txns.into_iter(),
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-label and per-relationship-type write stamps.
+//!
+//! Every commit that changes graph data takes the next sequence number and
+//! stamps it on the labels and relationship types it touched. A cached query
+//! result remembers the sequence it was computed at and stays valid while
+//! nothing it reads carries a later stamp. Labels and relationship types
+//! share the label interner, so both are keyed by [`LabelId`].
+
+use crate::idmap::LabelId;
+use std::collections::{BTreeSet, HashMap};
+
+/// What one commit touched.
+#[derive(Debug, Default)]
+pub(crate) struct Touched {
+    pub(crate) labels: BTreeSet<LabelId>,
+    pub(crate) rel_types: BTreeSet<LabelId>,
+    /// Deleted nodes hide their edges, whatever their types.
+    pub(crate) all_rel_types: bool,
+    /// Changes that can alter any result, like renaming a label.
+    pub(crate) everything: bool,
+}
+
+impl Touched {
+    fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+            && self.rel_types.is_empty()
+            && !self.all_rel_types
+            && !self.everything
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct WriteStamps {
+    seq: u64,
+    by_id: HashMap<LabelId, u64>,
+    /// Last commit touching any label / any relationship type.
+    any_label: u64,
+    any_rel_type: u64,
+    /// Last commit that touched every relationship type / everything.
+    all_rel_types: u64,
+    everything: u64,
+}
+
+impl WriteStamps {
+    pub(crate) fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub(crate) fn record(&mut self, touched: &Touched) {
+        if touched.is_empty() {
+            return;
+        }
+        self.seq += 1;
+        let seq = self.seq;
+        for id in touched.labels.iter().chain(&touched.rel_types) {
+            self.by_id.insert(*id, seq);
+        }
+        if !touched.labels.is_empty() {
+            self.any_label = seq;
+        }
+        if !touched.rel_types.is_empty() {
+            self.any_rel_type = seq;
+        }
+        if touched.all_rel_types {
+            self.all_rel_types = seq;
+            self.any_rel_type = seq;
+        }
+        if touched.everything {
+            self.everything = seq;
+        }
+    }
+
+    /// Whether a commit after `seq` touched one of `labels` or `rel_types`;
+    /// `None` stands for every label or every relationship type. Names not
+    /// interned yet map to `None` ids and count as untouched.
+    pub(crate) fn changed_since(
+        &self,
+        seq: u64,
+        labels: Option<&[Option<LabelId>]>,
+        rel_types: Option<&[Option<LabelId>]>,
+    ) -> bool {
+        if self.everything > seq {
+            return true;
+        }
+        let touched = |ids: &[Option<LabelId>]| {
+            ids.iter()
+                .flatten()
+                .any(|id| self.by_id.get(id).is_some_and(|stamp| *stamp > seq))
+        };
+        let labels_changed = match labels {
+            None => self.any_label > seq,
+            Some(ids) => touched(ids),
+        };
+        let rel_types_changed = match rel_types {
+            None => self.any_rel_type > seq,
+            Some(ids) => (!ids.is_empty() && self.all_rel_types > seq) || touched(ids),
+        };
+        labels_changed || rel_types_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_track_touched_labels_and_rel_types() {
+        let mut stamps = WriteStamps::default();
+        let start = stamps.seq();
+        stamps.record(&Touched {
+            labels: BTreeSet::from([1]),
+            ..Touched::default()
+        });
+        assert!(stamps.changed_since(start, Some(&[Some(1)]), Some(&[])));
+        assert!(!stamps.changed_since(start, Some(&[Some(2), None]), Some(&[Some(3)])));
+        assert!(stamps.changed_since(start, None, Some(&[])));
+        assert!(!stamps.changed_since(start, Some(&[]), None));
+
+        let after_labels = stamps.seq();
+        stamps.record(&Touched::default());
+        assert_eq!(stamps.seq(), after_labels);
+        stamps.record(&Touched {
+            all_rel_types: true,
+            ..Touched::default()
+        });
+        assert!(stamps.changed_since(after_labels, Some(&[]), Some(&[Some(3)])));
+        assert!(!stamps.changed_since(after_labels, Some(&[Some(1)]), Some(&[])));
+    }
+}
@@ -0,0 +1,47 @@
+//! Backpressure on writers while checkpoints fall behind.
+//!
+//! Every commit grows the WAL until a checkpoint rewrites it. When writes
+//! outpace checkpointing the log would grow until the disk is full, so a
+//! [`WriteThrottle`] first slows new write transactions down and then
+//! refuses their commits with [`Error::WriteThrottled`] until a checkpoint
+//! brings the WAL, sealed segments included, back under the watermarks.
+//!
+//! [`Error::WriteThrottled`]: crate::Error::WriteThrottled
+
+use std::time::Duration;
+
+/// WAL sizes at which writers are slowed down or refused. Either watermark
+/// can be disabled with `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteThrottle {
+    /// From this many WAL bytes on, each new write transaction waits
+    /// `delay` before it starts.
+    pub delay_wal_bytes: u64,
+    pub delay: Duration,
+    /// From this many WAL bytes on, commits fail with
+    /// [`Error::WriteThrottled`](crate::Error::WriteThrottled).
+    pub reject_wal_bytes: u64,
+}
+
+impl Default for WriteThrottle {
+    fn default() -> Self {
+        Self {
+            delay_wal_bytes: 256 * 1024 * 1024,
+            delay: Duration::from_millis(10),
+            reject_wal_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl WriteThrottle {
+    /// How long a write transaction starting at `wal_bytes` waits.
+    pub fn delay_at(&self, wal_bytes: u64) -> Option<Duration> {
+        (self.delay_wal_bytes > 0 && wal_bytes >= self.delay_wal_bytes && !self.delay.is_zero())
+            .then_some(self.delay)
+    }
+
+    /// Whether a commit at `wal_bytes` is refused.
+    pub fn rejects_at(&self, wal_bytes: u64) -> bool {
+        self.reject_wal_bytes > 0 && wal_bytes >= self.reject_wal_bytes
+    }
+}
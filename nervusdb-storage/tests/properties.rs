@@ -20,8 +20,10 @@ fn test_node_property_set_and_get() -> Result<()> {
             node_id,
             "name".to_string(),
             PropertyValue::String("Alice".to_string()),
-        );
-        txn.set_node_property(node_id, "age".to_string(), PropertyValue::Int(30));
+        )
+        .unwrap();
+        txn.set_node_property(node_id, "age".to_string(), PropertyValue::Int(30))
+            .unwrap();
         txn.commit()?;
     }
 
@@ -77,7 +79,7 @@ fn test_edge_property_set_and_get() -> Result<()> {
             node2,
             "weight".to_string(),
             PropertyValue::Float(0.5),
-        );
+        )?;
         txn.commit()?;
     }
 
@@ -109,14 +111,16 @@ fn test_property_overwrite() -> Result<()> {
     {
         let mut txn = engine.begin_write();
         let node_id = txn.create_node(1, 0)?;
-        txn.set_node_property(node_id, "age".to_string(), PropertyValue::Int(25));
+        txn.set_node_property(node_id, "age".to_string(), PropertyValue::Int(25))
+            .unwrap();
         txn.commit()?;
     }
 
     // Second transaction: overwrite property
     {
         let mut txn = engine.begin_write();
-        txn.set_node_property(0, "age".to_string(), PropertyValue::Int(30));
+        txn.set_node_property(0, "age".to_string(), PropertyValue::Int(30))
+            .unwrap();
         txn.commit()?;
     }
 
@@ -146,8 +150,9 @@ fn test_property_remove() -> Result<()> {
             node_id,
             "temp".to_string(),
             PropertyValue::String("value".to_string()),
-        );
-        txn.remove_node_property(node_id, "temp");
+        )
+        .unwrap();
+        txn.remove_node_property(node_id, "temp").unwrap();
         txn.commit()?;
     }
 
@@ -176,7 +181,8 @@ fn test_wal_replay_properties() -> Result<()> {
             node_id,
             "name".to_string(),
             PropertyValue::String("Bob".to_string()),
-        );
+        )
+        .unwrap();
         txn.commit()?;
     }
 
@@ -205,15 +211,20 @@ fn test_multiple_property_types() -> Result<()> {
     {
         let mut txn = engine.begin_write();
         let node_id = txn.create_node(1, 0)?;
-        txn.set_node_property(node_id, "null".to_string(), PropertyValue::Null);
-        txn.set_node_property(node_id, "bool".to_string(), PropertyValue::Bool(true));
-        txn.set_node_property(node_id, "int".to_string(), PropertyValue::Int(42));
-        txn.set_node_property(node_id, "float".to_string(), PropertyValue::Float(2.5));
+        txn.set_node_property(node_id, "null".to_string(), PropertyValue::Null)
+            .unwrap();
+        txn.set_node_property(node_id, "bool".to_string(), PropertyValue::Bool(true))
+            .unwrap();
+        txn.set_node_property(node_id, "int".to_string(), PropertyValue::Int(42))
+            .unwrap();
+        txn.set_node_property(node_id, "float".to_string(), PropertyValue::Float(2.5))
+            .unwrap();
         txn.set_node_property(
             node_id,
             "string".to_string(),
             PropertyValue::String("hello".to_string()),
-        );
+        )
+        .unwrap();
         txn.commit()?;
     }
 
@@ -176,7 +176,8 @@ fn t57_crash_recovery_preserves_committed() {
             a,
             "name".to_string(),
             PropertyValue::String("Bob".to_string()),
-        );
+        )
+        .unwrap();
         tx.commit().unwrap();
     }
 
@@ -209,7 +210,8 @@ fn t58_crash_recovery_rolls_back_uncommitted() {
             node,
             "name".to_string(),
             PropertyValue::String("Temp".to_string()),
-        );
+        )
+        .unwrap();
         // No commit - simulates crash
     }
 
@@ -279,7 +281,8 @@ fn t60_properties_persist_across_transactions() {
             node,
             "name".to_string(),
             PropertyValue::String("Alice".to_string()),
-        );
+        )
+        .unwrap();
         tx.commit().unwrap();
         node
     };
@@ -287,7 +290,8 @@ fn t60_properties_persist_across_transactions() {
     // Update property in new transaction
     {
         let mut tx = engine.begin_write();
-        tx.set_node_property(node, "age".to_string(), PropertyValue::Int(30));
+        tx.set_node_property(node, "age".to_string(), PropertyValue::Int(30))
+            .unwrap();
         tx.commit().unwrap();
     }
 
@@ -0,0 +1,249 @@
+//! Storage usage estimates and growth projections for capacity planning.
+
+use crate::{Db, DbSnapshot, Error, GraphSnapshot, PropertyValue, Result, derive_paths};
+use nervusdb_storage::blob_store::BlobStore;
+use nervusdb_storage::idmap::MAX_INTERNAL_NODE_IDS;
+use nervusdb_storage::property::stored_len;
+use nervusdb_storage::stats::StatsSample;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// Days ahead that [`CapacityReport::projections`] covers.
+pub const CAPACITY_HORIZON_DAYS: [u32; 3] = [30, 90, 365];
+
+/// Per-node bytes outside properties: the id map record plus outgoing and
+/// incoming CSR offsets.
+const NODE_RECORD_BYTES: u64 = 16 + 8 + 8;
+/// Per-edge bytes outside properties: one outgoing and one incoming CSR entry.
+const EDGE_RECORD_BYTES: u64 = 8 + 8;
+/// Property tree key prefixes, without the key name: a tag, the owner and
+/// the name length.
+const NODE_PROPERTY_KEY_BYTES: u64 = 1 + 4 + 4;
+const EDGE_PROPERTY_KEY_BYTES: u64 = 1 + 4 + 4 + 4 + 4;
+/// Property tree value: the blob page id.
+const PROPERTY_POINTER_BYTES: u64 = 8;
+
+const MILLIS_PER_DAY: f64 = 86_400_000.0;
+
+/// Storage estimate for the nodes of one label or the edges of one
+/// relationship type.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct EntityUsage {
+    /// Label or relationship type; empty for nodes without labels.
+    pub name: String,
+    pub count: u64,
+    pub properties: u64,
+    pub property_bytes: u64,
+    /// Record and property bytes divided by `count`.
+    pub bytes_per_entity: f64,
+}
+
+/// Growth between the oldest and newest compaction in the statistics
+/// history.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct GrowthRate {
+    pub samples: usize,
+    pub span_days: f64,
+    pub nodes_per_day: f64,
+    pub edges_per_day: f64,
+    pub file_bytes_per_day: f64,
+}
+
+/// Expected size after `days` at the current [`GrowthRate`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Projection {
+    pub days: u32,
+    pub nodes: u64,
+    pub edges: u64,
+    pub file_bytes: u64,
+}
+
+/// Result of [`capacity_report`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct CapacityReport {
+    pub file_bytes: u64,
+    /// Size of the active WAL file.
+    pub wal_bytes: u64,
+    pub nodes: u64,
+    pub edges: u64,
+    pub properties: u64,
+    /// Bytes the live records and properties need; the file also holds
+    /// indexes, statistics and free pages.
+    pub estimated_bytes: u64,
+    /// Nodes grouped by their first label.
+    pub labels: Vec<EntityUsage>,
+    pub rel_types: Vec<EntityUsage>,
+    /// `None` until two compactions at different times are recorded.
+    pub growth: Option<GrowthRate>,
+    /// One entry per [`CAPACITY_HORIZON_DAYS`] horizon when `growth` is known.
+    pub projections: Vec<Projection>,
+    /// Internal node ids left before the id space runs out; see
+    /// [`crate::MAX_INTERNAL_NODE_IDS`].
+    pub node_id_headroom: u64,
+    /// Days until `node_id_headroom` reaches zero at the current node growth.
+    pub days_to_node_id_limit: Option<f64>,
+}
+
+/// Estimates how much storage the database at `path` uses per label,
+/// relationship type and property, and projects its growth.
+///
+/// Growth comes from the statistics history that every checkpoint
+/// compaction appends to, so a database needs two checkpoints some time
+/// apart before `growth` and `projections` are filled in.
+pub fn capacity_report(path: impl AsRef<Path>) -> Result<CapacityReport> {
+    let (ndb_path, wal_path) = derive_paths(path.as_ref());
+    if !ndb_path.exists() {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no database at {}", ndb_path.display()),
+        )));
+    }
+    let db = Db::open(path.as_ref())?;
+    let snapshot = db.snapshot();
+    let file_size = |path: &Path| std::fs::metadata(path).map_or(0, |m| m.len());
+
+    let mut labels = BTreeMap::<String, Usage>::new();
+    let mut rel_types = BTreeMap::<String, Usage>::new();
+    let mut allocated_ids = 0u64;
+    for iid in snapshot.nodes() {
+        allocated_ids += 1;
+        if snapshot.is_tombstoned_node(iid) {
+            continue;
+        }
+        let label = snapshot
+            .node_label(iid)
+            .and_then(|label| snapshot.resolve_label_name(label))
+            .unwrap_or_default();
+        let properties = snapshot.node_properties(iid).unwrap_or_default();
+        labels.entry(label).or_default().add(
+            NODE_RECORD_BYTES,
+            NODE_PROPERTY_KEY_BYTES,
+            &properties,
+        );
+        for edge in snapshot.neighbors(iid, None) {
+            if snapshot.is_tombstoned_node(edge.dst) {
+                continue;
+            }
+            let rel_type = snapshot.resolve_rel_type_name(edge.rel).unwrap_or_default();
+            let properties = snapshot.edge_properties(edge).unwrap_or_default();
+            rel_types.entry(rel_type).or_default().add(
+                EDGE_RECORD_BYTES,
+                EDGE_PROPERTY_KEY_BYTES,
+                &properties,
+            );
+        }
+    }
+
+    let labels = into_usage(labels);
+    let rel_types = into_usage(rel_types);
+    let sum = |usage: &[EntityUsage], f: fn(&EntityUsage) -> u64| usage.iter().map(f).sum::<u64>();
+    let nodes = sum(&labels, |u| u.count);
+    let edges = sum(&rel_types, |u| u.count);
+    let file_bytes = file_size(&ndb_path);
+    let growth = growth_rate(&history(&snapshot));
+    let node_id_headroom = MAX_INTERNAL_NODE_IDS.saturating_sub(allocated_ids);
+
+    Ok(CapacityReport {
+        file_bytes,
+        wal_bytes: file_size(&wal_path),
+        nodes,
+        edges,
+        properties: sum(&labels, |u| u.properties) + sum(&rel_types, |u| u.properties),
+        estimated_bytes: nodes * NODE_RECORD_BYTES
+            + edges * EDGE_RECORD_BYTES
+            + sum(&labels, |u| u.property_bytes)
+            + sum(&rel_types, |u| u.property_bytes),
+        projections: growth
+            .as_ref()
+            .map(|growth| {
+                CAPACITY_HORIZON_DAYS
+                    .iter()
+                    .map(|&days| Projection {
+                        days,
+                        nodes: project(nodes, growth.nodes_per_day, days),
+                        edges: project(edges, growth.edges_per_day, days),
+                        file_bytes: project(file_bytes, growth.file_bytes_per_day, days),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        days_to_node_id_limit: growth
+            .as_ref()
+            .filter(|growth| growth.nodes_per_day > 0.0)
+            .map(|growth| node_id_headroom as f64 / growth.nodes_per_day),
+        growth,
+        labels,
+        rel_types,
+        node_id_headroom,
+    })
+}
+
+#[derive(Default)]
+struct Usage {
+    count: u64,
+    properties: u64,
+    property_bytes: u64,
+    record_bytes: u64,
+}
+
+impl Usage {
+    fn add(
+        &mut self,
+        record_bytes: u64,
+        key_bytes: u64,
+        properties: &BTreeMap<String, PropertyValue>,
+    ) {
+        self.count += 1;
+        self.record_bytes += record_bytes;
+        for (key, value) in properties {
+            self.properties += 1;
+            self.property_bytes += key_bytes
+                + key.len() as u64
+                + PROPERTY_POINTER_BYTES
+                + BlobStore::stored_bytes(stored_len(value));
+        }
+    }
+}
+
+fn into_usage(usage: BTreeMap<String, Usage>) -> Vec<EntityUsage> {
+    usage
+        .into_iter()
+        .map(|(name, usage)| EntityUsage {
+            name,
+            count: usage.count,
+            properties: usage.properties,
+            property_bytes: usage.property_bytes,
+            bytes_per_entity: (usage.record_bytes + usage.property_bytes) as f64
+                / usage.count as f64,
+        })
+        .collect()
+}
+
+fn history(snapshot: &DbSnapshot) -> Vec<StatsSample> {
+    snapshot
+        .inner
+        .statistics()
+        .map(|stats| stats.history)
+        .unwrap_or_default()
+}
+
+fn growth_rate(history: &[StatsSample]) -> Option<GrowthRate> {
+    let (first, last) = (history.first()?, history.last()?);
+    let span_days = last.unix_millis.checked_sub(first.unix_millis)? as f64 / MILLIS_PER_DAY;
+    if span_days <= 0.0 {
+        return None;
+    }
+    let per_day = |from: u64, to: u64| (to as f64 - from as f64) / span_days;
+    Some(GrowthRate {
+        samples: history.len(),
+        span_days,
+        nodes_per_day: per_day(first.total_nodes, last.total_nodes),
+        edges_per_day: per_day(first.total_edges, last.total_edges),
+        file_bytes_per_day: per_day(first.file_bytes, last.file_bytes),
+    })
+}
+
+fn project(current: u64, per_day: f64, days: u32) -> u64 {
+    (current as f64 + per_day * f64::from(days)).max(0.0) as u64
+}
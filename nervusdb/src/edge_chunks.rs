@@ -0,0 +1,151 @@
+//! Edge list streaming for bulk consumers such as GNN training loaders:
+//! every edge as an `(src, dst, weight)` row, in fixed-size columnar chunks.
+
+use crate::{
+    DbSnapshot, EdgeKey, ExternalId, GraphSnapshot, InternalNodeId, PropertyValue, RelTypeId,
+};
+
+/// One batch of edges as parallel columns: row `i` is the edge from `src[i]`
+/// to `dst[i]` with weight `weight[i]`, by external node id.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EdgeChunk {
+    pub src: Vec<ExternalId>,
+    pub dst: Vec<ExternalId>,
+    pub weight: Vec<f64>,
+}
+
+impl EdgeChunk {
+    pub fn len(&self) -> usize {
+        self.src.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.src.is_empty()
+    }
+}
+
+/// Iterator over a snapshot's edges in [`EdgeChunk`]s, from
+/// [`DbSnapshot::edges_chunked`].
+///
+/// Nodes are walked one at a time, so only the current node's adjacency and
+/// the chunk being filled are held in memory. [`EdgeChunks::fill`] writes
+/// straight into caller-owned buffers instead of allocating a chunk.
+pub struct EdgeChunks<'a> {
+    snapshot: &'a DbSnapshot,
+    nodes: Box<dyn Iterator<Item = InternalNodeId> + 'a>,
+    /// The node being walked, by external id, and its remaining edges.
+    current: Option<(ExternalId, Box<dyn Iterator<Item = EdgeKey> + 'a>)>,
+    rel: Option<RelTypeId>,
+    weight_property: Option<String>,
+    chunk_size: usize,
+}
+
+impl<'a> EdgeChunks<'a> {
+    /// Writes the next edges into the given buffers, up to the shortest of
+    /// them, and returns how many were written; 0 once every edge has been
+    /// returned. Without a weight buffer, weights are not read at all.
+    pub fn fill(
+        &mut self,
+        src: &mut [ExternalId],
+        dst: &mut [ExternalId],
+        mut weight: Option<&mut [f64]>,
+    ) -> usize {
+        let capacity = src
+            .len()
+            .min(dst.len())
+            .min(weight.as_ref().map_or(usize::MAX, |w| w.len()));
+        let mut len = 0;
+        while len < capacity {
+            let Some((src_id, edges)) = &mut self.current else {
+                match self.nodes.next() {
+                    Some(node) => {
+                        self.current = self
+                            .snapshot
+                            .resolve_external(node)
+                            .map(|id| (id, self.snapshot.neighbors(node, self.rel)));
+                        continue;
+                    }
+                    None => break,
+                }
+            };
+            let Some(edge) = edges.next() else {
+                self.current = None;
+                continue;
+            };
+            let Some(dst_id) = self.snapshot.resolve_external(edge.dst) else {
+                continue;
+            };
+            src[len] = *src_id;
+            dst[len] = dst_id;
+            if let Some(weight) = weight.as_deref_mut() {
+                weight[len] = self.weight(edge);
+            }
+            len += 1;
+        }
+        len
+    }
+
+    /// The edge's numeric weight property, or 1.0 when there is none.
+    fn weight(&self, edge: EdgeKey) -> f64 {
+        let Some(property) = &self.weight_property else {
+            return 1.0;
+        };
+        match self.snapshot.edge_property(edge, property) {
+            Some(PropertyValue::Int(w)) => w as f64,
+            Some(PropertyValue::Float(w)) => w,
+            _ => 1.0,
+        }
+    }
+}
+
+impl Iterator for EdgeChunks<'_> {
+    type Item = EdgeChunk;
+
+    fn next(&mut self) -> Option<EdgeChunk> {
+        let mut chunk = EdgeChunk {
+            src: vec![0; self.chunk_size],
+            dst: vec![0; self.chunk_size],
+            weight: vec![0.0; self.chunk_size],
+        };
+        let len = self.fill(&mut chunk.src, &mut chunk.dst, Some(&mut chunk.weight));
+        if len == 0 {
+            return None;
+        }
+        chunk.src.truncate(len);
+        chunk.dst.truncate(len);
+        chunk.weight.truncate(len);
+        Some(chunk)
+    }
+}
+
+impl DbSnapshot {
+    /// Streams every edge, or only those of relationship type `rel`, as
+    /// `(src, dst, weight)` chunks of up to `chunk_size` rows (at least 1).
+    ///
+    /// Weights come from the numeric edge property `weight_property`; edges
+    /// without one, and every edge when it is `None`, weigh 1.0. Edges are
+    /// listed by source node, so chunks can be fed to a training loader as
+    /// they arrive. An unknown relationship type yields no chunks.
+    pub fn edges_chunked(
+        &self,
+        rel: Option<&str>,
+        weight_property: Option<&str>,
+        chunk_size: usize,
+    ) -> EdgeChunks<'_> {
+        let (nodes, rel): (Box<dyn Iterator<Item = InternalNodeId>>, _) = match rel {
+            None => (self.nodes(), None),
+            Some(name) => match self.resolve_rel_type_id(name) {
+                Some(id) => (self.nodes(), Some(id)),
+                None => (Box::new(std::iter::empty()), None),
+            },
+        };
+        EdgeChunks {
+            snapshot: self,
+            nodes,
+            current: None,
+            rel,
+            weight_property: weight_property.map(str::to_string),
+            chunk_size: chunk_size.max(1),
+        }
+    }
+}
@@ -11,6 +11,9 @@ pub enum Error {
     Compatibility(String),
     /// Error during query execution.
     Query(String),
+    /// A long-running operation stopped because its
+    /// [`crate::OperationHandle`] was cancelled.
+    Cancelled,
     /// Other errors.
     Other(String),
 }
@@ -22,6 +25,7 @@ impl fmt::Display for Error {
             Error::Storage(e) => write!(f, "Storage error: {}", e),
             Error::Compatibility(e) => write!(f, "Compatibility error: {}", e),
             Error::Query(e) => write!(f, "Query error: {}", e),
+            Error::Cancelled => write!(f, "Operation cancelled"),
             Error::Other(e) => write!(f, "Error: {}", e),
         }
     }
@@ -47,6 +51,7 @@ impl From<nervusdb_storage::Error> for Error {
     fn from(e: nervusdb_storage::Error) -> Self {
         match e {
             nervusdb_storage::Error::Io(e) => Error::Io(e),
+            nervusdb_storage::Error::Cancelled => Error::Cancelled,
             nervusdb_storage::Error::StorageFormatMismatch { expected, found } => {
                 Error::Compatibility(format!(
                     "storage format mismatch: expected epoch {expected}, found {found}"
@@ -0,0 +1,1034 @@
+//! Graph export and import in GraphML and JSON Lines, with optional
+//! scrubbing of identifiers and property values on export.
+
+use crate::{
+    Db, DbSnapshot, EdgeKey, Error, ExternalId, GraphSnapshot, InternalNodeId, LabelId,
+    PropertyValue, Result, WriteTxn, derive_paths,
+};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike};
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, hash_map};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+use xml::reader::{EventReader, XmlEvent};
+
+/// File format for [`export`] and [`import`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// GraphML with node ids `n<external id>`, labels in a `labels` data key
+    /// (`:A:B`) and relationship types in a `type` data key.
+    GraphMl,
+    /// Newline-delimited JSON, one node or relationship object per line.
+    Jsonl,
+}
+
+impl FromStr for GraphFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "graphml" => Ok(GraphFormat::GraphMl),
+            "jsonl" | "ndjson" => Ok(GraphFormat::Jsonl),
+            _ => Err(Error::Other(format!(
+                "unknown graph format '{s}', expected graphml or jsonl"
+            ))),
+        }
+    }
+}
+
+/// Counts of what [`export`] wrote or [`import`] read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TransferStats {
+    pub nodes: u64,
+    pub relationships: u64,
+}
+
+/// Rewrites [`export_scrubbed`] applies to every node and relationship.
+///
+/// Property targets are either a bare name, matching that property on
+/// every node and relationship, or `Label.name` / `TYPE.name`, matching it
+/// only on nodes with the label or relationships of the type. Dropping wins
+/// over hashing; dates are generalized in whatever is left.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrubRules {
+    /// Key mixed into every hash. Exports with the same salt hash equal
+    /// values alike, so joins and distributions survive; keep it secret, or
+    /// guessable values such as emails can be recovered by trying them.
+    pub salt: String,
+    /// Replace external ids with hashes below 2^53. Relationships follow
+    /// their endpoints.
+    pub hash_ids: bool,
+    /// Properties whose values become 32-character hex digests.
+    pub hash_properties: Vec<String>,
+    /// Properties left out of the export.
+    pub drop_properties: Vec<String>,
+    /// Truncate dates and datetimes, in lists and maps too, to the start of
+    /// their year, month or day.
+    pub generalize_dates: Option<DateGranularity>,
+}
+
+/// How far [`ScrubRules::generalize_dates`] truncates temporal values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateGranularity {
+    Year,
+    Month,
+    Day,
+}
+
+impl FromStr for DateGranularity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "year" => Ok(DateGranularity::Year),
+            "month" => Ok(DateGranularity::Month),
+            "day" => Ok(DateGranularity::Day),
+            _ => Err(Error::Other(format!(
+                "unknown date granularity '{s}', expected year, month or day"
+            ))),
+        }
+    }
+}
+
+/// Writes every live node and relationship of the database at `path` to
+/// `writer`, with labels, properties and external ids.
+///
+/// JSON Lines output has one object per line, nodes first:
+///
+/// ```text
+/// {"type":"node","id":1,"labels":["User"],"properties":{"name":"ada"}}
+/// {"type":"relationship","start":1,"end":2,"rel_type":"KNOWS","properties":{}}
+/// ```
+///
+/// Blob and temporal values use the same `{"type": ..., "value": ...}`
+/// envelopes as the C API's JSON parameters. In GraphML they are stored as
+/// that JSON in string keys marked `nervusdb.json="true"`.
+pub fn export(
+    path: impl AsRef<Path>,
+    format: GraphFormat,
+    writer: impl Write,
+) -> Result<TransferStats> {
+    export_scrubbed(path, format, writer, &ScrubRules::default())
+}
+
+/// Like [`export`], rewriting ids and property values by `rules` on the way
+/// out so the file can be shared for debugging or benchmarking without the
+/// production data. The database itself is left untouched.
+pub fn export_scrubbed(
+    path: impl AsRef<Path>,
+    format: GraphFormat,
+    writer: impl Write,
+    rules: &ScrubRules,
+) -> Result<TransferStats> {
+    let (ndb_path, _) = derive_paths(path.as_ref());
+    if !ndb_path.exists() {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no database at {}", ndb_path.display()),
+        )));
+    }
+    let db = Db::open(path.as_ref())?;
+    let snapshot = db.snapshot();
+    let mut scrubber = Scrubber::new(rules);
+    let mut out = BufWriter::new(writer);
+    let stats = match format {
+        GraphFormat::GraphMl => write_graphml(&snapshot, &mut scrubber, &mut out)?,
+        GraphFormat::Jsonl => write_jsonl(&snapshot, &mut scrubber, &mut out)?,
+    };
+    out.flush()?;
+    Ok(stats)
+}
+
+/// Reads nodes and relationships in `format` from `reader` into the
+/// database at `path`, creating it if needed, in a single transaction.
+///
+/// Nodes keep the external ids they were exported with, so an id that
+/// already exists in the database fails the import. Relationships may
+/// appear before or after their endpoints but must connect nodes from the
+/// same input.
+pub fn import(
+    path: impl AsRef<Path>,
+    format: GraphFormat,
+    reader: impl Read,
+) -> Result<TransferStats> {
+    let db = Db::open(path.as_ref())?;
+    let mut importer = Importer {
+        txn: db.begin_write(),
+        nodes: HashMap::new(),
+        relationships: Vec::new(),
+    };
+    let reader = BufReader::new(reader);
+    match format {
+        GraphFormat::GraphMl => read_graphml(reader, &mut importer)?,
+        GraphFormat::Jsonl => read_jsonl(reader, &mut importer)?,
+    }
+    let stats = importer.finish()?;
+    db.checkpoint()?;
+    db.close()?;
+    Ok(stats)
+}
+
+struct NodeRecord {
+    id: ExternalId,
+    labels: Vec<String>,
+    properties: BTreeMap<String, PropertyValue>,
+}
+
+struct RelRecord {
+    start: ExternalId,
+    end: ExternalId,
+    rel_type: String,
+    properties: BTreeMap<String, PropertyValue>,
+}
+
+fn live_nodes(snapshot: &DbSnapshot) -> impl Iterator<Item = InternalNodeId> + '_ {
+    snapshot
+        .nodes()
+        .filter(|&iid| !snapshot.is_tombstoned_node(iid))
+}
+
+fn node_record(snapshot: &DbSnapshot, iid: InternalNodeId) -> Result<NodeRecord> {
+    let id = snapshot
+        .resolve_external(iid)
+        .ok_or_else(|| Error::Other(format!("node {iid} has no external id")))?;
+    let labels = snapshot
+        .resolve_node_labels(iid)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|label| snapshot.resolve_label_name(label))
+        .collect();
+    Ok(NodeRecord {
+        id,
+        labels,
+        properties: snapshot.node_properties(iid).unwrap_or_default(),
+    })
+}
+
+fn relationships(snapshot: &DbSnapshot) -> impl Iterator<Item = Result<RelRecord>> + '_ {
+    live_nodes(snapshot).flat_map(move |src| {
+        snapshot
+            .neighbors(src, None)
+            .filter(move |edge| !snapshot.is_tombstoned_node(edge.dst))
+            .map(move |edge| rel_record(snapshot, edge))
+    })
+}
+
+fn rel_record(snapshot: &DbSnapshot, edge: EdgeKey) -> Result<RelRecord> {
+    let external = |iid: InternalNodeId| {
+        snapshot
+            .resolve_external(iid)
+            .ok_or_else(|| Error::Other(format!("node {iid} has no external id")))
+    };
+    Ok(RelRecord {
+        start: external(edge.src)?,
+        end: external(edge.dst)?,
+        rel_type: snapshot
+            .resolve_rel_type_name(edge.rel)
+            .ok_or_else(|| Error::Other(format!("unknown relationship type {}", edge.rel)))?,
+        properties: snapshot.edge_properties(edge).unwrap_or_default(),
+    })
+}
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Applies [`ScrubRules`] to records on their way to the writer.
+struct Scrubber<'a> {
+    rules: &'a ScrubRules,
+    /// Hashed node id to original, to catch two nodes hashing alike.
+    hashed_ids: HashMap<ExternalId, ExternalId>,
+}
+
+impl<'a> Scrubber<'a> {
+    fn new(rules: &'a ScrubRules) -> Self {
+        Self {
+            rules,
+            hashed_ids: HashMap::new(),
+        }
+    }
+
+    fn digest(&self, domain: &[u8], data: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update((self.rules.salt.len() as u64).to_le_bytes());
+        hasher.update(self.rules.salt.as_bytes());
+        hasher.update(domain);
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+
+    /// Small enough to survive a round trip through a JavaScript number.
+    fn id(&self, id: ExternalId) -> ExternalId {
+        if !self.rules.hash_ids {
+            return id;
+        }
+        let digest = self.digest(b"id", &id.to_le_bytes());
+        let bytes = digest[..8].try_into().expect("8 bytes");
+        u64::from_le_bytes(bytes) & ((1 << 53) - 1)
+    }
+
+    fn node(&mut self, mut node: NodeRecord) -> Result<NodeRecord> {
+        let original = node.id;
+        node.id = self.id(original);
+        if self.rules.hash_ids {
+            match self.hashed_ids.entry(node.id) {
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(original);
+                }
+                hash_map::Entry::Occupied(entry) => {
+                    return Err(Error::Other(format!(
+                        "nodes {} and {original} hash to the same id, use another salt",
+                        entry.get()
+                    )));
+                }
+            }
+        }
+        node.properties = self.properties(&node.labels, node.properties);
+        Ok(node)
+    }
+
+    fn relationship(&self, mut rel: RelRecord) -> RelRecord {
+        rel.start = self.id(rel.start);
+        rel.end = self.id(rel.end);
+        rel.properties = self.properties(std::slice::from_ref(&rel.rel_type), rel.properties);
+        rel
+    }
+
+    /// `scopes` are the node's labels or the relationship's type.
+    fn properties(
+        &self,
+        scopes: &[String],
+        properties: BTreeMap<String, PropertyValue>,
+    ) -> BTreeMap<String, PropertyValue> {
+        let targets = |list: &[String], key: &str| {
+            list.iter().any(|target| match target.split_once('.') {
+                Some((scope, name)) => name == key && scopes.iter().any(|s| s == scope),
+                None => target == key,
+            })
+        };
+        properties
+            .into_iter()
+            .filter(|(key, _)| !targets(&self.rules.drop_properties, key))
+            .map(|(key, value)| {
+                let value = if targets(&self.rules.hash_properties, &key) {
+                    self.hash_value(value)
+                } else {
+                    self.generalize(value)
+                };
+                (key, value)
+            })
+            .collect()
+    }
+
+    fn hash_value(&self, value: PropertyValue) -> PropertyValue {
+        let text = property_to_json(value).to_string();
+        let digest = self.digest(b"value", text.as_bytes());
+        PropertyValue::String(digest[..16].iter().map(|b| format!("{b:02x}")).collect())
+    }
+
+    fn generalize(&self, value: PropertyValue) -> PropertyValue {
+        let Some(granularity) = self.rules.generalize_dates else {
+            return value;
+        };
+        match value {
+            PropertyValue::Date(days) => PropertyValue::Date(truncate_days(days, granularity)),
+            PropertyValue::DateTime(micros) => {
+                let days = micros.div_euclid(MICROS_PER_DAY);
+                let truncated = i32::try_from(days)
+                    .map(|days| i64::from(truncate_days(days, granularity)))
+                    .unwrap_or(days);
+                PropertyValue::DateTime(truncated * MICROS_PER_DAY)
+            }
+            PropertyValue::List(items) => {
+                PropertyValue::List(items.into_iter().map(|v| self.generalize(v)).collect())
+            }
+            PropertyValue::Map(map) => PropertyValue::Map(
+                map.into_iter()
+                    .map(|(key, v)| (key, self.generalize(v)))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// Days since the Unix epoch, moved back to the start of the period.
+fn truncate_days(days: i32, granularity: DateGranularity) -> i32 {
+    let Some(date) = unix_epoch_date().checked_add_signed(chrono::Duration::days(days.into()))
+    else {
+        return days;
+    };
+    let start = match granularity {
+        DateGranularity::Year => date.with_ordinal(1),
+        DateGranularity::Month => date.with_day(1),
+        DateGranularity::Day => Some(date),
+    };
+    start
+        .and_then(|start| i32::try_from((start - unix_epoch_date()).num_days()).ok())
+        .unwrap_or(days)
+}
+
+struct Importer<'a> {
+    txn: WriteTxn<'a>,
+    nodes: HashMap<ExternalId, InternalNodeId>,
+    relationships: Vec<RelRecord>,
+}
+
+impl Importer<'_> {
+    fn node(&mut self, node: NodeRecord) -> Result<()> {
+        let mut labels = node.labels.iter();
+        let first = match labels.next() {
+            Some(label) => self.txn.get_or_create_label(label)?,
+            // The label id the query engine gives nodes created without one.
+            None => LabelId::MAX,
+        };
+        let iid = self.txn.create_node(node.id, first)?;
+        for label in labels {
+            let label = self.txn.get_or_create_label(label)?;
+            self.txn.add_node_label(iid, label)?;
+        }
+        for (key, value) in node.properties {
+            self.txn.set_node_property(iid, key, value)?;
+        }
+        self.nodes.insert(node.id, iid);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<TransferStats> {
+        let stats = TransferStats {
+            nodes: self.nodes.len() as u64,
+            relationships: self.relationships.len() as u64,
+        };
+        for rel in std::mem::take(&mut self.relationships) {
+            let node = |id: ExternalId| {
+                self.nodes.get(&id).copied().ok_or_else(|| {
+                    Error::Other(format!(
+                        "relationship references node {id} not in the input"
+                    ))
+                })
+            };
+            let (src, dst) = (node(rel.start)?, node(rel.end)?);
+            let rel_type = self.txn.get_or_create_rel_type(&rel.rel_type)?;
+            self.txn.create_edge(src, rel_type, dst);
+            for (key, value) in rel.properties {
+                self.txn.set_edge_property(src, rel_type, dst, key, value)?;
+            }
+        }
+        self.txn.commit()?;
+        Ok(stats)
+    }
+}
+
+fn write_jsonl(
+    snapshot: &DbSnapshot,
+    scrubber: &mut Scrubber<'_>,
+    out: &mut impl Write,
+) -> Result<TransferStats> {
+    let mut stats = TransferStats::default();
+    for iid in live_nodes(snapshot) {
+        let node = scrubber.node(node_record(snapshot, iid)?)?;
+        let line = json!({
+            "type": "node",
+            "id": node.id,
+            "labels": node.labels,
+            "properties": properties_to_json(node.properties),
+        });
+        writeln!(out, "{line}")?;
+        stats.nodes += 1;
+    }
+    for rel in relationships(snapshot) {
+        let rel = scrubber.relationship(rel?);
+        let line = json!({
+            "type": "relationship",
+            "start": rel.start,
+            "end": rel.end,
+            "rel_type": rel.rel_type,
+            "properties": properties_to_json(rel.properties),
+        });
+        writeln!(out, "{line}")?;
+        stats.relationships += 1;
+    }
+    Ok(stats)
+}
+
+fn read_jsonl(reader: impl BufRead, importer: &mut Importer<'_>) -> Result<()> {
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let at_line = |msg: String| Error::Other(format!("line {}: {msg}", index + 1));
+        let value: JsonValue = serde_json::from_str(&line).map_err(|e| at_line(e.to_string()))?;
+        let id = |key: &str| {
+            value[key]
+                .as_u64()
+                .ok_or_else(|| at_line(format!("'{key}' must be a non-negative integer")))
+        };
+        let properties = match &value["properties"] {
+            JsonValue::Null => BTreeMap::new(),
+            JsonValue::Object(map) => map
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), json_to_property(value).map_err(at_line)?)))
+                .collect::<Result<_>>()?,
+            _ => return Err(at_line("'properties' must be an object".to_string())),
+        };
+        match value["type"].as_str() {
+            Some("node") => {
+                let labels = match &value["labels"] {
+                    JsonValue::Null => Vec::new(),
+                    JsonValue::Array(labels) => labels
+                        .iter()
+                        .map(|label| {
+                            label.as_str().map(str::to_string).ok_or_else(|| {
+                                at_line("'labels' must be an array of strings".to_string())
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                    _ => return Err(at_line("'labels' must be an array of strings".to_string())),
+                };
+                importer.node(NodeRecord {
+                    id: id("id")?,
+                    labels,
+                    properties,
+                })?;
+            }
+            Some("relationship") => {
+                let rel_type = value["rel_type"]
+                    .as_str()
+                    .ok_or_else(|| at_line("'rel_type' must be a string".to_string()))?;
+                importer.relationships.push(RelRecord {
+                    start: id("start")?,
+                    end: id("end")?,
+                    rel_type: rel_type.to_string(),
+                    properties,
+                });
+            }
+            _ => {
+                return Err(at_line(
+                    "'type' must be \"node\" or \"relationship\"".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum KeyKind {
+    Boolean,
+    Long,
+    Double,
+    String,
+    /// Any other value, stored as its JSON envelope.
+    Json,
+}
+
+impl KeyKind {
+    fn of(value: &PropertyValue) -> Option<Self> {
+        Some(match value {
+            PropertyValue::Null => return None,
+            PropertyValue::Bool(_) => KeyKind::Boolean,
+            PropertyValue::Int(_) => KeyKind::Long,
+            PropertyValue::Float(_) => KeyKind::Double,
+            PropertyValue::String(_) => KeyKind::String,
+            _ => KeyKind::Json,
+        })
+    }
+
+    fn attr_type(self) -> &'static str {
+        match self {
+            KeyKind::Boolean => "boolean",
+            KeyKind::Long => "long",
+            KeyKind::Double => "double",
+            KeyKind::String | KeyKind::Json => "string",
+        }
+    }
+
+    fn parse(self, text: &str) -> std::result::Result<PropertyValue, String> {
+        let invalid = || format!("invalid {} value '{text}'", self.attr_type());
+        match self {
+            KeyKind::Boolean => match text.trim() {
+                "true" => Ok(PropertyValue::Bool(true)),
+                "false" => Ok(PropertyValue::Bool(false)),
+                _ => Err(invalid()),
+            },
+            KeyKind::Long => text
+                .trim()
+                .parse()
+                .map(PropertyValue::Int)
+                .map_err(|_| invalid()),
+            KeyKind::Double => text
+                .trim()
+                .parse()
+                .map(PropertyValue::Float)
+                .map_err(|_| invalid()),
+            KeyKind::String => Ok(PropertyValue::String(text.to_string())),
+            KeyKind::Json => serde_json::from_str(text)
+                .map_err(|e| e.to_string())
+                .and_then(|value| json_to_property(&value)),
+        }
+    }
+}
+
+/// GraphML `<key>` declarations for one domain (`node` or `edge`), one per
+/// property name and value kind.
+struct GraphMlKeys {
+    prefix: &'static str,
+    ids: HashMap<(String, KeyKind), String>,
+    order: Vec<(String, String, KeyKind)>,
+}
+
+impl GraphMlKeys {
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            ids: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, properties: &BTreeMap<String, PropertyValue>) {
+        for (name, value) in properties {
+            let Some(kind) = KeyKind::of(value) else {
+                continue;
+            };
+            let next = format!("{}{}", self.prefix, self.order.len());
+            let id = self
+                .ids
+                .entry((name.clone(), kind))
+                .or_insert_with(|| next.clone());
+            if *id == next {
+                self.order.push((next, name.clone(), kind));
+            }
+        }
+    }
+
+    fn write_declarations(&self, domain: &str, out: &mut impl Write) -> io::Result<()> {
+        for (id, name, kind) in &self.order {
+            let json = if *kind == KeyKind::Json {
+                " nervusdb.json=\"true\""
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{}\" attr.type=\"{}\"{json}/>",
+                escape_xml(name),
+                kind.attr_type()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_data(
+        &self,
+        properties: BTreeMap<String, PropertyValue>,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        for (name, value) in properties {
+            let Some(kind) = KeyKind::of(&value) else {
+                continue;
+            };
+            let text = match value {
+                PropertyValue::Bool(b) => b.to_string(),
+                PropertyValue::Int(i) => i.to_string(),
+                PropertyValue::Float(f) => f.to_string(),
+                PropertyValue::String(s) => s,
+                other => property_to_json(other).to_string(),
+            };
+            writeln!(
+                out,
+                "      <data key=\"{}\">{}</data>",
+                self.ids[&(name, kind)],
+                escape_xml(&text)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn write_graphml(
+    snapshot: &DbSnapshot,
+    scrubber: &mut Scrubber<'_>,
+    out: &mut impl Write,
+) -> Result<TransferStats> {
+    // Keys are declared ahead of the graph, so a first pass collects them.
+    let mut node_keys = GraphMlKeys::new("n");
+    let mut edge_keys = GraphMlKeys::new("e");
+    for iid in live_nodes(snapshot) {
+        let node = node_record(snapshot, iid)?;
+        node_keys.add(&scrubber.properties(&node.labels, node.properties));
+    }
+    for rel in relationships(snapshot) {
+        edge_keys.add(&scrubber.relationship(rel?).properties);
+    }
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    writeln!(
+        out,
+        "  <key id=\"labels\" for=\"node\" attr.name=\"labels\" attr.type=\"string\"/>"
+    )?;
+    writeln!(
+        out,
+        "  <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>"
+    )?;
+    node_keys.write_declarations("node", out)?;
+    edge_keys.write_declarations("edge", out)?;
+    writeln!(out, "  <graph id=\"G\" edgedefault=\"directed\">")?;
+
+    let mut stats = TransferStats::default();
+    for iid in live_nodes(snapshot) {
+        let node = scrubber.node(node_record(snapshot, iid)?)?;
+        writeln!(out, "    <node id=\"n{}\">", node.id)?;
+        let labels: String = node
+            .labels
+            .iter()
+            .map(|label| format!(":{label}"))
+            .collect();
+        writeln!(
+            out,
+            "      <data key=\"labels\">{}</data>",
+            escape_xml(&labels)
+        )?;
+        node_keys.write_data(node.properties, out)?;
+        writeln!(out, "    </node>")?;
+        stats.nodes += 1;
+    }
+    for rel in relationships(snapshot) {
+        let rel = scrubber.relationship(rel?);
+        writeln!(
+            out,
+            "    <edge source=\"n{}\" target=\"n{}\">",
+            rel.start, rel.end
+        )?;
+        writeln!(
+            out,
+            "      <data key=\"type\">{}</data>",
+            escape_xml(&rel.rel_type)
+        )?;
+        edge_keys.write_data(rel.properties, out)?;
+        writeln!(out, "    </edge>")?;
+        stats.relationships += 1;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")?;
+    Ok(stats)
+}
+
+enum GraphMlElement {
+    Node(NodeRecord),
+    Edge {
+        start: ExternalId,
+        end: ExternalId,
+        rel_type: Option<String>,
+        properties: BTreeMap<String, PropertyValue>,
+    },
+}
+
+fn read_graphml(reader: impl Read, importer: &mut Importer<'_>) -> Result<()> {
+    let invalid = |msg: String| Error::Other(format!("GraphML: {msg}"));
+    let mut keys: HashMap<String, (String, KeyKind)> = HashMap::new();
+    let mut element: Option<GraphMlElement> = None;
+    // Key id and text of the `<data>` element being read.
+    let mut data: Option<(String, String)> = None;
+
+    for event in EventReader::new(reader) {
+        match event.map_err(|e| invalid(e.to_string()))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let attr = |key: &str| {
+                    attributes
+                        .iter()
+                        .find(|a| a.name.local_name == key && a.name.prefix.is_none())
+                        .map(|a| a.value.as_str())
+                };
+                let required = |key: &str| {
+                    attr(key).ok_or_else(|| {
+                        invalid(format!("<{}> without a {key} attribute", name.local_name))
+                    })
+                };
+                match name.local_name.as_str() {
+                    "key" => {
+                        let kind = match attr("attr.type").unwrap_or("string") {
+                            "boolean" => KeyKind::Boolean,
+                            "int" | "long" => KeyKind::Long,
+                            "float" | "double" => KeyKind::Double,
+                            "string" if attr("nervusdb.json") == Some("true") => KeyKind::Json,
+                            "string" => KeyKind::String,
+                            other => return Err(invalid(format!("unsupported attr.type {other}"))),
+                        };
+                        let id = required("id")?;
+                        let name = attr("attr.name").unwrap_or(id);
+                        keys.insert(id.to_string(), (name.to_string(), kind));
+                    }
+                    "node" => {
+                        element = Some(GraphMlElement::Node(NodeRecord {
+                            id: parse_node_ref(required("id")?).map_err(invalid)?,
+                            labels: Vec::new(),
+                            properties: BTreeMap::new(),
+                        }));
+                    }
+                    "edge" => {
+                        element = Some(GraphMlElement::Edge {
+                            start: parse_node_ref(required("source")?).map_err(invalid)?,
+                            end: parse_node_ref(required("target")?).map_err(invalid)?,
+                            rel_type: None,
+                            properties: BTreeMap::new(),
+                        });
+                    }
+                    "data" if element.is_some() => {
+                        data = Some((required("key")?.to_string(), String::new()));
+                    }
+                    _ => {}
+                }
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) | XmlEvent::Whitespace(text) => {
+                if let Some((_, buf)) = &mut data {
+                    buf.push_str(&text);
+                }
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "data" => {
+                    let Some((key, text)) = data.take() else {
+                        continue;
+                    };
+                    match (&mut element, key.as_str()) {
+                        (Some(GraphMlElement::Node(node)), "labels") => {
+                            node.labels = text
+                                .split(':')
+                                .filter(|label| !label.is_empty())
+                                .map(str::to_string)
+                                .collect();
+                        }
+                        (Some(GraphMlElement::Edge { rel_type, .. }), "type") => {
+                            *rel_type = Some(text);
+                        }
+                        (Some(element), _) => {
+                            let (name, kind) = keys
+                                .get(&key)
+                                .ok_or_else(|| invalid(format!("undeclared key '{key}'")))?;
+                            let value = kind.parse(&text).map_err(invalid)?;
+                            let properties = match element {
+                                GraphMlElement::Node(node) => &mut node.properties,
+                                GraphMlElement::Edge { properties, .. } => properties,
+                            };
+                            properties.insert(name.clone(), value);
+                        }
+                        (None, _) => {}
+                    }
+                }
+                "node" | "edge" => match element.take() {
+                    Some(GraphMlElement::Node(node)) => importer.node(node)?,
+                    Some(GraphMlElement::Edge {
+                        start,
+                        end,
+                        rel_type,
+                        properties,
+                    }) => importer.relationships.push(RelRecord {
+                        start,
+                        end,
+                        rel_type: rel_type.ok_or_else(|| {
+                            invalid(format!("edge n{start} -> n{end} has no type"))
+                        })?,
+                        properties,
+                    }),
+                    None => {}
+                },
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Node ids are the external id, optionally prefixed with `n`.
+fn parse_node_ref(id: &str) -> std::result::Result<ExternalId, String> {
+    id.strip_prefix('n')
+        .unwrap_or(id)
+        .parse()
+        .map_err(|_| format!("node id '{id}' is not n<external id>"))
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\r' => out.push_str("&#13;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn properties_to_json(properties: BTreeMap<String, PropertyValue>) -> JsonValue {
+    JsonValue::Object(
+        properties
+            .into_iter()
+            .map(|(key, value)| (key, property_to_json(value)))
+            .collect(),
+    )
+}
+
+fn unix_epoch_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date")
+}
+
+fn property_to_json(value: PropertyValue) -> JsonValue {
+    match value {
+        PropertyValue::Null => JsonValue::Null,
+        PropertyValue::Bool(b) => json!(b),
+        PropertyValue::Int(i) => json!(i),
+        PropertyValue::Float(f) => json!(f),
+        PropertyValue::String(s) => json!(s),
+        PropertyValue::DateTime(micros) => json!({ "type": "datetime", "value": micros }),
+        PropertyValue::Blob(bytes) => json!({ "type": "blob", "value": BASE64.encode(bytes) }),
+        PropertyValue::List(items) => {
+            JsonValue::Array(items.into_iter().map(property_to_json).collect())
+        }
+        PropertyValue::Map(map) => properties_to_json(map),
+        PropertyValue::Date(days) => {
+            let date = unix_epoch_date()
+                .checked_add_signed(chrono::Duration::days(days.into()))
+                .map_or(json!(days), |date| {
+                    json!(date.format("%Y-%m-%d").to_string())
+                });
+            json!({ "type": "date", "value": date })
+        }
+        PropertyValue::LocalTime(nanos) => {
+            let time = u32::try_from(nanos / 1_000_000_000)
+                .ok()
+                .and_then(|secs| {
+                    NaiveTime::from_num_seconds_from_midnight_opt(
+                        secs,
+                        (nanos % 1_000_000_000) as u32,
+                    )
+                })
+                .map_or(json!(nanos), |time| {
+                    json!(time.format("%H:%M:%S%.f").to_string())
+                });
+            json!({ "type": "localtime", "value": time })
+        }
+        PropertyValue::Duration {
+            months,
+            days,
+            nanos,
+        } => json!({ "type": "duration", "months": months, "days": days, "nanos": nanos }),
+        PropertyValue::IntArray(items) => json!({ "type": "int_array", "value": items }),
+        PropertyValue::FloatArray(items) => json!({ "type": "float_array", "value": items }),
+    }
+}
+
+fn json_to_property(value: &JsonValue) -> std::result::Result<PropertyValue, String> {
+    Ok(match value {
+        JsonValue::Null => PropertyValue::Null,
+        JsonValue::Bool(b) => PropertyValue::Bool(*b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => PropertyValue::Int(i),
+            None => PropertyValue::Float(n.as_f64().ok_or("unsupported numeric value")?),
+        },
+        JsonValue::String(s) => PropertyValue::String(s.clone()),
+        JsonValue::Array(items) => PropertyValue::List(
+            items
+                .iter()
+                .map(json_to_property)
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        JsonValue::Object(map) => match typed_property(map)? {
+            Some(value) => value,
+            None => PropertyValue::Map(
+                map.iter()
+                    .map(|(key, value)| Ok((key.clone(), json_to_property(value)?)))
+                    .collect::<std::result::Result<_, String>>()?,
+            ),
+        },
+    })
+}
+
+/// Reads the envelopes [`property_to_json`] writes; other objects are maps.
+fn typed_property(
+    map: &JsonMap<String, JsonValue>,
+) -> std::result::Result<Option<PropertyValue>, String> {
+    let Some(kind) = map.get("type").and_then(JsonValue::as_str) else {
+        return Ok(None);
+    };
+    let value = match map.get("value") {
+        Some(value) if map.len() == 2 => value,
+        _ if kind == "duration"
+            && map
+                .keys()
+                .all(|k| matches!(k.as_str(), "type" | "months" | "days" | "nanos" | "value")) =>
+        {
+            let part = |key: &str| match map.get(key) {
+                None => Ok(0),
+                Some(v) => v
+                    .as_i64()
+                    .ok_or_else(|| format!("duration {key} must be an integer")),
+            };
+            return Ok(Some(PropertyValue::Duration {
+                months: part("months")?,
+                days: part("days")?,
+                nanos: part("nanos")?,
+            }));
+        }
+        _ => return Ok(None),
+    };
+    let invalid = || format!("invalid {kind} value {value}");
+    Ok(Some(match kind {
+        "datetime" => PropertyValue::DateTime(value.as_i64().ok_or_else(invalid)?),
+        "blob" => PropertyValue::Blob(
+            value
+                .as_str()
+                .and_then(|text| BASE64.decode(text).ok())
+                .ok_or_else(invalid)?,
+        ),
+        "date" => PropertyValue::Date(match value {
+            JsonValue::String(text) => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| i32::try_from((date - unix_epoch_date()).num_days()).ok())
+                .ok_or_else(invalid)?,
+            _ => value
+                .as_i64()
+                .and_then(|days| i32::try_from(days).ok())
+                .ok_or_else(invalid)?,
+        }),
+        "localtime" => PropertyValue::LocalTime(match value {
+            JsonValue::String(text) => NaiveTime::parse_from_str(text, "%H:%M:%S%.f")
+                .map(|time| {
+                    i64::from(time.num_seconds_from_midnight()) * 1_000_000_000
+                        + i64::from(time.nanosecond())
+                })
+                .map_err(|_| invalid())?,
+            _ => value.as_i64().ok_or_else(invalid)?,
+        }),
+        "int_array" => PropertyValue::IntArray(
+            value
+                .as_array()
+                .and_then(|items| items.iter().map(JsonValue::as_i64).collect())
+                .ok_or_else(invalid)?,
+        ),
+        "float_array" => PropertyValue::FloatArray(
+            value
+                .as_array()
+                .and_then(|items| {
+                    items
+                        .iter()
+                        .map(|item| item.as_f64().map(|f| f as f32))
+                        .collect()
+                })
+                .ok_or_else(invalid)?,
+        ),
+        _ => return Ok(None),
+    }))
+}
@@ -45,26 +45,76 @@
 //! | `async` | (Planned) Enable async `Db` and `Txn` wrappers | `false` |
 //! | `serde` | (Implicit) Serde support for property values | `true` |
 
+mod capacity;
+mod edge_chunks;
 mod error;
+mod interchange;
+mod query_log;
+mod replication;
+mod trace;
+mod trigger;
+mod triple_graph;
 
 use nervusdb_storage::api::StorageSnapshot;
+use nervusdb_storage::checkpointer::Checkpointer;
 use nervusdb_storage::engine::GraphEngine;
+use nervusdb_storage::expiry::{ExpiryMetrics, ExpirySweeper};
+use nervusdb_storage::property::check_property_value_size;
 use nervusdb_storage::snapshot::Snapshot;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+pub use capacity::{
+    CAPACITY_HORIZON_DAYS, CapacityReport, EntityUsage, GrowthRate, Projection, capacity_report,
+};
+pub use edge_chunks::{EdgeChunk, EdgeChunks};
 pub use error::{Error, Result};
+pub use interchange::{
+    DateGranularity, GraphFormat, ScrubRules, TransferStats, export, export_scrubbed, import,
+};
 pub use nervusdb_api::{
-    EdgeKey, ExternalId, GraphSnapshot, GraphStore, InternalNodeId, LabelId, PropertyValue,
-    RelTypeId,
+    DegreeHistogram, EdgeKey, ExternalId, FileImport, GraphSnapshot, GraphStore, IndexInfo,
+    IndexKind, InternalNodeId, LabelId, PropertyValue, RelTypeDegrees, RelTypeId, Schema,
 };
 pub use nervusdb_query as query;
 pub use nervusdb_storage::PAGE_SIZE;
+pub use nervusdb_storage::backend::{
+    EncryptedBackend, FileBackend, MemoryBackend, OverlayBackend, PageCipher, RangeSource,
+    ReadOnlyBackend, StorageBackend,
+};
 pub use nervusdb_storage::backup::{
-    BackupHandle, BackupInfo, BackupManager, BackupManifest, BackupStatus,
+    BackupHandle, BackupInfo, BackupManager, BackupManifest, BackupStatus, BackupVerification,
+};
+pub use nervusdb_storage::bulkload::{
+    BulkEdge, BulkLoadStats, BulkLoader, BulkLoaderBuilder, BulkNode, DEFAULT_BULK_RUN_RECORDS,
 };
-pub use nervusdb_storage::bulkload::{BulkEdge, BulkLoader, BulkNode};
+pub use nervusdb_storage::checkpointer::AutoCheckpoint;
+pub use nervusdb_storage::cold_store::{ColdTierStats, StorageTier};
+pub use nervusdb_storage::engine::{EngineOptions, Savepoint};
+pub use nervusdb_storage::expiry::{EXPIRE_AT, ExpiryStats, SweepReport};
+pub use nervusdb_storage::idmap::MAX_INTERNAL_NODE_IDS;
+pub use nervusdb_storage::index::hnsw::HnswParams;
+pub use nervusdb_storage::index::vector::VectorMetric;
+pub use nervusdb_storage::names::{DEFAULT_MAX_NAME_BYTES, NameKind, NameLimits, NameUsage};
+pub use nervusdb_storage::operation::{OperationHandle, Progress};
+pub use nervusdb_storage::packed::PackReport;
+pub use nervusdb_storage::page_cache::PageCacheStats;
+pub use nervusdb_storage::property::MAX_PROPERTY_VALUE_BYTES;
 pub use nervusdb_storage::vacuum::VacuumReport;
+pub use nervusdb_storage::wal_archive::{ArchivedSegment, RetentionPolicy};
+pub use nervusdb_storage::wal_tail::{WalChange, WalTail, WalTxn};
+pub use nervusdb_storage::write_throttle::WriteThrottle;
+pub use query_log::{QueryCapture, ReplayReport, ReplayedQuery, replay_queries, result_digest};
+pub use replication::{
+    DEFAULT_MAX_FOLLOWERS, Follower, FollowerLag, FollowerOptions, MAX_BATCH_TXNS,
+    ReplicationServer, ReplicationState, TcpWalSource, WalBatch, WalSource, apply as apply_wal_txn,
+};
+pub use trace::{TraceId, TraceScope};
+pub use trigger::{TriggerCallback, TriggerEvent, TriggerInfo};
+pub use triple_graph::{TRIPLE_TERM_PROPERTY, TripleGraph};
 
 /// The main database handle for NervusDB v2.
 ///
@@ -82,9 +132,32 @@ pub use nervusdb_storage::vacuum::VacuumReport;
 /// through a single writer lock.
 #[derive(Debug)]
 pub struct Db {
-    engine: GraphEngine,
+    engine: Arc<GraphEngine>,
     ndb_path: PathBuf,
     wal_path: PathBuf,
+    /// Whether snapshots from this handle mask sensitive properties.
+    redact: AtomicBool,
+    /// Whether snapshots from this handle list edges and index hits in sorted
+    /// order.
+    deterministic: AtomicBool,
+    /// Locale whose separators `toString(value, format)` uses for numbers.
+    locale: Mutex<Option<Arc<str>>>,
+    /// Which files `LOAD CSV` may read; see [`Db::set_file_import`].
+    file_import: Mutex<Arc<FileImport>>,
+    /// Background checkpoint thread, when automatic checkpoints are on.
+    checkpointer: Mutex<Option<Checkpointer>>,
+    /// Background thread deleting expired entities, when sweeps are on.
+    expiry_sweeper: Mutex<Option<ExpirySweeper>>,
+    /// Counts of what expiry sweeps through this handle deleted.
+    expiry_metrics: Arc<ExpiryMetrics>,
+    /// Session part of the [`TraceId`]s this handle hands out.
+    trace_session: u32,
+    /// Queries numbered by [`Db::next_trace_id`] so far.
+    next_trace_seq: AtomicU64,
+    /// Triggers run after commits through this handle.
+    triggers: trigger::Triggers,
+    /// Set by [`Follower::start`]: only replication may write graph data.
+    replica: AtomicBool,
 }
 
 impl Db {
@@ -101,6 +174,13 @@ impl Db {
         Self::open_paths(ndb_path, wal_path)
     }
 
+    /// Opens a database like [`Db::open`], applying storage tunables such as
+    /// the WAL segment size.
+    pub fn open_with_options(path: impl AsRef<Path>, options: EngineOptions) -> Result<Self> {
+        let (ndb_path, wal_path) = derive_paths(path.as_ref());
+        Self::open_paths_with_options(ndb_path, wal_path, options)
+    }
+
     /// Opens a database with explicit paths for the data and WAL files.
     ///
     /// # Example
@@ -109,13 +189,106 @@ impl Db {
     /// let db = Db::open_paths("graph.ndb", "graph.wal").unwrap();
     /// ```
     pub fn open_paths(ndb_path: impl AsRef<Path>, wal_path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_paths_with_options(ndb_path, wal_path, EngineOptions::default())
+    }
+
+    /// Opens a database with explicit paths and storage tunables.
+    pub fn open_paths_with_options(
+        ndb_path: impl AsRef<Path>,
+        wal_path: impl AsRef<Path>,
+        options: EngineOptions,
+    ) -> Result<Self> {
+        let backend = if options.read_only {
+            FileBackend::open_read_only(ndb_path.as_ref())?
+        } else {
+            FileBackend::open(ndb_path.as_ref())?
+        };
+        Self::open_paths_with_backend(ndb_path, wal_path, options, Box::new(backend))
+    }
+
+    /// Opens a database whose data pages live in `backend`, such as a
+    /// [`MemoryBackend`] or an [`EncryptedBackend`]. The WAL is still the
+    /// local file derived from `path` like in [`Db::open`].
+    pub fn open_with_backend(
+        path: impl AsRef<Path>,
+        options: EngineOptions,
+        backend: Box<dyn StorageBackend>,
+    ) -> Result<Self> {
+        let (ndb_path, wal_path) = derive_paths(path.as_ref());
+        Self::open_paths_with_backend(ndb_path, wal_path, options, backend)
+    }
+
+    fn open_paths_with_backend(
+        ndb_path: impl AsRef<Path>,
+        wal_path: impl AsRef<Path>,
+        options: EngineOptions,
+        backend: Box<dyn StorageBackend>,
+    ) -> Result<Self> {
         let ndb_path = ndb_path.as_ref().to_path_buf();
         let wal_path = wal_path.as_ref().to_path_buf();
-        let engine = GraphEngine::open(&ndb_path, &wal_path)?;
+        let engine = Arc::new(GraphEngine::open_with_backend(
+            &ndb_path, &wal_path, options, backend,
+        )?);
+        // A read-only handle has nothing to checkpoint.
+        let checkpointer = options
+            .auto_checkpoint
+            .filter(|_| !options.read_only)
+            .map(|policy| Checkpointer::spawn(Arc::downgrade(&engine), policy));
         Ok(Self {
             engine,
             ndb_path,
             wal_path,
+            redact: AtomicBool::new(false),
+            deterministic: AtomicBool::new(false),
+            locale: Mutex::new(None),
+            // Read-only handles often serve untrusted queries.
+            file_import: Mutex::new(Arc::new(if options.read_only {
+                FileImport::Disabled
+            } else {
+                FileImport::Anywhere
+            })),
+            checkpointer: Mutex::new(checkpointer),
+            expiry_sweeper: Mutex::new(None),
+            expiry_metrics: Arc::default(),
+            trace_session: trace::new_session(),
+            next_trace_seq: AtomicU64::new(0),
+            triggers: trigger::Triggers::default(),
+            replica: AtomicBool::new(false),
+        })
+    }
+
+    /// Opens a packed image written by [`Db::pack`], read-only and without a
+    /// WAL. The file is read into memory once; writes, checkpoints and
+    /// schema changes fail with a read-only error.
+    pub fn open_packed(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref())?;
+        Self::open_packed_paths(path.as_ref().to_path_buf(), &bytes)
+    }
+
+    /// Opens a packed image already in memory, e.g. one embedded with
+    /// `include_bytes!`. Like [`Db::open_packed`], but nothing is read from
+    /// disk and [`Db::ndb_path`] is empty.
+    pub fn open_packed_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::open_packed_paths(PathBuf::new(), bytes)
+    }
+
+    fn open_packed_paths(path: PathBuf, bytes: &[u8]) -> Result<Self> {
+        let engine = GraphEngine::open_packed(&path, bytes, EngineOptions::default())?;
+        Ok(Self {
+            engine: Arc::new(engine),
+            ndb_path: path.clone(),
+            wal_path: path,
+            redact: AtomicBool::new(false),
+            deterministic: AtomicBool::new(false),
+            locale: Mutex::new(None),
+            file_import: Mutex::default(),
+            checkpointer: Mutex::new(None),
+            expiry_sweeper: Mutex::new(None),
+            expiry_metrics: Arc::default(),
+            trace_session: trace::new_session(),
+            next_trace_seq: AtomicU64::new(0),
+            triggers: trigger::Triggers::default(),
+            replica: AtomicBool::new(false),
         })
     }
 
@@ -147,7 +320,92 @@ impl Db {
     /// Returns a `DbSnapshot` that implements `GraphSnapshot` trait,
     /// suitable for use with the query engine.
     pub fn snapshot(&self) -> DbSnapshot {
-        DbSnapshot(self.engine.snapshot())
+        self.wrap_snapshot(self.engine.snapshot())
+    }
+
+    /// Creates a snapshot that also sees the uncommitted writes staged in
+    /// `txn`, for reads between statements of a multi-statement transaction.
+    ///
+    /// `txn` must belong to this database. The snapshot copies the staged
+    /// writes; take a new one after writing more.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut txn = db.begin_write();
+    /// prepare("CREATE (:User {name: 'ada'})")?.execute_write(&db.snapshot(), &mut txn, &params)?;
+    /// let staged = db.snapshot_in(&txn); // sees the new :User
+    /// ```
+    pub fn snapshot_in(&self, txn: &WriteTxn<'_>) -> DbSnapshot {
+        self.wrap_snapshot(txn.inner.staged_snapshot())
+    }
+
+    fn wrap_snapshot(&self, inner: StorageSnapshot) -> DbSnapshot {
+        let sensitive = self.engine.sensitive_properties();
+        let redacted = (self.redaction_enabled() && !sensitive.is_empty()).then_some(sensitive);
+        DbSnapshot {
+            inner,
+            redacted,
+            sorted: self.deterministic_enabled(),
+            locale: self.locale(),
+            file_import: Arc::clone(&self.file_import.lock().unwrap_or_else(|e| e.into_inner())),
+        }
+    }
+
+    /// Returns the bookmark of the latest commit visible to new snapshots.
+    ///
+    /// Pass it to [`Db::wait_for_bookmark`] on another handle or thread to
+    /// read its own writes.
+    pub fn last_bookmark(&self) -> Bookmark {
+        Bookmark(self.engine.visible_txid())
+    }
+
+    /// Blocks until the commit identified by `bookmark` is visible to new
+    /// snapshots, or fails once `timeout` elapses.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let bookmark = txn.commit_with_bookmark()?;
+    /// db.wait_for_bookmark(bookmark, Duration::from_secs(1))?;
+    /// let snapshot = db.snapshot(); // sees the commit
+    /// ```
+    pub fn wait_for_bookmark(&self, bookmark: Bookmark, timeout: Duration) -> Result<()> {
+        if self.engine.wait_for_txid(bookmark.0, timeout) {
+            Ok(())
+        } else {
+            Err(Error::Other(format!(
+                "bookmark {} not visible within {timeout:?}",
+                bookmark.0
+            )))
+        }
+    }
+
+    /// Numbers the next query run through this handle. Enter the ID while
+    /// running the query and tag its errors with [`Error::with_trace`].
+    pub fn next_trace_id(&self) -> TraceId {
+        let seq = self.next_trace_seq.fetch_add(1, Ordering::Relaxed) + 1;
+        TraceId::new(self.trace_session, seq)
+    }
+
+    /// Returns the write sequence, which advances with every commit that
+    /// changes graph data.
+    ///
+    /// Read it before taking the snapshot a cached result is computed from,
+    /// then pass it to [`Db::changed_since`] to check the result is current.
+    pub fn write_seq(&self) -> u64 {
+        self.engine.write_seq()
+    }
+
+    /// Whether a commit after write sequence `seq` touched a label or
+    /// relationship type in `read_set`.
+    pub fn changed_since(&self, seq: u64, read_set: &query::ReadSet) -> bool {
+        self.engine
+            .changed_since(seq, read_set.labels.names(), read_set.rel_types.names())
+    }
+
+    /// Returns the bookmark of the commit that carried the idempotency key
+    /// `key`, if one has been committed.
+    pub fn idempotency_key_bookmark(&self, key: &str) -> Option<Bookmark> {
+        self.engine.idempotency_key_txid(key).map(Bookmark)
     }
 
     /// Begins a write transaction.
@@ -161,9 +419,40 @@ impl Db {
     pub fn begin_write(&self) -> WriteTxn<'_> {
         WriteTxn {
             inner: self.engine.begin_write(),
+            db: self,
+            replicating: false,
         }
     }
 
+    /// Begins the write transaction a [`Follower`] applies a primary's
+    /// transaction in; the only kind a replica commits.
+    pub(crate) fn begin_replication_write(&self) -> WriteTxn<'_> {
+        WriteTxn {
+            replicating: true,
+            ..self.begin_write()
+        }
+    }
+
+    /// Whether a [`Follower`] applies a primary's log to this handle. Its
+    /// own write transactions, label renames and property renames then
+    /// fail, as they would break the replica's match with the primary.
+    pub fn is_replica(&self) -> bool {
+        self.replica.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_replica(&self) {
+        self.replica.store(true, Ordering::Release);
+    }
+
+    fn ensure_not_replica(&self) -> Result<()> {
+        if self.is_replica() {
+            return Err(Error::Other(
+                "this database is a replica; write to it only through replication".into(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Triggers a compaction operation.
     ///
     /// Compaction merges frozen MemTables into CSR segments and removes
@@ -173,41 +462,862 @@ impl Db {
         self.engine.compact().map_err(Error::from)
     }
 
+    /// Like [`Db::compact`], publishing progress to `handle` and calling
+    /// `progress` with `(done, total)`.
+    ///
+    /// Cancelling `handle` stops the compaction with [`Error::Cancelled`]
+    /// if it has not started rewriting the property store yet; after that it
+    /// runs to completion.
+    pub fn compact_with_progress(
+        &self,
+        handle: &OperationHandle,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<()> {
+        self.engine
+            .compact_with_progress(handle, &mut progress)
+            .map_err(Error::from)
+    }
+
     /// Creates a durability checkpoint.
     ///
-    /// In MVP, this is equivalent to `compact()`. Future versions may
-    /// implement lightweight checkpoints that don't require full compaction.
+    /// Compacts pending writes, fsyncs the data file, rewrites the WAL as a
+    /// snapshot and deletes sealed WAL segments that the snapshot supersedes.
     pub fn checkpoint(&self) -> Result<()> {
-        // MVP: checkpoint == explicit compaction boundary + durability manifest.
-        self.engine.compact().map_err(Error::from)
+        self.engine.checkpoint().map_err(Error::from)
+    }
+
+    /// Checkpoints, then writes the database to `out_path` as a packed
+    /// image: one LZ4-compressed file holding only the pages in use and the
+    /// metadata the WAL would carry. Open it with [`Db::open_packed`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let report = db.pack("reference.ndbpack")?;
+    /// let shipped = Db::open_packed("reference.ndbpack")?;
+    /// ```
+    pub fn pack(&self, out_path: impl AsRef<Path>) -> Result<PackReport> {
+        self.engine.pack(out_path).map_err(Error::from)
+    }
+
+    /// Whether this handle refuses writes: it serves a packed image, or was
+    /// opened with [`EngineOptions::read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.engine.is_read_only()
     }
 
     /// Explicitly closes the DB and performs a best-effort checkpoint-on-close (T106).
     ///
     /// This is intentionally not implemented in `Drop` to avoid hiding expensive IO.
     pub fn close(self) -> Result<()> {
+        self.checkpointer.lock().unwrap().take();
+        self.expiry_sweeper.lock().unwrap().take();
         self.engine.checkpoint_on_close().map_err(Error::from)?;
         Ok(())
     }
 
+    /// Starts, reconfigures or, with `None`, stops automatic checkpoints on
+    /// a background thread. The thread can also be started on open through
+    /// [`EngineOptions::auto_checkpoint`]. A paused thread stays paused when
+    /// reconfigured.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// db.set_auto_checkpoint(Some(AutoCheckpoint {
+    ///     interval: Some(Duration::from_secs(30)),
+    ///     wal_bytes: 16 << 20,
+    ///     io_bytes_per_sec: 32 << 20,
+    /// }));
+    /// ```
+    pub fn set_auto_checkpoint(&self, policy: Option<AutoCheckpoint>) {
+        let mut slot = self.checkpointer.lock().unwrap();
+        let paused = slot.take().is_some_and(|old| old.is_paused());
+        *slot = policy.map(|policy| {
+            let checkpointer = Checkpointer::spawn(Arc::downgrade(&self.engine), policy);
+            if paused {
+                checkpointer.pause();
+            }
+            checkpointer
+        });
+    }
+
+    /// Holds off automatic checkpoints until [`Db::resume_checkpoints`],
+    /// waiting for one in progress to finish, e.g. while copying the data
+    /// and WAL files. Explicit [`Db::checkpoint`] calls still run. Does
+    /// nothing when automatic checkpoints are off.
+    pub fn pause_checkpoints(&self) {
+        if let Some(checkpointer) = self.checkpointer.lock().unwrap().as_ref() {
+            checkpointer.pause();
+        }
+    }
+
+    /// Lets automatic checkpoints run again after [`Db::pause_checkpoints`].
+    pub fn resume_checkpoints(&self) {
+        if let Some(checkpointer) = self.checkpointer.lock().unwrap().as_ref() {
+            checkpointer.resume();
+        }
+    }
+
+    /// Number of checkpoints the current background thread has completed.
+    pub fn auto_checkpoints(&self) -> u64 {
+        self.checkpointer
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, Checkpointer::checkpoints)
+    }
+
+    /// Starts, reconfigures or, with `None`, stops deleting expired nodes and
+    /// relationships every `interval` on a background thread.
+    ///
+    /// Entities expire once their [`EXPIRE_AT`] property, a `DateTime` or
+    /// milliseconds since the Unix epoch, has passed; see
+    /// [`WriteTxn::set_node_expiry`]. Expired nodes are deleted with their
+    /// relationships, and stay visible to queries until the sweep after
+    /// they expire. Sweeps do not fire triggers.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// db.set_expiry_sweep(Some(Duration::from_secs(60)));
+    /// ```
+    pub fn set_expiry_sweep(&self, interval: Option<Duration>) {
+        let mut slot = self.expiry_sweeper.lock().unwrap();
+        slot.take();
+        *slot = interval.map(|interval| {
+            ExpirySweeper::spawn(
+                Arc::downgrade(&self.engine),
+                interval,
+                Arc::clone(&self.expiry_metrics),
+            )
+        });
+    }
+
+    /// Deletes what has expired now, in one commit, like a background sweep
+    /// of [`Db::set_expiry_sweep`] would.
+    pub fn sweep_expired(&self) -> Result<SweepReport> {
+        let outcome =
+            nervusdb_storage::expiry::sweep(&self.engine, nervusdb_storage::expiry::now_micros());
+        self.expiry_metrics.record(&outcome);
+        outcome.map_err(Error::from)
+    }
+
+    /// Totals over the background and explicit expiry sweeps of this handle.
+    pub fn expiry_stats(&self) -> ExpiryStats {
+        self.expiry_metrics.stats()
+    }
+
+    /// Slows down or, with `None`, stops slowing down writers while
+    /// checkpoints fall behind. Past [`WriteThrottle::delay_wal_bytes`] of
+    /// WAL each [`Db::begin_write`] waits [`WriteThrottle::delay`] first;
+    /// past [`WriteThrottle::reject_wal_bytes`] commits fail with a
+    /// "write throttled" storage error until a checkpoint shrinks the WAL.
+    /// Also settable on open through [`EngineOptions::write_throttle`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// db.set_write_throttle(Some(WriteThrottle {
+    ///     delay_wal_bytes: 256 << 20,
+    ///     delay: Duration::from_millis(10),
+    ///     reject_wal_bytes: 1 << 30,
+    /// }));
+    /// ```
+    pub fn set_write_throttle(&self, throttle: Option<WriteThrottle>) {
+        self.engine.set_write_throttle(throttle);
+    }
+
+    pub fn write_throttle(&self) -> Option<WriteThrottle> {
+        self.engine.write_throttle()
+    }
+
+    /// Reads the stored properties of every node labelled with one of
+    /// `labels` into the page cache, so the first queries after startup do
+    /// not pay for disk reads. Returns how many pages were loaded.
+    ///
+    /// The cache's page set is also saved on [`Db::close`] and reloaded on
+    /// open, so a restarted database starts warm without calling this.
+    pub fn preload_labels(&self, labels: &[&str]) -> Result<usize> {
+        self.engine.preload_labels(labels).map_err(Error::from)
+    }
+
+    /// Moves the properties of nodes labelled `label` to `tier`. The cold
+    /// tier keeps them LZ4-compressed in a file beside the database
+    /// (`<name>.cold`) and reads them without the page cache, keeping the
+    /// hot working set small when most data is archival. Properties move at
+    /// the next compaction or [`Db::checkpoint`]. Returns `false` if the
+    /// label was already in `tier`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// db.set_label_tier("Invoice2019", StorageTier::Cold)?;
+    /// db.checkpoint()?;
+    /// ```
+    pub fn set_label_tier(&self, label: &str, tier: StorageTier) -> Result<bool> {
+        self.engine.set_label_tier(label, tier).map_err(Error::from)
+    }
+
+    pub fn label_tier(&self, label: &str) -> StorageTier {
+        self.engine.label_tier(label)
+    }
+
+    /// Returns the labels assigned to the cold tier.
+    pub fn cold_labels(&self) -> Vec<String> {
+        self.engine.cold_labels().iter().cloned().collect()
+    }
+
+    /// Binds the namespace `prefix` to the base IRI `iri`, or unbinds it
+    /// when `iri` is `None`.
+    ///
+    /// Store external references as compact `prefix:local` strings and
+    /// resolve them with the Cypher `iri()` function: rebinding a prefix
+    /// moves every reference to the new base without rewriting nodes.
+    /// Returns `false` if the binding was already as requested.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// db.set_namespace("foaf", Some("http://xmlns.com/foaf/0.1/"))?;
+    /// // RETURN iri('foaf:knows') -> 'http://xmlns.com/foaf/0.1/knows'
+    /// ```
+    pub fn set_namespace(&self, prefix: &str, iri: Option<&str>) -> Result<bool> {
+        self.engine.set_namespace(prefix, iri).map_err(Error::from)
+    }
+
+    /// Returns the namespace catalog as `(prefix, iri)` pairs, sorted by prefix.
+    pub fn namespaces(&self) -> Vec<(String, String)> {
+        self.engine
+            .namespaces()
+            .iter()
+            .map(|(prefix, iri)| (prefix.clone(), iri.clone()))
+            .collect()
+    }
+
+    /// Returns how many nodes the cold tier holds and the size of its file.
+    pub fn cold_tier_stats(&self) -> ColdTierStats {
+        self.engine.cold_tier_stats()
+    }
+
+    /// Reads `count` pages starting at page `first` into the page cache,
+    /// skipping unallocated ones. Returns how many pages were loaded.
+    pub fn preload_pages(&self, first: u64, count: u64) -> Result<usize> {
+        self.engine.preload_pages(first, count).map_err(Error::from)
+    }
+
+    /// Returns hit and miss counts and the size of the page cache. Its
+    /// capacity is set with [`EngineOptions::page_cache_pages`].
+    pub fn page_cache_stats(&self) -> PageCacheStats {
+        self.engine.page_cache_stats()
+    }
+
+    /// Returns the graph changes committed after `since_txid`, one
+    /// [`WalTxn`] per transaction in commit order, for keeping external
+    /// indexes or caches in step with the database. Start from 0, then pass
+    /// the last txid applied.
+    ///
+    /// Property values are returned as written, without redaction. Fails
+    /// when the changes after `since_txid` were already checkpointed out of
+    /// the log, as happens across a close and reopen unless
+    /// [`Db::set_wal_retention`] or a legal hold kept them; rebuild from a
+    /// snapshot then and resume from the [`Db::last_bookmark`] read before
+    /// taking it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut applied = 0;
+    /// for txn in db.wal_tail(applied)? {
+    ///     for change in &txn.changes {
+    ///         if let WalChange::SetNodeProperty { node, key, value } = change {
+    ///             search_index.update(*node, key, value);
+    ///         }
+    ///     }
+    ///     applied = txn.txid;
+    /// }
+    /// ```
+    pub fn wal_tail(&self, since_txid: u64) -> Result<WalTail> {
+        self.engine.wal_tail(since_txid).map_err(Error::from)
+    }
+
+    /// [`Db::wal_tail`], decoding no more than `max_txns` transactions.
+    pub(crate) fn wal_tail_limited(&self, since_txid: u64, max_txns: usize) -> Result<WalTail> {
+        self.engine
+            .wal_tail_limited(since_txid, max_txns)
+            .map_err(Error::from)
+    }
+
+    /// Keeps checkpointed WAL history for `keep_for` instead of letting the
+    /// next checkpoint delete it, so [`Db::wal_tail`] and audits can reach
+    /// back that far. Checkpoints copy the log they replace into
+    /// `<wal>.archive/` and delete archives once they are older than
+    /// `keep_for`. `None` stops archiving unless a legal hold is placed.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// db.set_wal_retention(Some(Duration::from_secs(30 * 24 * 3600)))?;
+    /// ```
+    pub fn set_wal_retention(&self, keep_for: Option<Duration>) -> Result<()> {
+        self.engine.set_wal_retention(keep_for).map_err(Error::from)
+    }
+
+    /// Places the legal hold `name`: until [`Db::release_legal_hold`],
+    /// checkpoints archive the WAL and no archive is deleted, whatever the
+    /// retention period. Returns `false` if it was already placed.
+    pub fn place_legal_hold(&self, name: &str) -> Result<bool> {
+        self.engine.place_legal_hold(name).map_err(Error::from)
+    }
+
+    /// Releases the legal hold `name`, returning whether it was placed.
+    pub fn release_legal_hold(&self, name: &str) -> Result<bool> {
+        self.engine.release_legal_hold(name).map_err(Error::from)
+    }
+
+    /// Returns the retention period and the placed legal holds.
+    pub fn wal_retention(&self) -> RetentionPolicy {
+        (*self.engine.wal_retention()).clone()
+    }
+
+    /// Returns the WAL archives kept by the retention policy, oldest first.
+    pub fn archived_wal_segments(&self) -> Result<Vec<ArchivedSegment>> {
+        self.engine.archived_wal_segments().map_err(Error::from)
+    }
+
     /// Creates an index on the specified label and property.
     ///
     /// # Example
     /// ```ignore
     /// db.create_index("User", "email")?;
     /// ```
+    ///
+    /// Clears the shared plan cache so later queries are re-planned against
+    /// the new index.
     pub fn create_index(&self, label: &str, property: &str) -> Result<()> {
         self.engine
             .create_index(label, property)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(())
+    }
+
+    /// Creates a trigram text index so `CONTAINS` predicates on the label and
+    /// property only read candidate values instead of scanning every node.
+    ///
+    /// Existing values are indexed immediately.
+    pub fn create_text_index(&self, label: &str, property: &str) -> Result<()> {
+        self.engine
+            .create_text_index(label, property)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(())
+    }
+
+    /// Creates an equality index over several properties of a label, in the
+    /// given order. Existing nodes are indexed immediately.
+    ///
+    /// The index also serves predicates on a leading subset of the
+    /// properties, e.g. `last_name` alone for `["last_name", "first_name"]`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// db.create_composite_index("User", &["last_name", "first_name"])?;
+    /// ```
+    pub fn create_composite_index(&self, label: &str, properties: &[&str]) -> Result<()> {
+        self.engine
+            .create_composite_index(label, properties)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(())
+    }
+
+    /// Drops the equality index on `label` over `properties`: a single
+    /// property for [`Db::create_index`], several for
+    /// [`Db::create_composite_index`].
+    ///
+    /// Returns whether an index existed.
+    pub fn drop_index(&self, label: &str, properties: &[&str]) -> Result<bool> {
+        let dropped = self
+            .engine
+            .drop_index(label, properties)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(dropped)
+    }
+
+    /// Requires `property` to be unique among nodes labelled `label`.
+    ///
+    /// Writes that would give two such nodes the same non-null value, whether
+    /// through [`WriteTxn::set_node_property`], Cypher `CREATE`/`MERGE`/`SET` or a
+    /// label being added, fail with a constraint violation. Creation fails if
+    /// existing nodes already hold duplicates. The constraint is backed by an
+    /// index that also serves equality lookups on the property.
+    ///
+    /// # Example
+    /// ```ignore
+    /// db.create_unique_constraint("User", "email")?;
+    /// ```
+    pub fn create_unique_constraint(&self, label: &str, property: &str) -> Result<()> {
+        self.engine
+            .create_unique_constraint(label, property)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(())
+    }
+
+    /// Drops the unique constraint on `label.property`, returning whether one
+    /// existed.
+    pub fn drop_unique_constraint(&self, label: &str, property: &str) -> Result<bool> {
+        let dropped = self
+            .engine
+            .drop_unique_constraint(label, property)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(dropped)
+    }
+
+    /// Returns every unique constraint as `(label, property)`.
+    pub fn unique_constraints(&self) -> Vec<(String, String)> {
+        self.engine.unique_constraints()
+    }
+
+    /// Restricts relationships of type `rel_type` to the given `(source label,
+    /// target label)` pairs, replacing any earlier restriction on the type.
+    ///
+    /// Committing a relationship of that type whose endpoints carry none of
+    /// the allowed label pairs, whether through [`WriteTxn::create_edge`] or a
+    /// Cypher `CREATE`/`MERGE`, fails with a constraint violation. Creation
+    /// fails if an existing relationship already breaks the restriction.
+    /// Changing a node's labels later does not revisit its relationships.
+    ///
+    /// # Example
+    /// ```ignore
+    /// db.create_endpoint_constraint("KNOWS", &[("Person", "Person")])?;
+    /// ```
+    pub fn create_endpoint_constraint(
+        &self,
+        rel_type: &str,
+        endpoints: &[(&str, &str)],
+    ) -> Result<()> {
+        let endpoints: Vec<(String, String)> = endpoints
+            .iter()
+            .map(|(src, dst)| (src.to_string(), dst.to_string()))
+            .collect();
+        self.engine
+            .create_endpoint_constraint(rel_type, &endpoints)
             .map_err(Error::from)
     }
 
+    /// Lifts the endpoint constraint on `rel_type`, returning whether one
+    /// existed.
+    pub fn drop_endpoint_constraint(&self, rel_type: &str) -> Result<bool> {
+        self.engine
+            .drop_endpoint_constraint(rel_type)
+            .map_err(Error::from)
+    }
+
+    /// Returns every endpoint constraint as `(rel_type, source label, target
+    /// label)`, one entry per allowed pair.
+    pub fn endpoint_constraints(&self) -> Vec<(String, String, String)> {
+        self.engine
+            .endpoint_constraints()
+            .iter()
+            .flat_map(|(rel_type, pairs)| {
+                pairs
+                    .iter()
+                    .map(|(src, dst)| (rel_type.clone(), src.clone(), dst.clone()))
+            })
+            .collect()
+    }
+
+    /// Renames property `old` to `new` on every node labelled `label`, in one
+    /// write transaction. Indexes and unique constraints on the property are
+    /// renamed with it rather than rebuilt. Returns the number of nodes
+    /// renamed.
+    ///
+    /// Fails if one of those nodes already has `new`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// db.rename_property("User", "mail", "email")?;
+    /// ```
+    pub fn rename_property(&self, label: &str, old: &str, new: &str) -> Result<usize> {
+        self.rename_property_with_progress(label, old, new, |_, _| {})
+    }
+
+    /// Like [`Db::rename_property`], calling `progress` with
+    /// `(renamed, total)` as nodes are processed.
+    pub fn rename_property_with_progress(
+        &self,
+        label: &str,
+        old: &str,
+        new: &str,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<usize> {
+        self.ensure_not_replica()?;
+        let renamed = self
+            .engine
+            .rename_property(label, old, new, &mut progress)
+            .map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(renamed)
+    }
+
+    /// Renames label `old` to `new`, returning whether `old` existed.
+    ///
+    /// Nodes keep their label, so this only rewrites the name and the indexes
+    /// defined on it. Relationship types share the label name table, so a
+    /// relationship type called `old` is renamed as well.
+    pub fn rename_label(&self, old: &str, new: &str) -> Result<bool> {
+        self.ensure_not_replica()?;
+        self.rename_label_unguarded(old, new)
+    }
+
+    /// [`Db::rename_label`] for replication, which may rename on a replica.
+    pub(crate) fn rename_label_unguarded(&self, old: &str, new: &str) -> Result<bool> {
+        let renamed = self.engine.rename_label(old, new).map_err(Error::from)?;
+        nervusdb_query::invalidate_plan_cache();
+        Ok(renamed)
+    }
+
+    /// Lists label, relationship type and property key names that use at
+    /// least three quarters of [`NameLimits::max_bytes`], or that the current
+    /// limits would reject because they were created under looser ones.
+    ///
+    /// Property keys are gathered by scanning every node and relationship.
+    pub fn name_report(&self) -> Vec<NameUsage> {
+        let limits = self.engine.name_limits();
+        let snapshot = self.engine.snapshot();
+        let mut labels = BTreeSet::new();
+        let mut rel_types = BTreeSet::new();
+        let mut keys = BTreeSet::new();
+        for node in snapshot.nodes() {
+            if snapshot.is_tombstoned_node(node) {
+                continue;
+            }
+            labels.extend(snapshot.resolve_node_labels(node).unwrap_or_default());
+            keys.extend(
+                snapshot
+                    .node_properties(node)
+                    .unwrap_or_default()
+                    .into_keys(),
+            );
+            for edge in snapshot.neighbors(node, None) {
+                rel_types.insert(edge.rel);
+                keys.extend(
+                    snapshot
+                        .edge_properties(edge)
+                        .unwrap_or_default()
+                        .into_keys(),
+                );
+            }
+        }
+
+        let names = self.engine.label_snapshot();
+        let mut report = Vec::new();
+        for id in names.iter_ids() {
+            let Some(name) = names.get_name(id) else {
+                continue;
+            };
+            // Labels and relationship types share one name table.
+            if rel_types.contains(&id) {
+                report.extend(limits.usage(NameKind::RelType, name));
+            }
+            if labels.contains(&id) || !rel_types.contains(&id) {
+                report.extend(limits.usage(NameKind::Label, name));
+            }
+        }
+        report.extend(
+            keys.iter()
+                .filter_map(|key| limits.usage(NameKind::PropertyKey, key)),
+        );
+        report
+    }
+
+    /// Marks `label.property` as sensitive, returning whether it was not
+    /// already. On a handle with redaction enabled the value of a sensitive
+    /// property reads as [`REDACTED`] on every node carrying `label` (or edge
+    /// of type `label`), in results and in predicates alike.
+    pub fn mark_sensitive(&self, label: &str, property: &str) -> Result<bool> {
+        self.set_sensitive(label, property, true)
+    }
+
+    /// Removes the sensitive marker from `label.property`, returning whether
+    /// it was set.
+    pub fn unmark_sensitive(&self, label: &str, property: &str) -> Result<bool> {
+        self.set_sensitive(label, property, false)
+    }
+
+    fn set_sensitive(&self, label: &str, property: &str, sensitive: bool) -> Result<bool> {
+        let mut txn = self.begin_write();
+        let changed = txn.inner.set_sensitive(label, property, sensitive);
+        txn.commit()?;
+        Ok(changed)
+    }
+
+    /// Returns every sensitive property as `(label, property)`, sorted.
+    pub fn sensitive_properties(&self) -> Vec<(String, String)> {
+        self.engine.sensitive_properties().iter().cloned().collect()
+    }
+
+    /// Turns redaction of sensitive properties on or off for snapshots taken
+    /// from this handle. It is off by default; enable it on handles that serve
+    /// callers without access to the sensitive data.
+    pub fn set_redaction(&self, enabled: bool) {
+        self.redact.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether this handle redacts sensitive properties.
+    pub fn redaction_enabled(&self) -> bool {
+        self.redact.load(Ordering::Relaxed)
+    }
+
+    /// Turns deterministic execution on or off for snapshots taken from this
+    /// handle. Query results then come out in the same order whatever the
+    /// storage layout: edges are expanded sorted by type and endpoint, and
+    /// index lookups return nodes in id order, so snapshot tests of results
+    /// without `ORDER BY` do not flake before and after a compaction. It is
+    /// off by default, since sorting buffers every adjacency list it reads.
+    pub fn set_deterministic(&self, enabled: bool) {
+        self.deterministic.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns whether this handle executes queries deterministically.
+    pub fn deterministic_enabled(&self) -> bool {
+        self.deterministic.load(Ordering::Relaxed)
+    }
+
+    /// Sets the locale, a BCP 47 tag such as `de-DE`, whose group and
+    /// decimal separators `toString(value, format)` uses for numbers in
+    /// queries on snapshots from this handle; `None` restores the English
+    /// `1,234.5`. A third `toString()` argument still overrides it. Fails for
+    /// locales whose separators are unknown.
+    pub fn set_locale(&self, locale: Option<&str>) -> Result<()> {
+        if let Some(tag) = locale
+            && !nervusdb_query::evaluator::is_known_locale(tag)
+        {
+            return Err(Error::Other(format!("unknown locale '{tag}'")));
+        }
+        *self.locale.lock().unwrap_or_else(|e| e.into_inner()) = locale.map(Arc::from);
+        Ok(())
+    }
+
+    /// Returns the locale set with [`Db::set_locale`].
+    pub fn locale(&self) -> Option<Arc<str>> {
+        self.locale
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Sets which local files `LOAD CSV` may read in queries on snapshots
+    /// from this handle. Writable handles start at [`FileImport::Anywhere`]
+    /// and read-only ones at [`FileImport::Disabled`]; handles serving
+    /// untrusted queries should disable imports or confine them with
+    /// [`FileImport::Under`]. Fails if that directory cannot be resolved.
+    pub fn set_file_import(&self, access: FileImport) -> Result<()> {
+        let access = match access {
+            FileImport::Under(root) => {
+                let root = root.canonicalize()?;
+                if !root.is_dir() {
+                    return Err(Error::Other(format!(
+                        "import root {} is not a directory",
+                        root.display()
+                    )));
+                }
+                FileImport::Under(root)
+            }
+            access => access,
+        };
+        *self.file_import.lock().unwrap_or_else(|e| e.into_inner()) = Arc::new(access);
+        Ok(())
+    }
+
+    /// Returns the setting made with [`Db::set_file_import`].
+    pub fn file_import(&self) -> FileImport {
+        FileImport::clone(&self.file_import.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Stores `cypher` in the database catalog under `name`, replacing any
+    /// query already saved with that name.
+    ///
+    /// The query must compile; it is executed later by name with fresh
+    /// parameters, e.g. through `ndb_query_named` in the C ABI. This is the
+    /// same as running `CALL query.save(name, cypher)`.
+    pub fn save_query(&self, name: &str, cypher: &str) -> Result<()> {
+        nervusdb_query::validate_stored_query(name, cypher)
+            .map_err(|e| Error::Query(e.to_string()))?;
+        let mut txn = self.begin_write();
+        txn.inner.save_query(name, cypher);
+        txn.commit()
+    }
+
+    /// Removes the stored query `name`, returning whether it existed.
+    pub fn drop_query(&self, name: &str) -> Result<bool> {
+        let mut txn = self.begin_write();
+        let dropped = txn.inner.drop_query(name);
+        txn.commit()?;
+        Ok(dropped)
+    }
+
+    /// Returns the text of the stored query `name`.
+    pub fn named_query(&self, name: &str) -> Option<String> {
+        self.engine.named_query(name)
+    }
+
+    /// Registers the trigger `name`, replacing any trigger of that name: after
+    /// each commit through this handle with changes matching `event`, the
+    /// write statement `cypher` runs once per change in a new transaction,
+    /// with the parameters listed in the [`TriggerEvent`] docs.
+    ///
+    /// Triggers are not stored in the database, and commits made by them do
+    /// not fire triggers. A failing action does not undo the commit that
+    /// fired it; see [`Db::triggers`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// db.create_trigger(
+    ///     "welcome",
+    ///     "node_created:User".parse()?,
+    ///     "MATCH (u) WHERE id(u) = $id SET u.welcomed = true",
+    /// )?;
+    /// ```
+    pub fn create_trigger(&self, name: &str, event: TriggerEvent, cypher: &str) -> Result<()> {
+        self.triggers.create_cypher(name, event, cypher)
+    }
+
+    /// Like [`Db::create_trigger`], but calls `callback` with each matching
+    /// change instead of running Cypher. An error it returns is counted like
+    /// a failed statement.
+    pub fn create_trigger_callback(
+        &self,
+        name: &str,
+        event: TriggerEvent,
+        callback: impl Fn(&Db, &WalChange) -> Result<()> + Send + Sync + 'static,
+    ) -> Result<()> {
+        self.triggers
+            .create_callback(name, event, Arc::new(callback))
+    }
+
+    /// Removes the trigger `name`, returning whether it existed.
+    pub fn drop_trigger(&self, name: &str) -> bool {
+        self.triggers.drop_trigger(name)
+    }
+
+    /// Returns the registered triggers, sorted by name, with how often each
+    /// ran and failed.
+    pub fn triggers(&self) -> Vec<TriggerInfo> {
+        self.triggers.list()
+    }
+
+    /// Returns the labels, relationship types and property keys the database
+    /// has used, and its indexes.
+    ///
+    /// Names stay listed after the last node or relationship using them is
+    /// deleted. Labels and relationship types are listed by kind even though
+    /// they share one name table.
+    pub fn schema(&self) -> Schema {
+        self.engine.snapshot().schema()
+    }
+
+    /// Returns the out- and in-degree histograms of every relationship type,
+    /// sorted by type name, to spot supernodes and skew.
+    ///
+    /// The histograms are rebuilt at each compaction (and by the bulk
+    /// loader); relationships written since are not counted until the next
+    /// [`Db::checkpoint`].
+    pub fn degree_stats(&self) -> Vec<RelTypeDegrees> {
+        self.engine.snapshot().degree_stats()
+    }
+
+    /// Returns every stored query as `(name, cypher)` pairs, sorted by name.
+    pub fn named_queries(&self) -> Vec<(String, String)> {
+        self.engine
+            .named_queries()
+            .iter()
+            .map(|(name, cypher)| (name.clone(), cypher.clone()))
+            .collect()
+    }
+
     /// Searches for nodes with vectors similar to the query vector.
     ///
-    /// Returns a list of `(node_id, distance)` tuples.
+    /// Returns a list of `(node_id, distance)` tuples, nearest first. Uses the
+    /// HNSW index unless the database was opened with
+    /// [`EngineOptions::exact_vector_search`].
     pub fn search_vector(&self, query: &[f32], k: usize) -> Result<Vec<(InternalNodeId, f32)>> {
         self.engine.search_vector(query, k).map_err(Error::from)
     }
+
+    /// Like [`Db::search_vector`] but compares the query against every stored
+    /// vector, returning the exact nearest neighbours.
+    pub fn search_vector_exact(
+        &self,
+        query: &[f32],
+        k: usize,
+    ) -> Result<Vec<(InternalNodeId, f32)>> {
+        self.engine
+            .search_vector_exact(query, k)
+            .map_err(Error::from)
+    }
+
+    /// Searches for the `k` nodes nearest to `query` under `metric` that
+    /// match `filter`, nearest first.
+    ///
+    /// [`VectorMetric::L2`] uses the HNSW index (scanning when the filter is
+    /// too selective); cosine and dot-product searches scan every vector.
+    /// Filters see the same values as queries on this handle, so a redacted
+    /// property never matches its real value.
+    pub fn search_vector_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        metric: VectorMetric,
+        filter: &VectorFilter,
+    ) -> Result<Vec<(InternalNodeId, f32)>> {
+        let snapshot = self.snapshot();
+        let label = match &filter.label {
+            Some(name) => match snapshot.resolve_label_id(name) {
+                Some(id) => Some(id),
+                None => return Ok(Vec::new()),
+            },
+            None => None,
+        };
+        let mut accept = |iid: InternalNodeId| {
+            if snapshot.is_tombstoned_node(iid) {
+                return false;
+            }
+            if let Some(label) = label
+                && !snapshot
+                    .resolve_node_labels(iid)
+                    .is_some_and(|labels| labels.contains(&label))
+            {
+                return false;
+            }
+            match &filter.property {
+                Some((key, value)) => snapshot.node_property(iid, key).as_ref() == Some(value),
+                None => true,
+            }
+        };
+        self.engine
+            .search_vector_filtered(query, k, metric, &mut accept)
+            .map_err(Error::from)
+    }
+
+    /// Returns the HNSW tunables this handle was opened with.
+    pub fn hnsw_params(&self) -> HnswParams {
+        self.engine.hnsw_params()
+    }
+}
+
+/// Restricts [`Db::search_vector_filtered`] to matching nodes. The default
+/// filter accepts every live node.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorFilter {
+    /// Only nodes carrying this label.
+    pub label: Option<String>,
+    /// Only nodes whose property equals this value.
+    pub property: Option<(String, PropertyValue)>,
 }
 
 /// Performs in-place vacuum through the v2 facade.
@@ -219,6 +1329,24 @@ pub fn vacuum(path: impl AsRef<Path>) -> Result<VacuumReport> {
     nervusdb_storage::vacuum::vacuum_in_place(&ndb_path, &wal_path).map_err(Error::from)
 }
 
+/// Like [`vacuum`], publishing progress in pages copied to `handle` and
+/// calling `progress` with `(done, total)`. Cancelling `handle` stops the
+/// vacuum with [`Error::Cancelled`] and leaves the database file untouched.
+pub fn vacuum_with_progress(
+    path: impl AsRef<Path>,
+    handle: &OperationHandle,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<VacuumReport> {
+    let (ndb_path, wal_path) = derive_paths(path.as_ref());
+    nervusdb_storage::vacuum::vacuum_in_place_with_progress(
+        &ndb_path,
+        &wal_path,
+        handle,
+        &mut progress,
+    )
+    .map_err(Error::from)
+}
+
 /// Creates a consistent on-disk backup snapshot.
 ///
 /// The database path accepts either base path, `.ndb`, or `.wal`.
@@ -227,9 +1355,118 @@ pub fn backup(path: impl AsRef<Path>, backup_dir: impl AsRef<Path>) -> Result<Ba
     let (ndb_path, _) = derive_paths(path.as_ref());
     let manager = BackupManager::new(ndb_path, backup_dir.as_ref().to_path_buf());
     let handle = manager.begin_backup().map_err(Error::from)?;
-    manager.execute_backup(&handle).map_err(Error::from)?;
+    finish_backup(&manager, &handle)
+}
+
+/// Creates an incremental backup holding only the data pages that changed
+/// since `base`, a full or incremental backup in the same `backup_dir`.
+///
+/// Only the pages written since `base`, going by the page LSNs kept beside
+/// the data file, are read; those that differ from the base are written,
+/// along with the WAL minus sealed segments the base already holds. After
+/// a vacuum or restore replaced the data file, every page is read and
+/// compared instead. Restoring it with [`restore`] restores the base chain
+/// first.
+///
+/// # Example
+///
+/// ```ignore
+/// let base = nervusdb::backup("my.ndb", "backups")?;
+/// // ... writes ...
+/// let delta = nervusdb::backup_incremental_from("my.ndb", "backups", &base)?;
+/// ```
+pub fn backup_incremental_from(
+    path: impl AsRef<Path>,
+    backup_dir: impl AsRef<Path>,
+    base: &BackupInfo,
+) -> Result<BackupInfo> {
+    let (ndb_path, _) = derive_paths(path.as_ref());
+    let manager = BackupManager::new(ndb_path, backup_dir.as_ref().to_path_buf());
+    let handle = manager
+        .begin_incremental_backup(base.id)
+        .map_err(Error::from)?;
+    finish_backup(&manager, &handle)
+}
+
+/// Creates an incremental backup against the latest completed backup in
+/// `backup_dir` taken at checkpoint `since_txid`, its
+/// [`BackupInfo::checkpoint_txid`]. Only the data pages and sealed WAL
+/// segments changed since that backup are copied.
+///
+/// The database may be open while this runs; pause automatic checkpoints
+/// around it with [`Db::pause_checkpoints`] so the files hold still.
+///
+/// # Example
+///
+/// ```ignore
+/// let full = nervusdb::backup("my.ndb", "backups")?;
+/// // ... writes, checkpoints ...
+/// let delta = nervusdb::backup_incremental("my.ndb", "backups", full.checkpoint_txid)?;
+/// nervusdb::restore("backups", &delta, "restored.ndb")?;
+/// ```
+pub fn backup_incremental(
+    path: impl AsRef<Path>,
+    backup_dir: impl AsRef<Path>,
+    since_txid: u64,
+) -> Result<BackupInfo> {
+    let backup_dir = backup_dir.as_ref();
+    let base = BackupManager::list_backups(backup_dir)
+        .map_err(Error::from)?
+        .into_iter()
+        .filter(|info| info.checkpoint_txid == since_txid)
+        .max_by_key(|info| info.created_at)
+        .ok_or_else(|| {
+            Error::Other(format!(
+                "no completed backup at checkpoint txid {since_txid} in {}",
+                backup_dir.display()
+            ))
+        })?;
+    backup_incremental_from(path, backup_dir, &base)
+}
+
+/// Restores `backup` from `backup_dir` as a new database at `path`,
+/// applying its chain of base backups first when it is incremental.
+///
+/// Fails if a database already exists at `path`.
+pub fn restore(
+    backup_dir: impl AsRef<Path>,
+    backup: &BackupInfo,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let (ndb_path, wal_path) = derive_paths(path.as_ref());
+    if ndb_path.exists() || wal_path.exists() {
+        return Err(Error::Other(format!(
+            "database already exists at {}",
+            ndb_path.display()
+        )));
+    }
+    BackupManager::restore_from_backup(backup_dir.as_ref(), backup.id, &ndb_path)
+        .map_err(Error::from)
+}
 
-    match manager.status(&handle).map_err(Error::from)? {
+/// Verifies the backup stored at `dir` (`backup_dir/<backup-id>`) against
+/// its manifest: every file it and its base backups store must be present
+/// with the recorded size and checksum. With `test_restore` a backup that
+/// passes is also restored into a temporary location and opened.
+///
+/// Problems with the backup are listed in the returned report rather than
+/// returned as errors.
+///
+/// # Example
+///
+/// ```ignore
+/// let info = nervusdb::backup("my.ndb", "backups")?;
+/// let report = nervusdb::verify_backup(format!("backups/{}", info.id), true)?;
+/// assert!(report.is_ok(), "{:?}", report.problems);
+/// ```
+pub fn verify_backup(dir: impl AsRef<Path>, test_restore: bool) -> Result<BackupVerification> {
+    BackupManager::verify_backup(dir.as_ref(), test_restore).map_err(Error::from)
+}
+
+fn finish_backup(manager: &BackupManager, handle: &BackupHandle) -> Result<BackupInfo> {
+    manager.execute_backup(handle).map_err(Error::from)?;
+
+    match manager.status(handle).map_err(Error::from)? {
         BackupStatus::Completed(info) => Ok(info),
         BackupStatus::Failed { error } => Err(Error::Other(error)),
         BackupStatus::InProgress { .. } => Err(Error::Other(
@@ -240,6 +1477,19 @@ pub fn backup(path: impl AsRef<Path>, backup_dir: impl AsRef<Path>) -> Result<Ba
 
 /// Bulk loads data into a new database file in offline mode.
 pub fn bulkload(path: impl AsRef<Path>, nodes: Vec<BulkNode>, edges: Vec<BulkEdge>) -> Result<()> {
+    bulkload_with_progress(path, nodes, edges, &OperationHandle::new(), |_, _| {})
+}
+
+/// Like [`bulkload`], publishing progress to `handle` and calling `progress`
+/// with `(done, total)`. Cancelling `handle` stops the load with
+/// [`Error::Cancelled`] and removes the partially written database.
+pub fn bulkload_with_progress(
+    path: impl AsRef<Path>,
+    nodes: Vec<BulkNode>,
+    edges: Vec<BulkEdge>,
+    handle: &OperationHandle,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<()> {
     let (ndb_path, _) = derive_paths(path.as_ref());
     let mut loader = BulkLoader::new(ndb_path).map_err(Error::from)?;
     for node in nodes {
@@ -248,17 +1498,105 @@ pub fn bulkload(path: impl AsRef<Path>, nodes: Vec<BulkNode>, edges: Vec<BulkEdg
     for edge in edges {
         loader.add_edge(edge).map_err(Error::from)?;
     }
-    loader.commit().map_err(Error::from)
+    loader
+        .commit_with_progress(handle, &mut progress)
+        .map_err(Error::from)
 }
 
+/// Value that sensitive properties read as on a redacting handle.
+pub const REDACTED: &str = "[REDACTED]";
+
 /// A wrapper around the storage snapshot to hide internal types.
-pub struct DbSnapshot(StorageSnapshot);
+///
+/// Snapshots from a handle with redaction enabled mask sensitive properties
+/// and skip indexes over them, so lookups cannot probe the hidden values.
+/// Snapshots from a deterministic handle sort edges and index hits.
+pub struct DbSnapshot {
+    inner: StorageSnapshot,
+    /// Sensitive `(label, property)` pairs to mask; `None` when nothing is.
+    redacted: Option<Arc<BTreeSet<(String, String)>>>,
+    /// Whether to sort edges and index hits; see [`Db::set_deterministic`].
+    sorted: bool,
+    /// See [`Db::set_locale`].
+    locale: Option<Arc<str>>,
+    /// See [`Db::set_file_import`].
+    file_import: Arc<FileImport>,
+}
+
+impl DbSnapshot {
+    fn is_sensitive(&self, label: &str, property: &str) -> bool {
+        self.redacted
+            .as_ref()
+            .is_some_and(|set| set.contains(&(label.to_string(), property.to_string())))
+    }
+
+    /// Whether `property` is sensitive under any label; a cheap pre-check
+    /// before resolving an element's labels.
+    fn may_hide(&self, property: &str) -> bool {
+        self.redacted
+            .as_ref()
+            .is_some_and(|set| set.iter().any(|(_, p)| p == property))
+    }
+
+    /// Whether `property` is masked for an element with the given label names.
+    fn hides(&self, mut labels: impl Iterator<Item = String>, property: &str) -> bool {
+        labels.any(|label| self.is_sensitive(&label, property))
+    }
+
+    fn node_label_names(&self, iid: InternalNodeId) -> Vec<String> {
+        self.inner
+            .resolve_node_labels(iid)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| self.inner.resolve_label_name(id))
+            .collect()
+    }
+
+    fn redact_node(&self, iid: InternalNodeId, key: &str, value: PropertyValue) -> PropertyValue {
+        if self.may_hide(key) && self.hides(self.node_label_names(iid).into_iter(), key) {
+            PropertyValue::String(REDACTED.to_string())
+        } else {
+            value
+        }
+    }
+
+    fn in_order<'a>(
+        &self,
+        edges: Box<dyn Iterator<Item = EdgeKey> + 'a>,
+    ) -> Box<dyn Iterator<Item = EdgeKey> + 'a> {
+        if !self.sorted {
+            return edges;
+        }
+        let mut edges: Vec<EdgeKey> = edges.collect();
+        edges.sort_unstable();
+        Box::new(edges.into_iter())
+    }
+
+    fn nodes_in_order(&self, nodes: Option<Vec<InternalNodeId>>) -> Option<Vec<InternalNodeId>> {
+        nodes.map(|mut nodes| {
+            if self.sorted {
+                nodes.sort_unstable();
+            }
+            nodes
+        })
+    }
+
+    fn redact_edge(&self, edge: EdgeKey, key: &str, value: PropertyValue) -> PropertyValue {
+        if self.may_hide(key)
+            && self.hides(self.inner.resolve_rel_type_name(edge.rel).into_iter(), key)
+        {
+            PropertyValue::String(REDACTED.to_string())
+        } else {
+            value
+        }
+    }
+}
 
 impl GraphSnapshot for DbSnapshot {
     type Neighbors<'a> = Box<dyn Iterator<Item = EdgeKey> + 'a>;
 
     fn neighbors(&self, src: InternalNodeId, rel: Option<RelTypeId>) -> Self::Neighbors<'_> {
-        self.0.neighbors(src, rel)
+        self.in_order(self.inner.neighbors(src, rel))
     }
 
     fn incoming_neighbors(
@@ -266,59 +1604,87 @@ impl GraphSnapshot for DbSnapshot {
         dst: InternalNodeId,
         rel: Option<RelTypeId>,
     ) -> Self::Neighbors<'_> {
-        self.0.incoming_neighbors(dst, rel)
+        self.in_order(self.inner.incoming_neighbors(dst, rel))
     }
 
+    /// Nodes in id order, deterministic or not.
     fn nodes(&self) -> Box<dyn Iterator<Item = InternalNodeId> + '_> {
-        self.0.nodes()
+        self.inner.nodes()
     }
 
     fn resolve_external(&self, iid: InternalNodeId) -> Option<ExternalId> {
-        self.0.resolve_external(iid)
+        self.inner.resolve_external(iid)
     }
 
     fn node_label(&self, iid: InternalNodeId) -> Option<LabelId> {
-        self.0.node_label(iid)
+        self.inner.node_label(iid)
     }
 
     fn resolve_node_labels(&self, iid: InternalNodeId) -> Option<Vec<LabelId>> {
-        self.0.resolve_node_labels(iid)
+        self.inner.resolve_node_labels(iid)
     }
 
     fn is_tombstoned_node(&self, iid: InternalNodeId) -> bool {
-        self.0.is_tombstoned_node(iid)
+        self.inner.is_tombstoned_node(iid)
     }
 
     fn node_property(&self, iid: InternalNodeId, key: &str) -> Option<PropertyValue> {
-        self.0.node_property(iid, key)
+        let value = self.inner.node_property(iid, key)?;
+        Some(self.redact_node(iid, key, value))
     }
 
     fn edge_property(&self, edge: EdgeKey, key: &str) -> Option<PropertyValue> {
-        self.0.edge_property(edge, key)
+        let value = self.inner.edge_property(edge, key)?;
+        Some(self.redact_edge(edge, key, value))
     }
 
     fn node_properties(&self, iid: InternalNodeId) -> Option<BTreeMap<String, PropertyValue>> {
-        self.0.node_properties(iid)
+        let properties = self.inner.node_properties(iid)?;
+        if !properties.keys().any(|key| self.may_hide(key)) {
+            return Some(properties);
+        }
+        let labels = self.node_label_names(iid);
+        Some(
+            properties
+                .into_iter()
+                .map(|(key, value)| {
+                    if self.may_hide(&key) && self.hides(labels.iter().cloned(), &key) {
+                        (key, PropertyValue::String(REDACTED.to_string()))
+                    } else {
+                        (key, value)
+                    }
+                })
+                .collect(),
+        )
     }
 
     fn edge_properties(&self, edge: EdgeKey) -> Option<BTreeMap<String, PropertyValue>> {
-        self.0.edge_properties(edge)
+        let properties = self.inner.edge_properties(edge)?;
+        Some(
+            properties
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = self.redact_edge(edge, &key, value);
+                    (key, value)
+                })
+                .collect(),
+        )
     }
 
     fn resolve_label_id(&self, name: &str) -> Option<LabelId> {
-        self.0.resolve_label_id(name)
+        self.inner.resolve_label_id(name)
     }
 
     fn resolve_rel_type_id(&self, name: &str) -> Option<RelTypeId> {
-        self.0.resolve_rel_type_id(name)
+        self.inner.resolve_rel_type_id(name)
     }
 
     fn resolve_label_name(&self, id: LabelId) -> Option<String> {
-        self.0.resolve_label_name(id)
+        self.inner.resolve_label_name(id)
     }
 
     fn resolve_rel_type_name(&self, id: RelTypeId) -> Option<String> {
-        self.0.resolve_rel_type_name(id)
+        self.inner.resolve_rel_type_name(id)
     }
 
     fn lookup_index(
@@ -327,15 +1693,81 @@ impl GraphSnapshot for DbSnapshot {
         field: &str,
         value: &PropertyValue,
     ) -> Option<Vec<InternalNodeId>> {
-        self.0.lookup_index(label, field, value)
+        if self.is_sensitive(label, field) {
+            return None;
+        }
+        self.nodes_in_order(self.inner.lookup_index(label, field, value))
+    }
+
+    fn has_index(&self, label: &str, field: &str) -> bool {
+        !self.is_sensitive(label, field) && self.inner.has_index(label, field)
+    }
+
+    fn lookup_composite_index(
+        &self,
+        label: &str,
+        predicates: &[(String, PropertyValue)],
+    ) -> Option<Vec<InternalNodeId>> {
+        if predicates
+            .iter()
+            .any(|(field, _)| self.is_sensitive(label, field))
+        {
+            return None;
+        }
+        self.nodes_in_order(self.inner.lookup_composite_index(label, predicates))
+    }
+
+    fn has_composite_index(&self, label: &str, fields: &[String]) -> bool {
+        !fields.iter().any(|field| self.is_sensitive(label, field))
+            && self.inner.has_composite_index(label, fields)
+    }
+
+    fn lookup_text_index(
+        &self,
+        label: &str,
+        field: &str,
+        needle: &str,
+    ) -> Option<Vec<InternalNodeId>> {
+        if self.is_sensitive(label, field) {
+            return None;
+        }
+        self.nodes_in_order(self.inner.lookup_text_index(label, field, needle))
+    }
+
+    fn search_vector(&self, query: &[f32], k: usize) -> Option<Vec<(InternalNodeId, f32)>> {
+        self.inner.search_vector(query, k)
+    }
+
+    fn named_queries(&self) -> Vec<(String, String)> {
+        self.inner.named_queries()
+    }
+
+    fn namespaces(&self) -> Vec<(String, String)> {
+        self.inner.namespaces()
+    }
+
+    fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    fn file_import(&self) -> FileImport {
+        FileImport::clone(&self.file_import)
+    }
+
+    fn degree_stats(&self) -> Vec<RelTypeDegrees> {
+        self.inner.degree_stats()
+    }
+
+    fn schema(&self) -> Schema {
+        self.inner.schema()
     }
 
     fn node_count(&self, label: Option<LabelId>) -> u64 {
-        self.0.node_count(label)
+        self.inner.node_count(label)
     }
 
     fn edge_count(&self, rel: Option<RelTypeId>) -> u64 {
-        self.0.edge_count(rel)
+        self.inner.edge_count(rel)
     }
 }
 
@@ -365,12 +1797,35 @@ impl ReadTxn {
     }
 }
 
+/// Identifies a committed write transaction (its txid).
+///
+/// Bookmarks are ordered: a snapshot that sees one commit sees every commit
+/// with a smaller bookmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bookmark(u64);
+
+impl Bookmark {
+    /// The committed transaction id.
+    pub fn txid(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for Bookmark {
+    fn from(txid: u64) -> Self {
+        Bookmark(txid)
+    }
+}
+
 /// A write transaction.
 ///
 /// Created by [`Db::begin_write()`]. All modifications are buffered
 /// until `commit()` is called. The transaction consumes `self` on commit.
 pub struct WriteTxn<'a> {
     inner: nervusdb_storage::engine::WriteTxn<'a>,
+    db: &'a Db,
+    /// Begun by [`Db::begin_replication_write`].
+    replicating: bool,
 }
 
 impl<'a> WriteTxn<'a> {
@@ -387,6 +1842,19 @@ impl<'a> WriteTxn<'a> {
             .map_err(Error::from)
     }
 
+    /// Looks up a node by external ID, including nodes created earlier in
+    /// this transaction.
+    pub fn lookup_node(&self, external_id: ExternalId) -> Option<InternalNodeId> {
+        self.inner.lookup_internal_id(external_id)
+    }
+
+    /// Adds a label to a node, in addition to the one it was created with.
+    pub fn add_node_label(&mut self, node: InternalNodeId, label_id: LabelId) -> Result<()> {
+        self.inner
+            .add_node_label(node, label_id)
+            .map_err(Error::from)
+    }
+
     /// Gets or creates a label ID for the given name.
     pub fn get_or_create_label(&mut self, name: &str) -> Result<LabelId> {
         self.inner.get_or_create_label(name).map_err(Error::from)
@@ -421,21 +1889,26 @@ impl<'a> WriteTxn<'a> {
 
     /// Sets a property on a node.
     ///
-    /// If the property already exists, it is overwritten.
+    /// If the property already exists, it is overwritten. Values whose encoding exceeds
+    /// [`MAX_PROPERTY_VALUE_BYTES`] are rejected, as are values that would break a
+    /// unique constraint (see [`Db::create_unique_constraint`]).
     pub fn set_node_property(
         &mut self,
         node: InternalNodeId,
         key: String,
         value: PropertyValue,
     ) -> Result<()> {
+        check_property_value_size(&value)?;
         let storage_value = convert_to_storage_property_value(value);
-        self.inner.set_node_property(node, key, storage_value);
-        Ok(())
+        self.inner
+            .set_node_property(node, key, storage_value)
+            .map_err(Error::from)
     }
 
     /// Sets a property on an edge.
     ///
-    /// If the property already exists, it is overwritten.
+    /// If the property already exists, it is overwritten. Values whose encoding exceeds
+    /// [`MAX_PROPERTY_VALUE_BYTES`] are rejected.
     pub fn set_edge_property(
         &mut self,
         src: InternalNodeId,
@@ -444,18 +1917,38 @@ impl<'a> WriteTxn<'a> {
         key: String,
         value: PropertyValue,
     ) -> Result<()> {
+        check_property_value_size(&value)?;
         let storage_value = convert_to_storage_property_value(value);
         self.inner
-            .set_edge_property(src, rel, dst, key, storage_value);
-        Ok(())
+            .set_edge_property(src, rel, dst, key, storage_value)
+            .map_err(Error::from)
+    }
+
+    /// Makes a node expire at `at`, by setting its [`EXPIRE_AT`] property.
+    /// An expiry sweep after that time deletes it with its relationships;
+    /// see [`Db::set_expiry_sweep`].
+    pub fn set_node_expiry(&mut self, node: InternalNodeId, at: SystemTime) -> Result<()> {
+        self.set_node_property(node, EXPIRE_AT.to_string(), expiry_value(at))
+    }
+
+    /// Makes an edge expire at `at`, by setting its [`EXPIRE_AT`] property.
+    pub fn set_edge_expiry(
+        &mut self,
+        src: InternalNodeId,
+        rel: RelTypeId,
+        dst: InternalNodeId,
+        at: SystemTime,
+    ) -> Result<()> {
+        self.set_edge_property(src, rel, dst, EXPIRE_AT.to_string(), expiry_value(at))
     }
 
     /// Removes a property from a node.
     ///
     /// If the property doesn't exist, this is a no-op.
     pub fn remove_node_property(&mut self, node: InternalNodeId, key: &str) -> Result<()> {
-        self.inner.remove_node_property(node, key);
-        Ok(())
+        self.inner
+            .remove_node_property(node, key)
+            .map_err(Error::from)
     }
 
     /// Removes a property from an edge.
@@ -468,13 +1961,15 @@ impl<'a> WriteTxn<'a> {
         dst: InternalNodeId,
         key: &str,
     ) -> Result<()> {
-        self.inner.remove_edge_property(src, rel, dst, key);
-        Ok(())
+        self.inner
+            .remove_edge_property(src, rel, dst, key)
+            .map_err(Error::from)
     }
 
     /// Sets the vector embedding for a node.
     ///
-    /// This vector can be used for similarity search.
+    /// This vector can be used for similarity search once the transaction
+    /// commits.
     pub fn set_vector(&mut self, node: InternalNodeId, vector: Vec<f32>) -> Result<()> {
         self.inner.set_vector(node, vector).map_err(Error::from)
     }
@@ -487,8 +1982,112 @@ impl<'a> WriteTxn<'a> {
     /// # Returns
     ///
     /// Returns `Ok(())` on success, or an error if commit fails.
+    ///
+    /// Triggers registered with [`Db::create_trigger`] run before this
+    /// returns.
     pub fn commit(self) -> Result<()> {
-        self.inner.commit().map_err(Error::from)
+        self.commit_with_bookmark().map(|_| ())
+    }
+
+    /// Commits like [`WriteTxn::commit`] and returns the commit's bookmark.
+    ///
+    /// When the transaction's idempotency key was already committed, this is
+    /// the bookmark of that earlier commit.
+    pub fn commit_with_bookmark(self) -> Result<Bookmark> {
+        let db = self.db;
+        if !self.replicating {
+            db.ensure_not_replica()?;
+        }
+        if db.triggers.is_empty() {
+            return self
+                .inner
+                .commit_with_txid()
+                .map(Bookmark)
+                .map_err(Error::from);
+        }
+        let committed = self.inner.commit_with_changes()?;
+        db.triggers.fire(db, &committed.changes);
+        Ok(Bookmark(committed.txid))
+    }
+
+    /// Tags the transaction with a client-generated idempotency key.
+    ///
+    /// The key is stored durably with the commit. If a transaction with the
+    /// same key has already committed, its bookmark is returned and committing
+    /// this transaction applies nothing, so a request retried after an
+    /// ambiguous failure (e.g. a timeout during commit) is applied at most once.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut txn = db.begin_write();
+    /// if txn.set_idempotency_key("order-42")?.is_none() {
+    ///     // stage writes
+    /// }
+    /// txn.commit()?;
+    /// ```
+    pub fn set_idempotency_key(&mut self, key: &str) -> Result<Option<Bookmark>> {
+        if key.is_empty() {
+            return Err(Error::Other(
+                "idempotency key must not be empty".to_string(),
+            ));
+        }
+        Ok(self.inner.set_idempotency_key(key).map(Bookmark))
+    }
+
+    /// Defers unique constraint checks to commit for the writes that
+    /// follow, as SQL's `SET CONSTRAINTS ALL DEFERRED` does. Passing `false`
+    /// checks the writes deferred so far, failing if one breaks a
+    /// constraint, and returns to checking each write as it is staged.
+    ///
+    /// Lets a multi-step change, such as swapping two users' emails, pass
+    /// through states that break a constraint as long as the committed state
+    /// does not. Endpoint constraints are always checked at commit.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut txn = db.begin_write();
+    /// txn.set_constraints_deferred(true)?;
+    /// txn.set_node_property(alice, "email".into(), PropertyValue::String("b@x".into()))?;
+    /// txn.set_node_property(bob, "email".into(), PropertyValue::String("a@x".into()))?;
+    /// txn.commit()?;
+    /// ```
+    pub fn set_constraints_deferred(&mut self, deferred: bool) -> Result<()> {
+        self.inner
+            .set_constraints_deferred(deferred)
+            .map_err(Error::from)
+    }
+
+    /// Captures the changes staged so far, to return to with
+    /// [`WriteTxn::rollback_to`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// let before = txn.savepoint();
+    /// txn.set_node_property(node, "score".into(), PropertyValue::Int(0))?;
+    /// txn.rollback_to(&before)?; // `score` is left as it was
+    /// txn.commit()?;
+    /// ```
+    pub fn savepoint(&self) -> Savepoint {
+        self.inner.savepoint()
+    }
+
+    /// Discards the changes staged after `savepoint` was taken, including
+    /// created nodes and edges, property and label changes, and deletions.
+    /// The transaction stays open and may take further savepoints.
+    pub fn rollback_to(&mut self, savepoint: &Savepoint) -> Result<()> {
+        self.inner.rollback_to(savepoint).map_err(Error::from)
+    }
+
+    /// Rolls back the transaction, discarding all staged modifications and
+    /// releasing the writer lock. Labels interned while it was open stay
+    /// registered, since they are logged eagerly.
+    ///
+    /// Dropping an uncommitted `WriteTxn` has the same effect; this method
+    /// makes the intent explicit.
+    pub fn rollback(self) {
+        // Nothing reaches the WAL or the published state before commit, so
+        // dropping the staged changes is the whole rollback.
+        drop(self.inner);
     }
 }
 
@@ -498,6 +2097,15 @@ fn convert_to_storage_property_value(
     v
 }
 
+/// `at` as a `DateTime` value, in microseconds since the Unix epoch.
+fn expiry_value(at: SystemTime) -> PropertyValue {
+    let micros = |d: Duration| i64::try_from(d.as_micros()).unwrap_or(i64::MAX);
+    PropertyValue::DateTime(match at.duration_since(UNIX_EPOCH) {
+        Ok(after) => micros(after),
+        Err(before) => -micros(before.duration()),
+    })
+}
+
 fn derive_paths(path: &Path) -> (PathBuf, PathBuf) {
     match path.extension().and_then(|e| e.to_str()) {
         Some("ndb") => (path.to_path_buf(), path.with_extension("wal")),
@@ -624,8 +2232,11 @@ impl nervusdb_query::WriteableGraph for WriteTxn<'_> {
         value: nervusdb_storage::property::PropertyValue,
     ) -> nervusdb_query::Result<()> {
         // Query Engine uses storage PropertyValue directly now (from re-export)
-        self.inner.set_node_property(node, key, value);
-        Ok(())
+        check_property_value_size(&value)
+            .map_err(|e| nervusdb_query::Error::Other(e.to_string()))?;
+        self.inner
+            .set_node_property(node, key, value)
+            .map_err(|e| nervusdb_query::Error::Other(e.to_string()))
     }
 
     fn set_edge_property(
@@ -636,8 +2247,11 @@ impl nervusdb_query::WriteableGraph for WriteTxn<'_> {
         key: String,
         value: nervusdb_storage::property::PropertyValue,
     ) -> nervusdb_query::Result<()> {
-        self.inner.set_edge_property(src, rel, dst, key, value);
-        Ok(())
+        check_property_value_size(&value)
+            .map_err(|e| nervusdb_query::Error::Other(e.to_string()))?;
+        self.inner
+            .set_edge_property(src, rel, dst, key, value)
+            .map_err(|e| nervusdb_query::Error::Other(e.to_string()))
     }
 
     fn remove_node_property(
@@ -645,8 +2259,9 @@ impl nervusdb_query::WriteableGraph for WriteTxn<'_> {
         node: InternalNodeId,
         key: &str,
     ) -> nervusdb_query::Result<()> {
-        self.inner.remove_node_property(node, key);
-        Ok(())
+        self.inner
+            .remove_node_property(node, key)
+            .map_err(|e| nervusdb_query::Error::Other(e.to_string()))
     }
 
     fn remove_edge_property(
@@ -656,8 +2271,9 @@ impl nervusdb_query::WriteableGraph for WriteTxn<'_> {
         dst: InternalNodeId,
         key: &str,
     ) -> nervusdb_query::Result<()> {
-        self.inner.remove_edge_property(src, rel, dst, key);
-        Ok(())
+        self.inner
+            .remove_edge_property(src, rel, dst, key)
+            .map_err(|e| nervusdb_query::Error::Other(e.to_string()))
     }
 
     fn tombstone_node(&mut self, node: InternalNodeId) -> nervusdb_query::Result<()> {
@@ -690,4 +2306,13 @@ impl nervusdb_query::WriteableGraph for WriteTxn<'_> {
     fn staged_created_nodes_with_labels(&self) -> Vec<(InternalNodeId, Vec<String>)> {
         self.inner.staged_created_nodes_with_labels()
     }
+
+    fn save_named_query(&mut self, name: &str, cypher: &str) -> nervusdb_query::Result<()> {
+        self.inner.save_query(name, cypher);
+        Ok(())
+    }
+
+    fn drop_named_query(&mut self, name: &str) -> nervusdb_query::Result<bool> {
+        Ok(self.inner.drop_query(name))
+    }
 }
@@ -0,0 +1,352 @@
+//! Capturing executed queries and replaying them against another database.
+//!
+//! A [`QueryCapture`] appends one JSON line per query: the text, parameter
+//! values (unless left out) and shapes, the row count, the latency, a digest
+//! of the rows and the query's [`TraceId`] when one is current. Given a
+//! minimum duration it keeps only slow queries. [`replay_queries`] runs such a log against a
+//! database, typically a restored copy opened by a newer engine, and
+//! reports where results or latency differ.
+//!
+//! ```text
+//! {"cypher":"MATCH (n:User {name: $name}) RETURN n.age","write":false,"params":{"name":"ada"},"param_shapes":{"name":"string"},"rows":1,"micros":84,"digest":"5c0f...","trace":"4f1a9c-12"}
+//! ```
+
+use crate::query::{Params, Row, Value, prepare_cached};
+use crate::{Db, Error, Result, TraceId};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Appends executed queries to a log file for [`replay_queries`].
+///
+/// Recording takes a lock per line, so one capture can be shared by every
+/// thread running queries.
+#[derive(Debug)]
+pub struct QueryCapture {
+    file: Mutex<File>,
+    include_params: bool,
+    min_duration: Duration,
+}
+
+impl QueryCapture {
+    /// Opens `path` for appending, creating it if needed. Without
+    /// `include_params` only parameter shapes are kept, so the log can leave
+    /// the machine, but queries taking parameters cannot be replayed.
+    pub fn create(path: impl AsRef<Path>, include_params: bool) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())?;
+        Ok(Self {
+            file: Mutex::new(file),
+            include_params,
+            min_duration: Duration::ZERO,
+        })
+    }
+
+    /// Records only queries that take at least `threshold`, making the
+    /// capture a slow-query log.
+    pub fn with_min_duration(mut self, threshold: Duration) -> Self {
+        self.min_duration = threshold;
+        self
+    }
+
+    /// Records a read query and the reified rows it returned.
+    pub fn record_read(
+        &self,
+        cypher: &str,
+        params: &Params,
+        rows: &[Row],
+        elapsed: Duration,
+    ) -> Result<()> {
+        self.record(
+            cypher,
+            false,
+            params,
+            rows.len() as u64,
+            result_digest(rows),
+            elapsed,
+        )
+    }
+
+    /// Records a write query and the number of entities it changed.
+    pub fn record_write(
+        &self,
+        cypher: &str,
+        params: &Params,
+        changed: u32,
+        elapsed: Duration,
+    ) -> Result<()> {
+        self.record(cypher, true, params, changed.into(), String::new(), elapsed)
+    }
+
+    fn record(
+        &self,
+        cypher: &str,
+        write: bool,
+        params: &Params,
+        rows: u64,
+        digest: String,
+        elapsed: Duration,
+    ) -> Result<()> {
+        if elapsed < self.min_duration {
+            return Ok(());
+        }
+        let mut entry = json!({
+            "cypher": cypher,
+            "write": write,
+            "param_shapes": params
+                .iter()
+                .map(|(name, value)| (name.clone(), JsonValue::String(value_shape(value))))
+                .collect::<JsonMap<_, _>>(),
+            "rows": rows,
+            "micros": u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+            "digest": digest,
+        });
+        if let Some(trace) = TraceId::current() {
+            entry["trace"] = trace.to_string().into();
+        }
+        if self.include_params {
+            entry["params"] = params
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(value)))
+                .collect::<JsonMap<_, _>>()
+                .into();
+        }
+        let line = format!("{entry}\n");
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| Error::Other("query capture lock poisoned".to_string()))?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Hex digest of `rows`, independent of their order so that queries
+/// without `ORDER BY` compare equal across engine versions. Nodes and
+/// relationships include their internal ids, so compare databases restored
+/// from the same backup.
+pub fn result_digest(rows: &[Row]) -> String {
+    let mut lines: Vec<String> = rows
+        .iter()
+        .map(|row| format!("{:?}", row.columns()))
+        .collect();
+    lines.sort_unstable();
+    let mut hasher = Sha256::new();
+    for line in &lines {
+        hasher.update((line.len() as u64).to_le_bytes());
+        hasher.update(line.as_bytes());
+    }
+    let digest: [u8; 32] = hasher.finalize().into();
+    digest[..16].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// One log entry as [`replay_queries`] ran it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ReplayedQuery {
+    /// 1-based line in the log.
+    pub line: usize,
+    pub cypher: String,
+    pub captured_rows: u64,
+    pub replayed_rows: u64,
+    /// Same row count and, for reads, same result digest.
+    pub results_match: bool,
+    /// Why the replay failed, if it did.
+    pub error: Option<String>,
+    pub captured_micros: u64,
+    pub replayed_micros: u64,
+}
+
+/// What [`replay_queries`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ReplayReport {
+    pub queries: Vec<ReplayedQuery>,
+    /// Entries captured without the parameter values they need.
+    pub skipped: u64,
+}
+
+impl ReplayReport {
+    /// Queries whose results differ or that failed.
+    pub fn mismatches(&self) -> impl Iterator<Item = &ReplayedQuery> {
+        self.queries.iter().filter(|query| !query.results_match)
+    }
+
+    /// Total latency at capture and at replay.
+    pub fn total_micros(&self) -> (u64, u64) {
+        self.queries.iter().fold((0, 0), |(captured, replayed), q| {
+            (captured + q.captured_micros, replayed + q.replayed_micros)
+        })
+    }
+}
+
+/// Runs every query in the log at `log_path` against `db`, in order, and
+/// compares row counts, result digests and latency with the capture.
+/// Writes are applied and committed, so replay into a copy.
+pub fn replay_queries(db: &Db, log_path: impl AsRef<Path>) -> Result<ReplayReport> {
+    let reader = BufReader::new(File::open(log_path.as_ref())?);
+    let mut report = ReplayReport::default();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let at_line = |msg: &str| Error::Other(format!("query log line {}: {msg}", index + 1));
+        let entry: JsonValue = serde_json::from_str(&line).map_err(|e| at_line(&e.to_string()))?;
+        let cypher = entry["cypher"]
+            .as_str()
+            .ok_or_else(|| at_line("'cypher' must be a string"))?;
+        let params = match (&entry["params"], &entry["param_shapes"]) {
+            (JsonValue::Object(values), _) => {
+                let mut params = Params::new();
+                for (name, value) in values {
+                    params.insert(name.clone(), json_to_value(value));
+                }
+                params
+            }
+            (JsonValue::Null, JsonValue::Object(shapes)) if !shapes.is_empty() => {
+                report.skipped += 1;
+                continue;
+            }
+            _ => Params::new(),
+        };
+        let captured_rows = entry["rows"].as_u64().unwrap_or(0);
+        let captured_digest = entry["digest"].as_str().unwrap_or_default();
+
+        let started = Instant::now();
+        let outcome = run(db, cypher, &params);
+        let replayed_micros = u64::try_from(started.elapsed().as_micros()).unwrap_or(u64::MAX);
+        let (replayed_rows, results_match, error) = match outcome {
+            Ok((rows, digest)) => (
+                rows,
+                rows == captured_rows && digest == captured_digest,
+                None,
+            ),
+            Err(e) => (0, false, Some(e.to_string())),
+        };
+        report.queries.push(ReplayedQuery {
+            line: index + 1,
+            cypher: cypher.to_string(),
+            captured_rows,
+            replayed_rows,
+            results_match,
+            error,
+            captured_micros: entry["micros"].as_u64().unwrap_or(0),
+            replayed_micros,
+        });
+    }
+    Ok(report)
+}
+
+/// Runs one query the way the C API does, returning the row count (changed
+/// entities for writes) and the result digest (empty for writes).
+fn run(db: &Db, cypher: &str, params: &Params) -> Result<(u64, String)> {
+    let prepared = prepare_cached(cypher)?;
+    if !prepared.is_write() {
+        let snapshot = db.snapshot();
+        let rows = prepared
+            .execute_streaming(&snapshot, params)
+            .map(|row| row.and_then(|row| row.reify(&snapshot)))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        return Ok((rows.len() as u64, result_digest(&rows)));
+    }
+    let mut commit_error = None;
+    let changed = prepared
+        .execute_periodic(params, |params| {
+            let snapshot = db.snapshot();
+            let mut txn = db.begin_write();
+            let (_rows, changed) = prepared.execute_mixed(&snapshot, &mut txn, params)?;
+            txn.commit().map_err(|e| {
+                let message = e.to_string();
+                commit_error = Some(e);
+                crate::query::Error::Other(message)
+            })?;
+            Ok(changed)
+        })
+        .map_err(|e| match commit_error.take() {
+            Some(e) => e,
+            None => e.into(),
+        })?;
+    Ok((changed.into(), String::new()))
+}
+
+/// Type name of a parameter, e.g. `int` or `list<string>`.
+fn value_shape(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Int(_) => "int".to_string(),
+        Value::Float(_) => "float".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::DateTime(_) => "datetime".to_string(),
+        Value::Blob(_) => "blob".to_string(),
+        Value::Map(_) => "map".to_string(),
+        Value::List(items) => {
+            let mut shapes: Vec<String> = items.iter().map(value_shape).collect();
+            shapes.dedup();
+            match shapes.as_slice() {
+                [] => "list".to_string(),
+                [shape] => format!("list<{shape}>"),
+                _ => "list<any>".to_string(),
+            }
+        }
+        _ => "any".to_string(),
+    }
+}
+
+/// Parameter values in the JSON envelopes of the C API; graph entities,
+/// which parameters cannot carry, become null.
+fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::Bool(b) => json!(b),
+        Value::Int(i) => json!(i),
+        Value::Float(f) => json!(f),
+        Value::String(s) => json!(s),
+        Value::DateTime(micros) => json!({ "type": "datetime", "value": micros }),
+        Value::Blob(bytes) => json!({ "type": "blob", "value": BASE64.encode(bytes) }),
+        Value::List(items) => JsonValue::Array(items.iter().map(value_to_json).collect()),
+        Value::Map(map) => JsonValue::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), value_to_json(value)))
+                .collect(),
+        ),
+        _ => JsonValue::Null,
+    }
+}
+
+fn json_to_value(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(b) => Value::Bool(*b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        JsonValue::String(s) => Value::String(s.clone()),
+        JsonValue::Array(items) => Value::List(items.iter().map(json_to_value).collect()),
+        JsonValue::Object(map) => {
+            let typed = match (map.len(), map.get("type").and_then(JsonValue::as_str)) {
+                (2, Some("datetime")) => map["value"].as_i64().map(Value::DateTime),
+                (2, Some("blob")) => map["value"]
+                    .as_str()
+                    .and_then(|text| BASE64.decode(text).ok())
+                    .map(Value::Blob),
+                _ => None,
+            };
+            typed.unwrap_or_else(|| {
+                Value::Map(
+                    map.iter()
+                        .map(|(key, value)| (key.clone(), json_to_value(value)))
+                        .collect::<BTreeMap<_, _>>(),
+                )
+            })
+        }
+    }
+}
@@ -0,0 +1,894 @@
+//! Read replicas kept current by shipping committed WAL transactions.
+//!
+//! A primary serves its log through a [`WalSource`]: a [`ReplicationServer`]
+//! over TCP, an `Arc<Db>` in the same process, or any transport that moves
+//! [`WalBatch::encode`]d bytes. A [`Follower`] polls the source from a
+//! background thread and applies each transaction to its own database, in
+//! commit order, through ordinary write transactions.
+//!
+//! The follower records the last primary txid it applied in
+//! `<follower ndb>.replica`, next to its database, after every commit. A
+//! restarted follower resumes from there; without the file it starts at its
+//! own [`Db::last_bookmark`], which matches the primary's when the follower
+//! was restored from a backup of it. Applying is at-least-once: a batch
+//! replayed after a crash leaves the follower unchanged.
+//!
+//! Graph data (nodes, labels, edges, properties and renames) is replicated,
+//! including the system properties a primary opened with
+//! [`EngineOptions::system_properties`](crate::EngineOptions) stamps.
+//! Indexes, constraints, vectors and saved queries are not: create them on
+//! each follower. [`Follower::start`] marks the follower's handle as a
+//! replica, whose own write transactions and renames fail: a direct write
+//! would leave its node ids out of step with the primary's.
+
+use crate::{
+    Bookmark, Db, Error, GraphSnapshot, InternalNodeId, LabelId, PropertyValue, Result, WalChange,
+    WalTxn,
+};
+use nervusdb_storage::names::is_system_property;
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Most transactions a primary sends in one [`WalBatch`].
+pub const MAX_BATCH_TXNS: usize = 1024;
+/// Most followers a [`ReplicationServer`] serves at once, unless changed
+/// with [`ReplicationServer::with_max_followers`].
+pub const DEFAULT_MAX_FOLLOWERS: usize = 64;
+
+const BATCH_FORMAT: u8 = 1;
+const PROTOCOL_VERSION: u8 = 1;
+const MAX_FRAME_BYTES: usize = 256 * 1024 * 1024;
+
+/// Transactions committed on a primary after a follower's position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalBatch {
+    /// Committed transactions with graph changes, oldest first.
+    pub txns: Vec<WalTxn>,
+    /// The position a follower reaches once it has applied `txns`. Can be
+    /// past the last transaction's txid, because checkpoints and index
+    /// builds use txids without changing graph data.
+    pub up_to_txid: u64,
+    /// The primary's last committed txid when the batch was read.
+    pub primary_txid: u64,
+}
+
+impl WalBatch {
+    /// Reads the transactions `db` committed after `since_txid`, waiting up
+    /// to `wait` for one when there are none yet. Transports call this on
+    /// the primary to answer a follower's poll.
+    pub fn read(db: &Db, since_txid: u64, wait: Duration) -> Result<Self> {
+        if db.last_bookmark().txid() <= since_txid && !wait.is_zero() {
+            // A timeout just means an empty batch.
+            let _ = db.wait_for_bookmark(Bookmark::from(since_txid + 1), wait);
+        }
+        let primary_txid = db.last_bookmark().txid().max(since_txid);
+        let txns: Vec<WalTxn> = db.wal_tail_limited(since_txid, MAX_BATCH_TXNS)?.collect();
+        // A full batch may have more behind it; the next poll picks up there.
+        let up_to_txid = match txns.last() {
+            Some(last) if txns.len() == MAX_BATCH_TXNS => last.txid,
+            Some(last) => last.txid.max(primary_txid),
+            None => primary_txid,
+        };
+        Ok(Self {
+            txns,
+            up_to_txid,
+            primary_txid: primary_txid.max(up_to_txid),
+        })
+    }
+
+    /// Encodes the batch for a transport; [`WalBatch::decode`] reverses it.
+    /// Fails on changes this format does not know.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut out = vec![BATCH_FORMAT];
+        put_u64(&mut out, self.up_to_txid);
+        put_u64(&mut out, self.primary_txid);
+        put_u32(&mut out, self.txns.len() as u32);
+        for txn in &self.txns {
+            put_u64(&mut out, txn.txid);
+            put_u32(&mut out, txn.changes.len() as u32);
+            for change in &txn.changes {
+                encode_change(&mut out, change)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Decodes bytes written by [`WalBatch::encode`].
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut r = Reader { bytes, pos: 0 };
+        let format = r.u8()?;
+        if format != BATCH_FORMAT {
+            return Err(Error::Compatibility(format!(
+                "unsupported replication batch format {format}"
+            )));
+        }
+        let up_to_txid = r.u64()?;
+        let primary_txid = r.u64()?;
+        let txn_count = r.u32()?;
+        let mut txns = Vec::new();
+        for _ in 0..txn_count {
+            let txid = r.u64()?;
+            let change_count = r.u32()?;
+            let mut changes = Vec::new();
+            for _ in 0..change_count {
+                changes.push(decode_change(&mut r)?);
+            }
+            txns.push(WalTxn { txid, changes });
+        }
+        if r.pos != bytes.len() {
+            return Err(malformed());
+        }
+        Ok(Self {
+            txns,
+            up_to_txid,
+            primary_txid,
+        })
+    }
+}
+
+/// Where a [`Follower`] reads the primary's log from.
+pub trait WalSource: Send {
+    /// Returns the transactions committed after `since_txid`, waiting up to
+    /// `wait` for one when there are none yet.
+    fn poll(&mut self, since_txid: u64, wait: Duration) -> Result<WalBatch>;
+}
+
+/// Follows a primary opened in the same process.
+impl WalSource for Arc<Db> {
+    fn poll(&mut self, since_txid: u64, wait: Duration) -> Result<WalBatch> {
+        WalBatch::read(self, since_txid, wait)
+    }
+}
+
+/// Serves a primary's log to [`TcpWalSource`] followers.
+///
+/// Each follower gets a thread; its polls double as acknowledgements, so
+/// [`ReplicationServer::followers`] shows how far behind each one is.
+/// Connections past [`ReplicationServer::with_max_followers`] get an error
+/// reply and are closed; their followers retry later.
+#[derive(Debug)]
+pub struct ReplicationServer {
+    db: Arc<Db>,
+    listener: TcpListener,
+    followers: Arc<Mutex<HashMap<u64, FollowerLag>>>,
+    next_id: AtomicU64,
+    max_followers: usize,
+    connected: Arc<AtomicUsize>,
+}
+
+/// A connected follower as seen by a [`ReplicationServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowerLag {
+    pub peer: SocketAddr,
+    /// The txid the follower last reported as applied.
+    pub applied_txid: u64,
+    /// Txids the primary had committed past `applied_txid` at that poll.
+    pub lag: u64,
+}
+
+impl ReplicationServer {
+    /// Binds `addr` to serve `db`'s log. Call [`ReplicationServer::serve`]
+    /// to start accepting followers.
+    pub fn bind(db: Arc<Db>, addr: impl ToSocketAddrs) -> Result<Self> {
+        Ok(Self {
+            db,
+            listener: TcpListener::bind(addr)?,
+            followers: Arc::default(),
+            next_id: AtomicU64::new(0),
+            max_followers: DEFAULT_MAX_FOLLOWERS,
+            connected: Arc::default(),
+        })
+    }
+
+    /// Serves at most `max` followers at once.
+    pub fn with_max_followers(mut self, max: usize) -> Self {
+        self.max_followers = max;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// The connected followers, in no particular order.
+    pub fn followers(&self) -> Vec<FollowerLag> {
+        self.followers.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Accepts followers until the listener fails.
+    pub fn serve(&self) -> Result<()> {
+        loop {
+            let (stream, peer) = self.listener.accept()?;
+            if self.connected.fetch_add(1, Ordering::AcqRel) >= self.max_followers {
+                self.connected.fetch_sub(1, Ordering::AcqRel);
+                let error =
+                    Error::Other(format!("already serving {} followers", self.max_followers));
+                // The follower retries after its delay.
+                let _ = write_frame(&mut BufWriter::new(stream), &error_payload(&error));
+                continue;
+            }
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            let db = Arc::clone(&self.db);
+            let followers = Arc::clone(&self.followers);
+            let connected = Arc::clone(&self.connected);
+            let spawned = std::thread::Builder::new()
+                .name(format!("nervusdb-replication-{peer}"))
+                .spawn(move || {
+                    // The follower reconnects after any failure.
+                    let _ = serve_follower(&db, stream, peer, id, &followers);
+                    followers.lock().unwrap().remove(&id);
+                    connected.fetch_sub(1, Ordering::AcqRel);
+                });
+            if let Err(e) = spawned {
+                self.connected.fetch_sub(1, Ordering::AcqRel);
+                return Err(e.into());
+            }
+        }
+    }
+}
+
+fn serve_follower(
+    db: &Db,
+    stream: TcpStream,
+    peer: SocketAddr,
+    id: u64,
+    followers: &Mutex<HashMap<u64, FollowerLag>>,
+) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    loop {
+        let mut request = [0u8; 13];
+        match reader.read_exact(&mut request) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            other => other?,
+        }
+        let since_txid = u64::from_le_bytes(request[1..9].try_into().unwrap());
+        let wait =
+            Duration::from_millis(u32::from_le_bytes(request[9..].try_into().unwrap()).into());
+        let response = if request[0] != PROTOCOL_VERSION {
+            Err(Error::Compatibility(format!(
+                "unsupported replication protocol version {}",
+                request[0]
+            )))
+        } else {
+            followers.lock().unwrap().insert(
+                id,
+                FollowerLag {
+                    peer,
+                    applied_txid: since_txid,
+                    lag: db.last_bookmark().txid().saturating_sub(since_txid),
+                },
+            );
+            WalBatch::read(db, since_txid, wait)
+        };
+        let payload = match response.and_then(|batch| batch.encode()) {
+            Ok(encoded) => {
+                let mut payload = vec![0];
+                payload.extend(encoded);
+                payload
+            }
+            Err(e) => error_payload(&e),
+        };
+        write_frame(&mut writer, &payload)?;
+    }
+}
+
+fn error_payload(error: &Error) -> Vec<u8> {
+    let mut payload = vec![1];
+    payload.extend(error.to_string().into_bytes());
+    payload
+}
+
+fn write_frame(writer: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Follows a primary through its [`ReplicationServer`], reconnecting on the
+/// next poll after a failure.
+#[derive(Debug)]
+pub struct TcpWalSource {
+    addr: SocketAddr,
+    stream: Option<TcpStream>,
+}
+
+impl TcpWalSource {
+    pub fn new(addr: impl ToSocketAddrs) -> Result<Self> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::Other("replication address resolved to nothing".into()))?;
+        Ok(Self { addr, stream: None })
+    }
+
+    fn exchange(&mut self, since_txid: u64, wait: Duration) -> io::Result<Vec<u8>> {
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => {
+                let stream = TcpStream::connect(self.addr)?;
+                stream.set_nodelay(true)?;
+                self.stream.insert(stream)
+            }
+        };
+        let wait_ms = u32::try_from(wait.as_millis()).unwrap_or(u32::MAX);
+        // Leave the primary its whole wait before giving up on it.
+        stream.set_read_timeout(Some(wait + Duration::from_secs(30)))?;
+        let mut request = vec![PROTOCOL_VERSION];
+        put_u64(&mut request, since_txid);
+        put_u32(&mut request, wait_ms);
+        stream.write_all(&request)?;
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len == 0 || len > MAX_FRAME_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "malformed replication frame",
+            ));
+        }
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload)?;
+        Ok(payload)
+    }
+}
+
+impl WalSource for TcpWalSource {
+    fn poll(&mut self, since_txid: u64, wait: Duration) -> Result<WalBatch> {
+        let payload = match self.exchange(since_txid, wait) {
+            Ok(payload) => payload,
+            Err(e) => {
+                self.stream = None;
+                return Err(e.into());
+            }
+        };
+        match payload[0] {
+            0 => WalBatch::decode(&payload[1..]),
+            _ => Err(Error::Other(format!(
+                "primary: {}",
+                String::from_utf8_lossy(&payload[1..])
+            ))),
+        }
+    }
+}
+
+/// How a [`Follower`] polls its source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FollowerOptions {
+    /// How long one poll waits for new commits on the primary. Also bounds
+    /// how long [`Follower::stop`] takes.
+    pub poll_wait: Duration,
+    /// How long to wait before polling again after a failure.
+    pub retry_delay: Duration,
+}
+
+impl Default for FollowerOptions {
+    fn default() -> Self {
+        Self {
+            poll_wait: Duration::from_secs(1),
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A [`Follower`]'s progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationState {
+    /// The last primary txid applied here.
+    pub applied_txid: u64,
+    /// The primary's last committed txid as of the latest poll.
+    pub primary_txid: u64,
+    /// When the source last answered a poll.
+    pub last_contact: Option<SystemTime>,
+    /// Why the latest poll or apply failed; cleared by the next success.
+    pub last_error: Option<String>,
+    /// Transactions applied since the follower started.
+    pub applied_txns: u64,
+}
+
+impl ReplicationState {
+    /// Txids the follower is behind the primary, as of the latest poll.
+    pub fn lag(&self) -> u64 {
+        self.primary_txid.saturating_sub(self.applied_txid)
+    }
+}
+
+/// Applies a primary's log to a local database from a background thread.
+///
+/// # Example
+///
+/// ```no_run
+/// use nervusdb::{Db, Follower, FollowerOptions, TcpWalSource};
+/// use std::sync::Arc;
+///
+/// # fn main() -> nervusdb::Result<()> {
+/// let replica = Arc::new(Db::open("replica.ndb")?);
+/// let source = TcpWalSource::new("primary.internal:7688")?;
+/// let follower = Follower::start(Arc::clone(&replica), source, FollowerOptions::default())?;
+/// println!("lag: {}", follower.state().lag());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Follower {
+    state: Arc<Mutex<ReplicationState>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Follower {
+    /// Starts following `source` from the position recorded next to `db`,
+    /// or from `db`'s last bookmark when there is none. From here on `db`
+    /// is a replica (see [`Db::is_replica`]), also after the follower
+    /// stops; reopen the database to write to it directly.
+    pub fn start(
+        db: Arc<Db>,
+        source: impl WalSource + 'static,
+        options: FollowerOptions,
+    ) -> Result<Self> {
+        let position_path = position_path(db.ndb_path());
+        let applied_txid = match std::fs::read_to_string(&position_path) {
+            Ok(text) => text.trim().parse().map_err(|_| {
+                Error::Other(format!(
+                    "malformed replication position in {}",
+                    position_path.display()
+                ))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => db.last_bookmark().txid(),
+            Err(e) => return Err(e.into()),
+        };
+        db.set_replica();
+        let state = Arc::new(Mutex::new(ReplicationState {
+            applied_txid,
+            primary_txid: applied_txid,
+            last_contact: None,
+            last_error: None,
+            applied_txns: 0,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::Builder::new()
+            .name("nervusdb-follower".into())
+            .spawn({
+                let state = Arc::clone(&state);
+                let stop = Arc::clone(&stop);
+                move || follow(&db, source, options, &position_path, &state, &stop)
+            })?;
+        Ok(Self {
+            state,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn state(&self) -> ReplicationState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Blocks until the follower has applied the primary's `txid`, or fails
+    /// once `timeout` elapses.
+    pub fn wait_for_txid(&self, txid: u64, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.state.lock().unwrap().applied_txid >= txid {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Other(format!(
+                    "primary txid {txid} not applied within {timeout:?}"
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Stops following once the current poll returns.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Follower {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn position_path(ndb_path: &Path) -> PathBuf {
+    let mut path = ndb_path.as_os_str().to_owned();
+    path.push(".replica");
+    PathBuf::from(path)
+}
+
+fn follow(
+    db: &Db,
+    mut source: impl WalSource,
+    options: FollowerOptions,
+    position_path: &Path,
+    state: &Mutex<ReplicationState>,
+    stop: &AtomicBool,
+) {
+    let mut applied_txid = state.lock().unwrap().applied_txid;
+    while !stop.load(Ordering::Acquire) {
+        let result = source
+            .poll(applied_txid, options.poll_wait)
+            .and_then(|batch| {
+                state.lock().unwrap().last_contact = Some(SystemTime::now());
+                let since = applied_txid;
+                for txn in batch.txns.iter().filter(|txn| txn.txid > since) {
+                    apply(db, txn)?;
+                    applied_txid = txn.txid;
+                    save_position(position_path, applied_txid)?;
+                    let mut state = state.lock().unwrap();
+                    state.applied_txid = applied_txid;
+                    state.applied_txns += 1;
+                }
+                if batch.up_to_txid > applied_txid {
+                    applied_txid = batch.up_to_txid;
+                    save_position(position_path, applied_txid)?;
+                }
+                let mut state = state.lock().unwrap();
+                state.applied_txid = applied_txid;
+                state.primary_txid = batch.primary_txid;
+                state.last_error = None;
+                Ok(())
+            });
+        if let Err(e) = result {
+            state.lock().unwrap().last_error = Some(e.to_string());
+            let deadline = Instant::now() + options.retry_delay;
+            while !stop.load(Ordering::Acquire) && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+}
+
+fn save_position(path: &Path, txid: u64) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, txid.to_string())?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Applies one of a primary's transactions to `db` in a write transaction
+/// of its own. Applying a transaction again leaves `db` unchanged.
+///
+/// Fails, applying nothing, when `db`'s nodes no longer match the
+/// primary's: the follower was written to, or seeded from another database.
+pub fn apply(db: &Db, txn: &WalTxn) -> Result<()> {
+    let before = db.snapshot();
+    let mut write = db.begin_replication_write();
+    let mut renames = Vec::new();
+    for change in &txn.changes {
+        match change {
+            WalChange::CreateNode {
+                node,
+                external_id,
+                label,
+            } => {
+                let created = match write.lookup_node(*external_id) {
+                    Some(existing) => existing,
+                    None => {
+                        let label = match label {
+                            Some(name) => write.get_or_create_label(name)?,
+                            None => LabelId::MAX,
+                        };
+                        write.create_node(*external_id, label)?
+                    }
+                };
+                if created != *node {
+                    return Err(diverged(txn.txid, *node, created));
+                }
+            }
+            WalChange::DeleteNode { node } => write.tombstone_node(*node),
+            WalChange::AddLabel { node, label } => {
+                let label = write.get_or_create_label(label)?;
+                write.add_node_label(*node, label)?;
+            }
+            WalChange::RemoveLabel { node, label } => {
+                let label = write.get_or_create_label(label)?;
+                write.inner.remove_node_label(*node, label)?;
+            }
+            WalChange::CreateEdge { src, rel_type, dst } => {
+                let rel = write.get_or_create_rel_type(rel_type)?;
+                // Edges are keyed by their endpoints and type, so one that
+                // already exists was made by this transaction's replay.
+                if !before
+                    .neighbors(*src, Some(rel))
+                    .any(|edge| edge.dst == *dst)
+                {
+                    write.create_edge(*src, rel, *dst);
+                }
+            }
+            WalChange::DeleteEdge { src, rel_type, dst } => {
+                let rel = write.get_or_create_rel_type(rel_type)?;
+                write.tombstone_edge(*src, rel, *dst);
+            }
+            // System properties were stamped by the primary's commit.
+            WalChange::SetNodeProperty { node, key, value } if is_system_property(key) => {
+                write
+                    .inner
+                    .set_system_node_property(*node, key.clone(), value.clone())?;
+            }
+            WalChange::SetNodeProperty { node, key, value } => {
+                write.set_node_property(*node, key.clone(), value.clone())?;
+            }
+            WalChange::RemoveNodeProperty { node, key } => {
+                write.remove_node_property(*node, key)?;
+            }
+            WalChange::SetEdgeProperty {
+                src,
+                rel_type,
+                dst,
+                key,
+                value,
+            } => {
+                let rel = write.get_or_create_rel_type(rel_type)?;
+                if is_system_property(key) {
+                    write.inner.set_system_edge_property(
+                        *src,
+                        rel,
+                        *dst,
+                        key.clone(),
+                        value.clone(),
+                    )?;
+                } else {
+                    write.set_edge_property(*src, rel, *dst, key.clone(), value.clone())?;
+                }
+            }
+            WalChange::RemoveEdgeProperty {
+                src,
+                rel_type,
+                dst,
+                key,
+            } => {
+                let rel = write.get_or_create_rel_type(rel_type)?;
+                write.remove_edge_property(*src, rel, *dst, key)?;
+            }
+            WalChange::RenameLabel { from, to } => renames.push((from, to)),
+            other => return Err(unsupported(other)),
+        }
+    }
+    write.commit()?;
+    // Renames take their own transaction; replaying one finds `from` gone.
+    for (from, to) in renames {
+        db.rename_label_unguarded(from, to)?;
+    }
+    Ok(())
+}
+
+/// A change this version cannot replicate; following past it would leave
+/// the follower silently behind.
+fn unsupported(change: &WalChange) -> Error {
+    Error::Compatibility(format!("cannot replicate WAL change {change:?}"))
+}
+
+fn diverged(txid: u64, expected: InternalNodeId, found: InternalNodeId) -> Error {
+    Error::Other(format!(
+        "follower has diverged from the primary at txid {txid}: \
+         node {expected} was created here as {found}"
+    ))
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn put_value(out: &mut Vec<u8>, value: &PropertyValue) {
+    let bytes = value.encode();
+    put_u32(out, bytes.len() as u32);
+    out.extend_from_slice(&bytes);
+}
+
+fn encode_change(out: &mut Vec<u8>, change: &WalChange) -> Result<()> {
+    match change {
+        WalChange::CreateNode {
+            node,
+            external_id,
+            label,
+        } => {
+            out.push(0);
+            put_u32(out, *node);
+            put_u64(out, *external_id);
+            match label {
+                Some(label) => {
+                    out.push(1);
+                    put_str(out, label);
+                }
+                None => out.push(0),
+            }
+        }
+        WalChange::DeleteNode { node } => {
+            out.push(1);
+            put_u32(out, *node);
+        }
+        WalChange::AddLabel { node, label } => {
+            out.push(2);
+            put_u32(out, *node);
+            put_str(out, label);
+        }
+        WalChange::RemoveLabel { node, label } => {
+            out.push(3);
+            put_u32(out, *node);
+            put_str(out, label);
+        }
+        WalChange::CreateEdge { src, rel_type, dst } => {
+            out.push(4);
+            put_u32(out, *src);
+            put_str(out, rel_type);
+            put_u32(out, *dst);
+        }
+        WalChange::DeleteEdge { src, rel_type, dst } => {
+            out.push(5);
+            put_u32(out, *src);
+            put_str(out, rel_type);
+            put_u32(out, *dst);
+        }
+        WalChange::SetNodeProperty { node, key, value } => {
+            out.push(6);
+            put_u32(out, *node);
+            put_str(out, key);
+            put_value(out, value);
+        }
+        WalChange::RemoveNodeProperty { node, key } => {
+            out.push(7);
+            put_u32(out, *node);
+            put_str(out, key);
+        }
+        WalChange::SetEdgeProperty {
+            src,
+            rel_type,
+            dst,
+            key,
+            value,
+        } => {
+            out.push(8);
+            put_u32(out, *src);
+            put_str(out, rel_type);
+            put_u32(out, *dst);
+            put_str(out, key);
+            put_value(out, value);
+        }
+        WalChange::RemoveEdgeProperty {
+            src,
+            rel_type,
+            dst,
+            key,
+        } => {
+            out.push(9);
+            put_u32(out, *src);
+            put_str(out, rel_type);
+            put_u32(out, *dst);
+            put_str(out, key);
+        }
+        WalChange::RenameLabel { from, to } => {
+            out.push(10);
+            put_str(out, from);
+            put_str(out, to);
+        }
+        other => return Err(unsupported(other)),
+    }
+    Ok(())
+}
+
+fn decode_change(r: &mut Reader<'_>) -> Result<WalChange> {
+    Ok(match r.u8()? {
+        0 => WalChange::CreateNode {
+            node: r.u32()?,
+            external_id: r.u64()?,
+            label: match r.u8()? {
+                0 => None,
+                _ => Some(r.string()?),
+            },
+        },
+        1 => WalChange::DeleteNode { node: r.u32()? },
+        2 => WalChange::AddLabel {
+            node: r.u32()?,
+            label: r.string()?,
+        },
+        3 => WalChange::RemoveLabel {
+            node: r.u32()?,
+            label: r.string()?,
+        },
+        4 => WalChange::CreateEdge {
+            src: r.u32()?,
+            rel_type: r.string()?,
+            dst: r.u32()?,
+        },
+        5 => WalChange::DeleteEdge {
+            src: r.u32()?,
+            rel_type: r.string()?,
+            dst: r.u32()?,
+        },
+        6 => WalChange::SetNodeProperty {
+            node: r.u32()?,
+            key: r.string()?,
+            value: r.value()?,
+        },
+        7 => WalChange::RemoveNodeProperty {
+            node: r.u32()?,
+            key: r.string()?,
+        },
+        8 => WalChange::SetEdgeProperty {
+            src: r.u32()?,
+            rel_type: r.string()?,
+            dst: r.u32()?,
+            key: r.string()?,
+            value: r.value()?,
+        },
+        9 => WalChange::RemoveEdgeProperty {
+            src: r.u32()?,
+            rel_type: r.string()?,
+            dst: r.u32()?,
+            key: r.string()?,
+        },
+        10 => WalChange::RenameLabel {
+            from: r.string()?,
+            to: r.string()?,
+        },
+        _ => return Err(malformed()),
+    })
+}
+
+fn malformed() -> Error {
+    Error::Other("malformed replication batch".into())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).ok_or_else(malformed)?;
+        let bytes = self.bytes.get(self.pos..end).ok_or_else(malformed)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| malformed())
+    }
+
+    fn value(&mut self) -> Result<PropertyValue> {
+        let len = self.u32()? as usize;
+        PropertyValue::decode(self.take(len)?).map_err(|_| malformed())
+    }
+}
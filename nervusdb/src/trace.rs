@@ -0,0 +1,93 @@
+//! Short IDs that tie one executed query to the diagnostics it leaves.
+//!
+//! Every [`crate::Db`] handle is a session with a random six-digit hex ID,
+//! and [`crate::Db::next_trace_id`] numbers the queries run through it,
+//! giving IDs such as `4f1a9c-12`. While a [`TraceScope`] is entered the ID is the thread's
+//! [`TraceId::current`]: [`crate::QueryCapture`] writes it to the log, and a
+//! `nervusdb.query` tracing span carries it to subscribers. Errors name it
+//! through [`Error::with_trace`], so a failure reported by an application can
+//! be found in the engine's logs.
+
+use crate::Error;
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+
+thread_local! {
+    static CURRENT: Cell<Option<TraceId>> = const { Cell::new(None) };
+}
+
+/// Identifies one executed query; displays as `<session>-<sequence>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId {
+    session: u32,
+    seq: u64,
+}
+
+impl TraceId {
+    pub(crate) fn new(session: u32, seq: u64) -> Self {
+        Self { session, seq }
+    }
+
+    /// The ID of the query this thread is running, if it entered a scope.
+    pub fn current() -> Option<TraceId> {
+        CURRENT.with(Cell::get)
+    }
+
+    /// The session, shared by every query of one [`crate::Db`] handle.
+    pub fn session(&self) -> u32 {
+        self.session
+    }
+
+    /// Makes this the thread's [`TraceId::current`] and enters a
+    /// `nervusdb.query` span until the returned scope is dropped.
+    pub fn enter(self) -> TraceScope {
+        let span = tracing::info_span!("nervusdb.query", trace_id = %self).entered();
+        TraceScope {
+            previous: CURRENT.with(|current| current.replace(Some(self))),
+            _span: span,
+        }
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:06x}-{}", self.session, self.seq)
+    }
+}
+
+/// Keeps a [`TraceId`] current on this thread; see [`TraceId::enter`].
+#[derive(Debug)]
+#[must_use = "the trace ID is only current while the scope is held"]
+pub struct TraceScope {
+    previous: Option<TraceId>,
+    _span: tracing::span::EnteredSpan,
+}
+
+impl Drop for TraceScope {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// A random session ID for a newly opened handle.
+pub(crate) fn new_session() -> u32 {
+    (RandomState::new().build_hasher().finish() & 0xff_ffff) as u32
+}
+
+impl Error {
+    /// Appends ` [trace <id>]` to the message, so reports of the error can be
+    /// matched with the query's log entries. `Cancelled` is left as it is.
+    pub fn with_trace(self, trace: TraceId) -> Error {
+        let tag = |msg: String| format!("{msg} [trace {trace}]");
+        match self {
+            Error::Io(e) => Error::Io(std::io::Error::new(e.kind(), tag(e.to_string()))),
+            Error::Storage(msg) => Error::Storage(tag(msg)),
+            Error::Compatibility(msg) => Error::Compatibility(tag(msg)),
+            Error::Query(msg) => Error::Query(tag(msg)),
+            Error::Cancelled => Error::Cancelled,
+            Error::Other(msg) => Error::Other(tag(msg)),
+        }
+    }
+}
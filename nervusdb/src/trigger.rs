@@ -0,0 +1,332 @@
+//! Triggers that run after write transactions commit.
+//!
+//! A trigger pairs a [`TriggerEvent`] with an action: a Cypher statement or a
+//! Rust callback. When a commit through a [`Db`] handle changes the graph,
+//! each trigger registered on that handle sees the commit's matching
+//! [`WalChange`]s, in log order, right after the commit becomes visible.
+//!
+//! A Cypher action runs once per match in one write transaction per trigger
+//! and commit, with these parameters:
+//!
+//! | event | parameters |
+//! |-------|------------|
+//! | `node_created`, `node_deleted` | `$id` |
+//! | `node_property_set` | `$id`, `$key`, `$value` |
+//! | `relationship_created`, `relationship_deleted` | `$src`, `$dst`, `$type` |
+//!
+//! `$id`, `$src` and `$dst` are node ids, compared with `id(n)`. If any run
+//! fails, none of that trigger's writes for the commit are kept; the error is
+//! counted in its [`TriggerInfo`] and the triggering commit stands. Commits
+//! made by trigger actions do not fire triggers.
+//!
+//! Triggers belong to the handle and are not stored in the database: register
+//! them again after reopening.
+
+use crate::query::{Params, Value, prepare_cached};
+use crate::{Db, Error, GraphSnapshot, Result, WalChange};
+use nervusdb_query::executor::convert_api_property_to_value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// The graph changes a trigger fires on. `None` filters match any label,
+/// relationship type or key.
+///
+/// Parsed from and displayed as `node_created:User`, `node_deleted`,
+/// `node_property_set:User.email`, `relationship_created:KNOWS` or
+/// `relationship_deleted`, leaving out the parts that are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerEvent {
+    /// A node was created, carrying `label` once the commit is visible.
+    NodeCreated { label: Option<String> },
+    /// A node was deleted.
+    NodeDeleted,
+    /// Property `key` was set on a node carrying `label`.
+    NodePropertySet {
+        label: Option<String>,
+        key: Option<String>,
+    },
+    /// A relationship of type `rel_type` was created.
+    RelationshipCreated { rel_type: Option<String> },
+    /// A relationship of type `rel_type` was deleted.
+    RelationshipDeleted { rel_type: Option<String> },
+}
+
+impl TriggerEvent {
+    fn matches(&self, change: &WalChange, snapshot: &crate::DbSnapshot) -> bool {
+        let has_label = |node, label: &Option<String>| match label {
+            None => true,
+            Some(label) => snapshot
+                .resolve_label_id(label)
+                .zip(snapshot.resolve_node_labels(node))
+                .is_some_and(|(id, labels)| labels.contains(&id)),
+        };
+        let same =
+            |filter: &Option<String>, name: &str| filter.as_deref().map_or(true, |f| f == name);
+        match (self, change) {
+            (Self::NodeCreated { label }, WalChange::CreateNode { node, .. }) => {
+                has_label(*node, label)
+            }
+            (Self::NodeDeleted, WalChange::DeleteNode { .. }) => true,
+            (
+                Self::NodePropertySet { label, key },
+                WalChange::SetNodeProperty { node, key: k, .. },
+            ) => same(key, k) && has_label(*node, label),
+            (Self::RelationshipCreated { rel_type }, WalChange::CreateEdge { rel_type: t, .. })
+            | (Self::RelationshipDeleted { rel_type }, WalChange::DeleteEdge { rel_type: t, .. }) => {
+                same(rel_type, t)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for TriggerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, filter) = match self {
+            Self::NodeCreated { label } => ("node_created", label.clone()),
+            Self::NodeDeleted => ("node_deleted", None),
+            Self::NodePropertySet { label, key } => (
+                "node_property_set",
+                match (label, key) {
+                    (None, None) => None,
+                    (label, key) => Some(format!(
+                        "{}{}",
+                        label.as_deref().unwrap_or(""),
+                        key.as_deref().map(|k| format!(".{k}")).unwrap_or_default()
+                    )),
+                },
+            ),
+            Self::RelationshipCreated { rel_type } => ("relationship_created", rel_type.clone()),
+            Self::RelationshipDeleted { rel_type } => ("relationship_deleted", rel_type.clone()),
+        };
+        match filter {
+            Some(filter) => write!(f, "{kind}:{filter}"),
+            None => f.write_str(kind),
+        }
+    }
+}
+
+impl FromStr for TriggerEvent {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, filter) = match s.split_once(':') {
+            Some((kind, filter)) => (kind, Some(filter)),
+            None => (s, None),
+        };
+        let name = |part: Option<&str>| part.filter(|p| !p.is_empty()).map(str::to_string);
+        Ok(match (kind, filter) {
+            ("node_created", filter) => Self::NodeCreated {
+                label: name(filter),
+            },
+            ("node_deleted", None) => Self::NodeDeleted,
+            ("node_property_set", filter) => {
+                let (label, key) = match filter.map(|f| f.split_once('.')) {
+                    Some(Some((label, key))) => (Some(label), Some(key)),
+                    Some(None) => (filter, None),
+                    None => (None, None),
+                };
+                Self::NodePropertySet {
+                    label: name(label),
+                    key: name(key),
+                }
+            }
+            ("relationship_created", filter) => Self::RelationshipCreated {
+                rel_type: name(filter),
+            },
+            ("relationship_deleted", filter) => Self::RelationshipDeleted {
+                rel_type: name(filter),
+            },
+            _ => return Err(Error::Other(format!("unknown trigger event '{s}'"))),
+        })
+    }
+}
+
+/// Rust action of a trigger, called with the handle and each matching change.
+pub type TriggerCallback = Arc<dyn Fn(&Db, &WalChange) -> Result<()> + Send + Sync>;
+
+/// A registered trigger as listed by [`Db::triggers`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerInfo {
+    pub name: String,
+    pub event: TriggerEvent,
+    /// The Cypher action; `None` for a Rust callback.
+    pub cypher: Option<String>,
+    /// Commits the trigger ran its action for.
+    pub fired: u64,
+    /// Of those, how many failed.
+    pub failures: u64,
+    pub last_error: Option<String>,
+}
+
+enum Action {
+    Cypher(String),
+    Callback(TriggerCallback),
+}
+
+struct Trigger {
+    event: TriggerEvent,
+    action: Action,
+    fired: u64,
+    failures: u64,
+    last_error: Option<String>,
+}
+
+/// The triggers registered on one handle.
+#[derive(Default)]
+pub(crate) struct Triggers {
+    by_name: Mutex<BTreeMap<String, Trigger>>,
+}
+
+impl fmt::Debug for Triggers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.list()).finish()
+    }
+}
+
+impl Triggers {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub(crate) fn create_cypher(
+        &self,
+        name: &str,
+        event: TriggerEvent,
+        cypher: &str,
+    ) -> Result<()> {
+        if !prepare_cached(cypher)?.is_write() {
+            return Err(Error::Query(format!(
+                "trigger '{name}' must run a write statement"
+            )));
+        }
+        self.insert(name, event, Action::Cypher(cypher.to_string()))
+    }
+
+    pub(crate) fn create_callback(
+        &self,
+        name: &str,
+        event: TriggerEvent,
+        callback: TriggerCallback,
+    ) -> Result<()> {
+        self.insert(name, event, Action::Callback(callback))
+    }
+
+    fn insert(&self, name: &str, event: TriggerEvent, action: Action) -> Result<()> {
+        if name.is_empty() {
+            return Err(Error::Other("trigger name must not be empty".to_string()));
+        }
+        self.lock().insert(
+            name.to_string(),
+            Trigger {
+                event,
+                action,
+                fired: 0,
+                failures: 0,
+                last_error: None,
+            },
+        );
+        Ok(())
+    }
+
+    pub(crate) fn drop_trigger(&self, name: &str) -> bool {
+        self.lock().remove(name).is_some()
+    }
+
+    pub(crate) fn list(&self) -> Vec<TriggerInfo> {
+        self.lock()
+            .iter()
+            .map(|(name, trigger)| TriggerInfo {
+                name: name.clone(),
+                event: trigger.event.clone(),
+                cypher: match &trigger.action {
+                    Action::Cypher(cypher) => Some(cypher.clone()),
+                    Action::Callback(_) => None,
+                },
+                fired: trigger.fired,
+                failures: trigger.failures,
+                last_error: trigger.last_error.clone(),
+            })
+            .collect()
+    }
+
+    /// Runs the triggers matching `changes`, which `db` just committed.
+    pub(crate) fn fire(&self, db: &Db, changes: &[WalChange]) {
+        if changes.is_empty() {
+            return;
+        }
+        let snapshot = db.snapshot();
+        let due: Vec<(String, Vec<&WalChange>)> = self
+            .lock()
+            .iter()
+            .filter_map(|(name, trigger)| {
+                let matched: Vec<_> = changes
+                    .iter()
+                    .filter(|change| trigger.event.matches(change, &snapshot))
+                    .collect();
+                (!matched.is_empty()).then(|| (name.clone(), matched))
+            })
+            .collect();
+        // Actions run unlocked, so callbacks may manage triggers themselves.
+        for (name, matched) in due {
+            let action = match self.lock().get(&name).map(|t| &t.action) {
+                Some(Action::Cypher(cypher)) => Action::Cypher(cypher.clone()),
+                Some(Action::Callback(callback)) => Action::Callback(Arc::clone(callback)),
+                None => continue,
+            };
+            let outcome = match action {
+                Action::Cypher(cypher) => run_cypher(db, &cypher, &matched),
+                Action::Callback(callback) => matched.iter().try_for_each(|c| callback(db, c)),
+            };
+            if let Some(trigger) = self.lock().get_mut(&name) {
+                trigger.fired += 1;
+                if let Err(e) = outcome {
+                    tracing::warn!(trigger = %name, error = %e, "trigger failed");
+                    trigger.failures += 1;
+                    trigger.last_error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Trigger>> {
+        self.by_name.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Runs `cypher` once per change in one transaction, committed without
+/// firing triggers.
+fn run_cypher(db: &Db, cypher: &str, changes: &[&WalChange]) -> Result<()> {
+    let prepared = prepare_cached(cypher)?;
+    let mut txn = db.begin_write();
+    for change in changes {
+        let snapshot = db.snapshot_in(&txn);
+        prepared.execute_mixed(&snapshot, &mut txn, &change_params(change))?;
+    }
+    txn.inner.commit().map_err(Error::from)
+}
+
+fn change_params(change: &WalChange) -> Params {
+    let mut params = Params::new();
+    let id = |node: &u32| Value::Int(i64::from(*node));
+    match change {
+        WalChange::CreateNode { node, .. } | WalChange::DeleteNode { node } => {
+            params.insert("id", id(node));
+        }
+        WalChange::SetNodeProperty { node, key, value } => {
+            params.insert("id", id(node));
+            params.insert("key", Value::String(key.clone()));
+            params.insert("value", convert_api_property_to_value(value));
+        }
+        WalChange::CreateEdge { src, rel_type, dst }
+        | WalChange::DeleteEdge { src, rel_type, dst } => {
+            params.insert("src", id(src));
+            params.insert("dst", id(dst));
+            params.insert("type", Value::String(rel_type.clone()));
+        }
+        _ => {}
+    }
+    params
+}
@@ -0,0 +1,210 @@
+//! A read-only graph over subject-predicate-object triples, so datasets from
+//! the v1 triple store can be queried with the Cypher engine without
+//! migrating them into a `.ndb` file.
+//!
+//! The v1 storage format is not read here; feed the triples from its export
+//! (or any other source) to [`TripleGraph::from_triples`].
+
+use crate::{
+    EdgeKey, Error, ExternalId, GraphSnapshot, InternalNodeId, PropertyValue, RelTypeId, Result,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Property holding a node's subject or object term.
+pub const TRIPLE_TERM_PROPERTY: &str = "value";
+
+/// Triples viewed as a graph: every distinct subject and object is a node
+/// with its term in the `value` property, and every triple is a relationship
+/// from subject to object typed by its predicate. Nodes have no labels.
+/// Duplicate triples collapse into one relationship.
+///
+/// # Example
+///
+/// ```ignore
+/// let graph = TripleGraph::from_triples([("alice", "knows", "bob")])?;
+/// let rows = prepare("MATCH (a)-[:knows]->(b) RETURN b.value")?
+///     .execute_streaming(&graph, &Params::new());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TripleGraph {
+    terms: Vec<String>,
+    term_ids: HashMap<String, InternalNodeId>,
+    predicates: Vec<String>,
+    predicate_ids: HashMap<String, RelTypeId>,
+    /// Outgoing and incoming edges per node, sorted by `(rel, other end)`.
+    outgoing: Vec<Vec<EdgeKey>>,
+    incoming: Vec<Vec<EdgeKey>>,
+    edge_count: u64,
+}
+
+impl TripleGraph {
+    /// Builds the graph of `triples`.
+    ///
+    /// # Errors
+    /// Fails when the distinct terms or predicates outnumber the 32-bit ids
+    /// that nodes and relationship types have.
+    pub fn from_triples<I, S, P, O>(triples: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (S, P, O)>,
+        S: Into<String>,
+        P: Into<String>,
+        O: Into<String>,
+    {
+        let mut graph = Self::default();
+        for (subject, predicate, object) in triples {
+            let src = graph.intern_term(subject.into())?;
+            let rel = graph.intern_predicate(predicate.into())?;
+            let dst = graph.intern_term(object.into())?;
+            let edge = EdgeKey { src, rel, dst };
+            graph.outgoing[src as usize].push(edge);
+            graph.incoming[dst as usize].push(edge);
+        }
+        for edges in &mut graph.outgoing {
+            edges.sort_by_key(|e| (e.rel, e.dst));
+            edges.dedup();
+        }
+        for edges in &mut graph.incoming {
+            edges.sort_by_key(|e| (e.rel, e.src));
+            edges.dedup();
+        }
+        graph.edge_count = graph.outgoing.iter().map(|e| e.len() as u64).sum();
+        Ok(graph)
+    }
+
+    /// The node for `term`, if it appears as a subject or object.
+    pub fn node(&self, term: &str) -> Option<InternalNodeId> {
+        self.term_ids.get(term).copied()
+    }
+
+    /// The subject or object term of `node`.
+    pub fn term(&self, node: InternalNodeId) -> Option<&str> {
+        self.terms.get(node as usize).map(String::as_str)
+    }
+
+    fn intern_term(&mut self, term: String) -> Result<InternalNodeId> {
+        if let Some(&id) = self.term_ids.get(&term) {
+            return Ok(id);
+        }
+        // `InternalNodeId::MAX` stays free so the node count fits in an id.
+        let id = InternalNodeId::try_from(self.terms.len())
+            .ok()
+            .filter(|&id| id < InternalNodeId::MAX)
+            .ok_or_else(|| too_many("terms"))?;
+        self.terms.push(term.clone());
+        self.term_ids.insert(term, id);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        Ok(id)
+    }
+
+    fn intern_predicate(&mut self, predicate: String) -> Result<RelTypeId> {
+        if let Some(&id) = self.predicate_ids.get(&predicate) {
+            return Ok(id);
+        }
+        let id = RelTypeId::try_from(self.predicates.len())
+            .ok()
+            .filter(|&id| id < RelTypeId::MAX)
+            .ok_or_else(|| too_many("predicates"))?;
+        self.predicates.push(predicate.clone());
+        self.predicate_ids.insert(predicate, id);
+        Ok(id)
+    }
+
+    fn edges<'a>(
+        edges: Option<&'a Vec<EdgeKey>>,
+        rel: Option<RelTypeId>,
+    ) -> Box<dyn Iterator<Item = EdgeKey> + 'a> {
+        let edges = edges.map_or(&[][..], Vec::as_slice);
+        match rel {
+            None => Box::new(edges.iter().copied()),
+            Some(rel) => {
+                let start = edges.partition_point(|e| e.rel < rel);
+                let end = edges.partition_point(|e| e.rel <= rel);
+                Box::new(edges[start..end].iter().copied())
+            }
+        }
+    }
+}
+
+fn too_many(what: &str) -> Error {
+    Error::Other(format!(
+        "too many distinct {what} for a triple graph: at most {} fit",
+        u32::MAX - 1
+    ))
+}
+
+impl GraphSnapshot for TripleGraph {
+    type Neighbors<'a> = Box<dyn Iterator<Item = EdgeKey> + 'a>;
+
+    fn neighbors(&self, src: InternalNodeId, rel: Option<RelTypeId>) -> Self::Neighbors<'_> {
+        Self::edges(self.outgoing.get(src as usize), rel)
+    }
+
+    fn incoming_neighbors(
+        &self,
+        dst: InternalNodeId,
+        rel: Option<RelTypeId>,
+    ) -> Self::Neighbors<'_> {
+        Self::edges(self.incoming.get(dst as usize), rel)
+    }
+
+    fn nodes(&self) -> Box<dyn Iterator<Item = InternalNodeId> + '_> {
+        Box::new(0..self.terms.len() as InternalNodeId)
+    }
+
+    fn resolve_external(&self, iid: InternalNodeId) -> Option<ExternalId> {
+        self.term(iid).map(|_| ExternalId::from(iid))
+    }
+
+    fn resolve_node_labels(&self, iid: InternalNodeId) -> Option<Vec<crate::LabelId>> {
+        self.term(iid).map(|_| Vec::new())
+    }
+
+    fn node_property(&self, iid: InternalNodeId, key: &str) -> Option<PropertyValue> {
+        (key == TRIPLE_TERM_PROPERTY)
+            .then(|| self.term(iid))
+            .flatten()
+            .map(|term| PropertyValue::String(term.to_string()))
+    }
+
+    fn node_properties(&self, iid: InternalNodeId) -> Option<BTreeMap<String, PropertyValue>> {
+        self.term(iid).map(|term| {
+            BTreeMap::from([(
+                TRIPLE_TERM_PROPERTY.to_string(),
+                PropertyValue::String(term.to_string()),
+            )])
+        })
+    }
+
+    fn edge_properties(&self, edge: EdgeKey) -> Option<BTreeMap<String, PropertyValue>> {
+        self.neighbors(edge.src, Some(edge.rel))
+            .any(|e| e.dst == edge.dst)
+            .then(BTreeMap::new)
+    }
+
+    fn resolve_rel_type_id(&self, name: &str) -> Option<RelTypeId> {
+        self.predicate_ids.get(name).copied()
+    }
+
+    fn resolve_rel_type_name(&self, id: RelTypeId) -> Option<String> {
+        self.predicates.get(id as usize).cloned()
+    }
+
+    fn node_count(&self, label: Option<crate::LabelId>) -> u64 {
+        match label {
+            None => self.terms.len() as u64,
+            Some(_) => 0,
+        }
+    }
+
+    fn edge_count(&self, rel: Option<RelTypeId>) -> u64 {
+        match rel {
+            None => self.edge_count,
+            Some(rel) => self
+                .outgoing
+                .iter()
+                .map(|edges| Self::edges(Some(edges), Some(rel)).count() as u64)
+                .sum(),
+        }
+    }
+}
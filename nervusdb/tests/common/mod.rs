@@ -0,0 +1,57 @@
+//! Helpers shared by the integration tests; each test file uses a subset.
+#![allow(dead_code)]
+
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+
+/// Runs `cypher` in its own committed write transaction.
+pub fn write(db: &Db, cypher: &str) {
+    try_write(db, cypher).unwrap();
+}
+
+/// Like [`write`], but returns the number of changes, or the error from
+/// compiling, running or committing `cypher`.
+pub fn try_write(db: &Db, cypher: &str) -> nervusdb::Result<u32> {
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let (_, changes) = prepare(cypher)?.execute_mixed(&snapshot, &mut txn, &Params::new())?;
+    txn.commit()?;
+    Ok(changes)
+}
+
+/// The values of each row `cypher` returns, in column order.
+pub fn rows(db: &Db, cypher: &str) -> Vec<Vec<Value>> {
+    try_rows(db, cypher).unwrap()
+}
+
+/// Like [`rows`], but returns the error from compiling or running `cypher`.
+pub fn try_rows(db: &Db, cypher: &str) -> nervusdb::query::Result<Vec<Vec<Value>>> {
+    let snapshot = db.snapshot();
+    prepare(cypher)?
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| row.map(|row| row.columns().iter().map(|(_, v)| v.clone()).collect()))
+        .collect()
+}
+
+/// The integer in the first column of the first row `cypher` returns.
+pub fn count(db: &Db, cypher: &str) -> i64 {
+    match rows(db, cypher).first().and_then(|row| row.first()) {
+        Some(Value::Int(n)) => *n,
+        other => panic!("expected a count, got {other:?}"),
+    }
+}
+
+/// The first column of each row `cypher` returns, with nodes and
+/// relationships read in full.
+pub fn column(db: &Db, cypher: &str) -> Vec<Value> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| {
+            row.unwrap().reify(&snapshot).unwrap().columns()[0]
+                .1
+                .clone()
+        })
+        .collect()
+}
@@ -12,6 +12,8 @@ fn test_execute_options_default_balanced_profile() {
     assert_eq!(opts.max_collection_items, 200_000);
     assert_eq!(opts.soft_timeout_ms, 5_000);
     assert_eq!(opts.max_apply_rows_per_outer, 200_000);
+    assert_eq!(opts.max_memory_bytes, 256 * 1024 * 1024);
+    assert!(opts.spill_to_disk);
 }
 
 #[test]
@@ -0,0 +1,89 @@
+use nervusdb::{Db, GraphSnapshot};
+use tempfile::tempdir;
+
+#[test]
+fn t344_rollback_discards_staged_writes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path()).unwrap();
+
+    let mut txn = db.begin_write();
+    let label = txn.get_or_create_label("User").unwrap();
+    let a = txn.create_node(1, label).unwrap();
+    let b = txn.create_node(2, label).unwrap();
+    txn.create_edge(a, 1, b);
+    txn.commit().unwrap();
+
+    let mut txn = db.begin_write();
+    let c = txn.create_node(3, label).unwrap();
+    txn.create_edge(a, 1, c);
+    txn.tombstone_edge(a, 1, b);
+    txn.set_vector(a, vec![1.0, 0.0]).unwrap();
+    txn.rollback();
+
+    let snapshot = db.snapshot();
+    assert_eq!(snapshot.nodes().count(), 2);
+    let neighbors: Vec<_> = snapshot.neighbors(a, Some(1)).map(|e| e.dst).collect();
+    assert_eq!(neighbors, vec![b]);
+    assert!(db.search_vector(&[1.0, 0.0], 1).unwrap().is_empty());
+
+    // The writer lock is released, and later commits still apply.
+    let mut txn = db.begin_write();
+    txn.set_vector(b, vec![0.0, 1.0]).unwrap();
+    txn.commit().unwrap();
+    let hits = db.search_vector(&[0.0, 1.0], 1).unwrap();
+    assert_eq!(hits.first().map(|(id, _)| *id), Some(b));
+}
+
+#[test]
+fn t344_rollback_is_not_replayed_after_reopen() {
+    let dir = tempdir().unwrap();
+    {
+        let db = Db::open(dir.path()).unwrap();
+        let mut txn = db.begin_write();
+        let label = txn.get_or_create_label("User").unwrap();
+        txn.create_node(1, label).unwrap();
+        txn.commit().unwrap();
+
+        let mut txn = db.begin_write();
+        txn.create_node(2, label).unwrap();
+        txn.rollback();
+    }
+
+    let db = Db::open(dir.path()).unwrap();
+    let snapshot = db.snapshot();
+    let externals: Vec<_> = snapshot
+        .nodes()
+        .filter_map(|iid| snapshot.resolve_external(iid))
+        .collect();
+    assert_eq!(externals, vec![1]);
+}
+
+#[test]
+fn t344_committed_vectors_survive_a_crash_before_the_index_write() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path()).unwrap();
+    let mut txn = db.begin_write();
+    let label = txn.get_or_create_label("User").unwrap();
+    let a = txn.create_node(1, label).unwrap();
+    txn.commit().unwrap();
+    db.checkpoint().unwrap();
+    let ndb_path = db.ndb_path().to_path_buf();
+    let checkpointed = std::fs::read(&ndb_path).unwrap();
+
+    let mut txn = db.begin_write();
+    txn.set_vector(a, vec![1.0, 0.0]).unwrap();
+    txn.commit().unwrap();
+    drop(db);
+    // Lose every page written since the checkpoint, as a crash before they
+    // reached the disk would; only the WAL still knows the vector.
+    std::fs::write(&ndb_path, checkpointed).unwrap();
+
+    let db = Db::open(dir.path()).unwrap();
+    let hits = db.search_vector(&[1.0, 0.0], 1).unwrap();
+    assert_eq!(hits.first().map(|(id, _)| *id), Some(a));
+    db.close().unwrap();
+
+    // Reopening again finds the vector already indexed and keeps one copy.
+    let db = Db::open(dir.path()).unwrap();
+    assert_eq!(db.search_vector(&[1.0, 0.0], 5).unwrap().len(), 1);
+}
@@ -0,0 +1,90 @@
+use nervusdb::{Db, GraphSnapshot, PropertyValue};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn query_one(db: &Db, cypher: &str) -> nervusdb_query::Row {
+    let snapshot = db.snapshot();
+    let rows: Vec<_> = prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1, "{cypher}");
+    rows.into_iter().next().unwrap()
+}
+
+#[test]
+fn t345_temporal_properties_survive_reopen_and_read_as_cypher_values() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("temporal.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        let mut txn = db.begin_write();
+        let label = txn.get_or_create_label("Event").unwrap();
+        let node = txn.create_node(1, label).unwrap();
+        for (key, value) in [
+            ("day", PropertyValue::Date(19_753)),
+            (
+                "at",
+                PropertyValue::LocalTime(13 * 3_600_000_000_000 + 500_000_000),
+            ),
+            (
+                "span",
+                PropertyValue::Duration {
+                    months: 14,
+                    days: 3,
+                    nanos: 4_000_000_000,
+                },
+            ),
+        ] {
+            txn.set_node_property(node, key.to_string(), value).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    let row = query_one(
+        &db,
+        "MATCH (e:Event) RETURN e.day AS day, e.at AS at, e.span.months AS months, \
+         e.day = date('2024-01-31') AS same_day, e.day + e.span AS shifted",
+    );
+    assert_eq!(row.get("day"), Some(&Value::String("2024-01-31".into())));
+    assert_eq!(row.get("at"), Some(&Value::String("13:00:00.5".into())));
+    assert_eq!(row.get("months"), Some(&Value::Int(14)));
+    assert_eq!(row.get("same_day"), Some(&Value::Bool(true)));
+    assert_eq!(
+        row.get("shifted"),
+        Some(&Value::String("2025-04-03".into()))
+    );
+}
+
+#[test]
+fn t345_cypher_durations_are_stored_as_duration_properties() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("temporal.ndb")).unwrap();
+
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    prepare("CREATE (:Task {span: duration('P1Y2M3DT4S')})")
+        .unwrap()
+        .execute_write(&snapshot, &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let snapshot = db.snapshot();
+    let node = snapshot.nodes().next().unwrap();
+    assert_eq!(
+        snapshot.node_property(node, "span"),
+        Some(PropertyValue::Duration {
+            months: 14,
+            days: 3,
+            nanos: 4_000_000_000,
+        })
+    );
+
+    let row = query_one(
+        &db,
+        "MATCH (t:Task) RETURN t.span = duration('P1Y2M3DT4S') AS same",
+    );
+    assert_eq!(row.get("same"), Some(&Value::Bool(true)));
+}
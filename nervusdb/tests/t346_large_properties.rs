@@ -0,0 +1,79 @@
+use nervusdb::{Db, GraphSnapshot, MAX_PROPERTY_VALUE_BYTES, PropertyValue};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn big_text() -> String {
+    // ~6 MiB of mixed 1-4 byte UTF-8, well past a single WAL record.
+    "naïve 東京 🚀 ".repeat(300_000)
+}
+
+fn write_text(db: &Db, text: &str) -> nervusdb_query::Result<u32> {
+    let mut params = Params::new();
+    params.insert("text".to_string(), Value::String(text.to_string()));
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let count =
+        prepare("CREATE (:Doc {text: $text})")?.execute_write(&snapshot, &mut txn, &params)?;
+    txn.commit().unwrap();
+    Ok(count)
+}
+
+fn read_text(db: &Db) -> PropertyValue {
+    let snapshot = db.snapshot();
+    let node = snapshot.nodes().next().expect("one node");
+    snapshot.node_property(node, "text").expect("text property")
+}
+
+#[test]
+fn t346_multi_megabyte_unicode_survives_replay_and_checkpoint() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("large.ndb");
+    let text = big_text();
+    assert!(text.len() > 4 * 1024 * 1024);
+
+    {
+        let db = Db::open(&path).unwrap();
+        write_text(&db, &text).unwrap();
+        // Dropping without close leaves the value only in the WAL.
+    }
+    {
+        let db = Db::open(&path).unwrap();
+        assert_eq!(read_text(&db), PropertyValue::String(text.clone()));
+        db.checkpoint().unwrap();
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    assert_eq!(read_text(&db), PropertyValue::String(text));
+}
+
+#[test]
+fn t346_values_over_the_limit_are_rejected() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("large.ndb")).unwrap();
+
+    let too_big = "x".repeat(MAX_PROPERTY_VALUE_BYTES);
+    let err = write_text(&db, &too_big).expect_err("value over the limit");
+    assert!(
+        err.to_string().contains("property value too large"),
+        "{err}"
+    );
+
+    let mut txn = db.begin_write();
+    let label = txn.get_or_create_label("Doc").unwrap();
+    let node = txn.create_node(1, label).unwrap();
+    let err = txn
+        .set_node_property(
+            node,
+            "data".to_string(),
+            PropertyValue::Blob(vec![0; MAX_PROPERTY_VALUE_BYTES]),
+        )
+        .expect_err("blob over the limit");
+    assert!(
+        err.to_string().contains("property value too large"),
+        "{err}"
+    );
+    txn.commit().unwrap();
+
+    assert_eq!(db.snapshot().nodes().count(), 1);
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare_cached};
+use tempfile::tempdir;
+
+#[test]
+fn t347_cached_plans_are_reused_until_an_index_is_created() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("plans.ndb")).unwrap();
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    prepare_cached("UNWIND range(1, 5) AS i CREATE (:User {id: i})")
+        .unwrap()
+        .execute_write(&snapshot, &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let cypher = "MATCH (u:User) WHERE u.id = $id RETURN u.id AS id";
+    let first = prepare_cached(cypher).unwrap();
+    assert!(Arc::ptr_eq(&first, &prepare_cached(cypher).unwrap()));
+
+    db.create_index("User", "id").unwrap();
+    let replanned = prepare_cached(cypher).unwrap();
+    assert!(!Arc::ptr_eq(&first, &replanned));
+
+    let mut params = Params::new();
+    params.insert("id".to_string(), Value::Int(3));
+    let snapshot = db.snapshot();
+    let rows: Vec<_> = replanned
+        .execute_streaming(&snapshot, &params)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get("id"), Some(&Value::Int(3)));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::write;
+use nervusdb::{Db, GraphSnapshot, PropertyValue};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn matching_ids(db: &Db, needle: &str) -> Vec<i64> {
+    let mut params = Params::new();
+    params.insert("needle".to_string(), Value::String(needle.to_string()));
+    let snapshot = db.snapshot();
+    prepare("MATCH (d:Doc) WHERE d.body CONTAINS $needle RETURN d.id AS id ORDER BY id")
+        .unwrap()
+        .execute_streaming(&snapshot, &params)
+        .map(|row| match row.unwrap().get("id") {
+            Some(Value::Int(id)) => *id,
+            other => panic!("unexpected id {other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn t348_contains_uses_text_index_over_compressed_values() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("text.ndb");
+    let filler = "lorem ipsum dolor sit amet ".repeat(400);
+    {
+        let db = Db::open(&path).unwrap();
+        write(
+            &db,
+            &format!(
+                "CREATE (:Doc {{id: 1, body: '{filler} needle-one'}}), \
+                 (:Doc {{id: 2, body: '{filler} needle-two'}}), \
+                 (:Doc {{id: 3, body: 'short text'}}), \
+                 (:Note {{id: 4, body: 'needle-one in another label'}})"
+            ),
+        );
+        // Moves the long strings into the page store, where they are compressed.
+        db.checkpoint().unwrap();
+        db.create_text_index("Doc", "body").unwrap();
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    let snapshot = db.snapshot();
+    let doc = snapshot.nodes().next().unwrap();
+    assert!(matches!(
+        snapshot.node_property(doc, "body"),
+        Some(PropertyValue::String(s)) if s.len() > 10_000 && s.ends_with("needle-one")
+    ));
+
+    let explain = prepare("EXPLAIN MATCH (d:Doc) WHERE d.body CONTAINS 'needle' RETURN d")
+        .unwrap()
+        .explain_string()
+        .unwrap()
+        .to_string();
+    assert!(explain.contains("TextIndexSeek"), "{explain}");
+    assert_eq!(
+        snapshot
+            .lookup_text_index("Doc", "body", "needle")
+            .map(|c| c.len()),
+        Some(2)
+    );
+
+    assert_eq!(matching_ids(&db, "needle"), vec![1, 2]);
+    assert_eq!(matching_ids(&db, "needle-two"), vec![2]);
+    assert_eq!(matching_ids(&db, "ipsum dolor"), vec![1, 2]);
+    // Shorter than a trigram: answered by the label scan fallback.
+    assert_eq!(matching_ids(&db, "sh"), vec![3]);
+    assert_eq!(matching_ids(&db, "absent"), Vec::<i64>::new());
+
+    // Writes after creation keep the index current.
+    write(&db, "CREATE (:Doc {id: 5, body: 'late needle'})");
+    write(&db, "MATCH (d:Doc {id: 1}) SET d.body = 'rewritten'");
+    write(&db, "MATCH (d:Doc {id: 2}) DETACH DELETE d");
+    assert_eq!(matching_ids(&db, "needle"), vec![5]);
+    assert_eq!(matching_ids(&db, "rewritten"), vec![1]);
+}
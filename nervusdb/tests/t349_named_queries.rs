@@ -0,0 +1,97 @@
+mod common;
+
+use common::try_write;
+use nervusdb::Db;
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn run_named(db: &Db, name: &str, params: &Params) -> Vec<Value> {
+    let cypher = db.named_query(name).expect("stored query");
+    let snapshot = db.snapshot();
+    prepare(&cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, params)
+        .map(|row| row.unwrap().get("name").cloned().unwrap())
+        .collect()
+}
+
+fn listed(db: &Db) -> Vec<(Value, Value)> {
+    let snapshot = db.snapshot();
+    prepare("CALL query.list() YIELD name, query RETURN name, query")
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| {
+            let row = row.unwrap();
+            (
+                row.get("name").cloned().unwrap(),
+                row.get("query").cloned().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn t349_named_queries_persist_and_run_with_params() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("named.ndb");
+    let top_users = "MATCH (u:User) WHERE u.score >= $min RETURN u.name AS name ORDER BY name";
+    {
+        let db = Db::open(&path).unwrap();
+        try_write(
+            &db,
+            "CREATE (:User {name: 'ann', score: 9}), (:User {name: 'bo', score: 3})",
+        )
+        .unwrap();
+        let saved = try_write(&db, &format!("CALL query.save('topUsers', '{top_users}')")).unwrap();
+        assert_eq!(saved, 1);
+        db.save_query("scratch", "RETURN 1 AS name").unwrap();
+        // Dropped without close: the catalog is rebuilt from the WAL.
+    }
+    {
+        let db = Db::open(&path).unwrap();
+        assert_eq!(
+            listed(&db),
+            vec![
+                (
+                    Value::String("scratch".into()),
+                    Value::String("RETURN 1 AS name".into())
+                ),
+                (
+                    Value::String("topUsers".into()),
+                    Value::String(top_users.into())
+                ),
+            ]
+        );
+        assert_eq!(try_write(&db, "CALL query.drop('scratch')").unwrap(), 1);
+        assert!(!db.drop_query("scratch").unwrap());
+        db.checkpoint().unwrap();
+        db.close().unwrap();
+    }
+
+    // Reopened from the checkpoint snapshot written on close.
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        db.named_queries(),
+        vec![("topUsers".to_string(), top_users.to_string())]
+    );
+    let mut params = Params::new();
+    params.insert("min".to_string(), Value::Int(5));
+    assert_eq!(
+        run_named(&db, "topUsers", &params),
+        vec![Value::String("ann".into())]
+    );
+    params.insert("min".to_string(), Value::Int(0));
+    assert_eq!(run_named(&db, "topUsers", &params).len(), 2);
+}
+
+#[test]
+fn t349_invalid_queries_are_not_saved() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("named.ndb")).unwrap();
+
+    assert!(db.save_query("broken", "MATCH (n RETURN n").is_err());
+    assert!(db.save_query("", "RETURN 1").is_err());
+    let err = try_write(&db, "CALL query.save('broken', 'RETURN')").unwrap_err();
+    assert!(err.to_string().contains("does not compile"), "{err}");
+    assert!(db.named_queries().is_empty());
+}
@@ -0,0 +1,105 @@
+use nervusdb::Db;
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn seed(db: &Db) {
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    prepare("UNWIND range(1, 10) AS i CREATE (:User {id: i, active: i % 2 = 0})")
+        .unwrap()
+        .execute_mixed(&snapshot, &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+}
+
+fn int(value: Option<&Value>) -> i64 {
+    match value {
+        Some(Value::Int(i)) => *i,
+        other => panic!("expected int, got {other:?}"),
+    }
+}
+
+#[test]
+fn t350_profile_reports_rows_per_operator() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("profile.ndb")).unwrap();
+    seed(&db);
+
+    let snapshot = db.snapshot();
+    let query = prepare("PROFILE MATCH (u:User) WHERE u.active RETURN u.id AS id LIMIT 3").unwrap();
+    assert!(query.is_profile());
+    assert!(!query.is_write());
+    let rows: Vec<_> = query
+        .execute_streaming(&snapshot, &Params::new())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(int(row.get("rows")), 3);
+
+    let Some(Value::String(plan)) = row.get("plan") else {
+        panic!("missing plan: {row:?}");
+    };
+    assert!(plan.starts_with("Limit(limit="), "{plan}");
+    assert!(
+        plan.lines()
+            .next()
+            .unwrap()
+            .contains("[rows=3, invocations=1, time=")
+    );
+
+    let Some(Value::List(operators)) = row.get("operators") else {
+        panic!("missing operators: {row:?}");
+    };
+    let stats: Vec<(String, i64, i64)> = operators
+        .iter()
+        .map(|op| {
+            let Value::Map(op) = op else {
+                panic!("operator is not a map: {op:?}")
+            };
+            let Some(Value::String(name)) = op.get("operator") else {
+                panic!("missing operator name: {op:?}")
+            };
+            (name.clone(), int(op.get("depth")), int(op.get("rows")))
+        })
+        .collect();
+    assert_eq!(stats[0], ("Limit".to_string(), 0, 3));
+    let filter = stats
+        .iter()
+        .find(|(name, _, _)| name == "Filter")
+        .expect("filter operator");
+    assert_eq!(filter.2, 3, "LIMIT stops pulling after three matches");
+    assert!(stats.iter().all(|(_, depth, _)| *depth >= 0));
+}
+
+#[test]
+fn t350_explain_analyze_is_profile_and_rejects_writes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("profile.ndb")).unwrap();
+    seed(&db);
+
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let (rows, writes) = prepare("EXPLAIN ANALYZE MATCH (u:User) RETURN count(u) AS n")
+        .unwrap()
+        .execute_mixed(&snapshot, &mut txn, &Params::new())
+        .unwrap();
+    assert_eq!(writes, 0);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(int(rows[0].get("rows")), 1);
+    let Some(Value::String(plan)) = rows[0].get("plan") else {
+        panic!("missing plan: {rows:?}");
+    };
+    assert!(
+        plan.contains("NodeScan(alias=u") && plan.contains("rows=10"),
+        "{plan}"
+    );
+
+    let err = prepare("PROFILE CREATE (:User {id: 99})").unwrap_err();
+    assert!(err.to_string().contains("read queries only"), "{err}");
+    assert!(prepare("PROFILE").is_err());
+
+    // EXPLAIN alone still only renders the plan.
+    let explain = prepare("EXPLAIN MATCH (u:User) RETURN u").unwrap();
+    assert!(explain.is_explain() && !explain.is_profile());
+}
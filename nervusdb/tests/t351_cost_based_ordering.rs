@@ -0,0 +1,92 @@
+mod common;
+
+use common::{count, write};
+use nervusdb::Db;
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+/// 300 `Big` nodes, each with one `R` edge to one of 5 `Small` nodes.
+fn seed(db: &Db) {
+    write(
+        db,
+        "UNWIND range(0, 4) AS i CREATE (:Small {name: 's' + toString(i)})",
+    );
+    write(
+        db,
+        "UNWIND range(0, 299) AS j \
+         MATCH (s:Small {name: 's' + toString(j % 5)}) \
+         CREATE (:Big {id: j})-[:R]->(s)",
+    );
+}
+
+/// Returns the `[rows=…, invocations=…]` annotation of the first operator
+/// whose plan line starts with `operator`.
+fn profiled(db: &Db, cypher: &str, operator: &str) -> String {
+    let snapshot = db.snapshot();
+    let rows: Vec<_> = prepare(&format!("PROFILE {cypher}"))
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let Some(Value::String(plan)) = rows[0].get("plan") else {
+        panic!("missing plan");
+    };
+    let line = plan
+        .lines()
+        .find(|line| line.trim_start().starts_with(operator))
+        .unwrap_or_else(|| panic!("no {operator}:\n{plan}"));
+    line[line.find('[').unwrap()..].to_string()
+}
+
+#[test]
+fn t351_chain_is_anchored_at_the_rarer_label_once_statistics_exist() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("cbo.ndb")).unwrap();
+    seed(&db);
+    let cypher = "MATCH (b:Big)-[:R]->(s:Small {name: 's1'}) RETURN count(b) AS n";
+
+    // No statistics yet: the textual order is kept.
+    assert_eq!(count(&db, cypher), 60);
+    assert!(profiled(&db, cypher, "MatchOut(src=b").contains("invocations=1"));
+    assert!(profiled(&db, cypher, "IndexSeek(alias=s").contains("invocations=0"));
+
+    db.compact().unwrap();
+    assert_eq!(count(&db, cypher), 60);
+    assert!(profiled(&db, cypher, "MatchOut(src=b").contains("invocations=0"));
+    assert!(profiled(&db, cypher, "IndexSeek(alias=s").contains("rows=5, invocations=1"));
+}
+
+#[test]
+fn t351_patterns_are_reordered_by_estimated_cost() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("cbo.ndb")).unwrap();
+    seed(&db);
+    db.compact().unwrap();
+
+    let cypher = "MATCH (b:Big)-[:R]->(s), (s:Small {name: 's3'}) RETURN count(b) AS n";
+    assert_eq!(count(&db, cypher), 60);
+    assert!(profiled(&db, cypher, "MatchOut(src=b").contains("invocations=0"));
+    assert!(profiled(&db, cypher, "IndexSeek(alias=s").contains("invocations=1"));
+
+    // EXPLAIN lists every alternative the executor may choose from.
+    let explain = prepare(&format!("EXPLAIN {cypher}")).unwrap();
+    let plan = explain.explain_string().unwrap();
+    assert!(plan.contains("CostChoice(alternatives=2)"), "{plan}");
+}
+
+#[test]
+fn t351_index_seek_is_preferred_over_scanning_the_smaller_label() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("cbo.ndb")).unwrap();
+    seed(&db);
+    db.compact().unwrap();
+    let cypher = "MATCH (b:Big {id: 7})-[:R]->(s:Small) RETURN count(s) AS n";
+
+    // Without an index on Big.id the five Small nodes are the cheaper anchor.
+    assert_eq!(count(&db, cypher), 1);
+    assert!(profiled(&db, cypher, "MatchOut(src=b").contains("invocations=0"));
+
+    db.create_index("Big", "id").unwrap();
+    assert_eq!(count(&db, cypher), 1);
+    assert!(profiled(&db, cypher, "MatchOut(src=b").contains("rows=1, invocations=1"));
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb_api::{GraphSnapshot, PropertyValue};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn names(db: &Db, cypher: &str) -> Vec<String> {
+    let snapshot = db.snapshot();
+    let mut out: Vec<String> = prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| match row.unwrap().get("name") {
+            Some(Value::String(s)) => s.clone(),
+            other => panic!("expected name, got {other:?}"),
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+fn lookup(db: &Db, predicates: &[(&str, &str)]) -> Option<usize> {
+    let predicates: Vec<_> = predicates
+        .iter()
+        .map(|(field, value)| (field.to_string(), PropertyValue::String(value.to_string())))
+        .collect();
+    db.snapshot()
+        .lookup_composite_index("User", &predicates)
+        .map(|nodes| nodes.len())
+}
+
+fn seed(db: &Db) {
+    write(
+        db,
+        "CREATE (:User {last_name: 'Lovelace', first_name: 'Ada', city: 'London'}), \
+                (:User {last_name: 'Lovelace', first_name: 'Byron', city: 'London'}), \
+                (:User {last_name: 'Hopper', first_name: 'Grace', city: 'NYC'})",
+    );
+}
+
+#[test]
+fn t352_composite_index_backfills_and_answers_equality_and_prefix_lookups() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("composite.ndb")).unwrap();
+    seed(&db);
+    db.create_composite_index("User", &["last_name", "first_name"])
+        .unwrap();
+
+    assert_eq!(
+        lookup(&db, &[("last_name", "Lovelace"), ("first_name", "Ada")]),
+        Some(1)
+    );
+    // The leading property alone is a prefix of every key.
+    assert_eq!(lookup(&db, &[("last_name", "Lovelace")]), Some(2));
+    assert_eq!(lookup(&db, &[("first_name", "Ada")]), None);
+
+    let cypher =
+        "MATCH (u:User {last_name: 'Lovelace', first_name: 'Ada'}) RETURN u.first_name AS name";
+    assert_eq!(names(&db, cypher), vec!["Ada"]);
+    let explain = prepare(&format!("EXPLAIN {cypher}")).unwrap();
+    let plan = explain.explain_string().unwrap();
+    assert!(
+        plan.contains("CompositeIndexSeek(alias=u, label=User"),
+        "{plan}"
+    );
+
+    // A prefix seek still leaves the remaining predicates to the filter.
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (u:User {last_name: 'Lovelace', city: 'London'}) RETURN u.first_name AS name"
+        ),
+        vec!["Ada", "Byron"]
+    );
+
+    assert!(db.create_composite_index("User", &["last_name"]).is_err());
+    assert!(db.create_composite_index("User", &["a", "a"]).is_err());
+}
+
+#[test]
+fn t352_composite_index_follows_writes_and_can_be_dropped() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("composite.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        db.create_composite_index("User", &["last_name", "first_name"])
+            .unwrap();
+        seed(&db);
+        assert_eq!(lookup(&db, &[("last_name", "Lovelace")]), Some(2));
+
+        write(
+            &db,
+            "MATCH (u:User {first_name: 'Byron'}) SET u.first_name = 'Augusta'",
+        );
+        assert_eq!(
+            lookup(&db, &[("last_name", "Lovelace"), ("first_name", "Byron")]),
+            None
+        );
+        assert_eq!(
+            lookup(&db, &[("last_name", "Lovelace"), ("first_name", "Augusta")]),
+            Some(1)
+        );
+
+        // Without every indexed property the node leaves the index.
+        write(
+            &db,
+            "MATCH (u:User {first_name: 'Ada'}) REMOVE u.first_name",
+        );
+        assert_eq!(lookup(&db, &[("last_name", "Lovelace")]), Some(1));
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    assert_eq!(lookup(&db, &[("last_name", "Hopper")]), Some(1));
+    assert!(db.drop_index("User", &["last_name", "first_name"]).unwrap());
+    assert!(!db.drop_index("User", &["last_name", "first_name"]).unwrap());
+    assert_eq!(lookup(&db, &[("last_name", "Hopper")]), None);
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (u:User {last_name: 'Lovelace', first_name: 'Augusta'}) RETURN u.first_name AS name"
+        ),
+        vec!["Augusta"]
+    );
+
+    db.create_index("User", "city").unwrap();
+    assert!(db.drop_index("User", &["city"]).unwrap());
+}
@@ -0,0 +1,69 @@
+use nervusdb::{Bookmark, Db};
+use nervusdb_query::{Params, prepare};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::tempdir;
+
+fn create_user(db: &Db, id: i64) -> Bookmark {
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let mut params = Params::new();
+    params.insert("id", nervusdb_query::Value::Int(id));
+    prepare("CREATE (:User {id: $id})")
+        .unwrap()
+        .execute_write(&snapshot, &mut txn, &params)
+        .unwrap();
+    txn.commit_with_bookmark().unwrap()
+}
+
+#[test]
+fn t353_every_commit_yields_a_newer_visible_bookmark() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("bookmarks.ndb");
+    let first = {
+        let db = Db::open(&path).unwrap();
+        let first = create_user(&db, 1);
+        let second = create_user(&db, 2);
+        assert!(second > first);
+        assert_eq!(db.last_bookmark(), second);
+        db.wait_for_bookmark(first, Duration::ZERO).unwrap();
+        db.close().unwrap();
+        first
+    };
+
+    // Bookmarks stay meaningful after reopening.
+    let db = Db::open(&path).unwrap();
+    assert!(db.last_bookmark() > first);
+    db.wait_for_bookmark(first, Duration::ZERO).unwrap();
+
+    let future = Bookmark::from(db.last_bookmark().txid() + 100);
+    let err = db
+        .wait_for_bookmark(future, Duration::from_millis(20))
+        .unwrap_err();
+    assert!(err.to_string().contains("not visible"), "{err}");
+}
+
+#[test]
+fn t353_reader_waits_for_a_commit_from_another_thread() {
+    let dir = tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("bookmarks.ndb")).unwrap());
+    let pending = Bookmark::from(db.last_bookmark().txid() + 1);
+
+    let reader = {
+        let db = Arc::clone(&db);
+        std::thread::spawn(move || {
+            db.wait_for_bookmark(pending, Duration::from_secs(10))
+                .unwrap();
+            let snapshot = db.snapshot();
+            prepare("MATCH (u:User) RETURN u")
+                .unwrap()
+                .execute_streaming(&snapshot, &Params::new())
+                .count()
+        })
+    };
+
+    std::thread::sleep(Duration::from_millis(20));
+    let committed = create_user(&db, 7);
+    assert!(committed >= pending);
+    assert_eq!(reader.join().unwrap(), 1);
+}
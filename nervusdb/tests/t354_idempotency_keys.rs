@@ -0,0 +1,75 @@
+use nervusdb::{Bookmark, Db};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+/// Creates one `Order` node under `key`, as a client retrying a request would.
+fn create_order(db: &Db, key: &str) -> (Bookmark, bool) {
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let already_applied = txn.set_idempotency_key(key).unwrap().is_some();
+    let mut params = Params::new();
+    params.insert("key", Value::String(key.to_string()));
+    prepare("CREATE (:Order {key: $key})")
+        .unwrap()
+        .execute_write(&snapshot, &mut txn, &params)
+        .unwrap();
+    (txn.commit_with_bookmark().unwrap(), already_applied)
+}
+
+fn order_count(db: &Db) -> i64 {
+    let snapshot = db.snapshot();
+    let rows: Vec<_> = prepare("MATCH (o:Order) RETURN count(o) AS n")
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    match rows[0].get("n") {
+        Some(Value::Int(n)) => *n,
+        other => panic!("expected count, got {other:?}"),
+    }
+}
+
+#[test]
+fn t354_retried_transaction_with_the_same_key_is_applied_once() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("idempotency.ndb")).unwrap();
+
+    let (first, applied) = create_order(&db, "order-1");
+    assert!(!applied);
+    let (retry, applied) = create_order(&db, "order-1");
+    assert!(applied);
+    assert_eq!(retry, first);
+    assert_eq!(order_count(&db), 1);
+    assert_eq!(db.idempotency_key_bookmark("order-1"), Some(first));
+
+    let (other, applied) = create_order(&db, "order-2");
+    assert!(!applied);
+    assert!(other > first);
+    assert_eq!(order_count(&db), 2);
+
+    assert!(db.begin_write().set_idempotency_key("").is_err());
+    assert_eq!(db.idempotency_key_bookmark("order-3"), None);
+}
+
+#[test]
+fn t354_idempotency_keys_survive_wal_replay_and_checkpoint() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("idempotency.ndb");
+    let first = {
+        let db = Db::open(&path).unwrap();
+        create_order(&db, "order-1").0
+    };
+
+    // Reopening after an unclean shutdown replays the key from the WAL.
+    {
+        let db = Db::open(&path).unwrap();
+        assert_eq!(create_order(&db, "order-1"), (first, true));
+        db.close().unwrap();
+    }
+
+    // A clean close checkpoints the WAL; the key is carried over.
+    let db = Db::open(&path).unwrap();
+    assert_eq!(db.idempotency_key_bookmark("order-1"), Some(first));
+    assert_eq!(create_order(&db, "order-1"), (first, true));
+    assert_eq!(order_count(&db), 1);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{count, try_write};
+use nervusdb::{BulkLoader, BulkNode, Db, PropertyValue};
+use nervusdb_query::{Params, Value, prepare};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+#[test]
+fn t355_unique_constraint_rejects_duplicate_writes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("unique.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        try_write(&db, "CREATE (:User {email: 'a@x'}), (:User {email: 'b@x'})").unwrap();
+        db.create_unique_constraint("User", "email").unwrap();
+        assert_eq!(
+            db.unique_constraints(),
+            vec![("User".to_string(), "email".to_string())]
+        );
+
+        let err = try_write(&db, "CREATE (:User {email: 'a@x'})").unwrap_err();
+        assert!(err.to_string().contains("constraint violation"), "{err}");
+        assert!(try_write(&db, "CREATE (:User {email: 'c@x'}), (:User {email: 'c@x'})").is_err());
+        assert!(try_write(&db, "MATCH (u:User {email: 'b@x'}) SET u.email = 'a@x'").is_err());
+        // Other labels, null and MERGE onto the existing node are fine.
+        try_write(&db, "CREATE (:Admin {email: 'a@x'}), (:User)").unwrap();
+        try_write(&db, "MERGE (:User {email: 'a@x'})").unwrap();
+        // Giving an existing node the label counts too.
+        assert!(try_write(&db, "MATCH (a:Admin) SET a:User").is_err());
+
+        // A value freed in the same transaction can be reused.
+        try_write(
+            &db,
+            "MATCH (u:User {email: 'a@x'}) SET u.email = 'old@x' CREATE (:User {email: 'a@x'})",
+        )
+        .unwrap();
+        try_write(&db, "MATCH (u:User {email: 'old@x'}) DELETE u").unwrap();
+        try_write(&db, "CREATE (:User {email: 'old@x'})").unwrap();
+        assert_eq!(count(&db, "MATCH (u:User) RETURN count(u) AS n"), 4);
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let node = prepare("MATCH (u:User {email: 'b@x'}) RETURN u")
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .next()
+        .unwrap()
+        .unwrap();
+    let Some(Value::NodeId(node)) = node.get("u").cloned() else {
+        panic!("expected node");
+    };
+    assert!(
+        txn.set_node_property(
+            node,
+            "email".to_string(),
+            PropertyValue::String("a@x".into())
+        )
+        .is_err()
+    );
+    drop(txn);
+
+    assert!(db.drop_unique_constraint("User", "email").unwrap());
+    assert!(!db.drop_unique_constraint("User", "email").unwrap());
+    try_write(&db, "CREATE (:User {email: 'a@x'})").unwrap();
+    assert!(db.create_unique_constraint("User", "email").is_err());
+}
+
+#[test]
+fn t355_bulkload_enforces_declared_unique_constraints() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("bulk.ndb");
+    let user = |external_id, email: &str| BulkNode {
+        external_id,
+        label: "User".to_string(),
+        properties: BTreeMap::from([(
+            "email".to_string(),
+            PropertyValue::String(email.to_string()),
+        )]),
+    };
+    let mut loader = BulkLoader::new(path.clone()).unwrap();
+    loader.add_unique_constraint("User", "email").unwrap();
+    loader.add_node(user(1, "a@x")).unwrap();
+    loader.add_node(user(2, "b@x")).unwrap();
+    loader.commit().unwrap();
+
+    let db = Db::open(&path).unwrap();
+    assert!(try_write(&db, "CREATE (:User {email: 'b@x'})").is_err());
+    assert_eq!(
+        count(&db, "MATCH (u:User {email: 'a@x'}) RETURN count(u) AS n"),
+        1
+    );
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{count, try_write};
+use nervusdb::Db;
+use tempfile::tempdir;
+
+#[test]
+fn t356_rename_property_moves_values_and_constraints() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("rename_property.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        try_write(
+            &db,
+            "CREATE (:User {mail: 'a@x'}), (:User {mail: 'b@x'}), (:User), (:Post {mail: 'p@x'})",
+        )
+        .unwrap();
+        db.create_unique_constraint("User", "mail").unwrap();
+        db.create_index("User", "mail").unwrap();
+
+        let mut reports = Vec::new();
+        let renamed = db
+            .rename_property_with_progress("User", "mail", "email", |done, total| {
+                reports.push((done, total))
+            })
+            .unwrap();
+        assert_eq!(renamed, 2);
+        assert_eq!(reports, vec![(2, 2)]);
+
+        assert_eq!(
+            count(
+                &db,
+                "MATCH (u:User) WHERE u.email IS NOT NULL RETURN count(u) AS n"
+            ),
+            2
+        );
+        assert_eq!(
+            count(
+                &db,
+                "MATCH (u:User) WHERE u.mail IS NOT NULL RETURN count(u) AS n"
+            ),
+            0
+        );
+        assert_eq!(
+            count(&db, "MATCH (u:User {email: 'a@x'}) RETURN count(u) AS n"),
+            1
+        );
+        // Other labels keep the old name.
+        assert_eq!(
+            count(&db, "MATCH (p:Post {mail: 'p@x'}) RETURN count(p) AS n"),
+            1
+        );
+        assert_eq!(
+            db.unique_constraints(),
+            vec![("User".to_string(), "email".to_string())]
+        );
+        let err = try_write(&db, "CREATE (:User {email: 'a@x'})").unwrap_err();
+        assert!(err.to_string().contains("constraint violation"), "{err}");
+
+        // The target name must be free on every renamed node.
+        try_write(&db, "CREATE (:User {email: 'c@x', alias: 'c@x'})").unwrap();
+        assert!(db.rename_property("User", "alias", "email").is_err());
+        assert_eq!(
+            count(
+                &db,
+                "MATCH (u:User) WHERE u.alias IS NOT NULL RETURN count(u) AS n"
+            ),
+            1
+        );
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        count(&db, "MATCH (u:User {email: 'b@x'}) RETURN count(u) AS n"),
+        1
+    );
+    assert_eq!(
+        count(
+            &db,
+            "MATCH (u:User) WHERE u.mail IS NOT NULL RETURN count(u) AS n"
+        ),
+        0
+    );
+    assert_eq!(
+        db.unique_constraints(),
+        vec![("User".to_string(), "email".to_string())]
+    );
+}
+
+#[test]
+fn t356_rename_label_keeps_nodes_and_indexes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("rename_label.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        try_write(
+            &db,
+            "CREATE (:User {email: 'a@x'}), (:User {email: 'b@x'}), (:Team)",
+        )
+        .unwrap();
+        db.create_unique_constraint("User", "email").unwrap();
+
+        assert!(db.rename_label("User", "Customer").unwrap());
+        assert!(!db.rename_label("User", "Customer").unwrap());
+        assert!(db.rename_label("Customer", "Team").is_err());
+
+        assert_eq!(count(&db, "MATCH (c:Customer) RETURN count(c) AS n"), 2);
+        assert_eq!(count(&db, "MATCH (u:User) RETURN count(u) AS n"), 0);
+        assert_eq!(
+            db.unique_constraints(),
+            vec![("Customer".to_string(), "email".to_string())]
+        );
+        assert!(try_write(&db, "CREATE (:Customer {email: 'a@x'})").is_err());
+        // The old name is free again.
+        try_write(&db, "CREATE (:User {email: 'a@x'})").unwrap();
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    assert_eq!(count(&db, "MATCH (c:Customer) RETURN count(c) AS n"), 2);
+    assert_eq!(count(&db, "MATCH (u:User) RETURN count(u) AS n"), 1);
+    assert!(try_write(&db, "CREATE (:Customer {email: 'b@x'})").is_err());
+}
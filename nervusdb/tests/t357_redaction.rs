@@ -0,0 +1,79 @@
+mod common;
+
+use common::{column, write};
+use nervusdb::{Db, REDACTED};
+use nervusdb_query::Value;
+use tempfile::tempdir;
+
+fn redacted() -> Value {
+    Value::String(REDACTED.to_string())
+}
+
+#[test]
+fn t357_redaction_masks_sensitive_properties_per_handle() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("redaction.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        write(
+            &db,
+            "CREATE (:User {name: 'ann', ssn: '123'})-[:PAID {card: '4111'}]->(:Shop {ssn: 'shop'})",
+        );
+        db.create_index("User", "ssn").unwrap();
+        assert!(db.mark_sensitive("User", "ssn").unwrap());
+        assert!(!db.mark_sensitive("User", "ssn").unwrap());
+        assert!(db.mark_sensitive("PAID", "card").unwrap());
+
+        // Redaction is off until the handle opts in.
+        assert_eq!(
+            column(&db, "MATCH (u:User) RETURN u.ssn"),
+            vec![Value::String("123".to_string())]
+        );
+
+        db.set_redaction(true);
+        assert_eq!(column(&db, "MATCH (u:User) RETURN u.ssn"), vec![redacted()]);
+        assert_eq!(
+            column(&db, "MATCH (u:User) RETURN u.name"),
+            vec![Value::String("ann".to_string())]
+        );
+        assert_eq!(
+            column(&db, "MATCH ()-[p:PAID]->() RETURN p.card"),
+            vec![redacted()]
+        );
+        // Other labels keep the property visible.
+        assert_eq!(
+            column(&db, "MATCH (s:Shop) RETURN s.ssn"),
+            vec![Value::String("shop".to_string())]
+        );
+        // Whole nodes are masked too, and the index cannot be used to probe.
+        let nodes = column(&db, "MATCH (u:User) RETURN u");
+        let Value::Node(node) = &nodes[0] else {
+            panic!("expected node, got {:?}", nodes[0]);
+        };
+        assert_eq!(node.properties.get("ssn"), Some(&redacted()));
+        assert!(column(&db, "MATCH (u:User {ssn: '123'}) RETURN u.name").is_empty());
+
+        db.set_redaction(false);
+        assert_eq!(
+            column(&db, "MATCH (u:User {ssn: '123'}) RETURN u.name").len(),
+            1
+        );
+        db.close().unwrap();
+    }
+
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        db.sensitive_properties(),
+        vec![
+            ("PAID".to_string(), "card".to_string()),
+            ("User".to_string(), "ssn".to_string())
+        ]
+    );
+    assert!(db.unmark_sensitive("PAID", "card").unwrap());
+    db.set_redaction(true);
+    assert_eq!(
+        column(&db, "MATCH ()-[p:PAID]->() RETURN p.card"),
+        vec![Value::String("4111".to_string())]
+    );
+    assert_eq!(column(&db, "MATCH (u:User) RETURN u.ssn"), vec![redacted()]);
+}
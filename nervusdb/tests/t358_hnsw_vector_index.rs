@@ -0,0 +1,96 @@
+use nervusdb::{Db, EngineOptions, HnswParams, InternalNodeId};
+use tempfile::tempdir;
+
+const DIM: usize = 8;
+const COUNT: u64 = 1100;
+
+/// Deterministic pseudo-random vector for `seed`.
+fn vector(seed: u64) -> Vec<f32> {
+    let mut state = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    (0..DIM)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 40) as f32 / (1u64 << 24) as f32) - 0.5
+        })
+        .collect()
+}
+
+fn load(db: &Db) -> Vec<InternalNodeId> {
+    let mut ids = Vec::new();
+    for batch in (0..COUNT).collect::<Vec<_>>().chunks(250) {
+        let mut txn = db.begin_write();
+        let label = txn.get_or_create_label("Doc").unwrap();
+        for &i in batch {
+            let node = txn.create_node(i + 1, label).unwrap();
+            txn.set_vector(node, vector(i)).unwrap();
+            ids.push(node);
+        }
+        txn.commit().unwrap();
+    }
+    ids
+}
+
+fn nearest(hits: &[(InternalNodeId, f32)]) -> Vec<InternalNodeId> {
+    hits.iter().map(|(id, _)| *id).collect()
+}
+
+#[test]
+fn t358_hnsw_index_matches_exact_search_and_survives_reopen() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vectors.ndb");
+    let ids = {
+        let db = Db::open(&path).unwrap();
+        let ids = load(&db);
+        for i in [0u64, 7, 499, 1099] {
+            let hits = db.search_vector(&vector(i), 1).unwrap();
+            assert_eq!(nearest(&hits), vec![ids[i as usize]]);
+        }
+        let exact = db.search_vector_exact(&vector(3), 5).unwrap();
+        assert_eq!(exact.len(), 5);
+        assert_eq!(exact[0].0, ids[3]);
+        assert!(exact.windows(2).all(|w| w[0].1 <= w[1].1));
+        db.close().unwrap();
+        ids
+    };
+
+    let db = Db::open(&path).unwrap();
+    for i in [1u64, 250, 800, 1098] {
+        let hits = db.search_vector(&vector(i), 1).unwrap();
+        assert_eq!(nearest(&hits), vec![ids[i as usize]]);
+    }
+    let query = vector(COUNT + 1);
+    let approx = db.search_vector(&query, 10).unwrap();
+    let exact = db.search_vector_exact(&query, 10).unwrap();
+    let found = nearest(&approx)
+        .iter()
+        .filter(|id| nearest(&exact).contains(id))
+        .count();
+    assert!(found >= 8, "recall@10 too low: {found}/10");
+}
+
+#[test]
+fn t358_open_options_tune_hnsw_and_force_exact_search() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("exact.ndb");
+    let options = EngineOptions {
+        hnsw: HnswParams {
+            m: 8,
+            ef_construction: 64,
+            ef_search: 32,
+        },
+        exact_vector_search: true,
+        ..EngineOptions::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    assert_eq!(db.hnsw_params().m, 8);
+    load(&db);
+
+    let query = vector(COUNT + 7);
+    let hits = db.search_vector(&query, 10).unwrap();
+    assert_eq!(hits, db.search_vector_exact(&query, 10).unwrap());
+    assert!(db.search_vector(&query, 0).unwrap().is_empty());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+#[test]
+fn t359_count_rows_matches_streamed_row_count() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("count.ndb")).unwrap();
+    write(
+        &db,
+        "UNWIND range(1, 25) AS i CREATE (:Item {n: i, bucket: i % 3})",
+    );
+    let snapshot = db.snapshot();
+
+    for cypher in [
+        "MATCH (i:Item) RETURN i",
+        "MATCH (i:Item) RETURN i.n, i.bucket SKIP 5 LIMIT 10",
+        "MATCH (i:Item) RETURN i.n SKIP 20 LIMIT 10",
+        "MATCH (i:Item) WHERE i.n > 10 RETURN i ORDER BY i.n DESC LIMIT 4",
+        "MATCH (i:Item) RETURN DISTINCT i.bucket",
+        "MATCH (i:Item) RETURN i.bucket, count(*)",
+        "MATCH (i:Item) WITH i WHERE i.bucket = 0 RETURN i.n",
+        "MATCH (i:Missing) RETURN i",
+        "EXPLAIN MATCH (i:Item) RETURN i",
+    ] {
+        let query = prepare(cypher).unwrap();
+        let streamed = query.execute_streaming(&snapshot, &Params::new()).count() as u64;
+        assert_eq!(
+            query.count_rows(&snapshot, &Params::new()).unwrap(),
+            streamed,
+            "{cypher}"
+        );
+    }
+
+    let mut params = Params::new();
+    params.insert("min", Value::Int(20));
+    let query = prepare("MATCH (i:Item) WHERE i.n >= $min RETURN i.n").unwrap();
+    assert_eq!(query.count_rows(&snapshot, &params).unwrap(), 6);
+}
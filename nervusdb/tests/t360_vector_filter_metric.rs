@@ -0,0 +1,110 @@
+use nervusdb::{Db, InternalNodeId, PropertyValue, VectorFilter, VectorMetric};
+use tempfile::tempdir;
+
+fn lang(value: &str) -> Option<(String, PropertyValue)> {
+    Some(("lang".to_string(), PropertyValue::String(value.to_string())))
+}
+
+fn ids(hits: &[(InternalNodeId, f32)]) -> Vec<InternalNodeId> {
+    hits.iter().map(|(id, _)| *id).collect()
+}
+
+#[test]
+fn t360_filtered_search_by_label_property_and_metric() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("filtered.ndb")).unwrap();
+
+    let mut txn = db.begin_write();
+    let doc = txn.get_or_create_label("Document").unwrap();
+    let img = txn.get_or_create_label("Image").unwrap();
+    let mut add = |ext: u64, label, lang: &str, vector: Vec<f32>| {
+        let node = txn.create_node(ext, label).unwrap();
+        txn.set_node_property(node, "lang".to_string(), PropertyValue::String(lang.into()))
+            .unwrap();
+        txn.set_vector(node, vector).unwrap();
+        node
+    };
+    let img_en = add(1, img, "en", vec![1.0, 0.0]);
+    let doc_de = add(2, doc, "de", vec![0.9, 0.1]);
+    let doc_en_far = add(3, doc, "en", vec![4.0, 4.0]);
+    let doc_en_near = add(4, doc, "en", vec![0.5, 0.5]);
+    txn.commit().unwrap();
+
+    let query = [1.0, 0.0];
+    let all = db
+        .search_vector_filtered(&query, 4, VectorMetric::L2, &VectorFilter::default())
+        .unwrap();
+    assert_eq!(ids(&all)[0], img_en);
+    assert_eq!(all.len(), 4);
+
+    let english_docs = VectorFilter {
+        label: Some("Document".to_string()),
+        property: lang("en"),
+    };
+    let hits = db
+        .search_vector_filtered(&query, 5, VectorMetric::L2, &english_docs)
+        .unwrap();
+    assert_eq!(ids(&hits), vec![doc_en_near, doc_en_far]);
+
+    // Cosine ignores magnitude: [4, 4] and [0.5, 0.5] point the same way.
+    let docs = VectorFilter {
+        label: Some("Document".to_string()),
+        property: None,
+    };
+    let cosine = db
+        .search_vector_filtered(&query, 3, VectorMetric::Cosine, &docs)
+        .unwrap();
+    assert_eq!(ids(&cosine)[0], doc_de);
+    assert!((cosine[1].1 - cosine[2].1).abs() < 1e-6);
+
+    // Dot product favours the long vector.
+    let dot = db
+        .search_vector_filtered(&query, 1, VectorMetric::Dot, &docs)
+        .unwrap();
+    assert_eq!(ids(&dot), vec![doc_en_far]);
+    assert_eq!(dot[0].1, -4.0);
+
+    let unknown = VectorFilter {
+        label: Some("Missing".to_string()),
+        property: None,
+    };
+    assert!(
+        db.search_vector_filtered(&query, 3, VectorMetric::L2, &unknown)
+            .unwrap()
+            .is_empty()
+    );
+
+    db.mark_sensitive("Document", "lang").unwrap();
+    db.set_redaction(true);
+    assert!(
+        db.search_vector_filtered(&query, 3, VectorMetric::L2, &english_docs)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn t360_selective_filter_falls_back_to_a_scan() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("selective.ndb")).unwrap();
+
+    let mut txn = db.begin_write();
+    let common = txn.get_or_create_label("Common").unwrap();
+    let rare = txn.get_or_create_label("Rare").unwrap();
+    for i in 0..200u64 {
+        let node = txn.create_node(i + 1, common).unwrap();
+        txn.set_vector(node, vec![i as f32 * 0.01, 0.0]).unwrap();
+    }
+    let far = txn.create_node(1000, rare).unwrap();
+    txn.set_vector(far, vec![100.0, 100.0]).unwrap();
+    txn.commit().unwrap();
+
+    let filter = VectorFilter {
+        label: Some("Rare".to_string()),
+        property: None,
+    };
+    let hits = db
+        .search_vector_filtered(&[0.0, 0.0], 1, VectorMetric::L2, &filter)
+        .unwrap();
+    assert_eq!(ids(&hits), vec![far]);
+}
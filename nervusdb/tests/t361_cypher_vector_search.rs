@@ -0,0 +1,107 @@
+use nervusdb::{Db, InternalNodeId};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn rows(db: &Db, cypher: &str, params: &Params) -> Vec<Vec<(String, Value)>> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, params)
+        .map(|row| {
+            let row = row.unwrap();
+            row.columns().to_vec()
+        })
+        .collect()
+}
+
+fn embedding(values: &[f64]) -> Params {
+    let mut params = Params::new();
+    params.insert(
+        "embedding",
+        Value::List(values.iter().map(|v| Value::Float(*v)).collect()),
+    );
+    params
+}
+
+/// Three documents on a line, each written by an author.
+fn load(db: &Db) -> Vec<InternalNodeId> {
+    let mut docs = Vec::new();
+    let mut txn = db.begin_write();
+    let doc = txn.get_or_create_label("Doc").unwrap();
+    let author = txn.get_or_create_label("Author").unwrap();
+    let wrote = txn.get_or_create_rel_type("WROTE").unwrap();
+    for i in 0..3u64 {
+        let d = txn.create_node(10 + i, doc).unwrap();
+        txn.set_node_property(d, "title".to_string(), format!("doc{i}").into())
+            .unwrap();
+        txn.set_vector(d, vec![i as f32, 0.0]).unwrap();
+        docs.push(d);
+        let a = txn.create_node(20 + i, author).unwrap();
+        txn.set_node_property(a, "name".to_string(), format!("author{i}").into())
+            .unwrap();
+        txn.create_edge(a, wrote, d);
+    }
+    txn.commit().unwrap();
+    docs
+}
+
+#[test]
+fn t361_vector_search_yields_nodes_joinable_with_match() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("vectors.ndb")).unwrap();
+    load(&db);
+
+    let hits = rows(
+        &db,
+        "CALL vector.search($embedding, 2) YIELD node, score \
+         MATCH (a:Author)-[:WROTE]->(node) \
+         RETURN node.title AS title, a.name AS author, score",
+        &embedding(&[2.1, 0.0]),
+    );
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0][0].1, Value::String("doc2".to_string()));
+    assert_eq!(hits[0][1].1, Value::String("author2".to_string()));
+    assert_eq!(hits[1][0].1, Value::String("doc1".to_string()));
+    let (Value::Float(nearest), Value::Float(next)) = (&hits[0][2].1, &hits[1][2].1) else {
+        panic!("score should be a float: {hits:?}");
+    };
+    assert!(nearest < next);
+
+    let total = rows(
+        &db,
+        "CALL vector.search([0, 0], 10) YIELD node RETURN count(node) AS n",
+        &Params::new(),
+    );
+    assert_eq!(total[0][0].1, Value::Int(3));
+}
+
+#[test]
+fn t361_vector_search_skips_deleted_nodes_and_rejects_bad_arguments() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("deleted.ndb")).unwrap();
+    let docs = load(&db);
+
+    let mut txn = db.begin_write();
+    txn.tombstone_node(docs[0]);
+    txn.commit().unwrap();
+
+    let hits = rows(
+        &db,
+        "CALL vector.search($embedding, 1) YIELD node RETURN node.title",
+        &embedding(&[0.0, 0.0]),
+    );
+    assert_eq!(hits[0][0].1, Value::String("doc1".to_string()));
+
+    let snapshot = db.snapshot();
+    for cypher in [
+        "CALL vector.search('text', 1) YIELD node RETURN node",
+        "CALL vector.search([0, 0], -1) YIELD node RETURN node",
+        "CALL vector.search([0, 0]) YIELD node RETURN node",
+    ] {
+        let result: Result<Vec<_>, _> = prepare(cypher)
+            .unwrap()
+            .execute_streaming(&snapshot, &Params::new())
+            .collect();
+        assert!(result.is_err(), "{cypher}");
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+/// Collects every page of `cypher`, returning the first column of each row.
+fn all_pages(db: &Db, cypher: &str, page_size: usize) -> Vec<Vec<Value>> {
+    let query = prepare(cypher).unwrap();
+    let snapshot = db.snapshot();
+    let mut pages = Vec::new();
+    let mut token = None;
+    loop {
+        let page = query
+            .execute_page(&snapshot, &Params::new(), page_size, token.as_deref())
+            .unwrap();
+        assert!(page.rows.len() <= page_size);
+        pages.push(page.rows.iter().map(|r| r.columns()[0].1.clone()).collect());
+        match page.next_token {
+            Some(next) => token = Some(next),
+            None => return pages,
+        }
+    }
+}
+
+fn unpaged(db: &Db, cypher: &str) -> Vec<Value> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| row.unwrap().columns()[0].1.clone())
+        .collect()
+}
+
+#[test]
+fn t362_pages_concatenate_to_the_full_ordered_result() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("pages.ndb")).unwrap();
+    write(
+        &db,
+        "UNWIND range(1, 23) AS i CREATE (:Item {n: i, bucket: i % 4})",
+    );
+
+    for cypher in [
+        "MATCH (i:Item) RETURN i.n AS n ORDER BY n",
+        "MATCH (i:Item) RETURN i.n AS n ORDER BY n DESC",
+        // Ties on `bucket` resume by position among the tied rows.
+        "MATCH (i:Item) RETURN i.bucket AS b ORDER BY b",
+        "MATCH (i:Item) RETURN i.n AS n ORDER BY i.bucket DESC, n",
+    ] {
+        let pages = all_pages(&db, cypher, 5);
+        assert_eq!(pages.len(), 5, "{cypher}");
+        assert_eq!(pages.concat(), unpaged(&db, cypher), "{cypher}");
+    }
+    assert_eq!(
+        all_pages(&db, "MATCH (i:Item) RETURN i.n ORDER BY i.n", 23).len(),
+        1
+    );
+}
+
+#[test]
+fn t362_tokens_resume_after_writes_and_reject_misuse() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("resume.ndb")).unwrap();
+    write(&db, "UNWIND range(1, 10) AS i CREATE (:Item {n: i * 10})");
+
+    let query = prepare("MATCH (i:Item) RETURN i.n ORDER BY i.n").unwrap();
+    let first = query
+        .execute_page(&db.snapshot(), &Params::new(), 4, None)
+        .unwrap();
+    let token = first.next_token.unwrap();
+
+    // A row sorting before the cursor does not shift the next page.
+    write(&db, "CREATE (:Item {n: 5})");
+    let second = query
+        .execute_page(&db.snapshot(), &Params::new(), 4, Some(&token))
+        .unwrap();
+    let values: Vec<_> = second
+        .rows
+        .iter()
+        .map(|r| r.columns()[0].1.clone())
+        .collect();
+    assert_eq!(values, [50, 60, 70, 80].map(Value::Int).to_vec());
+
+    let snapshot = db.snapshot();
+    let other = prepare("MATCH (i:Item) RETURN i.n ORDER BY i.n DESC").unwrap();
+    assert!(
+        other
+            .execute_page(&snapshot, &Params::new(), 4, Some(&token))
+            .is_err()
+    );
+    assert!(
+        query
+            .execute_page(&snapshot, &Params::new(), 4, Some("not-a-token"))
+            .is_err()
+    );
+    assert!(
+        query
+            .execute_page(&snapshot, &Params::new(), 0, None)
+            .is_err()
+    );
+    for cypher in [
+        "MATCH (i:Item) RETURN i.n",
+        "MATCH (i:Item) RETURN i.n ORDER BY i.n LIMIT 3",
+        "EXPLAIN MATCH (i:Item) RETURN i.n ORDER BY i.n",
+    ] {
+        assert!(
+            prepare(cypher)
+                .unwrap()
+                .execute_page(&snapshot, &Params::new(), 4, None)
+                .is_err(),
+            "{cypher}"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use nervusdb::{Db, WriteTxn};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn run(db: &Db, txn: &mut WriteTxn<'_>, cypher: &str) {
+    let snapshot = db.snapshot_in(txn);
+    prepare(cypher)
+        .unwrap()
+        .execute_mixed(&snapshot, txn, &Params::new())
+        .unwrap();
+}
+
+fn names(snapshot: &nervusdb::DbSnapshot, cypher: &str) -> Vec<Value> {
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(snapshot, &Params::new())
+        .map(|row| row.unwrap().columns()[0].1.clone())
+        .collect()
+}
+
+fn strings(values: &[&str]) -> Vec<Value> {
+    values
+        .iter()
+        .map(|s| Value::String(s.to_string()))
+        .collect()
+}
+
+#[test]
+fn t363_statements_in_a_transaction_see_its_staged_writes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("ryw.ndb")).unwrap();
+    let mut txn = db.begin_write();
+    run(
+        &db,
+        &mut txn,
+        "CREATE (:User {name: 'ada'}), (:User {name: 'bob'})",
+    );
+    txn.commit().unwrap();
+
+    let mut txn = db.begin_write();
+    run(&db, &mut txn, "CREATE (:User {name: 'cy'})");
+    run(
+        &db,
+        &mut txn,
+        "MATCH (a:User {name: 'ada'}), (c:User {name: 'cy'}) CREATE (a)-[:KNOWS]->(c)",
+    );
+    run(
+        &db,
+        &mut txn,
+        "MATCH (b:User {name: 'bob'}) DETACH DELETE b",
+    );
+    run(&db, &mut txn, "MATCH (c:User {name: 'cy'}) SET c:Admin");
+
+    let staged = db.snapshot_in(&txn);
+    let query = "MATCH (u:User) RETURN u.name ORDER BY u.name";
+    assert_eq!(names(&staged, query), strings(&["ada", "cy"]));
+    assert_eq!(
+        names(&staged, "MATCH (:User)-[:KNOWS]->(f) RETURN f.name"),
+        strings(&["cy"])
+    );
+    assert_eq!(
+        names(&staged, "MATCH (a:Admin) RETURN a.name"),
+        strings(&["cy"])
+    );
+    // Other readers only see committed state.
+    assert_eq!(names(&db.snapshot(), query), strings(&["ada", "bob"]));
+
+    txn.commit().unwrap();
+    assert_eq!(names(&db.snapshot(), query), strings(&["ada", "cy"]));
+}
+
+#[test]
+fn t363_rolled_back_writes_stay_invisible() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("rollback.ndb")).unwrap();
+    let mut txn = db.begin_write();
+    run(&db, &mut txn, "CREATE (:User {name: 'ada'})");
+    assert_eq!(
+        names(&db.snapshot_in(&txn), "MATCH (u:User) RETURN u.name"),
+        strings(&["ada"])
+    );
+    txn.rollback();
+    assert!(names(&db.snapshot(), "MATCH (u:User) RETURN u.name").is_empty());
+}
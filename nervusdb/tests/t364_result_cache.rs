@@ -0,0 +1,86 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb_query::{Params, ResultCache, ResultKey, Row, Value, prepare};
+use std::sync::Arc;
+use tempfile::tempdir;
+
+fn read(db: &Db, cache: &ResultCache, cypher: &str) -> Arc<Vec<Row>> {
+    let params = Params::new();
+    let key = ResultKey::new(cypher, &params);
+    if let Some(rows) = cache.get(key, |read_set, seq| db.changed_since(seq, read_set)) {
+        return rows;
+    }
+    let query = prepare(cypher).unwrap();
+    let seq = db.write_seq();
+    let rows: Vec<Row> = query
+        .execute_streaming(&db.snapshot(), &params)
+        .map(Result::unwrap)
+        .collect();
+    let rows = Arc::new(rows);
+    cache.insert(key, query.read_set().unwrap(), seq, Arc::clone(&rows));
+    rows
+}
+
+fn count(rows: &[Row]) -> Value {
+    rows[0].columns()[0].1.clone()
+}
+
+#[test]
+fn t364_writes_invalidate_only_results_reading_what_they_touched() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("cache.ndb")).unwrap();
+    write(
+        &db,
+        "CREATE (:User {name: 'ada'})-[:KNOWS]->(:User {name: 'bob'})",
+    );
+    let cache = ResultCache::new(16, None);
+    let users = "MATCH (u:User) RETURN count(u) AS n";
+    let knows = "MATCH (:User)-[k:KNOWS]->(:User) RETURN count(k) AS n";
+
+    assert_eq!(count(&read(&db, &cache, users)), Value::Int(2));
+    assert_eq!(count(&read(&db, &cache, knows)), Value::Int(1));
+    assert_eq!(count(&read(&db, &cache, users)), Value::Int(2));
+    assert_eq!(cache.stats().hits, 1);
+
+    // Writes to unrelated labels leave both results cached.
+    write(&db, "CREATE (:Order {total: 10})");
+    read(&db, &cache, users);
+    read(&db, &cache, knows);
+    assert_eq!(cache.stats().hits, 3);
+
+    // A new relationship type leaves both cached; a new :User invalidates
+    // both, since the edge count matches :User endpoints.
+    write(
+        &db,
+        "MATCH (a:User {name: 'ada'}), (b:User {name: 'bob'}) CREATE (b)-[:FOLLOWS]->(a)",
+    );
+    read(&db, &cache, users);
+    read(&db, &cache, knows);
+    assert_eq!(cache.stats().hits, 5);
+    write(&db, "CREATE (:User {name: 'cy'})");
+    assert_eq!(count(&read(&db, &cache, users)), Value::Int(3));
+    assert_eq!(count(&read(&db, &cache, knows)), Value::Int(1));
+    assert_eq!(cache.stats().invalidations, 2);
+
+    // Deleting a node hides its edges, whatever their type.
+    write(&db, "MATCH (b:User {name: 'bob'}) DETACH DELETE b");
+    assert_eq!(count(&read(&db, &cache, knows)), Value::Int(0));
+}
+
+#[test]
+fn t364_write_seq_only_advances_on_data_changes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("seq.ndb")).unwrap();
+    let start = db.write_seq();
+    db.begin_write().commit().unwrap();
+    assert_eq!(db.write_seq(), start);
+    write(&db, "CREATE (:User)");
+    assert!(db.write_seq() > start);
+    let read_set = prepare("MATCH (o:Order) RETURN o")
+        .unwrap()
+        .read_set()
+        .unwrap();
+    assert!(!db.changed_since(start, &read_set));
+}
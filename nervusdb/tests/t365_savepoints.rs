@@ -0,0 +1,79 @@
+use nervusdb::{Db, PropertyValue, WriteTxn};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn run(db: &Db, txn: &mut WriteTxn<'_>, cypher: &str) {
+    let snapshot = db.snapshot_in(txn);
+    prepare(cypher)
+        .unwrap()
+        .execute_mixed(&snapshot, txn, &Params::new())
+        .unwrap();
+}
+
+fn first(db: &Db, cypher: &str) -> Value {
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &Params::new())
+        .map(|row| row.unwrap().columns()[0].1.clone())
+        .next()
+        .unwrap_or(Value::Null)
+}
+
+#[test]
+fn t365_rollback_to_discards_only_later_changes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("savepoints.ndb")).unwrap();
+    let mut txn = db.begin_write();
+    run(&db, &mut txn, "CREATE (:User {name: 'ada', score: 1})");
+    txn.commit().unwrap();
+
+    let mut txn = db.begin_write();
+    run(&db, &mut txn, "CREATE (:User {name: 'bob'})");
+    let savepoint = txn.savepoint();
+    run(
+        &db,
+        &mut txn,
+        "MATCH (a:User {name: 'ada'}) SET a.score = 2, a:Admin",
+    );
+    run(
+        &db,
+        &mut txn,
+        "MATCH (a:User {name: 'ada'}), (b:User {name: 'bob'}) CREATE (a)-[:KNOWS]->(b)",
+    );
+    run(&db, &mut txn, "CREATE (:User {name: 'cy'})");
+    txn.rollback_to(&savepoint).unwrap();
+
+    // New nodes after the rollback reuse the discarded ids cleanly.
+    let label = txn.get_or_create_label("User").unwrap();
+    let dee = txn.create_node(42, label).unwrap();
+    txn.set_node_property(dee, "name".to_string(), PropertyValue::String("dee".into()))
+        .unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(
+        first(&db, "MATCH (u:User) RETURN collect(u.name) AS names"),
+        Value::List(vec![
+            Value::String("ada".into()),
+            Value::String("bob".into()),
+            Value::String("dee".into()),
+        ])
+    );
+    assert_eq!(
+        first(&db, "MATCH (a:User {name: 'ada'}) RETURN a.score"),
+        Value::Int(1)
+    );
+    assert_eq!(first(&db, "MATCH (a:Admin) RETURN count(a)"), Value::Int(0));
+    assert_eq!(
+        first(&db, "MATCH ()-[k:KNOWS]->() RETURN count(k)"),
+        Value::Int(0)
+    );
+}
+
+#[test]
+fn t365_savepoints_do_not_cross_transactions() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("foreign.ndb")).unwrap();
+    let savepoint = db.begin_write().savepoint();
+    let mut txn = db.begin_write();
+    assert!(txn.rollback_to(&savepoint).is_err());
+}
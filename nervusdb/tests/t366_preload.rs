@@ -0,0 +1,68 @@
+mod common;
+
+use common::write;
+use nervusdb::{Db, EngineOptions};
+use nervusdb_query::{Params, prepare};
+use tempfile::tempdir;
+
+fn seed(db: &Db) {
+    write(
+        db,
+        "UNWIND range(1, 200) AS i CREATE (:User {name: 'user-' + toString(i), bio: 'x'})",
+    );
+    write(db, "UNWIND range(1, 50) AS i CREATE (:Order {total: i})");
+    db.compact().unwrap();
+}
+
+#[test]
+fn t366_preload_labels_reads_their_property_pages_once() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("preload.ndb");
+    let db = Db::open(&path).unwrap();
+    seed(&db);
+    db.close().unwrap();
+    std::fs::remove_file(path.with_extension("warm")).unwrap();
+
+    let db = Db::open(&path).unwrap();
+    let loaded = db.preload_labels(&["User", "Missing"]).unwrap();
+    assert!(loaded > 0);
+    assert_eq!(db.preload_labels(&["User"]).unwrap(), 0);
+    assert_eq!(db.preload_labels(&["Missing"]).unwrap(), 0);
+
+    // Reads of preloaded properties are served from the cache.
+    let misses = db.page_cache_stats().misses;
+    let rows: Vec<_> = prepare("MATCH (u:User) RETURN u.name")
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &Params::new())
+        .collect();
+    assert_eq!(rows.len(), 200);
+    assert_eq!(db.page_cache_stats().misses, misses);
+
+    assert_eq!(db.preload_pages(0, 2).unwrap(), 0, "meta pages are skipped");
+    assert_eq!(db.preload_pages(1 << 40, 10).unwrap(), 0);
+}
+
+#[test]
+fn t366_cache_warmth_survives_a_restart() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("warm.ndb");
+    let db = Db::open(&path).unwrap();
+    seed(&db);
+    db.preload_labels(&["User"]).unwrap();
+    let warm = db.page_cache_stats().len;
+    db.close().unwrap();
+    assert!(path.with_extension("warm").exists());
+
+    let db = Db::open(&path).unwrap();
+    assert!(db.page_cache_stats().len >= warm);
+    assert_eq!(db.preload_labels(&["User"]).unwrap(), 0);
+    db.close().unwrap();
+
+    // A disabled cache neither restores nor loads pages.
+    let options = EngineOptions {
+        page_cache_pages: 0,
+        ..EngineOptions::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    assert_eq!(db.page_cache_stats().len, 0);
+}
@@ -0,0 +1,87 @@
+use nervusdb::{Db, DbSnapshot};
+use nervusdb_query::{Params, Value, prepare};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use tempfile::tempdir;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn count(snapshot: &DbSnapshot) -> i64 {
+    let row = prepare("MATCH (e:Event) RETURN count(e) AS n")
+        .unwrap()
+        .execute_streaming(snapshot, &Params::new())
+        .next()
+        .unwrap()
+        .unwrap();
+    match row.columns()[0].1 {
+        Value::Int(n) => n,
+        ref other => panic!("unexpected count {other:?}"),
+    }
+}
+
+#[test]
+fn t367_db_and_snapshots_are_shareable_across_threads() {
+    assert_send_sync::<Db>();
+    assert_send_sync::<DbSnapshot>();
+}
+
+#[test]
+fn t367_readers_keep_stable_views_while_a_writer_commits() {
+    let dir = tempdir().unwrap();
+    let db = Arc::new(Db::open(dir.path().join("mvcc.ndb")).unwrap());
+    let done = Arc::new(AtomicBool::new(false));
+
+    let writer = {
+        let db = Arc::clone(&db);
+        let done = Arc::clone(&done);
+        thread::spawn(move || {
+            let create = prepare("CREATE (:Event {n: 1})").unwrap();
+            for _ in 0..100 {
+                let mut txn = db.begin_write();
+                let snapshot = db.snapshot_in(&txn);
+                create
+                    .execute_write(&snapshot, &mut txn, &Params::new())
+                    .unwrap();
+                txn.commit().unwrap();
+            }
+            done.store(true, Ordering::SeqCst);
+        })
+    };
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let db = Arc::clone(&db);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut last = 0;
+                loop {
+                    let finished = done.load(Ordering::SeqCst);
+                    let snapshot = db.snapshot();
+                    let seen = count(&snapshot);
+                    // Repeatable within a snapshot, monotonic across them.
+                    assert_eq!(count(&snapshot), seen);
+                    assert!(seen >= last);
+                    last = seen;
+                    if finished {
+                        return last;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    // A snapshot shared with other threads stays fixed as well.
+    let shared = Arc::new(db.snapshot());
+    let before = count(&shared);
+    writer.join().unwrap();
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), 100);
+    }
+    let shared_reader = {
+        let shared = Arc::clone(&shared);
+        thread::spawn(move || count(&shared))
+    };
+    assert_eq!(shared_reader.join().unwrap(), before);
+    assert_eq!(count(&db.snapshot()), 100);
+}
@@ -0,0 +1,148 @@
+use nervusdb::{Db, EngineOptions, PropertyValue, WalChange};
+use tempfile::tempdir;
+
+#[test]
+fn t368_wal_tail_yields_committed_changes_after_a_txid() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("tail.ndb")).unwrap();
+
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    let follows = txn.get_or_create_rel_type("FOLLOWS").unwrap();
+    let alice = txn.create_node(1, user).unwrap();
+    let bob = txn.create_node(2, user).unwrap();
+    txn.set_node_property(alice, "name".into(), PropertyValue::String("Alice".into()))
+        .unwrap();
+    txn.create_edge(alice, follows, bob);
+    let first = txn.commit_with_bookmark().unwrap().txid();
+
+    let mut txn = db.begin_write();
+    txn.remove_node_property(alice, "name").unwrap();
+    txn.tombstone_node(bob);
+    let second = txn.commit_with_bookmark().unwrap().txid();
+
+    let txns: Vec<_> = db.wal_tail(0).unwrap().collect();
+    assert_eq!(
+        txns.iter().map(|t| t.txid).collect::<Vec<_>>(),
+        vec![first, second]
+    );
+    assert_eq!(
+        txns[0].changes,
+        vec![
+            WalChange::CreateNode {
+                node: alice,
+                external_id: 1,
+                label: Some("User".into()),
+            },
+            WalChange::CreateNode {
+                node: bob,
+                external_id: 2,
+                label: Some("User".into()),
+            },
+            WalChange::CreateEdge {
+                src: alice,
+                rel_type: "FOLLOWS".into(),
+                dst: bob,
+            },
+            WalChange::SetNodeProperty {
+                node: alice,
+                key: "name".into(),
+                value: PropertyValue::String("Alice".into()),
+            },
+        ]
+    );
+
+    let rest: Vec<_> = db.wal_tail(first).unwrap().collect();
+    assert_eq!(rest.len(), 1);
+    assert!(
+        rest[0]
+            .changes
+            .contains(&WalChange::DeleteNode { node: bob })
+    );
+    assert!(rest[0].changes.contains(&WalChange::RemoveNodeProperty {
+        node: alice,
+        key: "name".into(),
+    }));
+    assert_eq!(db.wal_tail(second).unwrap().count(), 0);
+}
+
+#[test]
+fn t368_wal_tail_reports_history_lost_to_a_checkpoint() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("tail.ndb");
+    let db = Db::open(&path).unwrap();
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    txn.create_node(1, user).unwrap();
+    let first = txn.commit_with_bookmark().unwrap().txid();
+    db.close().unwrap();
+
+    let db = Db::open(&path).unwrap();
+    let err = db.wal_tail(first).unwrap_err();
+    assert!(err.to_string().contains("wal history unavailable"), "{err}");
+
+    // A consumer that lost its place rebuilds from a snapshot and resumes
+    // from the bookmark taken before it.
+    let resume = db.last_bookmark().txid();
+    assert_eq!(db.wal_tail(resume).unwrap().count(), 0);
+
+    let mut txn = db.begin_write();
+    txn.create_node(2, user).unwrap();
+    txn.commit().unwrap();
+    let txns: Vec<_> = db.wal_tail(resume).unwrap().collect();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(
+        txns[0].changes,
+        vec![WalChange::CreateNode {
+            node: 1,
+            external_id: 2,
+            label: Some("User".into()),
+        }]
+    );
+}
+
+#[test]
+fn t368_wal_tail_resumes_mid_log_across_segments_with_names_as_logged() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("tail.ndb");
+    let options = EngineOptions {
+        wal_segment_bytes: 4096,
+        ..Default::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    let mut txids = Vec::new();
+    for i in 0..40u64 {
+        let mut txn = db.begin_write();
+        let name = if i <= 20 { "User" } else { "Member" };
+        let label = txn.get_or_create_label(name).unwrap();
+        let node = txn.create_node(i + 1, label).unwrap();
+        txn.set_node_property(node, "bio".into(), PropertyValue::String("x".repeat(200)))
+            .unwrap();
+        txids.push(txn.commit_with_bookmark().unwrap().txid());
+        if i == 20 {
+            db.rename_label("User", "Member").unwrap();
+        }
+    }
+    let label_at = |since: u64| match &db.wal_tail(since).unwrap().next().unwrap().changes[0] {
+        WalChange::CreateNode { label, .. } => label.clone(),
+        other => panic!("{other:?}"),
+    };
+    // Names as of the change, not as of now, wherever reading starts.
+    assert_eq!(label_at(txids[10]), Some("User".into()));
+    assert_eq!(label_at(txids[30]), Some("Member".into()));
+
+    let all: Vec<_> = db.wal_tail(0).unwrap().collect();
+    let mut resumed = Vec::new();
+    let mut since = 0;
+    while let Some(txn) = db.wal_tail(since).unwrap().next() {
+        since = txn.txid;
+        resumed.push(txn);
+    }
+    assert_eq!(resumed, all);
+    drop(db);
+
+    // A reopened handle finds the same positions.
+    let db = Db::open_with_options(&path, options).unwrap();
+    let reopened: Vec<_> = db.wal_tail(txids[10]).unwrap().collect();
+    assert_eq!(reopened[..], all[11..]);
+}
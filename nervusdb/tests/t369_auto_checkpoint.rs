@@ -0,0 +1,102 @@
+use nervusdb::{AutoCheckpoint, Db, EngineOptions, GraphSnapshot, PropertyValue};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn write_some(db: &Db, first: u64, count: u64) {
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    for id in first..first + count {
+        let node = txn.create_node(id, user).unwrap();
+        txn.set_node_property(node, "bio".into(), PropertyValue::String("x".repeat(256)))
+            .unwrap();
+    }
+    txn.commit().unwrap();
+}
+
+fn wait_for(timeout: Duration, mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if done() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    done()
+}
+
+fn wal_len(path: &Path) -> u64 {
+    std::fs::metadata(path).unwrap().len()
+}
+
+#[test]
+fn t369_auto_checkpoint_truncates_wal_past_size_threshold() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("auto.ndb");
+    let options = EngineOptions {
+        auto_checkpoint: Some(AutoCheckpoint {
+            interval: None,
+            wal_bytes: 16 * 1024,
+            io_bytes_per_sec: 0,
+        }),
+        ..EngineOptions::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    write_some(&db, 0, 100);
+    assert!(
+        wait_for(Duration::from_secs(5), || db.auto_checkpoints() > 0),
+        "no background checkpoint"
+    );
+    assert!(wal_len(db.wal_path()) < 16 * 1024);
+
+    let count = db.snapshot().nodes().count();
+    db.close().unwrap();
+    let db = Db::open(&path).unwrap();
+    assert_eq!(db.snapshot().nodes().count(), count);
+}
+
+#[test]
+fn t369_pause_holds_off_auto_checkpoints_until_resumed() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("auto.ndb")).unwrap();
+    db.set_auto_checkpoint(Some(AutoCheckpoint {
+        interval: Some(Duration::from_millis(50)),
+        wal_bytes: 0,
+        io_bytes_per_sec: 0,
+    }));
+    db.pause_checkpoints();
+    write_some(&db, 0, 20);
+    let grown = wal_len(db.wal_path());
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(db.auto_checkpoints(), 0);
+    assert_eq!(wal_len(db.wal_path()), grown);
+
+    db.resume_checkpoints();
+    assert!(wait_for(Duration::from_secs(5), || db.auto_checkpoints() > 0));
+    assert!(wal_len(db.wal_path()) < grown);
+
+    // Nothing written since: the timer does not checkpoint again.
+    let after = db.auto_checkpoints();
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(db.auto_checkpoints(), after);
+
+    db.set_auto_checkpoint(None);
+    assert_eq!(db.auto_checkpoints(), 0);
+}
+
+#[test]
+fn t369_io_throttle_spaces_out_checkpoints() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("auto.ndb")).unwrap();
+    db.set_auto_checkpoint(Some(AutoCheckpoint {
+        interval: None,
+        wal_bytes: 1,
+        // Far below the WAL size: one checkpoint buys minutes of quiet.
+        io_bytes_per_sec: 1,
+    }));
+    write_some(&db, 0, 10);
+    assert!(wait_for(Duration::from_secs(5), || db.auto_checkpoints() == 1));
+    write_some(&db, 10, 10);
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(db.auto_checkpoints(), 1);
+}
@@ -0,0 +1,58 @@
+use nervusdb::{Db, EngineOptions, GraphSnapshot, PropertyValue};
+use tempfile::tempdir;
+
+fn write_users(db: &Db, first: u64, count: u64) {
+    for id in first..first + count {
+        let mut txn = db.begin_write();
+        let user = txn.get_or_create_label("User").unwrap();
+        let node = txn.create_node(id, user).unwrap();
+        txn.set_node_property(node, "bio".into(), PropertyValue::String("x".repeat(256)))
+            .unwrap();
+        txn.commit().unwrap();
+    }
+}
+
+#[test]
+fn t371_online_incremental_backup_skips_wal_segments_already_backed_up() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let backups = dir.path().join("backups");
+    let options = EngineOptions {
+        wal_segment_bytes: 4096,
+        ..EngineOptions::default()
+    };
+    let db = Db::open_with_options(&db_path, options).unwrap();
+    write_users(&db, 0, 40);
+
+    let full = nervusdb::backup(&db_path, &backups).unwrap();
+    let segment = "graph.wal.000001";
+    assert!(backups.join(full.id.to_string()).join(segment).exists());
+
+    write_users(&db, 40, 10);
+    let delta = nervusdb::backup_incremental(&db_path, &backups, full.checkpoint_txid).unwrap();
+    assert_eq!(delta.base_backup, Some(full.id));
+    assert!(!backups.join(delta.id.to_string()).join(segment).exists());
+
+    let restored = dir.path().join("restored");
+    nervusdb::restore(&backups, &delta, &restored).unwrap();
+    let copy = Db::open(&restored).unwrap();
+    assert_eq!(copy.snapshot().nodes().count(), 50);
+
+    let err = nervusdb::restore(&backups, &delta, &restored).unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{err}");
+}
+
+#[test]
+fn t371_backup_incremental_needs_a_backup_at_since_txid() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let backups = dir.path().join("backups");
+    let db = Db::open(&db_path).unwrap();
+    write_users(&db, 0, 3);
+    db.close().unwrap();
+
+    let full = nervusdb::backup(&db_path, &backups).unwrap();
+    let err =
+        nervusdb::backup_incremental(&db_path, &backups, full.checkpoint_txid + 1).unwrap_err();
+    assert!(err.to_string().contains("no completed backup"), "{err}");
+}
@@ -0,0 +1,96 @@
+use nervusdb::{Db, GraphSnapshot, PropertyValue};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write_users(path: &Path, first: u64, count: u64) {
+    let db = Db::open(path).unwrap();
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    for id in first..first + count {
+        let node = txn.create_node(id, user).unwrap();
+        txn.set_node_property(node, "bio".into(), PropertyValue::String("x".repeat(256)))
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+}
+
+#[test]
+fn t372_verify_backup_checks_files_and_test_restores_chain() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let backups = dir.path().join("backups");
+    write_users(&db_path, 0, 100);
+    let full = nervusdb::backup(&db_path, &backups).unwrap();
+    write_users(&db_path, 100, 10);
+    let delta = nervusdb::backup_incremental_from(&db_path, &backups, &full).unwrap();
+
+    let delta_dir = backups.join(delta.id.to_string());
+    let report = nervusdb::verify_backup(&delta_dir, true).unwrap();
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert_eq!(report.backup_id, delta.id);
+    assert!(report.test_restored);
+    // The delta's data and WAL files plus those of its base.
+    assert_eq!(report.files_checked, 4);
+
+    let restored = dir.path().join("restored");
+    nervusdb::restore(&backups, &delta, &restored).unwrap();
+    assert_eq!(Db::open(&restored).unwrap().snapshot().nodes().count(), 110);
+}
+
+#[test]
+fn t372_verify_backup_reports_corruption_in_the_base() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let backups = dir.path().join("backups");
+    write_users(&db_path, 0, 10);
+    let full = nervusdb::backup(&db_path, &backups).unwrap();
+    write_users(&db_path, 10, 10);
+    let delta = nervusdb::backup_incremental_from(&db_path, &backups, &full).unwrap();
+
+    let wal = backups.join(full.id.to_string()).join("graph.wal");
+    let mut bytes = std::fs::read(&wal).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    std::fs::write(&wal, bytes).unwrap();
+
+    let report = nervusdb::verify_backup(backups.join(delta.id.to_string()), true).unwrap();
+    assert!(!report.test_restored);
+    assert_eq!(report.problems.len(), 1, "{:?}", report.problems);
+    assert!(
+        report.problems[0].starts_with(&format!("base backup {}: graph.wal: checksum", full.id)),
+        "{:?}",
+        report.problems
+    );
+
+    std::fs::remove_file(&wal).unwrap();
+    let report = nervusdb::verify_backup(backups.join(full.id.to_string()), false).unwrap();
+    assert_eq!(report.problems, vec!["graph.wal is missing".to_string()]);
+}
+
+#[test]
+fn t372_verify_backup_lists_files_without_a_checksum_as_unverified() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let backups = dir.path().join("backups");
+    write_users(&db_path, 0, 10);
+    let full = nervusdb::backup(&db_path, &backups).unwrap();
+
+    // A manifest from before checksums were recorded.
+    let manifest_path = backups
+        .join(full.id.to_string())
+        .join("backup_manifest.json");
+    let mut manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+    for file in manifest["files"].as_array_mut().unwrap() {
+        if file["name"] == "graph.wal" {
+            file["checksum"] = "".into();
+        }
+    }
+    std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+    let report = nervusdb::verify_backup(backups.join(full.id.to_string()), false).unwrap();
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert_eq!(report.unverified, vec!["graph.wal".to_string()]);
+}
@@ -0,0 +1,158 @@
+use nervusdb::{Db, EngineOptions, FileImport};
+use nervusdb_query::{Params, Value, prepare};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn read(db: &Db, cypher: &str) -> Vec<Value> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| row.unwrap().get("v").cloned().unwrap())
+        .collect()
+}
+
+#[test]
+fn t373_load_csv_binds_lists_or_header_maps() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("csv.ndb")).unwrap();
+    let csv = dir.path().join("people.csv");
+    std::fs::write(&csv, "name;age\nada;36\n\"grace; rear admiral\";\n").unwrap();
+    let url = file_url(&csv);
+
+    assert_eq!(
+        read(
+            &db,
+            &format!("LOAD CSV FROM '{url}' AS row FIELDTERMINATOR ';' RETURN row[0] AS v")
+        ),
+        ["name", "ada", "grace; rear admiral"].map(|s| Value::String(s.into()))
+    );
+    assert_eq!(
+        read(
+            &db,
+            &format!(
+                "LOAD CSV WITH HEADERS FROM '{url}' AS row FIELDTERMINATOR ';' \
+                 RETURN row.age AS v"
+            )
+        ),
+        [Value::String("36".into()), Value::Null]
+    );
+
+    let err = prepare(&format!(
+        "LOAD CSV FROM '{url}' AS row FIELDTERMINATOR ';;' RETURN row"
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("single ASCII character"), "{err}");
+    let err = prepare("LOAD CSV FROM 'http://example.com/x.csv' AS row RETURN row AS v")
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &Params::new())
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(err.to_string().contains("file:// URLs only"), "{err}");
+}
+
+#[test]
+fn t373_periodic_commit_loads_in_batches() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("csv.ndb")).unwrap();
+    let csv = dir.path().join("items.csv");
+    let mut text = String::from("id,name\n");
+    for i in 0..25 {
+        text.push_str(&format!("{i},item {i}\n"));
+    }
+    std::fs::write(&csv, text).unwrap();
+
+    let query = prepare(&format!(
+        "USING PERIODIC COMMIT 10 LOAD CSV WITH HEADERS FROM '{}' AS row \
+         CREATE (:Item {{id: toInteger(row.id), name: row.name}})",
+        file_url(&csv)
+    ))
+    .unwrap();
+    assert_eq!(query.periodic_commit(), Some(10));
+
+    let mut batches = 0;
+    let written = query
+        .execute_periodic(&Params::new(), |params| {
+            batches += 1;
+            let snapshot = db.snapshot();
+            let mut txn = db.begin_write();
+            let (_, count) = query.execute_mixed(&snapshot, &mut txn, params)?;
+            txn.commit().unwrap();
+            Ok(count)
+        })
+        .unwrap();
+    assert_eq!(batches, 3);
+    assert_eq!(written, 25);
+    assert_eq!(
+        read(&db, "MATCH (i:Item) RETURN count(i) AS v"),
+        [Value::Int(25)]
+    );
+    assert_eq!(
+        read(&db, "MATCH (i:Item {id: 24}) RETURN i.name AS v"),
+        [Value::String("item 24".into())]
+    );
+
+    let err = prepare("USING PERIODIC COMMIT 10 MATCH (n) RETURN n").unwrap_err();
+    assert!(err.to_string().contains("LOAD CSV"), "{err}");
+}
+
+fn try_read(db: &Db, url: &str) -> Result<Vec<Value>, String> {
+    let snapshot = db.snapshot();
+    prepare(&format!("LOAD CSV FROM '{url}' AS row RETURN row[0] AS v"))
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::new())
+        .map(|row| row.map(|row| row.get("v").cloned().unwrap()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn t373_import_root_confines_load_csv() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("import");
+    std::fs::create_dir_all(root.join("nested")).unwrap();
+    std::fs::write(root.join("nested/in.csv"), "inside\n").unwrap();
+    let outside = dir.path().join("secret.csv");
+    std::fs::write(&outside, "secret\n").unwrap();
+    let db = Db::open(dir.path().join("csv.ndb")).unwrap();
+    assert_eq!(db.file_import(), FileImport::Anywhere);
+    assert_eq!(try_read(&db, &file_url(&outside)).unwrap().len(), 1);
+
+    db.set_file_import(FileImport::Under(root.clone())).unwrap();
+    let inside = [Value::String("inside".into())];
+    assert_eq!(try_read(&db, "file://nested/in.csv").unwrap(), inside);
+    assert_eq!(
+        try_read(&db, &file_url(&root.join("nested/in.csv"))).unwrap(),
+        inside
+    );
+    for url in [
+        file_url(&outside),
+        "file://../secret.csv".to_string(),
+        "file://nested/../../secret.csv".to_string(),
+    ] {
+        let err = try_read(&db, &url).unwrap_err();
+        assert!(err.contains("outside the import root"), "{url}: {err}");
+    }
+    assert!(
+        db.set_file_import(FileImport::Under(dir.path().join("missing")))
+            .is_err()
+    );
+
+    db.set_file_import(FileImport::Disabled).unwrap();
+    let err = try_read(&db, "file://nested/in.csv").unwrap_err();
+    assert!(err.contains("disabled"), "{err}");
+    db.close().unwrap();
+
+    let options = EngineOptions {
+        read_only: true,
+        ..Default::default()
+    };
+    let db = Db::open_with_options(dir.path().join("csv.ndb"), options).unwrap();
+    let err = try_read(&db, &file_url(&outside)).unwrap_err();
+    assert!(err.contains("disabled"), "{err}");
+}
@@ -0,0 +1,136 @@
+use nervusdb::{Db, GraphFormat, PropertyValue, TransferStats};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn build_graph(path: &Path) {
+    let db = Db::open(path).unwrap();
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    let knows = txn.get_or_create_rel_type("KNOWS").unwrap();
+
+    let ada = txn.create_node(1, user).unwrap();
+    let props = [
+        ("name", PropertyValue::String("Ada <\"&\">".into())),
+        ("age", PropertyValue::Int(36)),
+        ("score", PropertyValue::Float(0.5)),
+        ("active", PropertyValue::Bool(true)),
+        ("born", PropertyValue::Date(-47_000)),
+        ("wake", PropertyValue::LocalTime(7 * 3_600_000_000_000 + 5)),
+        ("avatar", PropertyValue::Blob(vec![0, 1, 255])),
+        (
+            "tags",
+            PropertyValue::List(vec![PropertyValue::Int(1), "x".into()]),
+        ),
+        (
+            "meta",
+            PropertyValue::Map(BTreeMap::from([("type".to_string(), "plain".into())])),
+        ),
+    ];
+    for (key, value) in props {
+        txn.set_node_property(ada, key.into(), value).unwrap();
+    }
+    let grace = txn.create_node(2, user).unwrap();
+    txn.set_node_property(grace, "name".into(), PropertyValue::Int(2))
+        .unwrap();
+    txn.create_node(3, nervusdb::LabelId::MAX).unwrap();
+
+    txn.create_edge(ada, knows, grace);
+    txn.set_edge_property(
+        ada,
+        knows,
+        grace,
+        "since".into(),
+        PropertyValue::DateTime(1_700_000_000_000_000),
+    )
+    .unwrap();
+    txn.commit().unwrap();
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+}
+
+fn export(path: &Path, format: GraphFormat) -> (TransferStats, String) {
+    let mut out = Vec::new();
+    let stats = nervusdb::export(path, format, &mut out).unwrap();
+    (stats, String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn t374_export_import_round_trips_both_formats() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("source.ndb");
+    build_graph(&source);
+    let (stats, jsonl) = export(&source, GraphFormat::Jsonl);
+    assert_eq!(
+        stats,
+        TransferStats {
+            nodes: 3,
+            relationships: 1
+        }
+    );
+    assert!(
+        jsonl.contains(r#""born":{"type":"date","value":"1841-04-27"}"#),
+        "{jsonl}"
+    );
+
+    for format in [GraphFormat::Jsonl, GraphFormat::GraphMl] {
+        let (_, text) = export(&source, format);
+        let copy = dir.path().join(format!("{format:?}.ndb"));
+        let imported = nervusdb::import(&copy, format, text.as_bytes()).unwrap();
+        assert_eq!(imported, stats);
+        assert_eq!(export(&copy, GraphFormat::Jsonl).1, jsonl, "{format:?}");
+    }
+
+    let (_, graphml) = export(&source, GraphFormat::GraphMl);
+    assert!(
+        graphml.contains("<data key=\"labels\">:User</data>"),
+        "{graphml}"
+    );
+    assert!(
+        graphml.contains("Ada &lt;&quot;&amp;&quot;&gt;"),
+        "{graphml}"
+    );
+}
+
+#[test]
+fn t374_import_accepts_relationships_first_and_rejects_bad_input() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph.ndb");
+    let input = r#"{"type":"relationship","start":1,"end":2,"rel_type":"R"}
+
+{"type":"node","id":1,"labels":["A"]}
+{"type":"node","id":2,"labels":[],"properties":{"n":1}}
+"#;
+    let stats = nervusdb::import(&path, GraphFormat::Jsonl, input.as_bytes()).unwrap();
+    assert_eq!(stats.relationships, 1);
+
+    let err = nervusdb::import(
+        &path,
+        GraphFormat::Jsonl,
+        r#"{"type":"node","id":1}"#.as_bytes(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{err}");
+
+    let err = nervusdb::import(
+        &path,
+        GraphFormat::Jsonl,
+        r#"{"type":"relationship","start":7,"end":8,"rel_type":"R"}"#.as_bytes(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("node 7 not in the input"), "{err}");
+
+    let err =
+        nervusdb::import(&path, GraphFormat::Jsonl, "{\"type\":\"edge\"}".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("line 1"), "{err}");
+
+    let err = "xml".parse::<GraphFormat>().unwrap_err();
+    assert!(err.to_string().contains("graphml or jsonl"), "{err}");
+    let err = nervusdb::export(
+        dir.path().join("missing.ndb"),
+        GraphFormat::Jsonl,
+        Vec::new(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no database"), "{err}");
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::try_write;
+use nervusdb::{Db, EngineOptions, NameKind, NameLimits, PropertyValue};
+use tempfile::tempdir;
+
+#[test]
+fn t375_pathological_names_fail_early_with_clear_errors() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("names.ndb")).unwrap();
+
+    let huge = "L".repeat(1 << 20);
+    let err = try_write(&db, &format!("CREATE (:{huge})")).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("invalid label"), "{msg}");
+    assert!(msg.contains("limit is 256"), "{msg}");
+    assert!(msg.len() < 300, "{} bytes", msg.len());
+
+    let err = try_write(&db, &format!("CREATE ()-[:{}]->()", "R".repeat(300))).unwrap_err();
+    assert!(
+        err.to_string().contains("invalid relationship type"),
+        "{err}"
+    );
+
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    let node = txn.create_node(1, user).unwrap();
+    let err = txn
+        .set_node_property(node, "na\0me".into(), PropertyValue::Int(1))
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid property key"), "{err}");
+    assert!(err.to_string().contains("NUL"), "{err}");
+    let err = txn.get_or_create_label("line\nbreak").unwrap_err();
+    assert!(err.to_string().contains("control character"), "{err}");
+}
+
+#[test]
+fn t375_limits_are_configurable_and_reported() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("names.ndb");
+    let db = Db::open(&path).unwrap();
+    try_write(
+        &db,
+        "CREATE (:TwentyBytesLongLabel {sixteen_byte_key: 1})-[:REL]->()",
+    )
+    .unwrap();
+    db.close().unwrap();
+
+    let options = EngineOptions {
+        name_limits: NameLimits {
+            max_bytes: 16,
+            allow_control_chars: true,
+        },
+        ..EngineOptions::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    // Names that already exist keep working under tighter limits.
+    assert_eq!(
+        try_write(&db, "CREATE (:TwentyBytesLongLabel {tab: 'a'})").unwrap(),
+        1
+    );
+    let err = try_write(&db, "CREATE (:SeventeenBytesLong)").unwrap_err();
+    assert!(err.to_string().contains("limit is 16"), "{err}");
+    try_write(&db, "CREATE (:`tab\tlabel`)").unwrap();
+
+    let report = db.name_report();
+    let entry = |name: &str| report.iter().find(|usage| usage.name == name);
+    let label = entry("TwentyBytesLongLabel").unwrap();
+    assert_eq!((label.kind, label.bytes), (NameKind::Label, 20));
+    assert!(label.problem.as_deref().unwrap().contains("limit is 16"));
+    let key = entry("sixteen_byte_key").unwrap();
+    assert_eq!(
+        (key.kind, key.bytes, key.problem.as_deref()),
+        (NameKind::PropertyKey, 16, None)
+    );
+    assert!(entry("REL").is_none());
+    assert!(entry("tab").is_none());
+}
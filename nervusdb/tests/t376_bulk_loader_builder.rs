@@ -0,0 +1,119 @@
+use nervusdb::{
+    BulkEdge, BulkLoadStats, BulkLoaderBuilder, BulkNode, Db, GraphSnapshot, PropertyValue,
+};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+fn node(external_id: u64, label: &str, rank: i64) -> BulkNode {
+    BulkNode {
+        external_id,
+        label: label.to_string(),
+        properties: BTreeMap::from([("rank".to_string(), PropertyValue::Int(rank))]),
+    }
+}
+
+fn edge(src: u64, dst: u64, weight: i64) -> BulkEdge {
+    BulkEdge {
+        src_external_id: src,
+        rel_type: "NEXT".to_string(),
+        dst_external_id: dst,
+        properties: BTreeMap::from([("weight".to_string(), PropertyValue::Int(weight))]),
+    }
+}
+
+#[test]
+fn t376_builder_sorts_spills_and_dedups() {
+    let dir = tempdir().unwrap();
+    let base = dir.path().join("graph");
+    let scratch = dir.path().join("scratch");
+    std::fs::create_dir(&scratch).unwrap();
+
+    // Descending ids, then a second pass over the even ids that should win.
+    let nodes = (1..=500u64)
+        .rev()
+        .map(|id| node(id, "Item", id as i64))
+        .chain(
+            (2..=500u64)
+                .step_by(2)
+                .map(|id| node(id, "Even", -(id as i64))),
+        );
+    let edges = (1..500u64)
+        .rev()
+        .map(|id| edge(id, id + 1, 1))
+        .chain((1..=10u64).map(|id| edge(id, id + 1, 2)));
+
+    let stats = BulkLoaderBuilder::new(base.with_extension("ndb"))
+        .temp_dir(scratch.clone())
+        .run_records(64)
+        .load(nodes, edges)
+        .unwrap();
+    assert_eq!(
+        stats,
+        BulkLoadStats {
+            nodes: 500,
+            edges: 499,
+            duplicate_nodes: 250,
+            duplicate_edges: 10,
+            spilled_runs: 12 + 8,
+        }
+    );
+    assert_eq!(std::fs::read_dir(&scratch).unwrap().count(), 0);
+
+    let db = Db::open(&base).unwrap();
+    let snap = db.snapshot();
+    let even = snap.resolve_label_id("Even").unwrap();
+    let item = snap.resolve_label_id("Item").unwrap();
+    let next = snap.resolve_rel_type_id("NEXT").unwrap();
+
+    let ids: Vec<_> = snap.nodes().collect();
+    assert_eq!(ids.len(), 500);
+    for iid in ids {
+        let external = snap.resolve_external(iid).unwrap();
+        assert_eq!(
+            iid as u64 + 1,
+            external,
+            "internal ids follow external order"
+        );
+        let (label, rank) = if external % 2 == 0 {
+            (even, -(external as i64))
+        } else {
+            (item, external as i64)
+        };
+        assert_eq!(snap.node_label(iid), Some(label));
+        assert_eq!(
+            snap.node_property(iid, "rank"),
+            Some(PropertyValue::Int(rank))
+        );
+
+        let out: Vec<_> = snap.neighbors(iid, Some(next)).collect();
+        if external == 500 {
+            assert!(out.is_empty());
+            continue;
+        }
+        assert_eq!(out.len(), 1);
+        let weight = if external <= 10 { 2 } else { 1 };
+        assert_eq!(
+            snap.edge_property(out[0], "weight"),
+            Some(PropertyValue::Int(weight))
+        );
+    }
+}
+
+#[test]
+fn t376_builder_rejects_dangling_edges_and_existing_db() {
+    let dir = tempdir().unwrap();
+    let ndb = dir.path().join("dangling.ndb");
+    let err = BulkLoaderBuilder::new(ndb.clone())
+        .load([node(1, "A", 0)], [edge(1, 2, 0)])
+        .unwrap_err();
+    assert!(err.to_string().contains("dst_external_id"), "{err}");
+    assert!(!dir.path().join("dangling.ndb.bulk-sort").exists());
+    assert!(!ndb.exists());
+    std::fs::write(&ndb, b"").unwrap();
+
+    assert!(
+        BulkLoaderBuilder::new(ndb)
+            .load(Vec::new(), Vec::new())
+            .is_err()
+    );
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::try_write;
+use nervusdb::query::{Params, Value};
+use nervusdb::{Db, EngineOptions};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn open(path: &Path) -> Db {
+    Db::open_with_options(
+        path,
+        EngineOptions {
+            system_properties: true,
+            ..EngineOptions::default()
+        },
+    )
+    .unwrap()
+}
+
+fn row(db: &Db, cypher: &str) -> Vec<Value> {
+    let snapshot = db.snapshot();
+    let rows: Vec<_> = nervusdb::query::prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 1, "{cypher}");
+    rows[0].columns().iter().map(|(_, v)| v.clone()).collect()
+}
+
+#[test]
+fn t377_writes_stamp_created_and_updated_system_properties() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = open(&path);
+
+    try_write(
+        &db,
+        "CREATE (:User {name: 'a'})-[:KNOWS {w: 1}]->(:User {name: 'b'})",
+    )
+    .unwrap();
+    let first = row(
+        &db,
+        "MATCH (a:User {name: 'a'})-[r:KNOWS]->(b) \
+         RETURN a.__created_tx, a.__updated_tx, r.__created_tx, b.__updated_tx, \
+                a.__updated_at, keys(a), keys(r)",
+    );
+    let Value::Int(created) = first[0] else {
+        panic!("expected txid, got {first:?}");
+    };
+    assert_eq!(first[1..4], vec![Value::Int(created); 3]);
+    assert!(matches!(first[4], Value::DateTime(_)), "{first:?}");
+    assert_eq!(first[5], Value::List(vec![Value::String("name".into())]));
+    assert_eq!(first[6], Value::List(vec![Value::String("w".into())]));
+
+    try_write(&db, "MATCH (a:User {name: 'a'}) SET a.name = 'a2'").unwrap();
+    let second = row(
+        &db,
+        "MATCH (a:User {name: 'a2'})-[r:KNOWS]->(b) \
+         RETURN a.__created_tx, a.__updated_tx, r.__updated_tx, b.__updated_tx",
+    );
+    let Value::Int(updated) = second[1] else {
+        panic!("expected txid, got {second:?}");
+    };
+    assert!(updated > created);
+    assert_eq!(
+        second,
+        vec![
+            Value::Int(created),
+            Value::Int(updated),
+            Value::Int(created),
+            Value::Int(created),
+        ]
+    );
+
+    for cypher in [
+        "MATCH (a:User) SET a.__created_tx = 0",
+        "MATCH (a:User) REMOVE a.__updated_tx",
+        "MATCH ()-[r:KNOWS]->() SET r.__note = 'x'",
+    ] {
+        let err = try_write(&db, cypher).unwrap_err();
+        assert!(err.to_string().contains("reserved"), "{cypher}: {err}");
+    }
+
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        row(&db, "MATCH (a:User {name: 'a2'}) RETURN a.__updated_tx"),
+        vec![Value::Int(updated)]
+    );
+    try_write(&db, "MATCH (a:User {name: 'a2'}) SET a.name = 'a3'").unwrap();
+    assert_eq!(
+        row(&db, "MATCH (a:User {name: 'a3'}) RETURN a.__updated_tx"),
+        vec![Value::Int(updated)],
+        "stamping is off unless enabled"
+    );
+}
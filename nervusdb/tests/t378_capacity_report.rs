@@ -0,0 +1,62 @@
+mod common;
+
+use common::write;
+use nervusdb::{CAPACITY_HORIZON_DAYS, Db, PAGE_SIZE, capacity_report};
+use std::thread::sleep;
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn t378_capacity_report_estimates_usage_and_projects_growth() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open(&path).unwrap();
+
+    write(
+        &db,
+        "CREATE (:User {name: 'a', age: 1})-[:KNOWS {since: 2020}]->(:User {name: 'b', age: 2})",
+    );
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+    let report = capacity_report(&path).unwrap();
+    assert_eq!((report.nodes, report.edges, report.properties), (2, 1, 5));
+    assert!(report.growth.is_none(), "one sample is not a rate");
+    assert!(report.projections.is_empty());
+
+    let user = &report.labels[0];
+    assert_eq!(
+        (user.name.as_str(), user.count, user.properties),
+        ("User", 2, 4)
+    );
+    assert!(user.property_bytes >= 4 * PAGE_SIZE as u64, "{user:?}");
+    assert_eq!(report.rel_types[0].name, "KNOWS");
+    assert!(report.estimated_bytes <= report.file_bytes, "{report:?}");
+
+    sleep(Duration::from_millis(20));
+    let db = Db::open(&path).unwrap();
+    write(
+        &db,
+        "UNWIND range(1, 20) AS i CREATE (:Item {n: i})-[:IN]->(:Bin)",
+    );
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+
+    let report = capacity_report(&path).unwrap();
+    assert_eq!(report.nodes, 42);
+    let names: Vec<_> = report.labels.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, ["Bin", "Item", "User"]);
+    let bin = &report.labels[0];
+    assert_eq!((bin.count, bin.properties), (20, 0));
+    assert!(bin.bytes_per_entity > 0.0);
+
+    let growth = report.growth.clone().expect("two samples");
+    assert_eq!(growth.samples, 2);
+    assert!(growth.nodes_per_day > 0.0 && growth.file_bytes_per_day > 0.0);
+    let days: Vec<_> = report.projections.iter().map(|p| p.days).collect();
+    assert_eq!(days, CAPACITY_HORIZON_DAYS);
+    assert!(report.projections[0].nodes > report.nodes);
+    assert!(report.projections[2].file_bytes > report.projections[0].file_bytes);
+    assert!(report.days_to_node_id_limit.unwrap() > 0.0);
+
+    assert!(capacity_report(dir.path().join("missing")).is_err());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{column, write};
+use nervusdb::query::{Params, Value};
+use nervusdb::{
+    BulkEdge, BulkLoaderBuilder, BulkNode, Db, EngineOptions, IndexInfo, IndexKind, PropertyValue,
+};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+fn strings(names: &[&str]) -> Vec<Value> {
+    names.iter().map(|n| Value::String(n.to_string())).collect()
+}
+
+#[test]
+fn t379_procedures_list_names_and_indexes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open_with_options(
+        &path,
+        EngineOptions {
+            system_properties: true,
+            ..EngineOptions::default()
+        },
+    )
+    .unwrap();
+
+    write(
+        &db,
+        "CREATE (:User {name: 'a'})-[:KNOWS {since: 2020}]->(:User:Admin {name: 'b'}), \
+                (:Tmp {scratch: 1})",
+    );
+    write(&db, "MATCH (t:Tmp) DELETE t");
+    db.create_index("User", "name").unwrap();
+    db.create_text_index("User", "bio").unwrap();
+    db.create_unique_constraint("User", "email").unwrap();
+
+    assert_eq!(
+        column(&db, "CALL db.labels() YIELD label RETURN label"),
+        strings(&["Admin", "Tmp", "User"]),
+        "names stay listed after their last use is deleted"
+    );
+    assert_eq!(
+        column(
+            &db,
+            "CALL db.relationshipTypes() YIELD relationshipType RETURN relationshipType"
+        ),
+        strings(&["KNOWS"])
+    );
+    assert_eq!(
+        column(
+            &db,
+            "CALL db.propertyKeys() YIELD propertyKey RETURN propertyKey"
+        ),
+        strings(&["name", "scratch", "since"]),
+        "system properties are not listed"
+    );
+
+    let snapshot = db.snapshot();
+    let rows: Vec<_> = nervusdb::query::prepare(
+        "CALL db.indexes() YIELD name, type, labelsOrTypes, properties, uniqueness \
+         RETURN name, type, labelsOrTypes, properties, uniqueness",
+    )
+    .unwrap()
+    .execute_streaming(&snapshot, &Params::default())
+    .collect::<Result<_, _>>()
+    .unwrap();
+    let unique: Vec<Value> = rows
+        .iter()
+        .find(|row| row.get("uniqueness") == Some(&Value::String("UNIQUE".into())))
+        .unwrap()
+        .columns()
+        .iter()
+        .map(|(_, v)| v.clone())
+        .collect();
+    assert_eq!(
+        unique,
+        vec![
+            Value::String("User(email)".into()),
+            Value::String("COMPOSITE".into()),
+            Value::List(strings(&["User"])),
+            Value::List(strings(&["email"])),
+            Value::String("UNIQUE".into()),
+        ]
+    );
+
+    let schema = db.schema();
+    assert_eq!(schema.rel_types, ["KNOWS"]);
+    assert_eq!(
+        schema.indexes,
+        vec![
+            IndexInfo {
+                name: "User(email)".into(),
+                kind: IndexKind::Composite,
+                label: "User".into(),
+                properties: vec!["email".into()],
+                unique: true,
+            },
+            IndexInfo {
+                name: "User.bio#text".into(),
+                kind: IndexKind::Text,
+                label: "User".into(),
+                properties: vec!["bio".into()],
+                unique: false,
+            },
+            IndexInfo {
+                name: "User.name".into(),
+                kind: IndexKind::BTree,
+                label: "User".into(),
+                properties: vec!["name".into()],
+                unique: false,
+            },
+        ]
+    );
+
+    db.checkpoint().unwrap();
+    write(&db, "CREATE (:Late {late: true})-[:AFTER]->(:Late)");
+    db.close().unwrap();
+    let db = Db::open(&path).unwrap();
+    let reopened = db.schema();
+    assert_eq!(reopened.labels, ["Admin", "Late", "Tmp", "User"]);
+    assert_eq!(reopened.rel_types, ["AFTER", "KNOWS"]);
+    assert_eq!(reopened.property_keys, ["late", "name", "scratch", "since"]);
+}
+
+#[test]
+fn t379_bulk_loaded_database_is_scanned_on_open() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let node = |external_id, label: &str, key: &str| BulkNode {
+        external_id,
+        label: label.to_string(),
+        properties: BTreeMap::from([(key.to_string(), PropertyValue::Int(1))]),
+    };
+    BulkLoaderBuilder::new(path.with_extension("ndb"))
+        .load(
+            [node(1, "Person", "age"), node(2, "City", "population")],
+            [BulkEdge {
+                src_external_id: 1,
+                rel_type: "LIVES_IN".to_string(),
+                dst_external_id: 2,
+                properties: BTreeMap::new(),
+            }],
+        )
+        .unwrap();
+
+    let db = Db::open(&path).unwrap();
+    let schema = db.schema();
+    assert_eq!(schema.labels, ["City", "Person"]);
+    assert_eq!(schema.rel_types, ["LIVES_IN"]);
+    assert_eq!(schema.property_keys, ["age", "population"]);
+    assert!(schema.indexes.is_empty());
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::write;
+use nervusdb::query::{Params, Value};
+use nervusdb::{
+    Db, EncryptedBackend, EngineOptions, FileBackend, MemoryBackend, PageCipher, ReadOnlyBackend,
+};
+use std::path::Path;
+use tempfile::tempdir;
+
+#[derive(Debug)]
+struct XorCipher(u8);
+
+impl PageCipher for XorCipher {
+    fn encrypt(&self, page_id: u64, page: &mut [u8]) {
+        for b in page {
+            *b ^= self.0 ^ page_id as u8;
+        }
+    }
+
+    fn decrypt(&self, page_id: u64, page: &mut [u8]) {
+        self.encrypt(page_id, page);
+    }
+}
+
+fn names(db: &Db) -> Vec<Value> {
+    let snapshot = db.snapshot();
+    nervusdb::query::prepare("MATCH (n:Secret) RETURN n.name ORDER BY n.name")
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .map(|row| row.unwrap().columns()[0].1.clone())
+        .collect()
+}
+
+fn open_encrypted(path: &Path) -> Db {
+    let backend = EncryptedBackend::new(
+        FileBackend::open(path.with_extension("ndb")).unwrap(),
+        XorCipher(0xA7),
+    );
+    Db::open_with_backend(path, EngineOptions::default(), Box::new(backend)).unwrap()
+}
+
+#[test]
+fn t380_memory_backend_keeps_pages_off_disk() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open_with_backend(
+        &path,
+        EngineOptions::default(),
+        Box::new(MemoryBackend::new()),
+    )
+    .unwrap();
+
+    write(
+        &db,
+        "CREATE (:Secret {name: 'alpha'}), (:Secret {name: 'beta'})",
+    );
+    db.checkpoint().unwrap();
+    assert_eq!(
+        names(&db),
+        [Value::String("alpha".into()), Value::String("beta".into())]
+    );
+    assert!(!path.with_extension("ndb").exists());
+}
+
+#[test]
+fn t380_encrypted_backend_roundtrips_through_ciphertext() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = open_encrypted(&path);
+    write(&db, "CREATE (:Secret {name: 'plaintext-marker-7f3a'})");
+    db.close().unwrap();
+
+    let bytes = std::fs::read(path.with_extension("ndb")).unwrap();
+    assert!(
+        !bytes
+            .windows(b"plaintext-marker-7f3a".len())
+            .any(|w| w == b"plaintext-marker-7f3a")
+    );
+    assert!(
+        Db::open(&path).is_err(),
+        "the meta page is unreadable without the cipher"
+    );
+
+    let db = open_encrypted(&path);
+    assert_eq!(names(&db), [Value::String("plaintext-marker-7f3a".into())]);
+}
+
+#[test]
+fn t380_read_only_backend_serves_a_checkpointed_image() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("source");
+    let db = Db::open(&source).unwrap();
+    write(&db, "CREATE (:Secret {name: 'remote'})");
+    db.close().unwrap();
+    let image = std::fs::read(source.with_extension("ndb")).unwrap();
+
+    let replica = dir.path().join("replica");
+    // The checkpointed WAL holds the segment manifest the pages belong to.
+    std::fs::copy(source.with_extension("wal"), replica.with_extension("wal")).unwrap();
+    let db = Db::open_with_backend(
+        &replica,
+        EngineOptions::default(),
+        Box::new(ReadOnlyBackend::new(image)),
+    )
+    .unwrap();
+    assert_eq!(names(&db), [Value::String("remote".into())]);
+
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    nervusdb::query::prepare("CREATE (:Secret {name: 'local'})")
+        .unwrap()
+        .execute_write(&snapshot, &mut txn, &Params::default())
+        .unwrap();
+    let err = txn.commit().unwrap_err();
+    assert!(err.to_string().contains("read-only"), "{err}");
+    assert_eq!(names(&db), [Value::String("remote".into())]);
+}
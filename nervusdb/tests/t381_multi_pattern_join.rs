@@ -0,0 +1,89 @@
+mod common;
+
+use common::rows;
+use nervusdb::Db;
+use nervusdb::query::{Params, Value};
+use tempfile::tempdir;
+
+fn setup() -> (tempfile::TempDir, Db) {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    nervusdb::query::prepare(
+        "CREATE (a:A {n: 1})-[:R]->(x:X {n: 10}), \
+                (:B {n: 2})-[:S]->(x)-[:T]->(:C {n: 4}), \
+                (:B {n: 5})-[:S]->(:X {n: 11})-[:T]->(:C {n: 6}), \
+                (:B {n: 3})",
+    )
+    .unwrap()
+    .execute_write(&snapshot, &mut txn, &Params::default())
+    .unwrap();
+    txn.commit().unwrap();
+    (dir, db)
+}
+
+fn explain(cypher: &str) -> String {
+    nervusdb::query::prepare(&format!("EXPLAIN {cypher}"))
+        .unwrap()
+        .explain_string()
+        .unwrap()
+        .to_string()
+}
+
+fn ints(values: &[i64]) -> Vec<Value> {
+    values.iter().map(|&v| Value::Int(v)).collect()
+}
+
+#[test]
+fn t381_patterns_sharing_a_middle_variable_are_hash_joined() {
+    let (_dir, db) = setup();
+
+    let cypher = "MATCH (x:X), (b:B)-[:S]->(x)-[:T]->(c:C) RETURN b.n, c.n ORDER BY b.n";
+    assert_eq!(rows(&db, cypher), [ints(&[2, 4]), ints(&[5, 6])]);
+    assert!(explain(cypher).contains("HashJoin(keys=[\"x\"])"));
+
+    let cypher = "MATCH (a:A)-[:R]->(x), (c:C)<-[:T]-(x)<-[:S]-(b:B) RETURN a.n, b.n, c.n";
+    assert_eq!(rows(&db, cypher), [ints(&[1, 2, 4])]);
+
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (x:X {n: 11}) WITH x MATCH (b:B)-[:S]->(x)-[:T]->(c) RETURN b.n, c.n"
+        ),
+        [ints(&[5, 6])]
+    );
+
+    let cypher = "MATCH (a:A), (b:B) RETURN a.n, b.n ORDER BY b.n";
+    assert_eq!(rows(&db, cypher).len(), 3);
+    assert!(!explain(cypher).contains("HashJoin"));
+}
+
+#[test]
+fn t381_optional_match_pattern_lists_are_null_padded_together() {
+    let (_dir, db) = setup();
+
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (a:A) OPTIONAL MATCH (a)-[:R]->(x), (b:B)-[:S]->(x)-[:T]->(c:C) \
+             RETURN a.n, b.n, c.n"
+        ),
+        [ints(&[1, 2, 4])]
+    );
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (a:A) OPTIONAL MATCH (a)-[:R]->(x), (z:Z)-[:S]->(x)-[:T]->(c) \
+             RETURN a.n, z.n, c.n"
+        ),
+        [vec![Value::Int(1), Value::Null, Value::Null]]
+    );
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (a:A) OPTIONAL MATCH (b:B {n: 3}), (c:C {n: 4}) RETURN a.n, b.n, c.n"
+        ),
+        [ints(&[1, 3, 4])]
+    );
+}
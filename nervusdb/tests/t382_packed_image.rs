@@ -0,0 +1,98 @@
+mod common;
+
+use common::{column, try_write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+#[test]
+fn t382_packed_image_serves_reads_and_refuses_writes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open(&path).unwrap();
+    try_write(
+        &db,
+        "UNWIND range(1, 300) AS i CREATE (:City {name: 'city-' + toString(i), size: i})",
+    )
+    .unwrap();
+    db.checkpoint().unwrap();
+    try_write(
+        &db,
+        "MATCH (a:City {size: 1}), (b:City {size: 2}) CREATE (a)-[:ROAD {km: 12}]->(b)",
+    )
+    .unwrap();
+    db.create_index("City", "size").unwrap();
+
+    let packed = dir.path().join("graph.ndbpack");
+    let report = db.pack(&packed).unwrap();
+    assert_eq!(
+        report.packed_bytes,
+        std::fs::metadata(&packed).unwrap().len()
+    );
+    assert!(report.packed_bytes < report.uncompressed_bytes);
+    let on_disk = std::fs::metadata(db.ndb_path()).unwrap().len()
+        + std::fs::metadata(db.wal_path()).unwrap().len();
+    assert!(report.packed_bytes < on_disk, "{report:?} vs {on_disk}");
+    try_write(&db, "CREATE (:City {name: 'after-pack', size: 0})").unwrap();
+    db.close().unwrap();
+
+    let shipped = Db::open_packed(&packed).unwrap();
+    assert!(shipped.is_read_only());
+    assert_eq!(
+        column(&shipped, "MATCH (c:City) RETURN count(c)"),
+        [Value::Int(300)]
+    );
+    assert_eq!(
+        column(
+            &shipped,
+            "MATCH (:City {size: 1})-[r:ROAD]->(b:City) RETURN b.name + ':' + toString(r.km)"
+        ),
+        [Value::String("city-2:12".into())]
+    );
+
+    let err = try_write(&shipped, "CREATE (:City {name: 'local', size: 0})").unwrap_err();
+    assert!(err.to_string().contains("read-only"), "{err}");
+    let err = try_write(&shipped, "CREATE (:Village)").unwrap_err();
+    assert!(err.to_string().contains("read-only"), "{err}");
+    assert!(shipped.create_index("City", "name").is_err());
+    assert!(shipped.checkpoint().is_err());
+    assert_eq!(
+        column(&shipped, "MATCH (c:City) RETURN count(c)"),
+        [Value::Int(300)]
+    );
+    shipped.close().unwrap();
+
+    let bytes = std::fs::read(&packed).unwrap();
+    let embedded = Db::open_packed_bytes(&bytes).unwrap();
+    assert_eq!(
+        column(&embedded, "MATCH (c:City {size: 150}) RETURN c.name"),
+        [Value::String("city-150".into())]
+    );
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.starts_with("graph.ndbpack."))
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn t382_open_packed_rejects_other_files() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open(&path).unwrap();
+    try_write(&db, "CREATE (:Node {n: 1})").unwrap();
+    db.close().unwrap();
+
+    assert!(Db::open_packed(path.with_extension("ndb")).is_err());
+    assert!(Db::open_packed(dir.path().join("missing.ndbpack")).is_err());
+
+    let db = Db::open(&path).unwrap();
+    let packed = dir.path().join("graph.ndbpack");
+    db.pack(&packed).unwrap();
+    let mut bytes = std::fs::read(&packed).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x5A;
+    let err = Db::open_packed_bytes(&bytes).unwrap_err();
+    assert!(err.to_string().contains("checksum"), "{err}");
+}
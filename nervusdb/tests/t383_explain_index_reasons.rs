@@ -0,0 +1,94 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+/// The `plan` text of an EXPLAIN, its only column.
+fn explain(db: &Db, cypher: &str) -> String {
+    let snapshot = db.snapshot();
+    let query = prepare(&format!("EXPLAIN {cypher}")).unwrap();
+    let row = query
+        .execute_streaming(&snapshot, &Params::default())
+        .next()
+        .unwrap()
+        .unwrap();
+    match row.columns() {
+        [(name, Value::String(plan))] if name == "plan" => plan.clone(),
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn t383_explain_reports_why_an_index_is_or_is_not_used() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(
+        &db,
+        "UNWIND range(1, 100) AS i CREATE (:City {name: 'city-' + toString(i), size: i})",
+    );
+
+    let cypher = "MATCH (c:City {name: 'city-7'}) RETURN c";
+    let plan = explain(&db, cypher);
+    assert!(
+        plan.contains("[NodeScan: no index on :City(name)"),
+        "{plan}"
+    );
+    assert!(plan.contains("statistics missing for :City"), "{plan}");
+
+    db.create_index("City", "name").unwrap();
+    db.create_index("City", "size").unwrap();
+    db.compact().unwrap();
+    let plan = explain(&db, cypher);
+    assert!(
+        plan.contains("[IndexSeek: equality on c.name uses index City.name]"),
+        "{plan}"
+    );
+
+    let plan = explain(&db, "MATCH (c:City) WHERE c.size > 90 RETURN c");
+    assert!(
+        plan.contains(
+            "[NodeScan: range comparison on c.size is not sargable; \
+             index City.size only answers equality; scans about 100 rows]"
+        ),
+        "{plan}"
+    );
+    assert!(!plan.contains("statistics missing"), "{plan}");
+
+    let plan = explain(
+        &db,
+        "MATCH (c:City) WHERE toLower(c.name) = 'city-7' RETURN c",
+    );
+    assert!(
+        plan.contains("function call on c.name is not sargable"),
+        "{plan}"
+    );
+
+    let plan = explain(
+        &db,
+        "MATCH (c:City) WHERE c.size = 1 OR c.size = 2 RETURN c",
+    );
+    assert!(
+        plan.contains("OR branch on c.size is not sargable"),
+        "{plan}"
+    );
+
+    let plan = explain(&db, "MATCH (n) WHERE n.name = 'city-7' RETURN n");
+    assert!(plan.contains("node pattern has no label"), "{plan}");
+
+    let plan = explain(&db, "MATCH (c:City) RETURN count(c)");
+    assert!(plan.contains("no property predicate on c"), "{plan}");
+}
+
+#[test]
+fn t383_explain_without_node_patterns_matches_the_prepared_plan() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let query = prepare("EXPLAIN RETURN 1").unwrap();
+    assert_eq!(explain(&db, "RETURN 1"), query.explain_string().unwrap());
+
+    // The prepared plan text stays snapshot-independent.
+    let query = prepare("EXPLAIN MATCH (c:City {name: 'x'}) RETURN c").unwrap();
+    assert!(!query.explain_string().unwrap().contains("no index"));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+/// A -> B -> D costs 1 + 5, A -> C -> D costs 2 + 1, and the direct
+/// A -> D road costs 10.
+fn roads(db: &Db) {
+    write(
+        db,
+        "CREATE (a:Town {name: 'A'}), (b:Town {name: 'B'}), (c:Town {name: 'C'}), \
+         (d:Town {name: 'D'}), (e:Town {name: 'E'}), \
+         (a)-[:ROAD {km: 1}]->(b), (b)-[:ROAD {km: 5}]->(d), \
+         (a)-[:ROAD {km: 2}]->(c), (c)-[:ROAD {km: 1.5}]->(d), \
+         (a)-[:ROAD {km: 10}]->(d), (d)-[:FERRY]->(e)",
+    );
+}
+
+#[test]
+fn t384_dijkstra_returns_the_cheapest_weighted_path() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    roads(&db);
+
+    let found = try_rows(
+        &db,
+        "MATCH (a:Town {name: 'A'}), (d:Town {name: 'D'}) \
+         CALL algo.dijkstra(a, d, 'km') YIELD path, cost \
+         RETURN [n IN nodes(path) | n.name], length(path), cost",
+    )
+    .unwrap();
+    assert_eq!(
+        found,
+        [vec![
+            Value::List(vec![
+                Value::String("A".into()),
+                Value::String("C".into()),
+                Value::String("D".into()),
+            ]),
+            Value::Int(2),
+            Value::Float(3.5),
+        ]]
+    );
+
+    // Unweighted relationships are not traversed, so E is unreachable.
+    let none = try_rows(
+        &db,
+        "MATCH (a:Town {name: 'A'}), (e:Town {name: 'E'}) \
+         CALL algo.dijkstra(a, e, 'km') YIELD path, cost RETURN cost",
+    )
+    .unwrap();
+    assert!(none.is_empty());
+
+    // Relationships are followed in their direction only.
+    let backwards = try_rows(
+        &db,
+        "MATCH (d:Town {name: 'D'}), (a:Town {name: 'A'}) \
+         CALL algo.dijkstra(d, a, 'km') YIELD cost RETURN cost",
+    )
+    .unwrap();
+    assert!(backwards.is_empty());
+
+    let same = try_rows(
+        &db,
+        "MATCH (a:Town {name: 'A'}) CALL algo.dijkstra(a, a, 'km') YIELD path, cost \
+         RETURN length(path), cost",
+    )
+    .unwrap();
+    assert_eq!(same, [vec![Value::Int(0), Value::Float(0.0)]]);
+}
+
+#[test]
+fn t384_dijkstra_rejects_bad_arguments_and_negative_weights() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    roads(&db);
+
+    let err = try_rows(
+        &db,
+        "MATCH (a:Town {name: 'A'}) CALL algo.dijkstra(a, 'D', 'km') YIELD cost RETURN cost",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("start and end nodes"), "{err}");
+    let err = try_rows(
+        &db,
+        "MATCH (a:Town {name: 'A'}) CALL algo.dijkstra(a, a) YIELD cost RETURN cost",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("3 arguments"), "{err}");
+
+    write(
+        &db,
+        "MATCH (e:Town {name: 'E'}), (a:Town {name: 'A'}) CREATE (a)-[:ROAD {km: -4}]->(e)",
+    );
+    let err = try_rows(
+        &db,
+        "MATCH (a:Town {name: 'A'}), (e:Town {name: 'E'}) \
+         CALL algo.dijkstra(a, e, 'km') YIELD cost RETURN cost",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("non-negative"), "{err}");
+}
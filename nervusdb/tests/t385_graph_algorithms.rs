@@ -0,0 +1,143 @@
+mod common;
+
+use common::{try_rows, try_write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+fn float(value: &Value) -> f64 {
+    match value {
+        Value::Float(f) => *f,
+        other => panic!("expected a float, got {other:?}"),
+    }
+}
+
+/// Pages a, b and c all link to hub; hub links back to a. A separate pair
+/// x -> y forms a second component.
+fn web(db: &Db) {
+    try_write(
+        db,
+        "CREATE (a:Page {name: 'a'}), (b:Page {name: 'b'}), (c:Page {name: 'c'}), \
+         (hub:Page {name: 'hub'}), (x:Page {name: 'x'}), (y:Page {name: 'y'}), \
+         (a)-[:LINK]->(hub), (b)-[:LINK]->(hub), (c)-[:LINK]->(hub), (hub)-[:LINK]->(a), \
+         (x)-[:LINK]->(y)",
+    )
+    .unwrap();
+}
+
+#[test]
+fn t385_page_rank_streams_scores_per_node() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    web(&db);
+
+    let ranked = try_rows(
+        &db,
+        "CALL algo.pageRank({iterations: 30, dampingFactor: 0.85}) YIELD node, score \
+         RETURN node.name, score ORDER BY score DESC",
+    )
+    .unwrap();
+    assert_eq!(ranked.len(), 6);
+    assert_eq!(ranked[0][0], Value::String("hub".into()));
+    assert_eq!(ranked[1][0], Value::String("a".into()));
+    let total: f64 = ranked.iter().map(|row| float(&row[1])).sum();
+    assert!((total - 1.0).abs() < 1e-9, "{total}");
+
+    // Without damping every node keeps the same share.
+    let flat = try_rows(
+        &db,
+        "CALL algo.pageRank({dampingFactor: 0}) YIELD score RETURN DISTINCT score",
+    )
+    .unwrap();
+    assert_eq!(flat.len(), 1);
+    assert!((float(&flat[0][0]) - 1.0 / 6.0).abs() < 1e-12);
+
+    let defaults = try_rows(&db, "CALL algo.pageRank() YIELD node RETURN count(node)").unwrap();
+    assert_eq!(defaults, [vec![Value::Int(6)]]);
+
+    let err = try_rows(
+        &db,
+        "CALL algo.pageRank({dampingFactor: 1.5}) YIELD score RETURN score",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("dampingFactor"), "{err}");
+    let err = try_rows(
+        &db,
+        "CALL algo.pageRank({maxIterations: 3}) YIELD score RETURN score",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown configuration key"),
+        "{err}"
+    );
+}
+
+#[test]
+fn t385_wcc_groups_nodes_regardless_of_direction() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    web(&db);
+    try_write(&db, "CREATE (:Page {name: 'alone'})").unwrap();
+
+    let components = try_rows(
+        &db,
+        "CALL algo.wcc() YIELD node, componentId \
+         RETURN componentId, collect(node.name) AS names ORDER BY size(names) DESC",
+    )
+    .unwrap();
+    let mut groups: Vec<Vec<String>> = components
+        .iter()
+        .map(|row| match &row[1] {
+            Value::List(names) => {
+                let mut names: Vec<String> = names
+                    .iter()
+                    .map(|name| match name {
+                        Value::String(name) => name.clone(),
+                        other => panic!("{other:?}"),
+                    })
+                    .collect();
+                names.sort();
+                names
+            }
+            other => panic!("{other:?}"),
+        })
+        .collect();
+    groups.sort();
+    assert_eq!(
+        groups,
+        [vec!["a", "b", "c", "hub"], vec!["alone"], vec!["x", "y"]]
+    );
+}
+
+#[test]
+fn t385_write_variants_store_results_as_properties() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    web(&db);
+
+    let written = try_write(
+        &db,
+        "CALL algo.pageRank.write({writeProperty: 'rank', iterations: 30}) \
+         YIELD nodePropertiesWritten RETURN nodePropertiesWritten",
+    )
+    .unwrap();
+    assert_eq!(written, 6);
+    let top = try_rows(
+        &db,
+        "MATCH (p:Page) RETURN p.name ORDER BY p.rank DESC LIMIT 1",
+    )
+    .unwrap();
+    assert_eq!(top, [vec![Value::String("hub".into())]]);
+
+    try_write(&db, "CALL algo.wcc.write({writeProperty: 'component'})").unwrap();
+    let same = try_rows(
+        &db,
+        "MATCH (a:Page {name: 'a'}), (b:Page {name: 'hub'}), (x:Page {name: 'x'}) \
+         RETURN a.component = b.component, a.component = x.component",
+    )
+    .unwrap();
+    assert_eq!(same, [vec![Value::Bool(true), Value::Bool(false)]]);
+
+    let err = try_write(&db, "CALL algo.wcc.write()").unwrap_err();
+    assert!(err.to_string().contains("writeProperty"), "{err}");
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{count, write};
+use nervusdb::{BulkEdge, BulkNode, Db, Error, OperationHandle, PropertyValue};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+#[test]
+fn t386_compact_reports_progress_and_honours_early_cancellation() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(
+        &db,
+        "UNWIND range(1, 500) AS i CREATE (:Item {n: i, tag: 'x'})",
+    );
+
+    let handle = OperationHandle::new();
+    handle.cancel();
+    let err = db.compact_with_progress(&handle, |_, _| {}).unwrap_err();
+    assert!(matches!(err, Error::Cancelled), "{err}");
+    assert_eq!(count(&db, "MATCH (i:Item) RETURN sum(i.n)"), 125_250);
+
+    let handle = OperationHandle::new();
+    let mut reports = Vec::new();
+    db.compact_with_progress(&handle, |done, total| reports.push((done, total)))
+        .unwrap();
+    // One unit for the segment, one per property sunk, one for the manifest.
+    assert_eq!(reports.first(), Some(&(0, 1002)));
+    assert_eq!(reports.last(), Some(&(1002, 1002)));
+    assert!(reports.len() <= 102, "{}", reports.len());
+    assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(handle.progress().percent(), 100.0);
+    assert_eq!(count(&db, "MATCH (i:Item) RETURN sum(i.n)"), 125_250);
+
+    // Nothing left to compact.
+    db.compact_with_progress(&handle, |_, _| {}).unwrap();
+    assert_eq!(handle.progress().total, 0);
+}
+
+#[test]
+fn t386_cancelled_vacuum_keeps_the_database() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    {
+        let db = Db::open(&path).unwrap();
+        write(&db, "UNWIND range(1, 200) AS i CREATE (:Item {n: i})");
+        db.checkpoint().unwrap();
+    }
+
+    let handle = OperationHandle::new();
+    let watcher = handle.clone();
+    let err = nervusdb::vacuum_with_progress(&path, &handle, |done, _| {
+        if done > 0 {
+            watcher.cancel();
+        }
+    })
+    .unwrap_err();
+    assert!(matches!(err, Error::Cancelled), "{err}");
+
+    let report = nervusdb::vacuum_with_progress(&path, &OperationHandle::new(), |_, _| {}).unwrap();
+    assert!(report.copied_data_pages > 0);
+    let db = Db::open(&path).unwrap();
+    assert_eq!(count(&db, "MATCH (i:Item) RETURN count(i)"), 200);
+}
+
+#[test]
+fn t386_cancelled_bulkload_leaves_no_database() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("bulk");
+    let nodes = || {
+        (0..100)
+            .map(|id| BulkNode {
+                external_id: id,
+                label: "Item".to_string(),
+                properties: BTreeMap::from([("n".to_string(), PropertyValue::Int(id as i64))]),
+            })
+            .collect::<Vec<_>>()
+    };
+    let edges = || {
+        (1..100)
+            .map(|id| BulkEdge {
+                src_external_id: id - 1,
+                rel_type: "NEXT".to_string(),
+                dst_external_id: id,
+                properties: BTreeMap::new(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let handle = OperationHandle::new();
+    let watcher = handle.clone();
+    let err = nervusdb::bulkload_with_progress(&path, nodes(), edges(), &handle, |done, _| {
+        if done >= 50 {
+            watcher.cancel();
+        }
+    })
+    .unwrap_err();
+    assert!(matches!(err, Error::Cancelled), "{err}");
+    assert!(!path.with_extension("ndb").exists());
+    assert!(!path.with_extension("wal").exists());
+
+    let mut last = (0, 0);
+    nervusdb::bulkload_with_progress(
+        &path,
+        nodes(),
+        edges(),
+        &OperationHandle::new(),
+        |done, total| {
+            last = (done, total);
+        },
+    )
+    .unwrap();
+    assert_eq!(last, (398, 398));
+    let db = Db::open(&path).unwrap();
+    assert_eq!(count(&db, "MATCH ()-[r:NEXT]->() RETURN count(r)"), 99);
+}
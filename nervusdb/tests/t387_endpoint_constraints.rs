@@ -0,0 +1,126 @@
+mod common;
+
+use common::{count, try_write};
+use nervusdb::Db;
+use tempfile::tempdir;
+
+#[test]
+fn t387_endpoint_constraint_rejects_relationships_between_other_labels() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    db.create_endpoint_constraint("KNOWS", &[("Person", "Person")])
+        .unwrap();
+
+    try_write(
+        &db,
+        "CREATE (:Person {name: 'a'})-[:KNOWS]->(:Person {name: 'b'})",
+    )
+    .unwrap();
+    let err = try_write(
+        &db,
+        "CREATE (:Person {name: 'c'})-[:KNOWS]->(:City {name: 'Oslo'})",
+    )
+    .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("constraint violation"), "{message}");
+    assert!(message.contains("(:Person)->(:City)"), "{message}");
+    assert!(
+        message.contains("only allows (:Person)->(:Person)"),
+        "{message}"
+    );
+    assert_eq!(count(&db, "MATCH (n) RETURN count(n)"), 2);
+
+    // Other relationship types are not affected, and extra labels are fine.
+    try_write(&db, "CREATE (:Person)-[:VISITED]->(:City)").unwrap();
+    try_write(&db, "CREATE (:Person:Admin)-[:KNOWS]->(:Robot:Person)").unwrap();
+
+    // Labels staged in the same transaction count.
+    try_write(
+        &db,
+        "CREATE (a:Draft), (b:Person) SET a:Person CREATE (a)-[:KNOWS]->(b)",
+    )
+    .unwrap();
+
+    let mut txn = db.begin_write();
+    let person = txn.get_or_create_label("Person").unwrap();
+    let city = txn.get_or_create_label("City").unwrap();
+    let knows = txn.get_or_create_rel_type("KNOWS").unwrap();
+    let src = txn.create_node(100, person).unwrap();
+    let dst = txn.create_node(101, city).unwrap();
+    txn.create_edge(src, knows, dst);
+    assert!(
+        txn.commit()
+            .unwrap_err()
+            .to_string()
+            .contains("constraint violation")
+    );
+
+    assert!(db.drop_endpoint_constraint("KNOWS").unwrap());
+    assert!(!db.drop_endpoint_constraint("KNOWS").unwrap());
+    try_write(&db, "CREATE (:Person)-[:KNOWS]->(:City)").unwrap();
+}
+
+#[test]
+fn t387_creation_checks_existing_relationships_and_replaces_pairs() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    try_write(
+        &db,
+        "CREATE (:Person)-[:WORKS_AT]->(:Company), (:Person)-[:WORKS_AT]->(:School)",
+    )
+    .unwrap();
+
+    let err = db
+        .create_endpoint_constraint("WORKS_AT", &[("Person", "Company")])
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("cannot constrain WORKS_AT"),
+        "{err}"
+    );
+    assert!(db.endpoint_constraints().is_empty());
+
+    db.create_endpoint_constraint("WORKS_AT", &[("Person", "Company"), ("Person", "School")])
+        .unwrap();
+    assert_eq!(
+        db.endpoint_constraints(),
+        [
+            ("WORKS_AT".into(), "Person".into(), "Company".into()),
+            ("WORKS_AT".into(), "Person".into(), "School".into()),
+        ]
+    );
+    assert!(db.create_endpoint_constraint("WORKS_AT", &[]).is_err());
+}
+
+#[test]
+fn t387_constraints_survive_reopen_and_follow_renamed_labels() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    {
+        let db = Db::open(&path).unwrap();
+        db.create_endpoint_constraint("KNOWS", &[("Person", "Person")])
+            .unwrap();
+        try_write(&db, "CREATE (:Person)-[:KNOWS]->(:Person)").unwrap();
+        assert!(db.rename_label("Person", "Human").unwrap());
+        assert_eq!(
+            db.endpoint_constraints(),
+            [("KNOWS".into(), "Human".into(), "Human".into())]
+        );
+    }
+    {
+        let db = Db::open(&path).unwrap();
+        assert_eq!(
+            db.endpoint_constraints(),
+            [("KNOWS".into(), "Human".into(), "Human".into())]
+        );
+        assert!(try_write(&db, "CREATE (:Human)-[:KNOWS]->(:City)").is_err());
+        assert!(db.rename_label("KNOWS", "FRIEND_OF").unwrap());
+        db.checkpoint().unwrap();
+    }
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        db.endpoint_constraints(),
+        [("FRIEND_OF".into(), "Human".into(), "Human".into())]
+    );
+    assert!(try_write(&db, "CREATE (:Human)-[:FRIEND_OF]->(:City)").is_err());
+    try_write(&db, "CREATE (:Human)-[:KNOWS]->(:City)").unwrap();
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+#[test]
+fn t388_triangle_count_yields_triangles_and_clustering_per_node() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    // a, b and c form a triangle, whichever way the relationships point;
+    // d hangs off c, and the duplicate and self-loop relationships add nothing.
+    write(
+        &db,
+        "CREATE (a:P {name: 'a'}), (b:P {name: 'b'}), (c:P {name: 'c'}), (d:P {name: 'd'}), \
+         (a)-[:KNOWS]->(b), (b)-[:KNOWS]->(c), (a)-[:KNOWS]->(c), (c)-[:KNOWS]->(a), \
+         (c)-[:KNOWS]->(d), (d)-[:KNOWS]->(d)",
+    );
+
+    let counted = try_rows(
+        &db,
+        "CALL algo.triangleCount() YIELD nodeId, triangles, coefficient \
+         MATCH (n) WHERE id(n) = nodeId \
+         RETURN n.name, triangles, coefficient ORDER BY n.name",
+    )
+    .unwrap();
+    assert_eq!(
+        counted,
+        [
+            vec![Value::String("a".into()), Value::Int(1), Value::Float(1.0)],
+            vec![Value::String("b".into()), Value::Int(1), Value::Float(1.0)],
+            vec![
+                Value::String("c".into()),
+                Value::Int(1),
+                Value::Float(1.0 / 3.0)
+            ],
+            vec![Value::String("d".into()), Value::Int(0), Value::Float(0.0)],
+        ]
+    );
+
+    let err = try_rows(
+        &db,
+        "CALL algo.triangleCount({concurrency: 4}) YIELD triangles RETURN triangles",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown configuration key"),
+        "{err}"
+    );
+}
+
+#[test]
+fn t388_triangle_count_matches_a_complete_graph() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(&db, "UNWIND range(1, 12) AS i CREATE (:K {i: i})");
+    write(
+        &db,
+        "MATCH (x:K), (y:K) WHERE x.i < y.i CREATE (x)-[:E]->(y)",
+    );
+
+    // Every node of K12 sits in C(11, 2) = 55 triangles and is fully clustered.
+    let summary = try_rows(
+        &db,
+        "CALL algo.triangleCount() YIELD triangles, coefficient \
+         RETURN count(*), min(triangles), max(triangles), min(coefficient)",
+    )
+    .unwrap();
+    assert_eq!(
+        summary,
+        [vec![
+            Value::Int(12),
+            Value::Int(55),
+            Value::Int(55),
+            Value::Float(1.0)
+        ]]
+    );
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+fn names(value: &Value) -> Vec<String> {
+    let Value::List(items) = value else {
+        panic!("expected a list, got {value:?}");
+    };
+    let mut names: Vec<String> = items
+        .iter()
+        .map(|item| match item {
+            Value::String(name) => name.clone(),
+            other => panic!("{other:?}"),
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Two fully connected groups of four joined by a single relationship, and
+/// one node on its own.
+fn two_cliques(db: &Db) {
+    write(
+        db,
+        "UNWIND ['a1', 'a2', 'a3', 'a4', 'b1', 'b2', 'b3', 'b4', 'loner'] AS name \
+         CREATE (:Person {name: name, team: substring(name, 0, 1)})",
+    );
+    write(
+        db,
+        "MATCH (x:Person), (y:Person) WHERE x.team = y.team AND x.team <> 'l' \
+         AND x.name < y.name CREATE (x)-[:KNOWS]->(y)",
+    );
+    write(
+        db,
+        "MATCH (a:Person {name: 'a4'}), (b:Person {name: 'b1'}) CREATE (b)-[:KNOWS]->(a)",
+    );
+}
+
+#[test]
+fn t389_louvain_separates_densely_connected_groups() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    two_cliques(&db);
+
+    let communities = try_rows(
+        &db,
+        "CALL algo.louvain() YIELD nodeId, communityId \
+         MATCH (p:Person) WHERE id(p) = nodeId \
+         RETURN communityId, collect(p.name) AS members, min(id(p)) = communityId",
+    )
+    .unwrap();
+    let mut groups: Vec<Vec<String>> = communities
+        .iter()
+        .map(|row| {
+            assert_eq!(row[2], Value::Bool(true), "{row:?}");
+            names(&row[1])
+        })
+        .collect();
+    groups.sort();
+    assert_eq!(
+        groups,
+        [
+            vec!["a1", "a2", "a3", "a4"],
+            vec!["b1", "b2", "b3", "b4"],
+            vec!["loner"],
+        ]
+    );
+
+    let same = try_rows(
+        &db,
+        "CALL algo.louvain(20, 0.0) YIELD communityId RETURN count(DISTINCT communityId)",
+    )
+    .unwrap();
+    assert_eq!(same, [vec![Value::Int(3)]]);
+}
+
+#[test]
+fn t389_louvain_validates_arguments() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(&db, "CREATE (:Person)");
+
+    let single = try_rows(
+        &db,
+        "CALL algo.louvain(5) YIELD nodeId RETURN count(nodeId)",
+    )
+    .unwrap();
+    assert_eq!(single, [vec![Value::Int(1)]]);
+
+    let err = try_rows(
+        &db,
+        "CALL algo.louvain(0) YIELD communityId RETURN communityId",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("maxIterations"), "{err}");
+    let err = try_rows(
+        &db,
+        "CALL algo.louvain(10, -1.0) YIELD communityId RETURN communityId",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("tolerance"), "{err}");
+    let err = try_rows(
+        &db,
+        "CALL algo.louvain(10, 0.1, 3) YIELD communityId RETURN communityId",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("at most 2 arguments"), "{err}");
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::{GraphSnapshot, Value};
+use tempfile::tempdir;
+
+/// A hub with 100 light `LINK`s, one heavy `LINK` and one weightless `LINK`,
+/// plus a `TAG` relationship.
+fn hub(db: &Db) {
+    write(db, "CREATE (:Hub {name: 'hub'})");
+    write(
+        db,
+        "MATCH (h:Hub) UNWIND range(1, 100) AS i \
+         CREATE (h)-[:LINK {w: 1}]->(:Leaf {name: 'leaf' + toString(i)})",
+    );
+    write(
+        db,
+        "MATCH (h:Hub) CREATE (h)-[:LINK {w: 100000.0}]->(:Leaf {name: 'heavy'}), \
+         (h)-[:LINK {w: 0}]->(:Leaf {name: 'never'}), (h)-[:TAG]->(:Tag {name: 'tag'})",
+    );
+}
+
+#[test]
+fn t390_snapshot_samples_distinct_edges_in_proportion_to_weight() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    hub(&db);
+    let snapshot = db.snapshot();
+    let hub = snapshot.nodes().next().unwrap();
+    let link = snapshot.resolve_rel_type_id("LINK");
+
+    let sample = snapshot.sample_neighbors(hub, link, 10, None, 7);
+    assert_eq!(sample.len(), 10);
+    let mut distinct = sample.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), 10);
+    assert_eq!(snapshot.sample_neighbors(hub, link, 10, None, 7), sample);
+    assert!(snapshot.sample_neighbors(hub, link, 0, None, 7).is_empty());
+
+    // Every edge is drawn when k covers them, except the zero-weight one.
+    assert_eq!(
+        snapshot.sample_neighbors(hub, None, 500, None, 1).len(),
+        103
+    );
+    assert_eq!(
+        snapshot
+            .sample_neighbors(hub, link, 500, Some("w"), 1)
+            .len(),
+        101
+    );
+
+    let heavy = (0..50)
+        .filter(|&seed| {
+            let drawn = snapshot.sample_neighbors(hub, link, 1, Some("w"), seed);
+            snapshot.node_property(drawn[0].dst, "name")
+                == Some(nervusdb::PropertyValue::String("heavy".into()))
+        })
+        .count();
+    assert!(heavy >= 45, "{heavy}");
+}
+
+#[test]
+fn t390_sample_neighbors_procedure_yields_relationships_and_neighbors() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    hub(&db);
+
+    let drawn = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, 5, {relationshipType: 'LINK', seed: 42}) \
+         YIELD relationship, neighbor RETURN neighbor.name, type(relationship)",
+    )
+    .unwrap();
+    assert_eq!(drawn.len(), 5);
+    assert!(
+        drawn
+            .iter()
+            .all(|row| row[1] == Value::String("LINK".into()))
+    );
+    let again = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, 5, {relationshipType: 'LINK', seed: 42}) \
+         YIELD relationship, neighbor RETURN neighbor.name, type(relationship)",
+    )
+    .unwrap();
+    assert_eq!(drawn, again);
+
+    let tag = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, 3, {relationshipType: 'TAG'}) \
+         YIELD neighbor RETURN neighbor.name",
+    )
+    .unwrap();
+    assert_eq!(tag, [vec![Value::String("tag".into())]]);
+
+    let weighted = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, 200, {weightProperty: 'w'}) \
+         YIELD neighbor RETURN count(neighbor)",
+    )
+    .unwrap();
+    assert_eq!(weighted, [vec![Value::Int(101)]]);
+
+    let none = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, 3, {relationshipType: 'MISSING'}) \
+         YIELD neighbor RETURN neighbor",
+    )
+    .unwrap();
+    assert!(none.is_empty());
+
+    let err = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, -1) YIELD neighbor RETURN neighbor",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("non-negative"), "{err}");
+    let err = try_rows(
+        &db,
+        "MATCH (h:Hub) CALL algo.sampleNeighbors(h, 1, {depth: 2}) YIELD neighbor RETURN neighbor",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown configuration key"),
+        "{err}"
+    );
+}
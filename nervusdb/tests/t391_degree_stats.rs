@@ -0,0 +1,117 @@
+mod common;
+
+use common::{rows, write};
+use nervusdb::query::Value;
+use nervusdb::{BulkEdge, BulkNode, Db};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+#[test]
+fn t391_degree_histograms_follow_compaction() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    // Ten fans follow one star; the first three fans also follow each other.
+    write(
+        &db,
+        "CREATE (:Star {name: 'star'}) WITH 1 AS x \
+         UNWIND range(1, 10) AS i CREATE (:Fan {n: i})",
+    );
+    write(&db, "MATCH (f:Fan), (s:Star) CREATE (f)-[:FOLLOWS]->(s)");
+    write(
+        &db,
+        "MATCH (a:Fan), (b:Fan) WHERE a.n < b.n AND b.n <= 3 CREATE (a)-[:FOLLOWS]->(b)",
+    );
+    assert!(db.degree_stats().is_empty());
+
+    db.checkpoint().unwrap();
+    let stats = db.degree_stats();
+    assert_eq!(stats.len(), 1);
+    let follows = &stats[0];
+    assert_eq!(follows.rel_type, "FOLLOWS");
+    assert_eq!(follows.relationships, 13);
+    // Out-degrees: fan 1 has 3, fan 2 has 2, the other eight have 1.
+    assert_eq!(follows.outgoing.buckets, [8, 2]);
+    assert_eq!(follows.outgoing.max_degree, 3);
+    // In-degrees: star 10, fan 2 has 1, fan 3 has 2.
+    assert_eq!(follows.incoming.buckets, [1, 1, 0, 1]);
+    assert_eq!(follows.incoming.max_degree, 10);
+
+    let top = rows(
+        &db,
+        "CALL db.stats.degrees() \
+         YIELD relType, direction, relationships, nodes, meanDegree, maxDegree, maxNode, buckets \
+         RETURN relType, direction, relationships, nodes, meanDegree, maxDegree, maxNode.name, buckets \
+         ORDER BY direction",
+    );
+    assert_eq!(
+        top,
+        [
+            vec![
+                Value::String("FOLLOWS".into()),
+                Value::String("INCOMING".into()),
+                Value::Int(13),
+                Value::Int(3),
+                Value::Float(13.0 / 3.0),
+                Value::Int(10),
+                Value::String("star".into()),
+                Value::List(vec![
+                    Value::Int(1),
+                    Value::Int(1),
+                    Value::Int(0),
+                    Value::Int(1)
+                ]),
+            ],
+            vec![
+                Value::String("FOLLOWS".into()),
+                Value::String("OUTGOING".into()),
+                Value::Int(13),
+                Value::Int(10),
+                Value::Float(1.3),
+                Value::Int(3),
+                Value::Null,
+                Value::List(vec![Value::Int(8), Value::Int(2)]),
+            ],
+        ]
+    );
+
+    // New relationships show up after the next compaction.
+    write(&db, "MATCH (s:Star) CREATE (s)-[:LIKES]->(:Fan)");
+    assert_eq!(db.degree_stats().len(), 1);
+    db.checkpoint().unwrap();
+    let names: Vec<String> = db.degree_stats().into_iter().map(|d| d.rel_type).collect();
+    assert_eq!(names, ["FOLLOWS", "LIKES"]);
+}
+
+#[test]
+fn t391_bulk_load_records_degrees_and_they_survive_reopen() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("bulk");
+    let nodes = (0..5)
+        .map(|id| BulkNode {
+            external_id: id,
+            label: "Item".to_string(),
+            properties: BTreeMap::new(),
+        })
+        .collect();
+    let edges = (1..5)
+        .map(|id| BulkEdge {
+            src_external_id: 0,
+            rel_type: "LINKS".to_string(),
+            dst_external_id: id,
+            properties: BTreeMap::new(),
+        })
+        .collect();
+    nervusdb::bulkload(&path, nodes, edges).unwrap();
+
+    let db = Db::open(&path).unwrap();
+    let stats = db.degree_stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].outgoing.buckets, [0, 0, 1]);
+    assert_eq!(stats[0].outgoing.max_degree, 4);
+    assert_eq!(stats[0].incoming.buckets, [4]);
+    drop(db);
+
+    let db = Db::open(&path).unwrap();
+    db.checkpoint().unwrap();
+    assert_eq!(db.degree_stats(), stats);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::{
+    ErasedSnapshot, Row, Signature, Value, register_function, register_procedure,
+    unregister_function, unregister_procedure,
+};
+use tempfile::tempdir;
+
+#[test]
+fn t392_registered_function_is_called_from_cypher() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(&db, "CREATE (:Item {price: 10}), (:Item {price: 25})");
+
+    register_function(
+        "t392.discount",
+        Signature::function(["price", "percent"]),
+        |args: &[Value]| match args {
+            [Value::Int(price), Value::Int(percent)] => {
+                Value::Float(*price as f64 * (100 - percent) as f64 / 100.0)
+            }
+            _ => Value::Null,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (i:Item) RETURN T392.DISCOUNT(i.price, 20) ORDER BY i.price"
+        )
+        .unwrap(),
+        [vec![Value::Float(8.0)], vec![Value::Float(20.0)]]
+    );
+    assert_eq!(
+        try_rows(&db, "RETURN t392.discount('free', 20)").unwrap(),
+        [vec![Value::Null]]
+    );
+    let err = try_rows(&db, "RETURN t392.discount(1)").unwrap_err();
+    assert!(
+        err.to_string().contains("InvalidNumberOfArguments"),
+        "{err}"
+    );
+
+    let err =
+        register_function("toUpper", Signature::default(), |_: &[Value]| Value::Null).unwrap_err();
+    assert!(err.to_string().contains("built-in"), "{err}");
+
+    assert!(unregister_function("t392.discount"));
+    assert!(!unregister_function("t392.discount"));
+    let err = try_rows(&db, "RETURN t392.discount(10, 20)").unwrap_err();
+    assert!(err.to_string().contains("UnknownFunction"), "{err}");
+}
+
+#[test]
+fn t392_registered_procedure_reads_the_snapshot() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(&db, "CREATE (:User {name: 'a'}), (:User {name: 'b'})");
+
+    register_procedure(
+        "t392.score",
+        Signature::procedure(["factor"], ["node", "score"]),
+        |snapshot: &dyn ErasedSnapshot, args: Vec<Value>| {
+            let Some(Value::Int(factor)) = args.first().cloned() else {
+                return Err(nervusdb::query::Error::Other(
+                    "t392.score: factor must be an integer".to_string(),
+                ));
+            };
+            Ok(snapshot
+                .nodes_erased()
+                .map(|node| {
+                    Row::new(vec![
+                        ("node".to_string(), Value::NodeId(node)),
+                        ("score".to_string(), Value::Int(factor * (node as i64 + 1))),
+                    ])
+                })
+                .collect())
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        try_rows(
+            &db,
+            "CALL t392.score(10) YIELD node, score RETURN node.name, score ORDER BY score"
+        )
+        .unwrap(),
+        [
+            vec![Value::String("a".into()), Value::Int(10)],
+            vec![Value::String("b".into()), Value::Int(20)],
+        ]
+    );
+    let err = try_rows(&db, "CALL t392.score('x') YIELD score RETURN score").unwrap_err();
+    assert!(
+        err.to_string().contains("factor must be an integer"),
+        "{err}"
+    );
+    let err = try_rows(&db, "CALL t392.score() YIELD score RETURN score").unwrap_err();
+    assert!(
+        err.to_string().contains("InvalidNumberOfArguments"),
+        "{err}"
+    );
+    let err = try_rows(&db, "CALL t392.score(1) YIELD rank RETURN rank").unwrap_err();
+    assert!(err.to_string().contains("no output 'rank'"), "{err}");
+
+    let err = register_procedure(
+        "db.labels",
+        Signature::default(),
+        |_: &dyn ErasedSnapshot, _: Vec<Value>| Ok(Vec::new()),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("built-in"), "{err}");
+
+    assert!(unregister_procedure("t392.score"));
+    assert!(try_rows(&db, "CALL t392.score(1) YIELD score RETURN score").is_err());
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn names(db: &Db, cypher: &str, params: &Params) -> Vec<Value> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, params)
+        .map(|row| row.unwrap().columns()[0].1.clone())
+        .collect()
+}
+
+fn garage(db: &Db) {
+    write(
+        db,
+        "CREATE (:Person {name: 'ada'})-[:OWNS]->(:Car {make: 'vw'}), \
+         (:Person {name: 'bob'})-[:OWNS]->(:Bike), (:Person {name: 'cy'})",
+    );
+}
+
+#[test]
+fn t393_exists_subquery_filters_on_correlated_variables() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    garage(&db);
+    let none = Params::default();
+
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (n:Person) WHERE EXISTS { MATCH (n)-[:OWNS]->(:Car) } RETURN n.name",
+            &none
+        ),
+        [Value::String("ada".into())]
+    );
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (n:Person) WHERE NOT EXISTS { MATCH (n)-[:OWNS]->(:Car) } \
+             RETURN n.name ORDER BY n.name",
+            &none
+        ),
+        [Value::String("bob".into()), Value::String("cy".into())]
+    );
+    // Variables bound by WITH and query parameters are visible inside.
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (n:Person) WITH n, 'vw' AS make \
+             WHERE EXISTS { MATCH (n)-[:OWNS]->(c) WHERE c.make = make } RETURN n.name",
+            &none
+        ),
+        [Value::String("ada".into())]
+    );
+    let mut params = Params::default();
+    params.insert("make", Value::String("audi".into()));
+    assert!(
+        names(
+            &db,
+            "MATCH (n:Person) WHERE EXISTS { MATCH (n)-[:OWNS]->(c) WHERE c.make = $make } \
+             RETURN n.name",
+            &params
+        )
+        .is_empty()
+    );
+    // Nested subqueries see both outer scopes.
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (n:Person) WHERE EXISTS { \
+               MATCH (n)-[:OWNS]->(x) WHERE EXISTS { MATCH (x:Car) WHERE n.name = 'ada' } \
+             } RETURN n.name",
+            &none
+        ),
+        [Value::String("ada".into())]
+    );
+}
+
+#[test]
+fn t393_exists_subquery_projects_as_a_boolean() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    garage(&db);
+
+    assert_eq!(
+        names(
+            &db,
+            "MATCH (n:Person) RETURN EXISTS { MATCH (n)-[:OWNS]->() } AS owns ORDER BY n.name",
+            &Params::default()
+        ),
+        [Value::Bool(true), Value::Bool(true), Value::Bool(false)]
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::write;
+use nervusdb::{Db, WalChange};
+use std::time::Duration;
+use tempfile::tempdir;
+
+fn created_nodes(db: &Db) -> usize {
+    db.wal_tail(0)
+        .unwrap()
+        .flat_map(|txn| txn.changes)
+        .filter(|change| matches!(change, WalChange::CreateNode { .. }))
+        .count()
+}
+
+#[test]
+fn t394_retention_keeps_history_across_checkpoints_and_reopen() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    {
+        let db = Db::open(&path).unwrap();
+        db.set_wal_retention(Some(Duration::from_secs(7 * 24 * 3600)))
+            .unwrap();
+        write(&db, "CREATE (:Event {n: 1})");
+        db.checkpoint().unwrap();
+        write(&db, "CREATE (:Event {n: 2})");
+        db.checkpoint().unwrap();
+        assert_eq!(db.archived_wal_segments().unwrap().len(), 2);
+        assert_eq!(created_nodes(&db), 2);
+    }
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        db.wal_retention().keep_for,
+        Some(Duration::from_secs(7 * 24 * 3600))
+    );
+    write(&db, "CREATE (:Event {n: 3})");
+    assert_eq!(created_nodes(&db), 3);
+
+    // Without retention the next checkpoint drops the archive and history.
+    db.set_wal_retention(None).unwrap();
+    db.checkpoint().unwrap();
+    assert!(db.archived_wal_segments().unwrap().is_empty());
+    let err = db.wal_tail(0).unwrap_err();
+    assert!(err.to_string().contains("wal history unavailable"), "{err}");
+}
+
+#[test]
+fn t394_legal_hold_pins_archives_until_released() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    {
+        let db = Db::open(&path).unwrap();
+        assert!(db.place_legal_hold("case-1138").unwrap());
+        assert!(!db.place_legal_hold("case-1138").unwrap());
+        write(&db, "CREATE (:Event {n: 1})");
+        db.checkpoint().unwrap();
+    }
+    let db = Db::open(&path).unwrap();
+    let policy = db.wal_retention();
+    assert_eq!(policy.keep_for, None);
+    assert_eq!(policy.holds.keys().collect::<Vec<_>>(), ["case-1138"]);
+    assert!(policy.holds["case-1138"] > 0);
+
+    // A retention period that has long passed deletes nothing while held.
+    db.set_wal_retention(Some(Duration::ZERO)).unwrap();
+    write(&db, "CREATE (:Event {n: 2})");
+    db.checkpoint().unwrap();
+    assert_eq!(db.archived_wal_segments().unwrap().len(), 2);
+    assert_eq!(created_nodes(&db), 2);
+
+    assert!(db.release_legal_hold("case-1138").unwrap());
+    assert!(!db.release_legal_hold("case-1138").unwrap());
+    db.checkpoint().unwrap();
+    assert!(db.archived_wal_segments().unwrap().is_empty());
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::{rows, write};
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn garage(db: &Db) {
+    write(
+        db,
+        "CREATE (a:Person {name: 'ada'}), (b:Person {name: 'bob'}), (:Person {name: 'cy'}), \
+         (a)-[:OWNS]->(:Car {make: 'vw', year: 2019}), \
+         (a)-[:OWNS]->(:Car {make: 'fiat', year: 2021}), \
+         (b)-[:OWNS]->(:Car {make: 'kia', year: 2015})",
+    );
+}
+
+fn s(text: &str) -> Value {
+    Value::String(text.to_string())
+}
+
+#[test]
+fn t395_count_subquery_in_return_and_where() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    garage(&db);
+
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) RETURN n.name, COUNT { MATCH (n)-[:OWNS]->(:Car) } AS cars \
+             ORDER BY n.name",
+        ),
+        [
+            vec![s("ada"), Value::Int(2)],
+            vec![s("bob"), Value::Int(1)],
+            vec![s("cy"), Value::Int(0)],
+        ]
+    );
+    // Bare pattern form, with and without WHERE.
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) WHERE COUNT { (n)-[:OWNS]->() } >= 1 RETURN n.name ORDER BY n.name",
+        ),
+        [vec![s("ada")], vec![s("bob")]]
+    );
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) \
+             RETURN n.name, count { (n)-[:OWNS]->(c:Car) WHERE c.year > 2016 } AS recent \
+             ORDER BY n.name",
+        ),
+        [
+            vec![s("ada"), Value::Int(2)],
+            vec![s("bob"), Value::Int(0)],
+            vec![s("cy"), Value::Int(0)],
+        ]
+    );
+    // count(...) stays the aggregate.
+    assert_eq!(
+        rows(&db, "MATCH (n:Person) RETURN count(n)"),
+        [vec![Value::Int(3)]]
+    );
+}
+
+#[test]
+fn t395_call_subquery_returns_a_single_value_or_null() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    garage(&db);
+
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) \
+             RETURN n.name, CALL { MATCH (n)-[:OWNS]->(c:Car) \
+             RETURN c.make AS make ORDER BY c.year DESC LIMIT 1 } AS newest \
+             ORDER BY n.name",
+        ),
+        [
+            vec![s("ada"), s("fiat")],
+            vec![s("bob"), s("kia")],
+            vec![s("cy"), Value::Null],
+        ]
+    );
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) \
+             WHERE CALL { MATCH (n)-[:OWNS]->(c:Car) RETURN max(c.year) } > 2020 \
+             RETURN n.name",
+        ),
+        [vec![s("ada")]]
+    );
+
+    for bad in [
+        "RETURN CALL { MATCH (c:Car) RETURN c.make, c.year } AS x",
+        "RETURN CALL { MATCH (c:Car) } AS x",
+        "MATCH (n) RETURN COUNT { MATCH (n)-->(c) CREATE (:Car) } AS x",
+    ] {
+        let Err(err) = prepare(bad) else {
+            panic!("{bad} should not prepare");
+        };
+        assert!(err.to_string().contains("syntax error"), "{bad}: {err}");
+    }
+}
+
+#[test]
+fn t395_uncorrelated_subqueries_match_their_correlated_results() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    garage(&db);
+
+    // The same subquery text evaluated once per row and, through the cache,
+    // once per execution gives the same answer on every row.
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) RETURN n.name, COUNT { MATCH (c:Car) } AS cars, \
+             CALL { MATCH (c:Car) RETURN min(c.year) } AS oldest ORDER BY n.name",
+        ),
+        [
+            vec![s("ada"), Value::Int(3), Value::Int(2015)],
+            vec![s("bob"), Value::Int(3), Value::Int(2015)],
+            vec![s("cy"), Value::Int(3), Value::Int(2015)],
+        ]
+    );
+    // A subquery naming an outer variable is correlated: every row gets its
+    // own value.
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (c:Car) RETURN c.make, COUNT { MATCH (p)-[:OWNS]->(c) } AS owners \
+             ORDER BY c.make",
+        ),
+        [
+            vec![s("fiat"), Value::Int(1)],
+            vec![s("kia"), Value::Int(1)],
+            vec![s("vw"), Value::Int(1)],
+        ]
+    );
+    // The cache does not outlive the execution.
+    let query = prepare("RETURN COUNT { MATCH (c:Car) } AS cars").unwrap();
+    let params = Params::default();
+    let count = |db: &Db| {
+        let snapshot = db.snapshot();
+        query
+            .execute_streaming(&snapshot, &params)
+            .next()
+            .unwrap()
+            .unwrap()
+            .columns()[0]
+            .1
+            .clone()
+    };
+    assert_eq!(count(&db), Value::Int(3));
+    write(&db, "CREATE (:Car {make: 'bmw', year: 2024})");
+    assert_eq!(count(&db), Value::Int(4));
+}
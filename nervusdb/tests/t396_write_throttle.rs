@@ -0,0 +1,65 @@
+use nervusdb::{Db, EngineOptions, GraphSnapshot, WriteThrottle};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn write_one(db: &Db, id: u64) -> nervusdb::Result<()> {
+    let mut txn = db.begin_write();
+    let label = txn.get_or_create_label("Event")?;
+    txn.create_node(id, label)?;
+    txn.commit()
+}
+
+fn wal_len(db: &Db) -> u64 {
+    std::fs::metadata(db.wal_path()).unwrap().len()
+}
+
+#[test]
+fn t396_writers_wait_past_the_delay_watermark() {
+    let dir = tempdir().unwrap();
+    let options = EngineOptions {
+        write_throttle: Some(WriteThrottle {
+            delay_wal_bytes: 1,
+            delay: Duration::from_millis(150),
+            reject_wal_bytes: 0,
+        }),
+        ..EngineOptions::default()
+    };
+    let db = Db::open_with_options(dir.path().join("graph"), options).unwrap();
+    write_one(&db, 1).unwrap();
+
+    let started = Instant::now();
+    write_one(&db, 2).unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(150));
+
+    db.set_write_throttle(None);
+    assert_eq!(db.write_throttle(), None);
+    let started = Instant::now();
+    write_one(&db, 3).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(150));
+}
+
+#[test]
+fn t396_commits_are_refused_past_the_reject_watermark_until_checkpointed() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write_one(&db, 1).unwrap();
+    db.checkpoint().unwrap();
+    write_one(&db, 2).unwrap();
+    // The WAL now holds one transaction past the checkpoint: the limit.
+    let throttle = WriteThrottle {
+        delay_wal_bytes: 0,
+        delay: Duration::ZERO,
+        reject_wal_bytes: wal_len(&db),
+    };
+    db.set_write_throttle(Some(throttle));
+    assert_eq!(db.write_throttle(), Some(throttle));
+
+    let err = write_one(&db, 3).unwrap_err();
+    assert!(err.to_string().contains("write throttled"), "{err}");
+    // The refused transaction left nothing behind.
+    assert_eq!(db.snapshot().nodes().count(), 2);
+
+    db.checkpoint().unwrap();
+    write_one(&db, 3).unwrap();
+    assert_eq!(db.snapshot().nodes().count(), 3);
+}
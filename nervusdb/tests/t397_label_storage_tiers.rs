@@ -0,0 +1,133 @@
+use nervusdb::{BackupManager, Db, GraphSnapshot, InternalNodeId, PropertyValue, StorageTier};
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Creates `count` `Invoice` nodes with a long memo, then one `User`, and
+/// returns all of them.
+fn populate(db: &Db, count: u64) -> Vec<InternalNodeId> {
+    let mut nodes = Vec::new();
+    let mut txn = db.begin_write();
+    let invoice = txn.get_or_create_label("Invoice").unwrap();
+    let user = txn.get_or_create_label("User").unwrap();
+    for id in 0..count {
+        let node = txn.create_node(id, invoice).unwrap();
+        nodes.push(node);
+        txn.set_node_property(node, "n".into(), PropertyValue::Int(id as i64))
+            .unwrap();
+        txn.set_node_property(
+            node,
+            "memo".into(),
+            PropertyValue::String(format!("invoice {id} ").repeat(40)),
+        )
+        .unwrap();
+    }
+    let ada = txn.create_node(1000, user).unwrap();
+    nodes.push(ada);
+    txn.set_node_property(ada, "name".into(), PropertyValue::String("ada".into()))
+        .unwrap();
+    txn.commit().unwrap();
+    nodes
+}
+
+fn property(db: &Db, node: InternalNodeId, key: &str) -> Option<PropertyValue> {
+    db.snapshot().node_property(node, key)
+}
+
+fn cold_file(db_path: &Path) -> std::path::PathBuf {
+    db_path.with_extension("cold")
+}
+
+#[test]
+fn t397_cold_labels_move_to_the_cold_file_and_back() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let db = Db::open(&db_path).unwrap();
+    let nodes = populate(&db, 50);
+    db.checkpoint().unwrap();
+    assert_eq!(db.cold_tier_stats().nodes, 0);
+    let hot_file_bytes = std::fs::metadata(&db_path).unwrap().len();
+
+    assert!(db.set_label_tier("Invoice", StorageTier::Cold).unwrap());
+    assert!(!db.set_label_tier("Invoice", StorageTier::Cold).unwrap());
+    assert_eq!(db.label_tier("Invoice"), StorageTier::Cold);
+    assert_eq!(db.label_tier("User"), StorageTier::Hot);
+    db.checkpoint().unwrap();
+
+    let stats = db.cold_tier_stats();
+    assert_eq!(stats.nodes, 50);
+    assert!(stats.file_bytes > 0);
+    // Compressed records take far less than a page per property.
+    assert!(stats.file_bytes < hot_file_bytes / 10, "{stats:?}");
+    assert!(cold_file(&db_path).exists());
+    assert_eq!(property(&db, nodes[7], "n"), Some(PropertyValue::Int(7)));
+    assert_eq!(
+        property(&db, nodes[7], "memo"),
+        Some(PropertyValue::String("invoice 7 ".repeat(40)))
+    );
+    assert_eq!(
+        property(&db, nodes[50], "name"),
+        Some(PropertyValue::String("ada".into()))
+    );
+
+    // New writes land in the page store first and join the cold record at
+    // the next compaction.
+    let mut txn = db.begin_write();
+    let node = nodes[7];
+    txn.set_node_property(node, "paid".into(), PropertyValue::Bool(true))
+        .unwrap();
+    txn.commit().unwrap();
+    db.checkpoint().unwrap();
+    let snapshot = db.snapshot();
+    let props = snapshot.node_properties(node).unwrap();
+    assert_eq!(props.len(), 3);
+    assert_eq!(props["paid"], PropertyValue::Bool(true));
+    assert_eq!(db.cold_tier_stats().nodes, 50);
+    db.close().unwrap();
+
+    let db = Db::open(&db_path).unwrap();
+    assert_eq!(db.cold_labels(), vec!["Invoice".to_string()]);
+    assert_eq!(
+        property(&db, nodes[7], "paid"),
+        Some(PropertyValue::Bool(true))
+    );
+    assert_eq!(property(&db, nodes[49], "n"), Some(PropertyValue::Int(49)));
+
+    assert!(db.set_label_tier("Invoice", StorageTier::Hot).unwrap());
+    db.checkpoint().unwrap();
+    assert_eq!(db.cold_tier_stats().nodes, 0);
+    assert!(!cold_file(&db_path).exists());
+    assert_eq!(
+        property(&db, nodes[7], "paid"),
+        Some(PropertyValue::Bool(true))
+    );
+    assert_eq!(
+        property(&db, nodes[12], "memo"),
+        Some(PropertyValue::String("invoice 12 ".repeat(40)))
+    );
+}
+
+#[test]
+fn t397_backups_carry_the_cold_tier() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("graph.ndb");
+    let backups = dir.path().join("backups");
+    let db = Db::open(&db_path).unwrap();
+    let nodes = populate(&db, 10);
+    db.set_label_tier("Invoice", StorageTier::Cold).unwrap();
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+
+    let info = nervusdb::backup(&db_path, &backups).unwrap();
+    let restored = dir.path().join("restored.ndb");
+    BackupManager::restore_from_backup(&backups, info.id, &restored).unwrap();
+    assert_eq!(
+        std::fs::read(cold_file(&restored)).unwrap(),
+        std::fs::read(cold_file(&db_path)).unwrap()
+    );
+
+    let db = Db::open(&restored).unwrap();
+    assert_eq!(db.cold_tier_stats().nodes, 10);
+    assert_eq!(property(&db, nodes[3], "n"), Some(PropertyValue::Int(3)));
+    // A packed image holds pages only, so it cannot take the cold tier along.
+    assert!(db.pack(dir.path().join("graph.pack")).is_err());
+}
@@ -0,0 +1,173 @@
+use nervusdb::{
+    DateGranularity, Db, GraphFormat, GraphSnapshot, PropertyValue, ScrubRules, TransferStats,
+};
+use serde_json::Value as JsonValue;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn build_graph(path: &Path) {
+    let db = Db::open(path).unwrap();
+    let mut txn = db.begin_write();
+    let user = txn.get_or_create_label("User").unwrap();
+    let company = txn.get_or_create_label("Company").unwrap();
+    let works_at = txn.get_or_create_rel_type("WORKS_AT").unwrap();
+
+    let acme = txn.create_node(100, company).unwrap();
+    txn.set_node_property(acme, "name".into(), "Acme".into())
+        .unwrap();
+    for (id, name, email) in [
+        (1, "ada", "ada@example.com"),
+        (2, "bob", "shared@example.com"),
+        (3, "cy", "shared@example.com"),
+    ] {
+        let node = txn.create_node(id, user).unwrap();
+        let props = [
+            ("name", PropertyValue::String(name.into())),
+            ("email", PropertyValue::String(email.into())),
+            ("password", PropertyValue::String("hunter2".into())),
+            // 2024-03-17
+            ("born", PropertyValue::Date(19_799)),
+            (
+                "logins",
+                PropertyValue::List(vec![PropertyValue::DateTime(1_710_684_000_000_000)]),
+            ),
+        ];
+        for (key, value) in props {
+            txn.set_node_property(node, key.into(), value).unwrap();
+        }
+        txn.create_edge(node, works_at, acme);
+        txn.set_edge_property(
+            node,
+            works_at,
+            acme,
+            "password".into(),
+            PropertyValue::Bool(true),
+        )
+        .unwrap();
+    }
+    txn.commit().unwrap();
+    db.checkpoint().unwrap();
+    db.close().unwrap();
+}
+
+fn rules(salt: &str) -> ScrubRules {
+    ScrubRules {
+        salt: salt.into(),
+        hash_ids: true,
+        hash_properties: vec!["email".into(), "User.name".into()],
+        drop_properties: vec!["password".into()],
+        generalize_dates: Some(DateGranularity::Month),
+    }
+}
+
+fn export(path: &Path, format: GraphFormat, rules: &ScrubRules) -> (TransferStats, String) {
+    let mut out = Vec::new();
+    let stats = nervusdb::export_scrubbed(path, format, &mut out, rules).unwrap();
+    (stats, String::from_utf8(out).unwrap())
+}
+
+fn records(jsonl: &str) -> Vec<JsonValue> {
+    jsonl
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn t398_scrubbed_export_hashes_drops_and_generalizes() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("source");
+    build_graph(&source);
+
+    let (stats, jsonl) = export(&source, GraphFormat::Jsonl, &rules("pepper"));
+    assert_eq!(
+        stats,
+        TransferStats {
+            nodes: 4,
+            relationships: 3
+        }
+    );
+    assert!(!jsonl.contains("example.com"), "{jsonl}");
+    assert!(!jsonl.contains("hunter2") && !jsonl.contains("password"));
+    assert!(!jsonl.contains("\"ada\""));
+
+    let records = records(&jsonl);
+    let (nodes, rels): (Vec<_>, Vec<_>) = records.iter().partition(|r| r["type"] == "node");
+    let ids: Vec<u64> = nodes.iter().map(|n| n["id"].as_u64().unwrap()).collect();
+    assert!(
+        ids.iter()
+            .all(|id| *id < 1 << 53 && ![1, 2, 3, 100].contains(id))
+    );
+    for rel in &rels {
+        assert!(ids.contains(&rel["start"].as_u64().unwrap()));
+        assert!(ids.contains(&rel["end"].as_u64().unwrap()));
+        assert_eq!(rel["properties"], serde_json::json!({}));
+    }
+
+    let company = nodes.iter().find(|n| n["labels"][0] == "Company").unwrap();
+    // `User.name` leaves other labels' names alone.
+    assert_eq!(company["properties"]["name"], "Acme");
+    let users: Vec<_> = nodes.iter().filter(|n| n["labels"][0] == "User").collect();
+    let email = |i: usize| users[i]["properties"]["email"].as_str().unwrap();
+    assert_eq!(email(0).len(), 32);
+    assert!(email(0).chars().all(|c| c.is_ascii_hexdigit()));
+    // Equal values hash alike, so the duplicate survives scrubbing.
+    assert_ne!(email(0), email(1));
+    assert_eq!(email(1), email(2));
+    assert_eq!(
+        users[0]["properties"]["born"],
+        serde_json::json!({"type": "date", "value": "2024-03-01"})
+    );
+    // 2024-03-01T00:00:00Z
+    assert_eq!(
+        users[0]["properties"]["logins"][0]["value"],
+        1_709_251_200_000_000_i64
+    );
+
+    // The same salt gives the same file; another salt gives other hashes.
+    assert_eq!(
+        export(&source, GraphFormat::Jsonl, &rules("pepper")).1,
+        jsonl
+    );
+    let salted = export(&source, GraphFormat::Jsonl, &rules("salt")).1;
+    assert!(!salted.contains(email(0)));
+
+    // Without rules the export is the plain one.
+    let mut plain = Vec::new();
+    nervusdb::export(&source, GraphFormat::Jsonl, &mut plain).unwrap();
+    assert_eq!(
+        export(&source, GraphFormat::Jsonl, &ScrubRules::default()).1,
+        String::from_utf8(plain).unwrap()
+    );
+}
+
+#[test]
+fn t398_scrubbed_exports_import_in_both_formats() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("source");
+    build_graph(&source);
+
+    for format in [GraphFormat::Jsonl, GraphFormat::GraphMl] {
+        let (_, text) = export(&source, format, &rules("pepper"));
+        assert!(!text.contains("hunter2"), "{format:?}");
+        let copy = dir.path().join(format!("{format:?}"));
+        let stats = nervusdb::import(&copy, format, text.as_bytes()).unwrap();
+        assert_eq!((stats.nodes, stats.relationships), (4, 3));
+
+        let db = Db::open(&copy).unwrap();
+        let snapshot = db.snapshot();
+        let user = snapshot.resolve_label_id("User").unwrap();
+        let born: Vec<_> = snapshot
+            .nodes()
+            .filter(|&n| snapshot.resolve_node_labels(n).unwrap().contains(&user))
+            .map(|n| snapshot.node_property(n, "born"))
+            .collect();
+        assert_eq!(born, vec![Some(PropertyValue::Date(19_783)); 3]);
+    }
+
+    assert!("fortnight".parse::<DateGranularity>().is_err());
+    assert_eq!(
+        "YEAR".parse::<DateGranularity>().unwrap(),
+        DateGranularity::Year
+    );
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+fn rows(db: &Db, cypher: &str) -> Vec<Vec<(String, Value)>> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .map(|row| row.unwrap().columns().to_vec())
+        .collect()
+}
+
+fn values(db: &Db, cypher: &str) -> Vec<Value> {
+    rows(db, cypher)
+        .into_iter()
+        .map(|mut row| row.remove(0).1)
+        .collect()
+}
+
+fn map(entries: &[(&str, Value)]) -> Value {
+    Value::Map(
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn s(text: &str) -> Value {
+    Value::String(text.to_string())
+}
+
+fn people(db: &Db) {
+    write(
+        db,
+        "CREATE (a:Person {name: 'ada', age: 36}), (b:Person {name: 'bob', age: 41}), \
+         (:Person {name: 'cy'}), (a)-[:KNOWS]->(b), (a)-[:KNOWS]->(:Person {name: 'dee'})",
+    );
+}
+
+#[test]
+fn t399_map_projection_selects_properties_variables_and_expressions() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    people(&db);
+
+    assert_eq!(
+        values(
+            &db,
+            "MATCH (n:Person) WHERE n.name IN ['ada', 'cy'] \
+             RETURN n {.name, .age, friends: size((n)--())} AS person ORDER BY n.name",
+        ),
+        [
+            map(&[
+                ("name", s("ada")),
+                ("age", Value::Int(36)),
+                ("friends", Value::Int(2))
+            ]),
+            map(&[
+                ("name", s("cy")),
+                ("age", Value::Null),
+                ("friends", Value::Int(0))
+            ]),
+        ]
+    );
+    // `.*` takes every property; listed entries and variables are added on top.
+    assert_eq!(
+        values(
+            &db,
+            "MATCH (n:Person {name: 'bob'}) WITH n, 'x' AS tag \
+             RETURN n {.*, tag, age: n.age + 1} AS person",
+        ),
+        [map(&[
+            ("name", s("bob")),
+            ("age", Value::Int(42)),
+            ("tag", s("x"))
+        ])]
+    );
+    // Maps project too, and a null subject projects to null.
+    assert_eq!(
+        values(&db, "WITH {a: 1, b: 2} AS m RETURN m {.a, c: 3} AS x"),
+        [map(&[("a", Value::Int(1)), ("c", Value::Int(3))])]
+    );
+    assert_eq!(
+        values(&db, "OPTIONAL MATCH (n:Robot) RETURN n {.name} AS x"),
+        [Value::Null]
+    );
+    // Without an alias the column is named after the projection.
+    assert_eq!(
+        rows(&db, "MATCH (n:Person {name: 'cy'}) RETURN n {.name}")[0][0].0,
+        "n {.name}"
+    );
+}
+
+#[test]
+fn t399_map_projection_groups_by_its_subject() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    people(&db);
+
+    assert_eq!(
+        values(
+            &db,
+            "MATCH (n:Person)-[:KNOWS]->(f) \
+             RETURN n {.name, friends: collect(f.name)} AS person",
+        ),
+        [map(&[
+            ("name", s("ada")),
+            ("friends", Value::List(vec![s("bob"), s("dee")])),
+        ])]
+    );
+}
+
+#[test]
+fn t399_size_of_a_pattern_counts_its_matches() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    people(&db);
+
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Person) WHERE size((n)-[:KNOWS]->()) > 0 \
+             RETURN n.name, size((n)-->()) AS out, size((n)--(:Person)) AS any",
+        )
+        .into_iter()
+        .map(|row| row.into_iter().map(|(_, v)| v).collect::<Vec<_>>())
+        .collect::<Vec<_>>(),
+        [vec![s("ada"), Value::Int(2), Value::Int(2)]]
+    );
+    // A bare pattern is still only a predicate.
+    let err = prepare("MATCH (n) RETURN (n)-->() AS p").unwrap_err();
+    assert!(err.to_string().contains("syntax error"), "{err}");
+}
@@ -0,0 +1,132 @@
+use nervusdb::query::{Params, Row, Value, prepare};
+use nervusdb::{Db, QueryCapture, replay_queries};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+const SEED: &str = "CREATE (:User {name: 'ada', age: 36}), (:User {name: 'bob', age: 41})";
+
+fn write(db: &Db, cypher: &str, params: &Params) -> u32 {
+    let snapshot = db.snapshot();
+    let mut txn = db.begin_write();
+    let changed = prepare(cypher)
+        .unwrap()
+        .execute_write(&snapshot, &mut txn, params)
+        .unwrap();
+    txn.commit().unwrap();
+    changed
+}
+
+fn read(db: &Db, cypher: &str, params: &Params) -> Vec<Row> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, params)
+        .map(|row| row.unwrap().reify(&snapshot).unwrap())
+        .collect()
+}
+
+/// Runs the workload on `db`, recording each query in `capture`.
+fn workload(db: &Db, capture: &QueryCapture) {
+    let mut params = Params::new();
+    params.insert("name".to_string(), Value::String("cy".into()));
+    params.insert("tags".to_string(), Value::List(vec![Value::Int(1)]));
+    let started = Instant::now();
+    let changed = write(db, "CREATE (:User {name: $name, tags: $tags})", &params);
+    capture
+        .record_write(
+            "CREATE (:User {name: $name, tags: $tags})",
+            &params,
+            changed,
+            started.elapsed(),
+        )
+        .unwrap();
+
+    for (cypher, params) in [
+        ("MATCH (u:User) RETURN u.name", Params::new()),
+        ("MATCH (u:User {name: $name}) RETURN u.tags", params),
+    ] {
+        let started = Instant::now();
+        let rows = read(db, cypher, &params);
+        capture
+            .record_read(cypher, &params, &rows, started.elapsed())
+            .unwrap();
+    }
+}
+
+#[test]
+fn t400_replay_matches_an_identical_database_and_flags_drift() {
+    let dir = tempdir().unwrap();
+    let log = dir.path().join("queries.jsonl");
+    let source = Db::open(dir.path().join("source")).unwrap();
+    write(&source, SEED, &Params::new());
+    workload(&source, &QueryCapture::create(&log, true).unwrap());
+
+    let text = std::fs::read_to_string(&log).unwrap();
+    let entries: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["write"], true);
+    assert_eq!(
+        entries[0]["param_shapes"],
+        serde_json::json!({"name": "string", "tags": "list<int>"})
+    );
+    assert_eq!(entries[1]["rows"], 3);
+
+    // A copy seeded the same way replays to the same results.
+    let copy = Db::open(dir.path().join("copy")).unwrap();
+    write(&copy, SEED, &Params::new());
+    let report = replay_queries(&copy, &log).unwrap();
+    assert_eq!(report.skipped, 0);
+    assert_eq!(report.queries.len(), 3);
+    assert_eq!(report.mismatches().count(), 0, "{report:?}");
+    assert_eq!(report.queries[2].line, 3);
+    assert_eq!(report.queries[2].replayed_rows, 1);
+    let captured: u64 = entries.iter().map(|e| e["micros"].as_u64().unwrap()).sum();
+    assert_eq!(report.total_micros().0, captured);
+
+    // A database that has drifted reports the queries that now differ.
+    let drifted = Db::open(dir.path().join("drifted")).unwrap();
+    write(&drifted, SEED, &Params::new());
+    write(
+        &drifted,
+        "MATCH (u:User {name: 'bob'}) SET u.name = 'rob'",
+        &Params::new(),
+    );
+    let report = replay_queries(&drifted, &log).unwrap();
+    let mismatched: Vec<usize> = report.mismatches().map(|q| q.line).collect();
+    assert_eq!(mismatched, vec![2]);
+    assert_eq!(report.queries[1].replayed_rows, 3);
+    assert_eq!(report.queries[1].error, None);
+}
+
+#[test]
+fn t400_captures_without_values_skip_parameterised_queries() {
+    let dir = tempdir().unwrap();
+    let log = dir.path().join("queries.jsonl");
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(&db, SEED, &Params::new());
+    workload(&db, &QueryCapture::create(&log, false).unwrap());
+    assert!(!std::fs::read_to_string(&log).unwrap().contains("\"cy\""));
+
+    let report = replay_queries(&db, &log).unwrap();
+    assert_eq!(report.skipped, 2);
+    assert_eq!(report.queries.len(), 1);
+    assert!(report.queries[0].results_match);
+
+    // Queries that no longer run are reported rather than aborting the replay.
+    let capture = QueryCapture::create(&log, false).unwrap();
+    capture
+        .record_read(
+            "MATCH (u:User) RETURN u.nmae(",
+            &Params::new(),
+            &[],
+            Duration::ZERO,
+        )
+        .unwrap();
+    let report = replay_queries(&db, &log).unwrap();
+    let failed = report.queries.last().unwrap();
+    assert!(!failed.results_match);
+    assert!(failed.error.is_some());
+}
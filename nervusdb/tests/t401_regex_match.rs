@@ -0,0 +1,104 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn column(db: &Db, cypher: &str, params: &Params) -> nervusdb::query::Result<Vec<Value>> {
+    let snapshot = db.snapshot();
+    prepare(cypher)?
+        .execute_streaming(&snapshot, params)
+        .map(|row| row.map(|row| row.columns()[0].1.clone()))
+        .collect()
+}
+
+fn strings(names: &[&str]) -> Vec<Value> {
+    names.iter().map(|n| Value::String(n.to_string())).collect()
+}
+
+#[test]
+fn t401_regex_match_filters_whole_strings() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(
+        &db,
+        "CREATE (:User {name: 'ada', email: 'ada@corp.com'}), \
+         (:User {name: 'bob', email: 'bob@corpxcom.net'}), \
+         (:User {name: 'cy', email: 'CY@CORP.COM'}), (:User {name: 'dee'})",
+    );
+    let none = Params::default();
+
+    assert_eq!(
+        column(
+            &db,
+            r"MATCH (u:User) WHERE u.email =~ '.*@corp\\.com' RETURN u.name ORDER BY u.name",
+            &none,
+        )
+        .unwrap(),
+        strings(&["ada"])
+    );
+    // The pattern must match the whole string, not just a part of it.
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (u:User) WHERE u.email =~ 'corp' RETURN u.name",
+            &none
+        )
+        .unwrap(),
+        Vec::<Value>::new()
+    );
+    let mut params = Params::new();
+    params.insert("re".to_string(), Value::String("(?i).*@corp\\.com".into()));
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (u:User) WHERE u.email =~ $re RETURN u.name ORDER BY u.name",
+            &params,
+        )
+        .unwrap(),
+        strings(&["ada", "cy"])
+    );
+
+    // Null and non-string operands give null.
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (u:User {name: 'dee'}) RETURN u.email =~ '.*'",
+            &none
+        )
+        .unwrap(),
+        vec![Value::Null]
+    );
+    assert_eq!(
+        column(&db, "RETURN 42 =~ '4.', 'ab' =~ null", &none).unwrap(),
+        vec![Value::Null]
+    );
+    assert_eq!(
+        column(&db, "RETURN NOT 'abc' =~ 'a.c' AS r", &none).unwrap(),
+        vec![Value::Bool(false)]
+    );
+    // `\\` in a literal is a single backslash.
+    assert_eq!(
+        column(&db, r"RETURN size('a\\b')", &none).unwrap(),
+        vec![Value::Int(3)]
+    );
+}
+
+#[test]
+fn t401_invalid_regex_is_an_error() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(&db, "CREATE (:User {name: 'ada'})");
+
+    let err = column(
+        &db,
+        "MATCH (u:User) WHERE u.name =~ '(unclosed' RETURN u.name",
+        &Params::default(),
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("invalid regular expression"),
+        "{err}"
+    );
+}
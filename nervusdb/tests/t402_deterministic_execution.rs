@@ -0,0 +1,89 @@
+mod common;
+
+use common::{rows, write};
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+/// A hub linked to three leaves in an order other than their ids, one
+/// transaction per edge, so the edges sit in separate uncompacted runs.
+fn hub(db: &Db) {
+    write(
+        db,
+        "CREATE (:Hub {name: 'hub'}), (:Leaf {name: 'x', kind: 'a'}), \
+         (:Leaf {name: 'y', kind: 'b'}), (:Leaf {name: 'z', kind: 'a'})",
+    );
+    for name in ["z", "x", "y"] {
+        write(
+            db,
+            &format!("MATCH (h:Hub), (l:Leaf {{name: '{name}'}}) CREATE (h)-[:LINK]->(l)"),
+        );
+    }
+}
+
+fn names(values: &[&str]) -> Vec<Vec<Value>> {
+    values
+        .iter()
+        .map(|v| vec![Value::String(v.to_string())])
+        .collect()
+}
+
+#[test]
+fn t402_deterministic_results_survive_compaction() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    hub(&db);
+    assert!(!db.deterministic_enabled());
+    db.set_deterministic(true);
+    assert!(db.deterministic_enabled());
+
+    let queries = [
+        "MATCH (:Hub)-[:LINK]->(l) RETURN l.name",
+        "MATCH (l)<-[:LINK]-(:Hub) RETURN l.name",
+        "MATCH (:Hub)-[:LINK]-(l) RETURN l.name",
+        "MATCH (:Hub)-[:LINK]->(l) RETURN l.kind, collect(l.name)",
+    ];
+    let before: Vec<_> = queries.iter().map(|q| rows(&db, q)).collect();
+    db.compact().unwrap();
+    let after: Vec<_> = queries.iter().map(|q| rows(&db, q)).collect();
+    assert_eq!(before, after);
+
+    assert_eq!(before[0], names(&["x", "y", "z"]));
+    // Groups come out in the order their first row arrived.
+    assert_eq!(
+        before[3],
+        vec![
+            vec![
+                Value::String("a".into()),
+                Value::List(vec![Value::String("x".into()), Value::String("z".into())]),
+            ],
+            vec![
+                Value::String("b".into()),
+                Value::List(vec![Value::String("y".into())]),
+            ],
+        ]
+    );
+}
+
+#[test]
+fn t402_without_the_flag_order_follows_storage() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    hub(&db);
+    let query = "MATCH (:Hub)-[:LINK]->(l) RETURN l.name";
+    let mut before = rows(&db, query);
+    assert_ne!(before, names(&["x", "y", "z"]));
+    before.sort_by_key(|row| format!("{row:?}"));
+    assert_eq!(before, names(&["x", "y", "z"]));
+
+    let snapshot = db.snapshot();
+    db.set_deterministic(true);
+    // The setting applies to snapshots taken after it changes.
+    let rows_from_old_snapshot: Vec<_> = prepare(query)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .map(|row| vec![row.unwrap().columns()[0].1.clone()])
+        .collect();
+    assert_ne!(rows_from_old_snapshot, names(&["x", "y", "z"]));
+    assert_eq!(rows(&db, query), names(&["x", "y", "z"]));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::plan_cache::PlanCache;
+use nervusdb::query::{Params, PreparedQuery, Value, prepare};
+use tempfile::tempdir;
+
+fn rows(db: &Db, query: &PreparedQuery, params: &Params) -> Vec<Vec<Value>> {
+    let snapshot = db.snapshot();
+    let mut rows: Vec<Vec<Value>> = query
+        .execute_streaming(&snapshot, params)
+        .map(|row| {
+            row.unwrap()
+                .columns()
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect()
+        })
+        .collect();
+    rows.sort_by_key(|row| format!("{row:?}"));
+    rows
+}
+
+fn people(db: &Db) {
+    write(
+        db,
+        "CREATE (a:User {name: 'ada', age: 36}), (b:User {name: 'bob', age: 41}), \
+         (c:User {name: 'cy', age: 29}), (a)-[:KNOWS {since: 2019}]->(b), \
+         (a)-[:KNOWS {since: 2023}]->(c)",
+    );
+}
+
+#[test]
+fn t403_texts_differing_in_literals_share_one_plan() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    people(&db);
+    db.create_index("User", "name").unwrap();
+
+    let cache = PlanCache::new(16);
+    let params = Params::new();
+    let queries = [
+        "MATCH (n:User {name: 'ada'}) RETURN n.age AS age",
+        "MATCH (n:User {name: 'bob'}) RETURN n.age AS age",
+        "MATCH (n:User {name: 'nobody'}) RETURN n.age AS age",
+        "MATCH (n:User) WHERE n.age > 30 AND n.name STARTS WITH 'a' RETURN n.name AS name",
+        "MATCH (n:User) WHERE n.age > 20 AND n.name STARTS WITH 'c' RETURN n.name AS name",
+        "MATCH (:User {name: 'ada'})-[r:KNOWS {since: 2019}]->(m) RETURN m.name AS name",
+        "MATCH (:User {name: 'ada'})-[r:KNOWS {since: 2023}]->(m) RETURN m.name AS name",
+    ];
+    for cypher in queries {
+        let cached = cache.prepare(cypher).unwrap();
+        let fresh = prepare(cypher).unwrap();
+        assert_eq!(
+            rows(&db, &cached, &params),
+            rows(&db, &fresh, &params),
+            "{cypher}"
+        );
+    }
+    let stats = cache.stats();
+    assert_eq!((stats.misses, stats.template_hits), (3, 4));
+
+    // Bound plans keep their own values while others run with the same params.
+    let ada = cache
+        .prepare("MATCH (n:User {name: 'ada'}) RETURN n.age AS age")
+        .unwrap();
+    let cy = cache
+        .prepare("MATCH (n:User {name: 'cy'}) RETURN n.age AS age")
+        .unwrap();
+    assert_eq!(rows(&db, &cy, &params), vec![vec![Value::Int(29)]]);
+    assert_eq!(rows(&db, &ada, &params), vec![vec![Value::Int(36)]]);
+}
+
+#[test]
+fn t403_templates_mix_with_parameters_and_keep_compile_errors() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    people(&db);
+
+    let cache = PlanCache::new(16);
+    let mut params = Params::new();
+    params.insert("min", Value::Int(30));
+    for (name, expected) in [("ada", 1), ("cy", 0)] {
+        let query = cache
+            .prepare(&format!(
+                "MATCH (n:User {{name: '{name}'}}) WHERE n.age > $min RETURN n"
+            ))
+            .unwrap();
+        assert_eq!(rows(&db, &query, &params).len(), expected, "{name}");
+    }
+    assert_eq!(cache.stats().template_hits, 1);
+
+    // EXPLAIN shows the text's own literals, not slots.
+    let explain = cache
+        .prepare("EXPLAIN MATCH (n:User {name: 'bob'}) RETURN n")
+        .unwrap();
+    assert!(!explain.explain_string().unwrap().contains("$#"));
+
+    assert!(
+        cache
+            .prepare("MATCH (n:User) WHERE n.name = 'ada' RETURN m")
+            .is_err()
+    );
+    assert!(
+        cache
+            .prepare("MATCH (n:User) WHERE n.name = 'bob' RETURN m")
+            .is_err()
+    );
+}
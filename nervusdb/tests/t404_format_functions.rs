@@ -0,0 +1,74 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+fn text(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+#[test]
+fn t404_to_string_formats_numbers_and_temporals() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    write(
+        &db,
+        "CREATE (:Order {total: 1234567.891, placed: date('2024-03-17'), \
+         at: datetime('2024-03-17T09:05:00Z')})",
+    );
+
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (o:Order) RETURN toString(o.total, '#,##0.00') AS total, \
+             toString(o.placed, '%d %B %Y') AS placed, \
+             toString(o.at, '%H:%M on %Y-%m-%d') AS at, \
+             toString(42, '000') AS padded, toString(null, '0.0') AS missing"
+        )
+        .unwrap(),
+        vec![vec![
+            text("1,234,567.89"),
+            text("17 March 2024"),
+            text("09:05 on 2024-03-17"),
+            text("042"),
+            Value::Null,
+        ]]
+    );
+
+    for bad in [
+        "RETURN toString(1, 'abc') AS s",
+        "RETURN toString(date('2024-03-17'), '%H') AS s",
+        "RETURN toString('hello', '0.0') AS s",
+        "RETURN toString(1.5, '0.0', 'tlh') AS s",
+    ] {
+        let err = try_rows(&db, bad).unwrap_err().to_string();
+        assert!(err.contains("InvalidArgumentValue"), "{bad}: {err}");
+    }
+}
+
+#[test]
+fn t404_locale_comes_from_the_handle_or_the_call() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    let query = "RETURN toString(1234.5, '#,##0.00') AS a, \
+                 toString(1234.5, '#,##0.00', 'en') AS b";
+
+    assert_eq!(
+        try_rows(&db, query).unwrap(),
+        vec![vec![text("1,234.50"), text("1,234.50")]]
+    );
+    db.set_locale(Some("de-DE")).unwrap();
+    assert_eq!(db.locale().as_deref(), Some("de-DE"));
+    assert_eq!(
+        try_rows(&db, query).unwrap(),
+        vec![vec![text("1.234,50"), text("1,234.50")]]
+    );
+    db.set_locale(Some("fr")).unwrap();
+    assert_eq!(try_rows(&db, query).unwrap()[0][0], text("1\u{a0}234,50"));
+
+    assert!(db.set_locale(Some("tlh")).is_err());
+    db.set_locale(None).unwrap();
+    assert_eq!(try_rows(&db, query).unwrap()[0][0], text("1,234.50"));
+}
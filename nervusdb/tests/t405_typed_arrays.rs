@@ -0,0 +1,127 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::query::Value;
+use nervusdb::{Db, GraphSnapshot, PropertyValue};
+use tempfile::tempdir;
+
+fn stored(db: &Db, key: &str) -> Option<PropertyValue> {
+    let snapshot = db.snapshot();
+    let node = snapshot.nodes().next().unwrap();
+    snapshot.node_property(node, key)
+}
+
+#[test]
+fn t405_array_functions_store_packed_arrays() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    write(
+        &db,
+        "CREATE (:Doc {ids: toIntegerArray([3, 1.9, -4]), plain: [1, 2]})",
+    );
+    write(
+        &db,
+        "MATCH (d:Doc) SET d.embedding = toFloatArray([0.5, 1, -2.25])",
+    );
+
+    assert_eq!(
+        stored(&db, "ids"),
+        Some(PropertyValue::IntArray(vec![3, 1, -4]))
+    );
+    assert_eq!(
+        stored(&db, "embedding"),
+        Some(PropertyValue::FloatArray(vec![0.5, 1.0, -2.25]))
+    );
+    assert!(matches!(stored(&db, "plain"), Some(PropertyValue::List(_))));
+
+    // Arrays read back as ordinary lists.
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (d:Doc) RETURN d.ids AS ids, d.embedding[1] AS e"
+        )
+        .unwrap(),
+        vec![vec![
+            Value::List(vec![Value::Int(3), Value::Int(1), Value::Int(-4)]),
+            Value::Float(1.0),
+        ]]
+    );
+    assert_eq!(
+        try_rows(
+            &db,
+            "RETURN toIntegerArray(null) AS n, toFloatArray([]) AS e"
+        )
+        .unwrap(),
+        vec![vec![Value::Null, Value::List(vec![])]]
+    );
+    for bad in [
+        "RETURN toIntegerArray(['a']) AS a",
+        "RETURN toFloatArray([1, null]) AS a",
+        "RETURN toIntegerArray(1) AS a",
+    ] {
+        let err = try_rows(&db, bad).unwrap_err().to_string();
+        assert!(err.contains("InvalidArgumentValue"), "{bad}: {err}");
+    }
+}
+
+#[test]
+fn t405_index_size_and_containment_on_stored_arrays() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    write(
+        &db,
+        "CREATE (:Doc {name: 'a', tags: toIntegerArray([7, 8, 9]), \
+         v: toFloatArray([0.5, 1.5])}), \
+         (:Doc {name: 'b', tags: toIntegerArray([]), v: toFloatArray([2])})",
+    );
+
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (d:Doc {name: 'a'}) RETURN d.tags[0] AS first, d.tags[-1] AS last, \
+             d.tags[5] AS missing, size(d.tags) AS n, d.v[1] AS v1"
+        )
+        .unwrap(),
+        vec![vec![
+            Value::Int(7),
+            Value::Int(9),
+            Value::Null,
+            Value::Int(3),
+            Value::Float(1.5),
+        ]]
+    );
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (d:Doc) WHERE 8 IN d.tags OR 2 IN d.v RETURN d.name AS name \
+             ORDER BY name"
+        )
+        .unwrap(),
+        vec![
+            vec![Value::String("a".into())],
+            vec![Value::String("b".into())]
+        ]
+    );
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (d:Doc) RETURN d.name AS name, 8.0 IN d.tags AS f, null IN d.tags AS n, \
+             'x' IN d.v AS s ORDER BY name"
+        )
+        .unwrap(),
+        vec![
+            vec![
+                Value::String("a".into()),
+                Value::Bool(true),
+                Value::Null,
+                Value::Bool(false),
+            ],
+            vec![
+                Value::String("b".into()),
+                Value::Bool(false),
+                Value::Bool(false),
+                Value::Bool(false),
+            ],
+        ]
+    );
+}
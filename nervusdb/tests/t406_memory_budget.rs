@@ -0,0 +1,96 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::{ExecuteOptions, Params, Value, prepare};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn rows(db: &Db, cypher: &str, params: &Params) -> nervusdb::query::Result<Vec<Vec<Value>>> {
+    let snapshot = db.snapshot();
+    prepare(cypher)?
+        .execute_streaming(&snapshot, params)
+        .map(|row| {
+            row.map(|row| {
+                row.columns()
+                    .iter()
+                    .map(|(_, value)| value.clone())
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+fn budget(bytes: usize, spill_to_disk: bool, spill_dir: &Path) -> Params {
+    Params::with_execute_options(ExecuteOptions {
+        max_memory_bytes: bytes,
+        spill_to_disk,
+        spill_dir: Some(spill_dir.to_path_buf()),
+        ..ExecuteOptions::default()
+    })
+}
+
+#[test]
+fn t406_order_by_and_distinct_spill_past_the_budget() {
+    let dir = tempdir().unwrap();
+    let spill = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    write(
+        &db,
+        "UNWIND range(1, 600) AS i CREATE (:Item {i: i, bucket: i % 7, name: 'item-' + toString(i)})",
+    );
+
+    let queries = [
+        "MATCH (n:Item) RETURN n.bucket AS b, n.i AS i ORDER BY b DESC, i",
+        "MATCH (n:Item) RETURN n ORDER BY n.name",
+        "MATCH (n:Item) RETURN DISTINCT n.bucket AS b, n.i % 50 AS m",
+    ];
+    for cypher in queries {
+        let mut expected = rows(&db, cypher, &Params::new()).unwrap();
+        let mut spilled = rows(&db, cypher, &budget(8 * 1024, true, spill.path())).unwrap();
+        if cypher.contains("DISTINCT") {
+            expected.sort_by_key(|row| format!("{row:?}"));
+            spilled.sort_by_key(|row| format!("{row:?}"));
+        }
+        assert_eq!(spilled, expected, "{cypher}");
+        assert_eq!(std::fs::read_dir(spill.path()).unwrap().count(), 0);
+    }
+}
+
+#[test]
+fn t406_budget_fails_without_spilling_and_for_aggregation() {
+    let dir = tempdir().unwrap();
+    let spill = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    write(&db, "UNWIND range(1, 600) AS i CREATE (:Item {i: i})");
+
+    let no_spill = budget(8 * 1024, false, spill.path());
+    for cypher in [
+        "MATCH (n:Item) RETURN n.i AS i ORDER BY i DESC",
+        "MATCH (n:Item) RETURN DISTINCT n.i AS i",
+    ] {
+        let err = rows(&db, cypher, &no_spill).unwrap_err().to_string();
+        assert!(err.contains("ResourceLimitExceeded"), "{cypher}: {err}");
+        assert!(err.contains("MemoryBytes"), "{cypher}: {err}");
+    }
+
+    let err = rows(
+        &db,
+        "MATCH (n:Item) RETURN n.i % 3 AS k, collect(n.i) AS xs",
+        &budget(8 * 1024, true, spill.path()),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("MemoryBytes"), "{err}");
+
+    // A zero budget is unlimited.
+    assert_eq!(
+        rows(
+            &db,
+            "MATCH (n:Item) RETURN count(DISTINCT n.i) AS n",
+            &budget(0, false, spill.path())
+        )
+        .unwrap(),
+        vec![vec![Value::Int(600)]]
+    );
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn t407_rebinding_a_prefix_moves_every_reference() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph.ndb");
+    {
+        let db = Db::open(&path).unwrap();
+        assert!(db.set_namespace("ex", Some("http://old.example/")).unwrap());
+        assert!(!db.set_namespace("ex", Some("http://old.example/")).unwrap());
+        write(
+            &db,
+            "CREATE (:Person {ref: 'ex:alice'}), (:Person {ref: 'ex:bob'})",
+        );
+        assert_eq!(
+            try_rows(
+                &db,
+                "MATCH (p:Person) RETURN iri(p.ref) AS iri ORDER BY iri"
+            )
+            .unwrap(),
+            vec![
+                vec![string("http://old.example/alice")],
+                vec![string("http://old.example/bob")],
+            ]
+        );
+        assert!(
+            db.set_namespace("ex", Some("https://new.example/id/"))
+                .unwrap()
+        );
+        db.close().unwrap();
+    }
+
+    // The catalog survives reopening, including the checkpoint on close.
+    let db = Db::open(&path).unwrap();
+    assert_eq!(
+        db.namespaces(),
+        vec![("ex".to_string(), "https://new.example/id/".to_string())]
+    );
+    assert_eq!(
+        try_rows(
+            &db,
+            "MATCH (p:Person) RETURN iri(p.ref) AS iri ORDER BY iri"
+        )
+        .unwrap(),
+        vec![
+            vec![string("https://new.example/id/alice")],
+            vec![string("https://new.example/id/bob")],
+        ]
+    );
+    assert_eq!(
+        try_rows(
+            &db,
+            "CALL db.namespaces() YIELD prefix, iri RETURN prefix, iri"
+        )
+        .unwrap(),
+        vec![vec![string("ex"), string("https://new.example/id/")]]
+    );
+
+    assert!(db.set_namespace("ex", None).unwrap());
+    assert!(db.namespaces().is_empty());
+    let err = try_rows(&db, "MATCH (p:Person) RETURN iri(p.ref) AS iri")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("InvalidArgumentValue"), "{err}");
+    assert!(err.contains("'ex' is not bound"), "{err}");
+}
+
+#[test]
+fn t407_curie_uses_the_longest_base_and_bad_bindings_are_rejected() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    db.set_namespace("ex", Some("http://example.org/")).unwrap();
+    db.set_namespace("people", Some("http://example.org/people/"))
+        .unwrap();
+
+    assert_eq!(
+        try_rows(
+            &db,
+            "RETURN curie('http://example.org/people/ann') AS a, \
+             curie('http://example.org/x') AS b, curie('urn:other') AS c, \
+             curie(null) AS d, iri(null) AS e"
+        )
+        .unwrap(),
+        vec![vec![
+            string("people:ann"),
+            string("ex:x"),
+            Value::Null,
+            Value::Null,
+            Value::Null,
+        ]]
+    );
+    for bad in [
+        "RETURN iri('no-prefix') AS a",
+        "RETURN iri(1) AS a",
+        "RETURN curie(1) AS a",
+    ] {
+        let err = try_rows(&db, bad).unwrap_err().to_string();
+        assert!(err.contains("InvalidArgumentValue"), "{bad}: {err}");
+    }
+
+    for (prefix, iri) in [
+        ("1x", "http://a/"),
+        ("a:b", "http://a/"),
+        ("ok", "relative/path"),
+    ] {
+        let err = db.set_namespace(prefix, Some(iri)).unwrap_err().to_string();
+        assert!(err.contains("invalid namespace"), "{prefix} {iri}: {err}");
+    }
+    assert_eq!(db.namespaces().len(), 2);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::write;
+use nervusdb::Db;
+use nervusdb::query::{Params, prepare};
+use tempfile::tempdir;
+
+fn row_columns(db: &Db, cypher: &str) -> Vec<String> {
+    let snapshot = db.snapshot();
+    let query = prepare(cypher).unwrap();
+    let row = query
+        .execute_streaming(&snapshot, &Params::default())
+        .next()
+        .unwrap()
+        .unwrap();
+    row.columns().iter().map(|(name, _)| name.clone()).collect()
+}
+
+#[test]
+fn t408_columns_match_the_rows_a_query_returns() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    write(
+        &db,
+        "CREATE (:P {name: 'a', x: 1})-[:KNOWS]->(:P {name: 'b', x: 2})",
+    );
+
+    for cypher in [
+        "MATCH (n:P) RETURN n.name, n.x AS x",
+        "MATCH (n:P) RETURN n.x AS k, count(*) AS c ORDER BY c DESC SKIP 0 LIMIT 5",
+        "MATCH (n:P) RETURN DISTINCT n.x % 2 AS parity",
+        "MATCH (n:P) RETURN n.name ORDER BY n.x",
+        "MATCH (n:P) WITH n RETURN *",
+        "MATCH p = (:P)-[:KNOWS]->(m) RETURN p, m",
+        "MATCH (n:P) CALL { WITH n RETURN n.x * 2 AS twice } RETURN twice, n",
+        "UNWIND [1, 2] AS i RETURN i UNION ALL RETURN 3 AS i",
+        "CALL db.labels() YIELD label AS l RETURN l",
+        "CALL db.labels() YIELD label",
+        "EXPLAIN MATCH (n) RETURN n",
+        "PROFILE MATCH (n) RETURN n",
+    ] {
+        let expected = row_columns(&db, cypher);
+        assert_eq!(
+            prepare(cypher).unwrap().columns(),
+            Some(expected),
+            "{cypher}"
+        );
+    }
+
+    assert_eq!(prepare("CREATE (:P)").unwrap().columns(), Some(Vec::new()));
+    assert_eq!(
+        prepare("MATCH (n:P) SET n.y = 1 RETURN n.y AS y")
+            .unwrap()
+            .columns(),
+        Some(vec!["y".to_string()])
+    );
+    // Built-in procedures declare no outputs; their rows name the columns.
+    assert_eq!(prepare("CALL db.labels()").unwrap().columns(), None);
+}
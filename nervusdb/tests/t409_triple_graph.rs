@@ -0,0 +1,78 @@
+use nervusdb::query::{Params, Value, prepare};
+use nervusdb::{GraphSnapshot, TripleGraph};
+
+fn rows(graph: &TripleGraph, cypher: &str) -> Vec<Vec<Value>> {
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(graph, &Params::default())
+        .map(|row| {
+            row.unwrap()
+                .columns()
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect()
+        })
+        .collect()
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn graph() -> TripleGraph {
+    TripleGraph::from_triples([
+        ("alice", "knows", "bob"),
+        ("bob", "knows", "carol"),
+        ("alice", "worksAt", "acme"),
+        ("carol", "worksAt", "acme"),
+        ("alice", "knows", "bob"),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn t409_triples_are_queried_as_a_graph() {
+    let graph = graph();
+    assert_eq!(graph.node_count(None), 4);
+    assert_eq!(graph.edge_count(None), 4);
+    assert_eq!(graph.term(graph.node("carol").unwrap()), Some("carol"));
+
+    assert_eq!(
+        rows(
+            &graph,
+            "MATCH (a {value: 'alice'})-[:knows]->(b) RETURN b.value AS b"
+        ),
+        vec![vec![string("bob")]]
+    );
+    assert_eq!(
+        rows(
+            &graph,
+            "MATCH (a)-[:knows*2]->(c)-[:worksAt]->(o) RETURN a.value, c.value, o.value"
+        ),
+        vec![vec![string("alice"), string("carol"), string("acme")]]
+    );
+    assert_eq!(
+        rows(
+            &graph,
+            "MATCH (p)-[r]->(o {value: 'acme'}) RETURN p.value AS p, type(r) AS t ORDER BY p"
+        ),
+        vec![
+            vec![string("alice"), string("worksAt")],
+            vec![string("carol"), string("worksAt")],
+        ]
+    );
+    assert_eq!(
+        rows(
+            &graph,
+            "MATCH (n)<-[:knows]-() RETURN n.value AS n, labels(n) AS l ORDER BY n"
+        ),
+        vec![
+            vec![string("bob"), Value::List(vec![])],
+            vec![string("carol"), Value::List(vec![])],
+        ]
+    );
+    assert_eq!(
+        rows(&graph, "MATCH ()-[r:missing]->() RETURN count(r) AS n"),
+        vec![vec![Value::Int(0)]]
+    );
+}
@@ -0,0 +1,107 @@
+use nervusdb::{Db, InternalNodeId, PropertyValue};
+use tempfile::tempdir;
+
+/// Nodes 100..=104 with `LINK` edges 100->101->102->103->104 weighted 0.5,
+/// 1.5, 2.5 and 3.5, plus an unweighted `TAG` edge 100->104.
+fn graph(db: &Db) -> Vec<InternalNodeId> {
+    let mut txn = db.begin_write();
+    let label = txn.get_or_create_label("N").unwrap();
+    let link = txn.get_or_create_rel_type("LINK").unwrap();
+    let tag = txn.get_or_create_rel_type("TAG").unwrap();
+    let nodes: Vec<_> = (100..105)
+        .map(|external| txn.create_node(external, label).unwrap())
+        .collect();
+    for (i, pair) in nodes.windows(2).enumerate() {
+        txn.create_edge(pair[0], link, pair[1]);
+        txn.set_edge_property(
+            pair[0],
+            link,
+            pair[1],
+            "w".to_string(),
+            PropertyValue::Float(i as f64 + 0.5),
+        )
+        .unwrap();
+    }
+    txn.create_edge(nodes[0], tag, nodes[4]);
+    txn.commit().unwrap();
+    nodes
+}
+
+fn sorted_rows(chunks: impl Iterator<Item = nervusdb::EdgeChunk>) -> Vec<(u64, u64, f64)> {
+    let mut rows: Vec<_> = chunks
+        .flat_map(|chunk| {
+            (0..chunk.len())
+                .map(|i| (chunk.src[i], chunk.dst[i], chunk.weight[i]))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    rows
+}
+
+#[test]
+fn t410_chunks_cover_every_edge_with_external_ids_and_weights() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    graph(&db);
+    let snapshot = db.snapshot();
+
+    let chunks: Vec<_> = snapshot.edges_chunked(None, Some("w"), 2).collect();
+    assert_eq!(
+        chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+        vec![2, 2, 1]
+    );
+    assert_eq!(
+        sorted_rows(chunks.into_iter()),
+        vec![
+            (100, 101, 0.5),
+            (100, 104, 1.0),
+            (101, 102, 1.5),
+            (102, 103, 2.5),
+            (103, 104, 3.5),
+        ]
+    );
+}
+
+#[test]
+fn t410_rel_filter_and_unweighted_edges() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    graph(&db);
+    let snapshot = db.snapshot();
+
+    assert_eq!(
+        sorted_rows(snapshot.edges_chunked(Some("LINK"), None, 100)),
+        vec![
+            (100, 101, 1.0),
+            (101, 102, 1.0),
+            (102, 103, 1.0),
+            (103, 104, 1.0),
+        ]
+    );
+    assert_eq!(
+        snapshot.edges_chunked(Some("MISSING"), None, 100).count(),
+        0
+    );
+    // A zero chunk size is treated as 1.
+    assert_eq!(snapshot.edges_chunked(Some("TAG"), None, 0).count(), 1);
+}
+
+#[test]
+fn t410_fill_writes_into_caller_buffers_and_skips_deleted_nodes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph.ndb")).unwrap();
+    let nodes = graph(&db);
+    let mut txn = db.begin_write();
+    txn.tombstone_node(nodes[2]);
+    txn.commit().unwrap();
+    let snapshot = db.snapshot();
+
+    let mut chunks = snapshot.edges_chunked(Some("LINK"), Some("w"), 1);
+    let (mut src, mut dst, mut weight) = ([0u64; 8], [0u64; 8], [0f64; 8]);
+    let len = chunks.fill(&mut src, &mut dst, Some(&mut weight));
+    let mut rows: Vec<_> = (0..len).map(|i| (src[i], dst[i], weight[i])).collect();
+    rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(rows, vec![(100, 101, 0.5), (103, 104, 3.5)]);
+    assert_eq!(chunks.fill(&mut src, &mut dst, None), 0);
+}
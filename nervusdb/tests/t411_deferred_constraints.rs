@@ -0,0 +1,127 @@
+use nervusdb::{Db, InternalNodeId, PropertyValue, WriteTxn};
+use nervusdb_query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+const SWAP: &str = "MATCH (a:User {name: 'ada'}), (b:User {name: 'bob'}) \
+                    SET a.email = 'b@x', b.email = 'a@x'";
+
+/// Users `ada` (a@x) and `bob` (b@x) with a unique constraint on email.
+fn users(db: &Db) -> (InternalNodeId, InternalNodeId) {
+    let mut txn = db.begin_write();
+    prepare("CREATE (:User {name: 'ada', email: 'a@x'}), (:User {name: 'bob', email: 'b@x'})")
+        .unwrap()
+        .execute_mixed(&db.snapshot(), &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+    db.create_unique_constraint("User", "email").unwrap();
+    (node(db, "ada"), node(db, "bob"))
+}
+
+fn node(db: &Db, name: &str) -> InternalNodeId {
+    let mut params = Params::new();
+    params.insert("name".to_string(), Value::String(name.to_string()));
+    let row = prepare("MATCH (u:User {name: $name}) RETURN u")
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &params)
+        .next()
+        .unwrap()
+        .unwrap();
+    match row.get("u") {
+        Some(Value::NodeId(id)) => *id,
+        other => panic!("expected node, got {other:?}"),
+    }
+}
+
+fn emails(db: &Db) -> Vec<(String, String)> {
+    let mut rows: Vec<_> = prepare("MATCH (u:User) RETURN u.name AS name, u.email AS email")
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &Params::new())
+        .map(|row| {
+            let row = row.unwrap();
+            match (row.get("name"), row.get("email")) {
+                (Some(Value::String(name)), Some(Value::String(email))) => {
+                    (name.clone(), email.clone())
+                }
+                other => panic!("unexpected row {other:?}"),
+            }
+        })
+        .collect();
+    rows.sort();
+    rows
+}
+
+fn set_email(txn: &mut WriteTxn<'_>, node: InternalNodeId, email: &str) -> nervusdb::Result<()> {
+    txn.set_node_property(
+        node,
+        "email".to_string(),
+        PropertyValue::String(email.to_string()),
+    )
+}
+
+#[test]
+fn t411_deferred_checks_let_a_swap_pass_through_a_violation() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("deferred.ndb")).unwrap();
+    users(&db);
+
+    let mut txn = db.begin_write();
+    let err = prepare(SWAP)
+        .unwrap()
+        .execute_mixed(&db.snapshot(), &mut txn, &Params::new())
+        .unwrap_err();
+    assert!(err.to_string().contains("constraint violation"), "{err}");
+    drop(txn);
+
+    let mut txn = db.begin_write();
+    txn.set_constraints_deferred(true).unwrap();
+    prepare(SWAP)
+        .unwrap()
+        .execute_mixed(&db.snapshot(), &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        emails(&db),
+        vec![
+            ("ada".to_string(), "b@x".to_string()),
+            ("bob".to_string(), "a@x".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn t411_violations_left_at_the_end_fail_commit_or_the_switch_back() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("deferred.ndb")).unwrap();
+    let (ada, bob) = users(&db);
+
+    let mut txn = db.begin_write();
+    txn.set_constraints_deferred(true).unwrap();
+    set_email(&mut txn, bob, "a@x").unwrap();
+    let err = txn.commit().unwrap_err();
+    assert!(err.to_string().contains("constraint violation"), "{err}");
+
+    // Switching back checks what was deferred; fixing it lets commit pass.
+    let mut txn = db.begin_write();
+    txn.set_constraints_deferred(true).unwrap();
+    set_email(&mut txn, bob, "a@x").unwrap();
+    assert!(txn.set_constraints_deferred(false).is_err());
+    set_email(&mut txn, ada, "c@x").unwrap();
+    txn.set_constraints_deferred(false).unwrap();
+    assert!(set_email(&mut txn, ada, "a@x").is_err());
+    txn.commit().unwrap();
+    assert_eq!(
+        emails(&db),
+        vec![
+            ("ada".to_string(), "c@x".to_string()),
+            ("bob".to_string(), "a@x".to_string()),
+        ]
+    );
+
+    // Writes rolled back to a savepoint are no longer checked.
+    let mut txn = db.begin_write();
+    txn.set_constraints_deferred(true).unwrap();
+    let savepoint = txn.savepoint();
+    set_email(&mut txn, ada, "a@x").unwrap();
+    txn.rollback_to(&savepoint).unwrap();
+    txn.commit().unwrap();
+}
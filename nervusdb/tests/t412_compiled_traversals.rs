@@ -0,0 +1,96 @@
+use nervusdb::Db;
+use nervusdb::query::{ExecuteOptions, Params, Value, prepare};
+use tempfile::tempdir;
+
+/// A small social graph with a cycle, a self-loop and parallel relationships.
+fn setup() -> (tempfile::TempDir, Db) {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let mut txn = db.begin_write();
+    prepare(
+        "CREATE (a:P {n: 'a'})-[:K]->(b:P {n: 'b'})-[:K]->(c:P:Q {n: 'c'})-[:K]->(a), \
+                (b)-[:K]->(b), (a)-[:K]->(b), (c)-[:L]->(d:Q {n: 'd'}), (d)-[:K]->(b)",
+    )
+    .unwrap()
+    .execute_write(&db.snapshot(), &mut txn, &Params::default())
+    .unwrap();
+    txn.commit().unwrap();
+    (dir, db)
+}
+
+fn run(db: &Db, cypher: &str, compiled: bool) -> Vec<Vec<(String, Value)>> {
+    let params = Params::with_execute_options(ExecuteOptions {
+        compiled_traversals: compiled,
+        ..ExecuteOptions::default()
+    });
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &params)
+        .map(|row| row.unwrap().columns().to_vec())
+        .collect()
+}
+
+#[test]
+fn t412_compiled_chains_return_what_the_interpreter_returns() {
+    let (_dir, db) = setup();
+    for cypher in [
+        "MATCH (x:P)-[:K]->(y)-[:K]->(z) RETURN x.n, y.n, z.n",
+        "MATCH (x)-[r]->(y)-[s:K|L]->(z:Q) RETURN x.n, r, y.n, s, z.n",
+        "MATCH (x)<-[:K]-(y)<-[:K]-(z) RETURN x.n, y.n, z.n",
+        "MATCH (x:Q)-[:K]->(y)<-[:K]-(z) RETURN x.n, y.n, z.n",
+        "MATCH (x)-[:K]->(y)-[:K]->(x) RETURN x.n, y.n",
+        "MATCH (x)-[:K]->(x) RETURN x.n",
+        "MATCH p = (x:P)-[:K]->()-[:K]->()-[:K]->() RETURN p",
+        "MATCH (x)-[:K]->(y)-[:K]->(z) RETURN *",
+        "MATCH (x)-[:K]->(y)-[:NOPE]->(z) RETURN z",
+        "MATCH (x:Nope)-[:K]->(y) RETURN y",
+        "MATCH (x)-[:K]->(y)-[:K]->(z) RETURN x.n, z.n LIMIT 3",
+    ] {
+        assert_eq!(run(&db, cypher, true), run(&db, cypher, false), "{cypher}");
+    }
+    assert_eq!(
+        run(
+            &db,
+            "MATCH (x:P)-[:K]->(y)-[:K]->(z) RETURN count(*) AS c",
+            true
+        ),
+        vec![vec![("c".to_string(), Value::Int(8))]]
+    );
+}
+
+#[test]
+fn t412_compiled_chains_keep_the_intermediate_row_budget() {
+    let (_dir, db) = setup();
+    let params = Params::with_execute_options(ExecuteOptions {
+        max_intermediate_rows: 6,
+        ..ExecuteOptions::default()
+    });
+    let err = prepare("MATCH (x)-[:K]->(y)-[:K]->(z) RETURN z")
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &params)
+        .collect::<Result<Vec<_>, _>>()
+        .expect_err("hops should count against the budget")
+        .to_string();
+    assert!(err.contains("IntermediateRows"), "err={err}");
+}
+
+#[test]
+fn t412_a_prepared_chain_sees_labels_created_after_prepare() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let query = prepare("MATCH (x)-[:LATER]->(y)-[:LATER]->(z:Late) RETURN z.n").unwrap();
+    let count = |db: &Db| {
+        query
+            .execute_streaming(&db.snapshot(), &Params::default())
+            .count()
+    };
+    assert_eq!(count(&db), 0);
+
+    let mut txn = db.begin_write();
+    prepare("CREATE ({n: 1})-[:LATER]->({n: 2})-[:LATER]->(:Late {n: 3})")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &Params::default())
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(count(&db), 1);
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::Value;
+use tempfile::tempdir;
+
+fn single(db: &Db, cypher: &str) -> Value {
+    try_rows(db, cypher).unwrap().remove(0).remove(0)
+}
+
+/// A pseudo-random directed graph without self-loops. `E` and `F` can link
+/// the same pair, which `MATCH` counts twice and a motif once.
+fn random_graph(db: &Db) {
+    write(db, "UNWIND range(0, 39) AS i CREATE (:V {i: i})");
+    write(
+        db,
+        "MATCH (x:V), (y:V) WHERE x.i <> y.i AND (x.i * 31 + y.i * 17) % 7 = 0 \
+         CREATE (x)-[:E]->(y)",
+    );
+    write(
+        db,
+        "MATCH (x:V), (y:V) WHERE x.i <> y.i AND (x.i * 13 + y.i * 5) % 11 = 0 \
+         CREATE (x)-[:F]->(y)",
+    );
+    write(db, "MATCH (x:V) WHERE x.i % 3 = 0 SET x:Hub");
+}
+
+#[test]
+fn t413_motif_counts_match_pattern_matching() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    random_graph(&db);
+
+    let cases = [
+        (
+            "(a)-->(b)-->(c)<--(a)",
+            "MATCH (a)-->(b)-->(c)<--(a) WHERE a <> b AND b <> c AND a <> c \
+             WITH DISTINCT a, b, c RETURN a, b, c",
+        ),
+        (
+            "(a)-[:E]->(b)-[:E]->(c)-[:E]->(a)",
+            "MATCH (a)-[:E]->(b)-[:E]->(c)-[:E]->(a) WHERE a <> b AND b <> c AND a <> c",
+        ),
+        (
+            "(a:Hub)--(b)--(c:Hub)",
+            "MATCH (a:Hub)--(b)--(c:Hub) WHERE a <> b AND b <> c AND a <> c \
+             WITH DISTINCT a, b, c RETURN a, b, c",
+        ),
+        (
+            "(a)-[:E|F]->(b), (a)-[:F]->(c)",
+            "MATCH (a)-[:E|F]->(b), (a)-[:F]->(c) WHERE a <> b AND b <> c AND a <> c \
+             WITH DISTINCT a, b, c RETURN a, b, c",
+        ),
+    ];
+    for (motif, query) in cases {
+        let expected = try_rows(&db, query).unwrap().len() as i64;
+        let counted = single(
+            &db,
+            &format!("CALL algo.motif.count('{motif}') YIELD count RETURN count"),
+        );
+        assert_eq!(counted, Value::Int(expected), "{motif}");
+        let listed = try_rows(
+            &db,
+            &format!("CALL algo.motif('{motif}') YIELD a, b, c RETURN a"),
+        )
+        .unwrap();
+        assert_eq!(listed.len() as i64, expected, "{motif}");
+    }
+    assert!(
+        matches!(
+            single(&db, "CALL algo.motif.count('(a)-->(b)-->(c)<--(a)') YIELD count RETURN count"),
+            Value::Int(n) if n > 0
+        ),
+        "the graph should contain feed-forward triangles"
+    );
+}
+
+#[test]
+fn t413_motif_yields_named_nodes_and_honors_limit() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    // One feed-forward triangle a->b->c, a->c, plus a dangling d.
+    write(
+        &db,
+        "CREATE (a:P {name: 'a'}), (b:P {name: 'b'}), (c:P {name: 'c'}), (d:P {name: 'd'}), \
+         (a)-[:R]->(b), (b)-[:R]->(c), (a)-[:R]->(c), (c)-[:R]->(d)",
+    );
+
+    let found = try_rows(
+        &db,
+        "CALL algo.motif('(x)-->(y)-->(z)<--(x)') YIELD x, y, z \
+         MATCH (p), (q), (r) WHERE id(p) = id(x) AND id(q) = id(y) AND id(r) = id(z) \
+         RETURN p.name, q.name, r.name",
+    )
+    .unwrap();
+    assert_eq!(
+        found,
+        [vec![
+            Value::String("a".into()),
+            Value::String("b".into()),
+            Value::String("c".into()),
+        ]]
+    );
+
+    let paths = try_rows(&db, "CALL algo.motif('(x)-->(y)-->(z)') YIELD x RETURN x").unwrap();
+    assert_eq!(paths.len(), 3);
+    let limited = try_rows(
+        &db,
+        "CALL algo.motif('(x)-->(y)-->(z)', {limit: 1}) YIELD x RETURN x",
+    )
+    .unwrap();
+    assert_eq!(limited.len(), 1);
+
+    for missing in ["(x:Nope)-->(y)", "(x)-[:NOPE]->(y)"] {
+        let count = single(
+            &db,
+            &format!("CALL algo.motif.count('{missing}') YIELD count RETURN count"),
+        );
+        assert_eq!(count, Value::Int(0), "{missing}");
+    }
+
+    for (bad, reason) in [
+        ("(x {name: \"a\"})-->(y)", "cannot have properties"),
+        ("(x)-[r]->(y)", "cannot be named"),
+        ("(x)-[*1..2]->(y)", "fixed length"),
+        ("p = (x)-->(y)", "path variable"),
+        ("(x)-->(", "invalid motif pattern"),
+    ] {
+        let err = try_rows(
+            &db,
+            &format!("CALL algo.motif.count('{bad}') YIELD count RETURN count"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(reason), "{bad}: {err}");
+    }
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{try_rows, write};
+use nervusdb::Db;
+use nervusdb::query::{HyperLogLog, Value};
+use tempfile::tempdir;
+
+fn int(db: &Db, cypher: &str) -> i64 {
+    match &try_rows(db, cypher).unwrap()[0][0] {
+        Value::Int(n) => *n,
+        other => panic!("expected an integer, got {other:?}"),
+    }
+}
+
+fn assert_close(estimate: i64, exact: i64, tolerance: f64) {
+    let error = (estimate - exact).abs() as f64 / exact as f64;
+    assert!(error <= tolerance, "estimate {estimate} vs {exact}");
+}
+
+#[test]
+fn t414_count_distinct_approx_estimates_per_group() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+
+    // 20000 rows over 5000 distinct values, plus nulls that are not counted.
+    let estimate = int(
+        &db,
+        "UNWIND range(1, 20000) AS i \
+         WITH CASE WHEN i % 7 = 0 THEN null ELSE i % 5000 END AS v \
+         RETURN countDistinctApprox(v)",
+    );
+    assert_close(estimate, 5000, 0.05);
+    assert_eq!(
+        int(
+            &db,
+            "UNWIND ['a', 'b', 'a', 'c'] AS s RETURN countDistinctApprox(s)"
+        ),
+        3
+    );
+    assert_close(
+        int(
+            &db,
+            "UNWIND range(1, 3000) AS i RETURN countDistinctApprox(i, 8)",
+        ),
+        3000,
+        0.25,
+    );
+
+    let grouped = try_rows(
+        &db,
+        "UNWIND range(1, 900) AS i \
+         RETURN i % 3 AS bucket, countDistinctApprox(i / 3) AS approx, \
+         count(DISTINCT i / 3) AS exact ORDER BY bucket",
+    )
+    .unwrap();
+    assert_eq!(grouped.len(), 3);
+    for row in grouped {
+        let (Value::Int(approx), Value::Int(exact)) = (&row[1], &row[2]) else {
+            panic!("unexpected row {row:?}");
+        };
+        assert_close(*approx, *exact, 0.02);
+    }
+
+    assert_eq!(
+        int(&db, "MATCH (n:Missing) RETURN countDistinctApprox(n)"),
+        0
+    );
+}
+
+#[test]
+fn t414_sketches_merge_across_partitions() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    // Three days of visits; visitor ids overlap from one day to the next.
+    write(
+        &db,
+        "UNWIND range(0, 2) AS day UNWIND range(day * 1000, day * 1000 + 1999) AS visitor \
+         CREATE (:Visit {day: day, visitor: visitor})",
+    );
+    write(
+        &db,
+        "MATCH (v:Visit) WITH v.day AS day, hllSketch(v.visitor) AS sketch \
+         CREATE (:Daily {day: day, sketch: sketch})",
+    );
+
+    let total = int(&db, "MATCH (d:Daily) RETURN hllCount(hllMerge(d.sketch))");
+    assert_close(total, 4000, 0.03);
+    let merged = int(
+        &db,
+        "MATCH (d:Daily) WHERE d.day < 2 RETURN hllCount(hllMerge(d.sketch))",
+    );
+    let direct = int(
+        &db,
+        "MATCH (v:Visit) WHERE v.day < 2 RETURN countDistinctApprox(v.visitor)",
+    );
+    assert_eq!(merged, direct);
+
+    // Sketches round-trip through the Rust API.
+    let Value::Blob(bytes) = try_rows(&db, "MATCH (d:Daily {day: 0}) RETURN d.sketch")
+        .unwrap()
+        .remove(0)
+        .remove(0)
+    else {
+        panic!("sketch should be a blob");
+    };
+    let sketch = HyperLogLog::from_bytes(&bytes).unwrap();
+    assert_eq!(sketch.precision(), 14);
+    assert_close(sketch.estimate() as i64, 2000, 0.03);
+}
+
+#[test]
+fn t414_rejects_bad_precision_and_non_sketches() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    for (query, message) in [
+        (
+            "UNWIND [1, 2] AS i RETURN countDistinctApprox(i, 30)",
+            "precision must be between",
+        ),
+        (
+            "UNWIND [1, 2] AS i RETURN countDistinctApprox(i, 'high')",
+            "precision must be an integer",
+        ),
+        (
+            "UNWIND [1, 2] AS i RETURN hllMerge(i)",
+            "hllMerge expects sketches",
+        ),
+    ] {
+        let err = try_rows(&db, query).unwrap_err();
+        assert!(err.to_string().contains(message), "{query}: {err}");
+    }
+    assert_eq!(
+        try_rows(&db, "RETURN hllCount('not a sketch')").unwrap(),
+        [vec![Value::Null]]
+    );
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use common::{rows, try_write, write};
+use nervusdb::query::Value;
+use nervusdb::{
+    Db, EngineOptions, Follower, FollowerOptions, MAX_BATCH_TXNS, ReplicationServer, TcpWalSource,
+    WalBatch, WalSource, apply_wal_txn,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::tempdir;
+
+const GRAPH: &str = "MATCH (p:Person) OPTIONAL MATCH (p)-[r]->(q) \
+                     RETURN p.name, labels(p), p.age, type(r), r.since, q.name \
+                     ORDER BY p.name, q.name";
+
+fn options() -> FollowerOptions {
+    FollowerOptions {
+        poll_wait: Duration::from_millis(50),
+        retry_delay: Duration::from_millis(20),
+    }
+}
+
+fn caught_up(follower: &Follower, primary: &Db) {
+    follower
+        .wait_for_txid(primary.last_bookmark().txid(), Duration::from_secs(10))
+        .unwrap();
+}
+
+#[test]
+fn t415_follower_applies_primary_commits_in_process() {
+    let dir = tempdir().unwrap();
+    let primary = Arc::new(Db::open(dir.path().join("primary")).unwrap());
+    let replica = Arc::new(Db::open(dir.path().join("replica")).unwrap());
+    write(
+        &primary,
+        "CREATE (a:Person {name: 'Ada', age: 36})-[:KNOWS {since: 1843}]->(b:Person {name: 'Charles'}), \
+         (:Person {name: 'Temp'})",
+    );
+
+    let follower = Follower::start(Arc::clone(&replica), Arc::clone(&primary), options()).unwrap();
+    caught_up(&follower, &primary);
+    assert_eq!(rows(&replica, GRAPH), rows(&primary, GRAPH));
+
+    // Later commits of every kind follow, including deletes and renames.
+    write(
+        &primary,
+        "MATCH (a:Person {name: 'Ada'}), (c:Person {name: 'Charles'}) \
+         SET a:Mathematician, a.age = 37, c.age = 80 REMOVE c.name \
+         CREATE (c)-[:ADMIRES]->(a)",
+    );
+    write(&primary, "MATCH (t:Person {name: 'Temp'}) DETACH DELETE t");
+    write(&primary, "MATCH (:Person)-[r:KNOWS]->() SET r.since = 1842");
+    primary.rename_label("Mathematician", "Scientist").unwrap();
+    caught_up(&follower, &primary);
+    assert_eq!(rows(&replica, GRAPH), rows(&primary, GRAPH));
+    assert_eq!(
+        rows(&replica, "MATCH (n:Scientist) RETURN n.name"),
+        [[Value::String("Ada".into())]]
+    );
+
+    let state = follower.state();
+    assert_eq!(state.lag(), 0);
+    assert_eq!(state.last_error, None);
+    assert!(state.last_contact.is_some());
+    assert!(state.applied_txns >= 4);
+    follower.stop();
+}
+
+#[test]
+fn t415_tcp_follower_resumes_from_recorded_position() {
+    let dir = tempdir().unwrap();
+    let primary = Arc::new(Db::open(dir.path().join("primary")).unwrap());
+    let replica = Arc::new(Db::open(dir.path().join("replica")).unwrap());
+    let server = Arc::new(ReplicationServer::bind(Arc::clone(&primary), "127.0.0.1:0").unwrap());
+    let addr = server.local_addr().unwrap();
+    std::thread::spawn({
+        let server = Arc::clone(&server);
+        move || server.serve()
+    });
+
+    write(&primary, "UNWIND range(1, 10) AS i CREATE (:Item {i: i})");
+    let follower = Follower::start(
+        Arc::clone(&replica),
+        TcpWalSource::new(addr).unwrap(),
+        options(),
+    )
+    .unwrap();
+    caught_up(&follower, &primary);
+    let followers = server.followers();
+    assert_eq!(followers.len(), 1);
+    follower.stop();
+
+    // Commits made while the follower is down are applied once on restart.
+    write(&primary, "UNWIND range(11, 15) AS i CREATE (:Item {i: i})");
+    let follower = Follower::start(
+        Arc::clone(&replica),
+        TcpWalSource::new(addr).unwrap(),
+        options(),
+    )
+    .unwrap();
+    caught_up(&follower, &primary);
+    assert_eq!(
+        rows(&replica, "MATCH (n:Item) RETURN count(n), sum(n.i)"),
+        [[Value::Int(15), Value::Int(120)]]
+    );
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(server.followers().iter().all(|f| f.lag == 0));
+    follower.stop();
+}
+
+#[test]
+fn t415_batches_round_trip_and_apply_idempotently() {
+    let dir = tempdir().unwrap();
+    let primary = Db::open(dir.path().join("primary")).unwrap();
+    let replica = Db::open(dir.path().join("replica")).unwrap();
+    write(
+        &primary,
+        "CREATE (:Doc {title: 'a', tags: ['x', 'y'], score: 1.5})-[:CITES {w: 2}]->(:Doc)",
+    );
+    write(&primary, "MATCH (d:Doc {title: 'a'}) REMOVE d.score");
+
+    let batch = WalBatch::read(&primary, 0, Duration::ZERO).unwrap();
+    assert_eq!(batch.up_to_txid, primary.last_bookmark().txid());
+    assert_eq!(WalBatch::decode(&batch.encode().unwrap()).unwrap(), batch);
+    assert!(WalBatch::decode(&batch.encode().unwrap()[..10]).is_err());
+
+    // A batch replayed after a crash changes nothing.
+    for _ in 0..2 {
+        for txn in &batch.txns {
+            apply_wal_txn(&replica, txn).unwrap();
+        }
+    }
+    let docs = "MATCH (d:Doc) OPTIONAL MATCH (d)-[c]->() \
+                RETURN d.title, d.tags, d.score, c.w ORDER BY d.title";
+    assert_eq!(rows(&replica, docs), rows(&primary, docs));
+
+    // A follower written to directly no longer matches the primary.
+    let other = Db::open(dir.path().join("other")).unwrap();
+    write(&other, "CREATE (:Local)");
+    let err = apply_wal_txn(&other, &batch.txns[0]).unwrap_err();
+    assert!(err.to_string().contains("diverged"), "{err}");
+}
+
+#[test]
+fn t415_system_properties_replicate_as_stamped() {
+    let dir = tempdir().unwrap();
+    let stamping = EngineOptions {
+        system_properties: true,
+        ..Default::default()
+    };
+    let primary = Arc::new(Db::open_with_options(dir.path().join("primary"), stamping).unwrap());
+    let replica = Arc::new(Db::open(dir.path().join("replica")).unwrap());
+    write(
+        &primary,
+        "CREATE (:Doc {title: 'a'})-[:CITES]->(:Doc {title: 'b'})",
+    );
+    write(&primary, "MATCH (d:Doc {title: 'a'}) SET d.title = 'A'");
+
+    let follower = Follower::start(Arc::clone(&replica), Arc::clone(&primary), options()).unwrap();
+    caught_up(&follower, &primary);
+    assert_eq!(follower.state().last_error, None);
+    let stamps = "MATCH (d:Doc)-[c:CITES]->() \
+                  RETURN d.title, d.__created_tx, d.__updated_tx, d.__updated_at, c.__created_tx";
+    let stamped = rows(&primary, stamps);
+    assert!(matches!(stamped[0][1], Value::Int(_)), "{stamped:?}");
+    assert_eq!(rows(&replica, stamps), stamped);
+    follower.stop();
+}
+
+#[test]
+fn t415_batches_stop_at_the_limit() {
+    let dir = tempdir().unwrap();
+    let primary = Db::open(dir.path().join("primary")).unwrap();
+    for i in 0..=MAX_BATCH_TXNS as u64 {
+        let mut txn = primary.begin_write();
+        let label = txn.get_or_create_label("Item").unwrap();
+        txn.create_node(i, label).unwrap();
+        txn.commit().unwrap();
+    }
+
+    let first = WalBatch::read(&primary, 0, Duration::ZERO).unwrap();
+    assert_eq!(first.txns.len(), MAX_BATCH_TXNS);
+    assert_eq!(first.up_to_txid, first.txns.last().unwrap().txid);
+    assert!(first.up_to_txid < first.primary_txid);
+    let rest = WalBatch::read(&primary, first.up_to_txid, Duration::ZERO).unwrap();
+    assert_eq!(rest.txns.len(), 1);
+    assert_eq!(rest.up_to_txid, primary.last_bookmark().txid());
+}
+
+#[test]
+fn t415_server_refuses_followers_past_its_limit() {
+    let dir = tempdir().unwrap();
+    let primary = Arc::new(Db::open(dir.path().join("primary")).unwrap());
+    let server = Arc::new(
+        ReplicationServer::bind(Arc::clone(&primary), "127.0.0.1:0")
+            .unwrap()
+            .with_max_followers(1),
+    );
+    let addr = server.local_addr().unwrap();
+    std::thread::spawn({
+        let server = Arc::clone(&server);
+        move || server.serve()
+    });
+    write(&primary, "CREATE (:Item)");
+
+    let mut first = TcpWalSource::new(addr).unwrap();
+    assert_eq!(first.poll(0, Duration::ZERO).unwrap().txns.len(), 1);
+    let mut second = TcpWalSource::new(addr).unwrap();
+    let err = second.poll(0, Duration::ZERO).unwrap_err();
+    assert!(err.to_string().contains("already serving 1"), "{err}");
+
+    // A slot frees up once a follower disconnects.
+    drop(first);
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    loop {
+        match second.poll(0, Duration::ZERO) {
+            Ok(batch) => {
+                assert_eq!(batch.txns.len(), 1);
+                break;
+            }
+            Err(_) if std::time::Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+#[test]
+fn t415_follower_handle_refuses_direct_writes() {
+    let dir = tempdir().unwrap();
+    let primary = Arc::new(Db::open(dir.path().join("primary")).unwrap());
+    let replica = Arc::new(Db::open(dir.path().join("replica")).unwrap());
+    write(&primary, "CREATE (:Person {name: 'Ada'})");
+
+    let follower = Follower::start(Arc::clone(&replica), Arc::clone(&primary), options()).unwrap();
+    assert!(replica.is_replica());
+    let err = try_write(&replica, "CREATE (:Local)").unwrap_err();
+    assert!(err.to_string().contains("replica"), "{err}");
+    assert!(replica.rename_label("Person", "Human").is_err());
+
+    // Replication itself still writes.
+    write(&primary, "CREATE (:Person {name: 'Charles'})");
+    caught_up(&follower, &primary);
+    assert_eq!(
+        rows(&replica, "MATCH (p:Person) RETURN p.name ORDER BY p.name"),
+        [
+            [Value::String("Ada".into())],
+            [Value::String("Charles".into())]
+        ]
+    );
+    assert_eq!(follower.state().last_error, None);
+    follower.stop();
+}
@@ -0,0 +1,78 @@
+use nervusdb::query::{Params, prepare};
+use nervusdb::{Db, Error, QueryCapture, TraceId};
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn t416_trace_ids_number_a_handles_queries() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let first = db.next_trace_id();
+    let second = db.next_trace_id();
+    assert_eq!(first.session(), second.session());
+    let session = format!("{:06x}", first.session());
+    assert_eq!(first.to_string(), format!("{session}-1"));
+    assert_eq!(second.to_string(), format!("{session}-2"));
+
+    assert_eq!(TraceId::current(), None);
+    {
+        let _outer = first.enter();
+        assert_eq!(TraceId::current(), Some(first));
+        {
+            let _inner = second.enter();
+            assert_eq!(TraceId::current(), Some(second));
+        }
+        assert_eq!(TraceId::current(), Some(first));
+    }
+    assert_eq!(TraceId::current(), None);
+
+    let err = Error::Query("Expected ')'".into()).with_trace(second);
+    assert_eq!(
+        err.to_string(),
+        format!("Query error: Expected ')' [trace {session}-2]")
+    );
+    assert!(matches!(
+        Error::Cancelled.with_trace(second),
+        Error::Cancelled
+    ));
+}
+
+#[test]
+fn t416_capture_logs_trace_ids_of_slow_queries() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let log = dir.path().join("slow.jsonl");
+    let capture = QueryCapture::create(&log, false)
+        .unwrap()
+        .with_min_duration(Duration::from_millis(50));
+
+    let cypher = "RETURN 1 AS one";
+    let rows: Vec<_> = prepare(cypher)
+        .unwrap()
+        .execute_streaming(&db.snapshot(), &Params::default())
+        .map(|row| row.unwrap().reify(&db.snapshot()).unwrap())
+        .collect();
+    let fast = db.next_trace_id();
+    let slow = db.next_trace_id();
+    {
+        let _scope = fast.enter();
+        capture
+            .record_read(cypher, &Params::default(), &rows, Duration::from_millis(2))
+            .unwrap();
+    }
+    {
+        let _scope = slow.enter();
+        capture
+            .record_read(cypher, &Params::default(), &rows, Duration::from_millis(80))
+            .unwrap();
+    }
+
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["trace"], slow.to_string());
+    assert_eq!(lines[0]["micros"], 80_000);
+}
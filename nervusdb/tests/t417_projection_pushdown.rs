@@ -0,0 +1,82 @@
+use nervusdb::query::{Params, Value, prepare};
+use nervusdb::{Db, PropertyValue};
+use tempfile::tempdir;
+
+fn rows(db: &Db, cypher: &str) -> Vec<Vec<(String, Value)>> {
+    let snapshot = db.snapshot();
+    prepare(cypher)
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .map(|row| row.unwrap().reify(&snapshot).unwrap().columns().to_vec())
+        .collect()
+}
+
+/// `(:W {name: "a", p0..p99})-[:R]->(:W {name: "b", p0..p99})`.
+fn wide_pair(db: &Db) {
+    let mut txn = db.begin_write();
+    let label = txn.get_or_create_label("W").unwrap();
+    let rel = txn.get_or_create_rel_type("R").unwrap();
+    let mut nodes = Vec::new();
+    for (external, name) in [(1, "a"), (2, "b")] {
+        let node = txn.create_node(external, label).unwrap();
+        for k in 0..100 {
+            txn.set_node_property(node, format!("p{k}"), PropertyValue::Int(k))
+                .unwrap();
+        }
+        txn.set_node_property(node, "name".into(), PropertyValue::String(name.into()))
+            .unwrap();
+        nodes.push(node);
+    }
+    txn.create_edge(nodes[0], rel, nodes[1]);
+    txn.commit().unwrap();
+}
+
+#[test]
+fn t417_endpoint_properties_are_read_by_key() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    wide_pair(&db);
+
+    let got = rows(
+        &db,
+        "MATCH (a)-[r:R]->() RETURN startNode(r).name AS s, endNode(r).name AS e, \
+         startNode(r) = a AS same",
+    );
+    assert_eq!(
+        got,
+        vec![vec![
+            ("s".to_string(), Value::String("a".into())),
+            ("e".to_string(), Value::String("b".into())),
+            ("same".to_string(), Value::Bool(true)),
+        ]]
+    );
+
+    // A returned endpoint is still a whole node.
+    let got = rows(&db, "MATCH ()-[r:R]->() RETURN endNode(r) AS n");
+    let Value::Node(node) = &got[0][0].1 else {
+        panic!("expected a node, got {:?}", got[0][0].1);
+    };
+    assert_eq!(node.labels, vec!["W".to_string()]);
+    assert_eq!(node.properties.len(), 101);
+}
+
+#[test]
+fn t417_map_projection_reads_only_listed_keys() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    wide_pair(&db);
+
+    let got = rows(&db, "MATCH (n:W {name: 'a'}) RETURN n {.name, .p7} AS m");
+    let Value::Map(map) = &got[0][0].1 else {
+        panic!("expected a map, got {:?}", got[0][0].1);
+    };
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["name"], Value::String("a".into()));
+    assert_eq!(map["p7"], Value::Int(7));
+
+    let got = rows(&db, "MATCH (n:W {name: 'a'}) RETURN n {.*, extra: 1} AS m");
+    let Value::Map(map) = &got[0][0].1 else {
+        panic!("expected a map, got {:?}", got[0][0].1);
+    };
+    assert_eq!(map.len(), 102);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{column, write};
+use nervusdb::query::Value;
+use nervusdb::{Db, Error, TriggerEvent, WalChange};
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+
+#[test]
+fn t418_cypher_trigger_runs_for_matching_nodes() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    db.create_trigger(
+        "welcome",
+        "node_created:User".parse().unwrap(),
+        "MATCH (u) WHERE id(u) = $id CREATE (u)-[:GOT]->(:Mail {to: u.name})",
+    )
+    .unwrap();
+
+    write(
+        &db,
+        "CREATE (:User {name: 'ada'}), (:Bot {name: 'r2'}), (:User {name: 'bob'})",
+    );
+
+    assert_eq!(
+        column(&db, "MATCH (m:Mail) RETURN m.to ORDER BY m.to"),
+        vec![Value::String("ada".into()), Value::String("bob".into())]
+    );
+    let info = db.triggers();
+    assert_eq!(info.len(), 1);
+    assert_eq!(info[0].event.to_string(), "node_created:User");
+    assert_eq!((info[0].fired, info[0].failures), (1, 0));
+
+    // Commits made by the trigger's own writes did not fire it again.
+    write(&db, "CREATE (:Bot)");
+    assert_eq!(db.triggers()[0].fired, 1);
+
+    assert!(db.drop_trigger("welcome"));
+    assert!(!db.drop_trigger("welcome"));
+    write(&db, "CREATE (:User {name: 'cy'})");
+    assert_eq!(
+        column(&db, "MATCH (m:Mail) RETURN count(m)"),
+        vec![Value::Int(2)]
+    );
+}
+
+#[test]
+fn t418_callback_sees_matching_changes_and_failures_are_counted() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    db.create_trigger_callback(
+        "audit",
+        TriggerEvent::NodePropertySet {
+            label: Some("User".into()),
+            key: Some("email".into()),
+        },
+        move |_db, change| {
+            if let WalChange::SetNodeProperty { key, value, .. } = change {
+                sink.lock().unwrap().push((key.clone(), value.clone()));
+            }
+            Ok(())
+        },
+    )
+    .unwrap();
+    db.create_trigger_callback(
+        "broken",
+        "relationship_created:KNOWS".parse().unwrap(),
+        |_db, _change| Err(Error::Other("no".into())),
+    )
+    .unwrap();
+
+    write(
+        &db,
+        "CREATE (a:User {email: 'a@x', name: 'a'})-[:KNOWS]->(:Bot {email: 'b@x'})",
+    );
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![("email".to_string(), "a@x".into())]
+    );
+    let broken = db
+        .triggers()
+        .into_iter()
+        .find(|t| t.name == "broken")
+        .unwrap();
+    assert_eq!((broken.fired, broken.failures), (1, 1));
+    assert_eq!(broken.last_error.as_deref(), Some("Error: no"));
+    // The commit that fired the failing trigger stands.
+    assert_eq!(
+        column(&db, "MATCH ()-[r:KNOWS]->() RETURN count(r)"),
+        vec![Value::Int(1)]
+    );
+}
+
+#[test]
+fn t418_events_parse_and_display() {
+    for text in [
+        "node_created",
+        "node_created:User",
+        "node_deleted",
+        "node_property_set:User.email",
+        "node_property_set:.email",
+        "relationship_deleted:KNOWS",
+    ] {
+        let event: TriggerEvent = text.parse().unwrap();
+        assert_eq!(event.to_string(), text);
+    }
+    assert!("node_updated".parse::<TriggerEvent>().is_err());
+
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let err = db
+        .create_trigger("reads", TriggerEvent::NodeDeleted, "MATCH (n) RETURN n")
+        .unwrap_err();
+    assert!(err.to_string().contains("write statement"), "{err}");
+}
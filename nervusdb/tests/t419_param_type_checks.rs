@@ -0,0 +1,112 @@
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+fn seeded() -> (tempfile::TempDir, Db) {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let mut txn = db.begin_write();
+    prepare("UNWIND range(1, 5) AS i CREATE (:Item {n: i})")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+    (dir, db)
+}
+
+fn run(db: &Db, cypher: &str, params: &[(&str, Value)]) -> Result<usize, String> {
+    let mut bound = Params::new();
+    for (name, value) in params {
+        bound.insert(*name, value.clone());
+    }
+    let snapshot = db.snapshot();
+    let query = prepare(cypher).map_err(|e| e.to_string())?;
+    query
+        .execute_streaming(&snapshot, &bound)
+        .collect::<Result<Vec<_>, _>>()
+        .map(|rows| rows.len())
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn t419_limit_and_skip_parameters_must_be_non_negative_integers() {
+    let (_dir, db) = seeded();
+    let cypher = "MATCH (i:Item) RETURN i.n SKIP $s LIMIT $k";
+
+    assert_eq!(
+        run(&db, cypher, &[("s", Value::Int(1)), ("k", Value::Int(2))]),
+        Ok(2)
+    );
+    assert_eq!(
+        run(
+            &db,
+            cypher,
+            &[("s", Value::Int(0)), ("k", Value::String("2".into()))]
+        ),
+        Err(
+            "parameter $k is used in LIMIT and must be a non-negative integer, got a string".into()
+        )
+    );
+    assert_eq!(
+        run(&db, cypher, &[("s", Value::Int(-1)), ("k", Value::Int(2))]),
+        Err("parameter $s is used in SKIP and must be a non-negative integer, got -1".into())
+    );
+    assert_eq!(
+        run(&db, cypher, &[("s", Value::Int(0))]),
+        Err(
+            "parameter $k is used in LIMIT and must be a non-negative integer, got nothing (it was not given)"
+                .into()
+        )
+    );
+    // Checked before any row is read, even when no rows would reach LIMIT.
+    assert!(
+        run(
+            &db,
+            "MATCH (i:Missing) WITH i LIMIT $k RETURN i",
+            &[("k", Value::Float(1.5))]
+        )
+        .unwrap_err()
+        .contains("got a float")
+    );
+}
+
+#[test]
+fn t419_list_and_map_parameters_are_checked_before_running() {
+    let (_dir, db) = seeded();
+
+    let cypher = "MATCH (i:Item) WHERE i.n IN $ns RETURN i";
+    assert_eq!(
+        run(
+            &db,
+            cypher,
+            &[("ns", Value::List(vec![Value::Int(2), Value::Int(4)]))]
+        ),
+        Ok(2)
+    );
+    assert_eq!(run(&db, cypher, &[("ns", Value::Null)]), Ok(0));
+    assert_eq!(
+        run(&db, cypher, &[("ns", Value::Int(2))]),
+        Err("parameter $ns is used as a list and must be a list or null, got 2".into())
+    );
+
+    let mut txn = db.begin_write();
+    let mut params = Params::new();
+    params.insert("props", Value::String("n=1".into()));
+    let err = prepare("MATCH (i:Item {n: 99}) SET i += $props")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &params)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "parameter $props is used as the map of a SET and must be a map or null, got a string"
+    );
+    params.insert(
+        "props",
+        Value::Map(BTreeMap::from([("seen".to_string(), Value::Bool(true))])),
+    );
+    prepare("MATCH (i:Item {n: 1}) SET i += $props")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &params)
+        .unwrap();
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{column, write};
+use nervusdb::query::Value;
+use nervusdb::{Db, ExpiryStats, SweepReport};
+use std::time::{Duration, Instant, SystemTime};
+use tempfile::tempdir;
+
+fn node_id(db: &Db, name: &str) -> u32 {
+    match &column(db, &format!("MATCH (n {{name: '{name}'}}) RETURN id(n)"))[..] {
+        [Value::Int(id)] => *id as u32,
+        other => panic!("no single node named {name}: {other:?}"),
+    }
+}
+
+#[test]
+fn t420_sweep_deletes_expired_nodes_with_their_relationships() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(
+        &db,
+        "CREATE (a:Session {name: 'old', expireAt: 1000})-[:OF]->(u:User {name: 'ada'}), \
+         (:Session {name: 'new', expireAt: 99999999999999})-[:OF]->(u), \
+         (:Session {name: 'dated'}), (:Session {name: 'odd', expireAt: 'yesterday'})",
+    );
+    let dated = node_id(&db, "dated");
+    let hour = Duration::from_secs(3600);
+    let mut txn = db.begin_write();
+    txn.set_node_expiry(dated, SystemTime::now() - hour)
+        .unwrap();
+    txn.commit().unwrap();
+    // Expiry times read from disk count as well as those still in memory.
+    db.checkpoint().unwrap();
+
+    assert_eq!(
+        db.sweep_expired().unwrap(),
+        SweepReport { nodes: 2, edges: 1 }
+    );
+    assert_eq!(
+        column(&db, "MATCH (s:Session) RETURN s.name ORDER BY s.name"),
+        vec![Value::String("new".into()), Value::String("odd".into())]
+    );
+    assert_eq!(
+        column(&db, "MATCH (:User)<-[r:OF]-() RETURN count(r)"),
+        vec![Value::Int(1)]
+    );
+
+    assert_eq!(db.sweep_expired().unwrap(), SweepReport::default());
+    assert_eq!(
+        db.expiry_stats(),
+        ExpiryStats {
+            sweeps: 2,
+            nodes_purged: 2,
+            edges_purged: 1,
+            failures: 0,
+        }
+    );
+}
+
+#[test]
+fn t420_relationships_expire_on_their_own() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    write(
+        &db,
+        "CREATE (a:P {name: 'a'})-[:LIKES {expireAt: 5}]->(b:P {name: 'b'}), (a)-[:KNOWS]->(b)",
+    );
+
+    assert_eq!(
+        db.sweep_expired().unwrap(),
+        SweepReport { nodes: 0, edges: 1 }
+    );
+    assert_eq!(
+        column(&db, "MATCH (:P)-[r]->(:P) RETURN type(r)"),
+        vec![Value::String("KNOWS".into())]
+    );
+    assert_eq!(
+        column(&db, "MATCH (p:P) RETURN count(p)"),
+        vec![Value::Int(2)]
+    );
+}
+
+#[test]
+fn t420_background_sweeper_purges_at_its_interval() {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    db.set_expiry_sweep(Some(Duration::from_millis(20)));
+    write(&db, "CREATE (:Temp {name: 't', expireAt: 0}), (:Keep)");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while db.expiry_stats().nodes_purged == 0 {
+        assert!(Instant::now() < deadline, "sweeper never purged");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        column(&db, "MATCH (n) RETURN labels(n)"),
+        vec![Value::List(vec![Value::String("Keep".into())])]
+    );
+
+    db.set_expiry_sweep(None);
+    let sweeps = db.expiry_stats().sweeps;
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(db.expiry_stats().sweeps, sweeps);
+}
@@ -0,0 +1,127 @@
+use nervusdb::Db;
+use nervusdb::query::{Params, Value, prepare};
+use tempfile::tempdir;
+
+fn seeded() -> (tempfile::TempDir, Db) {
+    let dir = tempdir().unwrap();
+    let db = Db::open(dir.path().join("graph")).unwrap();
+    let mut txn = db.begin_write();
+    prepare("UNWIND range(1, 7) AS i CREATE (:Item {n: i})")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &Params::new())
+        .unwrap();
+    txn.commit().unwrap();
+    (dir, db)
+}
+
+fn params(values: &[(&str, i64)]) -> Params {
+    let mut params = Params::new();
+    for (name, value) in values {
+        params.insert(*name, Value::Int(*value));
+    }
+    params
+}
+
+fn column(db: &Db, cypher: &str, params: &Params) -> Result<Vec<Value>, String> {
+    let snapshot = db.snapshot();
+    let query = prepare(cypher).map_err(|e| e.to_string())?;
+    query
+        .execute_streaming(&snapshot, params)
+        .map(|row| {
+            row.map(|row| row.reify(&snapshot).unwrap().columns()[0].1.clone())
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+fn ints(values: &[i64]) -> Vec<Value> {
+    values.iter().map(|v| Value::Int(*v)).collect()
+}
+
+#[test]
+fn t421_one_query_pages_through_results() {
+    let (_dir, db) = seeded();
+    let query = "MATCH (i:Item) RETURN i.n ORDER BY i.n SKIP ($page - 1) * $size LIMIT $size";
+    let pages: Vec<_> = (1..=3)
+        .map(|page| column(&db, query, &params(&[("page", page), ("size", 3)])).unwrap())
+        .collect();
+    assert_eq!(pages, vec![ints(&[1, 2, 3]), ints(&[4, 5, 6]), ints(&[7])]);
+
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (i:Item) WITH i ORDER BY i.n DESC SKIP $offset LIMIT $n + 1 RETURN i.n",
+            &params(&[("offset", 1), ("n", 1)])
+        ),
+        Ok(ints(&[6, 5]))
+    );
+    assert_eq!(
+        column(
+            &db,
+            "CALL { MATCH (i:Item) RETURN i.n AS n ORDER BY n LIMIT $n } RETURN n",
+            &params(&[("n", 2)])
+        ),
+        Ok(ints(&[1, 2]))
+    );
+
+    let mut txn = db.begin_write();
+    prepare("MATCH (i:Item) WITH i ORDER BY i.n LIMIT $n SET i.first = true")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &params(&[("n", 2)]))
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (i:Item) WHERE i.first RETURN i.n ORDER BY i.n",
+            &Params::new()
+        ),
+        Ok(ints(&[1, 2]))
+    );
+}
+
+#[test]
+fn t421_counts_from_parameters_are_checked_when_the_query_runs() {
+    let (_dir, db) = seeded();
+
+    // Negating a parameter compiles; only its value can be negative.
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (i:Item) RETURN i.n ORDER BY i.n LIMIT -$n",
+            &params(&[("n", -2)])
+        ),
+        Ok(ints(&[1, 2]))
+    );
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (i:Item) RETURN i.n LIMIT -$n",
+            &params(&[("n", 2)])
+        ),
+        Err(
+            "syntax error: NegativeIntegerArgument: LIMIT must be a non-negative integer, got -2"
+                .into()
+        )
+    );
+    assert_eq!(
+        column(
+            &db,
+            "MATCH (i:Item) RETURN i.n SKIP $n * 1.5",
+            &params(&[("n", 3)])
+        ),
+        Err(
+            "syntax error: InvalidArgumentType: SKIP must be a non-negative integer, got 4.5"
+                .into()
+        )
+    );
+
+    // Literal counts are still rejected up front.
+    for query in [
+        "MATCH (i:Item) RETURN i.n LIMIT -1",
+        "MATCH (i:Item) RETURN i.n SKIP -1.5",
+        "MATCH (i:Item) RETURN i.n LIMIT i.n",
+    ] {
+        assert!(prepare(query).is_err(), "{query}");
+    }
+}
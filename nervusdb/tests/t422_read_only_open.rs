@@ -0,0 +1,136 @@
+use nervusdb::query::{Params, Value, prepare};
+use nervusdb::{Db, EngineOptions};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::tempdir;
+
+fn count(db: &Db) -> Value {
+    let snapshot = db.snapshot();
+    prepare("MATCH (n:Item) RETURN count(n)")
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .next()
+        .unwrap()
+        .unwrap()
+        .columns()[0]
+        .1
+        .clone()
+}
+
+#[test]
+fn t422_read_only_open_serves_reads_and_refuses_writes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open(&path).unwrap();
+    let mut txn = db.begin_write();
+    prepare("UNWIND range(1, 3) AS i CREATE (:Item {n: i})")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &Params::default())
+        .unwrap();
+    txn.commit().unwrap();
+    db.close().unwrap();
+
+    let options = EngineOptions {
+        read_only: true,
+        ..Default::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    assert!(db.is_read_only());
+    assert_eq!(count(&db), Value::Int(3));
+
+    let mut txn = db.begin_write();
+    prepare("MATCH (i:Item) SET i.seen = true")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &Params::default())
+        .unwrap();
+    let err = txn.commit().unwrap_err();
+    assert!(err.to_string().contains("read-only"), "{err}");
+    // New names are refused as soon as they are asked for.
+    let mut txn = db.begin_write();
+    assert!(txn.get_or_create_label("Fresh").is_err());
+    drop(txn);
+    assert!(db.create_index("Item", "n").is_err());
+    assert!(db.checkpoint().is_err());
+    db.close().unwrap();
+
+    let db = Db::open(&path).unwrap();
+    assert!(!db.is_read_only());
+    assert_eq!(count(&db), Value::Int(3));
+    let snapshot = db.snapshot();
+    let seen = prepare("MATCH (i:Item) WHERE i.seen RETURN count(i)")
+        .unwrap()
+        .execute_streaming(&snapshot, &Params::default())
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(seen.columns()[0].1, Value::Int(0));
+}
+
+/// Length and modification time of every file under `dir`.
+fn file_stamps(dir: &Path) -> BTreeMap<PathBuf, (u64, SystemTime)> {
+    let mut stamps = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let meta = entry.metadata().unwrap();
+            if meta.is_dir() {
+                pending.push(entry.path());
+            } else {
+                stamps.insert(entry.path(), (meta.len(), meta.modified().unwrap()));
+            }
+        }
+    }
+    stamps
+}
+
+#[test]
+fn t422_read_only_open_leaves_the_files_untouched() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("graph");
+    let db = Db::open(&path).unwrap();
+    let mut txn = db.begin_write();
+    prepare("UNWIND range(1, 3) AS i CREATE (:Item {n: i})")
+        .unwrap()
+        .execute_write(&db.snapshot(), &mut txn, &Params::default())
+        .unwrap();
+    txn.commit().unwrap();
+    // Dropped without a checkpoint, so the data lives only in the WAL and a
+    // read-only open has to replay it.
+    drop(db);
+    // A torn tail, which a writable open would cut off.
+    let wal = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "wal"))
+        .unwrap();
+    let mut file = OpenOptions::new().append(true).open(&wal).unwrap();
+    file.write_all(&[0; 16]).unwrap();
+    drop(file);
+
+    let before = file_stamps(dir.path());
+    // Coarse mtime clocks would hide a write made within the same tick.
+    std::thread::sleep(Duration::from_millis(50));
+    let options = EngineOptions {
+        read_only: true,
+        ..Default::default()
+    };
+    let db = Db::open_with_options(&path, options).unwrap();
+    assert_eq!(count(&db), Value::Int(3));
+    db.close().unwrap();
+    assert_eq!(file_stamps(dir.path()), before);
+}
+
+#[test]
+fn t422_read_only_open_of_a_missing_database_fails() {
+    let dir = tempdir().unwrap();
+    let options = EngineOptions {
+        read_only: true,
+        ..Default::default()
+    };
+    assert!(Db::open_with_options(dir.path().join("graph"), options).is_err());
+    assert!(file_stamps(dir.path()).is_empty());
+}
@@ -979,12 +979,8 @@ impl SideEffectsSnapshot {
 
         let mut plus_properties = 0i64;
         let mut minus_properties = 0i64;
-        let mut plus_labels = 0i64;
-        let mut minus_labels = 0i64;
         let empty_props: std::collections::BTreeMap<String, Value> =
             std::collections::BTreeMap::new();
-        let empty_labels: std::collections::BTreeSet<nervusdb_api::LabelId> =
-            std::collections::BTreeSet::new();
 
         // Nodes: created/deleted nodes contribute their full property/label counts; updates count as +/-.
         let mut all_nodes = before.nodes.clone();
@@ -995,26 +991,17 @@ impl SideEffectsSnapshot {
             let before_props = before.node_props.get(&node_id).unwrap_or(&empty_props);
             let after_props = self.node_props.get(&node_id).unwrap_or(&empty_props);
 
-            let before_labels = before.node_labels.get(&node_id).unwrap_or(&empty_labels);
-            let after_labels = self.node_labels.get(&node_id).unwrap_or(&empty_labels);
-
             match (before_exists, after_exists) {
                 (false, true) => {
                     plus_properties += after_props.len() as i64;
-                    plus_labels += after_labels.len() as i64;
                 }
                 (true, false) => {
                     minus_properties += before_props.len() as i64;
-                    minus_labels += before_labels.len() as i64;
                 }
                 (true, true) => {
                     let (plus, minus) = diff_value_map(before_props, after_props);
                     plus_properties += plus;
                     minus_properties += minus;
-
-                    let (plus, minus) = diff_set(before_labels, after_labels);
-                    plus_labels += plus;
-                    minus_labels += minus;
                 }
                 (false, false) => {}
             }
@@ -1072,8 +1059,7 @@ impl SideEffectsSnapshot {
             .values()
             .flat_map(|labels| labels.iter().copied())
             .collect();
-        plus_labels = after_label_ids.difference(&before_label_ids).count() as i64;
-        minus_labels = before_label_ids.difference(&after_label_ids).count() as i64;
+        let (plus_labels, minus_labels) = diff_set(&before_label_ids, &after_label_ids);
 
         SideEffectsDelta {
             plus_nodes,
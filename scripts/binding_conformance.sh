@@ -0,0 +1,46 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+cd "$ROOT_DIR"
+
+# Runs examples-test/conformance/scenarios.json through the C API, Python and
+# Node layers and fails on any behavior drift between them.
+
+find_lib() {
+  local dir="$1" stem="$2"
+  for ext in so dylib; do
+    if [[ -f "${dir}/lib${stem}.${ext}" ]]; then
+      echo "${dir}/lib${stem}.${ext}"
+      return 0
+    fi
+  done
+  return 1
+}
+
+stage_dir="$ROOT_DIR/target/binding-conformance"
+mkdir -p "$stage_dir/python"
+
+echo "[binding-conformance] build Python binding"
+cargo build -p nervusdb-pyo3 --release
+py_lib="$(find_lib target/release nervusdb)" || {
+  echo "[binding-conformance] failed: Python extension artifact not found"
+  exit 1
+}
+cp "$py_lib" "$stage_dir/python/nervusdb.so"
+
+echo "[binding-conformance] build Node binding"
+cargo build --manifest-path nervusdb-node/Cargo.toml --release
+node_lib="$(find_lib nervusdb-node/target/release nervusdb_node)" || {
+  echo "[binding-conformance] failed: Node addon artifact not found"
+  exit 1
+}
+cp "$node_lib" "$stage_dir/nervusdb_node.node"
+
+echo "[binding-conformance] run scenario matrix"
+PYTHONPATH="$stage_dir/python${PYTHONPATH:+:$PYTHONPATH}" \
+NERVUSDB_CONFORMANCE_PYTHON="${PYTHON:-python3}" \
+NERVUSDB_CONFORMANCE_NODE_ADDON="$stage_dir/nervusdb_node.node" \
+  cargo test -p nervusdb-capi --test binding_conformance -- --nocapture
+
+echo "[binding-conformance] done"
@@ -38,6 +38,8 @@ coverage_status="success"
 coverage_rc=0
 binding_status="success"
 binding_rc=0
+conformance_status="success"
+conformance_rc=0
 contract_status="success"
 contract_rc=0
 
@@ -77,6 +79,15 @@ if [[ $binding_rc -ne 0 ]]; then
   all_passed=false
 fi
 
+set +e
+run_step "binding-conformance" bash scripts/binding_conformance.sh
+conformance_rc=$?
+set -e
+if [[ $conformance_rc -ne 0 ]]; then
+  conformance_status="failed"
+  all_passed=false
+fi
+
 set +e
 run_step "contract-smoke" bash scripts/contract_smoke.sh
 contract_rc=$?
@@ -96,6 +107,7 @@ cat > "$JSON_FILE" <<JSON
     "parity_softgate_audit": { "status": "${softgate_status}", "exit_code": ${softgate_rc} },
     "parity_coverage_audit": { "status": "${coverage_status}", "exit_code": ${coverage_rc} },
     "binding_smoke": { "status": "${binding_status}", "exit_code": ${binding_rc} },
+    "binding_conformance": { "status": "${conformance_status}", "exit_code": ${conformance_rc} },
     "contract_smoke": { "status": "${contract_status}", "exit_code": ${contract_rc} }
   }
 }
@@ -113,6 +125,7 @@ cat > "$MD_FILE" <<MD
 | scripts/parity_softgate_audit.sh | ${softgate_status} | ${softgate_rc} |
 | scripts/parity_coverage_audit.sh | ${coverage_status} | ${coverage_rc} |
 | scripts/binding_smoke.sh | ${binding_status} | ${binding_rc} |
+| scripts/binding_conformance.sh | ${conformance_status} | ${conformance_rc} |
 | scripts/contract_smoke.sh | ${contract_status} | ${contract_rc} |
 
 Artifacts: